@@ -52,7 +52,7 @@
 ; Properties
 ; ---------------------------------------------------------------------------
 (property_declaration
-  name: (identifier) @name) @definition.variable
+  name: (identifier) @name) @definition.property
 
 ; ---------------------------------------------------------------------------
 ; Fields
@@ -60,7 +60,7 @@
 (field_declaration
   (variable_declaration
     (variable_declarator
-      (identifier) @name))) @definition.variable
+      (identifier) @name))) @definition.field
 
 ; ---------------------------------------------------------------------------
 ; Delegates (function type)
@@ -117,3 +117,9 @@
 ; ---------------------------------------------------------------------------
 (generic_name
   (identifier) @name) @reference.type
+
+; ---------------------------------------------------------------------------
+; Member accesses: obj.Field (reads and writes)
+; ---------------------------------------------------------------------------
+(member_access_expression
+  name: (identifier) @name) @reference.field
@@ -3,7 +3,8 @@
 ; =============================================================================
 ; Captures: @definition.function, @definition.method, @definition.class (struct),
 ;           @definition.interface, @definition.type, @definition.variable,
-;           @name, @reference.call, @reference.import, @reference.type
+;           @definition.field, @name, @reference.call, @reference.import,
+;           @reference.type, @reference.field
 
 ; ---------------------------------------------------------------------------
 ; Functions
@@ -25,6 +26,13 @@
     name: (type_identifier) @name
     type: (struct_type))) @definition.class
 
+; ---------------------------------------------------------------------------
+; Struct fields
+; ---------------------------------------------------------------------------
+(field_declaration_list
+  (field_declaration
+    name: (field_identifier) @name) @definition.field)
+
 ; ---------------------------------------------------------------------------
 ; Interfaces
 ; ---------------------------------------------------------------------------
@@ -88,3 +96,9 @@
 ; Type references
 ; ---------------------------------------------------------------------------
 (type_identifier) @reference.type
+
+; ---------------------------------------------------------------------------
+; Field accesses: operand.field (reads and writes)
+; ---------------------------------------------------------------------------
+(selector_expression
+  field: (field_identifier) @name) @reference.field
@@ -41,7 +41,7 @@
 ; ---------------------------------------------------------------------------
 (field_declaration
   declarator: (variable_declarator
-    name: (identifier) @name)) @definition.variable
+    name: (identifier) @name)) @definition.field
 
 ; ---------------------------------------------------------------------------
 ; Local variables
@@ -107,3 +107,9 @@
 ; Type references
 ; ---------------------------------------------------------------------------
 (type_identifier) @reference.type
+
+; ---------------------------------------------------------------------------
+; Field accesses: this.field / obj.field (reads and writes)
+; ---------------------------------------------------------------------------
+(field_access
+  field: (identifier) @name) @reference.field
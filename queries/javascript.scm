@@ -40,6 +40,12 @@
 (method_definition
   name: (property_identifier) @name) @definition.method
 
+; ---------------------------------------------------------------------------
+; Class fields
+; ---------------------------------------------------------------------------
+(field_definition
+  property: (property_identifier) @name) @definition.field
+
 ; ---------------------------------------------------------------------------
 ; Variable declarations (non-function values)
 ; ---------------------------------------------------------------------------
@@ -102,3 +108,9 @@
 ; new X()
 (new_expression
   constructor: (identifier) @name) @reference.class
+
+; ---------------------------------------------------------------------------
+; Field accesses: obj.field (reads and writes)
+; ---------------------------------------------------------------------------
+(member_expression
+  property: (property_identifier) @name) @reference.field
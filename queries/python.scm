@@ -26,6 +26,15 @@
   superclasses: (argument_list
     (identifier) @superclass)) @definition.class_with_heritage
 
+; ---------------------------------------------------------------------------
+; Class attributes (fields declared in the class body)
+; ---------------------------------------------------------------------------
+(class_definition
+  body: (block
+    (expression_statement
+      (assignment
+        left: (identifier) @name) @definition.field)))
+
 ; ---------------------------------------------------------------------------
 ; Method definitions (functions inside a class body)
 ; ---------------------------------------------------------------------------
@@ -89,3 +98,9 @@
 
 ; Constructor-style calls detected via naming convention are handled
 ; at the extractor level (Python has no `new` keyword).
+
+; ---------------------------------------------------------------------------
+; Attribute accesses: obj.field (reads and writes)
+; ---------------------------------------------------------------------------
+(attribute
+  attribute: (identifier) @name) @reference.field
@@ -3,8 +3,8 @@
 ; =============================================================================
 ; Captures: @definition.function, @definition.class (struct), @definition.method,
 ;           @definition.interface (trait), @definition.enum, @definition.type,
-;           @definition.variable, @name, @reference.call, @reference.import,
-;           @reference.type
+;           @definition.variable, @definition.field, @name, @reference.call,
+;           @reference.import, @reference.type, @reference.field
 
 ; ---------------------------------------------------------------------------
 ; Functions
@@ -18,6 +18,13 @@
 (struct_item
   name: (type_identifier) @name) @definition.class
 
+; ---------------------------------------------------------------------------
+; Struct fields
+; ---------------------------------------------------------------------------
+(field_declaration_list
+  (field_declaration
+    name: (field_identifier) @name) @definition.field)
+
 ; ---------------------------------------------------------------------------
 ; Enums
 ; ---------------------------------------------------------------------------
@@ -119,3 +126,9 @@
 ; Type references
 ; ---------------------------------------------------------------------------
 (type_identifier) @reference.type
+
+; ---------------------------------------------------------------------------
+; Field accesses: value.field (reads and writes)
+; ---------------------------------------------------------------------------
+(field_expression
+  field: (field_identifier) @name) @reference.field
@@ -50,6 +50,15 @@
 (method_definition
   name: (property_identifier) @name) @definition.method
 
+; ---------------------------------------------------------------------------
+; Fields (class fields and interface properties)
+; ---------------------------------------------------------------------------
+(public_field_definition
+  name: (property_identifier) @name) @definition.field
+
+(property_signature
+  name: (property_identifier) @name) @definition.field
+
 ; ---------------------------------------------------------------------------
 ; Interface declarations
 ; ---------------------------------------------------------------------------
@@ -148,3 +157,9 @@
 ; Generic type arguments: Promise<Foo>
 (generic_type
   name: (type_identifier) @name) @reference.type
+
+; ---------------------------------------------------------------------------
+; Field accesses: obj.field (reads and writes)
+; ---------------------------------------------------------------------------
+(member_expression
+  property: (property_identifier) @name) @reference.field
@@ -9,8 +9,12 @@ use std::collections::HashSet;
 use std::path::Path;
 
 use super::preset::enabled_categories;
-use super::schema::{CategoryConfig, CodeGraphConfig, PresetName, ToolMetadata, ToolOverride};
+use super::schema::{
+    ApiKeyConfig, CategoryConfig, CodeGraphConfig, PresetName, ToolMetadata, ToolOverride,
+};
 use crate::error::CodeGraphError;
+use crate::graph::api_guard::GuardMode;
+use crate::sandbox::Trust;
 
 // ---------------------------------------------------------------------------
 // Public API
@@ -39,7 +43,14 @@ pub fn load_config(
     // Layer 3: project config
     if let Some(dir) = project_dir {
         if let Some(project) = load_project_config(dir) {
+            // A project may tighten the sandbox but never relax it, and
+            // never touches HTTP access.
+            let mut sandbox = config.sandbox.clone();
+            sandbox.tighten(&project.sandbox);
+            let http = config.http.clone();
             config = merge_configs(config, project);
+            config.sandbox = sandbox;
+            config.http = http;
         }
     }
 
@@ -113,6 +124,15 @@ pub fn detect_editor(client_name: &str) -> PresetName {
 /// Supported variables:
 /// - `CODEGRAPH_PRESET` — override the preset name
 /// - `CODEGRAPH_EXCLUDE_TESTS` — set to `"1"` or `"true"` to exclude tests
+/// - `CODEGRAPH_ADJACENCY_CACHE` — set to `"1"` or `"true"` to keep an in-memory adjacency
+/// - `CODEGRAPH_MAX_BODY_BYTES` — largest node body stored, in bytes (`0` stores none)
+/// - `CODEGRAPH_QUERY_LOG` — set to `"1"` or `"true"` to log tool calls for `codegraph db stats`
+/// - `CODEGRAPH_TRUST` — `trusted` or `untrusted`, the workspace trust level
+/// - `CODEGRAPH_COMMAND_TIMEOUT_SECS` — seconds before an external command is killed
+/// - `CODEGRAPH_LOCALE` — locale for tool response messages (e.g. `de`)
+/// - `CODEGRAPH_API_GUARD` — `off`, `warn` or `block`, the default breaking-change guard mode
+/// - `CODEGRAPH_PAGE_SIZE` — items per page of paged tool responses
+/// - `CODEGRAPH_API_KEY` — an API key for the HTTP transport, allowed every tool
 /// - `CODEGRAPH_DISABLED_TOOLS` — comma-separated tool names to disable
 /// - `CODEGRAPH_ENABLED_CATEGORIES` — comma-separated category names (disables all others)
 pub fn load_env_overrides(config: &mut CodeGraphConfig) {
@@ -128,6 +148,82 @@ pub fn load_env_overrides(config: &mut CodeGraphConfig) {
         config.performance.exclude_tests = matches!(val.as_str(), "1" | "true" | "yes");
     }
 
+    // Adjacency cache
+    if let Ok(val) = std::env::var("CODEGRAPH_ADJACENCY_CACHE") {
+        config.performance.adjacency_cache = matches!(val.as_str(), "1" | "true" | "yes");
+    }
+
+    // Body storage cap
+    if let Ok(val) = std::env::var("CODEGRAPH_MAX_BODY_BYTES") {
+        if let Ok(max) = val.trim().parse() {
+            config.performance.max_body_bytes = Some(max);
+        }
+    }
+
+    // Query log
+    if let Ok(val) = std::env::var("CODEGRAPH_QUERY_LOG") {
+        config.performance.query_log = matches!(val.as_str(), "1" | "true" | "yes");
+    }
+
+    // ANN index
+    if let Ok(val) = std::env::var("CODEGRAPH_ANN_INDEX") {
+        config.performance.ann_index = Some(matches!(val.as_str(), "1" | "true" | "yes"));
+    }
+
+    // Workspace trust and command timeout
+    if let Ok(val) = std::env::var("CODEGRAPH_TRUST") {
+        if let Some(trust) = Trust::from_str_loose(&val) {
+            config.sandbox.trust = Some(trust);
+        }
+    }
+    if let Ok(val) = std::env::var("CODEGRAPH_COMMAND_TIMEOUT_SECS") {
+        if let Ok(secs) = val.trim().parse() {
+            config.sandbox.timeout_secs = Some(secs);
+        }
+    }
+
+    // Response locale
+    if let Ok(val) = std::env::var("CODEGRAPH_LOCALE") {
+        if !val.trim().is_empty() {
+            config.i18n.locale = Some(val.trim().to_string());
+        }
+    }
+
+    // Breaking-change guard
+    if let Ok(val) = std::env::var("CODEGRAPH_API_GUARD") {
+        if let Some(mode) = GuardMode::from_str_loose(&val) {
+            config.api_guard.mode = Some(mode);
+        }
+    }
+
+    // Response profile
+    if let Ok(val) = std::env::var("CODEGRAPH_PROFILE") {
+        if !val.trim().is_empty() {
+            config.response.profile = Some(val.trim().to_string());
+        }
+    }
+    if let Ok(val) = std::env::var("CODEGRAPH_PAGE_SIZE") {
+        if let Ok(size) = val.trim().parse::<usize>() {
+            config.response.page_size = Some(size.max(1));
+        }
+    }
+    if let Ok(val) = std::env::var("CODEGRAPH_MAX_RESPONSE_TOKENS") {
+        if let Ok(tokens) = val.trim().parse::<usize>() {
+            config.response.max_response_tokens = Some(tokens.max(1));
+        }
+    }
+
+    // HTTP API key
+    if let Ok(val) = std::env::var("CODEGRAPH_API_KEY") {
+        if !val.is_empty() {
+            config.http.api_keys.push(ApiKeyConfig {
+                name: "CODEGRAPH_API_KEY".to_string(),
+                key: Some(val),
+                ..Default::default()
+            });
+        }
+    }
+
     // Disabled tools
     if let Ok(val) = std::env::var("CODEGRAPH_DISABLED_TOOLS") {
         for name in val.split(',').map(|s| s.trim()).filter(|s| !s.is_empty()) {
@@ -232,6 +328,16 @@ fn merge_configs(mut base: CodeGraphConfig, overlay: CodeGraphConfig) -> CodeGra
         base.tools.categories.insert(name, cat);
     }
 
+    // Tool limits — overlay values win, per-tool entries merge
+    let limits = overlay.tools.limits;
+    if limits.defaults.timeout_secs.is_some() {
+        base.tools.limits.defaults.timeout_secs = limits.defaults.timeout_secs;
+    }
+    if limits.defaults.max_concurrent.is_some() {
+        base.tools.limits.defaults.max_concurrent = limits.defaults.max_concurrent;
+    }
+    base.tools.limits.per_tool.extend(limits.per_tool);
+
     // Performance — overlay wins on non-default values
     if overlay.performance.max_tool_count.is_some() {
         base.performance.max_tool_count = overlay.performance.max_tool_count;
@@ -239,12 +345,173 @@ fn merge_configs(mut base: CodeGraphConfig, overlay: CodeGraphConfig) -> CodeGra
     if overlay.performance.exclude_tests {
         base.performance.exclude_tests = true;
     }
+    if overlay.performance.adjacency_cache {
+        base.performance.adjacency_cache = true;
+    }
+    if overlay.performance.max_body_bytes.is_some() {
+        base.performance.max_body_bytes = overlay.performance.max_body_bytes;
+    }
+    if overlay.performance.compress_bodies.is_some() {
+        base.performance.compress_bodies = overlay.performance.compress_bodies;
+    }
+    if overlay.performance.query_log {
+        base.performance.query_log = true;
+    }
+    if overlay.performance.tombstones.is_some() {
+        base.performance.tombstones = overlay.performance.tombstones;
+    }
+    if overlay.performance.tombstone_retention_days.is_some() {
+        base.performance.tombstone_retention_days = overlay.performance.tombstone_retention_days;
+    }
+    if overlay.performance.ann_index.is_some() {
+        base.performance.ann_index = overlay.performance.ann_index;
+    }
 
     // Contexts — overlay keys win
     for (path, desc) in overlay.contexts {
         base.contexts.insert(path, desc);
     }
 
+    // Synonyms and saved queries — overlay keys win
+    base.synonyms.extend(overlay.synonyms);
+    base.saved_queries.extend(overlay.saved_queries);
+
+    // Locales — overlay values win, per-tool entries merge
+    if overlay.i18n.locale.is_some() {
+        base.i18n.locale = overlay.i18n.locale;
+    }
+    if overlay.i18n.bundle_dir.is_some() {
+        base.i18n.bundle_dir = overlay.i18n.bundle_dir;
+    }
+    base.i18n.tools.extend(overlay.i18n.tools);
+
+    // Response profiles — overlay selection and definitions win
+    if overlay.response.profile.is_some() {
+        base.response.profile = overlay.response.profile;
+    }
+    if overlay.response.page_size.is_some() {
+        base.response.page_size = overlay.response.page_size;
+    }
+    if overlay.response.max_response_tokens.is_some() {
+        base.response.max_response_tokens = overlay.response.max_response_tokens;
+    }
+    base.response.profiles.extend(overlay.response.profiles);
+
+    // Ranking weights — overlay values win
+    if overlay.ranking.page_rank_weight.is_some() {
+        base.ranking.page_rank_weight = overlay.ranking.page_rank_weight;
+    }
+    if overlay.ranking.recency_weight.is_some() {
+        base.ranking.recency_weight = overlay.ranking.recency_weight;
+    }
+    if overlay.ranking.test_penalty.is_some() {
+        base.ranking.test_penalty = overlay.ranking.test_penalty;
+    }
+
+    // API guard — overlay values win, directory entries merge
+    if overlay.api_guard.mode.is_some() {
+        base.api_guard.mode = overlay.api_guard.mode;
+    }
+    if overlay.api_guard.note_files.is_some() {
+        base.api_guard.note_files = overlay.api_guard.note_files;
+    }
+    base.api_guard
+        .directories
+        .extend(overlay.api_guard.directories);
+
+    // Architecture — overlay layers win by name, overlay rules replace,
+    // coupling thresholds win one by one
+    base.architecture.layers.extend(overlay.architecture.layers);
+    if !overlay.architecture.rules.is_empty() {
+        base.architecture.rules = overlay.architecture.rules;
+    }
+    if overlay.architecture.edge_kinds.is_some() {
+        base.architecture.edge_kinds = overlay.architecture.edge_kinds;
+    }
+    let coupling = overlay.architecture.coupling;
+    let base_coupling = &mut base.architecture.coupling;
+    if coupling.max_afferent.is_some() {
+        base_coupling.max_afferent = coupling.max_afferent;
+    }
+    if coupling.max_efferent.is_some() {
+        base_coupling.max_efferent = coupling.max_efferent;
+    }
+    if coupling.max_instability.is_some() {
+        base_coupling.max_instability = coupling.max_instability;
+    }
+    if coupling.max_distance.is_some() {
+        base_coupling.max_distance = coupling.max_distance;
+    }
+
+    // Federation — overlay weight wins, indexes merge by name
+    if overlay.federation.local_weight.is_some() {
+        base.federation.local_weight = overlay.federation.local_weight;
+    }
+    for index in overlay.federation.indexes {
+        base.federation.indexes.retain(|i| i.name != index.name);
+        base.federation.indexes.push(index);
+    }
+
+    // HTTP — keys merge by name, overlay TLS wins
+    for key in overlay.http.api_keys {
+        base.http.api_keys.retain(|k| k.name != key.name);
+        base.http.api_keys.push(key);
+    }
+    if overlay.http.tls.is_some() {
+        base.http.tls = overlay.http.tls;
+    }
+
+    // Secrets — overlay values win, allowlist entries merge
+    if overlay.secrets.entropy.is_some() {
+        base.secrets.entropy = overlay.secrets.entropy;
+    }
+    if overlay.secrets.min_entropy.is_some() {
+        base.secrets.min_entropy = overlay.secrets.min_entropy;
+    }
+    base.secrets.allowlist.extend(overlay.secrets.allowlist);
+
+    // Dead code — overlay root switches win, extra roots merge
+    let dead_code = overlay.dead_code;
+    if dead_code.mains.is_some() {
+        base.dead_code.mains = dead_code.mains;
+    }
+    if dead_code.exports.is_some() {
+        base.dead_code.exports = dead_code.exports;
+    }
+    if dead_code.tests.is_some() {
+        base.dead_code.tests = dead_code.tests;
+    }
+    if dead_code.handlers.is_some() {
+        base.dead_code.handlers = dead_code.handlers;
+    }
+    base.dead_code.symbols.extend(dead_code.symbols);
+    base.dead_code.paths.extend(dead_code.paths);
+
+    // Git — overlay wins when set
+    if overlay.git.blame_metadata.is_some() {
+        base.git.blame_metadata = overlay.git.blame_metadata;
+    }
+
+    // Parser fallback chains — overlay languages win
+    base.parsing.fallback.extend(overlay.parsing.fallback);
+
+    // Sandbox — overlay wins on values it sets
+    if overlay.sandbox.trust.is_some() {
+        base.sandbox.trust = overlay.sandbox.trust;
+    }
+    if overlay.sandbox.allowed_binaries.is_some() {
+        base.sandbox.allowed_binaries = overlay.sandbox.allowed_binaries;
+    }
+    if overlay.sandbox.timeout_secs.is_some() {
+        base.sandbox.timeout_secs = overlay.sandbox.timeout_secs;
+    }
+    if overlay.sandbox.max_output_bytes.is_some() {
+        base.sandbox.max_output_bytes = overlay.sandbox.max_output_bytes;
+    }
+    if overlay.sandbox.max_file_bytes.is_some() {
+        base.sandbox.max_file_bytes = overlay.sandbox.max_file_bytes;
+    }
+
     base
 }
 
@@ -255,7 +522,7 @@ fn merge_configs(mut base: CodeGraphConfig, overlay: CodeGraphConfig) -> CodeGra
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::config::schema::PerformanceConfig;
+    use crate::config::schema::{PerformanceConfig, ResponseProfile, SandboxConfig};
     use std::io::Write;
     use std::sync::Mutex;
 
@@ -547,11 +814,27 @@ performance:
         overlay.performance = PerformanceConfig {
             max_tool_count: Some(20),
             exclude_tests: true,
+            adjacency_cache: true,
+            max_body_bytes: Some(0),
+            compress_bodies: Some(false),
+            query_log: true,
+            tombstones: Some(false),
+            tombstone_retention_days: Some(7),
+            ann_index: Some(true),
         };
 
         let merged = merge_configs(base, overlay);
         assert_eq!(merged.performance.max_tool_count, Some(20));
         assert!(merged.performance.exclude_tests);
+        assert!(merged.performance.adjacency_cache);
+        assert!(merged.performance.query_log);
+        let bodies = merged.performance.body_storage();
+        assert_eq!(bodies.max_bytes, 0);
+        assert!(!bodies.compress);
+        let tombstones = merged.performance.tombstone_policy();
+        assert!(!tombstones.enabled);
+        assert_eq!(tombstones.retention_secs, Some(7 * 86_400));
+        assert_eq!(merged.performance.ann_index, Some(true));
     }
 
     #[test]
@@ -566,6 +849,109 @@ performance:
         assert_eq!(merged.performance.max_tool_count, Some(42));
     }
 
+    #[test]
+    fn test_merge_i18n() {
+        let mut base = CodeGraphConfig::default();
+        base.i18n.locale = Some("de".to_string());
+        base.i18n
+            .tools
+            .insert("codegraph_node".to_string(), "es".to_string());
+        let mut overlay = CodeGraphConfig::default();
+        overlay
+            .i18n
+            .tools
+            .insert("codegraph_callers".to_string(), "tr".to_string());
+
+        let merged = merge_configs(base, overlay);
+        assert_eq!(merged.i18n.locale.as_deref(), Some("de"));
+        assert_eq!(merged.i18n.tools.len(), 2);
+    }
+
+    #[test]
+    fn test_merge_response_profiles() {
+        let mut base = CodeGraphConfig::default();
+        base.response.profile = Some("agent-compact".to_string());
+        let mut overlay = CodeGraphConfig::default();
+        overlay.response.profile = Some("tiny".to_string());
+        overlay.response.profiles.insert(
+            "tiny".to_string(),
+            ResponseProfile {
+                max_results: Some(3),
+                ..Default::default()
+            },
+        );
+
+        let merged = merge_configs(base, overlay);
+        let active = merged.response.active_profile().unwrap();
+        assert_eq!(active.max_results, Some(3));
+        assert!(!active.strip_bodies);
+    }
+
+    #[test]
+    fn test_merge_ranking_weights() {
+        let mut base = CodeGraphConfig::default();
+        base.ranking.page_rank_weight = Some(0.3);
+        base.ranking.test_penalty = Some(0.5);
+        let mut overlay = CodeGraphConfig::default();
+        overlay.ranking.test_penalty = Some(0.0);
+
+        let weights = merge_configs(base, overlay).ranking.prior_weights();
+        assert_eq!(weights.page_rank, 0.3);
+        assert_eq!(weights.test_penalty, 0.0);
+        assert_eq!(weights.recency, 0.05);
+    }
+
+    #[test]
+    fn test_merge_federation_indexes_by_name() {
+        use crate::graph::federation::FederatedIndex;
+        let index = |name: &str, path: &str| FederatedIndex {
+            name: name.to_string(),
+            path: path.into(),
+            weight: None,
+        };
+        let mut base = CodeGraphConfig::default();
+        base.federation.indexes = vec![index("lib", "old.db"), index("ui", "ui.db")];
+        let mut overlay = CodeGraphConfig::default();
+        overlay.federation.indexes = vec![index("lib", "new.db")];
+
+        let merged = merge_configs(base, overlay).federation.indexes;
+        assert_eq!(merged, vec![index("ui", "ui.db"), index("lib", "new.db")]);
+    }
+
+    #[test]
+    fn test_merge_parsing_fallback() {
+        use crate::indexer::fallback::ParserStage;
+        use crate::types::Language;
+
+        let mut base = CodeGraphConfig::default();
+        base.parsing.fallback.insert(
+            "default".to_string(),
+            vec!["tree-sitter".to_string(), "regex".to_string()],
+        );
+        base.parsing
+            .fallback
+            .insert("cpp".to_string(), vec!["tree-sitter".to_string()]);
+        let mut overlay = CodeGraphConfig::default();
+        overlay.parsing.fallback.insert(
+            "cpp".to_string(),
+            vec![
+                "tree-sitter".to_string(),
+                "lines".to_string(),
+                "ctags".to_string(),
+            ],
+        );
+
+        let chains = merge_configs(base, overlay).parsing.parser_chains();
+        assert_eq!(
+            chains.chain_for(Language::Cpp),
+            &[ParserStage::TreeSitter, ParserStage::Lines]
+        );
+        assert_eq!(
+            chains.chain_for(Language::Go),
+            &[ParserStage::TreeSitter, ParserStage::Regex]
+        );
+    }
+
     // -- filter_tools --------------------------------------------------
 
     fn sample_tools() -> Vec<ToolMetadata> {
@@ -1116,4 +1502,41 @@ contexts:
             Some("Auth service")
         );
     }
+    #[test]
+    fn project_config_can_only_tighten_the_sandbox() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join(".codegraph.yaml"),
+            "sandbox:\n  trust: trusted\n  allowed_binaries: [git, curl]\n  timeout_secs: 5\n",
+        )
+        .unwrap();
+
+        let config = load_config(None, Some(dir.path())).unwrap();
+        pa_eq!(config.sandbox.trust, None);
+        pa_eq!(config.sandbox.policy().allowed_binaries, vec!["git"]);
+        pa_eq!(config.sandbox.timeout_secs, Some(5));
+
+        let mut strict = SandboxConfig {
+            trust: Some(Trust::Untrusted),
+            ..Default::default()
+        };
+        strict.tighten(&SandboxConfig {
+            trust: Some(Trust::Trusted),
+            ..Default::default()
+        });
+        pa_eq!(strict.trust, Some(Trust::Untrusted));
+    }
+
+    #[test]
+    fn project_config_cannot_grant_http_access() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join(".codegraph.yaml"),
+            "http:\n  api_keys:\n    - name: mine\n      key: k\n",
+        )
+        .unwrap();
+
+        let config = load_config(None, Some(dir.path())).unwrap();
+        assert!(config.http.api_keys.iter().all(|k| k.name != "mine"));
+    }
 }
@@ -7,4 +7,4 @@ pub mod schema;
 // Re-export the most commonly used types.
 pub use loader::{detect_editor, filter_tools, load_config};
 pub use preset::{get_preset, PresetDefinition};
-pub use schema::{CodeGraphConfig, PresetName, ToolMetadata};
+pub use schema::{CodeGraphConfig, I18nConfig, PresetName, ToolMetadata};
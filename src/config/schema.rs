@@ -5,6 +5,22 @@
 
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::db::bodies::BodyStorage;
+use crate::db::saved_queries::SavedQuery;
+use crate::graph::api_guard::{GuardMode, GuardPolicy};
+use crate::graph::architecture::{ArchitecturePolicy, LayerRule};
+use crate::graph::coupling::CouplingThresholds;
+use crate::graph::expansion::SynonymMap;
+use crate::graph::federation::{FederatedIndex, Federation};
+use crate::graph::search::PriorWeights;
+use crate::graph::store::TombstonePolicy;
+use crate::indexer::fallback::{ParserChains, ParserStage};
+use crate::resolution::dead_code::DeadCodeRoots;
+use crate::sandbox::{SandboxPolicy, Trust};
+use crate::security::secrets::SecretScanOptions;
+use crate::types::{EdgeKind, Language};
 
 // ---------------------------------------------------------------------------
 // Top-level config
@@ -45,6 +61,79 @@ pub struct CodeGraphConfig {
     /// ```
     #[serde(default)]
     pub contexts: HashMap<String, String>,
+
+    /// Project synonyms for search query expansion, applied on top of the
+    /// built-in abbreviations and synonym groups.
+    ///
+    /// ```yaml
+    /// synonyms:
+    ///   auth: [authentication, login, session]
+    ///   kyc: [know your customer, identity verification]
+    /// ```
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub synonyms: HashMap<String, Vec<String>>,
+
+    /// Named searches shared with the team, run with
+    /// `codegraph query --saved <name>` or `codegraph_saved_query`.
+    ///
+    /// ```yaml
+    /// saved_queries:
+    ///   route-handlers:
+    ///     query: "handler for {route}"
+    ///     path: src/api
+    ///     exclude_kinds: [variable]
+    ///     detail_level: summary
+    /// ```
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub saved_queries: HashMap<String, SavedQuery>,
+
+    /// Language of human-readable messages in tool responses.
+    #[serde(default)]
+    pub i18n: I18nConfig,
+
+    /// Parser fallback chains per language.
+    #[serde(default)]
+    pub parsing: ParsingConfig,
+
+    /// Workspace trust and limits for external commands.
+    #[serde(default)]
+    pub sandbox: SandboxConfig,
+
+    /// Response profile capping the size of every tool response.
+    #[serde(default)]
+    pub response: ResponseConfig,
+
+    /// Weights of the PageRank, recency and test priors in search ranking.
+    #[serde(default)]
+    pub ranking: RankingConfig,
+
+    /// Breaking-change guard for symbols used from other files.
+    #[serde(default)]
+    pub api_guard: ApiGuardConfig,
+
+    /// Layers and the dependencies between them that are not allowed.
+    #[serde(default)]
+    pub architecture: ArchitectureConfig,
+
+    /// Other indexes searched together with this one.
+    #[serde(default)]
+    pub federation: FederationConfig,
+
+    /// API keys and TLS of the HTTP transport.
+    #[serde(default)]
+    pub http: HttpConfig,
+
+    /// Entropy detection and allowlists of `codegraph_find_secrets`.
+    #[serde(default)]
+    pub secrets: SecretsConfig,
+
+    /// Roots of the reachability check of `codegraph_dead_code`.
+    #[serde(default)]
+    pub dead_code: DeadCodeConfig,
+
+    /// Git metadata recorded while indexing.
+    #[serde(default)]
+    pub git: GitConfig,
 }
 
 impl Default for CodeGraphConfig {
@@ -55,11 +144,30 @@ impl Default for CodeGraphConfig {
             tools: ToolsConfig::default(),
             performance: PerformanceConfig::default(),
             contexts: HashMap::new(),
+            synonyms: HashMap::new(),
+            saved_queries: HashMap::new(),
+            i18n: I18nConfig::default(),
+            parsing: ParsingConfig::default(),
+            sandbox: SandboxConfig::default(),
+            response: ResponseConfig::default(),
+            ranking: RankingConfig::default(),
+            api_guard: ApiGuardConfig::default(),
+            architecture: ArchitectureConfig::default(),
+            federation: FederationConfig::default(),
+            http: HttpConfig::default(),
+            secrets: SecretsConfig::default(),
+            dead_code: DeadCodeConfig::default(),
+            git: GitConfig::default(),
         }
     }
 }
 
 impl CodeGraphConfig {
+    /// Query expansion map built from `synonyms`.
+    pub fn synonym_map(&self) -> SynonymMap {
+        SynonymMap::new(&self.synonyms)
+    }
+
     /// Check whether a specific category is enabled (defaults to true).
     pub fn is_category_enabled(&self, category: &str) -> bool {
         self.tools
@@ -158,6 +266,10 @@ pub struct ToolsConfig {
     /// Category-level toggles (enable/disable entire groups).
     #[serde(default)]
     pub categories: HashMap<String, CategoryConfig>,
+
+    /// Timeouts and concurrency limits of tool calls.
+    #[serde(default)]
+    pub limits: ToolLimitsConfig,
 }
 
 // ---------------------------------------------------------------------------
@@ -193,6 +305,78 @@ impl ToolOverride {
     }
 }
 
+// ---------------------------------------------------------------------------
+// ToolLimitsConfig
+// ---------------------------------------------------------------------------
+
+/// Seconds a tool call may run when no limit is configured.
+pub const DEFAULT_TOOL_TIMEOUT_SECS: u64 = 120;
+
+/// Calls of one tool that may run at once when no limit is configured.
+pub const DEFAULT_TOOL_MAX_CONCURRENT: usize = 4;
+
+/// Execution limits of one tool, or of every tool.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ToolLimit {
+    /// Seconds before a call fails with a timeout error; 0 disables.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_secs: Option<u64>,
+
+    /// Calls that may run at once; further calls wait for a slot within
+    /// their timeout. 0 disables.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_concurrent: Option<usize>,
+}
+
+/// Timeouts and concurrency limits enforced by the MCP server around
+/// every tool call, so one runaway call (a security scan of a huge tree)
+/// cannot wedge the server.
+///
+/// ```yaml
+/// tools:
+///   limits:
+///     timeout_secs: 60
+///     max_concurrent: 4
+///     per_tool:
+///       codegraph_scan_security:
+///         timeout_secs: 600
+///         max_concurrent: 1
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ToolLimitsConfig {
+    /// Limits of tools without their own entry.
+    #[serde(flatten)]
+    pub defaults: ToolLimit,
+
+    /// Limits by tool name; unset fields fall back to `defaults`.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub per_tool: HashMap<String, ToolLimit>,
+}
+
+impl ToolLimitsConfig {
+    /// How long a call of `tool` may run, `None` for no limit.
+    pub fn timeout(&self, tool: &str) -> Option<std::time::Duration> {
+        let secs = self
+            .per_tool
+            .get(tool)
+            .and_then(|l| l.timeout_secs)
+            .or(self.defaults.timeout_secs)
+            .unwrap_or(DEFAULT_TOOL_TIMEOUT_SECS);
+        (secs > 0).then(|| std::time::Duration::from_secs(secs))
+    }
+
+    /// How many calls of `tool` may run at once, `None` for no limit.
+    pub fn max_concurrent(&self, tool: &str) -> Option<usize> {
+        let max = self
+            .per_tool
+            .get(tool)
+            .and_then(|l| l.max_concurrent)
+            .or(self.defaults.max_concurrent)
+            .unwrap_or(DEFAULT_TOOL_MAX_CONCURRENT);
+        (max > 0).then_some(max)
+    }
+}
+
 // ---------------------------------------------------------------------------
 // CategoryConfig
 // ---------------------------------------------------------------------------
@@ -219,6 +403,733 @@ pub struct PerformanceConfig {
     /// Whether to exclude test files from indexing.
     #[serde(default)]
     pub exclude_tests: bool,
+
+    /// Keep an in-memory adjacency of the graph for traversals and
+    /// ranking. Speeds up deep caller/callee walks on large graphs at the
+    /// cost of memory proportional to the edge count.
+    #[serde(default)]
+    pub adjacency_cache: bool,
+
+    /// Largest node body stored, in bytes; longer bodies are truncated and
+    /// `0` stores none. Defaults to 4096.
+    #[serde(default)]
+    pub max_body_bytes: Option<usize>,
+
+    /// Whether stored node bodies are zstd-compressed. Defaults to true.
+    #[serde(default)]
+    pub compress_bodies: Option<bool>,
+
+    /// Record every MCP tool call with its duration in the database's
+    /// query log, for `codegraph db stats`.
+    #[serde(default)]
+    pub query_log: bool,
+
+    /// Whether removed symbols and edges leave tombstones, so tools can
+    /// report what the last index removed. Defaults to true.
+    #[serde(default)]
+    pub tombstones: Option<bool>,
+
+    /// Purge tombstones older than this many days after each index run.
+    /// Unset keeps them until `codegraph vacuum`.
+    #[serde(default)]
+    pub tombstone_retention_days: Option<u32>,
+
+    /// Keep an HNSW approximate nearest-neighbour index of the embeddings
+    /// next to the database (`codegraph.db.ann`) and search it instead of
+    /// scanning every vector. Worth it past a few hundred thousand
+    /// embeddings; turning it off deletes the file at the next index.
+    #[serde(default)]
+    pub ann_index: Option<bool>,
+}
+
+impl PerformanceConfig {
+    /// Body storage settings for the graph store.
+    pub fn body_storage(&self) -> BodyStorage {
+        let default = BodyStorage::default();
+        BodyStorage {
+            max_bytes: self.max_body_bytes.unwrap_or(default.max_bytes),
+            compress: self.compress_bodies.unwrap_or(default.compress),
+        }
+    }
+
+    /// Tombstone settings for the graph store.
+    pub fn tombstone_policy(&self) -> TombstonePolicy {
+        TombstonePolicy {
+            enabled: self.tombstones.unwrap_or(true),
+            retention_secs: self
+                .tombstone_retention_days
+                .map(|days| i64::from(days) * 86_400),
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// ParsingConfig
+// ---------------------------------------------------------------------------
+
+/// Which parsers to try, in order, when tree-sitter cannot make sense of
+/// a file. Stages are `tree-sitter`, `regex` and `lines`; `default`
+/// applies to languages without their own entry.
+///
+/// ```yaml
+/// parsing:
+///   fallback:
+///     default: [tree-sitter, regex]
+///     cpp: [tree-sitter, regex, lines]
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ParsingConfig {
+    /// Stage names per language name (or `default`).
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub fallback: HashMap<String, Vec<String>>,
+}
+
+impl ParsingConfig {
+    /// Fallback chains for the indexing pipeline. Unknown languages and
+    /// stage names are ignored with a warning.
+    pub fn parser_chains(&self) -> ParserChains {
+        let mut chains = ParserChains::default();
+        for (language, stages) in &self.fallback {
+            let chain: Vec<ParserStage> = stages
+                .iter()
+                .filter_map(|s| {
+                    let stage = ParserStage::from_str_loose(s);
+                    if stage.is_none() {
+                        tracing::warn!("Unknown parser stage '{}' for {}", s, language);
+                    }
+                    stage
+                })
+                .collect();
+            if language == "default" {
+                chains.set_default(chain);
+            } else if let Some(lang) = Language::from_str_loose(language) {
+                chains.set(lang, chain);
+            } else {
+                tracing::warn!("Unknown language '{}' in parsing.fallback", language);
+            }
+        }
+        chains
+    }
+}
+
+// ---------------------------------------------------------------------------
+// SandboxConfig
+// ---------------------------------------------------------------------------
+
+/// Trust and limits for the commands CodeGraph runs (`git`) and the files
+/// its scanners read; see [`crate::sandbox`]. Mark a deployment that
+/// serves repos it does not control as `untrusted`.
+///
+/// A project's own `.codegraph.yaml` can only tighten these settings: the
+/// project is what is being distrusted, so it cannot vouch for itself.
+///
+/// ```yaml
+/// sandbox:
+///   trust: untrusted
+///   allowed_binaries: [git]
+///   timeout_secs: 30
+///   max_output_bytes: 16777216
+///   max_file_bytes: 1048576
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SandboxConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trust: Option<Trust>,
+    /// Binaries that may be run (default `[git]`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub allowed_binaries: Option<Vec<String>>,
+    /// Seconds before a command is killed (default 120).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_secs: Option<u64>,
+    /// Most bytes read from a command's output (default 64 MiB).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_output_bytes: Option<usize>,
+    /// Largest file scanned in an untrusted workspace (default 2 MiB).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_file_bytes: Option<u64>,
+}
+
+impl SandboxConfig {
+    /// The policy these settings describe, with defaults filled in.
+    pub fn policy(&self) -> SandboxPolicy {
+        let defaults = SandboxPolicy::default();
+        SandboxPolicy {
+            trust: self.trust.unwrap_or(defaults.trust),
+            allowed_binaries: self
+                .allowed_binaries
+                .clone()
+                .unwrap_or(defaults.allowed_binaries),
+            jail: Vec::new(),
+            timeout: self
+                .timeout_secs
+                .map_or(defaults.timeout, std::time::Duration::from_secs),
+            max_output_bytes: self.max_output_bytes.unwrap_or(defaults.max_output_bytes),
+            max_file_bytes: self.max_file_bytes.unwrap_or(defaults.max_file_bytes),
+        }
+    }
+
+    /// Apply the settings of `project` that are stricter than these.
+    pub fn tighten(&mut self, project: &SandboxConfig) {
+        if project.trust == Some(Trust::Untrusted) {
+            self.trust = Some(Trust::Untrusted);
+        }
+        if let Some(ref allowed) = project.allowed_binaries {
+            let mut current = self.policy().allowed_binaries;
+            current.retain(|b| allowed.contains(b));
+            self.allowed_binaries = Some(current);
+        }
+        let current = self.policy();
+        if let Some(secs) = project.timeout_secs {
+            self.timeout_secs = Some(secs.min(current.timeout.as_secs()));
+        }
+        if let Some(max) = project.max_output_bytes {
+            self.max_output_bytes = Some(max.min(current.max_output_bytes));
+        }
+        if let Some(max) = project.max_file_bytes {
+            self.max_file_bytes = Some(max.min(current.max_file_bytes));
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// HttpConfig
+// ---------------------------------------------------------------------------
+
+/// Authentication and TLS of `codegraph serve --http`; see
+/// [`crate::mcp::http`]. With no API keys the endpoint is open, which is
+/// only safe on localhost.
+///
+/// Like the sandbox, this is read from the user config and the environment
+/// only: a project's `.codegraph.yaml` cannot grant itself access.
+///
+/// ```yaml
+/// http:
+///   api_keys:
+///     - name: ci
+///       key_env: CODEGRAPH_CI_KEY
+///       tools: [codegraph_query, codegraph_callers, codegraph_impact]
+///     - name: alice
+///       key: 6f1c0e5a9b2d4c7e
+///   tls:
+///     cert: /etc/codegraph/cert.pem
+///     key: /etc/codegraph/key.pem
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct HttpConfig {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub api_keys: Vec<ApiKeyConfig>,
+
+    /// Serve HTTPS with this certificate instead of plain HTTP.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tls: Option<TlsConfig>,
+}
+
+/// One client's API key, sent as `Authorization: Bearer <key>` or
+/// `X-API-Key: <key>`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ApiKeyConfig {
+    /// Who the key belongs to, for logs and error messages.
+    pub name: String,
+
+    /// The key itself.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+
+    /// Environment variable holding the key, so it stays out of the file.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key_env: Option<String>,
+
+    /// Tools the key may call. Every tool when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tools: Option<Vec<String>>,
+}
+
+impl ApiKeyConfig {
+    /// The key, from `key` or else `key_env`; `None` when neither is set
+    /// or the variable is empty.
+    pub fn secret(&self) -> Option<String> {
+        self.key
+            .clone()
+            .or_else(|| std::env::var(self.key_env.as_ref()?).ok())
+            .filter(|k| !k.is_empty())
+    }
+}
+
+/// PEM files of the server certificate chain and its private key.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TlsConfig {
+    pub cert: PathBuf,
+    pub key: PathBuf,
+}
+
+// ---------------------------------------------------------------------------
+// ResponseConfig
+// ---------------------------------------------------------------------------
+
+/// Which response profile shapes tool output, plus custom profiles.
+/// Built-in profiles are `full` (no shaping), `agent-compact` and
+/// `agent-minimal`; a custom profile with a built-in name replaces it.
+/// `page_size` is the default page of tools with paged lists (see
+/// [`pagination`](crate::mcp::pagination)); `max_response_tokens` bounds
+/// every response, see [`profile`](crate::mcp::profile).
+///
+/// ```yaml
+/// response:
+///   page_size: 100
+///   max_response_tokens: 8000
+///   profile: agent-minimal
+///   profiles:
+///     tiny:
+///       max_depth: 1
+///       max_results: 5
+///       strip_bodies: true
+///       shorten_paths: true
+///       max_chars: 2000
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ResponseConfig {
+    /// Active profile name. Unset means `full`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+
+    /// Custom profiles by name.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub profiles: HashMap<String, ResponseProfile>,
+
+    /// Items per page of paged tool responses (default 100).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub page_size: Option<usize>,
+
+    /// Estimated token budget of a single tool response. Larger responses
+    /// are re-run at a lower detail level, then cut down and marked
+    /// `truncated`. Unset means no budget.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_response_tokens: Option<usize>,
+}
+
+impl ResponseConfig {
+    /// The active profile, or `None` when responses are left as is
+    /// (no profile, `full`, or an unknown name).
+    pub fn active_profile(&self) -> Option<ResponseProfile> {
+        let name = self.profile.as_deref()?;
+        self.profiles
+            .get(name)
+            .cloned()
+            .or_else(|| ResponseProfile::builtin(name))
+    }
+}
+
+/// Caps applied to every tool call: arguments are clamped before the tool
+/// runs and its JSON response is pruned afterwards.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ResponseProfile {
+    /// Upper bound for `depth`/`max_depth` arguments.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_depth: Option<usize>,
+
+    /// Upper bound for `limit` arguments and for every list in a response.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_results: Option<usize>,
+
+    /// Drop symbol bodies and code excerpts from responses.
+    #[serde(default)]
+    pub strip_bodies: bool,
+
+    /// Make paths project-relative and elide their middle directories.
+    #[serde(default)]
+    pub shorten_paths: bool,
+
+    /// Longest string value kept, in characters.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_string_chars: Option<usize>,
+
+    /// Largest response, in characters (roughly 4 per token). Lists are
+    /// halved until the response fits.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_chars: Option<usize>,
+}
+
+impl ResponseProfile {
+    /// A built-in profile by name. `full` has no caps and yields `None`.
+    pub fn builtin(name: &str) -> Option<Self> {
+        match name {
+            "agent-minimal" => Some(Self {
+                max_depth: Some(1),
+                max_results: Some(10),
+                strip_bodies: true,
+                shorten_paths: true,
+                max_string_chars: Some(160),
+                max_chars: Some(6_000),
+            }),
+            "agent-compact" => Some(Self {
+                max_depth: Some(2),
+                max_results: Some(25),
+                strip_bodies: true,
+                shorten_paths: true,
+                max_string_chars: Some(400),
+                max_chars: Some(16_000),
+            }),
+            _ => None,
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// RankingConfig
+// ---------------------------------------------------------------------------
+
+/// How much per-node priors move search results. Each weight is the
+/// largest relative change its prior makes to a fused score; `0` turns it
+/// off.
+///
+/// ```yaml
+/// ranking:
+///   page_rank_weight: 0.1   # boost for central symbols
+///   recency_weight: 0.05    # boost for recently changed code
+///   test_penalty: 0.1       # demotion of test code
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RankingConfig {
+    /// Boost for the symbol with the highest PageRank (default 0.1).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub page_rank_weight: Option<f64>,
+
+    /// Boost for code changed today, halving every 30 days (default 0.05).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recency_weight: Option<f64>,
+
+    /// Fraction of the score taken off test code (default 0.1).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub test_penalty: Option<f64>,
+}
+
+impl RankingConfig {
+    /// Prior weights for search, with defaults for unset fields.
+    pub fn prior_weights(&self) -> PriorWeights {
+        let default = PriorWeights::default();
+        PriorWeights {
+            page_rank: self.page_rank_weight.unwrap_or(default.page_rank),
+            recency: self.recency_weight.unwrap_or(default.recency),
+            test_penalty: self.test_penalty.unwrap_or(default.test_penalty),
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// ApiGuardConfig
+// ---------------------------------------------------------------------------
+
+/// Breaking-change guard run by `codegraph api-guard` and its pre-commit
+/// hook. Directory modes override the default; the longest prefix wins.
+///
+/// ```yaml
+/// api_guard:
+///   mode: warn              # off | warn | block
+///   directories:
+///     "src/api": block
+///     "src/internal": off
+///   note_files: [CHANGELOG.md, docs/BREAKING.md]
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ApiGuardConfig {
+    /// Mode outside the configured directories (default `warn`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mode: Option<GuardMode>,
+
+    /// Modes per directory prefix.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub directories: HashMap<String, GuardMode>,
+
+    /// Files whose change counts as a version bump note (default
+    /// `CHANGELOG.md`, `CHANGES.md`, `HISTORY.md`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note_files: Option<Vec<String>>,
+}
+
+impl ApiGuardConfig {
+    /// Guard policy, with defaults for unset fields.
+    pub fn policy(&self) -> GuardPolicy {
+        let default = GuardPolicy::default();
+        GuardPolicy {
+            mode: self.mode.unwrap_or(default.mode),
+            directories: self
+                .directories
+                .iter()
+                .map(|(dir, mode)| (dir.clone(), *mode))
+                .collect(),
+            note_files: self.note_files.clone().unwrap_or(default.note_files),
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// ArchitectureConfig
+// ---------------------------------------------------------------------------
+
+/// Layering rules checked by `codegraph arch-check` and
+/// `codegraph_architecture_check`. A file belongs to the layer of its most
+/// specific directory; `deny` lists layers a layer may not depend on and
+/// `allow` the only ones it may.
+///
+/// ```yaml
+/// architecture:
+///   layers:
+///     ui: [src/ui, src/components]
+///     service: [src/services]
+///     db: [src/db]
+///   rules:
+///     - from: ui
+///       deny: [db]
+///       reason: UI goes through services
+///     - from: db
+///       allow: []
+///   edge_kinds: [imports, calls]
+///   coupling:
+///     max_efferent: 15
+///     max_distance: 0.6
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ArchitectureConfig {
+    /// Directories per layer name.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub layers: HashMap<String, Vec<String>>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rules: Vec<LayerRule>,
+
+    /// Edge kinds checked (default `imports` and `calls`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub edge_kinds: Option<Vec<String>>,
+
+    /// Limits above which `codegraph_module_coupling` flags a module.
+    #[serde(default, skip_serializing_if = "CouplingThresholds::is_unset")]
+    pub coupling: CouplingThresholds,
+}
+
+impl ArchitectureConfig {
+    /// Layer policy, with defaults for unset fields. Unknown edge kinds
+    /// are ignored with a warning.
+    pub fn policy(&self) -> ArchitecturePolicy {
+        let default = ArchitecturePolicy::default();
+        let mut layers: Vec<(String, Vec<String>)> = self
+            .layers
+            .iter()
+            .map(|(name, dirs)| (name.clone(), dirs.clone()))
+            .collect();
+        layers.sort();
+        let edge_kinds = match &self.edge_kinds {
+            Some(kinds) => kinds
+                .iter()
+                .filter_map(|k| {
+                    let kind = EdgeKind::from_str_loose(k);
+                    if kind.is_none() {
+                        tracing::warn!("Unknown edge kind '{}' in architecture.edge_kinds", k);
+                    }
+                    kind
+                })
+                .collect(),
+            None => default.edge_kinds,
+        };
+        ArchitecturePolicy {
+            layers,
+            rules: self.rules.clone(),
+            edge_kinds,
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// FederationConfig
+// ---------------------------------------------------------------------------
+
+/// Indexes searched by `codegraph query --federated` and
+/// `codegraph_federated_query` together with the local one, e.g. those of
+/// shared libraries. Weights scale each index's share of the fused ranking.
+///
+/// ```yaml
+/// federation:
+///   local_weight: 1.0
+///   indexes:
+///     - name: shared-lib
+///       path: ../shared-lib/.codegraph/codegraph.db
+///       weight: 0.5
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FederationConfig {
+    /// Weight of the local index (default 1.0).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub local_weight: Option<f64>,
+
+    /// The other indexes; paths are relative to the project root.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub indexes: Vec<FederatedIndex>,
+}
+
+impl FederationConfig {
+    /// Open the configured indexes, see [`Federation::open`].
+    pub fn open(&self, root: &Path) -> Federation {
+        Federation::open(self.local_weight.unwrap_or(1.0), &self.indexes, root)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// SecretsConfig
+// ---------------------------------------------------------------------------
+
+/// Secret scanning by `codegraph_find_secrets`. Allowlist entries map a
+/// directory to the rule IDs not reported in it (`"*"` for all).
+///
+/// ```yaml
+/// secrets:
+///   min_entropy: 4.5
+///   allowlist:
+///     "tests/fixtures": ["*"]
+///     "docs": [SECRET-ENTROPY]
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SecretsConfig {
+    /// Report high-entropy string literals (default true).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub entropy: Option<bool>,
+
+    /// Bits per character from which a literal counts as high-entropy
+    /// (default 4.0).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_entropy: Option<f64>,
+
+    /// Rule IDs not reported per directory prefix.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub allowlist: HashMap<String, Vec<String>>,
+}
+
+impl SecretsConfig {
+    /// Scan options, with defaults for unset fields.
+    pub fn options(&self) -> SecretScanOptions {
+        let default = SecretScanOptions::default();
+        let mut allowlist: Vec<(String, Vec<String>)> = self
+            .allowlist
+            .iter()
+            .map(|(dir, rules)| (dir.clone(), rules.clone()))
+            .collect();
+        allowlist.sort();
+        SecretScanOptions {
+            entropy: self.entropy.unwrap_or(default.entropy),
+            min_entropy: self.min_entropy.unwrap_or(default.min_entropy),
+            allowlist,
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// DeadCodeConfig
+// ---------------------------------------------------------------------------
+
+/// Roots `codegraph_dead_code` walks the graph from; a symbol none of them
+/// reaches is dead. Each built-in root kind is on by default, `symbols`
+/// takes names (a trailing `*` matches a prefix) and `paths` directories.
+///
+/// ```yaml
+/// dead_code:
+///   tests: false
+///   symbols: [registerPlugins, "migrations::*"]
+///   paths: [scripts]
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DeadCodeConfig {
+    /// `main` functions and other program entry points.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mains: Option<bool>,
+
+    /// Exported symbols and package exports.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exports: Option<bool>,
+
+    /// Tests; off reports code only tests use.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tests: Option<bool>,
+
+    /// Routes and CLI commands registered with a framework.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub handlers: Option<bool>,
+
+    /// Further root symbols.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub symbols: Vec<String>,
+
+    /// Directories whose symbols are all roots.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub paths: Vec<String>,
+}
+
+impl DeadCodeConfig {
+    /// Reachability roots, with defaults for unset fields.
+    pub fn roots(&self) -> DeadCodeRoots {
+        let default = DeadCodeRoots::default();
+        DeadCodeRoots {
+            mains: self.mains.unwrap_or(default.mains),
+            exports: self.exports.unwrap_or(default.exports),
+            tests: self.tests.unwrap_or(default.tests),
+            handlers: self.handlers.unwrap_or(default.handlers),
+            symbols: self.symbols.clone(),
+            paths: self.paths.clone(),
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// GitConfig
+// ---------------------------------------------------------------------------
+
+/// Git metadata recorded while indexing. Blaming a file for per-symbol
+/// authorship costs one `git blame` per changed file; large histories can
+/// turn it off.
+///
+/// ```yaml
+/// git:
+///   blame_metadata: false
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GitConfig {
+    /// Record last commit, author and age of each symbol (default on).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blame_metadata: Option<bool>,
+}
+
+impl GitConfig {
+    /// Whether indexing blames changed files, on unless disabled.
+    pub fn blame_metadata(&self) -> bool {
+        self.blame_metadata.unwrap_or(true)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// I18nConfig
+// ---------------------------------------------------------------------------
+
+/// Localization of tool response messages (errors, summaries, notes).
+/// JSON field names never change with the locale.
+///
+/// ```yaml
+/// i18n:
+///   locale: de
+///   tools:
+///     codegraph_dead_code: tr
+///   bundle_dir: .codegraph/locales
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct I18nConfig {
+    /// Locale for every tool (e.g. `de`, `pt-BR`). English when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub locale: Option<String>,
+
+    /// Per-tool locale overrides, keyed by tool name.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub tools: HashMap<String, String>,
+
+    /// Directory of extra `<locale>.yaml` message bundles, relative to the
+    /// project root. Entries override the built-in bundles.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bundle_dir: Option<PathBuf>,
 }
 
 // ---------------------------------------------------------------------------
@@ -327,12 +1238,113 @@ mod tests {
         let config = CodeGraphConfig {
             version: "1.0".to_string(),
             preset: PresetName::Balanced,
-            tools: ToolsConfig::default(),
+            tools: ToolsConfig {
+                limits: ToolLimitsConfig {
+                    defaults: ToolLimit {
+                        timeout_secs: Some(60),
+                        max_concurrent: None,
+                    },
+                    per_tool: HashMap::from([(
+                        "codegraph_scan_security".to_string(),
+                        ToolLimit {
+                            timeout_secs: Some(0),
+                            max_concurrent: Some(1),
+                        },
+                    )]),
+                },
+                ..Default::default()
+            },
             performance: PerformanceConfig {
                 max_tool_count: Some(30),
                 exclude_tests: true,
+                adjacency_cache: true,
+                max_body_bytes: Some(8192),
+                compress_bodies: None,
+                query_log: false,
+                tombstones: Some(false),
+                tombstone_retention_days: Some(30),
+                ann_index: None,
             },
             contexts: std::collections::HashMap::new(),
+            synonyms: HashMap::from([("auth".to_string(), vec!["login".to_string()])]),
+            saved_queries: HashMap::from([(
+                "handlers".to_string(),
+                SavedQuery {
+                    query: "handler for {route}".to_string(),
+                    limit: Some(5),
+                    ..Default::default()
+                },
+            )]),
+            i18n: I18nConfig::default(),
+            parsing: ParsingConfig::default(),
+            sandbox: SandboxConfig {
+                trust: Some(Trust::Untrusted),
+                timeout_secs: Some(30),
+                ..Default::default()
+            },
+            response: ResponseConfig {
+                profile: Some("agent-minimal".to_string()),
+                page_size: Some(50),
+                max_response_tokens: Some(8000),
+                ..Default::default()
+            },
+            ranking: RankingConfig {
+                test_penalty: Some(0.0),
+                ..Default::default()
+            },
+            api_guard: ApiGuardConfig {
+                directories: HashMap::from([("src/api".to_string(), GuardMode::Block)]),
+                ..Default::default()
+            },
+            architecture: ArchitectureConfig {
+                layers: HashMap::from([
+                    ("ui".to_string(), vec!["src/ui".to_string()]),
+                    ("db".to_string(), vec!["src/db".to_string()]),
+                ]),
+                rules: vec![LayerRule {
+                    from: "ui".to_string(),
+                    deny: vec!["db".to_string()],
+                    ..Default::default()
+                }],
+                edge_kinds: Some(vec!["imports".to_string()]),
+                coupling: CouplingThresholds {
+                    max_efferent: Some(15),
+                    ..Default::default()
+                },
+            },
+            federation: FederationConfig {
+                local_weight: None,
+                indexes: vec![FederatedIndex {
+                    name: "shared".to_string(),
+                    path: PathBuf::from("../shared/.codegraph/codegraph.db"),
+                    weight: Some(0.5),
+                }],
+            },
+            http: HttpConfig {
+                api_keys: vec![ApiKeyConfig {
+                    name: "ci".to_string(),
+                    key_env: Some("CODEGRAPH_CI_KEY".to_string()),
+                    tools: Some(vec!["codegraph_query".to_string()]),
+                    ..Default::default()
+                }],
+                tls: Some(TlsConfig {
+                    cert: PathBuf::from("cert.pem"),
+                    key: PathBuf::from("key.pem"),
+                }),
+            },
+            secrets: SecretsConfig {
+                min_entropy: Some(4.5),
+                allowlist: HashMap::from([("tests/fixtures".to_string(), vec!["*".to_string()])]),
+                ..Default::default()
+            },
+            dead_code: DeadCodeConfig {
+                tests: Some(false),
+                paths: vec!["scripts".to_string()],
+                ..Default::default()
+            },
+            git: GitConfig {
+                blame_metadata: Some(false),
+            },
         };
 
         let yaml = serde_yaml::to_string(&config).unwrap();
@@ -342,6 +1354,47 @@ mod tests {
         assert_eq!(back.preset, PresetName::Balanced);
         assert_eq!(back.performance.max_tool_count, Some(30));
         assert!(back.performance.exclude_tests);
+        let limits = &back.tools.limits;
+        assert_eq!(
+            limits.timeout("codegraph_query"),
+            Some(std::time::Duration::from_secs(60))
+        );
+        assert_eq!(limits.timeout("codegraph_scan_security"), None);
+        assert_eq!(
+            limits.max_concurrent("codegraph_query"),
+            Some(DEFAULT_TOOL_MAX_CONCURRENT)
+        );
+        assert_eq!(limits.max_concurrent("codegraph_scan_security"), Some(1));
+        assert_eq!(back.sandbox.trust, Some(Trust::Untrusted));
+        assert_eq!(back.sandbox.timeout_secs, Some(30));
+        assert_eq!(back.http, config.http);
+        assert_eq!(
+            back.response.active_profile(),
+            ResponseProfile::builtin("agent-minimal")
+        );
+        assert_eq!(back.response.max_response_tokens, Some(8000));
+        assert_eq!(back.ranking.prior_weights().test_penalty, 0.0);
+        assert_eq!(back.ranking.page_rank_weight, None);
+        assert_eq!(back.synonym_map().get("AUTH"), ["login"]);
+        assert_eq!(back.saved_queries["handlers"].parameters(), ["route"]);
+        let policy = back.api_guard.policy();
+        assert_eq!(policy.mode_for("src/api/v1.rs"), GuardMode::Block);
+        assert_eq!(policy.mode_for("src/lib.rs"), GuardMode::Warn);
+        let layers = back.architecture.policy();
+        assert_eq!(layers.layer_of("src/ui/app.ts"), Some("ui"));
+        assert!(layers.violated_rule("ui", "db").is_some());
+        assert_eq!(layers.edge_kinds, vec![EdgeKind::Imports]);
+        assert_eq!(back.architecture.coupling.max_efferent, Some(15));
+        assert_eq!(back.federation.indexes[0].name, "shared");
+        assert_eq!(back.federation.indexes[0].weight, Some(0.5));
+        let secrets = back.secrets.options();
+        assert!(secrets.entropy);
+        assert_eq!(secrets.min_entropy, 4.5);
+        assert!(secrets.allows("tests/fixtures/keys.py", "SECRET-001"));
+        let roots = back.dead_code.roots();
+        assert!(roots.mains && !roots.tests);
+        assert_eq!(roots.paths, ["scripts"]);
+        assert!(!back.git.blame_metadata());
     }
 
     #[test]
@@ -21,6 +21,7 @@ use std::collections::{HashMap, HashSet};
 use rusqlite::{params, Connection};
 
 use crate::context::budget::{estimate_tokens, signature_only, truncate_to_fit};
+use crate::db::bodies::select_nodes;
 use crate::db::converters::row_to_code_node;
 use crate::graph::search::{HybridSearch, SearchOptions};
 use crate::types::CodeNode;
@@ -297,7 +298,7 @@ impl<'a> ContextAssembler<'a> {
     /// Load a single [`CodeNode`] by ID from the database.
     fn load_node(&self, id: &str) -> Option<CodeNode> {
         self.conn
-            .query_row("SELECT * FROM nodes WHERE id = ?1", params![id], |row| {
+            .query_row(select_nodes!("WHERE n.id = ?1"), params![id], |row| {
                 row_to_code_node(row)
             })
             .ok()
@@ -346,8 +347,7 @@ impl<'a> ContextAssembler<'a> {
         let core_ids: HashSet<&str> = core_nodes.iter().map(|n| n.id.as_str()).collect();
 
         // Query for test/spec nodes.
-        let sql =
-            "SELECT * FROM nodes WHERE LOWER(name) LIKE '%test%' OR LOWER(name) LIKE '%spec%'";
+        let sql = select_nodes!("WHERE LOWER(n.name) LIKE '%test%' OR LOWER(n.name) LIKE '%spec%'");
         let mut stmt = match self.conn.prepare(sql) {
             Ok(s) => s,
             Err(_) => return tests,
@@ -418,7 +418,7 @@ impl<'a> ContextAssembler<'a> {
         let mut siblings: Vec<CodeNode> = Vec::new();
 
         for file in files {
-            let sql = "SELECT * FROM nodes WHERE file_path = ?1";
+            let sql = select_nodes!("WHERE n.file_path = ?1");
             let mut stmt = match self.conn.prepare(sql) {
                 Ok(s) => s,
                 Err(_) => continue,
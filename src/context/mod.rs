@@ -2,3 +2,4 @@
 
 pub mod assembler;
 pub mod budget;
+pub mod module_summary;
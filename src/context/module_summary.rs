@@ -0,0 +1,306 @@
+//! Context for natural-language module summaries.
+//!
+//! `codegraph_summarize_module` asks the client's model (MCP sampling) to
+//! summarize a directory. This module assembles what the model is shown:
+//! the directory's files, its highest-ranked symbols with signatures and
+//! the first line of their docs, the calls between its symbols, and the
+//! directories it calls into and is called from. The summary is cached
+//! under a hash of the content hashes of every indexed file in the
+//! directory, so re-indexing any change invalidates it.
+
+use std::collections::{BTreeSet, HashMap};
+use std::fmt::Write as _;
+
+use sha2::{Digest, Sha256};
+
+use super::budget::signature_only;
+use crate::error::Result;
+use crate::graph::api_guard::in_directory;
+use crate::graph::stats::directory_of;
+use crate::graph::store::GraphStore;
+use crate::graph::test_gaps::page_ranks;
+use crate::types::{CodeNode, NodeKind};
+
+/// Instructions sent as the sampling system prompt.
+pub const SYSTEM_PROMPT: &str = "You document software modules for engineers new to a codebase. \
+Given the structure of one directory, write a concise summary in plain prose: what the module \
+is responsible for, its main entry points, how its parts fit together, and what it depends on \
+and is used by. Do not list every symbol. Do not invent behaviour the context does not show.";
+
+/// Symbols shown when the caller names no limit.
+pub const DEFAULT_MAX_SYMBOLS: usize = 25;
+
+/// Upper bound on the tokens the model may write.
+pub const MAX_SUMMARY_TOKENS: u32 = 600;
+
+/// Internal calls listed in the context.
+const MAX_INTERNAL_CALLS: usize = 30;
+/// Directories listed per boundary direction.
+const MAX_NEIGHBOURS: usize = 10;
+
+const FILE_HASHES_SQL: &str = "SELECT file_path, content_hash FROM file_hashes ORDER BY file_path";
+const CALL_EDGES_SQL: &str = "\
+SELECT s.name, s.file_path, t.name, t.file_path
+FROM edges e
+JOIN nodes s ON s.id = e.source_id
+JOIN nodes t ON t.id = e.target_id
+WHERE e.type = 'calls'";
+
+/// What the model is shown for one directory.
+#[derive(Debug, Clone)]
+pub struct ModuleContext {
+    /// The directory, without a trailing slash; empty for the whole project.
+    pub directory: String,
+    /// Hash of every file's content hash; the cache key.
+    pub content_hash: String,
+    pub files: Vec<String>,
+    pub symbol_count: usize,
+    /// The context as a Markdown prompt.
+    pub prompt: String,
+}
+
+/// Context for the files under `directory`, showing at most `max_symbols`
+/// symbols, or `None` when no indexed file is there.
+pub fn module_context(
+    store: &GraphStore,
+    directory: &str,
+    max_symbols: usize,
+) -> Result<Option<ModuleContext>> {
+    let directory = directory.trim_matches('/').trim_start_matches("./");
+    let directory = if directory == "." { "" } else { directory };
+
+    let files: Vec<(String, String)> = {
+        let mut stmt = store.conn.prepare_cached(FILE_HASHES_SQL)?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        rows.collect::<std::result::Result<Vec<(String, String)>, _>>()?
+            .into_iter()
+            .filter(|(path, _)| in_directory(path, directory))
+            .collect()
+    };
+    if files.is_empty() {
+        return Ok(None);
+    }
+    let mut hasher = Sha256::new();
+    for (path, hash) in &files {
+        hasher.update(path.as_bytes());
+        hasher.update([0]);
+        hasher.update(hash.as_bytes());
+        hasher.update([0]);
+    }
+    let content_hash = hex::encode(hasher.finalize());
+
+    let mut symbols: Vec<CodeNode> = Vec::new();
+    for (path, _) in &files {
+        symbols.extend(
+            store
+                .get_nodes_by_file(path)?
+                .into_iter()
+                .filter(|n| !matches!(n.kind, NodeKind::Module | NodeKind::Variable)),
+        );
+    }
+    let ranks = page_ranks(store)?;
+    let rank = |n: &CodeNode| ranks.get(&n.id).copied().unwrap_or(0.0);
+    symbols.sort_by(|a, b| {
+        rank(b)
+            .total_cmp(&rank(a))
+            .then_with(|| (&a.file_path, a.start_line).cmp(&(&b.file_path, b.start_line)))
+    });
+
+    let label = if directory.is_empty() { "." } else { directory };
+    let mut prompt = format!("# Module `{label}`\n\n## Files ({})\n\n", files.len());
+    for (path, _) in &files {
+        let _ = writeln!(prompt, "- {path}");
+    }
+    let _ = writeln!(
+        prompt,
+        "\n## Key symbols (top {} of {} by PageRank)\n",
+        max_symbols.min(symbols.len()),
+        symbols.len()
+    );
+    for n in symbols.iter().take(max_symbols) {
+        let signature = n
+            .body
+            .as_deref()
+            .map(signature_only)
+            .unwrap_or_else(|| n.name.clone());
+        let _ = write!(
+            prompt,
+            "- {} `{}` ({}:{})",
+            n.kind.as_str(),
+            signature,
+            n.file_path,
+            n.start_line
+        );
+        if let Some(doc) = n
+            .documentation
+            .as_deref()
+            .and_then(|d| d.lines().map(str::trim).find(|l| !l.is_empty()))
+        {
+            let _ = write!(prompt, " — {doc}");
+        }
+        prompt.push('\n');
+    }
+    write_call_structure(store, directory, &mut prompt)?;
+
+    Ok(Some(ModuleContext {
+        directory: directory.to_string(),
+        content_hash,
+        files: files.into_iter().map(|(path, _)| path).collect(),
+        symbol_count: symbols.len(),
+        prompt,
+    }))
+}
+
+/// Calls inside the module, and per directory the calls crossing its
+/// boundary in each direction.
+fn write_call_structure(store: &GraphStore, directory: &str, prompt: &mut String) -> Result<()> {
+    let mut internal: BTreeSet<(String, String)> = BTreeSet::new();
+    let mut outgoing: HashMap<String, usize> = HashMap::new();
+    let mut incoming: HashMap<String, usize> = HashMap::new();
+    let mut stmt = store.conn.prepare_cached(CALL_EDGES_SQL)?;
+    let mut rows = stmt.query([])?;
+    while let Some(row) = rows.next()? {
+        let source_file: String = row.get(1)?;
+        let target_file: String = row.get(3)?;
+        match (
+            in_directory(&source_file, directory),
+            in_directory(&target_file, directory),
+        ) {
+            (true, true) => {
+                internal.insert((row.get(0)?, row.get(2)?));
+            }
+            (true, false) => {
+                *outgoing
+                    .entry(directory_of(&target_file).to_string())
+                    .or_default() += 1
+            }
+            (false, true) => {
+                *incoming
+                    .entry(directory_of(&source_file).to_string())
+                    .or_default() += 1
+            }
+            (false, false) => {}
+        }
+    }
+
+    if !internal.is_empty() {
+        let _ = writeln!(prompt, "\n## Internal calls\n");
+        for (caller, callee) in internal.iter().take(MAX_INTERNAL_CALLS) {
+            let _ = writeln!(prompt, "- {caller} → {callee}");
+        }
+        if internal.len() > MAX_INTERNAL_CALLS {
+            let _ = writeln!(prompt, "- … {} more", internal.len() - MAX_INTERNAL_CALLS);
+        }
+    }
+    for (title, counts) in [("Calls into", outgoing), ("Called from", incoming)] {
+        if counts.is_empty() {
+            continue;
+        }
+        let _ = writeln!(prompt, "\n## {title}\n");
+        let mut ranked: Vec<(String, usize)> = counts.into_iter().collect();
+        ranked.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        for (dir, n) in ranked.into_iter().take(MAX_NEIGHBOURS) {
+            let _ = writeln!(prompt, "- {dir} ({n} calls)");
+        }
+    }
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::schema::initialize_database;
+    use crate::types::{CodeEdge, EdgeKind, Language};
+
+    fn function(id: &str, file: &str, doc: Option<&str>) -> CodeNode {
+        CodeNode {
+            id: id.to_string(),
+            name: id.to_string(),
+            qualified_name: None,
+            kind: NodeKind::Function,
+            file_path: file.to_string(),
+            start_line: 1,
+            end_line: 5,
+            start_column: 0,
+            end_column: 0,
+            language: Language::TypeScript,
+            body: Some(format!("function {id}(): void {{\n}}")),
+            documentation: doc.map(str::to_string),
+            exported: Some(true),
+        }
+    }
+
+    fn call(source: &str, target: &str) -> CodeEdge {
+        CodeEdge {
+            source: source.to_string(),
+            target: target.to_string(),
+            kind: EdgeKind::Calls,
+            file_path: String::new(),
+            line: 1,
+            metadata: None,
+        }
+    }
+
+    fn set_hash(store: &GraphStore, path: &str, hash: &str) {
+        store
+            .conn
+            .execute(
+                "INSERT OR REPLACE INTO file_hashes (file_path, content_hash, language) \
+                 VALUES (?1, ?2, 'typescript')",
+                [path, hash],
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn context_covers_symbols_calls_and_the_module_boundary() {
+        let store = GraphStore::from_connection(initialize_database(":memory:").unwrap());
+        store
+            .upsert_nodes(&[
+                function(
+                    "login",
+                    "src/auth/login.ts",
+                    Some("Signs a user in.\nMore."),
+                ),
+                function("hashPassword", "src/auth/crypto.ts", None),
+                function("query", "src/db/conn.ts", None),
+                function("handler", "src/api/routes.ts", None),
+            ])
+            .unwrap();
+        store
+            .upsert_edges(&[
+                call("login", "hashPassword"),
+                call("login", "query"),
+                call("handler", "login"),
+            ])
+            .unwrap();
+        for path in ["src/auth/login.ts", "src/auth/crypto.ts", "src/db/conn.ts"] {
+            set_hash(&store, path, "1");
+        }
+
+        let ctx = module_context(&store, "src/auth/", 10).unwrap().unwrap();
+        assert_eq!(ctx.directory, "src/auth");
+        assert_eq!(ctx.files, vec!["src/auth/crypto.ts", "src/auth/login.ts"]);
+        assert_eq!(ctx.symbol_count, 2);
+        assert!(ctx.prompt.contains(
+            "- function `function login(): void` (src/auth/login.ts:1) — Signs a user in."
+        ));
+        assert!(ctx.prompt.contains("- login → hashPassword"));
+        assert!(ctx.prompt.contains("## Calls into\n\n- src/db (1 calls)"));
+        assert!(ctx.prompt.contains("## Called from\n\n- src/api (1 calls)"));
+
+        // Only files under the directory key the cache.
+        set_hash(&store, "src/db/conn.ts", "2");
+        let same = module_context(&store, "src/auth", 10).unwrap().unwrap();
+        assert_eq!(same.content_hash, ctx.content_hash);
+        set_hash(&store, "src/auth/crypto.ts", "2");
+        let changed = module_context(&store, "src/auth", 10).unwrap().unwrap();
+        assert_ne!(changed.content_hash, ctx.content_hash);
+
+        assert!(module_context(&store, "src/none", 10).unwrap().is_none());
+    }
+}
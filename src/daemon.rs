@@ -0,0 +1,422 @@
+//! Long-running daemon with a unix-socket control protocol.
+//!
+//! `codegraph daemon start` keeps the store, embedding model and (with the
+//! `reranking` feature) cross-encoder loaded, so CLI invocations that find
+//! a running daemon skip the model and database startup cost.
+//!
+//! The protocol is newline-delimited JSON over `<root>/.codegraph/daemon.sock`:
+//! each request line is a [`DaemonRequest`] and is answered by exactly one
+//! [`DaemonResponse`] line. A connection may carry several requests.
+//! Requests are served one at a time, matching the single-writer store.
+//!
+//! ```text
+//! -> {"cmd":"query","query":"parse config","limit":5}
+//! <- {"type":"results","results":[...]}
+//! -> {"cmd":"shutdown"}
+//! <- {"type":"shutting_down"}
+//! ```
+
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::fs::PermissionsExt;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::loader::load_config;
+use crate::error::{CodeGraphError, Result};
+use crate::graph::search::{HybridSearch, SearchOptions, SearchResult};
+use crate::graph::store::GraphStore;
+use crate::indexer::embedder::EmbeddingEngine;
+use crate::indexer::{IndexOptions, IndexingPipeline};
+
+/// How long the daemon waits on an idle client before dropping it, so a
+/// stuck client cannot block everyone else.
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Candidates fetched per requested result when a deep query is re-ranked.
+#[cfg(feature = "reranking")]
+const RERANK_CANDIDATES: usize = 3;
+
+// ---------------------------------------------------------------------------
+// Protocol
+// ---------------------------------------------------------------------------
+
+/// A control request sent to the daemon.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "cmd", rename_all = "snake_case")]
+pub enum DaemonRequest {
+    /// Re-index the daemon's project root.
+    Index {
+        #[serde(default)]
+        force: bool,
+    },
+    /// Hybrid search, optionally re-ranked by the cross-encoder.
+    Query {
+        query: String,
+        #[serde(default = "default_limit")]
+        limit: usize,
+        #[serde(default)]
+        deep: bool,
+    },
+    Status,
+    Shutdown,
+}
+
+fn default_limit() -> usize {
+    10
+}
+
+/// The daemon's answer to one [`DaemonRequest`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum DaemonResponse {
+    Indexed {
+        files_indexed: usize,
+        files_skipped: usize,
+        nodes_created: usize,
+        edges_created: usize,
+        duration_ms: u64,
+    },
+    Results {
+        results: Vec<SearchResult>,
+    },
+    Status(DaemonStatus),
+    ShuttingDown,
+    Error {
+        message: String,
+    },
+}
+
+/// Snapshot returned by [`DaemonRequest::Status`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DaemonStatus {
+    pub root: String,
+    pub pid: u32,
+    pub uptime_secs: u64,
+    pub requests_served: u64,
+    pub files: usize,
+    pub nodes: usize,
+    pub edges: usize,
+    pub embedder_loaded: bool,
+    pub reranker_loaded: bool,
+}
+
+/// Socket path of the daemon serving `root`.
+pub fn socket_path(root: &Path) -> PathBuf {
+    root.join(".codegraph").join("daemon.sock")
+}
+
+// ---------------------------------------------------------------------------
+// Server
+// ---------------------------------------------------------------------------
+
+/// Daemon state: the open store plus the warm models.
+pub struct Daemon {
+    root: PathBuf,
+    store: GraphStore,
+    embedder: Option<EmbeddingEngine>,
+    #[cfg(feature = "reranking")]
+    reranker: Option<crate::graph::reranker::Reranker>,
+    started: Instant,
+    requests_served: u64,
+}
+
+impl Daemon {
+    /// Load the models and wrap `store`. Models that fail to load are left
+    /// out; the requests that use them degrade as the CLI would.
+    pub fn new(root: PathBuf, store: GraphStore) -> Self {
+        let embedder = match EmbeddingEngine::try_new() {
+            Ok(e) => Some(e),
+            Err(e) => {
+                tracing::info!("daemon running without embeddings: {}", e);
+                None
+            }
+        };
+        Self {
+            root,
+            store,
+            embedder,
+            #[cfg(feature = "reranking")]
+            reranker: crate::graph::reranker::Reranker::try_new().ok(),
+            started: Instant::now(),
+            requests_served: 0,
+        }
+    }
+
+    /// Answer a single request.
+    pub fn handle(&mut self, request: DaemonRequest) -> DaemonResponse {
+        self.requests_served += 1;
+        let outcome = match request {
+            DaemonRequest::Index { force } => self.index(force),
+            DaemonRequest::Query { query, limit, deep } => self.query(&query, limit, deep),
+            DaemonRequest::Status => self.status().map(DaemonResponse::Status),
+            DaemonRequest::Shutdown => Ok(DaemonResponse::ShuttingDown),
+        };
+        outcome.unwrap_or_else(|e| DaemonResponse::Error {
+            message: e.to_string(),
+        })
+    }
+
+    fn index(&self, force: bool) -> Result<DaemonResponse> {
+        let config = load_config(None, Some(&self.root)).unwrap_or_default();
+        let pipeline = IndexingPipeline::with_embedder(&self.store, self.embedder.as_ref())
+            .with_blame_metadata(config.git.blame_metadata());
+        let result = pipeline.index_directory(&IndexOptions {
+            root_dir: self.root.clone(),
+            incremental: !force,
+        })?;
+        Ok(DaemonResponse::Indexed {
+            files_indexed: result.files_indexed,
+            files_skipped: result.files_skipped,
+            nodes_created: result.nodes_created,
+            edges_created: result.edges_created,
+            duration_ms: result.duration_ms as u64,
+        })
+    }
+
+    fn query(&self, query: &str, limit: usize, deep: bool) -> Result<DaemonResponse> {
+        let search = HybridSearch::with_embedder(&self.store.conn, self.embedder.as_ref());
+        #[cfg(feature = "reranking")]
+        if deep {
+            if let Some(ref reranker) = self.reranker {
+                let opts = SearchOptions {
+                    limit: Some(limit * RERANK_CANDIDATES),
+                    ..Default::default()
+                };
+                let candidates = search.search(query, &opts)?;
+                let results =
+                    crate::graph::reranker::deep_search(query, reranker, candidates, limit)
+                        .map_err(CodeGraphError::Other)?;
+                return Ok(DaemonResponse::Results { results });
+            }
+        }
+        #[cfg(not(feature = "reranking"))]
+        let _ = deep;
+
+        let opts = SearchOptions {
+            limit: Some(limit),
+            ..Default::default()
+        };
+        let results = search.search(query, &opts)?;
+        Ok(DaemonResponse::Results { results })
+    }
+
+    fn status(&self) -> Result<DaemonStatus> {
+        let stats = self.store.get_stats()?;
+        #[cfg(feature = "reranking")]
+        let reranker_loaded = self.reranker.is_some();
+        #[cfg(not(feature = "reranking"))]
+        let reranker_loaded = false;
+        Ok(DaemonStatus {
+            root: self.root.display().to_string(),
+            pid: std::process::id(),
+            uptime_secs: self.started.elapsed().as_secs(),
+            requests_served: self.requests_served,
+            files: stats.files,
+            nodes: stats.nodes,
+            edges: stats.edges,
+            embedder_loaded: self.embedder.is_some(),
+            reranker_loaded,
+        })
+    }
+
+    /// Listen on `socket` until a shutdown request arrives.
+    ///
+    /// Refuses to start if another daemon answers on the socket; a stale
+    /// socket file left by a killed daemon is replaced. The socket is
+    /// owner-only and removed on shutdown.
+    pub fn serve(mut self, socket: &Path) -> Result<()> {
+        if socket.exists() {
+            if UnixStream::connect(socket).is_ok() {
+                return Err(CodeGraphError::Other(format!(
+                    "a daemon is already listening on {}",
+                    socket.display()
+                )));
+            }
+            std::fs::remove_file(socket)?;
+        }
+        let listener = UnixListener::bind(socket)?;
+        std::fs::set_permissions(socket, std::fs::Permissions::from_mode(0o600))?;
+
+        let mut outcome = Ok(());
+        for stream in listener.incoming() {
+            let stream = match stream {
+                Ok(s) => s,
+                Err(e) => {
+                    tracing::warn!("daemon accept failed: {}", e);
+                    continue;
+                }
+            };
+            match self.serve_connection(stream) {
+                Ok(true) => break,
+                Ok(false) => {}
+                Err(CodeGraphError::Io(e))
+                    if matches!(
+                        e.kind(),
+                        std::io::ErrorKind::WouldBlock
+                            | std::io::ErrorKind::TimedOut
+                            | std::io::ErrorKind::BrokenPipe
+                    ) => {}
+                Err(e) => {
+                    outcome = Err(e);
+                    break;
+                }
+            }
+        }
+        let _ = std::fs::remove_file(socket);
+        outcome
+    }
+
+    /// Serve every request on one connection. Returns `true` once a
+    /// shutdown was requested.
+    fn serve_connection(&mut self, stream: UnixStream) -> Result<bool> {
+        stream.set_read_timeout(Some(CLIENT_TIMEOUT))?;
+        let mut writer = stream.try_clone()?;
+        for line in BufReader::new(stream).lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let response = match serde_json::from_str::<DaemonRequest>(&line) {
+                Ok(request) => self.handle(request),
+                Err(e) => DaemonResponse::Error {
+                    message: format!("invalid request: {e}"),
+                },
+            };
+            writeln!(writer, "{}", serde_json::to_string(&response)?)?;
+            if matches!(response, DaemonResponse::ShuttingDown) {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Client
+// ---------------------------------------------------------------------------
+
+/// Send one request to the daemon listening on `socket`.
+///
+/// Fails with an I/O error when no daemon is running, which callers use to
+/// fall back to doing the work in-process.
+pub fn request(socket: &Path, request: &DaemonRequest) -> Result<DaemonResponse> {
+    let mut stream = UnixStream::connect(socket)?;
+    writeln!(stream, "{}", serde_json::to_string(request)?)?;
+    let mut line = String::new();
+    BufReader::new(stream).read_line(&mut line)?;
+    if line.is_empty() {
+        return Err(CodeGraphError::Other(
+            "daemon closed the connection without answering".into(),
+        ));
+    }
+    Ok(serde_json::from_str(&line)?)
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::schema::initialize_database;
+
+    fn daemon(root: &Path) -> Daemon {
+        let store = GraphStore::from_connection(initialize_database(":memory:").unwrap());
+        Daemon::new(root.to_path_buf(), store)
+    }
+
+    #[test]
+    fn requests_round_trip_as_tagged_json() {
+        let req: DaemonRequest = serde_json::from_str(r#"{"cmd":"query","query":"x"}"#).unwrap();
+        assert_eq!(
+            req,
+            DaemonRequest::Query {
+                query: "x".into(),
+                limit: 10,
+                deep: false
+            }
+        );
+        let json = serde_json::to_string(&DaemonRequest::Index { force: true }).unwrap();
+        assert_eq!(json, r#"{"cmd":"index","force":true}"#);
+    }
+
+    #[test]
+    fn serves_index_query_status_and_shutdown_over_socket() {
+        let tmp = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(tmp.path().join(".codegraph")).unwrap();
+        std::fs::write(
+            tmp.path().join("lib.ts"),
+            "export function parseConfig(path: string) { return path; }\n",
+        )
+        .unwrap();
+        let socket = socket_path(tmp.path());
+        let d = daemon(tmp.path());
+        let server = {
+            let socket = socket.clone();
+            std::thread::spawn(move || d.serve(&socket))
+        };
+        while !socket.exists() {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+
+        match request(&socket, &DaemonRequest::Index { force: false }).unwrap() {
+            DaemonResponse::Indexed { files_indexed, .. } => assert_eq!(files_indexed, 1),
+            other => panic!("unexpected response: {other:?}"),
+        }
+        let query = DaemonRequest::Query {
+            query: "parseConfig".into(),
+            limit: 5,
+            deep: false,
+        };
+        match request(&socket, &query).unwrap() {
+            DaemonResponse::Results { results } => {
+                assert_eq!(results[0].name, "parseConfig")
+            }
+            other => panic!("unexpected response: {other:?}"),
+        }
+        match request(&socket, &DaemonRequest::Status).unwrap() {
+            DaemonResponse::Status(status) => {
+                assert_eq!(status.files, 1);
+                assert_eq!(status.requests_served, 3);
+            }
+            other => panic!("unexpected response: {other:?}"),
+        }
+
+        // A second daemon on the same socket is refused.
+        assert!(daemon(tmp.path()).serve(&socket).is_err());
+
+        assert!(matches!(
+            request(&socket, &DaemonRequest::Shutdown).unwrap(),
+            DaemonResponse::ShuttingDown
+        ));
+        server.join().unwrap().unwrap();
+        assert!(!socket.exists());
+        assert!(request(&socket, &DaemonRequest::Status).is_err());
+    }
+
+    #[test]
+    fn malformed_request_gets_error_response() {
+        let tmp = tempfile::tempdir().unwrap();
+        let socket = tmp.path().join("d.sock");
+        let d = daemon(tmp.path());
+        let server = {
+            let socket = socket.clone();
+            std::thread::spawn(move || d.serve(&socket))
+        };
+        while !socket.exists() {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+
+        let mut stream = UnixStream::connect(&socket).unwrap();
+        writeln!(stream, r#"{{"cmd":"explode"}}"#).unwrap();
+        writeln!(stream, r#"{{"cmd":"shutdown"}}"#).unwrap();
+        let mut lines = BufReader::new(stream).lines();
+        let first: DaemonResponse = serde_json::from_str(&lines.next().unwrap().unwrap()).unwrap();
+        assert!(matches!(first, DaemonResponse::Error { .. }));
+        server.join().unwrap().unwrap();
+    }
+}
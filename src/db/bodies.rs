@@ -0,0 +1,170 @@
+//! Node body storage.
+//!
+//! Node bodies (source text) are the bulk of an index. They live in the
+//! `node_bodies` table, one row per node, optionally zstd-compressed and
+//! truncated to a configurable size, instead of inside the `metadata` JSON.
+//! [`row_to_code_node`](super::converters::row_to_code_node) decodes them
+//! when a query joins the table in (see [`select_nodes!`]).
+
+use rusqlite::types::Type;
+
+/// Default cap on stored body size, matching the historical 4 KB limit.
+pub const DEFAULT_MAX_BODY_BYTES: usize = 4096;
+
+/// Bodies shorter than this are stored as-is; compression would not pay
+/// for its frame overhead.
+const MIN_COMPRESS_BYTES: usize = 256;
+
+/// zstd level used for bodies: fast, and close to the best ratio on code.
+const ZSTD_LEVEL: i32 = 3;
+
+/// How node bodies are written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BodyStorage {
+    /// Bodies longer than this many bytes are truncated at a char boundary.
+    /// `0` stores no bodies at all.
+    pub max_bytes: usize,
+    /// Compress bodies with zstd.
+    pub compress: bool,
+}
+
+impl Default for BodyStorage {
+    fn default() -> Self {
+        Self {
+            max_bytes: DEFAULT_MAX_BODY_BYTES,
+            compress: true,
+        }
+    }
+}
+
+/// Encoding of a stored body, persisted in `node_bodies.encoding`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BodyEncoding {
+    Plain,
+    Zstd,
+}
+
+impl BodyEncoding {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Plain => "plain",
+            Self::Zstd => "zstd",
+        }
+    }
+
+    pub fn from_str_loose(s: &str) -> Option<Self> {
+        match s {
+            "plain" => Some(Self::Plain),
+            "zstd" => Some(Self::Zstd),
+            _ => None,
+        }
+    }
+}
+
+/// Encode `body` for storage, or `None` when bodies are disabled.
+pub fn encode_body(body: &str, storage: BodyStorage) -> Option<(BodyEncoding, Vec<u8>)> {
+    if storage.max_bytes == 0 {
+        return None;
+    }
+    let body = if body.len() > storage.max_bytes {
+        &body[..body.floor_char_boundary(storage.max_bytes)]
+    } else {
+        body
+    };
+    if storage.compress && body.len() >= MIN_COMPRESS_BYTES {
+        if let Ok(compressed) = zstd::encode_all(body.as_bytes(), ZSTD_LEVEL) {
+            if compressed.len() < body.len() {
+                return Some((BodyEncoding::Zstd, compressed));
+            }
+        }
+    }
+    Some((BodyEncoding::Plain, body.as_bytes().to_vec()))
+}
+
+/// Decode a stored body. Errors are reported as conversion failures of
+/// column `column` so they surface like any other bad row.
+pub fn decode_body(encoding: &str, data: Vec<u8>, column: usize) -> rusqlite::Result<String> {
+    let bytes = match BodyEncoding::from_str_loose(encoding) {
+        Some(BodyEncoding::Plain) => data,
+        Some(BodyEncoding::Zstd) => zstd::decode_all(data.as_slice())
+            .map_err(|e| rusqlite::Error::FromSqlConversionFailure(column, Type::Blob, e.into()))?,
+        None => {
+            return Err(rusqlite::Error::FromSqlConversionFailure(
+                column,
+                Type::Blob,
+                format!("unknown body encoding '{encoding}'").into(),
+            ))
+        }
+    };
+    Ok(String::from_utf8(bytes)
+        .unwrap_or_else(|e| String::from_utf8_lossy(e.as_bytes()).into_owned()))
+}
+
+/// Expand to a `SELECT` over `nodes n` left-joined with `node_bodies b`,
+/// followed by the given SQL tail, in the column layout that
+/// [`row_to_code_node`](super::converters::row_to_code_node) expects.
+///
+/// ```ignore
+/// conn.prepare_cached(select_nodes!("WHERE n.id = ?1"))?;
+/// ```
+macro_rules! select_nodes {
+    () => {
+        "SELECT n.*, b.encoding AS body_encoding, b.body AS body_data \
+         FROM nodes n LEFT JOIN node_bodies b ON b.node_id = n.id"
+    };
+    ($tail:literal) => {
+        concat!(
+            "SELECT n.*, b.encoding AS body_encoding, b.body AS body_data \
+             FROM nodes n LEFT JOIN node_bodies b ON b.node_id = n.id ",
+            $tail
+        )
+    };
+}
+pub(crate) use select_nodes;
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_plain_and_compressed() {
+        let short = "fn a() {}";
+        let (enc, data) = encode_body(short, BodyStorage::default()).unwrap();
+        assert_eq!(enc, BodyEncoding::Plain);
+        assert_eq!(decode_body(enc.as_str(), data, 0).unwrap(), short);
+
+        let long = "let total = items.iter().map(|i| i.price).sum();\n".repeat(40);
+        let storage = BodyStorage {
+            max_bytes: 64 * 1024,
+            compress: true,
+        };
+        let (enc, data) = encode_body(&long, storage).unwrap();
+        assert_eq!(enc, BodyEncoding::Zstd);
+        assert!(data.len() < long.len() / 4);
+        assert_eq!(decode_body(enc.as_str(), data, 0).unwrap(), long);
+
+        assert!(decode_body("lz4", Vec::new(), 0).is_err());
+    }
+
+    #[test]
+    fn truncates_and_honours_disabled_storage() {
+        let body = "é".repeat(10); // 20 bytes
+        let storage = BodyStorage {
+            max_bytes: 5,
+            compress: false,
+        };
+        let (enc, data) = encode_body(&body, storage).unwrap();
+        assert_eq!(enc, BodyEncoding::Plain);
+        assert_eq!(String::from_utf8(data).unwrap(), "éé");
+
+        let none = BodyStorage {
+            max_bytes: 0,
+            compress: true,
+        };
+        assert!(encode_body(&body, none).is_none());
+    }
+}
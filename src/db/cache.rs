@@ -0,0 +1,122 @@
+//! Saving and restoring the index database as a CI cache artifact.
+//!
+//! CI runners start from scratch, so every job would otherwise re-index
+//! the whole repository. Instead a job restores the database saved by an
+//! earlier run and re-indexes only the files changed since that run's
+//! commit (`codegraph index --since <rev>`).
+//!
+//! [`save_cache`] writes a compacted, self-contained copy with
+//! `VACUUM INTO`, so the artifact never depends on a `-wal` file.
+//! [`restore_cache`] checks that a file is a CodeGraph index before it
+//! replaces the local database.
+
+use std::fs;
+use std::path::Path;
+
+use rusqlite::{params, Connection, OpenFlags};
+
+use crate::error::{CodeGraphError, Result};
+
+/// Tables an artifact must have to be restored.
+const REQUIRED_TABLES: &[&str] = &["nodes", "edges", "file_hashes"];
+
+/// Write a consistent copy of the database behind `conn` to `dest`,
+/// replacing any existing file. Returns the artifact size in bytes.
+pub fn save_cache(conn: &Connection, dest: &Path) -> Result<u64> {
+    if let Some(parent) = dest.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)?;
+    }
+    if dest.exists() {
+        fs::remove_file(dest)?;
+    }
+    conn.execute("VACUUM INTO ?1", params![dest.to_string_lossy()])?;
+    Ok(fs::metadata(dest)?.len())
+}
+
+/// Replace the database at `db_path` with the artifact `src`, after
+/// checking it is an index database. Stale `-wal`/`-shm` files of the old
+/// database are removed. Returns the number of files the artifact indexes.
+pub fn restore_cache(src: &Path, db_path: &Path) -> Result<usize> {
+    let files = {
+        let conn = Connection::open_with_flags(src, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+        for table in REQUIRED_TABLES {
+            let found: bool = conn.query_row(
+                "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = ?1)",
+                params![table],
+                |r| r.get(0),
+            )?;
+            if !found {
+                return Err(CodeGraphError::Other(format!(
+                    "{} is not a codegraph index: missing table '{}'",
+                    src.display(),
+                    table
+                )));
+            }
+        }
+        conn.query_row("SELECT COUNT(*) FROM file_hashes", [], |r| {
+            r.get::<_, i64>(0)
+        })? as usize
+    };
+
+    if let Some(parent) = db_path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)?;
+    }
+    for suffix in ["-wal", "-shm"] {
+        let mut side = db_path.as_os_str().to_owned();
+        side.push(suffix);
+        if Path::new(&side).exists() {
+            fs::remove_file(&side)?;
+        }
+    }
+    fs::copy(src, db_path)?;
+    Ok(files)
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::schema::initialize_database;
+
+    #[test]
+    fn saved_cache_restores_into_a_fresh_location() {
+        let tmp = tempfile::tempdir().unwrap();
+        let live = tmp.path().join("live.db");
+        let conn = initialize_database(live.to_str().unwrap()).unwrap();
+        conn.execute(
+            "INSERT INTO file_hashes (file_path, content_hash, language) VALUES ('a.rs', 'h', 'rust')",
+            [],
+        )
+        .unwrap();
+
+        let artifact = tmp.path().join("ci/index.db");
+        assert!(save_cache(&conn, &artifact).unwrap() > 0);
+        // Saving again overwrites instead of failing.
+        save_cache(&conn, &artifact).unwrap();
+
+        let restored = tmp.path().join("checkout/.codegraph/codegraph.db");
+        assert_eq!(restore_cache(&artifact, &restored).unwrap(), 1);
+        let copy = Connection::open(&restored).unwrap();
+        let path: String = copy
+            .query_row("SELECT file_path FROM file_hashes", [], |r| r.get(0))
+            .unwrap();
+        assert_eq!(path, "a.rs");
+    }
+
+    #[test]
+    fn restore_refuses_foreign_databases() {
+        let tmp = tempfile::tempdir().unwrap();
+        let foreign = tmp.path().join("other.db");
+        Connection::open(&foreign)
+            .unwrap()
+            .execute_batch("CREATE TABLE nodes (id TEXT);")
+            .unwrap();
+        let dest = tmp.path().join("codegraph.db");
+        let err = restore_cache(&foreign, &dest).unwrap_err().to_string();
+        assert!(err.contains("missing table 'edges'"), "{err}");
+        assert!(!dest.exists());
+    }
+}
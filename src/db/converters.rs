@@ -7,6 +7,7 @@ use std::collections::HashMap;
 
 use rusqlite::Row;
 
+use crate::db::bodies::decode_body;
 use crate::types::{CodeEdge, CodeNode, EdgeKind, Language, NodeKind};
 
 // ---------------------------------------------------------------------------
@@ -20,8 +21,11 @@ use crate::types::{CodeEdge, CodeNode, EdgeKind, Language, NodeKind};
 /// `id, type, name, file_path, start_line, end_line, start_column,
 ///  end_column, language, signature, doc_comment, source_hash, metadata`
 ///
-/// The `metadata` column is a JSON string; `body` and `exported` are
-/// extracted from it (matching the TypeScript converter behaviour).
+/// The `metadata` column is a JSON string; `exported` is extracted from it
+/// (matching the TypeScript converter behaviour). The body comes from the
+/// `body_encoding`/`body_data` columns of a
+/// [`select_nodes!`](crate::db::bodies) query, falling back to a `body` in
+/// `metadata` for rows written before bodies moved to `node_bodies`.
 pub fn row_to_code_node(row: &Row<'_>) -> rusqlite::Result<CodeNode> {
     let id: String = row.get("id")?;
     let kind_str: String = row.get("type")?;
@@ -36,17 +40,11 @@ pub fn row_to_code_node(row: &Row<'_>) -> rusqlite::Result<CodeNode> {
     let doc_comment: Option<String> = row.get("doc_comment")?;
     let metadata_json: Option<String> = row.get("metadata")?;
 
-    // Parse the metadata JSON to extract body / exported, matching the TS
-    // converter which reads startColumn, endColumn, body, exported from
-    // metadata.
-    let meta: HashMap<String, serde_json::Value> = metadata_json
-        .as_deref()
-        .and_then(|s| serde_json::from_str(s).ok())
-        .unwrap_or_default();
-
-    let body = meta.get("body").and_then(|v| v.as_str()).map(String::from);
-
-    let exported = meta.get("exported").and_then(|v| v.as_bool());
+    let (legacy_body, exported) = parse_node_metadata(metadata_json.as_deref());
+    let body = match stored_body(row)? {
+        Some(body) => Some(body),
+        None => legacy_body,
+    };
 
     let kind = NodeKind::from_str_loose(&kind_str).unwrap_or(NodeKind::Variable);
     let language = Language::from_str_loose(&language_str).unwrap_or(Language::TypeScript);
@@ -68,6 +66,20 @@ pub fn row_to_code_node(row: &Row<'_>) -> rusqlite::Result<CodeNode> {
     })
 }
 
+/// Decode the joined `node_bodies` columns, if the query selected them and
+/// the node has a stored body.
+fn stored_body(row: &Row<'_>) -> rusqlite::Result<Option<String>> {
+    let Ok(column) = row.as_ref().column_index("body_data") else {
+        return Ok(None);
+    };
+    let data: Option<Vec<u8>> = row.get(column)?;
+    let encoding: Option<String> = row.get("body_encoding")?;
+    match (encoding, data) {
+        (Some(encoding), Some(data)) => decode_body(&encoding, data, column).map(Some),
+        _ => Ok(None),
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Edge conversion
 // ---------------------------------------------------------------------------
@@ -86,18 +98,9 @@ pub fn row_to_code_edge(row: &Row<'_>) -> rusqlite::Result<CodeEdge> {
     let kind_str: String = row.get("type")?;
     let properties_json: Option<String> = row.get("properties")?;
 
-    let props: HashMap<String, String> = properties_json
-        .as_deref()
-        .and_then(|s| serde_json::from_str(s).ok())
-        .unwrap_or_default();
-
-    let file_path = props.get("filePath").cloned().unwrap_or_default();
-    let line: u32 = props.get("line").and_then(|l| l.parse().ok()).unwrap_or(0);
-
+    let (file_path, line, metadata) = parse_edge_properties(properties_json.as_deref());
     let kind = EdgeKind::from_str_loose(&kind_str).unwrap_or(EdgeKind::References);
 
-    let metadata = if props.is_empty() { None } else { Some(props) };
-
     Ok(CodeEdge {
         source,
         target,
@@ -108,6 +111,38 @@ pub fn row_to_code_edge(row: &Row<'_>) -> rusqlite::Result<CodeEdge> {
     })
 }
 
+// ---------------------------------------------------------------------------
+// JSON columns
+// ---------------------------------------------------------------------------
+
+/// Extract `(body, exported)` from a node's `metadata` JSON, matching the TS
+/// converter which reads body and exported from metadata.
+///
+/// Shared with non-SQLite backends, which store the same JSON.
+pub(crate) fn parse_node_metadata(json: Option<&str>) -> (Option<String>, Option<bool>) {
+    let meta: HashMap<String, serde_json::Value> = json
+        .and_then(|s| serde_json::from_str(s).ok())
+        .unwrap_or_default();
+
+    let body = meta.get("body").and_then(|v| v.as_str()).map(String::from);
+    let exported = meta.get("exported").and_then(|v| v.as_bool());
+    (body, exported)
+}
+
+/// Extract `(file_path, line, metadata)` from an edge's `properties` JSON.
+pub(crate) fn parse_edge_properties(
+    json: Option<&str>,
+) -> (String, u32, Option<HashMap<String, String>>) {
+    let props: HashMap<String, String> = json
+        .and_then(|s| serde_json::from_str(s).ok())
+        .unwrap_or_default();
+
+    let file_path = props.get("filePath").cloned().unwrap_or_default();
+    let line: u32 = props.get("line").and_then(|l| l.parse().ok()).unwrap_or(0);
+    let metadata = if props.is_empty() { None } else { Some(props) };
+    (file_path, line, metadata)
+}
+
 // ---------------------------------------------------------------------------
 // Tests
 // ---------------------------------------------------------------------------
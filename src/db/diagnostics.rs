@@ -0,0 +1,423 @@
+//! Database statistics and query planner diagnostics (`codegraph db stats`).
+//!
+//! Reports where the bytes of an index go (per table and per index, from
+//! SQLite's `dbstat` table), how fragmented the FTS5 index is, how large the
+//! vector table has grown, and whether the lookups every tool relies on are
+//! planned as index searches or full scans. When the query log is enabled
+//! (`performance.query_log`), the MCP server records each tool call with
+//! its duration in `query_log`, and the slowest are listed too.
+
+use std::time::Duration;
+
+use rusqlite::{params, Connection, OptionalExtension};
+
+use crate::error::Result;
+
+/// Rows kept in `query_log`; older entries are pruned on insert.
+pub const MAX_LOGGED_QUERIES: i64 = 1000;
+
+/// Longest query text stored per log entry, in bytes.
+const MAX_QUERY_TEXT: usize = 500;
+
+/// The lookups hot tool paths issue, checked with `EXPLAIN QUERY PLAN`.
+const PLAN_CHECKS: &[(&str, &str)] = &[
+    ("symbol by name", "SELECT id FROM nodes WHERE name = 'x'"),
+    (
+        "symbols in file",
+        "SELECT id FROM nodes WHERE file_path = 'x'",
+    ),
+    (
+        "callees",
+        "SELECT target_id FROM edges WHERE source_id = 'x'",
+    ),
+    (
+        "callers",
+        "SELECT source_id FROM edges WHERE target_id = 'x'",
+    ),
+    ("edges by type", "SELECT id FROM edges WHERE type = 'calls'"),
+    (
+        "node body",
+        "SELECT body FROM node_bodies WHERE node_id = 'x'",
+    ),
+    (
+        "full-text search",
+        "SELECT rowid FROM fts_nodes WHERE fts_nodes MATCH 'x'",
+    ),
+];
+
+/// Size of one table, including virtual-table shadow tables.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TableStat {
+    pub name: String,
+    /// Row count; `None` for virtual tables.
+    pub rows: Option<u64>,
+    pub bytes: u64,
+}
+
+/// Size of one index and which planner checks use it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndexStat {
+    pub name: String,
+    pub table: String,
+    pub bytes: u64,
+    /// `sqlite_stat1` statistics, present after `ANALYZE`.
+    pub analyzed: Option<String>,
+    /// Labels of the [`PlanCheck`]s whose plan uses this index.
+    pub used_by: Vec<String>,
+}
+
+/// The query plan of one hot lookup.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlanCheck {
+    pub label: String,
+    pub plan: Vec<String>,
+    /// The plan searches an index instead of scanning the table.
+    pub uses_index: bool,
+}
+
+/// One `query_log` entry.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoggedQuery {
+    /// What issued the query, e.g. the MCP tool name.
+    pub source: String,
+    pub query: String,
+    pub duration_ms: f64,
+    pub recorded_at: i64,
+}
+
+/// Outcome of [`collect_db_stats`].
+#[derive(Debug, Clone, Default)]
+pub struct DbStats {
+    pub page_size: u64,
+    pub page_count: u64,
+    pub freelist_count: u64,
+    /// Largest first.
+    pub tables: Vec<TableStat>,
+    /// Largest first.
+    pub indexes: Vec<IndexStat>,
+    /// FTS5 segments in `fts_nodes`; many segments slow full-text search
+    /// until they are merged.
+    pub fts_segments: u64,
+    /// Embeddings stored, or `None` when the vector table was never created.
+    pub vector_rows: Option<u64>,
+    /// Bytes of the vector table and its shadow tables.
+    pub vector_bytes: u64,
+    pub plans: Vec<PlanCheck>,
+    /// Slowest logged queries, slowest first.
+    pub slow_queries: Vec<LoggedQuery>,
+}
+
+impl DbStats {
+    /// Size of the database file, in bytes.
+    pub fn file_bytes(&self) -> u64 {
+        self.page_size * self.page_count
+    }
+}
+
+/// Gather statistics for `conn`, listing at most `slowest` logged queries.
+pub fn collect_db_stats(conn: &Connection, slowest: usize) -> Result<DbStats> {
+    let pragma = |name: &str| -> Result<u64> {
+        Ok(conn.query_row(&format!("PRAGMA {name}"), [], |row| row.get::<_, i64>(0))? as u64)
+    };
+    let mut stats = DbStats {
+        page_size: pragma("page_size")?,
+        page_count: pragma("page_count")?,
+        freelist_count: pragma("freelist_count")?,
+        ..Default::default()
+    };
+
+    // Bytes per b-tree, attributed to the table or index it belongs to.
+    let mut stmt = conn.prepare(
+        "SELECT m.type, s.name, m.tbl_name, SUM(s.pgsize) \
+         FROM dbstat s JOIN sqlite_master m ON m.name = s.name \
+         GROUP BY s.name",
+    )?;
+    let btrees = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, i64>(3)? as u64,
+            ))
+        })?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+
+    let has_stat1 = table_exists(conn, "sqlite_stat1")?;
+    for (kind, name, table, bytes) in &btrees {
+        if kind != "index" {
+            continue;
+        }
+        let analyzed = if has_stat1 {
+            conn.query_row(
+                "SELECT stat FROM sqlite_stat1 WHERE idx = ?1",
+                [name],
+                |row| row.get(0),
+            )
+            .optional()?
+        } else {
+            None
+        };
+        stats.indexes.push(IndexStat {
+            name: name.clone(),
+            table: table.clone(),
+            bytes: *bytes,
+            analyzed,
+            used_by: Vec::new(),
+        });
+    }
+
+    // Virtual tables own no b-tree themselves; their shadow tables
+    // (`fts_nodes_data`, `vec_embeddings_chunks`, ...) are folded into them.
+    let mut virtual_tables: Vec<String> = conn
+        .prepare(
+            "SELECT name FROM sqlite_master WHERE type = 'table' AND sql LIKE 'CREATE VIRTUAL%'",
+        )?
+        .query_map([], |row| row.get(0))?
+        .collect::<std::result::Result<_, _>>()?;
+    virtual_tables.sort_by_key(|name: &String| std::cmp::Reverse(name.len()));
+    for name in &virtual_tables {
+        stats.tables.push(TableStat {
+            name: name.clone(),
+            rows: None,
+            bytes: 0,
+        });
+    }
+    let shadow_owner = |name: &str| {
+        virtual_tables
+            .iter()
+            .find(|v| name.starts_with(&format!("{v}_")))
+            .cloned()
+    };
+    for (kind, name, table, bytes) in &btrees {
+        if kind != "table" {
+            // Indexes of shadow tables count towards their virtual table.
+            if let Some(owner) = shadow_owner(table) {
+                if let Some(t) = stats.tables.iter_mut().find(|t| t.name == owner) {
+                    t.bytes += bytes;
+                }
+            }
+            continue;
+        }
+        match shadow_owner(name) {
+            Some(owner) => {
+                if let Some(t) = stats.tables.iter_mut().find(|t| t.name == owner) {
+                    t.bytes += bytes;
+                }
+            }
+            None => {
+                let rows: i64 =
+                    conn.query_row(&format!("SELECT COUNT(*) FROM \"{name}\""), [], |row| {
+                        row.get(0)
+                    })?;
+                stats.tables.push(TableStat {
+                    name: name.clone(),
+                    rows: Some(rows as u64),
+                    bytes: *bytes,
+                });
+            }
+        }
+    }
+    stats.indexes.retain(|i| shadow_owner(&i.table).is_none());
+    stats
+        .tables
+        .sort_by(|a, b| b.bytes.cmp(&a.bytes).then(a.name.cmp(&b.name)));
+    stats
+        .indexes
+        .sort_by(|a, b| b.bytes.cmp(&a.bytes).then(a.name.cmp(&b.name)));
+
+    if table_exists(conn, "fts_nodes_idx")? {
+        stats.fts_segments = conn.query_row(
+            "SELECT COUNT(DISTINCT segid) FROM fts_nodes_idx",
+            [],
+            |row| row.get::<_, i64>(0),
+        )? as u64;
+    }
+    if let Some(vec) = stats.tables.iter().find(|t| t.name == "vec_embeddings") {
+        stats.vector_bytes = vec.bytes;
+        if table_exists(conn, "vec_embeddings_rowids")? {
+            stats.vector_rows = Some(conn.query_row(
+                "SELECT COUNT(*) FROM vec_embeddings_rowids",
+                [],
+                |row| row.get::<_, i64>(0),
+            )? as u64);
+        }
+    }
+
+    for (label, sql) in PLAN_CHECKS {
+        let Ok(mut stmt) = conn.prepare(&format!("EXPLAIN QUERY PLAN {sql}")) else {
+            continue; // table missing from this database
+        };
+        let plan: Vec<String> = stmt
+            .query_map([], |row| row.get::<_, String>(3))?
+            .collect::<std::result::Result<_, _>>()?;
+        let uses_index = plan
+            .iter()
+            .any(|step| step.starts_with("SEARCH") || step.contains("VIRTUAL TABLE INDEX"));
+        for index in &mut stats.indexes {
+            if plan
+                .iter()
+                .any(|step| plan_index(step) == Some(&index.name))
+            {
+                index.used_by.push(label.to_string());
+            }
+        }
+        stats.plans.push(PlanCheck {
+            label: label.to_string(),
+            plan,
+            uses_index,
+        });
+    }
+
+    stats.slow_queries = slowest_queries(conn, slowest)?;
+    Ok(stats)
+}
+
+/// Record one query in `query_log`, pruning entries beyond
+/// [`MAX_LOGGED_QUERIES`].
+pub fn log_query(conn: &Connection, source: &str, query: &str, duration: Duration) -> Result<()> {
+    let query = &query[..query.floor_char_boundary(MAX_QUERY_TEXT)];
+    conn.execute(
+        "INSERT INTO query_log (source, query, duration_ms) VALUES (?1, ?2, ?3)",
+        params![source, query, duration.as_secs_f64() * 1000.0],
+    )?;
+    conn.execute(
+        "DELETE FROM query_log WHERE id <= last_insert_rowid() - ?1",
+        [MAX_LOGGED_QUERIES],
+    )?;
+    Ok(())
+}
+
+/// The `limit` slowest logged queries, slowest first.
+pub fn slowest_queries(conn: &Connection, limit: usize) -> Result<Vec<LoggedQuery>> {
+    let mut stmt = conn.prepare(
+        "SELECT source, query, duration_ms, recorded_at FROM query_log \
+         ORDER BY duration_ms DESC LIMIT ?1",
+    )?;
+    let rows = stmt.query_map([limit as i64], |row| {
+        Ok(LoggedQuery {
+            source: row.get(0)?,
+            query: row.get(1)?,
+            duration_ms: row.get(2)?,
+            recorded_at: row.get(3)?,
+        })
+    })?;
+    rows.collect::<std::result::Result<Vec<_>, _>>()
+        .map_err(Into::into)
+}
+
+/// The index a plan step searches, from details such as
+/// `SEARCH nodes USING COVERING INDEX idx_nodes_name (name=?)`.
+fn plan_index(step: &str) -> Option<&str> {
+    let mut words = step.split_whitespace();
+    words.find(|w| *w == "INDEX")?;
+    words.next()
+}
+
+fn table_exists(conn: &Connection, name: &str) -> Result<bool> {
+    Ok(conn
+        .query_row(
+            "SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = ?1",
+            [name],
+            |_| Ok(()),
+        )
+        .optional()?
+        .is_some())
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::schema::initialize_database;
+
+    #[test]
+    fn reports_sizes_plans_and_fts_segments() {
+        let conn = initialize_database(":memory:").unwrap();
+        for i in 0..50 {
+            conn.execute(
+                "INSERT INTO nodes (id, type, name, file_path, start_line, end_line, language) \
+                 VALUES (?1, 'function', ?2, 'src/a.ts', 1, 2, 'typescript')",
+                params![format!("n{i}"), format!("handler{i}")],
+            )
+            .unwrap();
+        }
+
+        let stats = collect_db_stats(&conn, 10).unwrap();
+        assert!(stats.file_bytes() > 0);
+        let nodes = stats.tables.iter().find(|t| t.name == "nodes").unwrap();
+        assert_eq!(nodes.rows, Some(50));
+        assert!(nodes.bytes > 0);
+        // Shadow tables are folded into their virtual table.
+        assert!(stats
+            .tables
+            .iter()
+            .any(|t| t.name == "fts_nodes" && t.bytes > 0));
+        assert!(!stats.tables.iter().any(|t| t.name == "fts_nodes_data"));
+        assert!(stats.fts_segments >= 1);
+        assert_eq!(stats.vector_rows, Some(0));
+
+        let by_name = stats
+            .plans
+            .iter()
+            .find(|p| p.label == "symbol by name")
+            .unwrap();
+        assert!(by_name.uses_index, "{:?}", by_name.plan);
+        let name_index = stats
+            .indexes
+            .iter()
+            .find(|i| i.name == "idx_nodes_name")
+            .unwrap();
+        assert_eq!(name_index.table, "nodes");
+        assert!(name_index.used_by.contains(&"symbol by name".to_string()));
+        assert!(name_index.analyzed.is_none());
+
+        conn.execute_batch("DROP INDEX idx_edges_target").unwrap();
+        let stats = collect_db_stats(&conn, 10).unwrap();
+        let callers = stats.plans.iter().find(|p| p.label == "callers").unwrap();
+        assert!(!callers.uses_index);
+    }
+
+    #[test]
+    fn query_log_keeps_slowest_and_prunes_oldest() {
+        let conn = initialize_database(":memory:").unwrap();
+        log_query(
+            &conn,
+            "codegraph_query",
+            "{\"query\":\"auth\"}",
+            Duration::from_millis(40),
+        )
+        .unwrap();
+        log_query(&conn, "codegraph_callers", "{}", Duration::from_millis(900)).unwrap();
+        log_query(
+            &conn,
+            "codegraph_node",
+            &"x".repeat(2000),
+            Duration::from_millis(5),
+        )
+        .unwrap();
+
+        let slow = slowest_queries(&conn, 2).unwrap();
+        assert_eq!(slow.len(), 2);
+        assert_eq!(slow[0].source, "codegraph_callers");
+        assert!((slow[0].duration_ms - 900.0).abs() < 1e-6);
+        assert_eq!(slow[1].source, "codegraph_query");
+        assert_eq!(
+            slowest_queries(&conn, 10).unwrap()[2].query.len(),
+            MAX_QUERY_TEXT
+        );
+
+        for _ in 0..MAX_LOGGED_QUERIES {
+            log_query(&conn, "codegraph_node", "{}", Duration::from_millis(1)).unwrap();
+        }
+        let count: i64 = conn
+            .query_row("SELECT COUNT(*) FROM query_log", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(count, MAX_LOGGED_QUERIES);
+        // The 900 ms entry was among the oldest and has been pruned.
+        assert_eq!(slowest_queries(&conn, 1).unwrap()[0].duration_ms, 1.0);
+    }
+}
@@ -0,0 +1,230 @@
+//! Database integrity checks and repair (`codegraph doctor`).
+//!
+//! Runs SQLite's own integrity check, then the checks specific to a
+//! CodeGraph index: the FTS5 index must match `nodes`, every index the
+//! schema defines must exist, and every edge must point at nodes that
+//! exist. The first two are repaired in place since rebuilding them loses
+//! nothing; orphaned edges are only reported unless pruning is requested.
+
+use rusqlite::Connection;
+
+use crate::db::schema::{ensure_indexes, repair_fts};
+use crate::error::Result;
+
+/// Whether an edge endpoint is missing: not a node ID, not one of the
+/// extractor's placeholders for external modules (`module:`) and unresolved
+/// names (`unresolved:`), and not a `file:<path>` pseudo-node (file-level
+/// imports) of an indexed file.
+macro_rules! endpoint_missing {
+    ($column:literal) => {
+        concat!(
+            "(NOT EXISTS (SELECT 1 FROM nodes WHERE id = edges.",
+            $column,
+            ") AND edges.",
+            $column,
+            " NOT LIKE 'module:%' AND edges.",
+            $column,
+            " NOT LIKE 'unresolved:%' AND NOT (edges.",
+            $column,
+            " LIKE 'file:%' AND EXISTS (SELECT 1 FROM nodes WHERE file_path = substr(edges.",
+            $column,
+            ", 6))))"
+        )
+    };
+}
+
+const FIND_ORPHANED_EDGES_SQL: &str = concat!(
+    "SELECT id, source_id, target_id, type, ",
+    endpoint_missing!("source_id"),
+    ", ",
+    endpoint_missing!("target_id"),
+    " FROM edges WHERE ",
+    endpoint_missing!("source_id"),
+    " OR ",
+    endpoint_missing!("target_id"),
+    " ORDER BY id"
+);
+
+/// An edge whose source or target node no longer exists.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OrphanedEdge {
+    pub id: i64,
+    pub source: String,
+    pub target: String,
+    pub kind: String,
+    pub source_missing: bool,
+    pub target_missing: bool,
+}
+
+/// Outcome of [`run_doctor`].
+#[derive(Debug, Clone, Default)]
+pub struct DoctorReport {
+    /// Problems reported by `PRAGMA integrity_check`; empty when healthy.
+    pub integrity_errors: Vec<String>,
+    /// The FTS5 index was out of sync with `nodes` and has been rebuilt.
+    pub fts_rebuilt: bool,
+    /// Indexes that were missing and have been re-created.
+    pub recreated_indexes: Vec<String>,
+    pub orphaned_edges: Vec<OrphanedEdge>,
+    /// Orphaned edges deleted (only with `fix`).
+    pub pruned_edges: usize,
+}
+
+impl DoctorReport {
+    /// No integrity errors and no orphaned edges left behind.
+    pub fn is_healthy(&self) -> bool {
+        self.integrity_errors.is_empty() && self.orphaned_edges.len() == self.pruned_edges
+    }
+}
+
+/// Check `conn` and repair what can be rebuilt. With `fix`, orphaned edges
+/// are also deleted.
+///
+/// A database that fails SQLite's integrity check is reported as-is: the
+/// CodeGraph-level repairs assume the file itself is sound.
+pub fn run_doctor(conn: &Connection, fix: bool) -> Result<DoctorReport> {
+    let mut report = DoctorReport::default();
+
+    let mut stmt = conn.prepare("PRAGMA integrity_check")?;
+    report.integrity_errors = stmt
+        .query_map([], |row| row.get::<_, String>(0))?
+        .collect::<std::result::Result<Vec<_>, _>>()?
+        .into_iter()
+        .filter(|line| line != "ok")
+        .collect();
+    if !report.integrity_errors.is_empty() {
+        return Ok(report);
+    }
+
+    report.fts_rebuilt = repair_fts(conn)?;
+    report.recreated_indexes = ensure_indexes(conn)?;
+    report.orphaned_edges = find_orphaned_edges(conn)?;
+
+    if fix && !report.orphaned_edges.is_empty() {
+        let tx = conn.unchecked_transaction()?;
+        {
+            let mut delete = tx.prepare("DELETE FROM edges WHERE id = ?1")?;
+            for edge in &report.orphaned_edges {
+                report.pruned_edges += delete.execute([edge.id])?;
+            }
+        }
+        tx.commit()?;
+    }
+    Ok(report)
+}
+
+/// Edges with a missing source or target node.
+pub fn find_orphaned_edges(conn: &Connection) -> Result<Vec<OrphanedEdge>> {
+    let mut stmt = conn.prepare(FIND_ORPHANED_EDGES_SQL)?;
+    let rows = stmt.query_map([], |row| {
+        Ok(OrphanedEdge {
+            id: row.get(0)?,
+            source: row.get(1)?,
+            target: row.get(2)?,
+            kind: row.get(3)?,
+            source_missing: row.get(4)?,
+            target_missing: row.get(5)?,
+        })
+    })?;
+    rows.collect::<std::result::Result<Vec<_>, _>>()
+        .map_err(Into::into)
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::schema::initialize_database;
+
+    fn insert_node(conn: &Connection, id: &str, name: &str, file: &str) {
+        conn.execute(
+            "INSERT INTO nodes (id, type, name, file_path, start_line, end_line, language) \
+             VALUES (?1, 'function', ?2, ?3, 1, 2, 'typescript')",
+            [id, name, file],
+        )
+        .unwrap();
+    }
+
+    fn insert_edge(conn: &Connection, source: &str, target: &str) {
+        conn.execute(
+            "INSERT INTO edges (source_id, target_id, type) VALUES (?1, ?2, 'calls')",
+            [source, target],
+        )
+        .unwrap();
+    }
+
+    fn fts_matches(conn: &Connection, term: &str) -> i64 {
+        conn.query_row(
+            "SELECT COUNT(*) FROM fts_nodes WHERE fts_nodes MATCH ?1",
+            [term],
+            |row| row.get(0),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn healthy_database_needs_no_repairs() {
+        let conn = initialize_database(":memory:").unwrap();
+        insert_node(&conn, "a", "alpha", "src/a.ts");
+        insert_node(&conn, "b", "beta", "src/a.ts");
+        insert_edge(&conn, "a", "b");
+        insert_edge(&conn, "file:src/a.ts", "b");
+        insert_edge(&conn, "file:src/a.ts", "module:express");
+        insert_edge(&conn, "a", "unresolved:fetch");
+
+        let report = run_doctor(&conn, false).unwrap();
+        assert!(report.integrity_errors.is_empty());
+        assert!(!report.fts_rebuilt);
+        assert!(report.recreated_indexes.is_empty());
+        assert!(report.orphaned_edges.is_empty());
+        assert!(report.is_healthy());
+    }
+
+    #[test]
+    fn repairs_fts_and_indexes_and_prunes_orphans_with_fix() {
+        let conn = initialize_database(":memory:").unwrap();
+        insert_node(&conn, "a", "alpha", "src/a.ts");
+        // A node written while the sync trigger was missing.
+        conn.execute_batch("DROP TRIGGER nodes_ai").unwrap();
+        insert_node(&conn, "b", "betaHelper", "src/b.ts");
+        conn.execute_batch("DROP INDEX idx_edges_source").unwrap();
+        insert_edge(&conn, "a", "b");
+        insert_edge(&conn, "a", "gone");
+        insert_edge(&conn, "file:src/deleted.ts", "a");
+        assert_eq!(fts_matches(&conn, "betaHelper"), 0);
+
+        let report = run_doctor(&conn, false).unwrap();
+        assert!(report.fts_rebuilt);
+        assert_eq!(fts_matches(&conn, "betaHelper"), 1);
+        assert_eq!(report.recreated_indexes, vec!["idx_edges_source"]);
+        assert_eq!(report.orphaned_edges.len(), 2);
+        assert!(report.orphaned_edges[0].target_missing);
+        assert!(report.orphaned_edges[1].source_missing);
+        assert_eq!(report.pruned_edges, 0);
+        assert!(!report.is_healthy());
+
+        // The rebuilt triggers keep FTS in sync from now on.
+        insert_node(&conn, "c", "gammaHelper", "src/c.ts");
+        assert_eq!(fts_matches(&conn, "gammaHelper"), 1);
+
+        let report = run_doctor(&conn, true).unwrap();
+        assert!(!report.fts_rebuilt);
+        assert_eq!(report.pruned_edges, 2);
+        assert!(report.is_healthy());
+        assert!(find_orphaned_edges(&conn).unwrap().is_empty());
+        let edges: i64 = conn
+            .query_row("SELECT COUNT(*) FROM edges", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(edges, 1);
+
+        // Triggers intact but the index emptied behind their back.
+        conn.execute_batch("INSERT INTO fts_nodes(fts_nodes) VALUES ('delete-all')")
+            .unwrap();
+        assert_eq!(fts_matches(&conn, "gammaHelper"), 0);
+        assert!(run_doctor(&conn, false).unwrap().fts_rebuilt);
+        assert_eq!(fts_matches(&conn, "gammaHelper"), 1);
+    }
+}
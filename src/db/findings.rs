@@ -0,0 +1,634 @@
+//! Finding history — stable identities for security, dead-code and
+//! complexity findings across scans.
+//!
+//! Every scan is recorded with [`record_scan`]. Each reported finding is
+//! matched against the findings already stored for its category, rule and
+//! file: first exactly (same symbol or line, same description), then by
+//! cosine similarity of the embedded descriptions, so a finding keeps its ID
+//! when the code around it shifts or its message changes slightly.
+//! Near-identical findings within one scan collapse onto one ID as well.
+//!
+//! Matched findings get their `last_seen` bumped; unmatched ones are
+//! inserted with `first_seen = last_seen = now`; stored findings inside the
+//! scan's scope that were not reported again are marked `resolved` (and
+//! reopen under the same ID if they come back).
+//!
+//! Descriptions are embedded with the on-device model when it is available
+//! (feature `embedding`), falling back to a hashed bag-of-words vector.
+//! Vectors are only compared when produced by the same model.
+
+use std::collections::HashSet;
+
+use rusqlite::{params, Connection};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use crate::error::Result;
+
+// ---------------------------------------------------------------------------
+// Constants
+// ---------------------------------------------------------------------------
+
+/// Dimensions of the hashed bag-of-words fallback vector.
+const LEXICAL_DIM: usize = 256;
+
+/// Minimum cosine similarity for two lexical vectors to be the same finding.
+const LEXICAL_THRESHOLD: f32 = 0.85;
+
+/// Minimum cosine similarity for two model embeddings to be the same finding.
+#[cfg(feature = "embedding")]
+const MODEL_THRESHOLD: f32 = 0.93;
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// The analysis a finding came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FindingCategory {
+    Security,
+    DeadCode,
+    Complexity,
+}
+
+impl FindingCategory {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Security => "security",
+            Self::DeadCode => "dead_code",
+            Self::Complexity => "complexity",
+        }
+    }
+}
+
+/// A finding as reported by one scan.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NewFinding {
+    /// Rule ID or check name, e.g. `sql-injection` or `unused_function`.
+    pub rule: String,
+    /// Project-relative file path.
+    pub file_path: String,
+    /// Symbol the finding is about, when it has one.
+    pub symbol: Option<String>,
+    pub line: u32,
+    /// Human-readable description; this is what gets embedded.
+    pub description: String,
+}
+
+/// A finding with its stable identity and history.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrackedFinding {
+    pub id: String,
+    /// Unix timestamp of the first scan that reported it.
+    pub first_seen: i64,
+    /// Unix timestamp of the latest scan that reported it.
+    pub last_seen: i64,
+    /// Number of scans that reported it.
+    pub seen_count: u32,
+    /// Reported for the first time (or again after being resolved).
+    pub is_new: bool,
+}
+
+/// Outcome of [`record_scan`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ScanRecord {
+    /// One entry per input finding, in input order. Duplicates share an ID.
+    pub findings: Vec<TrackedFinding>,
+    /// Input findings folded into an earlier near-identical one.
+    pub duplicates: usize,
+    /// IDs of stored findings no longer reported within the scope.
+    pub resolved: Vec<String>,
+}
+
+impl ScanRecord {
+    /// Distinct findings reported for the first time.
+    pub fn new_count(&self) -> usize {
+        let mut seen = HashSet::new();
+        self.findings
+            .iter()
+            .filter(|f| f.is_new && seen.insert(&f.id))
+            .count()
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Embedding
+// ---------------------------------------------------------------------------
+
+/// Turns finding descriptions into vectors for near-duplicate matching.
+pub enum FindingEmbedder {
+    #[cfg(feature = "embedding")]
+    Model(crate::indexer::embedder::EmbeddingEngine),
+    /// Hashed bag-of-words; cheap and always available.
+    Lexical,
+}
+
+impl FindingEmbedder {
+    /// Use the embedding model if it can be loaded, else the lexical fallback.
+    pub fn new() -> Self {
+        #[cfg(feature = "embedding")]
+        if let Ok(engine) = crate::indexer::embedder::EmbeddingEngine::try_new() {
+            return Self::Model(engine);
+        }
+        Self::Lexical
+    }
+
+    fn model_name(&self) -> &'static str {
+        match self {
+            #[cfg(feature = "embedding")]
+            Self::Model(_) => "jina-embeddings-v2-base-code",
+            Self::Lexical => "lexical-256",
+        }
+    }
+
+    fn threshold(&self) -> f32 {
+        match self {
+            #[cfg(feature = "embedding")]
+            Self::Model(_) => MODEL_THRESHOLD,
+            Self::Lexical => LEXICAL_THRESHOLD,
+        }
+    }
+
+    fn embed(&self, texts: &[&str]) -> Vec<Vec<f32>> {
+        match self {
+            #[cfg(feature = "embedding")]
+            Self::Model(engine) => engine
+                .embed_batch(texts.to_vec())
+                .unwrap_or_else(|_| texts.iter().map(|t| lexical_vector(t)).collect()),
+            Self::Lexical => texts.iter().map(|t| lexical_vector(t)).collect(),
+        }
+    }
+}
+
+impl Default for FindingEmbedder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Debug for FindingEmbedder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("FindingEmbedder")
+            .field(&self.model_name())
+            .finish()
+    }
+}
+
+/// L2-normalised hashed word counts. Numbers are dropped so line numbers and
+/// counts embedded in messages do not split otherwise identical findings.
+pub(crate) fn lexical_vector(text: &str) -> Vec<f32> {
+    let mut v = vec![0f32; LEXICAL_DIM];
+    for word in text
+        .split(|c: char| !c.is_alphanumeric() && c != '_')
+        .filter(|w| !w.is_empty() && !w.chars().all(|c| c.is_ascii_digit()))
+    {
+        let hash = Sha256::digest(word.to_lowercase().as_bytes());
+        let bucket = u16::from_le_bytes([hash[0], hash[1]]) as usize % LEXICAL_DIM;
+        v[bucket] += 1.0;
+    }
+    let norm = v.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        v.iter_mut().for_each(|x| *x /= norm);
+    }
+    v
+}
+
+pub(crate) fn cosine(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() {
+        return 0.0;
+    }
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let na = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let nb = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if na == 0.0 || nb == 0.0 {
+        0.0
+    } else {
+        dot / (na * nb)
+    }
+}
+
+pub(crate) fn to_blob(v: &[f32]) -> Vec<u8> {
+    v.iter().flat_map(|f| f.to_le_bytes()).collect()
+}
+
+pub(crate) fn from_blob(b: &[u8]) -> Vec<f32> {
+    b.chunks_exact(4)
+        .map(|c| f32::from_le_bytes([c[0], c[1], c[2], c[3]]))
+        .collect()
+}
+
+// ---------------------------------------------------------------------------
+// Recording
+// ---------------------------------------------------------------------------
+
+/// A stored finding loaded for matching.
+struct StoredFinding {
+    id: String,
+    rule: String,
+    file_path: String,
+    symbol: Option<String>,
+    line: u32,
+    description: String,
+    embedding: Option<Vec<f32>>,
+    model: Option<String>,
+    status: String,
+    first_seen: i64,
+    seen_count: u32,
+}
+
+/// Record one scan of `category` and return stable identities for
+/// `findings`.
+///
+/// `scope` is the project-relative path prefix the scan covered (`""` for
+/// the whole project); only stored findings under it can be resolved. Pass
+/// `None` for partial scans (e.g. filtered by kind) that must not resolve
+/// anything.
+pub fn record_scan(
+    conn: &Connection,
+    category: FindingCategory,
+    scope: Option<&str>,
+    findings: &[NewFinding],
+    embedder: &FindingEmbedder,
+    now: i64,
+) -> Result<ScanRecord> {
+    let texts: Vec<&str> = findings.iter().map(|f| f.description.as_str()).collect();
+    let vectors = embedder.embed(&texts);
+    let model = embedder.model_name();
+    let threshold = embedder.threshold();
+
+    let mut stored = load_findings(conn, category)?;
+    let mut claimed: HashSet<usize> = HashSet::new();
+    let mut record = ScanRecord::default();
+    // Index into `record.findings` of each distinct finding in this scan,
+    // with the input index it came from (for duplicate detection).
+    let mut distinct: Vec<(usize, usize)> = Vec::new();
+
+    let tx = conn.unchecked_transaction()?;
+    for (i, finding) in findings.iter().enumerate() {
+        // Near-identical to a finding already reported by this scan?
+        let duplicate_of = distinct.iter().find(|(_, j)| {
+            let other = &findings[*j];
+            other.rule == finding.rule
+                && other.file_path == finding.file_path
+                && other.symbol == finding.symbol
+                && (other.description == finding.description
+                    || cosine(&vectors[i], &vectors[*j]) >= threshold)
+        });
+        if let Some(&(slot, _)) = duplicate_of {
+            record.duplicates += 1;
+            let tracked = record.findings[slot].clone();
+            record.findings.push(tracked);
+            continue;
+        }
+
+        let matched = match_stored(&stored, &claimed, finding, &vectors[i], model, threshold);
+        let tracked = match matched {
+            Some(idx) => {
+                claimed.insert(idx);
+                let s = &stored[idx];
+                tx.prepare_cached(
+                    "UPDATE findings SET line = ?2, description = ?3, embedding = ?4, \
+                     embedding_model = ?5, status = 'open', last_seen = ?6, \
+                     seen_count = seen_count + 1 WHERE id = ?1",
+                )?
+                .execute(params![
+                    s.id,
+                    finding.line,
+                    finding.description,
+                    to_blob(&vectors[i]),
+                    model,
+                    now
+                ])?;
+                TrackedFinding {
+                    id: s.id.clone(),
+                    first_seen: s.first_seen,
+                    last_seen: now,
+                    seen_count: s.seen_count + 1,
+                    is_new: s.status == "resolved",
+                }
+            }
+            None => {
+                let id = finding_id(category, finding, &stored);
+                tx.prepare_cached(
+                    "INSERT INTO findings (id, category, rule, file_path, symbol, line, \
+                     description, embedding, embedding_model, status, first_seen, last_seen, \
+                     seen_count) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, 'open', ?10, ?10, 1)",
+                )?
+                .execute(params![
+                    id,
+                    category.as_str(),
+                    finding.rule,
+                    finding.file_path,
+                    finding.symbol,
+                    finding.line,
+                    finding.description,
+                    to_blob(&vectors[i]),
+                    model,
+                    now
+                ])?;
+                // Later findings in this scan must not reuse the ID.
+                stored.push(StoredFinding {
+                    id: id.clone(),
+                    rule: finding.rule.clone(),
+                    file_path: finding.file_path.clone(),
+                    symbol: finding.symbol.clone(),
+                    line: finding.line,
+                    description: finding.description.clone(),
+                    embedding: None,
+                    model: None,
+                    status: "open".to_string(),
+                    first_seen: now,
+                    seen_count: 1,
+                });
+                claimed.insert(stored.len() - 1);
+                TrackedFinding {
+                    id,
+                    first_seen: now,
+                    last_seen: now,
+                    seen_count: 1,
+                    is_new: true,
+                }
+            }
+        };
+        distinct.push((record.findings.len(), i));
+        record.findings.push(tracked);
+    }
+
+    for (idx, s) in stored.iter().enumerate() {
+        let in_scope = scope.is_some_and(|scope| s.file_path.starts_with(scope));
+        if in_scope && s.status == "open" && !claimed.contains(&idx) {
+            tx.prepare_cached("UPDATE findings SET status = 'resolved' WHERE id = ?1")?
+                .execute(params![s.id])?;
+            record.resolved.push(s.id.clone());
+        }
+    }
+    tx.commit()?;
+    Ok(record)
+}
+
+fn load_findings(conn: &Connection, category: FindingCategory) -> Result<Vec<StoredFinding>> {
+    let mut stmt = conn.prepare_cached(
+        "SELECT id, rule, file_path, symbol, line, description, embedding, embedding_model, \
+         status, first_seen, seen_count FROM findings WHERE category = ?1 ORDER BY first_seen, id",
+    )?;
+    let rows = stmt.query_map(params![category.as_str()], |row| {
+        let blob: Option<Vec<u8>> = row.get(6)?;
+        Ok(StoredFinding {
+            id: row.get(0)?,
+            rule: row.get(1)?,
+            file_path: row.get(2)?,
+            symbol: row.get(3)?,
+            line: row.get(4)?,
+            description: row.get(5)?,
+            embedding: blob.as_deref().map(from_blob),
+            model: row.get(7)?,
+            status: row.get(8)?,
+            first_seen: row.get(9)?,
+            seen_count: row.get(10)?,
+        })
+    })?;
+    rows.collect::<std::result::Result<Vec<_>, _>>()
+        .map_err(Into::into)
+}
+
+/// Best unclaimed stored finding for `finding`: an exact match if there is
+/// one, else the most similar description above `threshold`, preferring the
+/// nearest line on ties.
+fn match_stored(
+    stored: &[StoredFinding],
+    claimed: &HashSet<usize>,
+    finding: &NewFinding,
+    vector: &[f32],
+    model: &str,
+    threshold: f32,
+) -> Option<usize> {
+    let candidates = || {
+        stored.iter().enumerate().filter(|(idx, s)| {
+            !claimed.contains(idx)
+                && s.rule == finding.rule
+                && s.file_path == finding.file_path
+                && s.symbol == finding.symbol
+        })
+    };
+
+    let exact = candidates()
+        .filter(|(_, s)| s.description == finding.description)
+        .min_by_key(|(_, s)| s.line.abs_diff(finding.line))
+        .filter(|(_, s)| finding.symbol.is_some() || s.line == finding.line);
+    if let Some((idx, _)) = exact {
+        return Some(idx);
+    }
+
+    candidates()
+        .filter_map(|(idx, s)| {
+            let emb = s.embedding.as_ref()?;
+            if s.model.as_deref() != Some(model) {
+                return None;
+            }
+            let sim = cosine(emb, vector);
+            (sim >= threshold).then_some((idx, sim, s.line.abs_diff(finding.line)))
+        })
+        .max_by(|a, b| {
+            a.1.partial_cmp(&b.1)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then_with(|| b.2.cmp(&a.2))
+        })
+        .map(|(idx, _, _)| idx)
+}
+
+/// Content-derived ID, extended on the rare collision with a stored one.
+fn finding_id(category: FindingCategory, finding: &NewFinding, stored: &[StoredFinding]) -> String {
+    let mut hasher = Sha256::new();
+    for part in [
+        category.as_str(),
+        &finding.rule,
+        &finding.file_path,
+        finding.symbol.as_deref().unwrap_or_default(),
+        &finding.description,
+    ] {
+        hasher.update(part.as_bytes());
+        hasher.update([0]);
+    }
+    let digest = hex::encode(hasher.finalize());
+    let mut len = 12;
+    while stored.iter().any(|s| s.id == digest[..len]) && len < digest.len() {
+        len += 4;
+    }
+    digest[..len].to_string()
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::schema::initialize_database;
+
+    fn finding(rule: &str, file: &str, line: u32, description: &str) -> NewFinding {
+        NewFinding {
+            rule: rule.to_string(),
+            file_path: file.to_string(),
+            symbol: None,
+            line,
+            description: description.to_string(),
+        }
+    }
+
+    #[test]
+    fn identities_are_stable_across_scans_and_line_shifts() {
+        let conn = initialize_database(":memory:").unwrap();
+        let embedder = FindingEmbedder::Lexical;
+        let first = vec![
+            finding(
+                "sql-injection",
+                "src/db.ts",
+                10,
+                "SQL built from user input: query(req.body.id)",
+            ),
+            finding("eval", "src/run.ts", 3, "Use of eval: eval(code)"),
+        ];
+        let r1 = record_scan(
+            &conn,
+            FindingCategory::Security,
+            Some(""),
+            &first,
+            &embedder,
+            100,
+        )
+        .unwrap();
+        assert_eq!(r1.new_count(), 2);
+
+        // The SQL finding moved down two lines; eval is gone.
+        let second = vec![finding(
+            "sql-injection",
+            "src/db.ts",
+            12,
+            "SQL built from user input: query(req.body.id)",
+        )];
+        let r2 = record_scan(
+            &conn,
+            FindingCategory::Security,
+            Some(""),
+            &second,
+            &embedder,
+            200,
+        )
+        .unwrap();
+        assert_eq!(r2.findings[0].id, r1.findings[0].id);
+        assert_eq!(r2.findings[0].first_seen, 100);
+        assert_eq!(r2.findings[0].last_seen, 200);
+        assert_eq!(r2.findings[0].seen_count, 2);
+        assert!(!r2.findings[0].is_new);
+        assert_eq!(r2.resolved, vec![r1.findings[1].id.clone()]);
+
+        // eval comes back: same ID, reported as new again.
+        let r3 = record_scan(
+            &conn,
+            FindingCategory::Security,
+            Some(""),
+            &first,
+            &embedder,
+            300,
+        )
+        .unwrap();
+        assert_eq!(r3.findings[1].id, r1.findings[1].id);
+        assert!(r3.findings[1].is_new);
+        assert_eq!(r3.findings[1].first_seen, 100);
+    }
+
+    #[test]
+    fn near_identical_findings_share_an_id() {
+        let conn = initialize_database(":memory:").unwrap();
+        let embedder = FindingEmbedder::Lexical;
+        let scan = vec![
+            finding("eval", "src/run.ts", 3, "Use of eval: eval(code)"),
+            finding("eval", "src/run.ts", 9, "Use of eval: eval(code)"),
+            finding(
+                "eval",
+                "src/run.ts",
+                20,
+                "Use of eval: eval(templateString + suffix)",
+            ),
+        ];
+        let r = record_scan(
+            &conn,
+            FindingCategory::Security,
+            Some(""),
+            &scan,
+            &embedder,
+            1,
+        )
+        .unwrap();
+        assert_eq!(r.duplicates, 1);
+        assert_eq!(r.findings[0].id, r.findings[1].id);
+        assert_ne!(r.findings[0].id, r.findings[2].id);
+        assert_eq!(r.new_count(), 2);
+
+        // A reworded message for the same symbol keeps its identity.
+        let dead = |desc: &str| NewFinding {
+            symbol: Some("oldHelper".into()),
+            ..finding("unused_function", "src/util.ts", 4, desc)
+        };
+        let a = record_scan(
+            &conn,
+            FindingCategory::DeadCode,
+            None,
+            &[dead("Unused function oldHelper in src/util.ts")],
+            &embedder,
+            1,
+        )
+        .unwrap();
+        let b = record_scan(
+            &conn,
+            FindingCategory::DeadCode,
+            None,
+            &[dead(
+                "Unused function oldHelper in src/util.ts (no callers)",
+            )],
+            &embedder,
+            2,
+        )
+        .unwrap();
+        assert_eq!(a.findings[0].id, b.findings[0].id);
+    }
+
+    #[test]
+    fn resolution_is_limited_to_scope() {
+        let conn = initialize_database(":memory:").unwrap();
+        let embedder = FindingEmbedder::Lexical;
+        let all = vec![
+            finding("eval", "src/a.ts", 1, "Use of eval: eval(a)"),
+            finding("eval", "lib/b.ts", 1, "Use of eval: eval(b)"),
+        ];
+        record_scan(
+            &conn,
+            FindingCategory::Security,
+            Some(""),
+            &all,
+            &embedder,
+            1,
+        )
+        .unwrap();
+        let r = record_scan(
+            &conn,
+            FindingCategory::Security,
+            Some("src/"),
+            &[],
+            &embedder,
+            2,
+        )
+        .unwrap();
+        assert_eq!(r.resolved.len(), 1);
+        let open: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM findings WHERE status = 'open'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(open, 1);
+    }
+}
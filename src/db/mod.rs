@@ -1,10 +1,32 @@
 //! Database layer for CodeGraph.
 //!
 //! - [`schema`] — DDL and initialization (`initialize_database`).
+//! - [`bodies`] — Compressed node body storage in `node_bodies`.
+//! - [`cache`] — Saving and restoring the database as a CI cache artifact.
 //! - [`converters`] — Row-to-struct conversions (`row_to_code_node`, `row_to_code_edge`).
+//! - [`snapshot`] — Labeled graph snapshots and structural diffs between index runs.
+//! - [`findings`] — Stable identities and first/last-seen tracking for analysis findings.
+//! - [`doctor`] — Integrity checks and repairs behind `codegraph doctor`.
+//! - [`diagnostics`] — Table/index sizes, query plans and the query log behind `codegraph db stats`.
+//! - [`risk_history`] — Per-directory security risk scores recorded for trend tracking.
+//! - [`saved_queries`] — Named, parameterized searches saved locally or shared in the config.
+//! - [`summaries`] — Cached module summaries written through MCP sampling.
+//! - [`usage`] — Per-tool usage analytics of MCP tool calls.
+//! - [`warm_start`] — Seeding a fresh index with unchanged files' rows from an older database.
 
+pub mod bodies;
+pub mod cache;
 pub mod converters;
+pub mod diagnostics;
+pub mod doctor;
+pub mod findings;
+pub mod risk_history;
+pub mod saved_queries;
 pub mod schema;
+pub mod snapshot;
+pub mod summaries;
+pub mod usage;
+pub mod warm_start;
 
 // Re-export the most commonly used items at the `db` level for convenience.
 pub use converters::{row_to_code_edge, row_to_code_node};
@@ -0,0 +1,121 @@
+//! Security risk history.
+//!
+//! Every `codegraph_security_summary` run records the risk score of each
+//! directory it covered in `security_risk`, so later runs can report how a
+//! directory's risk moved since the previous scan. Only the most recent
+//! [`MAX_RISK_SCANS`] scans are kept.
+
+use std::collections::HashMap;
+
+use rusqlite::{params, Connection};
+use serde::Serialize;
+
+use crate::error::Result;
+
+/// Scans kept in `security_risk`; older ones are pruned on insert.
+pub const MAX_RISK_SCANS: i64 = 100;
+
+/// One directory's risk in one scan.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RiskSample {
+    /// Project-relative directory, `.` for the project root.
+    pub directory: String,
+    /// Combined risk score, 0.0 to 10.0.
+    pub score: f64,
+    pub findings: usize,
+    /// Unix time of the scan that recorded it.
+    pub scanned_at: i64,
+}
+
+/// Record `samples` as one scan, pruning scans beyond [`MAX_RISK_SCANS`].
+/// A directory recorded twice in the same second keeps the later sample.
+pub fn record_risk(conn: &Connection, samples: &[RiskSample]) -> Result<()> {
+    let tx = conn.unchecked_transaction()?;
+    for s in samples {
+        tx.prepare_cached(
+            "INSERT OR REPLACE INTO security_risk (directory, scanned_at, score, findings) \
+             VALUES (?1, ?2, ?3, ?4)",
+        )?
+        .execute(params![
+            s.directory,
+            s.scanned_at,
+            s.score,
+            s.findings as i64
+        ])?;
+    }
+    tx.execute(
+        "DELETE FROM security_risk WHERE scanned_at < (\
+           SELECT MIN(scanned_at) FROM (\
+             SELECT DISTINCT scanned_at FROM security_risk ORDER BY scanned_at DESC LIMIT ?1))",
+        [MAX_RISK_SCANS],
+    )?;
+    tx.commit()?;
+    Ok(())
+}
+
+/// The latest sample of every directory inside `scope` (a project-relative
+/// directory, empty for the whole project), keyed by directory.
+pub fn latest_risk(conn: &Connection, scope: &str) -> Result<HashMap<String, RiskSample>> {
+    let mut stmt = conn.prepare(
+        "SELECT directory, scanned_at, score, findings FROM security_risk r \
+         WHERE scanned_at = (SELECT MAX(scanned_at) FROM security_risk WHERE directory = r.directory)",
+    )?;
+    let rows = stmt.query_map([], |row| {
+        Ok(RiskSample {
+            directory: row.get(0)?,
+            scanned_at: row.get(1)?,
+            score: row.get(2)?,
+            findings: row.get::<_, i64>(3)? as usize,
+        })
+    })?;
+    let mut latest = HashMap::new();
+    for sample in rows {
+        let sample = sample?;
+        if crate::graph::api_guard::in_directory(&sample.directory, scope) {
+            latest.insert(sample.directory.clone(), sample);
+        }
+    }
+    Ok(latest)
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::schema::initialize_database;
+
+    fn sample(directory: &str, score: f64, scanned_at: i64) -> RiskSample {
+        RiskSample {
+            directory: directory.to_string(),
+            score,
+            findings: 1,
+            scanned_at,
+        }
+    }
+
+    #[test]
+    fn latest_sample_per_directory_within_scope() {
+        let conn = initialize_database(":memory:").unwrap();
+        record_risk(&conn, &[sample("src", 7.5, 10), sample("src/db", 4.0, 10)]).unwrap();
+        record_risk(&conn, &[sample("src", 9.1, 20), sample(".", 2.0, 20)]).unwrap();
+
+        let all = latest_risk(&conn, "").unwrap();
+        assert_eq!(all.len(), 3);
+        assert_eq!(all["src"].score, 9.1);
+        assert_eq!(all["src/db"].scanned_at, 10);
+
+        let src = latest_risk(&conn, "src/").unwrap();
+        assert_eq!(src.len(), 2);
+        assert!(!src.contains_key("."));
+
+        for scan in 0..MAX_RISK_SCANS {
+            record_risk(&conn, &[sample("lib", 1.0, 100 + scan)]).unwrap();
+        }
+        let pruned = latest_risk(&conn, "").unwrap();
+        assert_eq!(pruned.keys().collect::<Vec<_>>(), vec!["lib"]);
+    }
+}
@@ -0,0 +1,319 @@
+//! Saved searches — named, parameterized `codegraph_query` calls.
+//!
+//! A [`SavedQuery`] bundles the query text with its filters, result limit
+//! and detail level. Queries saved from the CLI (`codegraph query --save`)
+//! or the `codegraph_saved_query` tool live in the `saved_queries` table;
+//! teams share theirs under `saved_queries:` in `.codegraph.yaml`. A local
+//! entry shadows a shared one of the same name.
+//!
+//! Query text and path may contain `{param}` placeholders that are filled
+//! in when the search runs, e.g. `handlers for {route}`.
+
+use std::collections::{BTreeMap, HashMap};
+
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{CodeGraphError, Result};
+use crate::graph::search::SearchOptions;
+use crate::graph::store::TagFilter;
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// A stored search.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SavedQuery {
+    /// Query text, possibly with `{param}` placeholders.
+    pub query: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kind: Option<String>,
+    /// Path prefix or glob, possibly with `{param}` placeholders.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exclude_languages: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exclude_kinds: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exclude_paths: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit: Option<usize>,
+    /// `summary`, `standard` or `full`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail_level: Option<String>,
+}
+
+/// Where a saved query came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SavedQuerySource {
+    /// Saved into this index.
+    Local,
+    /// Shared in the project config.
+    Config,
+}
+
+impl SavedQuerySource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Local => "local",
+            Self::Config => "config",
+        }
+    }
+}
+
+/// A saved query with its name and origin.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NamedQuery {
+    pub name: String,
+    pub source: SavedQuerySource,
+    pub parameters: Vec<String>,
+    #[serde(flatten)]
+    pub definition: SavedQuery,
+}
+
+impl SavedQuery {
+    /// Placeholder names in the query text and path, in order of
+    /// first appearance.
+    pub fn parameters(&self) -> Vec<String> {
+        let mut names = Vec::new();
+        for text in std::iter::once(&self.query).chain(&self.path) {
+            for name in placeholders(text) {
+                if !names.contains(&name) {
+                    names.push(name);
+                }
+            }
+        }
+        names
+    }
+
+    /// Fill in the placeholders, failing with the names of any left
+    /// without a value.
+    pub fn bind(&self, args: &HashMap<String, String>) -> Result<SavedQuery> {
+        let missing: Vec<String> = self
+            .parameters()
+            .into_iter()
+            .filter(|p| !args.contains_key(p))
+            .collect();
+        if !missing.is_empty() {
+            return Err(CodeGraphError::Other(format!(
+                "Missing saved query parameters: {}",
+                missing.join(", ")
+            )));
+        }
+        let fill = |text: &str| {
+            args.iter().fold(text.to_string(), |acc, (k, v)| {
+                acc.replace(&format!("{{{k}}}"), v)
+            })
+        };
+        Ok(SavedQuery {
+            query: fill(&self.query),
+            path: self.path.as_deref().map(fill),
+            ..self.clone()
+        })
+    }
+
+    /// Search options for a bound query.
+    pub fn search_options(&self) -> SearchOptions {
+        SearchOptions {
+            limit: Some(self.limit.unwrap_or(20)),
+            language: self.language.clone(),
+            node_type: self.kind.clone(),
+            tags: self
+                .tags
+                .iter()
+                .filter_map(|t| TagFilter::parse(t))
+                .collect(),
+            path: self.path.clone(),
+            exclude_languages: self.exclude_languages.clone(),
+            exclude_kinds: self.exclude_kinds.clone(),
+            exclude_paths: self.exclude_paths.clone(),
+            ..Default::default()
+        }
+    }
+}
+
+/// `{name}` placeholders in `text`.
+fn placeholders(text: &str) -> Vec<String> {
+    let mut out = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find('{') {
+        let after = &rest[start + 1..];
+        let Some(end) = after.find('}') else {
+            break;
+        };
+        let name = &after[..end];
+        if !name.is_empty() && name.chars().all(|c| c.is_alphanumeric() || c == '_') {
+            out.push(name.to_string());
+        }
+        rest = &after[end + 1..];
+    }
+    out
+}
+
+// ---------------------------------------------------------------------------
+// Storage
+// ---------------------------------------------------------------------------
+
+/// Save `query` under `name`, replacing an earlier local entry.
+pub fn save_query(conn: &Connection, name: &str, query: &SavedQuery) -> Result<()> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(CodeGraphError::Other(
+            "Saved query name must not be empty".to_string(),
+        ));
+    }
+    let definition = serde_json::to_string(query)
+        .map_err(|e| CodeGraphError::Other(format!("cannot encode saved query: {e}")))?;
+    conn.execute(
+        "INSERT INTO saved_queries (name, definition, updated_at) \
+         VALUES (?1, ?2, strftime('%s','now')) \
+         ON CONFLICT(name) DO UPDATE SET definition = excluded.definition, \
+         updated_at = excluded.updated_at",
+        params![name, definition],
+    )?;
+    Ok(())
+}
+
+/// Delete the local entry `name`. Returns whether one existed.
+pub fn delete_saved_query(conn: &Connection, name: &str) -> Result<bool> {
+    let n = conn.execute("DELETE FROM saved_queries WHERE name = ?1", params![name])?;
+    Ok(n > 0)
+}
+
+/// Look up `name`, locally first, then in the shared `config` entries.
+pub fn find_saved_query(
+    conn: &Connection,
+    config: &HashMap<String, SavedQuery>,
+    name: &str,
+) -> Result<Option<NamedQuery>> {
+    let local: Option<String> = conn
+        .query_row(
+            "SELECT definition FROM saved_queries WHERE name = ?1",
+            params![name],
+            |row| row.get(0),
+        )
+        .optional()?;
+    if let Some(definition) = local.and_then(|d| serde_json::from_str(&d).ok()) {
+        return Ok(Some(named(name, SavedQuerySource::Local, definition)));
+    }
+    Ok(config
+        .get(name)
+        .map(|q| named(name, SavedQuerySource::Config, q.clone())))
+}
+
+/// Every saved query by name, local entries shadowing shared ones.
+pub fn list_saved_queries(
+    conn: &Connection,
+    config: &HashMap<String, SavedQuery>,
+) -> Result<Vec<NamedQuery>> {
+    let mut all: BTreeMap<String, NamedQuery> = config
+        .iter()
+        .map(|(name, q)| {
+            let entry = named(name, SavedQuerySource::Config, q.clone());
+            (name.clone(), entry)
+        })
+        .collect();
+    let mut stmt = conn.prepare_cached("SELECT name, definition FROM saved_queries")?;
+    let rows = stmt.query_map([], |row| {
+        Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+    })?;
+    for (name, definition) in rows.flatten() {
+        if let Ok(q) = serde_json::from_str(&definition) {
+            let entry = named(&name, SavedQuerySource::Local, q);
+            all.insert(name, entry);
+        }
+    }
+    Ok(all.into_values().collect())
+}
+
+fn named(name: &str, source: SavedQuerySource, definition: SavedQuery) -> NamedQuery {
+    NamedQuery {
+        name: name.to_string(),
+        source,
+        parameters: definition.parameters(),
+        definition,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::schema::initialize_database;
+
+    fn query(text: &str) -> SavedQuery {
+        SavedQuery {
+            query: text.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn local_entries_shadow_shared_ones() {
+        let conn = initialize_database(":memory:").unwrap();
+        let shared = HashMap::from([
+            ("auth".to_string(), query("authentication")),
+            ("routes".to_string(), query("route handlers")),
+        ]);
+        save_query(&conn, "auth", &query("login session")).unwrap();
+        save_query(&conn, "auth", &query("login")).unwrap();
+
+        let auth = find_saved_query(&conn, &shared, "auth").unwrap().unwrap();
+        assert_eq!(auth.source, SavedQuerySource::Local);
+        assert_eq!(auth.definition.query, "login");
+
+        let names: Vec<(String, SavedQuerySource)> = list_saved_queries(&conn, &shared)
+            .unwrap()
+            .into_iter()
+            .map(|q| (q.name, q.source))
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                ("auth".to_string(), SavedQuerySource::Local),
+                ("routes".to_string(), SavedQuerySource::Config),
+            ]
+        );
+
+        assert!(delete_saved_query(&conn, "auth").unwrap());
+        let auth = find_saved_query(&conn, &shared, "auth").unwrap().unwrap();
+        assert_eq!(auth.source, SavedQuerySource::Config);
+        assert!(find_saved_query(&conn, &shared, "missing")
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn placeholders_are_bound_in_query_and_path() {
+        let saved = SavedQuery {
+            query: "handlers for {route} {route}".to_string(),
+            path: Some("src/{module}/**".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(saved.parameters(), vec!["route", "module"]);
+
+        let err = saved.bind(&HashMap::new()).unwrap_err();
+        assert!(err.to_string().contains("route, module"), "{err}");
+
+        let args = HashMap::from([
+            ("route".to_string(), "/login".to_string()),
+            ("module".to_string(), "api".to_string()),
+        ]);
+        let bound = saved.bind(&args).unwrap();
+        assert_eq!(bound.query, "handlers for /login /login");
+        assert_eq!(bound.search_options().path.as_deref(), Some("src/api/**"));
+    }
+}
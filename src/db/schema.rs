@@ -3,8 +3,19 @@
 //! Faithfully ports the TypeScript `db/schema.ts` to Rust, producing an
 //! identical on-disk schema so that databases are interchangeable between
 //! the TS and Rust implementations.
+//!
+//! The schema is built by numbered migrations recorded in
+//! `schema_migrations`. Opening a database applies the ones it is missing,
+//! so indexes created by older releases upgrade in place, and a database
+//! written by a newer release is refused rather than silently misread.
+
+use std::time::Duration;
+
+use rusqlite::{Connection, OpenFlags};
 
-use rusqlite::Connection;
+/// How long a connection waits for a competing writer (indexer, watcher,
+/// another server process) before failing with `database is locked`.
+pub const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
 
 // ---------------------------------------------------------------------------
 // DDL constants — kept as separate strings so each statement can be executed
@@ -70,19 +81,348 @@ CREATE TABLE IF NOT EXISTS unresolved_refs (
   FOREIGN KEY (source_id) REFERENCES nodes(id) ON DELETE CASCADE
 )";
 
-// Indexes ----------------------------------------------------------------
+const CREATE_CONFIG_KEYS: &str = "\
+CREATE TABLE IF NOT EXISTS config_keys (
+  id INTEGER PRIMARY KEY AUTOINCREMENT,
+  key TEXT NOT NULL,
+  file_path TEXT NOT NULL,
+  line INTEGER DEFAULT 0,
+  format TEXT NOT NULL
+)";
+
+const CREATE_CONFIG_REFS: &str = "\
+CREATE TABLE IF NOT EXISTS config_refs (
+  id INTEGER PRIMARY KEY AUTOINCREMENT,
+  key TEXT NOT NULL,
+  node_id TEXT NOT NULL,
+  file_path TEXT NOT NULL,
+  line INTEGER DEFAULT 0,
+  FOREIGN KEY (node_id) REFERENCES nodes(id) ON DELETE CASCADE
+)";
+
+const CREATE_SNAPSHOTS: &str = "\
+CREATE TABLE IF NOT EXISTS snapshots (
+  id INTEGER PRIMARY KEY AUTOINCREMENT,
+  label TEXT NOT NULL UNIQUE,
+  created_at INTEGER DEFAULT (strftime('%s','now')),
+  node_count INTEGER NOT NULL DEFAULT 0,
+  edge_count INTEGER NOT NULL DEFAULT 0
+)";
+
+const CREATE_SNAPSHOT_NODES: &str = "\
+CREATE TABLE IF NOT EXISTS snapshot_nodes (
+  snapshot_id INTEGER NOT NULL,
+  symbol_key TEXT NOT NULL,
+  name TEXT NOT NULL,
+  type TEXT NOT NULL,
+  file_path TEXT NOT NULL,
+  start_line INTEGER NOT NULL,
+  digest TEXT NOT NULL,
+  PRIMARY KEY (snapshot_id, symbol_key),
+  FOREIGN KEY (snapshot_id) REFERENCES snapshots(id) ON DELETE CASCADE
+)";
+
+const CREATE_SNAPSHOT_EDGES: &str = "\
+CREATE TABLE IF NOT EXISTS snapshot_edges (
+  snapshot_id INTEGER NOT NULL,
+  source_key TEXT NOT NULL,
+  target_key TEXT NOT NULL,
+  type TEXT NOT NULL,
+  PRIMARY KEY (snapshot_id, source_key, target_key, type),
+  FOREIGN KEY (snapshot_id) REFERENCES snapshots(id) ON DELETE CASCADE
+)";
+
+const CREATE_NODE_GIT_META: &str = "\
+CREATE TABLE IF NOT EXISTS node_git_meta (
+  node_id TEXT PRIMARY KEY,
+  file_path TEXT NOT NULL,
+  commit_hash TEXT NOT NULL,
+  author TEXT NOT NULL,
+  email TEXT NOT NULL,
+  committed_at INTEGER NOT NULL,
+  age_days INTEGER NOT NULL,
+  FOREIGN KEY (node_id) REFERENCES nodes(id) ON DELETE CASCADE
+)";
+
+const CREATE_WIRE_FIELDS: &str = "\
+CREATE TABLE IF NOT EXISTS wire_fields (
+  node_id TEXT PRIMARY KEY,
+  owner_id TEXT NOT NULL,
+  owner TEXT NOT NULL,
+  field_name TEXT NOT NULL,
+  wire_name TEXT NOT NULL,
+  skipped INTEGER NOT NULL DEFAULT 0,
+  optional INTEGER NOT NULL DEFAULT 0,
+  framework TEXT NOT NULL,
+  file_path TEXT NOT NULL,
+  line INTEGER NOT NULL,
+  FOREIGN KEY (node_id) REFERENCES nodes(id) ON DELETE CASCADE
+)";
+
+const CREATE_FINDINGS: &str = "\
+CREATE TABLE IF NOT EXISTS findings (
+  id TEXT PRIMARY KEY,
+  category TEXT NOT NULL,
+  rule TEXT NOT NULL,
+  file_path TEXT NOT NULL,
+  symbol TEXT,
+  line INTEGER NOT NULL,
+  description TEXT NOT NULL,
+  embedding BLOB,
+  embedding_model TEXT,
+  status TEXT NOT NULL DEFAULT 'open',
+  first_seen INTEGER NOT NULL,
+  last_seen INTEGER NOT NULL,
+  seen_count INTEGER NOT NULL DEFAULT 1
+)";
+
+const CREATE_NODE_TOMBSTONES: &str = "\
+CREATE TABLE IF NOT EXISTS node_tombstones (
+  id TEXT PRIMARY KEY,
+  type TEXT NOT NULL,
+  name TEXT NOT NULL,
+  qualified_name TEXT,
+  file_path TEXT NOT NULL,
+  start_line INTEGER NOT NULL,
+  end_line INTEGER NOT NULL,
+  language TEXT NOT NULL,
+  deleted_at INTEGER NOT NULL
+)";
+
+const CREATE_EDGE_TOMBSTONES: &str = "\
+CREATE TABLE IF NOT EXISTS edge_tombstones (
+  source_id TEXT NOT NULL,
+  target_id TEXT NOT NULL,
+  type TEXT NOT NULL,
+  file_path TEXT NOT NULL,
+  deleted_at INTEGER NOT NULL,
+  PRIMARY KEY (source_id, target_id, type)
+)";
+
+const CREATE_IMPORT_SIDE_EFFECTS: &str = "\
+CREATE TABLE IF NOT EXISTS import_side_effects (
+  file_path TEXT NOT NULL,
+  line INTEGER NOT NULL,
+  kind TEXT NOT NULL,
+  statement TEXT NOT NULL,
+  PRIMARY KEY (file_path, line)
+)";
+
+const CREATE_NODE_BODIES: &str = "\
+CREATE TABLE IF NOT EXISTS node_bodies (
+  node_id TEXT PRIMARY KEY,
+  encoding TEXT NOT NULL,
+  body BLOB NOT NULL,
+  FOREIGN KEY (node_id) REFERENCES nodes(id) ON DELETE CASCADE
+)";
+
+const CREATE_DIR_STATS: &str = "\
+CREATE TABLE IF NOT EXISTS dir_stats (
+  directory TEXT NOT NULL,
+  language TEXT NOT NULL,
+  files INTEGER NOT NULL,
+  nodes INTEGER NOT NULL,
+  functions INTEGER NOT NULL,
+  edges INTEGER NOT NULL,
+  complexity_total INTEGER NOT NULL,
+  complexity_max INTEGER NOT NULL,
+  PRIMARY KEY (directory, language)
+)";
+
+const CREATE_LITERALS: &str = "\
+CREATE TABLE IF NOT EXISTS literals (
+  file_path TEXT NOT NULL,
+  line INTEGER NOT NULL,
+  kind TEXT NOT NULL,
+  value TEXT NOT NULL,
+  name TEXT,
+  node_id TEXT
+)";
+
+/// User-defined labels. Keyed by node ID, but the symbol's file, kind and
+/// name are kept so a tag can follow its symbol when re-indexing changes
+/// the ID (IDs include the start line).
+const CREATE_NODE_TAGS: &str = "\
+CREATE TABLE IF NOT EXISTS node_tags (
+  node_id TEXT NOT NULL,
+  key TEXT NOT NULL,
+  value TEXT NOT NULL,
+  file_path TEXT NOT NULL,
+  kind TEXT NOT NULL,
+  symbol TEXT NOT NULL,
+  created_at INTEGER NOT NULL DEFAULT (strftime('%s','now')),
+  PRIMARY KEY (node_id, key)
+)";
+
+/// Free-text notes on nodes, kept and re-keyed like `node_tags`.
+const CREATE_NODE_NOTES: &str = "\
+CREATE TABLE IF NOT EXISTS node_notes (
+  id INTEGER PRIMARY KEY AUTOINCREMENT,
+  node_id TEXT NOT NULL,
+  note TEXT NOT NULL,
+  author TEXT,
+  file_path TEXT NOT NULL,
+  kind TEXT NOT NULL,
+  symbol TEXT NOT NULL,
+  created_at INTEGER NOT NULL DEFAULT (strftime('%s','now'))
+)";
+
+/// Tool calls recorded when `performance.query_log` is on, for
+/// `codegraph db stats`.
+const CREATE_QUERY_LOG: &str = "\
+CREATE TABLE IF NOT EXISTS query_log (
+  id INTEGER PRIMARY KEY AUTOINCREMENT,
+  source TEXT NOT NULL,
+  query TEXT NOT NULL,
+  duration_ms REAL NOT NULL,
+  recorded_at INTEGER NOT NULL DEFAULT (strftime('%s','now'))
+)";
+
+/// Global PageRank of each node, rebuilt after indexing and used as a
+/// search ranking prior.
+const CREATE_NODE_RANK: &str = "\
+CREATE TABLE IF NOT EXISTS node_rank (
+  node_id TEXT PRIMARY KEY,
+  page_rank REAL NOT NULL
+)";
 
-const CREATE_INDEXES: &[&str] = &[
+/// Named searches saved with `codegraph query --save`; `definition` is the
+/// JSON-encoded [`SavedQuery`](crate::db::saved_queries::SavedQuery).
+const CREATE_SAVED_QUERIES: &str = "\
+CREATE TABLE IF NOT EXISTS saved_queries (
+  name TEXT PRIMARY KEY,
+  definition TEXT NOT NULL,
+  created_at INTEGER NOT NULL DEFAULT (strftime('%s','now')),
+  updated_at INTEGER NOT NULL DEFAULT (strftime('%s','now'))
+)";
+
+/// Commit messages embedded for semantic commit search. `model` names the
+/// vector space of `embedding` (see [`commit_search`](crate::git::commit_search)).
+const CREATE_COMMIT_EMBEDDINGS: &str = "\
+CREATE TABLE IF NOT EXISTS commit_embeddings (
+  hash TEXT PRIMARY KEY,
+  author TEXT NOT NULL,
+  date TEXT NOT NULL,
+  timestamp INTEGER NOT NULL,
+  message TEXT NOT NULL,
+  embedding BLOB NOT NULL,
+  model TEXT NOT NULL
+)";
+
+/// Module summaries written by the client's model through MCP sampling,
+/// one per directory (see [`summaries`](crate::db::summaries)).
+const CREATE_SUMMARIES: &str = "\
+CREATE TABLE IF NOT EXISTS summaries (
+  directory TEXT PRIMARY KEY,
+  content_hash TEXT NOT NULL,
+  summary TEXT NOT NULL,
+  model TEXT,
+  created_at INTEGER NOT NULL
+)";
+
+/// Every MCP tool call, for usage analytics (see
+/// [`usage`](crate::db::usage)); `error` is set when the call failed.
+const CREATE_TOOL_INVOCATIONS: &str = "\
+CREATE TABLE IF NOT EXISTS tool_invocations (
+  id INTEGER PRIMARY KEY AUTOINCREMENT,
+  session TEXT NOT NULL,
+  tool TEXT NOT NULL,
+  duration_ms REAL NOT NULL,
+  result_bytes INTEGER NOT NULL,
+  error TEXT,
+  invoked_at INTEGER NOT NULL DEFAULT (strftime('%s','now'))
+)";
+
+const CREATE_SECURITY_RISK: &str = "\
+CREATE TABLE IF NOT EXISTS security_risk (
+  directory TEXT NOT NULL,
+  scanned_at INTEGER NOT NULL,
+  score REAL NOT NULL,
+  findings INTEGER NOT NULL,
+  PRIMARY KEY (directory, scanned_at)
+)";
+
+// Indexes (grouped by the migration that introduces them) ---------------
+
+const CORE_INDEXES: &[&str] = &[
     "CREATE INDEX IF NOT EXISTS idx_nodes_file ON nodes(file_path)",
     "CREATE INDEX IF NOT EXISTS idx_nodes_type ON nodes(type)",
     "CREATE INDEX IF NOT EXISTS idx_nodes_name ON nodes(name)",
-    "CREATE INDEX IF NOT EXISTS idx_nodes_is_test ON nodes(is_test) WHERE is_test = 1",
     "CREATE INDEX IF NOT EXISTS idx_edges_source ON edges(source_id)",
     "CREATE INDEX IF NOT EXISTS idx_edges_target ON edges(target_id)",
     "CREATE INDEX IF NOT EXISTS idx_edges_type ON edges(type)",
     "CREATE INDEX IF NOT EXISTS idx_unresolved_file ON unresolved_refs(file_path)",
 ];
 
+const IS_TEST_INDEXES: &[&str] =
+    &["CREATE INDEX IF NOT EXISTS idx_nodes_is_test ON nodes(is_test) WHERE is_test = 1"];
+
+const CONFIG_INDEXES: &[&str] = &[
+    "CREATE INDEX IF NOT EXISTS idx_config_keys_key ON config_keys(key)",
+    "CREATE INDEX IF NOT EXISTS idx_config_refs_key ON config_refs(key)",
+    "CREATE INDEX IF NOT EXISTS idx_config_refs_file ON config_refs(file_path)",
+];
+
+const NODE_GIT_META_INDEXES: &[&str] =
+    &["CREATE INDEX IF NOT EXISTS idx_node_git_meta_file ON node_git_meta(file_path)"];
+
+const WIRE_FIELDS_INDEXES: &[&str] = &[
+    "CREATE INDEX IF NOT EXISTS idx_wire_fields_owner ON wire_fields(owner_id)",
+    "CREATE INDEX IF NOT EXISTS idx_wire_fields_file ON wire_fields(file_path)",
+];
+
+const FINDINGS_INDEXES: &[&str] =
+    &["CREATE INDEX IF NOT EXISTS idx_findings_category_file ON findings(category, file_path)"];
+
+const TOMBSTONE_INDEXES: &[&str] = &[
+    "CREATE INDEX IF NOT EXISTS idx_node_tombstones_file ON node_tombstones(file_path)",
+    "CREATE INDEX IF NOT EXISTS idx_node_tombstones_name ON node_tombstones(name)",
+    "CREATE INDEX IF NOT EXISTS idx_node_tombstones_deleted ON node_tombstones(deleted_at)",
+    "CREATE INDEX IF NOT EXISTS idx_edge_tombstones_file ON edge_tombstones(file_path)",
+    EDGE_TOMBSTONES_TARGET_INDEX,
+];
+
+const EDGE_TOMBSTONES_TARGET_INDEX: &str =
+    "CREATE INDEX IF NOT EXISTS idx_edge_tombstones_target ON edge_tombstones(target_id)";
+
+const LITERALS_INDEXES: &[&str] = &[
+    "CREATE INDEX IF NOT EXISTS idx_literals_file ON literals(file_path)",
+    "CREATE INDEX IF NOT EXISTS idx_literals_value ON literals(value)",
+    "CREATE INDEX IF NOT EXISTS idx_literals_name ON literals(name) WHERE name IS NOT NULL",
+];
+
+const NODE_TAGS_INDEXES: &[&str] = &[
+    "CREATE INDEX IF NOT EXISTS idx_node_tags_key_value ON node_tags(key, value)",
+    "CREATE INDEX IF NOT EXISTS idx_node_tags_file ON node_tags(file_path)",
+];
+
+const NODE_NOTES_INDEXES: &[&str] = &[
+    "CREATE INDEX IF NOT EXISTS idx_node_notes_node ON node_notes(node_id)",
+    "CREATE INDEX IF NOT EXISTS idx_node_notes_file ON node_notes(file_path)",
+];
+
+const QUERY_LOG_INDEXES: &[&str] =
+    &["CREATE INDEX IF NOT EXISTS idx_query_log_duration ON query_log(duration_ms)"];
+
+const TOOL_INVOCATIONS_INDEXES: &[&str] =
+    &["CREATE INDEX IF NOT EXISTS idx_tool_invocations_time ON tool_invocations(invoked_at)"];
+
+/// Every index this build creates, for [`ensure_indexes`].
+const ALL_INDEXES: &[&[&str]] = &[
+    CORE_INDEXES,
+    IS_TEST_INDEXES,
+    CONFIG_INDEXES,
+    NODE_GIT_META_INDEXES,
+    WIRE_FIELDS_INDEXES,
+    FINDINGS_INDEXES,
+    TOMBSTONE_INDEXES,
+    LITERALS_INDEXES,
+    NODE_TAGS_INDEXES,
+    NODE_NOTES_INDEXES,
+    QUERY_LOG_INDEXES,
+    TOOL_INVOCATIONS_INDEXES,
+];
+
 // FTS5 -------------------------------------------------------------------
 
 const CREATE_FTS: &str = "\
@@ -119,6 +459,397 @@ CREATE VIRTUAL TABLE IF NOT EXISTS vec_embeddings USING vec0(
   embedding float[768]
 )";
 
+// ---------------------------------------------------------------------------
+// Migrations
+// ---------------------------------------------------------------------------
+
+const CREATE_SCHEMA_MIGRATIONS: &str = "\
+CREATE TABLE IF NOT EXISTS schema_migrations (
+  version INTEGER PRIMARY KEY,
+  name TEXT NOT NULL,
+  applied_at INTEGER NOT NULL DEFAULT (strftime('%s','now'))
+)";
+
+/// A numbered schema change.
+///
+/// Every migration must be idempotent: databases created before migrations
+/// were tracked have no `schema_migrations` rows, so all migrations run
+/// against them once regardless of which objects already exist.
+struct Migration {
+    version: u32,
+    name: &'static str,
+    up: fn(&Connection) -> rusqlite::Result<()>,
+}
+
+/// All migrations, in version order. Append new ones; never renumber or
+/// edit a released migration.
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        name: "core_tables",
+        up: migrate_core_tables,
+    },
+    Migration {
+        version: 2,
+        name: "nodes_name_tokens",
+        up: migrate_add_name_tokens,
+    },
+    Migration {
+        version: 3,
+        name: "fts_nodes",
+        up: migrate_fts_nodes,
+    },
+    Migration {
+        version: 4,
+        name: "nodes_is_test",
+        up: migrate_add_is_test,
+    },
+    Migration {
+        version: 5,
+        name: "config_keys",
+        up: |conn| {
+            create_all(
+                conn,
+                &[CREATE_CONFIG_KEYS, CREATE_CONFIG_REFS],
+                CONFIG_INDEXES,
+            )
+        },
+    },
+    Migration {
+        version: 6,
+        name: "snapshots",
+        up: |conn| {
+            create_all(
+                conn,
+                &[
+                    CREATE_SNAPSHOTS,
+                    CREATE_SNAPSHOT_NODES,
+                    CREATE_SNAPSHOT_EDGES,
+                ],
+                &[],
+            )
+        },
+    },
+    Migration {
+        version: 7,
+        name: "node_git_meta",
+        up: |conn| create_all(conn, &[CREATE_NODE_GIT_META], NODE_GIT_META_INDEXES),
+    },
+    Migration {
+        version: 8,
+        name: "wire_fields",
+        up: |conn| create_all(conn, &[CREATE_WIRE_FIELDS], WIRE_FIELDS_INDEXES),
+    },
+    Migration {
+        version: 9,
+        name: "findings",
+        up: |conn| create_all(conn, &[CREATE_FINDINGS], FINDINGS_INDEXES),
+    },
+    Migration {
+        version: 10,
+        name: "tombstones",
+        up: |conn| {
+            create_all(
+                conn,
+                &[CREATE_NODE_TOMBSTONES, CREATE_EDGE_TOMBSTONES],
+                TOMBSTONE_INDEXES,
+            )
+        },
+    },
+    Migration {
+        version: 11,
+        name: "import_side_effects",
+        up: |conn| create_all(conn, &[CREATE_IMPORT_SIDE_EFFECTS], &[]),
+    },
+    Migration {
+        version: 12,
+        name: "node_bodies",
+        up: migrate_node_bodies,
+    },
+    Migration {
+        version: 13,
+        name: "dir_stats",
+        up: |conn| create_all(conn, &[CREATE_DIR_STATS], &[]),
+    },
+    Migration {
+        version: 14,
+        name: "literals",
+        up: |conn| create_all(conn, &[CREATE_LITERALS], LITERALS_INDEXES),
+    },
+    Migration {
+        version: 15,
+        name: "node_tags",
+        up: |conn| create_all(conn, &[CREATE_NODE_TAGS], NODE_TAGS_INDEXES),
+    },
+    Migration {
+        version: 16,
+        name: "query_log",
+        up: |conn| create_all(conn, &[CREATE_QUERY_LOG], QUERY_LOG_INDEXES),
+    },
+    Migration {
+        version: 17,
+        name: "node_rank",
+        up: |conn| create_all(conn, &[CREATE_NODE_RANK], &[]),
+    },
+    Migration {
+        version: 18,
+        name: "edge_tombstone_targets",
+        up: |conn| create_all(conn, &[], &[EDGE_TOMBSTONES_TARGET_INDEX]),
+    },
+    Migration {
+        version: 19,
+        name: "saved_queries",
+        up: |conn| create_all(conn, &[CREATE_SAVED_QUERIES], &[]),
+    },
+    Migration {
+        version: 20,
+        name: "commit_embeddings",
+        up: |conn| create_all(conn, &[CREATE_COMMIT_EMBEDDINGS], &[]),
+    },
+    Migration {
+        version: 21,
+        name: "file_line_counts",
+        up: migrate_file_line_counts,
+    },
+    Migration {
+        version: 22,
+        name: "node_notes",
+        up: |conn| create_all(conn, &[CREATE_NODE_NOTES], NODE_NOTES_INDEXES),
+    },
+    Migration {
+        version: 23,
+        name: "summaries",
+        up: |conn| create_all(conn, &[CREATE_SUMMARIES], &[]),
+    },
+    Migration {
+        version: 24,
+        name: "tool_invocations",
+        up: |conn| create_all(conn, &[CREATE_TOOL_INVOCATIONS], TOOL_INVOCATIONS_INDEXES),
+    },
+    Migration {
+        version: 25,
+        name: "security_risk",
+        up: |conn| create_all(conn, &[CREATE_SECURITY_RISK], &[]),
+    },
+];
+
+/// Schema version produced by this build (the highest migration number).
+pub const SCHEMA_VERSION: u32 = MIGRATIONS[MIGRATIONS.len() - 1].version;
+
+/// Highest migration recorded in `conn`, or 0 for a database without
+/// migration tracking.
+pub fn schema_version(conn: &Connection) -> rusqlite::Result<u32> {
+    let tracked = conn
+        .prepare("SELECT 1 FROM schema_migrations LIMIT 0")
+        .is_ok();
+    if !tracked {
+        return Ok(0);
+    }
+    conn.query_row(
+        "SELECT COALESCE(MAX(version), 0) FROM schema_migrations",
+        [],
+        |row| row.get(0),
+    )
+}
+
+/// Fail if `conn` was migrated by a newer CodeGraph than this build.
+fn check_not_newer(conn: &Connection, db_path: &str) -> rusqlite::Result<u32> {
+    let version = schema_version(conn)?;
+    if version > SCHEMA_VERSION {
+        return Err(rusqlite::Error::SqliteFailure(
+            rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_ERROR),
+            Some(format!(
+                "database '{db_path}' has schema version {version}, but this build of \
+                 codegraph only supports up to {SCHEMA_VERSION}; upgrade codegraph \
+                 or re-index into a new database"
+            )),
+        ));
+    }
+    Ok(version)
+}
+
+/// Apply every migration newer than the recorded version, each in its own
+/// transaction together with its `schema_migrations` row.
+fn run_migrations(conn: &Connection, db_path: &str) -> rusqlite::Result<()> {
+    conn.execute_batch(CREATE_SCHEMA_MIGRATIONS)?;
+    let current = check_not_newer(conn, db_path)?;
+    for migration in MIGRATIONS.iter().filter(|m| m.version > current) {
+        let tx = conn.unchecked_transaction()?;
+        (migration.up)(&tx)?;
+        tx.execute(
+            "INSERT INTO schema_migrations (version, name) VALUES (?1, ?2)",
+            rusqlite::params![migration.version, migration.name],
+        )?;
+        tx.commit()?;
+        if current > 0 {
+            tracing::info!(
+                "Migrated schema to version {} ({})",
+                migration.version,
+                migration.name
+            );
+        }
+    }
+    Ok(())
+}
+
+fn create_all(conn: &Connection, tables: &[&str], indexes: &[&str]) -> rusqlite::Result<()> {
+    for ddl in tables.iter().chain(indexes) {
+        conn.execute_batch(ddl)?;
+    }
+    Ok(())
+}
+
+/// Migration 1: the graph itself.
+fn migrate_core_tables(conn: &Connection) -> rusqlite::Result<()> {
+    create_all(
+        conn,
+        &[
+            CREATE_NODES,
+            CREATE_EDGES,
+            CREATE_FILE_HASHES,
+            CREATE_EMBEDDING_CACHE,
+            CREATE_UNRESOLVED_REFS,
+        ],
+        CORE_INDEXES,
+    )
+}
+
+/// Migration 2: add `name_tokens` column to `nodes`.
+///
+/// For databases created before this column existed, we add it via
+/// ALTER TABLE and drop the FTS5 table + triggers so migration 3 recreates
+/// them with the new column.
+fn migrate_add_name_tokens(conn: &Connection) -> rusqlite::Result<()> {
+    // Check if the column already exists
+    let has_column: bool = conn
+        .prepare("SELECT name_tokens FROM nodes LIMIT 0")
+        .is_ok();
+
+    if !has_column {
+        conn.execute_batch("ALTER TABLE nodes ADD COLUMN name_tokens TEXT")?;
+        // Drop old FTS table and triggers so they get recreated with the new column
+        conn.execute_batch("DROP TRIGGER IF EXISTS nodes_ai")?;
+        conn.execute_batch("DROP TRIGGER IF EXISTS nodes_ad")?;
+        conn.execute_batch("DROP TRIGGER IF EXISTS nodes_au")?;
+        conn.execute_batch("DROP TABLE IF EXISTS fts_nodes")?;
+        tracing::info!("Migrated: added name_tokens column to nodes");
+    }
+
+    Ok(())
+}
+
+/// Migration 3: FTS5 index over `nodes`, rebuilt from existing rows so an
+/// upgraded database is searchable without re-indexing.
+fn migrate_fts_nodes(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute_batch(CREATE_FTS)?;
+    for trigger in CREATE_FTS_TRIGGERS {
+        conn.execute_batch(trigger)?;
+    }
+    conn.execute_batch("INSERT INTO fts_nodes(fts_nodes) VALUES ('rebuild')")
+}
+
+/// Re-create any index this build expects that is missing from `conn`
+/// (dropped by hand, or lost to a partial restore). Returns the names of
+/// the indexes created.
+pub fn ensure_indexes(conn: &Connection) -> rusqlite::Result<Vec<String>> {
+    let mut created = Vec::new();
+    for ddl in ALL_INDEXES.iter().flat_map(|group| group.iter()) {
+        // "CREATE INDEX IF NOT EXISTS <name> ON ..."
+        let Some(name) = ddl.split_whitespace().nth(5) else {
+            continue;
+        };
+        if !object_exists(conn, "index", name)? {
+            conn.execute_batch(ddl)?;
+            created.push(name.to_string());
+        }
+    }
+    Ok(created)
+}
+
+/// Check the FTS5 index against `nodes` and rebuild it when they disagree,
+/// re-creating the table and its sync triggers if any are missing. Returns
+/// `true` when a rebuild was needed.
+pub fn repair_fts(conn: &Connection) -> rusqlite::Result<bool> {
+    let mut missing = !object_exists(conn, "table", "fts_nodes")?;
+    for trigger in ["nodes_ai", "nodes_ad", "nodes_au"] {
+        missing |= !object_exists(conn, "trigger", trigger)?;
+    }
+    if missing {
+        migrate_fts_nodes(conn)?;
+        return Ok(true);
+    }
+    // With rank = 1 the check also compares the index to the content table.
+    let in_sync = conn
+        .execute(
+            "INSERT INTO fts_nodes(fts_nodes, rank) VALUES ('integrity-check', 1)",
+            [],
+        )
+        .is_ok();
+    if !in_sync {
+        conn.execute_batch("INSERT INTO fts_nodes(fts_nodes) VALUES ('rebuild')")?;
+    }
+    Ok(!in_sync)
+}
+
+fn object_exists(conn: &Connection, kind: &str, name: &str) -> rusqlite::Result<bool> {
+    conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE type = ?1 AND name = ?2)",
+        [kind, name],
+        |row| row.get(0),
+    )
+}
+
+/// Migration 4: add `is_test` column to `nodes`.
+///
+/// For databases created before this column existed, we add it via
+/// ALTER TABLE. The DEFAULT 0 ensures backward compatibility.
+fn migrate_add_is_test(conn: &Connection) -> rusqlite::Result<()> {
+    let has_column: bool = conn.prepare("SELECT is_test FROM nodes LIMIT 0").is_ok();
+
+    if !has_column {
+        conn.execute_batch("ALTER TABLE nodes ADD COLUMN is_test INTEGER NOT NULL DEFAULT 0")?;
+        tracing::info!("Migrated: added is_test column to nodes");
+    }
+
+    create_all(conn, &[], IS_TEST_INDEXES)
+}
+
+/// Migration 21: add cloc-style line counts to `file_hashes`, filled in as
+/// files are (re-)indexed.
+fn migrate_file_line_counts(conn: &Connection) -> rusqlite::Result<()> {
+    for column in [
+        "total_lines",
+        "code_lines",
+        "comment_lines",
+        "blank_lines",
+        "logical_lines",
+    ] {
+        let exists = conn
+            .prepare(&format!("SELECT {column} FROM file_hashes LIMIT 0"))
+            .is_ok();
+        if !exists {
+            conn.execute_batch(&format!(
+                "ALTER TABLE file_hashes ADD COLUMN {column} INTEGER NOT NULL DEFAULT 0"
+            ))?;
+        }
+    }
+    Ok(())
+}
+
+/// Migration 12: move node bodies out of the `metadata` JSON into
+/// `node_bodies`. Existing bodies are copied uncompressed; re-indexing
+/// rewrites them with the configured storage settings.
+fn migrate_node_bodies(conn: &Connection) -> rusqlite::Result<()> {
+    create_all(conn, &[CREATE_NODE_BODIES], &[])?;
+    conn.execute_batch(
+        "INSERT OR IGNORE INTO node_bodies (node_id, encoding, body)
+         SELECT id, 'plain', CAST(json_extract(metadata, '$.body') AS BLOB) FROM nodes
+         WHERE json_valid(metadata) AND json_extract(metadata, '$.body') IS NOT NULL;
+         UPDATE nodes SET metadata = json_remove(metadata, '$.body')
+         WHERE json_valid(metadata) AND json_extract(metadata, '$.body') IS NOT NULL;",
+    )
+}
+
 // ---------------------------------------------------------------------------
 // Public API
 // ---------------------------------------------------------------------------
@@ -153,16 +884,42 @@ pub fn create_vec_table(conn: &Connection) {
     }
 }
 
-/// Open (or create) the SQLite database at `db_path` and apply the full
-/// CodeGraph schema.
+/// Open an existing database at `db_path` for reading only.
+///
+/// No DDL is run; the database must already have been created by
+/// [`initialize_database`], and databases migrated by a newer CodeGraph are
+/// refused. Because the writer uses WAL mode, read-only
+/// connections see the last committed state and never block the writer.
+///
+/// # Errors
+///
+/// Returns a `rusqlite::Error` if the file does not exist or cannot be opened.
+pub fn open_read_only(db_path: &str) -> rusqlite::Result<Connection> {
+    load_sqlite_vec_extension();
+
+    let conn = Connection::open_with_flags(
+        db_path,
+        OpenFlags::SQLITE_OPEN_READ_ONLY
+            | OpenFlags::SQLITE_OPEN_NO_MUTEX
+            | OpenFlags::SQLITE_OPEN_URI,
+    )?;
+    conn.busy_timeout(BUSY_TIMEOUT)?;
+    conn.pragma_update(None, "query_only", "ON")?;
+    check_not_newer(&conn, db_path)?;
+    Ok(conn)
+}
+
+/// Open (or create) the SQLite database at `db_path` and migrate it to
+/// [`SCHEMA_VERSION`].
 ///
-/// The returned connection has WAL mode, foreign keys, and synchronous
-/// NORMAL already configured.
+/// The returned connection has WAL mode, foreign keys, synchronous NORMAL,
+/// and a [`BUSY_TIMEOUT`] already configured.
 ///
 /// # Errors
 ///
-/// Returns a `rusqlite::Error` if the database cannot be opened or any DDL
-/// statement fails (excluding the optional `vec_embeddings` table).
+/// Returns a `rusqlite::Error` if the database cannot be opened, any
+/// migration fails (excluding the optional `vec_embeddings` table), or the
+/// database was migrated by a newer CodeGraph.
 pub fn initialize_database(db_path: &str) -> rusqlite::Result<Connection> {
     // Register the sqlite-vec auto-extension *before* opening the connection.
     load_sqlite_vec_extension();
@@ -170,6 +927,9 @@ pub fn initialize_database(db_path: &str) -> rusqlite::Result<Connection> {
     let conn = Connection::open(db_path)?;
 
     // -- Pragmas ----------------------------------------------------------
+    // Set the busy timeout first so the WAL switch itself waits on a
+    // concurrently opening writer instead of failing.
+    conn.busy_timeout(BUSY_TIMEOUT)?;
     conn.pragma_update(None, "journal_mode", "WAL")?;
     // FK enforcement is OFF (matching TS better-sqlite3 default behavior).
     // Import/type-ref edges often target conceptual IDs (e.g. "module:./path")
@@ -177,27 +937,8 @@ pub fn initialize_database(db_path: &str) -> rusqlite::Result<Connection> {
     conn.pragma_update(None, "foreign_keys", "OFF")?;
     conn.pragma_update(None, "synchronous", "NORMAL")?;
 
-    // -- Core tables ------------------------------------------------------
-    conn.execute_batch(CREATE_NODES)?;
-    conn.execute_batch(CREATE_EDGES)?;
-    conn.execute_batch(CREATE_FILE_HASHES)?;
-    conn.execute_batch(CREATE_EMBEDDING_CACHE)?;
-    conn.execute_batch(CREATE_UNRESOLVED_REFS)?;
-
-    // -- Indexes ----------------------------------------------------------
-    for ddl in CREATE_INDEXES {
-        conn.execute_batch(ddl)?;
-    }
-
-    // -- Migrations -------------------------------------------------------
-    migrate_add_name_tokens(&conn)?;
-    migrate_add_is_test(&conn)?;
-
-    // -- FTS5 -------------------------------------------------------------
-    conn.execute_batch(CREATE_FTS)?;
-    for trigger in CREATE_FTS_TRIGGERS {
-        conn.execute_batch(trigger)?;
-    }
+    // -- Schema -----------------------------------------------------------
+    run_migrations(&conn, db_path)?;
 
     // -- sqlite-vec -------------------------------------------------------
     create_vec_table(&conn);
@@ -205,44 +946,6 @@ pub fn initialize_database(db_path: &str) -> rusqlite::Result<Connection> {
     Ok(conn)
 }
 
-/// Migration: add `name_tokens` column to `nodes` and recreate FTS5.
-///
-/// For databases created before this column existed, we add it via
-/// ALTER TABLE and then rebuild the FTS5 table + triggers to include it.
-fn migrate_add_name_tokens(conn: &Connection) -> rusqlite::Result<()> {
-    // Check if the column already exists
-    let has_column: bool = conn
-        .prepare("SELECT name_tokens FROM nodes LIMIT 0")
-        .is_ok();
-
-    if !has_column {
-        conn.execute_batch("ALTER TABLE nodes ADD COLUMN name_tokens TEXT")?;
-        // Drop old FTS table and triggers so they get recreated with the new column
-        conn.execute_batch("DROP TRIGGER IF EXISTS nodes_ai")?;
-        conn.execute_batch("DROP TRIGGER IF EXISTS nodes_ad")?;
-        conn.execute_batch("DROP TRIGGER IF EXISTS nodes_au")?;
-        conn.execute_batch("DROP TABLE IF EXISTS fts_nodes")?;
-        tracing::info!("Migrated: added name_tokens column to nodes");
-    }
-
-    Ok(())
-}
-
-/// Migration: add `is_test` column to `nodes`.
-///
-/// For databases created before this column existed, we add it via
-/// ALTER TABLE. The DEFAULT 0 ensures backward compatibility.
-fn migrate_add_is_test(conn: &Connection) -> rusqlite::Result<()> {
-    let has_column: bool = conn.prepare("SELECT is_test FROM nodes LIMIT 0").is_ok();
-
-    if !has_column {
-        conn.execute_batch("ALTER TABLE nodes ADD COLUMN is_test INTEGER NOT NULL DEFAULT 0")?;
-        tracing::info!("Migrated: added is_test column to nodes");
-    }
-
-    Ok(())
-}
-
 // ---------------------------------------------------------------------------
 // Tests
 // ---------------------------------------------------------------------------
@@ -283,6 +986,22 @@ mod tests {
             "file_hashes",
             "embedding_cache",
             "unresolved_refs",
+            "config_keys",
+            "config_refs",
+            "snapshots",
+            "snapshot_nodes",
+            "snapshot_edges",
+            "node_git_meta",
+            "wire_fields",
+            "findings",
+            "node_tombstones",
+            "edge_tombstones",
+            "import_side_effects",
+            "node_bodies",
+            "node_notes",
+            "summaries",
+            "tool_invocations",
+            "security_risk",
         ] {
             assert!(
                 object_exists(&conn, "table", table),
@@ -363,6 +1082,157 @@ mod tests {
             .unwrap();
         // NORMAL = 1
         assert_eq!(sync, 1, "synchronous should be NORMAL (1)");
+
+        let timeout: i64 = conn
+            .pragma_query_value(None, "busy_timeout", |row| row.get(0))
+            .unwrap();
+        assert_eq!(timeout, BUSY_TIMEOUT.as_millis() as i64);
+    }
+
+    #[test]
+    fn read_only_connection_reads_but_cannot_write() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("graph.db");
+        let path = path.to_str().unwrap();
+        let writer = initialize_database(path).unwrap();
+        writer
+            .execute(
+                "INSERT INTO file_hashes (file_path, content_hash, language, indexed_at) \
+                 VALUES ('a.ts', 'h', 'typescript', 0)",
+                [],
+            )
+            .unwrap();
+
+        let reader = open_read_only(path).unwrap();
+        let count: i64 = reader
+            .query_row("SELECT COUNT(*) FROM file_hashes", [], |r| r.get(0))
+            .unwrap();
+        assert_eq!(count, 1);
+        assert!(reader.execute("DELETE FROM file_hashes", []).is_err());
+    }
+
+    #[test]
+    fn fresh_database_records_every_migration() {
+        let conn = setup();
+        assert_eq!(schema_version(&conn).unwrap(), SCHEMA_VERSION);
+        let count: i64 = conn
+            .query_row("SELECT COUNT(*) FROM schema_migrations", [], |r| r.get(0))
+            .unwrap();
+        assert_eq!(count as usize, MIGRATIONS.len());
+        assert!(MIGRATIONS.windows(2).all(|w| w[0].version < w[1].version));
+    }
+
+    #[test]
+    fn reopening_does_not_rerun_migrations() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("graph.db");
+        let path = path.to_str().unwrap();
+        initialize_database(path).unwrap();
+        let conn = initialize_database(path).unwrap();
+        let count: i64 = conn
+            .query_row("SELECT COUNT(*) FROM schema_migrations", [], |r| r.get(0))
+            .unwrap();
+        assert_eq!(count as usize, MIGRATIONS.len());
+    }
+
+    #[test]
+    fn untracked_legacy_database_is_upgraded_in_place() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("legacy.db");
+        let path = path.to_str().unwrap();
+        {
+            // Shape of an index written before name_tokens, is_test and
+            // migration tracking existed.
+            let conn = Connection::open(path).unwrap();
+            conn.execute_batch(
+                "CREATE TABLE nodes (id TEXT PRIMARY KEY, type TEXT NOT NULL, \
+                 name TEXT NOT NULL, qualified_name TEXT, file_path TEXT NOT NULL, \
+                 start_line INTEGER NOT NULL, end_line INTEGER NOT NULL, \
+                 start_column INTEGER DEFAULT 0, end_column INTEGER DEFAULT 0, \
+                 language TEXT NOT NULL, signature TEXT, doc_comment TEXT, \
+                 source_hash TEXT, metadata TEXT);
+                 CREATE VIRTUAL TABLE fts_nodes USING fts5(name, qualified_name, \
+                 signature, doc_comment, file_path, content='nodes', content_rowid='rowid');
+                 INSERT INTO nodes (id, type, name, file_path, start_line, end_line, language) \
+                 VALUES ('n1', 'function', 'legacyHelper', 'a.ts', 1, 2, 'typescript');",
+            )
+            .unwrap();
+        }
+
+        let conn = initialize_database(path).unwrap();
+        assert_eq!(schema_version(&conn).unwrap(), SCHEMA_VERSION);
+        let is_test: i64 = conn
+            .query_row("SELECT is_test FROM nodes WHERE id = 'n1'", [], |r| {
+                r.get(0)
+            })
+            .unwrap();
+        assert_eq!(is_test, 0);
+        assert!(object_exists(&conn, "table", "findings"));
+        // Existing rows are searchable through the rebuilt FTS index.
+        let hits: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM fts_nodes WHERE fts_nodes MATCH 'legacyHelper'",
+                [],
+                |r| r.get(0),
+            )
+            .unwrap();
+        assert_eq!(hits, 1);
+    }
+
+    #[test]
+    fn node_bodies_migration_moves_metadata_bodies() {
+        let conn = setup();
+        conn.execute_batch(
+            "INSERT INTO nodes (id, type, name, file_path, start_line, end_line, language, metadata) \
+             VALUES ('n1', 'function', 'f', 'a.ts', 1, 2, 'typescript', \
+                     '{\"body\":\"function f() {}\",\"exported\":true}');",
+        )
+        .unwrap();
+        migrate_node_bodies(&conn).unwrap();
+
+        let (encoding, body): (String, Vec<u8>) = conn
+            .query_row(
+                "SELECT encoding, body FROM node_bodies WHERE node_id = 'n1'",
+                [],
+                |r| Ok((r.get(0)?, r.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!(encoding, "plain");
+        assert_eq!(body, b"function f() {}");
+        let metadata: String = conn
+            .query_row("SELECT metadata FROM nodes WHERE id = 'n1'", [], |r| {
+                r.get(0)
+            })
+            .unwrap();
+        assert_eq!(metadata, r#"{"exported":true}"#);
+    }
+
+    #[test]
+    fn newer_schema_is_refused() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("future.db");
+        let path = path.to_str().unwrap();
+        {
+            let conn = initialize_database(path).unwrap();
+            conn.execute(
+                "INSERT INTO schema_migrations (version, name) VALUES (?1, 'from_the_future')",
+                [SCHEMA_VERSION + 1],
+            )
+            .unwrap();
+        }
+        let err = initialize_database(path).unwrap_err().to_string();
+        assert!(
+            err.contains("newer") || err.contains("only supports"),
+            "{err}"
+        );
+        assert!(open_read_only(path).is_err());
+    }
+
+    #[test]
+    fn read_only_connection_requires_existing_database() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("missing.db");
+        assert!(open_read_only(path.to_str().unwrap()).is_err());
     }
 
     #[test]
@@ -0,0 +1,528 @@
+//! Labeled graph snapshots and structural diffs between index runs.
+//!
+//! A snapshot stores a compact digest of every node and edge in the graph
+//! under a user-chosen label. Diffing a snapshot against the live graph (or
+//! another snapshot) reports which symbols and edges were added, removed, or
+//! changed since the snapshot was taken.
+//!
+//! Node IDs embed the start line, so they are not stable across edits that
+//! merely shift code around. Snapshots therefore identify symbols by a
+//! *symbol key* — `kind:file_path:qualified_name` — and track content
+//! changes through a SHA-256 digest of the signature and doc comment.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use crate::error::{CodeGraphError, Result};
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// Summary of a stored snapshot.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotInfo {
+    pub label: String,
+    pub created_at: i64,
+    pub node_count: usize,
+    pub edge_count: usize,
+}
+
+/// Digest of a single symbol as captured in a snapshot.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SymbolDigest {
+    pub key: String,
+    pub name: String,
+    pub kind: String,
+    pub file_path: String,
+    pub start_line: u32,
+    #[serde(skip)]
+    pub digest: String,
+    /// For symbols removed from the live graph: when the index dropped
+    /// them (Unix seconds), from their tombstone.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deleted_at: Option<i64>,
+}
+
+/// Identity of an edge in terms of symbol keys rather than node IDs.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EdgeDigest {
+    pub source: String,
+    pub target: String,
+    pub kind: String,
+}
+
+/// Structural difference between two graph states.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GraphDiff {
+    pub added_nodes: Vec<SymbolDigest>,
+    pub removed_nodes: Vec<SymbolDigest>,
+    pub changed_nodes: Vec<SymbolDigest>,
+    pub added_edges: Vec<EdgeDigest>,
+    pub removed_edges: Vec<EdgeDigest>,
+}
+
+impl GraphDiff {
+    /// Returns `true` when the two graph states are structurally identical.
+    pub fn is_empty(&self) -> bool {
+        self.added_nodes.is_empty()
+            && self.removed_nodes.is_empty()
+            && self.changed_nodes.is_empty()
+            && self.added_edges.is_empty()
+            && self.removed_edges.is_empty()
+    }
+}
+
+/// In-memory view of a graph state, keyed by symbol key.
+struct GraphState {
+    nodes: BTreeMap<String, SymbolDigest>,
+    edges: BTreeSet<EdgeDigest>,
+}
+
+// ---------------------------------------------------------------------------
+// Capture
+// ---------------------------------------------------------------------------
+
+fn digest_hex(parts: &[&str]) -> String {
+    let mut hasher = Sha256::new();
+    for part in parts {
+        hasher.update(part.as_bytes());
+        hasher.update([0u8]);
+    }
+    format!("{:x}", hasher.finalize())
+}
+
+/// Read the live `nodes` / `edges` tables into a [`GraphState`].
+///
+/// Symbols that share a key (e.g. overloads) are disambiguated with a
+/// `#n` suffix in source order so every key stays unique.
+fn capture_live(conn: &Connection) -> Result<GraphState> {
+    let mut stmt = conn.prepare(
+        "SELECT id, type, name, qualified_name, file_path, start_line, signature, doc_comment \
+         FROM nodes ORDER BY file_path, start_line, id",
+    )?;
+    let rows = stmt.query_map([], |row| {
+        Ok((
+            row.get::<_, String>(0)?,
+            row.get::<_, String>(1)?,
+            row.get::<_, String>(2)?,
+            row.get::<_, Option<String>>(3)?,
+            row.get::<_, String>(4)?,
+            row.get::<_, u32>(5)?,
+            row.get::<_, Option<String>>(6)?,
+            row.get::<_, Option<String>>(7)?,
+        ))
+    })?;
+
+    let mut nodes = BTreeMap::new();
+    let mut id_to_key: HashMap<String, String> = HashMap::new();
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    for row in rows {
+        let (id, kind, name, qualified, file_path, start_line, signature, doc) = row?;
+        let base = format!(
+            "{}:{}:{}",
+            kind,
+            file_path,
+            qualified.as_deref().unwrap_or(&name)
+        );
+        let count = seen.entry(base.clone()).or_insert(0);
+        *count += 1;
+        let key = if *count == 1 {
+            base
+        } else {
+            format!("{}#{}", base, count)
+        };
+        let digest = digest_hex(&[
+            signature.as_deref().unwrap_or(""),
+            doc.as_deref().unwrap_or(""),
+        ]);
+        id_to_key.insert(id, key.clone());
+        nodes.insert(
+            key.clone(),
+            SymbolDigest {
+                key,
+                name,
+                kind,
+                file_path,
+                start_line,
+                digest,
+                deleted_at: None,
+            },
+        );
+    }
+
+    let mut stmt = conn.prepare("SELECT source_id, target_id, type FROM edges")?;
+    let rows = stmt.query_map([], |row| {
+        Ok((
+            row.get::<_, String>(0)?,
+            row.get::<_, String>(1)?,
+            row.get::<_, String>(2)?,
+        ))
+    })?;
+    let mut edges = BTreeSet::new();
+    for row in rows {
+        let (source_id, target_id, kind) = row?;
+        // Unresolved targets (e.g. `module:foo`) have no node row; keep the
+        // raw ID, which is already position-independent.
+        let source = id_to_key.get(&source_id).cloned().unwrap_or(source_id);
+        let target = id_to_key.get(&target_id).cloned().unwrap_or(target_id);
+        edges.insert(EdgeDigest {
+            source,
+            target,
+            kind,
+        });
+    }
+
+    Ok(GraphState { nodes, edges })
+}
+
+fn snapshot_id(conn: &Connection, label: &str) -> Result<i64> {
+    conn.query_row(
+        "SELECT id FROM snapshots WHERE label = ?1",
+        params![label],
+        |row| row.get(0),
+    )
+    .optional()?
+    .ok_or_else(|| CodeGraphError::Other(format!("Snapshot not found: {}", label)))
+}
+
+fn load_snapshot(conn: &Connection, label: &str) -> Result<GraphState> {
+    let id = snapshot_id(conn, label)?;
+
+    let mut stmt = conn.prepare(
+        "SELECT symbol_key, name, type, file_path, start_line, digest \
+         FROM snapshot_nodes WHERE snapshot_id = ?1",
+    )?;
+    let nodes = stmt
+        .query_map(params![id], |row| {
+            Ok(SymbolDigest {
+                key: row.get(0)?,
+                name: row.get(1)?,
+                kind: row.get(2)?,
+                file_path: row.get(3)?,
+                start_line: row.get(4)?,
+                digest: row.get(5)?,
+                deleted_at: None,
+            })
+        })?
+        .map(|r| r.map(|d| (d.key.clone(), d)))
+        .collect::<rusqlite::Result<BTreeMap<_, _>>>()?;
+
+    let mut stmt = conn.prepare(
+        "SELECT source_key, target_key, type FROM snapshot_edges WHERE snapshot_id = ?1",
+    )?;
+    let edges = stmt
+        .query_map(params![id], |row| {
+            Ok(EdgeDigest {
+                source: row.get(0)?,
+                target: row.get(1)?,
+                kind: row.get(2)?,
+            })
+        })?
+        .collect::<rusqlite::Result<BTreeSet<_>>>()?;
+
+    Ok(GraphState { nodes, edges })
+}
+
+// ---------------------------------------------------------------------------
+// Public API
+// ---------------------------------------------------------------------------
+
+/// Save the current graph under `label`, replacing any existing snapshot
+/// with the same label.
+pub fn create_snapshot(conn: &Connection, label: &str) -> Result<SnapshotInfo> {
+    if label.trim().is_empty() {
+        return Err(CodeGraphError::Other(
+            "Snapshot label must not be empty".to_string(),
+        ));
+    }
+    let state = capture_live(conn)?;
+
+    let tx = conn.unchecked_transaction()?;
+    // Delete children explicitly: foreign-key enforcement may be disabled.
+    if let Some(old) = tx
+        .query_row(
+            "SELECT id FROM snapshots WHERE label = ?1",
+            params![label],
+            |row| row.get::<_, i64>(0),
+        )
+        .optional()?
+    {
+        tx.execute(
+            "DELETE FROM snapshot_nodes WHERE snapshot_id = ?1",
+            params![old],
+        )?;
+        tx.execute(
+            "DELETE FROM snapshot_edges WHERE snapshot_id = ?1",
+            params![old],
+        )?;
+        tx.execute("DELETE FROM snapshots WHERE id = ?1", params![old])?;
+    }
+    tx.execute(
+        "INSERT INTO snapshots (label, node_count, edge_count) VALUES (?1, ?2, ?3)",
+        params![label, state.nodes.len() as i64, state.edges.len() as i64],
+    )?;
+    let id = tx.last_insert_rowid();
+    {
+        let mut stmt = tx.prepare_cached(
+            "INSERT INTO snapshot_nodes \
+             (snapshot_id, symbol_key, name, type, file_path, start_line, digest) \
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        )?;
+        for d in state.nodes.values() {
+            stmt.execute(params![
+                id,
+                d.key,
+                d.name,
+                d.kind,
+                d.file_path,
+                d.start_line,
+                d.digest
+            ])?;
+        }
+        let mut stmt = tx.prepare_cached(
+            "INSERT INTO snapshot_edges (snapshot_id, source_key, target_key, type) \
+             VALUES (?1, ?2, ?3, ?4)",
+        )?;
+        for e in &state.edges {
+            stmt.execute(params![id, e.source, e.target, e.kind])?;
+        }
+    }
+    tx.commit()?;
+
+    get_snapshot(conn, label)?
+        .ok_or_else(|| CodeGraphError::Other(format!("Snapshot not found: {}", label)))
+}
+
+/// Look up a snapshot by label.
+pub fn get_snapshot(conn: &Connection, label: &str) -> Result<Option<SnapshotInfo>> {
+    Ok(conn
+        .query_row(
+            "SELECT label, created_at, node_count, edge_count FROM snapshots WHERE label = ?1",
+            params![label],
+            row_to_info,
+        )
+        .optional()?)
+}
+
+/// List all snapshots, newest first.
+pub fn list_snapshots(conn: &Connection) -> Result<Vec<SnapshotInfo>> {
+    let mut stmt = conn.prepare(
+        "SELECT label, created_at, node_count, edge_count FROM snapshots \
+         ORDER BY created_at DESC, id DESC",
+    )?;
+    let rows = stmt.query_map([], row_to_info)?;
+    Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+}
+
+/// Delete a snapshot. Returns `true` if one was removed.
+pub fn delete_snapshot(conn: &Connection, label: &str) -> Result<bool> {
+    let Some(id) = conn
+        .query_row(
+            "SELECT id FROM snapshots WHERE label = ?1",
+            params![label],
+            |row| row.get::<_, i64>(0),
+        )
+        .optional()?
+    else {
+        return Ok(false);
+    };
+    let tx = conn.unchecked_transaction()?;
+    tx.execute(
+        "DELETE FROM snapshot_nodes WHERE snapshot_id = ?1",
+        params![id],
+    )?;
+    tx.execute(
+        "DELETE FROM snapshot_edges WHERE snapshot_id = ?1",
+        params![id],
+    )?;
+    tx.execute("DELETE FROM snapshots WHERE id = ?1", params![id])?;
+    tx.commit()?;
+    Ok(true)
+}
+
+/// Diff the snapshot `label` (the "before" state) against the live graph.
+/// Removed symbols that left a tombstone carry its `deleted_at`.
+pub fn diff_with_current(conn: &Connection, label: &str) -> Result<GraphDiff> {
+    let before = load_snapshot(conn, label)?;
+    let after = capture_live(conn)?;
+    let mut diff = diff_states(&before, &after);
+    let mut stmt = conn.prepare(
+        "SELECT max(deleted_at) FROM node_tombstones \
+         WHERE type = ?1 AND file_path = ?2 AND name = ?3",
+    )?;
+    for node in &mut diff.removed_nodes {
+        node.deleted_at = stmt.query_row(params![node.kind, node.file_path, node.name], |row| {
+            row.get(0)
+        })?;
+    }
+    Ok(diff)
+}
+
+/// Diff two stored snapshots, treating `from` as the "before" state.
+pub fn diff_snapshots(conn: &Connection, from: &str, to: &str) -> Result<GraphDiff> {
+    let before = load_snapshot(conn, from)?;
+    let after = load_snapshot(conn, to)?;
+    Ok(diff_states(&before, &after))
+}
+
+fn row_to_info(row: &rusqlite::Row<'_>) -> rusqlite::Result<SnapshotInfo> {
+    Ok(SnapshotInfo {
+        label: row.get(0)?,
+        created_at: row.get::<_, Option<i64>>(1)?.unwrap_or(0),
+        node_count: row.get::<_, i64>(2)? as usize,
+        edge_count: row.get::<_, i64>(3)? as usize,
+    })
+}
+
+fn diff_states(before: &GraphState, after: &GraphState) -> GraphDiff {
+    let mut diff = GraphDiff::default();
+    for (key, new) in &after.nodes {
+        match before.nodes.get(key) {
+            None => diff.added_nodes.push(new.clone()),
+            Some(old) if old.digest != new.digest => diff.changed_nodes.push(new.clone()),
+            Some(_) => {}
+        }
+    }
+    for (key, old) in &before.nodes {
+        if !after.nodes.contains_key(key) {
+            diff.removed_nodes.push(old.clone());
+        }
+    }
+    diff.added_edges = after.edges.difference(&before.edges).cloned().collect();
+    diff.removed_edges = before.edges.difference(&after.edges).cloned().collect();
+    diff
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::schema::initialize_database;
+
+    fn insert_node(conn: &Connection, id: &str, name: &str, line: u32, sig: &str) {
+        conn.execute(
+            "INSERT OR REPLACE INTO nodes (id, type, name, file_path, start_line, end_line, language, signature) \
+             VALUES (?1, 'function', ?2, 'src/a.ts', ?3, ?3, 'typescript', ?4)",
+            params![id, name, line, sig],
+        )
+        .unwrap();
+    }
+
+    fn insert_edge(conn: &Connection, source: &str, target: &str) {
+        conn.execute(
+            "INSERT INTO edges (source_id, target_id, type) VALUES (?1, ?2, 'calls')",
+            params![source, target],
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn snapshot_round_trip_and_empty_diff() {
+        let conn = initialize_database(":memory:").unwrap();
+        insert_node(&conn, "fn:a:1", "a", 1, "fn a()");
+        insert_node(&conn, "fn:b:5", "b", 5, "fn b()");
+        insert_edge(&conn, "fn:a:1", "fn:b:5");
+
+        let info = create_snapshot(&conn, "v1").unwrap();
+        assert_eq!(info.node_count, 2);
+        assert_eq!(info.edge_count, 1);
+        assert_eq!(list_snapshots(&conn).unwrap().len(), 1);
+        assert!(diff_with_current(&conn, "v1").unwrap().is_empty());
+    }
+
+    #[test]
+    fn diff_reports_added_removed_and_changed() {
+        let conn = initialize_database(":memory:").unwrap();
+        insert_node(&conn, "fn:a:1", "a", 1, "fn a()");
+        insert_node(&conn, "fn:b:5", "b", 5, "fn b()");
+        insert_edge(&conn, "fn:a:1", "fn:b:5");
+        create_snapshot(&conn, "before").unwrap();
+
+        // Shift `a` down (new ID, same content), change `b`, drop the edge,
+        // and add `c` with a call from `a`.
+        conn.execute_batch("DELETE FROM edges; DELETE FROM nodes;")
+            .unwrap();
+        insert_node(&conn, "fn:a:3", "a", 3, "fn a()");
+        insert_node(&conn, "fn:b:7", "b", 7, "fn b(x)");
+        insert_node(&conn, "fn:c:9", "c", 9, "fn c()");
+        insert_edge(&conn, "fn:a:3", "fn:c:9");
+
+        let diff = diff_with_current(&conn, "before").unwrap();
+        assert_eq!(diff.added_nodes.len(), 1);
+        assert_eq!(diff.added_nodes[0].name, "c");
+        assert!(diff.removed_nodes.is_empty());
+        assert_eq!(diff.changed_nodes.len(), 1);
+        assert_eq!(diff.changed_nodes[0].name, "b");
+        assert_eq!(diff.added_edges.len(), 1);
+        assert!(diff.added_edges[0].target.ends_with(":c"));
+        assert_eq!(diff.removed_edges.len(), 1);
+        assert!(diff.removed_edges[0].target.ends_with(":b"));
+    }
+
+    #[test]
+    fn snapshot_relabel_replaces_and_delete_works() {
+        let conn = initialize_database(":memory:").unwrap();
+        insert_node(&conn, "fn:a:1", "a", 1, "fn a()");
+        create_snapshot(&conn, "x").unwrap();
+        insert_node(&conn, "fn:b:5", "b", 5, "fn b()");
+        let info = create_snapshot(&conn, "x").unwrap();
+        assert_eq!(info.node_count, 2);
+        assert_eq!(list_snapshots(&conn).unwrap().len(), 1);
+
+        assert!(delete_snapshot(&conn, "x").unwrap());
+        assert!(!delete_snapshot(&conn, "x").unwrap());
+        assert!(diff_with_current(&conn, "x").is_err());
+    }
+
+    #[test]
+    fn diff_between_two_snapshots() {
+        let conn = initialize_database(":memory:").unwrap();
+        insert_node(&conn, "fn:a:1", "a", 1, "fn a()");
+        create_snapshot(&conn, "one").unwrap();
+        conn.execute("DELETE FROM nodes", []).unwrap();
+        insert_node(&conn, "fn:z:1", "z", 1, "fn z()");
+        create_snapshot(&conn, "two").unwrap();
+
+        let diff = diff_snapshots(&conn, "one", "two").unwrap();
+        assert_eq!(diff.added_nodes[0].name, "z");
+        assert_eq!(diff.removed_nodes[0].name, "a");
+        assert_eq!(diff.removed_nodes[0].deleted_at, None);
+    }
+
+    #[test]
+    fn diff_with_current_reports_when_symbols_were_removed() {
+        let conn = initialize_database(":memory:").unwrap();
+        insert_node(&conn, "fn:a:1", "a", 1, "fn a()");
+        insert_node(&conn, "fn:b:5", "b", 5, "fn b()");
+        create_snapshot(&conn, "before").unwrap();
+        conn.execute_batch(
+            "INSERT INTO node_tombstones \
+               (id, type, name, file_path, start_line, end_line, language, deleted_at) \
+             VALUES ('fn:a:1', 'function', 'a', 'src/a.ts', 1, 1, 'typescript', 1700000000); \
+             DELETE FROM nodes;",
+        )
+        .unwrap();
+
+        let diff = diff_with_current(&conn, "before").unwrap();
+        let removed: Vec<(&str, Option<i64>)> = diff
+            .removed_nodes
+            .iter()
+            .map(|n| (n.name.as_str(), n.deleted_at))
+            .collect();
+        assert_eq!(removed, vec![("a", Some(1_700_000_000)), ("b", None)]);
+        let json = serde_json::to_value(&diff).unwrap();
+        assert_eq!(json["removedNodes"][0]["deletedAt"], 1_700_000_000);
+        assert!(json["removedNodes"][1].get("deletedAt").is_none());
+    }
+}
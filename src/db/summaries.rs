@@ -0,0 +1,91 @@
+//! Cached natural-language module summaries.
+//!
+//! `codegraph_summarize_module` stores each summary the client's model
+//! writes in the `summaries` table, keyed by directory and the content
+//! hash of the context it was written from (see
+//! [`module_summary`](crate::context::module_summary)). A summary is
+//! reused until a file in the directory changes.
+
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+
+use crate::error::Result;
+
+/// A stored summary.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StoredSummary {
+    pub directory: String,
+    pub content_hash: String,
+    pub summary: String,
+    /// The model that wrote it, as reported by the client.
+    pub model: Option<String>,
+    pub created_at: i64,
+}
+
+/// The summary of `directory` written for `content_hash`, if any.
+pub fn find_summary(
+    conn: &Connection,
+    directory: &str,
+    content_hash: &str,
+) -> Result<Option<StoredSummary>> {
+    Ok(conn
+        .query_row(
+            "SELECT directory, content_hash, summary, model, created_at FROM summaries \
+             WHERE directory = ?1 AND content_hash = ?2",
+            params![directory, content_hash],
+            |row| {
+                Ok(StoredSummary {
+                    directory: row.get(0)?,
+                    content_hash: row.get(1)?,
+                    summary: row.get(2)?,
+                    model: row.get(3)?,
+                    created_at: row.get(4)?,
+                })
+            },
+        )
+        .optional()?)
+}
+
+/// Store the summary of `directory`, replacing the one for older content.
+pub fn save_summary(
+    conn: &Connection,
+    directory: &str,
+    content_hash: &str,
+    summary: &str,
+    model: Option<&str>,
+) -> Result<()> {
+    conn.execute(
+        "INSERT INTO summaries (directory, content_hash, summary, model, created_at) \
+         VALUES (?1, ?2, ?3, ?4, strftime('%s','now')) \
+         ON CONFLICT(directory) DO UPDATE SET content_hash = excluded.content_hash, \
+         summary = excluded.summary, model = excluded.model, created_at = excluded.created_at",
+        params![directory, content_hash, summary, model],
+    )?;
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::schema::initialize_database;
+
+    #[test]
+    fn summaries_are_replaced_when_the_content_changes() {
+        let conn = initialize_database(":memory:").unwrap();
+        save_summary(&conn, "src/auth", "h1", "Handles login.", Some("m")).unwrap();
+        let found = find_summary(&conn, "src/auth", "h1").unwrap().unwrap();
+        assert_eq!(found.summary, "Handles login.");
+        assert_eq!(found.model.as_deref(), Some("m"));
+
+        save_summary(&conn, "src/auth", "h2", "Handles login and tokens.", None).unwrap();
+        assert!(find_summary(&conn, "src/auth", "h1").unwrap().is_none());
+        let found = find_summary(&conn, "src/auth", "h2").unwrap().unwrap();
+        assert_eq!(found.summary, "Handles login and tokens.");
+        assert_eq!(found.model, None);
+    }
+}
@@ -0,0 +1,208 @@
+//! Tool usage analytics.
+//!
+//! The MCP server records every tool call — tool name, session, duration,
+//! response size and error — in `tool_invocations`. `codegraph_usage` and
+//! `codegraph usage` aggregate them per tool, so teams can see which tools
+//! agents actually call and trim their preset to those. Arguments are never
+//! recorded.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use rusqlite::{params, Connection};
+use serde::Serialize;
+
+use crate::error::Result;
+
+/// Rows kept in `tool_invocations`; older entries are pruned on insert.
+pub const MAX_INVOCATIONS: i64 = 100_000;
+
+/// Longest error message stored.
+const MAX_ERROR_TEXT: usize = 500;
+
+static NEXT_SESSION: AtomicU64 = AtomicU64::new(1);
+
+/// A new session ID, unique across the sessions of this database.
+pub fn new_session_id() -> String {
+    let started = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    format!(
+        "{started:x}-{:x}-{}",
+        std::process::id(),
+        NEXT_SESSION.fetch_add(1, Ordering::Relaxed)
+    )
+}
+
+/// One tool call to record.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ToolInvocation<'a> {
+    pub session: &'a str,
+    pub tool: &'a str,
+    pub duration: Duration,
+    /// Bytes of response text.
+    pub result_bytes: usize,
+    /// The error the call answered with, if any.
+    pub error: Option<&'a str>,
+}
+
+/// Record one tool call, pruning entries beyond [`MAX_INVOCATIONS`].
+pub fn record_invocation(conn: &Connection, call: &ToolInvocation) -> Result<()> {
+    let error = call
+        .error
+        .map(|e| &e[..e.floor_char_boundary(MAX_ERROR_TEXT)]);
+    conn.execute(
+        "INSERT INTO tool_invocations (session, tool, duration_ms, result_bytes, error) \
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![
+            call.session,
+            call.tool,
+            call.duration.as_secs_f64() * 1000.0,
+            call.result_bytes as i64,
+            error,
+        ],
+    )?;
+    conn.execute(
+        "DELETE FROM tool_invocations WHERE id <= last_insert_rowid() - ?1",
+        [MAX_INVOCATIONS],
+    )?;
+    Ok(())
+}
+
+/// Calls of one tool.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolUsage {
+    pub tool: String,
+    pub calls: u64,
+    pub errors: u64,
+    pub sessions: u64,
+    pub avg_ms: f64,
+    pub max_ms: f64,
+    pub avg_result_bytes: u64,
+    pub last_called_at: i64,
+    /// The most recent error message.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+}
+
+/// Recorded tool calls, most called tool first.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageReport {
+    pub total_calls: u64,
+    pub error_count: u64,
+    pub sessions: u64,
+    pub tools: Vec<ToolUsage>,
+}
+
+/// Aggregate the calls recorded since the unix time `since` (all calls
+/// without it), optionally only those of `session`.
+pub fn usage_report(
+    conn: &Connection,
+    since: Option<i64>,
+    session: Option<&str>,
+) -> Result<UsageReport> {
+    let since = since.unwrap_or(0);
+    let mut stmt = conn.prepare(
+        "SELECT tool, COUNT(*), COUNT(error), COUNT(DISTINCT session), AVG(duration_ms), \
+                MAX(duration_ms), AVG(result_bytes), MAX(invoked_at) \
+         FROM tool_invocations \
+         WHERE invoked_at >= ?1 AND (?2 IS NULL OR session = ?2) \
+         GROUP BY tool ORDER BY COUNT(*) DESC, tool",
+    )?;
+    let mut tools = stmt
+        .query_map(params![since, session], |row| {
+            Ok(ToolUsage {
+                tool: row.get(0)?,
+                calls: row.get::<_, i64>(1)? as u64,
+                errors: row.get::<_, i64>(2)? as u64,
+                sessions: row.get::<_, i64>(3)? as u64,
+                avg_ms: row.get(4)?,
+                max_ms: row.get(5)?,
+                avg_result_bytes: row.get::<_, f64>(6)? as u64,
+                last_called_at: row.get(7)?,
+                last_error: None,
+            })
+        })?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+
+    let mut stmt = conn.prepare(
+        "SELECT tool, error FROM tool_invocations \
+         WHERE error IS NOT NULL AND invoked_at >= ?1 AND (?2 IS NULL OR session = ?2) \
+         ORDER BY id",
+    )?;
+    let last_errors: HashMap<String, String> = stmt
+        .query_map(params![since, session], |row| {
+            Ok((row.get(0)?, row.get(1)?))
+        })?
+        .collect::<std::result::Result<_, _>>()?;
+    for usage in &mut tools {
+        usage.last_error = last_errors.get(&usage.tool).cloned();
+    }
+
+    let sessions: i64 = conn.query_row(
+        "SELECT COUNT(DISTINCT session) FROM tool_invocations \
+         WHERE invoked_at >= ?1 AND (?2 IS NULL OR session = ?2)",
+        params![since, session],
+        |row| row.get(0),
+    )?;
+    Ok(UsageReport {
+        total_calls: tools.iter().map(|t| t.calls).sum(),
+        error_count: tools.iter().map(|t| t.errors).sum(),
+        sessions: sessions as u64,
+        tools,
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::schema::initialize_database;
+
+    #[test]
+    fn calls_are_aggregated_per_tool() {
+        let conn = initialize_database(":memory:").unwrap();
+        let call = |session, tool, ms, error| ToolInvocation {
+            session,
+            tool,
+            duration: Duration::from_millis(ms),
+            result_bytes: 100,
+            error,
+        };
+        for c in [
+            call("s1", "codegraph_search", 10, None),
+            call("s1", "codegraph_search", 30, None),
+            call("s2", "codegraph_search", 20, Some("no index")),
+            call("s2", "codegraph_callers", 5, None),
+        ] {
+            record_invocation(&conn, &c).unwrap();
+        }
+
+        let report = usage_report(&conn, None, None).unwrap();
+        assert_eq!(report.total_calls, 4);
+        assert_eq!(report.error_count, 1);
+        assert_eq!(report.sessions, 2);
+        let search = &report.tools[0];
+        assert_eq!(search.tool, "codegraph_search");
+        assert_eq!((search.calls, search.errors, search.sessions), (3, 1, 2));
+        assert!((search.avg_ms - 20.0).abs() < 0.01);
+        assert!((search.max_ms - 30.0).abs() < 0.01);
+        assert_eq!(search.avg_result_bytes, 100);
+        assert_eq!(search.last_error.as_deref(), Some("no index"));
+        assert_eq!(report.tools[1].last_error, None);
+
+        let s1 = usage_report(&conn, None, Some("s1")).unwrap();
+        assert_eq!((s1.total_calls, s1.sessions, s1.tools.len()), (2, 1, 1));
+        let future = usage_report(&conn, Some(i64::MAX), None).unwrap();
+        assert_eq!(future, UsageReport::default());
+
+        assert_ne!(new_session_id(), new_session_id());
+    }
+}
@@ -0,0 +1,298 @@
+//! Warm-starting an index from a previous database.
+//!
+//! A full re-index (after a schema change, on a new machine, after
+//! deleting `.codegraph/`) normally re-parses every file. When an older
+//! database is at hand, the rows of every file whose content hash still
+//! matches can be copied over instead: nodes, their bodies and embeddings,
+//! outgoing edges, and the per-file side tables. The file hash is copied
+//! last, so the following incremental index skips those files and only
+//! parses what actually changed.
+//!
+//! Tables are copied column by column, using the columns both databases
+//! share; columns only the new schema has take their defaults. Because a
+//! new `nodes` column means extraction itself changed, an old database
+//! missing one is refused rather than copied half-filled.
+
+use std::path::Path;
+
+use rusqlite::{params, Connection, OpenFlags};
+
+use crate::error::{CodeGraphError, Result};
+
+/// Rows brought over by [`copy_files`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WarmStartStats {
+    pub files: usize,
+    pub nodes: usize,
+    pub edges: usize,
+}
+
+/// How the rows of a table are tied to a file.
+#[derive(Debug, Clone, Copy)]
+enum Scope {
+    /// The table has a `file_path` column.
+    File,
+    /// Rows belong to a node, through `node_id`.
+    Node,
+    /// Edges, owned by the file of their source node.
+    EdgeSource,
+}
+
+/// Per-file tables, dependents first. Derived data that is rebuilt on
+/// every run (`dir_stats`, `config_keys`) and history (`snapshots`,
+/// tombstones, `findings`) stays behind.
+const TABLES: &[(&str, Scope)] = &[
+    ("node_bodies", Scope::Node),
+    ("embedding_cache", Scope::Node),
+    ("vec_embeddings", Scope::Node),
+    ("edges", Scope::EdgeSource),
+    ("unresolved_refs", Scope::File),
+    ("config_refs", Scope::File),
+    ("node_git_meta", Scope::File),
+    ("wire_fields", Scope::File),
+    ("import_side_effects", Scope::File),
+    ("literals", Scope::File),
+    ("node_tags", Scope::File),
+    ("node_notes", Scope::File),
+    ("nodes", Scope::File),
+    ("file_hashes", Scope::File),
+];
+
+/// The content hash of every file indexed in `old_db`.
+pub fn old_file_hashes(old_db: &Path) -> Result<Vec<(String, String)>> {
+    let conn = Connection::open_with_flags(old_db, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+    let mut stmt = conn.prepare("SELECT file_path, content_hash FROM file_hashes")?;
+    let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+    rows.collect::<std::result::Result<Vec<_>, _>>()
+        .map_err(Into::into)
+}
+
+/// Replace the rows of `files` in `conn` with their rows from `old_db`,
+/// in one transaction.
+pub fn copy_files(conn: &Connection, old_db: &Path, files: &[String]) -> Result<WarmStartStats> {
+    if files.is_empty() {
+        return Ok(WarmStartStats::default());
+    }
+    conn.execute(
+        "ATTACH DATABASE ?1 AS warm",
+        params![old_db.to_string_lossy()],
+    )?;
+    let result = copy_attached(conn, old_db, files);
+    // DETACH cannot run inside a transaction; copy_attached has ended its.
+    conn.execute_batch("DETACH DATABASE warm")?;
+    result
+}
+
+fn copy_attached(conn: &Connection, old_db: &Path, files: &[String]) -> Result<WarmStartStats> {
+    let node_columns = columns(conn, "main", "nodes")?;
+    let old_node_columns = columns(conn, "warm", "nodes")?;
+    if let Some(missing) = node_columns.iter().find(|c| !old_node_columns.contains(c)) {
+        return Err(CodeGraphError::Other(format!(
+            "{} predates nodes.{}; index without warm start",
+            old_db.display(),
+            missing
+        )));
+    }
+
+    let tx = conn.unchecked_transaction()?;
+    tx.execute_batch(
+        "CREATE TEMP TABLE IF NOT EXISTS warm_files (file_path TEXT PRIMARY KEY);
+         DELETE FROM temp.warm_files;",
+    )?;
+    {
+        let mut ins = tx.prepare("INSERT OR IGNORE INTO temp.warm_files VALUES (?1)")?;
+        for file in files {
+            ins.execute(params![file])?;
+        }
+    }
+
+    // Drop whatever the new database already has for these files.
+    for &(table, scope) in TABLES {
+        if columns(&tx, "main", table)?.is_empty() {
+            continue;
+        }
+        tx.execute(
+            &format!(
+                "DELETE FROM main.{table} WHERE {}",
+                condition(scope, "main")
+            ),
+            [],
+        )?;
+    }
+
+    let mut stats = WarmStartStats::default();
+    for &(table, scope) in TABLES.iter().rev() {
+        let old = columns(&tx, "warm", table)?;
+        let shared: Vec<String> = columns(&tx, "main", table)?
+            .into_iter()
+            .filter(|c| old.contains(c) && !is_rowid_alias(&tx, table, c))
+            .collect();
+        if shared.is_empty() {
+            continue;
+        }
+        let list = shared.join(", ");
+        let sql = format!(
+            "INSERT INTO main.{table} ({list}) SELECT {list} FROM warm.{table} WHERE {}",
+            condition(scope, "warm")
+        );
+        let copied = match tx.execute(&sql, []) {
+            Ok(n) => n,
+            // Embeddings from another model (or dimension) are simply
+            // recomputed by the next index.
+            Err(e) if matches!(table, "vec_embeddings" | "embedding_cache") => {
+                tracing::warn!("Not copying {}: {}", table, e);
+                0
+            }
+            Err(e) => return Err(e.into()),
+        };
+        match table {
+            "nodes" => stats.nodes = copied,
+            "edges" => stats.edges = copied,
+            "file_hashes" => stats.files = copied,
+            _ => {}
+        }
+    }
+    tx.commit()?;
+    Ok(stats)
+}
+
+/// `WHERE` clause selecting the rows of the files in `temp.warm_files`,
+/// with node ownership resolved in database `db`.
+fn condition(scope: Scope, db: &str) -> String {
+    const FILES: &str = "SELECT file_path FROM temp.warm_files";
+    match scope {
+        Scope::File => format!("file_path IN ({FILES})"),
+        Scope::Node => {
+            format!("node_id IN (SELECT id FROM {db}.nodes WHERE file_path IN ({FILES}))")
+        }
+        Scope::EdgeSource => {
+            format!("source_id IN (SELECT id FROM {db}.nodes WHERE file_path IN ({FILES}))")
+        }
+    }
+}
+
+/// Column names of `db.table`, empty when the table does not exist.
+fn columns(conn: &Connection, db: &str, table: &str) -> Result<Vec<String>> {
+    let mut stmt = conn.prepare(&format!("PRAGMA {db}.table_info({table})"))?;
+    let names = stmt.query_map([], |row| row.get::<_, String>(1))?;
+    names
+        .collect::<std::result::Result<Vec<_>, _>>()
+        .map_err(Into::into)
+}
+
+/// Whether `column` is an `INTEGER PRIMARY KEY` of `table`. Those IDs are
+/// reassigned on copy so they cannot collide with existing rows.
+fn is_rowid_alias(conn: &Connection, table: &str, column: &str) -> bool {
+    conn.query_row(
+        &format!("SELECT type, pk FROM pragma_table_info('{table}', 'main') WHERE name = ?1"),
+        params![column],
+        |row| Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?)),
+    )
+    .is_ok_and(|(ty, pk)| pk == 1 && ty.eq_ignore_ascii_case("INTEGER"))
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::schema::initialize_database;
+
+    fn seed(conn: &Connection, file: &str, hash: &str, name: &str) {
+        conn.execute_batch(&format!(
+            "INSERT INTO nodes (id, type, name, file_path, start_line, end_line, language) \
+               VALUES ('fn:{file}:{name}:1', 'function', '{name}', '{file}', 1, 2, 'typescript');
+             INSERT INTO node_bodies (node_id, encoding, body) \
+               VALUES ('fn:{file}:{name}:1', 'raw', X'00');
+             INSERT INTO edges (source_id, target_id, type) \
+               VALUES ('fn:{file}:{name}:1', 'module:x', 'imports');
+             INSERT INTO literals (file_path, line, kind, value) VALUES ('{file}', 1, 'string', '{name}');
+             INSERT INTO file_hashes (file_path, content_hash, language) \
+               VALUES ('{file}', '{hash}', 'typescript');"
+        ))
+        .unwrap();
+    }
+
+    fn count(conn: &Connection, sql: &str) -> i64 {
+        conn.query_row(sql, [], |r| r.get(0)).unwrap()
+    }
+
+    #[test]
+    fn copies_only_the_requested_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let old_path = dir.path().join("old.db");
+        {
+            let old = initialize_database(old_path.to_str().unwrap()).unwrap();
+            seed(&old, "a.ts", "h1", "alpha");
+            seed(&old, "b.ts", "h2", "beta");
+        }
+        let hashes = old_file_hashes(&old_path).unwrap();
+        assert_eq!(hashes.len(), 2);
+
+        let conn = initialize_database(":memory:").unwrap();
+        // Stale rows for the copied file are replaced.
+        seed(&conn, "a.ts", "stale", "stale");
+        conn.execute_batch(
+            "INSERT INTO edges (source_id, target_id, type) VALUES ('x', 'y', 'calls')",
+        )
+        .unwrap();
+
+        let stats = copy_files(&conn, &old_path, &["a.ts".to_string()]).unwrap();
+        assert_eq!(
+            stats,
+            WarmStartStats {
+                files: 1,
+                nodes: 1,
+                edges: 1
+            }
+        );
+        assert_eq!(count(&conn, "SELECT count(*) FROM nodes"), 1);
+        assert_eq!(
+            count(&conn, "SELECT count(*) FROM nodes WHERE name = 'alpha'"),
+            1
+        );
+        assert_eq!(count(&conn, "SELECT count(*) FROM node_bodies"), 1);
+        assert_eq!(count(&conn, "SELECT count(*) FROM edges"), 2);
+        assert_eq!(
+            count(&conn, "SELECT count(*) FROM literals WHERE value = 'alpha'"),
+            1
+        );
+        assert_eq!(
+            count(
+                &conn,
+                "SELECT count(*) FROM file_hashes WHERE content_hash = 'h1'"
+            ),
+            1
+        );
+        // Copied nodes are searchable: the FTS triggers fired.
+        assert_eq!(
+            count(
+                &conn,
+                "SELECT count(*) FROM fts_nodes WHERE fts_nodes MATCH 'alpha'"
+            ),
+            1
+        );
+        // The old database is detached again.
+        assert!(conn.execute_batch("SELECT * FROM warm.nodes").is_err());
+    }
+
+    #[test]
+    fn refuses_databases_missing_node_columns() {
+        let dir = tempfile::tempdir().unwrap();
+        let old_path = dir.path().join("old.db");
+        {
+            let old = Connection::open(&old_path).unwrap();
+            old.execute_batch(
+                "CREATE TABLE nodes (id TEXT PRIMARY KEY, type TEXT, name TEXT, file_path TEXT);
+                 CREATE TABLE file_hashes (file_path TEXT PRIMARY KEY, content_hash TEXT, language TEXT);",
+            )
+            .unwrap();
+        }
+        let conn = initialize_database(":memory:").unwrap();
+        let err = copy_files(&conn, &old_path, &["a.ts".to_string()]).unwrap_err();
+        assert!(err.to_string().contains("predates nodes."));
+        assert!(conn.execute_batch("SELECT * FROM warm.nodes").is_err());
+    }
+}
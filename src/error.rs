@@ -7,6 +7,10 @@ pub enum CodeGraphError {
     #[error("SQLite error: {0}")]
     Database(#[from] rusqlite::Error),
 
+    #[cfg(feature = "postgres")]
+    #[error("Postgres error: {0}")]
+    Postgres(#[from] postgres::Error),
+
     #[error("I/O error: {0}")]
     Io(#[from] std::io::Error),
 
@@ -14,7 +14,11 @@ use crate::error::CodeGraphError;
 pub fn git_blame(repo_path: &Path, file_path: &str) -> Result<Vec<BlameLine>, CodeGraphError> {
     validate_input(file_path, "file_path")?;
 
-    let output = run_git(repo_path, &["blame", "--porcelain", "--", file_path])?;
+    // Blame the content as stored, not a textconv driver's rendering of it.
+    let output = run_git(
+        repo_path,
+        &["blame", "--porcelain", "--no-textconv", "--", file_path],
+    )?;
     parse_blame_porcelain(&output)
 }
 
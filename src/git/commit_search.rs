@@ -0,0 +1,360 @@
+//! Semantic commit search — natural-language queries over the history.
+//!
+//! Commit messages are embedded during indexing ([`index_commits`]) and
+//! stored in `commit_embeddings`. [`search_commits`] ranks them by cosine
+//! similarity to the query and fuses that ranking with `git log -S` hits for
+//! the query's identifier-like terms via Reciprocal Rank Fusion, so a commit
+//! whose diff actually touched `backoff` outranks one that merely talks
+//! about retries.
+//!
+//! Vectors come from the embedding model when one is loaded, else from the
+//! hashed bag-of-words used for findings. Each row records its model, and a
+//! search only compares vectors of the same model.
+
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use rusqlite::{params, Connection};
+use serde::Serialize;
+
+use super::{commit_messages, pickaxe_commits};
+use crate::db::findings::{cosine, from_blob, lexical_vector, to_blob};
+use crate::error::Result;
+use crate::indexer::embedder::EmbeddingEngine;
+
+/// Commits embedded per indexing run, newest first.
+pub const COMMIT_INDEX_LIMIT: usize = 1000;
+
+/// RRF smoothing constant, as in hybrid code search.
+const RRF_K: f64 = 60.0;
+
+/// Semantic candidates considered before fusion.
+const SEMANTIC_CANDIDATES: usize = 50;
+
+/// Pickaxe terms tried per query, longest first.
+const MAX_PICKAXE_TERMS: usize = 3;
+
+/// Pickaxe hits considered per term.
+const PICKAXE_LIMIT: usize = 50;
+
+/// Words that never make useful pickaxe terms.
+const STOPWORDS: &[&str] = &[
+    "about", "added", "after", "before", "change", "changed", "changes", "code", "commit", "does",
+    "fixed", "from", "have", "into", "logic", "made", "make", "that", "there", "this", "were",
+    "what", "when", "where", "which", "with", "work",
+];
+
+// ---------------------------------------------------------------------------
+// Embedder
+// ---------------------------------------------------------------------------
+
+/// Turns commit messages and queries into vectors.
+pub enum CommitEmbedder<'a> {
+    /// An already loaded embedding model.
+    Model(&'a EmbeddingEngine),
+    /// Hashed bag-of-words; cheap and always available.
+    Lexical,
+}
+
+impl CommitEmbedder<'_> {
+    /// Name stored with each vector.
+    pub fn model_name(&self) -> &'static str {
+        match self {
+            Self::Model(_) => "jina-embeddings-v2-base-code",
+            Self::Lexical => "lexical-256",
+        }
+    }
+
+    fn embed(&self, texts: &[&str]) -> Vec<Vec<f32>> {
+        #[cfg(feature = "embedding")]
+        if let Self::Model(engine) = self {
+            if let Ok(vectors) = engine.embed_batch(texts.to_vec()) {
+                return vectors;
+            }
+        }
+        texts.iter().map(|t| lexical_vector(t)).collect()
+    }
+}
+
+/// The embedding model for querying an index whose vectors come from
+/// `model`, loaded on demand. `None` for a lexical (or empty) index, or
+/// when the model cannot be loaded.
+pub fn engine_for(model: Option<&str>) -> Option<EmbeddingEngine> {
+    match model {
+        Some(m) if m != CommitEmbedder::Lexical.model_name() => EmbeddingEngine::try_new().ok(),
+        _ => None,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// A commit matching a history query.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommitMatch {
+    pub hash: String,
+    pub author: String,
+    pub date: String,
+    pub message: String,
+    /// Fused RRF score.
+    pub score: f64,
+    /// Cosine similarity of the message to the query, when ranked by it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub similarity: Option<f32>,
+    /// Query terms whose occurrence count the commit's diff changed.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub pickaxe_terms: Vec<String>,
+}
+
+// ---------------------------------------------------------------------------
+// Indexing
+// ---------------------------------------------------------------------------
+
+/// Embed the last `limit` commits of `repo` not yet stored for this
+/// embedder's model. Returns how many were added.
+pub fn index_commits(
+    conn: &Connection,
+    repo: &Path,
+    embedder: &CommitEmbedder<'_>,
+    limit: usize,
+) -> Result<usize> {
+    let model = embedder.model_name();
+    let known: HashSet<String> = {
+        let mut stmt =
+            conn.prepare_cached("SELECT hash FROM commit_embeddings WHERE model = ?1")?;
+        let rows = stmt.query_map(params![model], |row| row.get(0))?;
+        rows.flatten().collect()
+    };
+    let commits: Vec<_> = commit_messages(repo, limit)?
+        .into_iter()
+        .filter(|c| !known.contains(&c.hash))
+        .collect();
+
+    let mut stmt = conn.prepare_cached(
+        "INSERT OR REPLACE INTO commit_embeddings \
+         (hash, author, date, timestamp, message, embedding, model) \
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+    )?;
+    for chunk in commits.chunks(64) {
+        let texts: Vec<&str> = chunk.iter().map(|c| c.message.as_str()).collect();
+        for (commit, vector) in chunk.iter().zip(embedder.embed(&texts)) {
+            stmt.execute(params![
+                commit.hash,
+                commit.author,
+                commit.date,
+                commit.timestamp,
+                commit.message,
+                to_blob(&vector),
+                model,
+            ])?;
+        }
+    }
+    Ok(commits.len())
+}
+
+/// Number of embedded commits and the model most of them use.
+pub fn commit_index_status(conn: &Connection) -> Result<(usize, Option<String>)> {
+    let count: i64 = conn.query_row("SELECT COUNT(*) FROM commit_embeddings", [], |r| r.get(0))?;
+    let model = conn
+        .query_row(
+            "SELECT model FROM commit_embeddings GROUP BY model ORDER BY COUNT(*) DESC LIMIT 1",
+            [],
+            |r| r.get(0),
+        )
+        .ok();
+    Ok((count as usize, model))
+}
+
+// ---------------------------------------------------------------------------
+// Search
+// ---------------------------------------------------------------------------
+
+/// Rank commits for a natural-language `query`, best first.
+///
+/// Pickaxe lookups need the repository; when git fails (no repository,
+/// sandbox denial) the semantic ranking is returned alone.
+pub fn search_commits(
+    conn: &Connection,
+    repo: &Path,
+    embedder: &CommitEmbedder<'_>,
+    query: &str,
+    limit: usize,
+) -> Result<Vec<CommitMatch>> {
+    let mut fused: HashMap<String, CommitMatch> = HashMap::new();
+
+    for (rank, (commit, similarity)) in semantic_ranking(conn, embedder, query)?
+        .into_iter()
+        .enumerate()
+    {
+        let entry = fused.entry(commit.hash.clone()).or_insert(commit);
+        entry.similarity = Some(similarity);
+        entry.score += 1.0 / (RRF_K + rank as f64 + 1.0);
+    }
+
+    for term in pickaxe_terms(query) {
+        let Ok(hits) = pickaxe_commits(repo, &term, PICKAXE_LIMIT) else {
+            continue;
+        };
+        for (rank, hit) in hits.into_iter().enumerate() {
+            let entry = fused
+                .entry(hit.hash.clone())
+                .or_insert_with(|| CommitMatch {
+                    hash: hit.hash,
+                    author: hit.author,
+                    date: hit.date,
+                    message: hit.message,
+                    score: 0.0,
+                    similarity: None,
+                    pickaxe_terms: Vec::new(),
+                });
+            entry.pickaxe_terms.push(term.clone());
+            entry.score += 1.0 / (RRF_K + rank as f64 + 1.0);
+        }
+    }
+
+    let mut matches: Vec<CommitMatch> = fused.into_values().collect();
+    matches.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then_with(|| b.date.cmp(&a.date))
+    });
+    matches.truncate(limit);
+    Ok(matches)
+}
+
+/// Stored commits of the embedder's model by similarity to `query`,
+/// dropping those with no similarity at all.
+fn semantic_ranking(
+    conn: &Connection,
+    embedder: &CommitEmbedder<'_>,
+    query: &str,
+) -> Result<Vec<(CommitMatch, f32)>> {
+    let Some(query_vec) = embedder.embed(&[query]).pop() else {
+        return Ok(Vec::new());
+    };
+    let mut stmt = conn.prepare_cached(
+        "SELECT hash, author, date, message, embedding FROM commit_embeddings WHERE model = ?1",
+    )?;
+    let rows = stmt.query_map(params![embedder.model_name()], |row| {
+        Ok((
+            CommitMatch {
+                hash: row.get(0)?,
+                author: row.get(1)?,
+                date: row.get(2)?,
+                message: row.get(3)?,
+                score: 0.0,
+                similarity: None,
+                pickaxe_terms: Vec::new(),
+            },
+            row.get::<_, Vec<u8>>(4)?,
+        ))
+    })?;
+    let mut ranked: Vec<(CommitMatch, f32)> = rows
+        .flatten()
+        .map(|(commit, blob)| {
+            let similarity = cosine(&query_vec, &from_blob(&blob));
+            (commit, similarity)
+        })
+        .filter(|(_, similarity)| *similarity > 0.0)
+        .collect();
+    ranked.sort_by(|a, b| b.1.total_cmp(&a.1));
+    ranked.truncate(SEMANTIC_CANDIDATES);
+    Ok(ranked)
+}
+
+/// Identifier-like query terms worth a `git log -S`, longest first.
+///
+/// Words of four or more characters that are not stopwords qualify, as do
+/// tokens that look like code (`retry_backoff`, `maxRetries`).
+fn pickaxe_terms(query: &str) -> Vec<String> {
+    let mut terms: Vec<String> = Vec::new();
+    for token in query.split(|c: char| !c.is_alphanumeric() && c != '_') {
+        let lower = token.to_lowercase();
+        let code_like = token.contains('_') || token.chars().skip(1).any(char::is_uppercase);
+        let word_like = token.len() >= 4 && !STOPWORDS.contains(&lower.as_str());
+        if (code_like || word_like)
+            && !token.chars().all(|c| c.is_ascii_digit())
+            && !terms.iter().any(|t| t.eq_ignore_ascii_case(token))
+        {
+            terms.push(token.to_string());
+        }
+    }
+    terms.sort_by_key(|t| std::cmp::Reverse(t.len()));
+    terms.truncate(MAX_PICKAXE_TERMS);
+    terms
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::schema::initialize_database;
+
+    fn git(dir: &Path, args: &[&str]) {
+        let status = std::process::Command::new("git")
+            .args(args)
+            .current_dir(dir)
+            .env("GIT_AUTHOR_NAME", "Test Author")
+            .env("GIT_AUTHOR_EMAIL", "test@example.com")
+            .env("GIT_COMMITTER_NAME", "Test Author")
+            .env("GIT_COMMITTER_EMAIL", "test@example.com")
+            .status()
+            .unwrap();
+        assert!(status.success());
+    }
+
+    #[test]
+    fn pickaxe_terms_skip_stopwords_and_keep_identifiers() {
+        assert_eq!(
+            pickaxe_terms("when did we change the retry backoff logic"),
+            vec!["backoff", "retry"]
+        );
+        assert_eq!(pickaxe_terms("why is maxRetries 3"), vec!["maxRetries"]);
+    }
+
+    #[test]
+    fn search_fuses_message_similarity_with_pickaxe_hits() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = dir.path();
+        git(repo, &["init", "-q"]);
+        std::fs::write(repo.join("client.rs"), "fn send() {}\n").unwrap();
+        git(repo, &["add", "."]);
+        git(repo, &["commit", "-qm", "Add HTTP client"]);
+        std::fs::write(repo.join("client.rs"), "fn send() { backoff(2); }\n").unwrap();
+        git(repo, &["add", "."]);
+        git(repo, &["commit", "-qm", "Slow down reconnects"]);
+        std::fs::write(
+            repo.join("README.md"),
+            "Retry backoff is documented here.\n",
+        )
+        .unwrap();
+        git(repo, &["add", "."]);
+        git(repo, &["commit", "-qm", "Docs"]);
+
+        let conn = initialize_database(":memory:").unwrap();
+        let embedder = CommitEmbedder::Lexical;
+        assert_eq!(index_commits(&conn, repo, &embedder, 100).unwrap(), 3);
+        assert_eq!(index_commits(&conn, repo, &embedder, 100).unwrap(), 0);
+        assert_eq!(
+            commit_index_status(&conn).unwrap(),
+            (3, Some("lexical-256".to_string()))
+        );
+
+        let found = search_commits(&conn, repo, &embedder, "http client", 10).unwrap();
+        assert_eq!(found[0].message, "Add HTTP client");
+        assert!(found[0].similarity.unwrap() > 0.5);
+
+        // "Docs" is the newest commit touching "backoff", but the message
+        // match lifts the commit that introduced it in code above it.
+        let found = search_commits(&conn, repo, &embedder, "slow down backoff", 10).unwrap();
+        let messages: Vec<&str> = found.iter().map(|m| m.message.as_str()).collect();
+        assert_eq!(messages[..2], ["Slow down reconnects", "Docs"], "{found:?}");
+        assert_eq!(found[0].pickaxe_terms, vec!["backoff"]);
+        assert_eq!(found[1].similarity, None);
+    }
+}
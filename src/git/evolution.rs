@@ -0,0 +1,271 @@
+//! How a symbol's definition changed over its history.
+//!
+//! [`symbol_history`](super::symbol_history) lists the commits whose diff
+//! adds or removes the symbol's name (`git log -S`). That misses edits
+//! that keep the number of occurrences, such as a new parameter, so the
+//! files those commits touched are followed through their whole history.
+//! For each commit the touched files are parsed before and after it
+//! (`git show <rev>:<path>`) and the symbol's definition compared: added,
+//! removed, signature changed, or only its body changed. Pickaxe commits
+//! that leave the definition alone are references only; other commits
+//! that leave it alone are dropped.
+
+use std::collections::{BTreeSet, HashSet};
+use std::path::Path;
+
+use serde::Serialize;
+
+use super::{file_at_revision, files_history, symbol_history, CommitInfo};
+use crate::context::budget::signature_only;
+use crate::error::CodeGraphError;
+use crate::indexer::fallback::{self, ParserChains};
+use crate::indexer::CodeParser;
+use crate::types::{CodeNode, NodeKind};
+
+/// What a commit did to a symbol's definition in one file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DefinitionChange {
+    Added,
+    Removed,
+    SignatureChanged,
+    BodyChanged,
+    /// The definition is the same; the commit changed uses of the name.
+    ReferencesOnly,
+}
+
+/// The symbol's definition at one revision.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SymbolVersion {
+    pub kind: NodeKind,
+    pub signature: String,
+    pub body: String,
+    pub start_line: u32,
+    pub end_line: u32,
+}
+
+/// The symbol's definition in one file, before and after a commit.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DefinitionDiff {
+    pub file_path: String,
+    pub change: DefinitionChange,
+    pub before: Option<SymbolVersion>,
+    pub after: Option<SymbolVersion>,
+}
+
+/// A commit touching the symbol, with what it did to the definition.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SymbolEvolution {
+    pub commit: CommitInfo,
+    /// One entry per touched file defining the symbol before or after.
+    pub definitions: Vec<DefinitionDiff>,
+}
+
+impl SymbolEvolution {
+    /// The most significant change the commit made to the definition.
+    pub fn change(&self) -> DefinitionChange {
+        let order = |c: DefinitionChange| match c {
+            DefinitionChange::SignatureChanged => 0,
+            DefinitionChange::Added => 1,
+            DefinitionChange::Removed => 2,
+            DefinitionChange::BodyChanged => 3,
+            DefinitionChange::ReferencesOnly => 4,
+        };
+        self.definitions
+            .iter()
+            .map(|d| d.change)
+            .min_by_key(|c| order(*c))
+            .unwrap_or(DefinitionChange::ReferencesOnly)
+    }
+}
+
+/// Commits of the candidate files scanned per commit returned.
+const SCAN_FACTOR: usize = 5;
+
+/// The `limit` newest commits touching `symbol`, with its definition
+/// before and after each of them. A qualified `symbol` (`Cart.total`) is
+/// searched for by its last segment.
+pub fn symbol_evolution(
+    repo_path: &Path,
+    symbol: &str,
+    limit: usize,
+) -> Result<Vec<SymbolEvolution>, CodeGraphError> {
+    let name = symbol.rsplit('.').next().unwrap_or(symbol);
+    let pickaxe = symbol_history(repo_path, name)?;
+    let pickaxe_hashes: HashSet<&str> = pickaxe.iter().map(|c| c.hash.as_str()).collect();
+    let files: Vec<String> = pickaxe
+        .iter()
+        .flat_map(|c| c.files_changed.iter().cloned())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
+    if files.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let commits = files_history(repo_path, &files, limit.saturating_mul(SCAN_FACTOR))?;
+    let mut out = Vec::new();
+    for commit in commits {
+        if out.len() >= limit {
+            break;
+        }
+        let parent = format!("{}^", commit.hash);
+        let mut definitions = Vec::new();
+        for file in &commit.files_changed {
+            let before = file_at_revision(repo_path, &parent, file)?
+                .and_then(|source| find_definition(file, &source, symbol));
+            let after = file_at_revision(repo_path, &commit.hash, file)?
+                .and_then(|source| find_definition(file, &source, symbol));
+            let change = match (&before, &after) {
+                (None, None) => continue,
+                (None, Some(_)) => DefinitionChange::Added,
+                (Some(_), None) => DefinitionChange::Removed,
+                (Some(b), Some(a)) if b.signature != a.signature => {
+                    DefinitionChange::SignatureChanged
+                }
+                (Some(b), Some(a)) if b.body != a.body => DefinitionChange::BodyChanged,
+                _ => DefinitionChange::ReferencesOnly,
+            };
+            definitions.push(DefinitionDiff {
+                file_path: file.clone(),
+                change,
+                before,
+                after,
+            });
+        }
+        let touched = definitions
+            .iter()
+            .any(|d| d.change != DefinitionChange::ReferencesOnly);
+        if touched || pickaxe_hashes.contains(commit.hash.as_str()) {
+            out.push(SymbolEvolution {
+                commit,
+                definitions,
+            });
+        }
+    }
+    Ok(out)
+}
+
+/// The definition of `symbol` in one version of a file. A qualified name
+/// (`Cart.total`) matches only that member; a bare one prefers a function
+/// or method over other kinds.
+fn find_definition(file_path: &str, source: &str, symbol: &str) -> Option<SymbolVersion> {
+    let language = CodeParser::detect_language(file_path)?;
+    let extraction =
+        fallback::extract_nodes(&ParserChains::default(), file_path, language, source)?;
+    let node: &CodeNode = extraction
+        .nodes
+        .iter()
+        .filter(|n| n.name == symbol || n.qualified_name.as_deref() == Some(symbol))
+        .filter(|n| !matches!(n.kind, NodeKind::Module | NodeKind::Namespace))
+        .min_by_key(|n| {
+            (
+                !matches!(n.kind, NodeKind::Function | NodeKind::Method),
+                n.start_line,
+            )
+        })?;
+    let body = node.body.clone().unwrap_or_else(|| {
+        source
+            .lines()
+            .skip(node.start_line.saturating_sub(1) as usize)
+            .take((node.end_line + 1).saturating_sub(node.start_line) as usize)
+            .collect::<Vec<_>>()
+            .join("\n")
+    });
+    Some(SymbolVersion {
+        kind: node.kind,
+        signature: signature_only(&body),
+        body,
+        start_line: node.start_line,
+        end_line: node.end_line,
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn git(dir: &Path, args: &[&str]) {
+        let status = std::process::Command::new("git")
+            .args(args)
+            .current_dir(dir)
+            .env("GIT_AUTHOR_NAME", "Test Author")
+            .env("GIT_AUTHOR_EMAIL", "test@example.com")
+            .env("GIT_COMMITTER_NAME", "Test Author")
+            .env("GIT_COMMITTER_EMAIL", "test@example.com")
+            .status()
+            .unwrap();
+        assert!(status.success());
+    }
+
+    fn commit(dir: &Path, file: &str, contents: &str, message: &str) {
+        std::fs::write(dir.join(file), contents).unwrap();
+        git(dir, &["add", "."]);
+        git(dir, &["commit", "-qm", message]);
+    }
+
+    #[test]
+    fn classifies_each_commit_by_what_it_did_to_the_definition() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = dir.path();
+        git(repo, &["init", "-q"]);
+        commit(
+            repo,
+            "fee.ts",
+            "export function fee(x: number): number {\n  return x;\n}\n",
+            "Add fee",
+        );
+        commit(
+            repo,
+            "fee.ts",
+            "export function fee(x: number): number {\n  return x * 2;\n}\n",
+            "Double the fee",
+        );
+        commit(
+            repo,
+            "fee.ts",
+            "export function fee(x: number, rate: number): number {\n  return x * rate;\n}\n",
+            "Make the rate a parameter",
+        );
+        commit(
+            repo,
+            "pay.ts",
+            "import { fee } from './fee';\nexport const total = fee(1, 2);\n",
+            "Charge the fee",
+        );
+
+        let history = symbol_evolution(repo, "fee", 10).unwrap();
+        let timeline: Vec<(&str, DefinitionChange)> = history
+            .iter()
+            .map(|e| (e.commit.message.as_str(), e.change()))
+            .collect();
+        // Neither edit of `fee` changes how often the name occurs, so
+        // `git log -S` alone would miss both.
+        assert_eq!(
+            timeline,
+            vec![
+                ("Charge the fee", DefinitionChange::ReferencesOnly),
+                (
+                    "Make the rate a parameter",
+                    DefinitionChange::SignatureChanged
+                ),
+                ("Double the fee", DefinitionChange::BodyChanged),
+                ("Add fee", DefinitionChange::Added),
+            ]
+        );
+        assert!(history[0].definitions.is_empty());
+        let change = &history[1].definitions[0];
+        assert_eq!(
+            change.before.as_ref().unwrap().signature,
+            "function fee(x: number): number"
+        );
+        assert!(change.after.as_ref().unwrap().body.contains("x * rate"));
+    }
+}
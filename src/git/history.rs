@@ -1,15 +1,35 @@
 //! Git history — file history, recent changes, commit diff, symbol history,
-//! branch info, modified files.
+//! reference removals, commit messages, pickaxe search, branch info,
+//! modified files, files and lines changed since a revision, file contents
+//! at a revision.
 
 use std::path::Path;
 
-use super::{run_git, validate_input, BranchInfo, CommitInfo, DiffInfo, FileDiff, ModifiedFiles};
+use std::collections::BTreeMap;
+
+use super::{
+    run_git, validate_input, BranchInfo, ChangedFiles, ChangedLines, CommitInfo, CommitMessage,
+    DiffInfo, FileDiff, ModifiedFiles, ReferenceRemoval,
+};
 use crate::error::CodeGraphError;
 
 // ── Commit log format shared by several functions ───────────────────────
 
 const LOG_FORMAT: &str = "%H|%an|%ae|%aI|%s";
 
+/// Source files searched by the symbol pickaxe queries.
+const SOURCE_PATHSPECS: &[&str] = &[
+    "*.rs", "*.ts", "*.tsx", "*.js", "*.jsx", "*.py", "*.go", "*.java", "*.c", "*.cpp", "*.h",
+    "*.cs", "*.php", "*.rb", "*.swift", "*.kt",
+];
+
+/// Whether `line` starts a commit header (`<40 hex>|...`).
+fn is_commit_line(line: &str) -> bool {
+    line.len() > 41
+        && line.as_bytes()[40] == b'|'
+        && line[..40].bytes().all(|b| b.is_ascii_hexdigit())
+}
+
 /// Parse `git log --format=<LOG_FORMAT> --name-only` output into `CommitInfo`.
 fn parse_log_with_files(output: &str) -> Vec<CommitInfo> {
     let mut commits = Vec::new();
@@ -20,10 +40,7 @@ fn parse_log_with_files(output: &str) -> Vec<CommitInfo> {
             continue;
         }
         // A commit line has at least 40 hex chars followed by '|'
-        if line.len() > 41
-            && line.as_bytes()[40] == b'|'
-            && line[..40].bytes().all(|b| b.is_ascii_hexdigit())
-        {
+        if is_commit_line(line) {
             if let Some(c) = current.take() {
                 commits.push(c);
             }
@@ -75,6 +92,26 @@ pub fn file_history(
     Ok(parse_log_with_files(&output))
 }
 
+/// The last `limit` commits touching any of `files`, newest first. Each
+/// commit lists only those of `files` it changed.
+pub fn files_history(
+    repo_path: &Path,
+    files: &[String],
+    limit: usize,
+) -> Result<Vec<CommitInfo>, CodeGraphError> {
+    for file in files {
+        validate_input(file, "file_path")?;
+    }
+
+    let format = format!("--format={LOG_FORMAT}");
+    let limit_str = format!("-{limit}");
+    let mut args = vec!["log", &format, "--name-only", &limit_str, "--"];
+    args.extend(files.iter().map(String::as_str));
+    let output = run_git(repo_path, &args)?;
+
+    Ok(parse_log_with_files(&output))
+}
+
 /// Get the most recent commits across the entire repository.
 pub fn recent_changes(repo_path: &Path, limit: usize) -> Result<Vec<CommitInfo>, CodeGraphError> {
     let limit_str = format!("-{limit}");
@@ -91,6 +128,63 @@ pub fn recent_changes(repo_path: &Path, limit: usize) -> Result<Vec<CommitInfo>,
     Ok(parse_log_with_files(&output))
 }
 
+/// The last `limit` commits with their full messages, newest first.
+pub fn commit_messages(
+    repo_path: &Path,
+    limit: usize,
+) -> Result<Vec<CommitMessage>, CodeGraphError> {
+    let limit_str = format!("-{limit}");
+    let output = run_git(
+        repo_path,
+        &[
+            "log",
+            "--format=%x1e%H%x1f%an%x1f%at%x1f%aI%x1f%B",
+            &limit_str,
+        ],
+    )?;
+
+    Ok(output
+        .split('\x1e')
+        .filter_map(|record| {
+            let parts: Vec<&str> = record.splitn(5, '\x1f').collect();
+            (parts.len() == 5).then(|| CommitMessage {
+                hash: parts[0].trim().to_string(),
+                author: parts[1].to_string(),
+                timestamp: parts[2].parse().unwrap_or(0),
+                date: parts[3].to_string(),
+                message: parts[4].trim().to_string(),
+            })
+        })
+        .collect())
+}
+
+/// The newest `limit` commits whose diff changes the number of occurrences
+/// of `term`, ignoring case (`git log -S -i`). Unlike [`symbol_history`],
+/// all files are searched, so configuration changes are found too.
+pub fn pickaxe_commits(
+    repo_path: &Path,
+    term: &str,
+    limit: usize,
+) -> Result<Vec<CommitInfo>, CodeGraphError> {
+    validate_input(term, "term")?;
+
+    let s_flag = format!("-S{term}");
+    let limit_str = format!("-{limit}");
+    let output = run_git(
+        repo_path,
+        &[
+            "log",
+            &format!("--format={LOG_FORMAT}"),
+            "--name-only",
+            "--regexp-ignore-case",
+            &limit_str,
+            &s_flag,
+        ],
+    )?;
+
+    Ok(parse_log_with_files(&output))
+}
+
 /// Get the diff for a specific commit, with per-file addition/deletion counts.
 pub fn commit_diff(repo_path: &Path, commit_hash: &str) -> Result<DiffInfo, CodeGraphError> {
     validate_input(commit_hash, "commit_hash")?;
@@ -174,36 +268,131 @@ pub fn symbol_history(
     validate_input(symbol_name, "symbol_name")?;
 
     let s_flag = format!("-S{symbol_name}");
-    let output = run_git(
-        repo_path,
-        &[
-            "log",
-            &format!("--format={LOG_FORMAT}"),
-            "--name-only",
-            &s_flag,
-            "--",
-            "*.rs",
-            "*.ts",
-            "*.tsx",
-            "*.js",
-            "*.jsx",
-            "*.py",
-            "*.go",
-            "*.java",
-            "*.c",
-            "*.cpp",
-            "*.h",
-            "*.cs",
-            "*.php",
-            "*.rb",
-            "*.swift",
-            "*.kt",
-        ],
-    )?;
+    let format = format!("--format={LOG_FORMAT}");
+    let mut args = vec!["log", &format, "--name-only", &s_flag, "--"];
+    args.extend_from_slice(SOURCE_PATHSPECS);
+    let output = run_git(repo_path, &args)?;
 
     Ok(parse_log_with_files(&output))
 }
 
+/// Contents of `file` as of `rev`, or `None` when it does not exist there
+/// (or `rev` does not, as for the parent of a root commit).
+pub fn file_at_revision(
+    repo_path: &Path,
+    rev: &str,
+    file: &str,
+) -> Result<Option<String>, CodeGraphError> {
+    validate_input(rev, "rev")?;
+    validate_input(file, "file_path")?;
+
+    let spec = format!("{rev}:{file}");
+    Ok(run_git(repo_path, &["show", &spec]).ok())
+}
+
+/// The newest commit that removed more occurrences of `symbol_name` than
+/// it added, among the last `limit` commits that changed its count. `None`
+/// when no such commit exists, e.g. a symbol that was never referenced.
+pub fn last_reference_removal(
+    repo_path: &Path,
+    symbol_name: &str,
+    limit: usize,
+) -> Result<Option<ReferenceRemoval>, CodeGraphError> {
+    validate_input(symbol_name, "symbol_name")?;
+
+    let s_flag = format!("-S{symbol_name}");
+    let limit_str = format!("-{limit}");
+    let mut args = vec![
+        "log",
+        "--format=%H|%an|%at|%aI|%s",
+        "--patch",
+        "--unified=0",
+        "--no-color",
+        "--no-ext-diff",
+        &limit_str,
+        &s_flag,
+        "--",
+    ];
+    args.extend_from_slice(SOURCE_PATHSPECS);
+    let output = run_git(repo_path, &args)?;
+
+    Ok(parse_removals(&output, symbol_name)
+        .into_iter()
+        .find(|r| r.removed > 0))
+}
+
+/// Parse `git log --patch` output into per-commit net removal counts of
+/// `symbol` (zero for commits that added more than they removed).
+fn parse_removals(output: &str, symbol: &str) -> Vec<ReferenceRemoval> {
+    fn finish(
+        commit: Option<ReferenceRemoval>,
+        counts: &mut BTreeMap<String, (usize, usize)>,
+        out: &mut Vec<ReferenceRemoval>,
+    ) {
+        let Some(mut commit) = commit else {
+            return;
+        };
+        let (removed, added) = counts
+            .values()
+            .fold((0, 0), |(r, a), (fr, fa)| (r + fr, a + fa));
+        commit.removed = removed.saturating_sub(added);
+        commit.files = counts
+            .iter()
+            .filter(|(_, (r, a))| r > a)
+            .map(|(f, _)| f.clone())
+            .collect();
+        counts.clear();
+        out.push(commit);
+    }
+
+    let mut out = Vec::new();
+    let mut current: Option<ReferenceRemoval> = None;
+    let mut counts: BTreeMap<String, (usize, usize)> = BTreeMap::new();
+    let mut file = String::new();
+
+    for line in output.lines() {
+        if is_commit_line(line) {
+            finish(current.take(), &mut counts, &mut out);
+            let parts: Vec<&str> = line.splitn(5, '|').collect();
+            if parts.len() == 5 {
+                current = Some(ReferenceRemoval {
+                    hash: parts[0].to_string(),
+                    author: parts[1].to_string(),
+                    timestamp: parts[2].parse().unwrap_or(0),
+                    date: parts[3].to_string(),
+                    message: parts[4].to_string(),
+                    files: Vec::new(),
+                    removed: 0,
+                });
+            }
+        } else if let Some(path) = line.strip_prefix("--- a/") {
+            file = path.to_string();
+        } else if let Some(path) = line.strip_prefix("+++ b/") {
+            file = path.to_string();
+        } else if line.starts_with("--- /dev/null") || line.starts_with("+++ /dev/null") {
+            continue;
+        } else if let Some(text) = line.strip_prefix('-') {
+            counts.entry(file.clone()).or_default().0 += count_word(text, symbol);
+        } else if let Some(text) = line.strip_prefix('+') {
+            counts.entry(file.clone()).or_default().1 += count_word(text, symbol);
+        }
+    }
+    finish(current, &mut counts, &mut out);
+    out
+}
+
+/// Occurrences of `word` in `text` not embedded in a longer identifier.
+fn count_word(text: &str, word: &str) -> usize {
+    let is_ident = |c: char| c.is_alphanumeric() || c == '_' || c == '$';
+    text.match_indices(word)
+        .filter(|(i, _)| {
+            let before = text[..*i].chars().next_back();
+            let after = text[i + word.len()..].chars().next();
+            !before.is_some_and(is_ident) && !after.is_some_and(is_ident)
+        })
+        .count()
+}
+
 /// Get current branch name, tracking remote, and ahead/behind counts.
 pub fn branch_info(repo_path: &Path) -> Result<BranchInfo, CodeGraphError> {
     // Current branch name
@@ -302,6 +491,147 @@ pub fn modified_files(repo_path: &Path) -> Result<ModifiedFiles, CodeGraphError>
     })
 }
 
+/// Files under `repo_path` that differ between `rev` and the working tree,
+/// committed or not, plus untracked files that are not ignored. Paths are
+/// relative to `repo_path`, which may be a subdirectory of the repository.
+pub fn changed_files_since(repo_path: &Path, rev: &str) -> Result<ChangedFiles, CodeGraphError> {
+    validate_input(rev, "revision")?;
+    let commit = format!("{rev}^{{commit}}");
+    run_git(repo_path, &["rev-parse", "--verify", "--quiet", &commit])
+        .map_err(|_| CodeGraphError::Other(format!("Unknown revision: {rev}")))?;
+
+    let diff = run_git(
+        repo_path,
+        &[
+            "diff",
+            "--name-status",
+            "--no-renames",
+            "--no-ext-diff",
+            "--relative",
+            "-z",
+            rev,
+            "--",
+        ],
+    )?;
+    let mut files = ChangedFiles::default();
+    let mut fields = diff.split('\0').filter(|f| !f.is_empty());
+    while let (Some(status), Some(path)) = (fields.next(), fields.next()) {
+        if status.starts_with('D') {
+            files.deleted.push(path.to_string());
+        } else {
+            files.changed.push(path.to_string());
+        }
+    }
+
+    let untracked = run_git(
+        repo_path,
+        &["ls-files", "--others", "--exclude-standard", "-z"],
+    )?;
+    files.changed.extend(
+        untracked
+            .split('\0')
+            .filter(|f| !f.is_empty())
+            .map(str::to_string),
+    );
+    Ok(files)
+}
+
+/// Lines under `repo_path` that differ between `rev` (default `HEAD`) and
+/// the working tree, staged or not, per file. Untracked files that are not
+/// ignored count as changed throughout.
+pub fn changed_lines(
+    repo_path: &Path,
+    rev: Option<&str>,
+) -> Result<Vec<ChangedLines>, CodeGraphError> {
+    let rev = rev.unwrap_or("HEAD");
+    validate_input(rev, "revision")?;
+    let commit = format!("{rev}^{{commit}}");
+    run_git(repo_path, &["rev-parse", "--verify", "--quiet", &commit])
+        .map_err(|_| CodeGraphError::Other(format!("Unknown revision: {rev}")))?;
+
+    let diff = run_git(
+        repo_path,
+        &[
+            "diff",
+            "--unified=0",
+            "--no-renames",
+            "--no-color",
+            "--no-ext-diff",
+            "--no-textconv",
+            "--relative",
+            rev,
+            "--",
+        ],
+    )?;
+    let mut files = parse_changed_lines(&diff);
+
+    let untracked = run_git(
+        repo_path,
+        &["ls-files", "--others", "--exclude-standard", "-z"],
+    )?;
+    files.extend(
+        untracked
+            .split('\0')
+            .filter(|f| !f.is_empty())
+            .map(|path| ChangedLines {
+                path: path.to_string(),
+                ranges: Vec::new(),
+                whole_file: true,
+            }),
+    );
+    Ok(files)
+}
+
+/// Parse `git diff --unified=0` output into the changed line ranges of
+/// each file, on the new side of every hunk.
+fn parse_changed_lines(diff: &str) -> Vec<ChangedLines> {
+    let mut files: Vec<ChangedLines> = Vec::new();
+    let mut old_path: Option<&str> = None;
+    // `---`/`+++` are headers only before the first hunk: after it they
+    // are removed or added lines that happen to start with `--`/`++`.
+    let mut in_header = false;
+    for line in diff.lines() {
+        if line.starts_with("diff --git ") {
+            old_path = None;
+            in_header = true;
+        } else if let Some(path) = line.strip_prefix("--- ").filter(|_| in_header) {
+            old_path = path.strip_prefix("a/");
+        } else if let Some(path) = line.strip_prefix("+++ ").filter(|_| in_header) {
+            let (path, whole_file) = match path.strip_prefix("b/") {
+                Some(new) => (new, old_path.is_none()),
+                // Deleted: report it under its old path.
+                None => (old_path.unwrap_or_default(), true),
+            };
+            if !path.is_empty() {
+                files.push(ChangedLines {
+                    path: path.to_string(),
+                    ranges: Vec::new(),
+                    whole_file,
+                });
+            }
+        } else if let Some(hunk) = line.strip_prefix("@@ ") {
+            in_header = false;
+            let Some(file) = files.last_mut().filter(|f| !f.whole_file) else {
+                continue;
+            };
+            // "@@ -a[,b] +c[,d] @@ context"
+            let Some(new) = hunk.split_whitespace().find_map(|f| f.strip_prefix('+')) else {
+                continue;
+            };
+            let mut parts = new.splitn(2, ',');
+            let start: u32 = parts.next().and_then(|s| s.parse().ok()).unwrap_or(0);
+            let count: u32 = parts.next().and_then(|s| s.parse().ok()).unwrap_or(1);
+            let range = if count == 0 {
+                (start.max(1), start.max(1))
+            } else {
+                (start, start + count - 1)
+            };
+            file.ranges.push(range);
+        }
+    }
+    files
+}
+
 // ── Tests ───────────────────────────────────────────────────────────────
 
 #[cfg(test)]
@@ -467,6 +797,72 @@ mod tests {
         assert!(symbol_history(&path, "--exec=id").is_err());
     }
 
+    // ── last_reference_removal ──────────────────────────────────────────
+
+    fn commit_all(path: &Path, message: &str) {
+        for args in [&["add", "-A"][..], &["commit", "-q", "-m", message]] {
+            std::process::Command::new("git")
+                .args(args)
+                .current_dir(path)
+                .output()
+                .unwrap();
+        }
+    }
+
+    #[test]
+    fn test_last_reference_removal() {
+        let (_dir, path) = create_test_repo();
+        // Only the definition was ever added: never referenced.
+        assert_eq!(last_reference_removal(&path, "sub", 20).unwrap(), None);
+
+        std::fs::write(
+            path.join("main.rs"),
+            "fn main() {\n    let d = sub(2, 1);\n    let s = subtract(d);\n}\n",
+        )
+        .unwrap();
+        commit_all(&path, "call sub");
+        std::fs::write(
+            path.join("main.rs"),
+            "fn main() {\n    let s = subtract(1);\n}\n",
+        )
+        .unwrap();
+        commit_all(&path, "drop the sub call");
+
+        let removal = last_reference_removal(&path, "sub", 20).unwrap().unwrap();
+        assert_eq!(removal.message, "drop the sub call");
+        assert_eq!(removal.files, vec!["main.rs"]);
+        assert_eq!(removal.removed, 1);
+        assert!(removal.timestamp > 0);
+    }
+
+    #[test]
+    fn test_count_word_respects_identifier_boundaries() {
+        assert_eq!(count_word("sub(a) + subtract(b) + x.sub", "sub"), 2);
+        assert_eq!(count_word("$sub _sub sub_", "sub"), 0);
+    }
+
+    // ── commit_messages / pickaxe_commits ───────────────────────────────
+
+    #[test]
+    fn test_commit_messages_newest_first() {
+        let (_dir, path) = create_test_repo();
+        let commits = commit_messages(&path, 2).unwrap();
+        assert_eq!(commits.len(), 2);
+        assert_eq!(commits[0].message, "add sub function");
+        assert_eq!(commits[0].hash.len(), 40);
+        assert!(commits[0].timestamp > 0);
+        assert_eq!(commits[1].message, "add println and lib");
+    }
+
+    #[test]
+    fn test_pickaxe_commits_ignores_case() {
+        let (_dir, path) = create_test_repo();
+        let commits = pickaxe_commits(&path, "PRINTLN", 10).unwrap();
+        assert_eq!(commits.len(), 1);
+        assert_eq!(commits[0].message, "add println and lib");
+        assert!(pickaxe_commits(&path, "-x", 10).is_err());
+    }
+
     // ── branch_info ─────────────────────────────────────────────────────
 
     #[test]
@@ -846,6 +1242,98 @@ mod tests {
         assert!(mods.unstaged.contains(&"main.rs".to_string()));
     }
 
+    // ── changed_files_since ─────────────────────────────────────────────
+
+    #[test]
+    fn test_changed_files_since_covers_commits_worktree_and_untracked() {
+        let (_dir, path) = create_test_repo();
+        // main.rs and lib.rs both changed after the first commit.
+        let changes = changed_files_since(&path, "HEAD~2").unwrap();
+        let mut changed = changes.changed.clone();
+        changed.sort();
+        assert_eq!(changed, vec!["lib.rs", "main.rs"]);
+        assert!(changes.deleted.is_empty());
+
+        std::fs::remove_file(path.join("main.rs")).unwrap();
+        std::fs::write(path.join("new.rs"), "fn new() {}\n").unwrap();
+        let changes = changed_files_since(&path, "HEAD").unwrap();
+        assert_eq!(changes.changed, vec!["new.rs"]);
+        assert_eq!(changes.deleted, vec!["main.rs"]);
+    }
+
+    #[test]
+    fn test_parse_changed_lines_hunks_new_and_deleted_files() {
+        let diff = "\
+diff --git a/src/a.rs b/src/a.rs
+index 1..2 100644
+--- a/src/a.rs
++++ b/src/a.rs
+@@ -3 +3,2 @@ fn a() {
+-old
++new
++more
+@@ -10,2 +11,0 @@
+--- gone
+-gone
+diff --git a/src/new.rs b/src/new.rs
+new file mode 100644
+--- /dev/null
++++ b/src/new.rs
+@@ -0,0 +1 @@
++fn new() {}
+diff --git a/src/old.rs b/src/old.rs
+deleted file mode 100644
+--- a/src/old.rs
++++ /dev/null
+@@ -1 +0,0 @@
+-fn old() {}
+";
+        let files = parse_changed_lines(diff);
+        assert_eq!(
+            files,
+            vec![
+                ChangedLines {
+                    path: "src/a.rs".to_string(),
+                    ranges: vec![(3, 4), (11, 11)],
+                    whole_file: false,
+                },
+                ChangedLines {
+                    path: "src/new.rs".to_string(),
+                    ranges: vec![],
+                    whole_file: true,
+                },
+                ChangedLines {
+                    path: "src/old.rs".to_string(),
+                    ranges: vec![],
+                    whole_file: true,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_changed_lines_reads_the_working_tree() {
+        let (_dir, path) = create_test_repo();
+        let source = std::fs::read_to_string(path.join("lib.rs")).unwrap();
+        std::fs::write(path.join("lib.rs"), source.replace("a - b", "b - a")).unwrap();
+        std::fs::write(path.join("extra.rs"), "fn extra() {}\n").unwrap();
+        let mut files = changed_lines(&path, None).unwrap();
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0].path, "extra.rs");
+        assert!(files[0].whole_file);
+        assert_eq!(files[1].path, "lib.rs");
+        assert_eq!(files[1].ranges, vec![(2, 2)]);
+        assert!(changed_lines(&path, Some("nope")).is_err());
+    }
+
+    #[test]
+    fn test_changed_files_since_rejects_unknown_revision() {
+        let (_dir, path) = create_test_repo();
+        assert!(changed_files_since(&path, "no-such-branch").is_err());
+        assert!(changed_files_since(&path, "--output=x").is_err());
+    }
+
     // =====================================================================
     // parse_log_with_files edge cases
     // =====================================================================
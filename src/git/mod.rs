@@ -1,15 +1,21 @@
-//! Git integration module — blame, history, contributors, hotspots.
+//! Git integration module — blame, history, contributors, hotspots,
+//! symbol-level last-modified metadata, symbol evolution, review routing,
+//! and semantic commit search.
 //!
-//! Uses `std::process::Command` to call git CLI (no git2 dependency).
+//! Calls the git CLI (no git2 dependency) through [`crate::sandbox`], so
+//! commands are bounded by the workspace's trust policy.
 //! All functions take a `repo_path` and return `Result<T, CodeGraphError>`.
 
 pub mod analysis;
 pub mod blame;
+pub mod commit_search;
+pub mod evolution;
 pub mod history;
+pub mod node_meta;
+pub mod reviewers;
 
 use serde::Serialize;
 use std::path::Path;
-use std::process::Command;
 
 use crate::error::CodeGraphError;
 
@@ -70,6 +76,58 @@ pub struct ModifiedFiles {
     pub untracked: Vec<String>,
 }
 
+/// Files that differ between a revision and the working tree, relative to
+/// the directory the diff ran in. Renames appear as a deletion plus a change.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ChangedFiles {
+    /// Added, modified or untracked files.
+    pub changed: Vec<String>,
+    /// Files present at the revision but gone now.
+    pub deleted: Vec<String>,
+}
+
+/// Lines of one file that differ between a revision and the working tree,
+/// relative to the directory the diff ran in.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChangedLines {
+    pub path: String,
+    /// Inclusive 1-based line ranges of the working-tree file. Where lines
+    /// were only removed, the range is the line the removal follows.
+    pub ranges: Vec<(u32, u32)>,
+    /// Added, untracked or deleted: every line of the file changed.
+    pub whole_file: bool,
+}
+
+/// A commit that removed more occurrences of a symbol than it added.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReferenceRemoval {
+    pub hash: String,
+    pub author: String,
+    pub date: String,
+    /// Unix timestamp of the commit.
+    pub timestamp: i64,
+    pub message: String,
+    /// Files that lost occurrences.
+    pub files: Vec<String>,
+    /// Net occurrences removed.
+    pub removed: usize,
+}
+
+/// A commit with its full message, as embedded for commit search.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommitMessage {
+    pub hash: String,
+    pub author: String,
+    pub date: String,
+    /// Unix timestamp of the commit.
+    pub timestamp: i64,
+    /// Subject and body.
+    pub message: String,
+}
+
 /// A file that changes frequently.
 #[derive(Debug, Clone, Serialize)]
 pub struct Hotspot {
@@ -79,6 +137,20 @@ pub struct Hotspot {
     pub score: f64,
 }
 
+/// Last-modified git metadata for a single graph node, derived from blame.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NodeGitMeta {
+    pub node_id: String,
+    pub file_path: String,
+    pub commit_hash: String,
+    pub author: String,
+    pub email: String,
+    /// Unix timestamp of the newest commit touching the node's lines.
+    pub committed_at: i64,
+    pub age_days: i64,
+}
+
 /// A contributor to the repository.
 #[derive(Debug, Clone, Serialize)]
 pub struct Contributor {
@@ -108,11 +180,10 @@ pub(crate) fn validate_input(input: &str, name: &str) -> Result<(), CodeGraphErr
 
 /// Run a git command in `repo_path`, returning stdout on success.
 pub(crate) fn run_git(repo_path: &Path, args: &[&str]) -> Result<String, CodeGraphError> {
-    let output = Command::new("git")
-        .args(args)
-        .current_dir(repo_path)
-        .output()
-        .map_err(|e| CodeGraphError::Other(format!("Failed to run git: {e}")))?;
+    let output = crate::sandbox::run("git", args, repo_path).map_err(|e| match e {
+        CodeGraphError::Io(e) => CodeGraphError::Other(format!("Failed to run git: {e}")),
+        e => e,
+    })?;
 
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
@@ -127,7 +198,6 @@ pub(crate) fn run_git(repo_path: &Path, args: &[&str]) -> Result<String, CodeGra
 }
 
 /// Verify that `repo_path` is inside a git repository.
-#[allow(dead_code)]
 pub(crate) fn ensure_git_repo(repo_path: &Path) -> Result<(), CodeGraphError> {
     run_git(repo_path, &["rev-parse", "--git-dir"])?;
     Ok(())
@@ -137,5 +207,9 @@ pub(crate) fn ensure_git_repo(repo_path: &Path) -> Result<(), CodeGraphError> {
 pub use analysis::{contributors, hotspots};
 pub use blame::git_blame;
 pub use history::{
-    branch_info, commit_diff, file_history, modified_files, recent_changes, symbol_history,
+    branch_info, changed_files_since, changed_lines, commit_diff, commit_messages,
+    file_at_revision, file_history, files_history, last_reference_removal, modified_files,
+    pickaxe_commits, recent_changes, symbol_history,
 };
+pub use node_meta::compute_node_git_meta;
+pub use reviewers::suggest_reviewers;
@@ -0,0 +1,145 @@
+//! Symbol-level git metadata.
+//!
+//! Joins `git blame` output onto graph nodes so each symbol knows the newest
+//! commit that touched its line range, who authored it, and how old it is.
+//! The result is persisted in the `node_git_meta` table at index time, so MCP
+//! tools and search ranking can read it without shelling out to git.
+
+use std::path::Path;
+
+use super::{blame::git_blame, BlameLine, NodeGitMeta};
+use crate::error::CodeGraphError;
+use crate::types::CodeNode;
+
+/// Seconds in a day, for age computations.
+const SECS_PER_DAY: i64 = 86_400;
+
+/// Blame `file_path` once and derive [`NodeGitMeta`] for each of `nodes`.
+///
+/// `now` is a unix timestamp used to compute `age_days`.
+pub fn compute_node_git_meta(
+    repo_path: &Path,
+    file_path: &str,
+    nodes: &[CodeNode],
+    now: i64,
+) -> Result<Vec<NodeGitMeta>, CodeGraphError> {
+    let blame = git_blame(repo_path, file_path)?;
+    Ok(node_git_meta_from_blame(&blame, file_path, nodes, now))
+}
+
+/// Pick, for every node, the most recent blame line within its line range.
+///
+/// Nodes whose range has no blame data (e.g. the file changed since the last
+/// commit and shrank) are omitted.
+pub fn node_git_meta_from_blame(
+    blame: &[BlameLine],
+    file_path: &str,
+    nodes: &[CodeNode],
+    now: i64,
+) -> Vec<NodeGitMeta> {
+    let timestamps: Vec<i64> = blame.iter().map(|b| parse_blame_date(&b.date)).collect();
+
+    nodes
+        .iter()
+        .filter_map(|node| {
+            let start = node.start_line as usize;
+            let end = node.end_line.max(node.start_line) as usize;
+            let (line, ts) = blame
+                .iter()
+                .zip(&timestamps)
+                .filter(|(b, _)| b.line_number >= start && b.line_number <= end)
+                .max_by_key(|(_, ts)| **ts)?;
+            Some(NodeGitMeta {
+                node_id: node.id.clone(),
+                file_path: file_path.to_string(),
+                commit_hash: line.commit_hash.clone(),
+                author: line.author.clone(),
+                email: line.email.clone(),
+                committed_at: *ts,
+                age_days: age_in_days(*ts, now),
+            })
+        })
+        .collect()
+}
+
+/// Whole days between `committed_at` and `now`, never negative.
+pub fn age_in_days(committed_at: i64, now: i64) -> i64 {
+    ((now - committed_at) / SECS_PER_DAY).max(0)
+}
+
+/// Parse the `YYYY-MM-DD HH:MM:SS` (UTC) date emitted by [`git_blame`].
+fn parse_blame_date(date: &str) -> i64 {
+    chrono::NaiveDateTime::parse_from_str(date, "%Y-%m-%d %H:%M:%S")
+        .map(|dt| dt.and_utc().timestamp())
+        .unwrap_or(0)
+}
+
+// ── Tests ───────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{Language, NodeKind};
+
+    fn blame_line(line: usize, hash: &str, author: &str, date: &str) -> BlameLine {
+        BlameLine {
+            line_number: line,
+            commit_hash: hash.to_string(),
+            author: author.to_string(),
+            email: format!("{author}@example.com"),
+            date: date.to_string(),
+            content: String::new(),
+        }
+    }
+
+    fn node(id: &str, start: u32, end: u32) -> CodeNode {
+        CodeNode {
+            id: id.to_string(),
+            name: id.to_string(),
+            qualified_name: None,
+            kind: NodeKind::Function,
+            file_path: "lib.rs".to_string(),
+            start_line: start,
+            end_line: end,
+            start_column: 0,
+            end_column: 0,
+            language: Language::Rust,
+            body: None,
+            documentation: None,
+            exported: None,
+        }
+    }
+
+    #[test]
+    fn picks_newest_line_in_range() {
+        let blame = vec![
+            blame_line(1, "aaa", "alice", "2024-01-01 00:00:00"),
+            blame_line(2, "bbb", "bob", "2024-03-01 00:00:00"),
+            blame_line(3, "aaa", "alice", "2024-01-01 00:00:00"),
+            blame_line(4, "ccc", "carol", "2024-06-01 00:00:00"),
+        ];
+        let nodes = vec![node("f", 1, 3), node("g", 4, 4)];
+        let now = parse_blame_date("2024-06-11 00:00:00");
+        let meta = node_git_meta_from_blame(&blame, "lib.rs", &nodes, now);
+
+        assert_eq!(meta.len(), 2);
+        assert_eq!(meta[0].node_id, "f");
+        assert_eq!(meta[0].commit_hash, "bbb");
+        assert_eq!(meta[0].author, "bob");
+        assert_eq!(meta[1].commit_hash, "ccc");
+        assert_eq!(meta[1].age_days, 10);
+    }
+
+    #[test]
+    fn skips_nodes_without_blame() {
+        let blame = vec![blame_line(1, "aaa", "alice", "2024-01-01 00:00:00")];
+        let meta = node_git_meta_from_blame(&blame, "lib.rs", &[node("f", 10, 12)], 0);
+        assert!(meta.is_empty());
+    }
+
+    #[test]
+    fn age_is_never_negative() {
+        assert_eq!(age_in_days(100, 0), 0);
+        assert_eq!(age_in_days(0, SECS_PER_DAY * 3 + 5), 3);
+    }
+}
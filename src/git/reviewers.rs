@@ -0,0 +1,396 @@
+//! Review routing — suggests reviewers for a change set.
+//!
+//! Three signals are combined per candidate:
+//!
+//! - **Line ownership** — share of the changed files' committed lines the
+//!   candidate authored, from `git blame`.
+//! - **CODEOWNERS** — share of changed files the candidate is a declared
+//!   owner of (last matching rule wins, as on GitHub/GitLab).
+//! - **Impact** — symbols outside the change set that call into it or are
+//!   called by it, credited to their last author with a recency decay.
+//!
+//! The change author (the current `git config user.email`) is excluded.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use serde::Serialize;
+
+use super::{blame::git_blame, run_git, validate_input, BlameLine};
+use crate::error::CodeGraphError;
+
+// ---------------------------------------------------------------------------
+// Constants
+// ---------------------------------------------------------------------------
+
+/// Where CODEOWNERS files are looked up, in GitHub's precedence order.
+const CODEOWNERS_PATHS: &[&str] = &[".github/CODEOWNERS", "CODEOWNERS", "docs/CODEOWNERS"];
+
+const LINE_WEIGHT: f64 = 0.5;
+const CODEOWNERS_WEIGHT: f64 = 0.3;
+const IMPACT_WEIGHT: f64 = 0.2;
+
+/// Age (days) at which an impacted symbol's authorship counts half.
+const IMPACT_HALF_LIFE_DAYS: f64 = 30.0;
+
+/// Blame hash git uses for lines that are not committed yet.
+const UNCOMMITTED_HASH: &str = "0000000000000000000000000000000000000000";
+
+// ---------------------------------------------------------------------------
+// CODEOWNERS
+// ---------------------------------------------------------------------------
+
+/// Parsed CODEOWNERS rules.
+#[derive(Debug)]
+pub struct CodeOwners {
+    rules: Vec<(Gitignore, Vec<String>)>,
+}
+
+impl CodeOwners {
+    /// Load the first CODEOWNERS file found in the repository, if any.
+    pub fn load(repo_path: &Path) -> Option<Self> {
+        CODEOWNERS_PATHS.iter().find_map(|rel| {
+            let content = std::fs::read_to_string(repo_path.join(rel)).ok()?;
+            Some(Self::parse(repo_path, &content))
+        })
+    }
+
+    /// Parse CODEOWNERS `content`; patterns use gitignore syntax.
+    pub fn parse(repo_path: &Path, content: &str) -> Self {
+        let rules = content
+            .lines()
+            .filter_map(|line| {
+                let line = line.split('#').next()?.trim();
+                let mut parts = line.split_whitespace();
+                let pattern = parts.next()?;
+                let owners: Vec<String> = parts.map(String::from).collect();
+                let mut builder = GitignoreBuilder::new(repo_path);
+                builder.add_line(None, pattern).ok()?;
+                Some((builder.build().ok()?, owners))
+            })
+            .collect();
+        Self { rules }
+    }
+
+    /// Owners of `file_path` (repo-relative). A matching rule without
+    /// owners clears ownership.
+    pub fn owners_for(&self, file_path: &str) -> &[String] {
+        self.rules
+            .iter()
+            .rev()
+            .find(|(glob, _)| {
+                glob.matched_path_or_any_parents(file_path, false)
+                    .is_ignore()
+            })
+            .map(|(_, owners)| owners.as_slice())
+            .unwrap_or_default()
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// A symbol outside the change set connected to it in the call graph.
+#[derive(Debug, Clone)]
+pub struct ImpactedSymbol {
+    pub name: String,
+    pub file_path: String,
+    /// `"caller"` (depends on the change) or `"callee"` (used by the change).
+    pub relation: &'static str,
+    pub author: String,
+    pub email: String,
+    pub age_days: i64,
+}
+
+/// A ranked reviewer with the evidence behind the ranking.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReviewerSuggestion {
+    /// Author name, or the CODEOWNERS handle for owners unknown to blame.
+    pub reviewer: String,
+    pub email: Option<String>,
+    pub score: f64,
+    /// Fraction of the changed files' committed lines they authored.
+    pub line_share: f64,
+    /// Changed files they are a CODEOWNERS owner of.
+    pub owned_files: usize,
+    /// Impacted symbols they last modified.
+    pub impacted_symbols: Vec<String>,
+    pub rationale: Vec<String>,
+}
+
+#[derive(Default)]
+struct Candidate {
+    name: String,
+    email: Option<String>,
+    lines: usize,
+    owned_files: usize,
+    impact: f64,
+    impacted: Vec<(String, &'static str, i64)>,
+}
+
+// ---------------------------------------------------------------------------
+// Ranking
+// ---------------------------------------------------------------------------
+
+/// Blame `files`, load CODEOWNERS and rank reviewers for the change set.
+///
+/// Files that cannot be blamed (new, untracked) contribute no ownership but
+/// still count for CODEOWNERS.
+pub fn suggest_reviewers(
+    repo_path: &Path,
+    files: &[String],
+    impacted: &[ImpactedSymbol],
+    limit: usize,
+) -> Result<Vec<ReviewerSuggestion>, CodeGraphError> {
+    for f in files {
+        validate_input(f, "file_path")?;
+    }
+    let blame: HashMap<String, Vec<BlameLine>> = files
+        .iter()
+        .filter_map(|f| Some((f.clone(), git_blame(repo_path, f).ok()?)))
+        .collect();
+    let codeowners = CodeOwners::load(repo_path);
+    let author = run_git(repo_path, &["config", "user.email"])
+        .ok()
+        .map(|e| e.trim().to_string())
+        .filter(|e| !e.is_empty());
+
+    Ok(rank_reviewers(
+        files,
+        &blame,
+        codeowners.as_ref(),
+        impacted,
+        author.as_deref(),
+        limit,
+    ))
+}
+
+/// Combine the three signals into a ranked list of at most `limit`
+/// reviewers. `exclude_email` (the change author) is never suggested.
+pub fn rank_reviewers(
+    files: &[String],
+    blame: &HashMap<String, Vec<BlameLine>>,
+    codeowners: Option<&CodeOwners>,
+    impacted: &[ImpactedSymbol],
+    exclude_email: Option<&str>,
+    limit: usize,
+) -> Vec<ReviewerSuggestion> {
+    let mut candidates: HashMap<String, Candidate> = HashMap::new();
+    let mut total_lines = 0;
+    for lines in blame.values() {
+        for line in lines.iter().filter(|l| l.commit_hash != UNCOMMITTED_HASH) {
+            total_lines += 1;
+            candidate(&mut candidates, &line.author, &line.email).lines += 1;
+        }
+    }
+
+    if let Some(codeowners) = codeowners {
+        for file in files {
+            for owner in codeowners.owners_for(file) {
+                // Email owners merge with blame identities; handles stand alone.
+                let c = if owner.contains('@') && !owner.starts_with('@') {
+                    candidate(&mut candidates, owner, owner)
+                } else {
+                    candidate(&mut candidates, owner, "")
+                };
+                c.owned_files += 1;
+            }
+        }
+    }
+
+    let mut impact_total = 0.0;
+    for sym in impacted {
+        let weight = IMPACT_HALF_LIFE_DAYS / (IMPACT_HALF_LIFE_DAYS + sym.age_days as f64);
+        impact_total += weight;
+        let c = candidate(&mut candidates, &sym.author, &sym.email);
+        c.impact += weight;
+        c.impacted
+            .push((sym.name.clone(), sym.relation, sym.age_days));
+    }
+
+    let mut ranked: Vec<ReviewerSuggestion> = candidates
+        .into_values()
+        .filter(|c| match (exclude_email, &c.email) {
+            (Some(author), Some(email)) => !email.eq_ignore_ascii_case(author),
+            _ => true,
+        })
+        .map(|c| to_suggestion(c, total_lines, files.len(), impact_total))
+        .filter(|s| s.score > 0.0)
+        .collect();
+    ranked.sort_by(|a, b| {
+        b.score
+            .partial_cmp(&a.score)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| a.reviewer.cmp(&b.reviewer))
+    });
+    ranked.truncate(limit);
+    ranked
+}
+
+fn to_suggestion(
+    mut c: Candidate,
+    total_lines: usize,
+    file_count: usize,
+    impact_total: f64,
+) -> ReviewerSuggestion {
+    let line_share = ratio(c.lines as f64, total_lines as f64);
+    let owner_share = ratio(c.owned_files as f64, file_count as f64);
+    let impact_share = ratio(c.impact, impact_total);
+    let score =
+        LINE_WEIGHT * line_share + CODEOWNERS_WEIGHT * owner_share + IMPACT_WEIGHT * impact_share;
+
+    let mut rationale = Vec::new();
+    if c.lines > 0 {
+        rationale.push(format!(
+            "owns {:.0}% of affected lines ({}/{})",
+            line_share * 100.0,
+            c.lines,
+            total_lines
+        ));
+    }
+    if c.owned_files > 0 {
+        rationale.push(format!(
+            "CODEOWNERS owner of {}/{} changed files",
+            c.owned_files, file_count
+        ));
+    }
+    c.impacted.sort_by_key(|(_, _, age)| *age);
+    for (name, relation, age) in c.impacted.iter().take(3) {
+        rationale.push(format!(
+            "authored the {relation} {name} {}",
+            describe_age(*age)
+        ));
+    }
+
+    ReviewerSuggestion {
+        reviewer: c.name,
+        email: c.email,
+        score: (score * 1000.0).round() / 1000.0,
+        line_share: (line_share * 1000.0).round() / 1000.0,
+        owned_files: c.owned_files,
+        impacted_symbols: c.impacted.into_iter().map(|(name, _, _)| name).collect(),
+        rationale,
+    }
+}
+
+/// Look up or create a candidate, keyed by lowercased email and falling
+/// back to the name.
+fn candidate<'a>(
+    candidates: &'a mut HashMap<String, Candidate>,
+    name: &str,
+    email: &str,
+) -> &'a mut Candidate {
+    let key = if email.is_empty() { name } else { email };
+    let c = candidates.entry(key.to_ascii_lowercase()).or_default();
+    if c.name.is_empty() {
+        c.name = name.to_string();
+    }
+    if c.email.is_none() && !email.is_empty() {
+        c.email = Some(email.to_string());
+    }
+    c
+}
+
+fn ratio(part: f64, whole: f64) -> f64 {
+    if whole > 0.0 {
+        part / whole
+    } else {
+        0.0
+    }
+}
+
+fn describe_age(days: i64) -> String {
+    match days {
+        0 => "today".to_string(),
+        1 => "yesterday".to_string(),
+        2..=13 => format!("{days} days ago"),
+        14..=59 => format!("{} weeks ago", days / 7),
+        _ => format!("{} months ago", days / 30),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn blame_lines(author: &str, email: &str, count: usize) -> Vec<BlameLine> {
+        (1..=count)
+            .map(|n| BlameLine {
+                line_number: n,
+                commit_hash: "a".repeat(40),
+                author: author.to_string(),
+                email: email.to_string(),
+                date: "2024-01-01 00:00:00".to_string(),
+                content: String::new(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn codeowners_last_matching_rule_wins() {
+        let owners = CodeOwners::parse(
+            Path::new("/repo"),
+            "# comment\n* @org/everyone\n/src/api/ @alice bob@example.com\n*.md\n",
+        );
+        assert_eq!(
+            owners.owners_for("src/api/routes.ts"),
+            ["@alice", "bob@example.com"]
+        );
+        assert_eq!(owners.owners_for("lib/util.ts"), ["@org/everyone"]);
+        assert!(owners.owners_for("README.md").is_empty());
+    }
+
+    #[test]
+    fn ranks_by_ownership_codeowners_and_impact() {
+        let files = vec!["src/api.ts".to_string()];
+        let mut blame = HashMap::new();
+        let mut lines = blame_lines("Alice", "alice@example.com", 6);
+        lines.extend(blame_lines("Bob", "bob@example.com", 4));
+        lines.push(BlameLine {
+            commit_hash: UNCOMMITTED_HASH.to_string(),
+            ..blame_lines("Not Committed Yet", "not.committed.yet", 1).remove(0)
+        });
+        blame.insert("src/api.ts".to_string(), lines);
+        let owners = CodeOwners::parse(Path::new("/repo"), "src/ bob@example.com @org/api\n");
+        let impacted = vec![ImpactedSymbol {
+            name: "parseConfig".into(),
+            file_path: "src/config.ts".into(),
+            relation: "callee",
+            author: "Carol".into(),
+            email: "carol@example.com".into(),
+            age_days: 12,
+        }];
+
+        let ranked = rank_reviewers(&files, &blame, Some(&owners), &impacted, None, 10);
+        let names: Vec<&str> = ranked.iter().map(|r| r.reviewer.as_str()).collect();
+        assert_eq!(names, ["Bob", "@org/api", "Alice", "Carol"]);
+        assert_eq!(ranked[0].line_share, 0.4);
+        assert_eq!(ranked[0].owned_files, 1);
+        assert_eq!(
+            ranked[3].rationale,
+            ["authored the callee parseConfig 12 days ago"]
+        );
+        assert!(ranked[2].rationale[0].starts_with("owns 60% of affected lines"));
+    }
+
+    #[test]
+    fn excludes_change_author_and_applies_limit() {
+        let files = vec!["a.rs".to_string()];
+        let mut blame = HashMap::new();
+        let mut lines = blame_lines("Me", "Me@Example.com", 5);
+        lines.extend(blame_lines("Other", "other@example.com", 1));
+        lines.extend(blame_lines("Third", "third@example.com", 1));
+        blame.insert("a.rs".to_string(), lines);
+
+        let ranked = rank_reviewers(&files, &blame, None, &[], Some("me@example.com"), 1);
+        assert_eq!(ranked.len(), 1);
+        assert_eq!(ranked[0].reviewer, "Other");
+    }
+}
@@ -0,0 +1,311 @@
+//! In-memory adjacency (CSR) for fast traversal.
+//!
+//! The recursive CTEs in [`traversal`](crate::graph::traversal) issue one
+//! index probe per node per level, which is slow for deep walks on large
+//! graphs. [`Adjacency`] loads the edge list once into compressed sparse
+//! rows — both directions, with edge kinds — so walks become pointer
+//! chasing over integer indexes.
+//!
+//! Stores build it lazily when the cache is enabled (see
+//! [`GraphStore::enable_adjacency_cache`]) and rebuild it whenever the
+//! database changed since the last build.
+
+use std::collections::{HashMap, VecDeque};
+
+use rusqlite::Connection;
+
+use crate::error::Result;
+
+/// Direction of a walk over the adjacency.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    /// Follow edges source → target.
+    Outgoing,
+    /// Follow edges target → source.
+    Incoming,
+}
+
+/// Compressed sparse row adjacency over every node and edge in a graph.
+#[derive(Debug, Default)]
+pub struct Adjacency {
+    /// Node IDs in `nodes` rowid order; the position is the node index.
+    ids: Vec<String>,
+    index: HashMap<String, u32>,
+    /// Distinct edge kinds; edges store an index into this table.
+    kinds: Vec<String>,
+    out: Csr,
+    inc: Csr,
+}
+
+/// One direction of the adjacency. Neighbours of node `i` are
+/// `targets[offsets[i]..offsets[i + 1]]`, in edge insertion order.
+#[derive(Debug, Default)]
+struct Csr {
+    offsets: Vec<u32>,
+    targets: Vec<u32>,
+    kinds: Vec<u8>,
+}
+
+impl Csr {
+    fn build(
+        n: usize,
+        edges: &[(u32, u32, u8)],
+        key: impl Fn(&(u32, u32, u8)) -> (u32, u32),
+    ) -> Self {
+        let mut offsets = vec![0u32; n + 1];
+        for e in edges {
+            offsets[key(e).0 as usize + 1] += 1;
+        }
+        for i in 0..n {
+            offsets[i + 1] += offsets[i];
+        }
+        let mut cursor = offsets.clone();
+        let mut targets = vec![0u32; edges.len()];
+        let mut kinds = vec![0u8; edges.len()];
+        for e in edges {
+            let (from, to) = key(e);
+            let slot = cursor[from as usize] as usize;
+            targets[slot] = to;
+            kinds[slot] = e.2;
+            cursor[from as usize] += 1;
+        }
+        Self {
+            offsets,
+            targets,
+            kinds,
+        }
+    }
+
+    fn neighbors(&self, node: u32) -> impl Iterator<Item = (u32, u8)> + '_ {
+        let start = self.offsets[node as usize] as usize;
+        let end = self.offsets[node as usize + 1] as usize;
+        self.targets[start..end]
+            .iter()
+            .copied()
+            .zip(self.kinds[start..end].iter().copied())
+    }
+}
+
+impl Adjacency {
+    /// Load every node and edge from `conn`.
+    pub fn load(conn: &Connection) -> Result<Self> {
+        let mut ids = Vec::new();
+        let mut index = HashMap::new();
+        {
+            let mut stmt = conn.prepare("SELECT id FROM nodes")?;
+            let rows = stmt.query_map([], |row| row.get::<_, String>(0))?;
+            for id in rows {
+                let id = id?;
+                index.insert(id.clone(), ids.len() as u32);
+                ids.push(id);
+            }
+        }
+
+        let mut kinds: Vec<String> = Vec::new();
+        let mut edges: Vec<(u32, u32, u8)> = Vec::new();
+        {
+            let mut stmt = conn.prepare("SELECT source_id, target_id, type FROM edges")?;
+            let mut rows = stmt.query([])?;
+            while let Some(row) = rows.next()? {
+                let source: String = row.get(0)?;
+                let target: String = row.get(1)?;
+                let (Some(&s), Some(&t)) = (index.get(&source), index.get(&target)) else {
+                    continue;
+                };
+                let kind: String = row.get(2)?;
+                let k = match kinds.iter().position(|k| *k == kind) {
+                    Some(k) => k,
+                    None => {
+                        kinds.push(kind);
+                        kinds.len() - 1
+                    }
+                };
+                edges.push((s, t, k as u8));
+            }
+        }
+
+        let n = ids.len();
+        Ok(Self {
+            out: Csr::build(n, &edges, |e| (e.0, e.1)),
+            inc: Csr::build(n, &edges, |e| (e.1, e.0)),
+            ids,
+            index,
+            kinds,
+        })
+    }
+
+    /// Number of nodes.
+    pub fn node_count(&self) -> usize {
+        self.ids.len()
+    }
+
+    /// Number of edges.
+    pub fn edge_count(&self) -> usize {
+        self.out.targets.len()
+    }
+
+    /// Index of the node with `id`.
+    pub fn index_of(&self, id: &str) -> Option<u32> {
+        self.index.get(id).copied()
+    }
+
+    /// ID of the node at `index`.
+    pub fn id(&self, index: u32) -> &str {
+        &self.ids[index as usize]
+    }
+
+    /// All node IDs, indexed by node index.
+    pub fn ids(&self) -> &[String] {
+        &self.ids
+    }
+
+    /// Neighbours of `node` in `direction`, restricted to edges of `kind`
+    /// when given, in edge insertion order.
+    pub fn neighbors<'a>(
+        &'a self,
+        node: u32,
+        direction: Direction,
+        kind: Option<&str>,
+    ) -> impl Iterator<Item = u32> + 'a {
+        let csr = match direction {
+            Direction::Outgoing => &self.out,
+            Direction::Incoming => &self.inc,
+        };
+        // An unknown kind matches nothing.
+        let wanted = kind.map(|k| self.kinds.iter().position(|x| x == k));
+        csr.neighbors(node)
+            .filter(move |&(_, k)| match wanted {
+                None => true,
+                Some(Some(w)) => k as usize == w,
+                Some(None) => false,
+            })
+            .map(|(n, _)| n)
+    }
+
+    /// Breadth-first walk from `start` up to `max_depth` hops, returning
+    /// every other reachable node once, at its shortest distance.
+    pub fn walk(
+        &self,
+        start: &str,
+        direction: Direction,
+        kind: Option<&str>,
+        max_depth: u32,
+    ) -> Vec<(u32, u32)> {
+        let Some(start) = self.index_of(start) else {
+            return Vec::new();
+        };
+        let mut depth_of: HashMap<u32, u32> = HashMap::from([(start, 0)]);
+        let mut queue = VecDeque::from([start]);
+        let mut out = Vec::new();
+        while let Some(node) = queue.pop_front() {
+            let depth = depth_of[&node];
+            if depth >= max_depth {
+                continue;
+            }
+            for next in self.neighbors(node, direction, kind) {
+                if depth_of.contains_key(&next) {
+                    continue;
+                }
+                depth_of.insert(next, depth + 1);
+                out.push((next, depth + 1));
+                queue.push_back(next);
+            }
+        }
+        out
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::schema::initialize_database;
+    use crate::graph::store::GraphStore;
+    use crate::types::{CodeEdge, CodeNode, EdgeKind, Language, NodeKind};
+
+    fn node(id: &str) -> CodeNode {
+        CodeNode {
+            id: id.to_string(),
+            name: id.to_string(),
+            qualified_name: None,
+            kind: NodeKind::Function,
+            file_path: "a.ts".to_string(),
+            start_line: 1,
+            end_line: 2,
+            start_column: 0,
+            end_column: 0,
+            language: Language::TypeScript,
+            body: None,
+            documentation: None,
+            exported: None,
+        }
+    }
+
+    fn edge(source: &str, target: &str, kind: EdgeKind) -> CodeEdge {
+        CodeEdge {
+            source: source.to_string(),
+            target: target.to_string(),
+            kind,
+            file_path: "a.ts".to_string(),
+            line: 1,
+            metadata: None,
+        }
+    }
+
+    fn load() -> Adjacency {
+        let store = GraphStore::from_connection(initialize_database(":memory:").unwrap());
+        store
+            .upsert_nodes(&[node("a"), node("b"), node("c"), node("d")])
+            .unwrap();
+        store
+            .upsert_edges(&[
+                edge("a", "b", EdgeKind::Calls),
+                edge("b", "c", EdgeKind::Calls),
+                edge("a", "c", EdgeKind::Imports),
+                edge("c", "a", EdgeKind::Calls),
+            ])
+            .unwrap();
+        Adjacency::load(&store.conn).unwrap()
+    }
+
+    #[test]
+    fn loads_both_directions() {
+        let adj = load();
+        assert_eq!(adj.node_count(), 4);
+        assert_eq!(adj.edge_count(), 4);
+        let a = adj.index_of("a").unwrap();
+        let c = adj.index_of("c").unwrap();
+        let out: Vec<&str> = adj
+            .neighbors(a, Direction::Outgoing, None)
+            .map(|i| adj.id(i))
+            .collect();
+        assert_eq!(out, vec!["b", "c"]);
+        let calls_in: Vec<&str> = adj
+            .neighbors(c, Direction::Incoming, Some("calls"))
+            .map(|i| adj.id(i))
+            .collect();
+        assert_eq!(calls_in, vec!["b"]);
+        assert_eq!(
+            adj.neighbors(a, Direction::Outgoing, Some("nope")).count(),
+            0
+        );
+    }
+
+    #[test]
+    fn walk_reports_shortest_depth_and_stops_at_cycles() {
+        let adj = load();
+        let walked: Vec<(&str, u32)> = adj
+            .walk("a", Direction::Outgoing, Some("calls"), 10)
+            .into_iter()
+            .map(|(i, d)| (adj.id(i), d))
+            .collect();
+        assert_eq!(walked, vec![("b", 1), ("c", 2)]);
+
+        let shallow = adj.walk("a", Direction::Outgoing, None, 1);
+        assert_eq!(shallow.len(), 2);
+        assert!(adj.walk("missing", Direction::Outgoing, None, 5).is_empty());
+    }
+}
@@ -0,0 +1,694 @@
+//! Approximate nearest-neighbour index (HNSW) over node embeddings.
+//!
+//! sqlite-vec answers similarity queries with a full scan, which is fine
+//! for most repositories but slow past a few hundred thousand embeddings.
+//! With `performance.ann_index` enabled, the indexing pipeline keeps a
+//! Hierarchical Navigable Small World graph of every embedding in a sidecar
+//! file next to the database (`codegraph.db.ann`), and
+//! [`HybridSearch::search_by_similarity`](super::search::HybridSearch::search_by_similarity)
+//! walks it instead of scanning whenever the file is present.
+//!
+//! The index is updated incrementally: each run inserts the embeddings it
+//! (re)computed and drops nodes that no longer exist. Replaced and removed
+//! points stay in the graph as deleted routing points until they outnumber
+//! the live ones, when the graph is rebuilt from the live vectors.
+
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::SystemTime;
+
+use rusqlite::{params, Connection};
+
+use crate::error::{CodeGraphError, Result};
+
+/// Links per point on the upper layers; layer 0 keeps twice as many.
+const M: usize = 16;
+/// Candidate list size while inserting.
+const EF_CONSTRUCTION: usize = 100;
+/// Smallest candidate list size while searching.
+const EF_SEARCH: usize = 64;
+/// File header; the trailing byte is the format version.
+const MAGIC: &[u8; 6] = b"CGANN\x01";
+
+// ---------------------------------------------------------------------------
+// Index
+// ---------------------------------------------------------------------------
+
+/// Distance and point index, ordered by distance for the search heaps.
+#[derive(Clone, Copy, PartialEq)]
+struct Scored(f32, u32);
+
+impl Eq for Scored {}
+
+impl PartialOrd for Scored {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Scored {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.total_cmp(&other.0).then(self.1.cmp(&other.1))
+    }
+}
+
+/// An HNSW graph over unit vectors, searched by cosine distance.
+#[derive(Debug, Clone)]
+pub struct AnnIndex {
+    dim: usize,
+    ids: Vec<String>,
+    /// Normalized vectors, `dim` floats per point.
+    vectors: Vec<f32>,
+    /// Neighbours of each point, per layer (index 0 is the bottom layer).
+    links: Vec<Vec<Vec<u32>>>,
+    deleted: Vec<bool>,
+    by_id: HashMap<String, u32>,
+    entry: Option<u32>,
+}
+
+impl AnnIndex {
+    /// An empty index for `dim`-dimensional vectors.
+    pub fn new(dim: usize) -> Self {
+        Self {
+            dim,
+            ids: Vec::new(),
+            vectors: Vec::new(),
+            links: Vec::new(),
+            deleted: Vec::new(),
+            by_id: HashMap::new(),
+            entry: None,
+        }
+    }
+
+    pub fn dim(&self) -> usize {
+        self.dim
+    }
+
+    /// Number of live (not deleted) points.
+    pub fn len(&self) -> usize {
+        self.by_id.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.by_id.is_empty()
+    }
+
+    pub fn contains(&self, id: &str) -> bool {
+        self.by_id.contains_key(id)
+    }
+
+    /// Node IDs of the live points.
+    pub fn ids(&self) -> impl Iterator<Item = &str> {
+        self.by_id.keys().map(String::as_str)
+    }
+
+    fn vector(&self, p: u32) -> &[f32] {
+        let start = p as usize * self.dim;
+        &self.vectors[start..start + self.dim]
+    }
+
+    fn distance(&self, q: &[f32], p: u32) -> f32 {
+        1.0 - q
+            .iter()
+            .zip(self.vector(p))
+            .map(|(a, b)| a * b)
+            .sum::<f32>()
+    }
+
+    fn max_level(&self) -> usize {
+        self.entry.map_or(0, |e| self.links[e as usize].len() - 1)
+    }
+
+    /// Insert `vector` under `id`, replacing any earlier vector for it.
+    pub fn insert(&mut self, id: &str, vector: &[f32]) -> Result<()> {
+        if vector.len() != self.dim {
+            return Err(CodeGraphError::Other(format!(
+                "ANN index holds {}-d vectors, got {}",
+                self.dim,
+                vector.len()
+            )));
+        }
+        self.remove(id);
+        let norm = vector.iter().map(|x| x * x).sum::<f32>().sqrt();
+        let q: Vec<f32> = if norm > 0.0 {
+            vector.iter().map(|x| x / norm).collect()
+        } else {
+            vector.to_vec()
+        };
+
+        let p = self.ids.len() as u32;
+        let level = random_level(id);
+        self.ids.push(id.to_string());
+        self.vectors.extend_from_slice(&q);
+        self.links.push(vec![Vec::new(); level + 1]);
+        self.deleted.push(false);
+        self.by_id.insert(id.to_string(), p);
+
+        let Some(mut entry) = self.entry else {
+            self.entry = Some(p);
+            return Ok(());
+        };
+        let top = self.max_level();
+        for l in (level + 1..=top).rev() {
+            entry = self.search_layer(&q, entry, 1, l)[0].1;
+        }
+        for l in (0..=level.min(top)).rev() {
+            let found = self.search_layer(&q, entry, EF_CONSTRUCTION, l);
+            let cap = if l == 0 { 2 * M } else { M };
+            let neighbours: Vec<u32> = found.iter().take(cap).map(|s| s.1).collect();
+            for &n in &neighbours {
+                self.links[n as usize][l].push(p);
+                if self.links[n as usize][l].len() > cap {
+                    self.prune(n, l, cap);
+                }
+            }
+            self.links[p as usize][l] = neighbours;
+            entry = found[0].1;
+        }
+        if level > top {
+            self.entry = Some(p);
+        }
+        Ok(())
+    }
+
+    /// Keep the `cap` closest links of point `n` on layer `l`.
+    fn prune(&mut self, n: u32, l: usize, cap: usize) {
+        let base = self.vector(n).to_vec();
+        let mut scored: Vec<Scored> = self.links[n as usize][l]
+            .iter()
+            .map(|&m| Scored(self.distance(&base, m), m))
+            .collect();
+        scored.sort();
+        self.links[n as usize][l] = scored.into_iter().take(cap).map(|s| s.1).collect();
+    }
+
+    /// Mark the point for `id` deleted. It keeps routing searches but is
+    /// never returned. Returns whether `id` was present.
+    pub fn remove(&mut self, id: &str) -> bool {
+        match self.by_id.remove(id) {
+            Some(p) => {
+                self.deleted[p as usize] = true;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Whether deleted points outnumber live ones.
+    pub fn needs_compaction(&self) -> bool {
+        self.ids.len() - self.by_id.len() > self.by_id.len()
+    }
+
+    /// Rebuild the graph from the live points only.
+    pub fn compact(&mut self) {
+        let mut live: Vec<(u32, String)> =
+            self.by_id.iter().map(|(id, &p)| (p, id.clone())).collect();
+        live.sort();
+        let mut fresh = Self::new(self.dim);
+        for (p, id) in live {
+            // Vectors are stored normalized and have the right dimension.
+            let _ = fresh.insert(&id, self.vector(p));
+        }
+        *self = fresh;
+    }
+
+    /// Best-first search of layer `l` from `entry`, keeping `ef`
+    /// candidates. Returns them closest first (deleted points included).
+    fn search_layer(&self, q: &[f32], entry: u32, ef: usize, l: usize) -> Vec<Scored> {
+        let start = Scored(self.distance(q, entry), entry);
+        let mut visited = HashSet::from([entry]);
+        let mut candidates = BinaryHeap::from([Reverse(start)]);
+        let mut best = BinaryHeap::from([start]);
+        while let Some(Reverse(c)) = candidates.pop() {
+            if best.len() >= ef && best.peek().is_some_and(|w| c.0 > w.0) {
+                break;
+            }
+            for &n in self.links[c.1 as usize].get(l).into_iter().flatten() {
+                if !visited.insert(n) {
+                    continue;
+                }
+                let s = Scored(self.distance(q, n), n);
+                if best.len() < ef || best.peek().is_some_and(|w| s.0 < w.0) {
+                    candidates.push(Reverse(s));
+                    best.push(s);
+                    if best.len() > ef {
+                        best.pop();
+                    }
+                }
+            }
+        }
+        best.into_sorted_vec()
+    }
+
+    /// The `k` live points closest to `query`, as `(node_id, cosine
+    /// distance)` pairs, closest first.
+    pub fn search(&self, query: &[f32], k: usize) -> Vec<(String, f64)> {
+        let Some(mut entry) = self.entry else {
+            return Vec::new();
+        };
+        if query.len() != self.dim || k == 0 {
+            return Vec::new();
+        }
+        let norm = query.iter().map(|x| x * x).sum::<f32>().sqrt();
+        let q: Vec<f32> = if norm > 0.0 {
+            query.iter().map(|x| x / norm).collect()
+        } else {
+            query.to_vec()
+        };
+        for l in (1..=self.max_level()).rev() {
+            entry = self.search_layer(&q, entry, 1, l)[0].1;
+        }
+        // Deleted points take up candidate slots; widen the list for them.
+        let ef = (k * self.ids.len() / self.by_id.len().max(1)).max(EF_SEARCH);
+        self.search_layer(&q, entry, ef, 0)
+            .into_iter()
+            .filter(|s| !self.deleted[s.1 as usize])
+            .take(k)
+            .map(|s| (self.ids[s.1 as usize].clone(), f64::from(s.0)))
+            .collect()
+    }
+
+    // -----------------------------------------------------------------------
+    // Persistence
+    // -----------------------------------------------------------------------
+
+    /// Write the index to `path`, replacing it atomically.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let mut tmp = path.as_os_str().to_owned();
+        tmp.push(".tmp");
+        let tmp = PathBuf::from(tmp);
+        {
+            let mut w = BufWriter::new(File::create(&tmp)?);
+            w.write_all(MAGIC)?;
+            write_u32(&mut w, self.dim as u32)?;
+            write_u32(&mut w, self.ids.len() as u32)?;
+            write_u32(&mut w, self.entry.map_or(u32::MAX, |e| e))?;
+            for (p, id) in self.ids.iter().enumerate() {
+                write_u32(&mut w, id.len() as u32)?;
+                w.write_all(id.as_bytes())?;
+                w.write_all(&[u8::from(self.deleted[p])])?;
+                for x in self.vector(p as u32) {
+                    w.write_all(&x.to_le_bytes())?;
+                }
+                write_u32(&mut w, self.links[p].len() as u32)?;
+                for layer in &self.links[p] {
+                    write_u32(&mut w, layer.len() as u32)?;
+                    for &n in layer {
+                        write_u32(&mut w, n)?;
+                    }
+                }
+            }
+            w.flush()?;
+        }
+        fs::rename(&tmp, path)?;
+        Ok(())
+    }
+
+    /// Read an index written by [`save`](Self::save).
+    pub fn load(path: &Path) -> Result<Self> {
+        let corrupt =
+            || CodeGraphError::Other(format!("{} is not a CodeGraph ANN index", path.display()));
+        let mut r = BufReader::new(File::open(path)?);
+        let mut magic = [0u8; 6];
+        r.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(corrupt());
+        }
+        let dim = read_u32(&mut r)? as usize;
+        let count = read_u32(&mut r)?;
+        let entry = read_u32(&mut r)?;
+        let mut index = Self::new(dim);
+        for p in 0..count {
+            let mut id = vec![0u8; read_u32(&mut r)? as usize];
+            r.read_exact(&mut id)?;
+            let id = String::from_utf8(id).map_err(|_| corrupt())?;
+            let mut flag = [0u8; 1];
+            r.read_exact(&mut flag)?;
+            let mut buf = vec![0u8; dim * 4];
+            r.read_exact(&mut buf)?;
+            index.vectors.extend(
+                buf.chunks_exact(4)
+                    .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]])),
+            );
+            let mut layers = Vec::new();
+            for _ in 0..read_u32(&mut r)? {
+                let n = read_u32(&mut r)?;
+                let layer = (0..n)
+                    .map(|_| {
+                        read_u32(&mut r)
+                            .and_then(|m| if m < count { Ok(m) } else { Err(corrupt()) })
+                    })
+                    .collect::<Result<Vec<u32>>>()?;
+                layers.push(layer);
+            }
+            if layers.is_empty() {
+                return Err(corrupt());
+            }
+            if flag[0] == 0 {
+                index.by_id.insert(id.clone(), p);
+            }
+            index.ids.push(id);
+            index.deleted.push(flag[0] != 0);
+            index.links.push(layers);
+        }
+        index.entry = match entry {
+            u32::MAX => None,
+            e if e < count => Some(e),
+            _ => return Err(corrupt()),
+        };
+        Ok(index)
+    }
+}
+
+fn write_u32(w: &mut impl Write, v: u32) -> Result<()> {
+    w.write_all(&v.to_le_bytes())?;
+    Ok(())
+}
+
+fn read_u32(r: &mut impl Read) -> Result<u32> {
+    let mut b = [0u8; 4];
+    r.read_exact(&mut b)?;
+    Ok(u32::from_le_bytes(b))
+}
+
+/// Layer for a new point, drawn from the usual exponential distribution
+/// with a per-ID seed so rebuilding an index gives the same graph.
+fn random_level(id: &str) -> usize {
+    // FNV-1a, then a splitmix64 finalizer to spread the bits.
+    let mut h: u64 = 0xcbf2_9ce4_8422_2325;
+    for b in id.bytes() {
+        h = (h ^ u64::from(b)).wrapping_mul(0x0000_0100_0000_01b3);
+    }
+    h = (h ^ (h >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    h = (h ^ (h >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    h ^= h >> 31;
+    let u = ((h >> 11) as f64 + 1.0) / (1u64 << 53) as f64;
+    (-u.ln() / (M as f64).ln()).floor().min(16.0) as usize
+}
+
+// ---------------------------------------------------------------------------
+// Sidecar file
+// ---------------------------------------------------------------------------
+
+/// Path of the ANN sidecar for the database behind `conn`, or `None` for
+/// in-memory databases.
+pub fn sidecar_path(conn: &Connection) -> Option<PathBuf> {
+    conn.path()
+        .filter(|p| !p.is_empty())
+        .map(|p| PathBuf::from(format!("{p}.ann")))
+}
+
+/// Sidecars loaded by this process, reloaded when the file changes.
+type LoadedIndexes = Mutex<HashMap<PathBuf, (SystemTime, Arc<AnnIndex>)>>;
+
+fn loaded() -> &'static LoadedIndexes {
+    static LOADED: OnceLock<LoadedIndexes> = OnceLock::new();
+    LOADED.get_or_init(Default::default)
+}
+
+/// The ANN index for the database behind `conn`, if a readable sidecar
+/// exists. Loaded once per process and reloaded after a re-index.
+pub fn cached_index(conn: &Connection) -> Option<Arc<AnnIndex>> {
+    let path = sidecar_path(conn)?;
+    let modified = fs::metadata(&path).and_then(|m| m.modified()).ok()?;
+    let mut loaded = loaded().lock().unwrap_or_else(|e| e.into_inner());
+    if let Some((at, index)) = loaded.get(&path) {
+        if *at == modified {
+            return Some(Arc::clone(index));
+        }
+    }
+    match AnnIndex::load(&path) {
+        Ok(index) => {
+            let index = Arc::new(index);
+            loaded.insert(path, (modified, Arc::clone(&index)));
+            Some(index)
+        }
+        Err(e) => {
+            tracing::warn!("ignoring ANN index {}: {}", path.display(), e);
+            loaded.remove(&path);
+            None
+        }
+    }
+}
+
+fn decode_embedding(blob: &[u8]) -> Vec<f32> {
+    blob.chunks_exact(4)
+        .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .collect()
+}
+
+/// Bring the sidecar of the database behind `conn` up to date after an
+/// index run: insert the embeddings of `updated` nodes from
+/// `embedding_cache` and drop nodes that no longer exist.
+///
+/// `enabled` is the configured `performance.ann_index`: `Some(true)` builds
+/// a missing sidecar from every cached embedding, `Some(false)` deletes an
+/// existing one so searches never read a stale index, and `None` (callers
+/// without a config) maintains the sidecar only if it exists.
+///
+/// Returns the number of indexed vectors, or `None` when there is no
+/// sidecar to maintain.
+pub fn refresh_sidecar(
+    conn: &Connection,
+    updated: &[String],
+    enabled: Option<bool>,
+) -> Result<Option<usize>> {
+    let Some(path) = sidecar_path(conn) else {
+        return Ok(None);
+    };
+    match enabled {
+        Some(false) => {
+            if path.exists() {
+                fs::remove_file(&path)?;
+            }
+            return Ok(None);
+        }
+        None if !path.exists() => return Ok(None),
+        _ => {}
+    }
+
+    let mut stmt =
+        conn.prepare_cached("SELECT embedding FROM embedding_cache WHERE node_id = ?1")?;
+    let mut index = match path.exists().then(|| AnnIndex::load(&path)) {
+        Some(Ok(index)) => {
+            let mut index = index;
+            for id in updated {
+                let blob: Option<Vec<u8>> = stmt.query_row(params![id], |r| r.get(0)).ok();
+                match blob {
+                    Some(blob) => index.insert(id, &decode_embedding(&blob))?,
+                    None => {
+                        index.remove(id);
+                    }
+                }
+            }
+            index
+        }
+        // Missing or unreadable: rebuild from the cache.
+        _ => build_from_cache(conn)?,
+    };
+
+    let nodes: HashSet<String> = conn
+        .prepare("SELECT id FROM nodes")?
+        .query_map([], |r| r.get(0))?
+        .collect::<std::result::Result<_, _>>()?;
+    let gone: Vec<String> = index
+        .ids()
+        .filter(|id| !nodes.contains(*id))
+        .map(str::to_string)
+        .collect();
+    for id in &gone {
+        index.remove(id);
+    }
+    if index.needs_compaction() {
+        index.compact();
+    }
+    index.save(&path)?;
+    Ok(Some(index.len()))
+}
+
+/// An index of every embedding in `embedding_cache` whose node exists.
+fn build_from_cache(conn: &Connection) -> Result<AnnIndex> {
+    let mut stmt = conn.prepare(
+        "SELECT e.node_id, e.embedding FROM embedding_cache e
+         JOIN nodes n ON n.id = e.node_id ORDER BY e.node_id",
+    )?;
+    let rows = stmt.query_map([], |r| {
+        Ok((r.get::<_, String>(0)?, r.get::<_, Vec<u8>>(1)?))
+    })?;
+    let mut index: Option<AnnIndex> = None;
+    for row in rows {
+        let (id, blob) = row?;
+        let vector = decode_embedding(&blob);
+        index
+            .get_or_insert_with(|| AnnIndex::new(vector.len()))
+            .insert(&id, &vector)?;
+    }
+    Ok(index.unwrap_or_else(|| AnnIndex::new(768)))
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::schema::initialize_database;
+
+    /// Deterministic pseudo-random unit-ish vectors.
+    fn vectors(n: usize, dim: usize) -> Vec<Vec<f32>> {
+        let mut state: u64 = 42;
+        (0..n)
+            .map(|_| {
+                (0..dim)
+                    .map(|_| {
+                        state = state
+                            .wrapping_mul(6_364_136_223_846_793_005)
+                            .wrapping_add(1);
+                        ((state >> 33) as f32 / (1u64 << 31) as f32) - 0.5
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn brute_force(data: &[Vec<f32>], q: &[f32], k: usize) -> Vec<String> {
+        let unit = |v: &[f32]| {
+            let n = v.iter().map(|x| x * x).sum::<f32>().sqrt();
+            v.iter().map(|x| x / n).collect::<Vec<_>>()
+        };
+        let q = unit(q);
+        let mut scored: Vec<(f32, usize)> = data
+            .iter()
+            .enumerate()
+            .map(|(i, v)| {
+                (
+                    1.0 - unit(v).iter().zip(&q).map(|(a, b)| a * b).sum::<f32>(),
+                    i,
+                )
+            })
+            .collect();
+        scored.sort_by(|a, b| a.0.total_cmp(&b.0));
+        scored
+            .iter()
+            .take(k)
+            .map(|(_, i)| format!("n{i}"))
+            .collect()
+    }
+
+    #[test]
+    fn search_recalls_the_exact_neighbours() {
+        let data = vectors(2000, 16);
+        let mut index = AnnIndex::new(16);
+        for (i, v) in data.iter().enumerate() {
+            index.insert(&format!("n{i}"), v).unwrap();
+        }
+        let queries = vectors(20, 16);
+        let mut hits = 0;
+        for q in &queries {
+            let exact = brute_force(&data, q, 10);
+            let found: Vec<String> = index.search(q, 10).into_iter().map(|(id, _)| id).collect();
+            hits += found.iter().filter(|id| exact.contains(id)).count();
+        }
+        // HNSW is approximate, but should find nearly all of them.
+        assert!(hits >= 190, "recall {hits}/200");
+        assert!(index.insert("bad", &[1.0]).is_err());
+    }
+
+    #[test]
+    fn replaced_and_removed_points_are_not_returned() {
+        let mut index = AnnIndex::new(2);
+        index.insert("a", &[1.0, 0.0]).unwrap();
+        index.insert("b", &[0.0, 1.0]).unwrap();
+        assert_eq!(index.search(&[1.0, 0.1], 1)[0].0, "a");
+
+        // Moving `b` next to the query makes it the nearest.
+        index.insert("b", &[1.0, 0.1]).unwrap();
+        assert_eq!(index.len(), 2);
+        assert_eq!(index.search(&[1.0, 0.1], 1)[0].0, "b");
+
+        assert!(index.remove("b"));
+        assert!(!index.remove("b"));
+        let ids: Vec<String> = index
+            .search(&[1.0, 0.1], 5)
+            .into_iter()
+            .map(|(id, _)| id)
+            .collect();
+        assert_eq!(ids, vec!["a"]);
+        assert!(index.needs_compaction());
+        index.compact();
+        assert!(!index.needs_compaction());
+        assert_eq!(index.search(&[0.0, 1.0], 5).len(), 1);
+    }
+
+    #[test]
+    fn save_and_load_round_trip() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("codegraph.db.ann");
+        let mut index = AnnIndex::new(8);
+        for (i, v) in vectors(200, 8).iter().enumerate() {
+            index.insert(&format!("n{i}"), v).unwrap();
+        }
+        index.remove("n3");
+        index.save(&path).unwrap();
+
+        let loaded = AnnIndex::load(&path).unwrap();
+        assert_eq!(loaded.len(), 199);
+        assert!(!loaded.contains("n3"));
+        let q = &vectors(1, 8)[0];
+        assert_eq!(loaded.search(q, 5), index.search(q, 5));
+
+        fs::write(&path, b"not an index").unwrap();
+        assert!(AnnIndex::load(&path).is_err());
+    }
+
+    #[test]
+    fn refresh_builds_updates_and_removes_the_sidecar() {
+        let tmp = tempfile::tempdir().unwrap();
+        let db = tmp.path().join("codegraph.db");
+        let conn = initialize_database(db.to_str().unwrap()).unwrap();
+        let add = |id: &str, v: [f32; 3]| {
+            conn.execute(
+                "INSERT OR REPLACE INTO nodes (id, type, name, file_path, start_line, end_line, language)
+                 VALUES (?1, 'function', ?1, 'a.ts', 1, 2, 'typescript')",
+                params![id],
+            )
+            .unwrap();
+            let blob: Vec<u8> = v.iter().flat_map(|f| f.to_le_bytes()).collect();
+            conn.execute(
+                "INSERT OR REPLACE INTO embedding_cache (node_id, embedding) VALUES (?1, ?2)",
+                params![id, blob],
+            )
+            .unwrap();
+        };
+        add("x", [1.0, 0.0, 0.0]);
+        add("y", [0.0, 1.0, 0.0]);
+
+        let sidecar = sidecar_path(&conn).unwrap();
+        assert_eq!(refresh_sidecar(&conn, &[], None).unwrap(), None);
+        assert_eq!(refresh_sidecar(&conn, &[], Some(true)).unwrap(), Some(2));
+        assert!(sidecar.exists());
+        assert_eq!(
+            cached_index(&conn).unwrap().search(&[0.0, 1.0, 0.0], 1)[0].0,
+            "y"
+        );
+
+        add("z", [0.0, 0.0, 1.0]);
+        conn.execute("DELETE FROM nodes WHERE id = 'x'", [])
+            .unwrap();
+        assert_eq!(
+            refresh_sidecar(&conn, &["z".to_string()], None).unwrap(),
+            Some(2)
+        );
+        let index = AnnIndex::load(&sidecar).unwrap();
+        assert!(index.contains("z") && !index.contains("x"));
+
+        assert_eq!(refresh_sidecar(&conn, &[], Some(false)).unwrap(), None);
+        assert!(!sidecar.exists());
+        assert!(cached_index(&conn).is_none());
+    }
+}
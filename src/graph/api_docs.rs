@@ -0,0 +1,641 @@
+//! Public API surface export for documentation generators.
+//!
+//! Builds a structured, deterministic JSON document of every exported symbol
+//! in the graph, grouped by module (source file): signature, doc comment,
+//! stability, members of exported types, and usage examples taken from the
+//! tests that call each symbol. Static docs sites can regenerate from this
+//! file after every index run instead of maintaining a separate extraction
+//! step.
+//!
+//! Stability comes from markers in the doc comment and the body
+//! (`@deprecated`, `@Deprecated`, `Deprecated:`, `@experimental`,
+//! `@internal`) and, when sources are readable, from the attributes and
+//! decorators right above the symbol (`#[deprecated(note = "...")]`,
+//! `@deprecated("...")`, `[Obsolete("...")]`, `#[doc(hidden)]`), which the
+//! extractor does not keep in the body.
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+
+use serde::Serialize;
+
+use crate::context::budget::signature_only;
+use crate::error::Result;
+use crate::graph::store::{detect_is_test, GraphStore};
+use crate::types::{CodeNode, EdgeKind, NodeKind};
+
+// ---------------------------------------------------------------------------
+// Constants
+// ---------------------------------------------------------------------------
+
+/// Version of the emitted JSON layout; bump on breaking changes.
+pub const API_DOCS_SCHEMA_VERSION: u32 = 1;
+
+/// Examples kept per symbol unless overridden.
+pub const DEFAULT_MAX_EXAMPLES: usize = 3;
+
+/// Node batch size when reading the graph.
+const BATCH_SIZE: usize = 500;
+
+/// Maximum lines of test code quoted in one example.
+const MAX_EXAMPLE_LINES: usize = 30;
+
+/// Most attribute, decorator and comment lines read above a symbol.
+const MAX_LEADING_LINES: usize = 20;
+
+/// Markers of a deprecated symbol; text after the marker is the note.
+const DEPRECATED_MARKERS: &[&str] = &[
+    "#[deprecated",
+    "@deprecated",
+    "@Deprecated",
+    "[Obsolete",
+    "[System.Obsolete",
+    "@available(*, deprecated",
+    ".. deprecated::",
+    "Deprecated:",
+];
+
+/// Markers of an unstable or experimental symbol.
+const EXPERIMENTAL_MARKERS: &[&str] = &[
+    "#[unstable",
+    "@experimental",
+    "@Experimental",
+    "@beta",
+    "@alpha",
+    "@unstable",
+];
+
+/// Markers of a symbol exported for technical reasons only.
+const INTERNAL_MARKERS: &[&str] = &["#[doc(hidden)]", "@internal", "@hidden", "@InternalApi"];
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// Options controlling which symbols are exported.
+#[derive(Debug, Clone)]
+pub struct ApiDocsOptions {
+    /// Only include modules whose path starts with this prefix.
+    pub path_prefix: Option<String>,
+    /// Maximum usage examples collected per symbol.
+    pub max_examples: usize,
+    /// Project root to read the attributes and decorators above each
+    /// symbol from; `None` detects stability from the graph alone.
+    pub root: Option<PathBuf>,
+}
+
+impl Default for ApiDocsOptions {
+    fn default() -> Self {
+        Self {
+            path_prefix: None,
+            max_examples: DEFAULT_MAX_EXAMPLES,
+            root: None,
+        }
+    }
+}
+
+/// The full public API document.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiDocs {
+    pub schema_version: u32,
+    pub module_count: usize,
+    pub symbol_count: usize,
+    /// Top-level symbols marked deprecated.
+    pub deprecated_count: usize,
+    pub modules: Vec<ApiModule>,
+}
+
+/// Exported symbols of one source file.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiModule {
+    pub path: String,
+    pub language: String,
+    pub symbols: Vec<ApiSymbol>,
+}
+
+/// One documented symbol (or member of a documented type).
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiSymbol {
+    pub id: String,
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub qualified_name: Option<String>,
+    pub kind: String,
+    pub signature: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub docs: Option<String>,
+    pub line: u32,
+    /// Absent for stable symbols.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stability: Option<ApiStability>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub members: Vec<ApiSymbol>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub examples: Vec<ApiExample>,
+}
+
+/// How stable a symbol is, when marked as anything but stable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StabilityLevel {
+    Deprecated,
+    Experimental,
+    Internal,
+}
+
+/// A stability marker and the note that came with it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ApiStability {
+    pub level: StabilityLevel,
+    /// E.g. `use parse_v2 instead`, from the attribute or doc tag.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
+/// A test that exercises a symbol, quoted as a usage example.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiExample {
+    pub test: String,
+    pub file: String,
+    pub line: u32,
+    pub code: String,
+}
+
+// ---------------------------------------------------------------------------
+// Building
+// ---------------------------------------------------------------------------
+
+/// Collect the public API surface recorded in `store`.
+///
+/// A symbol is public when the extractor marked it exported. Test code is
+/// never part of the surface. Modules and symbols are sorted by path and
+/// line so repeated exports of an unchanged graph are byte-identical.
+pub fn build_api_docs(store: &GraphStore, options: &ApiDocsOptions) -> Result<ApiDocs> {
+    let mut by_module: BTreeMap<String, Vec<CodeNode>> = BTreeMap::new();
+    for batch in store.iter_nodes(BATCH_SIZE) {
+        for node in batch? {
+            if node.exported != Some(true) || is_test_node(&node) || !is_api_kind(node.kind) {
+                continue;
+            }
+            if let Some(prefix) = &options.path_prefix {
+                if !node.file_path.starts_with(prefix.as_str()) {
+                    continue;
+                }
+            }
+            by_module
+                .entry(node.file_path.clone())
+                .or_default()
+                .push(node);
+        }
+    }
+
+    let mut modules = Vec::with_capacity(by_module.len());
+    let mut symbol_count = 0;
+    let mut deprecated_count = 0;
+    let mut sources = SourceCache::new(options.root.clone());
+    for (path, mut nodes) in by_module {
+        nodes.sort_by_key(|n| (n.start_line, n.name.clone()));
+        let language = nodes[0].language.as_str().to_string();
+        let symbols: Vec<ApiSymbol> = nodes
+            .iter()
+            .map(|n| api_symbol(store, n, options, &mut sources, true))
+            .collect::<Result<_>>()?;
+        symbol_count += symbols.len();
+        deprecated_count += symbols
+            .iter()
+            .filter(|s| {
+                s.stability
+                    .as_ref()
+                    .is_some_and(|st| st.level == StabilityLevel::Deprecated)
+            })
+            .count();
+        modules.push(ApiModule {
+            path,
+            language,
+            symbols,
+        });
+    }
+
+    Ok(ApiDocs {
+        schema_version: API_DOCS_SCHEMA_VERSION,
+        module_count: modules.len(),
+        symbol_count,
+        deprecated_count,
+        modules,
+    })
+}
+
+fn api_symbol(
+    store: &GraphStore,
+    node: &CodeNode,
+    options: &ApiDocsOptions,
+    sources: &mut SourceCache,
+    with_members: bool,
+) -> Result<ApiSymbol> {
+    let mut members = Vec::new();
+    if with_members && is_container_kind(node.kind) {
+        let mut children: Vec<CodeNode> = store
+            .get_out_edges(&node.id, Some(EdgeKind::Contains.as_str()))?
+            .iter()
+            .filter_map(|e| store.get_node(&e.target).ok().flatten())
+            .filter(|c| is_api_kind(c.kind) && !is_private_name(&c.name))
+            .collect();
+        children.sort_by_key(|c| (c.start_line, c.name.clone()));
+        for child in &children {
+            members.push(api_symbol(store, child, options, sources, false)?);
+        }
+    }
+
+    Ok(ApiSymbol {
+        id: node.id.clone(),
+        name: node.name.clone(),
+        qualified_name: node.qualified_name.clone(),
+        kind: node.kind.as_str().to_string(),
+        signature: node
+            .body
+            .as_deref()
+            .map(signature_only)
+            .unwrap_or_else(|| node.name.clone()),
+        docs: node
+            .documentation
+            .as_deref()
+            .map(str::trim)
+            .filter(|d| !d.is_empty())
+            .map(String::from),
+        line: node.start_line,
+        stability: detect_stability(
+            node.documentation.as_deref(),
+            node.body.as_deref(),
+            &sources.leading_lines(node),
+        ),
+        members,
+        examples: examples_for(store, node, options.max_examples)?,
+    })
+}
+
+/// Source files read for the lines above symbols.
+pub(crate) struct SourceCache {
+    root: Option<PathBuf>,
+    files: HashMap<String, Option<Vec<String>>>,
+}
+
+impl SourceCache {
+    pub(crate) fn new(root: Option<PathBuf>) -> Self {
+        Self {
+            root,
+            files: HashMap::new(),
+        }
+    }
+
+    /// The lines of the project-relative `file_path`, read once; `None`
+    /// without a root, outside the sandbox, or when it cannot be read.
+    pub(crate) fn lines(&mut self, file_path: &str) -> Option<&[String]> {
+        let root = self.root.as_ref()?;
+        self.files
+            .entry(file_path.to_string())
+            .or_insert_with(|| {
+                let abs = root.join(file_path);
+                if !crate::sandbox::current().allows_file(&abs) {
+                    return None;
+                }
+                std::fs::read_to_string(abs)
+                    .ok()
+                    .map(|text| text.lines().map(str::to_string).collect())
+            })
+            .as_deref()
+    }
+
+    /// The attribute, decorator and comment lines directly above `node`,
+    /// nearest last.
+    pub(crate) fn leading_lines(&mut self, node: &CodeNode) -> Vec<String> {
+        let Some(lines) = self.lines(&node.file_path) else {
+            return Vec::new();
+        };
+        let end = (node.start_line as usize)
+            .saturating_sub(1)
+            .min(lines.len());
+        let mut start = end;
+        while start > 0 && end - start < MAX_LEADING_LINES {
+            let line = lines[start - 1].trim_start();
+            let leading = ["#[", "@", "[", "//", "/*", "*", "#"]
+                .iter()
+                .any(|p| line.starts_with(p));
+            if !leading {
+                break;
+            }
+            start -= 1;
+        }
+        lines[start..end].to_vec()
+    }
+}
+
+/// The stability marked in a symbol's docs, body or leading lines.
+/// Deprecation wins over the other levels.
+pub fn detect_stability(
+    docs: Option<&str>,
+    body: Option<&str>,
+    leading: &[String],
+) -> Option<ApiStability> {
+    // Only the head of the body: the signature and its annotations, not
+    // calls to deprecated things further down.
+    let head = body.map(signature_only).unwrap_or_default();
+    let texts: Vec<&str> = leading
+        .iter()
+        .map(String::as_str)
+        .chain(docs)
+        .chain(std::iter::once(head.as_str()))
+        .collect();
+    for text in &texts {
+        for line in text.lines() {
+            if let Some(at) = DEPRECATED_MARKERS
+                .iter()
+                .filter_map(|m| line.find(m).map(|i| i + m.len()))
+                .min()
+            {
+                return Some(ApiStability {
+                    level: StabilityLevel::Deprecated,
+                    note: deprecation_note(&line[at..]),
+                });
+            }
+        }
+    }
+    let has = |markers: &[&str]| texts.iter().any(|t| markers.iter().any(|m| t.contains(m)));
+    let level = if has(EXPERIMENTAL_MARKERS) {
+        StabilityLevel::Experimental
+    } else if has(INTERNAL_MARKERS) {
+        StabilityLevel::Internal
+    } else {
+        return None;
+    };
+    Some(ApiStability { level, note: None })
+}
+
+/// The note following a deprecation marker: a quoted string when there is
+/// one (`(since = "1.2", note = "use x")` takes the last), else the rest of
+/// the line.
+fn deprecation_note(rest: &str) -> Option<String> {
+    let quoted: Vec<&str> = rest.split('"').skip(1).step_by(2).collect();
+    let note = match quoted.last() {
+        Some(q) => q.to_string(),
+        None => rest
+            .trim_start_matches(|c: char| c.is_ascii_alphanumeric())
+            .trim_matches(|c: char| c.is_whitespace() || "():,*/]-".contains(c))
+            .to_string(),
+    };
+    (!note.is_empty()).then_some(note)
+}
+
+/// Test functions that call or reference `node`, quoted from their body.
+fn examples_for(store: &GraphStore, node: &CodeNode, max: usize) -> Result<Vec<ApiExample>> {
+    if max == 0 {
+        return Ok(Vec::new());
+    }
+    let mut edges = store.get_in_edges(&node.id, Some(EdgeKind::Calls.as_str()))?;
+    edges.extend(store.get_in_edges(&node.id, Some(EdgeKind::References.as_str()))?);
+    let mut tests: Vec<CodeNode> = edges
+        .iter()
+        .filter_map(|e| store.get_node(&e.source).ok().flatten())
+        .filter(|src| is_test_node(src) && src.body.is_some())
+        .collect();
+    tests.sort_by(|a, b| (&a.file_path, a.start_line).cmp(&(&b.file_path, b.start_line)));
+    tests.dedup_by(|a, b| a.id == b.id);
+
+    Ok(tests
+        .into_iter()
+        .take(max)
+        .map(|t| ApiExample {
+            code: t
+                .body
+                .as_deref()
+                .unwrap_or_default()
+                .lines()
+                .take(MAX_EXAMPLE_LINES)
+                .collect::<Vec<_>>()
+                .join("\n"),
+            test: t.name,
+            file: t.file_path,
+            line: t.start_line,
+        })
+        .collect())
+}
+
+fn is_test_node(node: &CodeNode) -> bool {
+    detect_is_test(
+        &node.name,
+        &node.file_path,
+        node.language.as_str(),
+        node.kind.as_str(),
+    )
+}
+
+/// Kinds that belong in API docs (skips modules, imports, parameters, etc.).
+fn is_api_kind(kind: NodeKind) -> bool {
+    matches!(
+        kind,
+        NodeKind::Function
+            | NodeKind::Method
+            | NodeKind::Class
+            | NodeKind::Struct
+            | NodeKind::Interface
+            | NodeKind::Trait
+            | NodeKind::Enum
+            | NodeKind::TypeAlias
+            | NodeKind::Constant
+            | NodeKind::Variable
+            | NodeKind::Property
+    )
+}
+
+fn is_container_kind(kind: NodeKind) -> bool {
+    matches!(
+        kind,
+        NodeKind::Class | NodeKind::Struct | NodeKind::Interface | NodeKind::Trait | NodeKind::Enum
+    )
+}
+
+/// Conventionally private member names (`_x`, `#x`, `__x`).
+fn is_private_name(name: &str) -> bool {
+    name.starts_with('_') || name.starts_with('#')
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::schema::initialize_database;
+    use crate::types::{CodeEdge, Language};
+
+    fn setup() -> GraphStore {
+        let conn = initialize_database(":memory:").unwrap();
+        GraphStore::from_connection(conn)
+    }
+
+    fn node(
+        id: &str,
+        name: &str,
+        file: &str,
+        kind: NodeKind,
+        line: u32,
+        exported: bool,
+    ) -> CodeNode {
+        CodeNode {
+            id: id.to_string(),
+            name: name.to_string(),
+            qualified_name: None,
+            kind,
+            file_path: file.to_string(),
+            start_line: line,
+            end_line: line + 3,
+            start_column: 0,
+            end_column: 0,
+            language: Language::TypeScript,
+            body: Some(format!(
+                "function {name}(a: number): number {{ return a; }}"
+            )),
+            documentation: None,
+            exported: Some(exported),
+        }
+    }
+
+    fn edge(source: &str, target: &str, kind: EdgeKind, file: &str) -> CodeEdge {
+        CodeEdge {
+            source: source.to_string(),
+            target: target.to_string(),
+            kind,
+            file_path: file.to_string(),
+            line: 1,
+            metadata: None,
+        }
+    }
+
+    #[test]
+    fn exports_only_public_non_test_symbols() {
+        let store = setup();
+        let mut add = node("f1", "add", "src/math.ts", NodeKind::Function, 3, true);
+        add.documentation = Some("Adds numbers.".to_string());
+        store
+            .upsert_nodes(&[
+                add,
+                node("f2", "helper", "src/math.ts", NodeKind::Function, 10, false),
+                node(
+                    "t1",
+                    "testAdd",
+                    "src/math.test.ts",
+                    NodeKind::Function,
+                    1,
+                    true,
+                ),
+            ])
+            .unwrap();
+        store
+            .upsert_edge(&edge("t1", "f1", EdgeKind::Calls, "src/math.test.ts"))
+            .unwrap();
+
+        let docs = build_api_docs(&store, &ApiDocsOptions::default()).unwrap();
+        assert_eq!(docs.schema_version, API_DOCS_SCHEMA_VERSION);
+        assert_eq!(docs.module_count, 1);
+        assert_eq!(docs.symbol_count, 1);
+        let sym = &docs.modules[0].symbols[0];
+        assert_eq!(sym.name, "add");
+        assert_eq!(sym.signature, "function add(a: number): number");
+        assert_eq!(sym.docs.as_deref(), Some("Adds numbers."));
+        assert_eq!(sym.examples.len(), 1);
+        assert_eq!(sym.examples[0].test, "testAdd");
+    }
+
+    #[test]
+    fn type_members_are_nested_and_private_members_skipped() {
+        let store = setup();
+        store
+            .upsert_nodes(&[
+                node("c1", "Calc", "src/calc.ts", NodeKind::Class, 1, true),
+                node("m1", "sum", "src/calc.ts", NodeKind::Method, 2, false),
+                node("m2", "_cache", "src/calc.ts", NodeKind::Method, 3, false),
+            ])
+            .unwrap();
+        store
+            .upsert_edges(&[
+                edge("c1", "m1", EdgeKind::Contains, "src/calc.ts"),
+                edge("c1", "m2", EdgeKind::Contains, "src/calc.ts"),
+            ])
+            .unwrap();
+
+        let docs = build_api_docs(&store, &ApiDocsOptions::default()).unwrap();
+        let class = &docs.modules[0].symbols[0];
+        assert_eq!(class.members.len(), 1);
+        assert_eq!(class.members[0].name, "sum");
+    }
+
+    #[test]
+    fn stability_comes_from_docs_body_and_attributes_above() {
+        assert_eq!(
+            detect_stability(Some("Parses.\n@deprecated Use parseV2 instead."), None, &[]),
+            Some(ApiStability {
+                level: StabilityLevel::Deprecated,
+                note: Some("Use parseV2 instead.".to_string()),
+            })
+        );
+        let java = "@Deprecated\npublic void run() { legacy(); }";
+        assert_eq!(
+            detect_stability(None, Some(java), &[]).map(|s| s.level),
+            Some(StabilityLevel::Deprecated)
+        );
+        assert_eq!(
+            detect_stability(Some("@experimental"), None, &[]).map(|s| s.level),
+            Some(StabilityLevel::Experimental)
+        );
+        assert_eq!(detect_stability(Some("Adds numbers."), None, &[]), None);
+
+        let tmp = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(tmp.path().join("src")).unwrap();
+        std::fs::write(
+            tmp.path().join("src/lib.rs"),
+            "fn other() {}\n\n/// Old parser.\n#[deprecated(since = \"1.2\", note = \"use parse_v2\")]\npub fn parse() {}\n",
+        )
+        .unwrap();
+        let store = setup();
+        let mut parse = node("p", "parse", "src/lib.rs", NodeKind::Function, 5, true);
+        parse.body = Some("pub fn parse() {}".to_string());
+        store.upsert_node(&parse).unwrap();
+        let docs = build_api_docs(
+            &store,
+            &ApiDocsOptions {
+                root: Some(tmp.path().to_path_buf()),
+                ..ApiDocsOptions::default()
+            },
+        )
+        .unwrap();
+        let stability = docs.modules[0].symbols[0].stability.clone().unwrap();
+        assert_eq!(stability.level, StabilityLevel::Deprecated);
+        assert_eq!(stability.note.as_deref(), Some("use parse_v2"));
+        assert_eq!(docs.deprecated_count, 1);
+    }
+
+    #[test]
+    fn path_prefix_filters_modules() {
+        let store = setup();
+        store
+            .upsert_nodes(&[
+                node("a", "a", "pkg/a/lib.ts", NodeKind::Function, 1, true),
+                node("b", "b", "pkg/b/lib.ts", NodeKind::Function, 1, true),
+            ])
+            .unwrap();
+        let docs = build_api_docs(
+            &store,
+            &ApiDocsOptions {
+                path_prefix: Some("pkg/b/".to_string()),
+                ..ApiDocsOptions::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(docs.module_count, 1);
+        assert_eq!(docs.modules[0].path, "pkg/b/lib.ts");
+    }
+}
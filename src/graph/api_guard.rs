@@ -0,0 +1,428 @@
+//! Breaking-change guard for the public API.
+//!
+//! Compares the symbols of changed files, parsed from the working tree,
+//! with the indexed version of the same files. A symbol used from other
+//! files whose signature changed, or which disappeared, is a breaking
+//! change unless the change set also carries a version bump note (a
+//! changelog edit by default).
+//!
+//! Each finding takes the mode of the most specific configured directory:
+//! `off` ignores it, `warn` reports it and `block` fails the check, so
+//! `codegraph api-guard` exits non-zero and stops the pre-commit hook or
+//! CI job running it.
+
+use std::collections::{BTreeSet, HashSet};
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::context::budget::signature_only;
+use crate::error::Result;
+use crate::graph::store::GraphStore;
+use crate::indexer::fallback::{self, ParserChains};
+use crate::indexer::CodeParser;
+use crate::types::{CodeNode, EdgeKind, NodeKind};
+
+/// Files whose change counts as a version bump note by default.
+pub const DEFAULT_NOTE_FILES: &[&str] = &["CHANGELOG.md", "CHANGES.md", "HISTORY.md"];
+
+/// Files larger than this are not parsed (matches the indexer's limit).
+const MAX_FILE_SIZE: u64 = 2 * 1024 * 1024;
+
+// ---------------------------------------------------------------------------
+// Policy
+// ---------------------------------------------------------------------------
+
+/// What to do about a breaking change in a directory.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GuardMode {
+    /// Not checked.
+    Off,
+    /// Reported, but the check passes.
+    #[default]
+    Warn,
+    /// Reported, and the check fails without a version bump note.
+    Block,
+}
+
+impl GuardMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Off => "off",
+            Self::Warn => "warn",
+            Self::Block => "block",
+        }
+    }
+
+    pub fn from_str_loose(s: &str) -> Option<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "off" | "none" | "false" => Some(Self::Off),
+            "warn" | "warning" => Some(Self::Warn),
+            "block" | "error" | "deny" => Some(Self::Block),
+            _ => None,
+        }
+    }
+}
+
+/// Resolved guard settings.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GuardPolicy {
+    /// Mode outside every configured directory.
+    pub mode: GuardMode,
+    /// Modes per directory prefix; the longest matching prefix wins.
+    pub directories: Vec<(String, GuardMode)>,
+    /// Files whose change counts as a version bump note. Bare names match
+    /// in any directory, paths only themselves.
+    pub note_files: Vec<String>,
+}
+
+impl Default for GuardPolicy {
+    fn default() -> Self {
+        Self {
+            mode: GuardMode::default(),
+            directories: Vec::new(),
+            note_files: DEFAULT_NOTE_FILES.iter().map(|f| f.to_string()).collect(),
+        }
+    }
+}
+
+impl GuardPolicy {
+    /// Mode for a project-relative file path.
+    pub fn mode_for(&self, file_path: &str) -> GuardMode {
+        self.directories
+            .iter()
+            .filter(|(dir, _)| in_directory(file_path, dir))
+            .max_by_key(|(dir, _)| dir.trim_end_matches('/').len())
+            .map_or(self.mode, |(_, mode)| *mode)
+    }
+
+    /// Whether changing `file_path` records a version bump.
+    pub fn is_note_file(&self, file_path: &str) -> bool {
+        let name = file_path.rsplit('/').next().unwrap_or(file_path);
+        self.note_files.iter().any(|note| {
+            if note.contains('/') {
+                note == file_path
+            } else {
+                note.eq_ignore_ascii_case(name)
+            }
+        })
+    }
+}
+
+/// Whether `file_path` is `dir` or inside it; an empty `dir` holds every path.
+pub(crate) fn in_directory(file_path: &str, dir: &str) -> bool {
+    let dir = dir.trim_end_matches('/');
+    dir.is_empty()
+        || file_path == dir
+        || file_path
+            .strip_prefix(dir)
+            .is_some_and(|rest| rest.starts_with('/'))
+}
+
+// ---------------------------------------------------------------------------
+// Report
+// ---------------------------------------------------------------------------
+
+/// How a used symbol changed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BreakKind {
+    SignatureChanged,
+    Removed,
+}
+
+/// A change to a symbol that other files depend on.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiBreak {
+    pub name: String,
+    pub kind: NodeKind,
+    pub file_path: String,
+    /// Line of the indexed definition.
+    pub line: u32,
+    pub change: BreakKind,
+    pub old_signature: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new_signature: Option<String>,
+    /// Distinct symbols in other files that use this one.
+    pub external_callers: usize,
+    pub caller_files: Vec<String>,
+    pub mode: GuardMode,
+}
+
+/// Outcome of a guard run.
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiGuardReport {
+    /// Changed files that had indexed symbols to compare.
+    pub files_checked: usize,
+    pub breaks: Vec<ApiBreak>,
+    /// Whether the change set edits a version bump note file.
+    pub version_note: bool,
+    /// Whether a `block` directory has breaks and no note covers them.
+    pub blocked: bool,
+}
+
+// ---------------------------------------------------------------------------
+// Check
+// ---------------------------------------------------------------------------
+
+/// Compare the changed and deleted files (project-relative) against the
+/// index. Files that cannot be read or parsed are skipped rather than
+/// reported as removing every symbol.
+pub fn check_api_changes(
+    store: &GraphStore,
+    root: &Path,
+    changed: &[String],
+    deleted: &[String],
+    policy: &GuardPolicy,
+) -> Result<ApiGuardReport> {
+    let mut report = ApiGuardReport {
+        version_note: changed
+            .iter()
+            .chain(deleted)
+            .any(|f| policy.is_note_file(f)),
+        ..Default::default()
+    };
+    let deleted_set: HashSet<&String> = deleted.iter().collect();
+
+    let mut files: Vec<&String> = changed.iter().chain(deleted).collect();
+    files.sort();
+    files.dedup();
+
+    for file_path in files {
+        let mode = policy.mode_for(file_path);
+        if mode == GuardMode::Off {
+            continue;
+        }
+        let indexed: Vec<CodeNode> = store
+            .get_nodes_by_file(file_path)?
+            .into_iter()
+            .filter(|n| is_api_kind(n.kind))
+            .collect();
+        if indexed.is_empty() {
+            continue;
+        }
+        let current = if deleted_set.contains(file_path) {
+            Vec::new()
+        } else {
+            match parse_current(root, file_path) {
+                Some(nodes) => nodes,
+                None => continue,
+            }
+        };
+        report.files_checked += 1;
+
+        for old in &indexed {
+            let matches: Vec<&CodeNode> = current.iter().filter(|n| same_symbol(n, old)).collect();
+            let old_signature = signature(old);
+            let (change, new_signature) = if matches.is_empty() {
+                (BreakKind::Removed, None)
+            } else if !has_call_signature(old.kind)
+                || old_signature.is_empty()
+                || matches.iter().any(|n| signature(n) == old_signature)
+            {
+                continue;
+            } else {
+                (BreakKind::SignatureChanged, Some(signature(matches[0])))
+            };
+
+            let (external_callers, caller_files) = external_callers(store, old)?;
+            if external_callers == 0 {
+                continue;
+            }
+            report.breaks.push(ApiBreak {
+                name: old
+                    .qualified_name
+                    .clone()
+                    .unwrap_or_else(|| old.name.clone()),
+                kind: old.kind,
+                file_path: old.file_path.clone(),
+                line: old.start_line,
+                change,
+                old_signature,
+                new_signature,
+                external_callers,
+                caller_files,
+                mode,
+            });
+        }
+    }
+
+    report.blocked =
+        !report.version_note && report.breaks.iter().any(|b| b.mode == GuardMode::Block);
+    Ok(report)
+}
+
+/// Symbols of the file as it is on disk now.
+fn parse_current(root: &Path, file_path: &str) -> Option<Vec<CodeNode>> {
+    let language = CodeParser::detect_language(file_path)?;
+    let abs_path = root.join(file_path);
+    if fs::metadata(&abs_path).ok()?.len() > MAX_FILE_SIZE {
+        return None;
+    }
+    let source = fs::read_to_string(&abs_path).ok()?;
+    let extraction =
+        fallback::extract_nodes(&ParserChains::default(), file_path, language, &source)?;
+    Some(extraction.nodes)
+}
+
+/// Distinct users of `node` outside its own file, and their files.
+fn external_callers(store: &GraphStore, node: &CodeNode) -> Result<(usize, Vec<String>)> {
+    let mut sources = HashSet::new();
+    let mut files = BTreeSet::new();
+    for edge in store.get_in_edges(&node.id, None)? {
+        if edge.kind == EdgeKind::Contains || edge.file_path == node.file_path {
+            continue;
+        }
+        files.insert(edge.file_path);
+        sources.insert(edge.source);
+    }
+    Ok((sources.len(), files.into_iter().collect()))
+}
+
+fn same_symbol(current: &CodeNode, indexed: &CodeNode) -> bool {
+    current.kind == indexed.kind
+        && match (&current.qualified_name, &indexed.qualified_name) {
+            (Some(a), Some(b)) => a == b,
+            _ => current.name == indexed.name,
+        }
+}
+
+fn signature(node: &CodeNode) -> String {
+    node.body.as_deref().map(signature_only).unwrap_or_default()
+}
+
+fn is_api_kind(kind: NodeKind) -> bool {
+    !matches!(kind, NodeKind::Module | NodeKind::Namespace)
+}
+
+/// Kinds whose header is a contract callers compile against. Other kinds
+/// only break by disappearing.
+fn has_call_signature(kind: NodeKind) -> bool {
+    matches!(kind, NodeKind::Function | NodeKind::Method)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::schema::initialize_database;
+    use crate::types::{make_node_id, CodeEdge, Language};
+
+    fn function(name: &str, file: &str, body: &str) -> CodeNode {
+        CodeNode {
+            id: make_node_id(NodeKind::Function, file, name, 1),
+            name: name.to_string(),
+            qualified_name: None,
+            kind: NodeKind::Function,
+            file_path: file.to_string(),
+            start_line: 1,
+            end_line: 1,
+            start_column: 0,
+            end_column: 0,
+            language: Language::Rust,
+            body: Some(body.to_string()),
+            documentation: None,
+            exported: None,
+        }
+    }
+
+    fn call(source: &CodeNode, target: &CodeNode) -> CodeEdge {
+        CodeEdge {
+            source: source.id.clone(),
+            target: target.id.clone(),
+            kind: EdgeKind::Calls,
+            file_path: source.file_path.clone(),
+            line: 1,
+            metadata: None,
+        }
+    }
+
+    /// Index `src/api.rs` with `add` (called from `src/main.rs`) and
+    /// `local` (called only from its own file), then rewrite it on disk.
+    fn setup(new_source: &str) -> (tempfile::TempDir, GraphStore) {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("src")).unwrap();
+        fs::write(dir.path().join("src/api.rs"), new_source).unwrap();
+
+        let store = GraphStore::from_connection(initialize_database(":memory:").unwrap());
+        let add = function(
+            "add",
+            "src/api.rs",
+            "pub fn add(a: i32, b: i32) -> i32 { a + b }",
+        );
+        let local = function("local", "src/api.rs", "fn local() {}");
+        let helper = function("helper", "src/api.rs", "fn helper() { local() }");
+        let main = function("main", "src/main.rs", "fn main() { add(1, 2); }");
+        for node in [&add, &local, &helper, &main] {
+            store.upsert_node(node).unwrap();
+        }
+        store.upsert_edge(&call(&main, &add)).unwrap();
+        store.upsert_edge(&call(&helper, &local)).unwrap();
+        (dir, store)
+    }
+
+    fn check(
+        dir: &Path,
+        store: &GraphStore,
+        changed: &[&str],
+        policy: &GuardPolicy,
+    ) -> ApiGuardReport {
+        let changed: Vec<String> = changed.iter().map(|s| s.to_string()).collect();
+        check_api_changes(store, dir, &changed, &[], policy).unwrap()
+    }
+
+    #[test]
+    fn changed_signature_with_external_caller_is_reported() {
+        let (dir, store) = setup(
+            "pub fn add(a: i64, b: i64) -> i64 { a + b }\nfn local(x: u8) {}\nfn helper() { local(1) }\n",
+        );
+        let report = check(dir.path(), &store, &["src/api.rs"], &GuardPolicy::default());
+
+        assert_eq!(report.files_checked, 1);
+        assert_eq!(report.breaks.len(), 1, "{:?}", report.breaks);
+        let brk = &report.breaks[0];
+        assert_eq!(brk.name, "add");
+        assert_eq!(brk.change, BreakKind::SignatureChanged);
+        assert_eq!(brk.old_signature, "pub fn add(a: i32, b: i32) -> i32");
+        assert_eq!(
+            brk.new_signature.as_deref(),
+            Some("pub fn add(a: i64, b: i64) -> i64")
+        );
+        assert_eq!(brk.caller_files, vec!["src/main.rs"]);
+        assert_eq!(brk.mode, GuardMode::Warn);
+        assert!(!report.blocked);
+    }
+
+    #[test]
+    fn unchanged_signature_passes() {
+        let (dir, store) = setup("pub fn add(a: i32, b: i32) -> i32 {\n    b + a\n}\n");
+        // `local` and `helper` disappeared, but nothing outside uses them.
+        let report = check(dir.path(), &store, &["src/api.rs"], &GuardPolicy::default());
+        assert!(report.breaks.is_empty(), "{:?}", report.breaks);
+    }
+
+    #[test]
+    fn block_directories_fail_without_a_version_note() {
+        let (dir, store) = setup("fn helper() {}\n");
+        let policy = GuardPolicy {
+            directories: vec![
+                ("src".to_string(), GuardMode::Block),
+                ("src/generated/".to_string(), GuardMode::Off),
+            ],
+            ..Default::default()
+        };
+        let report = check(dir.path(), &store, &["src/api.rs"], &policy);
+        assert_eq!(report.breaks[0].change, BreakKind::Removed);
+        assert!(report.blocked);
+
+        let report = check(dir.path(), &store, &["src/api.rs", "CHANGELOG.md"], &policy);
+        assert!(report.version_note);
+        assert!(!report.blocked);
+
+        assert_eq!(policy.mode_for("src/generated/api.rs"), GuardMode::Off);
+        assert_eq!(policy.mode_for("srcx/api.rs"), GuardMode::Warn);
+    }
+}
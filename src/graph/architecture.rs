@@ -0,0 +1,361 @@
+//! Architecture conformance: layer rules checked against the graph.
+//!
+//! Layers are named sets of directories; a file belongs to the layer of
+//! its most specific directory. Rules say which layers a layer may not
+//! depend on (`deny`), or the only ones it may (`allow`). Every import and
+//! call edge crossing from one layer into another is checked, and each
+//! edge a rule forbids is a violation with the file and line of the
+//! import or call.
+//!
+//! `codegraph arch-check` exits non-zero on violations, so it can gate a
+//! pre-commit hook or CI job.
+
+use serde::{Deserialize, Serialize};
+
+use crate::db::converters::parse_edge_properties;
+use crate::error::Result;
+use crate::graph::api_guard::in_directory;
+use crate::graph::store::GraphStore;
+use crate::types::EdgeKind;
+
+/// Edge kinds checked when the config names none.
+pub const DEFAULT_EDGE_KINDS: &[EdgeKind] = &[EdgeKind::Imports, EdgeKind::Calls];
+
+// ---------------------------------------------------------------------------
+// Policy
+// ---------------------------------------------------------------------------
+
+/// What a layer may depend on.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LayerRule {
+    /// The layer the rule constrains.
+    pub from: String,
+    /// Layers it may not depend on.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub deny: Vec<String>,
+    /// If set, the only other layers it may depend on.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub allow: Option<Vec<String>>,
+    /// Shown with each violation.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+impl LayerRule {
+    /// Whether the rule forbids a dependency on layer `to`.
+    pub fn forbids(&self, to: &str) -> bool {
+        to != self.from
+            && (self.deny.iter().any(|d| d == to)
+                || self
+                    .allow
+                    .as_ref()
+                    .is_some_and(|allow| !allow.iter().any(|a| a == to)))
+    }
+}
+
+/// Resolved layer settings.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArchitecturePolicy {
+    /// Directories per layer name.
+    pub layers: Vec<(String, Vec<String>)>,
+    pub rules: Vec<LayerRule>,
+    pub edge_kinds: Vec<EdgeKind>,
+}
+
+impl Default for ArchitecturePolicy {
+    fn default() -> Self {
+        Self {
+            layers: Vec::new(),
+            rules: Vec::new(),
+            edge_kinds: DEFAULT_EDGE_KINDS.to_vec(),
+        }
+    }
+}
+
+impl ArchitecturePolicy {
+    /// Layer of a project-relative file path: the one with the longest
+    /// matching directory. Files outside every layer have none.
+    pub fn layer_of(&self, file_path: &str) -> Option<&str> {
+        self.layers
+            .iter()
+            .flat_map(|(name, dirs)| dirs.iter().map(move |dir| (name, dir)))
+            .filter(|(_, dir)| in_directory(file_path, dir))
+            .max_by_key(|(_, dir)| dir.trim_end_matches('/').len())
+            .map(|(name, _)| name.as_str())
+    }
+
+    /// The first rule forbidding `from` to depend on `to`.
+    pub fn violated_rule(&self, from: &str, to: &str) -> Option<&LayerRule> {
+        self.rules
+            .iter()
+            .find(|rule| rule.from == from && rule.forbids(to))
+    }
+
+    /// Rules naming a layer that is not defined.
+    pub fn unknown_layers(&self) -> Vec<String> {
+        let mut unknown: Vec<String> = self
+            .rules
+            .iter()
+            .flat_map(|r| {
+                std::iter::once(&r.from)
+                    .chain(&r.deny)
+                    .chain(r.allow.iter().flatten())
+            })
+            .filter(|name| !self.layers.iter().any(|(layer, _)| layer == *name))
+            .cloned()
+            .collect();
+        unknown.sort();
+        unknown.dedup();
+        unknown
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Report
+// ---------------------------------------------------------------------------
+
+/// An edge from one layer into another that a rule forbids.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LayerViolation {
+    pub from_layer: String,
+    pub to_layer: String,
+    pub edge_kind: EdgeKind,
+    pub source_id: String,
+    pub source_name: String,
+    pub target_id: String,
+    pub target_name: String,
+    /// Where the import or call is.
+    pub file_path: String,
+    pub line: u32,
+    pub target_file: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+/// Outcome of an architecture check.
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchitectureReport {
+    /// Edges between two different layers.
+    pub edges_checked: usize,
+    /// Ordered by file and line.
+    pub violations: Vec<LayerViolation>,
+    /// Layer names used in rules but never defined.
+    pub unknown_layers: Vec<String>,
+}
+
+// ---------------------------------------------------------------------------
+// Check
+// ---------------------------------------------------------------------------
+
+const LAYER_EDGES_SQL: &str = "\
+SELECT e.source_id, e.target_id, e.type, e.properties,
+       s.name, s.file_path, t.name, t.file_path
+FROM edges e
+JOIN nodes s ON s.id = e.source_id
+JOIN nodes t ON t.id = e.target_id
+WHERE s.file_path != t.file_path";
+
+/// Check every cross-layer edge of the configured kinds against the rules.
+pub fn check_architecture(
+    store: &GraphStore,
+    policy: &ArchitecturePolicy,
+) -> Result<ArchitectureReport> {
+    let mut report = ArchitectureReport {
+        unknown_layers: policy.unknown_layers(),
+        ..Default::default()
+    };
+    if policy.rules.is_empty() {
+        return Ok(report);
+    }
+
+    let mut stmt = store.conn.prepare_cached(LAYER_EDGES_SQL)?;
+    let mut rows = stmt.query([])?;
+    while let Some(row) = rows.next()? {
+        let kind_str: String = row.get(2)?;
+        let Some(kind) = EdgeKind::from_str_loose(&kind_str) else {
+            continue;
+        };
+        if !policy.edge_kinds.contains(&kind) {
+            continue;
+        }
+        let source_file: String = row.get(5)?;
+        let target_file: String = row.get(7)?;
+        let (Some(from), Some(to)) = (policy.layer_of(&source_file), policy.layer_of(&target_file))
+        else {
+            continue;
+        };
+        if from == to {
+            continue;
+        }
+        report.edges_checked += 1;
+        let Some(rule) = policy.violated_rule(from, to) else {
+            continue;
+        };
+        let properties: Option<String> = row.get(3)?;
+        let (edge_file, line, _) = parse_edge_properties(properties.as_deref());
+        report.violations.push(LayerViolation {
+            from_layer: from.to_string(),
+            to_layer: to.to_string(),
+            edge_kind: kind,
+            source_id: row.get(0)?,
+            source_name: row.get(4)?,
+            target_id: row.get(1)?,
+            target_name: row.get(6)?,
+            file_path: if edge_file.is_empty() {
+                source_file
+            } else {
+                edge_file
+            },
+            line,
+            target_file,
+            reason: rule.reason.clone(),
+        });
+    }
+
+    report.violations.sort_by(|a, b| {
+        (&a.file_path, a.line, &a.target_id).cmp(&(&b.file_path, b.line, &b.target_id))
+    });
+    Ok(report)
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::schema::initialize_database;
+    use crate::types::{CodeEdge, CodeNode, Language, NodeKind};
+
+    fn function(id: &str, file: &str) -> CodeNode {
+        CodeNode {
+            id: id.to_string(),
+            name: id.to_string(),
+            qualified_name: None,
+            kind: NodeKind::Function,
+            file_path: file.to_string(),
+            start_line: 1,
+            end_line: 9,
+            start_column: 0,
+            end_column: 0,
+            language: Language::TypeScript,
+            body: None,
+            documentation: None,
+            exported: None,
+        }
+    }
+
+    fn edge(source: &str, target: &str, kind: EdgeKind, file: &str, line: u32) -> CodeEdge {
+        CodeEdge {
+            source: source.to_string(),
+            target: target.to_string(),
+            kind,
+            file_path: file.to_string(),
+            line,
+            metadata: None,
+        }
+    }
+
+    fn policy() -> ArchitecturePolicy {
+        ArchitecturePolicy {
+            layers: vec![
+                ("ui".to_string(), vec!["src/ui".to_string()]),
+                ("service".to_string(), vec!["src/services".to_string()]),
+                ("db".to_string(), vec!["src/db".to_string()]),
+                ("legacy".to_string(), vec!["src/ui/legacy".to_string()]),
+            ],
+            rules: vec![
+                LayerRule {
+                    from: "ui".to_string(),
+                    deny: vec!["db".to_string()],
+                    reason: Some("UI goes through services".to_string()),
+                    ..Default::default()
+                },
+                LayerRule {
+                    from: "db".to_string(),
+                    allow: Some(Vec::new()),
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn layers_match_the_most_specific_directory() {
+        let policy = policy();
+        assert_eq!(policy.layer_of("src/ui/button.tsx"), Some("ui"));
+        assert_eq!(policy.layer_of("src/ui/legacy/form.tsx"), Some("legacy"));
+        assert_eq!(policy.layer_of("src/uikit/x.ts"), None);
+        assert!(policy.violated_rule("ui", "db").is_some());
+        assert!(policy.violated_rule("ui", "service").is_none());
+        assert!(policy.violated_rule("db", "service").is_some());
+        assert!(policy.unknown_layers().is_empty());
+    }
+
+    #[test]
+    fn reports_forbidden_edges_with_their_location() {
+        let store = GraphStore::from_connection(initialize_database(":memory:").unwrap());
+        store
+            .upsert_nodes(&[
+                function("render", "src/ui/page.ts"),
+                function("oldForm", "src/ui/legacy/form.ts"),
+                function("loadUser", "src/services/user.ts"),
+                function("query", "src/db/conn.ts"),
+            ])
+            .unwrap();
+        store
+            .upsert_edges(&[
+                edge("render", "loadUser", EdgeKind::Calls, "src/ui/page.ts", 4),
+                edge("render", "query", EdgeKind::Calls, "src/ui/page.ts", 7),
+                edge(
+                    "loadUser",
+                    "query",
+                    EdgeKind::Calls,
+                    "src/services/user.ts",
+                    3,
+                ),
+                edge("query", "loadUser", EdgeKind::Calls, "src/db/conn.ts", 2),
+                // The legacy layer has no rules.
+                edge(
+                    "oldForm",
+                    "query",
+                    EdgeKind::Calls,
+                    "src/ui/legacy/form.ts",
+                    5,
+                ),
+                // References are not checked by default.
+                edge("render", "query", EdgeKind::References, "src/ui/page.ts", 8),
+            ])
+            .unwrap();
+
+        let report = check_architecture(&store, &policy()).unwrap();
+        assert_eq!(report.edges_checked, 5);
+        let found: Vec<(&str, u32, &str, &str)> = report
+            .violations
+            .iter()
+            .map(|v| {
+                (
+                    v.file_path.as_str(),
+                    v.line,
+                    v.from_layer.as_str(),
+                    v.to_layer.as_str(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("src/db/conn.ts", 2, "db", "service"),
+                ("src/ui/page.ts", 7, "ui", "db"),
+            ]
+        );
+        assert_eq!(
+            report.violations[1].reason.as_deref(),
+            Some("UI goes through services")
+        );
+    }
+}
@@ -0,0 +1,265 @@
+//! Storage backend abstraction.
+//!
+//! [`GraphBackend`] is the backend-neutral subset of [`GraphStore`]: node and
+//! edge CRUD plus the lookups traversal-style consumers need. The SQLite
+//! [`GraphStore`] remains the primary store (search, embeddings, snapshots
+//! and the other side tables are SQLite-only); other backends, such as the
+//! feature-gated Postgres store, hold the core graph so a team can share one
+//! central index between CI and developer machines.
+//!
+//! [`copy_graph`] mirrors one backend into another and is how indexes are
+//! published to and pulled from a shared backend.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use crate::error::Result;
+use crate::graph::store::{GraphStats, GraphStore, STREAM_BATCH_SIZE};
+use crate::types::{CodeEdge, CodeNode};
+
+// ---------------------------------------------------------------------------
+// Trait
+// ---------------------------------------------------------------------------
+
+/// Core graph storage operations, implemented by every backend.
+pub trait GraphBackend {
+    /// Batch-insert or update nodes.
+    fn upsert_nodes(&self, nodes: &[CodeNode]) -> Result<()>;
+
+    /// Batch-insert or update edges, keyed by `(source, target, kind)`.
+    fn upsert_edges(&self, edges: &[CodeEdge]) -> Result<()>;
+
+    /// Atomically replace every node (and edge touching them) of a file.
+    fn replace_file_data(
+        &self,
+        file_path: &str,
+        nodes: &[CodeNode],
+        edges: &[CodeEdge],
+    ) -> Result<()>;
+
+    /// Delete every node of a file and the edges touching them.
+    fn delete_file_nodes(&self, file_path: &str) -> Result<()>;
+
+    fn get_node(&self, id: &str) -> Result<Option<CodeNode>>;
+
+    fn get_nodes_by_file(&self, file_path: &str) -> Result<Vec<CodeNode>>;
+
+    fn get_nodes_by_name(&self, name: &str) -> Result<Vec<CodeNode>>;
+
+    fn get_out_edges(&self, node_id: &str, edge_type: Option<&str>) -> Result<Vec<CodeEdge>>;
+
+    fn get_in_edges(&self, node_id: &str, edge_type: Option<&str>) -> Result<Vec<CodeEdge>>;
+
+    fn get_all_nodes(&self) -> Result<Vec<CodeNode>>;
+
+    fn get_all_edges(&self) -> Result<Vec<CodeEdge>>;
+
+    /// Call `f` with every node. Backends that can stream hold only one
+    /// batch in memory; the default loads them all.
+    fn for_each_node(&self, f: &mut dyn FnMut(CodeNode) -> Result<()>) -> Result<()> {
+        self.get_all_nodes()?.into_iter().try_for_each(f)
+    }
+
+    /// Call `f` with every edge; see [`for_each_node`](Self::for_each_node).
+    fn for_each_edge(&self, f: &mut dyn FnMut(CodeEdge) -> Result<()>) -> Result<()> {
+        self.get_all_edges()?.into_iter().try_for_each(f)
+    }
+
+    fn get_stats(&self) -> Result<GraphStats>;
+}
+
+impl GraphBackend for GraphStore {
+    fn upsert_nodes(&self, nodes: &[CodeNode]) -> Result<()> {
+        GraphStore::upsert_nodes(self, nodes)
+    }
+
+    fn upsert_edges(&self, edges: &[CodeEdge]) -> Result<()> {
+        GraphStore::upsert_edges(self, edges)
+    }
+
+    fn replace_file_data(
+        &self,
+        file_path: &str,
+        nodes: &[CodeNode],
+        edges: &[CodeEdge],
+    ) -> Result<()> {
+        GraphStore::replace_file_data(self, file_path, nodes, edges)
+    }
+
+    fn delete_file_nodes(&self, file_path: &str) -> Result<()> {
+        GraphStore::delete_file_nodes(self, file_path)
+    }
+
+    fn get_node(&self, id: &str) -> Result<Option<CodeNode>> {
+        GraphStore::get_node(self, id)
+    }
+
+    fn get_nodes_by_file(&self, file_path: &str) -> Result<Vec<CodeNode>> {
+        GraphStore::get_nodes_by_file(self, file_path)
+    }
+
+    fn get_nodes_by_name(&self, name: &str) -> Result<Vec<CodeNode>> {
+        GraphStore::get_nodes_by_name(self, name)
+    }
+
+    fn get_out_edges(&self, node_id: &str, edge_type: Option<&str>) -> Result<Vec<CodeEdge>> {
+        GraphStore::get_out_edges(self, node_id, edge_type)
+    }
+
+    fn get_in_edges(&self, node_id: &str, edge_type: Option<&str>) -> Result<Vec<CodeEdge>> {
+        GraphStore::get_in_edges(self, node_id, edge_type)
+    }
+
+    fn get_all_nodes(&self) -> Result<Vec<CodeNode>> {
+        GraphStore::get_all_nodes(self)
+    }
+
+    fn get_all_edges(&self) -> Result<Vec<CodeEdge>> {
+        GraphStore::get_all_edges(self)
+    }
+
+    fn for_each_node(&self, f: &mut dyn FnMut(CodeNode) -> Result<()>) -> Result<()> {
+        for batch in self.iter_nodes(STREAM_BATCH_SIZE) {
+            batch?.into_iter().try_for_each(&mut *f)?;
+        }
+        Ok(())
+    }
+
+    fn for_each_edge(&self, f: &mut dyn FnMut(CodeEdge) -> Result<()>) -> Result<()> {
+        for batch in self.iter_edges(STREAM_BATCH_SIZE) {
+            batch?.into_iter().try_for_each(&mut *f)?;
+        }
+        Ok(())
+    }
+
+    fn get_stats(&self) -> Result<GraphStats> {
+        GraphStore::get_stats(self)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Mirroring
+// ---------------------------------------------------------------------------
+
+/// Make `to` hold exactly the graph in `from`.
+///
+/// Every file present in `from` is replaced in `to`, files only `to` has are
+/// deleted, and edges are written last so cross-file edges survive the
+/// per-file replacement. Returns the statistics of `to` afterwards.
+pub fn copy_graph(from: &dyn GraphBackend, to: &dyn GraphBackend) -> Result<GraphStats> {
+    let mut by_file: BTreeMap<String, Vec<CodeNode>> = BTreeMap::new();
+    for node in from.get_all_nodes()? {
+        by_file
+            .entry(node.file_path.clone())
+            .or_default()
+            .push(node);
+    }
+
+    let stale: HashSet<String> = to
+        .get_all_nodes()?
+        .into_iter()
+        .map(|n| n.file_path)
+        .filter(|f| !by_file.contains_key(f))
+        .collect();
+    for file in &stale {
+        to.delete_file_nodes(file)?;
+    }
+    for (file, nodes) in &by_file {
+        to.replace_file_data(file, nodes, &[])?;
+    }
+
+    // Deduplicate on the upsert key so backends that reject repeated keys
+    // within one statement batch behave the same as SQLite.
+    let mut edges: HashMap<(String, String, &'static str), CodeEdge> = HashMap::new();
+    for edge in from.get_all_edges()? {
+        edges.insert(
+            (edge.source.clone(), edge.target.clone(), edge.kind.as_str()),
+            edge,
+        );
+    }
+    let edges: Vec<CodeEdge> = edges.into_values().collect();
+    to.upsert_edges(&edges)?;
+
+    to.get_stats()
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::schema::initialize_database;
+    use crate::types::{EdgeKind, Language, NodeKind};
+
+    fn store() -> GraphStore {
+        GraphStore::from_connection(initialize_database(":memory:").unwrap())
+    }
+
+    fn node(id: &str, file: &str) -> CodeNode {
+        CodeNode {
+            id: id.to_string(),
+            name: id.to_string(),
+            qualified_name: None,
+            kind: NodeKind::Function,
+            file_path: file.to_string(),
+            start_line: 1,
+            end_line: 3,
+            start_column: 0,
+            end_column: 0,
+            language: Language::TypeScript,
+            body: Some(format!("function {id}() {{}}")),
+            documentation: None,
+            exported: Some(true),
+        }
+    }
+
+    fn edge(source: &str, target: &str, file: &str) -> CodeEdge {
+        CodeEdge {
+            source: source.to_string(),
+            target: target.to_string(),
+            kind: EdgeKind::Calls,
+            file_path: file.to_string(),
+            line: 2,
+            metadata: None,
+        }
+    }
+
+    #[test]
+    fn copy_graph_mirrors_source_and_keeps_cross_file_edges() {
+        let from = store();
+        from.upsert_nodes(&[node("a", "src/a.ts"), node("b", "src/b.ts")])
+            .unwrap();
+        from.upsert_edges(&[edge("b", "a", "src/b.ts"), edge("a", "b", "src/a.ts")])
+            .unwrap();
+
+        let to = store();
+        to.upsert_nodes(&[node("old", "src/old.ts"), node("a", "src/a.ts")])
+            .unwrap();
+        to.upsert_edge(&edge("old", "a", "src/old.ts")).unwrap();
+
+        let stats = copy_graph(&from, &to).unwrap();
+        assert_eq!(stats.nodes, 2);
+        assert_eq!(stats.edges, 2);
+        assert_eq!(stats.files, 2);
+        assert!(GraphStore::get_node(&to, "old").unwrap().is_none());
+        let a = GraphStore::get_node(&to, "a").unwrap().unwrap();
+        assert_eq!(a.body.as_deref(), Some("function a() {}"));
+        assert_eq!(
+            GraphStore::get_in_edges(&to, "a", Some("calls"))
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn sqlite_store_is_usable_as_trait_object() {
+        let s = store();
+        let backend: &dyn GraphBackend = &s;
+        backend.upsert_nodes(&[node("a", "src/a.ts")]).unwrap();
+        assert_eq!(backend.get_nodes_by_file("src/a.ts").unwrap().len(), 1);
+        backend.delete_file_nodes("src/a.ts").unwrap();
+        assert_eq!(backend.get_stats().unwrap().nodes, 0);
+    }
+}
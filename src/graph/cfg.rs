@@ -0,0 +1,1098 @@
+//! Per-function control-flow graphs built from tree-sitter syntax trees.
+//!
+//! [`function_cfg`] parses a file, finds the function spanning the given
+//! lines and splits its body into basic blocks: straight-line statements,
+//! branch conditions, loop headers, switch/match heads and exception
+//! handlers. Edges record how control moves between them — branch outcomes,
+//! loop back edges, `break`/`continue`, `return`, `throw` and fallthrough
+//! between `case` arms.
+//!
+//! Control structures are recognised by tree-sitter node kind and field
+//! names shared across the C-family, Python, Rust and Go grammars; other
+//! syntax is kept as plain statements. The graph is statement-level:
+//! short-circuit operators and ternaries do not split blocks, and nested
+//! functions and closures stay single statements of the enclosing block.
+
+use serde::Serialize;
+
+use crate::error::Result;
+use crate::indexer::parser::CodeParser;
+use crate::types::Language;
+
+/// Longest statement or condition text kept on a block.
+const MAX_TEXT: usize = 80;
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// What a basic block is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BlockKind {
+    Entry,
+    Exit,
+    /// Straight-line statements.
+    Basic,
+    /// An `if`/`elif` condition.
+    Condition,
+    /// A loop header, tested on every iteration.
+    Loop,
+    /// A `switch`/`match` head dispatching to its arms.
+    Switch,
+    /// A `catch`/`except` handler.
+    Handler,
+}
+
+impl BlockKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Entry => "entry",
+            Self::Exit => "exit",
+            Self::Basic => "basic",
+            Self::Condition => "condition",
+            Self::Loop => "loop",
+            Self::Switch => "switch",
+            Self::Handler => "handler",
+        }
+    }
+}
+
+/// How control moves along an edge.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CfgEdgeKind {
+    /// Falls through to the next block.
+    Next,
+    True,
+    False,
+    /// Loop body back to its header.
+    Back,
+    /// Switch head to one of its arms.
+    Case,
+    Break,
+    Continue,
+    Return,
+    Throw,
+    /// A `try` body to one of its handlers.
+    Exception,
+}
+
+impl CfgEdgeKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Next => "next",
+            Self::True => "true",
+            Self::False => "false",
+            Self::Back => "back",
+            Self::Case => "case",
+            Self::Break => "break",
+            Self::Continue => "continue",
+            Self::Return => "return",
+            Self::Throw => "throw",
+            Self::Exception => "exception",
+        }
+    }
+}
+
+/// A maximal run of statements entered only at the top.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BasicBlock {
+    pub id: usize,
+    pub kind: BlockKind,
+    /// 1-based lines covered; `None` for blocks without statements.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_line: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_line: Option<u32>,
+    /// First line of each statement, or the tested condition.
+    pub statements: Vec<String>,
+}
+
+/// A control transfer between two blocks.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CfgEdge {
+    pub from: usize,
+    pub to: usize,
+    pub kind: CfgEdgeKind,
+    /// The arm label of `case` edges.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+}
+
+/// The control-flow graph of one function.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ControlFlowGraph {
+    pub blocks: Vec<BasicBlock>,
+    pub edges: Vec<CfgEdge>,
+    pub entry: usize,
+    pub exit: usize,
+}
+
+impl ControlFlowGraph {
+    /// Blocks control can never reach from the entry, e.g. code after a
+    /// `return`.
+    pub fn unreachable(&self) -> Vec<usize> {
+        let reached = self.reachable();
+        (0..self.blocks.len()).filter(|b| !reached[*b]).collect()
+    }
+
+    /// McCabe cyclomatic complexity, `E - N + 2` over the reachable part of
+    /// the graph.
+    pub fn cyclomatic(&self) -> u32 {
+        let reached = self.reachable();
+        let nodes = reached.iter().filter(|r| **r).count() as i64;
+        let edges = self.edges.iter().filter(|e| reached[e.from]).count() as i64;
+        (edges - nodes + 2).max(1) as u32
+    }
+
+    /// The block holding `line`: the one with the narrowest line range,
+    /// preferring statements over a condition or loop header on the same
+    /// line.
+    pub fn block_at(&self, line: u32) -> Option<usize> {
+        self.blocks
+            .iter()
+            .filter_map(|b| Some((b, b.start_line?, b.end_line?)))
+            .filter(|(_, start, end)| (*start..=*end).contains(&line))
+            .min_by_key(|(b, start, end)| (end - start, b.kind != BlockKind::Basic))
+            .map(|(b, _, _)| b.id)
+    }
+
+    /// Whether each block can be reached from the entry, indexed by block.
+    pub fn reachable(&self) -> Vec<bool> {
+        let mut reached = vec![false; self.blocks.len()];
+        let mut stack = vec![self.entry];
+        while let Some(b) = stack.pop() {
+            if std::mem::replace(&mut reached[b], true) {
+                continue;
+            }
+            stack.extend(self.edges.iter().filter(|e| e.from == b).map(|e| e.to));
+        }
+        reached
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Public API
+// ---------------------------------------------------------------------------
+
+/// Build the control-flow graph of the function in `source` that starts on
+/// or after `start_line` and ends by `end_line` (1-based, as stored on graph
+/// nodes). `Ok(None)` when no function with a body spans those lines.
+pub fn function_cfg(
+    source: &str,
+    language: Language,
+    start_line: u32,
+    end_line: u32,
+) -> Result<Option<ControlFlowGraph>> {
+    let tree = CodeParser::new().parse(source, language)?;
+    let first = start_line.saturating_sub(1) as usize;
+    let last = end_line.saturating_sub(1) as usize;
+    Ok(find_function(tree.root_node(), first, last).and_then(|f| build_cfg(f, source)))
+}
+
+/// Build the control-flow graph of the innermost function in `source` whose
+/// body contains `line`, along with the function's first and last lines.
+/// `Ok(None)` when `line` is outside every function body.
+pub fn enclosing_cfg(
+    source: &str,
+    language: Language,
+    line: u32,
+) -> Result<Option<(ControlFlowGraph, u32, u32)>> {
+    let tree = CodeParser::new().parse(source, language)?;
+    let row = line.saturating_sub(1) as usize;
+    Ok(innermost_function(tree.root_node(), row).and_then(|f| {
+        let graph = build_cfg(f, source)?;
+        Some((
+            graph,
+            f.start_position().row as u32 + 1,
+            f.end_position().row as u32 + 1,
+        ))
+    }))
+}
+
+/// Control-flow graphs of every function with a body in `source`, nested
+/// ones included, each with the function's first and last lines.
+pub fn function_cfgs(
+    source: &str,
+    language: Language,
+) -> Result<Vec<(ControlFlowGraph, u32, u32)>> {
+    let tree = CodeParser::new().parse(source, language)?;
+    let mut functions = Vec::new();
+    collect_functions(tree.root_node(), &mut functions);
+    Ok(functions
+        .into_iter()
+        .filter_map(|f| {
+            let graph = build_cfg(f, source)?;
+            Some((
+                graph,
+                f.start_position().row as u32 + 1,
+                f.end_position().row as u32 + 1,
+            ))
+        })
+        .collect())
+}
+
+/// Build the control-flow graph of `function`, a function-like node of a
+/// tree parsed from `source`. `None` when it has no body.
+pub fn build_cfg(function: tree_sitter::Node, source: &str) -> Option<ControlFlowGraph> {
+    let body = function_body(function)?;
+    let mut builder = Builder {
+        source: source.as_bytes(),
+        blocks: Vec::new(),
+        edges: Vec::new(),
+        exit: 0,
+        breaks: Vec::new(),
+        continues: Vec::new(),
+        handlers: Vec::new(),
+        next_arm: None,
+    };
+    let entry = builder.block(BlockKind::Entry, None);
+    builder.exit = builder.block(BlockKind::Exit, None);
+    let first = builder.block(BlockKind::Basic, None);
+    builder.edge(entry, first, CfgEdgeKind::Next);
+    if let Some(end) = builder.statement(body, Some(first)) {
+        builder.edge(end, builder.exit, CfgEdgeKind::Next);
+    }
+    Some(builder.finish(entry))
+}
+
+// ---------------------------------------------------------------------------
+// Syntax helpers
+// ---------------------------------------------------------------------------
+
+fn is_function_kind(kind: &str) -> bool {
+    kind.contains("function")
+        || kind.contains("method")
+        || kind.contains("constructor")
+        || kind.contains("lambda")
+        || kind == "closure_expression"
+        || kind == "func_literal"
+}
+
+/// The outermost function-like node with a body inside rows `first..=last`.
+fn find_function(node: tree_sitter::Node, first: usize, last: usize) -> Option<tree_sitter::Node> {
+    let (start, end) = (node.start_position().row, node.end_position().row);
+    if end < first || start > last {
+        return None;
+    }
+    if start >= first
+        && end <= last
+        && is_function_kind(node.kind())
+        && function_body(node).is_some()
+    {
+        return Some(node);
+    }
+    let mut cursor = node.walk();
+    let children: Vec<_> = node.named_children(&mut cursor).collect();
+    children
+        .into_iter()
+        .find_map(|c| find_function(c, first, last))
+}
+
+fn collect_functions<'t>(node: tree_sitter::Node<'t>, out: &mut Vec<tree_sitter::Node<'t>>) {
+    if is_function_kind(node.kind()) && function_body(node).is_some() {
+        out.push(node);
+    }
+    let mut cursor = node.walk();
+    for child in node.named_children(&mut cursor) {
+        collect_functions(child, out);
+    }
+}
+
+/// The innermost function-like node whose body spans `row`.
+fn innermost_function(node: tree_sitter::Node, row: usize) -> Option<tree_sitter::Node> {
+    if node.start_position().row > row || node.end_position().row < row {
+        return None;
+    }
+    let mut cursor = node.walk();
+    let children: Vec<_> = node.named_children(&mut cursor).collect();
+    children
+        .into_iter()
+        .find_map(|c| innermost_function(c, row))
+        .or_else(|| {
+            let body = function_body(node).filter(|_| is_function_kind(node.kind()))?;
+            (body.start_position().row <= row && row <= body.end_position().row).then_some(node)
+        })
+}
+
+fn function_body(function: tree_sitter::Node) -> Option<tree_sitter::Node> {
+    function.child_by_field_name("body").or_else(|| {
+        let mut cursor = function.walk();
+        let children: Vec<_> = function.named_children(&mut cursor).collect();
+        children
+            .into_iter()
+            .rev()
+            .find(|c| c.kind().contains("block") || c.kind().contains("body"))
+    })
+}
+
+fn is_container(kind: &str) -> bool {
+    matches!(
+        kind,
+        "block" | "statement_block" | "compound_statement" | "statement_list" | "function_body"
+    )
+}
+
+fn is_if(kind: &str) -> bool {
+    matches!(kind, "if_statement" | "if_expression")
+}
+
+fn is_loop(kind: &str) -> bool {
+    matches!(
+        kind,
+        "for_statement"
+            | "for_in_statement"
+            | "for_expression"
+            | "enhanced_for_statement"
+            | "foreach_statement"
+            | "for_range_loop"
+            | "while_statement"
+            | "while_expression"
+            | "loop_expression"
+            | "do_statement"
+    )
+}
+
+fn is_switch(kind: &str) -> bool {
+    matches!(
+        kind,
+        "switch_statement"
+            | "switch_expression"
+            | "expression_switch_statement"
+            | "type_switch_statement"
+            | "match_expression"
+            | "match_statement"
+    )
+}
+
+fn is_arm(kind: &str) -> bool {
+    matches!(
+        kind,
+        "switch_case"
+            | "switch_default"
+            | "case_statement"
+            | "switch_block_statement_group"
+            | "switch_rule"
+            | "switch_section"
+            | "expression_case"
+            | "type_case"
+            | "default_case"
+            | "match_arm"
+            | "case_clause"
+    )
+}
+
+/// Arms that run into the next one unless they `break`.
+fn falls_through(kind: &str) -> bool {
+    matches!(
+        kind,
+        "switch_case" | "switch_default" | "case_statement" | "switch_block_statement_group"
+    )
+}
+
+fn is_handler(kind: &str) -> bool {
+    matches!(
+        kind,
+        "catch_clause" | "except_clause" | "except_group_clause" | "rescue"
+    )
+}
+
+fn is_control(kind: &str) -> bool {
+    is_if(kind)
+        || is_loop(kind)
+        || is_switch(kind)
+        || matches!(
+            kind,
+            "try_statement"
+                | "return_expression"
+                | "break_expression"
+                | "continue_expression"
+                | "block"
+        )
+}
+
+/// Named children, skipping comments.
+fn statements_of<'t>(node: tree_sitter::Node<'t>) -> Vec<tree_sitter::Node<'t>> {
+    let mut cursor = node.walk();
+    node.named_children(&mut cursor)
+        .filter(|c| !c.kind().contains("comment"))
+        .collect()
+}
+
+/// The statements an arm runs, without its label or pattern.
+fn arm_statements<'t>(arm: tree_sitter::Node<'t>) -> Vec<tree_sitter::Node<'t>> {
+    let mut walk = arm.walk();
+    let bodies: Vec<_> = arm.children_by_field_name("body", &mut walk).collect();
+    if !bodies.is_empty() {
+        return bodies;
+    }
+    for field in ["consequence", "value"] {
+        if arm.kind() != "match_arm" && field == "value" {
+            continue;
+        }
+        if let Some(body) = arm.child_by_field_name(field) {
+            return vec![body];
+        }
+    }
+    let mut walk = arm.walk();
+    let mut statements = Vec::new();
+    if walk.goto_first_child() {
+        loop {
+            let child = walk.node();
+            let labelled = matches!(walk.field_name(), Some("value" | "pattern" | "label"));
+            if child.is_named()
+                && !labelled
+                && !child.kind().contains("comment")
+                && !matches!(child.kind(), "switch_label" | "case_pattern")
+            {
+                statements.push(child);
+            }
+            if !walk.goto_next_sibling() {
+                break;
+            }
+        }
+    }
+    statements
+}
+
+fn truncate(text: &str) -> String {
+    let line = text.lines().next().unwrap_or("").trim();
+    match line.char_indices().nth(MAX_TEXT) {
+        Some((cut, _)) => format!("{}...", &line[..cut]),
+        None => line.to_string(),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Builder
+// ---------------------------------------------------------------------------
+
+/// The block control is in, `None` right after a jump.
+type Cursor = Option<usize>;
+
+struct Builder<'a> {
+    source: &'a [u8],
+    blocks: Vec<BasicBlock>,
+    edges: Vec<CfgEdge>,
+    exit: usize,
+    /// Targets of `break`, innermost last.
+    breaks: Vec<usize>,
+    /// Targets of `continue`, innermost last.
+    continues: Vec<usize>,
+    /// Handler blocks of the enclosing `try` bodies, innermost last.
+    handlers: Vec<Vec<usize>>,
+    /// The arm a Go `fallthrough` continues into.
+    next_arm: Option<usize>,
+}
+
+impl Builder<'_> {
+    fn text(&self, node: tree_sitter::Node) -> &str {
+        node.utf8_text(self.source).unwrap_or("")
+    }
+
+    /// Source from the start of `node` up to `before`, e.g. a loop header
+    /// without its body.
+    fn head(&self, node: tree_sitter::Node, before: Option<tree_sitter::Node>) -> String {
+        let end = before.map_or(node.end_byte(), |b| b.start_byte());
+        let text = std::str::from_utf8(&self.source[node.start_byte()..end]).unwrap_or("");
+        truncate(
+            text.trim_end()
+                .trim_end_matches([':', '{', '=', '>'])
+                .trim_end(),
+        )
+    }
+
+    /// A tested condition, without the parentheses C-family grammars keep.
+    fn condition(&self, node: tree_sitter::Node) -> String {
+        let text = self.text(node);
+        let text = match node.kind() {
+            "parenthesized_expression" => text
+                .strip_prefix('(')
+                .and_then(|t| t.strip_suffix(')'))
+                .unwrap_or(text),
+            _ => text,
+        };
+        truncate(text.trim())
+    }
+
+    fn block(&mut self, kind: BlockKind, node: Option<(tree_sitter::Node, String)>) -> usize {
+        let line = node
+            .as_ref()
+            .map(|(n, _)| n.start_position().row as u32 + 1);
+        self.blocks.push(BasicBlock {
+            id: self.blocks.len(),
+            kind,
+            start_line: line,
+            end_line: line,
+            statements: node.map(|(_, text)| vec![text]).unwrap_or_default(),
+        });
+        self.blocks.len() - 1
+    }
+
+    fn edge(&mut self, from: usize, to: usize, kind: CfgEdgeKind) {
+        self.edges.push(CfgEdge {
+            from,
+            to,
+            kind,
+            label: None,
+        });
+    }
+
+    /// The block to add to: `cursor`, or a new unreachable one after a jump.
+    fn current(&mut self, cursor: Cursor) -> usize {
+        cursor.unwrap_or_else(|| self.block(BlockKind::Basic, None))
+    }
+
+    /// Add `node` as a plain statement.
+    fn append(&mut self, cursor: Cursor, node: tree_sitter::Node) -> usize {
+        let b = self.current(cursor);
+        let text = truncate(self.text(node));
+        let (start, end) = (
+            node.start_position().row as u32 + 1,
+            node.end_position().row as u32 + 1,
+        );
+        let block = &mut self.blocks[b];
+        block.statements.push(text);
+        block.start_line = Some(block.start_line.map_or(start, |s| s.min(start)));
+        block.end_line = Some(block.end_line.map_or(end, |e| e.max(end)));
+        b
+    }
+
+    fn sequence(&mut self, nodes: Vec<tree_sitter::Node>, mut cursor: Cursor) -> Cursor {
+        for node in nodes {
+            cursor = self.statement(node, cursor);
+        }
+        cursor
+    }
+
+    fn statement(&mut self, node: tree_sitter::Node, cursor: Cursor) -> Cursor {
+        let kind = node.kind();
+        if is_container(kind) {
+            return self.sequence(statements_of(node), cursor);
+        }
+        if kind == "expression_statement" {
+            let inner = statements_of(node);
+            if let [only] = inner[..] {
+                if is_control(only.kind()) {
+                    return self.statement(only, cursor);
+                }
+            }
+        }
+        if is_if(kind) {
+            return self.if_statement(node, cursor);
+        }
+        if is_loop(kind) {
+            return self.loop_statement(node, cursor);
+        }
+        if is_switch(kind) {
+            return self.switch_statement(node, cursor);
+        }
+        match kind {
+            "try_statement" => self.try_statement(node, cursor),
+            "return_statement" | "return_expression" => {
+                let b = self.append(cursor, node);
+                self.edge(b, self.exit, CfgEdgeKind::Return);
+                None
+            }
+            "throw_statement" | "throw_expression" | "raise_statement" => {
+                let b = self.append(cursor, node);
+                let target = self
+                    .handlers
+                    .last()
+                    .and_then(|h| h.first().copied())
+                    .unwrap_or(self.exit);
+                self.edge(b, target, CfgEdgeKind::Throw);
+                None
+            }
+            "break_statement" | "break_expression" => {
+                let b = self.append(cursor, node);
+                if let Some(&target) = self.breaks.last() {
+                    self.edge(b, target, CfgEdgeKind::Break);
+                }
+                None
+            }
+            "continue_statement" | "continue_expression" => {
+                let b = self.append(cursor, node);
+                if let Some(&target) = self.continues.last() {
+                    self.edge(b, target, CfgEdgeKind::Continue);
+                }
+                None
+            }
+            "fallthrough_statement" => {
+                let b = self.append(cursor, node);
+                if let Some(target) = self.next_arm {
+                    self.edge(b, target, CfgEdgeKind::Next);
+                }
+                None
+            }
+            "with_statement" | "using_statement" | "synchronized_statement" => {
+                match node.child_by_field_name("body") {
+                    Some(body) => {
+                        let head = self.head(node, Some(body));
+                        let b = self.current(cursor);
+                        self.blocks[b].statements.push(head);
+                        self.statement(body, Some(b))
+                    }
+                    None => Some(self.append(cursor, node)),
+                }
+            }
+            "labeled_statement" => match statements_of(node).last() {
+                Some(&inner) => self.statement(inner, cursor),
+                None => Some(self.append(cursor, node)),
+            },
+            _ => Some(self.append(cursor, node)),
+        }
+    }
+
+    fn if_statement(&mut self, node: tree_sitter::Node, cursor: Cursor) -> Cursor {
+        let from = self.current(cursor);
+        let condition = node.child_by_field_name("condition");
+        let text = condition.map_or_else(|| self.head(node, None), |c| self.condition(c));
+        let cond = self.block(BlockKind::Condition, Some((node, text)));
+        self.edge(from, cond, CfgEdgeKind::Next);
+        let mut walk = node.walk();
+        let alternatives: Vec<_> = node
+            .children_by_field_name("alternative", &mut walk)
+            .collect();
+        self.branch(cond, node.child_by_field_name("consequence"), &alternatives)
+    }
+
+    /// The outcomes of condition block `cond`: `consequence` when true, the
+    /// first of `alternatives` (`elif`, `else` or a plain statement) when
+    /// false.
+    fn branch(
+        &mut self,
+        cond: usize,
+        consequence: Option<tree_sitter::Node>,
+        alternatives: &[tree_sitter::Node],
+    ) -> Cursor {
+        let then = self.block(BlockKind::Basic, None);
+        self.edge(cond, then, CfgEdgeKind::True);
+        let then_end = match consequence {
+            Some(c) => self.statement(c, Some(then)),
+            None => Some(then),
+        };
+
+        let else_end = match alternatives.split_first() {
+            None => None,
+            Some((alt, rest)) if alt.kind() == "elif_clause" => {
+                let text = alt
+                    .child_by_field_name("condition")
+                    .map_or_else(|| self.head(*alt, None), |c| self.condition(c));
+                let elif = self.block(BlockKind::Condition, Some((*alt, text)));
+                self.edge(cond, elif, CfgEdgeKind::False);
+                self.branch(elif, alt.child_by_field_name("consequence"), rest)
+            }
+            Some((alt, _)) => {
+                let body = match alt.kind() {
+                    "else_clause" => alt
+                        .child_by_field_name("body")
+                        .or_else(|| statements_of(*alt).into_iter().next()),
+                    _ => Some(*alt),
+                };
+                let otherwise = self.block(BlockKind::Basic, None);
+                self.edge(cond, otherwise, CfgEdgeKind::False);
+                match body {
+                    Some(b) => self.statement(b, Some(otherwise)),
+                    None => Some(otherwise),
+                }
+            }
+        };
+
+        if alternatives.is_empty() {
+            let join = self.block(BlockKind::Basic, None);
+            self.edge(cond, join, CfgEdgeKind::False);
+            if let Some(end) = then_end {
+                self.edge(end, join, CfgEdgeKind::Next);
+            }
+            return Some(join);
+        }
+        if then_end.is_none() && else_end.is_none() {
+            return None;
+        }
+        let join = self.block(BlockKind::Basic, None);
+        for end in [then_end, else_end].into_iter().flatten() {
+            self.edge(end, join, CfgEdgeKind::Next);
+        }
+        Some(join)
+    }
+
+    fn loop_statement(&mut self, node: tree_sitter::Node, cursor: Cursor) -> Cursor {
+        let from = self.current(cursor);
+        let body = node.child_by_field_name("body");
+        let text = match node.child_by_field_name("condition") {
+            Some(c) if node.kind() != "for_statement" => self.condition(c),
+            _ => self.head(node, body),
+        };
+        let after = self.block(BlockKind::Basic, None);
+
+        if node.kind() == "do_statement" {
+            let first = self.block(BlockKind::Basic, None);
+            self.edge(from, first, CfgEdgeKind::Next);
+            let header = self.block(BlockKind::Loop, Some((node, text)));
+            let end = self.loop_body(body, first, header, after);
+            if let Some(end) = end {
+                self.edge(end, header, CfgEdgeKind::Next);
+            }
+            self.edge(header, first, CfgEdgeKind::Back);
+            self.edge(header, after, CfgEdgeKind::False);
+            return Some(after);
+        }
+
+        let header = self.block(BlockKind::Loop, Some((node, text)));
+        self.edge(from, header, CfgEdgeKind::Next);
+        let first = self.block(BlockKind::Basic, None);
+        self.edge(header, first, CfgEdgeKind::True);
+        if let Some(end) = self.loop_body(body, first, header, after) {
+            self.edge(end, header, CfgEdgeKind::Back);
+        }
+        // `loop {}`, `for {}` and `for (;;)` only leave through a break.
+        let infinite = node.kind() == "loop_expression"
+            || (node.kind() == "for_statement" && statements_of(node).len() == 1);
+        if !infinite {
+            self.edge(header, after, CfgEdgeKind::False);
+        }
+        Some(after)
+    }
+
+    fn loop_body(
+        &mut self,
+        body: Option<tree_sitter::Node>,
+        first: usize,
+        header: usize,
+        after: usize,
+    ) -> Cursor {
+        self.breaks.push(after);
+        self.continues.push(header);
+        let end = match body {
+            Some(b) => self.statement(b, Some(first)),
+            None => Some(first),
+        };
+        self.breaks.pop();
+        self.continues.pop();
+        end
+    }
+
+    fn switch_statement(&mut self, node: tree_sitter::Node, cursor: Cursor) -> Cursor {
+        let from = self.current(cursor);
+        // Go's switch statements hold their arms directly.
+        let container = node.child_by_field_name("body").unwrap_or(node);
+        let arms: Vec<_> = statements_of(container)
+            .into_iter()
+            .filter(|a| is_arm(a.kind()))
+            .collect();
+        let head = match container == node {
+            true => self.head(node, arms.first().copied()),
+            false => self.head(node, Some(container)),
+        };
+        let switch = self.block(BlockKind::Switch, Some((node, head)));
+        self.edge(from, switch, CfgEdgeKind::Next);
+        let after = self.block(BlockKind::Basic, None);
+
+        let arm_blocks: Vec<usize> = arms
+            .iter()
+            .map(|_| self.block(BlockKind::Basic, None))
+            .collect();
+        let mut has_default = false;
+        let mut carried: Cursor = None;
+        self.breaks.push(after);
+        let saved_next_arm = self.next_arm;
+        for (i, arm) in arms.iter().enumerate() {
+            let body = arm_statements(*arm);
+            let label = self.head(*arm, body.first().copied());
+            has_default |= arm.kind().contains("default")
+                || label.starts_with("default")
+                || label == "_"
+                || label == "case _";
+            let b = arm_blocks[i];
+            self.edges.push(CfgEdge {
+                from: switch,
+                to: b,
+                kind: CfgEdgeKind::Case,
+                label: Some(label),
+            });
+            if let Some(end) = carried.take() {
+                self.edge(end, b, CfgEdgeKind::Next);
+            }
+            self.next_arm = arm_blocks.get(i + 1).copied();
+            let end = self.sequence(body, Some(b));
+            match end {
+                Some(end) if falls_through(arm.kind()) && i + 1 < arms.len() => carried = Some(end),
+                Some(end) => self.edge(end, after, CfgEdgeKind::Next),
+                None => {}
+            }
+        }
+        self.next_arm = saved_next_arm;
+        self.breaks.pop();
+        if !has_default {
+            self.edge(switch, after, CfgEdgeKind::False);
+        }
+        Some(after)
+    }
+
+    fn try_statement(&mut self, node: tree_sitter::Node, cursor: Cursor) -> Cursor {
+        let from = self.current(cursor);
+        let first = self.block(BlockKind::Basic, None);
+        self.edge(from, first, CfgEdgeKind::Next);
+
+        let children = statements_of(node);
+        let handlers: Vec<_> = children
+            .iter()
+            .filter(|c| is_handler(c.kind()))
+            .copied()
+            .collect();
+        let handler_blocks: Vec<usize> = handlers
+            .iter()
+            .map(|h| {
+                let body = h
+                    .child_by_field_name("body")
+                    .or_else(|| statements_of(*h).pop());
+                let head = self.head(*h, body);
+                self.block(BlockKind::Handler, Some((*h, head)))
+            })
+            .collect();
+        for &h in &handler_blocks {
+            self.edge(first, h, CfgEdgeKind::Exception);
+        }
+        let finally = children
+            .iter()
+            .find(|c| matches!(c.kind(), "finally_clause" | "ensure"))
+            .copied();
+        let after = self.block(BlockKind::Basic, None);
+        let target = match finally {
+            Some(_) => self.block(BlockKind::Basic, None),
+            None => after,
+        };
+
+        self.handlers.push(handler_blocks.clone());
+        let mut end = match node.child_by_field_name("body") {
+            Some(body) => self.statement(body, Some(first)),
+            None => Some(first),
+        };
+        self.handlers.pop();
+        // Python's `try ... else` runs when the body raised nothing.
+        if let Some(otherwise) = children.iter().find(|c| c.kind() == "else_clause") {
+            let body = otherwise
+                .child_by_field_name("body")
+                .or_else(|| statements_of(*otherwise).pop());
+            if let Some(body) = body {
+                end = self.statement(body, end);
+            }
+        }
+        if let Some(end) = end {
+            self.edge(end, target, CfgEdgeKind::Next);
+        }
+        for (handler, &b) in handlers.iter().zip(&handler_blocks) {
+            let body = handler
+                .child_by_field_name("body")
+                .or_else(|| statements_of(*handler).pop());
+            let end = match body {
+                Some(body) => self.statement(body, Some(b)),
+                None => Some(b),
+            };
+            if let Some(end) = end {
+                self.edge(end, target, CfgEdgeKind::Next);
+            }
+        }
+        if let Some(finally) = finally {
+            let body = finally
+                .child_by_field_name("body")
+                .or_else(|| statements_of(finally).pop());
+            let end = match body {
+                Some(body) => self.statement(body, Some(target)),
+                None => Some(target),
+            };
+            if let Some(end) = end {
+                self.edge(end, after, CfgEdgeKind::Next);
+            }
+        }
+        Some(after)
+    }
+
+    /// Drop empty pass-through blocks and unreachable empty blocks, then
+    /// number the remaining blocks in order.
+    fn finish(mut self, entry: usize) -> ControlFlowGraph {
+        let mut removed = vec![false; self.blocks.len()];
+        loop {
+            let mut changed = false;
+            for (b, block) in self.blocks.iter().enumerate() {
+                if removed[b] || block.kind != BlockKind::Basic || !block.statements.is_empty() {
+                    continue;
+                }
+                let outgoing: Vec<usize> = (0..self.edges.len())
+                    .filter(|&e| self.edges[e].from == b)
+                    .collect();
+                let incoming = self.edges.iter().filter(|e| e.to == b).count();
+                let target = match outgoing[..] {
+                    [e] if self.edges[e].kind == CfgEdgeKind::Next && self.edges[e].to != b => {
+                        Some(self.edges[e].to)
+                    }
+                    _ if incoming == 0 => None,
+                    _ => continue,
+                };
+                match target {
+                    Some(t) => self
+                        .edges
+                        .iter_mut()
+                        .filter(|e| e.to == b)
+                        .for_each(|e| e.to = t),
+                    None if !outgoing.is_empty() || incoming > 0 => continue,
+                    None => {}
+                }
+                self.edges.retain(|e| e.from != b);
+                removed[b] = true;
+                changed = true;
+            }
+            if !changed {
+                break;
+            }
+        }
+
+        let mut ids = vec![usize::MAX; self.blocks.len()];
+        let mut blocks = Vec::new();
+        for (b, mut block) in self.blocks.into_iter().enumerate() {
+            if !removed[b] {
+                ids[b] = blocks.len();
+                block.id = blocks.len();
+                blocks.push(block);
+            }
+        }
+        let mut edges: Vec<CfgEdge> = Vec::new();
+        for mut e in self.edges {
+            e.from = ids[e.from];
+            e.to = ids[e.to];
+            if !edges.contains(&e) {
+                edges.push(e);
+            }
+        }
+        ControlFlowGraph {
+            blocks,
+            edges,
+            entry: ids[entry],
+            exit: ids[self.exit],
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cfg(source: &str, language: Language) -> ControlFlowGraph {
+        let lines = source.lines().count() as u32;
+        function_cfg(source, language, 1, lines.max(1))
+            .unwrap()
+            .unwrap()
+    }
+
+    fn edge_kinds(cfg: &ControlFlowGraph, kind: CfgEdgeKind) -> usize {
+        cfg.edges.iter().filter(|e| e.kind == kind).count()
+    }
+
+    #[test]
+    fn if_else_chain_and_early_return() {
+        let source = "def f(x):\n    a()\n    if x > 1:\n        return 1\n    elif x:\n        b()\n    else:\n        c()\n    d()\n";
+        let g = cfg(source, Language::Python);
+        let conditions: Vec<_> = g
+            .blocks
+            .iter()
+            .filter(|b| b.kind == BlockKind::Condition)
+            .map(|b| b.statements[0].as_str())
+            .collect();
+        assert_eq!(conditions, vec!["x > 1", "x"]);
+        assert_eq!(edge_kinds(&g, CfgEdgeKind::Return), 1);
+        assert_eq!(g.cyclomatic(), 3);
+        assert!(g.unreachable().is_empty());
+        let tail = g.blocks.iter().find(|b| b.statements == ["d()"]).unwrap();
+        assert_eq!(tail.start_line, Some(9));
+    }
+
+    #[test]
+    fn loops_break_continue_and_dead_code() {
+        let source = "function f(xs) {\n  for (const x of xs) {\n    if (x) { continue; }\n    if (!x) break;\n  }\n  while (true) { g(); }\n  return 1;\n  dead();\n}\n";
+        let g = cfg(source, Language::TypeScript);
+        assert_eq!(
+            g.blocks
+                .iter()
+                .filter(|b| b.kind == BlockKind::Loop)
+                .count(),
+            2
+        );
+        assert_eq!(edge_kinds(&g, CfgEdgeKind::Continue), 1);
+        assert_eq!(edge_kinds(&g, CfgEdgeKind::Break), 1);
+        assert_eq!(edge_kinds(&g, CfgEdgeKind::Back), 2);
+        let unreachable = g.unreachable();
+        assert_eq!(unreachable.len(), 1);
+        assert_eq!(g.blocks[unreachable[0]].statements, ["dead();"]);
+    }
+
+    #[test]
+    fn switch_arms_fall_through_until_break() {
+        let source = "function f(x) {\n  switch (x) {\n    case 1:\n      a();\n    case 2:\n      b();\n      break;\n    default:\n      c();\n  }\n}\n";
+        let g = cfg(source, Language::JavaScript);
+        let labels: Vec<_> = g.edges.iter().filter_map(|e| e.label.as_deref()).collect();
+        assert_eq!(labels, vec!["case 1", "case 2", "default"]);
+        let a = g
+            .blocks
+            .iter()
+            .position(|b| b.statements == ["a();"])
+            .unwrap();
+        let b = g
+            .blocks
+            .iter()
+            .position(|b| b.statements == ["b();", "break;"])
+            .unwrap();
+        assert!(g
+            .edges
+            .iter()
+            .any(|e| e.from == a && e.to == b && e.kind == CfgEdgeKind::Next));
+        assert_eq!(edge_kinds(&g, CfgEdgeKind::False), 0);
+    }
+
+    #[test]
+    fn rust_match_loop_and_try_handlers() {
+        let source = "fn f(x: i32) -> i32 {\n    loop {\n        if x > 0 { break; }\n    }\n    match x {\n        1 => a(),\n        _ => { b() }\n    }\n}\n";
+        let g = cfg(source, Language::Rust);
+        assert_eq!(
+            g.blocks
+                .iter()
+                .filter(|b| b.kind == BlockKind::Switch)
+                .count(),
+            1
+        );
+        assert_eq!(edge_kinds(&g, CfgEdgeKind::Case), 2);
+        // The only way out of `loop` is the break.
+        let header = g
+            .blocks
+            .iter()
+            .position(|b| b.kind == BlockKind::Loop)
+            .unwrap();
+        assert!(!g
+            .edges
+            .iter()
+            .any(|e| e.from == header && e.kind == CfgEdgeKind::False));
+
+        let java = "class A {\n  void f() {\n    try {\n      a();\n      throw new E();\n    } catch (E e) {\n      b();\n    } finally {\n      c();\n    }\n  }\n}\n";
+        let g = function_cfg(java, Language::Java, 2, 11).unwrap().unwrap();
+        let handler = g
+            .blocks
+            .iter()
+            .position(|b| b.kind == BlockKind::Handler)
+            .unwrap();
+        assert_eq!(g.blocks[handler].statements[0], "catch (E e)");
+        assert_eq!(edge_kinds(&g, CfgEdgeKind::Exception), 1);
+        assert!(g
+            .edges
+            .iter()
+            .any(|e| e.kind == CfgEdgeKind::Throw && e.to == handler));
+        assert!(function_cfg(java, Language::Java, 20, 30)
+            .unwrap()
+            .is_none());
+    }
+}
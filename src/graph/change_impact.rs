@@ -0,0 +1,284 @@
+//! Impact of a set of changed lines.
+//!
+//! Maps each changed line range to the innermost symbols it touches (the
+//! method rather than its class, unless the change is in the class but
+//! outside every method), then walks the callers of those symbols. Tests
+//! among the callers, tests found by [`GraphTraversal::find_tests`], and
+//! changed tests themselves are the suggested tests to run.
+//!
+//! Line numbers are matched against the index, so a file edited since it
+//! was last indexed maps approximately; re-index for exact results.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use crate::error::Result;
+use crate::git::ChangedLines;
+use crate::graph::store::{detect_is_test, GraphStore};
+use crate::graph::traversal::GraphTraversal;
+use crate::types::{CodeNode, NodeKind};
+
+/// A symbol overlapping the changed lines.
+#[derive(Debug, Clone)]
+pub struct ChangedSymbol {
+    pub node: CodeNode,
+    /// The changed ranges overlapping it; empty when the whole file changed.
+    pub ranges: Vec<(u32, u32)>,
+}
+
+/// A caller of a changed symbol.
+#[derive(Debug, Clone)]
+pub struct AffectedCaller {
+    pub node: CodeNode,
+    /// Call distance to the nearest changed symbol.
+    pub depth: u32,
+    /// ID of that changed symbol.
+    pub via: String,
+}
+
+/// Changed symbols, their callers and the tests covering them.
+#[derive(Debug, Clone, Default)]
+pub struct ChangeImpact {
+    /// Ordered by file and line.
+    pub changed: Vec<ChangedSymbol>,
+    /// Nearest first, then by file and line; changed symbols excluded.
+    pub callers: Vec<AffectedCaller>,
+    /// Ordered by file and line.
+    pub tests: Vec<CodeNode>,
+    /// Changed files with no symbols in the index.
+    pub unindexed_files: Vec<String>,
+}
+
+impl ChangeImpact {
+    /// Files holding a changed symbol or one of its callers, sorted.
+    pub fn affected_files(&self) -> Vec<&str> {
+        let mut files: Vec<&str> = self
+            .changed
+            .iter()
+            .map(|c| c.node.file_path.as_str())
+            .chain(self.callers.iter().map(|c| c.node.file_path.as_str()))
+            .collect();
+        files.sort_unstable();
+        files.dedup();
+        files
+    }
+}
+
+/// Symbols touched by `changes` and what depends on them, following callers
+/// up to `max_depth` hops.
+pub fn change_impact(
+    store: &GraphStore,
+    changes: &[ChangedLines],
+    max_depth: u32,
+) -> Result<ChangeImpact> {
+    let mut impact = ChangeImpact::default();
+    for change in changes {
+        let nodes: Vec<CodeNode> = store
+            .get_nodes_by_file(&change.path)?
+            .into_iter()
+            .filter(|n| !matches!(n.kind, NodeKind::Module | NodeKind::Namespace))
+            .collect();
+        if nodes.is_empty() {
+            impact.unindexed_files.push(change.path.clone());
+            continue;
+        }
+        if change.whole_file {
+            impact
+                .changed
+                .extend(nodes.into_iter().map(|node| ChangedSymbol {
+                    node,
+                    ranges: Vec::new(),
+                }));
+            continue;
+        }
+        let mut touched: BTreeMap<String, ChangedSymbol> = BTreeMap::new();
+        for &range in &change.ranges {
+            for node in innermost_overlapping(&nodes, range) {
+                touched
+                    .entry(node.id.clone())
+                    .or_insert_with(|| ChangedSymbol {
+                        node: node.clone(),
+                        ranges: Vec::new(),
+                    })
+                    .ranges
+                    .push(range);
+            }
+        }
+        impact.changed.extend(touched.into_values());
+    }
+    impact.changed.sort_by(|a, b| {
+        (&a.node.file_path, a.node.start_line).cmp(&(&b.node.file_path, b.node.start_line))
+    });
+
+    let changed_ids: HashSet<&str> = impact.changed.iter().map(|c| c.node.id.as_str()).collect();
+    let traversal = GraphTraversal::new(store);
+    let mut callers: HashMap<String, AffectedCaller> = HashMap::new();
+    let mut tests: HashMap<String, CodeNode> = HashMap::new();
+    for changed in &impact.changed {
+        if is_test(&changed.node) {
+            tests.insert(changed.node.id.clone(), changed.node.clone());
+        }
+        for caller in traversal.find_callers(&changed.node.id, max_depth)? {
+            if changed_ids.contains(caller.node.id.as_str()) {
+                continue;
+            }
+            let nearer = callers
+                .get(&caller.node.id)
+                .is_none_or(|c| caller.depth < c.depth);
+            if nearer {
+                callers.insert(
+                    caller.node.id.clone(),
+                    AffectedCaller {
+                        node: caller.node,
+                        depth: caller.depth,
+                        via: changed.node.id.clone(),
+                    },
+                );
+            }
+        }
+        for test in traversal.find_tests(&changed.node.id)? {
+            tests.insert(test.id.clone(), test);
+        }
+    }
+    for caller in callers.values().filter(|c| is_test(&c.node)) {
+        tests.insert(caller.node.id.clone(), caller.node.clone());
+    }
+
+    impact.callers = callers.into_values().collect();
+    impact.callers.sort_by(|a, b| {
+        (a.depth, &a.node.file_path, a.node.start_line).cmp(&(
+            b.depth,
+            &b.node.file_path,
+            b.node.start_line,
+        ))
+    });
+    impact.tests = tests.into_values().collect();
+    impact
+        .tests
+        .sort_by(|a, b| (&a.file_path, a.start_line).cmp(&(&b.file_path, b.start_line)));
+    Ok(impact)
+}
+
+/// Nodes overlapping `range` that contain no other overlapping node.
+fn innermost_overlapping(nodes: &[CodeNode], (from, to): (u32, u32)) -> Vec<&CodeNode> {
+    let overlapping: Vec<&CodeNode> = nodes
+        .iter()
+        .filter(|n| n.start_line <= to && n.end_line >= from)
+        .collect();
+    overlapping
+        .iter()
+        .filter(|outer| {
+            !overlapping.iter().any(|inner| {
+                inner.id != outer.id
+                    && outer.start_line <= inner.start_line
+                    && inner.end_line <= outer.end_line
+                    && (inner.start_line, inner.end_line) != (outer.start_line, outer.end_line)
+                    // The change must fall inside the inner node for it
+                    // to take over; otherwise the outer node is touched
+                    // outside its children.
+                    && inner.start_line <= from
+                    && to <= inner.end_line
+            })
+        })
+        .copied()
+        .collect()
+}
+
+fn is_test(node: &CodeNode) -> bool {
+    detect_is_test(
+        &node.name,
+        &node.file_path,
+        node.language.as_str(),
+        node.kind.as_str(),
+    )
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{CodeEdge, EdgeKind, Language};
+
+    fn node(id: &str, kind: NodeKind, file: &str, start: u32, end: u32) -> CodeNode {
+        CodeNode {
+            id: id.to_string(),
+            name: id.to_string(),
+            qualified_name: None,
+            kind,
+            file_path: file.to_string(),
+            start_line: start,
+            end_line: end,
+            start_column: 0,
+            end_column: 0,
+            language: Language::TypeScript,
+            body: None,
+            documentation: None,
+            exported: None,
+        }
+    }
+
+    fn call(source: &str, target: &str) -> CodeEdge {
+        CodeEdge {
+            source: source.to_string(),
+            target: target.to_string(),
+            kind: EdgeKind::Calls,
+            file_path: String::new(),
+            line: 1,
+            metadata: None,
+        }
+    }
+
+    #[test]
+    fn maps_ranges_to_innermost_symbols_and_walks_callers() {
+        let store = GraphStore::from_connection(
+            crate::db::schema::initialize_database(":memory:").unwrap(),
+        );
+        store
+            .upsert_nodes(&[
+                node("Cart", NodeKind::Class, "src/cart.ts", 1, 30),
+                node("total", NodeKind::Method, "src/cart.ts", 5, 10),
+                node("add", NodeKind::Method, "src/cart.ts", 12, 20),
+                node("checkout", NodeKind::Function, "src/pay.ts", 1, 9),
+                node("testCheckout", NodeKind::Function, "test/pay.test.ts", 1, 5),
+            ])
+            .unwrap();
+        store
+            .upsert_edges(&[call("checkout", "total"), call("testCheckout", "checkout")])
+            .unwrap();
+
+        let changes = vec![
+            ChangedLines {
+                path: "src/cart.ts".to_string(),
+                ranges: vec![(6, 7), (25, 25)],
+                whole_file: false,
+            },
+            ChangedLines {
+                path: "README.md".to_string(),
+                ranges: vec![(1, 1)],
+                whole_file: false,
+            },
+        ];
+        let impact = change_impact(&store, &changes, 5).unwrap();
+        let changed: Vec<&str> = impact.changed.iter().map(|c| c.node.id.as_str()).collect();
+        // Line 25 is in the class but outside both methods.
+        assert_eq!(changed, vec!["Cart", "total"]);
+        let callers: Vec<(&str, u32, &str)> = impact
+            .callers
+            .iter()
+            .map(|c| (c.node.id.as_str(), c.depth, c.via.as_str()))
+            .collect();
+        assert_eq!(
+            callers,
+            vec![("checkout", 1, "total"), ("testCheckout", 2, "total")]
+        );
+        let tests: Vec<&str> = impact.tests.iter().map(|t| t.id.as_str()).collect();
+        assert_eq!(tests, vec!["testCheckout"]);
+        assert_eq!(impact.unindexed_files, vec!["README.md"]);
+        assert_eq!(
+            impact.affected_files(),
+            vec!["src/cart.ts", "src/pay.ts", "test/pay.test.ts"]
+        );
+    }
+}
@@ -0,0 +1,407 @@
+//! Semantic clone detection.
+//!
+//! Finds functions and methods whose embeddings are nearly parallel — code
+//! that does the same thing under different names, with renamed variables
+//! or reordered statements, which [`duplicates`](super::duplicates) misses
+//! because it only groups definitions sharing a name. Pairs above a cosine
+//! threshold are kept when their bodies have comparable token counts (a
+//! ten-line helper is not a clone of the two-hundred-line function that
+//! inlines it), and linked pairs are merged into clusters.
+//!
+//! Vectors come from `embedding_cache`. Without any embeddings, a hashed
+//! bag-of-words of each body stands in, which still catches copy-paste
+//! clones but not rewrites. With an ANN sidecar, each function's
+//! neighbours come from it instead of comparing every pair.
+
+use std::collections::HashMap;
+
+use rusqlite::params;
+
+use crate::db::findings::{from_blob, lexical_vector};
+use crate::error::Result;
+use crate::graph::ann;
+use crate::graph::duplicates::token_count;
+use crate::graph::store::GraphStore;
+use crate::security::rules::is_test_file;
+use crate::types::{CodeNode, NodeKind};
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// Where the compared vectors came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VectorSource {
+    Embeddings,
+    Lexical,
+}
+
+impl VectorSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Embeddings => "embeddings",
+            Self::Lexical => "lexical",
+        }
+    }
+}
+
+/// Knobs for [`find_clones`].
+#[derive(Debug, Clone)]
+pub struct CloneOptions {
+    /// Minimum cosine similarity of a clone pair.
+    pub threshold: f32,
+    /// Minimum ratio of the shorter body's token count to the longer's.
+    pub min_length_ratio: f64,
+    /// Functions shorter than this many lines are ignored.
+    pub min_lines: u32,
+    pub include_tests: bool,
+    /// Only clusters with a member under this path prefix.
+    pub path: Option<String>,
+}
+
+impl Default for CloneOptions {
+    fn default() -> Self {
+        Self {
+            threshold: 0.95,
+            min_length_ratio: 0.7,
+            min_lines: 4,
+            include_tests: false,
+            path: None,
+        }
+    }
+}
+
+/// Two clones, as indices into the cluster's `members`.
+#[derive(Debug, Clone)]
+pub struct ClonePair {
+    pub a: usize,
+    pub b: usize,
+    pub similarity: f32,
+}
+
+/// Functions linked by clone pairs.
+#[derive(Debug, Clone)]
+pub struct CloneCluster {
+    /// Ordered by file path, then line.
+    pub members: Vec<CodeNode>,
+    /// The pairs that linked the cluster, most similar first.
+    pub pairs: Vec<ClonePair>,
+    pub max_similarity: f32,
+}
+
+/// Result of a clone scan.
+#[derive(Debug, Clone)]
+pub struct CloneReport {
+    /// Largest and most similar first.
+    pub clusters: Vec<CloneCluster>,
+    /// Functions compared.
+    pub candidates: usize,
+    pub source: VectorSource,
+}
+
+// ---------------------------------------------------------------------------
+// Detection
+// ---------------------------------------------------------------------------
+
+/// Neighbours fetched per function from the ANN index.
+const ANN_NEIGHBOURS: usize = 10;
+
+/// Node batch size when reading functions.
+const BATCH_SIZE: usize = 500;
+
+/// Cluster near-duplicate functions, see the module docs.
+pub fn find_clones(store: &GraphStore, opts: &CloneOptions) -> Result<CloneReport> {
+    let mut candidates: Vec<CodeNode> = Vec::new();
+    for batch in store.iter_nodes(BATCH_SIZE) {
+        candidates.extend(batch?.into_iter().filter(|n| {
+            matches!(n.kind, NodeKind::Function | NodeKind::Method)
+                && n.body.is_some()
+                && n.end_line.saturating_sub(n.start_line) + 1 >= opts.min_lines
+                && (opts.include_tests || !is_test_file(&n.file_path))
+        }));
+    }
+
+    let mut embeddings = load_embeddings(store)?;
+    let source = if candidates.iter().any(|n| embeddings.contains_key(&n.id)) {
+        candidates.retain(|n| embeddings.contains_key(&n.id));
+        VectorSource::Embeddings
+    } else {
+        VectorSource::Lexical
+    };
+    candidates.sort_by(|a, b| {
+        a.file_path
+            .cmp(&b.file_path)
+            .then(a.start_line.cmp(&b.start_line))
+    });
+    let vectors: Vec<Vec<f32>> = candidates
+        .iter()
+        .map(|n| match source {
+            VectorSource::Embeddings => normalize(embeddings.remove(&n.id).unwrap_or_default()),
+            VectorSource::Lexical => lexical_vector(n.body.as_deref().unwrap_or("")),
+        })
+        .collect();
+    let tokens: Vec<usize> = candidates
+        .iter()
+        .map(|n| token_count(n.body.as_deref().unwrap_or("")))
+        .collect();
+
+    let comparable = |a: usize, b: usize| {
+        let (x, y) = (tokens[a].min(tokens[b]), tokens[a].max(tokens[b]));
+        y > 0
+            && x as f64 / y as f64 >= opts.min_length_ratio
+            && !nested(&candidates[a], &candidates[b])
+    };
+
+    let mut pairs: Vec<(usize, usize, f32)> = Vec::new();
+    let index = match source {
+        VectorSource::Embeddings => ann::cached_index(&store.conn),
+        VectorSource::Lexical => None,
+    };
+    if let Some(index) = index {
+        let position: HashMap<&str, usize> = candidates
+            .iter()
+            .enumerate()
+            .map(|(i, n)| (n.id.as_str(), i))
+            .collect();
+        for (a, vector) in vectors.iter().enumerate() {
+            for (id, distance) in index.search(vector, ANN_NEIGHBOURS + 1) {
+                let Some(&b) = position.get(id.as_str()) else {
+                    continue;
+                };
+                let similarity = 1.0 - distance as f32;
+                if a < b && similarity >= opts.threshold && comparable(a, b) {
+                    pairs.push((a, b, similarity));
+                }
+            }
+        }
+    } else {
+        for a in 0..candidates.len() {
+            for b in a + 1..candidates.len() {
+                if !comparable(a, b) {
+                    continue;
+                }
+                let similarity = dot(&vectors[a], &vectors[b]);
+                if similarity >= opts.threshold {
+                    pairs.push((a, b, similarity));
+                }
+            }
+        }
+    }
+
+    let n = candidates.len();
+    let mut clusters = cluster(candidates, pairs);
+    if let Some(prefix) = opts.path.as_deref() {
+        clusters.retain(|c| c.members.iter().any(|m| m.file_path.starts_with(prefix)));
+    }
+    Ok(CloneReport {
+        clusters,
+        candidates: n,
+        source,
+    })
+}
+
+/// Every cached embedding by node ID.
+fn load_embeddings(store: &GraphStore) -> Result<HashMap<String, Vec<f32>>> {
+    let mut stmt = store
+        .conn
+        .prepare_cached("SELECT node_id, embedding FROM embedding_cache")?;
+    let rows = stmt.query_map(params![], |row| {
+        Ok((row.get::<_, String>(0)?, row.get::<_, Vec<u8>>(1)?))
+    })?;
+    Ok(rows
+        .flatten()
+        .map(|(id, blob)| (id, from_blob(&blob)))
+        .collect())
+}
+
+fn normalize(mut v: Vec<f32>) -> Vec<f32> {
+    let norm = v.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        v.iter_mut().for_each(|x| *x /= norm);
+    }
+    v
+}
+
+/// Cosine similarity of two unit vectors.
+fn dot(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() {
+        return 0.0;
+    }
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+/// Whether one function is defined inside the other.
+fn nested(a: &CodeNode, b: &CodeNode) -> bool {
+    a.file_path == b.file_path
+        && ((a.start_line <= b.start_line && b.end_line <= a.end_line)
+            || (b.start_line <= a.start_line && a.end_line <= b.end_line))
+}
+
+/// Merge pairs into connected components (union-find).
+fn cluster(candidates: Vec<CodeNode>, pairs: Vec<(usize, usize, f32)>) -> Vec<CloneCluster> {
+    fn find(parent: &mut [usize], i: usize) -> usize {
+        let mut root = i;
+        while parent[root] != root {
+            root = parent[root];
+        }
+        let mut i = i;
+        while parent[i] != root {
+            let next = parent[i];
+            parent[i] = root;
+            i = next;
+        }
+        root
+    }
+
+    let mut parent: Vec<usize> = (0..candidates.len()).collect();
+    for &(a, b, _) in &pairs {
+        let (ra, rb) = (find(&mut parent, a), find(&mut parent, b));
+        if ra != rb {
+            parent[ra.max(rb)] = ra.min(rb);
+        }
+    }
+
+    let mut by_root: HashMap<usize, Vec<(usize, usize, f32)>> = HashMap::new();
+    for &(a, b, s) in &pairs {
+        by_root
+            .entry(find(&mut parent, a))
+            .or_default()
+            .push((a, b, s));
+    }
+    let mut slots: Vec<Option<CodeNode>> = candidates.into_iter().map(Some).collect();
+    let mut clusters: Vec<CloneCluster> = by_root
+        .into_values()
+        .map(|mut links| {
+            let mut ids: Vec<usize> = links.iter().flat_map(|&(a, b, _)| [a, b]).collect();
+            ids.sort_unstable();
+            ids.dedup();
+            let local = |i: usize| ids.binary_search(&i).unwrap_or(0);
+            links.sort_by(|x, y| y.2.total_cmp(&x.2));
+            let pairs: Vec<ClonePair> = links
+                .iter()
+                .map(|&(a, b, similarity)| ClonePair {
+                    a: local(a),
+                    b: local(b),
+                    similarity,
+                })
+                .collect();
+            CloneCluster {
+                max_similarity: pairs.first().map_or(0.0, |p| p.similarity),
+                members: ids.iter().filter_map(|&i| slots[i].take()).collect(),
+                pairs,
+            }
+        })
+        .collect();
+    clusters.sort_by(|a, b| {
+        b.members
+            .len()
+            .cmp(&a.members.len())
+            .then(b.max_similarity.total_cmp(&a.max_similarity))
+            .then(a.members[0].id.cmp(&b.members[0].id))
+    });
+    clusters
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::findings::to_blob;
+    use crate::db::schema::initialize_database;
+    use crate::types::Language;
+
+    fn function(id: &str, file: &str, line: u32, body: &str) -> CodeNode {
+        CodeNode {
+            id: id.to_string(),
+            name: id.to_string(),
+            qualified_name: None,
+            kind: NodeKind::Function,
+            file_path: file.to_string(),
+            start_line: line,
+            end_line: line + body.lines().count() as u32 - 1,
+            start_column: 0,
+            end_column: 0,
+            language: Language::TypeScript,
+            body: Some(body.to_string()),
+            documentation: None,
+            exported: None,
+        }
+    }
+
+    fn embed(store: &GraphStore, id: &str, vector: &[f32]) {
+        store
+            .conn
+            .execute(
+                "INSERT INTO embedding_cache (node_id, embedding) VALUES (?1, ?2)",
+                params![id, to_blob(vector)],
+            )
+            .unwrap();
+    }
+
+    const SUM: &str =
+        "function a(xs) {\n  let t = 0;\n  for (const x of xs) t += x;\n  return t;\n}";
+
+    #[test]
+    fn clusters_similar_embeddings_of_comparable_length() {
+        let store = GraphStore::from_connection(initialize_database(":memory:").unwrap());
+        let long = format!("{SUM}\n{SUM}\n{SUM}\n{SUM}");
+        store
+            .upsert_nodes(&[
+                function("total", "a.ts", 1, SUM),
+                function("sum", "b.ts", 1, SUM),
+                function("add_all", "c.ts", 1, SUM),
+                function("report", "d.ts", 1, &long),
+                function("render", "e.ts", 1, SUM),
+            ])
+            .unwrap();
+        embed(&store, "total", &[1.0, 0.0, 0.1]);
+        embed(&store, "sum", &[0.9, 0.0, 0.1]);
+        embed(&store, "add_all", &[1.0, 0.05, 0.1]);
+        // Same direction, but four times as long.
+        embed(&store, "report", &[1.0, 0.0, 0.1]);
+        embed(&store, "render", &[0.0, 1.0, 0.0]);
+
+        let report = find_clones(&store, &CloneOptions::default()).unwrap();
+        assert_eq!(report.source, VectorSource::Embeddings);
+        assert_eq!(report.candidates, 5);
+        assert_eq!(report.clusters.len(), 1);
+        let ids: Vec<&str> = report.clusters[0]
+            .members
+            .iter()
+            .map(|m| m.id.as_str())
+            .collect();
+        assert_eq!(ids, ["total", "sum", "add_all"]);
+        assert_eq!(report.clusters[0].pairs.len(), 3);
+        assert!(report.clusters[0].max_similarity > 0.99);
+    }
+
+    #[test]
+    fn falls_back_to_lexical_vectors_without_embeddings() {
+        let store = GraphStore::from_connection(initialize_database(":memory:").unwrap());
+        let other = "function b(s) {\n  const parts = s.split(',');\n  if (!parts.length) throw new Error('empty');\n  return parts.map(Number);\n}";
+        store
+            .upsert_nodes(&[
+                function("x", "src/a.ts", 1, SUM),
+                function("y", "lib/b.ts", 1, SUM),
+                function("z", "lib/c.ts", 1, other),
+            ])
+            .unwrap();
+        let opts = CloneOptions {
+            path: Some("src".to_string()),
+            ..Default::default()
+        };
+        let report = find_clones(&store, &opts).unwrap();
+        assert_eq!(report.source, VectorSource::Lexical);
+        assert_eq!(report.clusters.len(), 1);
+        assert_eq!(report.clusters[0].members.len(), 2);
+
+        let opts = CloneOptions {
+            path: Some("test".to_string()),
+            ..Default::default()
+        };
+        assert!(find_clones(&store, &opts).unwrap().clusters.is_empty());
+    }
+}
@@ -0,0 +1,423 @@
+//! Graph-aware symbol completion.
+//!
+//! Given a file, a cursor line and a typed prefix, returns the symbols that
+//! are most likely valid at that point, ranked in tiers: members of the
+//! enclosing scope, other symbols of the same file, symbols the file
+//! imports, and finally exported project symbols ordered by how often they
+//! are referenced. Backs the `codegraph_complete` MCP tool, which is meant
+//! for lightweight editor integrations without a language server.
+
+use std::collections::HashSet;
+
+use rusqlite::{params, Connection};
+
+use crate::error::Result;
+
+/// Candidates fetched per tier for every requested completion, so ranking
+/// and de-duplication still have enough rows to choose from.
+const CANDIDATE_FACTOR: usize = 4;
+
+/// Longest signature line returned with a completion.
+const MAX_SIGNATURE_CHARS: usize = 120;
+
+/// Where a completion came from. Earlier variants rank higher.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum CompletionSource {
+    /// Declared inside the innermost node enclosing the cursor.
+    Scope,
+    /// Declared elsewhere in the same file.
+    File,
+    /// Imported into the file via a resolved import edge.
+    Import,
+    /// Exported from another file in the project.
+    Project,
+}
+
+impl CompletionSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Scope => "scope",
+            Self::File => "file",
+            Self::Import => "import",
+            Self::Project => "project",
+        }
+    }
+
+    /// Base score of the tier; bonuses within a tier stay below 1.0.
+    fn base(&self) -> f64 {
+        match self {
+            Self::Scope => 4.0,
+            Self::File => 3.0,
+            Self::Import => 2.0,
+            Self::Project => 1.0,
+        }
+    }
+}
+
+/// One ranked completion candidate.
+#[derive(Debug, Clone)]
+pub struct Completion {
+    pub id: String,
+    pub name: String,
+    pub kind: String,
+    pub file_path: String,
+    pub line: u32,
+    /// First line of the declaration, if stored.
+    pub signature: Option<String>,
+    pub source: CompletionSource,
+    pub score: f64,
+}
+
+const SAME_FILE_SQL: &str = "\
+SELECT id, name, type, file_path, start_line, end_line, signature
+FROM nodes
+WHERE file_path = ?1 AND name LIKE ?2 ESCAPE '\\'
+ORDER BY start_line, id
+LIMIT ?3";
+
+const ENCLOSING_SQL: &str = "\
+SELECT start_line, end_line
+FROM nodes
+WHERE file_path = ?1 AND start_line <= ?2 AND end_line >= ?2
+ORDER BY end_line - start_line ASC
+LIMIT 1";
+
+const IMPORTED_SQL: &str = "\
+SELECT DISTINCT n.id, n.name, n.type, n.file_path, n.start_line, n.end_line, n.signature
+FROM edges e
+JOIN nodes n ON n.id = e.target_id
+WHERE e.type = 'imports' AND e.source_id = ?1
+  AND n.file_path != ?4 AND n.name LIKE ?2 ESCAPE '\\'
+ORDER BY n.name, n.id
+LIMIT ?3";
+
+const PROJECT_SQL: &str = "\
+SELECT n.id, n.name, n.type, n.file_path, n.start_line, n.end_line, n.signature,
+       (SELECT COUNT(*) FROM edges e WHERE e.target_id = n.id) AS fan_in
+FROM nodes n
+WHERE n.file_path != ?1 AND n.name LIKE ?2 ESCAPE '\\'
+  AND json_extract(n.metadata, '$.exported') IS true
+ORDER BY fan_in DESC, n.name, n.id
+LIMIT ?3";
+
+/// A candidate row before scoring.
+struct Row {
+    id: String,
+    name: String,
+    kind: String,
+    file_path: String,
+    start_line: u32,
+    end_line: u32,
+    signature: Option<String>,
+    fan_in: u32,
+}
+
+fn read_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<Row> {
+    Ok(Row {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        kind: row.get(2)?,
+        file_path: row.get(3)?,
+        start_line: row.get(4)?,
+        end_line: row.get(5)?,
+        signature: row.get(6)?,
+        fan_in: 0,
+    })
+}
+
+/// Escape `prefix` for a `LIKE ... ESCAPE '\'` pattern and append `%`.
+fn like_prefix(prefix: &str) -> String {
+    let mut pattern = String::with_capacity(prefix.len() + 1);
+    for c in prefix.chars() {
+        if matches!(c, '%' | '_' | '\\') {
+            pattern.push('\\');
+        }
+        pattern.push(c);
+    }
+    pattern.push('%');
+    pattern
+}
+
+/// Complete `prefix` at `line` of `file_path`, returning at most `limit`
+/// candidates ordered by descending score. Matching is case-insensitive;
+/// an exact-case match and a shorter name rank higher within a tier.
+pub fn complete(
+    conn: &Connection,
+    file_path: &str,
+    line: u32,
+    prefix: &str,
+    limit: usize,
+) -> Result<Vec<Completion>> {
+    if limit == 0 {
+        return Ok(Vec::new());
+    }
+    let pattern = like_prefix(prefix);
+    let fetch = (limit * CANDIDATE_FACTOR) as i64;
+
+    let enclosing: Option<(u32, u32)> = conn
+        .prepare_cached(ENCLOSING_SQL)?
+        .query_map(params![file_path, line], |r| Ok((r.get(0)?, r.get(1)?)))?
+        .next()
+        .transpose()?;
+
+    let mut candidates = Vec::new();
+    let same_file = conn
+        .prepare_cached(SAME_FILE_SQL)?
+        .query_map(params![file_path, pattern, fetch], read_row)?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    for row in same_file {
+        let in_scope = enclosing.is_some_and(|(start, end)| {
+            row.start_line >= start
+                && row.end_line <= end
+                && (row.start_line, row.end_line) != (start, end)
+        });
+        let source = if in_scope {
+            CompletionSource::Scope
+        } else {
+            CompletionSource::File
+        };
+        candidates.push((row, source));
+    }
+
+    let file_id = format!("file:{file_path}");
+    let imported = conn
+        .prepare_cached(IMPORTED_SQL)?
+        .query_map(params![file_id, pattern, fetch, file_path], read_row)?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    candidates.extend(imported.into_iter().map(|r| (r, CompletionSource::Import)));
+
+    let project = conn
+        .prepare_cached(PROJECT_SQL)?
+        .query_map(params![file_path, pattern, fetch], |r| {
+            let mut row = read_row(r)?;
+            row.fan_in = r.get(7)?;
+            Ok(row)
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    candidates.extend(project.into_iter().map(|r| (r, CompletionSource::Project)));
+
+    // Tiers were pushed best-first, so the first sighting of an ID wins.
+    let mut seen = HashSet::new();
+    let mut completions: Vec<Completion> = candidates
+        .into_iter()
+        .filter(|(row, _)| seen.insert(row.id.clone()))
+        .map(|(row, source)| {
+            let score = source.base() + bonus(&row, prefix);
+            Completion {
+                signature: row.signature.as_deref().and_then(signature_line),
+                id: row.id,
+                name: row.name,
+                kind: row.kind,
+                file_path: row.file_path,
+                line: row.start_line,
+                source,
+                score,
+            }
+        })
+        .collect();
+
+    completions.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then_with(|| a.name.cmp(&b.name))
+            .then_with(|| a.id.cmp(&b.id))
+    });
+    completions.truncate(limit);
+    Ok(completions)
+}
+
+/// In-tier bonus, always below 1.0: exact-case prefix (0.4), brevity (up
+/// to 0.3) and popularity by fan-in (up to 0.29).
+fn bonus(row: &Row, prefix: &str) -> f64 {
+    let case = if row.name.starts_with(prefix) {
+        0.4
+    } else {
+        0.0
+    };
+    let extra = row
+        .name
+        .chars()
+        .count()
+        .saturating_sub(prefix.chars().count());
+    let brevity = 0.3 / (1.0 + extra as f64);
+    let popularity = ((1.0 + row.fan_in as f64).ln() / 10.0).min(0.29);
+    case + brevity + popularity
+}
+
+/// First non-empty line of a stored declaration, truncated.
+fn signature_line(text: &str) -> Option<String> {
+    let line = text.lines().map(str::trim).find(|l| !l.is_empty())?;
+    Some(match line.char_indices().nth(MAX_SIGNATURE_CHARS) {
+        Some((idx, _)) => format!("{}...", &line[..idx]),
+        None => line.to_string(),
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::schema::initialize_database;
+    use crate::graph::store::GraphStore;
+    use crate::types::{CodeEdge, CodeNode, EdgeKind, Language, NodeKind};
+
+    fn node(id: &str, name: &str, kind: NodeKind, file: &str, lines: (u32, u32)) -> CodeNode {
+        CodeNode {
+            id: id.to_string(),
+            name: name.to_string(),
+            qualified_name: None,
+            kind,
+            file_path: file.to_string(),
+            start_line: lines.0,
+            end_line: lines.1,
+            start_column: 0,
+            end_column: 0,
+            language: Language::TypeScript,
+            body: Some(format!("function {name}() {{\n}}")),
+            documentation: None,
+            exported: Some(true),
+        }
+    }
+
+    fn edge(source: &str, target: &str, kind: EdgeKind) -> CodeEdge {
+        CodeEdge {
+            source: source.to_string(),
+            target: target.to_string(),
+            kind,
+            file_path: "src/app.ts".to_string(),
+            line: 1,
+            metadata: None,
+        }
+    }
+
+    fn setup() -> GraphStore {
+        let store = GraphStore::from_connection(initialize_database(":memory:").unwrap());
+        store
+            .upsert_nodes(&[
+                node("c:App", "App", NodeKind::Class, "src/app.ts", (1, 30)),
+                node(
+                    "m:render",
+                    "render",
+                    NodeKind::Method,
+                    "src/app.ts",
+                    (5, 10),
+                ),
+                node("m:reset", "reset", NodeKind::Method, "src/app.ts", (12, 20)),
+                node("f:run", "run", NodeKind::Function, "src/app.ts", (40, 45)),
+                node(
+                    "f:readFile",
+                    "readFile",
+                    NodeKind::Function,
+                    "src/fs.ts",
+                    (1, 5),
+                ),
+                node(
+                    "f:request",
+                    "request",
+                    NodeKind::Function,
+                    "src/http.ts",
+                    (1, 5),
+                ),
+                node(
+                    "f:retry",
+                    "retry",
+                    NodeKind::Function,
+                    "src/util.ts",
+                    (1, 5),
+                ),
+                node(
+                    "f:Retry",
+                    "Retry_",
+                    NodeKind::Function,
+                    "src/util.ts",
+                    (7, 9),
+                ),
+            ])
+            .unwrap();
+        store
+            .upsert_edges(&[
+                edge("file:src/app.ts", "f:readFile", EdgeKind::Imports),
+                edge("f:run", "f:request", EdgeKind::Calls),
+                edge("f:readFile", "f:request", EdgeKind::Calls),
+            ])
+            .unwrap();
+        store
+    }
+
+    fn names(completions: &[Completion]) -> Vec<(&str, &str)> {
+        completions
+            .iter()
+            .map(|c| (c.name.as_str(), c.source.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn ranks_scope_then_file_then_imports_then_project() {
+        let store = setup();
+        let got = complete(&store.conn, "src/app.ts", 25, "r", 10).unwrap();
+        assert_eq!(
+            names(&got),
+            vec![
+                ("reset", "scope"),
+                ("render", "scope"),
+                ("run", "file"),
+                ("readFile", "import"),
+                ("request", "project"),
+                ("retry", "project"),
+                ("Retry_", "project"),
+            ]
+        );
+        assert!(got.windows(2).all(|w| w[0].score >= w[1].score));
+        assert_eq!(got[0].signature.as_deref(), Some("function reset() {"));
+    }
+
+    #[test]
+    fn enclosing_node_itself_is_not_a_scope_member() {
+        let store = setup();
+        // Inside `reset`: its siblings are no longer in the innermost scope.
+        let got = complete(&store.conn, "src/app.ts", 14, "re", 10).unwrap();
+        assert_eq!(got[0].source, CompletionSource::File);
+        assert_eq!(got[1].source, CompletionSource::File);
+
+        let got = complete(&store.conn, "src/app.ts", 35, "App", 10).unwrap();
+        assert_eq!(names(&got), vec![("App", "file")]);
+    }
+
+    #[test]
+    fn prefix_is_case_insensitive_and_literal() {
+        let store = setup();
+        let got = complete(&store.conn, "src/app.ts", 1, "RETRY", 10).unwrap();
+        assert_eq!(
+            names(&got),
+            vec![("retry", "project"), ("Retry_", "project")]
+        );
+
+        // `_` is matched literally, not as a LIKE wildcard.
+        let got = complete(&store.conn, "src/app.ts", 1, "Retry_", 10).unwrap();
+        assert_eq!(names(&got), vec![("Retry_", "project")]);
+        assert!(got[0].score > CompletionSource::Project.base() + 0.4);
+    }
+
+    #[test]
+    fn respects_limit() {
+        let store = setup();
+        assert_eq!(
+            complete(&store.conn, "src/app.ts", 14, "", 2)
+                .unwrap()
+                .len(),
+            2
+        );
+        assert!(complete(&store.conn, "src/app.ts", 14, "", 0)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn like_prefix_escapes_wildcards() {
+        assert_eq!(like_prefix("a_b%c\\"), "a\\_b\\%c\\\\%");
+        assert_eq!(like_prefix(""), "%");
+    }
+}
@@ -5,6 +5,9 @@
 
 use rusqlite::Connection;
 
+use crate::db::bodies::select_nodes;
+use crate::db::converters::row_to_code_node;
+
 // ---------------------------------------------------------------------------
 // Result type
 // ---------------------------------------------------------------------------
@@ -77,41 +80,31 @@ pub fn calculate_complexity(
 /// Reads node bodies from the database and computes metrics for each
 /// function/method node that has a body.
 pub fn calculate_all_complexities(conn: &Connection) -> Vec<ComplexityResult> {
-    let sql = "\
-        SELECT n.id, n.name, n.file_path, n.metadata
-        FROM nodes n
-        WHERE n.type IN ('function', 'method')";
+    let sql = select_nodes!("WHERE n.type IN ('function', 'method')");
 
     let mut stmt = match conn.prepare(sql) {
         Ok(s) => s,
         Err(_) => return Vec::new(),
     };
 
-    let rows = match stmt.query_map([], |row| {
-        let id: String = row.get(0)?;
-        let name: String = row.get(1)?;
-        let file_path: String = row.get(2)?;
-        let metadata_json: Option<String> = row.get(3)?;
-        Ok((id, name, file_path, metadata_json))
-    }) {
+    let rows = match stmt.query_map([], row_to_code_node) {
         Ok(r) => r,
         Err(_) => return Vec::new(),
     };
 
     let mut results = Vec::new();
-    for row in rows.flatten() {
-        let (id, name, file_path, metadata_json) = row;
-        let body = metadata_json
-            .as_deref()
-            .and_then(|s| serde_json::from_str::<serde_json::Value>(s).ok())
-            .and_then(|v| v.get("body").and_then(|b| b.as_str()).map(String::from))
-            .unwrap_or_default();
-
+    for node in rows.flatten() {
+        let body = node.body.unwrap_or_default();
         if body.is_empty() {
             continue;
         }
 
-        results.push(calculate_complexity(&name, &body, &file_path, &id));
+        results.push(calculate_complexity(
+            &node.name,
+            &body,
+            &node.file_path,
+            &node.id,
+        ));
     }
 
     results
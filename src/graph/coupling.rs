@@ -0,0 +1,366 @@
+//! Module coupling metrics per directory.
+//!
+//! Each directory is a module, and import edges between files of two
+//! modules couple them. Per module this computes the package metrics of
+//! Robert C. Martin:
+//!
+//! - afferent coupling `Ca`: other modules importing this one,
+//! - efferent coupling `Ce`: other modules this one imports,
+//! - instability `I = Ce / (Ca + Ce)`, from 0 (only depended on) to 1
+//!   (only depends on others); 0 for modules without imports either way,
+//! - abstractness `A`: the share of interfaces, traits, protocols and
+//!   abstract classes among the module's types,
+//! - distance from the main sequence `D = |A + I - 1|`: stable modules
+//!   should be abstract and unstable ones concrete, so a high `D` marks
+//!   concrete code everyone depends on or abstractions nobody uses.
+//!
+//! Modules above the thresholds in `architecture.coupling` are flagged:
+//!
+//! ```yaml
+//! architecture:
+//!   coupling:
+//!     max_efferent: 15
+//!     max_distance: 0.6
+//! ```
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::Result;
+use crate::graph::store::GraphStore;
+use crate::types::{EdgeKind, NodeKind};
+
+/// `max_efferent` when the config sets none.
+pub const DEFAULT_MAX_EFFERENT: usize = 20;
+
+/// `max_distance` when the config sets none.
+pub const DEFAULT_MAX_DISTANCE: f64 = 0.7;
+
+/// Node batch size when reading types.
+const BATCH_SIZE: usize = 500;
+
+// ---------------------------------------------------------------------------
+// Thresholds
+// ---------------------------------------------------------------------------
+
+/// Limits above which a module is flagged. Unset afferent and instability
+/// limits flag nothing; unset efferent and distance limits default to
+/// [`DEFAULT_MAX_EFFERENT`] and [`DEFAULT_MAX_DISTANCE`].
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CouplingThresholds {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_afferent: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_efferent: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_instability: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_distance: Option<f64>,
+}
+
+impl CouplingThresholds {
+    pub fn is_unset(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// The thresholds `module` exceeds, as `"efferent > 20"`.
+    pub fn exceeded(&self, module: &ModuleCoupling) -> Vec<String> {
+        let mut exceeded = Vec::new();
+        if let Some(max) = self.max_afferent.filter(|max| module.afferent > *max) {
+            exceeded.push(format!("afferent > {max}"));
+        }
+        let max_efferent = self.max_efferent.unwrap_or(DEFAULT_MAX_EFFERENT);
+        if module.efferent > max_efferent {
+            exceeded.push(format!("efferent > {max_efferent}"));
+        }
+        if let Some(max) = self.max_instability.filter(|max| module.instability > *max) {
+            exceeded.push(format!("instability > {max}"));
+        }
+        let max_distance = self.max_distance.unwrap_or(DEFAULT_MAX_DISTANCE);
+        if module.distance > max_distance {
+            exceeded.push(format!("distance > {max_distance}"));
+        }
+        exceeded
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Metrics
+// ---------------------------------------------------------------------------
+
+/// Coupling metrics of one module.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModuleCoupling {
+    pub module: String,
+    pub files: usize,
+    pub types: usize,
+    pub abstract_types: usize,
+    pub afferent: usize,
+    pub efferent: usize,
+    pub instability: f64,
+    pub abstractness: f64,
+    pub distance: f64,
+    /// Modules importing this one.
+    pub dependents: Vec<String>,
+    /// Modules this one imports.
+    pub dependencies: Vec<String>,
+}
+
+/// The module of a project-relative file: its directory, cut to the first
+/// `depth` components when given. Top-level files belong to `.`.
+pub fn module_of(file_path: &str, depth: Option<usize>) -> String {
+    let dir = match file_path.rfind('/') {
+        Some(i) => &file_path[..i],
+        None => return ".".to_string(),
+    };
+    match depth {
+        Some(depth) => dir
+            .split('/')
+            .take(depth.max(1))
+            .collect::<Vec<_>>()
+            .join("/"),
+        None => dir.to_string(),
+    }
+}
+
+/// Whether a type declaration is abstract: interfaces, traits and classes
+/// declared `abstract` or deriving from Python's `ABC`/`Protocol`.
+fn is_abstract(kind: NodeKind, signature: &str) -> bool {
+    match kind {
+        NodeKind::Interface | NodeKind::Trait => true,
+        NodeKind::Class => {
+            signature.split_whitespace().any(|w| w == "abstract")
+                || ["ABC", "ABCMeta", "Protocol"].iter().any(|base| {
+                    signature.contains(&format!("({base}"))
+                        || signature.contains(&format!(", {base}"))
+                        || signature.contains(&format!("={base}"))
+                })
+        }
+        _ => false,
+    }
+}
+
+const IMPORT_FILES_SQL: &str = "\
+SELECT DISTINCT s.file_path, t.file_path
+FROM edges e
+JOIN nodes s ON s.id = e.source_id
+JOIN nodes t ON t.id = e.target_id
+WHERE e.type = ?1 AND s.file_path != t.file_path";
+
+/// Coupling metrics of every module, in module order.
+pub fn module_coupling(store: &GraphStore, depth: Option<usize>) -> Result<Vec<ModuleCoupling>> {
+    let mut files: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+    let mut types: BTreeMap<String, (usize, usize)> = BTreeMap::new();
+    for batch in store.iter_nodes(BATCH_SIZE) {
+        for node in batch? {
+            let module = module_of(&node.file_path, depth);
+            if matches!(
+                node.kind,
+                NodeKind::Class | NodeKind::Struct | NodeKind::Interface | NodeKind::Trait
+            ) {
+                let signature = node
+                    .body
+                    .as_deref()
+                    .and_then(|b| b.lines().next())
+                    .unwrap_or_default();
+                let counts = types.entry(module.clone()).or_default();
+                counts.0 += 1;
+                if is_abstract(node.kind, signature) {
+                    counts.1 += 1;
+                }
+            }
+            files.entry(module).or_default().insert(node.file_path);
+        }
+    }
+
+    let mut dependencies: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+    let mut dependents: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+    let mut stmt = store.conn.prepare_cached(IMPORT_FILES_SQL)?;
+    let mut rows = stmt.query([EdgeKind::Imports.as_str()])?;
+    while let Some(row) = rows.next()? {
+        let from = module_of(&row.get::<_, String>(0)?, depth);
+        let to = module_of(&row.get::<_, String>(1)?, depth);
+        if from != to {
+            dependencies
+                .entry(from.clone())
+                .or_default()
+                .insert(to.clone());
+            dependents.entry(to).or_default().insert(from);
+        }
+    }
+
+    Ok(files
+        .into_iter()
+        .map(|(module, files)| {
+            let dependencies: Vec<String> = dependencies
+                .remove(&module)
+                .unwrap_or_default()
+                .into_iter()
+                .collect();
+            let dependents: Vec<String> = dependents
+                .remove(&module)
+                .unwrap_or_default()
+                .into_iter()
+                .collect();
+            let (afferent, efferent) = (dependents.len(), dependencies.len());
+            let instability = if afferent + efferent == 0 {
+                0.0
+            } else {
+                efferent as f64 / (afferent + efferent) as f64
+            };
+            let (type_count, abstract_types) = types.get(&module).copied().unwrap_or_default();
+            let abstractness = if type_count == 0 {
+                0.0
+            } else {
+                abstract_types as f64 / type_count as f64
+            };
+            ModuleCoupling {
+                module,
+                files: files.len(),
+                types: type_count,
+                abstract_types,
+                afferent,
+                efferent,
+                instability: round(instability),
+                abstractness: round(abstractness),
+                distance: round((abstractness + instability - 1.0).abs()),
+                dependents,
+                dependencies,
+            }
+        })
+        .collect())
+}
+
+fn round(value: f64) -> f64 {
+    (value * 1000.0).round() / 1000.0
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::schema::initialize_database;
+    use crate::types::{CodeEdge, CodeNode, Language};
+
+    fn node(id: &str, kind: NodeKind, file: &str, body: &str) -> CodeNode {
+        CodeNode {
+            id: id.to_string(),
+            name: id.to_string(),
+            qualified_name: None,
+            kind,
+            file_path: file.to_string(),
+            start_line: 1,
+            end_line: 9,
+            start_column: 0,
+            end_column: 0,
+            language: Language::TypeScript,
+            body: Some(body.to_string()),
+            documentation: None,
+            exported: None,
+        }
+    }
+
+    fn import(source: &str, target: &str, file: &str) -> CodeEdge {
+        CodeEdge {
+            source: source.to_string(),
+            target: target.to_string(),
+            kind: EdgeKind::Imports,
+            file_path: file.to_string(),
+            line: 1,
+            metadata: None,
+        }
+    }
+
+    #[test]
+    fn computes_martin_metrics_per_directory() {
+        let store = GraphStore::from_connection(initialize_database(":memory:").unwrap());
+        store
+            .upsert_nodes(&[
+                node(
+                    "repo",
+                    NodeKind::Interface,
+                    "src/core/repo.ts",
+                    "interface Repo {",
+                ),
+                node(
+                    "base",
+                    NodeKind::Class,
+                    "src/core/base.ts",
+                    "export abstract class Base {",
+                ),
+                node(
+                    "impl",
+                    NodeKind::Class,
+                    "src/db/impl.ts",
+                    "class SqlRepo implements Repo {",
+                ),
+                node(
+                    "ui",
+                    NodeKind::Function,
+                    "src/ui/page.ts",
+                    "function page() {",
+                ),
+                node("main", NodeKind::Function, "main.ts", "function main() {"),
+            ])
+            .unwrap();
+        store
+            .upsert_edges(&[
+                import("impl", "repo", "src/db/impl.ts"),
+                import("ui", "repo", "src/ui/page.ts"),
+                import("ui", "impl", "src/ui/page.ts"),
+                import("main", "ui", "main.ts"),
+            ])
+            .unwrap();
+
+        let modules = module_coupling(&store, None).unwrap();
+        let get = |name: &str| modules.iter().find(|m| m.module == name).unwrap();
+        let core = get("src/core");
+        assert_eq!((core.afferent, core.efferent), (2, 0));
+        assert_eq!((core.types, core.abstract_types), (2, 2));
+        assert_eq!(
+            (core.instability, core.abstractness, core.distance),
+            (0.0, 1.0, 0.0)
+        );
+        let db = get("src/db");
+        assert_eq!((db.afferent, db.efferent), (1, 1));
+        assert_eq!((db.instability, db.distance), (0.5, 0.5));
+        let ui = get("src/ui");
+        assert_eq!(ui.dependencies, vec!["src/core", "src/db"]);
+        assert_eq!(ui.dependents, vec!["."]);
+        assert_eq!(get(".").instability, 1.0);
+
+        let grouped = module_coupling(&store, Some(1)).unwrap();
+        let src = grouped.iter().find(|m| m.module == "src").unwrap();
+        assert_eq!((src.files, src.afferent, src.efferent), (4, 1, 0));
+
+        let strict = CouplingThresholds {
+            max_afferent: Some(1),
+            max_distance: Some(0.4),
+            ..Default::default()
+        };
+        assert_eq!(strict.exceeded(core), vec!["afferent > 1"]);
+        assert_eq!(strict.exceeded(db), vec!["distance > 0.4"]);
+        assert!(CouplingThresholds::default().exceeded(db).is_empty());
+    }
+
+    #[test]
+    fn recognizes_abstract_types() {
+        assert!(is_abstract(NodeKind::Trait, "pub trait Store {"));
+        assert!(is_abstract(
+            NodeKind::Class,
+            "public abstract class Shape {"
+        ));
+        assert!(is_abstract(NodeKind::Class, "class Base(ABC):"));
+        assert!(is_abstract(NodeKind::Class, "class Reader(Protocol):"));
+        assert!(is_abstract(NodeKind::Class, "class M(metaclass=ABCMeta):"));
+        assert!(!is_abstract(NodeKind::Class, "class Abstractions:"));
+        assert!(!is_abstract(NodeKind::Struct, "struct Store {"));
+        assert_eq!(module_of("src/a/b/c.ts", Some(2)), "src/a");
+        assert_eq!(module_of("lib.rs", None), ".");
+    }
+}
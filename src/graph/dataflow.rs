@@ -1,10 +1,16 @@
 //! Lightweight data flow analysis via regex-based heuristics.
 //!
 //! Provides def-use chain analysis, dead store detection, uninitialized
-//! variable detection, and reaching definition queries. Operates on raw
-//! source text without tree-sitter, making it fast and language-flexible.
+//! variable detection, reaching definition queries, and unused parameter
+//! and discarded return value checks. Operates on raw source text without
+//! tree-sitter, making it fast and language-flexible. Reaching definitions
+//! inside a function follow its [control-flow graph](super::cfg) instead of
+//! line order.
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use super::cfg::{self, BlockKind, CfgEdge, CfgEdgeKind, ControlFlowGraph};
+use crate::types::Language;
 
 // ---------------------------------------------------------------------------
 // Types
@@ -35,6 +41,20 @@ pub struct DeadStore {
     pub assigned_value: String,
 }
 
+/// A dereference of a variable that may be null on some path reaching it.
+#[derive(Debug, Clone)]
+pub struct NullDeref {
+    pub variable: String,
+    pub line: u32,
+    /// The dereferencing statement or condition.
+    pub expression: String,
+    /// Where the variable may have become null.
+    pub null_line: u32,
+    /// What made it null: a null literal, a call that returns null when
+    /// nothing is found, an optional parameter or a failed null check.
+    pub null_source: String,
+}
+
 // ---------------------------------------------------------------------------
 // Language-specific patterns
 // ---------------------------------------------------------------------------
@@ -76,6 +96,54 @@ fn patterns_for(language: &str) -> LangPatterns {
     }
 }
 
+/// How a language spells null and which calls commonly return it.
+struct NullSyntax {
+    literals: &'static [&'static str],
+    /// Calls that return null when nothing is found. `.get(` counts only
+    /// with a single argument, since a second one is a default.
+    nullable_calls: &'static [&'static str],
+    /// Whether `*x` dereferences and `x[i]` does not (Go).
+    pointers: bool,
+}
+
+fn null_syntax(language: &str) -> Option<NullSyntax> {
+    match language.to_lowercase().as_str() {
+        "python" => Some(NullSyntax {
+            literals: &["None"],
+            nullable_calls: &[
+                ".get(",
+                "re.match(",
+                "re.search(",
+                "re.fullmatch(",
+                ".match(",
+                ".search(",
+                ".fullmatch(",
+            ],
+            pointers: false,
+        }),
+        "javascript" | "jsx" | "typescript" | "tsx" => Some(NullSyntax {
+            literals: &["null", "undefined"],
+            nullable_calls: &[
+                ".find(",
+                ".get(",
+                ".getElementById(",
+                ".querySelector(",
+                ".match(",
+                ".exec(",
+                ".pop(",
+                ".shift(",
+            ],
+            pointers: false,
+        }),
+        "go" | "golang" => Some(NullSyntax {
+            literals: &["nil"],
+            nullable_calls: &[],
+            pointers: true,
+        }),
+        _ => None,
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Public API
 // ---------------------------------------------------------------------------
@@ -271,26 +339,562 @@ pub fn find_uninitialized_uses(source: &str, language: &str) -> Vec<Location> {
 
 /// Find which definitions reach a given use point.
 ///
-/// Returns def-use chains where at least one definition occurs before
-/// `target_line` and the variable is used at or near `target_line`.
+/// Returns def-use chains for variables used at or near `target_line`,
+/// keeping only the definitions that reach it. When `target_line` is inside
+/// a function, definitions flow along the function's control-flow graph: a
+/// redefinition on every path kills the earlier one, loop bodies reach their
+/// header through the back edge, and unreachable code contributes nothing.
+/// Elsewhere, or when the source cannot be parsed, any definition on or
+/// before `target_line` counts.
 pub fn find_reaching_defs(source: &str, language: &str, target_line: u32) -> Vec<DefUseChain> {
     let chains = find_def_use_chains(source, language);
-
+    let graph = Language::from_str_loose(language)
+        .and_then(|lang| cfg::enclosing_cfg(source, lang, target_line).ok().flatten());
+    let used_near_target = |chain: &DefUseChain| {
+        chain
+            .uses
+            .iter()
+            .any(|u| u.line >= target_line.saturating_sub(1) && u.line <= target_line + 1)
+    };
+
+    let Some((graph, first, last)) = graph else {
+        return chains
+            .into_iter()
+            .filter(|chain| {
+                chain.definitions.iter().any(|d| d.line <= target_line) && used_near_target(chain)
+            })
+            .collect();
+    };
+    let reaching = reaching_along_cfg(&graph, first, last, &chains, target_line);
     chains
         .into_iter()
-        .filter(|chain| {
-            // Has at least one def before target_line
-            let has_reaching_def = chain.definitions.iter().any(|d| d.line <= target_line);
-            // Is used at or near target_line (within 2 lines)
-            let used_near_target = chain
-                .uses
+        .enumerate()
+        .filter_map(|(c, mut chain)| {
+            chain
+                .definitions
+                .retain(|d| reaching.contains(&(c, d.line)));
+            (!chain.definitions.is_empty() && used_near_target(&chain)).then_some(chain)
+        })
+        .collect()
+}
+
+/// Definitions reaching `target_line` in `graph`, the control-flow graph of
+/// the function on lines `first..=last`, as `(chain index, line)` pairs.
+/// Definitions above the function enter at its entry; those below it or on
+/// lines outside every block are ignored.
+fn reaching_along_cfg(
+    graph: &ControlFlowGraph,
+    first: u32,
+    last: u32,
+    chains: &[DefUseChain],
+    target_line: u32,
+) -> BTreeSet<(usize, u32)> {
+    type Defs = BTreeSet<(usize, u32)>;
+    let reachable = graph.reachable();
+    let mut gen: Vec<Vec<(usize, u32)>> = vec![Vec::new(); graph.blocks.len()];
+    let mut before: HashMap<usize, u32> = HashMap::new();
+    for (c, chain) in chains.iter().enumerate() {
+        for def in &chain.definitions {
+            if def.line < first {
+                let line = before.entry(c).or_insert(def.line);
+                *line = (*line).max(def.line);
+            } else if def.line <= last {
+                if let Some(b) = graph.block_at(def.line) {
+                    gen[b].push((c, def.line));
+                }
+            }
+        }
+    }
+    for defs in &mut gen {
+        defs.sort_by_key(|&(_, line)| line);
+    }
+    // Each definition in `b` (up to `until`) replaces earlier ones of its variable.
+    let transfer = |b: usize, input: &Defs, until: Option<u32>| -> Defs {
+        let mut out = input.clone();
+        for &(c, line) in gen[b].iter().filter(|(_, l)| until.is_none_or(|u| *l < u)) {
+            out.retain(|&(v, _)| v != c);
+            out.insert((c, line));
+        }
+        out
+    };
+
+    let at_entry: Defs = before.into_iter().collect();
+    let mut ins: Vec<Defs> = vec![Defs::new(); graph.blocks.len()];
+    let mut outs: Vec<Defs> = (0..graph.blocks.len())
+        .map(|b| transfer(b, &Defs::new(), None))
+        .collect();
+    loop {
+        let mut changed = false;
+        for b in (0..graph.blocks.len()).filter(|b| reachable[*b]) {
+            let mut input = if b == graph.entry {
+                at_entry.clone()
+            } else {
+                Defs::new()
+            };
+            for edge in graph
+                .edges
                 .iter()
-                .any(|u| u.line >= target_line.saturating_sub(1) && u.line <= target_line + 1);
-            has_reaching_def && used_near_target
+                .filter(|e| e.to == b && reachable[e.from])
+            {
+                input.extend(outs[edge.from].iter().copied());
+            }
+            let out = transfer(b, &input, None);
+            if out != outs[b] {
+                outs[b] = out;
+                changed = true;
+            }
+            ins[b] = input;
+        }
+        if !changed {
+            break;
+        }
+    }
+    match graph.block_at(target_line) {
+        Some(t) if reachable[t] => transfer(t, &ins[t], Some(target_line)),
+        _ => Defs::new(),
+    }
+}
+
+/// Find dereferences of variables that may be null along some path.
+///
+/// Walks the control-flow graph of every function in `source`. A variable
+/// becomes nullable when assigned a null literal, a call that returns null
+/// when nothing is found (`d.get(k)`, `re.match(...)`, `arr.find(...)`), a
+/// nullable variable, or when it is an optional parameter; it stops being
+/// nullable when reassigned, dereferenced, or on the branch where a guard
+/// (`x is not None`, `x !== null`, `x != nil`, plain truthiness) proved it
+/// set. The other branch of an explicit null comparison makes it null.
+/// Guards earlier in the same expression (`x && x.y`) count too.
+///
+/// `None` for languages other than Python, JavaScript/TypeScript and Go.
+pub fn find_null_derefs(source: &str, language: &str) -> Option<Vec<NullDeref>> {
+    let syntax = null_syntax(language)?;
+    let functions = Language::from_str_loose(language)
+        .and_then(|lang| cfg::function_cfgs(source, lang).ok())
+        .unwrap_or_default();
+    let lines: Vec<&str> = source.lines().collect();
+    let patterns = patterns_for(language);
+    let mut found = Vec::new();
+    for (graph, first, last) in &functions {
+        // Bodies of nested functions are checked on their own.
+        let nested: Vec<(u32, u32)> = functions
+            .iter()
+            .map(|(_, f, l)| (*f, *l))
+            .filter(|&(f, l)| (f, l) != (*first, *last) && f >= *first && l <= *last)
+            .collect();
+        let checker = NullChecker {
+            graph,
+            syntax: &syntax,
+            patterns: &patterns,
+            lines: &lines,
+            nested,
+        };
+        found.extend(checker.run(*first));
+    }
+    found.sort_by(|a, b| (a.line, &a.variable).cmp(&(b.line, &b.variable)));
+    found.dedup_by(|a, b| a.line == b.line && a.variable == b.variable);
+    Some(found)
+}
+
+/// Parameters of a function that are never referenced in its body.
+///
+/// `body` is the full definition text and `name` the function's name; the
+/// parameter list is the first parenthesized group after the name, which
+/// skips decorators, annotations and Go receivers. Parameters named with a
+/// leading `_`, receivers (`self`, `this`), destructuring patterns and
+/// TypeScript/Kotlin parameter properties are never reported, nor is
+/// anything in stubs, overrides, or bodies that read `arguments`/`locals()`.
+pub fn find_unused_params(body: &str, name: &str, language: &str) -> Vec<String> {
+    let Some((start, end)) = param_list(body, name) else {
+        return Vec::new();
+    };
+    let rest = &body[end + 1..];
+    if is_stub(rest)
+        || body.contains("@Override")
+        || find_word(&body[..start], "override").is_some()
+        || find_word(rest, "arguments").is_some()
+        || rest.contains("locals()")
+    {
+        return Vec::new();
+    }
+    split_top_level(&body[start..end])
+        .into_iter()
+        .filter_map(|segment| param_name(segment, language))
+        .filter(|param| find_word(rest, param).is_none())
+        .collect()
+}
+
+/// The declared or inferred non-unit return type of a function, or `None`
+/// when it returns nothing (`void`, `()`, `None`, `Unit`, a constructor) or
+/// the return type cannot be determined.
+///
+/// Dynamic languages without an annotation count as returning a value when
+/// the body has a `return <expr>`; the type is then reported as `"value"`.
+pub fn return_value_type(body: &str, name: &str, language: &str) -> Option<String> {
+    let (_, end) = param_list(body, name)?;
+    let tail = body[end + 1..].lines().next().unwrap_or("");
+    let declared: Option<&str> = match language {
+        "rust" | "python" | "swift" => tail
+            .split_once("->")
+            .map(|(_, t)| cut_at(t, &["{", " where"]).trim_end_matches(':')),
+        "typescript" | "tsx" | "kotlin" | "scala" => tail
+            .trim_start()
+            .strip_prefix(':')
+            .map(|t| cut_at(t, &["{", "=>", " ="])),
+        "go" => Some(cut_at(tail, &["{"])),
+        "java" | "csharp" | "c#" | "c" | "cpp" | "c++" | "dart" => {
+            let name_pos = find_word(body, name)?;
+            let prefix = body[..name_pos].lines().last().unwrap_or("").trim();
+            let last = prefix.rsplit(char::is_whitespace).next().unwrap_or("");
+            Some(if is_modifier(last) { "" } else { last })
+        }
+        _ => None,
+    };
+    match declared.map(str::trim) {
+        Some(t) if is_unit_type(t) => None,
+        Some(t) => Some(t.to_string()),
+        None if matches!(
+            language,
+            "python" | "javascript" | "jsx" | "typescript" | "tsx" | "ruby" | "php" | "lua"
+        ) =>
+        {
+            body[end + 1..]
+                .lines()
+                .map(str::trim)
+                .any(|l| {
+                    l.strip_prefix("return ")
+                        .is_some_and(|e| !e.trim_end_matches(';').trim().is_empty())
+                        || l.starts_with("yield ")
+                })
+                .then(|| "value".to_string())
+        }
+        None => None,
+    }
+}
+
+/// Whether `line` calls `callee` as a bare statement, discarding its
+/// result: nothing before the call but its receiver (`obj.`, `Type::`) or
+/// `await`, and nothing after it but `;`, `?` or `.await`. Calls spanning
+/// several lines are not judged.
+pub fn discards_call_result(line: &str, callee: &str) -> bool {
+    let Some(pos) = line.match_indices(callee).map(|(pos, _)| pos).find(|&pos| {
+        is_word_at(line, pos, callee.len())
+            && line[pos + callee.len()..].trim_start().starts_with('(')
+    }) else {
+        return false;
+    };
+
+    // Strip the receiver chain: `self.`, `obj?.`, `Type::`, `ptr->`, `a.b().`.
+    let mut before = line[..pos].trim_end();
+    while let Some(rest) = ["?.", ".", "::", "->"]
+        .iter()
+        .find_map(|sep| before.strip_suffix(sep))
+    {
+        let mut rest = rest.trim_end();
+        if rest.ends_with(')') {
+            let Some(open) = matching_open_paren(rest) else {
+                return false;
+            };
+            rest = &rest[..open];
+        }
+        before = rest.trim_end_matches(is_ident_char).trim_end();
+    }
+    let before = before.strip_suffix("await").unwrap_or(before).trim_end();
+    if !(before.is_empty() || before.ends_with([';', '{', '}'])) {
+        return false;
+    }
+
+    let open = pos + callee.len() + line[pos + callee.len()..].find('(').unwrap_or(0);
+    let Some(close) = matching_paren(line, open) else {
+        return false;
+    };
+    let mut after = line[close + 1..].trim();
+    while let Some(rest) = after
+        .strip_prefix('?')
+        .or_else(|| after.strip_prefix(".await"))
+    {
+        after = rest.trim_start();
+    }
+    after.is_empty() || after.starts_with(';') || after.starts_with("//") || after.starts_with('#')
+}
+
+/// A function's parameters in declaration order, for mapping call
+/// arguments onto them. Receivers (`self`, `cls`, `this`) are dropped, since
+/// call sites do not pass them; parameters without a plain name
+/// (destructuring patterns, `_`-prefixed names) are `None`.
+pub fn positional_params(body: &str, name: &str, language: &str) -> Vec<Option<String>> {
+    let Some((start, end)) = param_list(body, name) else {
+        return Vec::new();
+    };
+    split_top_level(&body[start..end])
+        .into_iter()
+        .filter(|segment| {
+            let bare = segment.trim().trim_start_matches('&');
+            let bare = bare.strip_prefix("mut ").unwrap_or(bare);
+            !bare.is_empty()
+                && !["self", "cls", "this"]
+                    .iter()
+                    .any(|r| find_word(bare, r) == Some(0))
         })
+        .map(|segment| param_name(segment, language))
         .collect()
 }
 
+/// The arguments of the first call to `callee` on `line`, split on
+/// top-level commas. Calls spanning several lines are not parsed.
+pub fn call_arguments(line: &str, callee: &str) -> Option<Vec<String>> {
+    let pos = line
+        .match_indices(callee)
+        .map(|(pos, _)| pos)
+        .find(|&pos| {
+            is_word_at(line, pos, callee.len())
+                && line[pos + callee.len()..].trim_start().starts_with('(')
+        })?;
+    let open = pos + callee.len() + line[pos + callee.len()..].find('(')?;
+    let close = matching_paren(line, open)?;
+    let args = line[open + 1..close].trim();
+    if args.is_empty() {
+        return Some(Vec::new());
+    }
+    Some(
+        split_top_level(args)
+            .into_iter()
+            .map(|a| a.trim().to_string())
+            .collect(),
+    )
+}
+
+// ---------------------------------------------------------------------------
+// Internal: signatures
+// ---------------------------------------------------------------------------
+
+fn is_ident_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '$'
+}
+
+fn is_word_at(text: &str, pos: usize, len: usize) -> bool {
+    !text[..pos].chars().next_back().is_some_and(is_ident_char)
+        && !text[pos + len..].chars().next().is_some_and(is_ident_char)
+}
+
+/// Byte offset of the first whole-word occurrence of `word` in `text`.
+fn find_word(text: &str, word: &str) -> Option<usize> {
+    text.match_indices(word)
+        .map(|(pos, _)| pos)
+        .find(|&pos| is_word_at(text, pos, word.len()))
+}
+
+/// Truncate `text` at the first of `stops`.
+fn cut_at<'a>(text: &'a str, stops: &[&str]) -> &'a str {
+    let end = stops
+        .iter()
+        .filter_map(|s| text.find(s))
+        .min()
+        .unwrap_or(text.len());
+    &text[..end]
+}
+
+/// Index of the `)` matching the `(` at `open`.
+fn matching_paren(text: &str, open: usize) -> Option<usize> {
+    let mut depth = 0usize;
+    for (i, c) in text[open..].char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(open + i);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Index of the `(` matching the `)` that ends `text`.
+fn matching_open_paren(text: &str) -> Option<usize> {
+    let mut depth = 0usize;
+    for (i, c) in text.char_indices().rev() {
+        match c {
+            ')' => depth += 1,
+            '(' => {
+                depth = depth.checked_sub(1)?;
+                if depth == 0 {
+                    return Some(i);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Byte range inside the parentheses of a function's parameter list.
+fn param_list(body: &str, name: &str) -> Option<(usize, usize)> {
+    let after_name = find_word(body, name)? + name.len();
+    let open = after_name + body[after_name..].find('(')?;
+    // Only generics or an arrow-function assignment may sit in between;
+    // anything else means the function has no parenthesized parameters.
+    let between = body[after_name..open].trim();
+    if !(between.is_empty() || between.starts_with('<') || between.starts_with('=')) {
+        return None;
+    }
+    let close = matching_paren(body, open)?;
+    Some((open + 1, close))
+}
+
+/// Split a parameter list on top-level commas.
+fn split_top_level(params: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0i32;
+    let mut start = 0;
+    let mut prev = ' ';
+    for (i, c) in params.char_indices() {
+        match c {
+            '(' | '[' | '{' | '<' => depth += 1,
+            ')' | ']' | '}' => depth -= 1,
+            // `=>` and `->` are arrows, not closing generics.
+            '>' if prev != '=' && prev != '-' => depth -= 1,
+            ',' if depth <= 0 => {
+                parts.push(&params[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+        prev = c;
+    }
+    parts.push(&params[start..]);
+    parts
+}
+
+/// The name bound by one parameter declaration, if it should be checked.
+fn param_name(segment: &str, language: &str) -> Option<String> {
+    let words: Vec<&str> = segment
+        .split_whitespace()
+        .filter(|w| !w.starts_with('@'))
+        .collect();
+    let segment = words.join(" ");
+    let segment = segment.trim();
+    if segment.is_empty() || segment.starts_with(['{', '[', '(']) {
+        return None;
+    }
+    let first = words.first().copied().unwrap_or("");
+    let name = match language {
+        "python" => cut_at(segment, &[":", "="]).trim_start_matches('*'),
+        "javascript" | "jsx" | "typescript" | "tsx" => {
+            if matches!(
+                first,
+                "public" | "private" | "protected" | "readonly" | "override"
+            ) {
+                return None;
+            }
+            cut_at(segment, &["?", ":", "="]).trim_start_matches("...")
+        }
+        "rust" => {
+            let name = cut_at(segment, &[":"]);
+            let name = name.trim_start_matches('&').trim();
+            name.strip_prefix("mut ").unwrap_or(name)
+        }
+        "go" => first,
+        "kotlin" | "scala" | "swift" => {
+            if matches!(first, "val" | "var") {
+                return None;
+            }
+            cut_at(segment, &[":"])
+                .split_whitespace()
+                .last()
+                .unwrap_or("")
+        }
+        _ => cut_at(segment, &["="])
+            .trim_end()
+            .trim_end_matches("[]")
+            .rsplit(|c: char| c.is_whitespace() || c == '*' || c == '&')
+            .next()
+            .unwrap_or(""),
+    };
+    let name = name.trim();
+    let bare = name.strip_prefix('$').unwrap_or(name);
+    if bare.starts_with('_')
+        || !is_valid_identifier(bare)
+        || matches!(name, "self" | "cls" | "this" | "void")
+    {
+        return None;
+    }
+    Some(name.to_string())
+}
+
+/// Whether the part of a definition after its parameter list has no real
+/// statements: a declaration, `pass`, `...`, or a not-implemented throw.
+fn is_stub(rest: &str) -> bool {
+    let (first, tail) = rest.split_once('\n').unwrap_or((rest, ""));
+    let inner = if let Some((_, expr)) = first.split_once("=>") {
+        format!("{}\n{}", expr, tail)
+    } else if let Some(i) = first.rfind(['{', ':']) {
+        format!("{}\n{}", &first[i + 1..], tail)
+    } else if tail.trim().is_empty() {
+        return true;
+    } else {
+        tail.to_string()
+    };
+    // Drop docstrings, comments and closing braces.
+    let code: Vec<&str> = inner
+        .split("\"\"\"")
+        .step_by(2)
+        .flat_map(str::lines)
+        .map(|l| l.trim().trim_matches(|c| c == '}' || c == ';').trim())
+        .filter(|l| !l.is_empty() && !l.starts_with("//") && !l.starts_with('#'))
+        .collect();
+    match code.as_slice() {
+        [] => true,
+        [only] => {
+            matches!(*only, "pass" | "...")
+                || [
+                    "raise NotImplementedError",
+                    "unimplemented!",
+                    "todo!",
+                    "throw new UnsupportedOperationException",
+                    "throw new NotImplementedException",
+                    "throw new Error(\"Not implemented",
+                ]
+                .iter()
+                .any(|p| only.starts_with(p))
+        }
+        _ => false,
+    }
+}
+
+fn is_modifier(word: &str) -> bool {
+    matches!(
+        word,
+        "" | "public"
+            | "private"
+            | "protected"
+            | "internal"
+            | "static"
+            | "final"
+            | "abstract"
+            | "synchronized"
+            | "native"
+            | "virtual"
+            | "override"
+            | "sealed"
+            | "inline"
+            | "extern"
+            | "explicit"
+            | "async"
+    )
+}
+
+fn is_unit_type(ty: &str) -> bool {
+    matches!(
+        ty,
+        "" | "()" | "void" | "Void" | "None" | "Unit" | "never" | "undefined" | "!"
+    ) || ty.contains("<()")
+        || ty.contains("<void>")
+        || ty.contains("<Void>")
+}
+
 // ---------------------------------------------------------------------------
 // Internal: assignment extraction
 // ---------------------------------------------------------------------------
@@ -523,6 +1127,466 @@ fn is_keyword(s: &str) -> bool {
     )
 }
 
+// ---------------------------------------------------------------------------
+// Internal: null checks
+// ---------------------------------------------------------------------------
+
+/// Variables that may be null, with the line and reason they became so.
+type Nullable = BTreeMap<String, (u32, String)>;
+
+struct NullChecker<'a> {
+    graph: &'a ControlFlowGraph,
+    syntax: &'a NullSyntax,
+    patterns: &'a LangPatterns,
+    lines: &'a [&'a str],
+    /// Line ranges of functions nested in this one.
+    nested: Vec<(u32, u32)>,
+}
+
+impl NullChecker<'_> {
+    /// Propagate nullability to a fixpoint, then report dereferences with
+    /// the state reaching each block.
+    fn run(&self, first: u32) -> Vec<NullDeref> {
+        let graph = self.graph;
+        let reachable = graph.reachable();
+        let mut ins: Vec<Nullable> = vec![Nullable::new(); graph.blocks.len()];
+        ins[graph.entry] = self.nullable_params(first);
+        let mut outs: Vec<Option<Nullable>> = vec![None; graph.blocks.len()];
+        loop {
+            let mut changed = false;
+            for b in (0..graph.blocks.len()).filter(|b| reachable[*b]) {
+                let mut input = ins[b].clone();
+                for edge in graph.edges.iter().filter(|e| e.to == b) {
+                    if let Some(out) = &outs[edge.from] {
+                        merge(&mut input, self.along(edge, out.clone()));
+                    }
+                }
+                let out = self.transfer(b, input.clone(), None);
+                if outs[b].as_ref() != Some(&out) {
+                    outs[b] = Some(out);
+                    changed = true;
+                }
+                ins[b] = input;
+            }
+            if !changed {
+                break;
+            }
+        }
+        let mut found = Vec::new();
+        for b in (0..graph.blocks.len()).filter(|b| reachable[*b]) {
+            self.transfer(b, ins[b].clone(), Some(&mut found));
+        }
+        found
+    }
+
+    /// Parameters nullable on entry: Python parameters defaulting to `None`
+    /// or typed `Optional`, TypeScript optional parameters and parameters
+    /// typed `| null` or `| undefined`.
+    fn nullable_params(&self, first: u32) -> Nullable {
+        let body = self
+            .graph
+            .blocks
+            .iter()
+            .filter_map(|b| b.start_line)
+            .min()
+            .unwrap_or(first);
+        let end = body.max(first) as usize;
+        let signature = self
+            .lines
+            .get(first as usize - 1..end.min(self.lines.len()))
+            .unwrap_or_default()
+            .join("\n");
+        let mut params = Nullable::new();
+        let Some(open) = signature.find('(') else {
+            return params;
+        };
+        let Some(close) = matching_paren(&signature, open) else {
+            return params;
+        };
+        for segment in split_top_level(&signature[open + 1..close]) {
+            let segment = segment.trim();
+            let name = cut_at(segment, &["?", ":", "="])
+                .trim()
+                .trim_start_matches('*');
+            let nullable = segment.contains("?:")
+                || segment.contains("Optional[")
+                || segment.contains("| None")
+                || segment.contains("None |")
+                || segment.contains("| null")
+                || segment.contains("| undefined")
+                || self.syntax.literals.iter().any(|lit| {
+                    segment
+                        .rsplit_once('=')
+                        .is_some_and(|(_, default)| default.trim() == *lit)
+                });
+            if nullable && is_valid_identifier(name) {
+                params.insert(name.to_string(), (first, segment.to_string()));
+            }
+        }
+        params
+    }
+
+    /// The statements of block `b` as `(line, text)`: a condition or loop
+    /// head as written, other blocks as the source lines they cover.
+    fn block_lines(&self, b: usize) -> Vec<(u32, String)> {
+        let block = &self.graph.blocks[b];
+        let (Some(start), Some(end)) = (block.start_line, block.end_line) else {
+            return Vec::new();
+        };
+        match block.kind {
+            BlockKind::Condition | BlockKind::Loop | BlockKind::Switch => block
+                .statements
+                .first()
+                .map(|s| vec![(start, s.clone())])
+                .unwrap_or_default(),
+            _ => (start..=end)
+                .filter(|line| {
+                    !self
+                        .nested
+                        .iter()
+                        .any(|&(f, l)| f < l && *line > f && *line <= l)
+                })
+                .filter_map(|line| {
+                    let text = self.lines.get(line as usize - 1)?.trim();
+                    (!text.is_empty() && !text.starts_with("//") && !text.starts_with('#'))
+                        .then(|| (line, text.to_string()))
+                })
+                .collect(),
+        }
+    }
+
+    /// Run block `b` on `state`, recording nullable dereferences in `found`.
+    fn transfer(
+        &self,
+        b: usize,
+        mut state: Nullable,
+        mut found: Option<&mut Vec<NullDeref>>,
+    ) -> Nullable {
+        for (line, text) in self.block_lines(b) {
+            // Dereferences first: `x = x.next` reads before it writes.
+            for (var, pos) in dereferences(&text, self.syntax.pointers) {
+                let Some((null_line, null_source)) = state.remove(var) else {
+                    continue;
+                };
+                if guarded_before(&text[..pos], var, self.syntax) {
+                    state.insert(var.to_string(), (null_line, null_source));
+                    continue;
+                }
+                if let Some(found) = found.as_deref_mut() {
+                    found.push(NullDeref {
+                        variable: var.to_string(),
+                        line,
+                        expression: text.clone(),
+                        null_line,
+                        null_source,
+                    });
+                }
+            }
+            let assertion = text
+                .strip_prefix("assert ")
+                .or_else(|| text.strip_prefix("assert("));
+            if let Some(condition) = assertion {
+                let condition = condition.trim_end_matches(';').trim_end_matches(')');
+                apply_facts(
+                    &mut state,
+                    guard_facts(condition, self.syntax, true),
+                    line,
+                    condition,
+                );
+            }
+            if let Some((var, value, _)) = extract_assignment(&text, self.patterns) {
+                match self.null_value(&text, &value, &state) {
+                    Some(source) => {
+                        state.insert(var, (line, source));
+                    }
+                    None => {
+                        state.remove(&var);
+                    }
+                }
+            }
+        }
+        state
+    }
+
+    /// `state` as it leaves along `edge`, narrowed by the guard of a
+    /// condition or loop head.
+    fn along(&self, edge: &CfgEdge, mut state: Nullable) -> Nullable {
+        let from = &self.graph.blocks[edge.from];
+        if !matches!(from.kind, BlockKind::Condition | BlockKind::Loop) {
+            return state;
+        }
+        let outcome = match edge.kind {
+            CfgEdgeKind::True | CfgEdgeKind::Back => true,
+            CfgEdgeKind::False => false,
+            _ => return state,
+        };
+        if let (Some(condition), Some(line)) = (from.statements.first(), from.start_line) {
+            apply_facts(
+                &mut state,
+                guard_facts(condition, self.syntax, outcome),
+                line,
+                condition,
+            );
+        }
+        state
+    }
+
+    /// Why assigning `value` may leave a variable null, `None` when it
+    /// cannot.
+    fn null_value(&self, statement: &str, value: &str, state: &Nullable) -> Option<String> {
+        let value = value.trim().trim_end_matches(';').trim();
+        if value.is_empty() {
+            // A declaration without a value: `let x;`, or `var p *T` in Go.
+            return match self.syntax.literals {
+                ["nil"] => statement.contains('*').then(|| "nil pointer".to_string()),
+                _ => Some("undefined".to_string()),
+            };
+        }
+        for lit in self.syntax.literals {
+            let conditional = value.ends_with(&format!(" {lit}"))
+                && (value.contains(" else ") || value.contains(':'));
+            if value == *lit || conditional {
+                return Some(value.to_string());
+            }
+        }
+        // `d.get(k) or {}` and `arr.find(f) ?? fallback` have defaults.
+        let defaulted = ["??", "||", " or "].iter().any(|op| value.contains(op));
+        if !defaulted
+            && self.syntax.nullable_calls.iter().any(|call| {
+                value.contains(call)
+                    && (*call != ".get("
+                        || call_arguments(value, "get").is_some_and(|a| a.len() == 1))
+            })
+        {
+            return Some(value.to_string());
+        }
+        state.get(value).map(|(_, source)| source.clone())
+    }
+}
+
+/// Union of two states, keeping the earliest source of each variable.
+fn merge(into: &mut Nullable, from: Nullable) {
+    for (var, (line, source)) in from {
+        match into.get(&var) {
+            Some((existing, _)) if *existing <= line => {}
+            _ => {
+                into.insert(var, (line, source));
+            }
+        }
+    }
+}
+
+/// Apply `(variable, is_null)` facts proved by `condition` on `line`.
+fn apply_facts(state: &mut Nullable, facts: Vec<(String, bool)>, line: u32, condition: &str) {
+    for (var, null) in facts {
+        if null {
+            state.insert(var, (line, condition.to_string()));
+        } else {
+            state.remove(&var);
+        }
+    }
+}
+
+/// What `condition` proves about variables when it evaluates to `outcome`,
+/// as `(variable, is_null)` pairs. Conjunctions prove their guards when
+/// true, disjunctions the negation of theirs when false.
+fn guard_facts(condition: &str, syntax: &NullSyntax, outcome: bool) -> Vec<(String, bool)> {
+    let condition = strip_parens(condition.trim());
+    let disjuncts = split_logical(condition, &["||", " or "]);
+    let conjuncts = split_logical(condition, &["&&", " and "]);
+    let atoms = match (disjuncts.len() > 1, conjuncts.len() > 1) {
+        (true, _) if !outcome => disjuncts,
+        (false, true) if outcome => conjuncts,
+        (false, false) => vec![condition],
+        _ => return Vec::new(),
+    };
+    atoms
+        .into_iter()
+        .filter_map(|atom| null_guard(atom, syntax))
+        .filter_map(|(var, set_when, explicit)| {
+            if set_when == outcome {
+                Some((var, false))
+            } else {
+                explicit.then_some((var, true))
+            }
+        })
+        .collect()
+}
+
+/// The variable a single check tests, whether the check holding means it
+/// is set, and whether it compares with null explicitly (so the opposite
+/// outcome means it is null): `x` and `x !== null` prove `x` set, `not x`
+/// and `x is None` prove it unset.
+fn null_guard(atom: &str, syntax: &NullSyntax) -> Option<(String, bool, bool)> {
+    let atom = strip_parens(atom.trim());
+    let negated = atom
+        .strip_prefix("not ")
+        .or_else(|| atom.strip_prefix('!').filter(|rest| !rest.starts_with('=')));
+    if let Some(rest) = negated {
+        return null_guard(rest, syntax).map(|(var, set, explicit)| (var, !set, explicit));
+    }
+    if is_valid_identifier(atom) && !is_keyword(atom) {
+        return Some((atom.to_string(), true, false));
+    }
+    if let Some(args) = atom.strip_prefix("isinstance(") {
+        let var = args.split(',').next()?.trim();
+        return is_valid_identifier(var).then(|| (var.to_string(), true, false));
+    }
+    let is_null = |text: &str| {
+        syntax.literals.contains(&text) || matches!(text, "\"undefined\"" | "'undefined'")
+    };
+    for (op, set) in [
+        (" is not ", true),
+        (" is ", false),
+        ("!==", true),
+        ("===", false),
+        ("!=", true),
+        ("==", false),
+    ] {
+        let Some(pos) = atom.find(op) else {
+            continue;
+        };
+        let (lhs, rhs) = (atom[..pos].trim(), atom[pos + op.len()..].trim());
+        let var = match (is_null(lhs), is_null(rhs)) {
+            (false, true) => lhs,
+            (true, false) => rhs,
+            _ => return None,
+        };
+        let var = var.strip_prefix("typeof ").unwrap_or(var).trim();
+        return is_valid_identifier(var).then(|| (var.to_string(), set, true));
+    }
+    None
+}
+
+/// Whether `prefix`, the part of a statement before a dereference of
+/// `var`, already checked it: `x && x.y`, `x is None or x.y`, `x ? x.y : d`.
+fn guarded_before(prefix: &str, var: &str, syntax: &NullSyntax) -> bool {
+    let checked = |separators: &[&str], set: bool| {
+        let pieces = split_logical(prefix, separators);
+        pieces[..pieces.len() - 1].iter().any(|piece| {
+            null_guard(last_operand(piece), syntax).is_some_and(|(v, s, _)| v == var && s == set)
+        })
+    };
+    checked(&["&&", " and ", " ? "], true) || checked(&["||", " or "], false)
+}
+
+/// The operand a logical operator applies to at the end of `text`, without
+/// a leading keyword, assignment or unclosed call.
+fn last_operand(text: &str) -> &str {
+    let mut text = text.trim();
+    for keyword in ["return ", "if ", "elif ", "while ", "assert "] {
+        text = text.strip_prefix(keyword).unwrap_or(text);
+    }
+    let mut depth = 0i32;
+    for (i, c) in text.char_indices().rev() {
+        match c {
+            ')' => depth += 1,
+            '(' if depth == 0 => {
+                text = &text[i + 1..];
+                break;
+            }
+            '(' => depth -= 1,
+            _ => {}
+        }
+    }
+    match text.rfind(" = ").or_else(|| text.rfind(" := ")) {
+        Some(pos) => text[pos..].trim_start_matches([' ', ':', '=']).trim(),
+        None => text.trim(),
+    }
+}
+
+/// `text` without parentheses wrapping all of it.
+fn strip_parens(mut text: &str) -> &str {
+    while text.starts_with('(') && matching_paren(text, 0) == Some(text.len() - 1) {
+        text = text[1..text.len() - 1].trim();
+    }
+    text
+}
+
+/// Split `text` on top-level occurrences of any of `separators`.
+fn split_logical<'a>(text: &'a str, separators: &[&str]) -> Vec<&'a str> {
+    let mut parts = Vec::new();
+    let mut depth = 0i32;
+    let mut start = 0;
+    let mut i = 0;
+    while i < text.len() {
+        let rest = &text[i..];
+        match rest.chars().next() {
+            Some('(' | '[' | '{') => depth += 1,
+            Some(')' | ']' | '}') => depth -= 1,
+            _ if depth == 0 => {
+                if let Some(sep) = separators.iter().find(|s| rest.starts_with(**s)) {
+                    parts.push(text[start..i].trim());
+                    i += sep.len();
+                    start = i;
+                    continue;
+                }
+            }
+            _ => {}
+        }
+        i += rest.chars().next().map_or(1, char::len_utf8);
+    }
+    parts.push(text[start..].trim());
+    parts
+}
+
+/// Variables dereferenced in `text` with their byte offsets: `x.attr` and
+/// `x[i]`, or `x.attr` and `*x` with `pointers`. Optional chaining
+/// (`x?.y`), non-null assertions (`x!.y`), attributes of other values
+/// (`a.x.y`) and string contents are skipped.
+fn dereferences(text: &str, pointers: bool) -> Vec<(&str, usize)> {
+    let bytes = text.as_bytes();
+    let is_ident = |b: u8| b.is_ascii_alphanumeric() || b == b'_' || b == b'$';
+    let mut found = Vec::new();
+    let mut quote: Option<u8> = None;
+    let mut i = 0;
+    while i < bytes.len() {
+        let c = bytes[i];
+        if let Some(q) = quote {
+            if c == b'\\' {
+                i += 1;
+            } else if c == q {
+                quote = None;
+            }
+            i += 1;
+            continue;
+        }
+        if matches!(c, b'"' | b'\'' | b'`') {
+            quote = Some(c);
+            i += 1;
+            continue;
+        }
+        if !(c.is_ascii_alphabetic() || c == b'_' || c == b'$') {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        while i < bytes.len() && is_ident(bytes[i]) {
+            i += 1;
+        }
+        let prev = start.checked_sub(1).map(|p| bytes[p]);
+        if prev == Some(b'.') {
+            continue;
+        }
+        let member = match bytes.get(i) {
+            Some(b'.') => bytes
+                .get(i + 1)
+                .is_some_and(|b| b.is_ascii_alphabetic() || *b == b'_'),
+            Some(b'[') => !pointers,
+            _ => false,
+        };
+        let star = pointers
+            && prev == Some(b'*')
+            && start
+                .checked_sub(2)
+                .is_none_or(|p| !is_ident(bytes[p]) && !matches!(bytes[p], b')' | b']'));
+        if member || star {
+            found.push((&text[start..i], start));
+        }
+    }
+    found
+}
+
 // ---------------------------------------------------------------------------
 // Tests
 // ---------------------------------------------------------------------------
@@ -975,6 +2039,129 @@ let result = a + b + c;";
         assert!(reaching.iter().any(|c| c.variable == "c"));
     }
 
+    #[test]
+    fn reaching_defs_skip_unreachable_code() {
+        let source = "\
+function f(flag) {
+  let x = 1;
+  if (flag) {
+    return 0;
+    x = 2;
+  }
+  return x;
+}";
+        let reaching = find_reaching_defs(source, "javascript", 7);
+        let x = reaching.iter().find(|c| c.variable == "x").unwrap();
+        let lines: Vec<u32> = x.definitions.iter().map(|d| d.line).collect();
+        assert_eq!(lines, vec![2]);
+    }
+
+    #[test]
+    fn reaching_defs_merge_branches_and_kill_overwritten() {
+        let source = "\
+function g(flag) {
+  let y = 0;
+  if (flag) {
+    y = 1;
+  } else {
+    y = 2;
+  }
+  return y;
+}";
+        let reaching = find_reaching_defs(source, "javascript", 8);
+        let y = reaching.iter().find(|c| c.variable == "y").unwrap();
+        let lines: Vec<u32> = y.definitions.iter().map(|d| d.line).collect();
+        assert_eq!(lines, vec![4, 6]);
+    }
+
+    #[test]
+    fn reaching_defs_follow_loop_back_edge() {
+        let source = "\
+function h(n) {
+  let i = 0;
+  while (i < n) {
+    i = i + 1;
+  }
+  return i;
+}";
+        for target in [3, 6] {
+            let reaching = find_reaching_defs(source, "javascript", target);
+            let i = reaching.iter().find(|c| c.variable == "i").unwrap();
+            let lines: Vec<u32> = i.definitions.iter().map(|d| d.line).collect();
+            assert_eq!(lines, vec![2, 4], "target line {target}");
+        }
+    }
+
+    // -- null dereferences --------------------------------------------------
+
+    fn null_derefs(source: &str, language: &str) -> Vec<(u32, String, u32)> {
+        find_null_derefs(source, language)
+            .unwrap()
+            .into_iter()
+            .map(|d| (d.line, d.variable, d.null_line))
+            .collect()
+    }
+
+    #[test]
+    fn null_derefs_python_guards_and_optional_params() {
+        let source = r#"def first_word(text, sep=None):
+    m = re.match(r"\w+", text)
+    if m is None:
+        return sep.join([])
+    word = m.group(0)
+    d = {}.get(word)
+    if d and d.size:
+        pass
+    return d.size
+"#;
+        assert_eq!(
+            null_derefs(source, "python"),
+            vec![(4, "sep".to_string(), 1), (9, "d".to_string(), 6)]
+        );
+    }
+
+    #[test]
+    fn null_derefs_typescript_skip_optional_chaining() {
+        let source = r#"function render(user?: User) {
+  const el = document.querySelector(".name");
+  el.textContent = user.name;
+  let label: string | null = null;
+  if (user !== null && user !== undefined) {
+    label = user.name;
+  }
+  return label.length + user?.id;
+}
+"#;
+        assert_eq!(
+            null_derefs(source, "typescript"),
+            vec![
+                (3, "el".to_string(), 2),
+                (3, "user".to_string(), 1),
+                (8, "label".to_string(), 4),
+            ]
+        );
+    }
+
+    #[test]
+    fn null_derefs_go_nil_pointers_on_some_paths() {
+        let source = "\
+func name(u *User) string {
+\tvar p *Profile
+\tif u.Active {
+\t\tp = u.Profile
+\t}
+\ttitle := p.Title
+\tvar q *Profile
+\tif q == nil {
+\t\treturn title
+\t}
+\treturn (*q).Name
+}
+";
+        assert_eq!(null_derefs(source, "go"), vec![(6, "p".to_string(), 2)]);
+        assert!(find_null_derefs(source, "rust").is_none());
+    }
+
     #[test]
     fn reaching_defs_empty_source() {
         let reaching = find_reaching_defs("", "javascript", 1);
@@ -1130,4 +2317,135 @@ let middle = 3;";
             );
         }
     }
+
+    // -------------------------------------------------------------------
+    // find_unused_params / return_value_type / discards_call_result
+    // -------------------------------------------------------------------
+
+    #[test]
+    fn unused_params_across_languages() {
+        let ts = "export function total(items: Item[], taxRate?: number, _opts = {}) {\n  return items.length;\n}";
+        assert_eq!(
+            find_unused_params(ts, "total", "typescript"),
+            vec!["taxRate"]
+        );
+
+        let py = "@cache\ndef load(self, path, *args, verbose=False):\n    return open(path)";
+        assert_eq!(
+            find_unused_params(py, "load", "python"),
+            vec!["args", "verbose"]
+        );
+
+        let rs = "pub fn apply(&mut self, mut delta: i32, ctx: &Ctx) -> i32 {\n    delta += 1;\n    delta\n}";
+        assert_eq!(find_unused_params(rs, "apply", "rust"), vec!["ctx"]);
+
+        let go =
+            "func (s *Server) Handle(w http.ResponseWriter, r *http.Request) {\n\tw.Write(nil)\n}";
+        assert_eq!(find_unused_params(go, "Handle", "go"), vec!["r"]);
+
+        let java = "@Override\npublic void run(String arg) {\n}";
+        assert!(find_unused_params(java, "run", "java").is_empty());
+        let java = "public int size(final List<String> items, int limit) {\n  return limit;\n}";
+        assert_eq!(find_unused_params(java, "size", "java"), vec!["items"]);
+
+        // Stubs and declarations keep their parameters.
+        assert!(find_unused_params("def hook(event):\n    pass", "hook", "python").is_empty());
+        assert!(find_unused_params("fn visit(&self, node: &Node);", "visit", "rust").is_empty());
+        assert!(find_unused_params(
+            "constructor(private readonly repo: Repo) {}",
+            "constructor",
+            "typescript"
+        )
+        .is_empty());
+    }
+
+    #[test]
+    fn return_value_types() {
+        assert_eq!(
+            return_value_type("fn parse(s: &str) -> Result<Ast, Error> {", "parse", "rust")
+                .as_deref(),
+            Some("Result<Ast, Error>")
+        );
+        assert_eq!(
+            return_value_type("fn save(&self) -> io::Result<()> {", "save", "rust"),
+            None
+        );
+        assert_eq!(
+            return_value_type("def size(self) -> int:\n    return 1", "size", "python").as_deref(),
+            Some("int")
+        );
+        assert_eq!(
+            return_value_type("def size(self):\n    return len(self.x)", "size", "python")
+                .as_deref(),
+            Some("value")
+        );
+        assert_eq!(
+            return_value_type("def log(msg):\n    print(msg)\n    return", "log", "python"),
+            None
+        );
+        assert_eq!(
+            return_value_type(
+                "async send(msg: string): Promise<void> {",
+                "send",
+                "typescript"
+            ),
+            None
+        );
+        assert_eq!(
+            return_value_type("func Open(path string) (*File, error) {", "Open", "go").as_deref(),
+            Some("(*File, error)")
+        );
+        assert_eq!(
+            return_value_type("public static List<String> names(int n) {", "names", "java")
+                .as_deref(),
+            Some("List<String>")
+        );
+        assert_eq!(
+            return_value_type("public Order(int id) {", "Order", "java"),
+            None
+        );
+    }
+
+    #[test]
+    fn discarded_call_results() {
+        assert!(discards_call_result("    validate(input);", "validate"));
+        assert!(discards_call_result("  self.repo.save(order)?;", "save"));
+        assert!(!discards_call_result(
+            "  await client.fetch(url).then(x);",
+            "fetch"
+        ));
+        assert!(discards_call_result("  await client.fetch(url);", "fetch"));
+        assert!(discards_call_result("  Parser::parse(src);", "parse"));
+        assert!(!discards_call_result("  let ast = parse(src);", "parse"));
+        assert!(!discards_call_result("  return parse(src);", "parse"));
+        assert!(!discards_call_result("  let _ = parse(src);", "parse"));
+        assert!(!discards_call_result("  if (validate(x)) {", "validate"));
+        assert!(!discards_call_result("  defer f.Close()", "Close"));
+        assert!(!discards_call_result("  use(parse(src));", "parse"));
+    }
+
+    #[test]
+    fn call_arguments_map_onto_positional_params() {
+        assert_eq!(
+            positional_params("def run(self, cmd, _opts, *args):", "run", "python"),
+            vec![Some("cmd".to_string()), None, Some("args".to_string())]
+        );
+        assert_eq!(
+            positional_params("fn exec(&mut self, query: &str) {", "exec", "rust"),
+            vec![Some("query".to_string())]
+        );
+        assert_eq!(
+            positional_params("function f({ a }, b) {", "f", "javascript"),
+            vec![None, Some("b".to_string())]
+        );
+        assert!(positional_params("function f() {", "f", "javascript").is_empty());
+
+        assert_eq!(
+            call_arguments("  db.run(build(a, b), \"x\");", "run"),
+            Some(vec!["build(a, b)".to_string(), "\"x\"".to_string()])
+        );
+        assert_eq!(call_arguments("  rerun(a)", "run"), None);
+        assert_eq!(call_arguments("  run()", "run"), Some(Vec::new()));
+        assert_eq!(call_arguments("  run(a,", "run"), None);
+    }
 }
@@ -0,0 +1,294 @@
+//! Human-readable dossier for a single node.
+//!
+//! Gathers a node's definition, documentation, relations, metrics and git
+//! history in one pass so IDs that show up in agent transcripts can be
+//! investigated without writing queries. Used by `codegraph explain`.
+
+use std::fmt::Write as _;
+
+use crate::error::Result;
+use crate::git::NodeGitMeta;
+use crate::graph::complexity::calculate_complexity;
+use crate::graph::store::GraphStore;
+use crate::types::{CodeEdge, CodeNode, NodeKind};
+
+/// Relations listed per direction before the rest are summarized.
+const MAX_LISTED_RELATIONS: usize = 20;
+
+/// A node at the other end of an edge.
+#[derive(Debug, Clone)]
+pub struct Relation {
+    /// Edge kind, e.g. `calls` or `imports`.
+    pub edge: String,
+    pub id: String,
+    pub name: String,
+    pub file_path: String,
+    pub line: u32,
+}
+
+/// Size, fan-in/out and complexity of a node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DossierMetrics {
+    pub line_count: u32,
+    pub fan_in: usize,
+    pub fan_out: usize,
+    /// Only computed for functions and methods with a stored body.
+    pub cyclomatic: Option<u32>,
+    pub cognitive: Option<u32>,
+}
+
+/// Everything known about one node.
+#[derive(Debug, Clone)]
+pub struct Dossier {
+    pub node: CodeNode,
+    pub incoming: Vec<Relation>,
+    pub outgoing: Vec<Relation>,
+    pub metrics: DossierMetrics,
+    pub git: Option<NodeGitMeta>,
+}
+
+/// Build the dossier for `node_ref`, a node ID or (failing that) a symbol
+/// name. Returns `None` when nothing matches.
+pub fn build_dossier(store: &GraphStore, node_ref: &str) -> Result<Option<Dossier>> {
+    let node = match store.get_node(node_ref)? {
+        Some(node) => node,
+        None => match store.get_nodes_by_name(node_ref)?.into_iter().next() {
+            Some(node) => node,
+            None => return Ok(None),
+        },
+    };
+
+    let incoming = relations(store, &store.get_in_edges(&node.id, None)?, |e| &e.source)?;
+    let outgoing = relations(store, &store.get_out_edges(&node.id, None)?, |e| &e.target)?;
+
+    let complexity = match (node.kind, node.body.as_deref()) {
+        (NodeKind::Function | NodeKind::Method, Some(body)) if !body.is_empty() => Some(
+            calculate_complexity(&node.name, body, &node.file_path, &node.id),
+        ),
+        _ => None,
+    };
+    let metrics = DossierMetrics {
+        line_count: node.end_line.saturating_sub(node.start_line) + 1,
+        fan_in: incoming.len(),
+        fan_out: outgoing.len(),
+        cyclomatic: complexity.as_ref().map(|c| c.cyclomatic),
+        cognitive: complexity.as_ref().map(|c| c.cognitive),
+    };
+    let git = store.get_node_git_meta(&node.id)?;
+
+    Ok(Some(Dossier {
+        node,
+        incoming,
+        outgoing,
+        metrics,
+        git,
+    }))
+}
+
+fn relations(
+    store: &GraphStore,
+    edges: &[CodeEdge],
+    other_end: impl Fn(&CodeEdge) -> &String,
+) -> Result<Vec<Relation>> {
+    let mut out = Vec::with_capacity(edges.len());
+    for edge in edges {
+        let id = other_end(edge);
+        let (name, file_path, line) = match store.get_node(id)? {
+            Some(n) => (n.name, n.file_path, n.start_line),
+            None => (id.clone(), String::new(), 0),
+        };
+        out.push(Relation {
+            edge: edge.kind.as_str().to_string(),
+            id: id.clone(),
+            name,
+            file_path,
+            line,
+        });
+    }
+    out.sort_by(|a, b| (&a.edge, &a.name).cmp(&(&b.edge, &b.name)));
+    Ok(out)
+}
+
+impl Dossier {
+    /// Render as plain text for a terminal. `now` (Unix seconds) is used
+    /// to express the last change as an age.
+    pub fn render(&self, now: i64) -> String {
+        let n = &self.node;
+        let mut out = String::new();
+        let _ = writeln!(out, "{} {} ({})", n.kind, n.name, n.language.as_str());
+        let _ = writeln!(out, "  id:        {}", n.id);
+        if let Some(ref qn) = n.qualified_name {
+            let _ = writeln!(out, "  qualified: {}", qn);
+        }
+        let _ = writeln!(
+            out,
+            "  defined:   {}:{}-{}",
+            n.file_path, n.start_line, n.end_line
+        );
+        if let Some(exported) = n.exported {
+            let _ = writeln!(out, "  exported:  {}", if exported { "yes" } else { "no" });
+        }
+
+        if let Some(sig) = n.body.as_deref().and_then(|b| b.lines().next()) {
+            if !sig.trim().is_empty() {
+                let _ = writeln!(out, "\nSignature\n  {}", sig.trim());
+            }
+        }
+        if let Some(ref doc) = n.documentation {
+            let _ = writeln!(out, "\nDocumentation");
+            for line in doc.lines() {
+                let _ = writeln!(out, "  {}", line);
+            }
+        }
+
+        let m = &self.metrics;
+        let _ = writeln!(out, "\nMetrics");
+        let _ = write!(
+            out,
+            "  lines {} · fan-in {} · fan-out {}",
+            m.line_count, m.fan_in, m.fan_out
+        );
+        if let (Some(cc), Some(cog)) = (m.cyclomatic, m.cognitive) {
+            let _ = write!(out, " · cyclomatic {} · cognitive {}", cc, cog);
+        }
+        out.push('\n');
+
+        render_relations(&mut out, "Incoming", "<-", &self.incoming);
+        render_relations(&mut out, "Outgoing", "->", &self.outgoing);
+
+        if let Some(ref git) = self.git {
+            let date = chrono::DateTime::from_timestamp(git.committed_at, 0)
+                .map(|dt| dt.format("%Y-%m-%d").to_string())
+                .unwrap_or_default();
+            let short = &git.commit_hash[..git.commit_hash.len().min(8)];
+            let _ = writeln!(
+                out,
+                "\nHistory\n  last changed {} by {} <{}> ({}, {} days ago)",
+                date,
+                git.author,
+                git.email,
+                short,
+                crate::git::node_meta::age_in_days(git.committed_at, now)
+            );
+        }
+        out
+    }
+}
+
+fn render_relations(out: &mut String, title: &str, arrow: &str, relations: &[Relation]) {
+    if relations.is_empty() {
+        return;
+    }
+    let _ = writeln!(out, "\n{} ({})", title, relations.len());
+    for r in relations.iter().take(MAX_LISTED_RELATIONS) {
+        if r.file_path.is_empty() {
+            let _ = writeln!(out, "  {:<12} {} {}", r.edge, arrow, r.name);
+        } else {
+            let _ = writeln!(
+                out,
+                "  {:<12} {} {} ({}:{})",
+                r.edge, arrow, r.name, r.file_path, r.line
+            );
+        }
+    }
+    if relations.len() > MAX_LISTED_RELATIONS {
+        let _ = writeln!(
+            out,
+            "  ... and {} more",
+            relations.len() - MAX_LISTED_RELATIONS
+        );
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::schema::initialize_database;
+    use crate::types::{EdgeKind, Language};
+
+    fn node(id: &str, name: &str, line: u32, body: &str) -> CodeNode {
+        CodeNode {
+            id: id.to_string(),
+            name: name.to_string(),
+            qualified_name: None,
+            kind: NodeKind::Function,
+            file_path: "src/a.ts".to_string(),
+            start_line: line,
+            end_line: line + 4,
+            start_column: 0,
+            end_column: 0,
+            language: Language::TypeScript,
+            body: Some(body.to_string()),
+            documentation: Some("Parses the config.".to_string()),
+            exported: Some(true),
+        }
+    }
+
+    fn edge(source: &str, target: &str) -> CodeEdge {
+        CodeEdge {
+            source: source.to_string(),
+            target: target.to_string(),
+            kind: EdgeKind::Calls,
+            file_path: "src/a.ts".to_string(),
+            line: 1,
+            metadata: None,
+        }
+    }
+
+    fn setup() -> GraphStore {
+        let store = GraphStore::from_connection(initialize_database(":memory:").unwrap());
+        store
+            .upsert_nodes(&[
+                node(
+                    "fn:parse",
+                    "parse",
+                    10,
+                    "function parse(s) {\n  if (s) { return 1; }\n}",
+                ),
+                node("fn:main", "main", 1, "function main() { parse(); }"),
+                node("fn:read", "read", 20, "function read() {}"),
+            ])
+            .unwrap();
+        store
+            .upsert_edges(&[edge("fn:main", "fn:parse"), edge("fn:parse", "fn:read")])
+            .unwrap();
+        store
+    }
+
+    #[test]
+    fn builds_dossier_by_id_or_name() {
+        let store = setup();
+        let by_id = build_dossier(&store, "fn:parse").unwrap().unwrap();
+        let by_name = build_dossier(&store, "parse").unwrap().unwrap();
+        assert_eq!(by_id.node.id, by_name.node.id);
+
+        assert_eq!(by_id.incoming.len(), 1);
+        assert_eq!(by_id.incoming[0].name, "main");
+        assert_eq!(by_id.outgoing[0].name, "read");
+        assert_eq!(by_id.metrics.fan_in, 1);
+        assert_eq!(by_id.metrics.line_count, 5);
+        assert_eq!(by_id.metrics.cyclomatic, Some(2));
+
+        assert!(build_dossier(&store, "missing").unwrap().is_none());
+    }
+
+    #[test]
+    fn render_includes_every_section() {
+        let store = setup();
+        let text = build_dossier(&store, "fn:parse")
+            .unwrap()
+            .unwrap()
+            .render(0);
+        assert!(text.starts_with("function parse (typescript)"));
+        assert!(text.contains("src/a.ts:10-14"));
+        assert!(text.contains("Parses the config."));
+        assert!(text.contains("Incoming (1)"));
+        assert!(text.contains("<- main (src/a.ts:1)"));
+        assert!(text.contains("-> read (src/a.ts:20)"));
+        assert!(text.contains("cyclomatic 2"));
+    }
+}
@@ -0,0 +1,351 @@
+//! Duplicate symbol definitions.
+//!
+//! Finds functions, methods and types that are defined more than once under
+//! the same qualified name — copy-pasted utilities, helpers shadowed by a
+//! local redefinition, the same class vendored into two packages. Each
+//! group carries pairwise body similarity so the near-identical copies that
+//! can be consolidated stand out from unrelated code that merely shares a
+//! name.
+//!
+//! Similarity is the weighted Jaccard index of the bodies' token bags, after
+//! comment lines are dropped: 1.0 for copies that differ only in whitespace
+//! or comments, near 0 for bodies with nothing in common.
+
+use std::collections::HashMap;
+
+use crate::error::Result;
+use crate::graph::store::GraphStore;
+use crate::security::rules::is_test_file;
+use crate::types::{CodeNode, NodeKind};
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// How closely the definitions in a group match.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DuplicateClass {
+    /// Every body is the same once comments and whitespace are ignored.
+    Identical,
+    /// The least similar pair reaches [`NEAR_DUPLICATE`].
+    NearDuplicate,
+    /// Same name, different code.
+    Divergent,
+}
+
+impl DuplicateClass {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Identical => "identical",
+            Self::NearDuplicate => "near_duplicate",
+            Self::Divergent => "divergent",
+        }
+    }
+
+    pub fn from_str_loose(s: &str) -> Option<Self> {
+        match s {
+            "identical" | "exact" => Some(Self::Identical),
+            "near_duplicate" | "near" => Some(Self::NearDuplicate),
+            "divergent" => Some(Self::Divergent),
+            _ => None,
+        }
+    }
+}
+
+/// Similarity of two definitions in a group, as indices into `members`.
+#[derive(Debug, Clone)]
+pub struct PairSimilarity {
+    pub a: usize,
+    pub b: usize,
+    pub similarity: f64,
+}
+
+/// Definitions sharing one qualified name.
+#[derive(Debug, Clone)]
+pub struct DuplicateGroup {
+    /// The shared qualified name (the plain name when none is recorded).
+    pub name: String,
+    /// Ordered by file path, then line.
+    pub members: Vec<CodeNode>,
+    /// Every pair of members, most similar first.
+    pub pairs: Vec<PairSimilarity>,
+    pub max_similarity: f64,
+    pub min_similarity: f64,
+    pub class: DuplicateClass,
+}
+
+/// Lowest pairwise similarity for a group to count as a near duplicate.
+pub const NEAR_DUPLICATE: f64 = 0.8;
+
+/// Node batch size when reading definitions.
+const BATCH_SIZE: usize = 500;
+
+/// Names that are conventionally defined once per program, test or class
+/// and are not sprawl when repeated.
+const CONVENTIONAL_NAMES: &[&str] = &[
+    "main",
+    "init",
+    "__init__",
+    "constructor",
+    "setUp",
+    "tearDown",
+    "setup",
+    "teardown",
+    "run",
+    "new",
+    "default",
+];
+
+// ---------------------------------------------------------------------------
+// Similarity
+// ---------------------------------------------------------------------------
+
+/// Token bag of a body: identifiers, numbers and single punctuation
+/// characters, skipping comment lines.
+fn token_bag(body: &str) -> HashMap<&str, usize> {
+    let mut bag = HashMap::new();
+    for line in body.lines() {
+        let line = line.trim();
+        if ["//", "#", "/*", "*", "--"]
+            .iter()
+            .any(|c| line.starts_with(c))
+        {
+            continue;
+        }
+        let mut start = None;
+        for (i, c) in line.char_indices() {
+            let word = c.is_alphanumeric() || c == '_';
+            match (word, start) {
+                (true, None) => start = Some(i),
+                (false, Some(s)) => {
+                    *bag.entry(&line[s..i]).or_insert(0) += 1;
+                    start = None;
+                }
+                _ => {}
+            }
+            if !word && !c.is_whitespace() {
+                *bag.entry(&line[i..i + c.len_utf8()]).or_insert(0) += 1;
+            }
+        }
+        if let Some(s) = start {
+            *bag.entry(&line[s..]).or_insert(0) += 1;
+        }
+    }
+    bag
+}
+
+/// Weighted Jaccard similarity of two token bags, in `0.0..=1.0`.
+fn bag_similarity(a: &HashMap<&str, usize>, b: &HashMap<&str, usize>) -> f64 {
+    let (mut shared, mut total) = (0, 0);
+    for (tok, &n) in a {
+        let m = b.get(tok).copied().unwrap_or(0);
+        shared += n.min(m);
+        total += n.max(m);
+    }
+    total += b
+        .iter()
+        .filter(|(tok, _)| !a.contains_key(*tok))
+        .map(|(_, &m)| m)
+        .sum::<usize>();
+    if total == 0 {
+        1.0
+    } else {
+        shared as f64 / total as f64
+    }
+}
+
+/// Number of tokens in a body, counted like [`body_similarity`] does.
+pub fn token_count(body: &str) -> usize {
+    token_bag(body).values().sum()
+}
+
+/// Similarity of two bodies, see the module docs.
+pub fn body_similarity(a: &str, b: &str) -> f64 {
+    bag_similarity(&token_bag(a), &token_bag(b))
+}
+
+// ---------------------------------------------------------------------------
+// Detection
+// ---------------------------------------------------------------------------
+
+/// Functions and methods group together, types together: a function and a
+/// class that share a name are not copies of each other.
+fn kind_family(kind: NodeKind) -> Option<&'static str> {
+    match kind {
+        NodeKind::Function | NodeKind::Method => Some("function"),
+        NodeKind::Class
+        | NodeKind::Struct
+        | NodeKind::Interface
+        | NodeKind::Trait
+        | NodeKind::Enum => Some("type"),
+        _ => None,
+    }
+}
+
+/// Every qualified name defined more than once, most members and most
+/// similar first. Definitions in test files are ignored unless
+/// `include_tests` is set.
+pub fn find_duplicate_definitions(
+    store: &GraphStore,
+    include_tests: bool,
+) -> Result<Vec<DuplicateGroup>> {
+    let mut groups: HashMap<(&'static str, String), Vec<CodeNode>> = HashMap::new();
+    for batch in store.iter_nodes(BATCH_SIZE) {
+        for n in batch? {
+            let Some(family) = kind_family(n.kind) else {
+                continue;
+            };
+            if CONVENTIONAL_NAMES.contains(&n.name.as_str())
+                || (!include_tests && is_test_file(&n.file_path))
+            {
+                continue;
+            }
+            let name = n.qualified_name.clone().unwrap_or_else(|| n.name.clone());
+            groups.entry((family, name)).or_default().push(n);
+        }
+    }
+
+    let mut result: Vec<DuplicateGroup> = groups
+        .into_iter()
+        .filter(|(_, members)| members.len() > 1)
+        .map(|((_, name), mut members)| {
+            members.sort_by(|a, b| {
+                a.file_path
+                    .cmp(&b.file_path)
+                    .then(a.start_line.cmp(&b.start_line))
+            });
+            group(name, members)
+        })
+        .collect();
+    result.sort_by(|a, b| {
+        b.members
+            .len()
+            .cmp(&a.members.len())
+            .then(b.max_similarity.total_cmp(&a.max_similarity))
+            .then(a.name.cmp(&b.name))
+    });
+    Ok(result)
+}
+
+fn group(name: String, members: Vec<CodeNode>) -> DuplicateGroup {
+    let bags: Vec<_> = members
+        .iter()
+        .map(|m| token_bag(m.body.as_deref().unwrap_or("")))
+        .collect();
+    let mut pairs = Vec::new();
+    for a in 0..members.len() {
+        for b in a + 1..members.len() {
+            pairs.push(PairSimilarity {
+                a,
+                b,
+                similarity: bag_similarity(&bags[a], &bags[b]),
+            });
+        }
+    }
+    pairs.sort_by(|x, y| y.similarity.total_cmp(&x.similarity));
+    let max_similarity = pairs.first().map_or(0.0, |p| p.similarity);
+    let min_similarity = pairs.last().map_or(0.0, |p| p.similarity);
+    let class = if min_similarity >= 1.0 {
+        DuplicateClass::Identical
+    } else if min_similarity >= NEAR_DUPLICATE {
+        DuplicateClass::NearDuplicate
+    } else {
+        DuplicateClass::Divergent
+    };
+    DuplicateGroup {
+        name,
+        members,
+        pairs,
+        max_similarity,
+        min_similarity,
+        class,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::schema::initialize_database;
+    use crate::types::Language;
+
+    fn node(id: &str, name: &str, kind: NodeKind, file: &str, body: &str) -> CodeNode {
+        CodeNode {
+            id: id.to_string(),
+            name: name.to_string(),
+            qualified_name: None,
+            kind,
+            file_path: file.to_string(),
+            start_line: 1,
+            end_line: body.lines().count() as u32,
+            start_column: 0,
+            end_column: 0,
+            language: Language::TypeScript,
+            body: Some(body.to_string()),
+            documentation: None,
+            exported: None,
+        }
+    }
+
+    #[test]
+    fn body_similarity_ignores_comments_and_whitespace() {
+        let a = "function clamp(x, lo, hi) {\n  return Math.min(hi, Math.max(lo, x));\n}";
+        let b = "function clamp(x, lo, hi) {\n  // keep x in range\n  return Math.min(hi,Math.max(lo,x));\n}";
+        assert_eq!(body_similarity(a, b), 1.0);
+        let c = "function clamp(value) {\n  throw new Error('unsupported');\n}";
+        assert!(body_similarity(a, c) < 0.5);
+    }
+
+    #[test]
+    fn finds_groups_and_classifies_them() {
+        let conn = initialize_database(":memory:").unwrap();
+        let store = GraphStore::from_connection(conn);
+        let slug = "function slugify(s) {\n  return s.toLowerCase().replace(/ /g, '-');\n}";
+        store
+            .upsert_nodes(&[
+                node("f:a", "slugify", NodeKind::Function, "a/util.ts", slug),
+                node("f:b", "slugify", NodeKind::Function, "b/util.ts", slug),
+                node(
+                    "f:c",
+                    "parse",
+                    NodeKind::Function,
+                    "a/parse.ts",
+                    "function parse(s) {\n  return JSON.parse(s);\n}",
+                ),
+                node(
+                    "f:d",
+                    "parse",
+                    NodeKind::Function,
+                    "b/parse.ts",
+                    "function parse(line) {\n  const [k, v] = line.split('=');\n  return { k, v };\n}",
+                ),
+                // A class and a function sharing a name are not duplicates.
+                node("c:e", "Parser", NodeKind::Class, "a/p.ts", "class Parser {}"),
+                node("f:f", "Parser", NodeKind::Function, "b/p.ts", "function Parser() {}"),
+                // Conventional names and test files are skipped.
+                node("f:g", "main", NodeKind::Function, "a/main.ts", "function main() {}"),
+                node("f:h", "main", NodeKind::Function, "b/main.ts", "function main() {}"),
+                node("f:i", "slugify", NodeKind::Function, "a/util.test.ts", slug),
+            ])
+            .unwrap();
+
+        let groups = find_duplicate_definitions(&store, false).unwrap();
+        assert_eq!(groups.len(), 2);
+        let slugify = groups.iter().find(|g| g.name == "slugify").unwrap();
+        assert_eq!(slugify.members.len(), 2);
+        assert_eq!(slugify.class, DuplicateClass::Identical);
+        let parse = groups.iter().find(|g| g.name == "parse").unwrap();
+        assert_eq!(parse.class, DuplicateClass::Divergent);
+        assert!(parse.max_similarity < NEAR_DUPLICATE);
+
+        let with_tests = find_duplicate_definitions(&store, true).unwrap();
+        let slugify = with_tests.iter().find(|g| g.name == "slugify").unwrap();
+        assert_eq!(slugify.members.len(), 3);
+        assert_eq!(slugify.pairs.len(), 3);
+        // The largest group sorts first.
+        assert_eq!(with_tests[0].name, "slugify");
+    }
+}
@@ -0,0 +1,555 @@
+//! Entry points: where execution starts.
+//!
+//! Found from the graph and the source lines around symbols:
+//!
+//! - `main`: functions and methods named `main`,
+//! - `route`: HTTP handlers, either decorated or annotated
+//!   (`@app.get("/users")`, `@GetMapping`, NestJS `@Get()`, `#[get("/")]`,
+//!   `[HttpGet]`) or registered by name (`app.get('/users', listUsers)`,
+//!   axum `.route("/users", get(list_users))`, Django
+//!   `path("users/", views.users)`),
+//! - `command`: CLI commands — clap `#[derive(Parser)]`/`Subcommand` types,
+//!   click and typer `@cli.command()` functions, commander
+//!   `.command('build').action(build)` and argparse
+//!   `set_defaults(func=handler)` handlers,
+//! - `package_export`: exported symbols of a package's root module
+//!   (`lib.rs`, `index.ts`, `__init__.py`, ...) that is not nested inside
+//!   another package root.
+//!
+//! Decorators and registrations are read from the source files, so without
+//! a project root only `main` functions and package exports are found.
+//! Test files are skipped.
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+use regex::Regex;
+use serde::Serialize;
+
+use crate::error::Result;
+use crate::graph::api_docs::SourceCache;
+use crate::graph::store::GraphStore;
+use crate::security::rules::is_test_file;
+use crate::types::{CodeNode, NodeKind};
+
+/// Node batch size when scanning the graph.
+const BATCH_SIZE: usize = 500;
+
+/// File names of a package's root module.
+const ROOT_FILES: &[&str] = &[
+    "lib.rs",
+    "index.ts",
+    "index.tsx",
+    "index.js",
+    "index.mjs",
+    "index.cjs",
+    "__init__.py",
+];
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// How execution reaches an entry point.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EntryKind {
+    Main,
+    Route,
+    Command,
+    PackageExport,
+}
+
+impl EntryKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Main => "main",
+            Self::Route => "route",
+            Self::Command => "command",
+            Self::PackageExport => "package_export",
+        }
+    }
+
+    pub fn from_str_loose(s: &str) -> Option<Self> {
+        match s {
+            "main" => Some(Self::Main),
+            "route" | "routes" | "http" => Some(Self::Route),
+            "command" | "commands" | "cli" => Some(Self::Command),
+            "package_export" | "package" | "export" | "exports" => Some(Self::PackageExport),
+            _ => None,
+        }
+    }
+}
+
+/// A symbol where execution starts.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EntryPoint {
+    pub kind: EntryKind,
+    pub id: String,
+    pub name: String,
+    pub symbol_kind: NodeKind,
+    pub file_path: String,
+    pub line: u32,
+    /// What marks it: `GET /users`, `clap Subcommand`, the package root.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+impl EntryPoint {
+    fn new(kind: EntryKind, node: &CodeNode, detail: Option<String>) -> Self {
+        Self {
+            kind,
+            id: node.id.clone(),
+            name: node.name.clone(),
+            symbol_kind: node.kind,
+            file_path: node.file_path.clone(),
+            line: node.start_line,
+            detail,
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Markers
+// ---------------------------------------------------------------------------
+
+/// Route decorators and annotations: (pattern, HTTP method when the
+/// pattern has no verb group). Group 1 is the verb, group 2 the path.
+fn route_markers() -> &'static [Regex] {
+    static RE: OnceLock<Vec<Regex>> = OnceLock::new();
+    RE.get_or_init(|| {
+        [
+            // Flask, FastAPI, Sanic: @app.get("/x"), @router.post(path="/x")
+            r#"^@\w+(?:\.\w+)*\.(get|post|put|delete|patch|head|options|route|api_route|websocket)\(\s*(?:path\s*=\s*)?(?:['"]([^'"]*)['"])?"#,
+            // Spring, NestJS, JAX-RS: @GetMapping("/x"), @Get(':id'), @GET
+            r#"^@(Get|Post|Put|Delete|Patch|Request|GET|POST|PUT|DELETE|PATCH)(?:Mapping)?\b(?:\(\s*(?:(?:value|path)\s*=\s*)?\{?\s*['"]([^'"]*)['"])?"#,
+            // actix-web, Rocket: #[get("/x")]
+            r#"^#\[(get|post|put|delete|patch|head|route)\(\s*"([^"]*)""#,
+            // ASP.NET: [HttpGet("x")]
+            r#"^\[Http(Get|Post|Put|Delete|Patch)(?:\(\s*"([^"]*)")?"#,
+        ]
+        .iter()
+        .map(|p| Regex::new(p).expect("valid regex"))
+        .collect()
+    })
+}
+
+fn command_marker() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(
+            r"^(?:#\[derive\([^)]*\b(Parser|Subcommand)\b|@\w+(?:\.\w+)*\.(command|group)\b)",
+        )
+        .expect("valid regex")
+    })
+}
+
+/// Registrations naming their handler: group 1 the verb, 2 the path, 3
+/// the handler.
+fn route_registrations() -> &'static [Regex] {
+    static RE: OnceLock<Vec<Regex>> = OnceLock::new();
+    RE.get_or_init(|| {
+        [
+            // Express, Koa, Fastify: app.get('/x', auth, handler)
+            r#"\b\w+\.(get|post|put|delete|patch|all|head|options)\(\s*['"`](/[^'"`]*)['"`]\s*,\s*(?:[\w$.]+\s*,\s*)*([A-Za-z_$][\w$]*(?:\.[A-Za-z_$][\w$]*)*)\s*\)"#,
+            // axum: .route("/x", get(handler))
+            r#"\.route\(\s*"(/[^"]*)"\s*,\s*(get|post|put|delete|patch|any)\(\s*([\w:]+)\s*\)"#,
+        ]
+        .iter()
+        .map(|p| Regex::new(p).expect("valid regex"))
+        .collect()
+    })
+}
+
+fn django_path() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(r#"\b(?:re_)?path\(\s*r?['"]([^'"]*)['"]\s*,\s*([\w.]+)"#).expect("valid regex")
+    })
+}
+
+fn command_registrations() -> &'static [Regex] {
+    static RE: OnceLock<Vec<Regex>> = OnceLock::new();
+    RE.get_or_init(|| {
+        [
+            // commander: .command('build <dir>').action(build)
+            r#"\.command\(\s*['"]([^'"\s]+)[^'"]*['"]\s*\).*?\.action\(\s*([A-Za-z_$][\w$]*)\s*\)"#,
+            // argparse: sub.set_defaults(func=handler)
+            r"()set_defaults\(\s*func\s*=\s*([\w.]+)",
+        ]
+        .iter()
+        .map(|p| Regex::new(p).expect("valid regex"))
+        .collect()
+    })
+}
+
+/// The route a decorator or annotation line declares, as `GET /users`.
+fn route_detail(line: &str) -> Option<String> {
+    route_markers().iter().find_map(|re| {
+        let caps = re.captures(line)?;
+        let verb = caps.get(1)?.as_str().to_ascii_uppercase();
+        let method = match verb.as_str() {
+            "ROUTE" | "API_ROUTE" | "REQUEST" => "ANY".to_string(),
+            _ => verb,
+        };
+        Some(match caps.get(2) {
+            Some(path) => format!("{method} {}", path.as_str()),
+            None => method,
+        })
+    })
+}
+
+/// The CLI framework a decorator or attribute line marks a command with.
+fn command_detail(line: &str) -> Option<String> {
+    let caps = command_marker().captures(line)?;
+    Some(match (caps.get(1), caps.get(2)) {
+        (Some(derive), _) => format!("clap {}", derive.as_str()),
+        (_, Some(kind)) => format!("@{}", line[1..].split('(').next().unwrap_or(kind.as_str())),
+        _ => return None,
+    })
+}
+
+/// The lines marking a symbol: those above it, plus the head of its body up
+/// to the line naming it, where Java and C# keep their annotations.
+fn marker_lines(sources: &mut SourceCache, node: &CodeNode) -> Vec<String> {
+    let mut lines = sources.leading_lines(node);
+    if let Some(body) = &node.body {
+        lines.extend(
+            body.lines()
+                .take_while(|l| !l.contains(node.name.as_str()))
+                .take(10)
+                .map(str::to_string),
+        );
+    }
+    lines
+}
+
+/// The last segment of a dotted or `::` path: `views.users` → `users`.
+fn last_segment(path: &str) -> &str {
+    path.rsplit(['.', ':']).next().unwrap_or(path)
+}
+
+// ---------------------------------------------------------------------------
+// Discovery
+// ---------------------------------------------------------------------------
+
+/// Every entry point of the indexed project, in kind, file and line order.
+/// `root` is the project root the indexed paths are relative to.
+pub fn find_entrypoints(store: &GraphStore, root: Option<PathBuf>) -> Result<Vec<EntryPoint>> {
+    let mut sources = SourceCache::new(root);
+    let mut entries: Vec<EntryPoint> = Vec::new();
+    let mut callables: HashMap<String, Vec<CodeNode>> = HashMap::new();
+    let mut files: BTreeSet<String> = BTreeSet::new();
+    let mut exports: HashMap<String, Vec<CodeNode>> = HashMap::new();
+
+    for batch in store.iter_nodes(BATCH_SIZE) {
+        for mut node in batch? {
+            if is_test_file(&node.file_path) {
+                continue;
+            }
+            files.insert(node.file_path.clone());
+            let file_name = node.file_path.rsplit('/').next().unwrap_or_default();
+            match node.kind {
+                NodeKind::Function | NodeKind::Method => {
+                    if node.name == "main" {
+                        entries.push(EntryPoint::new(EntryKind::Main, &node, None));
+                    }
+                    for line in marker_lines(&mut sources, &node) {
+                        let line = line.trim();
+                        if let Some(route) = route_detail(line) {
+                            entries.push(EntryPoint::new(EntryKind::Route, &node, Some(route)));
+                        } else if let Some(command) = command_detail(line) {
+                            entries.push(EntryPoint::new(EntryKind::Command, &node, Some(command)));
+                        }
+                    }
+                }
+                NodeKind::Class | NodeKind::Struct | NodeKind::Enum => {
+                    for line in sources.leading_lines(&node) {
+                        if let Some(command) = command_detail(line.trim()) {
+                            entries.push(EntryPoint::new(EntryKind::Command, &node, Some(command)));
+                        }
+                    }
+                }
+                _ => {}
+            }
+            let exported = node.exported == Some(true)
+                || (file_name == "__init__.py" && !node.name.starts_with('_'));
+            let exportable = !matches!(
+                node.kind,
+                NodeKind::Method | NodeKind::Property | NodeKind::Module | NodeKind::Namespace
+            );
+            if exported && exportable && ROOT_FILES.contains(&file_name) {
+                node.body = None;
+                exports
+                    .entry(node.file_path.clone())
+                    .or_default()
+                    .push(node.clone());
+            }
+            if matches!(node.kind, NodeKind::Function | NodeKind::Method) {
+                node.body = None;
+                callables.entry(node.name.clone()).or_default().push(node);
+            }
+        }
+    }
+
+    // Handlers registered by name, resolved in the registering file first
+    let resolve = |name: &str, file: &str| -> Option<&CodeNode> {
+        let candidates = callables.get(last_segment(name))?;
+        candidates
+            .iter()
+            .find(|n| n.file_path == file)
+            .or_else(|| (candidates.len() == 1).then(|| &candidates[0]))
+    };
+    for file in &files {
+        let Some(lines) = sources.lines(file) else {
+            continue;
+        };
+        let django = file.ends_with("urls.py");
+        for line in lines {
+            for re in route_registrations() {
+                for caps in re.captures_iter(line) {
+                    // axum puts the path before the verb
+                    let (verb, path) = if caps[1].starts_with('/') {
+                        (&caps[2], &caps[1])
+                    } else {
+                        (&caps[1], &caps[2])
+                    };
+                    if let Some(handler) = resolve(&caps[3], file) {
+                        let method = match verb.to_ascii_uppercase().as_str() {
+                            "ALL" => "ANY".to_string(),
+                            other => other.to_string(),
+                        };
+                        entries.push(EntryPoint::new(
+                            EntryKind::Route,
+                            handler,
+                            Some(format!("{method} {path}")),
+                        ));
+                    }
+                }
+            }
+            if django {
+                for caps in django_path().captures_iter(line) {
+                    if let Some(handler) = resolve(&caps[2], file) {
+                        entries.push(EntryPoint::new(
+                            EntryKind::Route,
+                            handler,
+                            Some(format!("ANY /{}", &caps[1])),
+                        ));
+                    }
+                }
+            }
+            for re in command_registrations() {
+                for caps in re.captures_iter(line) {
+                    if let Some(handler) = resolve(&caps[2], file) {
+                        let detail = match &caps[1] {
+                            "" => "argparse subcommand".to_string(),
+                            name => format!("command {name}"),
+                        };
+                        entries.push(EntryPoint::new(EntryKind::Command, handler, Some(detail)));
+                    }
+                }
+            }
+        }
+    }
+
+    // Exports of package roots not nested in another package root
+    let root_dirs: HashSet<&str> = exports
+        .keys()
+        .map(|f| f.rsplit_once('/').map_or("", |(dir, _)| dir))
+        .collect();
+    for (file, nodes) in &exports {
+        let dir = file.rsplit_once('/').map_or("", |(dir, _)| dir);
+        let nested = std::iter::successors(Some(dir), |d| {
+            (!d.is_empty()).then(|| d.rsplit_once('/').map_or("", |(parent, _)| parent))
+        })
+        .skip(1)
+        .any(|ancestor| root_dirs.contains(ancestor));
+        if nested {
+            continue;
+        }
+        for node in nodes {
+            entries.push(EntryPoint::new(
+                EntryKind::PackageExport,
+                node,
+                Some(format!("exported from {file}")),
+            ));
+        }
+    }
+
+    entries.sort_by(|a, b| {
+        (a.kind, &a.file_path, a.line, &a.detail).cmp(&(b.kind, &b.file_path, b.line, &b.detail))
+    });
+    entries.dedup_by(|a, b| a.kind == b.kind && a.id == b.id && a.detail == b.detail);
+    Ok(entries)
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::schema::initialize_database;
+    use crate::types::Language;
+
+    fn node(kind: NodeKind, name: &str, file: &str, line: u32, body: &str) -> CodeNode {
+        CodeNode {
+            id: format!("{}:{file}:{name}:{line}", kind.as_str()),
+            name: name.to_string(),
+            qualified_name: None,
+            kind,
+            file_path: file.to_string(),
+            start_line: line,
+            end_line: line + 1,
+            start_column: 0,
+            end_column: 0,
+            language: Language::TypeScript,
+            body: Some(body.to_string()),
+            documentation: None,
+            exported: None,
+        }
+    }
+
+    #[test]
+    fn recognizes_route_and_command_markers() {
+        assert_eq!(
+            route_detail(r#"@app.get("/users/{id}")"#).as_deref(),
+            Some("GET /users/{id}")
+        );
+        assert_eq!(
+            route_detail("@bp.route('/login', methods=['POST'])").as_deref(),
+            Some("ANY /login")
+        );
+        assert_eq!(
+            route_detail(r#"@PostMapping("/items")"#).as_deref(),
+            Some("POST /items")
+        );
+        assert_eq!(route_detail("@Get(':id')").as_deref(), Some("GET :id"));
+        assert_eq!(
+            route_detail(r#"#[get("/health")]"#).as_deref(),
+            Some("GET /health")
+        );
+        assert_eq!(route_detail("[HttpDelete]").as_deref(), Some("DELETE"));
+        assert_eq!(route_detail("@property"), None);
+        assert_eq!(route_detail("@Getter"), None);
+
+        assert_eq!(
+            command_detail("#[derive(Debug, Subcommand)]").as_deref(),
+            Some("clap Subcommand")
+        );
+        assert_eq!(
+            command_detail("@click.command()").as_deref(),
+            Some("@click.command")
+        );
+        assert_eq!(command_detail("@cli.group"), Some("@cli.group".to_string()));
+        assert_eq!(command_detail("#[derive(Debug, Clone)]"), None);
+    }
+
+    #[test]
+    fn finds_entry_points_from_graph_and_sources() {
+        let dir = tempfile::tempdir().unwrap();
+        let write = |path: &str, text: &str| {
+            let path = dir.path().join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, text).unwrap();
+        };
+        write(
+            "app.py",
+            "@app.get(\"/users\")\ndef list_users():\n    pass\n\n@click.command()\ndef cli():\n    pass\n",
+        );
+        write(
+            "server.js",
+            "function listUsers(req, res) {}\napp.post('/users', auth, listUsers);\nprogram.command('build <dir>').action(build);\nfunction build() {}\n",
+        );
+        write("pkg/index.ts", "export function api() {}\n");
+        write("pkg/inner/index.ts", "export function nested() {}\n");
+
+        let store = GraphStore::from_connection(initialize_database(":memory:").unwrap());
+        let mut api = node(
+            NodeKind::Function,
+            "api",
+            "pkg/index.ts",
+            1,
+            "export function api() {}",
+        );
+        api.exported = Some(true);
+        let mut nested = node(
+            NodeKind::Function,
+            "nested",
+            "pkg/inner/index.ts",
+            1,
+            "export function nested() {}",
+        );
+        nested.exported = Some(true);
+        store
+            .upsert_nodes(&[
+                node(
+                    NodeKind::Function,
+                    "list_users",
+                    "app.py",
+                    2,
+                    "def list_users():",
+                ),
+                node(NodeKind::Function, "cli", "app.py", 6, "def cli():"),
+                node(
+                    NodeKind::Function,
+                    "listUsers",
+                    "server.js",
+                    1,
+                    "function listUsers(req, res) {}",
+                ),
+                node(
+                    NodeKind::Function,
+                    "build",
+                    "server.js",
+                    4,
+                    "function build() {}",
+                ),
+                node(
+                    NodeKind::Method,
+                    "main",
+                    "Main.java",
+                    3,
+                    "public static void main(String[] a) {}",
+                ),
+                node(
+                    NodeKind::Function,
+                    "main",
+                    "app/tests/test_main.py",
+                    1,
+                    "def main():",
+                ),
+                api,
+                nested,
+            ])
+            .unwrap();
+
+        let entries = find_entrypoints(&store, Some(dir.path().to_path_buf())).unwrap();
+        let found: Vec<(EntryKind, &str, Option<&str>)> = entries
+            .iter()
+            .map(|e| (e.kind, e.name.as_str(), e.detail.as_deref()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (EntryKind::Main, "main", None),
+                (EntryKind::Route, "list_users", Some("GET /users")),
+                (EntryKind::Route, "listUsers", Some("POST /users")),
+                (EntryKind::Command, "cli", Some("@click.command")),
+                (EntryKind::Command, "build", Some("command build")),
+                (
+                    EntryKind::PackageExport,
+                    "api",
+                    Some("exported from pkg/index.ts")
+                ),
+            ]
+        );
+
+        // Without sources only the graph speaks
+        let graph_only = find_entrypoints(&store, None).unwrap();
+        assert_eq!(graph_only.len(), 2);
+    }
+}
@@ -0,0 +1,448 @@
+//! Usage examples: the most instructive call sites of a symbol.
+//!
+//! Call and reference edges say *where* a symbol is used; the example
+//! extractor goes back to the source on disk to show *how*. For each site
+//! it cuts out the full call expression (receiver and arguments, across
+//! lines if needed) plus a few lines of context, then picks a small, varied
+//! set: call sites with arguments — literal ones especially — and those in
+//! tests rank higher, and every file contributes one example before any
+//! file contributes a second.
+
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use crate::error::Result;
+use crate::graph::store::GraphStore;
+use crate::security::rules::is_test_file;
+use crate::types::{CodeNode, EdgeKind};
+
+/// Most lines a call expression may span.
+const MAX_CALL_LINES: usize = 8;
+
+/// Calls longer than this (in characters) read poorly as examples.
+const LONG_CALL: usize = 200;
+
+/// One call site of a symbol.
+#[derive(Debug, Clone)]
+pub struct UsageExample {
+    /// The symbol containing the call, when the edge source is known.
+    pub caller: Option<CodeNode>,
+    pub file_path: String,
+    /// 1-based line of the call.
+    pub line: u32,
+    /// The call expression, whitespace-collapsed, e.g. `store.get_node(&id)`.
+    /// `None` when the source could not be read or the call not located.
+    pub call: Option<String>,
+    /// Top-level argument expressions of the call.
+    pub arguments: Vec<String>,
+    /// The call with surrounding lines, as in the file.
+    pub snippet: Option<String>,
+    /// 1-based line of the snippet's first line.
+    pub snippet_start: u32,
+    pub is_test: bool,
+    /// How instructive the site is; higher is better.
+    pub score: f64,
+}
+
+/// The `limit` most instructive call sites of `target`, best first, with
+/// `context` lines of source around each. Files are read under `root`;
+/// the total number of sites found is returned alongside.
+pub fn find_usage_examples(
+    store: &GraphStore,
+    target: &CodeNode,
+    root: &Path,
+    limit: usize,
+    context: usize,
+) -> Result<(Vec<UsageExample>, usize)> {
+    let mut edges = store.get_in_edges(&target.id, Some(EdgeKind::Calls.as_str()))?;
+    if edges.is_empty() {
+        edges = store.get_in_edges(&target.id, Some(EdgeKind::References.as_str()))?;
+    }
+    let policy = crate::sandbox::current();
+    let mut sources: HashMap<String, Option<Vec<String>>> = HashMap::new();
+    let mut seen_sites = HashSet::new();
+    let mut candidates = Vec::new();
+    for edge in edges {
+        if edge.line == 0 || !seen_sites.insert((edge.file_path.clone(), edge.line)) {
+            continue;
+        }
+        let lines = sources.entry(edge.file_path.clone()).or_insert_with(|| {
+            let abs = root.join(&edge.file_path);
+            if !policy.allows_file(&abs) {
+                return None;
+            }
+            std::fs::read_to_string(abs)
+                .ok()
+                .map(|text| text.lines().map(str::to_string).collect())
+        });
+        let caller = store.get_node(&edge.source)?;
+        let is_test = is_test_file(&edge.file_path)
+            || caller.as_ref().is_some_and(|c| c.name.starts_with("test"));
+        let mut example = UsageExample {
+            caller,
+            file_path: edge.file_path.clone(),
+            line: edge.line,
+            call: None,
+            arguments: Vec::new(),
+            snippet: None,
+            snippet_start: edge.line,
+            is_test,
+            score: 0.0,
+        };
+        if let Some(lines) = lines {
+            let at = edge.line as usize - 1;
+            if at < lines.len() {
+                let mut last = at;
+                if let Some(call) = extract_call(lines, at, &target.name) {
+                    last = call.last_line;
+                    example.arguments = call.arguments;
+                    example.call = Some(call.text);
+                }
+                let first = at.saturating_sub(context);
+                let end = (last + context).min(lines.len() - 1);
+                example.snippet = Some(lines[first..=end].join("\n"));
+                example.snippet_start = first as u32 + 1;
+            }
+        }
+        example.score = score(&example, target);
+        candidates.push(example);
+    }
+    let total = candidates.len();
+    Ok((select_diverse(candidates, limit), total))
+}
+
+fn score(example: &UsageExample, target: &CodeNode) -> f64 {
+    let mut score = 0.0;
+    if example.is_test {
+        score += 2.0;
+    }
+    if let Some(ref call) = example.call {
+        score += 1.0;
+        if call.len() > LONG_CALL {
+            score -= 0.5;
+        }
+    }
+    if !example.arguments.is_empty() {
+        score += 1.0;
+        let literals = example.arguments.iter().filter(|a| is_literal(a)).count();
+        score += (literals as f64 * 0.25).min(1.0);
+    }
+    if example.file_path == target.file_path {
+        score -= 0.5;
+    }
+    score
+}
+
+/// Order by score, taking the best site of each file before a second site
+/// of any file, and skipping calls identical to one already taken.
+fn select_diverse(mut candidates: Vec<UsageExample>, limit: usize) -> Vec<UsageExample> {
+    candidates.sort_by(|a, b| {
+        b.score
+            .partial_cmp(&a.score)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| a.file_path.cmp(&b.file_path))
+            .then_with(|| a.line.cmp(&b.line))
+    });
+    let mut files = HashSet::new();
+    let mut calls = HashSet::new();
+    let (mut first, mut rest) = (Vec::new(), Vec::new());
+    for example in candidates {
+        if let Some(ref call) = example.call {
+            if !calls.insert(call.clone()) {
+                continue;
+            }
+        }
+        if files.insert(example.file_path.clone()) {
+            first.push(example);
+        } else {
+            rest.push(example);
+        }
+    }
+    first.extend(rest);
+    first.truncate(limit);
+    first
+}
+
+/// A call expression located in the source.
+struct ExtractedCall {
+    text: String,
+    arguments: Vec<String>,
+    /// 0-based index of the line holding the closing parenthesis.
+    last_line: usize,
+}
+
+/// The call of `name` on line `at` of `lines`: the name with its receiver
+/// chain (`a.b.`, `A::`, `a->`) and its parenthesized arguments, which may
+/// continue on following lines. Quotes are tracked so commas and brackets
+/// inside string literals do not split arguments.
+fn extract_call(lines: &[String], at: usize, name: &str) -> Option<ExtractedCall> {
+    let line = &lines[at];
+    let start = find_call_name(line, name)?;
+    let receiver_start = receiver_start(line, start);
+
+    // Scan from the opening parenthesis to its match, across lines.
+    let mut text = line[receiver_start..start + name.len()].to_string();
+    let mut depth = 0usize;
+    let mut quote: Option<char> = None;
+    let mut escaped = false;
+    let mut args = Vec::new();
+    let mut arg = String::new();
+    let last = (at + MAX_CALL_LINES).min(lines.len());
+    for (index, source) in lines.iter().enumerate().take(last).skip(at) {
+        let rest = if index == at {
+            &source[start + name.len()..]
+        } else {
+            text.push(' ');
+            source.trim()
+        };
+        for c in rest.chars() {
+            if depth == 0 {
+                // Only whitespace and generic arguments may come between the
+                // name and the opening parenthesis.
+                match c {
+                    '(' => depth = 1,
+                    c if c.is_whitespace() => continue,
+                    '<' | '>' | ':' | ',' | '_' | '&' => {}
+                    c if c.is_alphanumeric() => {}
+                    _ => return None,
+                }
+                text.push(c);
+                continue;
+            }
+            text.push(c);
+            if let Some(q) = quote {
+                arg.push(c);
+                if escaped {
+                    escaped = false;
+                } else if c == '\\' {
+                    escaped = true;
+                } else if c == q {
+                    quote = None;
+                }
+                continue;
+            }
+            match c {
+                '"' | '\'' | '`' => {
+                    quote = Some(c);
+                    arg.push(c);
+                }
+                '(' | '[' | '{' => {
+                    depth += 1;
+                    arg.push(c);
+                }
+                ')' | ']' | '}' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        push_argument(&mut args, &arg);
+                        return Some(ExtractedCall {
+                            text: collapse_whitespace(&text),
+                            arguments: args,
+                            last_line: index,
+                        });
+                    }
+                    arg.push(c);
+                }
+                ',' if depth == 1 => push_argument(&mut args, std::mem::take(&mut arg).as_str()),
+                _ => arg.push(c),
+            }
+        }
+        if depth == 0 {
+            // The parenthesis must open on the line of the call.
+            return None;
+        }
+        arg.push(' ');
+    }
+    None
+}
+
+/// Byte offset of `name` used as a call on `line`: a whole identifier
+/// followed, possibly after generics or whitespace, by `(`.
+fn find_call_name(line: &str, name: &str) -> Option<usize> {
+    let is_ident = |c: char| c.is_alphanumeric() || c == '_' || c == '$';
+    let mut from = 0;
+    while let Some(offset) = line[from..].find(name) {
+        let start = from + offset;
+        let end = start + name.len();
+        let before = line[..start].chars().next_back();
+        let after = line[end..].trim_start().chars().next();
+        if !before.is_some_and(is_ident)
+            && !line[end..].chars().next().is_some_and(is_ident)
+            && matches!(after, Some('(') | Some('<') | Some(':'))
+        {
+            return Some(start);
+        }
+        from = end;
+    }
+    None
+}
+
+/// Start of the receiver chain ending right before `start`, e.g. the `s`
+/// of `self.store.get(`.
+fn receiver_start(line: &str, start: usize) -> usize {
+    let bytes = line.as_bytes();
+    let mut i = start;
+    loop {
+        let sep = if i >= 2 && (&bytes[i - 2..i] == b"::" || &bytes[i - 2..i] == b"->") {
+            2
+        } else if i >= 1 && bytes[i - 1] == b'.' {
+            1
+        } else {
+            return i;
+        };
+        let mut j = i - sep;
+        while j > 0 && (bytes[j - 1].is_ascii_alphanumeric() || matches!(bytes[j - 1], b'_' | b'$'))
+        {
+            j -= 1;
+        }
+        if j == i - sep {
+            return i;
+        }
+        i = j;
+    }
+}
+
+fn push_argument(args: &mut Vec<String>, arg: &str) {
+    let arg = collapse_whitespace(arg);
+    if !arg.is_empty() {
+        args.push(arg);
+    }
+}
+
+fn collapse_whitespace(s: &str) -> String {
+    s.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// String, number, boolean or `null`-like literal.
+fn is_literal(arg: &str) -> bool {
+    let arg = arg.trim_start_matches('&');
+    arg.starts_with(['"', '\'', '`'])
+        || arg.parse::<f64>().is_ok()
+        || matches!(
+            arg,
+            "true" | "false" | "null" | "None" | "nil" | "undefined"
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::schema::initialize_database;
+    use crate::types::{CodeEdge, Language, NodeKind};
+
+    fn lines(src: &str) -> Vec<String> {
+        src.lines().map(str::to_string).collect()
+    }
+
+    fn node(id: &str, name: &str, file: &str, line: u32) -> CodeNode {
+        CodeNode {
+            id: id.to_string(),
+            name: name.to_string(),
+            qualified_name: None,
+            kind: NodeKind::Function,
+            file_path: file.to_string(),
+            start_line: line,
+            end_line: line + 3,
+            start_column: 0,
+            end_column: 0,
+            language: Language::TypeScript,
+            body: None,
+            documentation: None,
+            exported: Some(true),
+        }
+    }
+
+    fn call(source: &str, target: &str, file: &str, line: u32) -> CodeEdge {
+        CodeEdge {
+            source: source.to_string(),
+            target: target.to_string(),
+            kind: EdgeKind::Calls,
+            file_path: file.to_string(),
+            line,
+            metadata: None,
+        }
+    }
+
+    #[test]
+    fn extracts_receiver_and_multiline_arguments() {
+        let src = lines("let x = this.client.fetchUser(id,\n    { retries: 3, tag: \"a,b\" });\n");
+        let call = extract_call(&src, 0, "fetchUser").unwrap();
+        assert_eq!(
+            call.text,
+            "this.client.fetchUser(id, { retries: 3, tag: \"a,b\" })"
+        );
+        assert_eq!(call.arguments, vec!["id", "{ retries: 3, tag: \"a,b\" }"]);
+        assert_eq!(call.last_line, 1);
+    }
+
+    #[test]
+    fn skips_non_call_mentions_of_the_name() {
+        let src = lines("// fetchUserName is different; fetchUser is the call\nfetchUser::<T>()");
+        assert!(extract_call(&src, 0, "fetchUser").is_none());
+        let call = extract_call(&src, 1, "fetchUser").unwrap();
+        assert_eq!(call.text, "fetchUser::<T>()");
+        assert!(call.arguments.is_empty());
+    }
+
+    #[test]
+    fn examples_prefer_tests_literals_and_distinct_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        std::fs::create_dir_all(root.join("tests")).unwrap();
+        std::fs::write(
+            root.join("src/app.ts"),
+            "function main() {\n  parse(input);\n}\nfunction helper() { parse(other); }\n",
+        )
+        .unwrap();
+        std::fs::write(
+            root.join("tests/parse.test.ts"),
+            "test('x', () => {\n  expect(parse(\"a=1\")).toBe(1);\n});\n",
+        )
+        .unwrap();
+        std::fs::write(root.join("src/cli.ts"), "function run() {\n  parse();\n}\n").unwrap();
+
+        let store = GraphStore::from_connection(initialize_database(":memory:").unwrap());
+        let target = node("parse", "parse", "src/parse.ts", 1);
+        store
+            .upsert_nodes(&[
+                target.clone(),
+                node("main", "main", "src/app.ts", 1),
+                node("helper", "helper", "src/app.ts", 4),
+                node("t", "testParse", "tests/parse.test.ts", 1),
+                node("run", "run", "src/cli.ts", 1),
+            ])
+            .unwrap();
+        store
+            .upsert_edges(&[
+                call("main", "parse", "src/app.ts", 2),
+                call("helper", "parse", "src/app.ts", 4),
+                call("t", "parse", "tests/parse.test.ts", 2),
+                call("run", "parse", "src/cli.ts", 2),
+            ])
+            .unwrap();
+
+        let (examples, total) = find_usage_examples(&store, &target, root, 3, 1).unwrap();
+        assert_eq!(total, 4);
+        let sites: Vec<(&str, u32)> = examples
+            .iter()
+            .map(|e| (e.file_path.as_str(), e.line))
+            .collect();
+        assert_eq!(
+            sites,
+            vec![
+                ("tests/parse.test.ts", 2),
+                ("src/app.ts", 2),
+                ("src/cli.ts", 2)
+            ]
+        );
+        let best = &examples[0];
+        assert!(best.is_test);
+        assert_eq!(best.call.as_deref(), Some("parse(\"a=1\")"));
+        assert_eq!(best.arguments, vec!["\"a=1\""]);
+        assert_eq!(best.snippet_start, 1);
+        assert_eq!(
+            best.snippet.as_deref(),
+            Some("test('x', () => {\n  expect(parse(\"a=1\")).toBe(1);\n});")
+        );
+    }
+}
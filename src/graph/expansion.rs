@@ -2,10 +2,12 @@
 //!
 //! Expands user queries by splitting compound identifiers (camelCase,
 //! snake_case), mapping common abbreviations to full words (and vice
-//! versa), and substituting code-domain synonyms.  All rules are
-//! static — zero network calls, zero latency cost.
+//! versa), and substituting code-domain synonyms.  The built-in rules
+//! are static; projects add their own jargon with a [`SynonymMap`] loaded
+//! from the `synonyms:` section of `.codegraph.yaml`.  Zero network
+//! calls, zero latency cost.
 
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 // ---------------------------------------------------------------------------
 // Abbreviation map (short ↔ long)
@@ -105,6 +107,60 @@ const SYNONYM_GROUPS: &[&[&str]] = &[
     &["disable", "deactivate", "off"],
 ];
 
+// ---------------------------------------------------------------------------
+// Project synonyms
+// ---------------------------------------------------------------------------
+
+/// Project-specific expansions, one way: a query containing the key also
+/// searches for each of its values.
+///
+/// ```yaml
+/// synonyms:
+///   auth: [authentication, login, session]
+///   single sign on: [sso]
+/// ```
+///
+/// Keys match case-insensitively against the whole query, each word of it
+/// and each part of a compound identifier.
+#[derive(Debug, Clone, Default)]
+pub struct SynonymMap {
+    entries: HashMap<String, Vec<String>>,
+}
+
+impl SynonymMap {
+    /// Build from the configured map, normalising keys and dropping empty
+    /// values. A value may list several terms separated by commas.
+    pub fn new(map: &HashMap<String, Vec<String>>) -> Self {
+        let mut entries: HashMap<String, Vec<String>> = HashMap::new();
+        for (key, values) in map {
+            let key = key.split_whitespace().collect::<Vec<_>>().join(" ");
+            if key.is_empty() {
+                continue;
+            }
+            let terms = values
+                .iter()
+                .flat_map(|v| v.split(','))
+                .map(str::trim)
+                .filter(|t| !t.is_empty())
+                .map(str::to_string);
+            entries.entry(key.to_lowercase()).or_default().extend(terms);
+        }
+        entries.retain(|_, terms| !terms.is_empty());
+        Self { entries }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Expansions for `term` (any case).
+    pub fn get(&self, term: &str) -> &[String] {
+        self.entries
+            .get(&term.to_lowercase())
+            .map_or(&[], Vec::as_slice)
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Public API
 // ---------------------------------------------------------------------------
@@ -119,6 +175,12 @@ const SYNONYM_GROUPS: &[&[&str]] = &[
 /// The caller can give the original query higher fusion weight because
 /// it is always at index 0.
 pub fn expand_query(query: &str) -> Vec<String> {
+    expand_query_with(query, &SynonymMap::default())
+}
+
+/// [`expand_query`] with project synonyms applied before the built-in
+/// rules, so domain jargon expands first.
+pub fn expand_query_with(query: &str, synonyms: &SynonymMap) -> Vec<String> {
     let mut seen = HashSet::new();
     let mut result = Vec::new();
 
@@ -135,15 +197,24 @@ pub fn expand_query(query: &str) -> Vec<String> {
 
     // For each token, generate expansions.
     let mut expanded_tokens: Vec<String> = Vec::new();
+    if tokens.len() > 1 {
+        expanded_tokens.extend(synonyms.get(&tokens.join(" ")).iter().cloned());
+    }
     for token in &tokens {
         // Split compound identifiers.
         let parts = split_identifier(token);
+        if parts.len() > 1 {
+            expanded_tokens.extend(synonyms.get(token).iter().cloned());
+        }
         for part in &parts {
             let lower = part.to_lowercase();
             if lower.len() >= 2 && lower != token.to_lowercase() {
                 expanded_tokens.push(lower.clone());
             }
 
+            // Project synonyms.
+            expanded_tokens.extend(synonyms.get(&lower).iter().cloned());
+
             // Abbreviation expansion (both directions).
             for &(abbr, full) in ABBREVIATIONS {
                 if lower == abbr {
@@ -559,4 +630,47 @@ mod tests {
         // Abbreviation: del → delete
         assert!(expanded.iter().any(|s| s == "delete"));
     }
+
+    // -- project synonyms --------------------------------------------------
+
+    fn synonyms(entries: &[(&str, &[&str])]) -> SynonymMap {
+        let map: HashMap<String, Vec<String>> = entries
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.iter().map(|s| s.to_string()).collect()))
+            .collect();
+        SynonymMap::new(&map)
+    }
+
+    #[test]
+    fn project_synonyms_expand_before_builtins() {
+        let map = synonyms(&[("Auth", &["login, session"])]);
+        assert_eq!(
+            expand_query_with("auth", &map),
+            vec!["auth", "login", "session", "authentication"]
+        );
+
+        let map = synonyms(&[("auth", &["authentication", "login"])]);
+        let expanded = expand_query_with("checkAuth", &map);
+        assert!(expanded.contains(&"login".to_string()), "{expanded:?}");
+        assert!(expanded.contains(&"verify".to_string()), "{expanded:?}");
+        // The built-in abbreviation is not duplicated.
+        assert_eq!(
+            expanded.iter().filter(|e| *e == "authentication").count(),
+            1
+        );
+    }
+
+    #[test]
+    fn project_synonyms_match_whole_phrases_and_identifiers() {
+        let map = synonyms(&[("single sign on", &["sso"]), ("tx_pool", &["mempool"])]);
+        assert!(expand_query_with("Single  sign on", &map).contains(&"sso".to_string()));
+        assert!(expand_query_with("tx_pool", &map).contains(&"mempool".to_string()));
+        assert!(!expand_query("tx_pool").contains(&"mempool".to_string()));
+    }
+
+    #[test]
+    fn empty_synonym_entries_are_dropped() {
+        let map = synonyms(&[("", &["x"]), ("auth", &[" ", ""])]);
+        assert!(map.is_empty());
+    }
 }
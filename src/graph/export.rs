@@ -0,0 +1,717 @@
+//! Graph export to interchange formats.
+//!
+//! Dumps the whole graph, or a subgraph scoped by path prefix and/or a
+//! symbol's neighbourhood, to formats external tools understand:
+//!
+//! - **GraphML** — Gephi, yEd, NetworkX, Neo4j (`apoc.import.graphml`)
+//! - **DOT** — Graphviz
+//! - **JSON Lines** — one `node` / `edge` object per line, easy to stream
+//!   into Neo4j `LOAD CSV`-style pipelines, DuckDB, or jq.
+
+use std::collections::{HashSet, VecDeque};
+use std::io::Write;
+
+use crate::error::{CodeGraphError, Result};
+use crate::graph::backend::GraphBackend;
+use crate::types::{CodeEdge, CodeNode};
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// Output format for [`write_subgraph`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    GraphMl,
+    Dot,
+    Jsonl,
+}
+
+impl ExportFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::GraphMl => "graphml",
+            Self::Dot => "dot",
+            Self::Jsonl => "jsonl",
+        }
+    }
+
+    pub fn from_str_loose(s: &str) -> Option<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "graphml" | "xml" => Some(Self::GraphMl),
+            "dot" | "gv" | "graphviz" => Some(Self::Dot),
+            "jsonl" | "ndjson" | "json" => Some(Self::Jsonl),
+            _ => None,
+        }
+    }
+
+    /// Guess the format from an output file name's extension.
+    pub fn from_path(path: &str) -> Option<Self> {
+        path.rsplit_once('.')
+            .and_then(|(_, ext)| Self::from_str_loose(ext))
+    }
+}
+
+/// Which edges the walk from a symbol follows.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ExportDirection {
+    /// Outgoing edges: what the symbol calls, imports, uses.
+    Outgoing,
+    /// Incoming edges: what calls, imports, uses the symbol.
+    Incoming,
+    #[default]
+    Both,
+}
+
+impl ExportDirection {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Outgoing => "outgoing",
+            Self::Incoming => "incoming",
+            Self::Both => "both",
+        }
+    }
+
+    pub fn from_str_loose(s: &str) -> Option<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "outgoing" | "out" | "callees" | "down" => Some(Self::Outgoing),
+            "incoming" | "in" | "callers" | "up" => Some(Self::Incoming),
+            "both" | "all" => Some(Self::Both),
+            _ => None,
+        }
+    }
+}
+
+/// Which part of the graph to export. The default exports everything.
+#[derive(Debug, Clone, Default)]
+pub struct ExportScope {
+    /// Only keep nodes whose file path starts with this prefix.
+    pub path_prefix: Option<String>,
+    /// Start from this symbol (name or node ID) instead of the whole graph.
+    pub symbol: Option<String>,
+    /// Hops to follow from `symbol`.
+    pub depth: usize,
+    /// Edge directions to follow from `symbol`.
+    pub direction: ExportDirection,
+    /// Only follow and keep edges of these kinds (`calls`, `imports`, ...);
+    /// empty keeps all.
+    pub edge_kinds: Vec<String>,
+    /// Stop the walk from `symbol` after this many nodes.
+    pub max_nodes: Option<usize>,
+}
+
+impl ExportScope {
+    fn keeps_node(&self, node: &CodeNode) -> bool {
+        self.path_prefix
+            .as_deref()
+            .is_none_or(|p| node.file_path.starts_with(p))
+    }
+
+    fn keeps_edge(&self, edge: &CodeEdge) -> bool {
+        self.edge_kinds.is_empty() || self.edge_kinds.iter().any(|k| k == edge.kind.as_str())
+    }
+}
+
+/// A set of nodes plus every edge between them.
+#[derive(Debug, Clone, Default)]
+pub struct Subgraph {
+    pub nodes: Vec<CodeNode>,
+    pub edges: Vec<CodeEdge>,
+    /// Whether the walk stopped at `max_nodes` before reaching `depth`.
+    pub truncated: bool,
+}
+
+// ---------------------------------------------------------------------------
+// Collection
+// ---------------------------------------------------------------------------
+
+/// Collect the nodes and edges selected by `scope`.
+///
+/// With a symbol, every node with that ID or name seeds a breadth-first walk
+/// over the edges of `scope.direction` and `scope.edge_kinds` up to
+/// `scope.depth` hops. Edges are kept only when both endpoints are in the
+/// exported node set, so the output never references nodes it does not
+/// define.
+pub fn collect_subgraph(store: &dyn GraphBackend, scope: &ExportScope) -> Result<Subgraph> {
+    let mut truncated = false;
+    let mut nodes: Vec<CodeNode> = match &scope.symbol {
+        None => {
+            let mut nodes = Vec::new();
+            store.for_each_node(&mut |n| {
+                if scope.keeps_node(&n) {
+                    nodes.push(n);
+                }
+                Ok(())
+            })?;
+            nodes
+        }
+        Some(symbol) => {
+            let seeds = match store.get_node(symbol)? {
+                Some(n) => vec![n],
+                None => store.get_nodes_by_name(symbol)?,
+            };
+            if seeds.is_empty() {
+                return Err(CodeGraphError::Other(format!(
+                    "symbol \"{}\" not found",
+                    symbol
+                )));
+            }
+            let (nodes, cut) = neighbourhood(store, seeds, scope)?;
+            truncated = cut;
+            nodes.into_iter().filter(|n| scope.keeps_node(n)).collect()
+        }
+    };
+    nodes.sort_by(|a, b| a.id.cmp(&b.id));
+
+    let ids: HashSet<&str> = nodes.iter().map(|n| n.id.as_str()).collect();
+    let keeps = |e: &CodeEdge| {
+        ids.contains(e.source.as_str()) && ids.contains(e.target.as_str()) && scope.keeps_edge(e)
+    };
+    let mut edges: Vec<CodeEdge> = Vec::new();
+    match scope.symbol {
+        None => store.for_each_edge(&mut |e| {
+            if keeps(&e) {
+                edges.push(e);
+            }
+            Ok(())
+        })?,
+        // A neighbourhood is small: its out-edges are cheaper than a scan.
+        Some(_) => {
+            for id in &ids {
+                edges.extend(store.get_out_edges(id, None)?.into_iter().filter(keeps));
+            }
+        }
+    }
+    edges.sort_by(|a, b| {
+        (&a.source, &a.target, a.kind.as_str()).cmp(&(&b.source, &b.target, b.kind.as_str()))
+    });
+
+    Ok(Subgraph {
+        nodes,
+        edges,
+        truncated,
+    })
+}
+
+/// Nodes reachable from `seeds` within `scope.depth` hops along the edges
+/// the scope follows, and whether `scope.max_nodes` cut the walk short.
+fn neighbourhood(
+    store: &dyn GraphBackend,
+    seeds: Vec<CodeNode>,
+    scope: &ExportScope,
+) -> Result<(Vec<CodeNode>, bool)> {
+    let mut seen: HashSet<String> = seeds.iter().map(|n| n.id.clone()).collect();
+    let mut queue: VecDeque<(String, usize)> = seeds.iter().map(|n| (n.id.clone(), 0)).collect();
+    let mut out = seeds;
+    let max_nodes = scope.max_nodes.unwrap_or(usize::MAX);
+
+    while let Some((id, dist)) = queue.pop_front() {
+        if dist >= scope.depth {
+            continue;
+        }
+        let mut neighbours = Vec::new();
+        if scope.direction != ExportDirection::Incoming {
+            let out_edges = store.get_out_edges(&id, None)?;
+            neighbours.extend(
+                out_edges
+                    .into_iter()
+                    .filter(|e| scope.keeps_edge(e))
+                    .map(|e| e.target),
+            );
+        }
+        if scope.direction != ExportDirection::Outgoing {
+            let in_edges = store.get_in_edges(&id, None)?;
+            neighbours.extend(
+                in_edges
+                    .into_iter()
+                    .filter(|e| scope.keeps_edge(e))
+                    .map(|e| e.source),
+            );
+        }
+        for next in neighbours {
+            if seen.contains(&next) {
+                continue;
+            }
+            if out.len() >= max_nodes {
+                return Ok((out, true));
+            }
+            seen.insert(next.clone());
+            // Edges may point at conceptual targets (e.g. unresolved modules)
+            // that have no node row; those are not exported.
+            if let Some(node) = store.get_node(&next)? {
+                out.push(node);
+                queue.push_back((next, dist + 1));
+            }
+        }
+    }
+    Ok((out, false))
+}
+
+// ---------------------------------------------------------------------------
+// Writers
+// ---------------------------------------------------------------------------
+
+/// Write the graph selected by `scope` to `out`, returning the number of
+/// nodes and edges written.
+///
+/// A whole-graph export streams rows from `store` straight to `out`, holding
+/// only the exported node IDs in memory; nodes come in ID order and edges in
+/// insertion order. A symbol's neighbourhood is small and is collected with
+/// [`collect_subgraph`] first.
+pub fn export_graph<W: Write>(
+    store: &dyn GraphBackend,
+    scope: &ExportScope,
+    format: ExportFormat,
+    out: &mut W,
+) -> Result<(usize, usize)> {
+    if scope.symbol.is_some() {
+        let graph = collect_subgraph(store, scope)?;
+        write_subgraph(&graph, format, out)?;
+        return Ok((graph.nodes.len(), graph.edges.len()));
+    }
+
+    let mut rows = RowWriter::new(format, out);
+    rows.begin()?;
+    let mut ids: HashSet<String> = HashSet::new();
+    store.for_each_node(&mut |n| {
+        if scope.keeps_node(&n) {
+            rows.node(&n)?;
+            ids.insert(n.id);
+        }
+        Ok(())
+    })?;
+    store.for_each_edge(&mut |e| {
+        if ids.contains(&e.source) && ids.contains(&e.target) && scope.keeps_edge(&e) {
+            rows.edge(&e)?;
+        }
+        Ok(())
+    })?;
+    rows.end()?;
+    Ok((ids.len(), rows.edges))
+}
+
+/// Serialize `graph` to `out` in the given format.
+pub fn write_subgraph<W: Write>(graph: &Subgraph, format: ExportFormat, out: &mut W) -> Result<()> {
+    let mut rows = RowWriter::new(format, out);
+    rows.begin()?;
+    for n in &graph.nodes {
+        rows.node(n)?;
+    }
+    for e in &graph.edges {
+        rows.edge(e)?;
+    }
+    rows.end()
+}
+
+/// GraphML with typed node/edge attribute keys.
+pub fn write_graphml<W: Write>(graph: &Subgraph, out: &mut W) -> Result<()> {
+    write_subgraph(graph, ExportFormat::GraphMl, out)
+}
+
+/// Graphviz DOT, labelling nodes `name (kind)` and edges by kind.
+pub fn write_dot<W: Write>(graph: &Subgraph, out: &mut W) -> Result<()> {
+    write_subgraph(graph, ExportFormat::Dot, out)
+}
+
+/// JSON Lines: all nodes first, then all edges, one object per line.
+pub fn write_jsonl<W: Write>(graph: &Subgraph, out: &mut W) -> Result<()> {
+    write_subgraph(graph, ExportFormat::Jsonl, out)
+}
+
+/// Writes an export one row at a time: [`begin`](Self::begin), every node,
+/// every edge, then [`end`](Self::end).
+struct RowWriter<'a, W: Write> {
+    format: ExportFormat,
+    out: &'a mut W,
+    /// Edges written so far; GraphML numbers its edge IDs with it.
+    edges: usize,
+}
+
+impl<'a, W: Write> RowWriter<'a, W> {
+    fn new(format: ExportFormat, out: &'a mut W) -> Self {
+        Self {
+            format,
+            out,
+            edges: 0,
+        }
+    }
+
+    fn begin(&mut self) -> Result<()> {
+        let out = &mut *self.out;
+        match self.format {
+            ExportFormat::GraphMl => {
+                writeln!(out, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+                writeln!(
+                    out,
+                    r#"<graphml xmlns="http://graphml.graphdrawing.org/xmlns">"#
+                )?;
+                for (id, target, name, ty) in [
+                    ("n_name", "node", "name", "string"),
+                    ("n_kind", "node", "kind", "string"),
+                    ("n_file", "node", "filePath", "string"),
+                    ("n_line", "node", "startLine", "int"),
+                    ("n_lang", "node", "language", "string"),
+                    ("e_kind", "edge", "kind", "string"),
+                    ("e_file", "edge", "filePath", "string"),
+                    ("e_line", "edge", "line", "int"),
+                ] {
+                    writeln!(
+                        out,
+                        r#"  <key id="{id}" for="{target}" attr.name="{name}" attr.type="{ty}"/>"#
+                    )?;
+                }
+                writeln!(out, r#"  <graph id="codegraph" edgedefault="directed">"#)?;
+            }
+            ExportFormat::Dot => {
+                writeln!(out, "digraph codegraph {{")?;
+                writeln!(out, "  node [shape=box];")?;
+            }
+            ExportFormat::Jsonl => {}
+        }
+        Ok(())
+    }
+
+    fn node(&mut self, n: &CodeNode) -> Result<()> {
+        let out = &mut *self.out;
+        match self.format {
+            ExportFormat::GraphMl => {
+                writeln!(out, r#"    <node id="{}">"#, xml_escape(&n.id))?;
+                writeln!(
+                    out,
+                    r#"      <data key="n_name">{}</data>"#,
+                    xml_escape(&n.name)
+                )?;
+                writeln!(
+                    out,
+                    r#"      <data key="n_kind">{}</data>"#,
+                    n.kind.as_str()
+                )?;
+                writeln!(
+                    out,
+                    r#"      <data key="n_file">{}</data>"#,
+                    xml_escape(&n.file_path)
+                )?;
+                writeln!(out, r#"      <data key="n_line">{}</data>"#, n.start_line)?;
+                writeln!(
+                    out,
+                    r#"      <data key="n_lang">{}</data>"#,
+                    n.language.as_str()
+                )?;
+                writeln!(out, "    </node>")?;
+            }
+            ExportFormat::Dot => {
+                writeln!(
+                    out,
+                    "  \"{}\" [label=\"{} ({})\", file=\"{}\"];",
+                    dot_escape(&n.id),
+                    dot_escape(&n.name),
+                    n.kind.as_str(),
+                    dot_escape(&n.file_path)
+                )?;
+            }
+            ExportFormat::Jsonl => {
+                let line = serde_json::json!({
+                    "type": "node",
+                    "id": n.id,
+                    "name": n.name,
+                    "qualifiedName": n.qualified_name,
+                    "kind": n.kind.as_str(),
+                    "filePath": n.file_path,
+                    "startLine": n.start_line,
+                    "endLine": n.end_line,
+                    "language": n.language.as_str(),
+                    "exported": n.exported,
+                });
+                writeln!(out, "{}", line)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn edge(&mut self, e: &CodeEdge) -> Result<()> {
+        let out = &mut *self.out;
+        match self.format {
+            ExportFormat::GraphMl => {
+                writeln!(
+                    out,
+                    r#"    <edge id="e{}" source="{}" target="{}">"#,
+                    self.edges,
+                    xml_escape(&e.source),
+                    xml_escape(&e.target)
+                )?;
+                writeln!(
+                    out,
+                    r#"      <data key="e_kind">{}</data>"#,
+                    e.kind.as_str()
+                )?;
+                writeln!(
+                    out,
+                    r#"      <data key="e_file">{}</data>"#,
+                    xml_escape(&e.file_path)
+                )?;
+                writeln!(out, r#"      <data key="e_line">{}</data>"#, e.line)?;
+                writeln!(out, "    </edge>")?;
+            }
+            ExportFormat::Dot => {
+                writeln!(
+                    out,
+                    "  \"{}\" -> \"{}\" [label=\"{}\"];",
+                    dot_escape(&e.source),
+                    dot_escape(&e.target),
+                    e.kind.as_str()
+                )?;
+            }
+            ExportFormat::Jsonl => {
+                let line = serde_json::json!({
+                    "type": "edge",
+                    "source": e.source,
+                    "target": e.target,
+                    "kind": e.kind.as_str(),
+                    "filePath": e.file_path,
+                    "line": e.line,
+                });
+                writeln!(out, "{}", line)?;
+            }
+        }
+        self.edges += 1;
+        Ok(())
+    }
+
+    fn end(&mut self) -> Result<()> {
+        match self.format {
+            ExportFormat::GraphMl => {
+                writeln!(self.out, "  </graph>")?;
+                writeln!(self.out, "</graphml>")?;
+            }
+            ExportFormat::Dot => writeln!(self.out, "}}")?,
+            ExportFormat::Jsonl => {}
+        }
+        Ok(())
+    }
+}
+
+fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+fn dot_escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::schema::initialize_database;
+    use crate::graph::store::GraphStore;
+    use crate::types::{EdgeKind, Language, NodeKind};
+
+    fn node(id: &str, file: &str) -> CodeNode {
+        CodeNode {
+            id: id.to_string(),
+            name: id.to_string(),
+            qualified_name: None,
+            kind: NodeKind::Function,
+            file_path: file.to_string(),
+            start_line: 1,
+            end_line: 2,
+            start_column: 0,
+            end_column: 0,
+            language: Language::TypeScript,
+            body: None,
+            documentation: None,
+            exported: None,
+        }
+    }
+
+    fn edge(source: &str, target: &str) -> CodeEdge {
+        CodeEdge {
+            source: source.to_string(),
+            target: target.to_string(),
+            kind: EdgeKind::Calls,
+            file_path: "a.ts".to_string(),
+            line: 1,
+            metadata: None,
+        }
+    }
+
+    /// a -> b -> c -> d, with d in another directory.
+    fn setup() -> GraphStore {
+        let store = GraphStore::from_connection(initialize_database(":memory:").unwrap());
+        store
+            .upsert_nodes(&[
+                node("a", "src/a.ts"),
+                node("b", "src/b.ts"),
+                node("c", "src/c.ts"),
+                node("d", "lib/d.ts"),
+            ])
+            .unwrap();
+        store
+            .upsert_edges(&[edge("a", "b"), edge("b", "c"), edge("c", "d")])
+            .unwrap();
+        store
+    }
+
+    #[test]
+    fn full_export_and_path_scope() {
+        let store = setup();
+        let all = collect_subgraph(&store, &ExportScope::default()).unwrap();
+        assert_eq!(all.nodes.len(), 4);
+        assert_eq!(all.edges.len(), 3);
+
+        let src = collect_subgraph(
+            &store,
+            &ExportScope {
+                path_prefix: Some("src/".into()),
+                ..ExportScope::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(src.nodes.len(), 3);
+        // c -> d is dropped because d is outside the scope.
+        assert_eq!(src.edges.len(), 2);
+    }
+
+    #[test]
+    fn symbol_scope_walks_both_directions_to_depth() {
+        let store = setup();
+        let sub = collect_subgraph(
+            &store,
+            &ExportScope {
+                symbol: Some("b".into()),
+                depth: 1,
+                ..ExportScope::default()
+            },
+        )
+        .unwrap();
+        let ids: Vec<&str> = sub.nodes.iter().map(|n| n.id.as_str()).collect();
+        assert_eq!(ids, vec!["a", "b", "c"]);
+        assert_eq!(sub.edges.len(), 2);
+
+        let missing = collect_subgraph(
+            &store,
+            &ExportScope {
+                symbol: Some("nope".into()),
+                ..ExportScope::default()
+            },
+        );
+        assert!(missing.is_err());
+    }
+
+    #[test]
+    fn symbol_scope_follows_direction_kinds_and_node_cap() {
+        let store = setup();
+        store
+            .upsert_edge(&CodeEdge {
+                kind: EdgeKind::Imports,
+                ..edge("b", "d")
+            })
+            .unwrap();
+        let ids = |scope: ExportScope| {
+            let sub = collect_subgraph(&store, &scope).unwrap();
+            let ids: Vec<String> = sub.nodes.iter().map(|n| n.id.clone()).collect();
+            (ids, sub.edges.len(), sub.truncated)
+        };
+        let from_b = || ExportScope {
+            symbol: Some("b".into()),
+            depth: 5,
+            ..ExportScope::default()
+        };
+
+        let (down, edges, _) = ids(ExportScope {
+            direction: ExportDirection::Outgoing,
+            edge_kinds: vec!["calls".into()],
+            ..from_b()
+        });
+        assert_eq!(down, vec!["b", "c", "d"]);
+        // The imports edge b -> d is left out.
+        assert_eq!(edges, 2);
+
+        let (up, _, _) = ids(ExportScope {
+            direction: ExportDirection::Incoming,
+            ..from_b()
+        });
+        assert_eq!(up, vec!["a", "b"]);
+
+        let (capped, _, truncated) = ids(ExportScope {
+            max_nodes: Some(2),
+            ..from_b()
+        });
+        assert_eq!(capped.len(), 2);
+        assert!(truncated);
+        assert_eq!(
+            ExportDirection::from_str_loose("callers"),
+            Some(ExportDirection::Incoming)
+        );
+    }
+
+    #[test]
+    fn writers_emit_every_node_and_edge() {
+        let store = setup();
+        let sub = collect_subgraph(&store, &ExportScope::default()).unwrap();
+
+        let mut buf = Vec::new();
+        write_subgraph(&sub, ExportFormat::Jsonl, &mut buf).unwrap();
+        let text = String::from_utf8(buf).unwrap();
+        assert_eq!(text.lines().count(), 7);
+        for line in text.lines() {
+            serde_json::from_str::<serde_json::Value>(line).unwrap();
+        }
+
+        let mut buf = Vec::new();
+        write_subgraph(&sub, ExportFormat::GraphMl, &mut buf).unwrap();
+        let text = String::from_utf8(buf).unwrap();
+        assert_eq!(text.matches("<node ").count(), 4);
+        assert_eq!(text.matches("<edge ").count(), 3);
+
+        let mut buf = Vec::new();
+        write_subgraph(&sub, ExportFormat::Dot, &mut buf).unwrap();
+        let text = String::from_utf8(buf).unwrap();
+        assert!(text.starts_with("digraph codegraph {"));
+        assert!(text.contains("\"a\" -> \"b\" [label=\"calls\"];"));
+    }
+
+    #[test]
+    fn streamed_export_matches_the_collected_subgraph() {
+        let store = setup();
+        let scope = ExportScope {
+            path_prefix: Some("src/".into()),
+            ..ExportScope::default()
+        };
+        let sub = collect_subgraph(&store, &scope).unwrap();
+        for format in [
+            ExportFormat::Jsonl,
+            ExportFormat::GraphMl,
+            ExportFormat::Dot,
+        ] {
+            let mut collected = Vec::new();
+            write_subgraph(&sub, format, &mut collected).unwrap();
+            let mut streamed = Vec::new();
+            let counts = export_graph(&store, &scope, format, &mut streamed).unwrap();
+            assert_eq!(counts, (3, 2));
+            assert_eq!(streamed, collected, "{}", format.as_str());
+        }
+    }
+
+    #[test]
+    fn escaping_and_format_detection() {
+        assert_eq!(xml_escape("a<b>&\"c\""), "a&lt;b&gt;&amp;&quot;c&quot;");
+        assert_eq!(dot_escape(r#"say "hi"\"#), r#"say \"hi\"\\"#);
+        assert_eq!(
+            ExportFormat::from_path("out/graph.gv"),
+            Some(ExportFormat::Dot)
+        );
+        assert_eq!(
+            ExportFormat::from_path("graph.graphml"),
+            Some(ExportFormat::GraphMl)
+        );
+        assert_eq!(ExportFormat::from_path("graph"), None);
+    }
+}
@@ -0,0 +1,366 @@
+//! Federated search across several CodeGraph indexes.
+//!
+//! A service usually depends on shared libraries that are indexed on their
+//! own. A [`Federation`] opens those databases read-only next to the local
+//! index, runs the same hybrid search on each in parallel (one connection
+//! per thread, since connections are `!Sync`), and merges the result lists
+//! by weighted Reciprocal Rank Fusion: a result at rank `r` of an index
+//! with weight `w` scores `w / (60 + r)`. Scores of different indexes are
+//! not comparable, ranks are.
+//!
+//! Each result keeps the name of the index it came from; the same symbol
+//! in two indexes (a vendored copy, say) is reported once per index.
+//!
+//! ```yaml
+//! federation:
+//!   local_weight: 1.0
+//!   indexes:
+//!     - name: shared-lib
+//!       path: ../shared-lib/.codegraph/codegraph.db
+//!       weight: 0.5
+//! ```
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{CodeGraphError, Result};
+use crate::graph::expansion::SynonymMap;
+use crate::graph::search::{HybridSearch, PriorWeights, SearchOptions, SearchResult};
+use crate::graph::store::GraphStore;
+
+/// RRF smoothing constant, as in single-index fusion.
+const RRF_K: f64 = 60.0;
+
+/// Name under which the local index is reported.
+pub const LOCAL_INDEX: &str = "local";
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// Another index to search alongside the local one.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FederatedIndex {
+    /// Name reported with each of its results.
+    pub name: String,
+    /// Database path, relative to the project root unless absolute.
+    pub path: PathBuf,
+    /// Weight of its results in the fused ranking (default 1.0).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weight: Option<f64>,
+}
+
+/// A search result and the index it came from.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FederatedResult {
+    pub index: String,
+    /// Rank within its own index, from 1.
+    pub index_rank: usize,
+    #[serde(flatten)]
+    pub result: SearchResult,
+}
+
+/// Fused results, plus the indexes that could not be searched.
+#[derive(Debug, Clone, Default)]
+pub struct FederatedSearch {
+    /// Best first.
+    pub results: Vec<FederatedResult>,
+    /// `(index, error)` per index that failed to open or to search.
+    pub errors: Vec<(String, String)>,
+}
+
+struct Member {
+    name: String,
+    weight: f64,
+    store: GraphStore,
+}
+
+/// The attached indexes, see the module docs.
+pub struct Federation {
+    local_weight: f64,
+    members: Vec<Member>,
+    /// Indexes that failed to open, reported with every search.
+    unavailable: Vec<(String, String)>,
+    priors: PriorWeights,
+    synonyms: SynonymMap,
+}
+
+impl std::fmt::Debug for Federation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Federation")
+            .field("local_weight", &self.local_weight)
+            .field(
+                "members",
+                &self.members.iter().map(|m| &m.name).collect::<Vec<_>>(),
+            )
+            .field("unavailable", &self.unavailable)
+            .finish_non_exhaustive()
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Federation
+// ---------------------------------------------------------------------------
+
+impl Federation {
+    /// A federation of the local index alone, weighted `local_weight`.
+    pub fn new(local_weight: f64) -> Self {
+        Self {
+            local_weight,
+            members: Vec::new(),
+            unavailable: Vec::new(),
+            priors: PriorWeights::default(),
+            synonyms: SynonymMap::default(),
+        }
+    }
+
+    /// Open every index in `indexes` read-only, resolving relative paths
+    /// against `root`. Indexes that fail to open are skipped and reported
+    /// by [`search`](Self::search).
+    pub fn open(local_weight: f64, indexes: &[FederatedIndex], root: &Path) -> Self {
+        let mut federation = Self::new(local_weight);
+        for index in indexes {
+            let path = root.join(&index.path);
+            let opened = if path.is_file() {
+                GraphStore::open_read_only(&path.to_string_lossy())
+            } else {
+                Err(CodeGraphError::Other(format!(
+                    "no index at {}",
+                    path.display()
+                )))
+            };
+            let weight = index.weight.unwrap_or(1.0);
+            match opened.and_then(|store| federation.attach(&index.name, store, weight)) {
+                Ok(()) => {}
+                Err(e) => federation
+                    .unavailable
+                    .push((index.name.clone(), e.to_string())),
+            }
+        }
+        federation
+    }
+
+    /// Add an open index under `name`.
+    pub fn attach(&mut self, name: &str, store: GraphStore, weight: f64) -> Result<()> {
+        if name == LOCAL_INDEX || self.members.iter().any(|m| m.name == name) {
+            return Err(CodeGraphError::Other(format!(
+                "index name \"{name}\" is already in use"
+            )));
+        }
+        self.members.push(Member {
+            name: name.to_string(),
+            weight,
+            store,
+        });
+        Ok(())
+    }
+
+    /// Blend node priors into each index's scores with `weights`.
+    pub fn with_priors(mut self, weights: PriorWeights) -> Self {
+        self.priors = weights;
+        self
+    }
+
+    /// Expand queries with project synonyms in every index.
+    pub fn with_synonyms(mut self, synonyms: SynonymMap) -> Self {
+        self.synonyms = synonyms;
+        self
+    }
+
+    /// Names of the attached indexes, without the local one.
+    pub fn index_names(&self) -> Vec<&str> {
+        self.members.iter().map(|m| m.name.as_str()).collect()
+    }
+
+    /// Keep only the attached indexes named in `names`.
+    pub fn retain(&mut self, names: &[String]) {
+        self.members.retain(|m| names.contains(&m.name));
+    }
+
+    /// Search the local index and every attached one, fusing the results.
+    /// Each index is asked for `options.limit` results and the fused list
+    /// is cut to the same length.
+    pub fn search(
+        &mut self,
+        local: Option<&GraphStore>,
+        query: &str,
+        options: &SearchOptions,
+    ) -> Result<FederatedSearch> {
+        let limit = options.limit.unwrap_or(20);
+        let (priors, synonyms) = (self.priors, &self.synonyms);
+        let run = |store: &GraphStore| {
+            HybridSearch::new(&store.conn)
+                .with_priors(priors)
+                .with_synonyms(synonyms.clone())
+                .search(query, options)
+        };
+
+        let (local_results, remote) = std::thread::scope(|scope| {
+            let handles: Vec<_> = self
+                .members
+                .iter_mut()
+                .map(|m| {
+                    let run = &run;
+                    let name = m.name.clone();
+                    let weight = m.weight;
+                    let store = &mut m.store;
+                    (name, weight, scope.spawn(move || run(store)))
+                })
+                .collect();
+            let local_results = local.map(&run);
+            let remote: Vec<(String, f64, Result<Vec<SearchResult>>)> = handles
+                .into_iter()
+                .map(|(name, weight, handle)| {
+                    let outcome = handle.join().unwrap_or_else(|_| {
+                        Err(CodeGraphError::Other("search panicked".to_string()))
+                    });
+                    (name, weight, outcome)
+                })
+                .collect();
+            (local_results, remote)
+        });
+
+        let mut lists = Vec::new();
+        let mut errors = self.unavailable.clone();
+        if let Some(results) = local_results {
+            lists.push((LOCAL_INDEX.to_string(), self.local_weight, results?));
+        }
+        for (name, weight, outcome) in remote {
+            match outcome {
+                Ok(results) => lists.push((name, weight, results)),
+                Err(e) => errors.push((name, e.to_string())),
+            }
+        }
+        let mut results = fuse(lists);
+        results.truncate(limit);
+        Ok(FederatedSearch { results, errors })
+    }
+}
+
+/// Weighted RRF over per-index result lists; ties go to the index listed
+/// first, then to the node ID.
+fn fuse(lists: Vec<(String, f64, Vec<SearchResult>)>) -> Vec<FederatedResult> {
+    let mut fused: Vec<(usize, FederatedResult)> = Vec::new();
+    for (order, (index, weight, results)) in lists.into_iter().enumerate() {
+        for (i, mut result) in results.into_iter().enumerate() {
+            result.score = weight / (RRF_K + (i + 1) as f64);
+            fused.push((
+                order,
+                FederatedResult {
+                    index: index.clone(),
+                    index_rank: i + 1,
+                    result,
+                },
+            ));
+        }
+    }
+    fused.sort_by(|(oa, a), (ob, b)| {
+        b.result
+            .score
+            .total_cmp(&a.result.score)
+            .then(oa.cmp(ob))
+            .then_with(|| a.result.node_id.cmp(&b.result.node_id))
+    });
+    fused.into_iter().map(|(_, r)| r).collect()
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{CodeNode, Language, NodeKind};
+
+    fn node(id: &str, name: &str, file: &str) -> CodeNode {
+        CodeNode {
+            id: id.to_string(),
+            name: name.to_string(),
+            qualified_name: None,
+            kind: NodeKind::Function,
+            file_path: file.to_string(),
+            start_line: 1,
+            end_line: 5,
+            start_column: 0,
+            end_column: 1,
+            language: Language::TypeScript,
+            body: Some(format!("function {name}() {{}}")),
+            documentation: None,
+            exported: Some(true),
+        }
+    }
+
+    fn index_at(path: &Path, nodes: &[CodeNode]) {
+        let store = GraphStore::new(&path.to_string_lossy()).unwrap();
+        store.upsert_nodes(nodes).unwrap();
+    }
+
+    #[test]
+    fn merges_indexes_by_weighted_rank() {
+        let tmp = tempfile::tempdir().unwrap();
+        let local = GraphStore::new(&tmp.path().join("local.db").to_string_lossy()).unwrap();
+        local
+            .upsert_node(&node("l1", "parseConfig", "src/config.ts"))
+            .unwrap();
+        index_at(
+            &tmp.path().join("shared.db"),
+            &[node("s1", "parseConfig", "lib/config.ts")],
+        );
+
+        let indexes = vec![
+            FederatedIndex {
+                name: "shared".to_string(),
+                path: PathBuf::from("shared.db"),
+                weight: Some(2.0),
+            },
+            FederatedIndex {
+                name: "gone".to_string(),
+                path: PathBuf::from("missing.db"),
+                weight: None,
+            },
+        ];
+        let mut federation = Federation::open(1.0, &indexes, tmp.path());
+        assert_eq!(federation.index_names(), vec!["shared"]);
+
+        let opts = SearchOptions {
+            limit: Some(10),
+            ..Default::default()
+        };
+        let found = federation
+            .search(Some(&local), "parseConfig", &opts)
+            .unwrap();
+        let hits: Vec<(&str, &str)> = found
+            .results
+            .iter()
+            .map(|r| (r.index.as_str(), r.result.file_path.as_str()))
+            .collect();
+        assert_eq!(
+            hits,
+            vec![("shared", "lib/config.ts"), ("local", "src/config.ts")]
+        );
+        assert_eq!(found.errors.len(), 1);
+        assert_eq!(found.errors[0].0, "gone");
+
+        federation.retain(&[]);
+        let found = federation
+            .search(Some(&local), "parseConfig", &opts)
+            .unwrap();
+        assert_eq!(found.results.len(), 1);
+        assert_eq!(found.results[0].index, LOCAL_INDEX);
+    }
+
+    #[test]
+    fn index_names_must_be_unique() {
+        let mut federation = Federation::new(1.0);
+        let open = || {
+            let conn = crate::db::schema::initialize_database(":memory:").unwrap();
+            GraphStore::from_connection(conn)
+        };
+        federation.attach("lib", open(), 1.0).unwrap();
+        assert!(federation.attach("lib", open(), 1.0).is_err());
+        assert!(federation.attach(LOCAL_INDEX, open(), 1.0).is_err());
+    }
+}
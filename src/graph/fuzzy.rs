@@ -0,0 +1,218 @@
+//! Fuzzy symbol name matching for misspelled queries.
+//!
+//! FTS5 only matches whole tokens, so a typo like `HybirdSearch` finds
+//! nothing. When that happens, search falls back to comparing each query
+//! term against the distinct symbol names in the graph: candidates within a
+//! small edit distance (insertions, deletions, substitutions and adjacent
+//! transpositions) are suggested, closest first, with trigram overlap
+//! breaking ties.
+//!
+//! Names are compared case-insensitively. The allowed distance grows with
+//! the term length (one edit up to 5 characters, two up to 9, three
+//! beyond), and terms shorter than [`MIN_TERM_LEN`] are not matched at all
+//! since nearly every short name is a couple of edits from any other.
+
+use std::collections::{HashMap, HashSet};
+
+use rusqlite::{params, Connection};
+use serde::Serialize;
+
+use crate::error::Result;
+
+/// Shortest query term that gets fuzzy suggestions.
+pub const MIN_TERM_LEN: usize = 4;
+
+/// A symbol name close to a query term.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Suggestion {
+    /// The symbol name as stored in the graph.
+    pub name: String,
+    /// The query term it was matched against.
+    pub term: String,
+    /// Case-insensitive edit distance between the two.
+    pub distance: usize,
+    /// Trigram Dice similarity (0..1).
+    pub similarity: f64,
+}
+
+/// Suggest up to `limit` symbol names for the terms of `query`, closest
+/// first. Field prefixes, operators and FTS5 syntax characters are ignored.
+pub fn suggest_names(conn: &Connection, query: &str, limit: usize) -> Result<Vec<Suggestion>> {
+    let mut suggestions: Vec<Suggestion> = Vec::new();
+    let mut stmt = conn
+        .prepare_cached("SELECT DISTINCT name FROM nodes WHERE length(name) BETWEEN ?1 AND ?2")?;
+    for term in query_terms(query) {
+        let len = term.chars().count();
+        let max = max_distance(len);
+        let lower = term.to_lowercase();
+        let grams = trigrams(&lower);
+        let names = stmt.query_map(params![(len - max) as i64, (len + max) as i64], |row| {
+            row.get::<_, String>(0)
+        })?;
+        for name in names {
+            let name = name?;
+            let candidate = name.to_lowercase();
+            let distance = osa_distance(&lower, &candidate);
+            if distance > max {
+                continue;
+            }
+            suggestions.push(Suggestion {
+                similarity: dice(&grams, &trigrams(&candidate)),
+                name,
+                term: term.clone(),
+                distance,
+            });
+        }
+    }
+
+    suggestions.sort_by(|a, b| {
+        a.distance
+            .cmp(&b.distance)
+            .then(b.similarity.total_cmp(&a.similarity))
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    let mut seen = HashSet::new();
+    suggestions.retain(|s| seen.insert(s.name.clone()));
+    suggestions.truncate(limit);
+    Ok(suggestions)
+}
+
+/// The identifier-like words of a query, long enough to fuzz.
+fn query_terms(query: &str) -> Vec<String> {
+    let mut terms = Vec::new();
+    for word in query.split(|c: char| c.is_whitespace() || c == '"') {
+        if matches!(word, "AND" | "OR" | "NOT") {
+            continue;
+        }
+        let word = word.rsplit(':').next().unwrap_or(word);
+        let term: String = word
+            .chars()
+            .filter(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '$' | '.'))
+            .collect();
+        if term.chars().count() >= MIN_TERM_LEN && !terms.contains(&term) {
+            terms.push(term);
+        }
+    }
+    terms
+}
+
+/// Edits allowed for a term of `len` characters.
+fn max_distance(len: usize) -> usize {
+    match len {
+        0..=5 => 1,
+        6..=9 => 2,
+        _ => 3,
+    }
+}
+
+/// Optimal string alignment distance: Levenshtein plus transposition of
+/// adjacent characters, each edit costing one.
+pub fn osa_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let width = b.len() + 1;
+    let mut d = vec![0usize; (a.len() + 1) * width];
+    for (i, row) in d.chunks_mut(width).enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in d[..width].iter_mut().enumerate() {
+        *cell = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut best = (d[(i - 1) * width + j] + 1)
+                .min(d[i * width + j - 1] + 1)
+                .min(d[(i - 1) * width + j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                best = best.min(d[(i - 2) * width + j - 2] + 1);
+            }
+            d[i * width + j] = best;
+        }
+    }
+    d[a.len() * width + b.len()]
+}
+
+/// Character trigrams of `s`, padded so short words still produce some.
+fn trigrams(s: &str) -> HashMap<[char; 3], usize> {
+    let chars: Vec<char> = std::iter::once(' ')
+        .chain(s.chars())
+        .chain(std::iter::once(' '))
+        .collect();
+    let mut grams = HashMap::new();
+    for w in chars.windows(3) {
+        *grams.entry([w[0], w[1], w[2]]).or_insert(0) += 1;
+    }
+    grams
+}
+
+/// Dice coefficient of two trigram multisets.
+fn dice(a: &HashMap<[char; 3], usize>, b: &HashMap<[char; 3], usize>) -> f64 {
+    let total: usize = a.values().sum::<usize>() + b.values().sum::<usize>();
+    if total == 0 {
+        return 0.0;
+    }
+    let shared: usize = a
+        .iter()
+        .map(|(g, n)| (*n).min(b.get(g).copied().unwrap_or(0)))
+        .sum();
+    2.0 * shared as f64 / total as f64
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::schema::initialize_database;
+
+    fn insert(conn: &Connection, id: &str, name: &str) {
+        conn.execute(
+            "INSERT INTO nodes (id, type, name, file_path, start_line, end_line, language) \
+             VALUES (?1, 'class', ?2, 'src/a.rs', 1, 2, 'rust')",
+            params![id, name],
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn osa_counts_transpositions_as_one_edit() {
+        assert_eq!(osa_distance("hybirdsearch", "hybridsearch"), 1);
+        assert_eq!(osa_distance("kitten", "sitting"), 3);
+        assert_eq!(osa_distance("", "abc"), 3);
+        assert_eq!(osa_distance("same", "same"), 0);
+    }
+
+    #[test]
+    fn suggests_closest_names_first() {
+        let conn = initialize_database(":memory:").unwrap();
+        insert(&conn, "a", "HybridSearch");
+        insert(&conn, "b", "HybridSearcher");
+        insert(&conn, "c", "GraphStore");
+        insert(&conn, "d", "HybridSearch");
+
+        let found = suggest_names(&conn, "HybirdSearch", 5).unwrap();
+        let names: Vec<&str> = found.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["HybridSearch", "HybridSearcher"]);
+        assert_eq!(found[0].distance, 1);
+        assert_eq!(found[0].term, "HybirdSearch");
+
+        // Field prefixes and operators are skipped; each term is fuzzed.
+        let found = suggest_names(&conn, "name:GrapStore OR HybridSerch", 2).unwrap();
+        let mut names: Vec<&str> = found.iter().map(|s| s.name.as_str()).collect();
+        names.sort();
+        assert_eq!(names, vec!["GraphStore", "HybridSearch"]);
+    }
+
+    #[test]
+    fn short_and_distant_terms_get_nothing() {
+        let conn = initialize_database(":memory:").unwrap();
+        insert(&conn, "a", "run");
+        insert(&conn, "b", "HybridSearch");
+        assert!(suggest_names(&conn, "rum", 5).unwrap().is_empty());
+        assert!(suggest_names(&conn, "SemanticIndex", 5).unwrap().is_empty());
+    }
+}
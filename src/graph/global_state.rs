@@ -0,0 +1,855 @@
+//! Global mutable state inventory.
+//!
+//! Finds module-level mutable variables, mutable static fields and
+//! singletons, and lists the functions that read and write each one. Shared
+//! mutable state is what makes code hard to test in isolation, so the
+//! inventory doubles as a checklist when threading it through parameters.
+//!
+//! Static fields use the `reads`/`writes` edges recorded by the extractor.
+//! Module-level variables have no such edges; their accessors are found by
+//! scanning the stored bodies of functions in the declaring file and in the
+//! files that import it, with line-based heuristics in the spirit of
+//! [`dataflow`](super::dataflow).
+
+use std::collections::{HashMap, HashSet};
+
+use crate::error::Result;
+use crate::graph::store::GraphStore;
+use crate::types::{CodeEdge, CodeNode, EdgeKind, Language, NodeKind};
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// What kind of global state a node holds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GlobalStateKind {
+    /// A mutable top-level variable (`let`/`var` reassigned, a `static mut`,
+    /// a module-level container mutated in place).
+    ModuleVariable,
+    /// A static field that is not `final`/`readonly`, or holds a container.
+    StaticField,
+    /// A single shared instance of a project class, held in a module-level
+    /// variable or a static field of the class itself.
+    Singleton,
+}
+
+impl GlobalStateKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::ModuleVariable => "module_variable",
+            Self::StaticField => "static_field",
+            Self::Singleton => "singleton",
+        }
+    }
+
+    pub fn from_str_loose(s: &str) -> Option<Self> {
+        match s {
+            "module_variable" | "module" | "variable" => Some(Self::ModuleVariable),
+            "static_field" | "static" => Some(Self::StaticField),
+            "singleton" => Some(Self::Singleton),
+            _ => None,
+        }
+    }
+}
+
+/// Whether a function reads or writes a piece of state.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Access {
+    Read,
+    Write,
+}
+
+/// A function or method that touches a piece of global state.
+#[derive(Debug, Clone)]
+pub struct StateAccessor {
+    pub node_id: String,
+    /// Qualified name when known, else the plain name.
+    pub name: String,
+    pub kind: NodeKind,
+    pub file_path: String,
+    /// First line of the access (the first write, for writers).
+    pub line: u32,
+}
+
+/// One piece of global mutable state and who touches it.
+#[derive(Debug, Clone)]
+pub struct GlobalState {
+    pub node: CodeNode,
+    pub kind: GlobalStateKind,
+    /// First line of the declaration.
+    pub declaration: String,
+    pub writers: Vec<StateAccessor>,
+    pub readers: Vec<StateAccessor>,
+}
+
+// ---------------------------------------------------------------------------
+// Heuristics
+// ---------------------------------------------------------------------------
+
+/// Rust wrapper types that make a `static` mutable.
+const INTERIOR_MUTABILITY: &[&str] = &[
+    "Mutex", "RwLock", "Atomic", "RefCell", "Cell<", "OnceLock", "OnceCell", "Lazy",
+];
+
+/// Method calls that mutate their receiver. Taking a lock or a mutable
+/// borrow counts: the caller can write through it.
+const MUTATORS: &[&str] = &[
+    "push",
+    "pop",
+    "shift",
+    "unshift",
+    "splice",
+    "append",
+    "extend",
+    "insert",
+    "remove",
+    "clear",
+    "update",
+    "setdefault",
+    "add",
+    "discard",
+    "delete",
+    "set",
+    "put",
+    "putAll",
+    "store",
+    "swap",
+    "fetch_add",
+    "fetch_sub",
+    "lock",
+    "write",
+    "borrow_mut",
+    "get_mut",
+    "sort",
+    "reverse",
+];
+
+/// Assignment operators that rebind their left-hand side.
+const COMPOUND_ASSIGN: &[&str] = &[
+    "+=", "-=", "*=", "/=", "%=", "|=", "&=", "^=", "<<=", ">>=", "**=", "//=", "||=", "&&=",
+    "??=", "++", "--",
+];
+
+fn is_ident_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '$'
+}
+
+/// Byte offsets of whole-word occurrences of `name` in `line`.
+fn word_positions<'a>(line: &'a str, name: &'a str) -> impl Iterator<Item = usize> + 'a {
+    line.match_indices(name).filter_map(move |(pos, _)| {
+        let before = line[..pos].chars().next_back();
+        let after = line[pos + name.len()..].chars().next();
+        (!before.is_some_and(is_ident_char) && !after.is_some_and(is_ident_char)).then_some(pos)
+    })
+}
+
+/// The declaration with visibility prefixes removed.
+fn strip_visibility(decl: &str) -> &str {
+    let mut decl = decl.trim();
+    for prefix in [
+        "export default ",
+        "export ",
+        "pub(crate) ",
+        "pub(super) ",
+        "pub ",
+    ] {
+        if let Some(rest) = decl.strip_prefix(prefix) {
+            decl = rest.trim_start();
+        }
+    }
+    decl
+}
+
+/// The initializer of a declaration: the text after its first plain `=`.
+fn initializer(decl: &str) -> Option<&str> {
+    let bytes = decl.as_bytes();
+    (0..bytes.len()).find_map(|i| {
+        let plain = bytes[i] == b'='
+            && !matches!(bytes.get(i + 1), Some(b'=' | b'>'))
+            && !matches!(
+                i.checked_sub(1).map(|j| bytes[j]),
+                Some(b'=' | b'!' | b'<' | b'>')
+            );
+        plain.then(|| decl[i + 1..].trim())
+    })
+}
+
+/// Whether the initializer builds a container that can be mutated in place.
+fn is_container(value: &str) -> bool {
+    value.starts_with(['{', '['])
+        || value.starts_with("new ")
+        || [
+            "dict(",
+            "list(",
+            "set(",
+            "defaultdict(",
+            "OrderedDict(",
+            "deque(",
+        ]
+        .iter()
+        .any(|c| value.starts_with(c))
+}
+
+/// The project class instantiated by `value` (`new Foo(...)` or `Foo(...)`).
+fn instantiated_class<'a>(value: &'a str, classes: &HashSet<&str>) -> Option<&'a str> {
+    let value = value.strip_prefix("new ").unwrap_or(value).trim_start();
+    let end = value.find(|c: char| !is_ident_char(c))?;
+    let name = &value[..end];
+    (value[end..].starts_with(['(', '{']) && classes.contains(name)).then_some(name)
+}
+
+/// Whether a module-level variable is mutable by declaration alone.
+fn declared_mutable(decl: &str, language: Language) -> bool {
+    match language {
+        Language::Rust => {
+            decl.starts_with("static mut ")
+                || (decl.starts_with("static ")
+                    && INTERIOR_MUTABILITY.iter().any(|m| decl.contains(m)))
+        }
+        Language::Go => decl.starts_with("var"),
+        _ => initializer(decl).is_some_and(is_container),
+    }
+}
+
+/// Whether a variable node is a declaration this analysis should consider
+/// at all (not a module, package, or function bound to a name).
+fn is_value_declaration(decl: &str, language: Language) -> bool {
+    if decl.starts_with("mod ") || decl.starts_with("package ") {
+        return false;
+    }
+    if language == Language::Rust && (decl.starts_with("const ") || decl.starts_with("let ")) {
+        return false;
+    }
+    if language == Language::Go && decl.starts_with("const") {
+        return false;
+    }
+    !initializer(decl).is_some_and(|v| {
+        v.starts_with("function")
+            || v.starts_with("async ")
+            || v.starts_with("lambda")
+            || v.starts_with("class ")
+            || v.contains("=>")
+    })
+}
+
+/// Whether the rest of a line after a name rebinds it (`=`, `+=`, `++`, ...).
+fn rebinds(after: &str) -> bool {
+    (after.starts_with('=') && !after.starts_with("==") && !after.starts_with("=>"))
+        || COMPOUND_ASSIGN.iter().any(|op| after.starts_with(op))
+}
+
+/// Whether an occurrence of a name, with the text `before` and `after` it on
+/// the line, writes to it: rebinding, indexed or attribute assignment, a
+/// prefix increment, or a mutating method call.
+fn is_write(before: &str, after: &str) -> bool {
+    if before.ends_with("++") || before.ends_with("--") || rebinds(after) {
+        return true;
+    }
+    if let Some(rest) = after.strip_prefix('[') {
+        let mut depth = 1;
+        for (i, c) in rest.char_indices() {
+            match c {
+                '[' => depth += 1,
+                ']' => depth -= 1,
+                _ => {}
+            }
+            if depth == 0 {
+                return rebinds(rest[i + 1..].trim_start());
+            }
+        }
+        return false;
+    }
+    if let Some(rest) = after.strip_prefix('.') {
+        let end = rest.find(|c: char| !is_ident_char(c)).unwrap_or(rest.len());
+        let (member, tail) = rest.split_at(end);
+        let tail = tail.trim_start();
+        return rebinds(tail) || (tail.starts_with('(') && MUTATORS.contains(&member));
+    }
+    false
+}
+
+/// Whether the occurrence declares a new local binding of the name.
+fn is_local_declaration(before: &str, after: &str) -> bool {
+    let last_word = before
+        .rsplit(|c: char| !is_ident_char(c))
+        .find(|w| !w.is_empty())
+        .unwrap_or("");
+    (before.ends_with(|c: char| c.is_whitespace())
+        && matches!(last_word, "let" | "mut" | "var" | "const" | "auto"))
+        || after.starts_with(":=")
+}
+
+/// How the function `body` accesses the module-level `name`, with the
+/// 0-based line offset of the first such access; a write anywhere in the
+/// body makes the function a writer.
+///
+/// Returns `None` when the body never mentions the name, or shadows it with
+/// a parameter or local declaration. In Python, rebinding a name without a
+/// `global` statement creates a local, so that also counts as shadowing.
+pub fn scan_access(body: &str, name: &str, language: Language) -> Option<(Access, u32)> {
+    let python = language == Language::Python;
+    let js = matches!(
+        language,
+        Language::JavaScript | Language::Jsx | Language::TypeScript | Language::Tsx
+    );
+    let mut lines = body.lines().enumerate();
+    // The first line is the signature: a parameter of the same name shadows.
+    let (_, signature) = lines.next()?;
+    if word_positions(signature, name).next().is_some() {
+        return None;
+    }
+    let declared_global = python
+        && body.lines().any(|l| {
+            let t = l.trim_start();
+            t.strip_prefix("global ")
+                .or_else(|| t.strip_prefix("nonlocal "))
+                .is_some_and(|names| names.split(',').any(|n| n.trim() == name))
+        });
+
+    let mut first_read = None;
+    let mut first_write = None;
+    for (offset, line) in lines {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("//")
+            || trimmed.starts_with('#')
+            || trimmed.starts_with('*')
+            || trimmed.starts_with("/*")
+            || trimmed.starts_with("global ")
+            || trimmed.starts_with("nonlocal ")
+        {
+            continue;
+        }
+        for pos in word_positions(line, name) {
+            let before = line[..pos].trim_end();
+            let after = line[pos + name.len()..].trim_start();
+            // A member of some other object, not the global itself.
+            if before.ends_with('.') || before.ends_with("->") || before.ends_with("::") {
+                continue;
+            }
+            // Keyword arguments and object literal keys.
+            if (before.ends_with(['(', ',']) && python && rebinds(after))
+                || (js && after.starts_with(':') && before.ends_with(['{', ',']))
+            {
+                continue;
+            }
+            if is_local_declaration(&line[..pos], after) {
+                return None;
+            }
+            if is_write(before, after) {
+                if python && rebinds(after) && !declared_global {
+                    return None;
+                }
+                first_write.get_or_insert(offset as u32);
+            } else {
+                first_read.get_or_insert(offset as u32);
+            }
+        }
+    }
+    match (first_write, first_read) {
+        (Some(line), _) => Some((Access::Write, line)),
+        (None, Some(line)) => Some((Access::Read, line)),
+        (None, None) => None,
+    }
+}
+
+/// Whether a field declaration is static, and whether it is also frozen
+/// (`final`, `readonly`, `const`).
+fn static_modifiers(decl: &str) -> (bool, bool) {
+    let words: HashSet<&str> = decl
+        .split(|c: char| !is_ident_char(c))
+        .filter(|w| !w.is_empty())
+        .collect();
+    (
+        words.contains("static"),
+        ["final", "readonly", "const"]
+            .iter()
+            .any(|w| words.contains(w)),
+    )
+}
+
+// ---------------------------------------------------------------------------
+// Inventory
+// ---------------------------------------------------------------------------
+
+fn accessor(node: &CodeNode, line: u32) -> StateAccessor {
+    StateAccessor {
+        node_id: node.id.clone(),
+        name: node
+            .qualified_name
+            .clone()
+            .unwrap_or_else(|| node.name.clone()),
+        kind: node.kind,
+        file_path: node.file_path.clone(),
+        line,
+    }
+}
+
+fn first_line(body: Option<&str>) -> &str {
+    body.and_then(|b| b.lines().next()).unwrap_or("").trim()
+}
+
+/// Node and edge batch size when reading the graph.
+const BATCH_SIZE: usize = 500;
+
+/// Kinds that can hold state or scope it.
+fn is_inventoried(kind: NodeKind) -> bool {
+    matches!(
+        kind,
+        NodeKind::Variable
+            | NodeKind::Property
+            | NodeKind::Function
+            | NodeKind::Method
+            | NodeKind::Class
+            | NodeKind::Struct
+            | NodeKind::Interface
+            | NodeKind::Trait
+            | NodeKind::Enum
+    )
+}
+
+/// Inventory all global mutable state in the graph, most-written first.
+pub fn inventory_global_state(store: &GraphStore) -> Result<Vec<GlobalState>> {
+    // Only imports and field accesses are kept from the edges.
+    let mut imported_by: HashMap<String, HashSet<String>> = HashMap::new();
+    let mut access_edges: Vec<CodeEdge> = Vec::new();
+    for batch in store.iter_edges(BATCH_SIZE) {
+        for e in batch? {
+            match e.kind {
+                EdgeKind::Imports => {
+                    imported_by.entry(e.target).or_default().insert(e.file_path);
+                }
+                EdgeKind::Reads | EdgeKind::Writes => access_edges.push(e),
+                _ => {}
+            }
+        }
+    }
+    let access_sources: HashSet<&str> = access_edges.iter().map(|e| e.source.as_str()).collect();
+
+    // Files that import each file, resolved while the nodes stream by; only
+    // nodes that can hold, scope or access state are kept.
+    let mut importers: HashMap<String, HashSet<String>> = HashMap::new();
+    let mut nodes: Vec<CodeNode> = Vec::new();
+    for batch in store.iter_nodes(BATCH_SIZE) {
+        for n in batch? {
+            if let Some(files) = imported_by.remove(&n.id) {
+                let others: Vec<String> = files.into_iter().filter(|f| *f != n.file_path).collect();
+                if !others.is_empty() {
+                    importers
+                        .entry(n.file_path.clone())
+                        .or_default()
+                        .extend(others);
+                }
+            }
+            if is_inventoried(n.kind) || access_sources.contains(n.id.as_str()) {
+                nodes.push(n);
+            }
+        }
+    }
+
+    let by_id: HashMap<&str, &CodeNode> = nodes.iter().map(|n| (n.id.as_str(), n)).collect();
+    let classes: HashSet<&str> = nodes
+        .iter()
+        .filter(|n| matches!(n.kind, NodeKind::Class | NodeKind::Struct))
+        .map(|n| n.name.as_str())
+        .collect();
+
+    let mut scopes: HashMap<&str, Vec<&CodeNode>> = HashMap::new();
+    let mut functions: HashMap<&str, Vec<&CodeNode>> = HashMap::new();
+    for n in &nodes {
+        if matches!(
+            n.kind,
+            NodeKind::Function
+                | NodeKind::Method
+                | NodeKind::Class
+                | NodeKind::Struct
+                | NodeKind::Interface
+                | NodeKind::Trait
+                | NodeKind::Enum
+        ) {
+            scopes.entry(n.file_path.as_str()).or_default().push(n);
+        }
+        if matches!(n.kind, NodeKind::Function | NodeKind::Method) && n.body.is_some() {
+            functions.entry(n.file_path.as_str()).or_default().push(n);
+        }
+    }
+
+    // Field access edges per field.
+    let mut field_access: HashMap<&str, Vec<&CodeEdge>> = HashMap::new();
+    for e in &access_edges {
+        field_access.entry(e.target.as_str()).or_default().push(e);
+    }
+
+    let mut states = Vec::new();
+    for n in &nodes {
+        let state = match n.kind {
+            NodeKind::Variable => module_variable(n, &scopes, &functions, &importers, &classes),
+            NodeKind::Property => static_field(n, &by_id, &field_access),
+            _ => None,
+        };
+        states.extend(state);
+    }
+    states.sort_by(|a, b| {
+        b.writers
+            .len()
+            .cmp(&a.writers.len())
+            .then(b.readers.len().cmp(&a.readers.len()))
+            .then(a.node.file_path.cmp(&b.node.file_path))
+            .then(a.node.start_line.cmp(&b.node.start_line))
+    });
+    Ok(states)
+}
+
+fn module_variable(
+    n: &CodeNode,
+    scopes: &HashMap<&str, Vec<&CodeNode>>,
+    functions: &HashMap<&str, Vec<&CodeNode>>,
+    importers: &HashMap<String, HashSet<String>>,
+    classes: &HashSet<&str>,
+) -> Option<GlobalState> {
+    let decl = strip_visibility(first_line(n.body.as_deref()));
+    if !is_value_declaration(decl, n.language) {
+        return None;
+    }
+    // Module-level: not inside any function or type in the same file.
+    let enclosed = scopes.get(n.file_path.as_str()).is_some_and(|s| {
+        s.iter().any(|s| {
+            s.start_line <= n.start_line
+                && n.end_line <= s.end_line
+                && !(s.start_line == n.start_line && s.name == n.name)
+        })
+    });
+    if enclosed {
+        return None;
+    }
+
+    // Same file, importing files, and (for Go) the rest of the package.
+    let dir = n.file_path.rsplit_once('/').map_or("", |(d, _)| d);
+    let mut files: Vec<&str> = vec![n.file_path.as_str()];
+    if let Some(imp) = importers.get(n.file_path.as_str()) {
+        files.extend(imp.iter().map(String::as_str));
+    }
+    if n.language == Language::Go {
+        files.extend(
+            functions
+                .keys()
+                .copied()
+                .filter(|f| *f != n.file_path && f.rsplit_once('/').map_or("", |(d, _)| d) == dir),
+        );
+    }
+
+    let mut writers = Vec::new();
+    let mut readers = Vec::new();
+    for f in files
+        .iter()
+        .flat_map(|f| functions.get(f).into_iter().flatten())
+    {
+        let body = f.body.as_deref().unwrap_or("");
+        if !body.contains(n.name.as_str()) {
+            continue;
+        }
+        match scan_access(body, &n.name, n.language) {
+            Some((Access::Write, offset)) => writers.push(accessor(f, f.start_line + offset)),
+            Some((Access::Read, offset)) => readers.push(accessor(f, f.start_line + offset)),
+            None => {}
+        }
+    }
+
+    let singleton = initializer(decl).and_then(|v| instantiated_class(v, classes));
+    let kind = if singleton.is_some() {
+        GlobalStateKind::Singleton
+    } else if declared_mutable(decl, n.language) || !writers.is_empty() {
+        GlobalStateKind::ModuleVariable
+    } else {
+        return None;
+    };
+    Some(GlobalState {
+        node: n.clone(),
+        kind,
+        declaration: decl.to_string(),
+        writers,
+        readers,
+    })
+}
+
+fn static_field(
+    n: &CodeNode,
+    by_id: &HashMap<&str, &CodeNode>,
+    field_access: &HashMap<&str, Vec<&CodeEdge>>,
+) -> Option<GlobalState> {
+    let decl = first_line(n.body.as_deref());
+    let (is_static, frozen) = static_modifiers(decl);
+    if !is_static {
+        return None;
+    }
+    let owner = n
+        .qualified_name
+        .as_deref()
+        .and_then(|q| q.rsplit_once('.'))
+        .map(|(owner, _)| owner.rsplit('.').next().unwrap_or(owner));
+    let value = initializer(decl);
+    // `static Foo instance`, `static instance: Foo`, `= new Foo()` inside Foo.
+    let singleton = owner.is_some_and(|owner| {
+        decl.contains(&format!("{owner} {}", n.name))
+            || decl.contains(&format!("{}: {owner}", n.name))
+            || value.is_some_and(|v| instantiated_class(v, &HashSet::from([owner])).is_some())
+    });
+    let kind = if singleton {
+        GlobalStateKind::Singleton
+    } else if !frozen || value.is_some_and(is_container) {
+        GlobalStateKind::StaticField
+    } else {
+        return None;
+    };
+
+    let mut writers: Vec<StateAccessor> = Vec::new();
+    let mut readers: Vec<StateAccessor> = Vec::new();
+    for e in field_access.get(n.id.as_str()).into_iter().flatten() {
+        let Some(src) = by_id.get(e.source.as_str()) else {
+            continue;
+        };
+        let list = if e.kind == EdgeKind::Writes {
+            &mut writers
+        } else {
+            &mut readers
+        };
+        if !list.iter().any(|a| a.node_id == src.id) {
+            list.push(accessor(src, e.line));
+        }
+    }
+    // A function that writes is listed once, as a writer.
+    readers.retain(|r| !writers.iter().any(|w| w.node_id == r.node_id));
+    Some(GlobalState {
+        node: n.clone(),
+        kind,
+        declaration: decl.to_string(),
+        writers,
+        readers,
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::schema::initialize_database;
+
+    #[test]
+    fn scan_access_classifies_reads_writes_and_shadowing() {
+        let ts = Language::TypeScript;
+        assert_eq!(
+            scan_access("function bump() {\n  counter += 1;\n}", "counter", ts),
+            Some((Access::Write, 1))
+        );
+        assert_eq!(
+            scan_access(
+                "function show() {\n  log(counter);\n  cache.set(k, 1);\n}",
+                "cache",
+                ts
+            ),
+            Some((Access::Write, 2))
+        );
+        assert_eq!(
+            scan_access("function show() {\n  return counter * 2;\n}", "counter", ts),
+            Some((Access::Read, 1))
+        );
+        assert_eq!(
+            scan_access(
+                "function f() {\n  let counter = 0;\n  counter++;\n}",
+                "counter",
+                ts
+            ),
+            None
+        );
+        assert_eq!(
+            scan_access("function f(counter) {\n  counter++;\n}", "counter", ts),
+            None
+        );
+        assert_eq!(
+            scan_access("function f() {\n  return { counter: 1 };\n}", "counter", ts),
+            None
+        );
+
+        let py = Language::Python;
+        // Rebinding without `global` makes a local.
+        assert_eq!(scan_access("def f():\n    total = 1\n", "total", py), None);
+        assert_eq!(
+            scan_access("def f():\n    global total\n    total = 1\n", "total", py),
+            Some((Access::Write, 2))
+        );
+        assert_eq!(
+            scan_access("def f():\n    registry[key] = fn\n", "registry", py),
+            Some((Access::Write, 1))
+        );
+        assert_eq!(scan_access("def f():\n    g(total=1)\n", "total", py), None);
+    }
+
+    #[test]
+    fn inventory_finds_module_state_statics_and_singletons() {
+        let conn = initialize_database(":memory:").unwrap();
+        let store = GraphStore::from_connection(conn);
+        let node = |id: &str, name: &str, kind, file: &str, lines: (u32, u32), lang, body: &str| {
+            CodeNode {
+                id: id.to_string(),
+                name: name.to_string(),
+                qualified_name: None,
+                kind,
+                file_path: file.to_string(),
+                start_line: lines.0,
+                end_line: lines.1,
+                start_column: 0,
+                end_column: 0,
+                language: lang,
+                body: Some(body.to_string()),
+                documentation: None,
+                exported: None,
+            }
+        };
+        let py = Language::Python;
+        let java = Language::Java;
+        let mut instance = node(
+            "p:inst",
+            "INSTANCE",
+            NodeKind::Property,
+            "Cfg.java",
+            (2, 2),
+            java,
+            "private static final Config INSTANCE = new Config();",
+        );
+        instance.qualified_name = Some("Config.INSTANCE".into());
+        let mut hits = node(
+            "p:hits",
+            "hits",
+            NodeKind::Property,
+            "Cfg.java",
+            (3, 3),
+            java,
+            "static int hits = 0;",
+        );
+        hits.qualified_name = Some("Config.hits".into());
+        let mut name = node(
+            "p:name",
+            "NAME",
+            NodeKind::Property,
+            "Cfg.java",
+            (4, 4),
+            java,
+            "static final String NAME = \"cfg\";",
+        );
+        name.qualified_name = Some("Config.NAME".into());
+        let mut bump = node(
+            "m:bump",
+            "bump",
+            NodeKind::Method,
+            "Cfg.java",
+            (5, 7),
+            java,
+            "void bump() {\n  hits++;\n}",
+        );
+        bump.qualified_name = Some("Config.bump".into());
+        store
+            .upsert_nodes(&[
+                node(
+                    "c:cfg",
+                    "Config",
+                    NodeKind::Class,
+                    "Cfg.java",
+                    (1, 8),
+                    java,
+                    "class Config {",
+                ),
+                instance,
+                hits,
+                name,
+                bump,
+                node(
+                    "v:reg",
+                    "registry",
+                    NodeKind::Variable,
+                    "app.py",
+                    (1, 1),
+                    py,
+                    "registry = {}",
+                ),
+                node(
+                    "v:lim",
+                    "LIMIT",
+                    NodeKind::Variable,
+                    "app.py",
+                    (2, 2),
+                    py,
+                    "LIMIT = 10",
+                ),
+                node(
+                    "f:add",
+                    "register",
+                    NodeKind::Function,
+                    "app.py",
+                    (4, 5),
+                    py,
+                    "def register(name, fn):\n    registry[name] = fn",
+                ),
+                node(
+                    "f:get",
+                    "lookup",
+                    NodeKind::Function,
+                    "use.py",
+                    (1, 2),
+                    py,
+                    "def lookup(name):\n    return registry.get(name, LIMIT)",
+                ),
+                node(
+                    "v:tmp",
+                    "tmp",
+                    NodeKind::Variable,
+                    "use.py",
+                    (2, 2),
+                    py,
+                    "tmp = []",
+                ),
+            ])
+            .unwrap();
+        store
+            .upsert_edges(&[
+                CodeEdge {
+                    source: "m:bump".into(),
+                    target: "p:hits".into(),
+                    kind: EdgeKind::Writes,
+                    file_path: "Cfg.java".into(),
+                    line: 6,
+                    metadata: None,
+                },
+                CodeEdge {
+                    source: "file:use.py".into(),
+                    target: "v:reg".into(),
+                    kind: EdgeKind::Imports,
+                    file_path: "use.py".into(),
+                    line: 1,
+                    metadata: None,
+                },
+            ])
+            .unwrap();
+
+        let states = inventory_global_state(&store).unwrap();
+        let found: Vec<(&str, GlobalStateKind)> = states
+            .iter()
+            .map(|s| (s.node.name.as_str(), s.kind))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("registry", GlobalStateKind::ModuleVariable),
+                ("hits", GlobalStateKind::StaticField),
+                ("INSTANCE", GlobalStateKind::Singleton),
+            ]
+        );
+        let registry = &states[0];
+        assert_eq!(registry.writers[0].name, "register");
+        assert_eq!(registry.writers[0].line, 5);
+        assert_eq!(registry.readers[0].file_path, "use.py");
+        assert_eq!(states[1].writers[0].name, "Config.bump");
+    }
+}
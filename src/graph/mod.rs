@@ -1,11 +1,36 @@
 //! Graph layer — SQLite-backed graph store, search, and ranking.
 
+pub mod adjacency;
+pub mod ann;
+pub mod api_docs;
+pub mod api_guard;
+pub mod architecture;
+pub mod backend;
+pub mod cfg;
+pub mod change_impact;
+pub mod clones;
+pub mod complete;
 pub mod complexity;
+pub mod coupling;
 pub mod dataflow;
+pub mod dossier;
+pub mod duplicates;
+pub mod entrypoints;
+pub mod examples;
 pub mod expansion;
+pub mod export;
+pub mod federation;
+pub mod fuzzy;
+pub mod global_state;
+pub mod pool;
+#[cfg(feature = "postgres")]
+pub mod postgres;
 pub mod ranking;
+pub mod rename;
 #[cfg(feature = "reranking")]
 pub mod reranker;
 pub mod search;
+pub mod stats;
 pub mod store;
+pub mod test_gaps;
 pub mod traversal;
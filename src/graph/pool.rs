@@ -0,0 +1,192 @@
+//! Read-only connection pool for [`GraphStore`].
+//!
+//! A `GraphStore` owns a single `rusqlite::Connection`, which is `!Sync`, so
+//! long-running servers historically serialized every query behind one
+//! mutex. With the database in WAL mode, any number of read-only
+//! connections can query concurrently with the single writer (indexer or
+//! file watcher). [`ReadPool`] hands out such read-only stores on demand
+//! and recycles them when the guard is dropped. The MCP server runs its
+//! read-only tools on pooled connections, the visualization server every
+//! request.
+
+use std::ops::Deref;
+use std::sync::Mutex;
+
+use crate::error::Result;
+use crate::graph::store::GraphStore;
+
+/// Idle read-only stores kept around by default.
+pub const DEFAULT_MAX_IDLE: usize = 4;
+
+/// A pool of read-only [`GraphStore`]s over one on-disk database.
+///
+/// Connections are opened lazily, so the pool never holds more connections
+/// than were ever used at once, and at most `max_idle` once they are
+/// returned. In-memory databases cannot be shared between connections and
+/// are not supported.
+pub struct ReadPool {
+    db_path: String,
+    max_idle: usize,
+    idle: Mutex<Vec<GraphStore>>,
+}
+
+impl std::fmt::Debug for ReadPool {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ReadPool")
+            .field("db_path", &self.db_path)
+            .field("max_idle", &self.max_idle)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ReadPool {
+    /// Create a pool over the database at `db_path`, keeping up to
+    /// `max_idle` returned connections open for reuse.
+    pub fn new(db_path: &str, max_idle: usize) -> Self {
+        Self {
+            db_path: db_path.to_string(),
+            max_idle,
+            idle: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Borrow a read-only store, opening a new connection if none is idle.
+    pub fn get(&self) -> Result<PooledStore<'_>> {
+        Ok(PooledStore {
+            store: Some(self.take()?),
+            pool: self,
+        })
+    }
+
+    /// Take a read-only store out of the pool, for callers that cannot hold
+    /// a [`PooledStore`] borrow. Hand it back with [`put_back`](Self::put_back).
+    pub fn take(&self) -> Result<GraphStore> {
+        let reused = self.idle.lock().unwrap_or_else(|e| e.into_inner()).pop();
+        match reused {
+            Some(store) => Ok(store),
+            None => GraphStore::open_read_only(&self.db_path),
+        }
+    }
+
+    /// Number of connections currently waiting for reuse.
+    pub fn idle_count(&self) -> usize {
+        self.idle.lock().unwrap_or_else(|e| e.into_inner()).len()
+    }
+
+    /// Return a store taken with [`take`](Self::take) for reuse.
+    pub fn put_back(&self, store: GraphStore) {
+        let mut idle = self.idle.lock().unwrap_or_else(|e| e.into_inner());
+        if idle.len() < self.max_idle {
+            idle.push(store);
+        }
+    }
+}
+
+/// A read-only store borrowed from a [`ReadPool`]; returned on drop.
+pub struct PooledStore<'a> {
+    store: Option<GraphStore>,
+    pool: &'a ReadPool,
+}
+
+impl Deref for PooledStore<'_> {
+    type Target = GraphStore;
+
+    fn deref(&self) -> &GraphStore {
+        self.store.as_ref().expect("pooled store taken before drop")
+    }
+}
+
+impl Drop for PooledStore<'_> {
+    fn drop(&mut self) {
+        if let Some(store) = self.store.take() {
+            self.pool.put_back(store);
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{CodeNode, Language, NodeKind};
+
+    fn node(id: &str) -> CodeNode {
+        CodeNode {
+            id: id.to_string(),
+            name: id.to_string(),
+            qualified_name: None,
+            kind: NodeKind::Function,
+            file_path: "a.ts".to_string(),
+            start_line: 1,
+            end_line: 2,
+            start_column: 0,
+            end_column: 0,
+            language: Language::TypeScript,
+            body: None,
+            documentation: None,
+            exported: None,
+        }
+    }
+
+    fn setup() -> (tempfile::TempDir, String, GraphStore) {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("graph.db").to_str().unwrap().to_string();
+        let writer = GraphStore::new(&path).unwrap();
+        (dir, path, writer)
+    }
+
+    #[test]
+    fn readers_see_committed_writes() {
+        let (_dir, path, writer) = setup();
+        let pool = ReadPool::new(&path, DEFAULT_MAX_IDLE);
+
+        writer.upsert_node(&node("n1")).unwrap();
+        let reader = pool.get().unwrap();
+        assert!(reader.get_node("n1").unwrap().is_some());
+        assert!(reader.upsert_node(&node("n2")).is_err());
+    }
+
+    #[test]
+    fn connections_are_recycled_up_to_max_idle() {
+        let (_dir, path, _writer) = setup();
+        let pool = ReadPool::new(&path, 1);
+
+        let a = pool.get().unwrap();
+        let b = pool.get().unwrap();
+        assert_eq!(pool.idle_count(), 0);
+        drop(a);
+        drop(b);
+        assert_eq!(pool.idle_count(), 1);
+
+        let _c = pool.get().unwrap();
+        assert_eq!(pool.idle_count(), 0);
+    }
+
+    #[test]
+    fn reads_do_not_block_open_write_transaction() {
+        let (_dir, path, writer) = setup();
+        writer.upsert_node(&node("n1")).unwrap();
+        let pool = ReadPool::new(&path, DEFAULT_MAX_IDLE);
+
+        let tx = writer.conn.unchecked_transaction().unwrap();
+        tx.execute("DELETE FROM nodes", []).unwrap();
+
+        // The uncommitted delete is invisible and does not lock the reader out.
+        let reader = pool.get().unwrap();
+        assert!(reader.get_node("n1").unwrap().is_some());
+        tx.commit().unwrap();
+        drop(reader);
+
+        assert!(pool.get().unwrap().get_node("n1").unwrap().is_none());
+    }
+
+    #[test]
+    fn missing_database_is_an_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = ReadPool::new(dir.path().join("nope.db").to_str().unwrap(), 2);
+        assert!(pool.get().is_err());
+    }
+}
@@ -0,0 +1,412 @@
+//! Postgres graph backend (feature `postgres`).
+//!
+//! Stores the core graph — `nodes` and `edges` — in a shared Postgres
+//! database using the same columns and JSON encodings as the SQLite schema,
+//! so rows round-trip unchanged between backends. Intended as a central
+//! index that CI publishes to and developers pull from (see
+//! [`copy_graph`](crate::graph::backend::copy_graph)); search, embeddings
+//! and the other side tables stay in the local SQLite store.
+//!
+//! To keep several repositories in one database, give each its own schema
+//! through the connection string, e.g. `...?options=-csearch_path%3Dmyrepo`.
+
+use std::sync::Mutex;
+
+use postgres::{Client, NoTls, Row};
+
+use crate::db::converters::{parse_edge_properties, parse_node_metadata};
+use crate::error::Result;
+use crate::graph::backend::GraphBackend;
+use crate::graph::store::{
+    build_edge_properties, build_name_tokens, build_node_metadata, compute_simple_hash,
+    detect_is_test, GraphStats,
+};
+use crate::types::{CodeEdge, CodeNode, EdgeKind, Language, NodeKind};
+
+// ---------------------------------------------------------------------------
+// SQL constants
+// ---------------------------------------------------------------------------
+
+const CREATE_SCHEMA_SQL: &str = "\
+CREATE TABLE IF NOT EXISTS nodes (
+  id TEXT PRIMARY KEY,
+  type TEXT NOT NULL,
+  name TEXT NOT NULL,
+  qualified_name TEXT,
+  file_path TEXT NOT NULL,
+  start_line INTEGER NOT NULL,
+  end_line INTEGER NOT NULL,
+  start_column INTEGER NOT NULL DEFAULT 0,
+  end_column INTEGER NOT NULL DEFAULT 0,
+  language TEXT NOT NULL,
+  signature TEXT,
+  doc_comment TEXT,
+  source_hash TEXT,
+  metadata TEXT,
+  name_tokens TEXT,
+  is_test BOOLEAN NOT NULL DEFAULT FALSE
+);
+CREATE INDEX IF NOT EXISTS idx_nodes_file_path ON nodes(file_path);
+CREATE INDEX IF NOT EXISTS idx_nodes_name ON nodes(name);
+CREATE TABLE IF NOT EXISTS edges (
+  id BIGSERIAL PRIMARY KEY,
+  source_id TEXT NOT NULL,
+  target_id TEXT NOT NULL,
+  type TEXT NOT NULL,
+  properties TEXT,
+  UNIQUE (source_id, target_id, type)
+);
+CREATE INDEX IF NOT EXISTS idx_edges_source ON edges(source_id);
+CREATE INDEX IF NOT EXISTS idx_edges_target ON edges(target_id);";
+
+const UPSERT_NODE_SQL: &str = "\
+INSERT INTO nodes (id, type, name, qualified_name, file_path, start_line, end_line, start_column, end_column, language, signature, doc_comment, source_hash, metadata, name_tokens, is_test)
+VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16)
+ON CONFLICT (id) DO UPDATE SET
+  type = EXCLUDED.type,
+  name = EXCLUDED.name,
+  qualified_name = EXCLUDED.qualified_name,
+  file_path = EXCLUDED.file_path,
+  start_line = EXCLUDED.start_line,
+  end_line = EXCLUDED.end_line,
+  start_column = EXCLUDED.start_column,
+  end_column = EXCLUDED.end_column,
+  language = EXCLUDED.language,
+  signature = EXCLUDED.signature,
+  doc_comment = EXCLUDED.doc_comment,
+  source_hash = EXCLUDED.source_hash,
+  metadata = EXCLUDED.metadata,
+  name_tokens = EXCLUDED.name_tokens,
+  is_test = EXCLUDED.is_test";
+
+const UPSERT_EDGE_SQL: &str = "\
+INSERT INTO edges (source_id, target_id, type, properties)
+VALUES ($1, $2, $3, $4)
+ON CONFLICT (source_id, target_id, type) DO UPDATE SET
+  properties = EXCLUDED.properties";
+
+const DELETE_EDGES_BY_FILE_SQL: &str = "\
+DELETE FROM edges WHERE source_id IN (SELECT id FROM nodes WHERE file_path = $1)
+   OR target_id IN (SELECT id FROM nodes WHERE file_path = $1)";
+
+const DELETE_NODES_BY_FILE_SQL: &str = "DELETE FROM nodes WHERE file_path = $1";
+
+// ---------------------------------------------------------------------------
+// PostgresStore
+// ---------------------------------------------------------------------------
+
+/// Graph backend over a Postgres connection.
+///
+/// `postgres::Client` needs `&mut self` for every query, so it sits behind a
+/// mutex to satisfy the `&self` receivers of [`GraphBackend`].
+pub struct PostgresStore {
+    client: Mutex<Client>,
+}
+
+impl std::fmt::Debug for PostgresStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PostgresStore").finish_non_exhaustive()
+    }
+}
+
+impl PostgresStore {
+    /// Connect to `url` (a libpq-style connection string or
+    /// `postgres://` URL) and create the tables if they are missing.
+    pub fn connect(url: &str) -> Result<Self> {
+        let mut client = Client::connect(url, NoTls)?;
+        client.batch_execute(CREATE_SCHEMA_SQL)?;
+        Ok(Self {
+            client: Mutex::new(client),
+        })
+    }
+
+    fn client(&self) -> std::sync::MutexGuard<'_, Client> {
+        self.client.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn query_nodes(
+        &self,
+        sql: &str,
+        params: &[&(dyn postgres::types::ToSql + Sync)],
+    ) -> Result<Vec<CodeNode>> {
+        Ok(self
+            .client()
+            .query(sql, params)?
+            .iter()
+            .map(row_to_node)
+            .collect())
+    }
+
+    fn query_edges(
+        &self,
+        sql: &str,
+        params: &[&(dyn postgres::types::ToSql + Sync)],
+    ) -> Result<Vec<CodeEdge>> {
+        Ok(self
+            .client()
+            .query(sql, params)?
+            .iter()
+            .map(row_to_edge)
+            .collect())
+    }
+
+    fn count(&self, sql: &str) -> Result<usize> {
+        let count: i64 = self.client().query_one(sql, &[])?.get(0);
+        Ok(count as usize)
+    }
+}
+
+/// Upsert `nodes` inside `tx`.
+fn insert_nodes(
+    tx: &mut postgres::Transaction<'_>,
+    nodes: &[CodeNode],
+) -> std::result::Result<(), postgres::Error> {
+    let stmt = tx.prepare(UPSERT_NODE_SQL)?;
+    for node in nodes {
+        let is_test = detect_is_test(
+            &node.name,
+            &node.file_path,
+            node.language.as_str(),
+            node.kind.as_str(),
+        );
+        tx.execute(
+            &stmt,
+            &[
+                &node.id,
+                &node.kind.as_str(),
+                &node.name,
+                &node.qualified_name,
+                &node.file_path,
+                &(node.start_line as i32),
+                &(node.end_line as i32),
+                &(node.start_column as i32),
+                &(node.end_column as i32),
+                &node.language.as_str(),
+                &node.body,
+                &node.documentation,
+                &compute_simple_hash(&node.id),
+                &build_node_metadata(node),
+                &build_name_tokens(&node.name, node.qualified_name.as_deref()),
+                &is_test,
+            ],
+        )?;
+    }
+    Ok(())
+}
+
+/// Upsert `edges` inside `tx`.
+fn insert_edges(
+    tx: &mut postgres::Transaction<'_>,
+    edges: &[CodeEdge],
+) -> std::result::Result<(), postgres::Error> {
+    let stmt = tx.prepare(UPSERT_EDGE_SQL)?;
+    for edge in edges {
+        tx.execute(
+            &stmt,
+            &[
+                &edge.source,
+                &edge.target,
+                &edge.kind.as_str(),
+                &build_edge_properties(edge),
+            ],
+        )?;
+    }
+    Ok(())
+}
+
+fn row_to_node(row: &Row) -> CodeNode {
+    let kind: String = row.get("type");
+    let language: String = row.get("language");
+    let metadata: Option<String> = row.get("metadata");
+    let (body, exported) = parse_node_metadata(metadata.as_deref());
+    CodeNode {
+        id: row.get("id"),
+        name: row.get("name"),
+        qualified_name: row.get("qualified_name"),
+        kind: NodeKind::from_str_loose(&kind).unwrap_or(NodeKind::Variable),
+        file_path: row.get("file_path"),
+        start_line: row.get::<_, i32>("start_line") as u32,
+        end_line: row.get::<_, i32>("end_line") as u32,
+        start_column: row.get::<_, i32>("start_column") as u32,
+        end_column: row.get::<_, i32>("end_column") as u32,
+        language: Language::from_str_loose(&language).unwrap_or(Language::TypeScript),
+        body,
+        documentation: row.get("doc_comment"),
+        exported,
+    }
+}
+
+fn row_to_edge(row: &Row) -> CodeEdge {
+    let kind: String = row.get("type");
+    let properties: Option<String> = row.get("properties");
+    let (file_path, line, metadata) = parse_edge_properties(properties.as_deref());
+    CodeEdge {
+        source: row.get("source_id"),
+        target: row.get("target_id"),
+        kind: EdgeKind::from_str_loose(&kind).unwrap_or(EdgeKind::References),
+        file_path,
+        line,
+        metadata,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// GraphBackend
+// ---------------------------------------------------------------------------
+
+impl GraphBackend for PostgresStore {
+    fn upsert_nodes(&self, nodes: &[CodeNode]) -> Result<()> {
+        let mut client = self.client();
+        let mut tx = client.transaction()?;
+        insert_nodes(&mut tx, nodes)?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn upsert_edges(&self, edges: &[CodeEdge]) -> Result<()> {
+        let mut client = self.client();
+        let mut tx = client.transaction()?;
+        insert_edges(&mut tx, edges)?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn replace_file_data(
+        &self,
+        file_path: &str,
+        nodes: &[CodeNode],
+        edges: &[CodeEdge],
+    ) -> Result<()> {
+        let mut client = self.client();
+        let mut tx = client.transaction()?;
+        tx.execute(DELETE_EDGES_BY_FILE_SQL, &[&file_path])?;
+        tx.execute(DELETE_NODES_BY_FILE_SQL, &[&file_path])?;
+        insert_nodes(&mut tx, nodes)?;
+        insert_edges(&mut tx, edges)?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn delete_file_nodes(&self, file_path: &str) -> Result<()> {
+        let mut client = self.client();
+        let mut tx = client.transaction()?;
+        tx.execute(DELETE_EDGES_BY_FILE_SQL, &[&file_path])?;
+        tx.execute(DELETE_NODES_BY_FILE_SQL, &[&file_path])?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn get_node(&self, id: &str) -> Result<Option<CodeNode>> {
+        Ok(self
+            .query_nodes("SELECT * FROM nodes WHERE id = $1", &[&id])?
+            .pop())
+    }
+
+    fn get_nodes_by_file(&self, file_path: &str) -> Result<Vec<CodeNode>> {
+        self.query_nodes("SELECT * FROM nodes WHERE file_path = $1", &[&file_path])
+    }
+
+    fn get_nodes_by_name(&self, name: &str) -> Result<Vec<CodeNode>> {
+        self.query_nodes("SELECT * FROM nodes WHERE name = $1", &[&name])
+    }
+
+    fn get_out_edges(&self, node_id: &str, edge_type: Option<&str>) -> Result<Vec<CodeEdge>> {
+        match edge_type {
+            Some(t) => self.query_edges(
+                "SELECT * FROM edges WHERE source_id = $1 AND type = $2",
+                &[&node_id, &t],
+            ),
+            None => self.query_edges("SELECT * FROM edges WHERE source_id = $1", &[&node_id]),
+        }
+    }
+
+    fn get_in_edges(&self, node_id: &str, edge_type: Option<&str>) -> Result<Vec<CodeEdge>> {
+        match edge_type {
+            Some(t) => self.query_edges(
+                "SELECT * FROM edges WHERE target_id = $1 AND type = $2",
+                &[&node_id, &t],
+            ),
+            None => self.query_edges("SELECT * FROM edges WHERE target_id = $1", &[&node_id]),
+        }
+    }
+
+    fn get_all_nodes(&self) -> Result<Vec<CodeNode>> {
+        self.query_nodes("SELECT * FROM nodes", &[])
+    }
+
+    fn get_all_edges(&self) -> Result<Vec<CodeEdge>> {
+        self.query_edges("SELECT * FROM edges", &[])
+    }
+
+    fn get_stats(&self) -> Result<GraphStats> {
+        Ok(GraphStats {
+            nodes: self.count("SELECT count(*) FROM nodes")?,
+            edges: self.count("SELECT count(*) FROM edges")?,
+            files: self.count("SELECT count(DISTINCT file_path) FROM nodes")?,
+        })
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::schema::initialize_database;
+    use crate::graph::backend::copy_graph;
+    use crate::graph::store::GraphStore;
+
+    /// Round-trips a graph through a live server; point
+    /// `CODEGRAPH_TEST_POSTGRES_URL` at a scratch database to run it.
+    #[test]
+    #[ignore = "needs a Postgres server (CODEGRAPH_TEST_POSTGRES_URL)"]
+    fn round_trips_graph_through_postgres() {
+        let url = std::env::var("CODEGRAPH_TEST_POSTGRES_URL").unwrap();
+        let pg = PostgresStore::connect(&url).unwrap();
+
+        let local = GraphStore::from_connection(initialize_database(":memory:").unwrap());
+        let node = |id: &str, file: &str| CodeNode {
+            id: id.to_string(),
+            name: id.to_string(),
+            qualified_name: Some(format!("mod.{id}")),
+            kind: NodeKind::Function,
+            file_path: file.to_string(),
+            start_line: 4,
+            end_line: 9,
+            start_column: 2,
+            end_column: 1,
+            language: Language::Rust,
+            body: Some("fn x() {}".into()),
+            documentation: Some("Docs".into()),
+            exported: Some(true),
+        };
+        local
+            .upsert_nodes(&[node("pg_a", "src/a.rs"), node("pg_b", "src/b.rs")])
+            .unwrap();
+        local
+            .upsert_edge(&CodeEdge {
+                source: "pg_a".into(),
+                target: "pg_b".into(),
+                kind: EdgeKind::Calls,
+                file_path: "src/a.rs".into(),
+                line: 5,
+                metadata: None,
+            })
+            .unwrap();
+
+        let stats = copy_graph(&local, &pg).unwrap();
+        assert_eq!((stats.nodes, stats.edges), (2, 1));
+        let a = pg.get_node("pg_a").unwrap().unwrap();
+        assert_eq!(a.qualified_name.as_deref(), Some("mod.pg_a"));
+        assert_eq!((a.start_line, a.end_line), (4, 9));
+        assert_eq!(a.body.as_deref(), Some("fn x() {}"));
+        assert_eq!(a.exported, Some(true));
+        let out = pg.get_out_edges("pg_a", Some("calls")).unwrap();
+        assert_eq!(out[0].line, 5);
+
+        let back = GraphStore::from_connection(initialize_database(":memory:").unwrap());
+        assert_eq!(copy_graph(&pg, &back).unwrap().nodes, 2);
+    }
+}
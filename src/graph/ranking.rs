@@ -13,6 +13,8 @@ use std::fmt;
 
 use rusqlite::params;
 
+use crate::error::Result;
+use crate::graph::adjacency::Direction;
 use crate::graph::store::GraphStore;
 
 // ---------------------------------------------------------------------------
@@ -117,26 +119,23 @@ impl<'a> GraphRanking<'a> {
             }
 
             // Distribute each node's rank to its outgoing neighbors.
+            let mut dangling = 0.0_f64;
             for i in 0..n {
-                if let Some(targets) = graph.out_links.get(&i) {
-                    if !targets.is_empty() {
+                match graph.out_links.get(&i) {
+                    Some(targets) if !targets.is_empty() => {
                         let share = (damping * scores[i]) / targets.len() as f64;
                         for &t in targets {
                             next[t] += share;
                         }
-                    } else {
-                        // Dangling node: distribute evenly.
-                        let share = (damping * scores[i]) / n as f64;
-                        for j in 0..n {
-                            next[j] += share;
-                        }
-                    }
-                } else {
-                    // No entry at all — also a dangling node.
-                    let share = (damping * scores[i]) / n as f64;
-                    for j in 0..n {
-                        next[j] += share;
                     }
+                    // Dangling node: its rank is spread evenly below.
+                    _ => dangling += scores[i],
+                }
+            }
+            if dangling > 0.0 {
+                let share = (damping * dangling) / n as f64;
+                for v in next.iter_mut() {
+                    *v += share;
                 }
             }
 
@@ -345,8 +344,27 @@ impl<'a> GraphRanking<'a> {
     // Private: load graph into memory
     // -------------------------------------------------------------------
 
-    /// Load the full graph into memory as adjacency lists indexed by integer.
+    /// Load the full graph into memory as adjacency lists indexed by integer,
+    /// reusing the store's cached [`Adjacency`](crate::graph::adjacency::Adjacency)
+    /// when enabled.
     fn load_graph(&self) -> LoadedGraph {
+        if let Ok(Some(adj)) = self.store.adjacency() {
+            let mut out_links: HashMap<usize, Vec<usize>> = HashMap::new();
+            for i in 0..adj.node_count() as u32 {
+                let targets: Vec<usize> = adj
+                    .neighbors(i, Direction::Outgoing, None)
+                    .map(|t| t as usize)
+                    .collect();
+                if !targets.is_empty() {
+                    out_links.insert(i as usize, targets);
+                }
+            }
+            return LoadedGraph {
+                node_ids: adj.ids().to_vec(),
+                out_links,
+            };
+        }
+
         let conn = &self.store.conn;
 
         // Load all node IDs.
@@ -397,6 +415,27 @@ impl<'a> GraphRanking<'a> {
     }
 }
 
+// ---------------------------------------------------------------------------
+// Stored PageRank
+// ---------------------------------------------------------------------------
+
+/// Recompute global PageRank and store it in the `node_rank` table, where
+/// search reads it as a ranking prior. Returns the number of nodes ranked.
+pub fn refresh_node_rank(store: &GraphStore) -> Result<usize> {
+    let ranked = GraphRanking::new(store).compute_page_rank(0.85, 100);
+    let tx = store.conn.unchecked_transaction()?;
+    tx.execute("DELETE FROM node_rank", [])?;
+    {
+        let mut insert =
+            tx.prepare_cached("INSERT INTO node_rank (node_id, page_rank) VALUES (?1, ?2)")?;
+        for node in &ranked {
+            insert.execute(params![node.node_id, node.score])?;
+        }
+    }
+    tx.commit()?;
+    Ok(ranked.len())
+}
+
 // ---------------------------------------------------------------------------
 // Tests
 // ---------------------------------------------------------------------------
@@ -502,6 +541,21 @@ mod tests {
         );
     }
 
+    #[test]
+    fn page_rank_same_with_adjacency_cache() {
+        let store = setup();
+        seed_diamond(&store);
+        let ranking = GraphRanking::new(&store);
+        let via_sql = ranking.compute_page_rank(0.85, 30);
+        store.enable_adjacency_cache();
+        let via_adjacency = ranking.compute_page_rank(0.85, 30);
+        assert_eq!(via_sql.len(), via_adjacency.len());
+        for (a, b) in via_sql.iter().zip(&via_adjacency) {
+            assert_eq!(a.node_id, b.node_id);
+            assert!((a.score - b.score).abs() < 1e-12);
+        }
+    }
+
     #[test]
     fn page_rank_sink_node_ranks_highest() {
         let store = setup();
@@ -524,6 +578,31 @@ mod tests {
 
     // -- personalized_page_rank --------------------------------------------
 
+    #[test]
+    fn refresh_node_rank_stores_page_rank() {
+        let store = setup();
+        seed_diamond(&store);
+        assert_eq!(refresh_node_rank(&store).unwrap(), 4);
+        let top: String = store
+            .conn
+            .query_row(
+                "SELECT node_id FROM node_rank ORDER BY page_rank DESC LIMIT 1",
+                [],
+                |r| r.get(0),
+            )
+            .unwrap();
+        let expected = GraphRanking::new(&store).compute_page_rank(0.85, 100);
+        assert_eq!(top, expected[0].node_id);
+
+        // A second refresh replaces the rows rather than adding to them.
+        assert_eq!(refresh_node_rank(&store).unwrap(), 4);
+        let rows: i64 = store
+            .conn
+            .query_row("SELECT COUNT(*) FROM node_rank", [], |r| r.get(0))
+            .unwrap();
+        assert_eq!(rows, 4);
+    }
+
     #[test]
     fn ppr_on_nonexistent_node_returns_empty() {
         let store = setup();
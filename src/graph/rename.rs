@@ -0,0 +1,431 @@
+//! Rename impact: every place a rename of a symbol has to touch.
+//!
+//! The graph knows which lines reference a symbol (its incoming edges) but
+//! not where on the line. The planner goes back to the source on disk, finds
+//! the name as a whole word on the definition line and on each reference
+//! line, and turns every occurrence into an edit with an exact column.
+//!
+//! What the graph cannot prove is reported separately as unverified sites
+//! instead of being edited: string literals spelling the name (`getattr(obj,
+//! "charge")`, `emit("charge")`, reflection, routing tables), references the
+//! resolver left unresolved under this name, and reference lines on which
+//! the name could not be located.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::error::{CodeGraphError, Result};
+use crate::graph::store::GraphStore;
+use crate::resolution::literals::LiteralKind;
+use crate::types::CodeNode;
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// One occurrence of the old name to replace.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RenameEdit {
+    pub file_path: String,
+    /// 1-based line.
+    pub line: u32,
+    /// 1-based character column of the first character of the name.
+    pub column: u32,
+    /// 1-based character column just past the name.
+    pub end_column: u32,
+    /// `definition`, or the kind of the referencing edge (`calls`,
+    /// `imports`, ...).
+    pub site: String,
+}
+
+/// Why a site is reported instead of edited.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UnverifiedKind {
+    /// A string literal equal to the name: a dynamic lookup, perhaps.
+    StringLiteral,
+    /// A reference the resolver could not bind, made under this name.
+    Unresolved,
+    /// A reference line on which the name does not appear.
+    NameNotOnLine,
+    /// A file the planner could not read.
+    Unreadable,
+}
+
+impl UnverifiedKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::StringLiteral => "string_literal",
+            Self::Unresolved => "unresolved",
+            Self::NameNotOnLine => "name_not_on_line",
+            Self::Unreadable => "unreadable",
+        }
+    }
+}
+
+/// A site that may refer to the symbol but is not part of the edit plan.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnverifiedSite {
+    pub file_path: String,
+    pub line: u32,
+    pub kind: UnverifiedKind,
+    /// The literal, the unresolved specifier, or the edge kind.
+    pub detail: String,
+}
+
+/// Everything a rename of one symbol touches.
+#[derive(Debug, Clone)]
+pub struct RenamePlan {
+    pub old_name: String,
+    pub new_name: String,
+    /// Ordered by file, then line and column.
+    pub edits: Vec<RenameEdit>,
+    /// Ordered by file and line.
+    pub unverified: Vec<UnverifiedSite>,
+    /// Symbols already named `new_name` that the rename would collide
+    /// with: those in a file the rename edits, and exported ones when the
+    /// symbol is exported.
+    pub conflicts: Vec<CodeNode>,
+}
+
+impl RenamePlan {
+    /// The edits grouped by file, each file's edits last-to-first so they
+    /// can be applied in order without shifting the ones still to come.
+    pub fn edits_by_file(&self) -> BTreeMap<&str, Vec<&RenameEdit>> {
+        let mut files: BTreeMap<&str, Vec<&RenameEdit>> = BTreeMap::new();
+        for edit in &self.edits {
+            files.entry(&edit.file_path).or_default().push(edit);
+        }
+        for edits in files.values_mut() {
+            edits.sort_by_key(|e| std::cmp::Reverse((e.line, e.column)));
+        }
+        files
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Planning
+// ---------------------------------------------------------------------------
+
+/// Whether `name` can stand in for an identifier in the languages indexed:
+/// letters, digits, `_` and `$`, not starting with a digit.
+pub fn is_valid_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_alphabetic() || c == '_' || c == '$')
+        && chars.all(is_ident_char)
+}
+
+/// Plan renaming `target` to `new_name`, reading sources under `root`.
+pub fn plan_rename(
+    store: &GraphStore,
+    target: &CodeNode,
+    new_name: &str,
+    root: &Path,
+) -> Result<RenamePlan> {
+    if !is_valid_identifier(new_name) {
+        return Err(CodeGraphError::Other(format!(
+            "\"{new_name}\" is not a valid identifier"
+        )));
+    }
+    if new_name == target.name {
+        return Err(CodeGraphError::Other(format!(
+            "\"{new_name}\" is already the symbol's name"
+        )));
+    }
+    let name = target.name.as_str();
+
+    // Lines to edit: the definition, then every incoming reference.
+    let mut sites: Vec<(String, u32, String)> = vec![(
+        target.file_path.clone(),
+        target.start_line,
+        "definition".to_string(),
+    )];
+    for edge in store.get_in_edges(&target.id, None)? {
+        if edge.line > 0 {
+            sites.push((edge.file_path, edge.line, edge.kind.as_str().to_string()));
+        }
+    }
+
+    let policy = crate::sandbox::current();
+    let mut sources: HashMap<String, Option<Vec<String>>> = HashMap::new();
+    let mut seen_lines = HashSet::new();
+    let mut edits = Vec::new();
+    let mut unverified = Vec::new();
+    for (file_path, line, site) in sites {
+        if !seen_lines.insert((file_path.clone(), line)) {
+            continue;
+        }
+        let lines = sources.entry(file_path.clone()).or_insert_with(|| {
+            let abs = root.join(&file_path);
+            if !policy.allows_file(&abs) {
+                return None;
+            }
+            std::fs::read_to_string(abs)
+                .ok()
+                .map(|text| text.lines().map(str::to_string).collect())
+        });
+        let Some(lines) = lines else {
+            unverified.push(UnverifiedSite {
+                file_path,
+                line,
+                kind: UnverifiedKind::Unreadable,
+                detail: site,
+            });
+            continue;
+        };
+        let Some(text) = lines.get(line as usize - 1) else {
+            unverified.push(UnverifiedSite {
+                file_path,
+                line,
+                kind: UnverifiedKind::NameNotOnLine,
+                detail: site,
+            });
+            continue;
+        };
+        let (code, quoted) = find_occurrences(text, name);
+        if code.is_empty() && quoted.is_empty() {
+            unverified.push(UnverifiedSite {
+                file_path: file_path.clone(),
+                line,
+                kind: UnverifiedKind::NameNotOnLine,
+                detail: site.clone(),
+            });
+        }
+        for column in code {
+            edits.push(RenameEdit {
+                file_path: file_path.clone(),
+                line,
+                column,
+                end_column: column + name.chars().count() as u32,
+                site: site.clone(),
+            });
+        }
+        if !quoted.is_empty() {
+            unverified.push(UnverifiedSite {
+                file_path,
+                line,
+                kind: UnverifiedKind::StringLiteral,
+                detail: name.to_string(),
+            });
+        }
+    }
+
+    // Dynamic references the graph cannot see.
+    for literal in store.find_literals(name, true, Some(LiteralKind::String))? {
+        unverified.push(UnverifiedSite {
+            file_path: literal.file_path,
+            line: literal.line,
+            kind: UnverifiedKind::StringLiteral,
+            detail: literal.value,
+        });
+    }
+    for unresolved in store.get_unresolved_refs(None)? {
+        let last = unresolved
+            .specifier
+            .rsplit(['.', ':', '/'])
+            .next()
+            .unwrap_or_default();
+        if last == name {
+            unverified.push(UnverifiedSite {
+                file_path: unresolved.file_path,
+                line: unresolved.line,
+                kind: UnverifiedKind::Unresolved,
+                detail: unresolved.specifier,
+            });
+        }
+    }
+    let mut seen_unverified = HashSet::new();
+    unverified.retain(|u| seen_unverified.insert((u.file_path.clone(), u.line, u.kind)));
+    unverified.sort_by(|a, b| (&a.file_path, a.line).cmp(&(&b.file_path, b.line)));
+    edits.sort_by(|a, b| (&a.file_path, a.line, a.column).cmp(&(&b.file_path, b.line, b.column)));
+
+    let edited: HashSet<&str> = edits.iter().map(|e| e.file_path.as_str()).collect();
+    let exported = target.exported == Some(true);
+    let conflicts = store
+        .get_nodes_by_name(new_name)?
+        .into_iter()
+        .filter(|n| edited.contains(n.file_path.as_str()) || (exported && n.exported == Some(true)))
+        .collect();
+
+    Ok(RenamePlan {
+        old_name: target.name.clone(),
+        new_name: new_name.to_string(),
+        edits,
+        unverified,
+        conflicts,
+    })
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '$'
+}
+
+/// 1-based character columns of `name` as a whole word on `line`, split
+/// into occurrences in code and occurrences inside string literals.
+fn find_occurrences(line: &str, name: &str) -> (Vec<u32>, Vec<u32>) {
+    let (mut code, mut quoted) = (Vec::new(), Vec::new());
+    let mut quote: Option<char> = None;
+    let mut escaped = false;
+    let mut prev: Option<char> = None;
+    for (column, (at, c)) in line.char_indices().enumerate() {
+        if let Some(q) = quote {
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == q {
+                quote = None;
+            }
+        } else if matches!(c, '"' | '\'' | '`') {
+            quote = Some(c);
+            prev = Some(c);
+            continue;
+        }
+        let at_word_start = !prev.is_some_and(is_ident_char);
+        prev = Some(c);
+        if !at_word_start || !line[at..].starts_with(name) {
+            continue;
+        }
+        if line[at + name.len()..]
+            .chars()
+            .next()
+            .is_some_and(is_ident_char)
+        {
+            continue;
+        }
+        let column = column as u32 + 1;
+        if quote.is_some() {
+            quoted.push(column);
+        } else {
+            code.push(column);
+        }
+    }
+    (code, quoted)
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resolution::literals::Literal;
+    use crate::types::{CodeEdge, EdgeKind, Language, NodeKind};
+
+    fn node(id: &str, name: &str, file: &str, line: u32) -> CodeNode {
+        CodeNode {
+            id: id.to_string(),
+            name: name.to_string(),
+            qualified_name: None,
+            kind: NodeKind::Function,
+            file_path: file.to_string(),
+            start_line: line,
+            end_line: line + 2,
+            start_column: 0,
+            end_column: 1,
+            language: Language::TypeScript,
+            body: None,
+            documentation: None,
+            exported: Some(true),
+        }
+    }
+
+    fn call(source: &str, target: &str, file: &str, line: u32) -> CodeEdge {
+        CodeEdge {
+            source: source.to_string(),
+            target: target.to_string(),
+            kind: EdgeKind::Calls,
+            file_path: file.to_string(),
+            line,
+            metadata: None,
+        }
+    }
+
+    #[test]
+    fn finds_whole_word_occurrences_outside_strings() {
+        let (code, quoted) =
+            find_occurrences(r#"charge(x); charger(); log("charge"); charge"#, "charge");
+        assert_eq!(code, vec![1, 38]);
+        assert_eq!(quoted, vec![28]);
+        assert!(is_valid_identifier("chargeCard"));
+        assert!(!is_valid_identifier("9lives"));
+        assert!(!is_valid_identifier("charge card"));
+    }
+
+    #[test]
+    fn plans_edits_and_flags_dynamic_references() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        std::fs::write(
+            root.join("src/pay.ts"),
+            "export function charge(a) {\n  return a;\n}\n",
+        )
+        .unwrap();
+        std::fs::write(
+            root.join("src/cart.ts"),
+            "function checkout() {\n  charge(1); charge(2);\n  bus.emit(\"charge\");\n}\n",
+        )
+        .unwrap();
+        let conn = crate::db::schema::initialize_database(":memory:").unwrap();
+        let store = GraphStore::from_connection(conn);
+        let target = node("f:charge", "charge", "src/pay.ts", 1);
+        store
+            .upsert_nodes(&[
+                target.clone(),
+                node("f:checkout", "checkout", "src/cart.ts", 1),
+                node("f:bill", "bill", "src/cart.ts", 9),
+            ])
+            .unwrap();
+        store
+            .upsert_edge(&call("f:checkout", "f:charge", "src/cart.ts", 2))
+            .unwrap();
+        store
+            .replace_literals_for_file(
+                "src/cart.ts",
+                &[Literal {
+                    file_path: "src/cart.ts".to_string(),
+                    line: 3,
+                    kind: LiteralKind::String,
+                    value: "charge".to_string(),
+                    name: None,
+                    node_id: Some("f:checkout".to_string()),
+                }],
+            )
+            .unwrap();
+
+        let plan = plan_rename(&store, &target, "bill", root).unwrap();
+        let sites: Vec<(&str, u32, u32, &str)> = plan
+            .edits
+            .iter()
+            .map(|e| (e.file_path.as_str(), e.line, e.column, e.site.as_str()))
+            .collect();
+        assert_eq!(
+            sites,
+            vec![
+                ("src/cart.ts", 2, 3, "calls"),
+                ("src/cart.ts", 2, 14, "calls"),
+                ("src/pay.ts", 1, 17, "definition"),
+            ]
+        );
+        assert_eq!(plan.unverified.len(), 1);
+        assert_eq!(plan.unverified[0].kind, UnverifiedKind::StringLiteral);
+        assert_eq!(plan.unverified[0].line, 3);
+        assert_eq!(plan.conflicts.len(), 1);
+        assert_eq!(plan.conflicts[0].id, "f:bill");
+
+        let by_file = plan.edits_by_file();
+        let cart: Vec<u32> = by_file["src/cart.ts"].iter().map(|e| e.column).collect();
+        assert_eq!(cart, vec![14, 3]);
+
+        assert!(plan_rename(&store, &target, "charge", root).is_err());
+        assert!(plan_rename(&store, &target, "not valid", root).is_err());
+    }
+}
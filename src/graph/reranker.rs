@@ -10,6 +10,10 @@
 //! (they encode the pair jointly) but dramatically more accurate because
 //! they see both query and document together.
 //!
+//! File-level results ([`FileSearchResult`]) are re-ranked the same way,
+//! with each file represented by its top matching symbols and their doc
+//! comments (see [`file_candidates`]).
+//!
 //! Feature-gated behind `reranking`.  Requires the `embedding` feature
 //! (and thus fastembed) to be enabled.
 //!
@@ -25,7 +29,20 @@
 #[cfg(feature = "reranking")]
 use fastembed::{RerankInitOptions, RerankResult, TextRerank};
 
-use crate::graph::search::SearchResult;
+use crate::graph::search::{FileSearchResult, SearchResult};
+use crate::graph::store::GraphStore;
+use crate::types::CodeNode;
+
+/// Longest doc comment taken per symbol when building a file's text.
+const MAX_DOC_CHARS: usize = 200;
+
+/// A file-level search result together with the text the cross-encoder
+/// scores it by.
+#[derive(Debug, Clone)]
+pub struct FileCandidate {
+    pub result: FileSearchResult,
+    pub text: String,
+}
 
 // ---------------------------------------------------------------------------
 // Reranker
@@ -98,6 +115,38 @@ impl Reranker {
 
         Ok(output)
     }
+
+    /// Re-rank file-level results against a query using the cross-encoder.
+    ///
+    /// Works like [`rerank`](Self::rerank): at most `top_k` files are
+    /// returned in cross-encoder order, with `relevance_score` replaced by
+    /// the cross-encoder score.
+    pub fn rerank_files(
+        &self,
+        query: &str,
+        candidates: &[FileCandidate],
+        top_k: usize,
+    ) -> Result<Vec<FileSearchResult>, String> {
+        if candidates.is_empty() || top_k == 0 {
+            return Ok(Vec::new());
+        }
+
+        let doc_refs: Vec<&str> = candidates.iter().map(|c| c.text.as_str()).collect();
+        let reranked: Vec<RerankResult> = self
+            .model
+            .rerank(query, doc_refs, false, None)
+            .map_err(|e| format!("Reranking failed: {e}"))?;
+
+        Ok(reranked
+            .into_iter()
+            .take(top_k)
+            .map(|rr| {
+                let mut result = candidates[rr.index].result.clone();
+                result.relevance_score = rr.score as f64;
+                result
+            })
+            .collect())
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -126,6 +175,49 @@ pub fn deep_search(
     reranker.rerank(query, &search_results, top_k)
 }
 
+/// File-level counterpart of [`deep_search`]: re-ranks BM25-aggregated
+/// file candidates (see [`file_candidates`]) through the cross-encoder.
+///
+/// This is what the MCP `codegraph_deep_file_query` tool calls.
+#[cfg(feature = "reranking")]
+pub fn deep_file_search(
+    query: &str,
+    reranker: &Reranker,
+    candidates: Vec<FileCandidate>,
+    top_k: usize,
+) -> Result<Vec<FileSearchResult>, String> {
+    if candidates.is_empty() {
+        return Ok(Vec::new());
+    }
+    reranker.rerank_files(query, &candidates, top_k)
+}
+
+/// Attach rerank text to file-level results by looking up each file's top
+/// symbols in `store`. Files whose symbols cannot be loaded fall back to
+/// their path and symbol names.
+pub fn file_candidates(store: &GraphStore, files: Vec<FileSearchResult>) -> Vec<FileCandidate> {
+    files
+        .into_iter()
+        .map(|result| {
+            let nodes = store
+                .get_nodes_by_file(&result.file_path)
+                .unwrap_or_default();
+            // Keep the top-symbol order, one node per name.
+            let symbols: Vec<&CodeNode> = result
+                .top_symbols
+                .iter()
+                .filter_map(|name| nodes.iter().find(|n| &n.name == name))
+                .collect();
+            let text = if symbols.is_empty() {
+                format!("{}: {}", result.file_path, result.top_symbols.join(", "))
+            } else {
+                file_result_to_rerank_text(&result.file_path, &symbols)
+            };
+            FileCandidate { result, text }
+        })
+        .collect()
+}
+
 // ---------------------------------------------------------------------------
 // Text construction
 // ---------------------------------------------------------------------------
@@ -146,6 +238,31 @@ fn search_result_to_rerank_text(result: &SearchResult) -> String {
     .to_string()
 }
 
+/// Build a text representation of a file for the cross-encoder from its
+/// top symbols.
+///
+/// Format: `"{file_path}: {kind} {name} — {doc}; {kind} {name}; ..."`, with
+/// each doc comment cut to its first [`MAX_DOC_CHARS`] characters.
+fn file_result_to_rerank_text(file_path: &str, symbols: &[&CodeNode]) -> String {
+    let parts: Vec<String> = symbols
+        .iter()
+        .map(|node| {
+            let head = format!("{} {}", node.kind.as_str(), node.name);
+            match node.documentation.as_deref().map(str::trim) {
+                Some(doc) if !doc.is_empty() => {
+                    let doc: String = doc.chars().take(MAX_DOC_CHARS).collect();
+                    format!(
+                        "{head} — {}",
+                        doc.split_whitespace().collect::<Vec<_>>().join(" ")
+                    )
+                }
+                _ => head,
+            }
+        })
+        .collect();
+    format!("{}: {}", file_path, parts.join("; "))
+}
+
 // ---------------------------------------------------------------------------
 // Tests
 // ---------------------------------------------------------------------------
@@ -345,6 +462,127 @@ mod tests {
         assert!(result[0].score != 0.5);
     }
 
+    // -- file_candidates ------------------------------------------------------
+
+    #[test]
+    fn file_candidates_use_top_symbols_and_docs() {
+        use crate::db::schema::initialize_database;
+        use crate::graph::search::HybridSearch;
+        use crate::types::{Language, NodeKind};
+
+        let store = GraphStore::from_connection(initialize_database(":memory:").unwrap());
+        let node = |name: &str, file: &str, line: u32, doc: Option<&str>| CodeNode {
+            id: format!("function:{file}:{name}:{line}"),
+            name: name.to_string(),
+            qualified_name: None,
+            kind: NodeKind::Function,
+            file_path: file.to_string(),
+            start_line: line,
+            end_line: line + 3,
+            start_column: 0,
+            end_column: 1,
+            language: Language::TypeScript,
+            body: None,
+            documentation: doc.map(str::to_string),
+            exported: Some(true),
+        };
+        store
+            .upsert_nodes(&[
+                node(
+                    "parseInvoice",
+                    "src/billing.ts",
+                    1,
+                    Some("Parse a raw\n   invoice payload."),
+                ),
+                node("parseRefund", "src/billing.ts", 10, None),
+                node("parseConfig", "src/config.ts", 1, None),
+            ])
+            .unwrap();
+
+        let files = HybridSearch::new(&store.conn)
+            .search_files("parse", 10)
+            .unwrap();
+        let candidates = file_candidates(&store, files);
+        assert_eq!(candidates.len(), 2);
+        let billing = candidates
+            .iter()
+            .find(|c| c.result.file_path == "src/billing.ts")
+            .unwrap();
+        assert!(billing.text.starts_with("src/billing.ts: function parse"));
+        assert!(billing
+            .text
+            .contains("function parseInvoice — Parse a raw invoice payload."));
+        assert!(billing.text.contains("function parseRefund"));
+
+        // A file that is no longer in the store keeps its symbol names.
+        let stale = FileSearchResult {
+            file_path: "src/gone.ts".to_string(),
+            matched_symbols: 1,
+            top_symbols: vec!["parseGone".to_string()],
+            relevance_score: 1.0,
+        };
+        let candidates = file_candidates(&store, vec![stale]);
+        assert_eq!(candidates[0].text, "src/gone.ts: parseGone");
+    }
+
+    #[test]
+    fn file_rerank_text_truncates_long_docs() {
+        use crate::types::{Language, NodeKind};
+
+        let doc = "word ".repeat(100);
+        let node = CodeNode {
+            id: "class:a.ts:Store:1".to_string(),
+            name: "Store".to_string(),
+            qualified_name: None,
+            kind: NodeKind::Class,
+            file_path: "a.ts".to_string(),
+            start_line: 1,
+            end_line: 2,
+            start_column: 0,
+            end_column: 1,
+            language: Language::TypeScript,
+            body: None,
+            documentation: Some(doc),
+            exported: None,
+        };
+        let text = file_result_to_rerank_text("a.ts", &[&node]);
+        assert!(text.starts_with("a.ts: class Store — word word"));
+        assert!(text.len() < "a.ts: class Store — ".len() + MAX_DOC_CHARS);
+    }
+
+    #[cfg(feature = "reranking")]
+    #[test]
+    fn reranker_reranks_files() {
+        let reranker = match Reranker::try_new() {
+            Ok(r) => r,
+            Err(_) => return,
+        };
+        let file = |path: &str, text: &str, score: f64| FileCandidate {
+            result: FileSearchResult {
+                file_path: path.to_string(),
+                matched_symbols: 1,
+                top_symbols: Vec::new(),
+                relevance_score: score,
+            },
+            text: text.to_string(),
+        };
+        let candidates = vec![
+            file(
+                "src/mail.ts",
+                "src/mail.ts: function sendEmail — Send an email",
+                5.0,
+            ),
+            file(
+                "src/json.ts",
+                "src/json.ts: function parseJson — Parse a JSON string",
+                1.0,
+            ),
+        ];
+        let reranked = deep_file_search("how to parse JSON", &reranker, candidates, 1).unwrap();
+        assert_eq!(reranked.len(), 1);
+        assert_eq!(reranked[0].file_path, "src/json.ts");
+    }
+
     // -- Non-feature-gated tests (always run) ---------------------------------
 
     #[test]
@@ -6,20 +6,41 @@
 //!
 //! Supports query intent detection to dynamically adjust FTS5/vector
 //! blending weights, and file-level search for grouped results.
+//!
+//! Queries are plain keywords by default, matched if any term matches.
+//! Power users can write `"exact phrases"`, `AND` / `OR` / `NOT`, and
+//! field-scoped terms (`name:parse file:src/indexer`); see
+//! [`parse_fts_query`].
+//!
+//! Results come back in a deterministic order (score, then node ID), so
+//! large result sets can be paged with `offset` or with the opaque
+//! `next_cursor` of the previous page; see [`encode_cursor`].
+//!
+//! A `path` option scopes results to a directory prefix (`src/server`) or
+//! glob (`src/server/**`, `*.rs`); see [`path_glob`]. Languages, kinds
+//! and paths can also be excluded; these negative filters, like `path`,
+//! are applied to each candidate list before fusion.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
-use rusqlite::{params, Connection};
+use rusqlite::{params, Connection, OptionalExtension};
+use sha2::{Digest, Sha256};
 
-use crate::error::Result;
-use crate::graph::expansion::expand_query;
+use crate::db::findings::{cosine, from_blob};
+use crate::error::{CodeGraphError, Result};
+use crate::graph::ann;
+use crate::graph::expansion::{expand_query_with, SynonymMap};
+use crate::graph::fuzzy;
+use crate::graph::store::{tagged_node_ids, TagFilter};
+use crate::indexer::embedder::EmbeddingEngine;
 
 // ---------------------------------------------------------------------------
 // Query intent detection
 // ---------------------------------------------------------------------------
 
 /// Detected intent of a search query, used to adjust RRF blending weights.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
 pub enum QueryIntent {
     /// Looks like a code symbol: camelCase, snake_case, PascalCase, dots, `::`.
     SymbolLookup,
@@ -164,7 +185,8 @@ pub fn detect_query_intent(query: &str) -> QueryIntent {
 }
 
 /// RRF blending weights for FTS5 and vector based on query intent.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
 pub struct BlendWeights {
     pub fts_weight: f64,
     pub vec_weight: f64,
@@ -211,7 +233,7 @@ pub struct FileSearchResult {
 // ---------------------------------------------------------------------------
 
 /// A single search result with composite scoring.
-#[derive(Debug, Clone, serde::Serialize)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct SearchResult {
     /// The unique node ID from the `nodes` table.
     pub node_id: String,
@@ -231,6 +253,68 @@ pub struct SearchResult {
     pub snippet: Option<String>,
 }
 
+/// Search results that may come from a fuzzy fallback, with the names
+/// matched instead of the query.
+#[derive(Debug, Clone, Default)]
+pub struct SuggestedResults {
+    /// Symbol names close to the query, closest first. Empty when the
+    /// query matched as written.
+    pub did_you_mean: Vec<String>,
+    pub results: Vec<SearchResult>,
+    /// Cursor of the following page, when there are more results.
+    pub next_cursor: Option<String>,
+    /// How the results were ranked, when [`SearchOptions::debug`] is set.
+    pub explanation: Option<SearchExplanation>,
+}
+
+/// Why a search ranked its results as it did: the query-wide signals, then
+/// one [`ResultExplanation`] per returned result, in the same order.
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchExplanation {
+    /// Detected query intent, which picks the blend weights.
+    pub intent: QueryIntent,
+    /// RRF weights of the keyword and vector lists.
+    pub blend_weights: BlendWeights,
+    /// Expansion terms searched besides the query (at half keyword weight).
+    pub expansions: Vec<String>,
+    /// Whether nothing matched as written and fuzzy name matching was used.
+    pub fuzzy_fallback: bool,
+    /// Weights of the per-node priors.
+    pub prior_weights: PriorWeights,
+    pub results: Vec<ResultExplanation>,
+}
+
+/// The signals behind one search result's score.
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResultExplanation {
+    pub node_id: String,
+    /// 1-based position among the keyword (FTS5) candidates.
+    pub fts_rank: Option<usize>,
+    /// BM25 score (inverted so higher = better).
+    pub fts_score: Option<f64>,
+    /// 1-based position among the vector candidates.
+    pub vec_rank: Option<usize>,
+    /// Cosine similarity to the query embedding.
+    pub vec_similarity: Option<f64>,
+    /// 1-based position among the expansion candidates.
+    pub expansion_rank: Option<usize>,
+    /// Expansion terms that match this result on their own.
+    pub expansion_terms: Vec<String>,
+    /// RRF score before priors.
+    pub fused_score: f64,
+    /// Stored priors of the node.
+    pub prior: Option<NodePrior>,
+    /// Factor the priors applied to `fused_score`.
+    pub prior_multiplier: f64,
+    /// Final score.
+    pub score: f64,
+}
+
+/// Most names a fuzzy fallback suggests.
+const MAX_SUGGESTIONS: usize = 5;
+
 /// Options that control search behaviour.
 #[derive(Debug, Clone, Default)]
 pub struct SearchOptions {
@@ -242,6 +326,148 @@ pub struct SearchOptions {
     pub node_type: Option<String>,
     /// Discard results below this RRF score (default 0).
     pub min_score: Option<f64>,
+    /// Keep only nodes carrying every one of these tags.
+    pub tags: Vec<TagFilter>,
+    /// Require every query term to match instead of any.
+    pub match_all: bool,
+    /// Results to skip before the first one returned.
+    pub offset: usize,
+    /// `next_cursor` of a previous page of the same search; takes
+    /// precedence over `offset`.
+    pub cursor: Option<String>,
+    /// Keep only symbols under this path prefix or matching this glob.
+    pub path: Option<String>,
+    /// Drop symbols in any of these languages.
+    pub exclude_languages: Vec<String>,
+    /// Drop symbols of any of these node types/kinds.
+    pub exclude_kinds: Vec<String>,
+    /// Drop symbols under any of these path prefixes or globs.
+    pub exclude_paths: Vec<String>,
+    /// Also explain the ranking (see [`SuggestedResults::explanation`]).
+    pub debug: bool,
+}
+
+impl SearchOptions {
+    /// Index of the first result of the requested page.
+    pub fn start(&self, query: &str) -> Result<usize> {
+        match &self.cursor {
+            Some(cursor) => decode_cursor(cursor, query, &self.cursor_scope()),
+            None => Ok(self.offset),
+        }
+    }
+
+    /// Everything besides the query that shapes the result list. A cursor
+    /// is only honoured under the same options it was issued for.
+    fn cursor_scope(&self) -> String {
+        format!(
+            "hybrid|{:?}|{:?}|{:?}|{:?}|{}|{}",
+            self.language,
+            self.node_type,
+            self.min_score,
+            self.tags,
+            self.match_all,
+            self.candidate_scope()
+        )
+    }
+
+    /// The filters applied to candidates before fusion, for cursor scopes
+    /// of searches that share them (see `codegraph_search`).
+    pub fn candidate_scope(&self) -> String {
+        format!(
+            "{:?}|{:?}|{:?}|{:?}",
+            self.path, self.exclude_languages, self.exclude_kinds, self.exclude_paths
+        )
+    }
+}
+
+/// The pre-fusion filters of [`SearchOptions`], as bound to
+/// `FTS_SEARCH_SQL` and `CANDIDATE_ADMITTED_SQL`. Exclusion lists are JSON
+/// arrays (read with `json_each`) so the statements stay cacheable.
+#[derive(Debug, Default)]
+struct CandidateFilter {
+    glob: Option<String>,
+    exclude_languages: Option<String>,
+    exclude_kinds: Option<String>,
+    exclude_globs: Option<String>,
+}
+
+impl CandidateFilter {
+    fn new(options: &SearchOptions) -> Self {
+        let json = |values: Vec<String>| {
+            (!values.is_empty()).then(|| serde_json::Value::from(values).to_string())
+        };
+        Self {
+            glob: options.path.as_deref().and_then(path_glob),
+            exclude_languages: json(options.exclude_languages.clone()),
+            exclude_kinds: json(options.exclude_kinds.clone()),
+            exclude_globs: json(
+                options
+                    .exclude_paths
+                    .iter()
+                    .filter_map(|p| path_glob(p))
+                    .collect(),
+            ),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.glob.is_none()
+            && self.exclude_languages.is_none()
+            && self.exclude_kinds.is_none()
+            && self.exclude_globs.is_none()
+    }
+}
+
+/// Opaque cursor for the page of `query`'s results starting at `offset`.
+/// `scope` describes the filters in effect; decoding with another query
+/// or scope fails rather than silently paging a different result list.
+pub fn encode_cursor(query: &str, scope: &str, offset: usize) -> String {
+    format!("{}.{}", offset, cursor_fingerprint(query, scope))
+}
+
+/// Offset of a cursor made by [`encode_cursor`] for the same `query` and
+/// `scope`.
+pub fn decode_cursor(cursor: &str, query: &str, scope: &str) -> Result<usize> {
+    cursor
+        .split_once('.')
+        .filter(|(_, fingerprint)| *fingerprint == cursor_fingerprint(query, scope))
+        .and_then(|(offset, _)| offset.parse().ok())
+        .ok_or_else(|| {
+            CodeGraphError::Other(format!(
+                "invalid cursor '{}': it belongs to a different query or filters",
+                cursor
+            ))
+        })
+}
+
+/// SQLite `GLOB` pattern for a path filter. A plain path is a prefix
+/// (`src/server` matches `src/server/auth.rs` and `src/server.rs`); a path
+/// with `*`, `?` or `[` is a glob over the whole relative path. `*` and
+/// `**` both match across directories, so `src/**/*.ts` and `src/*.ts`
+/// are the same filter.
+pub fn path_glob(spec: &str) -> Option<String> {
+    let spec = spec.trim();
+    let spec = spec.strip_prefix("./").unwrap_or(spec);
+    if spec.is_empty() || spec == "." {
+        return None;
+    }
+    if !spec.contains(['*', '?', '[']) {
+        return Some(format!("{}*", spec));
+    }
+    let mut glob = spec.replace("**/", "*");
+    while glob.contains("**") {
+        glob = glob.replace("**", "*");
+    }
+    Some(glob)
+}
+
+fn cursor_fingerprint(query: &str, scope: &str) -> String {
+    let digest = Sha256::new()
+        .chain_update(query.trim())
+        .chain_update([0])
+        .chain_update(scope)
+        .finalize();
+    digest[..6].iter().map(|b| format!("{:02x}", b)).collect()
 }
 
 // ---------------------------------------------------------------------------
@@ -270,14 +496,111 @@ SELECT n.id, n.name, n.type, n.file_path, n.language,
        n.signature, n.doc_comment,
        bm25(fts_nodes, 10.0, 8.0, 5.0, 3.0, 1.0, 7.0) AS rank
 FROM fts_nodes fts
-JOIN nodes n ON n.rowid = fts.rowid
+JOIN nodes n ON n.rowid = fts.rowid AND (?3 IS NULL OR n.file_path GLOB ?3)
+  AND (?4 IS NULL OR n.language NOT IN (SELECT value FROM json_each(?4)))
+  AND (?5 IS NULL OR n.type NOT IN (SELECT value FROM json_each(?5)))
+  AND (?6 IS NULL OR NOT EXISTS (SELECT 1 FROM json_each(?6) WHERE n.file_path GLOB value))
 WHERE fts_nodes MATCH ?1
-ORDER BY rank
+ORDER BY rank, n.id
 LIMIT ?2";
 
+/// Whether a node passes a [`CandidateFilter`]; same conditions as the
+/// join in `FTS_SEARCH_SQL`.
+const CANDIDATE_ADMITTED_SQL: &str = "\
+SELECT EXISTS (SELECT 1 FROM nodes n WHERE n.id = ?1
+  AND (?2 IS NULL OR n.file_path GLOB ?2)
+  AND (?3 IS NULL OR n.language NOT IN (SELECT value FROM json_each(?3)))
+  AND (?4 IS NULL OR n.type NOT IN (SELECT value FROM json_each(?4)))
+  AND (?5 IS NULL OR NOT EXISTS (SELECT 1 FROM json_each(?5) WHERE n.file_path GLOB value)))";
+
 const GET_NODE_LANGUAGE_SQL: &str = "\
 SELECT language FROM nodes WHERE id = ?1";
 
+/// The stored ranking priors of one node, with its PageRank divided by the
+/// highest in the graph (?2).
+const GET_NODE_PRIOR_SQL: &str = "\
+SELECT n.is_test, r.page_rank / ?2, g.committed_at
+FROM nodes n
+LEFT JOIN node_rank r ON r.node_id = n.id
+LEFT JOIN node_git_meta g ON g.node_id = n.id
+WHERE n.id = ?1";
+
+const MAX_PAGE_RANK_SQL: &str = "SELECT MAX(page_rank) FROM node_rank";
+
+/// Maximum relative boost given to freshly modified code.
+const FRESHNESS_WEIGHT: f64 = 0.05;
+
+/// Age (in days) at which the freshness boost halves.
+const FRESHNESS_HALF_LIFE_DAYS: f64 = 30.0;
+
+/// Score multiplier for a symbol last modified `age_days` ago.
+///
+/// Decays from `1 + FRESHNESS_WEIGHT` for code changed today towards `1.0`,
+/// so recency only breaks near-ties and never overrides relevance.
+pub fn freshness_multiplier(age_days: i64) -> f64 {
+    1.0 + FRESHNESS_WEIGHT * recency_decay(age_days)
+}
+
+/// 1 for code changed today, halving every [`FRESHNESS_HALF_LIFE_DAYS`].
+fn recency_decay(age_days: i64) -> f64 {
+    let age = age_days.max(0) as f64;
+    0.5_f64.powf(age / FRESHNESS_HALF_LIFE_DAYS)
+}
+
+/// Per-node ranking priors, loaded from the database by
+/// [`HybridSearch::load_priors`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NodePrior {
+    /// Global PageRank relative to the highest in the graph (0–1); 0 when
+    /// ranks have not been computed.
+    pub page_rank: f64,
+    /// Days since the symbol was last changed, when git metadata exists.
+    pub age_days: Option<i64>,
+    /// Whether the symbol is test code.
+    pub is_test: bool,
+}
+
+/// How strongly [`NodePrior`]s move fused scores. Each weight is the
+/// largest relative change it can make, so priors reorder near-ties
+/// without overriding relevance.
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PriorWeights {
+    /// Boost for the most central symbol, scaled by relative PageRank.
+    pub page_rank: f64,
+    /// Boost for code changed today, halving every 30 days.
+    pub recency: f64,
+    /// Fraction of the score taken off test code.
+    pub test_penalty: f64,
+}
+
+impl Default for PriorWeights {
+    fn default() -> Self {
+        Self {
+            page_rank: 0.1,
+            recency: FRESHNESS_WEIGHT,
+            test_penalty: 0.1,
+        }
+    }
+}
+
+impl PriorWeights {
+    /// Score multiplier for a node with `prior`.
+    pub fn multiplier(&self, prior: &NodePrior) -> f64 {
+        let centrality = 1.0 + self.page_rank * prior.page_rank.clamp(0.0, 1.0);
+        let recency = prior
+            .age_days
+            .map_or(1.0, |age| 1.0 + self.recency * recency_decay(age));
+        let test = if prior.is_test {
+            1.0 - self.test_penalty.clamp(0.0, 1.0)
+        } else {
+            1.0
+        };
+        centrality * recency * test
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Hybrid search engine
 // ---------------------------------------------------------------------------
@@ -292,12 +615,46 @@ SELECT language FROM nodes WHERE id = ?1";
 /// lengths.
 pub struct HybridSearch<'a> {
     conn: &'a Connection,
+    embedder: Option<&'a EmbeddingEngine>,
+    priors: PriorWeights,
+    synonyms: SynonymMap,
 }
 
 impl<'a> HybridSearch<'a> {
     /// Create a new search engine backed by `conn`.
+    ///
+    /// The embedding model is loaded on demand for each similarity search.
     pub fn new(conn: &'a Connection) -> Self {
-        Self { conn }
+        Self {
+            conn,
+            embedder: None,
+            priors: PriorWeights::default(),
+            synonyms: SynonymMap::default(),
+        }
+    }
+
+    /// Create a search engine that reuses an already loaded embedding model
+    /// (long-running processes such as the daemon keep one warm).
+    pub fn with_embedder(conn: &'a Connection, embedder: Option<&'a EmbeddingEngine>) -> Self {
+        Self {
+            conn,
+            embedder,
+            priors: PriorWeights::default(),
+            synonyms: SynonymMap::default(),
+        }
+    }
+
+    /// Blend node priors into fused scores with `weights` instead of the
+    /// defaults.
+    pub fn with_priors(mut self, weights: PriorWeights) -> Self {
+        self.priors = weights;
+        self
+    }
+
+    /// Expand queries with project synonyms on top of the built-in rules.
+    pub fn with_synonyms(mut self, synonyms: SynonymMap) -> Self {
+        self.synonyms = synonyms;
+        self
     }
 
     /// Execute a hybrid search: FTS5 keyword + vector similarity, fused
@@ -306,17 +663,55 @@ impl<'a> HybridSearch<'a> {
     /// Automatically detects query intent (symbol lookup vs semantic
     /// search) and adjusts FTS5/vector blending weights accordingly.
     pub fn search(&self, query: &str, options: &SearchOptions) -> Result<Vec<SearchResult>> {
+        Ok(self.search_with_suggestions(query, options)?.results)
+    }
+
+    /// [`search`](Self::search), also reporting the names used when no
+    /// keyword matched and the query fell back to fuzzy name matching
+    /// ([`search_fuzzy`](Self::search_fuzzy)). Names are only reported
+    /// when symbols carrying them survive the filters in `options`.
+    pub fn search_with_suggestions(
+        &self,
+        query: &str,
+        options: &SearchOptions,
+    ) -> Result<SuggestedResults> {
         let limit = options.limit.unwrap_or(20);
+        let start = options.start(query)?;
         // Fetch more candidates than needed so fusion has room to merge.
-        let fetch_limit = limit * 3;
-
-        let fts_results = self.search_by_keyword(query, fetch_limit)?;
-        let vec_results = self.search_by_similarity(query, fetch_limit);
+        // Earlier pages are fetched too: fused ranks depend on them.
+        let fetch_limit = (start + limit) * 3;
+
+        let parsed = parse_fts_query(query, options.match_all);
+        let strict = parsed.explicit || options.match_all;
+        let filter = CandidateFilter::new(options);
+        let mut fts_results = self.run_fts(&parsed.expr, fetch_limit, &filter)?;
+        let mut did_you_mean = Vec::new();
+        let fuzzy_fallback = fts_results.is_empty();
+        if fuzzy_fallback {
+            let fuzzy = self.search_fuzzy(query, fetch_limit, options)?;
+            did_you_mean = fuzzy.did_you_mean;
+            fts_results = fuzzy.results;
+        }
+        let mut vec_results = self.search_by_similarity(&parsed.text, fetch_limit);
+        if !filter.is_empty() {
+            vec_results.retain(|r| self.admits(&r.node_id, &filter));
+        }
+        if strict {
+            // Phrases, operators and field scopes must hold for every
+            // result; similarity only re-ranks the keyword matches.
+            let matched: HashSet<&str> = fts_results.iter().map(|r| r.node_id.as_str()).collect();
+            vec_results.retain(|r| matched.contains(r.node_id.as_str()));
+        }
 
         // Query expansion: generate alternative search terms and run
         // them through FTS5.  Expanded results are fused at 0.5x
         // weight (giving the original query 2x relative weight).
-        let expansions = expand_query(query);
+        // Precise queries are not broadened.
+        let expansions = if strict {
+            Vec::new()
+        } else {
+            expand_query_with(query, &self.synonyms)
+        };
         let expansion_fts = if expansions.len() > 1 {
             // Build an OR query from all expanded terms (skip index 0
             // which is the original query — already searched above).
@@ -325,7 +720,7 @@ impl<'a> HybridSearch<'a> {
             if safe.is_empty() {
                 Vec::new()
             } else {
-                self.search_by_keyword(&safe, fetch_limit)
+                self.run_fts(&safe, fetch_limit, &filter)
                     .unwrap_or_default()
             }
         } else {
@@ -333,10 +728,25 @@ impl<'a> HybridSearch<'a> {
         };
 
         // Detect intent and adjust blending weights.
-        let weights: BlendWeights = detect_query_intent(query).into();
-
-        let mut fused =
-            fuse_results_weighted(&fts_results, &vec_results, &expansion_fts, 60, weights);
+        let intent = detect_query_intent(query);
+        let weights: BlendWeights = intent.into();
+
+        let ids: Vec<&str> = fts_results
+            .iter()
+            .chain(&vec_results)
+            .chain(&expansion_fts)
+            .map(|r| r.node_id.as_str())
+            .collect();
+        let priors = self.load_priors(&ids);
+        let mut fused = fuse_results_with_priors(
+            &fts_results,
+            &vec_results,
+            &expansion_fts,
+            60,
+            weights,
+            &priors,
+            self.priors,
+        );
 
         // Apply optional filters.
         if let Some(ref lang) = options.language {
@@ -350,24 +760,155 @@ impl<'a> HybridSearch<'a> {
                 fused.retain(|r| r.score >= min_score);
             }
         }
+        if !options.tags.is_empty() {
+            let tagged: HashSet<String> = tagged_node_ids(self.conn, &options.tags)?
+                .into_iter()
+                .collect();
+            fused.retain(|r| tagged.contains(&r.node_id));
+        }
 
-        fused.truncate(limit);
-        Ok(fused)
+        let next_cursor = (fused.len() > start + limit)
+            .then(|| encode_cursor(query, &options.cursor_scope(), start + limit));
+        let results: Vec<SearchResult> = fused.into_iter().skip(start).take(limit).collect();
+        did_you_mean.retain(|name| results.iter().any(|r| r.name == *name));
+        let explanation = options.debug.then(|| {
+            let expansions = expansions.get(1..).unwrap_or_default().to_vec();
+            // Which expansion terms match each result by themselves.
+            let mut term_matches: HashMap<&str, Vec<String>> = HashMap::new();
+            for term in &expansions {
+                let safe = sanitize_fts_query(term);
+                if safe.is_empty() {
+                    continue;
+                }
+                for r in self
+                    .run_fts(&safe, fetch_limit, &filter)
+                    .unwrap_or_default()
+                {
+                    if let Some(hit) = results.iter().find(|x| x.node_id == r.node_id) {
+                        term_matches
+                            .entry(hit.node_id.as_str())
+                            .or_default()
+                            .push(term.clone());
+                    }
+                }
+            }
+            let rank_in = |list: &[SearchResult], id: &str| {
+                list.iter().position(|r| r.node_id == id).map(|i| i + 1)
+            };
+            let explained = results
+                .iter()
+                .map(|r| {
+                    let id = r.node_id.as_str();
+                    let prior = priors.get(id).copied();
+                    let multiplier = prior.map_or(1.0, |p| self.priors.multiplier(&p));
+                    ResultExplanation {
+                        node_id: r.node_id.clone(),
+                        fts_rank: rank_in(&fts_results, id),
+                        fts_score: r.fts_score,
+                        vec_rank: rank_in(&vec_results, id),
+                        vec_similarity: r.vec_score,
+                        expansion_rank: rank_in(&expansion_fts, id),
+                        expansion_terms: term_matches.remove(id).unwrap_or_default(),
+                        fused_score: r.score / multiplier,
+                        prior,
+                        prior_multiplier: multiplier,
+                        score: r.score,
+                    }
+                })
+                .collect();
+            SearchExplanation {
+                intent,
+                blend_weights: weights,
+                expansions,
+                fuzzy_fallback,
+                prior_weights: self.priors,
+                results: explained,
+            }
+        });
+        Ok(SuggestedResults {
+            did_you_mean,
+            results,
+            next_cursor,
+            explanation,
+        })
     }
 
     /// FTS5 keyword search on the `fts_nodes` virtual table.
     ///
     /// Uses the built-in BM25 ranking (exposed as `rank`). Queries are
     /// sanitized: special FTS5 syntax characters are quoted to prevent
-    /// user input from breaking the query.
+    /// user input from breaking the query, while phrases, `AND`/`OR`/`NOT`
+    /// and field scopes are translated (see [`parse_fts_query`]).
     pub fn search_by_keyword(&self, query: &str, limit: usize) -> Result<Vec<SearchResult>> {
-        let safe_query = sanitize_fts_query(query);
-        if safe_query.is_empty() {
+        self.search_by_keyword_in(query, limit, &SearchOptions::default())
+    }
+
+    /// [`search_by_keyword`](Self::search_by_keyword) restricted by the
+    /// pre-fusion filters of `options`: `path` and the exclusion lists.
+    /// Other options are ignored.
+    pub fn search_by_keyword_in(
+        &self,
+        query: &str,
+        limit: usize,
+        options: &SearchOptions,
+    ) -> Result<Vec<SearchResult>> {
+        let filter = CandidateFilter::new(options);
+        self.run_fts(&sanitize_fts_query(query), limit, &filter)
+    }
+
+    /// Fallback for queries that found nothing: look up symbols whose names
+    /// are within a few edits of the query terms (see [`fuzzy`]), so a typo
+    /// like `HybirdSearch` still finds `HybridSearch`. Suggestions come
+    /// from the whole graph; results only from candidates passing the
+    /// pre-fusion filters of `options`.
+    pub fn search_fuzzy(
+        &self,
+        query: &str,
+        limit: usize,
+        options: &SearchOptions,
+    ) -> Result<SuggestedResults> {
+        let suggestions = fuzzy::suggest_names(self.conn, query, MAX_SUGGESTIONS)?;
+        let did_you_mean: Vec<String> = suggestions.into_iter().map(|s| s.name).collect();
+        let expr = did_you_mean
+            .iter()
+            .map(|name| format!("name : \"{}\"", name.replace('"', "")))
+            .collect::<Vec<_>>()
+            .join(" OR ");
+        // FTS ranks exact name matches of every suggestion alike; keep the
+        // suggestion order instead.
+        let filter = CandidateFilter::new(options);
+        let mut results = self.run_fts(&expr, limit, &filter)?;
+        results.sort_by_key(|r| did_you_mean.iter().position(|n| *n == r.name));
+        Ok(SuggestedResults {
+            did_you_mean,
+            results,
+            next_cursor: None,
+            explanation: None,
+        })
+    }
+
+    /// Run an already sanitized FTS5 expression over the candidates
+    /// passing `filter`.
+    fn run_fts(
+        &self,
+        expr: &str,
+        limit: usize,
+        filter: &CandidateFilter,
+    ) -> Result<Vec<SearchResult>> {
+        if expr.is_empty() {
             return Ok(Vec::new());
         }
 
         let mut stmt = self.conn.prepare_cached(FTS_SEARCH_SQL)?;
-        let rows = stmt.query_map(params![safe_query, limit as i64], |row| {
+        let args = params![
+            expr,
+            limit as i64,
+            filter.glob,
+            filter.exclude_languages,
+            filter.exclude_kinds,
+            filter.exclude_globs,
+        ];
+        let rows = stmt.query_map(args, |row| {
             Ok(FtsRow {
                 id: row.get(0)?,
                 name: row.get(1)?,
@@ -407,17 +948,27 @@ impl<'a> HybridSearch<'a> {
     ///
     /// Embeds the query text, finds nearest neighbors by cosine distance
     /// in the `vec_embeddings` virtual table, and decorates each result
-    /// with node metadata.
+    /// with node metadata. When the database has an ANN sidecar
+    /// ([`ann`](crate::graph::ann)), neighbours come from it instead of a
+    /// full scan.
     ///
     /// Returns an empty `Vec` if no embedder is provided or if the
     /// `vec_embeddings` table has no data.
     pub fn search_by_similarity(&self, query: &str, limit: usize) -> Vec<SearchResult> {
         #[cfg(feature = "embedding")]
         {
-            // Try to get embedder; if unavailable, return empty
-            let embedder = match crate::indexer::embedder::EmbeddingEngine::try_new() {
-                Ok(e) => e,
-                Err(_) => return Vec::new(),
+            // Use the preloaded embedder, else try to load one; if
+            // unavailable, return empty
+            let loaded;
+            let embedder = match self.embedder {
+                Some(e) => e,
+                None => match EmbeddingEngine::try_new() {
+                    Ok(e) => {
+                        loaded = e;
+                        &loaded
+                    }
+                    Err(_) => return Vec::new(),
+                },
             };
 
             let query_vec = match embedder.embed(query) {
@@ -425,6 +976,10 @@ impl<'a> HybridSearch<'a> {
                 Err(_) => return Vec::new(),
             };
 
+            if let Some(index) = ann::cached_index(self.conn) {
+                return self.decorate_neighbours(index.search(&query_vec, limit));
+            }
+
             // Convert to JSON array for sqlite-vec MATCH
             let vec_json = match serde_json::to_string(&query_vec) {
                 Ok(j) => j,
@@ -478,15 +1033,163 @@ impl<'a> HybridSearch<'a> {
 
         #[cfg(not(feature = "embedding"))]
         {
-            let _ = (query, limit);
+            let _ = (query, limit, self.embedder);
             Vec::new()
         }
     }
 
+    /// The `k` nodes whose stored embeddings are closest to that of
+    /// `node_id`, most similar first, excluding the node itself. Scores are
+    /// cosine similarities.
+    ///
+    /// Neighbours come from the ANN sidecar when there is one, else from a
+    /// scan of `embedding_cache`. No model is loaded, so this works
+    /// without the `embedding` feature as long as the index has vectors.
+    /// Fails when `node_id` has no stored embedding.
+    pub fn similar_to(&self, node_id: &str, k: usize) -> Result<Vec<SearchResult>> {
+        let blob: Option<Vec<u8>> = self
+            .conn
+            .query_row(
+                "SELECT embedding FROM embedding_cache WHERE node_id = ?1",
+                params![node_id],
+                |row| row.get(0),
+            )
+            .optional()?;
+        let Some(blob) = blob else {
+            return Err(CodeGraphError::Embedding(format!(
+                "no stored embedding for node {node_id}"
+            )));
+        };
+        let vector = from_blob(&blob);
+
+        let neighbours = match ann::cached_index(self.conn) {
+            Some(index) => index.search(&vector, k + 1),
+            None => {
+                let mut stmt = self.conn.prepare_cached(
+                    "SELECT e.node_id, e.embedding FROM embedding_cache e
+                     JOIN nodes n ON n.id = e.node_id",
+                )?;
+                let rows = stmt.query_map([], |row| {
+                    Ok((row.get::<_, String>(0)?, row.get::<_, Vec<u8>>(1)?))
+                })?;
+                let mut scored: Vec<(String, f64)> = rows
+                    .flatten()
+                    .map(|(id, blob)| {
+                        let distance = 1.0 - cosine(&vector, &from_blob(&blob)) as f64;
+                        (id, distance)
+                    })
+                    .collect();
+                scored.sort_by(|a, b| a.1.total_cmp(&b.1).then_with(|| a.0.cmp(&b.0)));
+                scored.truncate(k + 1);
+                scored
+            }
+        };
+        let neighbours = neighbours
+            .into_iter()
+            .filter(|(id, _)| id != node_id)
+            .take(k)
+            .collect();
+        Ok(self
+            .decorate_neighbours(neighbours)
+            .into_iter()
+            .map(|r| SearchResult {
+                score: r.vec_score.unwrap_or(0.0),
+                ..r
+            })
+            .collect())
+    }
+
     // -------------------------------------------------------------------
     // Internal helpers
     // -------------------------------------------------------------------
 
+    /// Similarity results for `(node_id, cosine distance)` neighbours from
+    /// the ANN index. Nodes removed since the index was saved are skipped.
+    fn decorate_neighbours(&self, neighbours: Vec<(String, f64)>) -> Vec<SearchResult> {
+        let Ok(mut stmt) = self
+            .conn
+            .prepare_cached("SELECT name, type, file_path FROM nodes WHERE id = ?1")
+        else {
+            return Vec::new();
+        };
+        neighbours
+            .into_iter()
+            .filter_map(|(node_id, distance)| {
+                let (name, kind, file_path) = stmt
+                    .query_row(params![node_id], |row| {
+                        Ok((
+                            row.get::<_, String>(0)?,
+                            row.get::<_, String>(1)?,
+                            row.get::<_, String>(2)?,
+                        ))
+                    })
+                    .ok()?;
+                Some(SearchResult {
+                    node_id,
+                    name: name.clone(),
+                    kind,
+                    file_path,
+                    score: 0.0, // Will be set by fusion
+                    fts_score: None,
+                    vec_score: Some(1.0 - distance),
+                    snippet: Some(name),
+                })
+            })
+            .collect()
+    }
+
+    /// The stored priors of `node_ids` (see [`NodePrior`]). Nodes that no
+    /// longer exist are left out.
+    pub fn load_priors(&self, node_ids: &[&str]) -> HashMap<String, NodePrior> {
+        let mut priors = HashMap::new();
+        let max_rank: f64 = self
+            .conn
+            .query_row(MAX_PAGE_RANK_SQL, [], |row| row.get::<_, Option<f64>>(0))
+            .ok()
+            .flatten()
+            .filter(|max| *max > 0.0)
+            .unwrap_or(1.0);
+        let Ok(mut stmt) = self.conn.prepare_cached(GET_NODE_PRIOR_SQL) else {
+            return priors;
+        };
+        let now = chrono::Utc::now().timestamp();
+        for id in node_ids {
+            let row = stmt.query_row(params![id, max_rank], |row| {
+                Ok(NodePrior {
+                    is_test: row.get(0)?,
+                    page_rank: row.get::<_, Option<f64>>(1)?.unwrap_or(0.0),
+                    age_days: row
+                        .get::<_, Option<i64>>(2)?
+                        .map(|at| crate::git::node_meta::age_in_days(at, now)),
+                })
+            });
+            if let Ok(prior) = row {
+                priors.insert(id.to_string(), prior);
+            }
+        }
+        priors
+    }
+
+    /// Whether the node `node_id` passes `filter`, with the same semantics
+    /// as the filter pushed into the FTS query.
+    fn admits(&self, node_id: &str, filter: &CandidateFilter) -> bool {
+        self.conn
+            .prepare_cached(CANDIDATE_ADMITTED_SQL)
+            .and_then(|mut stmt| {
+                stmt.query_row(
+                    params![
+                        node_id,
+                        filter.glob,
+                        filter.exclude_languages,
+                        filter.exclude_kinds,
+                        filter.exclude_globs,
+                    ],
+                    |row| row.get(0),
+                )
+            })
+            .unwrap_or(false)
+    }
+
     /// Look up the language for a node (used for post-fusion filtering).
     fn get_node_language(&self, node_id: &str) -> Option<String> {
         let mut stmt = self.conn.prepare_cached(GET_NODE_LANGUAGE_SQL).ok()?;
@@ -643,9 +1346,13 @@ pub fn fuse_results(
         }
     }
 
-    // Sort by combined RRF score descending.
+    // Sort by combined RRF score descending; ties by node ID so paging is stable.
     let mut fused: Vec<(SearchResult, f64)> = score_map.into_values().collect();
-    fused.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+    fused.sort_by(|a, b| {
+        b.1.partial_cmp(&a.1)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| a.0.node_id.cmp(&b.0.node_id))
+    });
 
     fused
         .into_iter()
@@ -719,9 +1426,13 @@ pub fn fuse_results_with_expansion(
         }
     }
 
-    // Sort by combined RRF score descending.
+    // Sort by combined RRF score descending; ties by node ID so paging is stable.
     let mut fused: Vec<(SearchResult, f64)> = score_map.into_values().collect();
-    fused.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+    fused.sort_by(|a, b| {
+        b.1.partial_cmp(&a.1)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| a.0.node_id.cmp(&b.0.node_id))
+    });
 
     fused
         .into_iter()
@@ -796,7 +1507,11 @@ pub fn fuse_results_weighted(
     }
 
     let mut fused: Vec<(SearchResult, f64)> = score_map.into_values().collect();
-    fused.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+    fused.sort_by(|a, b| {
+        b.1.partial_cmp(&a.1)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| a.0.node_id.cmp(&b.0.node_id))
+    });
 
     fused
         .into_iter()
@@ -807,38 +1522,258 @@ pub fn fuse_results_weighted(
         .collect()
 }
 
+/// [`fuse_results_weighted`] with per-node priors. Candidates whose keyword
+/// or vector scores tie are ordered by prior before fusion, so the prior
+/// decides who gets the top-rank bonus, and fused scores are then scaled
+/// by [`apply_priors`].
+pub fn fuse_results_with_priors(
+    fts_results: &[SearchResult],
+    vec_results: &[SearchResult],
+    expansion_results: &[SearchResult],
+    k: u32,
+    weights: BlendWeights,
+    priors: &HashMap<String, NodePrior>,
+    prior_weights: PriorWeights,
+) -> Vec<SearchResult> {
+    let boost = |r: &SearchResult| {
+        priors
+            .get(&r.node_id)
+            .map_or(1.0, |p| prior_weights.multiplier(p))
+    };
+    let fts = order_ties(fts_results, |r| r.fts_score, &boost);
+    let vec = order_ties(vec_results, |r| r.vec_score, &boost);
+    let expansion = order_ties(expansion_results, |r| r.fts_score, &boost);
+    let mut fused = fuse_results_weighted(&fts, &vec, &expansion, k, weights);
+    apply_priors(&mut fused, priors, prior_weights);
+    fused
+}
+
+/// `results` with each run of equal scores re-ordered by `boost`, highest
+/// first.
+fn order_ties(
+    results: &[SearchResult],
+    score: impl Fn(&SearchResult) -> Option<f64>,
+    boost: &impl Fn(&SearchResult) -> f64,
+) -> Vec<SearchResult> {
+    let mut out = results.to_vec();
+    let mut start = 0;
+    while start < out.len() {
+        let key = score(&out[start]);
+        let run = out[start..].iter().take_while(|r| score(r) == key).count();
+        out[start..start + run].sort_by(|a, b| {
+            boost(b)
+                .partial_cmp(&boost(a))
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        start += run;
+    }
+    out
+}
+
+/// Scale fused scores by each node's prior (see [`PriorWeights`]) and
+/// re-sort. Results without a prior keep their score.
+pub fn apply_priors(
+    results: &mut [SearchResult],
+    priors: &HashMap<String, NodePrior>,
+    weights: PriorWeights,
+) {
+    for r in results.iter_mut() {
+        if let Some(prior) = priors.get(&r.node_id) {
+            r.score *= weights.multiplier(prior);
+        }
+    }
+    results.sort_by(|a, b| {
+        b.score
+            .partial_cmp(&a.score)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| a.node_id.cmp(&b.node_id))
+    });
+}
+
+/// Query fields a term can be scoped to, as `field:term`, with the
+/// `fts_nodes` column each searches.
+const QUERY_FIELDS: &[(&str, &str)] = &[
+    ("name", "name"),
+    ("qname", "qualified_name"),
+    ("qualified_name", "qualified_name"),
+    ("sig", "signature"),
+    ("signature", "signature"),
+    ("doc", "doc_comment"),
+    ("file", "file_path"),
+    ("path", "file_path"),
+];
+
+/// A user query translated into an FTS5 MATCH expression.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FtsQuery {
+    /// The MATCH expression; empty when nothing searchable remains.
+    pub expr: String,
+    /// The query used phrases, boolean operators or field scopes.
+    pub explicit: bool,
+    /// The terms a match should contain (negated terms and field names
+    /// dropped), for similarity search.
+    pub text: String,
+}
+
+enum QueryToken {
+    Term {
+        column: Option<&'static str>,
+        text: String,
+        phrase: bool,
+    },
+    Op(&'static str),
+}
+
 /// Sanitize a user query for FTS5 MATCH syntax.
 ///
 /// FTS5 has its own query grammar where characters like `*`, `"`, `-`,
 /// `(`, `)` carry meaning. We strip those special characters from each
 /// token and wrap it in double quotes for exact matching, then join
 /// tokens with `OR` for broadest recall. RRF will rank appropriately.
+/// Phrases, operators and field scopes are kept; see [`parse_fts_query`].
 pub fn sanitize_fts_query(query: &str) -> String {
-    let tokens: Vec<String> = query
-        .split_whitespace()
-        .filter_map(|token| {
-            let clean: String = token
-                .chars()
-                .filter(|c| {
-                    !matches!(
-                        c,
-                        '*' | '"' | '(' | ')' | '{' | '}' | '[' | ']' | '^' | '~' | ':'
-                    )
-                })
-                .collect();
-            if clean.is_empty() {
-                None
-            } else {
-                Some(format!("\"{}\"", clean))
+    parse_fts_query(query, false).expr
+}
+
+/// Parse a user query into an FTS5 expression.
+///
+/// Understands `"exact phrases"`, upper-case `AND`, `OR` and `NOT`, and
+/// terms scoped to a field (`name:parse`, `file:src/indexer`,
+/// `doc:"retry policy"`; fields are `name`, `qname`, `sig`, `doc` and
+/// `file`). Terms without an operator between them are joined with `OR`
+/// for plain keyword queries, and with `AND` once the query uses any of
+/// this syntax or `match_all` is set. Operators without a term on both
+/// sides are dropped, and `NOT` wins when several are adjacent, so
+/// `a AND NOT b` means "a but not b".
+pub fn parse_fts_query(query: &str, match_all: bool) -> FtsQuery {
+    let tokens = tokenize_query(query);
+    let explicit = tokens.iter().any(|t| match t {
+        QueryToken::Term { column, phrase, .. } => column.is_some() || *phrase,
+        QueryToken::Op(_) => true,
+    });
+    let implicit = if explicit || match_all { "AND" } else { "OR" };
+
+    let mut expr = String::new();
+    let mut text = Vec::new();
+    let mut pending: Option<&'static str> = None;
+    for token in tokens {
+        match token {
+            QueryToken::Op(op) => {
+                pending = Some(match (pending, op) {
+                    (Some("NOT"), _) | (_, "NOT") => "NOT",
+                    (Some("OR"), _) | (_, "OR") => "OR",
+                    _ => "AND",
+                });
             }
-        })
-        .collect();
+            QueryToken::Term {
+                column, text: term, ..
+            } => {
+                let op = pending.take();
+                if expr.is_empty() {
+                    // FTS5 `NOT` is binary: a leading negation has nothing
+                    // to exclude from.
+                    if op == Some("NOT") {
+                        continue;
+                    }
+                } else {
+                    expr.push_str(&format!(" {} ", op.unwrap_or(implicit)));
+                }
+                if op != Some("NOT") {
+                    text.push(term.clone());
+                }
+                if let Some(column) = column {
+                    expr.push_str(&format!("{column} : "));
+                }
+                expr.push_str(&format!("\"{}\"", term));
+            }
+        }
+    }
 
-    if tokens.is_empty() {
-        return String::new();
+    FtsQuery {
+        expr,
+        explicit,
+        text: text.join(" "),
     }
+}
 
-    tokens.join(" OR ")
+/// Split a query into terms and operators. Bare terms lose FTS5 special
+/// characters; phrases keep everything but the quotes.
+fn tokenize_query(query: &str) -> Vec<QueryToken> {
+    fn clean(word: &str) -> String {
+        word.chars()
+            .filter(|c| {
+                !matches!(
+                    c,
+                    '*' | '"' | '(' | ')' | '{' | '}' | '[' | ']' | '^' | '~' | ':'
+                )
+            })
+            .collect()
+    }
+    fn field_column(word: &str) -> Option<(&'static str, &str)> {
+        let (field, rest) = word.split_once(':')?;
+        let column = QUERY_FIELDS
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(field))?
+            .1;
+        Some((column, rest))
+    }
+
+    let mut tokens = Vec::new();
+    let mut rest = query.trim_start();
+    while !rest.is_empty() {
+        // A word runs to the next whitespace, or to an opening quote that
+        // starts a phrase (`"..."` alone or after `field:`).
+        let end = rest
+            .find(|c: char| c.is_whitespace() || c == '"')
+            .unwrap_or(rest.len());
+        let (word, after) = rest.split_at(end);
+        let column = field_column(word)
+            .filter(|(_, r)| r.is_empty())
+            .map(|(c, _)| c);
+        let phrase_start = after.starts_with('"') && (word.is_empty() || column.is_some());
+
+        if phrase_start {
+            let body = &after[1..];
+            let close = body.find('"').unwrap_or(body.len());
+            let phrase = body[..close]
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" ");
+            if !phrase.is_empty() {
+                tokens.push(QueryToken::Term {
+                    column,
+                    text: phrase,
+                    phrase: true,
+                });
+            }
+            rest = body.get(close + 1..).unwrap_or("");
+        } else {
+            // A stray quote inside a word: treat it as a separator.
+            let next = after.strip_prefix('"').unwrap_or(after);
+            match word {
+                "AND" => tokens.push(QueryToken::Op("AND")),
+                "OR" => tokens.push(QueryToken::Op("OR")),
+                "NOT" => tokens.push(QueryToken::Op("NOT")),
+                _ => {
+                    let (column, term) = match field_column(word) {
+                        Some((column, term)) if !clean(term).is_empty() => (Some(column), term),
+                        _ => (None, word),
+                    };
+                    let term = clean(term);
+                    if !term.is_empty() {
+                        tokens.push(QueryToken::Term {
+                            column,
+                            text: term,
+                            phrase: false,
+                        });
+                    }
+                }
+            }
+            rest = next;
+        }
+        rest = rest.trim_start();
+    }
+    tokens
 }
 
 /// Build a short display snippet from a node's name, signature, and
@@ -1250,6 +2185,36 @@ mod tests {
         assert!(results.iter().all(|r| r.kind == "class"));
     }
 
+    #[test]
+    fn hybrid_search_applies_tag_filter() {
+        let store = setup();
+        for (id, file) in [("fn:a.ts:charge:1", "a.ts"), ("fn:b.ts:charge:1", "b.ts")] {
+            store
+                .upsert_node(&make_node(
+                    id,
+                    "charge",
+                    file,
+                    NodeKind::Function,
+                    1,
+                    None,
+                    None,
+                ))
+                .unwrap();
+        }
+        store
+            .set_node_tag("fn:b.ts:charge:1", "team", "payments")
+            .unwrap();
+
+        let search = HybridSearch::new(&store.conn);
+        let opts = SearchOptions {
+            tags: vec![TagFilter::parse("team=payments").unwrap()],
+            ..Default::default()
+        };
+        let results = search.search("charge", &opts).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].node_id, "fn:b.ts:charge:1");
+    }
+
     #[test]
     fn hybrid_search_applies_language_filter() {
         let store = setup();
@@ -1432,6 +2397,182 @@ mod tests {
         assert!(results.len() <= 20);
     }
 
+    #[test]
+    fn freshness_multiplier_decays_with_age() {
+        assert!((freshness_multiplier(0) - (1.0 + FRESHNESS_WEIGHT)).abs() < 1e-9);
+        let half = freshness_multiplier(FRESHNESS_HALF_LIFE_DAYS as i64);
+        assert!((half - (1.0 + FRESHNESS_WEIGHT / 2.0)).abs() < 1e-9);
+        assert!(freshness_multiplier(3650) < 1.001);
+        assert_eq!(freshness_multiplier(-5), freshness_multiplier(0));
+    }
+
+    #[test]
+    fn hybrid_search_boosts_recently_modified_symbols() {
+        let store = setup();
+        store
+            .upsert_node(&make_node(
+                "fn:a.ts:gizmo:1",
+                "gizmo",
+                "a.ts",
+                NodeKind::Function,
+                1,
+                Some("function gizmo()"),
+                None,
+            ))
+            .unwrap();
+        let search = HybridSearch::new(&store.conn);
+        let before = search.search("gizmo", &SearchOptions::default()).unwrap()[0].score;
+
+        store
+            .conn
+            .execute(
+                "INSERT INTO node_git_meta \
+                 (node_id, file_path, commit_hash, author, email, committed_at, age_days) \
+                 VALUES ('fn:a.ts:gizmo:1', 'a.ts', 'abc', 'A', 'a@x', ?1, 0)",
+                params![chrono::Utc::now().timestamp()],
+            )
+            .unwrap();
+        let after = search.search("gizmo", &SearchOptions::default()).unwrap()[0].score;
+        assert!(after > before);
+        assert!(after <= before * (1.0 + FRESHNESS_WEIGHT) + 1e-9);
+    }
+
+    #[test]
+    fn prior_weights_multiplier_blends_each_prior() {
+        let weights = PriorWeights::default();
+        assert_eq!(weights.multiplier(&NodePrior::default()), 1.0);
+        let central = NodePrior {
+            page_rank: 1.0,
+            ..Default::default()
+        };
+        assert!((weights.multiplier(&central) - 1.1).abs() < 1e-9);
+        let fresh_test = NodePrior {
+            age_days: Some(0),
+            is_test: true,
+            ..Default::default()
+        };
+        assert!((weights.multiplier(&fresh_test) - 1.05 * 0.9).abs() < 1e-9);
+        let off = PriorWeights {
+            page_rank: 0.0,
+            recency: 0.0,
+            test_penalty: 0.0,
+        };
+        assert_eq!(off.multiplier(&central), 1.0);
+        assert_eq!(off.multiplier(&fresh_test), 1.0);
+    }
+
+    #[test]
+    fn hybrid_search_ranks_central_symbols_above_helpers() {
+        let store = setup();
+        for (id, file) in [("fn:b.ts:parse:1", "b.ts"), ("fn:a.ts:parse:1", "a.ts")] {
+            store
+                .upsert_node(&make_node(
+                    id,
+                    "parse",
+                    file,
+                    NodeKind::Function,
+                    1,
+                    Some("function parse()"),
+                    None,
+                ))
+                .unwrap();
+        }
+        let search = HybridSearch::new(&store.conn);
+        let ids = |search: &HybridSearch| -> Vec<String> {
+            search
+                .search("parse", &SearchOptions::default())
+                .unwrap()
+                .into_iter()
+                .map(|r| r.node_id)
+                .collect()
+        };
+        // Equal matches tie-break on ID.
+        assert_eq!(ids(&search)[0], "fn:a.ts:parse:1");
+
+        store
+            .conn
+            .execute_batch(
+                "INSERT INTO node_rank VALUES ('fn:a.ts:parse:1', 0.01), \
+                 ('fn:b.ts:parse:1', 0.2)",
+            )
+            .unwrap();
+        assert_eq!(ids(&search)[0], "fn:b.ts:parse:1");
+
+        store
+            .conn
+            .execute(
+                "UPDATE nodes SET is_test = 1 WHERE id = 'fn:b.ts:parse:1'",
+                [],
+            )
+            .unwrap();
+        assert_eq!(ids(&search)[0], "fn:a.ts:parse:1");
+
+        let neutral = HybridSearch::new(&store.conn).with_priors(PriorWeights {
+            page_rank: 0.0,
+            recency: 0.0,
+            test_penalty: 0.0,
+        });
+        assert_eq!(ids(&neutral)[0], "fn:a.ts:parse:1");
+    }
+
+    #[test]
+    fn debug_search_explains_each_result() {
+        let store = setup();
+        store
+            .upsert_node(&make_node(
+                "fn:a.ts:findUser:1",
+                "findUser",
+                "a.ts",
+                NodeKind::Function,
+                1,
+                Some("function findUser()"),
+                None,
+            ))
+            .unwrap();
+        store
+            .upsert_node(&make_node(
+                "fn:b.ts:lookupAccount:1",
+                "lookupAccount",
+                "b.ts",
+                NodeKind::Function,
+                1,
+                Some("function lookupAccount()"),
+                Some("lookup an account"),
+            ))
+            .unwrap();
+        store
+            .conn
+            .execute(
+                "INSERT INTO node_rank VALUES ('fn:a.ts:findUser:1', 0.5)",
+                [],
+            )
+            .unwrap();
+        let search = HybridSearch::new(&store.conn);
+        let plain = search
+            .search_with_suggestions("find user", &SearchOptions::default())
+            .unwrap();
+        assert!(plain.explanation.is_none());
+
+        let found = search
+            .search_with_suggestions(
+                "find user",
+                &SearchOptions {
+                    debug: true,
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        let why = found.explanation.unwrap();
+        assert_eq!(why.intent, detect_query_intent("find user"));
+        assert_eq!(why.results.len(), found.results.len());
+        let top = &why.results[0];
+        assert_eq!(top.node_id, "fn:a.ts:findUser:1");
+        assert_eq!(top.fts_rank, Some(1));
+        assert_eq!(top.prior.unwrap().page_rank, 1.0);
+        assert!((top.fused_score * top.prior_multiplier - top.score).abs() < 1e-12);
+        assert_eq!(top.score, found.results[0].score);
+    }
+
     #[test]
     fn hybrid_search_applies_limit() {
         let store = setup();
@@ -1500,6 +2641,320 @@ mod tests {
         assert_eq!(result, r#""test" OR "value" OR "object""#);
     }
 
+    // -- parse_fts_query (phrases, operators, fields) ----------------------
+
+    #[test]
+    fn parse_fts_query_phrases_and_operators() {
+        let q = parse_fts_query(r#""retry policy" AND NOT deprecated"#, false);
+        assert_eq!(q.expr, r#""retry policy" NOT "deprecated""#);
+        assert!(q.explicit);
+        assert_eq!(q.text, "retry policy");
+
+        // Bare terms next to syntax are required, not optional.
+        assert_eq!(
+            parse_fts_query("parse OR lex token", false).expr,
+            r#""parse" OR "lex" AND "token""#
+        );
+        // Dangling operators and an unclosed quote are tolerated.
+        assert_eq!(
+            parse_fts_query(r#"AND NOT x OR "open phrase"#, false).expr,
+            r#""open phrase""#
+        );
+        assert_eq!(parse_fts_query("a OR", false).expr, r#""a""#);
+        // Lower-case words are plain terms.
+        assert_eq!(
+            parse_fts_query("and or not", false).expr,
+            r#""and" OR "or" OR "not""#
+        );
+    }
+
+    #[test]
+    fn parse_fts_query_field_scopes() {
+        let q = parse_fts_query("name:parse file:src/indexer", false);
+        assert_eq!(q.expr, r#"name : "parse" AND file_path : "src/indexer""#);
+        assert_eq!(q.text, "parse src/indexer");
+        assert_eq!(
+            parse_fts_query(r#"doc:"retry policy" SIG:i32"#, false).expr,
+            r#"doc_comment : "retry policy" AND signature : "i32""#
+        );
+        // Unknown fields and bare prefixes keep the old stripping.
+        assert_eq!(parse_fts_query("baz:qux", false).expr, r#""bazqux""#);
+        assert_eq!(parse_fts_query("name:", false).expr, r#""name""#);
+    }
+
+    #[test]
+    fn parse_fts_query_match_all() {
+        let q = parse_fts_query("hello world", true);
+        assert_eq!(q.expr, r#""hello" AND "world""#);
+        assert!(!q.explicit);
+    }
+
+    #[test]
+    fn search_honours_query_syntax() {
+        let store = setup();
+        for (id, name, file, doc) in [
+            (
+                "fn:a",
+                "parseConfig",
+                "src/indexer/config.ts",
+                "Parse the retry policy.",
+            ),
+            (
+                "fn:b",
+                "parseArgs",
+                "src/cli/args.ts",
+                "Policy for retry handling.",
+            ),
+            (
+                "fn:c",
+                "loadConfig",
+                "src/indexer/load.ts",
+                "Read the retry policy.",
+            ),
+        ] {
+            store
+                .upsert_node(&make_node(
+                    id,
+                    name,
+                    file,
+                    NodeKind::Function,
+                    1,
+                    None,
+                    Some(doc),
+                ))
+                .unwrap();
+        }
+        let search = HybridSearch::new(&store.conn);
+        let ids = |query: &str| {
+            let mut ids: Vec<String> = search
+                .search(query, &SearchOptions::default())
+                .unwrap()
+                .into_iter()
+                .map(|r| r.node_id)
+                .collect();
+            ids.sort();
+            ids
+        };
+
+        assert_eq!(ids(r#""retry policy""#), vec!["fn:a", "fn:c"]);
+        assert_eq!(ids(r#""retry policy" NOT loadConfig"#), vec!["fn:a"]);
+        assert_eq!(ids("file:src/indexer"), vec!["fn:a", "fn:c"]);
+        assert_eq!(
+            ids("name:parseArgs OR name:loadConfig"),
+            vec!["fn:b", "fn:c"]
+        );
+
+        let opts = SearchOptions {
+            match_all: true,
+            ..Default::default()
+        };
+        let hits = search.search("parseConfig retry", &opts).unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].node_id, "fn:a");
+    }
+
+    #[test]
+    fn pages_cover_results_once_in_order() {
+        let store = setup();
+        for i in 0..7 {
+            store
+                .upsert_node(&make_node(
+                    &format!("fn:{}", i),
+                    &format!("handler{}", i),
+                    "src/handlers.ts",
+                    NodeKind::Function,
+                    i * 10 + 1,
+                    Some("function handler(req)"),
+                    None,
+                ))
+                .unwrap();
+        }
+        let search = HybridSearch::new(&store.conn);
+        let all: Vec<String> = search
+            .search("handler", &SearchOptions::default())
+            .unwrap()
+            .into_iter()
+            .map(|r| r.node_id)
+            .collect();
+        assert_eq!(all.len(), 7);
+
+        let mut paged = Vec::new();
+        let mut cursor = None;
+        loop {
+            let page = search
+                .search_with_suggestions(
+                    "handler",
+                    &SearchOptions {
+                        limit: Some(3),
+                        cursor: cursor.take(),
+                        ..Default::default()
+                    },
+                )
+                .unwrap();
+            paged.extend(page.results.into_iter().map(|r| r.node_id));
+            match page.next_cursor {
+                Some(next) => cursor = Some(next),
+                None => break,
+            }
+        }
+        assert_eq!(paged, all);
+
+        let by_offset = search
+            .search(
+                "handler",
+                &SearchOptions {
+                    limit: Some(3),
+                    offset: 6,
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        assert_eq!(by_offset.len(), 1);
+        assert_eq!(by_offset[0].node_id, all[6]);
+    }
+
+    #[test]
+    fn path_glob_treats_plain_paths_as_prefixes() {
+        assert_eq!(path_glob("src/server").as_deref(), Some("src/server*"));
+        assert_eq!(path_glob("./src/").as_deref(), Some("src/*"));
+        assert_eq!(path_glob("src/server/**").as_deref(), Some("src/server/*"));
+        assert_eq!(path_glob("src/**/*.ts").as_deref(), Some("src/*.ts"));
+        assert_eq!(path_glob("*.rs").as_deref(), Some("*.rs"));
+        assert_eq!(path_glob(" . "), None);
+    }
+
+    #[test]
+    fn path_filter_scopes_keyword_and_hybrid_search() {
+        let store = setup();
+        for (id, file) in [
+            ("fn:a", "src/server/auth.ts"),
+            ("fn:b", "tests/server/auth.test.ts"),
+            ("fn:c", "vendor/auth/index.ts"),
+        ] {
+            store
+                .upsert_node(&make_node(
+                    id,
+                    "authMiddleware",
+                    file,
+                    NodeKind::Function,
+                    1,
+                    None,
+                    None,
+                ))
+                .unwrap();
+        }
+        let search = HybridSearch::new(&store.conn);
+
+        let ids = |path: &str| {
+            let opts = SearchOptions {
+                path: Some(path.to_string()),
+                ..Default::default()
+            };
+            let mut ids: Vec<String> = search
+                .search("auth middleware", &opts)
+                .unwrap()
+                .into_iter()
+                .map(|r| r.node_id)
+                .collect();
+            ids.sort();
+            ids
+        };
+        assert_eq!(ids("src/server/**"), vec!["fn:a"]);
+        assert_eq!(ids("src"), vec!["fn:a"]);
+        assert_eq!(ids("*.test.ts"), vec!["fn:b"]);
+        assert_eq!(ids("."), vec!["fn:a", "fn:b", "fn:c"]);
+
+        let hits = search
+            .search_by_keyword_in(
+                "authMiddleware",
+                10,
+                &SearchOptions {
+                    path: Some("vendor/".to_string()),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].node_id, "fn:c");
+    }
+
+    #[test]
+    fn exclusions_apply_before_the_candidate_budget() {
+        let store = setup();
+        // Excluded symbols sort first; if they were dropped after fusion
+        // they would use up the whole candidate budget of a 1-result page.
+        let mut nodes: Vec<CodeNode> = (1..=5)
+            .map(|i| {
+                make_node(
+                    &format!("fn:a{i}"),
+                    "loadConfig",
+                    &format!("vendor/lib{i}.ts"),
+                    NodeKind::Function,
+                    1,
+                    None,
+                    None,
+                )
+            })
+            .collect();
+        let mut py = make_node(
+            "fn:b",
+            "loadConfig",
+            "src/cfg.py",
+            NodeKind::Function,
+            1,
+            None,
+            None,
+        );
+        py.language = Language::Python;
+        nodes.push(py);
+        nodes.push(make_node(
+            "fn:c",
+            "loadConfig",
+            "src/cfg.ts",
+            NodeKind::Variable,
+            1,
+            None,
+            None,
+        ));
+        nodes.push(make_node(
+            "fn:z",
+            "loadConfig",
+            "src/cfg.ts",
+            NodeKind::Function,
+            9,
+            None,
+            None,
+        ));
+        store.upsert_nodes(&nodes).unwrap();
+        let search = HybridSearch::new(&store.conn);
+
+        let opts = SearchOptions {
+            limit: Some(1),
+            exclude_languages: vec!["python".to_string()],
+            exclude_kinds: vec!["variable".to_string()],
+            exclude_paths: vec!["vendor".to_string(), "*.md".to_string()],
+            ..Default::default()
+        };
+        let hits = search.search("loadConfig", &opts).unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].node_id, "fn:z");
+
+        let keyword = search.search_by_keyword_in("loadConfig", 1, &opts).unwrap();
+        assert_eq!(keyword[0].node_id, "fn:z");
+
+        let unfiltered = search.search_by_keyword("loadConfig", 1).unwrap();
+        assert_eq!(unfiltered[0].node_id, "fn:a1");
+    }
+
+    #[test]
+    fn cursors_are_bound_to_their_query() {
+        let cursor = encode_cursor("handler", "scope", 20);
+        assert_eq!(decode_cursor(&cursor, "handler", "scope").unwrap(), 20);
+        assert!(decode_cursor(&cursor, "other", "scope").is_err());
+        assert!(decode_cursor(&cursor, "handler", "other").is_err());
+        assert!(decode_cursor("garbage", "handler", "scope").is_err());
+    }
+
     #[test]
     fn build_snippet_empty_doc_with_signature() {
         let snippet = build_snippet("foo", Some("fn foo()"), Some(""));
@@ -2058,4 +3513,47 @@ mod tests {
             "results should be sorted by relevance descending"
         );
     }
+
+    // -- similar_to --------------------------------------------------------
+
+    #[test]
+    fn similar_to_ranks_stored_neighbours_and_excludes_self() {
+        let store = setup();
+        let vectors = [
+            ("a", vec![1.0f32, 0.0, 0.0]),
+            ("b", vec![0.9, 0.1, 0.0]),
+            ("c", vec![0.0, 1.0, 0.0]),
+            ("d", vec![0.5, 0.5, 0.0]),
+        ];
+        for (i, (name, vector)) in vectors.iter().enumerate() {
+            let id = format!("fn:a.ts:{name}:{i}");
+            store
+                .upsert_node(&make_node(
+                    &id,
+                    name,
+                    "a.ts",
+                    NodeKind::Function,
+                    i as u32 * 10,
+                    None,
+                    None,
+                ))
+                .unwrap();
+            store
+                .conn
+                .execute(
+                    "INSERT INTO embedding_cache (node_id, embedding) VALUES (?1, ?2)",
+                    params![id, crate::db::findings::to_blob(vector)],
+                )
+                .unwrap();
+        }
+
+        let search = HybridSearch::new(&store.conn);
+        let similar = search.similar_to("fn:a.ts:a:0", 2).unwrap();
+        let names: Vec<&str> = similar.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, vec!["b", "d"]);
+        assert!(similar[0].score > similar[1].score);
+        assert!(similar[0].score > 0.99);
+
+        assert!(search.similar_to("fn:a.ts:missing:0", 2).is_err());
+    }
 }
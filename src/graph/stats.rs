@@ -0,0 +1,436 @@
+//! Graph statistics: whole-graph totals and per-directory rollups.
+//!
+//! [`GraphStats`] holds the cheap totals behind
+//! [`GraphStore::get_stats`](super::store::GraphStore::get_stats). The
+//! per-directory, per-language breakdown needs function bodies for its
+//! complexity figures, so it is materialized into the `dir_stats` table
+//! after indexing instead of being recomputed on every query; readers load
+//! the rows with [`load_dir_stats`] and fold them with [`rollup`].
+//!
+//! A directory is the parent of a file path (`.` for files at the root);
+//! rows are not cumulative over subdirectories.
+//!
+//! Line counts are stored per file in `file_hashes` at index time and
+//! summed per language by [`load_language_lines`].
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use rusqlite::{params, Connection};
+
+use crate::db::bodies::select_nodes;
+use crate::db::converters::row_to_code_node;
+use crate::error::Result;
+use crate::graph::complexity::calculate_complexity;
+use crate::indexer::loc::LineCounts;
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// Aggregate statistics about the stored graph.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GraphStats {
+    pub nodes: usize,
+    pub edges: usize,
+    pub files: usize,
+}
+
+/// Counts for the files of one language directly inside one directory.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DirStats {
+    pub directory: String,
+    pub language: String,
+    pub files: usize,
+    pub nodes: usize,
+    /// Function and method nodes.
+    pub functions: usize,
+    /// Edges whose source node lives in these files.
+    pub edges: usize,
+    /// Sum of the cyclomatic complexity of the functions.
+    pub complexity_total: u64,
+    /// Highest cyclomatic complexity of any one function.
+    pub complexity_max: u32,
+}
+
+impl DirStats {
+    /// Mean cyclomatic complexity per function, 0 without functions.
+    pub fn avg_complexity(&self) -> f64 {
+        if self.functions == 0 {
+            0.0
+        } else {
+            self.complexity_total as f64 / self.functions as f64
+        }
+    }
+
+    fn absorb(&mut self, other: &DirStats) {
+        self.files += other.files;
+        self.nodes += other.nodes;
+        self.functions += other.functions;
+        self.edges += other.edges;
+        self.complexity_total += other.complexity_total;
+        self.complexity_max = self.complexity_max.max(other.complexity_max);
+    }
+}
+
+/// How [`rollup`] groups [`DirStats`] rows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RollupKey {
+    /// One row per directory; `language` lists its languages, comma-separated.
+    Directory,
+    /// One row per language; `directory` is empty.
+    Language,
+}
+
+// ---------------------------------------------------------------------------
+// Public API
+// ---------------------------------------------------------------------------
+
+/// Directory a file belongs to for the rollups: its parent, or `.`.
+pub fn directory_of(file_path: &str) -> &str {
+    match file_path.rsplit_once('/') {
+        Some((dir, _)) if !dir.is_empty() => dir,
+        _ => ".",
+    }
+}
+
+/// Compute the rows for every directory, or only those in `scope`.
+pub fn compute_dir_stats(
+    conn: &Connection,
+    scope: Option<&HashSet<String>>,
+) -> Result<Vec<DirStats>> {
+    let in_scope = |path: &str| scope.is_none_or(|dirs| dirs.contains(directory_of(path)));
+
+    // Per-file counts first; folding them by directory happens in Rust
+    // because SQLite has no dirname().
+    let mut files: BTreeMap<(String, String), DirStats> = BTreeMap::new();
+    let mut stmt = conn.prepare(
+        "SELECT file_path, language, COUNT(*), SUM(type IN ('function', 'method'))
+         FROM nodes GROUP BY file_path, language",
+    )?;
+    let rows = stmt.query_map([], |row| {
+        Ok((
+            row.get::<_, String>(0)?,
+            row.get::<_, String>(1)?,
+            row.get::<_, i64>(2)?,
+            row.get::<_, i64>(3)?,
+        ))
+    })?;
+    for row in rows {
+        let (path, language, nodes, functions) = row?;
+        if in_scope(&path) {
+            files.insert(
+                (path, language),
+                DirStats {
+                    files: 1,
+                    nodes: nodes as usize,
+                    functions: functions as usize,
+                    ..Default::default()
+                },
+            );
+        }
+    }
+
+    let mut stmt = conn.prepare(
+        "SELECT n.file_path, n.language, COUNT(*) FROM edges e
+         JOIN nodes n ON n.id = e.source_id GROUP BY n.file_path, n.language",
+    )?;
+    let rows = stmt.query_map([], |row| {
+        Ok((
+            row.get::<_, String>(0)?,
+            row.get::<_, String>(1)?,
+            row.get::<_, i64>(2)?,
+        ))
+    })?;
+    for row in rows {
+        let (path, language, edges) = row?;
+        if let Some(stats) = files.get_mut(&(path, language)) {
+            stats.edges = edges as usize;
+        }
+    }
+
+    // Complexity needs the bodies, so only read those in scope.
+    let mut functions = Vec::new();
+    match scope {
+        None => {
+            let mut stmt = conn.prepare(select_nodes!("WHERE n.type IN ('function', 'method')"))?;
+            let rows = stmt.query_map([], row_to_code_node)?;
+            for node in rows {
+                functions.push(node?);
+            }
+        }
+        Some(_) => {
+            let mut stmt = conn.prepare(select_nodes!(
+                "WHERE n.file_path = ?1 AND n.type IN ('function', 'method')"
+            ))?;
+            let paths: HashSet<&str> = files.keys().map(|(path, _)| path.as_str()).collect();
+            for path in paths {
+                let rows = stmt.query_map([path], row_to_code_node)?;
+                for node in rows {
+                    functions.push(node?);
+                }
+            }
+        }
+    }
+    for node in functions {
+        let Some(body) = node.body.as_deref().filter(|b| !b.is_empty()) else {
+            continue;
+        };
+        let key = (node.file_path, node.language.as_str().to_string());
+        if let Some(stats) = files.get_mut(&key) {
+            let cyclomatic = calculate_complexity(&node.name, body, &key.0, &node.id).cyclomatic;
+            stats.complexity_total += u64::from(cyclomatic);
+            stats.complexity_max = stats.complexity_max.max(cyclomatic);
+        }
+    }
+
+    let mut dirs: BTreeMap<(String, String), DirStats> = BTreeMap::new();
+    for ((path, language), stats) in &files {
+        let directory = directory_of(path).to_string();
+        dirs.entry((directory.clone(), language.clone()))
+            .or_insert_with(|| DirStats {
+                directory,
+                language: language.clone(),
+                ..Default::default()
+            })
+            .absorb(stats);
+    }
+    Ok(dirs.into_values().collect())
+}
+
+/// Recompute and store the rows for every directory. Returns the number of
+/// rows written.
+pub fn refresh_dir_stats(conn: &Connection) -> Result<usize> {
+    let rows = compute_dir_stats(conn, None)?;
+    let tx = conn.unchecked_transaction()?;
+    tx.execute("DELETE FROM dir_stats", [])?;
+    insert_rows(&tx, &rows)?;
+    tx.commit()?;
+    Ok(rows.len())
+}
+
+/// Recompute and store the rows of the directories containing `file_paths`,
+/// e.g. after the watcher re-indexed or removed them.
+pub fn refresh_dir_stats_for(conn: &Connection, file_paths: &[&str]) -> Result<()> {
+    let scope: HashSet<String> = file_paths
+        .iter()
+        .map(|path| directory_of(path).to_string())
+        .collect();
+    let rows = compute_dir_stats(conn, Some(&scope))?;
+    let tx = conn.unchecked_transaction()?;
+    {
+        let mut delete = tx.prepare("DELETE FROM dir_stats WHERE directory = ?1")?;
+        for directory in &scope {
+            delete.execute([directory])?;
+        }
+    }
+    insert_rows(&tx, &rows)?;
+    tx.commit()?;
+    Ok(())
+}
+
+/// Stored rows, ordered by directory then language. Empty until the first
+/// refresh.
+pub fn load_dir_stats(conn: &Connection) -> Result<Vec<DirStats>> {
+    let mut stmt = conn.prepare(
+        "SELECT directory, language, files, nodes, functions, edges,
+                complexity_total, complexity_max
+         FROM dir_stats ORDER BY directory, language",
+    )?;
+    let rows = stmt.query_map([], |row| {
+        Ok(DirStats {
+            directory: row.get(0)?,
+            language: row.get(1)?,
+            files: row.get::<_, i64>(2)? as usize,
+            nodes: row.get::<_, i64>(3)? as usize,
+            functions: row.get::<_, i64>(4)? as usize,
+            edges: row.get::<_, i64>(5)? as usize,
+            complexity_total: row.get::<_, i64>(6)? as u64,
+            complexity_max: row.get(7)?,
+        })
+    })?;
+    rows.collect::<std::result::Result<Vec<_>, _>>()
+        .map_err(Into::into)
+}
+
+/// Line counts of the indexed files summed per language. Files indexed
+/// before line counting existed count as zero until re-indexed.
+pub fn load_language_lines(conn: &Connection) -> Result<BTreeMap<String, LineCounts>> {
+    let mut stmt = conn.prepare(
+        "SELECT language, SUM(total_lines), SUM(code_lines), SUM(comment_lines),
+                SUM(blank_lines), SUM(logical_lines)
+         FROM file_hashes GROUP BY language",
+    )?;
+    let rows = stmt.query_map([], |row| {
+        Ok((
+            row.get::<_, String>(0)?,
+            LineCounts {
+                total: row.get::<_, i64>(1)? as usize,
+                code: row.get::<_, i64>(2)? as usize,
+                comment: row.get::<_, i64>(3)? as usize,
+                blank: row.get::<_, i64>(4)? as usize,
+                logical: row.get::<_, i64>(5)? as usize,
+            },
+        ))
+    })?;
+    rows.collect::<std::result::Result<BTreeMap<_, _>, _>>()
+        .map_err(Into::into)
+}
+
+/// Fold rows by directory or by language, largest (by node count) first.
+pub fn rollup(rows: &[DirStats], key: RollupKey) -> Vec<DirStats> {
+    let mut groups: HashMap<&str, DirStats> = HashMap::new();
+    let mut languages: HashMap<&str, Vec<&str>> = HashMap::new();
+    for row in rows {
+        let group = match key {
+            RollupKey::Directory => row.directory.as_str(),
+            RollupKey::Language => row.language.as_str(),
+        };
+        let entry = groups.entry(group).or_insert_with(|| match key {
+            RollupKey::Directory => DirStats {
+                directory: group.to_string(),
+                ..Default::default()
+            },
+            RollupKey::Language => DirStats {
+                language: group.to_string(),
+                ..Default::default()
+            },
+        });
+        entry.absorb(row);
+        if key == RollupKey::Directory {
+            languages.entry(group).or_default().push(&row.language);
+        }
+    }
+    for (directory, langs) in languages {
+        if let Some(entry) = groups.get_mut(directory) {
+            entry.language = langs.join(",");
+        }
+    }
+    let mut out: Vec<DirStats> = groups.into_values().collect();
+    out.sort_by(|a, b| {
+        b.nodes
+            .cmp(&a.nodes)
+            .then_with(|| a.directory.cmp(&b.directory))
+            .then_with(|| a.language.cmp(&b.language))
+    });
+    out
+}
+
+// ---------------------------------------------------------------------------
+// Internal
+// ---------------------------------------------------------------------------
+
+fn insert_rows(conn: &Connection, rows: &[DirStats]) -> Result<()> {
+    let mut insert = conn.prepare(
+        "INSERT INTO dir_stats (directory, language, files, nodes, functions, edges,
+                                complexity_total, complexity_max)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+    )?;
+    for row in rows {
+        insert.execute(params![
+            row.directory,
+            row.language,
+            row.files as i64,
+            row.nodes as i64,
+            row.functions as i64,
+            row.edges as i64,
+            row.complexity_total as i64,
+            row.complexity_max,
+        ])?;
+    }
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::schema::initialize_database;
+
+    fn insert_node(conn: &Connection, id: &str, kind: &str, file: &str, language: &str) {
+        conn.execute(
+            "INSERT INTO nodes (id, type, name, file_path, start_line, end_line, language) \
+             VALUES (?1, ?2, ?1, ?3, 1, 5, ?4)",
+            [id, kind, file, language],
+        )
+        .unwrap();
+    }
+
+    fn insert_body(conn: &Connection, id: &str, body: &str) {
+        conn.execute(
+            "INSERT INTO node_bodies (node_id, encoding, body) VALUES (?1, 'plain', ?2)",
+            params![id, body.as_bytes()],
+        )
+        .unwrap();
+    }
+
+    fn insert_edge(conn: &Connection, source: &str, target: &str) {
+        conn.execute(
+            "INSERT INTO edges (source_id, target_id, type) VALUES (?1, ?2, 'calls')",
+            [source, target],
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn directory_of_uses_parent_or_root() {
+        assert_eq!(directory_of("src/graph/stats.rs"), "src/graph");
+        assert_eq!(directory_of("main.rs"), ".");
+        assert_eq!(directory_of("/abs.rs"), ".");
+    }
+
+    #[test]
+    fn computes_refreshes_and_rolls_up() {
+        let conn = initialize_database(":memory:").unwrap();
+        insert_node(&conn, "a", "function", "src/a.ts", "typescript");
+        insert_body(
+            &conn,
+            "a",
+            "function a(x) { if (x) { return 1; } return 2; }",
+        );
+        insert_node(&conn, "b", "function", "src/b.ts", "typescript");
+        insert_body(&conn, "b", "function b() { return 1; }");
+        insert_node(&conn, "B", "class", "src/b.ts", "typescript");
+        insert_node(&conn, "p", "function", "src/tool.py", "python");
+        insert_node(&conn, "m", "function", "main.ts", "typescript");
+        insert_edge(&conn, "a", "b");
+        insert_edge(&conn, "b", "unresolved:fetch");
+        insert_edge(&conn, "m", "a");
+
+        assert_eq!(refresh_dir_stats(&conn).unwrap(), 3);
+        let rows = load_dir_stats(&conn).unwrap();
+        assert_eq!(rows.len(), 3);
+        assert_eq!((rows[0].directory.as_str(), rows[0].files), (".", 1));
+        let ts = &rows[2];
+        assert_eq!(
+            (ts.directory.as_str(), ts.language.as_str()),
+            ("src", "typescript")
+        );
+        assert_eq!((ts.files, ts.nodes, ts.functions, ts.edges), (2, 3, 2, 2));
+        assert_eq!((ts.complexity_total, ts.complexity_max), (3, 2));
+        assert_eq!(ts.avg_complexity(), 1.5);
+
+        let by_dir = rollup(&rows, RollupKey::Directory);
+        assert_eq!(by_dir[0].directory, "src");
+        assert_eq!(by_dir[0].nodes, 4);
+        assert_eq!(by_dir[0].language, "python,typescript");
+        let by_lang = rollup(&rows, RollupKey::Language);
+        assert_eq!(by_lang[0].language, "typescript");
+        assert_eq!((by_lang[0].files, by_lang[0].edges), (3, 3));
+
+        // A scoped refresh rewrites only the touched directory.
+        conn.execute("DELETE FROM nodes WHERE file_path = 'src/tool.py'", [])
+            .unwrap();
+        conn.execute("DELETE FROM nodes WHERE file_path = 'main.ts'", [])
+            .unwrap();
+        refresh_dir_stats_for(&conn, &["src/tool.py"]).unwrap();
+        let rows = load_dir_stats(&conn).unwrap();
+        let dirs: Vec<_> = rows
+            .iter()
+            .map(|r| (r.directory.as_str(), r.language.as_str()))
+            .collect();
+        assert_eq!(dirs, vec![(".", "typescript"), ("src", "typescript")]);
+    }
+}
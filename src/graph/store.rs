@@ -4,23 +4,171 @@
 //! `prepare_cached` for automatic statement caching — the Rust equivalent
 //! of the TS version's eagerly-prepared statement map.
 
-use rusqlite::{params, Connection};
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::sync::Arc;
 
+use rusqlite::{params, Connection, OptionalExtension};
+
+use crate::db::bodies::{encode_body, select_nodes, BodyStorage};
 use crate::db::converters::{row_to_code_edge, row_to_code_node};
-use crate::db::schema::initialize_database;
-use crate::error::Result;
+use crate::db::schema::{initialize_database, open_read_only};
+use crate::error::{CodeGraphError, Result};
+use crate::git::NodeGitMeta;
+use crate::graph::adjacency::Adjacency;
+use crate::resolution::config_keys::{ConfigFormat, ConfigKey, ConfigKeyRef};
+use crate::resolution::literals::{Literal, LiteralKind};
+use crate::resolution::side_effects::{SideEffect, SideEffectKind};
+use crate::resolution::wire_contract::{WireField, WireFramework};
 use crate::types::{CodeEdge, CodeNode, UnresolvedRef};
 
+pub use crate::graph::stats::GraphStats;
+
+/// Batch size for callers of [`GraphStore::iter_nodes`] and
+/// [`GraphStore::iter_edges`] that have no reason to pick their own.
+pub const STREAM_BATCH_SIZE: usize = 1000;
+
 // ---------------------------------------------------------------------------
-// GraphStats
+// Tombstones
 // ---------------------------------------------------------------------------
 
-/// Aggregate statistics about the stored graph.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub struct GraphStats {
+/// A symbol that an earlier index contained and a later one removed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tombstone {
+    pub id: String,
+    pub kind: String,
+    pub name: String,
+    pub qualified_name: Option<String>,
+    pub file_path: String,
+    pub start_line: u32,
+    pub end_line: u32,
+    pub language: String,
+    /// Unix seconds at which the symbol disappeared from the graph.
+    pub deleted_at: i64,
+}
+
+/// An edge that disappeared when `file_path` was re-indexed or removed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EdgeTombstone {
+    pub source_id: String,
+    pub target_id: String,
+    pub kind: String,
+    pub file_path: String,
+    pub deleted_at: i64,
+}
+
+/// Rows purged by [`GraphStore::vacuum`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct VacuumStats {
     pub nodes: usize,
     pub edges: usize,
-    pub files: usize,
+}
+
+/// Whether removed rows leave tombstones, and how long they are kept.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TombstonePolicy {
+    /// Record tombstones when symbols and edges are removed.
+    pub enabled: bool,
+    /// Tombstones older than this many seconds are purged after each
+    /// directory index; `None` keeps them until `codegraph vacuum`.
+    pub retention_secs: Option<i64>,
+}
+
+impl Default for TombstonePolicy {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            retention_secs: None,
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Node tags
+// ---------------------------------------------------------------------------
+
+/// A user-defined key/value label on a node, e.g. `layer=api`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NodeTag {
+    pub node_id: String,
+    pub key: String,
+    pub value: String,
+}
+
+/// A free-text note on a node, e.g. "do not touch: used by the billing
+/// export".
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NodeNote {
+    pub id: i64,
+    #[serde(skip)]
+    pub node_id: String,
+    pub note: String,
+    /// Who wrote it (a user name or `agent`), if given.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    pub created_at: i64,
+}
+
+/// The tags and notes on one node.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NodeAnnotations {
+    pub tags: Vec<NodeTag>,
+    pub notes: Vec<NodeNote>,
+}
+
+/// A tag condition: `key=value`, or a bare `key` matching any value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TagFilter {
+    pub key: String,
+    pub value: Option<String>,
+}
+
+impl TagFilter {
+    /// Parse `key=value` or `key`. `None` for an empty key.
+    pub fn parse(s: &str) -> Option<Self> {
+        let (key, value) = match s.split_once('=') {
+            Some((k, v)) => (k.trim(), Some(v.trim().to_string())),
+            None => (s.trim(), None),
+        };
+        (!key.is_empty()).then(|| Self {
+            key: key.to_string(),
+            value,
+        })
+    }
+}
+
+fn note_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<NodeNote> {
+    Ok(NodeNote {
+        id: row.get(0)?,
+        node_id: row.get(1)?,
+        note: row.get(2)?,
+        author: row.get(3)?,
+        created_at: row.get(4)?,
+    })
+}
+
+/// IDs of the nodes carrying every tag in `filters`, sorted.
+pub(crate) fn tagged_node_ids(conn: &Connection, filters: &[TagFilter]) -> Result<Vec<String>> {
+    let mut sql = String::from("SELECT DISTINCT node_id FROM node_tags t0 WHERE 1");
+    let mut values: Vec<&str> = Vec::new();
+    for f in filters {
+        values.push(&f.key);
+        sql.push_str(&format!(
+            " AND EXISTS (SELECT 1 FROM node_tags t WHERE t.node_id = t0.node_id AND t.key = ?{}",
+            values.len()
+        ));
+        if let Some(value) = &f.value {
+            values.push(value);
+            sql.push_str(&format!(" AND t.value = ?{}", values.len()));
+        }
+        sql.push(')');
+    }
+    sql.push_str(" ORDER BY node_id");
+    let mut stmt = conn.prepare_cached(&sql)?;
+    let rows = stmt.query_map(rusqlite::params_from_iter(values), |row| row.get(0))?;
+    rows.collect::<std::result::Result<Vec<_>, _>>()
+        .map_err(Into::into)
 }
 
 // ---------------------------------------------------------------------------
@@ -34,8 +182,26 @@ pub struct GraphStats {
 /// internal LRU cache. This matches the performance characteristics of the
 /// TypeScript version's eagerly-prepared statements while being more
 /// ergonomic (no upfront prepare step, no lifetime gymnastics).
+///
+/// The database runs in WAL mode with a busy timeout, so one writing store
+/// can coexist with read-only stores from a
+/// [`ReadPool`](crate::graph::pool::ReadPool), including ones in other
+/// processes.
 pub struct GraphStore {
     pub conn: Connection,
+    adjacency: AdjacencyCache,
+    bodies: Cell<BodyStorage>,
+    tombstones: Cell<TombstonePolicy>,
+}
+
+/// Lazily built [`Adjacency`], tagged with the database state it was
+/// built from.
+#[derive(Default)]
+struct AdjacencyCache {
+    enabled: Cell<bool>,
+    /// `(PRAGMA data_version, total_changes, adjacency)`. The first moves
+    /// when another connection commits, the second on this one's writes.
+    built: RefCell<Option<(i64, u64, Arc<Adjacency>)>>,
 }
 
 impl std::fmt::Debug for GraphStore {
@@ -79,6 +245,53 @@ DELETE FROM edges WHERE source_id IN (SELECT id FROM nodes WHERE file_path = ?1)
 const DELETE_NODES_BY_FILE_SQL: &str = "\
 DELETE FROM nodes WHERE file_path = ?1";
 
+// Foreign keys are off, so bodies are removed explicitly, before their nodes.
+const DELETE_BODIES_BY_FILE_SQL: &str = "\
+DELETE FROM node_bodies WHERE node_id IN (SELECT id FROM nodes WHERE file_path = ?1)";
+
+const UPSERT_BODY_SQL: &str = "\
+INSERT OR REPLACE INTO node_bodies (node_id, encoding, body) VALUES (?1, ?2, ?3)";
+
+const DELETE_BODY_SQL: &str = "DELETE FROM node_bodies WHERE node_id = ?1";
+
+// Tombstones must be written before the DELETE_*_BY_FILE statements run,
+// since both select the doomed rows through `nodes.file_path`.
+const TOMBSTONE_EDGES_BY_FILE_SQL: &str = "\
+INSERT OR REPLACE INTO edge_tombstones (source_id, target_id, type, file_path, deleted_at)
+SELECT source_id, target_id, type, ?1, ?2 FROM edges
+WHERE source_id IN (SELECT id FROM nodes WHERE file_path = ?1)
+   OR target_id IN (SELECT id FROM nodes WHERE file_path = ?1)";
+
+const TOMBSTONE_NODES_BY_FILE_SQL: &str = "\
+INSERT OR REPLACE INTO node_tombstones
+  (id, type, name, qualified_name, file_path, start_line, end_line, language, deleted_at)
+SELECT id, type, name, qualified_name, file_path, start_line, end_line, language, ?2
+FROM nodes WHERE file_path = ?1";
+
+const RESURRECT_NODE_SQL: &str = "DELETE FROM node_tombstones WHERE id = ?1";
+
+/// Point tags of `file_path` whose node ID disappeared at the re-indexed
+/// node with the same kind and name, if there is one.
+const REKEY_TAGS_BY_FILE_SQL: &str = "\
+UPDATE OR REPLACE node_tags SET node_id = (
+  SELECT n.id FROM nodes n WHERE n.file_path = node_tags.file_path AND n.type = node_tags.kind
+    AND COALESCE(n.qualified_name, n.name) = node_tags.symbol ORDER BY n.start_line LIMIT 1)
+WHERE file_path = ?1 AND node_id NOT IN (SELECT id FROM nodes WHERE file_path = ?1)
+  AND EXISTS (SELECT 1 FROM nodes n WHERE n.file_path = node_tags.file_path
+    AND n.type = node_tags.kind AND COALESCE(n.qualified_name, n.name) = node_tags.symbol)";
+
+/// Same as [`REKEY_TAGS_BY_FILE_SQL`], for notes.
+const REKEY_NOTES_BY_FILE_SQL: &str = "\
+UPDATE node_notes SET node_id = (
+  SELECT n.id FROM nodes n WHERE n.file_path = node_notes.file_path AND n.type = node_notes.kind
+    AND COALESCE(n.qualified_name, n.name) = node_notes.symbol ORDER BY n.start_line LIMIT 1)
+WHERE file_path = ?1 AND node_id NOT IN (SELECT id FROM nodes WHERE file_path = ?1)
+  AND EXISTS (SELECT 1 FROM nodes n WHERE n.file_path = node_notes.file_path
+    AND n.type = node_notes.kind AND COALESCE(n.qualified_name, n.name) = node_notes.symbol)";
+
+const RESURRECT_EDGE_SQL: &str = "\
+DELETE FROM edge_tombstones WHERE source_id = ?1 AND target_id = ?2 AND type = ?3";
+
 const ENSURE_EDGE_UNIQUE_INDEX_SQL: &str = "\
 CREATE UNIQUE INDEX IF NOT EXISTS idx_edges_source_target_type \
 ON edges(source_id, target_id, type)";
@@ -91,7 +304,7 @@ ON edges(source_id, target_id, type)";
 ///
 /// Produces the same output as `((hash << 5) - hash + ch) | 0` in JS,
 /// which is a 32-bit signed integer converted to base-36.
-fn compute_simple_hash(input: &str) -> String {
+pub(crate) fn compute_simple_hash(input: &str) -> String {
     let mut hash: i32 = 0;
     for ch in input.encode_utf16() {
         hash = hash.wrapping_mul(31).wrapping_add(ch as i32);
@@ -225,8 +438,18 @@ pub fn detect_is_test(name: &str, file_path: &str, language: &str, kind: &str) -
 }
 
 /// Build the metadata JSON object that the TS version stores alongside
-/// each node row.
-fn build_node_metadata(node: &CodeNode) -> String {
+/// each node row, body included. Used by backends without `node_bodies`.
+#[cfg_attr(not(feature = "postgres"), allow(dead_code))]
+pub(crate) fn build_node_metadata(node: &CodeNode) -> String {
+    node_metadata(node, true)
+}
+
+/// Metadata JSON for SQLite rows, whose bodies live in `node_bodies`.
+fn build_node_metadata_without_body(node: &CodeNode) -> String {
+    node_metadata(node, false)
+}
+
+fn node_metadata(node: &CodeNode, with_body: bool) -> String {
     let mut map = serde_json::Map::new();
     map.insert(
         "startColumn".to_string(),
@@ -236,7 +459,7 @@ fn build_node_metadata(node: &CodeNode) -> String {
         "endColumn".to_string(),
         serde_json::Value::from(node.end_column),
     );
-    if let Some(ref body) = node.body {
+    if let Some(body) = node.body.as_ref().filter(|_| with_body) {
         // Truncate body to 4 KB to match the TS version's behaviour.
         let truncated = if body.len() > 4096 {
             &body[..body.floor_char_boundary(4096)]
@@ -252,7 +475,7 @@ fn build_node_metadata(node: &CodeNode) -> String {
 }
 
 /// Build the properties JSON for an edge row.
-fn build_edge_properties(edge: &CodeEdge) -> String {
+pub(crate) fn build_edge_properties(edge: &CodeEdge) -> String {
     let mut map = serde_json::Map::new();
     // Merge any caller-supplied metadata first.
     if let Some(ref meta) = edge.metadata {
@@ -300,7 +523,7 @@ pub fn split_identifier(name: &str) -> String {
 ///
 /// Both are split into words and concatenated so that searching for any
 /// component word matches the node.
-fn build_name_tokens(name: &str, qualified_name: Option<&str>) -> String {
+pub(crate) fn build_name_tokens(name: &str, qualified_name: Option<&str>) -> String {
     let name_expanded = split_identifier(name);
 
     match qualified_name {
@@ -372,7 +595,38 @@ impl GraphStore {
         let conn = initialize_database(db_path)?;
         // Ensure the unique index on edges exists so upsert works correctly.
         conn.execute_batch(ENSURE_EDGE_UNIQUE_INDEX_SQL)?;
-        Ok(Self { conn })
+        Ok(Self::wrap(conn))
+    }
+
+    /// Open an existing database for queries only.
+    ///
+    /// Any mutation fails. Used by [`ReadPool`](crate::graph::pool::ReadPool)
+    /// so readers can run alongside the indexer without contending for the
+    /// writer's connection.
+    pub fn open_read_only(db_path: &str) -> Result<Self> {
+        Ok(Self::wrap(open_read_only(db_path)?))
+    }
+
+    /// Open a read-only sibling connection held at the database's current
+    /// snapshot.
+    ///
+    /// Every query on the returned store sees the graph as it was at this
+    /// call, even after the indexer commits, until the store is dropped.
+    /// Holding a snapshot keeps the WAL from being checkpointed past it, so
+    /// drop it when done. In-memory databases cannot be shared and fail.
+    pub fn pin_snapshot(&self) -> Result<Self> {
+        let path = self.conn.path().filter(|p| !p.is_empty()).ok_or_else(|| {
+            CodeGraphError::Other("snapshots need an on-disk database".to_string())
+        })?;
+        let conn = open_read_only(path)?;
+        // A deferred transaction takes its snapshot at the first read.
+        conn.execute_batch("BEGIN DEFERRED")?;
+        conn.query_row("SELECT count(*) FROM nodes", [], |row| row.get::<_, i64>(0))?;
+        let pinned = Self::wrap(conn);
+        pinned.adjacency.enabled.set(self.adjacency.enabled.get());
+        pinned.bodies.set(self.bodies.get());
+        pinned.tombstones.set(self.tombstones.get());
+        Ok(pinned)
     }
 
     /// Wrap an already-open connection. Useful in tests where the caller
@@ -382,7 +636,75 @@ impl GraphStore {
         // been applied yet this will silently fail, but it's the caller's
         // responsibility to ensure the schema is present.
         let _ = conn.execute_batch(ENSURE_EDGE_UNIQUE_INDEX_SQL);
-        Self { conn }
+        Self::wrap(conn)
+    }
+
+    fn wrap(conn: Connection) -> Self {
+        Self {
+            conn,
+            adjacency: AdjacencyCache::default(),
+            bodies: Cell::new(BodyStorage::default()),
+            tombstones: Cell::new(TombstonePolicy::default()),
+        }
+    }
+
+    // -------------------------------------------------------------------
+    // Body storage
+    // -------------------------------------------------------------------
+
+    /// Set how node bodies are written from now on (size cap and
+    /// compression). Already stored bodies are rewritten on re-index.
+    pub fn set_body_storage(&self, storage: BodyStorage) {
+        self.bodies.set(storage);
+    }
+
+    /// Current body storage settings.
+    pub fn body_storage(&self) -> BodyStorage {
+        self.bodies.get()
+    }
+
+    /// Set whether removals leave tombstones and how long they are kept.
+    /// Turning tombstones off does not purge existing ones.
+    pub fn set_tombstone_policy(&self, policy: TombstonePolicy) {
+        self.tombstones.set(policy);
+    }
+
+    /// Current tombstone policy.
+    pub fn tombstone_policy(&self) -> TombstonePolicy {
+        self.tombstones.get()
+    }
+
+    // -------------------------------------------------------------------
+    // Adjacency cache
+    // -------------------------------------------------------------------
+
+    /// Serve traversals and ranking from an in-memory [`Adjacency`] instead
+    /// of per-level SQL. Worth it for long-lived stores (servers, daemons)
+    /// on large graphs; the adjacency is built on first use and rebuilt
+    /// after any write.
+    pub fn enable_adjacency_cache(&self) {
+        self.adjacency.enabled.set(true);
+    }
+
+    /// The cached adjacency, rebuilt if the database changed since it was
+    /// built, or `None` when the cache is not enabled.
+    pub fn adjacency(&self) -> Result<Option<Arc<Adjacency>>> {
+        if !self.adjacency.enabled.get() {
+            return Ok(None);
+        }
+        let data_version: i64 = self
+            .conn
+            .pragma_query_value(None, "data_version", |row| row.get(0))?;
+        let changes = self.conn.total_changes();
+        let mut built = self.adjacency.built.borrow_mut();
+        if let Some((v, c, adj)) = built.as_ref() {
+            if *v == data_version && *c == changes {
+                return Ok(Some(Arc::clone(adj)));
+            }
+        }
+        let adj = Arc::new(Adjacency::load(&self.conn)?);
+        *built = Some((data_version, changes, Arc::clone(&adj)));
+        Ok(Some(adj))
     }
 
     // -------------------------------------------------------------------
@@ -408,13 +730,17 @@ impl GraphStore {
             node.start_line,
             node.end_line,
             node.language.as_str(),
-            node.body,                     // signature column
-            node.documentation,            // doc_comment column
-            compute_simple_hash(&node.id), // source_hash
-            build_node_metadata(node),     // metadata JSON
-            name_tokens,                   // pre-split identifier tokens
-            is_test as i32,                // is_test flag
+            node.body,                              // signature column
+            node.documentation,                     // doc_comment column
+            compute_simple_hash(&node.id),          // source_hash
+            build_node_metadata_without_body(node), // metadata JSON
+            name_tokens,                            // pre-split identifier tokens
+            is_test as i32,                         // is_test flag
         ])?;
+        write_node_body(&self.conn, node, self.bodies.get())?;
+        self.conn
+            .prepare_cached(RESURRECT_NODE_SQL)?
+            .execute(params![node.id])?;
         Ok(())
     }
 
@@ -427,6 +753,9 @@ impl GraphStore {
             edge.kind.as_str(),
             build_edge_properties(edge),
         ])?;
+        self.conn
+            .prepare_cached(RESURRECT_EDGE_SQL)?
+            .execute(params![edge.source, edge.target, edge.kind.as_str()])?;
         Ok(())
     }
 
@@ -439,6 +768,7 @@ impl GraphStore {
         let tx = self.conn.unchecked_transaction()?;
         {
             let mut stmt = tx.prepare_cached(UPSERT_NODE_SQL)?;
+            let mut resurrect = tx.prepare_cached(RESURRECT_NODE_SQL)?;
             for node in nodes {
                 let name_tokens = build_name_tokens(&node.name, node.qualified_name.as_deref());
                 let is_test = detect_is_test(
@@ -459,10 +789,12 @@ impl GraphStore {
                     node.body,
                     node.documentation,
                     compute_simple_hash(&node.id),
-                    build_node_metadata(node),
+                    build_node_metadata_without_body(node),
                     name_tokens,
                     is_test as i32,
                 ])?;
+                write_node_body(&tx, node, self.bodies.get())?;
+                resurrect.execute(params![node.id])?;
             }
         }
         tx.commit()?;
@@ -474,6 +806,7 @@ impl GraphStore {
         let tx = self.conn.unchecked_transaction()?;
         {
             let mut stmt = tx.prepare_cached(UPSERT_EDGE_SQL)?;
+            let mut resurrect = tx.prepare_cached(RESURRECT_EDGE_SQL)?;
             for edge in edges {
                 stmt.execute(params![
                     edge.source,
@@ -481,6 +814,7 @@ impl GraphStore {
                     edge.kind.as_str(),
                     build_edge_properties(edge),
                 ])?;
+                resurrect.execute(params![edge.source, edge.target, edge.kind.as_str()])?;
             }
         }
         tx.commit()?;
@@ -491,72 +825,80 @@ impl GraphStore {
     ///
     /// Deletes every node and edge associated with `file_path`, then
     /// inserts the new `nodes` and `edges` — all inside one transaction.
+    /// Rows that are not re-inserted are left behind as tombstones (see
+    /// [`get_tombstones`](Self::get_tombstones)) until [`vacuum`](Self::vacuum)
+    /// or the [`TombstonePolicy`] retention period purges them.
     pub fn replace_file_data(
         &self,
         file_path: &str,
         nodes: &[CodeNode],
         edges: &[CodeEdge],
     ) -> Result<()> {
-        let tx = self.conn.unchecked_transaction()?;
-        {
-            // Delete edges first (they reference nodes via FK).
-            let mut del_edges = tx.prepare_cached(DELETE_EDGES_BY_FILE_SQL)?;
-            del_edges.execute(params![file_path])?;
-
-            let mut del_nodes = tx.prepare_cached(DELETE_NODES_BY_FILE_SQL)?;
-            del_nodes.execute(params![file_path])?;
-
-            // Insert replacements.
-            let mut ins_node = tx.prepare_cached(UPSERT_NODE_SQL)?;
-            for node in nodes {
-                let name_tokens = build_name_tokens(&node.name, node.qualified_name.as_deref());
-                let is_test = detect_is_test(
-                    &node.name,
-                    &node.file_path,
-                    node.language.as_str(),
-                    node.kind.as_str(),
-                );
-                ins_node.execute(params![
-                    node.id,
-                    node.kind.as_str(),
-                    node.name,
-                    node.qualified_name,
-                    node.file_path,
-                    node.start_line,
-                    node.end_line,
-                    node.language.as_str(),
-                    node.body,
-                    node.documentation,
-                    compute_simple_hash(&node.id),
-                    build_node_metadata(node),
-                    name_tokens,
-                    is_test as i32,
-                ])?;
-            }
+        self.replace_files_data(&[(file_path, nodes, edges)])
+    }
 
-            let mut ins_edge = tx.prepare_cached(UPSERT_EDGE_SQL)?;
-            for edge in edges {
-                ins_edge.execute(params![
-                    edge.source,
-                    edge.target,
-                    edge.kind.as_str(),
-                    build_edge_properties(edge),
-                ])?;
-            }
+    /// Atomically replace the graph data of several files.
+    ///
+    /// Same as [`replace_file_data`](Self::replace_file_data) per file, but
+    /// the whole batch commits in a single transaction: a failure part-way
+    /// leaves every file as it was, and the commit is synced to disk once.
+    pub fn replace_files_data(&self, files: &[(&str, &[CodeNode], &[CodeEdge])]) -> Result<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        for &(file_path, nodes, edges) in files {
+            replace_file_rows(
+                &tx,
+                file_path,
+                nodes,
+                edges,
+                self.bodies.get(),
+                self.tombstones.get().enabled,
+            )?;
         }
         tx.commit()?;
         Ok(())
     }
 
-    /// Delete all nodes and edges associated with `file_path`.
+    /// Delete all nodes and edges associated with `file_path`, leaving
+    /// tombstones behind unless the [`TombstonePolicy`] disables them.
     pub fn delete_file_nodes(&self, file_path: &str) -> Result<()> {
         let tx = self.conn.unchecked_transaction()?;
         {
+            if self.tombstones.get().enabled {
+                let now = unix_now();
+                tx.prepare_cached(TOMBSTONE_EDGES_BY_FILE_SQL)?
+                    .execute(params![file_path, now])?;
+                tx.prepare_cached(TOMBSTONE_NODES_BY_FILE_SQL)?
+                    .execute(params![file_path, now])?;
+            }
+
             let mut del_edges = tx.prepare_cached(DELETE_EDGES_BY_FILE_SQL)?;
             del_edges.execute(params![file_path])?;
 
+            tx.prepare_cached(DELETE_BODIES_BY_FILE_SQL)?
+                .execute(params![file_path])?;
             let mut del_nodes = tx.prepare_cached(DELETE_NODES_BY_FILE_SQL)?;
             del_nodes.execute(params![file_path])?;
+
+            let mut del_refs = tx.prepare_cached("DELETE FROM config_refs WHERE file_path = ?1")?;
+            del_refs.execute(params![file_path])?;
+
+            let mut del_keys = tx.prepare_cached("DELETE FROM config_keys WHERE file_path = ?1")?;
+            del_keys.execute(params![file_path])?;
+
+            let mut del_meta =
+                tx.prepare_cached("DELETE FROM node_git_meta WHERE file_path = ?1")?;
+            del_meta.execute(params![file_path])?;
+
+            let mut del_wire = tx.prepare_cached("DELETE FROM wire_fields WHERE file_path = ?1")?;
+            del_wire.execute(params![file_path])?;
+
+            let mut del_effects =
+                tx.prepare_cached("DELETE FROM import_side_effects WHERE file_path = ?1")?;
+            del_effects.execute(params![file_path])?;
+
+            let mut del_literals =
+                tx.prepare_cached("DELETE FROM literals WHERE file_path = ?1")?;
+            del_literals.execute(params![file_path])?;
         }
         tx.commit()?;
         Ok(())
@@ -568,9 +910,7 @@ impl GraphStore {
 
     /// Retrieve a single node by its ID, or `None` if it doesn't exist.
     pub fn get_node(&self, id: &str) -> Result<Option<CodeNode>> {
-        let mut stmt = self
-            .conn
-            .prepare_cached("SELECT * FROM nodes WHERE id = ?1")?;
+        let mut stmt = self.conn.prepare_cached(select_nodes!("WHERE n.id = ?1"))?;
         let mut rows = stmt.query_and_then(params![id], row_to_code_node)?;
         match rows.next() {
             Some(Ok(node)) => Ok(Some(node)),
@@ -587,7 +927,7 @@ impl GraphStore {
     pub fn get_nodes_by_file(&self, file_path: &str) -> Result<Vec<CodeNode>> {
         let mut stmt = self
             .conn
-            .prepare_cached("SELECT * FROM nodes WHERE file_path = ?1")?;
+            .prepare_cached(select_nodes!("WHERE n.file_path = ?1"))?;
         let rows = stmt.query_and_then(params![file_path], row_to_code_node)?;
         rows.collect::<std::result::Result<Vec<_>, _>>()
             .map_err(Into::into)
@@ -597,7 +937,7 @@ impl GraphStore {
     pub fn get_nodes_by_name(&self, name: &str) -> Result<Vec<CodeNode>> {
         let mut stmt = self
             .conn
-            .prepare_cached("SELECT * FROM nodes WHERE name = ?1")?;
+            .prepare_cached(select_nodes!("WHERE n.name = ?1"))?;
         let rows = stmt.query_and_then(params![name], row_to_code_node)?;
         rows.collect::<std::result::Result<Vec<_>, _>>()
             .map_err(Into::into)
@@ -607,7 +947,7 @@ impl GraphStore {
     pub fn get_nodes_by_type(&self, kind: &str) -> Result<Vec<CodeNode>> {
         let mut stmt = self
             .conn
-            .prepare_cached("SELECT * FROM nodes WHERE type = ?1")?;
+            .prepare_cached(select_nodes!("WHERE n.type = ?1"))?;
         let rows = stmt.query_and_then(params![kind], row_to_code_node)?;
         rows.collect::<std::result::Result<Vec<_>, _>>()
             .map_err(Into::into)
@@ -667,7 +1007,7 @@ impl GraphStore {
 
     /// Return every node in the graph.
     pub fn get_all_nodes(&self) -> Result<Vec<CodeNode>> {
-        let mut stmt = self.conn.prepare_cached("SELECT * FROM nodes")?;
+        let mut stmt = self.conn.prepare_cached(select_nodes!())?;
         let rows = stmt.query_and_then([], row_to_code_node)?;
         rows.collect::<std::result::Result<Vec<_>, _>>()
             .map_err(Into::into)
@@ -681,6 +1021,28 @@ impl GraphStore {
             .map_err(Into::into)
     }
 
+    /// Stream every node in batches of at most `batch_size`, ordered by ID.
+    ///
+    /// Each batch is a separate keyset-paginated query, so memory stays
+    /// bounded by the batch size no matter how large the graph is.
+    pub fn iter_nodes(&self, batch_size: usize) -> NodeBatches<'_> {
+        NodeBatches {
+            store: self,
+            batch_size: batch_size.max(1),
+            after: Some(String::new()),
+        }
+    }
+
+    /// Stream every edge in batches of at most `batch_size`, in insertion
+    /// order. See [`iter_nodes`](Self::iter_nodes).
+    pub fn iter_edges(&self, batch_size: usize) -> EdgeBatches<'_> {
+        EdgeBatches {
+            store: self,
+            batch_size: batch_size.max(1),
+            after: Some(0),
+        }
+    }
+
     // -------------------------------------------------------------------
     // Queries — aggregate counts
     // -------------------------------------------------------------------
@@ -708,6 +1070,16 @@ impl GraphStore {
         Ok(count as usize)
     }
 
+    /// Every distinct file path across all nodes, sorted.
+    pub fn get_file_paths(&self) -> Result<Vec<String>> {
+        let mut stmt = self
+            .conn
+            .prepare_cached("SELECT DISTINCT file_path FROM nodes ORDER BY file_path")?;
+        let rows = stmt.query_map([], |row| row.get::<_, String>(0))?;
+        rows.collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(Into::into)
+    }
+
     /// Get aggregate statistics (node count, edge count, file count).
     pub fn get_stats(&self) -> Result<GraphStats> {
         Ok(GraphStats {
@@ -780,23 +1152,899 @@ impl GraphStore {
         }
     }
 
-    /// Clear all unresolved references for a given file path.
-    pub fn clear_unresolved_refs_for_file(&self, file_path: &str) -> Result<()> {
-        let mut stmt = self
-            .conn
-            .prepare_cached("DELETE FROM unresolved_refs WHERE file_path = ?1")?;
-        stmt.execute(params![file_path])?;
-        Ok(())
+    /// Clear all unresolved references for a given file path.
+    pub fn clear_unresolved_refs_for_file(&self, file_path: &str) -> Result<()> {
+        let mut stmt = self
+            .conn
+            .prepare_cached("DELETE FROM unresolved_refs WHERE file_path = ?1")?;
+        stmt.execute(params![file_path])?;
+        Ok(())
+    }
+
+    /// Get the total count of unresolved references.
+    pub fn get_unresolved_ref_count(&self) -> Result<usize> {
+        let mut stmt = self
+            .conn
+            .prepare_cached("SELECT count(*) FROM unresolved_refs")?;
+        let count: i64 = stmt.query_row([], |row| row.get(0))?;
+        Ok(count as usize)
+    }
+
+    // -------------------------------------------------------------------
+    // Configuration keys
+    // -------------------------------------------------------------------
+
+    /// Replace every stored config key with `keys` in one transaction.
+    pub fn replace_config_keys(&self, keys: &[ConfigKey]) -> Result<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        {
+            tx.execute("DELETE FROM config_keys", [])?;
+            let mut stmt = tx.prepare_cached(
+                "INSERT INTO config_keys (key, file_path, line, format) VALUES (?1, ?2, ?3, ?4)",
+            )?;
+            for k in keys {
+                stmt.execute(params![k.key, k.file_path, k.line, k.format.as_str()])?;
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Replace the config keys recorded for a single configuration file.
+    pub fn replace_config_keys_for_file(&self, file_path: &str, keys: &[ConfigKey]) -> Result<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        {
+            let mut del = tx.prepare_cached("DELETE FROM config_keys WHERE file_path = ?1")?;
+            del.execute(params![file_path])?;
+            let mut ins = tx.prepare_cached(
+                "INSERT INTO config_keys (key, file_path, line, format) VALUES (?1, ?2, ?3, ?4)",
+            )?;
+            for k in keys {
+                ins.execute(params![k.key, k.file_path, k.line, k.format.as_str()])?;
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Replace the config key reads recorded for a single source file.
+    pub fn replace_config_refs_for_file(
+        &self,
+        file_path: &str,
+        refs: &[ConfigKeyRef],
+    ) -> Result<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        {
+            let mut del = tx.prepare_cached("DELETE FROM config_refs WHERE file_path = ?1")?;
+            del.execute(params![file_path])?;
+            let mut ins = tx.prepare_cached(
+                "INSERT INTO config_refs (key, node_id, file_path, line) VALUES (?1, ?2, ?3, ?4)",
+            )?;
+            for r in refs {
+                ins.execute(params![r.key, r.node_id, r.file_path, r.line])?;
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Replace the config key nodes, and every edge into them, with
+    /// `nodes` and `edges` in one transaction. See
+    /// [`config_key_graph`](crate::resolution::config_keys::config_key_graph).
+    pub fn replace_config_key_graph(&self, nodes: &[CodeNode], edges: &[CodeEdge]) -> Result<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        {
+            tx.execute(
+                "DELETE FROM edges WHERE target_id IN \
+                 (SELECT id FROM nodes WHERE type = 'config_key')",
+                [],
+            )?;
+            tx.execute("DELETE FROM nodes WHERE type = 'config_key'", [])?;
+            let mut ins_node = tx.prepare_cached(UPSERT_NODE_SQL)?;
+            let mut resurrect_node = tx.prepare_cached(RESURRECT_NODE_SQL)?;
+            for node in nodes {
+                ins_node.execute(params![
+                    node.id,
+                    node.kind.as_str(),
+                    node.name,
+                    node.qualified_name,
+                    node.file_path,
+                    node.start_line,
+                    node.end_line,
+                    node.language.as_str(),
+                    node.body,
+                    node.documentation,
+                    compute_simple_hash(&node.id),
+                    build_node_metadata_without_body(node),
+                    build_name_tokens(&node.name, node.qualified_name.as_deref()),
+                    0,
+                ])?;
+                resurrect_node.execute(params![node.id])?;
+            }
+            let mut ins_edge = tx.prepare_cached(UPSERT_EDGE_SQL)?;
+            let mut resurrect_edge = tx.prepare_cached(RESURRECT_EDGE_SQL)?;
+            for edge in edges {
+                ins_edge.execute(params![
+                    edge.source,
+                    edge.target,
+                    edge.kind.as_str(),
+                    build_edge_properties(edge),
+                ])?;
+                resurrect_edge.execute(params![edge.source, edge.target, edge.kind.as_str()])?;
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Return every stored config key, ordered by file and line.
+    pub fn get_config_keys(&self) -> Result<Vec<ConfigKey>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT key, file_path, line, format FROM config_keys ORDER BY file_path, line",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            let format: String = row.get(3)?;
+            Ok(ConfigKey {
+                key: row.get(0)?,
+                file_path: row.get(1)?,
+                line: row.get(2)?,
+                format: ConfigFormat::from_str_loose(&format).unwrap_or(ConfigFormat::Env),
+            })
+        })?;
+        rows.collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(Into::into)
+    }
+
+    /// Return every recorded config key read, ordered by file and line.
+    pub fn get_config_refs(&self) -> Result<Vec<ConfigKeyRef>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT key, node_id, file_path, line FROM config_refs ORDER BY file_path, line",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok(ConfigKeyRef {
+                key: row.get(0)?,
+                node_id: row.get(1)?,
+                file_path: row.get(2)?,
+                line: row.get(3)?,
+            })
+        })?;
+        rows.collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(Into::into)
+    }
+
+    // -------------------------------------------------------------------
+    // Symbol-level git metadata
+    // -------------------------------------------------------------------
+
+    /// Replace all git metadata recorded for nodes in `file_path`.
+    pub fn replace_node_git_meta_for_file(
+        &self,
+        file_path: &str,
+        metas: &[NodeGitMeta],
+    ) -> Result<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        {
+            let mut del = tx.prepare_cached("DELETE FROM node_git_meta WHERE file_path = ?1")?;
+            del.execute(params![file_path])?;
+            let mut ins = tx.prepare_cached(
+                "INSERT OR REPLACE INTO node_git_meta \
+                 (node_id, file_path, commit_hash, author, email, committed_at, age_days) \
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            )?;
+            for m in metas {
+                ins.execute(params![
+                    m.node_id,
+                    m.file_path,
+                    m.commit_hash,
+                    m.author,
+                    m.email,
+                    m.committed_at,
+                    m.age_days
+                ])?;
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Look up git metadata for a node, if any was recorded.
+    pub fn get_node_git_meta(&self, node_id: &str) -> Result<Option<NodeGitMeta>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT node_id, file_path, commit_hash, author, email, committed_at, age_days \
+             FROM node_git_meta WHERE node_id = ?1",
+        )?;
+        let mut rows = stmt.query_map(params![node_id], row_to_node_git_meta)?;
+        Ok(rows.next().transpose()?)
+    }
+
+    /// Git metadata recorded for the nodes of one file.
+    pub fn get_node_git_meta_for_file(&self, file_path: &str) -> Result<Vec<NodeGitMeta>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT node_id, file_path, commit_hash, author, email, committed_at, age_days \
+             FROM node_git_meta WHERE file_path = ?1",
+        )?;
+        let rows = stmt.query_map(params![file_path], row_to_node_git_meta)?;
+        Ok(rows.collect::<std::result::Result<_, _>>()?)
+    }
+
+    // -------------------------------------------------------------------
+    // Parser provenance
+    // -------------------------------------------------------------------
+
+    /// Record that the nodes of `file_path` came from `parser` (a
+    /// [`ParserStage`](crate::indexer::fallback::ParserStage) name), as
+    /// `parser` in their metadata. Re-indexing the file rewrites the
+    /// metadata, so call this after every store of fallback nodes.
+    pub fn record_parser(&self, file_path: &str, parser: &str) -> Result<()> {
+        self.conn
+            .prepare_cached(
+                "UPDATE nodes SET metadata = json_set(COALESCE(metadata, '{}'), '$.parser', ?2) \
+                 WHERE file_path = ?1",
+            )?
+            .execute(params![file_path, parser])?;
+        Ok(())
+    }
+
+    /// The fallback parser that produced a node, or `None` for nodes from
+    /// the primary (tree-sitter) extractor.
+    pub fn get_node_parser(&self, node_id: &str) -> Result<Option<String>> {
+        let parser = self
+            .conn
+            .prepare_cached("SELECT json_extract(metadata, '$.parser') FROM nodes WHERE id = ?1")?
+            .query_row(params![node_id], |row| row.get(0))
+            .optional()?;
+        Ok(parser.flatten())
+    }
+
+    // -------------------------------------------------------------------
+    // Serialization contracts
+    // -------------------------------------------------------------------
+
+    /// Replace all wire fields recorded for types in `file_path`.
+    pub fn replace_wire_fields_for_file(
+        &self,
+        file_path: &str,
+        fields: &[WireField],
+    ) -> Result<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        {
+            let mut del = tx.prepare_cached("DELETE FROM wire_fields WHERE file_path = ?1")?;
+            del.execute(params![file_path])?;
+            let mut ins = tx.prepare_cached(
+                "INSERT OR REPLACE INTO wire_fields \
+                 (node_id, owner_id, owner, field_name, wire_name, skipped, optional, \
+                  framework, file_path, line) \
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            )?;
+            for f in fields {
+                ins.execute(params![
+                    f.node_id,
+                    f.owner_id,
+                    f.owner,
+                    f.field_name,
+                    f.wire_name,
+                    f.skipped,
+                    f.optional,
+                    f.framework.as_str(),
+                    f.file_path,
+                    f.line
+                ])?;
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Wire fields of the type with node ID `owner_id`, in source order.
+    pub fn get_wire_fields(&self, owner_id: &str) -> Result<Vec<WireField>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT node_id, owner_id, owner, field_name, wire_name, skipped, optional, \
+                    framework, file_path, line \
+             FROM wire_fields WHERE owner_id = ?1 ORDER BY line",
+        )?;
+        let rows = stmt.query_map(params![owner_id], |row| {
+            let framework: String = row.get(7)?;
+            Ok(WireField {
+                node_id: row.get(0)?,
+                owner_id: row.get(1)?,
+                owner: row.get(2)?,
+                field_name: row.get(3)?,
+                wire_name: row.get(4)?,
+                skipped: row.get(5)?,
+                optional: row.get(6)?,
+                framework: WireFramework::from_str_loose(&framework)
+                    .unwrap_or(WireFramework::Serde),
+                file_path: row.get(8)?,
+                line: row.get(9)?,
+            })
+        })?;
+        rows.collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(Into::into)
+    }
+
+    // -------------------------------------------------------------------
+    // Import-time side effects
+    // -------------------------------------------------------------------
+
+    /// Replace the import-time side effects recorded for `file_path`.
+    pub fn replace_side_effects_for_file(
+        &self,
+        file_path: &str,
+        effects: &[SideEffect],
+    ) -> Result<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        {
+            let mut del =
+                tx.prepare_cached("DELETE FROM import_side_effects WHERE file_path = ?1")?;
+            del.execute(params![file_path])?;
+            let mut ins = tx.prepare_cached(
+                "INSERT OR REPLACE INTO import_side_effects (file_path, line, kind, statement) \
+                 VALUES (?1, ?2, ?3, ?4)",
+            )?;
+            for e in effects {
+                ins.execute(params![e.file_path, e.line, e.kind.as_str(), e.statement])?;
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Recorded import-time side effects, optionally for one file only,
+    /// ordered by file and line.
+    pub fn get_side_effects(&self, file_path: Option<&str>) -> Result<Vec<SideEffect>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT file_path, line, kind, statement FROM import_side_effects \
+             WHERE ?1 IS NULL OR file_path = ?1 ORDER BY file_path, line",
+        )?;
+        let rows = stmt.query_map(params![file_path], |row| {
+            let kind: String = row.get(2)?;
+            Ok(SideEffect {
+                file_path: row.get(0)?,
+                line: row.get(1)?,
+                kind: SideEffectKind::from_str_loose(&kind).unwrap_or(SideEffectKind::Call),
+                statement: row.get(3)?,
+            })
+        })?;
+        rows.collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(Into::into)
+    }
+
+    // -------------------------------------------------------------------
+    // String literals and constants
+    // -------------------------------------------------------------------
+
+    /// Replace the literals and constants recorded for `file_path`.
+    pub fn replace_literals_for_file(&self, file_path: &str, literals: &[Literal]) -> Result<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        {
+            let mut del = tx.prepare_cached("DELETE FROM literals WHERE file_path = ?1")?;
+            del.execute(params![file_path])?;
+            let mut ins = tx.prepare_cached(
+                "INSERT INTO literals (file_path, line, kind, value, name, node_id) \
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            )?;
+            for l in literals {
+                ins.execute(params![
+                    l.file_path,
+                    l.line,
+                    l.kind.as_str(),
+                    l.value,
+                    l.name,
+                    l.node_id
+                ])?;
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Literals whose value or constant name equals `query` (`exact`) or
+    /// contains it case-insensitively, ordered by file and line.
+    pub fn find_literals(
+        &self,
+        query: &str,
+        exact: bool,
+        kind: Option<LiteralKind>,
+    ) -> Result<Vec<Literal>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT file_path, line, kind, value, name, node_id FROM literals \
+             WHERE (?3 IS NULL OR kind = ?3) AND CASE WHEN ?2 \
+               THEN value = ?1 OR name = ?1 \
+               ELSE instr(lower(value), lower(?1)) > 0 OR instr(lower(name), lower(?1)) > 0 \
+             END ORDER BY file_path, line",
+        )?;
+        let rows = stmt.query_map(params![query, exact, kind.map(|k| k.as_str())], |row| {
+            let kind: String = row.get(2)?;
+            Ok(Literal {
+                file_path: row.get(0)?,
+                line: row.get(1)?,
+                kind: LiteralKind::from_str_loose(&kind).unwrap_or(LiteralKind::String),
+                value: row.get(3)?,
+                name: row.get(4)?,
+                node_id: row.get(5)?,
+            })
+        })?;
+        rows.collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(Into::into)
+    }
+
+    // -------------------------------------------------------------------
+    // Node tags
+    // -------------------------------------------------------------------
+
+    /// Set tag `key` on node `node_id` to `value`, replacing any previous
+    /// value. Returns `false` when the node does not exist.
+    ///
+    /// Tags are not removed with their node: a symbol that comes back under
+    /// a new ID (moved within its file) keeps them.
+    pub fn set_node_tag(&self, node_id: &str, key: &str, value: &str) -> Result<bool> {
+        let changed = self
+            .conn
+            .prepare_cached(
+                "INSERT INTO node_tags (node_id, key, value, file_path, kind, symbol) \
+             SELECT id, ?2, ?3, file_path, type, COALESCE(qualified_name, name) \
+             FROM nodes WHERE id = ?1 \
+             ON CONFLICT(node_id, key) DO UPDATE SET value = excluded.value",
+            )?
+            .execute(params![node_id, key, value])?;
+        Ok(changed > 0)
+    }
+
+    /// Remove tag `key` from node `node_id`. Returns whether it was set.
+    pub fn remove_node_tag(&self, node_id: &str, key: &str) -> Result<bool> {
+        let removed = self
+            .conn
+            .prepare_cached("DELETE FROM node_tags WHERE node_id = ?1 AND key = ?2")?
+            .execute(params![node_id, key])?;
+        Ok(removed > 0)
+    }
+
+    /// Tags on node `node_id`, ordered by key.
+    pub fn get_node_tags(&self, node_id: &str) -> Result<Vec<NodeTag>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT node_id, key, value FROM node_tags WHERE node_id = ?1 ORDER BY key",
+        )?;
+        let rows = stmt.query_map(params![node_id], |row| {
+            Ok(NodeTag {
+                node_id: row.get(0)?,
+                key: row.get(1)?,
+                value: row.get(2)?,
+            })
+        })?;
+        rows.collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(Into::into)
+    }
+
+    /// IDs of the nodes carrying every tag in `filters`, sorted. An empty
+    /// filter list matches every tagged node.
+    pub fn find_tagged_nodes(&self, filters: &[TagFilter]) -> Result<Vec<String>> {
+        tagged_node_ids(&self.conn, filters)
+    }
+
+    /// Every `(key, value)` in use with its node count, ordered by key and
+    /// value.
+    pub fn get_tag_counts(&self) -> Result<Vec<(String, String, usize)>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT key, value, COUNT(*) FROM node_tags GROUP BY key, value ORDER BY key, value",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get::<_, i64>(2)? as usize))
+        })?;
+        rows.collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(Into::into)
+    }
+
+    // -------------------------------------------------------------------
+    // Node notes
+    // -------------------------------------------------------------------
+
+    /// Add a note to node `node_id`. Returns the note's ID, or `None` when
+    /// the node does not exist. Notes follow moved symbols like tags.
+    pub fn add_node_note(
+        &self,
+        node_id: &str,
+        note: &str,
+        author: Option<&str>,
+    ) -> Result<Option<i64>> {
+        let added = self
+            .conn
+            .prepare_cached(
+                "INSERT INTO node_notes (node_id, note, author, file_path, kind, symbol) \
+             SELECT id, ?2, ?3, file_path, type, COALESCE(qualified_name, name) \
+             FROM nodes WHERE id = ?1",
+            )?
+            .execute(params![node_id, note, author])?;
+        Ok((added > 0).then(|| self.conn.last_insert_rowid()))
+    }
+
+    /// Delete note `id` of node `node_id`. Returns whether it existed.
+    pub fn remove_node_note(&self, node_id: &str, id: i64) -> Result<bool> {
+        let removed = self
+            .conn
+            .prepare_cached("DELETE FROM node_notes WHERE node_id = ?1 AND id = ?2")?
+            .execute(params![node_id, id])?;
+        Ok(removed > 0)
+    }
+
+    /// Notes on node `node_id`, oldest first.
+    pub fn get_node_notes(&self, node_id: &str) -> Result<Vec<NodeNote>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT id, node_id, note, author, created_at FROM node_notes \
+             WHERE node_id = ?1 ORDER BY id",
+        )?;
+        let rows = stmt.query_map(params![node_id], note_from_row)?;
+        rows.collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(Into::into)
+    }
+
+    /// Tags and notes of every annotated node, by node ID. Annotations
+    /// are few, so callers decorating a response load them all at once.
+    pub fn get_all_annotations(&self) -> Result<HashMap<String, NodeAnnotations>> {
+        let mut all: HashMap<String, NodeAnnotations> = HashMap::new();
+        let mut stmt = self
+            .conn
+            .prepare_cached("SELECT node_id, key, value FROM node_tags ORDER BY key")?;
+        let tags = stmt.query_map([], |row| {
+            Ok(NodeTag {
+                node_id: row.get(0)?,
+                key: row.get(1)?,
+                value: row.get(2)?,
+            })
+        })?;
+        for tag in tags {
+            let tag = tag?;
+            all.entry(tag.node_id.clone()).or_default().tags.push(tag);
+        }
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT id, node_id, note, author, created_at FROM node_notes ORDER BY id",
+        )?;
+        for note in stmt.query_map([], note_from_row)? {
+            let note = note?;
+            all.entry(note.node_id.clone())
+                .or_default()
+                .notes
+                .push(note);
+        }
+        Ok(all)
+    }
+
+    /// Files with an import edge into a symbol of `file_path`.
+    pub fn get_importing_files(&self, file_path: &str) -> Result<Vec<String>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT DISTINCT e.file_path FROM edges e JOIN nodes n ON n.id = e.target_id \
+             WHERE e.type = 'imports' AND n.file_path = ?1 AND e.file_path != ?1 \
+             ORDER BY e.file_path",
+        )?;
+        let rows = stmt.query_map(params![file_path], |row| row.get(0))?;
+        rows.collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(Into::into)
+    }
+
+    // -------------------------------------------------------------------
+    // Tombstones
+    // -------------------------------------------------------------------
+
+    /// Symbols removed from the graph, newest first, optionally limited to
+    /// one file and to removals at or after `since` (Unix seconds).
+    pub fn get_tombstones(
+        &self,
+        file_path: Option<&str>,
+        since: Option<i64>,
+    ) -> Result<Vec<Tombstone>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT id, type, name, qualified_name, file_path, start_line, end_line, \
+                    language, deleted_at \
+             FROM node_tombstones \
+             WHERE (?1 IS NULL OR file_path = ?1) AND deleted_at >= ?2 \
+             ORDER BY deleted_at DESC, file_path, start_line",
+        )?;
+        let rows = stmt.query_map(params![file_path, since.unwrap_or(0)], row_to_tombstone)?;
+        rows.collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(Into::into)
+    }
+
+    /// Tombstones for symbols named `name` (or whose qualified name is
+    /// `name`), newest first.
+    pub fn get_tombstones_by_name(&self, name: &str) -> Result<Vec<Tombstone>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT id, type, name, qualified_name, file_path, start_line, end_line, \
+                    language, deleted_at \
+             FROM node_tombstones WHERE id = ?1 OR name = ?1 OR qualified_name = ?1 \
+             ORDER BY deleted_at DESC",
+        )?;
+        let rows = stmt.query_map(params![name], row_to_tombstone)?;
+        rows.collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(Into::into)
+    }
+
+    /// Edges removed from the graph, newest first, optionally limited to
+    /// the file whose re-index dropped them.
+    pub fn get_edge_tombstones(&self, file_path: Option<&str>) -> Result<Vec<EdgeTombstone>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT source_id, target_id, type, file_path, deleted_at \
+             FROM edge_tombstones WHERE ?1 IS NULL OR file_path = ?1 \
+             ORDER BY deleted_at DESC",
+        )?;
+        let rows = stmt.query_map(params![file_path], |row| {
+            Ok(EdgeTombstone {
+                source_id: row.get(0)?,
+                target_id: row.get(1)?,
+                kind: row.get(2)?,
+                file_path: row.get(3)?,
+                deleted_at: row.get(4)?,
+            })
+        })?;
+        rows.collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(Into::into)
+    }
+
+    /// Removed edges that pointed at `target_id`, newest first.
+    pub fn get_edge_tombstones_to(&self, target_id: &str) -> Result<Vec<EdgeTombstone>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT source_id, target_id, type, file_path, deleted_at \
+             FROM edge_tombstones WHERE target_id = ?1 \
+             ORDER BY deleted_at DESC",
+        )?;
+        let rows = stmt.query_map(params![target_id], |row| {
+            Ok(EdgeTombstone {
+                source_id: row.get(0)?,
+                target_id: row.get(1)?,
+                kind: row.get(2)?,
+                file_path: row.get(3)?,
+                deleted_at: row.get(4)?,
+            })
+        })?;
+        rows.collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(Into::into)
+    }
+
+    /// Purge tombstones recorded before `older_than` (Unix seconds), or all
+    /// of them when `None`, without compacting the database.
+    pub fn purge_tombstones(&self, older_than: Option<i64>) -> Result<VacuumStats> {
+        let cutoff = older_than.unwrap_or(i64::MAX);
+        let nodes = self.conn.execute(
+            "DELETE FROM node_tombstones WHERE deleted_at < ?1",
+            params![cutoff],
+        )?;
+        let edges = self.conn.execute(
+            "DELETE FROM edge_tombstones WHERE deleted_at < ?1",
+            params![cutoff],
+        )?;
+        Ok(VacuumStats { nodes, edges })
+    }
+
+    /// Purge tombstones older than the policy's retention period. A no-op
+    /// when the policy keeps tombstones indefinitely.
+    pub fn purge_expired_tombstones(&self) -> Result<VacuumStats> {
+        match self.tombstones.get().retention_secs {
+            Some(secs) => self.purge_tombstones(Some(unix_now() - secs)),
+            None => Ok(VacuumStats::default()),
+        }
+    }
+
+    /// Purge tombstones recorded before `older_than` (Unix seconds), or all
+    /// of them when `None`, then compact the database file.
+    pub fn vacuum(&self, older_than: Option<i64>) -> Result<VacuumStats> {
+        let tx = self.conn.unchecked_transaction()?;
+        let stats = self.purge_tombstones(older_than)?;
+        tx.execute(
+            "DELETE FROM node_bodies WHERE node_id NOT IN (SELECT id FROM nodes)",
+            [],
+        )?;
+        tx.commit()?;
+        // VACUUM cannot run inside a transaction.
+        self.conn.execute_batch("VACUUM")?;
+        Ok(stats)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Batch streaming
+// ---------------------------------------------------------------------------
+
+/// Iterator returned by [`GraphStore::iter_nodes`].
+pub struct NodeBatches<'a> {
+    store: &'a GraphStore,
+    batch_size: usize,
+    /// ID of the last node yielded; `None` once the table is exhausted.
+    after: Option<String>,
+}
+
+impl Iterator for NodeBatches<'_> {
+    type Item = Result<Vec<CodeNode>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let after = self.after.take()?;
+        let batch = (|| -> Result<Vec<CodeNode>> {
+            let mut stmt = self
+                .store
+                .conn
+                .prepare_cached(select_nodes!("WHERE n.id > ?1 ORDER BY n.id LIMIT ?2"))?;
+            let rows =
+                stmt.query_and_then(params![after, self.batch_size as i64], row_to_code_node)?;
+            rows.collect::<std::result::Result<Vec<_>, _>>()
+                .map_err(Into::into)
+        })();
+        match batch {
+            Ok(nodes) if nodes.is_empty() => None,
+            Ok(nodes) => {
+                if nodes.len() == self.batch_size {
+                    self.after = nodes.last().map(|n| n.id.clone());
+                }
+                Some(Ok(nodes))
+            }
+            Err(e) => Some(Err(e)),
+        }
+    }
+}
+
+/// Iterator returned by [`GraphStore::iter_edges`].
+pub struct EdgeBatches<'a> {
+    store: &'a GraphStore,
+    batch_size: usize,
+    /// Row ID of the last edge yielded; `None` once the table is exhausted.
+    after: Option<i64>,
+}
+
+impl Iterator for EdgeBatches<'_> {
+    type Item = Result<Vec<CodeEdge>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let after = self.after.take()?;
+        let batch = (|| -> Result<Vec<(i64, CodeEdge)>> {
+            let mut stmt = self
+                .store
+                .conn
+                .prepare_cached("SELECT * FROM edges WHERE id > ?1 ORDER BY id LIMIT ?2")?;
+            let rows = stmt.query_map(params![after, self.batch_size as i64], |row| {
+                Ok((row.get("id")?, row_to_code_edge(row)?))
+            })?;
+            rows.collect::<std::result::Result<Vec<_>, _>>()
+                .map_err(Into::into)
+        })();
+        match batch {
+            Ok(edges) if edges.is_empty() => None,
+            Ok(edges) => {
+                if edges.len() == self.batch_size {
+                    self.after = edges.last().map(|(id, _)| *id);
+                }
+                Some(Ok(edges.into_iter().map(|(_, e)| e).collect()))
+            }
+            Err(e) => Some(Err(e)),
+        }
+    }
+}
+
+/// Replace the nodes and edges of one file. `tx` must be inside a
+/// transaction owned by the caller.
+fn replace_file_rows(
+    tx: &Connection,
+    file_path: &str,
+    nodes: &[CodeNode],
+    edges: &[CodeEdge],
+    bodies: BodyStorage,
+    tombstones: bool,
+) -> Result<()> {
+    // Tombstone everything, then clear the stones of rows that come
+    // back below — whatever remains was removed by this index.
+    if tombstones {
+        let now = unix_now();
+        tx.prepare_cached(TOMBSTONE_EDGES_BY_FILE_SQL)?
+            .execute(params![file_path, now])?;
+        tx.prepare_cached(TOMBSTONE_NODES_BY_FILE_SQL)?
+            .execute(params![file_path, now])?;
+    }
+
+    // Delete edges first (they reference nodes via FK).
+    let mut del_edges = tx.prepare_cached(DELETE_EDGES_BY_FILE_SQL)?;
+    del_edges.execute(params![file_path])?;
+
+    tx.prepare_cached(DELETE_BODIES_BY_FILE_SQL)?
+        .execute(params![file_path])?;
+    let mut del_nodes = tx.prepare_cached(DELETE_NODES_BY_FILE_SQL)?;
+    del_nodes.execute(params![file_path])?;
+
+    // Insert replacements.
+    let mut ins_node = tx.prepare_cached(UPSERT_NODE_SQL)?;
+    let mut resurrect_node = tx.prepare_cached(RESURRECT_NODE_SQL)?;
+    for node in nodes {
+        let name_tokens = build_name_tokens(&node.name, node.qualified_name.as_deref());
+        let is_test = detect_is_test(
+            &node.name,
+            &node.file_path,
+            node.language.as_str(),
+            node.kind.as_str(),
+        );
+        ins_node.execute(params![
+            node.id,
+            node.kind.as_str(),
+            node.name,
+            node.qualified_name,
+            node.file_path,
+            node.start_line,
+            node.end_line,
+            node.language.as_str(),
+            node.body,
+            node.documentation,
+            compute_simple_hash(&node.id),
+            build_node_metadata_without_body(node),
+            name_tokens,
+            is_test as i32,
+        ])?;
+        write_node_body(tx, node, bodies)?;
+        resurrect_node.execute(params![node.id])?;
+    }
+
+    let mut ins_edge = tx.prepare_cached(UPSERT_EDGE_SQL)?;
+    let mut resurrect_edge = tx.prepare_cached(RESURRECT_EDGE_SQL)?;
+    for edge in edges {
+        ins_edge.execute(params![
+            edge.source,
+            edge.target,
+            edge.kind.as_str(),
+            build_edge_properties(edge),
+        ])?;
+        resurrect_edge.execute(params![edge.source, edge.target, edge.kind.as_str()])?;
     }
 
-    /// Get the total count of unresolved references.
-    pub fn get_unresolved_ref_count(&self) -> Result<usize> {
-        let mut stmt = self
-            .conn
-            .prepare_cached("SELECT count(*) FROM unresolved_refs")?;
-        let count: i64 = stmt.query_row([], |row| row.get(0))?;
-        Ok(count as usize)
+    tx.prepare_cached(REKEY_TAGS_BY_FILE_SQL)?
+        .execute(params![file_path])?;
+    tx.prepare_cached(REKEY_NOTES_BY_FILE_SQL)?
+        .execute(params![file_path])?;
+    Ok(())
+}
+
+/// Store `node`'s body in `node_bodies`, or drop a stale one when the node
+/// has no body (or bodies are disabled).
+fn write_node_body(conn: &Connection, node: &CodeNode, storage: BodyStorage) -> Result<()> {
+    match node.body.as_deref().and_then(|b| encode_body(b, storage)) {
+        Some((encoding, data)) => {
+            conn.prepare_cached(UPSERT_BODY_SQL)?.execute(params![
+                node.id,
+                encoding.as_str(),
+                data
+            ])?;
+        }
+        None => {
+            conn.prepare_cached(DELETE_BODY_SQL)?
+                .execute(params![node.id])?;
+        }
     }
+    Ok(())
+}
+
+fn row_to_node_git_meta(row: &rusqlite::Row<'_>) -> rusqlite::Result<NodeGitMeta> {
+    Ok(NodeGitMeta {
+        node_id: row.get(0)?,
+        file_path: row.get(1)?,
+        commit_hash: row.get(2)?,
+        author: row.get(3)?,
+        email: row.get(4)?,
+        committed_at: row.get(5)?,
+        age_days: row.get(6)?,
+    })
+}
+
+fn row_to_tombstone(row: &rusqlite::Row<'_>) -> rusqlite::Result<Tombstone> {
+    Ok(Tombstone {
+        id: row.get(0)?,
+        kind: row.get(1)?,
+        name: row.get(2)?,
+        qualified_name: row.get(3)?,
+        file_path: row.get(4)?,
+        start_line: row.get(5)?,
+        end_line: row.get(6)?,
+        language: row.get(7)?,
+        deleted_at: row.get(8)?,
+    })
+}
+
+/// Current time in Unix seconds, used to stamp tombstones.
+fn unix_now() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
 }
 
 // ---------------------------------------------------------------------------
@@ -1013,6 +2261,151 @@ mod tests {
         assert_eq!(fresh.name, "fresh");
     }
 
+    #[test]
+    fn replace_files_data_commits_batch_or_nothing() {
+        let store = setup();
+        store
+            .replace_files_data(&[
+                (
+                    "a.ts",
+                    &[make_node("a1", "a", "a.ts", NodeKind::Function, 1)],
+                    &[],
+                ),
+                (
+                    "b.ts",
+                    &[make_node("b1", "b", "b.ts", NodeKind::Function, 1)],
+                    &[],
+                ),
+            ])
+            .unwrap();
+        assert_eq!(store.get_node_count().unwrap(), 2);
+
+        // A failure on the second file rolls back the first as well.
+        store
+            .conn
+            .execute_batch(
+                "CREATE TRIGGER fail_boom BEFORE INSERT ON nodes WHEN NEW.name = 'boom' \
+                 BEGIN SELECT RAISE(ABORT, 'boom'); END",
+            )
+            .unwrap();
+        let result = store.replace_files_data(&[
+            (
+                "a.ts",
+                &[make_node("a2", "a2", "a.ts", NodeKind::Function, 1)],
+                &[],
+            ),
+            (
+                "b.ts",
+                &[make_node("b2", "boom", "b.ts", NodeKind::Function, 1)],
+                &[],
+            ),
+        ]);
+        assert!(result.is_err());
+        assert!(store.get_node("a1").unwrap().is_some());
+        assert!(store.get_node("a2").unwrap().is_none());
+        assert!(store.get_node("b1").unwrap().is_some());
+    }
+
+    #[test]
+    fn replace_file_data_tombstones_removed_symbols() {
+        let store = setup();
+        store
+            .replace_file_data(
+                "a.ts",
+                &[
+                    make_node("n1", "kept", "a.ts", NodeKind::Function, 1),
+                    make_node("n2", "dropped", "a.ts", NodeKind::Function, 10),
+                ],
+                &[make_edge("n1", "n2", EdgeKind::Calls, "a.ts", 3)],
+            )
+            .unwrap();
+        assert!(store.get_tombstones(None, None).unwrap().is_empty());
+
+        store
+            .replace_file_data(
+                "a.ts",
+                &[make_node("n1", "kept", "a.ts", NodeKind::Function, 1)],
+                &[],
+            )
+            .unwrap();
+
+        let stones = store.get_tombstones(Some("a.ts"), None).unwrap();
+        assert_eq!(stones.len(), 1);
+        assert_eq!(stones[0].id, "n2");
+        assert_eq!(stones[0].name, "dropped");
+        assert_eq!(stones[0].start_line, 10);
+        assert!(stones[0].deleted_at > 0);
+        assert_eq!(store.get_tombstones_by_name("dropped").unwrap().len(), 1);
+
+        let edge_stones = store.get_edge_tombstones(Some("a.ts")).unwrap();
+        assert_eq!(edge_stones.len(), 1);
+        assert_eq!(edge_stones[0].source_id, "n1");
+        assert_eq!(edge_stones[0].target_id, "n2");
+
+        // A symbol that comes back loses its tombstone.
+        store
+            .upsert_node(&make_node("n2", "dropped", "a.ts", NodeKind::Function, 10))
+            .unwrap();
+        assert!(store.get_tombstones(None, None).unwrap().is_empty());
+    }
+
+    #[test]
+    fn vacuum_purges_tombstones_by_age() {
+        let store = setup();
+        store
+            .upsert_node(&make_node("n1", "gone", "a.ts", NodeKind::Function, 1))
+            .unwrap();
+        store.delete_file_nodes("a.ts").unwrap();
+        assert_eq!(store.get_tombstones(None, None).unwrap().len(), 1);
+
+        let kept = store.vacuum(Some(0)).unwrap();
+        assert_eq!(kept, VacuumStats::default());
+        assert_eq!(store.get_tombstones(None, None).unwrap().len(), 1);
+
+        let purged = store.vacuum(None).unwrap();
+        assert_eq!(purged.nodes, 1);
+        assert!(store.get_tombstones(None, None).unwrap().is_empty());
+    }
+
+    #[test]
+    fn tombstone_policy_disables_and_expires_tombstones() {
+        let store = setup();
+        store.set_tombstone_policy(TombstonePolicy {
+            enabled: false,
+            retention_secs: None,
+        });
+        store
+            .upsert_node(&make_node("n1", "gone", "a.ts", NodeKind::Function, 1))
+            .unwrap();
+        store.replace_file_data("a.ts", &[], &[]).unwrap();
+        assert!(store.get_tombstones(None, None).unwrap().is_empty());
+
+        store.set_tombstone_policy(TombstonePolicy::default());
+        store
+            .upsert_node(&make_node("n2", "old", "b.ts", NodeKind::Function, 1))
+            .unwrap();
+        store.delete_file_nodes("b.ts").unwrap();
+        // Keeping tombstones forever never purges them.
+        assert_eq!(
+            store.purge_expired_tombstones().unwrap(),
+            VacuumStats::default()
+        );
+
+        store
+            .conn
+            .execute(
+                "UPDATE node_tombstones SET deleted_at = deleted_at - 7200",
+                [],
+            )
+            .unwrap();
+        store.set_tombstone_policy(TombstonePolicy {
+            enabled: true,
+            retention_secs: Some(3600),
+        });
+        assert_eq!(store.purge_expired_tombstones().unwrap().nodes, 1);
+        assert!(store.get_tombstones(None, None).unwrap().is_empty());
+    }
+
     #[test]
     fn replace_file_data_does_not_affect_other_files() {
         let store = setup();
@@ -1142,6 +2535,85 @@ mod tests {
 
     // -- empty database ----------------------------------------------------
 
+    #[test]
+    fn iter_nodes_and_edges_stream_in_batches() {
+        let store = setup();
+        let nodes: Vec<CodeNode> = (0..7)
+            .map(|i| make_node(&format!("n{i}"), "f", "a.ts", NodeKind::Function, i))
+            .collect();
+        let edges: Vec<CodeEdge> = (1..7)
+            .map(|i| make_edge("n0", &format!("n{i}"), EdgeKind::Calls, "a.ts", i))
+            .collect();
+        store.upsert_nodes(&nodes).unwrap();
+        store.upsert_edges(&edges).unwrap();
+
+        let batches: Vec<Vec<CodeNode>> = store.iter_nodes(3).map(|b| b.unwrap()).collect();
+        assert_eq!(
+            batches.iter().map(Vec::len).collect::<Vec<_>>(),
+            vec![3, 3, 1]
+        );
+        let mut ids: Vec<String> = batches.into_iter().flatten().map(|n| n.id).collect();
+        ids.dedup();
+        assert_eq!(ids.len(), 7);
+
+        let edge_batches: Vec<usize> = store.iter_edges(3).map(|b| b.unwrap().len()).collect();
+        assert_eq!(edge_batches, vec![3, 3]);
+        assert_eq!(store.iter_edges(0).count(), 6);
+    }
+
+    #[test]
+    fn iter_nodes_on_empty_store_yields_nothing() {
+        let store = setup();
+        assert_eq!(store.iter_nodes(100).count(), 0);
+        assert_eq!(store.iter_edges(100).count(), 0);
+    }
+
+    #[test]
+    fn pinned_snapshot_ignores_later_commits() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("graph.db");
+        let store = GraphStore::new(path.to_str().unwrap()).unwrap();
+        store
+            .upsert_node(&make_node("n1", "a", "a.ts", NodeKind::Function, 1))
+            .unwrap();
+
+        let pinned = store.pin_snapshot().unwrap();
+        store
+            .upsert_node(&make_node("n2", "b", "a.ts", NodeKind::Function, 5))
+            .unwrap();
+
+        assert_eq!(store.get_node_count().unwrap(), 2);
+        assert_eq!(pinned.get_node_count().unwrap(), 1);
+        assert!(pinned.get_node("n2").unwrap().is_none());
+        assert!(
+            setup().pin_snapshot().is_err(),
+            "in-memory stores cannot pin"
+        );
+    }
+
+    #[test]
+    fn adjacency_cache_is_opt_in_and_tracks_writes() {
+        let store = setup();
+        assert!(store.adjacency().unwrap().is_none());
+
+        store.enable_adjacency_cache();
+        store
+            .upsert_nodes(&[
+                make_node("a", "a", "a.ts", NodeKind::Function, 1),
+                make_node("b", "b", "a.ts", NodeKind::Function, 5),
+            ])
+            .unwrap();
+        let first = store.adjacency().unwrap().unwrap();
+        assert_eq!(first.edge_count(), 0);
+        let again = store.adjacency().unwrap().unwrap();
+        assert!(Arc::ptr_eq(&first, &again), "unchanged db reuses the build");
+
+        store
+            .upsert_edge(&make_edge("a", "b", EdgeKind::Calls, "a.ts", 2))
+            .unwrap();
+        assert_eq!(store.adjacency().unwrap().unwrap().edge_count(), 1);
+    }
+
     #[test]
     fn empty_store_returns_zeros_and_empty_vecs() {
         let store = setup();
@@ -1398,6 +2870,237 @@ mod tests {
         assert_eq!(store.get_node_count().unwrap(), 1);
     }
 
+    #[test]
+    fn node_git_meta_round_trip_and_cleared_with_file() {
+        let store = setup();
+        store
+            .upsert_node(&make_node("n1", "a", "a.ts", NodeKind::Function, 1))
+            .unwrap();
+        let meta = NodeGitMeta {
+            node_id: "n1".to_string(),
+            file_path: "a.ts".to_string(),
+            commit_hash: "abc123".to_string(),
+            author: "Alice".to_string(),
+            email: "alice@example.com".to_string(),
+            committed_at: 1_700_000_000,
+            age_days: 12,
+        };
+        store
+            .replace_node_git_meta_for_file("a.ts", std::slice::from_ref(&meta))
+            .unwrap();
+        assert_eq!(store.get_node_git_meta("n1").unwrap(), Some(meta));
+
+        store.delete_file_nodes("a.ts").unwrap();
+        assert_eq!(store.get_node_git_meta("n1").unwrap(), None);
+    }
+
+    #[test]
+    fn wire_fields_round_trip_and_cleared_with_file() {
+        let store = setup();
+        let field = WireField {
+            node_id: "f1".to_string(),
+            owner_id: "t1".to_string(),
+            owner: "Order".to_string(),
+            field_name: "order_id".to_string(),
+            wire_name: "orderId".to_string(),
+            skipped: false,
+            optional: true,
+            framework: WireFramework::Serde,
+            file_path: "order.rs".to_string(),
+            line: 4,
+        };
+        store
+            .replace_wire_fields_for_file("order.rs", std::slice::from_ref(&field))
+            .unwrap();
+        assert_eq!(store.get_wire_fields("t1").unwrap(), vec![field]);
+
+        store.delete_file_nodes("order.rs").unwrap();
+        assert!(store.get_wire_fields("t1").unwrap().is_empty());
+    }
+
+    #[test]
+    fn side_effects_round_trip_and_cleared_with_file() {
+        let store = setup();
+        let effect = SideEffect {
+            file_path: "app.py".to_string(),
+            line: 3,
+            kind: SideEffectKind::Initializer,
+            statement: "db = connect()".to_string(),
+        };
+        store
+            .replace_side_effects_for_file("app.py", std::slice::from_ref(&effect))
+            .unwrap();
+        assert_eq!(store.get_side_effects(None).unwrap(), vec![effect.clone()]);
+        assert_eq!(
+            store.get_side_effects(Some("app.py")).unwrap(),
+            vec![effect]
+        );
+        assert!(store.get_side_effects(Some("other.py")).unwrap().is_empty());
+
+        store.delete_file_nodes("app.py").unwrap();
+        assert!(store.get_side_effects(None).unwrap().is_empty());
+    }
+
+    #[test]
+    fn node_tags_filter_and_follow_moved_symbols() {
+        let store = setup();
+        store
+            .replace_file_data(
+                "api.ts",
+                &[
+                    make_node("fn:api.ts:list:1", "list", "api.ts", NodeKind::Function, 1),
+                    make_node("fn:api.ts:pay:5", "pay", "api.ts", NodeKind::Function, 5),
+                ],
+                &[],
+            )
+            .unwrap();
+        assert!(store
+            .set_node_tag("fn:api.ts:list:1", "layer", "api")
+            .unwrap());
+        assert!(store
+            .set_node_tag("fn:api.ts:pay:5", "layer", "api")
+            .unwrap());
+        assert!(store
+            .set_node_tag("fn:api.ts:pay:5", "team", "billing")
+            .unwrap());
+        assert!(store
+            .set_node_tag("fn:api.ts:pay:5", "team", "payments")
+            .unwrap());
+        assert!(!store.set_node_tag("missing", "layer", "api").unwrap());
+
+        let filter = |s: &str| TagFilter::parse(s).unwrap();
+        assert_eq!(
+            store.find_tagged_nodes(&[filter("layer=api")]).unwrap(),
+            vec!["fn:api.ts:list:1", "fn:api.ts:pay:5"]
+        );
+        assert_eq!(
+            store
+                .find_tagged_nodes(&[filter("layer=api"), filter("team")])
+                .unwrap(),
+            vec!["fn:api.ts:pay:5"]
+        );
+        assert!(store
+            .find_tagged_nodes(&[filter("team=billing")])
+            .unwrap()
+            .is_empty());
+        assert_eq!(TagFilter::parse(" =x"), None);
+        assert_eq!(
+            store.get_tag_counts().unwrap(),
+            vec![
+                ("layer".to_string(), "api".to_string(), 2),
+                ("team".to_string(), "payments".to_string(), 1),
+            ]
+        );
+
+        // `pay` moves down two lines; its tags follow the new ID.
+        store
+            .replace_file_data(
+                "api.ts",
+                &[
+                    make_node("fn:api.ts:list:1", "list", "api.ts", NodeKind::Function, 1),
+                    make_node("fn:api.ts:pay:7", "pay", "api.ts", NodeKind::Function, 7),
+                ],
+                &[],
+            )
+            .unwrap();
+        let tags = store.get_node_tags("fn:api.ts:pay:7").unwrap();
+        assert_eq!(
+            tags.iter()
+                .map(|t| (t.key.as_str(), t.value.as_str()))
+                .collect::<Vec<_>>(),
+            vec![("layer", "api"), ("team", "payments")]
+        );
+        assert!(store.remove_node_tag("fn:api.ts:pay:7", "team").unwrap());
+        assert!(!store.remove_node_tag("fn:api.ts:pay:7", "team").unwrap());
+    }
+
+    #[test]
+    fn node_notes_follow_moved_symbols() {
+        let store = setup();
+        let nodes = |line| {
+            vec![make_node(
+                &format!("fn:api.ts:pay:{line}"),
+                "pay",
+                "api.ts",
+                NodeKind::Function,
+                line,
+            )]
+        };
+        store.replace_file_data("api.ts", &nodes(5), &[]).unwrap();
+        let first = store
+            .add_node_note("fn:api.ts:pay:5", "do not touch", Some("alice"))
+            .unwrap()
+            .unwrap();
+        store
+            .add_node_note("fn:api.ts:pay:5", "hot path", None)
+            .unwrap();
+        assert_eq!(store.add_node_note("missing", "x", None).unwrap(), None);
+        store
+            .set_node_tag("fn:api.ts:pay:5", "perf", "critical")
+            .unwrap();
+
+        store.replace_file_data("api.ts", &nodes(9), &[]).unwrap();
+        let notes = store.get_node_notes("fn:api.ts:pay:9").unwrap();
+        assert_eq!(
+            notes.iter().map(|n| n.note.as_str()).collect::<Vec<_>>(),
+            vec!["do not touch", "hot path"]
+        );
+        assert_eq!(notes[0].author.as_deref(), Some("alice"));
+
+        let all = store.get_all_annotations().unwrap();
+        assert_eq!(all.len(), 1);
+        assert_eq!(all["fn:api.ts:pay:9"].tags[0].key, "perf");
+        assert_eq!(all["fn:api.ts:pay:9"].notes.len(), 2);
+
+        assert!(store.remove_node_note("fn:api.ts:pay:9", first).unwrap());
+        assert!(!store.remove_node_note("fn:api.ts:pay:9", first).unwrap());
+    }
+
+    #[test]
+    fn literals_match_exact_or_substring_and_clear_with_file() {
+        let store = setup();
+        let literal = |line, kind, value: &str, name: Option<&str>| Literal {
+            file_path: "errors.ts".to_string(),
+            line,
+            kind,
+            value: value.to_string(),
+            name: name.map(str::to_string),
+            node_id: None,
+        };
+        let code = literal(1, LiteralKind::Constant, "E404", Some("ERR_NOT_FOUND"));
+        let message = literal(5, LiteralKind::String, "Page not found", None);
+        store
+            .replace_literals_for_file("errors.ts", &[code.clone(), message.clone()])
+            .unwrap();
+
+        assert_eq!(
+            store.find_literals("E404", true, None).unwrap(),
+            vec![code.clone()]
+        );
+        assert_eq!(
+            store.find_literals("ERR_NOT_FOUND", true, None).unwrap(),
+            vec![code.clone()]
+        );
+        assert!(store.find_literals("e404", true, None).unwrap().is_empty());
+        assert_eq!(
+            store.find_literals("NOT FOUND", false, None).unwrap(),
+            vec![message.clone()]
+        );
+        assert_eq!(
+            store.find_literals("not_found", false, None).unwrap(),
+            vec![code]
+        );
+        assert_eq!(
+            store
+                .find_literals("o", false, Some(LiteralKind::String))
+                .unwrap(),
+            vec![message]
+        );
+
+        store.delete_file_nodes("errors.ts").unwrap();
+        assert!(store.find_literals("E404", true, None).unwrap().is_empty());
+    }
+
     #[test]
     fn delete_file_nodes_removes_cross_file_edges() {
         let store = setup();
@@ -1778,9 +3481,52 @@ mod tests {
         node.body = Some(long_body);
         store.upsert_node(&node).unwrap();
 
-        // The node is stored; metadata body is truncated but node itself is fine
+        // The node is stored; its body is truncated but the node itself is fine
         let got = store.get_node("n1").unwrap().unwrap();
         assert_eq!(got.name, "bigFunc");
+        assert_eq!(got.body.map(|b| b.len()), Some(4096));
+    }
+
+    #[test]
+    fn bodies_live_in_node_bodies_with_configured_storage() {
+        let store = setup();
+        store.set_body_storage(BodyStorage {
+            max_bytes: 64 * 1024,
+            compress: true,
+        });
+        let body = "if (ready) { start(); }\n".repeat(400);
+        let mut node = make_node("n1", "boot", "a.ts", NodeKind::Function, 1);
+        node.body = Some(body.clone());
+        store.replace_file_data("a.ts", &[node], &[]).unwrap();
+
+        let got = store.get_node("n1").unwrap().unwrap();
+        assert_eq!(got.body.as_deref(), Some(body.as_str()));
+        let (encoding, stored, metadata): (String, i64, String) = store
+            .conn
+            .query_row(
+                "SELECT b.encoding, length(b.body), n.metadata \
+                 FROM node_bodies b JOIN nodes n ON n.id = b.node_id",
+                [],
+                |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?)),
+            )
+            .unwrap();
+        assert_eq!(encoding, "zstd");
+        assert!((stored as usize) < body.len() / 10);
+        assert!(!metadata.contains("body"));
+
+        // Same results through every read path.
+        assert_eq!(store.get_nodes_by_file("a.ts").unwrap()[0].body, got.body);
+        assert_eq!(
+            store.iter_nodes(10).next().unwrap().unwrap()[0].body,
+            got.body
+        );
+
+        store.delete_file_nodes("a.ts").unwrap();
+        let left: i64 = store
+            .conn
+            .query_row("SELECT count(*) FROM node_bodies", [], |r| r.get(0))
+            .unwrap();
+        assert_eq!(left, 0);
     }
 
     // -- node without optional fields -------------------------------------
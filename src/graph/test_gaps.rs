@@ -0,0 +1,275 @@
+//! Complex code that no test reaches.
+//!
+//! A function or method is covered when a test (a node flagged `is_test`
+//! at index time) reaches it through `calls` or `references` edges within
+//! a few hops. Uncovered non-test functions at or above a cyclomatic
+//! threshold are the gaps, ranked by PageRank × cyclomatic complexity so
+//! that central, branchy code comes first.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use crate::error::Result;
+use crate::graph::complexity::{calculate_all_complexities, ComplexityResult};
+use crate::graph::ranking::GraphRanking;
+use crate::graph::store::GraphStore;
+
+/// Options for [`find_test_gaps`].
+#[derive(Debug, Clone)]
+pub struct TestGapOptions {
+    /// Minimum cyclomatic complexity for a function to count.
+    pub min_complexity: u32,
+    /// Only report functions in files under this path prefix.
+    pub path_prefix: Option<String>,
+    /// How many edges a test may follow to reach a function.
+    pub max_depth: u32,
+}
+
+impl Default for TestGapOptions {
+    fn default() -> Self {
+        Self {
+            min_complexity: 5,
+            path_prefix: None,
+            max_depth: 3,
+        }
+    }
+}
+
+/// An untested function.
+#[derive(Debug, Clone)]
+pub struct TestGap {
+    pub complexity: ComplexityResult,
+    pub start_line: u32,
+    /// PageRank relative to the highest-ranked node, in `0.0..=1.0`.
+    pub page_rank: f64,
+    /// `page_rank × cyclomatic`.
+    pub score: f64,
+}
+
+/// The gaps and the counts they were picked from.
+#[derive(Debug, Clone, Default)]
+pub struct TestGapReport {
+    /// Highest score first.
+    pub gaps: Vec<TestGap>,
+    /// Non-test functions at or above the threshold.
+    pub candidates: usize,
+    /// Candidates reached by a test.
+    pub covered: usize,
+}
+
+const FUNCTIONS_SQL: &str =
+    "SELECT id, start_line, is_test FROM nodes WHERE type IN ('function', 'method')";
+const TEST_IDS_SQL: &str = "SELECT id FROM nodes WHERE is_test = 1";
+const COVERAGE_EDGES_SQL: &str =
+    "SELECT source_id, target_id FROM edges WHERE type IN ('calls', 'references')";
+const STORED_RANK_SQL: &str = "SELECT node_id, page_rank FROM node_rank";
+
+/// Complex non-test functions that no test reaches, highest score first.
+pub fn find_test_gaps(store: &GraphStore, opts: &TestGapOptions) -> Result<TestGapReport> {
+    let conn = &store.conn;
+    let functions: HashMap<String, (u32, bool)> = {
+        let mut stmt = conn.prepare_cached(FUNCTIONS_SQL)?;
+        let rows = stmt.query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, (row.get(1)?, row.get(2)?)))
+        })?;
+        rows.collect::<std::result::Result<_, _>>()?
+    };
+
+    let candidates: Vec<(ComplexityResult, u32)> = calculate_all_complexities(conn)
+        .into_iter()
+        .filter(|c| c.cyclomatic >= opts.min_complexity)
+        .filter(|c| {
+            opts.path_prefix
+                .as_deref()
+                .is_none_or(|p| c.file_path.starts_with(p))
+        })
+        .filter_map(|c| match functions.get(&c.node_id) {
+            Some(&(line, false)) => Some((c, line)),
+            _ => None,
+        })
+        .collect();
+    if candidates.is_empty() {
+        return Ok(TestGapReport::default());
+    }
+
+    let reached = reached_by_tests(store, opts.max_depth)?;
+    let ranks = page_ranks(store)?;
+    let max_rank = ranks.values().copied().fold(0.0_f64, f64::max);
+
+    let mut report = TestGapReport {
+        candidates: candidates.len(),
+        ..TestGapReport::default()
+    };
+    for (complexity, start_line) in candidates {
+        if reached.contains(&complexity.node_id) {
+            report.covered += 1;
+            continue;
+        }
+        let page_rank = match ranks.get(&complexity.node_id) {
+            Some(r) if max_rank > 0.0 => r / max_rank,
+            _ => 0.0,
+        };
+        report.gaps.push(TestGap {
+            score: page_rank * f64::from(complexity.cyclomatic),
+            complexity,
+            start_line,
+            page_rank,
+        });
+    }
+    report.gaps.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then(b.complexity.cyclomatic.cmp(&a.complexity.cyclomatic))
+            .then_with(|| a.complexity.file_path.cmp(&b.complexity.file_path))
+            .then(a.start_line.cmp(&b.start_line))
+    });
+    Ok(report)
+}
+
+/// Nodes reachable from a test within `max_depth` coverage edges.
+fn reached_by_tests(store: &GraphStore, max_depth: u32) -> Result<HashSet<String>> {
+    let conn = &store.conn;
+    let tests: Vec<String> = {
+        let mut stmt = conn.prepare_cached(TEST_IDS_SQL)?;
+        let rows = stmt.query_map([], |row| row.get(0))?;
+        rows.collect::<std::result::Result<_, _>>()?
+    };
+    let mut out_links: HashMap<String, Vec<String>> = HashMap::new();
+    {
+        let mut stmt = conn.prepare_cached(COVERAGE_EDGES_SQL)?;
+        let rows = stmt.query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })?;
+        for row in rows {
+            let (source, target) = row?;
+            out_links.entry(source).or_default().push(target);
+        }
+    }
+
+    let mut reached: HashSet<String> = HashSet::new();
+    let mut queue: VecDeque<(String, u32)> = tests.into_iter().map(|t| (t, 0)).collect();
+    while let Some((id, depth)) = queue.pop_front() {
+        if depth >= max_depth {
+            continue;
+        }
+        for target in out_links.get(&id).into_iter().flatten() {
+            if reached.insert(target.clone()) {
+                queue.push_back((target.clone(), depth + 1));
+            }
+        }
+    }
+    Ok(reached)
+}
+
+/// The PageRank stored at index time, or a fresh one if none is stored.
+pub(crate) fn page_ranks(store: &GraphStore) -> Result<HashMap<String, f64>> {
+    let stored: HashMap<String, f64> = {
+        let mut stmt = store.conn.prepare_cached(STORED_RANK_SQL)?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        rows.collect::<std::result::Result<_, _>>()?
+    };
+    if !stored.is_empty() {
+        return Ok(stored);
+    }
+    Ok(GraphRanking::new(store)
+        .compute_page_rank(0.85, 100)
+        .into_iter()
+        .map(|r| (r.node_id, r.score))
+        .collect())
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{CodeEdge, CodeNode, EdgeKind, Language, NodeKind};
+
+    const BRANCHY: &str = "fn f(x: i32) {\n    if x > 0 { a() } else if x < 0 { b() }\n    \
+                           for i in 0..x { if i == 2 { c() } }\n    while x > 9 { d() }\n}";
+
+    fn function(id: &str, file: &str, body: &str) -> CodeNode {
+        CodeNode {
+            id: id.to_string(),
+            name: id.to_string(),
+            qualified_name: None,
+            kind: NodeKind::Function,
+            file_path: file.to_string(),
+            start_line: 1,
+            end_line: 5,
+            start_column: 0,
+            end_column: 0,
+            language: Language::Rust,
+            body: Some(body.to_string()),
+            documentation: None,
+            exported: None,
+        }
+    }
+
+    fn call(source: &str, target: &str) -> CodeEdge {
+        CodeEdge {
+            source: source.to_string(),
+            target: target.to_string(),
+            kind: EdgeKind::Calls,
+            file_path: String::new(),
+            line: 1,
+            metadata: None,
+        }
+    }
+
+    #[test]
+    fn reports_uncovered_complex_functions_by_rank() {
+        let store = GraphStore::from_connection(
+            crate::db::schema::initialize_database(":memory:").unwrap(),
+        );
+        store
+            .upsert_nodes(&[
+                function("parse", "src/parse.rs", BRANCHY),
+                function("validate", "src/parse.rs", BRANCHY),
+                function("render", "src/render.rs", BRANCHY),
+                function("covered", "src/render.rs", BRANCHY),
+                function("helper", "src/render.rs", BRANCHY),
+                function("simple", "src/render.rs", "fn simple() {}"),
+                function("test_render", "tests/render.rs", BRANCHY),
+            ])
+            .unwrap();
+        store
+            .upsert_edges(&[
+                call("test_render", "covered"),
+                call("covered", "helper"),
+                // `validate` is called from two places, so it outranks
+                // `render`.
+                call("parse", "validate"),
+                call("render", "validate"),
+            ])
+            .unwrap();
+
+        let report = find_test_gaps(&store, &TestGapOptions::default()).unwrap();
+        let gaps: Vec<&str> = report
+            .gaps
+            .iter()
+            .map(|g| g.complexity.name.as_str())
+            .collect();
+        assert_eq!(gaps[0], "validate");
+        assert_eq!(gaps.len(), 3, "{gaps:?}");
+        assert!(!gaps.contains(&"covered") && !gaps.contains(&"helper"));
+        assert_eq!(report.candidates, 5);
+        assert_eq!(report.covered, 2);
+
+        // Direct references only: `helper` becomes a gap.
+        let opts = TestGapOptions {
+            max_depth: 1,
+            path_prefix: Some("src/render".to_string()),
+            ..TestGapOptions::default()
+        };
+        let report = find_test_gaps(&store, &opts).unwrap();
+        let mut gaps: Vec<&str> = report
+            .gaps
+            .iter()
+            .map(|g| g.complexity.name.as_str())
+            .collect();
+        gaps.sort_unstable();
+        assert_eq!(gaps, vec!["helper", "render"]);
+    }
+}
@@ -5,12 +5,15 @@
 //! Tarjan's SCC algorithm implemented in Rust (not SQL), matching the
 //! original design decision.
 
+use std::collections::hash_map::Entry;
 use std::collections::{HashMap, HashSet, VecDeque};
 
 use rusqlite::params;
 
+use crate::db::bodies::select_nodes;
 use crate::db::converters::{row_to_code_edge, row_to_code_node};
 use crate::error::Result;
+use crate::graph::adjacency::{Adjacency, Direction};
 use crate::graph::store::GraphStore;
 use crate::types::{CodeEdge, CodeNode};
 
@@ -32,6 +35,35 @@ pub struct CycleInfo {
     pub size: usize,
 }
 
+/// A recursion cycle in the call graph: one function calling itself, or a
+/// group of functions calling each other.
+#[derive(Debug, Clone)]
+pub struct RecursionInfo {
+    pub node_ids: Vec<String>,
+    /// A single self-recursive function rather than mutual recursion.
+    pub direct: bool,
+    /// Calls in the shortest round trip through the cycle.
+    pub cycle_length: usize,
+    /// Longest chain of distinct members one round of recursion can stack.
+    /// A lower bound when the cycle is too large to search exhaustively.
+    pub max_depth_hint: usize,
+    /// Longest acyclic call chain leading into the cycle.
+    pub entry_depth: usize,
+    /// Functions outside the cycle that call into it.
+    pub entry_points: Vec<String>,
+    /// The calls from outside the cycle into it, by file and line.
+    pub entry_edges: Vec<EntryEdge>,
+}
+
+/// A call from outside a recursion cycle into one of its members.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EntryEdge {
+    pub caller: String,
+    pub callee: String,
+    pub file_path: String,
+    pub line: u32,
+}
+
 /// A bidirectional subgraph around a focal node.
 #[derive(Debug, Clone)]
 pub struct Neighborhood {
@@ -59,9 +91,10 @@ WITH RECURSIVE deps(id, depth, path) AS (
     WHERE d.depth < ?2
       AND instr(d.path, e.target_id) = 0
 )
-SELECT DISTINCT n.*, d.depth
+SELECT DISTINCT n.*, b.encoding AS body_encoding, b.body AS body_data, d.depth
 FROM deps d
 JOIN nodes n ON n.id = d.id
+LEFT JOIN node_bodies b ON b.node_id = n.id
 ORDER BY d.depth ASC, n.name ASC";
 
 const FIND_CALLEES_SQL: &str = "\
@@ -80,9 +113,10 @@ WITH RECURSIVE callees(id, depth, path) AS (
     WHERE c.depth < ?2
       AND instr(c.path, e.target_id) = 0
 )
-SELECT DISTINCT n.*, c.depth
+SELECT DISTINCT n.*, b.encoding AS body_encoding, b.body AS body_data, c.depth
 FROM callees c
 JOIN nodes n ON n.id = c.id
+LEFT JOIN node_bodies b ON b.node_id = n.id
 ORDER BY c.depth ASC, n.name ASC";
 
 const FIND_CALLERS_SQL: &str = "\
@@ -101,9 +135,10 @@ WITH RECURSIVE callers(id, depth, path) AS (
     WHERE c.depth < ?2
       AND instr(c.path, e.source_id) = 0
 )
-SELECT DISTINCT n.*, c.depth
+SELECT DISTINCT n.*, b.encoding AS body_encoding, b.body AS body_data, c.depth
 FROM callers c
 JOIN nodes n ON n.id = c.id
+LEFT JOIN node_bodies b ON b.node_id = n.id
 ORDER BY c.depth ASC, n.name ASC";
 
 const FIND_TESTS_SQL: &str = "\
@@ -120,9 +155,10 @@ WITH RECURSIVE callers(id, depth, path) AS (
     WHERE c.depth < 5
       AND instr(c.path, e.source_id) = 0
 )
-SELECT DISTINCT n.*
+SELECT DISTINCT n.*, b.encoding AS body_encoding, b.body AS body_data
 FROM callers c
 JOIN nodes n ON n.id = c.id
+LEFT JOIN node_bodies b ON b.node_id = n.id
 WHERE (
     n.file_path LIKE '%test%'
     OR n.file_path LIKE '%spec%'
@@ -151,8 +187,9 @@ WITH RECURSIVE
         JOIN edges e ON e.target_id = i.id
         WHERE i.depth < ?6 AND instr(i.path, e.source_id) = 0
     )
-SELECT DISTINCT n.*
+SELECT DISTINCT n.*, b.encoding AS body_encoding, b.body AS body_data
 FROM nodes n
+LEFT JOIN node_bodies b ON b.node_id = n.id
 WHERE n.id IN (SELECT id FROM outgoing UNION SELECT id FROM incoming)
 ORDER BY n.name ASC";
 
@@ -181,6 +218,10 @@ impl<'a> GraphTraversal<'a> {
     /// Find all dependencies (outgoing edges) from a node, up to `max_depth`.
     /// Follows: calls, imports, references, extends, implements.
     pub fn find_dependencies(&self, node_id: &str, max_depth: u32) -> Result<Vec<NodeWithDepth>> {
+        if let Some(adj) = self.store.adjacency()? {
+            // The CTE always returns direct dependencies, even at depth 0.
+            return self.walk(&adj, node_id, Direction::Outgoing, None, max_depth.max(1));
+        }
         let mut stmt = self.store.conn.prepare_cached(FIND_DEPENDENCIES_SQL)?;
         let rows = stmt.query_and_then(params![node_id, max_depth], |row| {
             let node = row_to_code_node(row)?;
@@ -201,6 +242,9 @@ impl<'a> GraphTraversal<'a> {
         if max_depth == 0 {
             return Ok(Vec::new());
         }
+        if let Some(adj) = self.store.adjacency()? {
+            return self.walk(&adj, node_id, Direction::Outgoing, Some("calls"), max_depth);
+        }
         let mut stmt = self.store.conn.prepare_cached(FIND_CALLEES_SQL)?;
         let rows = stmt.query_and_then(params![node_id, max_depth], |row| {
             let node = row_to_code_node(row)?;
@@ -220,6 +264,9 @@ impl<'a> GraphTraversal<'a> {
         if max_depth == 0 {
             return Ok(Vec::new());
         }
+        if let Some(adj) = self.store.adjacency()? {
+            return self.walk(&adj, node_id, Direction::Incoming, Some("calls"), max_depth);
+        }
         let mut stmt = self.store.conn.prepare_cached(FIND_CALLERS_SQL)?;
         let rows = stmt.query_and_then(params![node_id, max_depth], |row| {
             let node = row_to_code_node(row)?;
@@ -289,93 +336,7 @@ impl<'a> GraphTraversal<'a> {
             all_nodes.insert(target.clone());
             adj.entry(source.clone()).or_default().push(target.clone());
         }
-
-        // Tarjan's strongly connected components (iterative to avoid stack
-        // overflow on deep graphs — the TS version uses recursion, but Rust's
-        // default stack is smaller).
-        let mut index_counter: u32 = 0;
-        let mut node_index: HashMap<String, u32> = HashMap::new();
-        let mut node_lowlink: HashMap<String, u32> = HashMap::new();
-        let mut on_stack: HashSet<String> = HashSet::new();
-        let mut stack: Vec<String> = Vec::new();
-        let mut sccs: Vec<Vec<String>> = Vec::new();
-
-        // Recursive inner function — using an explicit closure isn't
-        // ergonomic with mutable borrows in Rust, so we use a helper
-        // function with all state passed by reference.
-        #[allow(clippy::too_many_arguments)]
-        fn strong_connect(
-            v: &str,
-            adj: &HashMap<String, Vec<String>>,
-            index_counter: &mut u32,
-            node_index: &mut HashMap<String, u32>,
-            node_lowlink: &mut HashMap<String, u32>,
-            on_stack: &mut HashSet<String>,
-            stack: &mut Vec<String>,
-            sccs: &mut Vec<Vec<String>>,
-        ) {
-            node_index.insert(v.to_string(), *index_counter);
-            node_lowlink.insert(v.to_string(), *index_counter);
-            *index_counter += 1;
-            stack.push(v.to_string());
-            on_stack.insert(v.to_string());
-
-            if let Some(neighbors) = adj.get(v) {
-                for w in neighbors {
-                    if !node_index.contains_key(w.as_str()) {
-                        strong_connect(
-                            w,
-                            adj,
-                            index_counter,
-                            node_index,
-                            node_lowlink,
-                            on_stack,
-                            stack,
-                            sccs,
-                        );
-                        let w_low = *node_lowlink.get(w.as_str()).unwrap();
-                        let v_low = node_lowlink.get_mut(v).unwrap();
-                        if w_low < *v_low {
-                            *v_low = w_low;
-                        }
-                    } else if on_stack.contains(w.as_str()) {
-                        let w_idx = *node_index.get(w.as_str()).unwrap();
-                        let v_low = node_lowlink.get_mut(v).unwrap();
-                        if w_idx < *v_low {
-                            *v_low = w_idx;
-                        }
-                    }
-                }
-            }
-
-            if node_lowlink.get(v) == node_index.get(v) {
-                let mut scc: Vec<String> = Vec::new();
-                loop {
-                    let w = stack.pop().unwrap();
-                    on_stack.remove(&w);
-                    scc.push(w.clone());
-                    if w == v {
-                        break;
-                    }
-                }
-                sccs.push(scc);
-            }
-        }
-
-        for node in &all_nodes {
-            if !node_index.contains_key(node.as_str()) {
-                strong_connect(
-                    node,
-                    &adj,
-                    &mut index_counter,
-                    &mut node_index,
-                    &mut node_lowlink,
-                    &mut on_stack,
-                    &mut stack,
-                    &mut sccs,
-                );
-            }
-        }
+        let sccs = strongly_connected_components(&adj, &all_nodes);
 
         // Only return SCCs with 2+ nodes (actual cycles)
         Ok(sccs
@@ -388,6 +349,114 @@ impl<'a> GraphTraversal<'a> {
             .collect())
     }
 
+    // -------------------------------------------------------------------
+    // find_recursion
+    // -------------------------------------------------------------------
+
+    /// Find direct and mutual recursion in the call graph: self-calls plus
+    /// strongly connected components of `calls` edges. Largest cycles first.
+    pub fn find_recursion(&self) -> Result<Vec<RecursionInfo>> {
+        let mut stmt = self.store.conn.prepare_cached(
+            "SELECT DISTINCT source_id, target_id FROM edges WHERE type = 'calls'",
+        )?;
+        let edge_pairs: Vec<(String, String)> = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        let mut adj: HashMap<String, Vec<String>> = HashMap::new();
+        let mut callers: HashMap<&str, Vec<&str>> = HashMap::new();
+        let mut all_nodes: HashSet<String> = HashSet::new();
+        let mut self_calls: HashSet<&str> = HashSet::new();
+        for (source, target) in &edge_pairs {
+            all_nodes.insert(source.clone());
+            all_nodes.insert(target.clone());
+            adj.entry(source.clone()).or_default().push(target.clone());
+            callers.entry(target).or_default().push(source);
+            if source == target {
+                self_calls.insert(source);
+            }
+        }
+        let sccs = strongly_connected_components(&adj, &all_nodes);
+        let component: HashMap<&str, usize> = sccs
+            .iter()
+            .enumerate()
+            .flat_map(|(i, scc)| scc.iter().map(move |id| (id.as_str(), i)))
+            .collect();
+
+        // Components come out callees-first, so walking them backwards
+        // visits every caller before its callees.
+        let mut entry_depth = vec![0usize; sccs.len()];
+        for i in (0..sccs.len()).rev() {
+            for v in &sccs[i] {
+                for w in adj.get(v).into_iter().flatten() {
+                    let j = component[w.as_str()];
+                    if j != i {
+                        entry_depth[j] = entry_depth[j].max(entry_depth[i] + 1);
+                    }
+                }
+            }
+        }
+
+        let mut cycles: Vec<RecursionInfo> = Vec::new();
+        for (i, scc) in sccs.iter().enumerate() {
+            let direct = scc.len() == 1;
+            if direct && !self_calls.contains(scc[0].as_str()) {
+                continue;
+            }
+            let members: HashSet<&str> = scc.iter().map(String::as_str).collect();
+            let mut entry_points: Vec<String> = scc
+                .iter()
+                .flat_map(|v| callers.get(v.as_str()).into_iter().flatten())
+                .filter(|c| !members.contains(*c))
+                .map(|c| c.to_string())
+                .collect::<HashSet<_>>()
+                .into_iter()
+                .collect();
+            entry_points.sort();
+            let mut entry_edges: Vec<EntryEdge> = Vec::new();
+            for member in scc {
+                for edge in self.store.get_in_edges(member, Some("calls"))? {
+                    if !members.contains(edge.source.as_str()) {
+                        entry_edges.push(EntryEdge {
+                            caller: edge.source,
+                            callee: edge.target,
+                            file_path: edge.file_path,
+                            line: edge.line,
+                        });
+                    }
+                }
+            }
+            entry_edges.sort_by(|a, b| (&a.file_path, a.line).cmp(&(&b.file_path, b.line)));
+            entry_edges.dedup();
+            let (cycle_length, max_depth_hint) = if direct {
+                (1, 1)
+            } else {
+                (
+                    shortest_cycle(&adj, &members),
+                    longest_simple_path(&adj, &members),
+                )
+            };
+            cycles.push(RecursionInfo {
+                node_ids: scc.clone(),
+                direct,
+                cycle_length,
+                max_depth_hint,
+                entry_depth: entry_depth[i],
+                entry_points,
+                entry_edges,
+            });
+        }
+        cycles.sort_by(|a, b| {
+            b.node_ids
+                .len()
+                .cmp(&a.node_ids.len())
+                .then(b.entry_depth.cmp(&a.entry_depth))
+                .then(a.node_ids.cmp(&b.node_ids))
+        });
+        Ok(cycles)
+    }
+
     // -------------------------------------------------------------------
     // get_neighborhood
     // -------------------------------------------------------------------
@@ -459,7 +528,7 @@ impl<'a> GraphTraversal<'a> {
         if from_id == to_id {
             // Path from a node to itself: return just that node.
             let node = self.store.conn.query_row(
-                "SELECT * FROM nodes WHERE id = ?1",
+                select_nodes!("WHERE n.id = ?1"),
                 params![from_id],
                 row_to_code_node,
             );
@@ -470,6 +539,10 @@ impl<'a> GraphTraversal<'a> {
             };
         }
 
+        if let Some(adj) = self.store.adjacency()? {
+            return self.call_path_in(&adj, from_id, to_id, max_depth);
+        }
+
         // BFS: queue holds (node_id, path_of_ids_so_far).
         let mut queue: VecDeque<(String, Vec<String>)> = VecDeque::new();
         let mut visited: HashSet<String> = HashSet::new();
@@ -504,7 +577,7 @@ impl<'a> GraphTraversal<'a> {
                     let mut nodes = Vec::with_capacity(full_path.len());
                     for id in &full_path {
                         let node = self.store.conn.query_row(
-                            "SELECT * FROM nodes WHERE id = ?1",
+                            select_nodes!("WHERE n.id = ?1"),
                             params![id],
                             row_to_code_node,
                         )?;
@@ -524,6 +597,253 @@ impl<'a> GraphTraversal<'a> {
 
         Ok(None)
     }
+
+    // -------------------------------------------------------------------
+    // Adjacency-backed variants
+    // -------------------------------------------------------------------
+
+    /// Walk the cached adjacency and load the reached nodes, ordered like
+    /// the CTE results (depth, then name). Each node is reported once, at
+    /// its shortest distance.
+    fn walk(
+        &self,
+        adj: &Adjacency,
+        start: &str,
+        direction: Direction,
+        kind: Option<&str>,
+        max_depth: u32,
+    ) -> Result<Vec<NodeWithDepth>> {
+        let mut out = Vec::new();
+        for (idx, depth) in adj.walk(start, direction, kind, max_depth) {
+            if let Some(node) = self.store.get_node(adj.id(idx))? {
+                out.push(NodeWithDepth { node, depth });
+            }
+        }
+        out.sort_by(|a, b| {
+            a.depth
+                .cmp(&b.depth)
+                .then_with(|| a.node.name.cmp(&b.node.name))
+        });
+        Ok(out)
+    }
+
+    /// [`find_call_path`](Self::find_call_path) over the cached adjacency;
+    /// explores neighbours in the same order as the SQL version.
+    fn call_path_in(
+        &self,
+        adj: &Adjacency,
+        from_id: &str,
+        to_id: &str,
+        max_depth: u32,
+    ) -> Result<Option<Vec<CodeNode>>> {
+        let (Some(from), Some(to)) = (adj.index_of(from_id), adj.index_of(to_id)) else {
+            return Ok(None);
+        };
+        let mut parent: HashMap<u32, u32> = HashMap::new();
+        let mut depth_of: HashMap<u32, u32> = HashMap::from([(from, 0)]);
+        let mut queue = VecDeque::from([from]);
+
+        while let Some(current) = queue.pop_front() {
+            let depth = depth_of[&current];
+            if depth >= max_depth {
+                continue;
+            }
+            for neighbor in adj.neighbors(current, Direction::Outgoing, Some("calls")) {
+                if neighbor == to {
+                    let mut path = vec![to, current];
+                    let mut at = current;
+                    while let Some(&p) = parent.get(&at) {
+                        path.push(p);
+                        at = p;
+                    }
+                    path.reverse();
+                    let mut nodes = Vec::with_capacity(path.len());
+                    for idx in path {
+                        match self.store.get_node(adj.id(idx))? {
+                            Some(node) => nodes.push(node),
+                            None => return Ok(None),
+                        }
+                    }
+                    return Ok(Some(nodes));
+                }
+                if let Entry::Vacant(slot) = depth_of.entry(neighbor) {
+                    slot.insert(depth + 1);
+                    parent.insert(neighbor, current);
+                    queue.push_back(neighbor);
+                }
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// Members searched from when measuring a cycle; bounds the cost on very
+/// large components.
+const MAX_CYCLE_STARTS: usize = 64;
+
+/// Step budget for the longest-simple-path search, which is exponential in
+/// the worst case.
+const MAX_PATH_STEPS: usize = 20_000;
+
+/// Length of the shortest cycle through `members`, following only edges
+/// that stay inside the component.
+fn shortest_cycle(adj: &HashMap<String, Vec<String>>, members: &HashSet<&str>) -> usize {
+    let mut best = usize::MAX;
+    for &start in members.iter().take(MAX_CYCLE_STARTS) {
+        let mut dist: HashMap<&str, usize> = HashMap::from([(start, 0)]);
+        let mut queue: VecDeque<&str> = VecDeque::from([start]);
+        'bfs: while let Some(v) = queue.pop_front() {
+            let d = dist[v];
+            for w in adj.get(v).into_iter().flatten() {
+                let w = w.as_str();
+                if w == start {
+                    best = best.min(d + 1);
+                    break 'bfs;
+                }
+                if members.contains(w) && !dist.contains_key(w) {
+                    dist.insert(w, d + 1);
+                    queue.push_back(w);
+                }
+            }
+        }
+    }
+    if best == usize::MAX {
+        members.len()
+    } else {
+        best
+    }
+}
+
+/// Number of members on the longest simple call chain inside the
+/// component, searched depth-first within [`MAX_PATH_STEPS`].
+fn longest_simple_path(adj: &HashMap<String, Vec<String>>, members: &HashSet<&str>) -> usize {
+    fn dfs<'a>(
+        v: &'a str,
+        adj: &'a HashMap<String, Vec<String>>,
+        members: &HashSet<&str>,
+        on_path: &mut HashSet<&'a str>,
+        steps: &mut usize,
+        best: &mut usize,
+    ) {
+        *best = (*best).max(on_path.len());
+        if *best == members.len() || *steps >= MAX_PATH_STEPS {
+            return;
+        }
+        for w in adj.get(v).into_iter().flatten() {
+            let w = w.as_str();
+            if members.contains(w) && on_path.insert(w) {
+                *steps += 1;
+                dfs(w, adj, members, on_path, steps, best);
+                on_path.remove(w);
+            }
+        }
+    }
+
+    let mut best = 0;
+    let mut steps = 0;
+    for &start in members.iter().take(MAX_CYCLE_STARTS) {
+        let Some((start, _)) = adj.get_key_value(start) else {
+            continue;
+        };
+        let mut on_path = HashSet::from([start.as_str()]);
+        dfs(start, adj, members, &mut on_path, &mut steps, &mut best);
+    }
+    best
+}
+
+/// Tarjan's strongly connected components over an adjacency list, in
+/// reverse topological order (a component comes before its callers).
+fn strongly_connected_components(
+    adj: &HashMap<String, Vec<String>>,
+    all_nodes: &HashSet<String>,
+) -> Vec<Vec<String>> {
+    // Tarjan's strongly connected components (iterative to avoid stack
+    // overflow on deep graphs — the TS version uses recursion, but Rust's
+    // default stack is smaller).
+    let mut index_counter: u32 = 0;
+    let mut node_index: HashMap<String, u32> = HashMap::new();
+    let mut node_lowlink: HashMap<String, u32> = HashMap::new();
+    let mut on_stack: HashSet<String> = HashSet::new();
+    let mut stack: Vec<String> = Vec::new();
+    let mut sccs: Vec<Vec<String>> = Vec::new();
+
+    // Recursive inner function — using an explicit closure isn't
+    // ergonomic with mutable borrows in Rust, so we use a helper
+    // function with all state passed by reference.
+    #[allow(clippy::too_many_arguments)]
+    fn strong_connect(
+        v: &str,
+        adj: &HashMap<String, Vec<String>>,
+        index_counter: &mut u32,
+        node_index: &mut HashMap<String, u32>,
+        node_lowlink: &mut HashMap<String, u32>,
+        on_stack: &mut HashSet<String>,
+        stack: &mut Vec<String>,
+        sccs: &mut Vec<Vec<String>>,
+    ) {
+        node_index.insert(v.to_string(), *index_counter);
+        node_lowlink.insert(v.to_string(), *index_counter);
+        *index_counter += 1;
+        stack.push(v.to_string());
+        on_stack.insert(v.to_string());
+
+        if let Some(neighbors) = adj.get(v) {
+            for w in neighbors {
+                if !node_index.contains_key(w.as_str()) {
+                    strong_connect(
+                        w,
+                        adj,
+                        index_counter,
+                        node_index,
+                        node_lowlink,
+                        on_stack,
+                        stack,
+                        sccs,
+                    );
+                    let w_low = *node_lowlink.get(w.as_str()).unwrap();
+                    let v_low = node_lowlink.get_mut(v).unwrap();
+                    if w_low < *v_low {
+                        *v_low = w_low;
+                    }
+                } else if on_stack.contains(w.as_str()) {
+                    let w_idx = *node_index.get(w.as_str()).unwrap();
+                    let v_low = node_lowlink.get_mut(v).unwrap();
+                    if w_idx < *v_low {
+                        *v_low = w_idx;
+                    }
+                }
+            }
+        }
+
+        if node_lowlink.get(v) == node_index.get(v) {
+            let mut scc: Vec<String> = Vec::new();
+            loop {
+                let w = stack.pop().unwrap();
+                on_stack.remove(&w);
+                scc.push(w.clone());
+                if w == v {
+                    break;
+                }
+            }
+            sccs.push(scc);
+        }
+    }
+
+    for node in all_nodes {
+        if !node_index.contains_key(node.as_str()) {
+            strong_connect(
+                node,
+                adj,
+                &mut index_counter,
+                &mut node_index,
+                &mut node_lowlink,
+                &mut on_stack,
+                &mut stack,
+                &mut sccs,
+            );
+        }
+    }
+    sccs
 }
 
 // ---------------------------------------------------------------------------
@@ -1087,6 +1407,41 @@ mod tests {
             .unwrap();
     }
 
+    #[test]
+    fn adjacency_cache_matches_sql_traversals() {
+        let store = setup();
+        seed_diamond(&store);
+        store
+            .upsert_edge(&make_edge("d", "a", EdgeKind::Imports, "src/d.ts", 1))
+            .unwrap();
+        let summarize = |store: &GraphStore| {
+            let t = GraphTraversal::new(store);
+            let ids = |v: Vec<NodeWithDepth>| {
+                v.into_iter()
+                    .map(|n| (n.node.id, n.depth))
+                    .collect::<Vec<_>>()
+            };
+            (
+                ids(t.find_dependencies("a", 5).unwrap()),
+                ids(t.find_callees("a", 1).unwrap()),
+                ids(t.find_callers("d", 5).unwrap()),
+                t.find_call_path("a", "d", 5)
+                    .unwrap()
+                    .map(|p| p.into_iter().map(|n| n.id).collect::<Vec<_>>()),
+                t.find_call_path("d", "a", 5).unwrap().is_none(),
+            )
+        };
+
+        let via_sql = summarize(&store);
+        store.enable_adjacency_cache();
+        let via_adjacency = summarize(&store);
+        assert_eq!(via_sql, via_adjacency);
+        assert_eq!(
+            via_adjacency.3,
+            Some(vec!["a".to_string(), "b".to_string(), "d".to_string()])
+        );
+    }
+
     #[test]
     fn diamond_dependencies_from_root() {
         let store = setup();
@@ -1350,6 +1705,65 @@ mod tests {
         assert!(cycles.is_empty() || cycles.iter().all(|c| c.size >= 2));
     }
 
+    #[test]
+    fn find_recursion_reports_direct_and_mutual_cycles() {
+        let store = setup();
+        store
+            .upsert_nodes(&[
+                make_node("main", "main", "src/main.ts", NodeKind::Function, 1),
+                make_node("parse", "parse", "src/p.ts", NodeKind::Function, 1),
+                make_node("expr", "expr", "src/p.ts", NodeKind::Function, 10),
+                make_node("term", "term", "src/p.ts", NodeKind::Function, 20),
+                make_node("fact", "fact", "src/m.ts", NodeKind::Function, 1),
+                make_node("util", "util", "src/m.ts", NodeKind::Function, 10),
+            ])
+            .unwrap();
+        // main -> parse -> expr <-> term (and term -> term), main -> fact -> fact
+        store
+            .upsert_edges(&[
+                make_edge("main", "parse", EdgeKind::Calls, "src/main.ts", 2),
+                make_edge("parse", "expr", EdgeKind::Calls, "src/p.ts", 2),
+                make_edge("expr", "term", EdgeKind::Calls, "src/p.ts", 11),
+                make_edge("term", "expr", EdgeKind::Calls, "src/p.ts", 21),
+                make_edge("term", "term", EdgeKind::Calls, "src/p.ts", 22),
+                make_edge("main", "fact", EdgeKind::Calls, "src/main.ts", 3),
+                make_edge("fact", "fact", EdgeKind::Calls, "src/m.ts", 2),
+                make_edge("fact", "util", EdgeKind::Calls, "src/m.ts", 3),
+                // Imports never make recursion.
+                make_edge("util", "main", EdgeKind::Imports, "src/m.ts", 1),
+            ])
+            .unwrap();
+
+        let traversal = GraphTraversal::new(&store);
+        let cycles = traversal.find_recursion().unwrap();
+        assert_eq!(cycles.len(), 2);
+
+        let mutual = &cycles[0];
+        assert!(!mutual.direct);
+        let mut ids = mutual.node_ids.clone();
+        ids.sort();
+        assert_eq!(ids, vec!["expr", "term"]);
+        assert_eq!(mutual.cycle_length, 1, "term also calls itself");
+        assert_eq!(mutual.max_depth_hint, 2);
+        assert_eq!(mutual.entry_depth, 2);
+        assert_eq!(mutual.entry_points, vec!["parse"]);
+        assert_eq!(
+            mutual.entry_edges,
+            vec![EntryEdge {
+                caller: "parse".into(),
+                callee: "expr".into(),
+                file_path: "src/p.ts".into(),
+                line: 2,
+            }]
+        );
+
+        let direct = &cycles[1];
+        assert!(direct.direct);
+        assert_eq!(direct.node_ids, vec!["fact"]);
+        assert_eq!(direct.entry_depth, 1);
+        assert_eq!(direct.entry_points, vec!["main"]);
+    }
+
     #[test]
     fn detect_cycles_empty_graph() {
         let store = setup();
@@ -82,7 +82,7 @@ This project is indexed by CodeGraph. **Use CodeGraph MCP tools instead of Grep/
 
 **Deep Search:** `codegraph_deep_query` (cross-encoder re-ranked search for highest precision)
 **Structure & Analysis:** `codegraph_structure` (PageRank overview), `codegraph_impact` (blast radius), `codegraph_stats`, `codegraph_circular_imports`, `codegraph_project_tree`, `codegraph_export_map`, `codegraph_import_graph`, `codegraph_file`, `codegraph_diagram`, `codegraph_dead_code`, `codegraph_tests`, `codegraph_frameworks`, `codegraph_languages`
-**Git:** `codegraph_blame`, `codegraph_file_history`, `codegraph_recent_changes`, `codegraph_commit_diff`, `codegraph_symbol_history`, `codegraph_branch_info`, `codegraph_modified_files`, `codegraph_hotspots`, `codegraph_contributors`
+**Git:** `codegraph_blame`, `codegraph_file_history`, `codegraph_recent_changes`, `codegraph_commit_diff`, `codegraph_symbol_history`, `codegraph_branch_info`, `codegraph_modified_files`, `codegraph_hotspots`, `codegraph_contributors`, `codegraph_reviewers`
 **Security:** `codegraph_scan_security`, `codegraph_check_owasp`, `codegraph_check_cwe`, `codegraph_explain_vulnerability`, `codegraph_suggest_fix`, `codegraph_find_injections`, `codegraph_taint_sources`, `codegraph_security_summary`, `codegraph_trace_taint`
 **Data Flow:** `codegraph_find_path` (call path between functions), `codegraph_complexity`, `codegraph_data_flow`, `codegraph_dead_stores`, `codegraph_find_uninitialized`, `codegraph_reaching_defs`
 
@@ -327,7 +327,7 @@ mod tests {
             "codegraph_dead_code",
             "codegraph_frameworks",
             "codegraph_languages",
-            // Git (9)
+            // Git (10)
             "codegraph_blame",
             "codegraph_file_history",
             "codegraph_recent_changes",
@@ -337,6 +337,7 @@ mod tests {
             "codegraph_modified_files",
             "codegraph_hotspots",
             "codegraph_contributors",
+            "codegraph_reviewers",
             // Security (9)
             "codegraph_scan_security",
             "codegraph_check_owasp",
@@ -365,7 +366,7 @@ mod tests {
             "codegraph_find_uninitialized",
             "codegraph_reaching_defs",
         ];
-        assert_eq!(expected_tools.len(), 46, "should test all 46 tools");
+        assert_eq!(expected_tools.len(), 47, "should test all 47 tools");
         for tool in expected_tools {
             assert!(section.contains(tool), "missing tool: {tool}");
         }
@@ -1,9 +1,10 @@
 //! Git hook integration — installs a `post-commit` hook that triggers
-//! incremental re-indexing after every commit.
+//! incremental re-indexing after every commit, and optionally a
+//! `pre-commit` hook running the breaking-change API guard.
 //!
-//! The hook runs `codegraph index <project_dir>` in the background so
-//! it never slows down the commit workflow. Installation is additive: if a
-//! `post-commit` hook already exists, the codegraph line is appended.
+//! The post-commit hook runs `codegraph index <project_dir>` in the
+//! background so it never slows down the commit workflow. Installation is
+//! additive: if a hook already exists, the codegraph line is appended.
 
 use std::fs;
 #[cfg(unix)]
@@ -16,6 +17,9 @@ use crate::error::Result;
 /// our line without disturbing user-written hooks.
 const MARKER: &str = "# codegraph-auto-index";
 
+/// Marker for the `pre-commit` API guard line.
+const GUARD_MARKER: &str = "# codegraph-api-guard";
+
 /// Check whether `project_dir` is (or is inside) a git repository.
 pub fn is_git_repo(project_dir: &str) -> bool {
     Path::new(project_dir).join(".git").is_dir()
@@ -30,6 +34,45 @@ pub fn is_git_repo(project_dir: &str) -> bool {
 /// The hook invokes `codegraph index <project_dir>` with stderr redirected
 /// to `/dev/null` and backgrounded (`&`) so the commit returns immediately.
 pub fn install_git_post_commit_hook(project_dir: &str) -> Result<()> {
+    install_hook(
+        project_dir,
+        "post-commit",
+        MARKER,
+        &format!("codegraph index {project_dir} 2>/dev/null &"),
+    )
+}
+
+/// Remove the codegraph line from the `post-commit` hook.
+///
+/// If the hook contains only the shebang and our codegraph block, the file
+/// is deleted entirely. Otherwise only the codegraph lines are stripped.
+pub fn uninstall_git_post_commit_hook(project_dir: &str) -> Result<()> {
+    uninstall_hook(project_dir, "post-commit", MARKER, "codegraph index")
+}
+
+/// Install a `pre-commit` hook running `codegraph api-guard`, which fails
+/// the commit when a blocking directory has breaking API changes and no
+/// version bump note. Installation follows the `post-commit` rules.
+pub fn install_git_api_guard_hook(project_dir: &str) -> Result<()> {
+    install_hook(
+        project_dir,
+        "pre-commit",
+        GUARD_MARKER,
+        &format!("codegraph api-guard {project_dir} || exit 1"),
+    )
+}
+
+/// Remove the `codegraph api-guard` line from the `pre-commit` hook.
+pub fn uninstall_git_api_guard_hook(project_dir: &str) -> Result<()> {
+    uninstall_hook(
+        project_dir,
+        "pre-commit",
+        GUARD_MARKER,
+        "codegraph api-guard",
+    )
+}
+
+fn install_hook(project_dir: &str, hook: &str, marker: &str, command: &str) -> Result<()> {
     let root = Path::new(project_dir);
     let hooks_dir = root.join(".git").join("hooks");
 
@@ -42,15 +85,15 @@ pub fn install_git_post_commit_hook(project_dir: &str) -> Result<()> {
 
     fs::create_dir_all(&hooks_dir)?;
 
-    let hook_path = hooks_dir.join("post-commit");
-    let codegraph_line = format!("{MARKER}\ncodegraph index {project_dir} 2>/dev/null &");
+    let hook_path = hooks_dir.join(hook);
+    let codegraph_line = format!("{marker}\n{command}");
 
     if hook_path.exists() {
         let content = fs::read_to_string(&hook_path)?;
 
         // Already installed — nothing to do.
-        if content.contains(MARKER) {
-            tracing::info!("post-commit hook already installed.");
+        if content.contains(marker) {
+            tracing::info!("{hook} hook already installed.");
             return Ok(());
         }
 
@@ -65,26 +108,19 @@ pub fn install_git_post_commit_hook(project_dir: &str) -> Result<()> {
 
     #[cfg(unix)]
     fs::set_permissions(&hook_path, fs::Permissions::from_mode(0o755))?;
-    tracing::info!("Installed post-commit hook at {}", hook_path.display());
+    tracing::info!("Installed {hook} hook at {}", hook_path.display());
     Ok(())
 }
 
-/// Remove the codegraph line from the `post-commit` hook.
-///
-/// If the hook contains only the shebang and our codegraph block, the file
-/// is deleted entirely. Otherwise only the codegraph lines are stripped.
-pub fn uninstall_git_post_commit_hook(project_dir: &str) -> Result<()> {
-    let hook_path = Path::new(project_dir)
-        .join(".git")
-        .join("hooks")
-        .join("post-commit");
+fn uninstall_hook(project_dir: &str, hook: &str, marker: &str, command: &str) -> Result<()> {
+    let hook_path = Path::new(project_dir).join(".git").join("hooks").join(hook);
 
     if !hook_path.exists() {
         return Ok(());
     }
 
     let content = fs::read_to_string(&hook_path)?;
-    if !content.contains(MARKER) {
+    if !content.contains(marker) {
         // Our hook isn't here — nothing to remove.
         return Ok(());
     }
@@ -92,7 +128,7 @@ pub fn uninstall_git_post_commit_hook(project_dir: &str) -> Result<()> {
     // Remove our marker line and the command line that follows it.
     let filtered: Vec<&str> = content
         .lines()
-        .filter(|line| !line.contains(MARKER) && !line.contains("codegraph index"))
+        .filter(|line| !line.contains(marker) && !line.contains(command))
         .collect();
 
     // If only the shebang (or nothing) remains, delete the file.
@@ -103,11 +139,11 @@ pub fn uninstall_git_post_commit_hook(project_dir: &str) -> Result<()> {
 
     if meaningful.is_empty() {
         fs::remove_file(&hook_path)?;
-        tracing::info!("Removed post-commit hook (file deleted).");
+        tracing::info!("Removed {hook} hook (file deleted).");
     } else {
         let cleaned = filtered.join("\n");
         fs::write(&hook_path, format!("{}\n", cleaned.trim_end()))?;
-        tracing::info!("Removed codegraph line from post-commit hook.");
+        tracing::info!("Removed codegraph line from {hook} hook.");
     }
 
     Ok(())
@@ -416,4 +452,23 @@ mod tests {
         let content = fs::read_to_string(dir.join(".git/hooks/post-commit")).unwrap();
         assert!(content.contains("codegraph index"));
     }
+
+    #[test]
+    fn api_guard_hook_is_a_separate_pre_commit_hook() {
+        let tmp = TempDir::new().unwrap();
+        make_git_dir(&tmp);
+        let dir = tmp.path().to_str().unwrap();
+
+        install_git_post_commit_hook(dir).unwrap();
+        install_git_api_guard_hook(dir).unwrap();
+
+        let hook = tmp.path().join(".git/hooks/pre-commit");
+        let content = fs::read_to_string(&hook).unwrap();
+        assert!(content.contains(GUARD_MARKER));
+        assert!(content.contains(&format!("codegraph api-guard {dir} || exit 1")));
+
+        uninstall_git_api_guard_hook(dir).unwrap();
+        assert!(!hook.exists());
+        assert!(tmp.path().join(".git/hooks/post-commit").exists());
+    }
 }
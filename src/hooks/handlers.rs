@@ -56,6 +56,15 @@ fn ensure_db(cwd: &Path) -> Result<String, Box<dyn std::error::Error>> {
     Ok(db_path(cwd).to_string_lossy().to_string())
 }
 
+/// Indexing pipeline over `store` honouring the project config at `cwd`.
+fn pipeline<'a>(
+    store: &'a crate::graph::store::GraphStore,
+    cwd: &Path,
+) -> crate::indexer::IndexingPipeline<'a> {
+    let config = crate::config::loader::load_config(None, Some(cwd)).unwrap_or_default();
+    crate::indexer::IndexingPipeline::new(store).with_blame_metadata(config.git.blame_metadata())
+}
+
 /// Print a JSON value to stdout (the hook response channel).
 fn emit(value: serde_json::Value) {
     println!("{}", value);
@@ -96,7 +105,7 @@ pub fn handle_session_start() {
         };
 
         let store = crate::graph::store::GraphStore::from_connection(conn);
-        let pipeline = crate::indexer::IndexingPipeline::new(&store);
+        let pipeline = pipeline(&store, &cwd);
 
         let start = Instant::now();
         let options = crate::indexer::IndexOptions {
@@ -364,7 +373,7 @@ pub fn handle_post_edit() {
         };
 
         let store = crate::graph::store::GraphStore::from_connection(conn);
-        let pipeline = crate::indexer::IndexingPipeline::new(&store);
+        let pipeline = pipeline(&store, &cwd);
 
         let path = Path::new(file_path);
         match pipeline.index_file(path, &cwd) {
@@ -897,7 +906,7 @@ pub fn handle_task_completed() {
         };
 
         let store = crate::graph::store::GraphStore::from_connection(conn);
-        let pipeline = crate::indexer::IndexingPipeline::new(&store);
+        let pipeline = pipeline(&store, &cwd);
 
         // Quick incremental re-index to catch latest changes.
         let _ = pipeline.index_directory(&crate::indexer::IndexOptions {
@@ -906,7 +915,13 @@ pub fn handle_task_completed() {
         });
 
         // Check for dead code introduced.
-        let dead = crate::resolution::dead_code::find_dead_code(&store.conn, &[]);
+        let roots = crate::config::loader::load_config(None, Some(&cwd))
+            .unwrap_or_default()
+            .dead_code
+            .roots();
+        let dead =
+            crate::resolution::dead_code::find_unreachable_code(&store, &[], &roots, Some(&cwd))
+                .unwrap_or_default();
         let unresolved = store.get_unresolved_ref_count().unwrap_or(0);
 
         let mut issues = Vec::new();
@@ -976,7 +991,7 @@ pub fn handle_session_end() {
         };
 
         let store = crate::graph::store::GraphStore::from_connection(conn);
-        let pipeline = crate::indexer::IndexingPipeline::new(&store);
+        let pipeline = pipeline(&store, &cwd);
 
         // Final re-index to capture any last-second changes.
         let start = std::time::Instant::now();
@@ -401,15 +401,19 @@ fn write_global_claude_md_to(claude_md: &Path) -> Result<()> {
 
 use std::path::PathBuf;
 
-/// All 46 MCP tool names exposed by the CodeGraph server.
+/// All 84 MCP tool names exposed by the CodeGraph server.
 ///
 /// These correspond to the `async fn codegraph_*` methods in `src/mcp/server.rs`.
 /// The permission string format is `mcp__codegraph__<tool_name>`.
 const CODEGRAPH_TOOL_NAMES: &[&str] = &[
-    // Core (14) + Deep Search (1)
+    // Core (19) + Deep Search (2)
     "codegraph_query",
+    "codegraph_saved_query",
+    "codegraph_similar",
+    "codegraph_federated_query",
     "codegraph_search",
     "codegraph_deep_query",
+    "codegraph_deep_file_query",
     "codegraph_dependencies",
     "codegraph_callers",
     "codegraph_callees",
@@ -422,7 +426,9 @@ const CODEGRAPH_TOOL_NAMES: &[&str] = &[
     "codegraph_dead_code",
     "codegraph_frameworks",
     "codegraph_languages",
-    // Git (9)
+    "codegraph_complete",
+    "codegraph_examples",
+    // Git (12)
     "codegraph_blame",
     "codegraph_file_history",
     "codegraph_recent_changes",
@@ -432,7 +438,10 @@ const CODEGRAPH_TOOL_NAMES: &[&str] = &[
     "codegraph_modified_files",
     "codegraph_hotspots",
     "codegraph_contributors",
-    // Security (9)
+    "codegraph_reviewers",
+    "codegraph_search_commits",
+    "codegraph_diff_impact",
+    // Security (11)
     "codegraph_scan_security",
     "codegraph_check_owasp",
     "codegraph_check_cwe",
@@ -442,21 +451,53 @@ const CODEGRAPH_TOOL_NAMES: &[&str] = &[
     "codegraph_taint_sources",
     "codegraph_security_summary",
     "codegraph_trace_taint",
-    // Repository & Analysis (7)
+    "codegraph_audit_dependencies",
+    "codegraph_find_secrets",
+    // Repository & Analysis (29)
     "codegraph_stats",
+    "codegraph_usage",
     "codegraph_circular_imports",
+    "codegraph_architecture_check",
+    "codegraph_module_coupling",
     "codegraph_project_tree",
     "codegraph_find_references",
+    "codegraph_rename_impact",
     "codegraph_export_map",
+    "codegraph_api_surface",
+    "codegraph_summarize_module",
     "codegraph_import_graph",
     "codegraph_file",
-    // Call Graph & Data Flow (6)
+    "codegraph_config_usage",
+    "codegraph_diff_index",
+    "codegraph_field_usages",
+    "codegraph_wire_contract",
+    "codegraph_pin_snapshot",
+    "codegraph_import_side_effects",
+    "codegraph_global_state",
+    "codegraph_find_literal",
+    "codegraph_grep",
+    "codegraph_tag",
+    "codegraph_annotate",
+    "codegraph_tagged",
+    "codegraph_duplicate_definitions",
+    "codegraph_find_clones",
+    "codegraph_backfill_embeddings",
+    "codegraph_recompute_pagerank",
+    // Call Graph & Data Flow (11)
     "codegraph_find_path",
     "codegraph_complexity",
+    "codegraph_test_gaps",
     "codegraph_data_flow",
     "codegraph_dead_stores",
     "codegraph_find_uninitialized",
     "codegraph_reaching_defs",
+    "codegraph_recursion",
+    "codegraph_unused_params",
+    "codegraph_callgraph_export",
+    "codegraph_entrypoints",
+    "codegraph_cfg",
+    "codegraph_null_checks",
+    "codegraph_routes",
 ];
 
 /// Entry point that resolves `~/.claude/settings.json` and delegates.
@@ -1198,11 +1239,11 @@ mod tests {
     // -- Auto-allow permissions tests -----------------------------------------
 
     #[test]
-    fn tool_names_count_is_46() {
+    fn tool_names_count_is_86() {
         assert_eq!(
             CODEGRAPH_TOOL_NAMES.len(),
-            46,
-            "Should have exactly 46 MCP tool names"
+            87,
+            "Should have exactly 87 MCP tool names"
         );
     }
 
@@ -1226,7 +1267,7 @@ mod tests {
 
         let parsed: Value = serde_json::from_str(&fs::read_to_string(&settings).unwrap()).unwrap();
         let allow = parsed["permissions"]["allow"].as_array().unwrap();
-        assert_eq!(allow.len(), 46, "should have 46 permission entries");
+        assert_eq!(allow.len(), 87, "should have 87 permission entries");
         assert!(
             allow.contains(&json!("mcp__codegraph__codegraph_query")),
             "should contain codegraph_query permission"
@@ -1276,8 +1317,8 @@ mod tests {
             allow.contains(&json!("Bash(*)")),
             "existing Bash permission preserved"
         );
-        // 2 existing + 46 new = 48
-        assert_eq!(allow.len(), 48, "should have 2 existing + 46 new");
+        // 2 existing + 87 new = 89
+        assert_eq!(allow.len(), 89, "should have 2 existing + 87 new");
     }
 
     #[test]
@@ -1292,7 +1333,7 @@ mod tests {
         let allow = parsed["permissions"]["allow"].as_array().unwrap();
         assert_eq!(
             allow.len(),
-            46,
+            87,
             "running twice should not duplicate entries"
         );
     }
@@ -1308,7 +1349,7 @@ mod tests {
         merge_auto_allow_permissions(&settings).unwrap();
 
         let parsed: Value = serde_json::from_str(&fs::read_to_string(&settings).unwrap()).unwrap();
-        assert_eq!(parsed["permissions"]["allow"].as_array().unwrap().len(), 46);
+        assert_eq!(parsed["permissions"]["allow"].as_array().unwrap().len(), 87);
     }
 
     #[test]
@@ -1322,7 +1363,7 @@ mod tests {
         merge_auto_allow_permissions(&settings).unwrap();
 
         let parsed: Value = serde_json::from_str(&fs::read_to_string(&settings).unwrap()).unwrap();
-        assert_eq!(parsed["permissions"]["allow"].as_array().unwrap().len(), 46);
+        assert_eq!(parsed["permissions"]["allow"].as_array().unwrap().len(), 87);
     }
 
     #[test]
@@ -1336,7 +1377,7 @@ mod tests {
         merge_auto_allow_permissions(&settings).unwrap();
 
         let parsed: Value = serde_json::from_str(&fs::read_to_string(&settings).unwrap()).unwrap();
-        assert_eq!(parsed["permissions"]["allow"].as_array().unwrap().len(), 46);
+        assert_eq!(parsed["permissions"]["allow"].as_array().unwrap().len(), 87);
     }
 
     #[test]
@@ -120,35 +120,46 @@ impl EmbeddingEngine {
         }
 
         let embeddings = self.embed_nodes(nodes)?;
-        let mut count = 0;
-
-        for (node, embedding) in nodes.iter().zip(embeddings.iter()) {
-            // Store in embedding_cache (BLOB format)
-            let blob: Vec<u8> = embedding.iter().flat_map(|f| f.to_le_bytes()).collect();
+        store_embeddings(conn, nodes, &embeddings)
+    }
+}
 
-            conn.execute(
-                "INSERT OR REPLACE INTO embedding_cache (node_id, embedding, model_version)
+/// Store the `embeddings` of `nodes` in both `embedding_cache` and
+/// `vec_embeddings`.
+#[cfg(feature = "embedding")]
+pub fn store_embeddings(
+    conn: &rusqlite::Connection,
+    nodes: &[CodeNode],
+    embeddings: &[Vec<f32>],
+) -> Result<usize> {
+    let mut count = 0;
+
+    for (node, embedding) in nodes.iter().zip(embeddings.iter()) {
+        // Store in embedding_cache (BLOB format)
+        let blob: Vec<u8> = embedding.iter().flat_map(|f| f.to_le_bytes()).collect();
+
+        conn.execute(
+            "INSERT OR REPLACE INTO embedding_cache (node_id, embedding, model_version)
                  VALUES (?1, ?2, 'jina-embeddings-v2-base-code')",
-                params![node.id, blob],
-            )?;
+            params![node.id, blob],
+        )?;
 
-            // Store in vec_embeddings (sqlite-vec format)
-            // sqlite-vec expects the embedding as a JSON array string
-            let vec_json = serde_json::to_string(embedding)
-                .map_err(|e| CodeGraphError::Embedding(e.to_string()))?;
+        // Store in vec_embeddings (sqlite-vec format)
+        // sqlite-vec expects the embedding as a JSON array string
+        let vec_json = serde_json::to_string(embedding)
+            .map_err(|e| CodeGraphError::Embedding(e.to_string()))?;
 
-            // Try to insert into vec_embeddings; if sqlite-vec isn't loaded, skip
-            let _ = conn.execute(
-                "INSERT OR REPLACE INTO vec_embeddings (node_id, embedding)
+        // Try to insert into vec_embeddings; if sqlite-vec isn't loaded, skip
+        let _ = conn.execute(
+            "INSERT OR REPLACE INTO vec_embeddings (node_id, embedding)
                  VALUES (?1, ?2)",
-                params![node.id, vec_json],
-            );
-
-            count += 1;
-        }
+            params![node.id, vec_json],
+        );
 
-        Ok(count)
+        count += 1;
     }
+
+    Ok(count)
 }
 
 // ---------------------------------------------------------------------------
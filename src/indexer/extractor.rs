@@ -13,7 +13,8 @@
 //!   references.
 //! - **Stateless per file**: each call to `extract_nodes` / `extract_edges` is
 //!   self-contained.
-//! - Handles all six node kinds and all six edge kinds defined in `types.rs`.
+//! - Handles all node kinds and edge kinds defined in `types.rs`, including
+//!   field read/write edges for data-model lineage.
 
 use std::collections::{HashMap, HashSet};
 
@@ -22,6 +23,7 @@ use tree_sitter::{QueryCursor, QueryMatch, Tree};
 
 use crate::error::Result;
 use crate::indexer::parser::CodeParser;
+use crate::resolution::frameworks::find_routes;
 use crate::types::{make_node_id, CodeEdge, CodeNode, EdgeKind, Language, NodeKind};
 
 // ---------------------------------------------------------------------------
@@ -43,6 +45,7 @@ fn definition_kind(capture_name: &str) -> Option<NodeKind> {
         "definition.enum" => Some(NodeKind::Enum),
         "definition.variable" => Some(NodeKind::Variable),
         "definition.decorated_function" => Some(NodeKind::Function),
+        "definition.field" | "definition.property" => Some(NodeKind::Property),
         _ => None,
     }
 }
@@ -74,6 +77,8 @@ fn is_more_specific_kind(new_kind: NodeKind, old_kind: NodeKind) -> bool {
             NodeKind::Namespace => 2,
             NodeKind::Property => 2,
             NodeKind::Constant => 2,
+            NodeKind::Route => 0,
+            NodeKind::ConfigKey => 0,
         }
     };
     specificity(new_kind) > specificity(old_kind)
@@ -358,6 +363,7 @@ impl Extractor {
         }
 
         populate_qualified_names(&mut nodes);
+        nodes.extend(route_nodes(file_path, language, source_text));
 
         Ok(nodes)
     }
@@ -369,7 +375,8 @@ impl Extractor {
     /// Extract relationships from a parsed file.
     ///
     /// Produces edges for: imports, calls, contains (nesting), extends,
-    /// implements, and type references.
+    /// implements, type references, field reads/writes, and the handlers of
+    /// routes.
     ///
     /// `node_index` maps symbol *names* to all known `CodeNode`s across the
     /// project for cross-file resolution.
@@ -391,6 +398,16 @@ impl Extractor {
         // --- Containment edges (class/interface -> method) ---
         extract_containment_edges(file_nodes, &mut edges);
 
+        // --- Handles edges (route -> handler) ---
+        extract_handles_edges(
+            file_path,
+            language,
+            source_text,
+            file_nodes,
+            node_index,
+            &mut edges,
+        );
+
         let mut matches = cursor.matches(&query, tree.root_node(), source_bytes);
         while let Some(m) = matches.next() {
             let pattern_name = primary_capture_name(m, capture_names);
@@ -456,6 +473,17 @@ impl Extractor {
                         &mut edges,
                     );
                 }
+                "reference.field" => {
+                    extract_field_access_edges(
+                        m,
+                        capture_names,
+                        file_path,
+                        source_bytes,
+                        file_nodes,
+                        node_index,
+                        &mut edges,
+                    );
+                }
                 _ => {}
             }
         }
@@ -464,6 +492,84 @@ impl Extractor {
     }
 }
 
+// ---------------------------------------------------------------------------
+// Routes
+// ---------------------------------------------------------------------------
+
+/// Lines between a route decorator or annotation and its handler.
+const ROUTE_HANDLER_REACH: u32 = 10;
+
+/// Route nodes for the HTTP routes a file declares, see [`find_routes`].
+/// The qualified name leads with the framework: `express GET /users`.
+fn route_nodes(file_path: &str, language: Language, source_text: &str) -> Vec<CodeNode> {
+    let lines: Vec<&str> = source_text.lines().collect();
+    find_routes(language, source_text)
+        .into_iter()
+        .map(|route| {
+            let name = route.name();
+            let text = lines.get(route.line as usize - 1).copied().unwrap_or("");
+            CodeNode {
+                id: make_node_id(NodeKind::Route, file_path, &name, route.line),
+                qualified_name: Some(format!("{} {}", route.framework, name)),
+                name,
+                kind: NodeKind::Route,
+                file_path: file_path.to_string(),
+                start_line: route.line,
+                end_line: route.line,
+                start_column: 0,
+                end_column: text.len() as u32,
+                language,
+                body: Some(text.trim().to_string()),
+                documentation: None,
+                exported: None,
+            }
+        })
+        .collect()
+}
+
+/// Handles edges: a route is served by the function a registration names,
+/// or by the one its decorator or annotation sits on.
+fn extract_handles_edges(
+    file_path: &str,
+    language: Language,
+    source_text: &str,
+    file_nodes: &[CodeNode],
+    node_index: &HashMap<String, Vec<CodeNode>>,
+    edges: &mut Vec<CodeEdge>,
+) {
+    for route in find_routes(language, source_text) {
+        let target = match &route.handler {
+            Some(handler) => {
+                let name = handler.rsplit(['.', ':']).next().unwrap_or(handler);
+                resolve_node(name, file_path, file_nodes, node_index)
+                    .map(|n| n.id.clone())
+                    .unwrap_or_else(|| format!("unresolved:{}", name))
+            }
+            None => {
+                let decorated = file_nodes
+                    .iter()
+                    .filter(|n| matches!(n.kind, NodeKind::Function | NodeKind::Method))
+                    .filter(|n| {
+                        n.end_line >= route.line && n.start_line <= route.line + ROUTE_HANDLER_REACH
+                    })
+                    .min_by_key(|n| n.start_line.abs_diff(route.line));
+                match decorated {
+                    Some(n) => n.id.clone(),
+                    None => continue,
+                }
+            }
+        };
+        edges.push(CodeEdge {
+            source: make_node_id(NodeKind::Route, file_path, &route.name(), route.line),
+            target,
+            kind: EdgeKind::Handles,
+            file_path: file_path.to_string(),
+            line: route.line,
+            metadata: None,
+        });
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Qualified name population
 // ---------------------------------------------------------------------------
@@ -522,8 +628,8 @@ pub fn populate_qualified_names(nodes: &mut [CodeNode]) {
 // Edge extraction helpers
 // ---------------------------------------------------------------------------
 
-/// Containment edges: a method is "contained" by the tightest enclosing
-/// class or interface whose line range encloses it.
+/// Containment edges: a method or field is "contained" by the tightest
+/// enclosing class or interface whose line range encloses it.
 fn extract_containment_edges(file_nodes: &[CodeNode], edges: &mut Vec<CodeEdge>) {
     let containers: Vec<&CodeNode> = file_nodes
         .iter()
@@ -540,7 +646,7 @@ fn extract_containment_edges(file_nodes: &[CodeNode], edges: &mut Vec<CodeEdge>)
         .collect();
     let members: Vec<&CodeNode> = file_nodes
         .iter()
-        .filter(|n| n.kind == NodeKind::Method)
+        .filter(|n| matches!(n.kind, NodeKind::Method | NodeKind::Property))
         .collect();
 
     for member in &members {
@@ -611,13 +717,14 @@ fn extract_import_edges(
     let source_id = format!("file:{}", file_path);
     let target_id = format!("module:{}", module_specifier);
 
-    let metadata = if imported_names.is_empty() {
-        None
-    } else {
-        let mut map = HashMap::new();
-        map.insert("names".to_string(), imported_names.join(","));
-        Some(map)
-    };
+    let mut metadata = HashMap::new();
+    if !imported_names.is_empty() {
+        metadata.insert("names".to_string(), imported_names.join(","));
+    }
+    if let Some(path) = rust_use_path(m, capture_names, source_bytes) {
+        metadata.insert("path".to_string(), path);
+    }
+    let metadata = (!metadata.is_empty()).then_some(metadata);
 
     edges.push(CodeEdge {
         source: source_id,
@@ -629,6 +736,25 @@ fn extract_import_edges(
     });
 }
 
+/// The module path a Rust `use` imports from: `shared::util` for
+/// `use shared::util::helper` or `use shared::util::{a, b}`. The specifier
+/// keeps only the last segment, so the path is what tells a workspace crate
+/// apart (see `resolution::imports`).
+fn rust_use_path(m: &QueryMatch, capture_names: &[&str], source_bytes: &[u8]) -> Option<String> {
+    let import = m
+        .captures
+        .iter()
+        .find(|c| capture_names[c.index as usize] == "reference.import")?
+        .node;
+    if import.kind() != "use_declaration" {
+        return None;
+    }
+    let path = import
+        .child_by_field_name("argument")?
+        .child_by_field_name("path")?;
+    Some(node_text(&path, source_bytes))
+}
+
 /// Inheritance edges: child `extends` parent.
 fn extract_inheritance_edges(
     m: &QueryMatch,
@@ -920,6 +1046,173 @@ fn extract_type_ref_edges(
     }
 }
 
+/// Field access edges: enclosing function reads or writes a field.
+///
+/// Only accesses that resolve to a known `Property` node produce an edge, so
+/// method calls and accesses on external types are ignored. An access on the
+/// left-hand side of an assignment (or an increment/decrement) is a write;
+/// everything else is a read.
+fn extract_field_access_edges(
+    m: &QueryMatch,
+    capture_names: &[&str],
+    file_path: &str,
+    source_bytes: &[u8],
+    file_nodes: &[CodeNode],
+    node_index: &HashMap<String, Vec<CodeNode>>,
+    edges: &mut Vec<CodeEdge>,
+) {
+    let access = m
+        .captures
+        .iter()
+        .find(|c| capture_names[c.index as usize] == "reference.field");
+    let name_capture = m
+        .captures
+        .iter()
+        .find(|c| capture_names[c.index as usize] == "name");
+    let (access, name_capture) = match (access, name_capture) {
+        (Some(a), Some(n)) => (a.node, n),
+        _ => return,
+    };
+
+    // `obj.method()` is a call, not a field access.
+    if let Some(parent) = access.parent() {
+        if parent.child_by_field_name("function") == Some(access) {
+            return;
+        }
+    }
+
+    let field_name = node_text(&name_capture.node, source_bytes);
+    let line = access.start_position().row as u32 + 1;
+    let object = access
+        .named_child(0)
+        .filter(|o| *o != name_capture.node)
+        .map(|o| node_text(&o, source_bytes))
+        .unwrap_or_default();
+
+    let Some(target) = resolve_field(&field_name, &object, line, file_nodes, node_index) else {
+        return;
+    };
+    let Some(accessor) = find_enclosing_node(file_nodes, line) else {
+        return;
+    };
+    // A field initializer is part of the field's own definition.
+    if accessor.id == target.id {
+        return;
+    }
+
+    let kind = if is_write_access(&access) {
+        EdgeKind::Writes
+    } else {
+        EdgeKind::Reads
+    };
+    edges.push(CodeEdge {
+        source: accessor.id.clone(),
+        target: target.id.clone(),
+        kind,
+        file_path: file_path.to_string(),
+        line,
+        metadata: None,
+    });
+}
+
+/// Returns `true` if the field access node is the target of an assignment,
+/// compound assignment, or increment/decrement.
+fn is_write_access(access: &tree_sitter::Node) -> bool {
+    let mut child = *access;
+    let Some(mut parent) = access.parent() else {
+        return false;
+    };
+    // Destructuring / multi-assignment wrappers (`a.x, b.y = 1, 2`).
+    while matches!(
+        parent.kind(),
+        "expression_list" | "pattern_list" | "tuple_pattern" | "parenthesized_expression"
+    ) {
+        child = parent;
+        match parent.parent() {
+            Some(p) => parent = p,
+            None => return false,
+        }
+    }
+    match parent.kind() {
+        "update_expression" | "inc_statement" | "dec_statement" | "postfix_unary_expression" => {
+            true
+        }
+        kind if kind.contains("assign") => parent.child_by_field_name("left") == Some(child),
+        _ => false,
+    }
+}
+
+/// Resolve a field name to a `Property` node.
+///
+/// With several same-named fields in the project, the receiver decides:
+/// `this`/`self` picks the field of the enclosing class, and an object named
+/// after a type (`order.status` -> `Order.status`) picks that type's field.
+/// Otherwise a unique same-file or project-wide match is used.
+fn resolve_field<'a>(
+    name: &str,
+    object: &str,
+    line: u32,
+    file_nodes: &'a [CodeNode],
+    node_index: &'a HashMap<String, Vec<CodeNode>>,
+) -> Option<&'a CodeNode> {
+    let local: Vec<&CodeNode> = file_nodes
+        .iter()
+        .filter(|n| n.kind == NodeKind::Property && n.name == name)
+        .collect();
+    let global: Vec<&CodeNode> = node_index
+        .get(name)
+        .map(|v| v.iter().filter(|n| n.kind == NodeKind::Property).collect())
+        .unwrap_or_default();
+    if local.is_empty() && global.is_empty() {
+        return None;
+    }
+
+    let owner_of = |n: &CodeNode| -> Option<String> {
+        n.qualified_name
+            .as_deref()
+            .and_then(|q| q.rsplit_once('.'))
+            .map(|(owner, _)| owner.rsplit('.').next().unwrap_or(owner).to_lowercase())
+    };
+
+    let receiver = object.rsplit(['.', ':', '>']).next().unwrap_or("").trim();
+    if matches!(receiver, "this" | "self" | "@") || receiver.is_empty() {
+        let enclosing_class = file_nodes
+            .iter()
+            .filter(|n| {
+                matches!(n.kind, NodeKind::Class | NodeKind::Struct)
+                    && n.start_line <= line
+                    && n.end_line >= line
+            })
+            .min_by_key(|n| n.end_line - n.start_line);
+        if let Some(class) = enclosing_class {
+            let owner = class.name.to_lowercase();
+            if let Some(hit) = local
+                .iter()
+                .find(|n| owner_of(n).as_deref() == Some(&owner))
+            {
+                return Some(hit);
+            }
+        }
+    } else {
+        let wanted = receiver.trim_start_matches('_').to_lowercase();
+        if let Some(hit) = local
+            .iter()
+            .chain(global.iter())
+            .find(|n| owner_of(n).as_deref() == Some(&wanted))
+        {
+            return Some(hit);
+        }
+    }
+
+    if local.len() == 1 {
+        return Some(local[0]);
+    }
+    if local.is_empty() && global.len() == 1 {
+        return Some(global[0]);
+    }
+    None
+}
+
 // ---------------------------------------------------------------------------
 // Resolution helpers
 // ---------------------------------------------------------------------------
@@ -2189,4 +2482,219 @@ public class Calculator {
             }
         }
     }
+
+    // -- Field usage tests -----------------------------------------------
+
+    #[test]
+    fn extract_typescript_fields_with_read_and_write_edges() {
+        let source = r#"
+class Order {
+    status: string;
+    total: number;
+
+    ship(): void {
+        this.status = "shipped";
+    }
+
+    describe(): string {
+        return this.status + this.total;
+    }
+}
+"#;
+        let nodes = parse_and_extract_nodes(source, Language::TypeScript);
+        let status = nodes
+            .iter()
+            .find(|n| n.name == "status")
+            .expect("should find status field");
+        assert_eq!(status.kind, NodeKind::Property);
+        assert_eq!(status.qualified_name.as_deref(), Some("Order.status"));
+
+        let edges = parse_and_extract_edges(source, Language::TypeScript, &nodes);
+        let ship = nodes.iter().find(|n| n.name == "ship").unwrap();
+        let describe = nodes.iter().find(|n| n.name == "describe").unwrap();
+        assert!(edges
+            .iter()
+            .any(|e| e.kind == EdgeKind::Writes && e.source == ship.id && e.target == status.id));
+        assert!(edges.iter().any(|e| e.kind == EdgeKind::Reads
+            && e.source == describe.id
+            && e.target == status.id));
+        assert!(!edges
+            .iter()
+            .any(|e| e.kind == EdgeKind::Reads && e.source == ship.id && e.target == status.id));
+        // Fields are contained by their class.
+        assert!(edges
+            .iter()
+            .any(|e| e.kind == EdgeKind::Contains && e.target == status.id));
+    }
+
+    #[test]
+    fn extract_rust_struct_fields_and_compound_assignment() {
+        let source = r#"
+struct Counter {
+    hits: u32,
+}
+
+impl Counter {
+    fn bump(&mut self) {
+        self.hits += 1;
+    }
+
+    fn get(&self) -> u32 {
+        self.hits
+    }
+}
+"#;
+        let nodes = parse_and_extract_nodes_file(source, Language::Rust, "counter.rs");
+        let hits = nodes
+            .iter()
+            .find(|n| n.name == "hits" && n.kind == NodeKind::Property)
+            .expect("should find hits field");
+        let edges = parse_and_extract_edges_file(source, Language::Rust, &nodes, "counter.rs");
+        let bump = nodes.iter().find(|n| n.name == "bump").unwrap();
+        let get = nodes.iter().find(|n| n.name == "get").unwrap();
+        assert!(edges
+            .iter()
+            .any(|e| e.kind == EdgeKind::Writes && e.source == bump.id && e.target == hits.id));
+        assert!(edges
+            .iter()
+            .any(|e| e.kind == EdgeKind::Reads && e.source == get.id && e.target == hits.id));
+    }
+
+    #[test]
+    fn field_access_ignores_method_calls() {
+        let source = r#"
+class Box {
+    size: number;
+
+    size2(): number {
+        return this.size;
+    }
+
+    grow(): void {
+        this.size2();
+    }
+}
+"#;
+        let nodes = parse_and_extract_nodes(source, Language::TypeScript);
+        let edges = parse_and_extract_edges(source, Language::TypeScript, &nodes);
+        let grow = nodes.iter().find(|n| n.name == "grow").unwrap();
+        assert!(!edges
+            .iter()
+            .any(|e| e.source == grow.id && matches!(e.kind, EdgeKind::Reads | EdgeKind::Writes)));
+    }
+
+    #[test]
+    fn field_access_disambiguates_by_receiver_name() {
+        let source = r#"
+class Order {
+    status: string;
+}
+
+class Invoice {
+    status: string;
+}
+
+function close(order: Order): void {
+    order.status = "closed";
+}
+"#;
+        let nodes = parse_and_extract_nodes(source, Language::TypeScript);
+        let edges = parse_and_extract_edges(source, Language::TypeScript, &nodes);
+        let writes: Vec<&CodeEdge> = edges
+            .iter()
+            .filter(|e| e.kind == EdgeKind::Writes)
+            .collect();
+        assert_eq!(writes.len(), 1);
+        let target = nodes.iter().find(|n| n.id == writes[0].target).unwrap();
+        assert_eq!(target.qualified_name.as_deref(), Some("Order.status"));
+    }
+
+    #[test]
+    fn extract_python_class_attributes() {
+        let source = r#"
+class Order:
+    status: str = "new"
+
+    def ship(self):
+        self.status = "shipped"
+"#;
+        let nodes = parse_and_extract_nodes_file(source, Language::Python, "order.py");
+        let status = nodes
+            .iter()
+            .find(|n| n.name == "status")
+            .expect("should find status attribute");
+        assert_eq!(status.kind, NodeKind::Property);
+        let edges = parse_and_extract_edges_file(source, Language::Python, &nodes, "order.py");
+        assert!(edges
+            .iter()
+            .any(|e| e.kind == EdgeKind::Writes && e.target == status.id));
+    }
+
+    #[test]
+    fn extract_express_route_handled_by_named_function() {
+        let source = r#"
+function listUsers(req, res) {
+    res.json([]);
+}
+
+app.get('/users', listUsers);
+"#;
+        let nodes = parse_and_extract_nodes_file(source, Language::JavaScript, "app.js");
+        let route = nodes
+            .iter()
+            .find(|n| n.kind == NodeKind::Route)
+            .expect("should find the route");
+        assert_eq!(route.name, "GET /users");
+        assert_eq!(route.qualified_name.as_deref(), Some("express GET /users"));
+        assert_eq!(route.start_line, 6);
+
+        let handler = nodes.iter().find(|n| n.name == "listUsers").unwrap();
+        let edges = parse_and_extract_edges_file(source, Language::JavaScript, &nodes, "app.js");
+        assert!(edges.iter().any(|e| e.kind == EdgeKind::Handles
+            && e.source == route.id
+            && e.target == handler.id));
+    }
+
+    #[test]
+    fn extract_fastapi_route_handled_by_decorated_function() {
+        let source = r#"
+@app.post("/items")
+async def create_item(item):
+    return item
+"#;
+        let nodes = parse_and_extract_nodes_file(source, Language::Python, "main.py");
+        let route = nodes
+            .iter()
+            .find(|n| n.kind == NodeKind::Route)
+            .expect("should find the route");
+        assert_eq!(route.name, "POST /items");
+
+        let handler = nodes.iter().find(|n| n.name == "create_item").unwrap();
+        let edges = parse_and_extract_edges_file(source, Language::Python, &nodes, "main.py");
+        let handles: Vec<&CodeEdge> = edges
+            .iter()
+            .filter(|e| e.kind == EdgeKind::Handles)
+            .collect();
+        assert_eq!(handles.len(), 1);
+        assert_eq!(handles[0].target, handler.id);
+    }
+
+    #[test]
+    fn extract_rust_use_records_the_module_path() {
+        let source = "use shared::util::{pad, trim};\nuse shared::Config;\nuse shared;\n";
+        let nodes = parse_and_extract_nodes_file(source, Language::Rust, "main.rs");
+        let edges = parse_and_extract_edges_file(source, Language::Rust, &nodes, "main.rs");
+        let paths: Vec<(&str, Option<&str>)> = edges
+            .iter()
+            .filter(|e| e.kind == EdgeKind::Imports)
+            .map(|e| {
+                let path = e.metadata.as_ref().and_then(|m| m.get("path"));
+                (e.target.as_str(), path.map(String::as_str))
+            })
+            .collect();
+        assert!(paths.contains(&("module:pad", Some("shared::util"))));
+        assert!(paths.contains(&("module:trim", Some("shared::util"))));
+        assert!(paths.contains(&("module:Config", Some("shared"))));
+        assert!(paths.contains(&("module:shared", None)));
+    }
 }
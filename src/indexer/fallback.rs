@@ -0,0 +1,403 @@
+//! Parser fallback chains.
+//!
+//! Tree-sitter is the primary parser, but a grammar can choke on a file
+//! (unsupported syntax, a preprocessor-heavy header, a half-written file)
+//! and extraction then yields nothing. A [`ParserChains`] lists, per
+//! language, the stages to try in order:
+//!
+//! - [`ParserStage::TreeSitter`] — the full extractor.
+//! - [`ParserStage::Regex`] — declaration keywords (`fn`, `def`, `class`,
+//!   `struct`, ...) matched line by line. Finds most named definitions in
+//!   any C-like, ML-like or scripting language, without nesting or edges.
+//! - [`ParserStage::Lines`] — one module node for the file plus unindented
+//!   assignments, so the file at least shows up in the graph.
+//!
+//! The first stage that produces symbols wins; a file where none does is
+//! indexed with the last stage's (possibly empty) result, or skipped when
+//! every stage errors. Nodes from the fallback stages get no edges, and the
+//! stage is recorded in each node's metadata as `parser` (see
+//! [`GraphStore::record_parser`](crate::graph::store::GraphStore::record_parser)).
+//!
+//! The default chain is tree-sitter alone, which matches indexing without
+//! fallbacks.
+
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use regex::Regex;
+
+use crate::indexer::extractor::Extractor;
+use crate::indexer::parser::CodeParser;
+use crate::types::{make_node_id, CodeNode, Language, NodeKind};
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// One way of extracting symbols from a file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ParserStage {
+    TreeSitter,
+    Regex,
+    Lines,
+}
+
+impl ParserStage {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::TreeSitter => "tree-sitter",
+            Self::Regex => "regex",
+            Self::Lines => "lines",
+        }
+    }
+
+    pub fn from_str_loose(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "tree-sitter" | "tree_sitter" | "treesitter" => Some(Self::TreeSitter),
+            "regex" => Some(Self::Regex),
+            "lines" | "line" => Some(Self::Lines),
+            _ => None,
+        }
+    }
+}
+
+/// Parser stages to try, in order, for each language.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParserChains {
+    default: Vec<ParserStage>,
+    by_language: HashMap<Language, Vec<ParserStage>>,
+}
+
+impl Default for ParserChains {
+    fn default() -> Self {
+        Self {
+            default: vec![ParserStage::TreeSitter],
+            by_language: HashMap::new(),
+        }
+    }
+}
+
+impl ParserChains {
+    /// Use `chain` for languages without their own. An empty chain keeps
+    /// the current default.
+    pub fn set_default(&mut self, chain: Vec<ParserStage>) {
+        if !chain.is_empty() {
+            self.default = chain;
+        }
+    }
+
+    /// Use `chain` for `language`. An empty chain is ignored.
+    pub fn set(&mut self, language: Language, chain: Vec<ParserStage>) {
+        if !chain.is_empty() {
+            self.by_language.insert(language, chain);
+        }
+    }
+
+    /// The stages tried for `language`.
+    pub fn chain_for(&self, language: Language) -> &[ParserStage] {
+        self.by_language.get(&language).unwrap_or(&self.default)
+    }
+}
+
+/// Symbols of one file and the stage that produced them.
+#[derive(Debug, Clone)]
+pub struct Extraction {
+    pub stage: ParserStage,
+    pub nodes: Vec<CodeNode>,
+}
+
+// ---------------------------------------------------------------------------
+// Chain
+// ---------------------------------------------------------------------------
+
+/// Run the chain for `language` over a file. `None` when every stage
+/// failed outright.
+pub fn extract_nodes(
+    chains: &ParserChains,
+    file_path: &str,
+    language: Language,
+    source: &str,
+) -> Option<Extraction> {
+    let chain = chains.chain_for(language);
+    let mut last = None;
+    for (i, &stage) in chain.iter().enumerate() {
+        let is_last = i + 1 == chain.len();
+        let nodes = match stage {
+            ParserStage::TreeSitter => {
+                let Ok(tree) = CodeParser::new().parse(source, language) else {
+                    continue;
+                };
+                let Ok(nodes) = Extractor::extract_nodes(&tree, file_path, language, source) else {
+                    continue;
+                };
+                // A tree with syntax errors and no symbols is a failed
+                // parse; a clean tree without symbols is just an empty file.
+                if nodes.is_empty() && tree.root_node().has_error() && !is_last {
+                    last = Some(Extraction { stage, nodes });
+                    continue;
+                }
+                nodes
+            }
+            ParserStage::Regex => extract_regex(file_path, language, source),
+            ParserStage::Lines => extract_lines(file_path, language, source),
+        };
+        if !nodes.is_empty() || is_last {
+            return Some(Extraction { stage, nodes });
+        }
+        last = Some(Extraction { stage, nodes });
+    }
+    last
+}
+
+// ---------------------------------------------------------------------------
+// Regex stage
+// ---------------------------------------------------------------------------
+
+fn function_pattern() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(
+            r"^(\s*)(?:(?:export|default|pub(?:\([^)]*\))?|public|private|protected|internal|static|async|unsafe|extern|inline|override|final|open|local|defp?)\s+)*(?:fn|func|fun|function|def|sub|proc)\s+(?:\([^)]*\)\s*)?([A-Za-z_$][\w$]*[?!]?)",
+        )
+        .expect("valid regex")
+    })
+}
+
+fn type_pattern() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(
+            r"^(\s*)(?:(?:export|default|pub(?:\([^)]*\))?|public|private|protected|internal|static|abstract|sealed|final|open|data|partial)\s+)*(class|struct|interface|trait|enum|protocol|module|namespace|object|record|type)\s+([A-Za-z_$][\w$.]*)",
+        )
+        .expect("valid regex")
+    })
+}
+
+/// Named declarations found by keyword. A symbol spans up to the line
+/// before the next declaration at the same or a shallower indentation.
+pub fn extract_regex(file_path: &str, language: Language, source: &str) -> Vec<CodeNode> {
+    let lines: Vec<&str> = source.lines().collect();
+    // (indent, kind, name, 1-based line)
+    let mut found: Vec<(usize, NodeKind, String, u32)> = Vec::new();
+    for (i, line) in lines.iter().enumerate() {
+        if let Some(caps) = function_pattern().captures(line) {
+            let indent = caps[1].len();
+            let enclosing = found.iter().rev().find(|f| f.0 < indent);
+            let kind = if enclosing.is_some_and(|f| is_container(f.1)) {
+                NodeKind::Method
+            } else {
+                NodeKind::Function
+            };
+            found.push((indent, kind, caps[2].to_string(), i as u32 + 1));
+        } else if let Some(caps) = type_pattern().captures(line) {
+            let kind = match &caps[2] {
+                "class" | "object" | "record" => NodeKind::Class,
+                "struct" => NodeKind::Struct,
+                "interface" | "protocol" => NodeKind::Interface,
+                "trait" => NodeKind::Trait,
+                "enum" => NodeKind::Enum,
+                "module" => NodeKind::Module,
+                "namespace" => NodeKind::Namespace,
+                _ => NodeKind::TypeAlias,
+            };
+            found.push((caps[1].len(), kind, caps[3].to_string(), i as u32 + 1));
+        }
+    }
+
+    let total = lines.len().max(1) as u32;
+    found
+        .iter()
+        .enumerate()
+        .map(|(i, (indent, kind, name, line))| {
+            let end_line = found[i + 1..]
+                .iter()
+                .find(|next| next.0 <= *indent)
+                .map_or(total, |next| next.3.saturating_sub(1).max(*line));
+            fallback_node(file_path, language, *kind, name, *line, end_line, &lines)
+        })
+        .collect()
+}
+
+fn is_container(kind: NodeKind) -> bool {
+    matches!(
+        kind,
+        NodeKind::Class | NodeKind::Struct | NodeKind::Interface | NodeKind::Trait
+    )
+}
+
+// ---------------------------------------------------------------------------
+// Lines stage
+// ---------------------------------------------------------------------------
+
+fn assignment_pattern() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(
+            r"^(?:(?:export|const|let|var|local|my|our|readonly)\s+)*([A-Za-z_][\w]*)\s*:?=[^=]",
+        )
+        .expect("valid regex")
+    })
+}
+
+/// A module node for the whole file, plus a variable (or constant, when
+/// the name is all caps) for every unindented assignment.
+pub fn extract_lines(file_path: &str, language: Language, source: &str) -> Vec<CodeNode> {
+    let lines: Vec<&str> = source.lines().collect();
+    let stem = std::path::Path::new(file_path)
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or(file_path);
+    let total = lines.len().max(1) as u32;
+    let mut nodes = vec![fallback_node(
+        file_path,
+        language,
+        NodeKind::Module,
+        stem,
+        1,
+        total,
+        &[],
+    )];
+    for (i, line) in lines.iter().enumerate() {
+        let Some(caps) = assignment_pattern().captures(line) else {
+            continue;
+        };
+        let name = &caps[1];
+        let kind = if name.chars().any(|c| c.is_ascii_lowercase()) {
+            NodeKind::Variable
+        } else {
+            NodeKind::Constant
+        };
+        let line_no = i as u32 + 1;
+        nodes.push(fallback_node(
+            file_path, language, kind, name, line_no, line_no, &lines,
+        ));
+    }
+    nodes
+}
+
+/// A node with the declaration line as its body, when `lines` has it.
+fn fallback_node(
+    file_path: &str,
+    language: Language,
+    kind: NodeKind,
+    name: &str,
+    start_line: u32,
+    end_line: u32,
+    lines: &[&str],
+) -> CodeNode {
+    CodeNode {
+        id: make_node_id(kind, file_path, name, start_line),
+        name: name.to_string(),
+        qualified_name: None,
+        kind,
+        file_path: file_path.to_string(),
+        start_line,
+        end_line,
+        start_column: 0,
+        end_column: 0,
+        language,
+        body: lines
+            .get(start_line as usize - 1)
+            .map(|l| l.trim().to_string()),
+        documentation: None,
+        exported: None,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(nodes: &[CodeNode]) -> Vec<(&str, &str, u32, u32)> {
+        nodes
+            .iter()
+            .map(|n| (n.name.as_str(), n.kind.as_str(), n.start_line, n.end_line))
+            .collect()
+    }
+
+    #[test]
+    fn regex_stage_finds_declarations() {
+        let src = "\
+export class Cart {
+  async function total(items) {
+  }
+}
+pub fn checkout() {}
+def refund(order):
+    pass
+";
+        let nodes = extract_regex("src/cart.ts", Language::TypeScript, src);
+        assert_eq!(
+            names(&nodes),
+            vec![
+                ("Cart", "class", 1, 4),
+                ("total", "method", 2, 4),
+                ("checkout", "function", 5, 5),
+                ("refund", "function", 6, 7),
+            ]
+        );
+        assert_eq!(nodes[0].id, "class:src/cart.ts:Cart:1");
+        assert_eq!(nodes[2].body.as_deref(), Some("pub fn checkout() {}"));
+    }
+
+    #[test]
+    fn lines_stage_records_file_and_assignments() {
+        let src = "MAX_RETRIES = 3\nname := \"x\"\n  nested = 1\nif a == b\n";
+        let nodes = extract_lines("conf/settings.cfg", Language::Bash, src);
+        assert_eq!(
+            names(&nodes),
+            vec![
+                ("settings", "module", 1, 4),
+                ("MAX_RETRIES", "constant", 1, 1),
+                ("name", "variable", 2, 2),
+            ]
+        );
+    }
+
+    #[test]
+    fn chain_falls_through_to_the_first_stage_with_symbols() {
+        let mut chains = ParserChains::default();
+        // Default: tree-sitter only, even for a file it cannot make sense of.
+        let garbage = "}}} {{{\nfn broken( {{{\n)))\n";
+        let out = extract_nodes(&chains, "a.py", Language::Python, garbage).unwrap();
+        assert_eq!(out.stage, ParserStage::TreeSitter);
+
+        chains.set(
+            Language::Python,
+            vec![
+                ParserStage::TreeSitter,
+                ParserStage::Regex,
+                ParserStage::Lines,
+            ],
+        );
+        let out = extract_nodes(&chains, "a.py", Language::Python, garbage).unwrap();
+        assert_eq!(out.stage, ParserStage::Regex);
+        assert_eq!(out.nodes[0].name, "broken");
+
+        // Clean files stay with tree-sitter.
+        let out =
+            extract_nodes(&chains, "b.py", Language::Python, "def ok():\n    pass\n").unwrap();
+        assert_eq!(out.stage, ParserStage::TreeSitter);
+        assert_eq!(out.nodes[0].name, "ok");
+
+        // Other languages keep the default chain.
+        assert_eq!(chains.chain_for(Language::Go), &[ParserStage::TreeSitter]);
+    }
+
+    #[test]
+    fn stage_names_parse_loosely() {
+        assert_eq!(
+            ParserStage::from_str_loose("Tree_Sitter"),
+            Some(ParserStage::TreeSitter)
+        );
+        assert_eq!(
+            ParserStage::from_str_loose("line"),
+            Some(ParserStage::Lines)
+        );
+        assert_eq!(ParserStage::from_str_loose("ctags"), None);
+    }
+}
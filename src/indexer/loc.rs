@@ -0,0 +1,188 @@
+//! Line counts per file, cloc style.
+//!
+//! Every line of a file is exactly one of code, comment or blank. A line
+//! holding both code and a comment counts as code; blank lines inside a
+//! block comment count as blank. Logical lines are the code lines that
+//! carry more than brackets and punctuation, so a closing `});` does not
+//! inflate them. Comment markers inside string literals are not recognized,
+//! which is the usual approximation of line counters.
+
+use crate::types::Language;
+
+/// Line counts of one file (or a sum of files).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LineCounts {
+    /// Physical lines.
+    pub total: usize,
+    pub code: usize,
+    pub comment: usize,
+    pub blank: usize,
+    /// Code lines with an identifier, number or string on them.
+    pub logical: usize,
+}
+
+impl LineCounts {
+    /// Comment lines per non-blank line, 0 for an empty file.
+    pub fn comment_ratio(&self) -> f64 {
+        let non_blank = self.code + self.comment;
+        if non_blank == 0 {
+            0.0
+        } else {
+            self.comment as f64 / non_blank as f64
+        }
+    }
+
+    pub fn add(&mut self, other: &LineCounts) {
+        self.total += other.total;
+        self.code += other.code;
+        self.comment += other.comment;
+        self.blank += other.blank;
+        self.logical += other.logical;
+    }
+}
+
+/// Line and block comment markers of a language.
+struct CommentSyntax {
+    line: &'static [&'static str],
+    block: Option<(&'static str, &'static str)>,
+}
+
+fn comment_syntax(language: Language) -> CommentSyntax {
+    use Language::*;
+    let (line, block): (&'static [&'static str], _) = match language {
+        TypeScript | Tsx | JavaScript | Jsx | Go | Rust | Java | C | Cpp | CSharp | Swift
+        | Kotlin | Scala | Dart | Verilog | Groovy => (&["//"], Some(("/*", "*/"))),
+        Php => (&["//", "#"], Some(("/*", "*/"))),
+        Zig => (&["//"], None),
+        Python | Bash | R => (&["#"], None),
+        Ruby => (&["#"], Some(("=begin", "=end"))),
+        PowerShell => (&["#"], Some(("<#", "#>"))),
+        Julia => (&["#"], Some(("#=", "=#"))),
+        Nix => (&["#"], Some(("/*", "*/"))),
+        Elixir => (&["#"], None),
+        Lua => (&["--"], Some(("--[[", "]]"))),
+        Haskell | Elm => (&["--"], Some(("{-", "-}"))),
+        Clojure => (&[";"], None),
+        Erlang => (&["%"], None),
+        Fortran => (&["!"], None),
+    };
+    CommentSyntax { line, block }
+}
+
+/// Count the lines of `source`, written in `language`.
+pub fn count_lines(source: &str, language: Language) -> LineCounts {
+    let syntax = comment_syntax(language);
+    let mut counts = LineCounts::default();
+    let mut in_block = false;
+
+    for line in source.lines() {
+        counts.total += 1;
+        let mut rest = line.trim();
+        if rest.is_empty() {
+            counts.blank += 1;
+            continue;
+        }
+        let mut code = String::new();
+        loop {
+            if in_block {
+                let end = syntax.block.map_or("", |(_, end)| end);
+                match rest.find(end) {
+                    Some(i) => {
+                        in_block = false;
+                        rest = rest[i + end.len()..].trim_start();
+                    }
+                    None => break,
+                }
+            }
+            if rest.is_empty() {
+                break;
+            }
+            if let Some((start, _)) = syntax.block.filter(|(start, _)| rest.starts_with(start)) {
+                in_block = true;
+                rest = &rest[start.len()..];
+                continue;
+            }
+            // Code up to the first comment marker; a block marker that
+            // starts with a line marker (`--[[`) wins at the same offset.
+            let line_at = syntax.line.iter().filter_map(|m| rest.find(m)).min();
+            let block_at = syntax.block.and_then(|(start, _)| rest.find(start));
+            match (block_at, line_at) {
+                (Some(b), l) if l.is_none_or(|l| b <= l) => {
+                    code.push_str(&rest[..b]);
+                    rest = &rest[b..];
+                }
+                (_, Some(l)) => {
+                    code.push_str(&rest[..l]);
+                    break;
+                }
+                _ => {
+                    code.push_str(rest);
+                    break;
+                }
+            }
+        }
+        let code = code.trim();
+        if code.is_empty() {
+            counts.comment += 1;
+        } else {
+            counts.code += 1;
+            if code
+                .chars()
+                .any(|c| c.is_alphanumeric() || matches!(c, '"' | '\'' | '`'))
+            {
+                counts.logical += 1;
+            }
+        }
+    }
+    counts
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_code_comments_and_blanks() {
+        let src = "\
+// header
+/* block
+   still comment
+
+*/
+fn main() { // trailing
+    let x = 1; /* inline */ let y = 2;
+}
+";
+        let c = count_lines(src, Language::Rust);
+        assert_eq!(
+            c,
+            LineCounts {
+                total: 8,
+                code: 3,
+                comment: 4,
+                blank: 1,
+                logical: 2,
+            }
+        );
+        assert!((c.comment_ratio() - 4.0 / 7.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn uses_the_comment_syntax_of_the_language() {
+        let py = "# comment\nx = 1  # trailing\n\n\"\"\"docstring\"\"\"\n";
+        let c = count_lines(py, Language::Python);
+        assert_eq!((c.code, c.comment, c.blank), (2, 1, 1));
+
+        let lua = "--[[ long\ncomment ]]\nlocal x = 1\n-- line\n";
+        let c = count_lines(lua, Language::Lua);
+        assert_eq!((c.code, c.comment), (1, 3));
+
+        let mut sum = count_lines(py, Language::Python);
+        sum.add(&c);
+        assert_eq!(sum.total, 8);
+    }
+}
@@ -2,6 +2,8 @@
 
 pub mod embedder;
 pub mod extractor;
+pub mod fallback;
+pub mod loc;
 pub mod parser;
 pub mod pipeline;
 
@@ -12,6 +12,21 @@
 //! - **Pass 2**: Build a cross-file node index, then extract edges. Edge
 //!   extraction needs the global symbol table, but each file is still
 //!   independent once the index is built.
+//!
+//! After persisting, each file whose content changed is blamed once (inside
+//! a git repository) to record per-symbol last-commit metadata in
+//! `node_git_meta`; unchanged files re-indexed by a forced run keep theirs.
+//! [`with_blame_metadata`](IndexingPipeline::with_blame_metadata) turns this
+//! off. New commit messages are embedded for semantic commit search.
+//! Wire names of fields on serializable types are recorded in `wire_fields`,
+//! module-level code that runs at import time in `import_side_effects`, and
+//! string literals and named constants in `literals`. Each file's code,
+//! comment and blank line counts are stored with its hash.
+//!
+//! Files tree-sitter cannot make sense of can fall back to lighter
+//! extractors, per the [`ParserChains`] given to
+//! [`with_parser_chains`](IndexingPipeline::with_parser_chains). Their nodes
+//! get no edges and record the parser that produced them.
 
 use std::collections::{HashMap, HashSet};
 use std::fs;
@@ -23,11 +38,25 @@ use ignore::WalkBuilder;
 use rayon::prelude::*;
 use sha2::{Digest, Sha256};
 
+use crate::db::warm_start::{self, WarmStartStats};
 use crate::error::{CodeGraphError, Result};
+use crate::git::commit_search::{index_commits, CommitEmbedder, COMMIT_INDEX_LIMIT};
+use crate::git::{compute_node_git_meta, ensure_git_repo, NodeGitMeta};
+use crate::graph::ranking::refresh_node_rank;
+use crate::graph::stats::{load_dir_stats, refresh_dir_stats, refresh_dir_stats_for};
 use crate::graph::store::GraphStore;
+use crate::indexer::embedder::EmbeddingEngine;
 use crate::indexer::extractor::Extractor;
+use crate::indexer::fallback::{self, ParserChains, ParserStage};
+use crate::indexer::loc::{count_lines, LineCounts};
 use crate::indexer::parser::CodeParser;
-use crate::resolution::imports::resolve_imports;
+use crate::resolution::config_keys::{
+    config_key_graph, read_config_keys, scan_config_keys, ConfigAccessorMatcher, ConfigFormat,
+};
+use crate::resolution::imports::{resolve_workspace_imports, WorkspaceLayout};
+use crate::resolution::literals::{extract_literals, Literal};
+use crate::resolution::side_effects::{extract_side_effects, SideEffect};
+use crate::resolution::wire_contract::{extract_wire_fields, WireField};
 use crate::types::{CodeEdge, CodeNode, Language};
 
 // ---------------------------------------------------------------------------
@@ -78,6 +107,7 @@ struct FileParseState {
     content_hash: String,
     source_text: String,
     nodes: Vec<CodeNode>,
+    parser: ParserStage,
 }
 
 // ---------------------------------------------------------------------------
@@ -87,11 +117,59 @@ struct FileParseState {
 /// The two-pass indexing pipeline.
 pub struct IndexingPipeline<'a> {
     store: &'a GraphStore,
+    embedder: Option<&'a EmbeddingEngine>,
+    parsers: ParserChains,
+    #[cfg_attr(not(feature = "embedding"), allow(dead_code))]
+    ann_index: Option<bool>,
+    blame_metadata: bool,
 }
 
 impl<'a> IndexingPipeline<'a> {
     pub fn new(store: &'a GraphStore) -> Self {
-        Self { store }
+        Self {
+            store,
+            embedder: None,
+            parsers: ParserChains::default(),
+            ann_index: None,
+            blame_metadata: true,
+        }
+    }
+
+    /// Pipeline that embeds with an already loaded model instead of loading
+    /// one per run.
+    pub fn with_embedder(store: &'a GraphStore, embedder: Option<&'a EmbeddingEngine>) -> Self {
+        Self {
+            store,
+            embedder,
+            parsers: ParserChains::default(),
+            ann_index: None,
+            blame_metadata: true,
+        }
+    }
+
+    /// Use `chains` to pick the parsers tried for each language.
+    #[must_use]
+    pub fn with_parser_chains(mut self, chains: ParserChains) -> Self {
+        self.parsers = chains;
+        self
+    }
+
+    /// Build (`true`) or delete (`false`) the ANN sidecar of the embeddings,
+    /// per `performance.ann_index`. Without this an existing sidecar is
+    /// kept up to date and a missing one is left alone. See
+    /// [`crate::graph::ann`].
+    #[must_use]
+    pub fn with_ann_index(mut self, enabled: bool) -> Self {
+        self.ann_index = Some(enabled);
+        self
+    }
+
+    /// Record per-symbol git metadata of changed files (the default), per
+    /// `git.blame_metadata`. Off, no file is blamed.
+    #[must_use]
+    pub fn with_blame_metadata(mut self, enabled: bool) -> Self {
+        self.blame_metadata = enabled;
+        self
     }
 
     /// Index an entire directory tree.
@@ -104,17 +182,15 @@ impl<'a> IndexingPipeline<'a> {
 
         // Pre-fetch all file hashes for incremental checks (before rayon).
         // This avoids touching the non-Sync Connection from parallel threads.
-        let stored_hashes: HashMap<String, String> = if options.incremental {
-            self.load_all_file_hashes()
-        } else {
-            HashMap::new()
-        };
+        // Forced runs use them to keep the git metadata of unchanged files.
+        let stored_hashes = self.load_all_file_hashes();
 
         let files_skipped = AtomicUsize::new(0);
 
         // ---- Pass 1: parse & extract nodes (parallel via rayon) ----
         // The closure only captures `root`, `stored_hashes`, `files_skipped`,
-        // and `options.incremental` — all are Sync. No DB access here.
+        // `parsers` and `options.incremental` — all are Sync. No DB access here.
+        let parsers = &self.parsers;
         let parsed: Vec<FileParseState> = file_paths
             .par_iter()
             .filter_map(|abs_path| {
@@ -171,24 +247,14 @@ impl<'a> IndexingPipeline<'a> {
                     }
                 };
 
-                // Parse with a thread-local Parser (Parser is NOT Send/Sync)
-                let parser = CodeParser::new();
-                let tree = match parser.parse(&source_text, language) {
-                    Ok(t) => t,
-                    Err(_) => {
-                        files_skipped.fetch_add(1, Ordering::Relaxed);
-                        return None;
-                    }
-                };
-
-                // Extract nodes
-                let nodes = match Extractor::extract_nodes(&tree, &rel_path, language, &source_text)
-                {
-                    Ok(n) => n,
-                    Err(_) => {
-                        files_skipped.fetch_add(1, Ordering::Relaxed);
-                        return None;
-                    }
+                // Parse and extract nodes, falling back along the chain.
+                // tree-sitter's Parser is NOT Send/Sync, so each task
+                // creates its own.
+                let Some(extraction) =
+                    fallback::extract_nodes(parsers, &rel_path, language, &source_text)
+                else {
+                    files_skipped.fetch_add(1, Ordering::Relaxed);
+                    return None;
                 };
 
                 Some(FileParseState {
@@ -196,7 +262,8 @@ impl<'a> IndexingPipeline<'a> {
                     language,
                     content_hash,
                     source_text,
-                    nodes,
+                    nodes: extraction.nodes,
+                    parser: extraction.stage,
                 })
             })
             .collect();
@@ -224,6 +291,49 @@ impl<'a> IndexingPipeline<'a> {
 
         let node_index = build_node_index(&all_nodes);
 
+        // Wire names and side effects only depend on the file itself.
+        let mut wire_by_file: HashMap<String, Vec<WireField>> = parsed
+            .par_iter()
+            .map(|state| {
+                (
+                    state.relative_path.clone(),
+                    extract_wire_fields(&state.source_text, &state.nodes),
+                )
+            })
+            .collect();
+        let mut side_effects_by_file: HashMap<String, Vec<SideEffect>> = parsed
+            .par_iter()
+            .map(|state| {
+                (
+                    state.relative_path.clone(),
+                    extract_side_effects(&state.relative_path, state.language, &state.source_text),
+                )
+            })
+            .collect();
+        let mut lines_by_file: HashMap<String, LineCounts> = parsed
+            .par_iter()
+            .map(|state| {
+                (
+                    state.relative_path.clone(),
+                    count_lines(&state.source_text, state.language),
+                )
+            })
+            .collect();
+        let mut literals_by_file: HashMap<String, Vec<Literal>> = parsed
+            .par_iter()
+            .map(|state| {
+                (
+                    state.relative_path.clone(),
+                    extract_literals(
+                        &state.relative_path,
+                        state.language,
+                        &state.source_text,
+                        &state.nodes,
+                    ),
+                )
+            })
+            .collect();
+
         // ---- Pass 2: extract edges & persist (parallel edge extraction) ----
         #[allow(clippy::type_complexity)]
         let edge_results: Vec<
@@ -231,18 +341,22 @@ impl<'a> IndexingPipeline<'a> {
         > = parsed
             .par_iter()
             .map(|state| {
-                // Each thread creates its own Parser (not Send/Sync)
-                let parser = CodeParser::new();
-                let tree = parser.parse(&state.source_text, state.language)?;
-
-                let edges = Extractor::extract_edges(
-                    &tree,
-                    &state.relative_path,
-                    state.language,
-                    &state.source_text,
-                    &state.nodes,
-                    &node_index,
-                )?;
+                // Fallback extractors produce no edges.
+                let edges = if state.parser == ParserStage::TreeSitter {
+                    // Each thread creates its own Parser (not Send/Sync)
+                    let parser = CodeParser::new();
+                    let tree = parser.parse(&state.source_text, state.language)?;
+                    Extractor::extract_edges(
+                        &tree,
+                        &state.relative_path,
+                        state.language,
+                        &state.source_text,
+                        &state.nodes,
+                        &node_index,
+                    )?
+                } else {
+                    Vec::new()
+                };
 
                 Ok((
                     state.relative_path.clone(),
@@ -290,11 +404,12 @@ impl<'a> IndexingPipeline<'a> {
             .collect();
         let all_edges_owned: Vec<CodeEdge> = all_edges.iter().map(|e| (*e).clone()).collect();
 
-        let resolution_result = resolve_imports(
+        let resolution_result = resolve_workspace_imports(
             &all_edges_owned,
             &indexed_files,
             &node_index,
             &nodes_by_file,
+            &WorkspaceLayout::load(root),
         );
 
         // Group resolved edges by their source file for merging
@@ -310,23 +425,82 @@ impl<'a> IndexingPipeline<'a> {
         let mut files_indexed = 0usize;
         let mut nodes_created = 0usize;
         let mut edges_created = 0usize;
+        let config_matcher = ConfigAccessorMatcher::shared();
+        let mut persisted: Vec<(String, Vec<CodeNode>)> = Vec::with_capacity(file_data.len());
 
-        for (rel_path, language, content_hash, nodes, mut edges) in file_data {
+        for (rel_path, _, _, _, edges) in &mut file_data {
             // Merge resolved import edges into this file's edges
-            if let Some(extra_edges) = resolved_by_file.remove(&rel_path) {
+            if let Some(extra_edges) = resolved_by_file.remove(rel_path) {
                 edges.extend(extra_edges);
             }
+        }
 
+        // Only a forced run re-indexes unchanged files; replacing their nodes
+        // drops metadata that blaming them again would reproduce.
+        let kept_git_meta = self.unchanged_git_meta(
+            file_data
+                .iter()
+                .map(|(path, _, hash, _, _)| (path.as_str(), hash.as_str())),
+            &stored_hashes,
+        )?;
+
+        // Replace the graph data of every file in one transaction so a
+        // failure part-way never leaves the graph half-updated.
+        let batch: Vec<(&str, &[CodeNode], &[CodeEdge])> = file_data
+            .iter()
+            .map(|(path, _, _, nodes, edges)| (path.as_str(), nodes.as_slice(), edges.as_slice()))
+            .collect();
+        self.store.replace_files_data(&batch)?;
+        for state in parsed
+            .iter()
+            .filter(|s| s.parser != ParserStage::TreeSitter)
+        {
+            self.store
+                .record_parser(&state.relative_path, state.parser.as_str())?;
+        }
+
+        // File hashes are written last: a file whose hash was not recorded
+        // is simply re-indexed on the next incremental run.
+        for (rel_path, language, content_hash, nodes, edges) in file_data {
             // Clear and persist unresolved refs for this file
             self.store.clear_unresolved_refs_for_file(&rel_path)?;
 
-            self.store.replace_file_data(&rel_path, &nodes, &edges)?;
-            self.upsert_file_hash(&rel_path, &content_hash, language)?;
+            self.store
+                .replace_config_refs_for_file(&rel_path, &config_matcher.find_refs(&nodes))?;
+            self.store.replace_wire_fields_for_file(
+                &rel_path,
+                &wire_by_file.remove(&rel_path).unwrap_or_default(),
+            )?;
+            self.store.replace_side_effects_for_file(
+                &rel_path,
+                &side_effects_by_file.remove(&rel_path).unwrap_or_default(),
+            )?;
+            self.store.replace_literals_for_file(
+                &rel_path,
+                &literals_by_file.remove(&rel_path).unwrap_or_default(),
+            )?;
+            self.upsert_file_hash(
+                &rel_path,
+                &content_hash,
+                language,
+                &lines_by_file.remove(&rel_path).unwrap_or_default(),
+            )?;
 
             nodes_created += nodes.len();
             edges_created += edges.len();
             files_indexed += 1;
+            persisted.push((rel_path, nodes));
+        }
+
+        for (path, meta) in &kept_git_meta {
+            self.store.replace_node_git_meta_for_file(path, meta)?;
         }
+        let git_files: Vec<(&str, &[CodeNode])> = persisted
+            .iter()
+            .filter(|(path, _)| !kept_git_meta.contains_key(path))
+            .map(|(path, nodes)| (path.as_str(), nodes.as_slice()))
+            .collect();
+        self.refresh_node_git_meta(root, &git_files)?;
 
         // Persist unresolved refs
         for uref in &resolution_result.unresolved_refs {
@@ -339,16 +513,54 @@ impl<'a> IndexingPipeline<'a> {
             )?;
         }
 
+        // Config files are cheap to re-read, so their keys are refreshed on
+        // every run rather than tracked through file_hashes.
+        self.store.replace_config_keys(&scan_config_keys(root))?;
+        self.link_config_keys()?;
+
+        // Directory rollups read every function body, so they are only
+        // rebuilt when something changed (or was never computed).
+        if files_indexed > 0 || load_dir_stats(&self.store.conn)?.is_empty() {
+            refresh_dir_stats(&self.store.conn)?;
+        }
+        // Global PageRank, read by search as a ranking prior.
+        let unranked: bool = self.store.conn.query_row(
+            "SELECT NOT EXISTS (SELECT 1 FROM node_rank)",
+            [],
+            |row| row.get(0),
+        )?;
+        if files_indexed > 0 || unranked {
+            refresh_node_rank(self.store)?;
+        }
+
+        // ---- Purge tombstones past their retention period ----
+        self.store.purge_expired_tombstones()?;
+
         // ---- Optional: generate embeddings ----
         #[cfg(feature = "embedding")]
         if files_indexed > 0 {
-            if let Ok(engine) = crate::indexer::embedder::EmbeddingEngine::try_new() {
+            let loaded;
+            let engine = match self.embedder {
+                Some(e) => Some(e),
+                None => match EmbeddingEngine::try_new() {
+                    Ok(e) => {
+                        loaded = e;
+                        Some(&loaded)
+                    }
+                    Err(_) => None,
+                },
+            };
+            if let Some(engine) = engine {
                 let batch_size = 64;
                 let mut embedded = 0usize;
+                let mut embedded_ids: Vec<String> = Vec::new();
                 for chunk in all_nodes.chunks(batch_size) {
                     let chunk_owned: Vec<CodeNode> = chunk.iter().map(|n| (*n).clone()).collect();
                     match engine.embed_and_store(&self.store.conn, &chunk_owned) {
-                        Ok(n) => embedded += n,
+                        Ok(n) => {
+                            embedded += n;
+                            embedded_ids.extend(chunk_owned.into_iter().map(|n| n.id));
+                        }
                         Err(e) => {
                             eprintln!("[codegraph] WARNING: embedding batch failed: {e}");
                             break;
@@ -358,9 +570,20 @@ impl<'a> IndexingPipeline<'a> {
                 if embedded > 0 {
                     eprintln!("[codegraph] Generated embeddings for {embedded} nodes");
                 }
+                match crate::graph::ann::refresh_sidecar(
+                    &self.store.conn,
+                    &embedded_ids,
+                    self.ann_index,
+                ) {
+                    Ok(Some(n)) => eprintln!("[codegraph] ANN index holds {n} vectors"),
+                    Ok(None) => {}
+                    Err(e) => eprintln!("[codegraph] WARNING: ANN index update failed: {e}"),
+                }
             }
         }
 
+        self.refresh_commit_index(root);
+
         Ok(IndexResult {
             files_indexed,
             files_skipped: files_skipped.load(Ordering::Relaxed),
@@ -370,8 +593,38 @@ impl<'a> IndexingPipeline<'a> {
         })
     }
 
-    /// Index (or re-index) a single file. Used by the file watcher.
+    /// Index (or re-index) a single file.
     pub fn index_file(&self, file_path: &Path, root_dir: &Path) -> Result<Option<IndexResult>> {
+        let result = self.reindex_file(file_path, root_dir, &WorkspaceLayout::load(root_dir))?;
+        self.link_config_keys()?;
+        Ok(result)
+    }
+
+    /// Index (or re-index) a batch of files, such as one debounce window of
+    /// the file watcher. The workspace layout is loaded, and config keys are
+    /// linked, once for the whole batch; the results follow `files`.
+    pub fn index_files(
+        &self,
+        files: &[PathBuf],
+        root_dir: &Path,
+    ) -> Result<Vec<Result<Option<IndexResult>>>> {
+        let layout = WorkspaceLayout::load(root_dir);
+        let results = files
+            .iter()
+            .map(|file| self.reindex_file(file, root_dir, &layout))
+            .collect();
+        self.link_config_keys()?;
+        Ok(results)
+    }
+
+    /// [`index_file`](Self::index_file) against an already loaded layout,
+    /// leaving config keys for the caller to link.
+    fn reindex_file(
+        &self,
+        file_path: &Path,
+        root_dir: &Path,
+        layout: &WorkspaceLayout,
+    ) -> Result<Option<IndexResult>> {
         let start = Instant::now();
 
         let abs_path = if file_path.is_absolute() {
@@ -379,6 +632,24 @@ impl<'a> IndexingPipeline<'a> {
         } else {
             root_dir.join(file_path)
         };
+        let rel_path = abs_path
+            .strip_prefix(root_dir)
+            .unwrap_or(&abs_path)
+            .to_string_lossy()
+            .to_string();
+
+        // Config files hold keys rather than code.
+        if ConfigFormat::from_path(&rel_path).is_some() {
+            self.store
+                .replace_config_keys_for_file(&rel_path, &read_config_keys(root_dir, &abs_path))?;
+            return Ok(Some(IndexResult {
+                files_indexed: 1,
+                files_skipped: 0,
+                nodes_created: 0,
+                edges_created: 0,
+                duration_ms: start.elapsed().as_millis(),
+            }));
+        }
 
         let language = match CodeParser::detect_language(&abs_path.to_string_lossy()) {
             Some(l) => l,
@@ -393,15 +664,14 @@ impl<'a> IndexingPipeline<'a> {
         let source_text = fs::read_to_string(&abs_path).map_err(CodeGraphError::Io)?;
         let content_hash = sha256_hex(&source_text);
 
-        let rel_path = abs_path
-            .strip_prefix(root_dir)
-            .unwrap_or(&abs_path)
-            .to_string_lossy()
-            .to_string();
-
-        let parser = CodeParser::new();
-        let tree = parser.parse(&source_text, language)?;
-        let nodes = Extractor::extract_nodes(&tree, &rel_path, language, &source_text)?;
+        let Some(extraction) =
+            fallback::extract_nodes(&self.parsers, &rel_path, language, &source_text)
+        else {
+            // Every stage failed: surface the primary parser's error.
+            CodeParser::new().parse(&source_text, language)?;
+            return Ok(None);
+        };
+        let nodes = extraction.nodes;
 
         // Build node index: existing DB nodes + this file's new nodes
         let existing = self.store.get_all_nodes()?;
@@ -414,14 +684,19 @@ impl<'a> IndexingPipeline<'a> {
         }
         let node_index = build_node_index(&all_nodes);
 
-        let mut edges = Extractor::extract_edges(
-            &tree,
-            &rel_path,
-            language,
-            &source_text,
-            &nodes,
-            &node_index,
-        )?;
+        let mut edges = if extraction.stage == ParserStage::TreeSitter {
+            let tree = CodeParser::new().parse(&source_text, language)?;
+            Extractor::extract_edges(
+                &tree,
+                &rel_path,
+                language,
+                &source_text,
+                &nodes,
+                &node_index,
+            )?
+        } else {
+            Vec::new()
+        };
 
         // Cross-file import resolution for single file re-index
         let mut indexed_files: HashSet<String> =
@@ -438,7 +713,7 @@ impl<'a> IndexingPipeline<'a> {
         nodes_by_file.insert(rel_path.clone(), nodes.clone());
 
         let resolution_result =
-            resolve_imports(&edges, &indexed_files, &node_index, &nodes_by_file);
+            resolve_workspace_imports(&edges, &indexed_files, &node_index, &nodes_by_file, layout);
         edges.extend(resolution_result.resolved_edges);
 
         // Clear and persist unresolved refs for this file
@@ -453,8 +728,44 @@ impl<'a> IndexingPipeline<'a> {
             )?;
         }
 
+        let stored_hashes: HashMap<String, String> =
+            self.load_file_hash(&rel_path)?.into_iter().collect();
+        let kept_git_meta = self.unchanged_git_meta(
+            [(rel_path.as_str(), content_hash.as_str())].into_iter(),
+            &stored_hashes,
+        )?;
         self.store.replace_file_data(&rel_path, &nodes, &edges)?;
-        self.upsert_file_hash(&rel_path, &content_hash, language)?;
+        if extraction.stage != ParserStage::TreeSitter {
+            self.store
+                .record_parser(&rel_path, extraction.stage.as_str())?;
+        }
+        self.store.replace_config_refs_for_file(
+            &rel_path,
+            &ConfigAccessorMatcher::shared().find_refs(&nodes),
+        )?;
+        self.store
+            .replace_wire_fields_for_file(&rel_path, &extract_wire_fields(&source_text, &nodes))?;
+        self.store.replace_side_effects_for_file(
+            &rel_path,
+            &extract_side_effects(&rel_path, language, &source_text),
+        )?;
+        self.store.replace_literals_for_file(
+            &rel_path,
+            &extract_literals(&rel_path, language, &source_text, &nodes),
+        )?;
+        self.upsert_file_hash(
+            &rel_path,
+            &content_hash,
+            language,
+            &count_lines(&source_text, language),
+        )?;
+        match kept_git_meta.get(&rel_path) {
+            Some(meta) => self.store.replace_node_git_meta_for_file(&rel_path, meta)?,
+            None => {
+                self.refresh_node_git_meta(root_dir, &[(rel_path.as_str(), nodes.as_slice())])?
+            }
+        }
+        refresh_dir_stats_for(&self.store.conn, &[rel_path.as_str()])?;
 
         Ok(Some(IndexResult {
             files_indexed: 1,
@@ -465,13 +776,173 @@ impl<'a> IndexingPipeline<'a> {
         }))
     }
 
+    /// Rebuild the config key nodes and their `Reads` edges from the stored
+    /// keys and reads.
+    fn link_config_keys(&self) -> Result<()> {
+        let (nodes, edges) = config_key_graph(
+            &self.store.get_config_keys()?,
+            &self.store.get_config_refs()?,
+        );
+        self.store.replace_config_key_graph(&nodes, &edges)
+    }
+
     /// Remove a file from the index entirely.
     pub fn remove_file(&self, relative_path: &str) -> Result<()> {
+        self.unindex_file(relative_path)?;
+        self.link_config_keys()
+    }
+
+    /// [`remove_file`](Self::remove_file), leaving config keys for the
+    /// caller to link.
+    fn unindex_file(&self, relative_path: &str) -> Result<()> {
         self.store.delete_file_nodes(relative_path)?;
         self.delete_file_hash(relative_path)?;
+        refresh_dir_stats_for(&self.store.conn, &[relative_path])?;
+        Ok(())
+    }
+
+    /// Re-index only `changed` files and drop `deleted` ones, e.g. the set
+    /// reported by [`crate::git::changed_files_since`] against a restored
+    /// index. Paths are relative to `root`; unsupported files and files a
+    /// full index would not walk into (see [`ALWAYS_SKIP_DIRS`]) are skipped.
+    pub fn index_changed(
+        &self,
+        root: &Path,
+        changed: &[String],
+        deleted: &[String],
+    ) -> Result<IndexResult> {
+        let start = Instant::now();
+        let mut total = IndexResult {
+            files_indexed: 0,
+            files_skipped: 0,
+            nodes_created: 0,
+            edges_created: 0,
+            duration_ms: 0,
+        };
+        for path in deleted {
+            self.unindex_file(path)?;
+        }
+        let layout = WorkspaceLayout::load(root);
+        for path in changed {
+            let abs = root.join(path);
+            if !abs.is_file() || !(is_walked(path) || is_walked_config(path)) {
+                total.files_skipped += 1;
+                continue;
+            }
+            match self.reindex_file(&abs, root, &layout)? {
+                Some(result) => {
+                    total.files_indexed += result.files_indexed;
+                    total.nodes_created += result.nodes_created;
+                    total.edges_created += result.edges_created;
+                }
+                None => total.files_skipped += 1,
+            }
+        }
+        self.link_config_keys()?;
+        if total.files_indexed > 0 || !deleted.is_empty() {
+            refresh_node_rank(self.store)?;
+        }
+        total.duration_ms = start.elapsed().as_millis();
+        Ok(total)
+    }
+
+    /// Copy the rows of every file under `root` that is unchanged since it
+    /// was indexed into `old_db`, so a following incremental index only
+    /// parses new and modified files. See [`crate::db::warm_start`].
+    pub fn warm_start(&self, old_db: &Path, root: &Path) -> Result<WarmStartStats> {
+        let current = self.load_all_file_hashes();
+        let old = warm_start::old_file_hashes(old_db)?;
+        let unchanged: Vec<String> = old
+            .into_par_iter()
+            .filter(|(path, hash)| {
+                if current.get(path) == Some(hash) {
+                    return false;
+                }
+                let abs = root.join(path);
+                let small = fs::metadata(&abs).is_ok_and(|m| m.len() <= MAX_FILE_SIZE);
+                small && fs::read_to_string(&abs).is_ok_and(|text| sha256_hex(&text) == *hash)
+            })
+            .map(|(path, _)| path)
+            .collect();
+
+        let stats = warm_start::copy_files(&self.store.conn, old_db, &unchanged)?;
+        let paths: Vec<&str> = unchanged.iter().map(String::as_str).collect();
+        refresh_dir_stats_for(&self.store.conn, &paths)?;
+        Ok(stats)
+    }
+
+    // -----------------------------------------------------------------------
+    // Symbol-level git metadata
+    // -----------------------------------------------------------------------
+
+    /// Blame each file once and store last-commit metadata for its nodes.
+    ///
+    /// A no-op outside a git repository or with blame metadata turned off.
+    /// Files git cannot blame (untracked, ignored) simply end up with no
+    /// metadata.
+    fn refresh_node_git_meta(&self, root: &Path, files: &[(&str, &[CodeNode])]) -> Result<()> {
+        if !self.blame_metadata || files.is_empty() || ensure_git_repo(root).is_err() {
+            return Ok(());
+        }
+        let now = chrono::Utc::now().timestamp();
+        let metas: Vec<_> = files
+            .par_iter()
+            .map(|(path, nodes)| {
+                (
+                    *path,
+                    compute_node_git_meta(root, path, nodes, now).unwrap_or_default(),
+                )
+            })
+            .collect();
+        for (path, meta) in metas {
+            self.store.replace_node_git_meta_for_file(path, &meta)?;
+        }
         Ok(())
     }
 
+    /// Stored git metadata of the `(path, content hash)` files whose content
+    /// matches `stored_hashes`, keyed by path, to put back after their nodes
+    /// are replaced instead of blaming them again. Files never blamed are
+    /// left out.
+    fn unchanged_git_meta<'p>(
+        &self,
+        files: impl Iterator<Item = (&'p str, &'p str)>,
+        stored_hashes: &HashMap<String, String>,
+    ) -> Result<HashMap<String, Vec<NodeGitMeta>>> {
+        let mut kept = HashMap::new();
+        if !self.blame_metadata {
+            return Ok(kept);
+        }
+        for (path, hash) in files {
+            if stored_hashes.get(path).map(String::as_str) != Some(hash) {
+                continue;
+            }
+            let meta = self.store.get_node_git_meta_for_file(path)?;
+            if !meta.is_empty() {
+                kept.insert(path.to_string(), meta);
+            }
+        }
+        Ok(kept)
+    }
+
+    /// Embed commits made since the last run for commit search, with the
+    /// preloaded model if there is one, else lexically. A no-op outside a
+    /// git repository or before its first commit; failures only warn.
+    fn refresh_commit_index(&self, root: &Path) {
+        if crate::git::run_git(root, &["rev-parse", "--verify", "-q", "HEAD"]).is_err() {
+            return;
+        }
+        let embedder = match self.embedder {
+            Some(engine) => CommitEmbedder::Model(engine),
+            None => CommitEmbedder::Lexical,
+        };
+        match index_commits(&self.store.conn, root, &embedder, COMMIT_INDEX_LIMIT) {
+            Ok(0) => {}
+            Ok(n) => eprintln!("[codegraph] Embedded {n} commit messages"),
+            Err(e) => eprintln!("[codegraph] WARNING: commit indexing failed: {e}"),
+        }
+    }
+
     // -----------------------------------------------------------------------
     // File hash helpers (incremental indexing)
     // -----------------------------------------------------------------------
@@ -500,26 +971,49 @@ impl<'a> IndexingPipeline<'a> {
         map
     }
 
+    /// The stored content hash of one file, if it was indexed before.
+    fn load_file_hash(&self, file_path: &str) -> Result<Option<(String, String)>> {
+        use rusqlite::OptionalExtension;
+        Ok(self
+            .store
+            .conn
+            .prepare_cached("SELECT file_path, content_hash FROM file_hashes WHERE file_path = ?1")?
+            .query_row([file_path], |row| Ok((row.get(0)?, row.get(1)?)))
+            .optional()?)
+    }
+
     fn upsert_file_hash(
         &self,
         file_path: &str,
         content_hash: &str,
         language: Language,
+        lines: &LineCounts,
     ) -> Result<()> {
         self.store
             .conn
             .prepare_cached(
-                "INSERT INTO file_hashes (file_path, content_hash, language)
-             VALUES (?1, ?2, ?3)
+                "INSERT INTO file_hashes (file_path, content_hash, language, total_lines,
+               code_lines, comment_lines, blank_lines, logical_lines)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
              ON CONFLICT(file_path) DO UPDATE SET
                content_hash = excluded.content_hash,
                indexed_at = datetime('now'),
-               language = excluded.language",
+               language = excluded.language,
+               total_lines = excluded.total_lines,
+               code_lines = excluded.code_lines,
+               comment_lines = excluded.comment_lines,
+               blank_lines = excluded.blank_lines,
+               logical_lines = excluded.logical_lines",
             )?
             .execute(rusqlite::params![
                 file_path,
                 content_hash,
-                language.as_str()
+                language.as_str(),
+                lines.total,
+                lines.code,
+                lines.comment,
+                lines.blank,
+                lines.logical,
             ])?;
         Ok(())
     }
@@ -538,7 +1032,7 @@ impl<'a> IndexingPipeline<'a> {
 // ---------------------------------------------------------------------------
 
 /// Directories that are always skipped, regardless of `.gitignore`.
-const ALWAYS_SKIP_DIRS: &[&str] = &[
+pub(crate) const ALWAYS_SKIP_DIRS: &[&str] = &[
     "node_modules",
     ".git",
     "vendor",
@@ -583,6 +1077,37 @@ fn collect_files(root: &Path) -> Vec<PathBuf> {
     files
 }
 
+/// Whether [`collect_files`] would descend to the relative `path`: no
+/// hidden file or directory and no always-skipped directory on the way.
+fn is_walked(path: &str) -> bool {
+    let names: Vec<_> = Path::new(path)
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect();
+    let Some((_, dirs)) = names.split_last() else {
+        return false;
+    };
+    names.iter().all(|n| !n.starts_with('.'))
+        && dirs.iter().all(|d| !ALWAYS_SKIP_DIRS.contains(&d.as_ref()))
+}
+
+/// Whether [`scan_config_keys`] would read the config file at `path`: as
+/// for [`is_walked`], except that hidden `.env*` files are kept.
+fn is_walked_config(path: &str) -> bool {
+    let names: Vec<_> = Path::new(path)
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect();
+    let Some((file, dirs)) = names.split_last() else {
+        return false;
+    };
+    ConfigFormat::from_path(path).is_some()
+        && (!file.starts_with('.') || file.starts_with(".env"))
+        && dirs
+            .iter()
+            .all(|d| !d.starts_with('.') && !ALWAYS_SKIP_DIRS.contains(&d.as_ref()))
+}
+
 // ---------------------------------------------------------------------------
 // Node index builder
 // ---------------------------------------------------------------------------
@@ -708,6 +1233,63 @@ class Calculator:
         let stats = store.get_stats().unwrap();
         assert_eq!(stats.nodes, result.nodes_created);
         assert_eq!(stats.files, 2);
+
+        // Directory rollups are materialized alongside.
+        let dir_stats = load_dir_stats(&store.conn).unwrap();
+        assert_eq!(dir_stats.iter().map(|d| d.files).sum::<usize>(), 2);
+        assert_eq!(
+            dir_stats.iter().map(|d| d.nodes).sum::<usize>(),
+            result.nodes_created
+        );
+
+        // So are line counts per language.
+        let lines = crate::graph::stats::load_language_lines(&store.conn).unwrap();
+        let py = lines["python"];
+        assert_eq!((py.total, py.code, py.blank, py.comment), (7, 5, 2, 0));
+    }
+
+    #[test]
+    fn fallback_chain_indexes_files_tree_sitter_rejects() {
+        let (tmp, store) = setup_test_project();
+        fs::write(
+            tmp.path().join("broken.py"),
+            "}}} {{{\nfn rescue( {{{\n)))\n",
+        )
+        .unwrap();
+        let mut chains = ParserChains::default();
+        chains.set(
+            Language::Python,
+            vec![ParserStage::TreeSitter, ParserStage::Regex],
+        );
+        let pipeline = IndexingPipeline::new(&store).with_parser_chains(chains);
+        pipeline
+            .index_directory(&IndexOptions {
+                root_dir: tmp.path().to_path_buf(),
+                incremental: false,
+            })
+            .unwrap();
+
+        let rescued = store.get_nodes_by_name("rescue").unwrap();
+        assert_eq!(rescued.len(), 1);
+        assert_eq!(
+            store.get_node_parser(&rescued[0].id).unwrap().as_deref(),
+            Some("regex")
+        );
+        // Files tree-sitter handles keep their edges and no provenance.
+        let helper = &store.get_nodes_by_name("helper").unwrap()[0];
+        assert_eq!(store.get_node_parser(&helper.id).unwrap(), None);
+
+        // The single-file path applies the same chain.
+        fs::write(tmp.path().join("broken.py"), "}}}\nfn rescue2( {{{\n").unwrap();
+        pipeline
+            .index_file(&tmp.path().join("broken.py"), tmp.path())
+            .unwrap()
+            .unwrap();
+        let rescued = store.get_nodes_by_name("rescue2").unwrap();
+        assert_eq!(
+            store.get_node_parser(&rescued[0].id).unwrap().as_deref(),
+            Some("regex")
+        );
     }
 
     #[test]
@@ -771,6 +1353,55 @@ export function greetV2(name: string): string {
         assert_eq!(r2.files_skipped, 1);
     }
 
+    #[test]
+    fn warm_start_copies_unchanged_files_from_old_database() {
+        let (tmp, _store) = setup_test_project();
+        let db_dir = tempfile::tempdir().unwrap();
+        let old_db = db_dir.path().join("old.db");
+        {
+            let old =
+                GraphStore::from_connection(initialize_database(old_db.to_str().unwrap()).unwrap());
+            IndexingPipeline::new(&old)
+                .index_directory(&IndexOptions {
+                    root_dir: tmp.path().to_path_buf(),
+                    incremental: false,
+                })
+                .unwrap();
+        }
+        fs::write(
+            tmp.path().join("util.py"),
+            "def helper_v2():\n    return 7\n",
+        )
+        .unwrap();
+
+        let store = GraphStore::from_connection(initialize_database(":memory:").unwrap());
+        let pipeline = IndexingPipeline::new(&store);
+        let copied = pipeline.warm_start(&old_db, tmp.path()).unwrap();
+        assert_eq!(copied.files, 1);
+        assert!(copied.nodes > 0);
+
+        let result = pipeline
+            .index_directory(&IndexOptions {
+                root_dir: tmp.path().to_path_buf(),
+                incremental: true,
+            })
+            .unwrap();
+        assert_eq!(result.files_indexed, 1);
+        assert_eq!(result.files_skipped, 1);
+
+        let names: Vec<String> = store
+            .conn
+            .prepare("SELECT name FROM nodes")
+            .unwrap()
+            .query_map([], |r| r.get(0))
+            .unwrap()
+            .collect::<std::result::Result<_, _>>()
+            .unwrap();
+        assert!(names.iter().any(|n| n == "greet"));
+        assert!(names.iter().any(|n| n == "helper_v2"));
+        assert!(!names.iter().any(|n| n == "Calculator"));
+    }
+
     #[test]
     fn index_single_file() {
         let (tmp, store) = setup_test_project();
@@ -907,4 +1538,347 @@ function processInput(input: string): string {
         assert!(after.nodes < before.nodes);
         assert_eq!(after.files, 1); // only util.py remains
     }
+
+    #[test]
+    fn index_directory_records_config_keys_and_reads() {
+        let (tmp, store) = setup_test_project();
+        fs::write(tmp.path().join(".env"), "DB_HOST=localhost\n").unwrap();
+        fs::write(
+            tmp.path().join("settings.py"),
+            "import os\n\ndef connect():\n    return os.getenv(\"DB_HOST\")\n",
+        )
+        .unwrap();
+
+        let pipeline = IndexingPipeline::new(&store);
+        pipeline
+            .index_directory(&IndexOptions {
+                root_dir: tmp.path().to_path_buf(),
+                incremental: false,
+            })
+            .unwrap();
+
+        let keys = store.get_config_keys().unwrap();
+        assert!(keys
+            .iter()
+            .any(|k| k.key == "DB_HOST" && k.file_path == ".env"));
+
+        let refs = store.get_config_refs().unwrap();
+        let read = refs.iter().find(|r| r.key == "DB_HOST").unwrap();
+        assert_eq!(read.file_path, "settings.py");
+        assert_eq!(read.line, 4);
+    }
+
+    #[test]
+    fn config_keys_become_nodes_linked_from_their_readers() {
+        let (tmp, store) = setup_test_project();
+        fs::write(tmp.path().join(".env"), "DB_HOST=localhost\nUNUSED=1\n").unwrap();
+        let settings = tmp.path().join("settings.py");
+        fs::write(
+            &settings,
+            "import os\n\ndef connect():\n    return os.getenv(\"DB_HOST\")\n",
+        )
+        .unwrap();
+
+        let pipeline = IndexingPipeline::new(&store);
+        pipeline
+            .index_directory(&IndexOptions {
+                root_dir: tmp.path().to_path_buf(),
+                incremental: false,
+            })
+            .unwrap();
+
+        let keys = store.get_nodes_by_type("config_key").unwrap();
+        assert_eq!(keys.len(), 1, "only read keys become nodes: {keys:?}");
+        assert_eq!(keys[0].name, "DB_HOST");
+        assert_eq!(keys[0].file_path, ".env");
+
+        // Re-indexing the reader drops its edges; they must be re-linked.
+        fs::write(
+            &settings,
+            "import os\n\n\ndef connect():\n    return os.getenv(\"DB_HOST\")\n",
+        )
+        .unwrap();
+        pipeline.index_file(&settings, tmp.path()).unwrap();
+        let reads = store.get_in_edges(&keys[0].id, Some("reads")).unwrap();
+        assert_eq!(reads.len(), 1);
+        assert_eq!(reads[0].file_path, "settings.py");
+        assert_eq!(reads[0].line, 5);
+    }
+
+    #[test]
+    fn index_directory_records_wire_fields() {
+        let (tmp, store) = setup_test_project();
+        fs::write(
+            tmp.path().join("order.go"),
+            "package model\n\ntype Order struct {\n\tID int `json:\"order_id\"`\n}\n",
+        )
+        .unwrap();
+
+        let pipeline = IndexingPipeline::new(&store);
+        pipeline
+            .index_directory(&IndexOptions {
+                root_dir: tmp.path().to_path_buf(),
+                incremental: false,
+            })
+            .unwrap();
+
+        let order = store
+            .get_nodes_by_file("order.go")
+            .unwrap()
+            .into_iter()
+            .find(|n| n.name == "Order")
+            .unwrap();
+        let fields = store.get_wire_fields(&order.id).unwrap();
+        assert_eq!(fields.len(), 1);
+        assert_eq!(fields[0].wire_name, "order_id");
+    }
+
+    #[test]
+    fn index_directory_records_node_git_meta_in_git_repo() {
+        let (tmp, store) = setup_test_project();
+        let git = |args: &[&str]| {
+            std::process::Command::new("git")
+                .args(args)
+                .current_dir(tmp.path())
+                .env("GIT_AUTHOR_NAME", "Test Author")
+                .env("GIT_AUTHOR_EMAIL", "test@example.com")
+                .env("GIT_COMMITTER_NAME", "Test Author")
+                .env("GIT_COMMITTER_EMAIL", "test@example.com")
+                .output()
+                .unwrap()
+        };
+        git(&["init"]);
+        git(&["add", "."]);
+        git(&["commit", "-m", "initial"]);
+
+        let pipeline = IndexingPipeline::new(&store);
+        pipeline
+            .index_directory(&IndexOptions {
+                root_dir: tmp.path().to_path_buf(),
+                incremental: false,
+            })
+            .unwrap();
+
+        let greet = store
+            .get_nodes_by_name("greet")
+            .unwrap()
+            .into_iter()
+            .find(|n| n.file_path == "hello.ts")
+            .unwrap();
+        let meta = store.get_node_git_meta(&greet.id).unwrap().unwrap();
+        assert_eq!(meta.author, "Test Author");
+        assert_eq!(meta.commit_hash.len(), 40);
+        assert_eq!(meta.age_days, 0);
+    }
+
+    #[test]
+    fn forced_reindex_keeps_git_meta_of_unchanged_files() {
+        let (tmp, store) = setup_test_project();
+        let git = |args: &[&str]| {
+            std::process::Command::new("git")
+                .args(args)
+                .current_dir(tmp.path())
+                .env("GIT_AUTHOR_NAME", "Test Author")
+                .env("GIT_AUTHOR_EMAIL", "test@example.com")
+                .env("GIT_COMMITTER_NAME", "Test Author")
+                .env("GIT_COMMITTER_EMAIL", "test@example.com")
+                .output()
+                .unwrap()
+        };
+        git(&["init"]);
+        git(&["add", "."]);
+        git(&["commit", "-m", "initial"]);
+
+        let pipeline = IndexingPipeline::new(&store);
+        let options = IndexOptions {
+            root_dir: tmp.path().to_path_buf(),
+            incremental: false,
+        };
+        pipeline.index_directory(&options).unwrap();
+        // Marks metadata that only survives if the file is not blamed again.
+        store
+            .conn
+            .execute("UPDATE node_git_meta SET author = 'Kept'", [])
+            .unwrap();
+        pipeline.index_directory(&options).unwrap();
+
+        let greet = store
+            .get_nodes_by_name("greet")
+            .unwrap()
+            .into_iter()
+            .find(|n| n.file_path == "hello.ts")
+            .unwrap();
+        let meta = store.get_node_git_meta(&greet.id).unwrap().unwrap();
+        assert_eq!(meta.author, "Kept");
+
+        fs::write(
+            tmp.path().join("hello.ts"),
+            "export function greet(name: string): string {\n  return `Hi ${name}`;\n}\n",
+        )
+        .unwrap();
+        pipeline.index_directory(&options).unwrap();
+        let greet = store
+            .get_nodes_by_name("greet")
+            .unwrap()
+            .into_iter()
+            .find(|n| n.file_path == "hello.ts")
+            .unwrap();
+        let meta = store.get_node_git_meta(&greet.id).unwrap().unwrap();
+        assert_ne!(meta.author, "Kept");
+    }
+
+    #[test]
+    fn blame_metadata_off_records_no_git_meta() {
+        let (tmp, store) = setup_test_project();
+        let git = |args: &[&str]| {
+            std::process::Command::new("git")
+                .args(args)
+                .current_dir(tmp.path())
+                .env("GIT_AUTHOR_NAME", "Test Author")
+                .env("GIT_AUTHOR_EMAIL", "test@example.com")
+                .env("GIT_COMMITTER_NAME", "Test Author")
+                .env("GIT_COMMITTER_EMAIL", "test@example.com")
+                .output()
+                .unwrap()
+        };
+        git(&["init"]);
+        git(&["add", "."]);
+        git(&["commit", "-m", "initial"]);
+
+        let pipeline = IndexingPipeline::new(&store).with_blame_metadata(false);
+        pipeline
+            .index_directory(&IndexOptions {
+                root_dir: tmp.path().to_path_buf(),
+                incremental: false,
+            })
+            .unwrap();
+        let count: i64 = store
+            .conn
+            .query_row("SELECT COUNT(*) FROM node_git_meta", [], |r| r.get(0))
+            .unwrap();
+        assert_eq!(count, 0);
+    }
+
+    #[test]
+    fn index_directory_outside_git_records_no_git_meta() {
+        let (tmp, store) = setup_test_project();
+        let pipeline = IndexingPipeline::new(&store);
+        pipeline
+            .index_directory(&IndexOptions {
+                root_dir: tmp.path().to_path_buf(),
+                incremental: false,
+            })
+            .unwrap();
+        let count: i64 = store
+            .conn
+            .query_row("SELECT COUNT(*) FROM node_git_meta", [], |r| r.get(0))
+            .unwrap();
+        assert_eq!(count, 0);
+    }
+
+    #[test]
+    fn index_changed_reindexes_only_the_given_files() {
+        let (tmp, store) = setup_test_project();
+        let pipeline = IndexingPipeline::new(&store);
+        pipeline
+            .index_directory(&IndexOptions {
+                root_dir: tmp.path().to_path_buf(),
+                incremental: false,
+            })
+            .unwrap();
+
+        fs::write(tmp.path().join("hello.ts"), "export function wave() {}\n").unwrap();
+        fs::remove_file(tmp.path().join("util.py")).unwrap();
+        fs::create_dir_all(tmp.path().join("vendor")).unwrap();
+        fs::write(
+            tmp.path().join("vendor/dep.ts"),
+            "export function dep() {}\n",
+        )
+        .unwrap();
+
+        let result = pipeline
+            .index_changed(
+                tmp.path(),
+                &["hello.ts".to_string(), "vendor/dep.ts".to_string()],
+                &["util.py".to_string()],
+            )
+            .unwrap();
+        assert_eq!(result.files_indexed, 1);
+        assert_eq!(result.files_skipped, 1);
+        assert!(store.get_nodes_by_name("greet").unwrap().is_empty());
+        assert_eq!(store.get_nodes_by_name("wave").unwrap().len(), 1);
+        assert!(store.get_nodes_by_name("helper").unwrap().is_empty());
+        assert!(store.get_nodes_by_name("dep").unwrap().is_empty());
+    }
+
+    #[test]
+    fn index_files_reindexes_a_batch_in_order() {
+        let (tmp, store) = setup_test_project();
+        let pipeline = IndexingPipeline::new(&store);
+        fs::write(tmp.path().join("hello.ts"), "export function wave() {}\n").unwrap();
+        fs::write(tmp.path().join("notes.txt"), "not code\n").unwrap();
+
+        let results = pipeline
+            .index_files(
+                &[tmp.path().join("notes.txt"), tmp.path().join("hello.ts")],
+                tmp.path(),
+            )
+            .unwrap();
+        assert_eq!(results.len(), 2);
+        assert!(results[0].as_ref().unwrap().is_none());
+        assert_eq!(
+            results[1].as_ref().unwrap().as_ref().unwrap().files_indexed,
+            1
+        );
+        assert_eq!(store.get_nodes_by_name("wave").unwrap().len(), 1);
+    }
+
+    #[test]
+    fn index_changed_refreshes_config_keys() {
+        let (tmp, store) = setup_test_project();
+        fs::write(tmp.path().join(".env"), "DB_HOST=localhost\n").unwrap();
+        fs::write(tmp.path().join("app.yaml"), "server:\n  port: 80\n").unwrap();
+        let pipeline = IndexingPipeline::new(&store);
+        pipeline
+            .index_directory(&IndexOptions {
+                root_dir: tmp.path().to_path_buf(),
+                incremental: false,
+            })
+            .unwrap();
+
+        fs::write(tmp.path().join(".env"), "DB_HOST=localhost\nDB_PORT=5432\n").unwrap();
+        fs::remove_file(tmp.path().join("app.yaml")).unwrap();
+        let result = pipeline
+            .index_changed(tmp.path(), &[".env".to_string()], &["app.yaml".to_string()])
+            .unwrap();
+        assert_eq!(result.files_indexed, 1);
+
+        let keys: Vec<String> = store
+            .get_config_keys()
+            .unwrap()
+            .into_iter()
+            .map(|k| k.key)
+            .collect();
+        assert_eq!(keys, vec!["DB_HOST", "DB_PORT"]);
+    }
+
+    #[test]
+    fn is_walked_config_keeps_env_files() {
+        assert!(is_walked_config(".env"));
+        assert!(is_walked_config("config/.env.local"));
+        assert!(is_walked_config("config/app.yaml"));
+        assert!(!is_walked_config(".github/workflows/ci.yml"));
+        assert!(!is_walked_config("node_modules/x/package.json"));
+        assert!(!is_walked_config("src/lib.rs"));
+    }
+
+    #[test]
+    fn is_walked_matches_the_directory_walker() {
+        assert!(is_walked("src/lib.rs"));
+        assert!(!is_walked("node_modules/x/index.js"));
+        assert!(!is_walked("src/.hidden/a.ts"));
+        assert!(!is_walked(".eslintrc.js"));
+        // Skipped names only apply to directories.
+        assert!(is_walked("src/build.rs"));
+    }
 }
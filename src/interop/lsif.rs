@@ -0,0 +1,218 @@
+//! LSIF dump parser.
+//!
+//! LSIF is a graph of vertices and edges, one JSON object per line (older
+//! tools emit a single JSON array). The parts used here:
+//!
+//! - `document` vertices carry a `uri`; `contains` edges attach `range`s.
+//! - `next` edges chain ranges to `resultSet`s.
+//! - `textDocument/definition` edges lead to a `definitionResult`, whose
+//!   `item` edges list the defining ranges.
+//!
+//! Every range whose result set resolves to a definition elsewhere becomes a
+//! [`PreciseReference`].
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use serde_json::Value;
+
+use super::PreciseReference;
+use crate::error::{CodeGraphError, Result};
+
+/// Parse an LSIF dump into `(document count, references)`.
+///
+/// Document URIs are made relative to `project_root` (or to the dump's
+/// `metaData.projectRoot` when present).
+pub fn parse_lsif(text: &str, project_root: &Path) -> Result<(usize, Vec<PreciseReference>)> {
+    let elements: Vec<Value> = if text.trim_start().starts_with('[') {
+        serde_json::from_str(text)?
+    } else {
+        text.lines()
+            .filter(|l| !l.trim().is_empty())
+            .map(serde_json::from_str)
+            .collect::<std::result::Result<_, _>>()?
+    };
+
+    let mut root_uri = format!(
+        "file://{}",
+        project_root.to_string_lossy().trim_end_matches('/')
+    );
+    let mut documents: HashMap<String, String> = HashMap::new();
+    let mut ranges: HashMap<String, u32> = HashMap::new();
+    let mut range_doc: HashMap<String, String> = HashMap::new();
+    let mut next: HashMap<String, String> = HashMap::new();
+    let mut definition_result: HashMap<String, String> = HashMap::new();
+    let mut items: HashMap<String, Vec<String>> = HashMap::new();
+
+    for el in &elements {
+        let id = element_id(el.get("id"));
+        let label = el.get("label").and_then(Value::as_str).unwrap_or_default();
+        match (el.get("type").and_then(Value::as_str), label) {
+            (Some("vertex"), "metaData") => {
+                if let Some(root) = el.get("projectRoot").and_then(Value::as_str) {
+                    root_uri = root.trim_end_matches('/').to_string();
+                }
+            }
+            (Some("vertex"), "document") => {
+                let uri = el.get("uri").and_then(Value::as_str).unwrap_or_default();
+                documents.insert(id, uri.to_string());
+            }
+            (Some("vertex"), "range") => {
+                let line = el
+                    .pointer("/start/line")
+                    .and_then(Value::as_u64)
+                    .ok_or_else(|| {
+                        CodeGraphError::Other(format!("LSIF range {id} has no start"))
+                    })?;
+                ranges.insert(id, line as u32 + 1);
+            }
+            (Some("edge"), "contains") => {
+                let out_v = element_id(el.get("outV"));
+                for in_v in in_vertices(el) {
+                    range_doc.insert(in_v, out_v.clone());
+                }
+            }
+            (Some("edge"), "next") => {
+                next.insert(element_id(el.get("outV")), element_id(el.get("inV")));
+            }
+            (Some("edge"), "textDocument/definition") => {
+                definition_result.insert(element_id(el.get("outV")), element_id(el.get("inV")));
+            }
+            (Some("edge"), "item") => {
+                items
+                    .entry(element_id(el.get("outV")))
+                    .or_default()
+                    .extend(in_vertices(el));
+            }
+            _ => {}
+        }
+    }
+
+    let relative = |doc_id: &str| -> Option<String> {
+        let uri = documents.get(doc_id)?;
+        let rel = uri
+            .strip_prefix(&root_uri)
+            .map(|p| p.trim_start_matches('/'))
+            .unwrap_or(uri);
+        Some(rel.to_string())
+    };
+
+    // Follow `next` edges from a range to the result set holding its
+    // definition, guarding against malformed cycles.
+    let definition_of = |range_id: &str| -> Option<&Vec<String>> {
+        let mut current = range_id;
+        for _ in 0..16 {
+            if let Some(result) = definition_result.get(current) {
+                return items.get(result);
+            }
+            current = next.get(current)?;
+        }
+        None
+    };
+
+    let mut refs = Vec::new();
+    let mut range_ids: Vec<&String> = ranges.keys().collect();
+    range_ids.sort();
+    for range_id in range_ids {
+        let Some(defs) = definition_of(range_id) else {
+            continue;
+        };
+        // A range that is itself a definition is not a reference.
+        if defs.contains(range_id) {
+            continue;
+        }
+        let (Some(file), Some(&line)) = (
+            range_doc.get(range_id).and_then(|d| relative(d)),
+            ranges.get(range_id),
+        ) else {
+            continue;
+        };
+        for def in defs {
+            let (Some(def_file), Some(&def_line)) = (
+                range_doc.get(def).and_then(|d| relative(d)),
+                ranges.get(def),
+            ) else {
+                continue;
+            };
+            refs.push(PreciseReference {
+                file_path: file.clone(),
+                line,
+                definition_file: def_file,
+                definition_line: def_line,
+            });
+        }
+    }
+
+    Ok((documents.len(), refs))
+}
+
+/// LSIF IDs may be numbers or strings; normalise to strings.
+fn element_id(v: Option<&Value>) -> String {
+    match v {
+        Some(Value::String(s)) => s.clone(),
+        Some(other) => other.to_string(),
+        None => String::new(),
+    }
+}
+
+fn in_vertices(el: &Value) -> Vec<String> {
+    match el.get("inVs").and_then(Value::as_array) {
+        Some(vs) => vs.iter().map(|v| element_id(Some(v))).collect(),
+        None => el
+            .get("inV")
+            .map(|v| vec![element_id(Some(v))])
+            .unwrap_or_default(),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DUMP: &str = r#"
+{"id":1,"type":"vertex","label":"metaData","version":"0.5.0","projectRoot":"file:///repo"}
+{"id":2,"type":"vertex","label":"document","uri":"file:///repo/src/lib.ts","languageId":"typescript"}
+{"id":3,"type":"vertex","label":"document","uri":"file:///repo/src/app.ts","languageId":"typescript"}
+{"id":4,"type":"vertex","label":"range","start":{"line":2,"character":16},"end":{"line":2,"character":21}}
+{"id":5,"type":"vertex","label":"range","start":{"line":6,"character":2},"end":{"line":6,"character":7}}
+{"id":6,"type":"vertex","label":"resultSet"}
+{"id":7,"type":"vertex","label":"definitionResult"}
+{"id":8,"type":"edge","label":"contains","outV":2,"inVs":[4]}
+{"id":9,"type":"edge","label":"contains","outV":3,"inVs":[5]}
+{"id":10,"type":"edge","label":"next","outV":4,"inV":6}
+{"id":11,"type":"edge","label":"next","outV":5,"inV":6}
+{"id":12,"type":"edge","label":"textDocument/definition","outV":6,"inV":7}
+{"id":13,"type":"edge","label":"item","outV":7,"inVs":[4],"document":2}
+"#;
+
+    #[test]
+    fn resolves_reference_through_result_set() {
+        let (docs, refs) = parse_lsif(DUMP, Path::new("/elsewhere")).unwrap();
+        assert_eq!(docs, 2);
+        assert_eq!(
+            refs,
+            vec![PreciseReference {
+                file_path: "src/app.ts".into(),
+                line: 7,
+                definition_file: "src/lib.ts".into(),
+                definition_line: 3,
+            }]
+        );
+    }
+
+    #[test]
+    fn accepts_json_array_form() {
+        let array = format!("[{}]", DUMP.trim().lines().collect::<Vec<_>>().join(","));
+        let (_, refs) = parse_lsif(&array, Path::new("/repo")).unwrap();
+        assert_eq!(refs.len(), 1);
+    }
+
+    #[test]
+    fn invalid_json_is_an_error() {
+        assert!(parse_lsif("{not json", Path::new("/")).is_err());
+    }
+}
@@ -0,0 +1,287 @@
+//! Interop with other code indexers.
+//!
+//! Imports precise cross-references from compiler-backed indexers and merges
+//! them into the CodeGraph schema as `references` edges:
+//!
+//! - [`scip`] — SCIP indexes (`index.scip`, protobuf) from scip-typescript,
+//!   scip-java, rust-analyzer, ...
+//! - [`lsif`] — LSIF dumps (`dump.lsif`, JSON lines) from older indexers.
+//!
+//! Both parsers reduce their input to [`PreciseReference`]s — a use site and
+//! the location of its definition — which [`merge_references`] maps onto the
+//! tightest enclosing graph nodes. Edges carry `precise = <format>` metadata
+//! so they can be told apart from tree-sitter heuristics. Re-indexing a file
+//! replaces its edges, so imports should be re-run after a full index.
+
+pub mod lsif;
+pub mod scip;
+
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use crate::error::{CodeGraphError, Result};
+use crate::graph::store::GraphStore;
+use crate::types::{CodeEdge, CodeNode, EdgeKind};
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// Supported external index formats.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IndexFormat {
+    Scip,
+    Lsif,
+}
+
+impl IndexFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Scip => "scip",
+            Self::Lsif => "lsif",
+        }
+    }
+
+    pub fn from_str_loose(s: &str) -> Option<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "scip" => Some(Self::Scip),
+            "lsif" => Some(Self::Lsif),
+            _ => None,
+        }
+    }
+
+    /// Guess the format from the file name, falling back to the content:
+    /// LSIF is JSON text, SCIP is binary protobuf.
+    pub fn detect(path: &Path, bytes: &[u8]) -> Self {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("scip") => Self::Scip,
+            Some("lsif") | Some("json") | Some("jsonl") => Self::Lsif,
+            _ => match bytes.iter().find(|b| !b.is_ascii_whitespace()) {
+                Some(b'{') | Some(b'[') => Self::Lsif,
+                _ => Self::Scip,
+            },
+        }
+    }
+}
+
+/// A use site and the definition it resolves to, both 1-based lines in
+/// project-relative files.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PreciseReference {
+    pub file_path: String,
+    pub line: u32,
+    pub definition_file: String,
+    pub definition_line: u32,
+}
+
+/// Outcome of an import.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImportStats {
+    /// Documents present in the external index.
+    pub documents: usize,
+    /// Cross-references found in the external index.
+    pub references: usize,
+    /// New edges written to the graph.
+    pub edges_added: usize,
+    /// Edges the graph already had (from tree-sitter extraction).
+    pub edges_confirmed: usize,
+    /// References whose use site or definition maps to no graph node.
+    pub unmatched: usize,
+}
+
+// ---------------------------------------------------------------------------
+// Entry point
+// ---------------------------------------------------------------------------
+
+/// Parse an external index file and merge its references into `store`.
+///
+/// `project_root` is used to relativise absolute document URIs (LSIF).
+pub fn import_index(
+    store: &GraphStore,
+    path: &Path,
+    format: Option<IndexFormat>,
+    project_root: &Path,
+) -> Result<ImportStats> {
+    let bytes = std::fs::read(path)?;
+    let format = format.unwrap_or_else(|| IndexFormat::detect(path, &bytes));
+    let (documents, refs) = match format {
+        IndexFormat::Scip => scip::parse_scip(&bytes)?,
+        IndexFormat::Lsif => {
+            let text = String::from_utf8(bytes)
+                .map_err(|e| CodeGraphError::Other(format!("LSIF dump is not UTF-8: {e}")))?;
+            lsif::parse_lsif(&text, project_root)?
+        }
+    };
+    let mut stats = merge_references(store, &refs, format)?;
+    stats.documents = documents;
+    Ok(stats)
+}
+
+/// Map references onto graph nodes and upsert `references` edges.
+pub fn merge_references(
+    store: &GraphStore,
+    refs: &[PreciseReference],
+    format: IndexFormat,
+) -> Result<ImportStats> {
+    let mut stats = ImportStats {
+        references: refs.len(),
+        ..ImportStats::default()
+    };
+    let mut nodes_by_file: HashMap<String, Vec<CodeNode>> = HashMap::new();
+    let mut new_edges: Vec<CodeEdge> = Vec::new();
+    let mut seen: HashSet<(String, String)> = HashSet::new();
+
+    for r in refs {
+        for file in [&r.file_path, &r.definition_file] {
+            if !nodes_by_file.contains_key(file) {
+                nodes_by_file.insert(file.clone(), store.get_nodes_by_file(file)?);
+            }
+        }
+        let source = enclosing_node(&nodes_by_file[&r.file_path], r.line);
+        let target = definition_node(&nodes_by_file[&r.definition_file], r.definition_line);
+        let (Some(source), Some(target)) = (source, target) else {
+            stats.unmatched += 1;
+            continue;
+        };
+        if source.id == target.id || !seen.insert((source.id.clone(), target.id.clone())) {
+            continue;
+        }
+
+        let existing = store.get_out_edges(&source.id, None)?;
+        if existing.iter().any(|e| e.target == target.id) {
+            stats.edges_confirmed += 1;
+            continue;
+        }
+        new_edges.push(CodeEdge {
+            source: source.id.clone(),
+            target: target.id.clone(),
+            kind: EdgeKind::References,
+            file_path: r.file_path.clone(),
+            line: r.line,
+            metadata: Some(HashMap::from([(
+                "precise".to_string(),
+                format.as_str().to_string(),
+            )])),
+        });
+    }
+
+    stats.edges_added = new_edges.len();
+    store.upsert_edges(&new_edges)?;
+    Ok(stats)
+}
+
+/// Innermost node whose line range contains `line`.
+fn enclosing_node(nodes: &[CodeNode], line: u32) -> Option<&CodeNode> {
+    nodes
+        .iter()
+        .filter(|n| n.start_line <= line && line <= n.end_line.max(n.start_line))
+        .min_by_key(|n| n.end_line.saturating_sub(n.start_line))
+}
+
+/// The node defined at `line`, preferring one that starts there.
+fn definition_node(nodes: &[CodeNode], line: u32) -> Option<&CodeNode> {
+    nodes
+        .iter()
+        .filter(|n| n.start_line == line)
+        .min_by_key(|n| n.end_line.saturating_sub(n.start_line))
+        .or_else(|| enclosing_node(nodes, line))
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::schema::initialize_database;
+    use crate::types::{Language, NodeKind};
+
+    fn node(id: &str, file: &str, start: u32, end: u32) -> CodeNode {
+        CodeNode {
+            id: id.to_string(),
+            name: id.to_string(),
+            qualified_name: None,
+            kind: NodeKind::Function,
+            file_path: file.to_string(),
+            start_line: start,
+            end_line: end,
+            start_column: 0,
+            end_column: 0,
+            language: Language::TypeScript,
+            body: None,
+            documentation: None,
+            exported: None,
+        }
+    }
+
+    fn reference(file: &str, line: u32, def_file: &str, def_line: u32) -> PreciseReference {
+        PreciseReference {
+            file_path: file.to_string(),
+            line,
+            definition_file: def_file.to_string(),
+            definition_line: def_line,
+        }
+    }
+
+    #[test]
+    fn merges_references_onto_enclosing_nodes() {
+        let store = GraphStore::from_connection(initialize_database(":memory:").unwrap());
+        store
+            .upsert_nodes(&[
+                node("caller", "src/app.ts", 1, 10),
+                node("callee", "src/lib.ts", 3, 6),
+                node("other", "src/lib.ts", 8, 9),
+            ])
+            .unwrap();
+        store
+            .upsert_edge(&CodeEdge {
+                source: "caller".into(),
+                target: "other".into(),
+                kind: EdgeKind::Calls,
+                file_path: "src/app.ts".into(),
+                line: 5,
+                metadata: None,
+            })
+            .unwrap();
+
+        let refs = vec![
+            reference("src/app.ts", 4, "src/lib.ts", 3),
+            reference("src/app.ts", 5, "src/lib.ts", 8),
+            reference("src/app.ts", 40, "src/lib.ts", 3),
+        ];
+        let stats = merge_references(&store, &refs, IndexFormat::Scip).unwrap();
+        assert_eq!(stats.edges_added, 1);
+        assert_eq!(stats.edges_confirmed, 1);
+        assert_eq!(stats.unmatched, 1);
+
+        let edges = store.get_out_edges("caller", Some("references")).unwrap();
+        assert_eq!(edges.len(), 1);
+        assert_eq!(edges[0].target, "callee");
+        assert_eq!(
+            edges[0]
+                .metadata
+                .as_ref()
+                .unwrap()
+                .get("precise")
+                .map(String::as_str),
+            Some("scip")
+        );
+    }
+
+    #[test]
+    fn detects_format_from_extension_and_content() {
+        assert_eq!(
+            IndexFormat::detect(Path::new("index.scip"), b"{"),
+            IndexFormat::Scip
+        );
+        assert_eq!(
+            IndexFormat::detect(Path::new("dump"), b"  {\"id\":1}"),
+            IndexFormat::Lsif
+        );
+        assert_eq!(
+            IndexFormat::detect(Path::new("dump"), &[0x0a, 0x02]),
+            IndexFormat::Scip
+        );
+    }
+}
@@ -0,0 +1,292 @@
+//! SCIP index parser.
+//!
+//! SCIP indexes are protobuf messages. Only a handful of fields are needed,
+//! so instead of pulling in a protobuf code generator this module walks the
+//! wire format directly:
+//!
+//! ```text
+//! Index      { 2: repeated Document documents }
+//! Document   { 1: string relative_path, 2: repeated Occurrence occurrences }
+//! Occurrence { 1: repeated int32 range, 2: string symbol, 3: int32 symbol_roles }
+//! ```
+//!
+//! Occurrences with the `Definition` role locate each global symbol; every
+//! other occurrence of that symbol becomes a [`PreciseReference`].
+//! Document-local symbols (`local N`) are skipped since they never cross a
+//! graph node boundary worth recording.
+
+use std::collections::HashMap;
+
+use super::PreciseReference;
+use crate::error::{CodeGraphError, Result};
+
+/// `SymbolRole.Definition` bit in `Occurrence.symbol_roles`.
+const ROLE_DEFINITION: u64 = 0x1;
+
+/// A single SCIP occurrence, reduced to what the importer needs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Occurrence {
+    /// 1-based start line.
+    line: u32,
+    symbol: String,
+    roles: u64,
+}
+
+/// Parse a binary SCIP index into `(document count, references)`.
+pub fn parse_scip(bytes: &[u8]) -> Result<(usize, Vec<PreciseReference>)> {
+    let mut documents: Vec<(String, Vec<Occurrence>)> = Vec::new();
+    let mut index = ProtoReader::new(bytes);
+    while let Some((field, value)) = index.next_field()? {
+        if let (2, WireValue::Bytes(doc)) = (field, value) {
+            documents.push(parse_document(doc)?);
+        }
+    }
+
+    let mut definitions: HashMap<&str, (&str, u32)> = HashMap::new();
+    for (path, occurrences) in &documents {
+        for occ in occurrences {
+            if occ.roles & ROLE_DEFINITION != 0 && !occ.symbol.starts_with("local ") {
+                definitions.entry(&occ.symbol).or_insert((path, occ.line));
+            }
+        }
+    }
+
+    let mut refs = Vec::new();
+    for (path, occurrences) in &documents {
+        for occ in occurrences {
+            if occ.roles & ROLE_DEFINITION != 0 {
+                continue;
+            }
+            if let Some(&(def_file, def_line)) = definitions.get(occ.symbol.as_str()) {
+                refs.push(PreciseReference {
+                    file_path: path.clone(),
+                    line: occ.line,
+                    definition_file: def_file.to_string(),
+                    definition_line: def_line,
+                });
+            }
+        }
+    }
+
+    Ok((documents.len(), refs))
+}
+
+fn parse_document(bytes: &[u8]) -> Result<(String, Vec<Occurrence>)> {
+    let mut path = String::new();
+    let mut occurrences = Vec::new();
+    let mut reader = ProtoReader::new(bytes);
+    while let Some((field, value)) = reader.next_field()? {
+        match (field, value) {
+            (1, WireValue::Bytes(b)) => path = utf8(b)?,
+            (2, WireValue::Bytes(b)) => {
+                if let Some(occ) = parse_occurrence(b)? {
+                    occurrences.push(occ);
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok((path, occurrences))
+}
+
+fn parse_occurrence(bytes: &[u8]) -> Result<Option<Occurrence>> {
+    let mut range: Vec<u64> = Vec::new();
+    let mut symbol = String::new();
+    let mut roles = 0;
+    let mut reader = ProtoReader::new(bytes);
+    while let Some((field, value)) = reader.next_field()? {
+        match (field, value) {
+            // `range` is packed, but decoders must also accept unpacked.
+            (1, WireValue::Bytes(b)) => {
+                let mut packed = ProtoReader::new(b);
+                while !packed.is_empty() {
+                    range.push(packed.varint()?);
+                }
+            }
+            (1, WireValue::Varint(v)) => range.push(v),
+            (2, WireValue::Bytes(b)) => symbol = utf8(b)?,
+            (3, WireValue::Varint(v)) => roles = v,
+            _ => {}
+        }
+    }
+    if symbol.is_empty() || range.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(Occurrence {
+        line: range[0] as u32 + 1,
+        symbol,
+        roles,
+    }))
+}
+
+fn utf8(bytes: &[u8]) -> Result<String> {
+    String::from_utf8(bytes.to_vec())
+        .map_err(|e| CodeGraphError::Other(format!("invalid UTF-8 in SCIP index: {e}")))
+}
+
+// ---------------------------------------------------------------------------
+// Protobuf wire format
+// ---------------------------------------------------------------------------
+
+enum WireValue<'a> {
+    Varint(u64),
+    Bytes(&'a [u8]),
+    Fixed,
+}
+
+struct ProtoReader<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> ProtoReader<'a> {
+    fn new(buf: &'a [u8]) -> Self {
+        Self { buf, pos: 0 }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.pos >= self.buf.len()
+    }
+
+    fn varint(&mut self) -> Result<u64> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = *self.buf.get(self.pos).ok_or_else(truncated)?;
+            self.pos += 1;
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(CodeGraphError::Other(
+            "malformed varint in SCIP index".into(),
+        ))
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        let end = self.pos.checked_add(len).ok_or_else(truncated)?;
+        let slice = self.buf.get(self.pos..end).ok_or_else(truncated)?;
+        self.pos = end;
+        Ok(slice)
+    }
+
+    /// Next `(field number, value)`, or `None` at the end of the message.
+    fn next_field(&mut self) -> Result<Option<(u64, WireValue<'a>)>> {
+        if self.is_empty() {
+            return Ok(None);
+        }
+        let key = self.varint()?;
+        let value = match key & 0x7 {
+            0 => WireValue::Varint(self.varint()?),
+            1 => {
+                self.take(8)?;
+                WireValue::Fixed
+            }
+            2 => {
+                let len = self.varint()? as usize;
+                WireValue::Bytes(self.take(len)?)
+            }
+            5 => {
+                self.take(4)?;
+                WireValue::Fixed
+            }
+            other => {
+                return Err(CodeGraphError::Other(format!(
+                    "unsupported protobuf wire type {other} in SCIP index"
+                )))
+            }
+        };
+        Ok(Some((key >> 3, value)))
+    }
+}
+
+fn truncated() -> CodeGraphError {
+    CodeGraphError::Other("truncated SCIP index".into())
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn varint(mut v: u64, out: &mut Vec<u8>) {
+        while v >= 0x80 {
+            out.push((v as u8) | 0x80);
+            v >>= 7;
+        }
+        out.push(v as u8);
+    }
+
+    fn bytes_field(field: u64, data: &[u8], out: &mut Vec<u8>) {
+        varint((field << 3) | 2, out);
+        varint(data.len() as u64, out);
+        out.extend_from_slice(data);
+    }
+
+    fn occurrence(line: u64, symbol: &str, roles: u64) -> Vec<u8> {
+        let mut range = Vec::new();
+        for v in [line, 4, 9] {
+            varint(v, &mut range);
+        }
+        let mut out = Vec::new();
+        bytes_field(1, &range, &mut out);
+        bytes_field(2, symbol.as_bytes(), &mut out);
+        varint(3 << 3, &mut out);
+        varint(roles, &mut out);
+        out
+    }
+
+    fn document(path: &str, occurrences: &[Vec<u8>]) -> Vec<u8> {
+        let mut out = Vec::new();
+        bytes_field(1, path.as_bytes(), &mut out);
+        for occ in occurrences {
+            bytes_field(2, occ, &mut out);
+        }
+        // Unknown field (language) must be skipped.
+        bytes_field(4, b"typescript", &mut out);
+        out
+    }
+
+    fn index(documents: &[Vec<u8>]) -> Vec<u8> {
+        let mut out = Vec::new();
+        bytes_field(1, b"\x0a\x00", &mut out); // metadata
+        for doc in documents {
+            bytes_field(2, doc, &mut out);
+        }
+        out
+    }
+
+    #[test]
+    fn references_resolve_to_definitions_across_documents() {
+        let sym = "scip-typescript npm app 1.0 src/`lib.ts`/greet().";
+        let bytes = index(&[
+            document("src/lib.ts", &[occurrence(2, sym, ROLE_DEFINITION)]),
+            document(
+                "src/app.ts",
+                &[occurrence(6, sym, 0), occurrence(7, "local 0", 0)],
+            ),
+        ]);
+
+        let (docs, refs) = parse_scip(&bytes).unwrap();
+        assert_eq!(docs, 2);
+        assert_eq!(
+            refs,
+            vec![PreciseReference {
+                file_path: "src/app.ts".into(),
+                line: 7,
+                definition_file: "src/lib.ts".into(),
+                definition_line: 3,
+            }]
+        );
+    }
+
+    #[test]
+    fn truncated_input_is_an_error() {
+        let mut bytes = index(&[document("a.ts", &[occurrence(0, "s", 1)])]);
+        bytes.truncate(bytes.len() - 3);
+        assert!(parse_scip(&bytes).is_err());
+    }
+}
@@ -6,6 +6,8 @@
 pub mod cli;
 pub mod config;
 pub mod context;
+#[cfg(unix)]
+pub mod daemon;
 pub mod db;
 pub mod error;
 pub mod eval;
@@ -14,9 +16,12 @@ pub mod git;
 pub mod graph;
 pub mod hooks;
 pub mod indexer;
+pub mod interop;
+pub mod lsp;
 pub mod mcp;
 pub mod observability;
 pub mod resolution;
+pub mod sandbox;
 pub mod security;
 pub mod types;
 pub mod viz;
@@ -0,0 +1,514 @@
+//! Language Server Protocol mode (`codegraph lsp`).
+//!
+//! Serves the index to editors over stdio, without MCP: go-to-definition,
+//! find-references, workspace symbol search and hover, all answered from
+//! the same [`GraphStore`] the MCP server uses. Nothing is re-parsed on
+//! edits; answers reflect the last `codegraph index` (or the watcher), and
+//! buffers are only read to find the identifier under the cursor.
+//!
+//! Resolution is name-based with the graph as a tie-breaker: the word under
+//! the cursor is looked up among the targets of the enclosing symbol's
+//! edges first, and among all symbols of that name otherwise. Columns are
+//! counted in characters, which matches UTF-16 for the usual ASCII code.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, RwLock};
+
+use tower_lsp::jsonrpc::Result as LspResult;
+use tower_lsp::lsp_types::{
+    DidChangeTextDocumentParams, DidCloseTextDocumentParams, DidOpenTextDocumentParams,
+    GotoDefinitionParams, GotoDefinitionResponse, Hover, HoverContents, HoverParams,
+    HoverProviderCapability, InitializeParams, InitializeResult, InitializedParams, Location,
+    MarkupContent, MarkupKind, MessageType, OneOf, Position, Range, ReferenceParams,
+    ServerCapabilities, ServerInfo, SymbolInformation, SymbolKind, TextDocumentSyncCapability,
+    TextDocumentSyncKind, Url, WorkspaceSymbolParams,
+};
+use tower_lsp::{Client, LanguageServer, LspService, Server};
+
+use crate::graph::search::HybridSearch;
+use crate::graph::store::GraphStore;
+use crate::types::{CodeNode, NodeKind};
+
+/// Most results of a workspace symbol search.
+const WORKSPACE_SYMBOL_LIMIT: usize = 100;
+
+// ---------------------------------------------------------------------------
+// Server
+// ---------------------------------------------------------------------------
+
+struct Backend {
+    client: Client,
+    store: Arc<Mutex<GraphStore>>,
+    /// Directory the indexed paths are relative to.
+    root: RwLock<PathBuf>,
+    /// Text of open buffers, by URI.
+    documents: Mutex<HashMap<Url, String>>,
+}
+
+impl Backend {
+    /// The indexed path of `uri`: relative to the root when under it.
+    fn indexed_path(&self, uri: &Url) -> Option<String> {
+        let path = uri.to_file_path().ok()?;
+        let root = self.root.read().unwrap_or_else(|e| e.into_inner());
+        let path = path.strip_prefix(&*root).unwrap_or(&path);
+        Some(path.to_string_lossy().into_owned())
+    }
+
+    fn uri(&self, file_path: &str) -> Option<Url> {
+        let path = Path::new(file_path);
+        if path.is_absolute() {
+            return Url::from_file_path(path).ok();
+        }
+        let root = self.root.read().unwrap_or_else(|e| e.into_inner());
+        Url::from_file_path(root.join(path)).ok()
+    }
+
+    /// Line `line` (0-based) of the buffer, or of the file on disk.
+    fn line_text(&self, uri: &Url, line: u32) -> Option<String> {
+        let documents = self.documents.lock().unwrap_or_else(|e| e.into_inner());
+        let text = match documents.get(uri) {
+            Some(text) => text.clone(),
+            None => std::fs::read_to_string(uri.to_file_path().ok()?).ok()?,
+        };
+        text.lines().nth(line as usize).map(str::to_string)
+    }
+
+    /// The indexed path and the identifier at a cursor position.
+    fn word_at_position(&self, uri: &Url, position: Position) -> Option<(String, String)> {
+        let line = self.line_text(uri, position.line)?;
+        let (word, _) = word_at(&line, position.character as usize)?;
+        Some((self.indexed_path(uri)?, word))
+    }
+
+    fn location(&self, file_path: &str, line: u32, column: u32, len: usize) -> Option<Location> {
+        let start = Position::new(line.saturating_sub(1), column);
+        let end = Position::new(start.line, column + len as u32);
+        Some(Location::new(self.uri(file_path)?, Range::new(start, end)))
+    }
+
+    /// Location of a symbol's name within its first line, when it can be
+    /// found there.
+    fn node_location(&self, node: &CodeNode) -> Option<Location> {
+        let uri = self.uri(&node.file_path)?;
+        let column = self
+            .line_text(&uri, node.start_line.saturating_sub(1))
+            .and_then(|text| column_of(&text, &node.name))
+            .unwrap_or(node.start_column);
+        self.location(
+            &node.file_path,
+            node.start_line,
+            column,
+            node.name.chars().count(),
+        )
+    }
+}
+
+#[tower_lsp::async_trait]
+impl LanguageServer for Backend {
+    async fn initialize(&self, params: InitializeParams) -> LspResult<InitializeResult> {
+        let root = params
+            .workspace_folders
+            .as_ref()
+            .and_then(|folders| folders.first())
+            .map(|folder| folder.uri.clone())
+            .or(params.root_uri)
+            .and_then(|uri| uri.to_file_path().ok());
+        if let Some(root) = root {
+            *self.root.write().unwrap_or_else(|e| e.into_inner()) = root;
+        }
+        Ok(InitializeResult {
+            capabilities: ServerCapabilities {
+                text_document_sync: Some(TextDocumentSyncCapability::Kind(
+                    TextDocumentSyncKind::FULL,
+                )),
+                definition_provider: Some(OneOf::Left(true)),
+                references_provider: Some(OneOf::Left(true)),
+                workspace_symbol_provider: Some(OneOf::Left(true)),
+                hover_provider: Some(HoverProviderCapability::Simple(true)),
+                ..Default::default()
+            },
+            server_info: Some(ServerInfo {
+                name: "codegraph".to_string(),
+                version: Some(env!("CARGO_PKG_VERSION").to_string()),
+            }),
+        })
+    }
+
+    async fn initialized(&self, _: InitializedParams) {
+        let root = self.root.read().unwrap_or_else(|e| e.into_inner()).clone();
+        self.client
+            .log_message(
+                MessageType::INFO,
+                format!("codegraph: serving the index of {}", root.display()),
+            )
+            .await;
+    }
+
+    async fn shutdown(&self) -> LspResult<()> {
+        Ok(())
+    }
+
+    async fn did_open(&self, params: DidOpenTextDocumentParams) {
+        let mut documents = self.documents.lock().unwrap_or_else(|e| e.into_inner());
+        documents.insert(params.text_document.uri, params.text_document.text);
+    }
+
+    async fn did_change(&self, params: DidChangeTextDocumentParams) {
+        // Full sync: the last change holds the whole buffer.
+        if let Some(change) = params.content_changes.into_iter().last() {
+            let mut documents = self.documents.lock().unwrap_or_else(|e| e.into_inner());
+            documents.insert(params.text_document.uri, change.text);
+        }
+    }
+
+    async fn did_close(&self, params: DidCloseTextDocumentParams) {
+        let mut documents = self.documents.lock().unwrap_or_else(|e| e.into_inner());
+        documents.remove(&params.text_document.uri);
+    }
+
+    async fn goto_definition(
+        &self,
+        params: GotoDefinitionParams,
+    ) -> LspResult<Option<GotoDefinitionResponse>> {
+        let at = params.text_document_position_params;
+        let Some((file, word)) = self.word_at_position(&at.text_document.uri, at.position) else {
+            return Ok(None);
+        };
+        let definitions = {
+            let store = self.store.lock().unwrap_or_else(|e| e.into_inner());
+            resolve_definitions(&store, &file, at.position.line + 1, &word)
+        };
+        let locations: Vec<Location> = definitions
+            .iter()
+            .filter_map(|n| self.node_location(n))
+            .collect();
+        Ok((!locations.is_empty()).then_some(GotoDefinitionResponse::Array(locations)))
+    }
+
+    async fn references(&self, params: ReferenceParams) -> LspResult<Option<Vec<Location>>> {
+        let at = params.text_document_position;
+        let Some((file, word)) = self.word_at_position(&at.text_document.uri, at.position) else {
+            return Ok(None);
+        };
+        let (definitions, sites) = {
+            let store = self.store.lock().unwrap_or_else(|e| e.into_inner());
+            let definitions = resolve_definitions(&store, &file, at.position.line + 1, &word);
+            let sites = reference_sites(&store, &definitions);
+            (definitions, sites)
+        };
+        let mut locations = Vec::new();
+        if params.context.include_declaration {
+            locations.extend(definitions.iter().filter_map(|n| self.node_location(n)));
+        }
+        for (file_path, line) in sites {
+            let column = self
+                .uri(&file_path)
+                .and_then(|uri| self.line_text(&uri, line.saturating_sub(1)))
+                .and_then(|text| column_of(&text, &word))
+                .unwrap_or(0);
+            locations.extend(self.location(&file_path, line, column, word.chars().count()));
+        }
+        Ok(Some(locations))
+    }
+
+    async fn symbol(
+        &self,
+        params: WorkspaceSymbolParams,
+    ) -> LspResult<Option<Vec<SymbolInformation>>> {
+        if params.query.trim().is_empty() {
+            return Ok(Some(Vec::new()));
+        }
+        let nodes = {
+            let store = self.store.lock().unwrap_or_else(|e| e.into_inner());
+            search_symbols(&store, &params.query)
+        };
+        #[allow(deprecated)] // `deprecated` is a required field
+        let symbols = nodes
+            .iter()
+            .filter_map(|n| {
+                Some(SymbolInformation {
+                    name: n.name.clone(),
+                    kind: symbol_kind(n.kind),
+                    tags: None,
+                    deprecated: None,
+                    location: self.node_location(n)?,
+                    container_name: n.qualified_name.clone(),
+                })
+            })
+            .collect();
+        Ok(Some(symbols))
+    }
+
+    async fn hover(&self, params: HoverParams) -> LspResult<Option<Hover>> {
+        let at = params.text_document_position_params;
+        let Some((file, word)) = self.word_at_position(&at.text_document.uri, at.position) else {
+            return Ok(None);
+        };
+        let store = self.store.lock().unwrap_or_else(|e| e.into_inner());
+        let definitions = resolve_definitions(&store, &file, at.position.line + 1, &word);
+        let Some(node) = definitions.first() else {
+            return Ok(None);
+        };
+        let callers = store
+            .get_in_edges(&node.id, None)
+            .map(|edges| edges.len())
+            .unwrap_or(0);
+        Ok(Some(Hover {
+            contents: HoverContents::Markup(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value: hover_markdown(node, callers, definitions.len()),
+            }),
+            range: None,
+        }))
+    }
+}
+
+/// Serve `store` over stdio until the client exits. `root` is the
+/// directory indexed paths are relative to, unless the client names a
+/// workspace folder.
+pub async fn run_stdio(store: GraphStore, root: PathBuf) {
+    let store = Arc::new(Mutex::new(store));
+    let (service, socket) = LspService::new(|client| Backend {
+        client,
+        store,
+        root: RwLock::new(root),
+        documents: Mutex::default(),
+    });
+    Server::new(tokio::io::stdin(), tokio::io::stdout(), socket)
+        .serve(service)
+        .await;
+}
+
+// ---------------------------------------------------------------------------
+// Graph lookups
+// ---------------------------------------------------------------------------
+
+/// Symbols that `word`, used at `line` (1-based) of `file`, refers to:
+/// the targets named `word` of the enclosing symbol's edges, else every
+/// symbol named `word`, those in `file` first.
+pub fn resolve_definitions(store: &GraphStore, file: &str, line: u32, word: &str) -> Vec<CodeNode> {
+    let enclosing = store
+        .get_nodes_by_file(file)
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|n| n.start_line <= line && line <= n.end_line)
+        .min_by_key(|n| n.end_line - n.start_line);
+    if let Some(enclosing) = &enclosing {
+        if enclosing.name == word && enclosing.start_line == line {
+            return vec![enclosing.clone()];
+        }
+        let targets: Vec<CodeNode> = store
+            .get_out_edges(&enclosing.id, None)
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|e| store.get_node(&e.target).ok().flatten())
+            .filter(|n| n.name == word)
+            .collect();
+        if !targets.is_empty() {
+            return dedup(targets);
+        }
+    }
+    let mut named = store.get_nodes_by_name(word).unwrap_or_default();
+    named.sort_by_key(|n| (n.file_path != file, n.file_path.clone(), n.start_line));
+    named
+}
+
+/// `(file, line)` of every edge into `definitions`, in file order.
+pub fn reference_sites(store: &GraphStore, definitions: &[CodeNode]) -> Vec<(String, u32)> {
+    let mut sites: Vec<(String, u32)> = definitions
+        .iter()
+        .flat_map(|d| store.get_in_edges(&d.id, None).unwrap_or_default())
+        .map(|e| (e.file_path, e.line))
+        .collect();
+    sites.sort();
+    sites.dedup();
+    sites
+}
+
+/// Keyword search over symbol names, best match first.
+fn search_symbols(store: &GraphStore, query: &str) -> Vec<CodeNode> {
+    HybridSearch::new(&store.conn)
+        .search_by_keyword(query, WORKSPACE_SYMBOL_LIMIT)
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|r| store.get_node(&r.node_id).ok().flatten())
+        .collect()
+}
+
+fn dedup(mut nodes: Vec<CodeNode>) -> Vec<CodeNode> {
+    let mut seen = std::collections::HashSet::new();
+    nodes.retain(|n| seen.insert(n.id.clone()));
+    nodes
+}
+
+// ---------------------------------------------------------------------------
+// Text helpers
+// ---------------------------------------------------------------------------
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '$'
+}
+
+/// The identifier around character `character` of `line`, with its start.
+pub fn word_at(line: &str, character: usize) -> Option<(String, usize)> {
+    let chars: Vec<char> = line.chars().collect();
+    let at = character.min(chars.len());
+    let mut start = at;
+    while start > 0 && is_word_char(chars[start - 1]) {
+        start -= 1;
+    }
+    let mut end = at;
+    while end < chars.len() && is_word_char(chars[end]) {
+        end += 1;
+    }
+    (start < end).then(|| (chars[start..end].iter().collect(), start))
+}
+
+/// Character column of the first whole-word occurrence of `word` in `line`.
+fn column_of(line: &str, word: &str) -> Option<u32> {
+    let chars: Vec<char> = line.chars().collect();
+    let needle: Vec<char> = word.chars().collect();
+    (0..chars.len().checked_sub(needle.len())? + 1)
+        .find(|&i| {
+            chars[i..i + needle.len()] == needle[..]
+                && (i == 0 || !is_word_char(chars[i - 1]))
+                && chars
+                    .get(i + needle.len())
+                    .is_none_or(|c| !is_word_char(*c))
+        })
+        .map(|i| i as u32)
+}
+
+fn hover_markdown(node: &CodeNode, callers: usize, candidates: usize) -> String {
+    let mut out = String::new();
+    if let Some(signature) = node.body.as_deref().and_then(|b| b.lines().next()) {
+        out.push_str(&format!(
+            "```{}\n{}\n```\n\n",
+            node.language.grammar_name(),
+            signature.trim()
+        ));
+    }
+    if let Some(doc) = node
+        .documentation
+        .as_deref()
+        .filter(|d| !d.trim().is_empty())
+    {
+        out.push_str(doc.trim());
+        out.push_str("\n\n");
+    }
+    out.push_str(&format!(
+        "*{}* `{}` — {}:{} · {} incoming reference{}",
+        node.kind.as_str(),
+        node.qualified_name.as_deref().unwrap_or(&node.name),
+        node.file_path,
+        node.start_line,
+        callers,
+        if callers == 1 { "" } else { "s" },
+    ));
+    if candidates > 1 {
+        out.push_str(&format!(" · {} definitions share this name", candidates));
+    }
+    out
+}
+
+fn symbol_kind(kind: NodeKind) -> SymbolKind {
+    match kind {
+        NodeKind::Function => SymbolKind::FUNCTION,
+        NodeKind::Class => SymbolKind::CLASS,
+        NodeKind::Method => SymbolKind::METHOD,
+        NodeKind::Interface => SymbolKind::INTERFACE,
+        NodeKind::TypeAlias => SymbolKind::TYPE_PARAMETER,
+        NodeKind::Enum => SymbolKind::ENUM,
+        NodeKind::Variable => SymbolKind::VARIABLE,
+        NodeKind::Struct => SymbolKind::STRUCT,
+        NodeKind::Trait => SymbolKind::INTERFACE,
+        NodeKind::Module => SymbolKind::MODULE,
+        NodeKind::Property => SymbolKind::PROPERTY,
+        NodeKind::Namespace => SymbolKind::NAMESPACE,
+        NodeKind::Constant => SymbolKind::CONSTANT,
+        NodeKind::Route => SymbolKind::EVENT,
+        NodeKind::ConfigKey => SymbolKind::KEY,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::schema::initialize_database;
+    use crate::types::{CodeEdge, EdgeKind, Language};
+
+    fn node(id: &str, name: &str, file: &str, start: u32, end: u32) -> CodeNode {
+        CodeNode {
+            id: id.to_string(),
+            name: name.to_string(),
+            qualified_name: None,
+            kind: NodeKind::Function,
+            file_path: file.to_string(),
+            start_line: start,
+            end_line: end,
+            start_column: 0,
+            end_column: 1,
+            language: Language::TypeScript,
+            body: Some(format!("function {name}() {{")),
+            documentation: None,
+            exported: None,
+        }
+    }
+
+    fn edge(source: &str, target: &str, file: &str, line: u32) -> CodeEdge {
+        CodeEdge {
+            source: source.to_string(),
+            target: target.to_string(),
+            kind: EdgeKind::Calls,
+            file_path: file.to_string(),
+            line,
+            metadata: None,
+        }
+    }
+
+    #[test]
+    fn words_are_found_around_the_cursor() {
+        assert_eq!(word_at("  parse(input)", 4), Some(("parse".to_string(), 2)));
+        assert_eq!(word_at("  parse(input)", 7), Some(("parse".to_string(), 2)));
+        assert_eq!(word_at("a + b", 2), None);
+        assert_eq!(column_of("reparse(x); parse(y)", "parse"), Some(12));
+        assert_eq!(column_of("x", "parse"), None);
+    }
+
+    #[test]
+    fn definitions_prefer_edges_of_the_enclosing_symbol() {
+        let store = GraphStore::from_connection(initialize_database(":memory:").unwrap());
+        store
+            .upsert_nodes(&[
+                node("main", "main", "src/main.ts", 1, 10),
+                node("parse_a", "parse", "src/a.ts", 1, 5),
+                node("parse_b", "parse", "src/b.ts", 1, 5),
+            ])
+            .unwrap();
+        store
+            .upsert_edges(&[edge("main", "parse_b", "src/main.ts", 3)])
+            .unwrap();
+
+        let found = resolve_definitions(&store, "src/main.ts", 3, "parse");
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].id, "parse_b");
+
+        // Outside any symbol: every definition, the current file's first.
+        let found = resolve_definitions(&store, "src/b.ts", 20, "parse");
+        let ids: Vec<&str> = found.iter().map(|n| n.id.as_str()).collect();
+        assert_eq!(ids, ["parse_b", "parse_a"]);
+
+        assert_eq!(
+            reference_sites(&store, &found),
+            [("src/main.ts".to_string(), 3)]
+        );
+        let hover = hover_markdown(&found[0], 1, 2);
+        assert!(hover.contains("function parse() {"));
+        assert!(hover.contains("1 incoming reference ·"));
+    }
+}
@@ -1,4 +1,4 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process;
 use std::sync::mpsc;
 use std::time::Duration;
@@ -8,11 +8,17 @@ use ignore::WalkBuilder;
 use notify::{RecursiveMode, Watcher};
 
 use codegraph::cli::installer;
+use codegraph::config::loader::load_config;
+#[cfg(unix)]
+use codegraph::daemon::{DaemonRequest, DaemonResponse};
+use codegraph::db::saved_queries::{find_saved_query, list_saved_queries, save_query, SavedQuery};
 use codegraph::db::schema::initialize_database;
 use codegraph::graph::ranking::GraphRanking;
 use codegraph::graph::search::{HybridSearch, SearchOptions};
+use codegraph::graph::stats::{compute_dir_stats, load_dir_stats, rollup, RollupKey};
 use codegraph::graph::store::GraphStore;
 use codegraph::indexer::{CodeParser, IndexOptions, IndexingPipeline};
+use codegraph::resolution::config_keys::ConfigFormat;
 use codegraph::types::Language;
 
 #[derive(Parser)]
@@ -54,6 +60,77 @@ enum WorkspaceAction {
     },
 }
 
+#[derive(Subcommand)]
+enum DbAction {
+    /// Show table and index sizes, FTS segments, query plans and the slowest logged queries
+    Stats {
+        /// Number of slowest logged queries to list
+        #[arg(long, default_value_t = 10)]
+        slowest: usize,
+        /// Database path
+        #[arg(long, default_value = ".codegraph/codegraph.db")]
+        db: String,
+    },
+    /// Save a compacted copy of the index as a CI cache artifact
+    CacheSave {
+        /// Artifact path to write
+        out: PathBuf,
+        /// Database path
+        #[arg(long, default_value = ".codegraph/codegraph.db")]
+        db: String,
+    },
+    /// Replace the index with a cache artifact saved by `db cache-save`
+    CacheRestore {
+        /// Artifact path to read
+        artifact: PathBuf,
+        /// Database path
+        #[arg(long, default_value = ".codegraph/codegraph.db")]
+        db: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum RulesAction {
+    /// List the security rules in effect: bundled rules and rule packs
+    List {
+        /// Only rules from the packs in .codegraph/rules
+        #[arg(long)]
+        packs_only: bool,
+        /// Project directory
+        #[arg(long, default_value = ".")]
+        directory: String,
+    },
+    /// Validate the rule packs in .codegraph/rules; exits non-zero on errors
+    Validate {
+        /// Project directory
+        #[arg(long, default_value = ".")]
+        directory: String,
+    },
+}
+
+#[cfg(unix)]
+#[derive(Subcommand)]
+enum DaemonAction {
+    /// Run the daemon in the foreground until stopped
+    Start {
+        /// Project directory (default: current dir)
+        #[arg(default_value = ".")]
+        directory: String,
+    },
+    /// Show the running daemon's status
+    Status {
+        /// Project directory (default: current dir)
+        #[arg(default_value = ".")]
+        directory: String,
+    },
+    /// Ask the running daemon to shut down
+    Stop {
+        /// Project directory (default: current dir)
+        #[arg(default_value = ".")]
+        directory: String,
+    },
+}
+
 #[derive(Subcommand)]
 enum Commands {
     /// Set up CodeGraph: index codebase, configure MCP server, install hooks
@@ -73,14 +150,36 @@ enum Commands {
         /// Force full re-index
         #[arg(long)]
         force: bool,
+        /// Copy unchanged files from an older database before indexing
+        #[arg(long, value_name = "PATH")]
+        warm_start: Option<PathBuf>,
+        /// Only re-index files changed since this git revision (e.g. after `db cache-restore`)
+        #[arg(long, value_name = "REV", conflicts_with_all = ["force", "warm_start"])]
+        since: Option<String>,
     },
     /// Search the code graph
     Query {
-        /// Search query
-        query: String,
+        /// Search query; may contain `{param}` placeholders when saved
+        #[arg(required_unless_present_any = ["saved", "list_saved"])]
+        query: Option<String>,
         /// Maximum results
         #[arg(short = 'n', long, default_value_t = 10)]
         limit: usize,
+        /// Save the query under this name instead of running it
+        #[arg(long, value_name = "NAME", conflicts_with_all = ["saved", "list_saved"])]
+        save: Option<String>,
+        /// Run the saved query with this name
+        #[arg(long, value_name = "NAME", conflicts_with = "list_saved")]
+        saved: Option<String>,
+        /// Placeholder value for a saved query (repeatable)
+        #[arg(long = "param", value_name = "KEY=VALUE")]
+        params: Vec<String>,
+        /// List saved queries, local and shared in config
+        #[arg(long)]
+        list_saved: bool,
+        /// Also search the indexes configured under `federation`
+        #[arg(long, conflicts_with_all = ["save", "saved", "list_saved"])]
+        federated: bool,
     },
     /// Show blast radius of changing a file or symbol
     Impact {
@@ -90,6 +189,14 @@ enum Commands {
         #[arg(long, default_value = ".codegraph/codegraph.db")]
         db: String,
     },
+    /// Print a readable dossier for a node ID (or symbol name)
+    Explain {
+        /// Node ID as it appears in tool output, or a symbol name
+        node_id: String,
+        /// Database path
+        #[arg(long, default_value = ".codegraph/codegraph.db")]
+        db: String,
+    },
     /// Watch for file changes and re-index incrementally
     Watch {
         /// Directory to watch (default: current dir)
@@ -104,12 +211,40 @@ enum Commands {
         /// Start HTTP server on the given address (e.g. 0.0.0.0:8080)
         #[arg(long)]
         http: Option<String>,
+        /// Host every indexed repo of this workspace (requires --http); the
+        /// admin API is enabled by setting CODEGRAPH_ADMIN_TOKEN
+        #[arg(long, requires = "http")]
+        workspace: Option<String>,
+        /// Also serve the project indexed at PATH, selected by passing
+        /// `workspace: NAME` to tools (repeatable)
+        #[arg(long = "root", value_name = "NAME=PATH", conflicts_with = "workspace")]
+        roots: Vec<String>,
+    },
+    /// Start a Language Server Protocol server on stdio (definitions,
+    /// references, workspace symbols and hover from the index)
+    Lsp {
+        /// Database path
+        #[arg(long, default_value = ".codegraph/codegraph.db")]
+        db: String,
     },
     /// Show index statistics
     Stats {
         /// Database path
         #[arg(long, default_value = ".codegraph/codegraph.db")]
         db: String,
+        /// Break the counts down per directory, with complexity figures
+        #[arg(long)]
+        by_dir: bool,
+    },
+    /// Show which MCP tools agents called: calls, errors and duration per
+    /// tool, and enabled tools never called
+    Usage {
+        /// Only calls from the last N days
+        #[arg(long)]
+        days: Option<u32>,
+        /// Database path
+        #[arg(long, default_value = ".codegraph/codegraph.db")]
+        db: String,
     },
     /// Install CodeGraph hooks into Claude Code settings
     InstallHooks {
@@ -125,6 +260,24 @@ enum Commands {
         /// Filter by node kind (e.g., function, class, method)
         #[arg(long)]
         kind: Option<String>,
+        /// Show when each symbol lost its last reference (git + index history)
+        #[arg(long)]
+        history: bool,
+    },
+    /// Report semantic clones (near-identical functions) grouped by cluster
+    Clones {
+        /// Database path
+        #[arg(long, default_value = ".codegraph/codegraph.db")]
+        db: String,
+        /// Minimum cosine similarity of a clone pair
+        #[arg(long, default_value_t = 0.95)]
+        threshold: f32,
+        /// Minimum token-count ratio of the shorter to the longer body
+        #[arg(long, default_value_t = 0.7)]
+        min_length_ratio: f64,
+        /// Include functions in test files
+        #[arg(long)]
+        include_tests: bool,
     },
     /// Detect frameworks and libraries used in the project
     Frameworks {
@@ -132,6 +285,29 @@ enum Commands {
         #[arg(default_value = ".")]
         directory: String,
     },
+    /// Scan for security vulnerabilities, reporting only findings that are
+    /// neither suppressed inline nor accepted in the baseline; exits
+    /// non-zero when any are left
+    ScanSecurity {
+        /// Project directory
+        #[arg(default_value = ".")]
+        directory: String,
+        /// Record every current finding in .codegraph/security-baseline.json
+        #[arg(long)]
+        write_baseline: bool,
+        /// Also report findings the baseline accepts
+        #[arg(long)]
+        all: bool,
+        /// Scan test files too
+        #[arg(long)]
+        include_tests: bool,
+    },
+    /// List or validate security rules, including custom rule packs
+    Rules {
+        /// Rules action
+        #[command(subcommand)]
+        action: RulesAction,
+    },
     /// Show language breakdown statistics
     Languages {
         /// Database path
@@ -140,7 +316,7 @@ enum Commands {
     },
     /// Install or manage git hooks
     GitHooks {
-        /// Action: install or uninstall
+        /// Action: install, uninstall, install-guard or uninstall-guard
         #[arg(default_value = "install")]
         action: String,
         /// Project directory
@@ -156,6 +332,145 @@ enum Commands {
         #[arg(long, default_value = ".codegraph/codegraph.db")]
         db: String,
     },
+    /// Save a labeled snapshot of the current graph (or list snapshots)
+    Snapshot {
+        /// Snapshot label (omit with --list)
+        label: Option<String>,
+        /// List existing snapshots instead of creating one
+        #[arg(long)]
+        list: bool,
+        /// Delete the snapshot with the given label
+        #[arg(long)]
+        delete: bool,
+        /// Database path
+        #[arg(long, default_value = ".codegraph/codegraph.db")]
+        db: String,
+    },
+    /// Report symbols and edges added/removed/changed since a snapshot
+    DiffIndex {
+        /// Snapshot label to compare from
+        snapshot: String,
+        /// Compare against another snapshot instead of the current graph
+        #[arg(long)]
+        against: Option<String>,
+        /// Database path
+        #[arg(long, default_value = ".codegraph/codegraph.db")]
+        db: String,
+    },
+    /// Purge tombstones of removed symbols and compact the database
+    Vacuum {
+        /// Only purge tombstones older than this many days (default: all)
+        #[arg(long)]
+        older_than_days: Option<u64>,
+        /// Database path
+        #[arg(long, default_value = ".codegraph/codegraph.db")]
+        db: String,
+    },
+    /// Check database integrity, rebuild the search index and missing indexes, and report orphaned edges
+    Doctor {
+        /// Delete orphaned edges instead of only reporting them
+        #[arg(long)]
+        fix: bool,
+        /// Database path
+        #[arg(long, default_value = ".codegraph/codegraph.db")]
+        db: String,
+    },
+    /// Inspect the index database
+    Db {
+        /// Database action
+        #[command(subcommand)]
+        action: DbAction,
+    },
+    /// Export the graph (or a scoped subgraph) to GraphML, DOT, or JSON Lines
+    Export {
+        /// Output format: graphml, dot, or jsonl (default: from --out extension, else jsonl)
+        #[arg(long, short)]
+        format: Option<String>,
+        /// Write to this file instead of stdout
+        #[arg(long, short)]
+        out: Option<String>,
+        /// Only include symbols under this path prefix
+        #[arg(long)]
+        path: Option<String>,
+        /// Export the neighbourhood of this symbol (name or node ID)
+        #[arg(long)]
+        symbol: Option<String>,
+        /// Hops to follow from --symbol
+        #[arg(long, default_value_t = 2)]
+        depth: usize,
+        /// Database path
+        #[arg(long, default_value = ".codegraph/codegraph.db")]
+        db: String,
+    },
+    /// Export the public API surface as JSON for docs generators
+    ApiDocs {
+        /// Write to this file instead of stdout
+        #[arg(long, short)]
+        out: Option<String>,
+        /// Only include modules under this path prefix
+        #[arg(long)]
+        path: Option<String>,
+        /// Maximum test-derived usage examples per symbol
+        #[arg(long, default_value_t = codegraph::graph::api_docs::DEFAULT_MAX_EXAMPLES)]
+        max_examples: usize,
+        /// Database path
+        #[arg(long, default_value = ".codegraph/codegraph.db")]
+        db: String,
+    },
+    /// Check changed files for breaking changes to symbols used elsewhere
+    ApiGuard {
+        /// Project directory
+        #[arg(default_value = ".")]
+        directory: String,
+        /// Compare the working tree against this revision
+        #[arg(long, default_value = "HEAD")]
+        since: String,
+        /// Print the report as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Check import and call edges against the architecture layer rules
+    ArchCheck {
+        /// Project directory
+        #[arg(default_value = ".")]
+        directory: String,
+        /// Print the report as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Import precise references from a SCIP index or LSIF dump
+    Import {
+        /// Path to index.scip or dump.lsif
+        file: String,
+        /// Index format: scip or lsif (default: detected from the file)
+        #[arg(long, short)]
+        format: Option<String>,
+        /// Project root used to relativise absolute document URIs
+        #[arg(long, default_value = ".")]
+        root: String,
+        /// Database path
+        #[arg(long, default_value = ".codegraph/codegraph.db")]
+        db: String,
+    },
+    /// Publish the local index to (or pull it from) a shared Postgres database
+    #[cfg(feature = "postgres")]
+    Sync {
+        /// Postgres connection string, e.g. postgres://user@host/codegraph
+        url: String,
+        /// Replace the local graph with the shared one instead of publishing
+        #[arg(long)]
+        pull: bool,
+        /// Database path
+        #[arg(long, default_value = ".codegraph/codegraph.db")]
+        db: String,
+    },
+    /// Keep the index and models loaded behind a unix socket for fast CLI calls
+    #[cfg(unix)]
+    Daemon {
+        /// Daemon action
+        #[command(subcommand)]
+        action: DaemonAction,
+    },
     /// Multi-repo workspace management
     Workspace {
         /// Workspace action
@@ -195,33 +510,97 @@ fn main() {
         } => {
             cmd_init(&directory, non_interactive);
         }
-        Commands::Index { directory, force } => {
-            cmd_index(&directory, force);
+        Commands::Index {
+            directory,
+            force,
+            warm_start,
+            since,
+        } => {
+            cmd_index(&directory, force, warm_start.as_deref(), since.as_deref());
         }
-        Commands::Query { query, limit } => {
-            cmd_query(&query, limit);
+        Commands::Query {
+            query,
+            limit,
+            save,
+            saved,
+            params,
+            list_saved,
+            federated,
+        } => {
+            if list_saved {
+                cmd_list_saved_queries();
+            } else if federated {
+                cmd_federated_query(query.as_deref().unwrap_or_default(), limit);
+            } else if let Some(name) = saved {
+                cmd_run_saved_query(&name, &params);
+            } else if let Some(name) = save {
+                cmd_save_query(&name, query.as_deref().unwrap_or_default());
+            } else {
+                cmd_query(query.as_deref().unwrap_or_default(), limit);
+            }
         }
         Commands::Impact { target, db } => {
             cmd_impact(&target, &db);
         }
+        Commands::Explain { node_id, db } => {
+            cmd_explain(&node_id, &db);
+        }
         Commands::Watch { directory } => {
             cmd_watch(&directory);
         }
-        Commands::Serve { db, http } => {
-            cmd_serve(&db, http.as_deref());
+        Commands::Serve {
+            db,
+            http,
+            workspace: Some(workspace),
+            ..
+        } => {
+            let _ = db;
+            cmd_serve_workspace(&workspace, http.as_deref().unwrap_or_default());
+        }
+        Commands::Serve {
+            db, http, roots, ..
+        } => {
+            cmd_serve(&db, http.as_deref(), &roots);
+        }
+        Commands::Lsp { db } => {
+            cmd_lsp(&db);
         }
-        Commands::Stats { db } => {
-            cmd_stats(&db);
+        Commands::Stats { db, by_dir } => {
+            cmd_stats(&db, by_dir);
+        }
+        Commands::Usage { days, db } => {
+            cmd_usage(days, &db);
         }
         Commands::InstallHooks { directory } => {
             cmd_install_hooks(&directory);
         }
-        Commands::DeadCode { db, kind } => {
-            cmd_dead_code(&db, kind.as_deref());
+        Commands::DeadCode { db, kind, history } => {
+            cmd_dead_code(&db, kind.as_deref(), history);
+        }
+        Commands::Clones {
+            db,
+            threshold,
+            min_length_ratio,
+            include_tests,
+        } => {
+            cmd_clones(&db, threshold, min_length_ratio, include_tests);
         }
         Commands::Frameworks { directory } => {
             cmd_frameworks(&directory);
         }
+        Commands::ScanSecurity {
+            directory,
+            write_baseline,
+            all,
+            include_tests,
+        } => cmd_scan_security(&directory, write_baseline, all, include_tests),
+        Commands::Rules { action } => match action {
+            RulesAction::List {
+                packs_only,
+                directory,
+            } => cmd_rules_list(&directory, packs_only),
+            RulesAction::Validate { directory } => cmd_rules_validate(&directory),
+        },
         Commands::Languages { db } => {
             cmd_languages(&db);
         }
@@ -239,6 +618,81 @@ fn main() {
                 std::process::exit(1);
             }
         }
+        Commands::Snapshot {
+            label,
+            list,
+            delete,
+            db,
+        } => {
+            cmd_snapshot(label.as_deref(), list, delete, &db);
+        }
+        Commands::DiffIndex {
+            snapshot,
+            against,
+            db,
+        } => {
+            cmd_diff_index(&snapshot, against.as_deref(), &db);
+        }
+        Commands::Vacuum {
+            older_than_days,
+            db,
+        } => {
+            cmd_vacuum(older_than_days, &db);
+        }
+        Commands::Doctor { fix, db } => {
+            cmd_doctor(fix, &db);
+        }
+        Commands::Db { action } => match action {
+            DbAction::Stats { slowest, db } => cmd_db_stats(slowest, &db),
+            DbAction::CacheSave { out, db } => cmd_cache_save(&out, &db),
+            DbAction::CacheRestore { artifact, db } => cmd_cache_restore(&artifact, &db),
+        },
+        Commands::Export {
+            format,
+            out,
+            path,
+            symbol,
+            depth,
+            db,
+        } => {
+            cmd_export(format.as_deref(), out.as_deref(), path, symbol, depth, &db);
+        }
+        Commands::ApiDocs {
+            out,
+            path,
+            max_examples,
+            db,
+        } => {
+            cmd_api_docs(out.as_deref(), path, max_examples, &db);
+        }
+        Commands::ApiGuard {
+            directory,
+            since,
+            json,
+        } => {
+            cmd_api_guard(&directory, &since, json);
+        }
+        Commands::ArchCheck { directory, json } => {
+            cmd_arch_check(&directory, json);
+        }
+        Commands::Import {
+            file,
+            format,
+            root,
+            db,
+        } => {
+            cmd_import(&file, format.as_deref(), &root, &db);
+        }
+        #[cfg(feature = "postgres")]
+        Commands::Sync { url, pull, db } => {
+            cmd_sync(&url, pull, &db);
+        }
+        #[cfg(unix)]
+        Commands::Daemon { action } => match action {
+            DaemonAction::Start { directory } => cmd_daemon_start(&directory),
+            DaemonAction::Status { directory } => cmd_daemon_status(&directory),
+            DaemonAction::Stop { directory } => cmd_daemon_stop(&directory),
+        },
         Commands::Workspace { action } => {
             let dir = std::path::Path::new(".");
             let result = match action {
@@ -376,7 +830,7 @@ fn cmd_init(directory: &str, non_interactive: bool) {
     // Step 6: Index with progress
     let total_files = file_paths.len() as u64;
     let spinner = installer::create_spinner(&format!("Indexing {} files...", total_files));
-    cmd_index(directory, false);
+    cmd_index(directory, false, None, None);
     spinner.finish_and_clear();
 
     // Get stats for summary
@@ -467,7 +921,12 @@ fn cmd_install_hooks(directory: &str) {
     tracing::info!("Hooks installed in {}", root.display());
 }
 
-fn cmd_index(directory: &str, force: bool) {
+fn cmd_index(
+    directory: &str,
+    force: bool,
+    warm_start: Option<&std::path::Path>,
+    since: Option<&str>,
+) {
     let root = PathBuf::from(directory).canonicalize().unwrap_or_else(|e| {
         tracing::error!("cannot resolve directory '{}': {}", directory, e);
         process::exit(1);
@@ -479,19 +938,92 @@ fn cmd_index(directory: &str, force: bool) {
         process::exit(1);
     });
 
+    // The daemon has no warm-start or --since request; index in-process.
+    #[cfg(unix)]
+    if let (None, None, Ok(response)) = (
+        warm_start,
+        since,
+        codegraph::daemon::request(
+            &codegraph::daemon::socket_path(&root),
+            &DaemonRequest::Index { force },
+        ),
+    ) {
+        match response {
+            DaemonResponse::Indexed {
+                files_indexed,
+                files_skipped,
+                nodes_created,
+                edges_created,
+                duration_ms,
+            } => println!(
+                "Indexed {} files ({} skipped): {} nodes, {} edges in {}ms (via daemon)",
+                files_indexed, files_skipped, nodes_created, edges_created, duration_ms,
+            ),
+            other => daemon_failed(other),
+        }
+        return;
+    }
+
     let db_path = db_dir.join("codegraph.db");
     let store = open_store(db_path.to_str().unwrap());
-    let pipeline = IndexingPipeline::new(&store);
+    let config = load_config(None, Some(&root)).unwrap_or_default();
+    store.set_body_storage(config.performance.body_storage());
+    store.set_tombstone_policy(config.performance.tombstone_policy());
+    codegraph::sandbox::register(config.sandbox.policy(), &root);
+    let pipeline = IndexingPipeline::new(&store)
+        .with_parser_chains(config.parsing.parser_chains())
+        .with_blame_metadata(config.git.blame_metadata());
+    let pipeline = match config.performance.ann_index {
+        Some(enabled) => pipeline.with_ann_index(enabled),
+        None => pipeline,
+    };
 
-    let result = pipeline
-        .index_directory(&IndexOptions {
-            root_dir: root.clone(),
-            incremental: !force,
-        })
-        .unwrap_or_else(|e| {
-            tracing::error!("indexing failed: {}", e);
+    if let Some(old_db) = warm_start {
+        let copied = pipeline.warm_start(old_db, &root).unwrap_or_else(|e| {
+            tracing::error!("warm start failed: {}", e);
             process::exit(1);
         });
+        println!(
+            "Warm start: copied {} unchanged files ({} nodes, {} edges) from {}",
+            copied.files,
+            copied.nodes,
+            copied.edges,
+            old_db.display(),
+        );
+    }
+
+    // Without a restored index there is nothing to update; index it all.
+    let restored = store.get_stats().is_ok_and(|s| s.files > 0);
+    let result = match since {
+        Some(rev) if restored => {
+            let changes = codegraph::git::changed_files_since(&root, rev).unwrap_or_else(|e| {
+                tracing::error!("cannot list changes since {}: {}", rev, e);
+                process::exit(1);
+            });
+            println!(
+                "Changed since {}: {} files, {} deleted",
+                rev,
+                changes.changed.len(),
+                changes.deleted.len(),
+            );
+            pipeline.index_changed(&root, &changes.changed, &changes.deleted)
+        }
+        _ => {
+            if since.is_some() {
+                eprintln!("No existing index to update; indexing everything.");
+            }
+            // After a warm start the copied files must be skipped, even
+            // with --force.
+            pipeline.index_directory(&IndexOptions {
+                root_dir: root.clone(),
+                incremental: !force || warm_start.is_some(),
+            })
+        }
+    }
+    .unwrap_or_else(|e| {
+        tracing::error!("indexing failed: {}", e);
+        process::exit(1);
+    });
 
     println!("{}", result);
 
@@ -503,14 +1035,46 @@ fn cmd_index(directory: &str, force: bool) {
 }
 
 fn cmd_query(query: &str, limit: usize) {
-    let store = open_store(".codegraph/codegraph.db");
-    let search = HybridSearch::new(&store.conn);
-    let opts = SearchOptions {
-        limit: Some(limit),
-        ..Default::default()
+    #[cfg(unix)]
+    let via_daemon = codegraph::daemon::request(
+        &codegraph::daemon::socket_path(std::path::Path::new(".")),
+        &DaemonRequest::Query {
+            query: query.to_string(),
+            limit,
+            deep: false,
+        },
+    )
+    .ok()
+    .map(|response| match response {
+        DaemonResponse::Results { results } => results,
+        other => daemon_failed(other),
+    });
+    #[cfg(not(unix))]
+    let via_daemon = None;
+
+    let outcome = match via_daemon {
+        Some(results) => Ok(results),
+        None => {
+            let store = open_store(".codegraph/codegraph.db");
+            let config = load_config(None, Some(std::path::Path::new("."))).unwrap_or_default();
+            let search = HybridSearch::new(&store.conn)
+                .with_priors(config.ranking.prior_weights())
+                .with_synonyms(config.synonym_map());
+            let opts = SearchOptions {
+                limit: Some(limit),
+                ..Default::default()
+            };
+            search.search(query, &opts)
+        }
     };
+    print_search_results(query, outcome);
+}
 
-    match search.search(query, &opts) {
+fn print_search_results(
+    query: &str,
+    outcome: codegraph::error::Result<Vec<codegraph::graph::search::SearchResult>>,
+) {
+    match outcome {
         Ok(results) => {
             if results.is_empty() {
                 println!("No results found for \"{}\".", query);
@@ -537,6 +1101,133 @@ fn cmd_query(query: &str, limit: usize) {
     }
 }
 
+fn cmd_federated_query(query: &str, limit: usize) {
+    let store = open_store(".codegraph/codegraph.db");
+    let root = std::path::Path::new(".");
+    let config = load_config(None, Some(root)).unwrap_or_default();
+    if config.federation.indexes.is_empty() {
+        eprintln!("No federation indexes configured; add them under federation.indexes in .codegraph.yaml.");
+    }
+    let mut federation = config
+        .federation
+        .open(root)
+        .with_priors(config.ranking.prior_weights())
+        .with_synonyms(config.synonym_map());
+    let opts = SearchOptions {
+        limit: Some(limit),
+        ..Default::default()
+    };
+    match federation.search(Some(&store), query, &opts) {
+        Ok(found) => {
+            for (index, error) in &found.errors {
+                eprintln!("skipped index {index}: {error}");
+            }
+            if found.results.is_empty() {
+                println!("No results found for \"{}\".", query);
+            }
+            for (i, r) in found.results.iter().enumerate() {
+                println!(
+                    "{}. [{}] {} ({}) — {} [score: {:.4}]",
+                    i + 1,
+                    r.index,
+                    r.result.name,
+                    r.result.kind,
+                    r.result.file_path,
+                    r.result.score
+                );
+            }
+        }
+        Err(e) => {
+            tracing::error!("search failed: {}", e);
+            process::exit(1);
+        }
+    }
+}
+
+fn cmd_save_query(name: &str, query: &str) {
+    let store = open_store(".codegraph/codegraph.db");
+    let saved = SavedQuery {
+        query: query.to_string(),
+        ..Default::default()
+    };
+    match save_query(&store.conn, name, &saved) {
+        Ok(()) => {
+            println!("Saved query \"{}\".", name.trim());
+            let params = saved.parameters();
+            if !params.is_empty() {
+                println!("Parameters: {}", params.join(", "));
+            }
+        }
+        Err(e) => {
+            eprintln!("{e}");
+            process::exit(1);
+        }
+    }
+}
+
+fn cmd_run_saved_query(name: &str, params: &[String]) {
+    let store = open_store(".codegraph/codegraph.db");
+    let config = load_config(None, Some(std::path::Path::new("."))).unwrap_or_default();
+    let saved = match find_saved_query(&store.conn, &config.saved_queries, name) {
+        Ok(Some(saved)) => saved,
+        Ok(None) => {
+            eprintln!("No saved query named \"{name}\". See `codegraph query --list-saved`.");
+            process::exit(1);
+        }
+        Err(e) => {
+            eprintln!("{e}");
+            process::exit(1);
+        }
+    };
+    let mut args = std::collections::HashMap::new();
+    for param in params {
+        let Some((key, value)) = param.split_once('=') else {
+            eprintln!("Invalid --param '{param}'; expected KEY=VALUE.");
+            process::exit(1);
+        };
+        args.insert(key.trim().to_string(), value.to_string());
+    }
+    let bound = saved.definition.bind(&args).unwrap_or_else(|e| {
+        eprintln!("{e}");
+        process::exit(1);
+    });
+    let search = HybridSearch::new(&store.conn)
+        .with_priors(config.ranking.prior_weights())
+        .with_synonyms(config.synonym_map());
+    let outcome = search.search(&bound.query, &bound.search_options());
+    print_search_results(&bound.query, outcome);
+}
+
+fn cmd_list_saved_queries() {
+    let store = open_store(".codegraph/codegraph.db");
+    let config = load_config(None, Some(std::path::Path::new("."))).unwrap_or_default();
+    let queries = list_saved_queries(&store.conn, &config.saved_queries).unwrap_or_else(|e| {
+        eprintln!("{e}");
+        process::exit(1);
+    });
+    if queries.is_empty() {
+        println!("No saved queries. Save one with `codegraph query <text> --save <name>`.");
+        return;
+    }
+    for q in &queries {
+        let params = if q.parameters.is_empty() {
+            String::new()
+        } else {
+            format!(" ({})", q.parameters.join(", "))
+        };
+        println!(
+            "{}{} [{}] — {}",
+            q.name,
+            params,
+            q.source.as_str(),
+            q.definition.query
+        );
+        if let Some(ref description) = q.definition.description {
+            println!("   {description}");
+        }
+    }
+}
+
 fn cmd_impact(target: &str, db_path: &str) {
     let store = open_store(db_path);
     let ranking = GraphRanking::new(&store);
@@ -552,15 +1243,45 @@ fn cmd_impact(target: &str, db_path: &str) {
     }
 }
 
-fn cmd_serve(db_path: &str, http_addr: Option<&str>) {
-    let db = PathBuf::from(db_path);
-    if !db.exists() {
-        tracing::error!("database not found at '{}'", db_path);
-        tracing::error!("Run `codegraph index <dir>` first to create an index.");
-        process::exit(1);
+fn cmd_explain(node_ref: &str, db_path: &str) {
+    let store = open_store(db_path);
+    match codegraph::graph::dossier::build_dossier(&store, node_ref) {
+        Ok(Some(dossier)) => print!("{}", dossier.render(chrono::Utc::now().timestamp())),
+        Ok(None) => {
+            if let Some(stone) = store
+                .get_tombstones_by_name(node_ref)
+                .ok()
+                .and_then(|s| s.into_iter().next())
+            {
+                let when = chrono::DateTime::from_timestamp(stone.deleted_at, 0)
+                    .map(|dt| dt.format("%Y-%m-%d %H:%M:%S").to_string())
+                    .unwrap_or_default();
+                println!(
+                    "{} {} was removed from {}:{} at {}.",
+                    stone.kind, stone.name, stone.file_path, stone.start_line, when
+                );
+            } else {
+                tracing::error!("no node matches '{}'", node_ref);
+                process::exit(1);
+            }
+        }
+        Err(e) => {
+            tracing::error!("cannot explain '{}': {}", node_ref, e);
+            process::exit(1);
+        }
+    }
+}
+
+fn cmd_serve(db_path: &str, http_addr: Option<&str>, root_specs: &[String]) {
+    let db = PathBuf::from(db_path);
+    if !db.exists() {
+        tracing::error!("database not found at '{}'", db_path);
+        tracing::error!("Run `codegraph index <dir>` first to create an index.");
+        process::exit(1);
     }
 
     let store = open_store(db_path);
+    let roots = open_roots(root_specs);
 
     match http_addr {
         Some(addr) => {
@@ -574,7 +1295,7 @@ fn cmd_serve(db_path: &str, http_addr: Option<&str>) {
                 });
 
             rt.block_on(async {
-                if let Err(e) = codegraph::mcp::http::run_http_server(store, addr).await {
+                if let Err(e) = codegraph::mcp::http::run_http_server(store, roots, addr).await {
                     tracing::error!("HTTP MCP server failed: {}", e);
                     process::exit(1);
                 }
@@ -591,7 +1312,7 @@ fn cmd_serve(db_path: &str, http_addr: Option<&str>) {
                 });
 
             rt.block_on(async {
-                if let Err(e) = codegraph::mcp::server::run_server(store).await {
+                if let Err(e) = codegraph::mcp::server::run_server(store, roots).await {
                     tracing::error!("MCP server failed: {}", e);
                     process::exit(1);
                 }
@@ -600,7 +1321,89 @@ fn cmd_serve(db_path: &str, http_addr: Option<&str>) {
     }
 }
 
-fn cmd_dead_code(db_path: &str, kind_filter: Option<&str>) {
+/// Open the index of every `--root NAME=PATH` of `codegraph serve`.
+fn open_roots(specs: &[String]) -> Vec<codegraph::mcp::roots::ProjectRoot> {
+    let mut roots: Vec<codegraph::mcp::roots::ProjectRoot> = Vec::new();
+    for spec in specs {
+        let Some((name, path)) = codegraph::mcp::roots::parse_root_spec(spec) else {
+            tracing::error!(
+                "invalid --root '{}': expected NAME=PATH, NAME of letters, digits, '-', '_' or '.'",
+                spec
+            );
+            process::exit(1);
+        };
+        if roots.iter().any(|r| r.name == name) {
+            tracing::error!("--root '{}' given twice", name);
+            process::exit(1);
+        }
+        let db = path.join(".codegraph").join("codegraph.db");
+        if !db.exists() {
+            tracing::error!("database not found at '{}'", db.display());
+            tracing::error!("Run `codegraph index {}` first.", path.display());
+            process::exit(1);
+        }
+        let store = open_store(&db.to_string_lossy());
+        let path = path.canonicalize().unwrap_or(path);
+        roots.push(codegraph::mcp::roots::ProjectRoot::new(name, path, store));
+    }
+    roots
+}
+
+fn cmd_lsp(db_path: &str) {
+    let db = PathBuf::from(db_path);
+    if !db.exists() {
+        tracing::error!("database not found at '{}'", db_path);
+        tracing::error!("Run `codegraph index <dir>` first to create an index.");
+        process::exit(1);
+    }
+    // Indexed paths are relative to the directory holding `.codegraph/`.
+    let db = db.canonicalize().unwrap_or(db);
+    let root = db
+        .parent()
+        .filter(|dir| dir.file_name().is_some_and(|n| n == ".codegraph"))
+        .and_then(|dir| dir.parent())
+        .map(PathBuf::from)
+        .unwrap_or_else(|| std::env::current_dir().unwrap_or_else(|_| PathBuf::from(".")));
+
+    let store = open_store(db_path);
+    let rt = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+        .unwrap_or_else(|e| {
+            tracing::error!("cannot create async runtime: {}", e);
+            process::exit(1);
+        });
+    rt.block_on(codegraph::lsp::run_stdio(store, root));
+    // Serving can end on a message after `exit`, with the stdin reader
+    // thread still blocked on a read.
+    rt.shutdown_background();
+}
+
+fn cmd_serve_workspace(workspace_dir: &str, addr: &str) {
+    let admin_token = std::env::var("CODEGRAPH_ADMIN_TOKEN").ok();
+    let rt = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+        .unwrap_or_else(|e| {
+            tracing::error!("cannot create async runtime: {}", e);
+            process::exit(1);
+        });
+
+    rt.block_on(async {
+        if let Err(e) = codegraph::mcp::http::run_multi_tenant_server(
+            std::path::Path::new(workspace_dir),
+            addr,
+            admin_token,
+        )
+        .await
+        {
+            tracing::error!("multi-project HTTP server failed: {}", e);
+            process::exit(1);
+        }
+    });
+}
+
+fn cmd_dead_code(db_path: &str, kind_filter: Option<&str>, history: bool) {
     let store = open_store(db_path);
     let kinds: Vec<codegraph::types::NodeKind> = match kind_filter {
         Some(k) => k
@@ -610,18 +1413,114 @@ fn cmd_dead_code(db_path: &str, kind_filter: Option<&str>) {
         None => Vec::new(),
     };
 
-    let results = codegraph::resolution::dead_code::find_dead_code(&store.conn, &kinds);
+    let root = std::path::Path::new(".");
+    let roots = load_config(None, Some(root))
+        .unwrap_or_default()
+        .dead_code
+        .roots();
+    let results = match codegraph::resolution::dead_code::find_unreachable_code(
+        &store,
+        &kinds,
+        &roots,
+        Some(root),
+    ) {
+        Ok(r) => r,
+        Err(e) => {
+            eprintln!("Dead code analysis failed: {e}");
+            process::exit(1);
+        }
+    };
     if results.is_empty() {
         println!("No dead code found.");
         return;
     }
 
     println!("Potentially unused symbols ({} found):", results.len());
+    let now = chrono::Utc::now().timestamp();
     for r in &results {
         println!(
             "  {} ({}) — {}:{}",
             r.name, r.kind, r.file_path, r.start_line
         );
+        if !r.referenced_by.is_empty() {
+            println!(
+                "      used only by dead code: {}",
+                r.referenced_by.join(", ")
+            );
+        }
+        if history {
+            use codegraph::resolution::dead_code::{orphan_evidence, OrphanStatus};
+
+            let evidence = orphan_evidence(&store, Some(root), r, now);
+            let status = match evidence.status {
+                OrphanStatus::NeverUsed => "never used",
+                OrphanStatus::RecentlyOrphaned => "recently orphaned",
+                OrphanStatus::Orphaned => "orphaned",
+            };
+            match &evidence.removing_commit {
+                Some(c) => println!(
+                    "      {} — last references removed in {} ({}, {}): {}",
+                    status,
+                    &c.hash[..c.hash.len().min(10)],
+                    c.author,
+                    c.date,
+                    c.message
+                ),
+                None => println!("      {}", status),
+            }
+            if let Some(caller) = &evidence.last_caller {
+                println!("      last caller in the index: {}", caller);
+            }
+        }
+    }
+}
+
+fn cmd_clones(db_path: &str, threshold: f32, min_length_ratio: f64, include_tests: bool) {
+    use codegraph::graph::clones::{find_clones, CloneOptions};
+
+    let store = open_store(db_path);
+    let opts = CloneOptions {
+        threshold,
+        min_length_ratio,
+        include_tests,
+        ..Default::default()
+    };
+    let report = find_clones(&store, &opts).unwrap_or_else(|e| {
+        eprintln!("Clone detection failed: {e}");
+        process::exit(1);
+    });
+    if report.clusters.is_empty() {
+        println!(
+            "No clones found among {} functions ({} vectors).",
+            report.candidates,
+            report.source.as_str()
+        );
+        return;
+    }
+
+    println!(
+        "{} clone clusters among {} functions ({} vectors):",
+        report.clusters.len(),
+        report.candidates,
+        report.source.as_str()
+    );
+    for (i, cluster) in report.clusters.iter().enumerate() {
+        println!(
+            "\nCluster {} — {} functions, max similarity {:.3}",
+            i + 1,
+            cluster.members.len(),
+            cluster.max_similarity
+        );
+        for m in &cluster.members {
+            println!(
+                "  {} ({}) — {}:{}-{}",
+                m.name,
+                m.kind.as_str(),
+                m.file_path,
+                m.start_line,
+                m.end_line
+            );
+        }
     }
 }
 
@@ -646,6 +1545,117 @@ fn cmd_frameworks(directory: &str) {
     }
 }
 
+fn cmd_scan_security(directory: &str, write_baseline: bool, all: bool, include_tests: bool) {
+    use codegraph::security::baseline::{Baseline, BASELINE_FILE};
+
+    let root = Path::new(directory);
+    let rules = codegraph::security::packs::project_rules(root);
+    let mut summary = codegraph::security::scan_directory(root, &rules, !include_tests);
+
+    if write_baseline {
+        let baseline = Baseline::from_findings(&summary.findings, root);
+        if let Err(e) = baseline.save(root) {
+            eprintln!("Error: {e}");
+            std::process::exit(1);
+        }
+        println!(
+            "Recorded {} accepted findings in {}",
+            baseline.findings.len(),
+            root.join(BASELINE_FILE).display()
+        );
+        return;
+    }
+    if !all {
+        match Baseline::load(root) {
+            Ok(Some(baseline)) => summary.apply_baseline(&baseline, root),
+            Ok(None) => {}
+            Err(e) => eprintln!("warning: {e}"),
+        }
+    }
+
+    for f in &summary.findings {
+        println!(
+            "  {:<8} {}:{}  {}  {}",
+            f.severity.to_string(),
+            f.file_path,
+            f.line_number,
+            f.rule_id,
+            f.message
+        );
+    }
+    println!(
+        "{} findings in {} files ({} critical, {} high, {} medium, {} low); {} suppressed, {} baselined",
+        summary.total_findings,
+        summary.files_scanned,
+        summary.critical,
+        summary.high,
+        summary.medium,
+        summary.low,
+        summary.suppressed,
+        summary.baselined
+    );
+    if summary.total_findings > 0 {
+        std::process::exit(1);
+    }
+}
+
+fn cmd_rules_list(directory: &str, packs_only: bool) {
+    use codegraph::security::packs;
+
+    let dir = Path::new(directory).join(packs::RULES_DIR);
+    let (packs, errors) = packs::load_packs(&dir);
+    for error in &errors {
+        eprintln!("warning: skipping invalid pack: {error}");
+    }
+    let bundled = if packs_only {
+        Vec::new()
+    } else {
+        codegraph::security::load_bundled_rules()
+    };
+    let pack_rules: Vec<_> = packs.iter().flat_map(|p| &p.rules).collect();
+    println!(
+        "Security rules ({} bundled, {} from {} pack{}):",
+        bundled.len(),
+        pack_rules.len(),
+        packs.len(),
+        if packs.len() == 1 { "" } else { "s" }
+    );
+    for rule in bundled.iter().chain(pack_rules) {
+        println!(
+            "  {:<28} {:<9} {:<16} {}",
+            rule.id,
+            rule.severity.to_string(),
+            rule.category.to_string(),
+            rule.name
+        );
+    }
+}
+
+fn cmd_rules_validate(directory: &str) {
+    use codegraph::security::packs;
+
+    let dir = Path::new(directory).join(packs::RULES_DIR);
+    let (packs, errors) = packs::load_packs(&dir);
+    if packs.is_empty() && errors.is_empty() {
+        println!("No rule packs in {}", dir.display());
+        return;
+    }
+    for pack in &packs {
+        println!(
+            "ok     {} ({} rules, namespace {}/)",
+            pack.path.display(),
+            pack.rules.len(),
+            pack.name
+        );
+    }
+    for error in &errors {
+        println!("error  {error}");
+    }
+    if !errors.is_empty() {
+        std::process::exit(1);
+    }
+}
+
 fn cmd_languages(db_path: &str) {
     let store = open_store(db_path);
     let stats = store
@@ -656,26 +1666,642 @@ fn cmd_languages(db_path: &str) {
             edges: 0,
         });
 
-    // Query language breakdown from file_hashes
+    let lines = codegraph::graph::stats::load_language_lines(&store.conn).unwrap_or_default();
     let mut stmt = store
         .conn
-        .prepare(
-            "SELECT language, COUNT(*) FROM file_hashes GROUP BY language ORDER BY COUNT(*) DESC",
-        )
+        .prepare("SELECT language, COUNT(*) FROM file_hashes GROUP BY language")
         .unwrap();
-    let rows: Vec<(String, i64)> = stmt
+    let mut rows: Vec<(String, i64)> = stmt
         .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
         .unwrap()
         .filter_map(|r| r.ok())
         .collect();
+    let code_of = |lang: &str| lines.get(lang).map_or(0, |c| c.code);
+    rows.sort_by(|a, b| code_of(&b.0).cmp(&code_of(&a.0)).then(b.1.cmp(&a.1)));
 
     println!("Language breakdown ({} total files):", stats.files);
+    println!(
+        "  {:12} {:>7} {:>9} {:>9} {:>9} {:>9} {:>8}",
+        "language", "files", "blank", "comment", "code", "logical", "comment%"
+    );
+    let mut total = codegraph::indexer::loc::LineCounts::default();
     for (lang, count) in &rows {
-        println!("  {:12} — {} files", lang, count);
+        let c = lines.get(lang).copied().unwrap_or_default();
+        total.add(&c);
+        println!(
+            "  {:12} {:>7} {:>9} {:>9} {:>9} {:>9} {:>7.1}%",
+            lang,
+            count,
+            c.blank,
+            c.comment,
+            c.code,
+            c.logical,
+            c.comment_ratio() * 100.0
+        );
     }
+    println!(
+        "  {:12} {:>7} {:>9} {:>9} {:>9} {:>9} {:>7.1}%",
+        "total",
+        stats.files,
+        total.blank,
+        total.comment,
+        total.code,
+        total.logical,
+        total.comment_ratio() * 100.0
+    );
     println!("\nTotal: {} nodes, {} edges", stats.nodes, stats.edges);
 }
 
+fn cmd_snapshot(label: Option<&str>, list: bool, delete: bool, db_path: &str) {
+    use codegraph::db::snapshot;
+
+    let store = open_store(db_path);
+    if list {
+        let snapshots = snapshot::list_snapshots(&store.conn).unwrap_or_else(|e| {
+            tracing::error!("cannot list snapshots: {}", e);
+            process::exit(1);
+        });
+        if snapshots.is_empty() {
+            println!("No snapshots.");
+        }
+        for s in &snapshots {
+            println!(
+                "  {} — {} nodes, {} edges (created {})",
+                s.label, s.node_count, s.edge_count, s.created_at
+            );
+        }
+        return;
+    }
+
+    let Some(label) = label else {
+        tracing::error!("a snapshot label is required (or pass --list)");
+        process::exit(1);
+    };
+
+    if delete {
+        match snapshot::delete_snapshot(&store.conn, label) {
+            Ok(true) => println!("Deleted snapshot '{}'.", label),
+            Ok(false) => println!("No snapshot named '{}'.", label),
+            Err(e) => {
+                tracing::error!("cannot delete snapshot: {}", e);
+                process::exit(1);
+            }
+        }
+        return;
+    }
+
+    match snapshot::create_snapshot(&store.conn, label) {
+        Ok(info) => println!(
+            "Saved snapshot '{}' ({} nodes, {} edges).",
+            info.label, info.node_count, info.edge_count
+        ),
+        Err(e) => {
+            tracing::error!("cannot create snapshot: {}", e);
+            process::exit(1);
+        }
+    }
+}
+
+fn cmd_export(
+    format: Option<&str>,
+    out: Option<&str>,
+    path_prefix: Option<String>,
+    symbol: Option<String>,
+    depth: usize,
+    db_path: &str,
+) {
+    use codegraph::graph::export::{export_graph, ExportFormat, ExportScope};
+
+    let format = match format {
+        Some(f) => ExportFormat::from_str_loose(f).unwrap_or_else(|| {
+            eprintln!("Unknown export format '{}'. Use graphml, dot, or jsonl.", f);
+            process::exit(1);
+        }),
+        None => out
+            .and_then(ExportFormat::from_path)
+            .unwrap_or(ExportFormat::Jsonl),
+    };
+    let store = open_store(db_path);
+    let scope = ExportScope {
+        path_prefix,
+        symbol,
+        depth,
+        ..ExportScope::default()
+    };
+    // Rows stream from the store to the output as they are read.
+    let written = match out {
+        Some(path) => std::fs::File::create(path)
+            .map_err(Into::into)
+            .and_then(|file| {
+                use std::io::Write;
+                let mut writer = std::io::BufWriter::new(file);
+                let counts = export_graph(&store, &scope, format, &mut writer)?;
+                writer.flush()?;
+                Ok(counts)
+            }),
+        None => export_graph(&store, &scope, format, &mut std::io::stdout().lock()),
+    };
+    let (nodes, edges) = written.unwrap_or_else(|e| {
+        tracing::error!("cannot export graph: {}", e);
+        process::exit(1);
+    });
+    if let Some(path) = out {
+        eprintln!(
+            "Exported {} nodes and {} edges to {} ({})",
+            nodes,
+            edges,
+            path,
+            format.as_str()
+        );
+    }
+}
+
+fn cmd_api_docs(
+    out: Option<&str>,
+    path_prefix: Option<String>,
+    max_examples: usize,
+    db_path: &str,
+) {
+    use codegraph::graph::api_docs::{build_api_docs, ApiDocsOptions};
+
+    let store = open_store(db_path);
+    let options = ApiDocsOptions {
+        path_prefix,
+        max_examples,
+        root: std::env::current_dir().ok(),
+    };
+    let docs = build_api_docs(&store, &options).unwrap_or_else(|e| {
+        tracing::error!("cannot build API docs: {}", e);
+        process::exit(1);
+    });
+    let json = serde_json::to_string_pretty(&docs).unwrap_or_default();
+
+    match out {
+        Some(path) => {
+            if let Err(e) = std::fs::write(path, json) {
+                tracing::error!("cannot write {}: {}", path, e);
+                process::exit(1);
+            }
+            eprintln!(
+                "Wrote {} symbols across {} modules to {}",
+                docs.symbol_count, docs.module_count, path
+            );
+        }
+        None => println!("{}", json),
+    }
+}
+
+fn cmd_api_guard(directory: &str, since: &str, json: bool) {
+    use codegraph::graph::api_guard::{check_api_changes, BreakKind};
+
+    let root = PathBuf::from(directory).canonicalize().unwrap_or_else(|e| {
+        tracing::error!("cannot resolve directory '{}': {}", directory, e);
+        process::exit(1);
+    });
+    let db_path = root.join(".codegraph").join("codegraph.db");
+    if !db_path.exists() {
+        eprintln!("No index found. Run `codegraph index` first.");
+        process::exit(1);
+    }
+    let store = open_store(&db_path.to_string_lossy());
+    let config = load_config(None, Some(&root)).unwrap_or_default();
+
+    let files = codegraph::git::history::changed_files_since(&root, since).unwrap_or_else(|e| {
+        tracing::error!("cannot list changed files: {}", e);
+        process::exit(1);
+    });
+    let report = check_api_changes(
+        &store,
+        &root,
+        &files.changed,
+        &files.deleted,
+        &config.api_guard.policy(),
+    )
+    .unwrap_or_else(|e| {
+        tracing::error!("API guard failed: {}", e);
+        process::exit(1);
+    });
+
+    if json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&report).unwrap_or_default()
+        );
+    } else if report.breaks.is_empty() {
+        println!(
+            "No breaking API changes ({} files checked).",
+            report.files_checked
+        );
+    } else {
+        println!("Breaking API changes ({} found):", report.breaks.len());
+        for b in &report.breaks {
+            println!(
+                "  [{}] {} ({}) — {}:{}, used by {} symbols in {}",
+                b.mode.as_str(),
+                b.name,
+                b.kind,
+                b.file_path,
+                b.line,
+                b.external_callers,
+                b.caller_files.join(", ")
+            );
+            match (b.change, &b.new_signature) {
+                (BreakKind::SignatureChanged, Some(new)) => {
+                    println!("      - {}\n      + {}", b.old_signature, new);
+                }
+                _ => println!("      removed: {}", b.old_signature),
+            }
+        }
+        if report.version_note {
+            println!("A version bump note is part of the change.");
+        } else if report.blocked {
+            eprintln!("Blocked: add a version bump note (e.g. a CHANGELOG.md entry) to proceed.");
+        }
+    }
+
+    if report.blocked {
+        process::exit(1);
+    }
+}
+
+fn cmd_arch_check(directory: &str, json: bool) {
+    use codegraph::graph::architecture::check_architecture;
+
+    let root = PathBuf::from(directory).canonicalize().unwrap_or_else(|e| {
+        tracing::error!("cannot resolve directory '{}': {}", directory, e);
+        process::exit(1);
+    });
+    let db_path = root.join(".codegraph").join("codegraph.db");
+    if !db_path.exists() {
+        eprintln!("No index found. Run `codegraph index` first.");
+        process::exit(1);
+    }
+    let store = open_store(&db_path.to_string_lossy());
+    let config = load_config(None, Some(&root)).unwrap_or_default();
+    let policy = config.architecture.policy();
+    if policy.rules.is_empty() {
+        eprintln!(
+            "No architecture rules. Add `architecture:` layers and rules to .codegraph.yaml."
+        );
+        process::exit(1);
+    }
+
+    let report = check_architecture(&store, &policy).unwrap_or_else(|e| {
+        tracing::error!("architecture check failed: {}", e);
+        process::exit(1);
+    });
+
+    if json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&report).unwrap_or_default()
+        );
+    } else {
+        for name in &report.unknown_layers {
+            eprintln!("Warning: rules name undefined layer '{}'", name);
+        }
+        if report.violations.is_empty() {
+            println!(
+                "No layer violations ({} cross-layer edges checked).",
+                report.edges_checked
+            );
+        } else {
+            println!("Layer violations ({} found):", report.violations.len());
+            for v in &report.violations {
+                println!(
+                    "  {}:{} {} -> {} ({} {} -> {} in {})",
+                    v.file_path,
+                    v.line,
+                    v.from_layer,
+                    v.to_layer,
+                    v.edge_kind.as_str(),
+                    v.source_name,
+                    v.target_name,
+                    v.target_file
+                );
+                if let Some(reason) = &v.reason {
+                    println!("      {}", reason);
+                }
+            }
+        }
+    }
+
+    if !report.violations.is_empty() {
+        process::exit(1);
+    }
+}
+
+fn cmd_import(file: &str, format: Option<&str>, root: &str, db_path: &str) {
+    use codegraph::interop::{import_index, IndexFormat};
+
+    let format = format.map(|f| {
+        IndexFormat::from_str_loose(f).unwrap_or_else(|| {
+            eprintln!("Unknown index format '{}'. Use scip or lsif.", f);
+            process::exit(1);
+        })
+    });
+    let root = std::fs::canonicalize(root).unwrap_or_else(|_| std::path::PathBuf::from(root));
+    let store = open_store(db_path);
+    let stats =
+        import_index(&store, std::path::Path::new(file), format, &root).unwrap_or_else(|e| {
+            tracing::error!("cannot import {}: {}", file, e);
+            process::exit(1);
+        });
+
+    println!(
+        "Imported {} references from {} documents",
+        stats.references, stats.documents
+    );
+    println!("  New edges:       {}", stats.edges_added);
+    println!("  Confirmed edges: {}", stats.edges_confirmed);
+    println!("  Unmatched:       {}", stats.unmatched);
+}
+
+#[cfg(feature = "postgres")]
+fn cmd_sync(url: &str, pull: bool, db_path: &str) {
+    use codegraph::graph::backend::copy_graph;
+    use codegraph::graph::postgres::PostgresStore;
+
+    let local = open_store(db_path);
+    let remote = PostgresStore::connect(url).unwrap_or_else(|e| {
+        tracing::error!("cannot connect to Postgres: {}", e);
+        process::exit(1);
+    });
+    let (stats, direction) = if pull {
+        (copy_graph(&remote, &local), "Pulled")
+    } else {
+        (copy_graph(&local, &remote), "Published")
+    };
+    match stats {
+        Ok(s) => println!(
+            "{} {} nodes and {} edges across {} files",
+            direction, s.nodes, s.edges, s.files
+        ),
+        Err(e) => {
+            tracing::error!("sync failed: {}", e);
+            process::exit(1);
+        }
+    }
+}
+
+fn cmd_vacuum(older_than_days: Option<u64>, db_path: &str) {
+    let store = open_store(db_path);
+    let cutoff = older_than_days.map(|days| {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or(0);
+        now - (days as i64) * 86_400
+    });
+    match store.vacuum(cutoff) {
+        Ok(stats) => println!(
+            "Purged {} symbol and {} edge tombstones; database compacted.",
+            stats.nodes, stats.edges
+        ),
+        Err(e) => {
+            tracing::error!("vacuum failed: {}", e);
+            process::exit(1);
+        }
+    }
+}
+
+fn cmd_doctor(fix: bool, db_path: &str) {
+    use codegraph::db::doctor;
+
+    /// Orphaned edges listed before the rest are summarized.
+    const MAX_LISTED: usize = 20;
+
+    let store = open_store(db_path);
+    let report = doctor::run_doctor(&store.conn, fix).unwrap_or_else(|e| {
+        tracing::error!("doctor failed: {}", e);
+        process::exit(1);
+    });
+
+    if !report.integrity_errors.is_empty() {
+        println!("Integrity check: FAILED");
+        for line in &report.integrity_errors {
+            println!("  {}", line);
+        }
+        println!("Restore from a backup or re-index with `codegraph index --force`.");
+        process::exit(1);
+    }
+    println!("Integrity check: ok");
+    if report.fts_rebuilt {
+        println!("Search index: out of sync with nodes, rebuilt");
+    } else {
+        println!("Search index: ok");
+    }
+    if report.recreated_indexes.is_empty() {
+        println!("Indexes: ok");
+    } else {
+        println!(
+            "Indexes: re-created {}",
+            report.recreated_indexes.join(", ")
+        );
+    }
+    if report.orphaned_edges.is_empty() {
+        println!("Orphaned edges: none");
+        return;
+    }
+    println!("Orphaned edges: {}", report.orphaned_edges.len());
+    for e in report.orphaned_edges.iter().take(MAX_LISTED) {
+        let missing = match (e.source_missing, e.target_missing) {
+            (true, true) => "source and target missing",
+            (true, false) => "source missing",
+            _ => "target missing",
+        };
+        println!("  {} -[{}]-> {} ({})", e.source, e.kind, e.target, missing);
+    }
+    if report.orphaned_edges.len() > MAX_LISTED {
+        println!(
+            "  ... and {} more",
+            report.orphaned_edges.len() - MAX_LISTED
+        );
+    }
+    if fix {
+        println!("Pruned {} orphaned edges.", report.pruned_edges);
+    } else {
+        println!("Run `codegraph doctor --fix` to prune them.");
+        process::exit(1);
+    }
+}
+
+fn cmd_cache_save(out: &std::path::Path, db_path: &str) {
+    let store = open_store(db_path);
+    match codegraph::db::cache::save_cache(&store.conn, out) {
+        Ok(bytes) => println!(
+            "Saved index cache to {} ({:.2} MiB)",
+            out.display(),
+            bytes as f64 / (1024.0 * 1024.0)
+        ),
+        Err(e) => {
+            tracing::error!("cache save failed: {}", e);
+            process::exit(1);
+        }
+    }
+}
+
+fn cmd_cache_restore(artifact: &std::path::Path, db_path: &str) {
+    match codegraph::db::cache::restore_cache(artifact, std::path::Path::new(db_path)) {
+        Ok(files) => println!(
+            "Restored index cache from {} ({} files); run `codegraph index --since <rev>` to update it",
+            artifact.display(),
+            files
+        ),
+        Err(e) => {
+            tracing::error!("cache restore failed: {}", e);
+            process::exit(1);
+        }
+    }
+}
+
+fn cmd_db_stats(slowest: usize, db_path: &str) {
+    use codegraph::db::diagnostics;
+
+    /// FTS5 segment count above which full-text search is worth merging.
+    const MANY_FTS_SEGMENTS: u64 = 32;
+
+    fn mib(bytes: u64) -> String {
+        format!("{:.2} MiB", bytes as f64 / (1024.0 * 1024.0))
+    }
+
+    let store = open_store(db_path);
+    let stats = diagnostics::collect_db_stats(&store.conn, slowest).unwrap_or_else(|e| {
+        tracing::error!("cannot collect database stats: {}", e);
+        process::exit(1);
+    });
+
+    println!(
+        "Database: {} ({} pages of {} bytes, {} free)",
+        mib(stats.file_bytes()),
+        stats.page_count,
+        stats.page_size,
+        stats.freelist_count
+    );
+
+    println!("\nTables:");
+    for t in stats
+        .tables
+        .iter()
+        .filter(|t| t.bytes > 0 || t.rows.unwrap_or(0) > 0)
+    {
+        let rows = t
+            .rows
+            .map(|r| format!("{r} rows"))
+            .unwrap_or_else(|| "virtual".to_string());
+        println!("  {:<24} {:>12}  {}", t.name, mib(t.bytes), rows);
+    }
+
+    println!("\nIndexes:");
+    for i in &stats.indexes {
+        let mut notes = Vec::new();
+        if !i.used_by.is_empty() {
+            notes.push(format!("used by: {}", i.used_by.join(", ")));
+        }
+        if let Some(stat) = &i.analyzed {
+            notes.push(format!("stat1: {stat}"));
+        }
+        let line = format!(
+            "  {:<36} {:>12}  {}",
+            i.name,
+            mib(i.bytes),
+            notes.join("; ")
+        );
+        println!("{}", line.trim_end());
+    }
+
+    println!("\nFull-text index: {} segments", stats.fts_segments);
+    if stats.fts_segments > MANY_FTS_SEGMENTS {
+        println!(
+            "  Many segments slow searches; merge them with: \
+             sqlite3 {} \"INSERT INTO fts_nodes(fts_nodes) VALUES('optimize')\"",
+            db_path
+        );
+    }
+    match stats.vector_rows {
+        Some(rows) => println!(
+            "Vector table: {} embeddings, {}",
+            rows,
+            mib(stats.vector_bytes)
+        ),
+        None => println!("Vector table: not created"),
+    }
+
+    println!("\nQuery plans:");
+    for p in &stats.plans {
+        let verdict = if p.uses_index { "index" } else { "FULL SCAN" };
+        println!("  {:<18} {:<10} {}", p.label, verdict, p.plan.join(" | "));
+    }
+    if stats.plans.iter().any(|p| !p.uses_index) {
+        println!(
+            "  Full scans on large tables are slow; `codegraph doctor` re-creates missing indexes."
+        );
+    }
+
+    if stats.slow_queries.is_empty() {
+        println!(
+            "\nSlowest queries: none logged (enable performance.query_log or CODEGRAPH_QUERY_LOG=1)"
+        );
+    } else {
+        println!("\nSlowest queries:");
+        for q in &stats.slow_queries {
+            println!("  {:>9.1} ms  {}  {}", q.duration_ms, q.source, q.query);
+        }
+    }
+}
+
+fn cmd_diff_index(snapshot_label: &str, against: Option<&str>, db_path: &str) {
+    use codegraph::db::snapshot;
+
+    let store = open_store(db_path);
+    let diff = match against {
+        Some(to) => snapshot::diff_snapshots(&store.conn, snapshot_label, to),
+        None => snapshot::diff_with_current(&store.conn, snapshot_label),
+    }
+    .unwrap_or_else(|e| {
+        tracing::error!("cannot diff index: {}", e);
+        process::exit(1);
+    });
+
+    println!(
+        "Diff: {} -> {}",
+        snapshot_label,
+        against.unwrap_or("current")
+    );
+    if diff.is_empty() {
+        println!("  No changes.");
+        return;
+    }
+    for (label, nodes) in [
+        ("Added symbols", &diff.added_nodes),
+        ("Removed symbols", &diff.removed_nodes),
+        ("Changed symbols", &diff.changed_nodes),
+    ] {
+        if nodes.is_empty() {
+            continue;
+        }
+        println!("  {} ({}):", label, nodes.len());
+        for n in nodes {
+            println!(
+                "    {} ({}) — {}:{}",
+                n.name, n.kind, n.file_path, n.start_line
+            );
+        }
+    }
+    for (label, edges) in [
+        ("Added edges", &diff.added_edges),
+        ("Removed edges", &diff.removed_edges),
+    ] {
+        if edges.is_empty() {
+            continue;
+        }
+        println!("  {} ({}):", label, edges.len());
+        for e in edges {
+            println!("    {} -[{}]-> {}", e.source, e.kind, e.target);
+        }
+    }
+}
+
 fn cmd_git_hooks(action: &str, directory: &str) {
     match action {
         "install" => {
@@ -692,8 +2318,25 @@ fn cmd_git_hooks(action: &str, directory: &str) {
             }
             println!("Git post-commit hook removed.");
         }
+        "install-guard" => {
+            if let Err(e) = codegraph::hooks::git_hooks::install_git_api_guard_hook(directory) {
+                tracing::error!("{}", e);
+                process::exit(1);
+            }
+            println!("Git pre-commit API guard installed.");
+        }
+        "uninstall-guard" => {
+            if let Err(e) = codegraph::hooks::git_hooks::uninstall_git_api_guard_hook(directory) {
+                tracing::error!("{}", e);
+                process::exit(1);
+            }
+            println!("Git pre-commit API guard removed.");
+        }
         other => {
-            tracing::error!("Unknown action '{}'. Use 'install' or 'uninstall'.", other);
+            tracing::error!(
+                "Unknown action '{}'. Use 'install', 'uninstall', 'install-guard' or 'uninstall-guard'.",
+                other
+            );
             process::exit(1);
         }
     }
@@ -715,7 +2358,7 @@ fn cmd_watch(directory: &str) {
 
     if !db_path_buf.exists() {
         println!("No index found. Running initial index...");
-        cmd_index(directory, false);
+        cmd_index(directory, false, None, None);
     }
 
     println!(
@@ -732,7 +2375,10 @@ fn cmd_watch(directory: &str) {
             match event.kind {
                 EventKind::Modify(_) | EventKind::Create(_) => {
                     for path in &event.paths {
-                        if codegraph::indexer::CodeParser::is_supported(&path.to_string_lossy()) {
+                        let name = path.to_string_lossy();
+                        if codegraph::indexer::CodeParser::is_supported(&name)
+                            || ConfigFormat::from_path(&name).is_some()
+                        {
                             let _ = tx.send(path.clone());
                         }
                     }
@@ -755,7 +2401,11 @@ fn cmd_watch(directory: &str) {
 
     // Debounce: collect events for 200ms then re-index changed files.
     let store = open_store(db_path_buf.to_str().unwrap());
-    let pipeline = codegraph::indexer::IndexingPipeline::new(&store);
+    let config = load_config(None, Some(&root)).unwrap_or_default();
+    codegraph::sandbox::register(config.sandbox.policy(), &root);
+    let pipeline = codegraph::indexer::IndexingPipeline::new(&store)
+        .with_parser_chains(config.parsing.parser_chains())
+        .with_blame_metadata(config.git.blame_metadata());
 
     while let Ok(first_path) = rx.recv() {
         {
@@ -767,9 +2417,17 @@ fn cmd_watch(directory: &str) {
                 changed.insert(path);
             }
 
-            // Re-index each changed file.
-            for path in &changed {
-                match pipeline.index_file(path, &root) {
+            // Re-index the changed files as one batch.
+            let changed: Vec<PathBuf> = changed.into_iter().collect();
+            let results = match pipeline.index_files(&changed, &root) {
+                Ok(results) => results,
+                Err(e) => {
+                    tracing::error!("re-indexing: {}", e);
+                    continue;
+                }
+            };
+            for (path, result) in changed.iter().zip(results) {
+                match result {
                     Ok(Some(result)) => {
                         println!(
                             "  Re-indexed {} ({} nodes, {} edges) in {}ms",
@@ -789,7 +2447,140 @@ fn cmd_watch(directory: &str) {
     }
 }
 
-fn cmd_stats(db_path: &str) {
+#[cfg(unix)]
+fn cmd_daemon_start(directory: &str) {
+    let root = PathBuf::from(directory).canonicalize().unwrap_or_else(|e| {
+        tracing::error!("cannot resolve directory '{}': {}", directory, e);
+        process::exit(1);
+    });
+    let db_path = root.join(".codegraph").join("codegraph.db");
+    if !db_path.exists() {
+        println!("No index found. Running initial index...");
+        cmd_index(directory, false, None, None);
+    }
+
+    let store = open_store(db_path.to_str().unwrap());
+    let socket = codegraph::daemon::socket_path(&root);
+    let daemon = codegraph::daemon::Daemon::new(root, store);
+    println!(
+        "CodeGraph daemon listening on {} (stop with `codegraph daemon stop`)",
+        socket.display()
+    );
+    if let Err(e) = daemon.serve(&socket) {
+        tracing::error!("daemon failed: {}", e);
+        process::exit(1);
+    }
+}
+
+#[cfg(unix)]
+fn cmd_daemon_status(directory: &str) {
+    let socket = codegraph::daemon::socket_path(std::path::Path::new(directory));
+    match codegraph::daemon::request(&socket, &DaemonRequest::Status) {
+        Ok(DaemonResponse::Status(status)) => {
+            println!("CodeGraph daemon (pid {})", status.pid);
+            println!("  Root:        {}", status.root);
+            println!("  Uptime:      {}s", status.uptime_secs);
+            println!("  Requests:    {}", status.requests_served);
+            println!("  Files:       {}", status.files);
+            println!("  Nodes:       {}", status.nodes);
+            println!("  Edges:       {}", status.edges);
+            println!("  Embedder:    {}", loaded(status.embedder_loaded));
+            println!("  Reranker:    {}", loaded(status.reranker_loaded));
+        }
+        Ok(other) => daemon_failed(other),
+        Err(_) => {
+            println!("No daemon running ({}).", socket.display());
+            process::exit(1);
+        }
+    }
+}
+
+#[cfg(unix)]
+fn cmd_daemon_stop(directory: &str) {
+    let socket = codegraph::daemon::socket_path(std::path::Path::new(directory));
+    match codegraph::daemon::request(&socket, &DaemonRequest::Shutdown) {
+        Ok(DaemonResponse::ShuttingDown) => println!("Daemon stopped."),
+        Ok(other) => daemon_failed(other),
+        Err(_) => println!("No daemon running ({}).", socket.display()),
+    }
+}
+
+#[cfg(unix)]
+fn loaded(yes: bool) -> &'static str {
+    if yes {
+        "loaded"
+    } else {
+        "unavailable"
+    }
+}
+
+/// Report an error (or unexpected) daemon response and exit.
+#[cfg(unix)]
+fn daemon_failed(response: DaemonResponse) -> ! {
+    match response {
+        DaemonResponse::Error { message } => tracing::error!("daemon: {}", message),
+        other => tracing::error!("unexpected daemon response: {:?}", other),
+    }
+    process::exit(1);
+}
+
+fn cmd_usage(days: Option<u32>, db_path: &str) {
+    let db = PathBuf::from(db_path);
+    if !db.exists() {
+        tracing::error!("database not found at '{}'", db_path);
+        tracing::error!("Run `codegraph index <dir>` first to create an index.");
+        process::exit(1);
+    }
+
+    let store = open_store(db_path);
+    let since = days.map(|d| chrono::Utc::now().timestamp() - i64::from(d) * 86_400);
+    let report = codegraph::db::usage::usage_report(&store.conn, since, None).unwrap_or_else(|e| {
+        tracing::error!("cannot read tool usage: {}", e);
+        process::exit(1);
+    });
+
+    match days {
+        Some(d) => println!("Tool usage, last {} days", d),
+        None => println!("Tool usage"),
+    }
+    println!(
+        "  {} calls in {} sessions, {} failed",
+        report.total_calls, report.sessions, report.error_count
+    );
+    if !report.tools.is_empty() {
+        println!(
+            "\n  {:<36} {:>7} {:>7} {:>10} {:>10} {:>10}",
+            "Tool", "Calls", "Errors", "Avg ms", "Max ms", "Avg bytes"
+        );
+    }
+    for t in &report.tools {
+        println!(
+            "  {:<36} {:>7} {:>7} {:>10.1} {:>10.1} {:>10}",
+            t.tool, t.calls, t.errors, t.avg_ms, t.max_ms, t.avg_result_bytes
+        );
+    }
+
+    let project_root = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+    let config =
+        codegraph::config::loader::load_config(None, Some(&project_root)).unwrap_or_default();
+    let mut unused: Vec<String> = codegraph::mcp::registry::enabled_tool_names(&config)
+        .into_iter()
+        .filter(|name| !report.tools.iter().any(|t| &t.tool == name))
+        .collect();
+    unused.sort_unstable();
+    if !unused.is_empty() {
+        println!(
+            "\nEnabled in the '{}' preset but never called ({}):",
+            config.preset,
+            unused.len()
+        );
+        for name in &unused {
+            println!("  {}", name);
+        }
+    }
+}
+
+fn cmd_stats(db_path: &str, by_dir: bool) {
     let db = PathBuf::from(db_path);
     if !db.exists() {
         tracing::error!("database not found at '{}'", db_path);
@@ -810,4 +2601,33 @@ fn cmd_stats(db_path: &str) {
     println!("  Nodes:       {}", stats.nodes);
     println!("  Edges:       {}", stats.edges);
     println!("  Unresolved:  {}", unresolved);
+
+    if !by_dir {
+        return;
+    }
+    // Indexes built before the rollups existed have none stored yet.
+    let rows = match load_dir_stats(&store.conn) {
+        Ok(rows) if !rows.is_empty() => rows,
+        _ => compute_dir_stats(&store.conn, None).unwrap_or_else(|e| {
+            tracing::error!("cannot compute directory stats: {}", e);
+            process::exit(1);
+        }),
+    };
+    println!();
+    println!(
+        "  {:<40} {:>6} {:>7} {:>7} {:>7} {:>7}  Languages",
+        "Directory", "Files", "Nodes", "Edges", "AvgCC", "MaxCC"
+    );
+    for d in rollup(&rows, RollupKey::Directory) {
+        println!(
+            "  {:<40} {:>6} {:>7} {:>7} {:>7.1} {:>7}  {}",
+            d.directory,
+            d.files,
+            d.nodes,
+            d.edges,
+            d.avg_complexity(),
+            d.complexity_max,
+            d.language
+        );
+    }
 }
@@ -0,0 +1,125 @@
+//! Surfacing user annotations in every tool response.
+//!
+//! Tags (`codegraph_tag`) and notes (`codegraph_annotate`) mark symbols as
+//! legacy, perf-critical, do-not-touch and the like. Rather than teaching
+//! each tool about them, [`annotate_response`] runs after every tool call:
+//! any JSON object in the response whose `id`, `nodeId` or `node_id` names
+//! an annotated node gets an `annotations` field with its tags and notes.
+//! Responses without annotated nodes are returned unchanged.
+
+use std::collections::{BTreeMap, HashMap};
+
+use serde_json::{Map, Value};
+
+use crate::graph::store::NodeAnnotations;
+
+/// Response fields holding a node ID.
+const ID_FIELDS: &[&str] = &["id", "nodeId", "node_id"];
+
+/// Tools whose responses already carry the annotations they edit.
+pub const SELF_ANNOTATED_TOOLS: &[&str] = &["codegraph_tag", "codegraph_annotate"];
+
+/// `text` with annotations attached to annotated nodes, or `None` when it
+/// is not JSON or mentions none of them.
+pub fn annotate_response(
+    text: &str,
+    annotations: &HashMap<String, NodeAnnotations>,
+) -> Option<String> {
+    if annotations.is_empty() {
+        return None;
+    }
+    let mut value: Value = serde_json::from_str(text).ok()?;
+    if !decorate(&mut value, annotations) {
+        return None;
+    }
+    serde_json::to_string_pretty(&value).ok()
+}
+
+/// JSON for one node's annotations: tags as a `key: value` map, notes in
+/// the order they were added.
+pub fn annotations_json(annotations: &NodeAnnotations) -> Value {
+    let mut out = Map::new();
+    if !annotations.tags.is_empty() {
+        let tags: BTreeMap<&str, &str> = annotations
+            .tags
+            .iter()
+            .map(|t| (t.key.as_str(), t.value.as_str()))
+            .collect();
+        out.insert("tags".to_string(), serde_json::json!(tags));
+    }
+    if !annotations.notes.is_empty() {
+        out.insert("notes".to_string(), serde_json::json!(annotations.notes));
+    }
+    Value::Object(out)
+}
+
+fn decorate(value: &mut Value, annotations: &HashMap<String, NodeAnnotations>) -> bool {
+    match value {
+        Value::Object(map) => {
+            let mut changed = false;
+            for v in map.values_mut() {
+                changed |= decorate(v, annotations);
+            }
+            if map.contains_key("annotations") {
+                return changed;
+            }
+            let found = ID_FIELDS
+                .iter()
+                .filter_map(|f| map.get(*f).and_then(Value::as_str))
+                .find_map(|id| annotations.get(id));
+            if let Some(found) = found {
+                map.insert("annotations".to_string(), annotations_json(found));
+                changed = true;
+            }
+            changed
+        }
+        Value::Array(items) => items
+            .iter_mut()
+            .fold(false, |changed, v| decorate(v, annotations) | changed),
+        _ => false,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::store::{NodeNote, NodeTag};
+
+    #[test]
+    fn attaches_annotations_to_nodes_at_any_depth() {
+        let annotations = HashMap::from([(
+            "fn:a.ts:pay:1".to_string(),
+            NodeAnnotations {
+                tags: vec![NodeTag {
+                    node_id: "fn:a.ts:pay:1".to_string(),
+                    key: "status".to_string(),
+                    value: "legacy".to_string(),
+                }],
+                notes: vec![NodeNote {
+                    id: 1,
+                    node_id: "fn:a.ts:pay:1".to_string(),
+                    note: "do not touch".to_string(),
+                    author: None,
+                    created_at: 0,
+                }],
+            },
+        )]);
+        let text = r#"{"target": {"id": "fn:a.ts:pay:1"},
+                       "callers": [{"nodeId": "fn:b.ts:run:1"}, {"node_id": "fn:a.ts:pay:1"}]}"#;
+        let out: Value =
+            serde_json::from_str(&annotate_response(text, &annotations).unwrap()).unwrap();
+        assert_eq!(out["target"]["annotations"]["tags"]["status"], "legacy");
+        assert_eq!(
+            out["callers"][1]["annotations"]["notes"][0]["note"],
+            "do not touch"
+        );
+        assert!(out["callers"][0].get("annotations").is_none());
+
+        assert_eq!(annotate_response(r#"{"id": "other"}"#, &annotations), None);
+        assert_eq!(annotate_response("not json", &annotations), None);
+    }
+}
@@ -3,22 +3,64 @@
 //! Enables remote MCP clients (Gemini CLI, Cursor, Copilot, remote agents)
 //! to connect to CodeGraph over HTTP instead of stdio.
 //!
-//! Usage: `codegraph serve --http 0.0.0.0:8080`
+//! Usage: `codegraph serve --http 0.0.0.0:8080`, or
+//! `codegraph serve --http 0.0.0.0:8080 --workspace <dir>` to host every
+//! repository of a workspace (see [`super::tenants`]).
+//!
+//! Access is controlled by the `http` section of the user config
+//! ([`HttpConfig`]):
+//!
+//! - **API keys** — once any key is configured, every request must send
+//!   one as `Authorization: Bearer <key>` or `X-API-Key: <key>`, or is
+//!   rejected with `401`. Keys are compared in constant time.
+//! - **Tool allowlists** — a key with `tools` set gets `403` for a
+//!   `tools/call` of any other tool.
+//! - **TLS** — with `tls` set, the server terminates HTTPS itself, so it
+//!   can be exposed beyond localhost without a reverse proxy.
+//!
+//! The single-project server also accepts WebSocket connections at `/ws`
+//! for browser clients, see [`super::websocket`].
 
-use std::path::PathBuf;
+use std::collections::HashSet;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::body::{Body, Bytes};
+use axum::extract::{Request, State};
+use axum::http::{header, HeaderMap, Method, StatusCode};
+use axum::middleware::{from_fn_with_state, Next};
+use axum::response::Response;
+use axum::Router;
+use tokio::net::{TcpListener, TcpStream};
+use tokio_rustls::server::TlsStream;
+use tokio_rustls::TlsAcceptor;
 
 use crate::config::loader::load_config;
+use crate::config::schema::{HttpConfig, TlsConfig};
 use crate::graph::store::GraphStore;
 
+use super::roots::ProjectRoot;
 use super::server::CodeGraphServer;
+use super::tenants::{self, error_response, tool_calls, TenantRegistry, MAX_REQUEST_BYTES};
+use super::websocket::{self, WsSessions};
+
+/// Header carrying an API key as an alternative to a bearer token.
+pub const API_KEY_HEADER: &str = "x-api-key";
+
+/// Time a client has to complete the TLS handshake.
+const TLS_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
 
 /// Start the MCP server over HTTP on the given address.
 ///
 /// The server exposes a single `/mcp` endpoint that handles the MCP
 /// streamable HTTP protocol (POST for requests, SSE for server-initiated
-/// messages). Each client gets its own session.
+/// messages). Each client gets its own session. Tools take a `workspace`
+/// argument selecting one of `roots`, see [`roots`](super::roots).
 pub async fn run_http_server(
     store: GraphStore,
+    roots: Vec<ProjectRoot>,
     addr: &str,
 ) -> Result<(), Box<dyn std::error::Error>> {
     use rmcp::transport::streamable_http_server::{
@@ -27,33 +69,336 @@ pub async fn run_http_server(
 
     let project_root = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
     let config = load_config(None, Some(&project_root)).unwrap_or_default();
-    let server = CodeGraphServer::with_config(store, project_root, config);
+    let http = config.http.clone();
+    let server = CodeGraphServer::with_config(store, project_root, config).with_roots(roots);
+    let ws_server = server.clone();
 
     let service = StreamableHttpService::new(
-        move || Ok(server.clone()),
+        move || Ok(server.for_session()),
         LocalSessionManager::default().into(),
         Default::default(),
     );
 
-    let router = axum::Router::new().nest_service("/mcp", service);
-    let listener = tokio::net::TcpListener::bind(addr).await?;
+    let auth = Arc::new(HttpAuth::from_config(&http));
+    let router = axum::Router::new()
+        .nest_service("/mcp", service)
+        .route(
+            "/ws",
+            axum::routing::get(websocket::ws_handler)
+                .with_state(Arc::new(WsSessions::new(ws_server))),
+        )
+        .layer(from_fn_with_state(Arc::clone(&auth), require_api_key));
+    let listener = TcpListener::bind(addr).await?;
+    warn_if_exposed(&listener, &auth);
 
-    tracing::info!("CodeGraph MCP server listening on http://{}/mcp", addr);
-    eprintln!("CodeGraph MCP server listening on http://{}/mcp", addr);
+    let scheme = scheme(&http);
+    let ws_scheme = if http.tls.is_some() { "wss" } else { "ws" };
+    tracing::info!("CodeGraph MCP server listening on {scheme}://{}/mcp", addr);
+    eprintln!("CodeGraph MCP server listening on {scheme}://{}/mcp", addr);
+    eprintln!("  WebSocket transport at {ws_scheme}://{}/ws", addr);
 
-    axum::serve(listener, router)
-        .with_graceful_shutdown(async {
-            let _ = tokio::signal::ctrl_c().await;
-            tracing::info!("Shutting down HTTP server");
-        })
-        .await?;
+    serve(listener, router, http.tls.as_ref()).await
+}
+
+/// Serve every indexed repository of the workspace in `workspace_dir` over
+/// HTTP, each at `/projects/<name>/mcp` under its own quota.
+///
+/// The admin API (`/admin/projects`) is mounted only when `admin_token` is
+/// set; requests must send it as a bearer token.
+pub async fn run_multi_tenant_server(
+    workspace_dir: &Path,
+    addr: &str,
+    admin_token: Option<String>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let registry = Arc::new(TenantRegistry::load(workspace_dir, admin_token)?);
+    let names: Vec<&str> = registry.tenants().map(|t| t.name()).collect();
+    if names.is_empty() {
+        return Err("no indexed repositories in the workspace".into());
+    }
+    let http = load_config(None, None).unwrap_or_default().http;
+    let scheme = scheme(&http);
+    for name in &names {
+        eprintln!("  {scheme}://{}/projects/{}/mcp", addr, name);
+    }
+    let auth = Arc::new(HttpAuth::from_config(&http));
+    let router = tenants::router(Arc::clone(&registry), Arc::clone(&auth));
+    let listener = TcpListener::bind(addr).await?;
+    warn_if_exposed(&listener, &auth);
+
+    tracing::info!(
+        "CodeGraph multi-project MCP server listening on {scheme}://{}",
+        addr
+    );
+    eprintln!(
+        "CodeGraph MCP server hosting {} projects on {scheme}://{}",
+        names.len(),
+        addr
+    );
+
+    serve(listener, router, http.tls.as_ref()).await
+}
 
+async fn serve(
+    listener: TcpListener,
+    router: Router,
+    tls: Option<&TlsConfig>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let shutdown = async {
+        let _ = tokio::signal::ctrl_c().await;
+        tracing::info!("Shutting down HTTP server");
+    };
+    match tls {
+        Some(tls) => {
+            let listener = TlsListener::new(listener, tls_acceptor(tls)?)?;
+            axum::serve(listener, router)
+                .with_graceful_shutdown(shutdown)
+                .await?
+        }
+        None => {
+            axum::serve(listener, router)
+                .with_graceful_shutdown(shutdown)
+                .await?
+        }
+    }
     Ok(())
 }
 
+fn scheme(http: &HttpConfig) -> &'static str {
+    if http.tls.is_some() {
+        "https"
+    } else {
+        "http"
+    }
+}
+
+fn warn_if_exposed(listener: &TcpListener, auth: &HttpAuth) {
+    let exposed = listener
+        .local_addr()
+        .is_ok_and(|addr| !addr.ip().is_loopback());
+    if exposed && !auth.is_enabled() {
+        tracing::warn!("HTTP server is reachable beyond localhost without API keys");
+        eprintln!(
+            "Warning: listening beyond localhost without authentication. \
+             Configure http.api_keys in the user config or set CODEGRAPH_API_KEY."
+        );
+    }
+}
+
+// ---------------------------------------------------------------------------
+// API keys
+// ---------------------------------------------------------------------------
+
+/// A configured key with its secret resolved.
+#[derive(Debug, Clone)]
+pub struct ApiKey {
+    pub name: String,
+    secret: String,
+    /// Tools the key may call; every tool when `None`.
+    tools: Option<HashSet<String>>,
+}
+
+impl ApiKey {
+    pub fn allows(&self, tool: &str) -> bool {
+        self.tools.as_ref().is_none_or(|tools| tools.contains(tool))
+    }
+}
+
+/// The API keys accepted by a server. With none configured, every request
+/// is let through.
+#[derive(Debug, Default)]
+pub struct HttpAuth {
+    keys: Vec<ApiKey>,
+    /// Whether keys were configured, even if none resolved: a missing
+    /// secret must lock the server, not open it.
+    required: bool,
+}
+
+impl HttpAuth {
+    /// Resolve the keys of `config`. A key whose secret is missing (an
+    /// unset `key_env`) is skipped with a warning and never matches.
+    pub fn from_config(config: &HttpConfig) -> Self {
+        let keys = config
+            .api_keys
+            .iter()
+            .filter_map(|key| {
+                let Some(secret) = key.secret() else {
+                    tracing::warn!("skipping API key '{}': no key configured", key.name);
+                    return None;
+                };
+                Some(ApiKey {
+                    name: key.name.clone(),
+                    secret,
+                    tools: key.tools.as_ref().map(|t| t.iter().cloned().collect()),
+                })
+            })
+            .collect();
+        Self {
+            keys,
+            required: !config.api_keys.is_empty(),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.required
+    }
+
+    /// The key presented by a request, if it is one of ours.
+    pub fn authenticate(&self, headers: &HeaderMap) -> Option<&ApiKey> {
+        let presented = headers
+            .get(header::AUTHORIZATION)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.strip_prefix("Bearer "))
+            .or_else(|| headers.get(API_KEY_HEADER).and_then(|v| v.to_str().ok()))?;
+        self.keys
+            .iter()
+            .find(|key| constant_time_eq(presented, &key.secret))
+    }
+}
+
+/// Compare without an early exit so timing does not leak the secret.
+pub(crate) fn constant_time_eq(presented: &str, secret: &str) -> bool {
+    presented.len() == secret.len()
+        && presented
+            .bytes()
+            .zip(secret.bytes())
+            .fold(0u8, |acc, (a, b)| acc | (a ^ b))
+            == 0
+}
+
+/// Middleware rejecting requests without a valid API key, and tool calls
+/// outside the key's allowlist.
+pub async fn require_api_key(
+    State(auth): State<Arc<HttpAuth>>,
+    mut req: Request,
+    next: Next,
+) -> Response {
+    if !auth.is_enabled() {
+        return next.run(req).await;
+    }
+    let Some(key) = auth.authenticate(req.headers()).cloned() else {
+        let mut response = error_response(StatusCode::UNAUTHORIZED, "missing or invalid API key");
+        response
+            .headers_mut()
+            .insert(header::WWW_AUTHENTICATE, "Bearer".parse().unwrap());
+        return response;
+    };
+    // The WebSocket transport checks the allowlist per message.
+    req.extensions_mut().insert(key.clone());
+    if key.tools.is_none() || req.method() != Method::POST {
+        return next.run(req).await;
+    }
+
+    let (parts, body) = req.into_parts();
+    let bytes: Bytes = match axum::body::to_bytes(body, MAX_REQUEST_BYTES).await {
+        Ok(b) => b,
+        Err(_) => return error_response(StatusCode::PAYLOAD_TOO_LARGE, "request body too large"),
+    };
+    if let Some(tool) = tool_calls(&bytes).into_iter().find(|t| !key.allows(t)) {
+        return error_response(
+            StatusCode::FORBIDDEN,
+            &format!("API key '{}' may not call {tool}", key.name),
+        );
+    }
+    next.run(Request::from_parts(parts, Body::from(bytes)))
+        .await
+}
+
+// ---------------------------------------------------------------------------
+// TLS
+// ---------------------------------------------------------------------------
+
+/// Build the acceptor for the PEM certificate chain and key of `tls`.
+pub fn tls_acceptor(tls: &TlsConfig) -> Result<TlsAcceptor, Box<dyn std::error::Error>> {
+    use tokio_rustls::rustls::pki_types::pem::PemObject;
+    use tokio_rustls::rustls::pki_types::{CertificateDer, PrivateKeyDer};
+    use tokio_rustls::rustls::{crypto, ServerConfig};
+
+    let read = |path: &Path| {
+        std::fs::read(path).map_err(|e| format!("cannot read {}: {e}", path.display()))
+    };
+    let certs = CertificateDer::pem_slice_iter(&read(&tls.cert)?)
+        .collect::<Result<Vec<CertificateDer<'static>>, _>>()
+        .map_err(|e| format!("invalid certificate {}: {e}", tls.cert.display()))?;
+    if certs.is_empty() {
+        return Err(format!("no certificate in {}", tls.cert.display()).into());
+    }
+    let key = PrivateKeyDer::from_pem_slice(&read(&tls.key)?)
+        .map_err(|e| format!("invalid private key {}: {e}", tls.key.display()))?;
+
+    let mut config =
+        ServerConfig::builder_with_provider(Arc::new(crypto::ring::default_provider()))
+            .with_safe_default_protocol_versions()?
+            .with_no_client_auth()
+            .with_single_cert(certs, key)?;
+    config.alpn_protocols = vec![b"http/1.1".to_vec()];
+    Ok(TlsAcceptor::from(Arc::new(config)))
+}
+
+/// A listener handing axum connections that completed the TLS handshake.
+///
+/// Handshakes run in their own tasks, so a slow or stalled client never
+/// holds up the accept loop.
+pub struct TlsListener {
+    accepted: tokio::sync::mpsc::Receiver<(TlsStream<TcpStream>, SocketAddr)>,
+    local_addr: SocketAddr,
+}
+
+impl TlsListener {
+    pub fn new(listener: TcpListener, acceptor: TlsAcceptor) -> std::io::Result<Self> {
+        let local_addr = listener.local_addr()?;
+        let (sender, accepted) = tokio::sync::mpsc::channel(64);
+        tokio::spawn(async move {
+            while !sender.is_closed() {
+                let (tcp, addr) = match listener.accept().await {
+                    Ok(conn) => conn,
+                    Err(e) => {
+                        tracing::debug!("accept failed: {e}");
+                        tokio::time::sleep(Duration::from_millis(100)).await;
+                        continue;
+                    }
+                };
+                let acceptor = acceptor.clone();
+                let sender = sender.clone();
+                tokio::spawn(async move {
+                    match tokio::time::timeout(TLS_HANDSHAKE_TIMEOUT, acceptor.accept(tcp)).await {
+                        Ok(Ok(stream)) => {
+                            let _ = sender.send((stream, addr)).await;
+                        }
+                        Ok(Err(e)) => tracing::debug!("TLS handshake with {addr} failed: {e}"),
+                        Err(_) => tracing::debug!("TLS handshake with {addr} timed out"),
+                    }
+                });
+            }
+        });
+        Ok(Self {
+            accepted,
+            local_addr,
+        })
+    }
+}
+
+impl axum::serve::Listener for TlsListener {
+    type Io = TlsStream<TcpStream>;
+    type Addr = SocketAddr;
+
+    async fn accept(&mut self) -> (Self::Io, Self::Addr) {
+        match self.accepted.recv().await {
+            Some(conn) => conn,
+            // The accept loop only stops once this receiver is dropped.
+            None => std::future::pending().await,
+        }
+    }
+
+    fn local_addr(&self) -> std::io::Result<Self::Addr> {
+        Ok(self.local_addr)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::schema::ApiKeyConfig;
+    use std::io::{Read, Write};
 
     #[test]
     fn server_can_be_cloned_for_http_factory() {
@@ -66,4 +411,149 @@ mod tests {
         let server = CodeGraphServer::with_config(store, project_root, config);
         let _cloned = server.clone();
     }
+
+    fn auth() -> HttpAuth {
+        HttpAuth::from_config(&HttpConfig {
+            api_keys: vec![
+                ApiKeyConfig {
+                    name: "admin".into(),
+                    key: Some("full-access".into()),
+                    ..Default::default()
+                },
+                ApiKeyConfig {
+                    name: "ci".into(),
+                    key: Some("read-only".into()),
+                    tools: Some(vec!["codegraph_query".into()]),
+                    ..Default::default()
+                },
+                ApiKeyConfig {
+                    name: "unset".into(),
+                    key_env: Some("CODEGRAPH_TEST_UNSET_KEY".into()),
+                    ..Default::default()
+                },
+            ],
+            tls: None,
+        })
+    }
+
+    #[test]
+    fn keys_are_read_from_bearer_and_api_key_headers() {
+        let auth = auth();
+        assert!(auth.is_enabled());
+        assert!(!HttpAuth::default().is_enabled());
+        let unresolved = HttpAuth::from_config(&HttpConfig {
+            api_keys: vec![ApiKeyConfig {
+                name: "unset".into(),
+                key_env: Some("CODEGRAPH_TEST_UNSET_KEY".into()),
+                ..Default::default()
+            }],
+            tls: None,
+        });
+        assert!(unresolved.is_enabled());
+
+        let headers = |name: &str, value: &str| {
+            let mut h = HeaderMap::new();
+            h.insert(
+                header::HeaderName::from_bytes(name.as_bytes()).unwrap(),
+                value.parse().unwrap(),
+            );
+            h
+        };
+        let key = |h: &HeaderMap| auth.authenticate(h).map(|k| k.name.clone());
+        assert_eq!(
+            key(&headers("authorization", "Bearer full-access")),
+            Some("admin".into())
+        );
+        assert_eq!(key(&headers("x-api-key", "read-only")), Some("ci".into()));
+        assert_eq!(key(&headers("authorization", "Bearer read-onlx")), None);
+        assert_eq!(key(&headers("authorization", "full-access")), None);
+        assert_eq!(key(&HeaderMap::new()), None);
+
+        let ci = auth
+            .authenticate(&headers("x-api-key", "read-only"))
+            .unwrap();
+        assert!(ci.allows("codegraph_query"));
+        assert!(!ci.allows("codegraph_node"));
+    }
+
+    fn http(addr: SocketAddr, key: Option<&str>, body: &str) -> String {
+        let mut stream = std::net::TcpStream::connect(addr).unwrap();
+        let auth = key
+            .map(|k| format!("Authorization: Bearer {k}\r\n"))
+            .unwrap_or_default();
+        let request = format!(
+            "POST /mcp HTTP/1.1\r\nHost: x\r\n{auth}Content-Type: application/json\r\n\
+             Content-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            body.len()
+        );
+        stream.write_all(request.as_bytes()).unwrap();
+        let mut out = String::new();
+        stream.read_to_string(&mut out).unwrap();
+        out
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn middleware_rejects_unknown_keys_and_disallowed_tools() {
+        let router = Router::new()
+            .route("/mcp", axum::routing::post(|| async { "ok" }))
+            .layer(from_fn_with_state(Arc::new(auth()), require_api_key));
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, router).await });
+
+        let call = |tool: &str| {
+            format!(
+                r#"{{"jsonrpc":"2.0","id":1,"method":"tools/call","params":{{"name":"{tool}"}}}}"#
+            )
+        };
+        let send = move |key: Option<&'static str>, body: String| {
+            tokio::task::spawn_blocking(move || http(addr, key, &body))
+        };
+
+        let anonymous = send(None, call("codegraph_query")).await.unwrap();
+        assert!(anonymous.starts_with("HTTP/1.1 401"), "{anonymous}");
+        assert!(
+            anonymous.contains("www-authenticate: Bearer"),
+            "{anonymous}"
+        );
+        let wrong = send(Some("nope"), call("codegraph_query")).await.unwrap();
+        assert!(wrong.starts_with("HTTP/1.1 401"), "{wrong}");
+
+        let allowed = send(Some("read-only"), call("codegraph_query"))
+            .await
+            .unwrap();
+        assert!(allowed.starts_with("HTTP/1.1 200"), "{allowed}");
+        let denied = send(Some("read-only"), call("codegraph_node"))
+            .await
+            .unwrap();
+        assert!(denied.starts_with("HTTP/1.1 403"), "{denied}");
+        assert!(denied.contains("may not call codegraph_node"), "{denied}");
+        let listing = send(Some("read-only"), r#"{"method":"tools/list"}"#.into())
+            .await
+            .unwrap();
+        assert!(listing.starts_with("HTTP/1.1 200"), "{listing}");
+
+        let admin = send(Some("full-access"), call("codegraph_node"))
+            .await
+            .unwrap();
+        assert!(admin.starts_with("HTTP/1.1 200"), "{admin}");
+    }
+
+    #[test]
+    fn tls_config_errors_name_the_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let cert = dir.path().join("cert.pem");
+        std::fs::write(&cert, "not a certificate").unwrap();
+        let tls = |cert: &Path| TlsConfig {
+            cert: cert.to_path_buf(),
+            key: dir.path().join("key.pem"),
+        };
+
+        let err = tls_acceptor(&tls(&dir.path().join("missing.pem")))
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("missing.pem"), "{err}");
+        let err = tls_acceptor(&tls(&cert)).err().unwrap();
+        assert!(err.to_string().contains("no certificate"), "{err}");
+    }
 }
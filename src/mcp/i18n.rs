@@ -0,0 +1,330 @@
+//! Localization of human-readable messages in tool responses.
+//!
+//! Tool handlers always write English. When `i18n.locale` (or a per-tool
+//! entry in `i18n.tools`) selects another language, [`Localizer`] rewrites
+//! the messages after the handler returns: every string under a message
+//! field (`error`, `message`, `note`, ...) that matches a template of the
+//! English bundle is re-rendered from the target bundle, with the variable
+//! parts carried over. Field names, values in other fields, and messages no
+//! bundle covers are left untouched, so agents parse the same shape in any
+//! language.
+//!
+//! Bundles are flat YAML maps of message key to template, with `{name}`
+//! placeholders. `en`, `de`, `es` and `tr` ship in `locales/`; a project
+//! can add or override bundles with `<bundle_dir>/<locale>.yaml`.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use regex::Regex;
+
+use crate::config::schema::I18nConfig;
+
+/// Built-in bundles, keyed by language code.
+const BUILTIN_BUNDLES: &[(&str, &str)] = &[
+    ("en", include_str!("../../locales/en.yaml")),
+    ("de", include_str!("../../locales/de.yaml")),
+    ("es", include_str!("../../locales/es.yaml")),
+    ("tr", include_str!("../../locales/tr.yaml")),
+];
+
+/// JSON fields whose string values are human-readable messages.
+const MESSAGE_FIELDS: &[&str] = &["error", "message", "note", "hint", "warning", "summary"];
+
+/// An English template compiled for matching emitted messages.
+#[derive(Debug)]
+struct Template {
+    key: String,
+    pattern: Regex,
+    /// Placeholder names, in capture-group order.
+    params: Vec<String>,
+}
+
+impl Template {
+    fn compile(key: &str, text: &str) -> Option<Self> {
+        let placeholder = Regex::new(r"\{(\w+)\}").ok()?;
+        let mut pattern = String::from("^");
+        let mut params = Vec::new();
+        let mut last = 0;
+        for caps in placeholder.captures_iter(text) {
+            let whole = caps.get(0)?;
+            pattern.push_str(&regex::escape(&text[last..whole.start()]));
+            pattern.push_str("(.*?)");
+            params.push(caps[1].to_string());
+            last = whole.end();
+        }
+        pattern.push_str(&regex::escape(&text[last..]));
+        pattern.push('$');
+        Some(Self {
+            key: key.to_string(),
+            pattern: Regex::new(&pattern).ok()?,
+            params,
+        })
+    }
+}
+
+/// Translates tool messages into the locale configured for each tool.
+#[derive(Debug, Default)]
+pub struct Localizer {
+    default_locale: Option<String>,
+    tool_locales: HashMap<String, String>,
+    templates: Vec<Template>,
+    /// Non-English bundles by locale.
+    bundles: HashMap<String, HashMap<String, String>>,
+}
+
+impl Localizer {
+    /// Build from config. Custom bundles in `bundle_dir` (relative to
+    /// `project_root`) extend and override the built-in ones; a locale
+    /// without any bundle leaves messages in English.
+    pub fn new(config: &I18nConfig, project_root: &Path) -> Self {
+        let mut bundles: HashMap<String, HashMap<String, String>> = BUILTIN_BUNDLES
+            .iter()
+            .filter_map(|(locale, source)| {
+                Some((locale.to_string(), serde_yaml::from_str(source).ok()?))
+            })
+            .collect();
+
+        if let Some(dir) = &config.bundle_dir {
+            let dir = project_root.join(dir);
+            if let Ok(entries) = std::fs::read_dir(&dir) {
+                for path in entries.flatten().map(|e| e.path()) {
+                    if path.extension().and_then(|e| e.to_str()) != Some("yaml") {
+                        continue;
+                    }
+                    let Some(locale) = path.file_stem().and_then(|s| s.to_str()) else {
+                        continue;
+                    };
+                    let parsed = std::fs::read_to_string(&path)
+                        .map_err(|e| e.to_string())
+                        .and_then(|s| {
+                            serde_yaml::from_str::<HashMap<String, String>>(&s)
+                                .map_err(|e| e.to_string())
+                        });
+                    match parsed {
+                        Ok(messages) => bundles
+                            .entry(normalize_locale(locale))
+                            .or_default()
+                            .extend(messages),
+                        Err(e) => tracing::warn!("Ignoring bundle {}: {}", path.display(), e),
+                    }
+                }
+            }
+        }
+
+        let mut templates: Vec<Template> = bundles
+            .remove("en")
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|(key, text)| Template::compile(key, text))
+            .collect();
+        templates.sort_by(|a, b| a.key.cmp(&b.key));
+
+        Self {
+            default_locale: config.locale.as_deref().map(normalize_locale),
+            tool_locales: config
+                .tools
+                .iter()
+                .map(|(tool, locale)| (tool.clone(), normalize_locale(locale)))
+                .collect(),
+            templates,
+            bundles,
+        }
+    }
+
+    /// The bundle locale that applies to `tool`, or `None` when its
+    /// responses stay English.
+    pub fn locale_for(&self, tool: &str) -> Option<&str> {
+        let locale = self
+            .tool_locales
+            .get(tool)
+            .or(self.default_locale.as_ref())?;
+        if self.bundles.contains_key(locale) {
+            return Some(locale);
+        }
+        // `pt-BR` falls back to `pt`.
+        let language = locale.split('-').next()?;
+        self.bundles
+            .get_key_value(language)
+            .map(|(k, _)| k.as_str())
+    }
+
+    /// Translate one English message, or `None` when no template matches
+    /// or the bundle lacks its key.
+    pub fn translate(&self, locale: &str, message: &str) -> Option<String> {
+        let bundle = self.bundles.get(locale)?;
+        self.templates.iter().find_map(|template| {
+            let caps = template.pattern.captures(message)?;
+            let mut out = bundle.get(&template.key)?.clone();
+            for (i, name) in template.params.iter().enumerate() {
+                let value = caps.get(i + 1).map_or("", |m| m.as_str());
+                out = out.replace(&format!("{{{name}}}"), value);
+            }
+            Some(out)
+        })
+    }
+
+    /// Localize a tool response for `tool`. JSON responses have their
+    /// message fields translated and are re-serialized in the same pretty
+    /// form; any other text is translated as a whole message.
+    pub fn localize(&self, tool: &str, text: &str) -> String {
+        let Some(locale) = self.locale_for(tool) else {
+            return text.to_string();
+        };
+        match serde_json::from_str::<serde_json::Value>(text) {
+            Ok(mut value) if value.is_object() || value.is_array() => {
+                if self.localize_value(locale, &mut value) {
+                    super::server::json_text(&value)
+                } else {
+                    text.to_string()
+                }
+            }
+            _ => self
+                .translate(locale, text)
+                .unwrap_or_else(|| text.to_string()),
+        }
+    }
+
+    /// Translate message fields in place; returns whether anything changed.
+    fn localize_value(&self, locale: &str, value: &mut serde_json::Value) -> bool {
+        let mut changed = false;
+        match value {
+            serde_json::Value::Object(map) => {
+                for (key, field) in map.iter_mut() {
+                    if let serde_json::Value::String(message) = field {
+                        if MESSAGE_FIELDS.contains(&key.as_str()) {
+                            if let Some(translated) = self.translate(locale, message) {
+                                *message = translated;
+                                changed = true;
+                            }
+                        }
+                    } else {
+                        changed |= self.localize_value(locale, field);
+                    }
+                }
+            }
+            serde_json::Value::Array(items) => {
+                for item in items {
+                    changed |= self.localize_value(locale, item);
+                }
+            }
+            _ => {}
+        }
+        changed
+    }
+}
+
+/// Lowercase with `-` separators: `pt_BR` and `PT-br` both become `pt-br`.
+fn normalize_locale(locale: &str) -> String {
+    locale.trim().to_lowercase().replace('_', "-")
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn localizer(locale: Option<&str>, tools: &[(&str, &str)]) -> Localizer {
+        let config = I18nConfig {
+            locale: locale.map(str::to_string),
+            tools: tools
+                .iter()
+                .map(|(t, l)| (t.to_string(), l.to_string()))
+                .collect(),
+            bundle_dir: None,
+        };
+        Localizer::new(&config, Path::new("."))
+    }
+
+    #[test]
+    fn builtin_bundles_cover_every_english_key() {
+        let l = localizer(None, &[]);
+        let keys: Vec<&str> = l.templates.iter().map(|t| t.key.as_str()).collect();
+        assert!(keys.len() > 20);
+        for (locale, bundle) in &l.bundles {
+            for key in &keys {
+                assert!(bundle.contains_key(*key), "{locale} is missing {key}");
+            }
+        }
+    }
+
+    #[test]
+    fn translates_message_fields_and_keeps_field_names() {
+        let l = localizer(Some("de"), &[("codegraph_node", "tr_TR")]);
+        let out = l.localize(
+            "codegraph_callers",
+            r#"{"error": "Symbol \"fooBar\" not found in the graph.", "symbol": "Symbol \"x\" not found."}"#,
+        );
+        let v: serde_json::Value = serde_json::from_str(&out).unwrap();
+        assert_eq!(
+            v["error"],
+            "Symbol \"fooBar\" wurde im Graphen nicht gefunden."
+        );
+        // Not a message field: data stays as-is.
+        assert_eq!(v["symbol"], "Symbol \"x\" not found.");
+
+        // Per-tool override, with the region falling back to the language.
+        let out = l.localize(
+            "codegraph_node",
+            r#"{"message": "No recursion found in the call graph."}"#,
+        );
+        assert!(out.contains("Çağrı grafiğinde özyineleme bulunamadı."));
+
+        let out = l.localize(
+            "codegraph_find_path",
+            r#"[{"message": "No call path found from \"a\" to \"b\"."}]"#,
+        );
+        assert!(out.contains(r#"Kein Aufrufpfad von \"a\" nach \"b\" gefunden."#));
+    }
+
+    #[test]
+    fn english_and_unknown_messages_pass_through() {
+        let body = r#"{"error": "Something unexpected"}"#;
+        assert_eq!(localizer(None, &[]).localize("codegraph_node", body), body);
+        assert_eq!(
+            localizer(Some("en"), &[]).localize("codegraph_node", body),
+            body
+        );
+        assert_eq!(
+            localizer(Some("xx"), &[]).localize("codegraph_node", body),
+            body
+        );
+        assert_eq!(
+            localizer(Some("es"), &[]).localize("codegraph_node", body),
+            body
+        );
+
+        let plain = "Tool 'codegraph_x' is not available in the current preset (minimal). \
+                     Change the preset in .codegraph.yaml or set CODEGRAPH_PRESET=full to enable all tools.";
+        let out = localizer(Some("es"), &[]).localize("codegraph_x", plain);
+        assert!(out.starts_with("La herramienta 'codegraph_x' no está disponible"));
+        assert!(out.contains("(minimal)"));
+    }
+
+    #[test]
+    fn custom_bundle_dir_adds_locales() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("fr.yaml"),
+            "graph_empty: \"Le graphe de code est vide.\"\n",
+        )
+        .unwrap();
+        let config = I18nConfig {
+            locale: Some("fr".to_string()),
+            tools: HashMap::new(),
+            bundle_dir: Some(dir.path().to_path_buf()),
+        };
+        let l = Localizer::new(&config, Path::new("."));
+        let out = l.localize(
+            "codegraph_structure",
+            r#"{"error": "The code graph is empty."}"#,
+        );
+        assert!(out.contains("Le graphe de code est vide."));
+        // Keys the custom bundle lacks stay English.
+        let body = r#"{"message": "No global mutable state found."}"#;
+        assert_eq!(l.localize("codegraph_global_state", body), body);
+    }
+}
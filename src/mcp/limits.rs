@@ -0,0 +1,238 @@
+//! Timeouts and concurrency limits around tool calls.
+//!
+//! Tool handlers do their work synchronously, so a slow one would block
+//! the stdio server's only thread. [`ToolLimiter::run`] moves each call to
+//! the blocking thread pool, waits for a free slot of the tool's
+//! concurrency limit, and gives up on the call when its timeout (waiting
+//! included) runs out. A timed-out call cannot be interrupted: it keeps its
+//! slot until it finishes and its result is dropped, so a tool with
+//! runaway calls stops accepting new ones instead of piling them up.
+//!
+//! Limits come from `tools.limits` in `.codegraph.yaml`, see
+//! [`ToolLimitsConfig`].
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use crate::config::schema::ToolLimitsConfig;
+
+/// Why a call was not completed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ToolLimitError {
+    /// Every slot stayed taken for the whole timeout.
+    Busy {
+        max_concurrent: usize,
+        timeout: Duration,
+    },
+    /// The call ran past its timeout.
+    TimedOut { timeout: Duration },
+    /// The handler panicked.
+    Panicked(String),
+}
+
+impl ToolLimitError {
+    /// Machine-readable kind: `busy`, `timeout` or `panic`.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Busy { .. } => "busy",
+            Self::TimedOut { .. } => "timeout",
+            Self::Panicked(_) => "panic",
+        }
+    }
+
+    /// JSON payload returned to the client in place of the tool's output.
+    pub fn to_json(&self, tool: &str) -> serde_json::Value {
+        let mut payload = serde_json::json!({
+            "error": self.message(tool),
+            "code": self.code(),
+            "tool": tool,
+        });
+        match self {
+            Self::Busy {
+                max_concurrent,
+                timeout,
+            } => {
+                payload["maxConcurrent"] = (*max_concurrent).into();
+                payload["timeoutSecs"] = timeout.as_secs().into();
+            }
+            Self::TimedOut { timeout } => payload["timeoutSecs"] = timeout.as_secs().into(),
+            Self::Panicked(_) => {}
+        }
+        payload
+    }
+
+    fn message(&self, tool: &str) -> String {
+        match self {
+            Self::Busy {
+                max_concurrent,
+                timeout,
+            } => format!(
+                "{tool} already has {max_concurrent} call(s) running and none finished within {}s. \
+                 Retry later, or raise tools.limits.max_concurrent in .codegraph.yaml.",
+                timeout.as_secs()
+            ),
+            Self::TimedOut { timeout } => format!(
+                "{tool} did not finish within {}s and was abandoned. Narrow the input \
+                 (a subdirectory, a smaller limit), or raise tools.limits.timeout_secs in \
+                 .codegraph.yaml.",
+                timeout.as_secs()
+            ),
+            Self::Panicked(message) => format!("{tool} failed: {message}"),
+        }
+    }
+}
+
+/// Enforces [`ToolLimitsConfig`] for every session of a server.
+#[derive(Debug, Default)]
+pub struct ToolLimiter {
+    config: ToolLimitsConfig,
+    slots: Mutex<HashMap<String, Arc<Semaphore>>>,
+}
+
+impl ToolLimiter {
+    pub fn new(config: &ToolLimitsConfig) -> Self {
+        Self {
+            config: config.clone(),
+            slots: Mutex::default(),
+        }
+    }
+
+    /// Run `work` for a call of `tool` on the blocking pool, within the
+    /// tool's concurrency limit and timeout.
+    pub async fn run<T, F>(&self, tool: &str, work: F) -> Result<T, ToolLimitError>
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        let timeout = self.config.timeout(tool);
+        let started = Instant::now();
+        let permit = match self.config.max_concurrent(tool) {
+            Some(max) => Some(self.acquire(tool, max, timeout).await?),
+            None => None,
+        };
+        let call = tokio::task::spawn_blocking(move || {
+            let _permit: Option<OwnedSemaphorePermit> = permit;
+            work()
+        });
+        let joined = match timeout {
+            Some(timeout) => {
+                let remaining = timeout.saturating_sub(started.elapsed());
+                tokio::time::timeout(remaining, call)
+                    .await
+                    .map_err(|_| ToolLimitError::TimedOut { timeout })?
+            }
+            None => call.await,
+        };
+        joined.map_err(|e| match e.try_into_panic() {
+            Ok(panic) => ToolLimitError::Panicked(
+                panic
+                    .downcast_ref::<&str>()
+                    .map(|s| s.to_string())
+                    .or_else(|| panic.downcast_ref::<String>().cloned())
+                    .unwrap_or_else(|| "the tool panicked".to_string()),
+            ),
+            Err(e) => ToolLimitError::Panicked(e.to_string()),
+        })
+    }
+
+    async fn acquire(
+        &self,
+        tool: &str,
+        max: usize,
+        timeout: Option<Duration>,
+    ) -> Result<OwnedSemaphorePermit, ToolLimitError> {
+        let slots = {
+            let mut all = self.slots.lock().unwrap_or_else(|e| e.into_inner());
+            Arc::clone(
+                all.entry(tool.to_string())
+                    .or_insert_with(|| Arc::new(Semaphore::new(max))),
+            )
+        };
+        let acquire = slots.acquire_owned();
+        let permit =
+            match timeout {
+                Some(timeout) => tokio::time::timeout(timeout, acquire).await.map_err(|_| {
+                    ToolLimitError::Busy {
+                        max_concurrent: max,
+                        timeout,
+                    }
+                })?,
+                None => acquire.await,
+            };
+        // The semaphore is never closed.
+        Ok(permit.expect("tool semaphore closed"))
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::schema::ToolLimit;
+
+    /// One-second timeouts: the config takes whole seconds.
+    fn limiter(max_concurrent: usize) -> Arc<ToolLimiter> {
+        Arc::new(ToolLimiter::new(&ToolLimitsConfig {
+            defaults: ToolLimit {
+                timeout_secs: Some(1),
+                max_concurrent: Some(max_concurrent),
+            },
+            ..Default::default()
+        }))
+    }
+
+    #[tokio::test]
+    async fn calls_past_their_timeout_fail_and_keep_their_slot() {
+        let limiter = limiter(1);
+        assert_eq!(limiter.run("fast", || 7).await, Ok(7));
+
+        let slow = limiter
+            .run("slow", || std::thread::sleep(Duration::from_millis(1500)))
+            .await;
+        assert_eq!(
+            slow,
+            Err(ToolLimitError::TimedOut {
+                timeout: Duration::from_secs(1)
+            })
+        );
+        // The abandoned call still runs for another ~0.5s and holds the
+        // only slot, but a slot frees up within the next call's timeout.
+        assert_eq!(limiter.run("slow", || 1).await, Ok(1));
+
+        let err = limiter
+            .run("panics", || -> u32 { panic!("boom") })
+            .await
+            .unwrap_err();
+        assert_eq!(err, ToolLimitError::Panicked("boom".to_string()));
+        assert_eq!(err.to_json("panics")["code"], "panic");
+    }
+
+    #[tokio::test]
+    async fn waiting_for_a_slot_counts_against_the_timeout() {
+        let limiter = limiter(1);
+        let busy = Arc::clone(&limiter);
+        let running = tokio::spawn(async move {
+            busy.run("scan", || std::thread::sleep(Duration::from_millis(2500)))
+                .await
+        });
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        let err = limiter.run("scan", || ()).await.unwrap_err();
+        assert_eq!(
+            err,
+            ToolLimitError::Busy {
+                max_concurrent: 1,
+                timeout: Duration::from_secs(1)
+            }
+        );
+        assert_eq!(err.to_json("scan")["maxConcurrent"], 1);
+        // Other tools have their own slots.
+        assert_eq!(limiter.run("query", || 2).await, Ok(2));
+        assert!(running.await.unwrap().is_err());
+    }
+}
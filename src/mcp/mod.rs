@@ -2,22 +2,45 @@
 //!
 //! Tool handler logic is split into modules by domain:
 //! - [`tools_core`] — 14 core tools (query, dependencies, callers, etc.)
-//! - [`tools_git`] — 9 git integration tools (blame, history, etc.)
+//! - [`tools_git`] — 10 git integration tools (blame, history, reviewers, etc.)
 //! - [`tools_security`] — 9 security scanning tools (OWASP, CWE, taint, etc.)
-//! - [`tools_analysis`] — 7 repository & analysis tools (stats, imports, etc.)
+//! - [`tools_analysis`] — 11 repository & analysis tools (stats, imports, config keys, wire contracts, etc.)
 //! - [`tools_dataflow`] — 6 call graph & data flow tools (find_path, complexity, etc.)
-//! - [`server`] — deep_query tool (cross-encoder re-ranked search)
-//! - [`tasks`] — MCP Tasks for async operations (indexing, etc.)
+//! - [`server`] — deep_query and deep_file_query tools (cross-encoder re-ranked search), summarize_module (MCP sampling)
+//! - [`tasks`] — MCP Tasks for long-running tool calls (security scan, embedding backfill, PageRank)
 //! - [`http`] — HTTP transport (streamable HTTP via axum)
+//! - [`websocket`] — WebSocket transport with resumable sessions
+//! - [`roots`] — multi-root servers, tools picking a project by `workspace`
+//! - [`tenants`] — multi-project HTTP hosting with per-project quotas
+//! - [`i18n`] — localization of response messages
+//! - [`profile`] — response profiles capping depth, lists and size of tool output
+//! - [`annotations`] — user tags and notes attached to every response
+//! - [`pagination`] — cursor paging of tools with large result lists
+//! - [`limits`] — per-tool timeouts and concurrency limits
+//! - [`structured`] — output schemas and `structuredContent` of the core tools
+//! - [`resources`] — per-file outline and per-symbol MCP resources
+//! - [`subscriptions`] — resource subscriptions, notified when the index changes
 //!
-//! Also exposes 3 MCP Prompts: review-security, explain-function, pre-refactor-check.
+//! Also exposes 5 MCP Prompts: review-security, explain-function, pre-refactor-check,
+//! codebase-onboarding, locate-bug.
 
+pub mod annotations;
 pub mod http;
+pub mod i18n;
+pub mod limits;
+pub mod pagination;
+pub mod profile;
 pub mod registry;
+pub mod resources;
+pub mod roots;
 pub mod server;
+pub mod structured;
+pub mod subscriptions;
 pub mod tasks;
+pub mod tenants;
 pub mod tools_analysis;
 pub mod tools_core;
 pub mod tools_dataflow;
 pub mod tools_git;
 pub mod tools_security;
+pub mod websocket;
@@ -0,0 +1,239 @@
+//! Cursor pagination of large tool responses.
+//!
+//! Tools such as `codegraph_structure` or `codegraph_dead_code` can return
+//! hundreds of kilobytes, which clients cut off mid-JSON. Instead of each
+//! handler paging its own output, the tools in [`PAGED_TOOLS`] get two
+//! extra arguments in their schema, `cursor` and `page_size`, and their
+//! main list is paged after the handler ran:
+//!
+//! ```json
+//! { "callers": [...first page...],
+//!   "pagination": { "field": "callers", "total": 340, "offset": 0,
+//!                   "returned": 100, "hasMore": true, "nextCursor": "100.9f2c…" } }
+//! ```
+//!
+//! The page size defaults to `response.page_size` (100). A cursor encodes
+//! the offset and a fingerprint of the tool and its other arguments, so a
+//! cursor replayed with different arguments is rejected instead of paging
+//! another result list. Handlers return their lists in a stable order.
+
+use serde_json::{Map, Value};
+
+use crate::graph::search::{decode_cursor, encode_cursor};
+
+/// Page size when `response.page_size` is unset.
+pub const DEFAULT_PAGE_SIZE: usize = 100;
+
+/// Tools with a paged list, and the response field holding it.
+pub const PAGED_TOOLS: &[(&str, &str)] = &[
+    ("codegraph_dependencies", "dependencies"),
+    ("codegraph_callers", "callers"),
+    ("codegraph_callees", "callees"),
+    ("codegraph_impact", "affectedFiles"),
+    ("codegraph_structure", "modules"),
+    ("codegraph_dead_code", "files"),
+    ("codegraph_circular_imports", "cycles"),
+    ("codegraph_project_tree", "tree"),
+    ("codegraph_find_references", "references"),
+    ("codegraph_export_map", "files"),
+    ("codegraph_api_surface", "modules"),
+    ("codegraph_global_state", "states"),
+    ("codegraph_tagged", "symbols"),
+    ("codegraph_duplicate_definitions", "groups"),
+    ("codegraph_find_clones", "clusters"),
+];
+
+/// The paged field of `tool`'s response, if it is paged.
+pub fn paged_field(tool: &str) -> Option<&'static str> {
+    PAGED_TOOLS
+        .iter()
+        .find(|(name, _)| *name == tool)
+        .map(|(_, field)| *field)
+}
+
+/// Add the `cursor` and `page_size` arguments to a paged tool's input
+/// schema.
+pub fn add_page_params(schema: &mut Map<String, Value>) {
+    let properties = schema
+        .entry("properties")
+        .or_insert_with(|| Value::Object(Map::new()));
+    if let Some(properties) = properties.as_object_mut() {
+        properties.insert(
+            "cursor".to_string(),
+            serde_json::json!({
+                "type": ["string", "null"],
+                "description": "nextCursor of the previous page, to fetch the next one",
+            }),
+        );
+        properties.insert(
+            "page_size".to_string(),
+            serde_json::json!({
+                "type": ["integer", "null"],
+                "format": "uint",
+                "minimum": 1,
+                "description": "Items per page (default from response.page_size, 100)",
+            }),
+        );
+    }
+}
+
+/// A page requested through a tool's arguments.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PageRequest {
+    pub cursor: Option<String>,
+    pub page_size: Option<usize>,
+    /// Fingerprint of the remaining arguments, binding the cursor to them.
+    scope: String,
+}
+
+impl PageRequest {
+    /// Remove the paging arguments from `args`, leaving the handler's own.
+    pub fn take(args: &mut Map<String, Value>) -> Self {
+        let cursor = args
+            .remove("cursor")
+            .and_then(|v| v.as_str().map(str::to_string));
+        let page_size = args
+            .remove("page_size")
+            .and_then(|v| v.as_u64())
+            .map(|n| n as usize);
+        // serde_json maps are ordered by key, so equal arguments give
+        // equal scopes.
+        let scope = Value::Object(args.clone()).to_string();
+        Self {
+            cursor,
+            page_size,
+            scope,
+        }
+    }
+}
+
+/// Page the `field` list of `tool`'s JSON response. Responses that are not
+/// JSON objects or have no such list are returned unchanged; a cursor that
+/// does not belong to this call gives an error response.
+pub fn paginate(
+    tool: &str,
+    field: &str,
+    text: &str,
+    request: &PageRequest,
+    default_size: usize,
+) -> String {
+    let Ok(mut value) = serde_json::from_str::<Value>(text) else {
+        return text.to_string();
+    };
+    let Some(items) = value.get_mut(field).and_then(Value::as_array_mut) else {
+        return text.to_string();
+    };
+    let offset = match &request.cursor {
+        Some(cursor) => match decode_cursor(cursor, tool, &request.scope) {
+            Ok(offset) => offset,
+            Err(e) => return render(&serde_json::json!({"error": e.to_string()})),
+        },
+        None => 0,
+    };
+    let size = request.page_size.unwrap_or(default_size).max(1);
+    let total = items.len();
+    let page: Vec<Value> = items.drain(..).skip(offset).take(size).collect();
+    let returned = page.len();
+    *items = page;
+    let has_more = offset + returned < total;
+    if request.cursor.is_none() && !has_more {
+        // Everything fits in one page: leave the response as it was.
+        return text.to_string();
+    }
+    value["pagination"] = serde_json::json!({
+        "field": field,
+        "total": total,
+        "offset": offset,
+        "returned": returned,
+        "hasMore": has_more,
+        "nextCursor": has_more.then(|| encode_cursor(tool, &request.scope, offset + returned)),
+    });
+    render(&value)
+}
+
+fn render(value: &Value) -> String {
+    serde_json::to_string_pretty(value).unwrap_or_default()
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(args: Value) -> PageRequest {
+        PageRequest::take(&mut args.as_object().unwrap().clone())
+    }
+
+    #[test]
+    fn pages_follow_the_cursor_until_exhausted() {
+        let text = serde_json::json!({
+            "callerCount": 5,
+            "callers": [1, 2, 3, 4, 5],
+        })
+        .to_string();
+        let first = request(serde_json::json!({"symbol": "f", "page_size": 2}));
+        let page: Value = serde_json::from_str(&paginate(
+            "codegraph_callers",
+            "callers",
+            &text,
+            &first,
+            100,
+        ))
+        .unwrap();
+        assert_eq!(page["callers"], serde_json::json!([1, 2]));
+        assert_eq!(page["callerCount"], 5);
+        assert_eq!(page["pagination"]["hasMore"], true);
+        let cursor = page["pagination"]["nextCursor"].as_str().unwrap();
+
+        let mut cursor = cursor.to_string();
+        let mut seen = vec![1, 2];
+        loop {
+            let next = request(serde_json::json!({
+                "symbol": "f", "page_size": 2, "cursor": cursor,
+            }));
+            let page: Value =
+                serde_json::from_str(&paginate("codegraph_callers", "callers", &text, &next, 100))
+                    .unwrap();
+            seen.extend(
+                page["callers"]
+                    .as_array()
+                    .unwrap()
+                    .iter()
+                    .map(|v| v.as_i64().unwrap()),
+            );
+            match page["pagination"]["nextCursor"].as_str() {
+                Some(c) => cursor = c.to_string(),
+                None => break,
+            }
+        }
+        assert_eq!(seen, vec![1, 2, 3, 4, 5]);
+
+        // A cursor replayed with other arguments is rejected.
+        let other = request(serde_json::json!({"symbol": "g", "cursor": cursor}));
+        let page = paginate("codegraph_callers", "callers", &text, &other, 100);
+        assert!(page.contains("invalid cursor"), "{page}");
+    }
+
+    #[test]
+    fn small_and_non_json_responses_are_untouched() {
+        let text = r#"{"callers": [1, 2]}"#;
+        let req = request(serde_json::json!({}));
+        assert_eq!(
+            paginate("codegraph_callers", "callers", text, &req, 100),
+            text
+        );
+        assert_eq!(
+            paginate("codegraph_callers", "callers", "oops", &req, 1),
+            "oops"
+        );
+        assert_eq!(paged_field("codegraph_structure"), Some("modules"));
+        assert_eq!(paged_field("codegraph_query"), None);
+
+        let mut schema = Map::new();
+        add_page_params(&mut schema);
+        assert!(schema["properties"]["cursor"].is_object());
+    }
+}
@@ -0,0 +1,434 @@
+//! Response profiles — global caps that keep tool responses small.
+//!
+//! A profile (`response.profile`, see
+//! [`ResponseProfile`](crate::config::schema::ResponseProfile)) lets a
+//! preset for cheap models guarantee a token envelope without every tool
+//! knowing about it. [`ResponseShaper`] applies it around each tool call:
+//!
+//! - before the call, `depth`/`limit`/`budget` arguments the tool accepts
+//!   are clamped to the profile (and filled in when omitted);
+//! - after the call, the JSON response loses symbol bodies, its paths are
+//!   shortened, long strings are cut and every list is capped, recording
+//!   `<field>Omitted` counts next to shortened lists;
+//! - finally, while the response is over `max_chars`, its longest list is
+//!   halved and `truncated: true` is set.
+//!
+//! Responses that are not JSON are only cut to `max_chars`.
+//!
+//! Independently of the profile, `response.max_response_tokens` bounds the
+//! estimated token count of every response. A call over the budget is
+//! re-run with a lower `detail_level` (full, then standard, then summary)
+//! when the tool has one; what is still too large then has its longest
+//! lists halved and gets `truncated: true` with a hint to page or narrow
+//! the call.
+
+use std::path::Path;
+
+use serde_json::{Map, Value};
+
+use crate::config::schema::{ResponseConfig, ResponseProfile};
+use crate::context::budget::estimate_tokens;
+
+/// Arguments bounded by `max_depth`.
+const DEPTH_ARGS: &[&str] = &["depth", "max_depth"];
+/// Arguments bounded by `max_results`.
+const LIMIT_ARGS: &[&str] = &["limit", "top_k", "max_results"];
+/// Token-budget arguments, bounded by a quarter of `max_chars`.
+const BUDGET_ARGS: &[&str] = &["budget", "max_tokens"];
+/// Response fields holding source code.
+const BODY_FIELDS: &[&str] = &["body", "code", "sourceCode", "source_code"];
+/// Response fields holding file or directory paths.
+const PATH_FIELDS: &[&str] = &["file", "filePath", "file_path", "path", "directory"];
+/// `detail_level` values, most detailed first.
+const DETAIL_LEVELS: &[&str] = &["full", "standard", "summary"];
+
+/// Applies the configured response profile to tool calls.
+#[derive(Debug, Default)]
+pub struct ResponseShaper {
+    profile: Option<ResponseProfile>,
+    /// `response.max_response_tokens`.
+    max_tokens: Option<usize>,
+    /// Project root with a trailing `/`, stripped from absolute paths.
+    root_prefix: String,
+}
+
+impl ResponseShaper {
+    pub fn new(config: &ResponseConfig, project_root: &Path) -> Self {
+        let root = project_root.to_string_lossy();
+        Self {
+            profile: config.active_profile(),
+            max_tokens: config.max_response_tokens,
+            root_prefix: format!("{}/", root.trim_end_matches('/')),
+        }
+    }
+
+    /// Whether a profile is active; without one calls pass through.
+    pub fn is_active(&self) -> bool {
+        self.profile.is_some()
+    }
+
+    /// Whether responses have a token budget.
+    pub fn has_budget(&self) -> bool {
+        self.max_tokens.is_some()
+    }
+
+    /// Lower the `detail_level` argument of a call whose response `text` is
+    /// over the token budget, for a tool with `schema`. Returns the new
+    /// level, or `None` when the response fits or cannot get less detailed.
+    pub fn lower_detail(
+        &self,
+        schema: &Map<String, Value>,
+        args: &mut Map<String, Value>,
+        text: &str,
+    ) -> Option<&'static str> {
+        if !self.over_budget(text)
+            || !schema
+                .get("properties")
+                .and_then(Value::as_object)
+                .is_some_and(|p| p.contains_key("detail_level"))
+        {
+            return None;
+        }
+        let current = args
+            .get("detail_level")
+            .and_then(Value::as_str)
+            .unwrap_or("standard");
+        let position = DETAIL_LEVELS.iter().position(|l| *l == current)?;
+        let lower = DETAIL_LEVELS.get(position + 1)?;
+        args.insert("detail_level".to_string(), Value::from(*lower));
+        Some(lower)
+    }
+
+    /// Fit a response into the token budget: halve its longest lists until
+    /// it fits and mark it `truncated`. `degraded_to` is the detail level
+    /// the call was lowered to by [`lower_detail`](Self::lower_detail),
+    /// which marks the response too.
+    pub fn fit_budget(&self, text: &str, degraded_to: Option<&str>) -> String {
+        let Some(max) = self.max_tokens else {
+            return text.to_string();
+        };
+        if degraded_to.is_none() && !self.over_budget(text) {
+            return text.to_string();
+        }
+        let Ok(mut value) = serde_json::from_str::<Value>(text) else {
+            let mut cut_at = text.chars().count();
+            let mut out = text.to_string();
+            while self.over_budget(&out) && cut_at > 0 {
+                cut_at = cut_at * 3 / 4;
+                out = format!("{}\n…(truncated)", cut(text, cut_at));
+            }
+            return out;
+        };
+        if !value.is_object() {
+            value = serde_json::json!({ "results": value });
+        }
+        value["truncated"] = Value::Bool(true);
+        if let Some(level) = degraded_to {
+            value["detailLevel"] = Value::from(level);
+        }
+        value["truncatedHint"] = Value::from(format!(
+            "The response was reduced to fit response.max_response_tokens ({max}). \
+             Page through lists with `cursor`/`page_size`, lower `limit`, or narrow the query \
+             to see everything."
+        ));
+        let mut out = render(&value);
+        while self.over_budget(&out) && shrink(&mut value) {
+            out = render(&value);
+        }
+        out
+    }
+
+    fn over_budget(&self, text: &str) -> bool {
+        self.max_tokens
+            .is_some_and(|max| text.len() > max && estimate_tokens(text) > max)
+    }
+
+    /// Clamp the arguments of a call to a tool with `schema` (its JSON
+    /// input schema), adding the cap for bounded arguments left out.
+    pub fn cap_arguments(&self, schema: &Map<String, Value>, args: &mut Map<String, Value>) {
+        let Some(profile) = &self.profile else {
+            return;
+        };
+        let Some(properties) = schema.get("properties").and_then(Value::as_object) else {
+            return;
+        };
+        let caps = [
+            (DEPTH_ARGS, profile.max_depth),
+            (LIMIT_ARGS, profile.max_results),
+            (BUDGET_ARGS, profile.max_chars.map(|c| c / 4)),
+        ];
+        for (names, cap) in caps {
+            let Some(cap) = cap else {
+                continue;
+            };
+            for name in names.iter().filter(|n| properties.contains_key(**n)) {
+                let given = args.get(*name).and_then(Value::as_u64);
+                if given.is_none_or(|v| v > cap as u64) {
+                    args.insert(name.to_string(), Value::from(cap));
+                }
+            }
+        }
+    }
+
+    /// Prune a tool's text response to the profile.
+    pub fn shape(&self, text: &str) -> String {
+        let Some(profile) = &self.profile else {
+            return text.to_string();
+        };
+        let Ok(mut value) = serde_json::from_str::<Value>(text) else {
+            return match profile.max_chars {
+                Some(max) if text.chars().count() > max => {
+                    format!("{}\n…(truncated)", cut(text, max))
+                }
+                _ => text.to_string(),
+            };
+        };
+        self.prune(profile, &mut value);
+
+        let mut out = render(&value);
+        let Some(max) = profile.max_chars else {
+            return out;
+        };
+        if out.len() > max {
+            if let Value::Object(map) = &mut value {
+                map.insert("truncated".to_string(), Value::Bool(true));
+            }
+            out = render(&value);
+            while out.len() > max && shrink(&mut value) {
+                out = render(&value);
+            }
+        }
+        out
+    }
+
+    fn prune(&self, profile: &ResponseProfile, value: &mut Value) {
+        match value {
+            Value::Object(map) => {
+                if profile.strip_bodies {
+                    map.retain(|k, _| !BODY_FIELDS.contains(&k.as_str()));
+                }
+                let mut omitted = Vec::new();
+                for (key, child) in map.iter_mut() {
+                    if profile.shorten_paths && PATH_FIELDS.contains(&key.as_str()) {
+                        if let Value::String(path) = child {
+                            *path = self.shorten_path(path);
+                            continue;
+                        }
+                    }
+                    if let (Value::Array(items), Some(max)) = (&mut *child, profile.max_results) {
+                        if items.len() > max {
+                            omitted.push((format!("{key}Omitted"), items.len() - max));
+                            items.truncate(max);
+                        }
+                    }
+                    self.prune(profile, child);
+                }
+                for (key, n) in omitted {
+                    map.insert(key, Value::from(n));
+                }
+            }
+            Value::Array(items) => {
+                if let Some(max) = profile.max_results {
+                    items.truncate(max);
+                }
+                for item in items {
+                    self.prune(profile, item);
+                }
+            }
+            Value::String(s) => {
+                if let Some(max) = profile.max_string_chars {
+                    if s.chars().count() > max {
+                        *s = format!("{}…", cut(s, max));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Project-relative path with middle directories elided:
+    /// `/repo/src/mcp/tools/core.rs` becomes `src/…/tools/core.rs`.
+    fn shorten_path(&self, path: &str) -> String {
+        let rel = path.strip_prefix(&self.root_prefix).unwrap_or(path);
+        let parts: Vec<&str> = rel.split('/').filter(|p| !p.is_empty()).collect();
+        match parts.as_slice() {
+            [first, .., parent, name] if parts.len() > 3 => format!("{first}/…/{parent}/{name}"),
+            _ => rel.to_string(),
+        }
+    }
+}
+
+fn render(value: &Value) -> String {
+    serde_json::to_string_pretty(value).unwrap_or_default()
+}
+
+/// The first `max` characters of `s`.
+fn cut(s: &str, max: usize) -> &str {
+    s.char_indices().nth(max).map_or(s, |(i, _)| &s[..i])
+}
+
+/// Halve the longest list in `value`, or failing that the longest string.
+/// Returns false once nothing is left to shrink.
+fn shrink(value: &mut Value) -> bool {
+    fn longest(value: &Value, at: String, best: &mut Option<(usize, String, bool)>) {
+        let candidate = match value {
+            Value::Array(items) if items.len() > 1 => Some((items.len(), true)),
+            Value::String(s) if s.chars().count() > 16 => Some((s.chars().count(), false)),
+            _ => None,
+        };
+        if let Some((len, is_list)) = candidate {
+            // Any list beats any string; within a kind, the longest wins.
+            if best
+                .as_ref()
+                .is_none_or(|(l, _, list)| (is_list, len) > (*list, *l))
+            {
+                *best = Some((len, at.clone(), is_list));
+            }
+        }
+        match value {
+            Value::Array(items) => {
+                for (i, item) in items.iter().enumerate() {
+                    longest(item, format!("{at}/{i}"), best);
+                }
+            }
+            Value::Object(map) => {
+                for (key, child) in map {
+                    let key = key.replace('~', "~0").replace('/', "~1");
+                    longest(child, format!("{at}/{key}"), best);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut best = None;
+    longest(value, String::new(), &mut best);
+    let Some((len, pointer, _)) = best else {
+        return false;
+    };
+    match value.pointer_mut(&pointer) {
+        Some(Value::Array(items)) => items.truncate(len / 2),
+        Some(Value::String(s)) => *s = format!("{}…", cut(s, len / 2)),
+        _ => return false,
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn shaper(profile: ResponseProfile) -> ResponseShaper {
+        ResponseShaper {
+            profile: Some(profile),
+            max_tokens: None,
+            root_prefix: "/repo/".to_string(),
+        }
+    }
+
+    #[test]
+    fn full_profile_leaves_responses_alone() {
+        let config = ResponseConfig {
+            profile: Some("full".to_string()),
+            ..Default::default()
+        };
+        let shaper = ResponseShaper::new(&config, Path::new("/repo"));
+        assert!(!shaper.is_active());
+        let text = render(&json!({"body": "fn main() {}"}));
+        assert_eq!(shaper.shape(&text), text);
+    }
+
+    #[test]
+    fn arguments_are_clamped_and_filled_in() {
+        let shaper = shaper(ResponseProfile::builtin("agent-minimal").unwrap());
+        let schema = json!({"properties": {"depth": {}, "limit": {}, "symbol": {}}});
+        let mut args = json!({"depth": 5, "symbol": "main"});
+        shaper.cap_arguments(schema.as_object().unwrap(), args.as_object_mut().unwrap());
+        assert_eq!(args, json!({"depth": 1, "limit": 10, "symbol": "main"}));
+
+        let mut args = json!({"depth": 0});
+        shaper.cap_arguments(schema.as_object().unwrap(), args.as_object_mut().unwrap());
+        assert_eq!(args["depth"], 0);
+    }
+
+    #[test]
+    fn responses_lose_bodies_long_lists_and_long_paths() {
+        let shaper = shaper(ResponseProfile {
+            max_results: Some(2),
+            strip_bodies: true,
+            shorten_paths: true,
+            max_string_chars: Some(8),
+            ..Default::default()
+        });
+        let text = render(&json!({
+            "name": "handler",
+            "body": "fn handler() {}",
+            "file": "/repo/src/mcp/tools/core.rs",
+            "callers": [{"file": "src/a.rs"}, {"file": "src/b.rs"}, {"file": "src/c.rs"}],
+            "doc": "a rather long description",
+        }));
+        let shaped: Value = serde_json::from_str(&shaper.shape(&text)).unwrap();
+        assert_eq!(
+            shaped,
+            json!({
+                "name": "handler",
+                "file": "src/…/tools/core.rs",
+                "callers": [{"file": "src/a.rs"}, {"file": "src/b.rs"}],
+                "callersOmitted": 1,
+                "doc": "a rather…",
+            })
+        );
+    }
+
+    #[test]
+    fn oversized_responses_are_shrunk_to_the_envelope() {
+        let shaper = shaper(ResponseProfile {
+            max_chars: Some(400),
+            ..Default::default()
+        });
+        let items: Vec<Value> = (0..200).map(|i| json!({"id": i})).collect();
+        let text = render(&json!({"count": 200, "items": items}));
+        let shaped = shaper.shape(&text);
+        assert!(shaped.len() <= 400, "{}", shaped.len());
+        let value: Value = serde_json::from_str(&shaped).unwrap();
+        assert_eq!(value["truncated"], true);
+        assert_eq!(value["count"], 200);
+
+        let plain = "x".repeat(1000);
+        assert!(shaper.shape(&plain).ends_with("…(truncated)"));
+    }
+
+    #[test]
+    fn responses_over_the_token_budget_lose_detail_then_items() {
+        let config = ResponseConfig {
+            max_response_tokens: Some(300),
+            ..Default::default()
+        };
+        let shaper = ResponseShaper::new(&config, Path::new("/repo"));
+        assert!(!shaper.is_active() && shaper.has_budget());
+
+        let items: Vec<Value> = (0..200).map(|i| json!({"id": i, "name": "x"})).collect();
+        let text = render(&json!({"count": 200, "items": items}));
+        let schema = json!({"properties": {"detail_level": {}}});
+        let schema = schema.as_object().unwrap();
+        let mut args = json!({"detail_level": "full"});
+        let args = args.as_object_mut().unwrap();
+        assert_eq!(shaper.lower_detail(schema, args, &text), Some("standard"));
+        assert_eq!(shaper.lower_detail(schema, args, &text), Some("summary"));
+        assert_eq!(shaper.lower_detail(schema, args, &text), None);
+        assert_eq!(args["detail_level"], "summary");
+        assert_eq!(shaper.lower_detail(schema, args, "{}"), None);
+
+        let fitted = shaper.fit_budget(&text, Some("summary"));
+        assert!(estimate_tokens(&fitted) <= 300, "{fitted}");
+        let value: Value = serde_json::from_str(&fitted).unwrap();
+        assert_eq!(value["truncated"], true);
+        assert_eq!(value["detailLevel"], "summary");
+        assert_eq!(value["count"], 200);
+        assert!(value["truncatedHint"].as_str().unwrap().contains("cursor"));
+
+        let small = render(&json!({"count": 1}));
+        assert_eq!(shaper.fit_budget(&small, None), small);
+    }
+}
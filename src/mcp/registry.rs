@@ -1,24 +1,42 @@
 //! Tool-to-category registry for preset-based filtering.
 //!
-//! Maps each of the 46 MCP tools to its category so that `filter_tools()`
+//! Maps each of the 84 MCP tools to its category so that `filter_tools()`
 //! can decide which tools are visible for a given config preset.
 
 use crate::config::preset::*;
 use crate::config::schema::ToolMetadata;
 
-/// Return metadata for all 46 MCP tools, mapping each to its category.
+/// Return metadata for all 84 MCP tools, mapping each to its category.
 ///
 /// The order here mirrors the tool numbering in CLAUDE.md.
 /// Categories come from [`crate::config::preset`] constants.
 pub fn all_tool_metadata() -> Vec<ToolMetadata> {
     vec![
-        // ── Core (14) ─────────────────────────────────────────────
+        // ── Core (19) ─────────────────────────────────────────────
         meta(
             "codegraph_query",
             CATEGORY_SEARCH,
             "Hybrid semantic + keyword search with query expansion",
             200,
         ),
+        meta(
+            "codegraph_saved_query",
+            CATEGORY_SEARCH,
+            "Run or manage named, parameterized searches",
+            200,
+        ),
+        meta(
+            "codegraph_similar",
+            CATEGORY_SEARCH,
+            "Symbols with the nearest stored embeddings",
+            150,
+        ),
+        meta(
+            "codegraph_federated_query",
+            CATEGORY_SEARCH,
+            "Hybrid search across this and configured shared-library indexes",
+            200,
+        ),
         meta(
             "codegraph_search",
             CATEGORY_SEARCH,
@@ -97,7 +115,19 @@ pub fn all_tool_metadata() -> Vec<ToolMetadata> {
             "Language breakdown statistics",
             100,
         ),
-        // ── Git Integration (9) ──────────────────────────────────
+        meta(
+            "codegraph_complete",
+            CATEGORY_SEARCH,
+            "Graph-aware symbol completion at a cursor",
+            150,
+        ),
+        meta(
+            "codegraph_examples",
+            CATEGORY_CALL_GRAPH,
+            "Instructive call sites of a symbol",
+            300,
+        ),
+        // ── Git Integration (12) ─────────────────────────────────
         meta("codegraph_blame", CATEGORY_GIT, "Line-by-line blame", 200),
         meta(
             "codegraph_file_history",
@@ -120,7 +150,7 @@ pub fn all_tool_metadata() -> Vec<ToolMetadata> {
         meta(
             "codegraph_symbol_history",
             CATEGORY_GIT,
-            "Symbol modification history",
+            "Symbol history with before/after definitions",
             180,
         ),
         meta(
@@ -147,7 +177,25 @@ pub fn all_tool_metadata() -> Vec<ToolMetadata> {
             "Contributor statistics",
             150,
         ),
-        // ── Security (9) ─────────────────────────────────────────
+        meta(
+            "codegraph_reviewers",
+            CATEGORY_GIT,
+            "Ownership-weighted reviewer suggestions",
+            250,
+        ),
+        meta(
+            "codegraph_search_commits",
+            CATEGORY_GIT,
+            "Natural-language commit history search",
+            250,
+        ),
+        meta(
+            "codegraph_diff_impact",
+            CATEGORY_GIT,
+            "Changed symbols, their callers and tests to run",
+            350,
+        ),
+        // ── Security (11) ────────────────────────────────────────
         meta(
             "codegraph_scan_security",
             CATEGORY_SECURITY,
@@ -202,19 +250,49 @@ pub fn all_tool_metadata() -> Vec<ToolMetadata> {
             "Data flow tracing from source",
             200,
         ),
-        // ── Repository & Analysis (7) ────────────────────────────
+        meta(
+            "codegraph_audit_dependencies",
+            CATEGORY_SECURITY,
+            "Vulnerable lockfile packages (OSV) and their importers",
+            350,
+        ),
+        meta(
+            "codegraph_find_secrets",
+            CATEGORY_SECURITY,
+            "Hardcoded secrets by pattern and entropy",
+            300,
+        ),
+        // ── Repository & Analysis (29) ────────────────────────────
         meta(
             "codegraph_stats",
             CATEGORY_REPOSITORY,
             "Index statistics",
             100,
         ),
+        meta(
+            "codegraph_usage",
+            CATEGORY_REPOSITORY,
+            "Tool call analytics per tool",
+            150,
+        ),
         meta(
             "codegraph_circular_imports",
             CATEGORY_ANALYSIS,
             "Cycle detection (Tarjan SCC)",
             180,
         ),
+        meta(
+            "codegraph_architecture_check",
+            CATEGORY_ANALYSIS,
+            "Import/call edges violating configured layer rules",
+            250,
+        ),
+        meta(
+            "codegraph_module_coupling",
+            CATEGORY_ANALYSIS,
+            "Coupling, instability and abstractness per directory",
+            250,
+        ),
         meta(
             "codegraph_project_tree",
             CATEGORY_REPOSITORY,
@@ -227,12 +305,30 @@ pub fn all_tool_metadata() -> Vec<ToolMetadata> {
             "Cross-reference search",
             180,
         ),
+        meta(
+            "codegraph_rename_impact",
+            CATEGORY_ANALYSIS,
+            "Every edit a rename needs, plus dynamic references to check",
+            300,
+        ),
         meta(
             "codegraph_export_map",
             CATEGORY_REPOSITORY,
             "Module export listing",
             150,
         ),
+        meta(
+            "codegraph_api_surface",
+            CATEGORY_ANALYSIS,
+            "Exported symbols per module with signatures, docs, stability",
+            400,
+        ),
+        meta(
+            "codegraph_summarize_module",
+            CATEGORY_ANALYSIS,
+            "Cached natural-language summary of a directory via MCP sampling",
+            400,
+        ),
         meta(
             "codegraph_import_graph",
             CATEGORY_ANALYSIS,
@@ -245,7 +341,103 @@ pub fn all_tool_metadata() -> Vec<ToolMetadata> {
             "File symbol listing",
             150,
         ),
-        // ── Call Graph & Data Flow (6) ───────────────────────────
+        meta(
+            "codegraph_config_usage",
+            CATEGORY_ANALYSIS,
+            "Config key definitions and reading code",
+            200,
+        ),
+        meta(
+            "codegraph_diff_index",
+            CATEGORY_ANALYSIS,
+            "Symbol/edge diff against a saved snapshot",
+            250,
+        ),
+        meta(
+            "codegraph_field_usages",
+            CATEGORY_ANALYSIS,
+            "Functions reading/writing a struct or class field",
+            200,
+        ),
+        meta(
+            "codegraph_wire_contract",
+            CATEGORY_ANALYSIS,
+            "Serialized JSON shape of a type and its parsers",
+            250,
+        ),
+        meta(
+            "codegraph_pin_snapshot",
+            CATEGORY_REPOSITORY,
+            "Pin session reads to one database snapshot",
+            60,
+        ),
+        meta(
+            "codegraph_import_side_effects",
+            CATEGORY_ANALYSIS,
+            "Modules that run code at import time",
+            200,
+        ),
+        meta(
+            "codegraph_global_state",
+            CATEGORY_ANALYSIS,
+            "Global mutable state with readers and writers",
+            300,
+        ),
+        meta(
+            "codegraph_find_literal",
+            CATEGORY_ANALYSIS,
+            "String literals and constants by value",
+            200,
+        ),
+        meta(
+            "codegraph_grep",
+            CATEGORY_ANALYSIS,
+            "Regex search over indexed symbol bodies",
+            200,
+        ),
+        meta(
+            "codegraph_tag",
+            CATEGORY_ANALYSIS,
+            "Attach or remove key/value tags on a symbol",
+            81,
+        ),
+        meta(
+            "codegraph_annotate",
+            CATEGORY_ANALYSIS,
+            "Add or remove free-text notes on a symbol",
+            81,
+        ),
+        meta(
+            "codegraph_tagged",
+            CATEGORY_ANALYSIS,
+            "Symbols carrying given tags, or all tags in use",
+            150,
+        ),
+        meta(
+            "codegraph_duplicate_definitions",
+            CATEGORY_ANALYSIS,
+            "Symbols defined more than once, with body similarity",
+            250,
+        ),
+        meta(
+            "codegraph_find_clones",
+            CATEGORY_ANALYSIS,
+            "Semantic clone clusters from embedding similarity",
+            300,
+        ),
+        meta(
+            "codegraph_backfill_embeddings",
+            CATEGORY_ANALYSIS,
+            "Embed symbols missing an embedding (task-capable)",
+            120,
+        ),
+        meta(
+            "codegraph_recompute_pagerank",
+            CATEGORY_ANALYSIS,
+            "Recompute and store global PageRank (task-capable)",
+            150,
+        ),
+        // ── Call Graph & Data Flow (14) ──────────────────────────
         meta(
             "codegraph_find_path",
             CATEGORY_CALL_GRAPH,
@@ -258,6 +450,12 @@ pub fn all_tool_metadata() -> Vec<ToolMetadata> {
             "Cyclomatic + cognitive complexity",
             180,
         ),
+        meta(
+            "codegraph_test_gaps",
+            CATEGORY_ANALYSIS,
+            "Complex untested functions ranked by PageRank",
+            250,
+        ),
         meta(
             "codegraph_data_flow",
             CATEGORY_CALL_GRAPH,
@@ -282,13 +480,61 @@ pub fn all_tool_metadata() -> Vec<ToolMetadata> {
             "Reaching definition analysis",
             180,
         ),
-        // ── Deep Search (1) ─────────────────────────────────────
+        meta(
+            "codegraph_recursion",
+            CATEGORY_CALL_GRAPH,
+            "Direct and mutual recursion cycles",
+            220,
+        ),
+        meta(
+            "codegraph_unused_params",
+            CATEGORY_CALL_GRAPH,
+            "Unused parameters and ignored return values",
+            250,
+        ),
+        meta(
+            "codegraph_callgraph_export",
+            CATEGORY_CALL_GRAPH,
+            "Subgraph around a symbol as nodes and edges",
+            400,
+        ),
+        meta(
+            "codegraph_entrypoints",
+            CATEGORY_CALL_GRAPH,
+            "Mains, routes, commands and package exports with callees",
+            400,
+        ),
+        meta(
+            "codegraph_cfg",
+            CATEGORY_CALL_GRAPH,
+            "Control-flow graph of a function as JSON or Mermaid",
+            350,
+        ),
+        meta(
+            "codegraph_null_checks",
+            CATEGORY_CALL_GRAPH,
+            "Dereferences of possibly null variables",
+            250,
+        ),
+        meta(
+            "codegraph_routes",
+            CATEGORY_CALL_GRAPH,
+            "HTTP routes with method, path and handler",
+            300,
+        ),
+        // ── Deep Search (2) ─────────────────────────────────────
         meta(
             "codegraph_deep_query",
             CATEGORY_SEARCH,
             "Cross-encoder re-ranked deep search",
             250,
         ),
+        meta(
+            "codegraph_deep_file_query",
+            CATEGORY_SEARCH,
+            "Cross-encoder re-ranked file search",
+            220,
+        ),
     ]
 }
 
@@ -326,12 +572,12 @@ mod tests {
     use std::collections::HashSet;
 
     #[test]
-    fn registry_has_46_tools() {
+    fn registry_has_87_tools() {
         let tools = all_tool_metadata();
         assert_eq!(
             tools.len(),
-            46,
-            "expected 46 tools in registry, got {}",
+            87,
+            "expected 87 tools in registry, got {}",
             tools.len()
         );
     }
@@ -358,13 +604,13 @@ mod tests {
     }
 
     #[test]
-    fn full_preset_enables_all_46() {
+    fn full_preset_enables_all_87() {
         let config = CodeGraphConfig::default(); // Full preset
         let enabled = enabled_tool_names(&config);
         assert_eq!(
             enabled.len(),
-            46,
-            "full preset should enable all 46 tools, got {}",
+            87,
+            "full preset should enable all 87 tools, got {}",
             enabled.len()
         );
     }
@@ -386,8 +632,8 @@ mod tests {
             );
         }
         assert!(
-            enabled.len() < 46,
-            "minimal should have fewer than 46 tools"
+            enabled.len() < 87,
+            "minimal should have fewer than 87 tools"
         );
         assert!(enabled.len() >= 10, "minimal should have at least 10 tools");
     }
@@ -454,10 +700,13 @@ mod tests {
             counts[CATEGORY_SEARCH] >= 5,
             "Search should have >= 5 tools"
         );
-        assert!(counts[CATEGORY_GIT] == 9, "Git should have exactly 9 tools");
         assert!(
-            counts[CATEGORY_SECURITY] == 9,
-            "Security should have exactly 9 tools"
+            counts[CATEGORY_GIT] == 12,
+            "Git should have exactly 12 tools"
+        );
+        assert!(
+            counts[CATEGORY_SECURITY] == 11,
+            "Security should have exactly 11 tools"
         );
     }
 }
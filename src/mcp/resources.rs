@@ -0,0 +1,353 @@
+//! Per-file and per-symbol MCP resources.
+//!
+//! Besides the fixed `codegraph://status` and `codegraph://overview`
+//! resources, the server advertises two URI templates:
+//!
+//! - `codegraph://file/{+path}` — the symbol outline of an indexed file,
+//!   nested by line-range containment.
+//! - `codegraph://symbol/{id}` — one symbol: location, signature, docs,
+//!   body, and its direct callers and callees.
+//!
+//! Every outline entry and relation carries the `uri` of its symbol
+//! document, so clients can follow, cache or subscribe to them instead of
+//! calling `codegraph_file` and `codegraph_node` again. Path and ID
+//! segments are percent-encoded in URIs.
+
+use std::collections::{BTreeSet, HashMap};
+
+use rmcp::model::{Annotated, RawResourceTemplate, ResourceTemplate};
+use serde_json::Value;
+
+use crate::context::budget::signature_only;
+use crate::error::Result;
+use crate::graph::store::GraphStore;
+use crate::types::{CodeNode, EdgeKind};
+
+/// Prefix of file outline URIs.
+pub const FILE_URI_PREFIX: &str = "codegraph://file/";
+/// Prefix of symbol document URIs.
+pub const SYMBOL_URI_PREFIX: &str = "codegraph://symbol/";
+
+/// Callers and callees listed in a symbol document.
+const MAX_RELATIONS: usize = 50;
+
+/// A resource addressed by one of the templates.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResourceRef {
+    File(String),
+    Symbol(String),
+}
+
+impl ResourceRef {
+    /// Parse a `codegraph://file/...` or `codegraph://symbol/...` URI.
+    pub fn parse(uri: &str) -> Option<Self> {
+        if let Some(path) = uri.strip_prefix(FILE_URI_PREFIX) {
+            let path = percent_decode(path)?;
+            return (!path.is_empty()).then_some(Self::File(path));
+        }
+        let id = percent_decode(uri.strip_prefix(SYMBOL_URI_PREFIX)?)?;
+        (!id.is_empty()).then_some(Self::Symbol(id))
+    }
+
+    pub fn uri(&self) -> String {
+        match self {
+            Self::File(path) => file_uri(path),
+            Self::Symbol(id) => symbol_uri(id),
+        }
+    }
+}
+
+/// URI of a file's outline. Slashes are kept so the URI reads as a path.
+pub fn file_uri(path: &str) -> String {
+    format!("{FILE_URI_PREFIX}{}", percent_encode(path, true))
+}
+
+/// URI of a symbol's document.
+pub fn symbol_uri(id: &str) -> String {
+    format!("{SYMBOL_URI_PREFIX}{}", percent_encode(id, false))
+}
+
+/// The URI templates advertised by `resources/templates/list`.
+pub fn resource_templates() -> Vec<ResourceTemplate> {
+    let template = |uri_template: &str, name: &str, description: &str| {
+        Annotated::new(
+            RawResourceTemplate {
+                uri_template: uri_template.into(),
+                name: name.into(),
+                title: None,
+                description: Some(description.into()),
+                mime_type: Some("application/json".into()),
+                icons: None,
+            },
+            None,
+        )
+    };
+    vec![
+        template(
+            "codegraph://file/{+path}",
+            "File Outline",
+            "Symbols of an indexed file (project-relative path), nested by containment, each with its symbol URI.",
+        ),
+        template(
+            "codegraph://symbol/{id}",
+            "Symbol",
+            "One symbol by node ID: location, signature, documentation, body, direct callers and callees.",
+        ),
+    ]
+}
+
+/// Outline of `path`, or `None` when the file has no indexed symbols.
+pub fn file_outline(store: &GraphStore, path: &str) -> Result<Option<Value>> {
+    let mut nodes = store.get_nodes_by_file(path)?;
+    if nodes.is_empty() {
+        return Ok(None);
+    }
+    // Outer symbols first, so each one's parent is already on the stack.
+    nodes.sort_by(|a, b| {
+        (a.start_line, std::cmp::Reverse(a.end_line), &a.id).cmp(&(
+            b.start_line,
+            std::cmp::Reverse(b.end_line),
+            &b.id,
+        ))
+    });
+    let language = nodes[0].language.as_str();
+    let symbol_count = nodes.len();
+
+    // Index of each node's innermost enclosing node.
+    let mut parents: Vec<Option<usize>> = Vec::with_capacity(nodes.len());
+    let mut stack: Vec<usize> = Vec::new();
+    for (i, node) in nodes.iter().enumerate() {
+        while let Some(&top) = stack.last() {
+            if nodes[top].end_line >= node.end_line {
+                break;
+            }
+            stack.pop();
+        }
+        parents.push(stack.last().copied());
+        stack.push(i);
+    }
+    let mut children: HashMap<Option<usize>, Vec<usize>> = HashMap::new();
+    for (i, parent) in parents.iter().enumerate() {
+        children.entry(*parent).or_default().push(i);
+    }
+
+    fn entry(i: usize, nodes: &[CodeNode], children: &HashMap<Option<usize>, Vec<usize>>) -> Value {
+        let n = &nodes[i];
+        let mut out = serde_json::json!({
+            "id": n.id,
+            "uri": symbol_uri(&n.id),
+            "name": n.name,
+            "kind": n.kind.as_str(),
+            "startLine": n.start_line,
+            "endLine": n.end_line,
+        });
+        if let Some(exported) = n.exported {
+            out["exported"] = serde_json::json!(exported);
+        }
+        if let Some(kids) = children.get(&Some(i)) {
+            out["children"] = kids.iter().map(|&c| entry(c, nodes, children)).collect();
+        }
+        out
+    }
+    let symbols: Vec<Value> = children
+        .get(&None)
+        .into_iter()
+        .flatten()
+        .map(|&i| entry(i, &nodes, &children))
+        .collect();
+
+    Ok(Some(serde_json::json!({
+        "uri": file_uri(path),
+        "filePath": path,
+        "language": language,
+        "symbolCount": symbol_count,
+        "symbols": symbols,
+    })))
+}
+
+/// Document of the symbol `id`, or `None` when it is not in the index.
+pub fn symbol_document(store: &GraphStore, id: &str) -> Result<Option<Value>> {
+    let Some(node) = store.get_node(id)? else {
+        return Ok(None);
+    };
+    let calls = EdgeKind::Calls.as_str();
+    let callers: BTreeSet<String> = store
+        .get_in_edges(id, Some(calls))?
+        .into_iter()
+        .map(|e| e.source)
+        .collect();
+    let callees: BTreeSet<String> = store
+        .get_out_edges(id, Some(calls))?
+        .into_iter()
+        .map(|e| e.target)
+        .collect();
+    let relation = |ids: &BTreeSet<String>| -> Result<Vec<Value>> {
+        let mut out = Vec::new();
+        for rel in ids.iter().take(MAX_RELATIONS) {
+            if let Some(n) = store.get_node(rel)? {
+                out.push(serde_json::json!({
+                    "id": n.id,
+                    "uri": symbol_uri(&n.id),
+                    "name": n.name,
+                    "kind": n.kind.as_str(),
+                    "filePath": n.file_path,
+                    "startLine": n.start_line,
+                }));
+            }
+        }
+        Ok(out)
+    };
+
+    Ok(Some(serde_json::json!({
+        "uri": symbol_uri(&node.id),
+        "id": node.id,
+        "name": node.name,
+        "qualifiedName": node.qualified_name,
+        "kind": node.kind.as_str(),
+        "language": node.language.as_str(),
+        "filePath": node.file_path,
+        "fileUri": file_uri(&node.file_path),
+        "startLine": node.start_line,
+        "endLine": node.end_line,
+        "exported": node.exported,
+        "signature": node.body.as_deref().map(signature_only),
+        "documentation": node.documentation,
+        "body": node.body,
+        "callerCount": callers.len(),
+        "callers": relation(&callers)?,
+        "calleeCount": callees.len(),
+        "callees": relation(&callees)?,
+    })))
+}
+
+// ---------------------------------------------------------------------------
+// Percent-encoding
+// ---------------------------------------------------------------------------
+
+/// Percent-encode everything outside RFC 3986's unreserved set (and `/`
+/// when `keep_slash`).
+fn percent_encode(s: &str, keep_slash: bool) -> String {
+    let mut out = String::with_capacity(s.len());
+    for b in s.bytes() {
+        if b.is_ascii_alphanumeric() || b"-._~".contains(&b) || (keep_slash && b == b'/') {
+            out.push(b as char);
+        } else {
+            out.push_str(&format!("%{b:02X}"));
+        }
+    }
+    out
+}
+
+/// Decode `%XX` escapes; `None` for a malformed escape or invalid UTF-8.
+fn percent_decode(s: &str) -> Option<String> {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = s.get(i + 1..i + 3)?;
+            out.push(u8::from_str_radix(hex, 16).ok()?);
+            i += 3;
+        } else {
+            out.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(out).ok()
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::schema::initialize_database;
+    use crate::types::{CodeEdge, Language, NodeKind};
+
+    fn node(id: &str, kind: NodeKind, start: u32, end: u32) -> CodeNode {
+        CodeNode {
+            id: id.to_string(),
+            name: id.to_string(),
+            qualified_name: None,
+            kind,
+            file_path: "src/cart.ts".to_string(),
+            start_line: start,
+            end_line: end,
+            start_column: 0,
+            end_column: 0,
+            language: Language::TypeScript,
+            body: Some(format!("function {id}() {{}}")),
+            documentation: None,
+            exported: None,
+        }
+    }
+
+    #[test]
+    fn outline_nests_symbols_and_documents_link_relations() {
+        let store = GraphStore::from_connection(initialize_database(":memory:").unwrap());
+        store
+            .upsert_nodes(&[
+                node("Cart", NodeKind::Class, 1, 20),
+                node("total", NodeKind::Method, 2, 8),
+                node("add", NodeKind::Method, 10, 19),
+                node("helper", NodeKind::Function, 22, 25),
+            ])
+            .unwrap();
+        store
+            .upsert_edges(&[CodeEdge {
+                source: "add".to_string(),
+                target: "total".to_string(),
+                kind: EdgeKind::Calls,
+                file_path: "src/cart.ts".to_string(),
+                line: 12,
+                metadata: None,
+            }])
+            .unwrap();
+
+        let outline = file_outline(&store, "src/cart.ts").unwrap().unwrap();
+        assert_eq!(outline["symbolCount"], 4);
+        let top: Vec<&str> = outline["symbols"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|s| s["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(top, vec!["Cart", "helper"]);
+        assert_eq!(outline["symbols"][0]["children"][1]["name"], "add");
+        assert_eq!(
+            outline["symbols"][0]["children"][1]["uri"],
+            "codegraph://symbol/add"
+        );
+        assert!(file_outline(&store, "src/none.ts").unwrap().is_none());
+
+        let doc = symbol_document(&store, "total").unwrap().unwrap();
+        assert_eq!(doc["callerCount"], 1);
+        assert_eq!(doc["callers"][0]["uri"], "codegraph://symbol/add");
+        assert_eq!(doc["fileUri"], "codegraph://file/src/cart.ts");
+        assert_eq!(doc["signature"], "function total()");
+        assert!(symbol_document(&store, "missing").unwrap().is_none());
+    }
+
+    #[test]
+    fn uris_round_trip_through_percent_encoding() {
+        let id = "method:src/cart.ts:Cart.total:12";
+        let uri = symbol_uri(id);
+        assert_eq!(
+            uri,
+            "codegraph://symbol/method%3Asrc%2Fcart.ts%3ACart.total%3A12"
+        );
+        assert_eq!(
+            ResourceRef::parse(&uri),
+            Some(ResourceRef::Symbol(id.into()))
+        );
+
+        let uri = file_uri("src/my file.ts");
+        assert_eq!(uri, "codegraph://file/src/my%20file.ts");
+        assert_eq!(ResourceRef::parse(&uri).map(|r| r.uri()), Some(uri.clone()));
+        assert_eq!(ResourceRef::parse("codegraph://symbol/%zz"), None);
+        assert_eq!(ResourceRef::parse("codegraph://file/"), None);
+        assert_eq!(ResourceRef::parse("codegraph://status"), None);
+    }
+}
@@ -0,0 +1,139 @@
+//! Multi-root servers: several indexed projects behind one MCP server.
+//!
+//! Monorepos often hold several logical projects, each indexed on its own.
+//! Instead of one server per project, `codegraph serve --root api=services/api
+//! --root web=apps/web` opens every root's `.codegraph/codegraph.db` next to
+//! the default project. Every tool then takes an optional `workspace`
+//! argument naming the root to run against; calls without it use the
+//! default project:
+//!
+//! ```json
+//! { "name": "codegraph_callers", "arguments": { "symbol": "login", "workspace": "api" } }
+//! ```
+//!
+//! Each root is also listed as a `codegraph://workspaces/<name>` resource
+//! reporting its path and index status. Roots share the server's config,
+//! sessions and limits; snapshots pinned by `codegraph_pin_snapshot` only
+//! apply to the default project.
+
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use serde_json::{Map, Value};
+
+use crate::graph::store::GraphStore;
+
+/// Argument naming the root a tool call runs against.
+pub const WORKSPACE_PARAM: &str = "workspace";
+
+/// URI prefix of the per-root resources.
+const RESOURCE_PREFIX: &str = "codegraph://workspaces/";
+
+/// An indexed project served next to the default one.
+#[derive(Clone)]
+pub struct ProjectRoot {
+    pub name: String,
+    pub project_root: PathBuf,
+    pub store: Arc<Mutex<GraphStore>>,
+}
+
+impl ProjectRoot {
+    pub fn new(name: impl Into<String>, project_root: PathBuf, store: GraphStore) -> Self {
+        Self {
+            name: name.into(),
+            project_root,
+            store: Arc::new(Mutex::new(store)),
+        }
+    }
+}
+
+/// Parse a `--root` value, `<name>=<path>`. Names are letters, digits,
+/// `-`, `_` and `.`, so they fit into resource URIs.
+pub fn parse_root_spec(spec: &str) -> Option<(String, PathBuf)> {
+    let (name, path) = spec.split_once('=')?;
+    let valid = !name.is_empty()
+        && name
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_' | b'.'));
+    (valid && !path.is_empty()).then(|| (name.to_string(), PathBuf::from(path)))
+}
+
+/// Add the `workspace` argument, limited to the root `names`, to a tool's
+/// input schema.
+pub fn add_workspace_param(schema: &mut Map<String, Value>, names: &[&str]) {
+    let properties = schema
+        .entry("properties")
+        .or_insert_with(|| Value::Object(Map::new()));
+    let choices: Vec<Value> = names
+        .iter()
+        .map(|n| Value::from(*n))
+        .chain([Value::Null])
+        .collect();
+    if let Some(properties) = properties.as_object_mut() {
+        properties.insert(
+            WORKSPACE_PARAM.to_string(),
+            serde_json::json!({
+                "type": ["string", "null"],
+                "enum": choices,
+                "description": "Project root to run against (default: the server's main project)",
+            }),
+        );
+    }
+}
+
+/// Remove the `workspace` argument from `args`, returning the root it names.
+pub fn take_workspace(args: &mut Option<Map<String, Value>>) -> Option<String> {
+    args.as_mut()?
+        .remove(WORKSPACE_PARAM)
+        .and_then(|v| v.as_str().map(str::to_string))
+}
+
+/// The resource URI of the root `name`.
+pub fn resource_uri(name: &str) -> String {
+    format!("{RESOURCE_PREFIX}{name}")
+}
+
+/// The root named by a `codegraph://workspaces/<name>` URI.
+pub fn parse_resource_uri(uri: &str) -> Option<&str> {
+    uri.strip_prefix(RESOURCE_PREFIX).filter(|n| !n.is_empty())
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn workspace_arguments_and_root_specs() {
+        assert_eq!(
+            parse_root_spec("api=services/api"),
+            Some(("api".to_string(), PathBuf::from("services/api")))
+        );
+        assert_eq!(parse_root_spec("services/api"), None);
+        assert_eq!(parse_root_spec("a/b=services"), None);
+        assert_eq!(parse_root_spec("api="), None);
+
+        let mut schema = Map::new();
+        add_workspace_param(&mut schema, &["api", "web"]);
+        assert_eq!(
+            schema["properties"]["workspace"]["enum"],
+            serde_json::json!(["api", "web", null])
+        );
+
+        let mut args = serde_json::json!({"symbol": "login", "workspace": "api"})
+            .as_object()
+            .cloned();
+        assert_eq!(take_workspace(&mut args).as_deref(), Some("api"));
+        assert_eq!(
+            args,
+            serde_json::json!({"symbol": "login"}).as_object().cloned()
+        );
+        assert_eq!(take_workspace(&mut None), None);
+
+        assert_eq!(parse_resource_uri(&resource_uri("web")), Some("web"));
+        assert_eq!(parse_resource_uri("codegraph://workspaces/"), None);
+    }
+}
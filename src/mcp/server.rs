@@ -1,29 +1,44 @@
 //! MCP server implementation using rmcp over stdio transport.
 //!
-//! Provides 46 CodeGraph tools that Claude (or any MCP client) can invoke
+//! Provides 87 CodeGraph tools that Claude (or any MCP client) can invoke
 //! to search, navigate, analyze, secure, and visualize a codebase.
 
 use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Mutex, OnceLock, Weak};
+use std::time::{Duration, Instant};
 
 use rmcp::handler::server::wrapper::Parameters;
 use rmcp::model::{
-    Annotated, CallToolRequestParams, CallToolResult, GetPromptRequestParams, GetPromptResult,
-    ListPromptsResult, ListResourcesResult, ListToolsResult, PaginatedRequestParams, Prompt,
-    PromptArgument, PromptMessage, PromptMessageRole, RawResource, ReadResourceRequestParams,
-    ReadResourceResult, ResourceContents, ServerCapabilities, ServerInfo,
+    Annotated, CallToolRequestParams, CallToolResult, CancelTaskParams, CreateTaskResult,
+    CustomRequest, CustomResult, GetPromptRequestParams, GetPromptResult, GetTaskInfoParams,
+    GetTaskInfoResult, GetTaskResultParams, ListPromptsResult, ListResourceTemplatesResult,
+    ListResourcesResult, ListTasksResult, ListToolsResult, PaginatedRequestParams, Prompt,
+    PromptArgument, PromptMessage, PromptMessageRole, RawContent, RawResource,
+    ReadResourceRequestParams, ReadResourceResult, ResourceContents, ServerCapabilities,
+    ServerInfo, SubscribeRequestParams, TaskResult, TasksCapability, UnsubscribeRequestParams,
 };
 use rmcp::service::{RequestContext, RoleServer};
 use rmcp::{tool, tool_router, ErrorData as McpError, ServerHandler, ServiceExt};
 use serde::{Deserialize, Serialize};
 
 use crate::config::schema::CodeGraphConfig;
+use crate::db::diagnostics::log_query;
+use crate::db::findings::{record_scan, FindingCategory, FindingEmbedder, NewFinding, ScanRecord};
+use crate::db::usage::{new_session_id, record_invocation, ToolInvocation};
+use crate::graph::pool::{ReadPool, DEFAULT_MAX_IDLE};
 use crate::graph::ranking::GraphRanking;
-use crate::graph::store::GraphStore;
+use crate::graph::store::{GraphStore, STREAM_BATCH_SIZE};
 use crate::graph::traversal::NodeWithDepth;
 use crate::types::CodeNode;
 
+use super::i18n::Localizer;
+use super::limits::ToolLimiter;
+use super::profile::ResponseShaper;
+use super::roots::ProjectRoot;
+use super::subscriptions::{IndexWatch, Subscriptions, POLL_INTERVAL};
+use super::tasks::{TaskManager, TaskProgress, TASK_TOOLS};
+
 // ---------------------------------------------------------------------------
 // Server struct
 // ---------------------------------------------------------------------------
@@ -36,8 +51,36 @@ use crate::types::CodeNode;
 #[derive(Clone)]
 pub struct CodeGraphServer {
     store: Arc<Mutex<GraphStore>>,
+    /// Snapshot pinned by `codegraph_pin_snapshot`; tool calls read from it
+    /// instead of `store` while set. Shared by clones, so each MCP session
+    /// starts from [`for_session`](Self::for_session).
+    pinned: PinSlot,
+    /// The pin slots of every session holding a snapshot, so any session's
+    /// call can release expired pins and the total stays capped.
+    live_pins: Arc<Mutex<Vec<Weak<PinCell>>>>,
+    /// Read-only connections [`POOLED_TOOLS`] run on, so they do not wait
+    /// for `store`'s lock; `None` for in-memory stores.
+    readers: Option<Arc<ReadPool>>,
+    /// The pooled connection of the call this clone runs, see
+    /// [`for_call`](Self::for_call).
+    reader: Option<Arc<ReaderLease>>,
     project_root: PathBuf,
     config: CodeGraphConfig,
+    localizer: Arc<Localizer>,
+    shaper: Arc<ResponseShaper>,
+    /// Resources this session subscribed to, see
+    /// [`subscriptions`](super::subscriptions).
+    subscriptions: Arc<Subscriptions>,
+    /// Timeouts and concurrency limits of tool calls, shared by sessions.
+    limiter: Arc<ToolLimiter>,
+    /// Tool calls this session runs as tasks, see [`tasks`](super::tasks).
+    tasks: TaskManager,
+    /// Identifies this session's calls in the usage analytics, see
+    /// [`usage`](crate::db::usage).
+    session_id: Arc<str>,
+    /// Further projects served next to this one, picked by the `workspace`
+    /// argument of tool calls, see [`roots`](super::roots).
+    roots: Arc<Vec<ProjectRoot>>,
     #[cfg(feature = "reranking")]
     reranker: Option<Arc<crate::graph::reranker::Reranker>>,
 }
@@ -60,9 +103,20 @@ impl CodeGraphServer {
     /// Create a new MCP server backed by the given store.
     pub fn new(store: GraphStore) -> Self {
         Self {
+            readers: read_pool(&store),
+            reader: None,
             store: Arc::new(Mutex::new(store)),
+            pinned: Arc::default(),
+            live_pins: Arc::default(),
             project_root: PathBuf::from("."),
             config: CodeGraphConfig::default(),
+            localizer: Arc::default(),
+            shaper: Arc::default(),
+            subscriptions: Arc::default(),
+            limiter: Arc::default(),
+            tasks: TaskManager::default(),
+            session_id: new_session_id().into(),
+            roots: Arc::default(),
             #[cfg(feature = "reranking")]
             reranker: crate::graph::reranker::Reranker::try_new()
                 .ok()
@@ -73,9 +127,20 @@ impl CodeGraphServer {
     /// Create a new MCP server with an explicit project root.
     pub fn with_project_root(store: GraphStore, project_root: PathBuf) -> Self {
         Self {
+            readers: read_pool(&store),
+            reader: None,
             store: Arc::new(Mutex::new(store)),
+            pinned: Arc::default(),
+            live_pins: Arc::default(),
             project_root,
             config: CodeGraphConfig::default(),
+            localizer: Arc::default(),
+            shaper: Arc::default(),
+            subscriptions: Arc::default(),
+            limiter: Arc::default(),
+            tasks: TaskManager::default(),
+            session_id: new_session_id().into(),
+            roots: Arc::default(),
             #[cfg(feature = "reranking")]
             reranker: crate::graph::reranker::Reranker::try_new()
                 .ok()
@@ -85,18 +150,708 @@ impl CodeGraphServer {
 
     /// Create a new MCP server with an explicit project root and config.
     pub fn with_config(store: GraphStore, project_root: PathBuf, config: CodeGraphConfig) -> Self {
+        if config.performance.adjacency_cache {
+            store.enable_adjacency_cache();
+        }
+        store.set_body_storage(config.performance.body_storage());
+        store.set_tombstone_policy(config.performance.tombstone_policy());
+        crate::sandbox::register(config.sandbox.policy(), &project_root);
+        let localizer = Arc::new(Localizer::new(&config.i18n, &project_root));
+        let shaper = Arc::new(ResponseShaper::new(&config.response, &project_root));
+        let limiter = Arc::new(ToolLimiter::new(&config.tools.limits));
         Self {
+            readers: read_pool(&store),
+            reader: None,
             store: Arc::new(Mutex::new(store)),
+            pinned: Arc::default(),
+            live_pins: Arc::default(),
             project_root,
             config,
+            localizer,
+            shaper,
+            subscriptions: Arc::default(),
+            limiter,
+            tasks: TaskManager::default(),
+            session_id: new_session_id().into(),
+            roots: Arc::default(),
             #[cfg(feature = "reranking")]
             reranker: crate::graph::reranker::Reranker::try_new()
                 .ok()
                 .map(Arc::new),
         }
     }
+
+    /// Serve `roots` next to this project, configured like it.
+    pub fn with_roots(mut self, roots: Vec<ProjectRoot>) -> Self {
+        for root in &roots {
+            let store = root.store.lock().unwrap_or_else(|e| e.into_inner());
+            if self.config.performance.adjacency_cache {
+                store.enable_adjacency_cache();
+            }
+            store.set_body_storage(self.config.performance.body_storage());
+            store.set_tombstone_policy(self.config.performance.tombstone_policy());
+            crate::sandbox::register(self.config.sandbox.policy(), &root.project_root);
+        }
+        self.roots = Arc::new(roots);
+        self
+    }
+
+    /// This session running against the root `name` instead of the default
+    /// project. Pinned snapshots belong to the default project, so the
+    /// clone reads the root's live store.
+    fn for_workspace(&self, name: &str) -> Option<Self> {
+        let root = self.roots.iter().find(|r| r.name == name)?;
+        Some(Self {
+            store: Arc::clone(&root.store),
+            pinned: Arc::default(),
+            readers: None,
+            project_root: root.project_root.clone(),
+            ..self.clone()
+        })
+    }
+
+    /// The store shared by every clone of this server.
+    pub(crate) fn store(&self) -> &Arc<Mutex<GraphStore>> {
+        &self.store
+    }
+
+    /// The project configuration the tools run with.
+    pub(crate) fn config(&self) -> &CodeGraphConfig {
+        &self.config
+    }
+
+    /// A clone for a new MCP session: same store and config, but no pinned
+    /// snapshot, subscriptions or tasks, so one client's pin never leaks into
+    /// another's reads.
+    pub fn for_session(&self) -> Self {
+        Self {
+            pinned: Arc::default(),
+            subscriptions: Arc::default(),
+            tasks: TaskManager::default(),
+            session_id: new_session_id().into(),
+            ..self.clone()
+        }
+    }
+
+    /// Whether `other` is a clone of this session rather than another one.
+    #[cfg(test)]
+    pub(crate) fn same_session(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.subscriptions, &other.subscriptions)
+    }
+
+    /// The store tool calls read from: the pinned snapshot when this
+    /// session holds one, else the call's pooled reader, otherwise the live
+    /// store.
+    fn active_store(&self) -> Arc<Mutex<GraphStore>> {
+        self.release_expired_pins();
+        if let Some(pin) = self
+            .pinned
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .as_ref()
+        {
+            return Arc::clone(&pin.store);
+        }
+        match &self.reader {
+            Some(lease) => lease.store(),
+            None => Arc::clone(&self.store),
+        }
+    }
+
+    /// The clone a `tool` call runs on: for [`POOLED_TOOLS`], one reading
+    /// from a pooled read-only connection (returned when the clone is
+    /// dropped). Falls back to the shared store when pinned or when no
+    /// connection can be opened.
+    fn for_call(&self, tool: &str) -> Self {
+        let pinned = self
+            .pinned
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .is_some();
+        let reader = match &self.readers {
+            Some(pool) if !pinned && POOLED_TOOLS.contains(&tool) => match pool.take() {
+                Ok(store) => {
+                    if self.config.performance.adjacency_cache {
+                        store.enable_adjacency_cache();
+                    }
+                    store.set_body_storage(self.config.performance.body_storage());
+                    store.set_tombstone_policy(self.config.performance.tombstone_policy());
+                    Some(Arc::new(ReaderLease {
+                        store: Some(Arc::new(Mutex::new(store))),
+                        pool: Arc::clone(pool),
+                    }))
+                }
+                Err(e) => {
+                    tracing::warn!("no pooled reader for {}: {}", tool, e);
+                    None
+                }
+            },
+            _ => None,
+        };
+        Self {
+            reader,
+            ..self.clone()
+        }
+    }
+
+    /// Release every session's pin older than [`MAX_PIN_AGE`] and return
+    /// how many pins are still live. Must not be called while holding a
+    /// pin slot's lock.
+    fn release_expired_pins(&self) -> usize {
+        let mut slots = self.live_pins.lock().unwrap_or_else(|e| e.into_inner());
+        slots.retain(|slot| {
+            let Some(slot) = slot.upgrade() else {
+                return false;
+            };
+            let mut pin = slot.lock().unwrap_or_else(|e| e.into_inner());
+            if pin
+                .as_ref()
+                .is_some_and(|p| p.pinned_at.elapsed() >= MAX_PIN_AGE)
+            {
+                *pin = None;
+            }
+            pin.is_some()
+        });
+        slots.len()
+    }
+
+    /// Attach the tags and notes of annotated nodes to a tool response, see
+    /// [`annotations`](super::annotations). They are read from the live
+    /// store, where they are written, even while a snapshot is pinned.
+    fn attach_annotations(&self, result: &mut CallToolResult) {
+        let annotations = {
+            let store = self.store.lock().unwrap_or_else(|e| e.into_inner());
+            store.get_all_annotations().unwrap_or_default()
+        };
+        if annotations.is_empty() {
+            return;
+        }
+        for content in &mut result.content {
+            if let RawContent::Text(text) = &mut content.raw {
+                if let Some(annotated) =
+                    super::annotations::annotate_response(&text.text, &annotations)
+                {
+                    text.text = annotated;
+                }
+            }
+        }
+    }
+
+    /// Fill in `structuredContent` of a structured tool's response, or flag
+    /// it as an error, see [`structured`](super::structured).
+    fn structure_result(&self, tool: &str, result: &mut CallToolResult) {
+        let Some(text) = result.content.iter().find_map(|c| c.as_text()) else {
+            return;
+        };
+        match super::structured::structure(tool, &text.text) {
+            super::structured::Structured::Content(value) => {
+                result.structured_content = Some(value)
+            }
+            super::structured::Structured::Error => result.is_error = Some(true),
+            super::structured::Structured::Unstructured => {}
+        }
+    }
+
+    /// Cut the paged list of a tool response to the requested page, see
+    /// [`pagination`](super::pagination).
+    fn paginate_result(
+        &self,
+        tool: &str,
+        field: &str,
+        page: &super::pagination::PageRequest,
+        result: &mut CallToolResult,
+    ) {
+        let default_size = self
+            .config
+            .response
+            .page_size
+            .unwrap_or(super::pagination::DEFAULT_PAGE_SIZE);
+        for content in &mut result.content {
+            if let RawContent::Text(text) = &mut content.raw {
+                text.text =
+                    super::pagination::paginate(tool, field, &text.text, page, default_size);
+            }
+        }
+    }
+
+    /// Read a file outline or symbol document, see
+    /// [`resources`](super::resources).
+    fn read_templated_resource(
+        &self,
+        resource: &super::resources::ResourceRef,
+    ) -> Result<ReadResourceResult, McpError> {
+        use super::resources::{file_outline, symbol_document, ResourceRef};
+
+        let store = self.active_store();
+        let store = store.lock().unwrap_or_else(|e| e.into_inner());
+        let document = match resource {
+            ResourceRef::File(path) => file_outline(&store, path),
+            ResourceRef::Symbol(id) => symbol_document(&store, id),
+        }
+        .map_err(|e| McpError::internal_error(format!("Failed to read resource: {e}"), None))?;
+        let uri = resource.uri();
+        match document {
+            Some(json) => Ok(ReadResourceResult {
+                contents: vec![ResourceContents::text(
+                    serde_json::to_string_pretty(&json).unwrap_or_default(),
+                    uri,
+                )],
+            }),
+            None => Err(McpError::resource_not_found(
+                format!("Not in the index: {uri}"),
+                None,
+            )),
+        }
+    }
+
+    /// Read the path and index status of the root `name`, see
+    /// [`roots`](super::roots).
+    fn read_workspace_resource(&self, name: &str) -> Result<ReadResourceResult, McpError> {
+        let uri = super::roots::resource_uri(name);
+        let Some(root) = self.roots.iter().find(|r| r.name == name) else {
+            return Err(McpError::resource_not_found(
+                format!("No such workspace: {uri}"),
+                None,
+            ));
+        };
+        let store = root.store.lock().unwrap_or_else(|e| e.into_inner());
+        let stats = store.get_stats().map_err(|e| {
+            McpError::internal_error(format!("Failed to read index stats: {e}"), None)
+        })?;
+        let json = serde_json::json!({
+            "name": root.name,
+            "projectRoot": root.project_root.to_string_lossy(),
+            "nodes": stats.nodes,
+            "edges": stats.edges,
+            "files": stats.files,
+            "unresolvedRefs": store.get_unresolved_ref_count().unwrap_or(0),
+        });
+        Ok(ReadResourceResult {
+            contents: vec![ResourceContents::text(
+                serde_json::to_string_pretty(&json).unwrap_or_default(),
+                uri,
+            )],
+        })
+    }
+
+    /// Start a [`TASK_TOOLS`] call in the background and return its task.
+    fn start_task(&self, request: CallToolRequestParams) -> Result<CreateTaskResult, McpError> {
+        let tool = request.name.to_string();
+        if !TASK_TOOLS.contains(&tool.as_str()) {
+            return Err(McpError::invalid_params(
+                format!(
+                    "{tool} cannot run as a task. Tools that can: {}",
+                    TASK_TOOLS.join(", ")
+                ),
+                None,
+            ));
+        }
+        if !super::registry::enabled_tool_names(&self.config).contains(tool.as_str()) {
+            return Err(McpError::invalid_params(
+                format!(
+                    "Tool '{tool}' is not available in the current preset ({})",
+                    self.config.preset
+                ),
+                None,
+            ));
+        }
+        let arguments = serde_json::Value::Object(request.arguments.unwrap_or_default());
+        let task_id = self.tasks.create_task(&format!("{tool} {arguments}"));
+
+        let progress = TaskProgress::new(&self.tasks, &task_id);
+        let server = self.clone();
+        let work =
+            tokio::task::spawn_blocking(move || server.run_task_tool(&tool, arguments, &progress));
+        let tasks = self.tasks.clone();
+        let id = task_id.clone();
+        tokio::spawn(async move {
+            match work.await {
+                Ok(Ok(text)) => tasks.complete_task(
+                    &id,
+                    CallToolResult::success(vec![rmcp::model::Content::text(text)]),
+                ),
+                Ok(Err(e)) => tasks.fail_task(&id, &e),
+                Err(e) => tasks.fail_task(&id, &format!("the tool panicked: {e}")),
+            }
+        });
+        super::tasks::make_create_result(&self.tasks, &task_id)
+    }
+
+    /// Run a task's tool call to its output. A response with an `error`
+    /// fails the task.
+    fn run_task_tool(
+        &self,
+        tool: &str,
+        arguments: serde_json::Value,
+        progress: &TaskProgress,
+    ) -> Result<String, String> {
+        fn parse<T: serde::de::DeserializeOwned>(
+            arguments: serde_json::Value,
+        ) -> Result<T, String> {
+            serde_json::from_value(arguments).map_err(|e| format!("invalid arguments: {e}"))
+        }
+        let output = match tool {
+            "codegraph_scan_security" => {
+                let p: ScanSecurityParams = parse(arguments)?;
+                super::tools_security::handle_scan_security(
+                    &self.store,
+                    &self.project_root,
+                    p.directory,
+                    p.exclude_tests,
+                    p.include_baselined.unwrap_or(false),
+                    Some(progress),
+                )
+            }
+            "codegraph_backfill_embeddings" => {
+                let p: BackfillEmbeddingsParams = parse(arguments)?;
+                super::tools_analysis::handle_backfill_embeddings(
+                    &self.store,
+                    p.limit,
+                    self.config.performance.ann_index,
+                    Some(progress),
+                )
+            }
+            "codegraph_recompute_pagerank" => {
+                let p: RecomputePagerankParams = parse(arguments)?;
+                super::tools_analysis::handle_recompute_pagerank(&self.store, p.top, Some(progress))
+            }
+            other => return Err(format!("{other} cannot run as a task")),
+        };
+        let error = serde_json::from_str::<serde_json::Value>(&output)
+            .ok()
+            .and_then(|v| v["error"].as_str().map(str::to_string));
+        match error {
+            Some(error) => Err(error),
+            None => Ok(output),
+        }
+    }
+
+    /// The file a subscribable resource changes with, `None` for the
+    /// whole-index resources. Symbols must exist; files need not be
+    /// indexed yet.
+    fn subscription_file(&self, uri: &str) -> Result<Option<String>, McpError> {
+        use super::resources::ResourceRef;
+
+        match ResourceRef::parse(uri) {
+            Some(ResourceRef::File(path)) => Ok(Some(path)),
+            Some(ResourceRef::Symbol(id)) => {
+                let store = self.store.lock().unwrap_or_else(|e| e.into_inner());
+                match store.get_node(&id) {
+                    Ok(Some(node)) => Ok(Some(node.file_path)),
+                    _ => Err(McpError::resource_not_found(
+                        format!("Not in the index: {uri}"),
+                        None,
+                    )),
+                }
+            }
+            None if uri == "codegraph://status" || uri == "codegraph://overview" => Ok(None),
+            None => Err(McpError::resource_not_found(
+                format!("Unknown resource: {uri}"),
+                None,
+            )),
+        }
+    }
+
+    /// Poll the live store while the client is connected and send
+    /// `notifications/resources/updated` for each subscribed resource a
+    /// re-index changed.
+    fn spawn_index_poller(&self, peer: rmcp::service::Peer<RoleServer>) {
+        let store = Arc::clone(&self.store);
+        let subscriptions = Arc::clone(&self.subscriptions);
+        let watch = {
+            let store = store.lock().unwrap_or_else(|e| e.into_inner());
+            IndexWatch::new(&store)
+        };
+        let mut watch = match watch {
+            Ok(w) => w,
+            Err(e) => {
+                tracing::warn!("cannot watch the index for resource updates: {e}");
+                return;
+            }
+        };
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(POLL_INTERVAL);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            'poll: loop {
+                interval.tick().await;
+                if peer.is_transport_closed() {
+                    break;
+                }
+                let changed = {
+                    let store = store.lock().unwrap_or_else(|e| e.into_inner());
+                    watch.poll(&store)
+                };
+                let changed = match changed {
+                    Ok(c) => c,
+                    Err(e) => {
+                        tracing::warn!("index poll failed: {e}");
+                        continue;
+                    }
+                };
+                for uri in subscriptions.affected(&changed) {
+                    let param = rmcp::model::ResourceUpdatedNotificationParam { uri };
+                    if peer.notify_resource_updated(param).await.is_err() {
+                        break 'poll;
+                    }
+                }
+            }
+            subscriptions.stop_polling();
+        });
+    }
+
+    /// The context for a module summary and the cached summary written for
+    /// it, if any and not refreshed.
+    fn module_summary_context(
+        &self,
+        p: &SummarizeModuleParams,
+    ) -> Result<
+        (
+            crate::context::module_summary::ModuleContext,
+            Option<crate::db::summaries::StoredSummary>,
+        ),
+        String,
+    > {
+        let context = {
+            let store = self.active_store();
+            let store = store.lock().unwrap_or_else(|e| e.into_inner());
+            crate::context::module_summary::module_context(
+                &store,
+                &p.path,
+                p.max_symbols
+                    .unwrap_or(crate::context::module_summary::DEFAULT_MAX_SYMBOLS),
+            )
+        };
+        let context = match context {
+            Ok(Some(c)) => c,
+            Ok(None) => return Err(format!("No indexed files under \"{}\".", p.path)),
+            Err(e) => return Err(e.to_string()),
+        };
+        if p.refresh.unwrap_or(false) {
+            return Ok((context, None));
+        }
+        // Summaries are cached in the live store, also while pinned.
+        let store = self.store.lock().unwrap_or_else(|e| e.into_inner());
+        let cached = crate::db::summaries::find_summary(
+            &store.conn,
+            &context.directory,
+            &context.content_hash,
+        )
+        .map_err(|e| e.to_string())?;
+        Ok((context, cached))
+    }
+
+    async fn summarize_module(
+        &self,
+        peer: &rmcp::service::Peer<RoleServer>,
+        p: &SummarizeModuleParams,
+    ) -> String {
+        use crate::context::module_summary::{MAX_SUMMARY_TOKENS, SYSTEM_PROMPT};
+        use rmcp::model::{Content, CreateMessageRequestParams, Role, SamplingMessage};
+
+        let (context, cached) = match self.module_summary_context(p) {
+            Ok(found) => found,
+            Err(e) => return json_text(&serde_json::json!({"error": e})),
+        };
+        let directory = if context.directory.is_empty() {
+            ".".to_string()
+        } else {
+            context.directory.clone()
+        };
+        if let Some(stored) = cached {
+            return json_text(&serde_json::json!({
+                "directory": directory,
+                "summary": stored.summary,
+                "model": stored.model,
+                "cached": true,
+                "createdAt": stored.created_at,
+                "files": context.files.len(),
+                "symbols": context.symbol_count,
+            }));
+        }
+
+        let sampling = peer
+            .peer_info()
+            .is_some_and(|info| info.capabilities.sampling.is_some());
+        if !sampling {
+            return json_text(&serde_json::json!({
+                "directory": directory,
+                "sampling": false,
+                "hint": "The client does not support MCP sampling. Summarize this context yourself.",
+                "instructions": SYSTEM_PROMPT,
+                "context": context.prompt,
+            }));
+        }
+        let request = CreateMessageRequestParams {
+            meta: None,
+            task: None,
+            messages: vec![SamplingMessage {
+                role: Role::User,
+                content: Content::text(context.prompt.clone()),
+            }],
+            model_preferences: None,
+            system_prompt: Some(SYSTEM_PROMPT.to_string()),
+            include_context: None,
+            temperature: Some(0.2),
+            max_tokens: MAX_SUMMARY_TOKENS,
+            stop_sequences: None,
+            metadata: None,
+        };
+        let result = match peer.create_message(request).await {
+            Ok(r) => r,
+            Err(e) => {
+                return json_text(&serde_json::json!({
+                    "error": format!("Sampling failed: {e}"),
+                    "context": context.prompt,
+                }))
+            }
+        };
+        let Some(summary) = result
+            .message
+            .content
+            .as_text()
+            .map(|t| t.text.trim().to_string())
+            .filter(|t| !t.is_empty())
+        else {
+            return json_text(&serde_json::json!({
+                "error": "The client's model returned no text.",
+                "context": context.prompt,
+            }));
+        };
+        {
+            let store = self.store.lock().unwrap_or_else(|e| e.into_inner());
+            if let Err(e) = crate::db::summaries::save_summary(
+                &store.conn,
+                &context.directory,
+                &context.content_hash,
+                &summary,
+                Some(&result.model),
+            ) {
+                tracing::warn!("cannot cache the summary of {directory}: {e}");
+            }
+        }
+        json_text(&serde_json::json!({
+            "directory": directory,
+            "summary": summary,
+            "model": result.model,
+            "cached": false,
+            "files": context.files.len(),
+            "symbols": context.symbol_count,
+        }))
+    }
+
+    fn pin_snapshot(&self, release: bool) -> String {
+        let live = self.release_expired_pins();
+        let mut pinned = self.pinned.lock().unwrap_or_else(|e| e.into_inner());
+        if release {
+            let was_pinned = pinned.take().is_some();
+            return json_text(&serde_json::json!({"pinned": false, "released": was_pinned}));
+        }
+        if pinned.is_none() && live >= MAX_LIVE_PINS {
+            return json_text(&serde_json::json!({
+                "error": format!(
+                    "{live} snapshots are already pinned; release one or wait for it to expire"
+                ),
+            }));
+        }
+        let snapshot = {
+            let store = self.store.lock().unwrap_or_else(|e| e.into_inner());
+            match store.pin_snapshot() {
+                Ok(s) => s,
+                Err(e) => return json_text(&serde_json::json!({"error": e.to_string()})),
+            }
+        };
+        let stats = snapshot.get_stats().ok();
+        let replaced = pinned
+            .replace(PinnedSnapshot {
+                store: Arc::new(Mutex::new(snapshot)),
+                pinned_at: Instant::now(),
+            })
+            .is_some();
+        drop(pinned);
+        let slot = Arc::downgrade(&self.pinned);
+        let mut slots = self.live_pins.lock().unwrap_or_else(|e| e.into_inner());
+        if !slots.iter().any(|s| s.ptr_eq(&slot)) {
+            slots.push(slot);
+        }
+        json_text(&serde_json::json!({
+            "pinned": true,
+            "replacedPrevious": replaced,
+            "expiresInSecs": MAX_PIN_AGE.as_secs(),
+            "nodes": stats.map(|s| s.nodes),
+            "edges": stats.map(|s| s.edges),
+        }))
+    }
+}
+
+/// Tools that only read the graph, run on a pooled read-only connection
+/// instead of waiting for the shared store's lock (held by the file watcher
+/// while it re-indexes, or by a slow scan).
+const POOLED_TOOLS: &[&str] = &[
+    "codegraph_query",
+    "codegraph_search",
+    "codegraph_similar",
+    "codegraph_dependencies",
+    "codegraph_callers",
+    "codegraph_callees",
+    "codegraph_impact",
+    "codegraph_node",
+    "codegraph_structure",
+    "codegraph_context",
+    "codegraph_deep_query",
+];
+
+/// Pool of read-only connections over `store`'s database, if it has one on
+/// disk.
+fn read_pool(store: &GraphStore) -> Option<Arc<ReadPool>> {
+    store
+        .conn
+        .path()
+        .filter(|p| !p.is_empty())
+        .map(|p| Arc::new(ReadPool::new(p, DEFAULT_MAX_IDLE)))
+}
+
+/// A connection taken from a [`ReadPool`] for one tool call, handed back
+/// when the last clone of the call's server is dropped.
+struct ReaderLease {
+    store: Option<Arc<Mutex<GraphStore>>>,
+    pool: Arc<ReadPool>,
+}
+
+impl ReaderLease {
+    fn store(&self) -> Arc<Mutex<GraphStore>> {
+        Arc::clone(self.store.as_ref().expect("leased store taken before drop"))
+    }
+}
+
+impl Drop for ReaderLease {
+    fn drop(&mut self) {
+        // Still shared only if a handler kept its store handle; the
+        // connection is then closed instead of reused.
+        if let Some(Ok(store)) = self.store.take().map(Arc::try_unwrap) {
+            self.pool
+                .put_back(store.into_inner().unwrap_or_else(|e| e.into_inner()));
+        }
+    }
+}
+
+/// Longest a snapshot stays pinned. Older pins are released on the next
+/// tool call of any session, so an abandoned session cannot hold back WAL
+/// checkpoints for long.
+const MAX_PIN_AGE: Duration = Duration::from_secs(10 * 60);
+
+/// Most snapshots pinned at once across sessions; each session holds at
+/// most one, re-pinning replaces it.
+const MAX_LIVE_PINS: usize = 8;
+
+/// A session's pinned snapshot and when it was taken.
+struct PinnedSnapshot {
+    store: Arc<Mutex<GraphStore>>,
+    pinned_at: Instant,
 }
 
+type PinCell = Mutex<Option<PinnedSnapshot>>;
+type PinSlot = Arc<PinCell>;
+
 /// Resolve a symbol reference to a CodeNode from a store.
 /// Accepts either a full node ID or a symbol name (returns the first match).
 pub(crate) fn resolve_symbol(store: &Arc<Mutex<GraphStore>>, symbol_ref: &str) -> Option<CodeNode> {
@@ -112,6 +867,29 @@ pub(crate) fn resolve_symbol(store: &Arc<Mutex<GraphStore>>, symbol_ref: &str) -
     None
 }
 
+/// Error payload for a symbol that [`resolve_symbol`] could not find. When
+/// the symbol was removed by an earlier re-index, says where and when.
+pub(crate) fn symbol_not_found(store: &Arc<Mutex<GraphStore>>, symbol_ref: &str) -> String {
+    let store = store.lock().unwrap_or_else(|e| e.into_inner());
+    let mut payload = serde_json::json!({
+        "error": format!("Symbol \"{}\" not found in the graph.", symbol_ref),
+    });
+    if let Some(stone) = store
+        .get_tombstones_by_name(symbol_ref)
+        .ok()
+        .and_then(|s| s.into_iter().next())
+    {
+        payload["removed"] = serde_json::json!({
+            "id": stone.id,
+            "kind": stone.kind,
+            "filePath": stone.file_path,
+            "startLine": stone.start_line,
+            "deletedAt": stone.deleted_at,
+        });
+    }
+    json_text(&payload)
+}
+
 // ---------------------------------------------------------------------------
 // Helper: serialize to JSON text
 // ---------------------------------------------------------------------------
@@ -120,6 +898,41 @@ pub(crate) fn json_text<T: Serialize>(data: &T) -> String {
     serde_json::to_string_pretty(data).unwrap_or_else(|e| format!("{{\"error\":\"{}\"}}", e))
 }
 
+/// The message of a failed tool call's response: its `error` field, or
+/// the whole text when it is not JSON.
+fn tool_error_message(text: &str) -> String {
+    serde_json::from_str::<serde_json::Value>(text)
+        .ok()
+        .and_then(|v| v.get("error").and_then(|e| e.as_str()).map(str::to_string))
+        .unwrap_or_else(|| text.to_string())
+}
+
+// ---------------------------------------------------------------------------
+// Helper: finding history
+// ---------------------------------------------------------------------------
+
+/// Record a scan in the finding history so findings keep stable IDs across
+/// scans. Failures are logged and yield `None`; the scan result itself is
+/// still returned to the caller.
+pub(crate) fn track_findings(
+    store: &GraphStore,
+    category: FindingCategory,
+    scope: Option<&str>,
+    findings: &[NewFinding],
+) -> Option<ScanRecord> {
+    // Loading the model is slow and may download it, so every scan shares one.
+    static EMBEDDER: OnceLock<FindingEmbedder> = OnceLock::new();
+    let embedder = EMBEDDER.get_or_init(FindingEmbedder::new);
+    let now = chrono::Utc::now().timestamp();
+    match record_scan(&store.conn, category, scope, findings, embedder, now) {
+        Ok(record) => Some(record),
+        Err(e) => {
+            tracing::warn!("Failed to record {} findings: {}", category.as_str(), e);
+            None
+        }
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Progressive disclosure: detail_level support
 // ---------------------------------------------------------------------------
@@ -257,22 +1070,128 @@ pub(crate) fn generate_graph_diagram(
 
 #[derive(Deserialize, schemars::JsonSchema)]
 pub(crate) struct QueryParams {
-    #[schemars(description = "Natural language or keyword search query")]
+    #[schemars(
+        description = "Natural language or keyword search query. Supports \"exact phrases\", AND/OR/NOT, and field scopes (name:, qname:, sig:, doc:, file:)"
+    )]
     pub query: String,
     #[schemars(description = "Maximum results to return (default 20)")]
     pub limit: Option<usize>,
+    #[schemars(description = "Results to skip, for paging (default 0)")]
+    pub offset: Option<usize>,
+    #[schemars(
+        description = "nextCursor from the previous page of the same query; takes precedence over offset"
+    )]
+    pub cursor: Option<String>,
     #[schemars(description = "Filter by language (e.g. 'typescript', 'python')")]
     pub language: Option<String>,
+    #[schemars(
+        description = "Only symbols under this path prefix or matching this glob (e.g. 'src/server' or 'src/server/**')"
+    )]
+    pub path: Option<String>,
+    #[schemars(description = "Keep only symbols with all of these tags ('key=value' or 'key')")]
+    pub tags: Option<Vec<String>>,
+    #[schemars(description = "Drop symbols in these languages (e.g. ['javascript'])")]
+    pub exclude_languages: Option<Vec<String>>,
+    #[schemars(description = "Drop symbols of these kinds (e.g. ['variable', 'property'])")]
+    pub exclude_kinds: Option<Vec<String>>,
+    #[schemars(
+        description = "Drop symbols under these path prefixes or matching these globs (e.g. ['vendor', '**/*.test.ts'])"
+    )]
+    pub exclude_paths: Option<Vec<String>>,
+    #[schemars(
+        description = "Explain the ranking: per result the keyword rank, vector similarity, matching expansion terms and priors, plus the query intent and blend weights (default false)"
+    )]
+    pub debug: Option<bool>,
+}
+
+#[derive(Deserialize, schemars::JsonSchema)]
+pub(crate) struct FederatedQueryParams {
+    #[schemars(description = "Natural language or keyword search query")]
+    pub query: String,
+    #[schemars(description = "Maximum fused results (default 20)")]
+    pub limit: Option<usize>,
+    #[schemars(description = "Filter by language")]
+    pub language: Option<String>,
+    #[schemars(
+        description = "Search only these configured indexes (by name) besides the local one; default all"
+    )]
+    pub indexes: Option<Vec<String>>,
+}
+
+#[derive(Deserialize, schemars::JsonSchema)]
+pub(crate) struct SimilarParams {
+    #[schemars(description = "Symbol name or node ID to find look-alikes of")]
+    pub symbol: String,
+    #[schemars(description = "Number of similar symbols to return (default 10, max 100)")]
+    pub limit: Option<usize>,
+}
+
+#[derive(Deserialize, schemars::JsonSchema)]
+pub(crate) struct SavedQueryParams {
+    #[schemars(description = "'run' (default), 'list', 'save' or 'delete'")]
+    pub action: Option<String>,
+    #[schemars(description = "Saved query name (required except for 'list')")]
+    pub name: Option<String>,
+    #[schemars(
+        description = "Values for the query's {param} placeholders when running it, as 'key=value' (e.g. ['route=/login'])"
+    )]
+    pub params: Option<Vec<String>>,
+    #[schemars(
+        description = "Query text to save; may contain {param} placeholders (e.g. 'handlers for {route}')"
+    )]
+    pub query: Option<String>,
+    #[schemars(description = "What the saved query finds")]
+    pub description: Option<String>,
+    #[schemars(description = "Filter by language")]
+    pub language: Option<String>,
+    #[schemars(description = "Filter by node kind (e.g. 'function')")]
+    pub kind: Option<String>,
+    #[schemars(description = "Path prefix or glob; may contain {param} placeholders")]
+    pub path: Option<String>,
+    #[schemars(description = "Keep only symbols with all of these tags ('key=value' or 'key')")]
+    pub tags: Option<Vec<String>>,
+    #[schemars(description = "Drop symbols in these languages")]
+    pub exclude_languages: Option<Vec<String>>,
+    #[schemars(description = "Drop symbols of these kinds")]
+    pub exclude_kinds: Option<Vec<String>>,
+    #[schemars(description = "Drop symbols under these path prefixes or globs")]
+    pub exclude_paths: Option<Vec<String>>,
+    #[schemars(description = "Maximum results (default 20)")]
+    pub limit: Option<usize>,
+    #[schemars(description = "Result detail: 'summary', 'standard' (default) or 'full'")]
+    pub detail_level: Option<String>,
 }
 
 #[derive(Deserialize, schemars::JsonSchema)]
 pub(crate) struct SearchParams {
-    #[schemars(description = "Keyword query — symbol name, function name, or exact term")]
+    #[schemars(
+        description = "Keyword query — symbol name, function name, or exact term. Supports \"exact phrases\", AND/OR/NOT, and field scopes (e.g. 'name:parse file:src/indexer')"
+    )]
     pub query: String,
     #[schemars(description = "Maximum results to return (default 10)")]
     pub limit: Option<usize>,
+    #[schemars(description = "Results to skip, for paging (default 0)")]
+    pub offset: Option<usize>,
+    #[schemars(
+        description = "nextCursor from the previous page of the same query; takes precedence over offset"
+    )]
+    pub cursor: Option<String>,
     #[schemars(description = "Filter by node kind (e.g. 'function', 'class', 'method')")]
     pub kind: Option<String>,
+    #[schemars(
+        description = "Only symbols under this path prefix or matching this glob (e.g. 'src/server' or 'src/server/**')"
+    )]
+    pub path: Option<String>,
+    #[schemars(description = "Keep only symbols with all of these tags ('key=value' or 'key')")]
+    pub tags: Option<Vec<String>>,
+    #[schemars(description = "Drop symbols in these languages (e.g. ['javascript'])")]
+    pub exclude_languages: Option<Vec<String>>,
+    #[schemars(description = "Drop symbols of these kinds (e.g. ['variable', 'property'])")]
+    pub exclude_kinds: Option<Vec<String>>,
+    #[schemars(
+        description = "Drop symbols under these path prefixes or matching these globs (e.g. ['vendor', '**/*.test.ts'])"
+    )]
+    pub exclude_paths: Option<Vec<String>>,
 }
 
 #[derive(Deserialize, schemars::JsonSchema)]
@@ -319,6 +1238,18 @@ pub(crate) struct SymbolParams {
     pub symbol: String,
 }
 
+#[derive(Deserialize, schemars::JsonSchema)]
+pub(crate) struct SymbolHistoryParams {
+    #[schemars(description = "Symbol name, or Class.member for a member")]
+    pub symbol: String,
+    #[schemars(description = "Maximum number of commits (default 20)")]
+    pub limit: Option<usize>,
+    #[schemars(
+        description = "Include the definition body before and after each commit (default true)"
+    )]
+    pub include_bodies: Option<bool>,
+}
+
 #[derive(Deserialize, schemars::JsonSchema)]
 pub(crate) struct ContextParams {
     #[schemars(description = "Natural language question or topic to gather context for")]
@@ -359,8 +1290,36 @@ pub(crate) struct DeadCodeParams {
         description = "Filter by symbol kinds (comma-separated, e.g. 'function,class'). If omitted, all kinds are checked."
     )]
     pub kinds: Option<String>,
-    #[schemars(description = "Include exported symbols in results (default false)")]
+    #[schemars(
+        description = "Include exported symbols in results by not treating them as roots (default false)"
+    )]
     pub include_exported: Option<bool>,
+    #[schemars(
+        description = "Add history to the first 20 results: status (never_used, recently_orphaned, orphaned), the last caller the index dropped and the commit that removed the last references (default false)"
+    )]
+    pub history: Option<bool>,
+}
+
+#[derive(Deserialize, schemars::JsonSchema)]
+pub(crate) struct CompleteParams {
+    #[schemars(description = "File being edited, relative to the project root")]
+    pub file_path: String,
+    #[schemars(description = "1-based cursor line, used to find the enclosing scope")]
+    pub line: u32,
+    #[schemars(description = "Identifier prefix typed so far (case-insensitive; may be empty)")]
+    pub prefix: String,
+    #[schemars(description = "Maximum completions to return (default 20)")]
+    pub limit: Option<usize>,
+}
+
+#[derive(Deserialize, schemars::JsonSchema)]
+pub(crate) struct ExamplesParams {
+    #[schemars(description = "Symbol name or node ID whose usage to show")]
+    pub symbol: String,
+    #[schemars(description = "Maximum examples to return (default 5)")]
+    pub limit: Option<usize>,
+    #[schemars(description = "Lines of source around each call (default 2)")]
+    pub context_lines: Option<usize>,
 }
 
 #[derive(Deserialize, schemars::JsonSchema)]
@@ -375,6 +1334,14 @@ pub(crate) struct FilePathParams {
     pub file_path: String,
 }
 
+#[derive(Deserialize, schemars::JsonSchema)]
+pub(crate) struct SearchCommitsParams {
+    #[schemars(description = "What to look for in the history, in natural language or keywords")]
+    pub query: String,
+    #[schemars(description = "Maximum commits to return (default 10, max 50)")]
+    pub limit: Option<usize>,
+}
+
 #[derive(Deserialize, schemars::JsonSchema)]
 pub(crate) struct FileHistoryParams {
     #[schemars(description = "File path to get history for")]
@@ -402,11 +1369,35 @@ pub(crate) struct OptionalFilePathParams {
 }
 
 #[derive(Deserialize, schemars::JsonSchema)]
-pub(crate) struct ScanSecurityParams {
-    #[schemars(description = "Directory to scan (defaults to project root)")]
+pub(crate) struct ReviewersParams {
+    #[schemars(
+        description = "Changed files (repo-relative); defaults to staged and unstaged changes"
+    )]
+    pub files: Option<Vec<String>>,
+    #[schemars(description = "Maximum reviewers to suggest (default 5)")]
+    pub limit: Option<usize>,
+}
+
+#[derive(Deserialize, schemars::JsonSchema)]
+pub(crate) struct DiffImpactParams {
+    #[schemars(description = "Revision to diff the working tree against (default HEAD)")]
+    pub base: Option<String>,
+    #[schemars(description = "Caller levels to follow from each changed symbol (default 3)")]
+    pub max_depth: Option<u32>,
+    #[schemars(description = "Max affected callers to return (default 100)")]
+    pub limit: Option<usize>,
+}
+
+#[derive(Deserialize, schemars::JsonSchema)]
+pub(crate) struct ScanSecurityParams {
+    #[schemars(description = "Directory to scan (defaults to project root)")]
     pub directory: Option<String>,
     #[schemars(description = "Exclude test files from scan (default true)")]
     pub exclude_tests: Option<bool>,
+    #[schemars(
+        description = "Also report findings accepted in .codegraph/security-baseline.json (default false)"
+    )]
+    pub include_baselined: Option<bool>,
 }
 
 #[derive(Deserialize, schemars::JsonSchema)]
@@ -431,6 +1422,22 @@ pub(crate) struct SourceLangParams {
     pub language: String,
 }
 
+#[derive(Deserialize, schemars::JsonSchema)]
+pub(crate) struct FindInjectionsParams {
+    #[schemars(
+        description = "Source code to analyze (omit to analyze the indexed project across function calls)"
+    )]
+    pub source: Option<String>,
+    #[schemars(description = "Programming language of `source` (e.g. 'python', 'javascript')")]
+    pub language: Option<String>,
+    #[schemars(description = "Index mode: only flows with a source or sink under this path")]
+    pub path: Option<String>,
+    #[schemars(description = "Index mode: calls to follow from a source (default 4)")]
+    pub max_hops: Option<usize>,
+    #[schemars(description = "Index mode: maximum number of flows to return (default 50)")]
+    pub limit: Option<usize>,
+}
+
 #[derive(Deserialize, schemars::JsonSchema)]
 pub(crate) struct TraceTaintParams {
     #[schemars(description = "Source code to analyze")]
@@ -453,6 +1460,330 @@ pub(crate) struct OptionalScopeParams {
     pub scope: Option<String>,
 }
 
+#[derive(Deserialize, schemars::JsonSchema)]
+pub(crate) struct ConfigUsageParams {
+    #[schemars(
+        description = "Config key to look up (dotted path like 'database.host' or env var like 'DB_HOST'). If omitted, lists all keys with read counts."
+    )]
+    pub key: Option<String>,
+    #[schemars(description = "Restrict to keys defined in this config file")]
+    pub file_path: Option<String>,
+}
+
+#[derive(Deserialize, schemars::JsonSchema)]
+pub(crate) struct DiffIndexParams {
+    #[schemars(
+        description = "Snapshot label to diff from. If omitted (and save is not set), lists available snapshots."
+    )]
+    pub snapshot: Option<String>,
+    #[schemars(description = "Diff against this snapshot instead of the current graph")]
+    pub against: Option<String>,
+    #[schemars(description = "Save the current graph as a snapshot with this label")]
+    pub save: Option<String>,
+}
+
+#[derive(Deserialize, schemars::JsonSchema)]
+pub(crate) struct PinSnapshotParams {
+    #[schemars(description = "Release the pinned snapshot and read the live graph again")]
+    pub release: Option<bool>,
+}
+
+#[derive(Deserialize, schemars::JsonSchema)]
+pub(crate) struct FieldUsagesParams {
+    #[schemars(
+        description = "Field to look up: qualified name like 'Order.status', bare field name, or node ID"
+    )]
+    pub field: String,
+    #[schemars(description = "Restrict to 'read' or 'write' accesses (default: both)")]
+    pub access: Option<String>,
+}
+
+#[derive(Deserialize, schemars::JsonSchema)]
+pub(crate) struct WireContractParams {
+    #[schemars(description = "Type name (struct/class/model) or node ID")]
+    pub name: String,
+}
+
+#[derive(Deserialize, schemars::JsonSchema)]
+pub(crate) struct ImportSideEffectsParams {
+    #[schemars(description = "Restrict to one module (file path)")]
+    pub file_path: Option<String>,
+    #[schemars(
+        description = "Only this kind of side effect: 'call', 'initializer', 'control_flow', or 'static_block'"
+    )]
+    pub kind: Option<String>,
+    #[schemars(description = "Include test files (default: false)")]
+    pub include_tests: Option<bool>,
+}
+
+#[derive(Deserialize, schemars::JsonSchema)]
+pub(crate) struct GlobalStateParams {
+    #[schemars(description = "Restrict to state declared under this file or directory path")]
+    pub file_path: Option<String>,
+    #[schemars(
+        description = "Only this kind of state: 'module_variable', 'static_field', or 'singleton'"
+    )]
+    pub kind: Option<String>,
+    #[schemars(description = "Include state declared in test files (default: false)")]
+    pub include_tests: Option<bool>,
+    #[schemars(description = "Max entries to return (default 50)")]
+    pub limit: Option<usize>,
+}
+
+#[derive(Deserialize, schemars::JsonSchema)]
+pub(crate) struct FindLiteralParams {
+    #[schemars(description = "String value or constant name to look up")]
+    pub query: String,
+    #[schemars(
+        description = "Match the whole value or name exactly (default: false, case-insensitive substring)"
+    )]
+    pub exact: Option<bool>,
+    #[schemars(description = "Only 'string' literals or named 'constant's")]
+    pub kind: Option<String>,
+    #[schemars(description = "Restrict to literals under this file or directory path")]
+    pub file_path: Option<String>,
+    #[schemars(description = "Include literals in test files (default: false)")]
+    pub include_tests: Option<bool>,
+    #[schemars(description = "Max constants and distinct strings to return (default 50)")]
+    pub limit: Option<usize>,
+}
+
+#[derive(Deserialize, schemars::JsonSchema)]
+pub(crate) struct GrepParams {
+    #[schemars(description = "Regular expression (Rust regex syntax), matched line by line")]
+    pub pattern: String,
+    #[schemars(description = "Restrict to symbols under this file or directory path")]
+    pub path: Option<String>,
+    #[schemars(description = "Only symbols of this kind (function, method, class, struct, ...)")]
+    pub kind: Option<String>,
+    #[schemars(description = "Match case-sensitively (default: false)")]
+    pub case_sensitive: Option<bool>,
+    #[schemars(description = "Include symbols in test files (default: false)")]
+    pub include_tests: Option<bool>,
+    #[schemars(description = "Max matching lines to return (default 100, max 1000)")]
+    pub limit: Option<usize>,
+}
+
+#[derive(Deserialize, schemars::JsonSchema)]
+pub(crate) struct UsageParams {
+    #[schemars(description = "Only calls from the last N days (default: all recorded calls)")]
+    pub days: Option<u32>,
+    #[schemars(description = "Only calls of the current MCP session (default: false)")]
+    pub current_session: Option<bool>,
+}
+
+#[derive(Deserialize, schemars::JsonSchema)]
+pub(crate) struct TagParams {
+    #[schemars(description = "Symbol name or node ID to tag")]
+    pub symbol: String,
+    #[schemars(description = "Tags to set, as 'key=value' (e.g. 'layer=api', 'team=payments')")]
+    pub set: Option<Vec<String>>,
+    #[schemars(description = "Tag keys to remove")]
+    pub remove: Option<Vec<String>>,
+}
+
+#[derive(Deserialize, schemars::JsonSchema)]
+pub(crate) struct AnnotateParams {
+    #[schemars(description = "Symbol name or node ID to annotate")]
+    pub symbol: String,
+    #[schemars(description = "Note to add (e.g. 'do not touch: the billing export parses this')")]
+    pub note: Option<String>,
+    #[schemars(description = "Who wrote the note (a user name, or 'agent')")]
+    pub author: Option<String>,
+    #[schemars(description = "IDs of notes to delete")]
+    pub remove: Option<Vec<i64>>,
+}
+
+#[derive(Deserialize, schemars::JsonSchema)]
+pub(crate) struct ApiSurfaceParams {
+    #[schemars(description = "Directory or package path prefix (default: whole project)")]
+    pub path: Option<String>,
+    #[schemars(description = "Usage examples from tests per symbol (default 0)")]
+    pub max_examples: Option<usize>,
+}
+
+#[derive(Deserialize, schemars::JsonSchema)]
+pub(crate) struct SummarizeModuleParams {
+    #[schemars(
+        description = "Directory to summarize, relative to the project root ('.' for the whole project)"
+    )]
+    pub path: String,
+    #[schemars(description = "Most important symbols shown to the model (default 25)")]
+    pub max_symbols: Option<usize>,
+    #[schemars(description = "Write a new summary even if a cached one matches the current code")]
+    pub refresh: Option<bool>,
+}
+
+#[derive(Deserialize, schemars::JsonSchema)]
+pub(crate) struct BackfillEmbeddingsParams {
+    #[schemars(
+        description = "Most nodes to embed in this call (default: every node without an embedding)"
+    )]
+    pub limit: Option<usize>,
+}
+
+#[derive(Deserialize, schemars::JsonSchema)]
+pub(crate) struct RecomputePagerankParams {
+    #[schemars(description = "Highest-ranked symbols to return (default 10)")]
+    pub top: Option<usize>,
+}
+
+#[derive(Deserialize, schemars::JsonSchema)]
+pub(crate) struct ArchitectureCheckParams {
+    #[schemars(description = "Only violations from this layer")]
+    pub layer: Option<String>,
+    #[schemars(description = "Maximum number of violations to return (default 100)")]
+    pub limit: Option<usize>,
+}
+
+#[derive(Deserialize, schemars::JsonSchema)]
+pub(crate) struct ModuleCouplingParams {
+    #[schemars(
+        description = "Group files by their first N directory levels (default: each directory is a module)"
+    )]
+    pub depth: Option<usize>,
+    #[schemars(description = "Only modules under this directory")]
+    pub path: Option<String>,
+    #[schemars(
+        description = "Sort by: distance (default), instability, abstractness, afferent, efferent, module"
+    )]
+    pub sort_by: Option<String>,
+    #[schemars(description = "Only modules exceeding a threshold (default: false)")]
+    pub flagged_only: Option<bool>,
+    #[schemars(description = "Maximum number of modules to return (default 100)")]
+    pub limit: Option<usize>,
+}
+
+#[derive(Deserialize, schemars::JsonSchema)]
+pub(crate) struct RenameImpactParams {
+    #[schemars(description = "Symbol name or node ID to rename")]
+    pub symbol: String,
+    #[schemars(description = "Proposed new name")]
+    pub new_name: String,
+}
+
+#[derive(Deserialize, schemars::JsonSchema)]
+pub(crate) struct TaggedParams {
+    #[schemars(
+        description = "Tags every result must carry ('key=value' or 'key'); omit to list the tags in use"
+    )]
+    pub tags: Option<Vec<String>>,
+    #[schemars(description = "Filter by node kind (e.g. 'function', 'class')")]
+    pub kind: Option<String>,
+    #[schemars(description = "Max symbols to return (default 50)")]
+    pub limit: Option<usize>,
+}
+
+#[derive(Deserialize, schemars::JsonSchema)]
+pub(crate) struct DuplicateDefinitionsParams {
+    #[schemars(description = "Only groups with a definition under this file or directory path")]
+    pub file_path: Option<String>,
+    #[schemars(description = "Only 'identical', 'near_duplicate' or 'divergent' groups")]
+    pub class: Option<String>,
+    #[schemars(description = "Minimum similarity of the closest pair, 0.0-1.0 (default 0.0)")]
+    pub min_similarity: Option<f64>,
+    #[schemars(description = "Include definitions in test files (default: false)")]
+    pub include_tests: Option<bool>,
+    #[schemars(description = "Max groups to return (default 50)")]
+    pub limit: Option<usize>,
+}
+
+#[derive(Deserialize, schemars::JsonSchema)]
+pub(crate) struct FindClonesParams {
+    #[schemars(description = "Minimum cosine similarity of a clone pair, 0.0-1.0 (default 0.95)")]
+    pub threshold: Option<f32>,
+    #[schemars(
+        description = "Minimum token-count ratio of the shorter to the longer body (default 0.7)"
+    )]
+    pub min_length_ratio: Option<f64>,
+    #[schemars(description = "Ignore functions shorter than this many lines (default 4)")]
+    pub min_lines: Option<u32>,
+    #[schemars(description = "Only clusters with a member under this path prefix")]
+    pub path: Option<String>,
+    #[schemars(description = "Include functions in test files (default: false)")]
+    pub include_tests: Option<bool>,
+    #[schemars(description = "Max clusters to return (default 50)")]
+    pub limit: Option<usize>,
+}
+
+#[derive(Deserialize, schemars::JsonSchema)]
+pub(crate) struct AuditDependenciesParams {
+    #[schemars(
+        description = "Use only cached OSV answers, whatever their age, without network access (default false)"
+    )]
+    pub offline: Option<bool>,
+    #[schemars(
+        description = "Hours a cached OSV answer is reused before asking again (default 24)"
+    )]
+    pub max_cache_age_hours: Option<u64>,
+}
+
+#[derive(Deserialize, schemars::JsonSchema)]
+pub(crate) struct FindSecretsParams {
+    #[schemars(description = "Only scan indexed files under this path")]
+    pub path: Option<String>,
+    #[schemars(description = "Also scan test files (default false)")]
+    pub include_tests: Option<bool>,
+    #[schemars(
+        description = "Lowest severity reported: info, low, medium, high or critical (default info)"
+    )]
+    pub min_severity: Option<String>,
+    #[schemars(description = "Maximum number of findings to return (default 50)")]
+    pub limit: Option<usize>,
+}
+
+#[derive(Deserialize, schemars::JsonSchema)]
+pub(crate) struct EntrypointsParams {
+    #[schemars(
+        description = "Only entry points of this kind: main, route, command or package_export"
+    )]
+    pub kind: Option<String>,
+    #[schemars(description = "Only entry points in files under this path")]
+    pub path: Option<String>,
+    #[schemars(description = "Levels of callees to include per entry point (default 2, max 3)")]
+    pub depth: Option<usize>,
+    #[schemars(description = "Maximum number of entry points to return (default 50)")]
+    pub limit: Option<usize>,
+}
+
+#[derive(Deserialize, schemars::JsonSchema)]
+pub(crate) struct RoutesParams {
+    #[schemars(description = "Only routes for this HTTP method, e.g. GET or POST")]
+    pub method: Option<String>,
+    #[schemars(description = "Only routes whose path starts with this prefix, e.g. /api/users")]
+    pub path: Option<String>,
+    #[schemars(
+        description = "Only routes of this framework: express, fastapi, flask, axum or spring"
+    )]
+    pub framework: Option<String>,
+    #[schemars(description = "Maximum number of routes to return (default 100)")]
+    pub limit: Option<usize>,
+}
+
+#[derive(Deserialize, schemars::JsonSchema)]
+pub(crate) struct CfgParams {
+    #[schemars(description = "Function or method name, or node ID")]
+    pub symbol: String,
+    #[schemars(description = "'json' (default) or 'mermaid'")]
+    pub format: Option<String>,
+}
+
+#[derive(Deserialize, schemars::JsonSchema)]
+pub(crate) struct CallgraphExportParams {
+    #[schemars(description = "Root symbol name or node ID")]
+    pub symbol: String,
+    #[schemars(description = "'outgoing' (callees, default), 'incoming' (callers) or 'both'")]
+    pub direction: Option<String>,
+    #[schemars(description = "Hops to follow from the root (default 2, max 10)")]
+    pub depth: Option<usize>,
+    #[schemars(
+        description = "Edge kinds to follow and keep, e.g. ['calls', 'imports'] (default ['calls'])"
+    )]
+    pub edge_kinds: Option<Vec<String>>,
+    #[schemars(description = "Stop after this many nodes (default 200)")]
+    pub max_nodes: Option<usize>,
+}
+
 #[derive(Deserialize, schemars::JsonSchema)]
 pub(crate) struct FindPathParams {
     #[schemars(description = "Source symbol name or node ID")]
@@ -469,6 +1800,40 @@ pub(crate) struct ComplexityParams {
     pub min_complexity: Option<u32>,
 }
 
+#[derive(Deserialize, schemars::JsonSchema)]
+pub(crate) struct TestGapsParams {
+    #[schemars(description = "Minimum cyclomatic complexity to include in results (default 5)")]
+    pub min_complexity: Option<u32>,
+    #[schemars(description = "Only functions in files under this path prefix")]
+    pub path: Option<String>,
+    #[schemars(
+        description = "Call/reference hops a test may take to count as covering a function (default 3, 1 = direct references only)"
+    )]
+    pub max_depth: Option<u32>,
+    #[schemars(description = "Maximum number of gaps to return (default 30)")]
+    pub limit: Option<usize>,
+}
+
+#[derive(Deserialize, schemars::JsonSchema)]
+pub(crate) struct RecursionParams {
+    #[schemars(description = "Only cycles with a member under this file or directory path")]
+    pub file_path: Option<String>,
+    #[schemars(description = "Include cycles entirely inside test files (default: false)")]
+    pub include_tests: Option<bool>,
+    #[schemars(description = "Max cycles to return (default 50)")]
+    pub limit: Option<usize>,
+}
+
+#[derive(Deserialize, schemars::JsonSchema)]
+pub(crate) struct UnusedParamsParams {
+    #[schemars(description = "Only functions under this file or directory path")]
+    pub file_path: Option<String>,
+    #[schemars(description = "Include test files (default: false)")]
+    pub include_tests: Option<bool>,
+    #[schemars(description = "Max entries per list (default 50)")]
+    pub limit: Option<usize>,
+}
+
 #[derive(Deserialize, schemars::JsonSchema)]
 pub(crate) struct DataFlowParams {
     #[schemars(description = "Path to source file (reads file and auto-detects language)")]
@@ -509,6 +1874,16 @@ pub(crate) struct DeepQueryParams {
     pub limit: Option<usize>,
 }
 
+#[derive(Deserialize, schemars::JsonSchema)]
+pub(crate) struct DeepFileQueryParams {
+    #[schemars(
+        description = "Natural language or keyword query — files are re-ranked by a cross-encoder over their top symbols and doc comments"
+    )]
+    pub query: String,
+    #[schemars(description = "Maximum files to return after re-ranking (default 10)")]
+    pub limit: Option<usize>,
+}
+
 // ---------------------------------------------------------------------------
 // Tool implementations
 // ---------------------------------------------------------------------------
@@ -518,19 +1893,109 @@ impl CodeGraphServer {
     // 1. codegraph_query — Hybrid keyword + semantic search
     #[tool(
         name = "codegraph_query",
-        description = "Hybrid semantic + keyword search with query expansion. Best for conceptual queries and natural language. For exact symbol name lookups, use codegraph_search instead (10x faster). Use instead of Grep/Glob when searching for code symbols or concepts."
+        description = "Hybrid semantic + keyword search with query expansion. Best for conceptual queries and natural language. For exact symbol name lookups, use codegraph_search instead (10x faster). Use instead of Grep/Glob when searching for code symbols or concepts. When more results exist, the response carries a nextCursor for the next page."
     )]
     async fn codegraph_query(&self, Parameters(p): Parameters<QueryParams>) -> String {
-        super::tools_core::handle_query(&self.store, &p.query, p.limit, p.language, &self.config)
+        super::tools_core::handle_query(
+            &self.active_store(),
+            &p.query,
+            p.limit,
+            p.offset,
+            p.cursor,
+            p.language,
+            p.path,
+            p.tags.unwrap_or_default(),
+            p.exclude_languages.unwrap_or_default(),
+            p.exclude_kinds.unwrap_or_default(),
+            p.exclude_paths.unwrap_or_default(),
+            p.debug.unwrap_or(false),
+            &self.config,
+        )
+    }
+
+    // 64. codegraph_saved_query — Named, parameterized searches
+    #[tool(
+        name = "codegraph_saved_query",
+        description = "Run a saved search by name, filling its {param} placeholders from params. Saved queries bundle query text, filters and detail level; teams share them under saved_queries in .codegraph.yaml. Use action 'list' to see them, 'save' to store a new one (query plus optional filters) and 'delete' to remove a local one."
+    )]
+    async fn codegraph_saved_query(&self, Parameters(p): Parameters<SavedQueryParams>) -> String {
+        let definition = p.query.map(|query| crate::db::saved_queries::SavedQuery {
+            query,
+            description: p.description,
+            language: p.language,
+            kind: p.kind,
+            path: p.path,
+            tags: p.tags.unwrap_or_default(),
+            exclude_languages: p.exclude_languages.unwrap_or_default(),
+            exclude_kinds: p.exclude_kinds.unwrap_or_default(),
+            exclude_paths: p.exclude_paths.unwrap_or_default(),
+            limit: p.limit,
+            detail_level: p.detail_level,
+        });
+        super::tools_core::handle_saved_query(
+            &self.active_store(),
+            &self.config,
+            p.action.as_deref(),
+            p.name.as_deref(),
+            p.params
+                .unwrap_or_default()
+                .iter()
+                .filter_map(|kv| kv.split_once('='))
+                .map(|(k, v)| (k.trim().to_string(), v.to_string()))
+                .collect(),
+            definition,
+        )
+    }
+
+    // 68. codegraph_federated_query — Search across several indexes
+    #[tool(
+        name = "codegraph_federated_query",
+        description = "Hybrid search across this project's index and the other CodeGraph indexes configured under federation.indexes (e.g. shared libraries), merged by weighted rank. Each result names the index it came from. Use when the answer may live in a dependency indexed separately."
+    )]
+    async fn codegraph_federated_query(
+        &self,
+        Parameters(p): Parameters<FederatedQueryParams>,
+    ) -> String {
+        super::tools_core::handle_federated_query(
+            &self.active_store(),
+            &self.config,
+            &self.project_root,
+            &p.query,
+            p.limit,
+            p.language,
+            p.indexes,
+        )
+    }
+
+    // 67. codegraph_similar — Nearest neighbours of a symbol's embedding
+    #[tool(
+        name = "codegraph_similar",
+        description = "Show me functions like this one: returns the symbols whose stored embeddings are nearest to the given symbol's (cosine similarity), excluding the symbol itself. Use to find alternative implementations, related helpers or code to keep consistent with. Requires an index built with embeddings."
+    )]
+    async fn codegraph_similar(&self, Parameters(p): Parameters<SimilarParams>) -> String {
+        super::tools_core::handle_similar(&self.active_store(), &p.symbol, p.limit)
     }
 
     // 1b. codegraph_search — Fast keyword-only search (FTS5, no embeddings)
     #[tool(
         name = "codegraph_search",
-        description = "Fast keyword search for exact symbol name lookups (<10ms). FTS5-only, no embeddings, no RRF fusion. Use this when you know the symbol name. For semantic/conceptual search, use codegraph_query instead."
+        description = "Fast keyword search for exact symbol name lookups (<10ms). FTS5-only, no embeddings, no RRF fusion. Use this when you know the symbol name; misspelled names fall back to close matches, listed under didYouMean. Page through long result lists with offset or the returned nextCursor. For semantic/conceptual search, use codegraph_query instead."
     )]
     async fn codegraph_search(&self, Parameters(p): Parameters<SearchParams>) -> String {
-        super::tools_core::handle_search(&self.store, &p.query, p.limit, p.kind, &self.config)
+        super::tools_core::handle_search(
+            &self.active_store(),
+            &p.query,
+            p.limit,
+            p.offset,
+            p.cursor,
+            p.kind,
+            p.path,
+            p.tags.unwrap_or_default(),
+            p.exclude_languages.unwrap_or_default(),
+            p.exclude_kinds.unwrap_or_default(),
+            p.exclude_paths.unwrap_or_default(),
+            &self.config,
+        )
     }
 
     // 2. codegraph_dependencies — Forward dependency traversal
@@ -539,19 +2004,24 @@ impl CodeGraphServer {
         description = "Find all dependencies of a file or module (imports, type references, etc.). Returns a dependency tree with depth levels. Use instead of Explore agents to trace imports and dependencies. For call-only relationships from a specific function, use codegraph_callees."
     )]
     async fn codegraph_dependencies(&self, Parameters(p): Parameters<SymbolDepthParams>) -> String {
-        super::tools_core::handle_dependencies(&self.store, &p.symbol, p.max_depth)
+        super::tools_core::handle_dependencies(&self.active_store(), &p.symbol, p.max_depth)
     }
 
     // 3. codegraph_callers — Reverse call graph traversal
     #[tool(
         name = "codegraph_callers",
-        description = "Find what CALLS this function/method. Returns a caller tree with depth levels. Use instead of Grep for caller analysis — 100% precise, no false positives. For all reference types (not just calls), use codegraph_find_references."
+        description = "Find what CALLS this function/method. Returns a caller tree with depth levels. Callers of the interface, trait or base-class methods it implements are included with `via` naming the declaration they call through. Use instead of Grep for caller analysis — 100% precise, no false positives. For all reference types (not just calls), use codegraph_find_references."
     )]
     async fn codegraph_callers(
         &self,
         Parameters(p): Parameters<SymbolDepthDetailParams>,
     ) -> String {
-        super::tools_core::handle_callers(&self.store, &p.symbol, p.max_depth, p.detail_level)
+        super::tools_core::handle_callers(
+            &self.active_store(),
+            &p.symbol,
+            p.max_depth,
+            p.detail_level,
+        )
     }
 
     // 4. codegraph_callees — Forward call graph traversal
@@ -563,7 +2033,12 @@ impl CodeGraphServer {
         &self,
         Parameters(p): Parameters<SymbolDepthDetailParams>,
     ) -> String {
-        super::tools_core::handle_callees(&self.store, &p.symbol, p.max_depth, p.detail_level)
+        super::tools_core::handle_callees(
+            &self.active_store(),
+            &p.symbol,
+            p.max_depth,
+            p.detail_level,
+        )
     }
 
     // 5. codegraph_impact — Blast radius analysis
@@ -572,16 +2047,21 @@ impl CodeGraphServer {
         description = "Analyze the blast radius of changing a file or symbol. Returns affected files and functions grouped by risk level. Use before refactoring to understand what might break."
     )]
     async fn codegraph_impact(&self, Parameters(p): Parameters<ImpactParams>) -> String {
-        super::tools_core::handle_impact(&self.store, p.file_path, p.symbol)
+        super::tools_core::handle_impact(&self.active_store(), p.file_path, p.symbol)
     }
 
     // 5. codegraph_structure — Project overview with PageRank
     #[tool(
         name = "codegraph_structure",
-        description = "Get a project overview: modules, key classes/functions, and dependency summary. Uses PageRank to identify the most important symbols. Use instead of Explore agents for project overview."
+        description = "Get a project overview: modules, key classes/functions, and dependency summary. Uses PageRank to identify the most important symbols. In monorepos, also rolls up stats, top symbols, and inter-package dependencies per detected project. Use instead of Explore agents for project overview."
     )]
     async fn codegraph_structure(&self, Parameters(p): Parameters<StructureParams>) -> String {
-        super::tools_core::handle_structure(&self.store, p.path, p.depth)
+        super::tools_core::handle_structure(
+            &self.active_store(),
+            &self.project_root,
+            p.path,
+            p.depth,
+        )
     }
 
     // 6. codegraph_tests — Test coverage discovery
@@ -590,7 +2070,7 @@ impl CodeGraphServer {
         description = "Find test files and functions that cover a given symbol. Returns test locations grouped by file."
     )]
     async fn codegraph_tests(&self, Parameters(p): Parameters<SymbolParams>) -> String {
-        super::tools_core::handle_tests(&self.store, &p.symbol)
+        super::tools_core::handle_tests(&self.active_store(), &p.symbol)
     }
 
     // 7. codegraph_context — 4-tier token-budgeted LLM context assembly
@@ -599,7 +2079,7 @@ impl CodeGraphServer {
         description = "Assemble optimal context for Claude from the code graph. Uses a tiered approach (core -> near -> extended -> background) to pack the most relevant code within a token budget. Use instead of reading multiple files — provides pre-ranked, token-budgeted context."
     )]
     async fn codegraph_context(&self, Parameters(p): Parameters<ContextParams>) -> String {
-        super::tools_core::handle_context(&self.store, &p.query, p.budget, p.detail_level)
+        super::tools_core::handle_context(&self.active_store(), &p.query, p.budget, p.detail_level)
     }
 
     // 8. codegraph_diagram — Mermaid diagram generation
@@ -608,7 +2088,7 @@ impl CodeGraphServer {
         description = "Generate a Mermaid diagram from the code graph. Supports dependency graphs, call graphs, and module-level diagrams."
     )]
     async fn codegraph_diagram(&self, Parameters(p): Parameters<DiagramParams>) -> String {
-        super::tools_core::handle_diagram(&self.store, p.symbol, p.diagram_type)
+        super::tools_core::handle_diagram(&self.active_store(), p.symbol, p.diagram_type)
     }
 
     // 9. codegraph_node — Direct node lookup with full details
@@ -617,16 +2097,28 @@ impl CodeGraphServer {
         description = "Look up a specific code symbol by name or ID and return its full details including source code, documentation, file location, and relationships. Use instead of Grep for exact symbol lookup."
     )]
     async fn codegraph_node(&self, Parameters(p): Parameters<NodeParams>) -> String {
-        super::tools_core::handle_node(&self.store, &p.symbol, p.include_relations, p.detail_level)
+        super::tools_core::handle_node(
+            &self.active_store(),
+            &p.symbol,
+            p.include_relations,
+            p.detail_level,
+        )
     }
 
     // 10. codegraph_dead_code — Find potentially unused symbols
     #[tool(
         name = "codegraph_dead_code",
-        description = "Find potentially unused/dead code symbols that have no incoming references"
+        description = "Find potentially unused/dead code: symbols not reachable from any root (main functions, exported APIs, tests, framework-registered routes and commands). Helpers used only by other dead code are reported too, with the dead symbols that use them in referencedBy. Roots are configured in the `dead_code` section of .codegraph.yaml"
     )]
     async fn codegraph_dead_code(&self, Parameters(p): Parameters<DeadCodeParams>) -> String {
-        super::tools_core::handle_dead_code(&self.store, p.kinds, p.include_exported)
+        super::tools_core::handle_dead_code(
+            &self.active_store(),
+            &self.project_root,
+            &self.config.dead_code.roots(),
+            p.kinds,
+            p.include_exported,
+            p.history,
+        )
     }
 
     // 10. codegraph_frameworks — Detect frameworks and libraries
@@ -635,16 +2127,46 @@ impl CodeGraphServer {
         description = "Detect frameworks and libraries used in the project"
     )]
     async fn codegraph_frameworks(&self, Parameters(p): Parameters<FrameworksParams>) -> String {
-        super::tools_core::handle_frameworks(&self.store, p.project_dir)
+        super::tools_core::handle_frameworks(&self.active_store(), p.project_dir)
     }
 
     // 11. codegraph_languages — Language breakdown statistics
     #[tool(
         name = "codegraph_languages",
-        description = "Show language breakdown statistics for the indexed codebase"
+        description = "Show language breakdown statistics for the indexed codebase: files, symbols, edges, code/comment/blank/logical line counts, comment ratio and average symbol size per language"
     )]
     async fn codegraph_languages(&self) -> String {
-        super::tools_core::handle_languages(&self.store)
+        super::tools_core::handle_languages(&self.active_store())
+    }
+
+    // 61. codegraph_complete — Graph-aware symbol completion
+    #[tool(
+        name = "codegraph_complete",
+        description = "Complete an identifier prefix at a cursor position using the code graph. Returns ranked symbols valid in that scope: members of the enclosing class/function first, then other symbols in the same file, symbols the file imports, and finally exported project symbols ordered by how often they are referenced. Each result carries its source tier, kind, location and signature. Intended for lightweight editor integrations without a language server."
+    )]
+    async fn codegraph_complete(&self, Parameters(p): Parameters<CompleteParams>) -> String {
+        super::tools_core::handle_complete(
+            &self.active_store(),
+            &p.file_path,
+            p.line,
+            &p.prefix,
+            p.limit,
+        )
+    }
+
+    // 63. codegraph_examples — Instructive call sites of a symbol
+    #[tool(
+        name = "codegraph_examples",
+        description = "Show how a function or API is used: returns its most instructive call sites with the full call expression, argument expressions and surrounding source lines. Prefers call sites in tests and calls with (literal) arguments, and spreads examples across files. Use for \"show me how this is used\" instead of grepping for the name."
+    )]
+    async fn codegraph_examples(&self, Parameters(p): Parameters<ExamplesParams>) -> String {
+        super::tools_core::handle_examples(
+            &self.active_store(),
+            &self.project_root,
+            &p.symbol,
+            p.limit,
+            p.context_lines,
+        )
     }
 
     // 46. codegraph_deep_query — Cross-encoder re-ranked search
@@ -656,8 +2178,11 @@ impl CodeGraphServer {
         let top_k = p.limit.unwrap_or(10);
         // Gather candidates via hybrid search (fetch more than needed for re-ranking)
         let candidates = {
-            let store = self.store.lock().unwrap_or_else(|e| e.into_inner());
-            let search = crate::graph::search::HybridSearch::new(&store.conn);
+            let store = self.active_store();
+            let store = store.lock().unwrap_or_else(|e| e.into_inner());
+            let search = crate::graph::search::HybridSearch::new(&store.conn)
+                .with_priors(self.config.ranking.prior_weights())
+                .with_synonyms(self.config.synonym_map());
             let opts = crate::graph::search::SearchOptions {
                 limit: Some(30),
                 ..Default::default()
@@ -683,16 +2208,58 @@ impl CodeGraphServer {
         json_text(&truncated)
     }
 
-    // =========================================================================
-    // Git Integration Tools (9)
-    // =========================================================================
-
-    // 14. codegraph_blame
+    // 60. codegraph_deep_file_query — Cross-encoder re-ranked file search
     #[tool(
-        name = "codegraph_blame",
-        description = "Show git blame for a file — line-by-line author, date, and commit hash. Use instead of running git blame via Bash."
+        name = "codegraph_deep_file_query",
+        description = "Find the files most relevant to a query, re-ranked by a cross-encoder. Gathers candidate files by BM25 symbol matches, describes each by its top symbols and their doc comments, and re-ranks them through the BAAI/bge-reranker-base cross-encoder. Use to decide which files to open for a conceptual question. Falls back to BM25 file ranking if the reranker model is unavailable."
     )]
-    async fn codegraph_blame(&self, Parameters(p): Parameters<FilePathParams>) -> String {
+    async fn codegraph_deep_file_query(
+        &self,
+        Parameters(p): Parameters<DeepFileQueryParams>,
+    ) -> String {
+        let top_k = p.limit.unwrap_or(10);
+        let store = self.active_store();
+        // Gather candidate files (more than needed for re-ranking)
+        let files = {
+            let store = store.lock().unwrap_or_else(|e| e.into_inner());
+            let search = crate::graph::search::HybridSearch::new(&store.conn);
+            match search.search_files(&p.query, 30) {
+                Ok(files) => files,
+                Err(e) => return json_text(&serde_json::json!({"error": e.to_string()})),
+            }
+        };
+
+        #[cfg(feature = "reranking")]
+        {
+            if let Some(ref reranker) = self.reranker {
+                let candidates = {
+                    let store = store.lock().unwrap_or_else(|e| e.into_inner());
+                    crate::graph::reranker::file_candidates(&store, files)
+                };
+                match crate::graph::reranker::deep_file_search(
+                    &p.query, reranker, candidates, top_k,
+                ) {
+                    Ok(reranked) => return json_text(&reranked),
+                    Err(e) => return json_text(&serde_json::json!({"error": e})),
+                }
+            }
+        }
+
+        // Fallback: no reranker available, return the BM25 file ranking
+        let truncated: Vec<_> = files.into_iter().take(top_k).collect();
+        json_text(&truncated)
+    }
+
+    // =========================================================================
+    // Git Integration Tools (12)
+    // =========================================================================
+
+    // 14. codegraph_blame
+    #[tool(
+        name = "codegraph_blame",
+        description = "Show git blame for a file — line-by-line author, date, and commit hash. Use instead of running git blame via Bash."
+    )]
+    async fn codegraph_blame(&self, Parameters(p): Parameters<FilePathParams>) -> String {
         super::tools_git::handle_blame(&self.project_root, &p.file_path)
     }
 
@@ -726,10 +2293,18 @@ impl CodeGraphServer {
     // 18. codegraph_symbol_history
     #[tool(
         name = "codegraph_symbol_history",
-        description = "Find commits that modified a specific symbol (uses git log -S)."
+        description = "Semantic change history of a symbol: the commits that added, removed or edited its definition or its uses (found with git log -S, then following the files involved), newest first. Each commit lists the definition before and after it (signature, lines, body) and whether the signature changed, giving an evolution timeline rather than just hashes."
     )]
-    async fn codegraph_symbol_history(&self, Parameters(p): Parameters<SymbolParams>) -> String {
-        super::tools_git::handle_symbol_history(&self.project_root, &p.symbol)
+    async fn codegraph_symbol_history(
+        &self,
+        Parameters(p): Parameters<SymbolHistoryParams>,
+    ) -> String {
+        super::tools_git::handle_symbol_history(
+            &self.project_root,
+            &p.symbol,
+            p.limit,
+            p.include_bodies,
+        )
     }
 
     // 19. codegraph_branch_info
@@ -771,6 +2346,52 @@ impl CodeGraphServer {
         super::tools_git::handle_contributors(&self.project_root, p.file_path.as_deref())
     }
 
+    // 73. codegraph_diff_impact
+    #[tool(
+        name = "codegraph_diff_impact",
+        description = "Impact analysis of the current working-tree changes in one call: parses the git diff against a base revision (default HEAD, staged and unstaged plus untracked files), maps changed line ranges to the enclosing symbols, and returns those symbols, their transitive callers with risk levels, and the tests to run. Use before committing instead of passing each changed symbol to codegraph_impact."
+    )]
+    async fn codegraph_diff_impact(&self, Parameters(p): Parameters<DiffImpactParams>) -> String {
+        super::tools_git::handle_diff_impact(
+            &self.active_store(),
+            &self.project_root,
+            p.base.as_deref(),
+            p.max_depth,
+            p.limit,
+        )
+    }
+
+    // 51. codegraph_reviewers
+    #[tool(
+        name = "codegraph_reviewers",
+        description = "Suggest reviewers for a change set by combining CODEOWNERS, blame ownership of the changed lines, and the last authors of impacted callers/callees. Each suggestion includes a rationale. Defaults to the working tree's changed files."
+    )]
+    async fn codegraph_reviewers(&self, Parameters(p): Parameters<ReviewersParams>) -> String {
+        super::tools_git::handle_reviewers(
+            &self.active_store(),
+            &self.project_root,
+            p.files,
+            p.limit,
+        )
+    }
+
+    // 65. codegraph_search_commits — Natural-language history search
+    #[tool(
+        name = "codegraph_search_commits",
+        description = "Search the git history in natural language (e.g. \"when did we change the retry backoff logic\"). Ranks commits by similarity of their messages to the query, fused with commits whose diffs added or removed the query's identifier-like terms (git log -S) for precision. Use instead of scanning git log output."
+    )]
+    async fn codegraph_search_commits(
+        &self,
+        Parameters(p): Parameters<SearchCommitsParams>,
+    ) -> String {
+        super::tools_git::handle_search_commits(
+            &self.active_store(),
+            &self.project_root,
+            &p.query,
+            p.limit,
+        )
+    }
+
     // =========================================================================
     // Security Tools (9)
     // =========================================================================
@@ -778,16 +2399,19 @@ impl CodeGraphServer {
     // 23. codegraph_scan_security
     #[tool(
         name = "codegraph_scan_security",
-        description = "Scan a directory for security vulnerabilities using YAML-based pattern matching rules. Use instead of grep-based pattern matching for vulnerability detection. This is the primary security scanning tool. Supports filtering by standard (OWASP, CWE, or all)."
+        description = "Scan a directory for security vulnerabilities using YAML-based pattern matching rules: the bundled rules plus custom rule packs in .codegraph/rules. Findings suppressed by a `codegraph-ignore: <rule-id>` comment or accepted in the committed .codegraph/security-baseline.json are left out, so only new findings are reported. Use instead of grep-based pattern matching for vulnerability detection. This is the primary security scanning tool. Supports filtering by standard (OWASP, CWE, or all). Can run as an MCP task on large repositories."
     )]
     async fn codegraph_scan_security(
         &self,
         Parameters(p): Parameters<ScanSecurityParams>,
     ) -> String {
         super::tools_security::handle_scan_security(
+            &self.store,
             &self.project_root,
             p.directory,
             p.exclude_tests,
+            p.include_baselined.unwrap_or(false),
+            None,
         )
     }
 
@@ -833,13 +2457,26 @@ impl CodeGraphServer {
     // 28. codegraph_find_injections
     #[tool(
         name = "codegraph_find_injections",
-        description = "Find injection vulnerabilities (SQL, XSS, command, path traversal) via taint analysis."
+        description = "Find injection vulnerabilities (SQL, XSS, command, path traversal) via taint analysis. With `source` and `language`, analyzes that code. Without them, analyzes the indexed project and follows tainted arguments across calls into helper functions in other files, reporting each flow with its call chain."
     )]
     async fn codegraph_find_injections(
         &self,
-        Parameters(p): Parameters<SourceLangParams>,
+        Parameters(p): Parameters<FindInjectionsParams>,
     ) -> String {
-        super::tools_security::handle_find_injections(&p.source, &p.language)
+        match (p.source, p.language) {
+            (Some(source), Some(language)) => {
+                super::tools_security::handle_find_injections(&source, &language)
+            }
+            (Some(_), None) => json_text(&serde_json::json!({
+                "error": "`language` is required with `source`.",
+            })),
+            (None, _) => super::tools_security::handle_graph_injections(
+                &self.active_store(),
+                p.path.as_deref(),
+                p.max_hops,
+                p.limit,
+            ),
+        }
     }
 
     // 29. codegraph_taint_sources
@@ -854,13 +2491,13 @@ impl CodeGraphServer {
     // 30. codegraph_security_summary
     #[tool(
         name = "codegraph_security_summary",
-        description = "Comprehensive security risk assessment combining rule scanning and taint analysis."
+        description = "Comprehensive security risk assessment combining rule scanning and taint analysis. Findings for the same issue across rules and taint flows are merged, scored on a CVSS-like 0-10 scale and ranked; per-directory risk scores are recorded so each run reports how they moved since the previous one."
     )]
     async fn codegraph_security_summary(
         &self,
         Parameters(p): Parameters<OptionalDirParams>,
     ) -> String {
-        super::tools_security::handle_security_summary(&self.project_root, p.directory)
+        super::tools_security::handle_security_summary(&self.store, &self.project_root, p.directory)
     }
 
     // 31. codegraph_trace_taint
@@ -872,8 +2509,42 @@ impl CodeGraphServer {
         super::tools_security::handle_trace_taint(&p.source, &p.language, p.from_line)
     }
 
+    // 83. codegraph_audit_dependencies
+    #[tool(
+        name = "codegraph_audit_dependencies",
+        description = "Audit the packages pinned in Cargo.lock, package-lock.json and poetry.lock files against the OSV vulnerability database, and report each vulnerable package with its advisories (severity, aliases, fixed versions) and the indexed files and names that import it. OSV answers are cached in .codegraph/osv-cache.json; offline mode uses only the cache."
+    )]
+    async fn codegraph_audit_dependencies(
+        &self,
+        Parameters(p): Parameters<AuditDependenciesParams>,
+    ) -> String {
+        super::tools_security::handle_audit_dependencies(
+            &self.active_store(),
+            &self.project_root,
+            p.offline.unwrap_or(false),
+            p.max_cache_age_hours,
+        )
+    }
+
+    // 84. codegraph_find_secrets
+    #[tool(
+        name = "codegraph_find_secrets",
+        description = "Scan the indexed files for hardcoded secrets: credential assignments (API keys, passwords, tokens, AWS keys, connection strings), Bearer tokens, the secret rules of the security rule set, and high-entropy string literals. Findings come in the codegraph_scan_security format with severities and the secret masked. Paths and rules can be allowlisted in the `secrets` section of .codegraph.yaml; `codegraph-ignore: <rule-id>` suppresses a line."
+    )]
+    async fn codegraph_find_secrets(&self, Parameters(p): Parameters<FindSecretsParams>) -> String {
+        super::tools_security::handle_find_secrets(
+            &self.active_store(),
+            &self.project_root,
+            &self.config.secrets.options(),
+            p.path.as_deref(),
+            p.include_tests.unwrap_or(false),
+            p.min_severity.as_deref(),
+            p.limit,
+        )
+    }
+
     // =========================================================================
-    // Existing Feature Exposure Tools (8)
+    // Existing Feature Exposure Tools (19)
     // =========================================================================
 
     // 32. codegraph_stats
@@ -882,7 +2553,7 @@ impl CodeGraphServer {
         description = "Show index statistics — node, edge, file counts, and unresolved references."
     )]
     async fn codegraph_stats(&self) -> String {
-        super::tools_analysis::handle_stats(&self.store)
+        super::tools_analysis::handle_stats(&self.active_store())
     }
 
     // 33. codegraph_circular_imports
@@ -891,7 +2562,44 @@ impl CodeGraphServer {
         description = "Detect circular import dependencies using Tarjan's SCC algorithm."
     )]
     async fn codegraph_circular_imports(&self) -> String {
-        super::tools_analysis::handle_circular_imports(&self.store)
+        super::tools_analysis::handle_circular_imports(&self.active_store())
+    }
+
+    // 75. codegraph_architecture_check
+    #[tool(
+        name = "codegraph_architecture_check",
+        description = "Check import and call edges against the layering rules in .codegraph.yaml (`architecture:` layers as directories, rules like `ui` may not depend on `db`). Returns each violating edge with both layers, the symbols involved, and the file and line of the import or call. Use before adding a dependency across layers or as an architecture conformance gate."
+    )]
+    async fn codegraph_architecture_check(
+        &self,
+        Parameters(p): Parameters<ArchitectureCheckParams>,
+    ) -> String {
+        super::tools_analysis::handle_architecture_check(
+            &self.active_store(),
+            &self.config.architecture.policy(),
+            p.layer.as_deref(),
+            p.limit,
+        )
+    }
+
+    // 81. codegraph_module_coupling
+    #[tool(
+        name = "codegraph_module_coupling",
+        description = "Coupling metrics per directory from import edges: afferent coupling (modules importing it), efferent coupling (modules it imports), instability, abstractness (share of interfaces, traits and abstract classes) and distance from the main sequence. Modules above the `architecture.coupling` thresholds in .codegraph.yaml are flagged. Use to find modules everything depends on, modules that depend on everything, and refactoring candidates."
+    )]
+    async fn codegraph_module_coupling(
+        &self,
+        Parameters(p): Parameters<ModuleCouplingParams>,
+    ) -> String {
+        super::tools_analysis::handle_module_coupling(
+            &self.active_store(),
+            &self.config.architecture.coupling,
+            p.depth,
+            p.path.as_deref(),
+            p.sort_by.as_deref(),
+            p.flagged_only.unwrap_or(false),
+            p.limit,
+        )
     }
 
     // 34. codegraph_project_tree
@@ -900,7 +2608,7 @@ impl CodeGraphServer {
         description = "Show a directory tree of the indexed project with file counts per directory."
     )]
     async fn codegraph_project_tree(&self, Parameters(p): Parameters<MaxDepthParams>) -> String {
-        super::tools_analysis::handle_project_tree(&self.store, p.max_depth)
+        super::tools_analysis::handle_project_tree(&self.active_store(), p.max_depth)
     }
 
     // 35. codegraph_find_references
@@ -909,7 +2617,24 @@ impl CodeGraphServer {
         description = "Find ALL references to a symbol across the codebase (calls, imports, type usage, etc.). Use instead of Grep for cross-file reference search. For call-only relationships, use codegraph_callers instead."
     )]
     async fn codegraph_find_references(&self, Parameters(p): Parameters<SymbolParams>) -> String {
-        super::tools_analysis::handle_find_references(&self.store, &p.symbol)
+        super::tools_analysis::handle_find_references(&self.active_store(), &p.symbol)
+    }
+
+    // 70. codegraph_rename_impact
+    #[tool(
+        name = "codegraph_rename_impact",
+        description = "Plan renaming a symbol: every reference location (file, line, column) to edit, including the definition, as a machine-readable edit plan grouped per file with edits ordered last-to-first so they apply without offset shifts. Also flags what the graph cannot verify (string literals spelling the name, unresolved references under that name) and existing symbols the new name would collide with. Nothing is written."
+    )]
+    async fn codegraph_rename_impact(
+        &self,
+        Parameters(p): Parameters<RenameImpactParams>,
+    ) -> String {
+        super::tools_analysis::handle_rename_impact(
+            &self.active_store(),
+            &self.project_root,
+            &p.symbol,
+            &p.new_name,
+        )
     }
 
     // 36. codegraph_export_map
@@ -918,7 +2643,63 @@ impl CodeGraphServer {
         description = "List all exported symbols grouped by file."
     )]
     async fn codegraph_export_map(&self) -> String {
-        super::tools_analysis::handle_export_map(&self.store)
+        super::tools_analysis::handle_export_map(&self.active_store())
+    }
+
+    // 72. codegraph_api_surface
+    #[tool(
+        name = "codegraph_api_surface",
+        description = "Report the public API surface of a directory or package: every exported symbol grouped by module, with its signature, doc comment, public members of exported types, and stability hints (deprecated with its note, experimental, internal) from attributes, decorators and doc tags like #[deprecated], @deprecated, [Obsolete]. Use when maintaining an SDK or writing API docs."
+    )]
+    async fn codegraph_api_surface(&self, Parameters(p): Parameters<ApiSurfaceParams>) -> String {
+        super::tools_analysis::handle_api_surface(
+            &self.active_store(),
+            &self.project_root,
+            p.path,
+            p.max_examples.unwrap_or(0),
+        )
+    }
+
+    // 76. codegraph_summarize_module
+    #[tool(
+        name = "codegraph_summarize_module",
+        description = "Natural-language summary of a directory: what it is responsible for, its entry points, how its parts fit together, and what it depends on. Assembles the directory's top symbols and call structure and asks the client's model to summarize them (MCP sampling). Summaries are cached until a file in the directory changes. Without sampling support, returns the assembled context to summarize instead."
+    )]
+    async fn codegraph_summarize_module(
+        &self,
+        peer: rmcp::service::Peer<RoleServer>,
+        Parameters(p): Parameters<SummarizeModuleParams>,
+    ) -> String {
+        self.summarize_module(&peer, &p).await
+    }
+
+    // 77. codegraph_backfill_embeddings
+    #[tool(
+        name = "codegraph_backfill_embeddings",
+        description = "Embed the indexed symbols that have no embedding yet (e.g. after indexing without the model, or after a failed batch), then update the ANN index. Semantic search and codegraph_similar cover only embedded symbols. Can take minutes on a large repository: call it as an MCP task to poll its progress."
+    )]
+    async fn codegraph_backfill_embeddings(
+        &self,
+        Parameters(p): Parameters<BackfillEmbeddingsParams>,
+    ) -> String {
+        super::tools_analysis::handle_backfill_embeddings(
+            &self.store,
+            p.limit,
+            self.config.performance.ann_index,
+            None,
+        )
+    }
+
+    // 78. codegraph_recompute_pagerank
+    #[tool(
+        name = "codegraph_recompute_pagerank",
+        description = "Recompute global PageRank over the whole graph and store it as the search ranking prior, returning the highest-ranked symbols. Indexing does this automatically; use after bulk edits to the graph. Can run as an MCP task on large repositories."
+    )]
+    async fn codegraph_recompute_pagerank(
+        &self,
+        Parameters(p): Parameters<RecomputePagerankParams>,
+    ) -> String {
+        super::tools_analysis::handle_recompute_pagerank(&self.store, p.top, None)
     }
 
     // 37. codegraph_import_graph
@@ -930,7 +2711,7 @@ impl CodeGraphServer {
         &self,
         Parameters(p): Parameters<OptionalScopeParams>,
     ) -> String {
-        super::tools_analysis::handle_import_graph(&self.store, p.scope)
+        super::tools_analysis::handle_import_graph(&self.active_store(), p.scope)
     }
 
     // 38. codegraph_file
@@ -939,11 +2720,230 @@ impl CodeGraphServer {
         description = "Get all symbols defined in a specific file. Use before reading a file to understand its structure first."
     )]
     async fn codegraph_file(&self, Parameters(p): Parameters<FilePathParams>) -> String {
-        super::tools_analysis::handle_file(&self.store, &p.file_path)
+        super::tools_analysis::handle_file(&self.active_store(), &p.file_path)
+    }
+
+    // 47. codegraph_config_usage
+    #[tool(
+        name = "codegraph_config_usage",
+        description = "Cross-reference configuration keys (YAML/TOML/JSON/.env) with the code that reads them via getenv, process.env, config.get, etc. Answers \"what does changing this setting affect\". Omit key to list all keys with read counts."
+    )]
+    async fn codegraph_config_usage(&self, Parameters(p): Parameters<ConfigUsageParams>) -> String {
+        super::tools_analysis::handle_config_usage(&self.active_store(), p.key, p.file_path)
+    }
+
+    // 48. codegraph_diff_index
+    #[tool(
+        name = "codegraph_diff_index",
+        description = "Compare the graph against a labeled snapshot from an earlier index run: added, removed, and changed symbols plus added/removed edges. Pass save to snapshot the current graph; omit snapshot to list snapshots."
+    )]
+    async fn codegraph_diff_index(&self, Parameters(p): Parameters<DiffIndexParams>) -> String {
+        super::tools_analysis::handle_diff_index(
+            &self.active_store(),
+            p.snapshot,
+            p.against,
+            p.save,
+        )
+    }
+
+    // 49. codegraph_field_usages
+    #[tool(
+        name = "codegraph_field_usages",
+        description = "Find which functions read or write a struct/class field (e.g. who writes Order.status). Finer-grained than symbol references; use before changing a data model."
+    )]
+    async fn codegraph_field_usages(&self, Parameters(p): Parameters<FieldUsagesParams>) -> String {
+        super::tools_analysis::handle_field_usages(&self.active_store(), &p.field, p.access)
+    }
+
+    // 50. codegraph_wire_contract
+    #[tool(
+        name = "codegraph_wire_contract",
+        description = "Show the external JSON shape of a type (serde/Jackson/pydantic/Go json tags/.NET renames, skipped and optional fields) and which functions parse or produce it. Use before renaming model fields to avoid wire-format breaks."
+    )]
+    async fn codegraph_wire_contract(
+        &self,
+        Parameters(p): Parameters<WireContractParams>,
+    ) -> String {
+        super::tools_analysis::handle_wire_contract(&self.active_store(), &p.name)
+    }
+
+    // 52. codegraph_pin_snapshot
+    #[tool(
+        name = "codegraph_pin_snapshot",
+        description = "Pin this session to the current database snapshot so a multi-step investigation (search, then callers, then context) sees one consistent graph even if re-indexing commits in between. Reads stay pinned until called with release=true or for at most 10 minutes; only 8 sessions can hold a pin at once. Tools that record history do not persist while pinned."
+    )]
+    async fn codegraph_pin_snapshot(&self, Parameters(p): Parameters<PinSnapshotParams>) -> String {
+        self.pin_snapshot(p.release.unwrap_or(false))
+    }
+
+    // 53. codegraph_import_side_effects
+    #[tool(
+        name = "codegraph_import_side_effects",
+        description = "List modules that do work at import time: top-level calls, loops and computed initializers in Python/JS/TS, static initializer blocks in Java. Use when debugging slow startup or tests that depend on import order."
+    )]
+    async fn codegraph_import_side_effects(
+        &self,
+        Parameters(p): Parameters<ImportSideEffectsParams>,
+    ) -> String {
+        super::tools_analysis::handle_import_side_effects(
+            &self.active_store(),
+            p.file_path,
+            p.kind,
+            p.include_tests.unwrap_or(false),
+        )
+    }
+
+    // 54. codegraph_global_state
+    #[tool(
+        name = "codegraph_global_state",
+        description = "Inventory global mutable state: module-level mutable variables, non-final static fields and singletons, each with the functions that write and read it. Use when refactoring toward testability or hunting hidden coupling between modules."
+    )]
+    async fn codegraph_global_state(&self, Parameters(p): Parameters<GlobalStateParams>) -> String {
+        super::tools_analysis::handle_global_state(
+            &self.active_store(),
+            p.file_path,
+            p.kind,
+            p.include_tests.unwrap_or(false),
+            p.limit,
+        )
+    }
+
+    // 57. codegraph_find_literal
+    #[tool(
+        name = "codegraph_find_literal",
+        description = "Find string literals and named constants by value or constant name (exact or substring), with the symbols that contain each string and the functions that use each constant. Use to trace a user-visible message, error code or magic value back to the code without grep."
+    )]
+    async fn codegraph_find_literal(&self, Parameters(p): Parameters<FindLiteralParams>) -> String {
+        super::tools_analysis::handle_find_literal(
+            &self.active_store(),
+            &p.query,
+            p.exact.unwrap_or(false),
+            p.kind,
+            p.file_path,
+            p.include_tests.unwrap_or(false),
+            p.limit,
+        )
+    }
+
+    // 79. codegraph_grep
+    #[tool(
+        name = "codegraph_grep",
+        description = "Regex search over the source of indexed symbols, with optional path and kind filters. Each matching line comes with the innermost symbol containing it (name, kind, file, line range). Use instead of filesystem grep to search only code the graph knows about — no vendored, generated or ignored files."
+    )]
+    async fn codegraph_grep(&self, Parameters(p): Parameters<GrepParams>) -> String {
+        super::tools_analysis::handle_grep(
+            &self.active_store(),
+            &p.pattern,
+            p.path,
+            p.kind,
+            p.case_sensitive.unwrap_or(false),
+            p.include_tests.unwrap_or(false),
+            p.limit,
+        )
+    }
+
+    // 80. codegraph_usage
+    #[tool(
+        name = "codegraph_usage",
+        description = "Tool usage analytics: calls, errors, sessions, average and max duration and response size per tool, from every MCP tool call recorded in the index database, plus enabled tools that were never called. Use to see which tools agents actually use and trim the preset to them."
+    )]
+    async fn codegraph_usage(&self, Parameters(p): Parameters<UsageParams>) -> String {
+        // Calls are recorded in the live store, see `record_usage`.
+        let session = p
+            .current_session
+            .unwrap_or(false)
+            .then_some(&*self.session_id);
+        super::tools_analysis::handle_usage(
+            &self.store,
+            &super::registry::enabled_tool_names(&self.config),
+            p.days,
+            session,
+        )
+    }
+
+    // 58. codegraph_tag
+    #[tool(
+        name = "codegraph_tag",
+        description = "Attach or remove user-defined key/value tags on a symbol (e.g. layer=api, team=payments, status=legacy) to record architecture layers, ownership or other classification. Tags persist across re-indexing, can filter codegraph_query, codegraph_search and codegraph_tagged, and are attached as an 'annotations' field wherever the symbol appears in a tool response."
+    )]
+    async fn codegraph_tag(&self, Parameters(p): Parameters<TagParams>) -> String {
+        // Tags are written to the live store even while a snapshot is pinned.
+        super::tools_analysis::handle_tag(
+            &self.store,
+            &p.symbol,
+            p.set.unwrap_or_default(),
+            p.remove.unwrap_or_default(),
+        )
+    }
+
+    // 69. codegraph_annotate
+    #[tool(
+        name = "codegraph_annotate",
+        description = "Add or remove free-text notes on a symbol (e.g. 'legacy, being replaced by v2', 'perf-critical: called per request'). Notes and tags persist across re-indexing and are attached as an 'annotations' field wherever the symbol appears in any tool's response. Returns the symbol's notes with their IDs, and its tags."
+    )]
+    async fn codegraph_annotate(&self, Parameters(p): Parameters<AnnotateParams>) -> String {
+        // Notes are written to the live store even while a snapshot is pinned.
+        super::tools_analysis::handle_annotate(
+            &self.store,
+            &p.symbol,
+            p.note,
+            p.author,
+            p.remove.unwrap_or_default(),
+        )
+    }
+
+    // 59. codegraph_tagged
+    #[tool(
+        name = "codegraph_tagged",
+        description = "List symbols carrying the given tags ('layer=api', or just 'team' for any value), with all their tags. Without tags, lists every tag key/value in use with its symbol count. Use for architecture-conformance checks against user-defined layers."
+    )]
+    async fn codegraph_tagged(&self, Parameters(p): Parameters<TaggedParams>) -> String {
+        super::tools_analysis::handle_tagged(
+            &self.active_store(),
+            p.tags.unwrap_or_default(),
+            p.kind,
+            p.limit,
+        )
+    }
+
+    // 62. codegraph_duplicate_definitions
+    #[tool(
+        name = "codegraph_duplicate_definitions",
+        description = "Find functions, methods and types defined more than once under the same qualified name (copy-pasted utils, shadowed helpers), with pairwise body similarity and an identical/near_duplicate/divergent verdict per group. Use to consolidate utility sprawl."
+    )]
+    async fn codegraph_duplicate_definitions(
+        &self,
+        Parameters(p): Parameters<DuplicateDefinitionsParams>,
+    ) -> String {
+        super::tools_analysis::handle_duplicate_definitions(
+            &self.active_store(),
+            p.file_path,
+            p.class,
+            p.min_similarity,
+            p.include_tests.unwrap_or(false),
+            p.limit,
+        )
+    }
+
+    // 66. codegraph_find_clones
+    #[tool(
+        name = "codegraph_find_clones",
+        description = "Find semantic clones: functions whose embeddings are near-identical (cosine similarity above threshold, default 0.95) and whose bodies have comparable token counts, grouped into clusters. Catches copies with renamed identifiers that codegraph_duplicate_definitions misses. Falls back to lexical vectors when no embeddings are indexed."
+    )]
+    async fn codegraph_find_clones(&self, Parameters(p): Parameters<FindClonesParams>) -> String {
+        let defaults = crate::graph::clones::CloneOptions::default();
+        let opts = crate::graph::clones::CloneOptions {
+            threshold: p.threshold.unwrap_or(defaults.threshold),
+            min_length_ratio: p.min_length_ratio.unwrap_or(defaults.min_length_ratio),
+            min_lines: p.min_lines.unwrap_or(defaults.min_lines),
+            include_tests: p.include_tests.unwrap_or(false),
+            path: p.path,
+        };
+        super::tools_analysis::handle_find_clones(&self.active_store(), opts, p.limit)
     }
 
     // =========================================================================
-    // Call Graph & Analysis Tools (6)
+    // Call Graph & Analysis Tools (10)
     // =========================================================================
 
     // 39. codegraph_find_path
@@ -952,7 +2952,7 @@ impl CodeGraphServer {
         description = "Find the shortest call path between two functions using BFS on the call graph."
     )]
     async fn codegraph_find_path(&self, Parameters(p): Parameters<FindPathParams>) -> String {
-        super::tools_dataflow::handle_find_path(&self.store, &p.from, &p.to, p.max_depth)
+        super::tools_dataflow::handle_find_path(&self.active_store(), &p.from, &p.to, p.max_depth)
     }
 
     // 40. codegraph_complexity
@@ -961,7 +2961,22 @@ impl CodeGraphServer {
         description = "Calculate cyclomatic and cognitive complexity for all functions in the codebase."
     )]
     async fn codegraph_complexity(&self, Parameters(p): Parameters<ComplexityParams>) -> String {
-        super::tools_dataflow::handle_complexity(&self.store, p.min_complexity)
+        super::tools_dataflow::handle_complexity(&self.active_store(), p.min_complexity)
+    }
+
+    // 74. codegraph_test_gaps
+    #[tool(
+        name = "codegraph_test_gaps",
+        description = "Prioritized list of where to write tests: non-test functions above a cyclomatic complexity threshold that no test reaches through calls or references, ranked by PageRank × complexity so central, branchy code comes first."
+    )]
+    async fn codegraph_test_gaps(&self, Parameters(p): Parameters<TestGapsParams>) -> String {
+        super::tools_dataflow::handle_test_gaps(
+            &self.active_store(),
+            p.min_complexity,
+            p.path.as_deref(),
+            p.max_depth,
+            p.limit,
+        )
     }
 
     // 41. codegraph_data_flow
@@ -1026,6 +3041,115 @@ impl CodeGraphServer {
             &self.project_root,
         )
     }
+
+    // 55. codegraph_recursion
+    #[tool(
+        name = "codegraph_recursion",
+        description = "Find recursive functions: self-calls and mutually recursive groups in the call graph, with cycle members, shortest round trip, a max stack depth hint per round, and the callers and call sites (entry edges) that enter each cycle. Complements codegraph_circular_imports, which only covers imports. Use when reviewing stack-overflow risk."
+    )]
+    async fn codegraph_recursion(&self, Parameters(p): Parameters<RecursionParams>) -> String {
+        super::tools_dataflow::handle_recursion(
+            &self.active_store(),
+            p.file_path,
+            p.include_tests.unwrap_or(false),
+            p.limit,
+        )
+    }
+
+    // 56. codegraph_unused_params
+    #[tool(
+        name = "codegraph_unused_params",
+        description = "Find parameters never used inside their function and call sites that discard a non-void return value. Skips stubs, overrides and `_`-prefixed names. Use to guide API cleanup."
+    )]
+    async fn codegraph_unused_params(
+        &self,
+        Parameters(p): Parameters<UnusedParamsParams>,
+    ) -> String {
+        super::tools_dataflow::handle_unused_params(
+            &self.active_store(),
+            p.file_path,
+            p.include_tests.unwrap_or(false),
+            p.limit,
+        )
+    }
+
+    // 71. codegraph_callgraph_export
+    #[tool(
+        name = "codegraph_callgraph_export",
+        description = "Export the subgraph around a root symbol in one call: follows edges of the given kinds (default calls) in the given direction (outgoing, incoming, both) up to a depth, and returns the induced subgraph as {nodes, edges} JSON in the same shape as the viz API. Use to render custom call-graph or dependency diagrams without walking callers/callees one level at a time."
+    )]
+    async fn codegraph_callgraph_export(
+        &self,
+        Parameters(p): Parameters<CallgraphExportParams>,
+    ) -> String {
+        super::tools_dataflow::handle_callgraph_export(
+            &self.active_store(),
+            &p.symbol,
+            p.direction,
+            p.depth,
+            p.edge_kinds,
+            p.max_nodes,
+        )
+    }
+
+    // 82. codegraph_entrypoints
+    #[tool(
+        name = "codegraph_entrypoints",
+        description = "List where execution starts: main functions, HTTP route handlers (decorators, annotations and registrations such as app.get('/x', handler) or .route(\"/x\", get(handler))), CLI commands (clap, click, typer, commander, argparse) and exported symbols of package root modules. Each entry point comes with the first levels of its callee tree. Use first when exploring an unfamiliar codebase."
+    )]
+    async fn codegraph_entrypoints(&self, Parameters(p): Parameters<EntrypointsParams>) -> String {
+        super::tools_dataflow::handle_entrypoints(
+            &self.active_store(),
+            &self.project_root,
+            p.kind.as_deref(),
+            p.path.as_deref(),
+            p.depth,
+            p.limit,
+        )
+    }
+
+    // 85. codegraph_cfg
+    #[tool(
+        name = "codegraph_cfg",
+        description = "Control-flow graph of one function, built from its syntax tree: basic blocks (straight-line statements, conditions, loops, switches, catch handlers) joined by next, true/false, back, case, break, continue, return and throw edges. Reports cyclomatic complexity and unreachable blocks. Returns JSON or a Mermaid flowchart (format: mermaid). Use to understand tangled branching before refactoring."
+    )]
+    async fn codegraph_cfg(&self, Parameters(p): Parameters<CfgParams>) -> String {
+        super::tools_dataflow::handle_cfg(
+            &self.active_store(),
+            &self.project_root,
+            &p.symbol,
+            p.format.as_deref(),
+        )
+    }
+
+    // 86. codegraph_null_checks
+    #[tool(
+        name = "codegraph_null_checks",
+        description = "Flag dereferences of variables that may be null/None/undefined/nil on some path: assigned a null literal, a lookup that returns null when nothing is found (dict.get, re.match, Array.find, querySelector), or an optional parameter, and not guarded by a null check or truthiness test on that path. Follows each function's control-flow graph. Python, JavaScript/TypeScript and Go. A quick pre-review smell detector, not a type checker. Pass file_path to read a file directly (language auto-detected), or pass source + language explicitly."
+    )]
+    async fn codegraph_null_checks(&self, Parameters(p): Parameters<DataFlowParams>) -> String {
+        super::tools_dataflow::handle_null_checks(
+            p.file_path.as_deref(),
+            p.source.as_deref(),
+            p.language.as_deref(),
+            &self.project_root,
+        )
+    }
+
+    // 87. codegraph_routes
+    #[tool(
+        name = "codegraph_routes",
+        description = "List the HTTP surface of the app: every route registered with Express, FastAPI, Flask, axum or Spring, as method + path with the handler function serving it. Filter by method, path prefix or framework. Use to see which endpoints exist and where each one is implemented."
+    )]
+    async fn codegraph_routes(&self, Parameters(p): Parameters<RoutesParams>) -> String {
+        super::tools_dataflow::handle_routes(
+            &self.active_store(),
+            p.method.as_deref(),
+            p.path.as_deref(),
+            p.framework.as_deref(),
+            p.limit,
+        )
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -1036,7 +3160,7 @@ impl ServerHandler for CodeGraphServer {
     fn get_info(&self) -> ServerInfo {
         ServerInfo {
             instructions: Some(
-                "CodeGraph — codebase intelligence MCP server with 46 tools across 32 languages. \
+                "CodeGraph — codebase intelligence MCP server with 87 tools across 32 languages. \
                  ALWAYS prefer CodeGraph tools over Grep/Glob/Explore agents for code analysis: \
                  codegraph_query (semantic search), codegraph_dependencies (import tracing), \
                  codegraph_callers (call graph), codegraph_find_references (cross-file refs), \
@@ -1044,11 +3168,15 @@ impl ServerHandler for CodeGraphServer {
                  These use a pre-built AST index and return results in milliseconds."
                     .into(),
             ),
-            capabilities: ServerCapabilities::builder()
-                .enable_tools()
-                .enable_resources()
-                .enable_prompts()
-                .build(),
+            capabilities: ServerCapabilities {
+                tasks: Some(TasksCapability::server_default()),
+                ..ServerCapabilities::builder()
+                    .enable_tools()
+                    .enable_resources()
+                    .enable_resources_subscribe()
+                    .enable_prompts()
+                    .build()
+            },
             server_info: rmcp::model::Implementation {
                 name: "codegraph".into(),
                 version: env!("CARGO_PKG_VERSION").into(),
@@ -1058,6 +3186,18 @@ impl ServerHandler for CodeGraphServer {
         }
     }
 
+    fn on_initialized(
+        &self,
+        context: rmcp::service::NotificationContext<RoleServer>,
+    ) -> impl std::future::Future<Output = ()> + Send + '_ {
+        // A resumed session (see `websocket`) keeps its subscriptions but
+        // lost the poller with its previous connection.
+        if !self.subscriptions.is_empty() && self.subscriptions.start_polling() {
+            self.spawn_index_poller(context.peer);
+        }
+        std::future::ready(())
+    }
+
     fn list_resources(
         &self,
         _request: Option<PaginatedRequestParams>,
@@ -1097,21 +3237,140 @@ impl ServerHandler for CodeGraphServer {
                 None,
             ),
         ];
-        std::future::ready(Ok(ListResourcesResult {
-            meta: None,
-            next_cursor: None,
-            resources,
-        }))
-    }
+        let roots = self.roots.iter().map(|root| {
+            Annotated::new(
+                RawResource {
+                    uri: super::roots::resource_uri(&root.name),
+                    name: format!("Workspace {}", root.name),
+                    title: None,
+                    description: Some(format!(
+                        "Project root {}: path and index status. Pass workspace: \"{}\" to tools to query it.",
+                        root.project_root.display(),
+                        root.name
+                    )),
+                    mime_type: Some("application/json".into()),
+                    size: None,
+                    icons: None,
+                    meta: None,
+                },
+                None,
+            )
+        });
+        let resources = resources.into_iter().chain(roots).collect();
+        std::future::ready(Ok(ListResourcesResult {
+            meta: None,
+            next_cursor: None,
+            resources,
+        }))
+    }
+
+    fn list_resource_templates(
+        &self,
+        _request: Option<PaginatedRequestParams>,
+        _context: RequestContext<RoleServer>,
+    ) -> impl std::future::Future<Output = Result<ListResourceTemplatesResult, McpError>> + Send + '_
+    {
+        std::future::ready(Ok(ListResourceTemplatesResult {
+            meta: None,
+            next_cursor: None,
+            resource_templates: super::resources::resource_templates(),
+        }))
+    }
+
+    fn subscribe(
+        &self,
+        request: SubscribeRequestParams,
+        context: RequestContext<RoleServer>,
+    ) -> impl std::future::Future<Output = Result<(), McpError>> + Send + '_ {
+        let result = self.subscription_file(&request.uri).map(|file| {
+            self.subscriptions.subscribe(&request.uri, file);
+            if self.subscriptions.start_polling() {
+                self.spawn_index_poller(context.peer);
+            }
+        });
+        std::future::ready(result)
+    }
+
+    fn unsubscribe(
+        &self,
+        request: UnsubscribeRequestParams,
+        _context: RequestContext<RoleServer>,
+    ) -> impl std::future::Future<Output = Result<(), McpError>> + Send + '_ {
+        self.subscriptions.unsubscribe(&request.uri);
+        std::future::ready(Ok(()))
+    }
+
+    fn enqueue_task(
+        &self,
+        request: CallToolRequestParams,
+        _context: RequestContext<RoleServer>,
+    ) -> impl std::future::Future<Output = Result<CreateTaskResult, McpError>> + Send + '_ {
+        std::future::ready(self.start_task(request))
+    }
+
+    fn list_tasks(
+        &self,
+        request: Option<PaginatedRequestParams>,
+        _context: RequestContext<RoleServer>,
+    ) -> impl std::future::Future<Output = Result<ListTasksResult, McpError>> + Send + '_ {
+        std::future::ready(super::tasks::make_list_result(&self.tasks, request))
+    }
+
+    fn get_task_info(
+        &self,
+        request: GetTaskInfoParams,
+        _context: RequestContext<RoleServer>,
+    ) -> impl std::future::Future<Output = Result<GetTaskInfoResult, McpError>> + Send + '_ {
+        std::future::ready(super::tasks::make_task_info(&self.tasks, request))
+    }
+
+    fn get_task_result(
+        &self,
+        request: GetTaskResultParams,
+        _context: RequestContext<RoleServer>,
+    ) -> impl std::future::Future<Output = Result<TaskResult, McpError>> + Send + '_ {
+        std::future::ready(super::tasks::make_task_result(&self.tasks, request))
+    }
+
+    fn cancel_task(
+        &self,
+        request: CancelTaskParams,
+        _context: RequestContext<RoleServer>,
+    ) -> impl std::future::Future<Output = Result<(), McpError>> + Send + '_ {
+        std::future::ready(super::tasks::handle_cancel(&self.tasks, &request.task_id))
+    }
+
+    fn on_custom_request(
+        &self,
+        request: CustomRequest,
+        _context: RequestContext<RoleServer>,
+    ) -> impl std::future::Future<Output = Result<CustomResult, McpError>> + Send + '_ {
+        let result = match super::tasks::handle_task_request(&self.tasks, &request) {
+            Some(result) => result.map(CustomResult::new),
+            None => Err(McpError::new(
+                rmcp::model::ErrorCode::METHOD_NOT_FOUND,
+                request.method,
+                None,
+            )),
+        };
+        std::future::ready(result)
+    }
 
     fn read_resource(
         &self,
         request: ReadResourceRequestParams,
         _context: RequestContext<RoleServer>,
     ) -> impl std::future::Future<Output = Result<ReadResourceResult, McpError>> + Send + '_ {
+        if let Some(resource) = super::resources::ResourceRef::parse(&request.uri) {
+            return std::future::ready(self.read_templated_resource(&resource));
+        }
+        if let Some(name) = super::roots::parse_resource_uri(&request.uri) {
+            return std::future::ready(self.read_workspace_resource(name));
+        }
         let result = match request.uri.as_str() {
             "codegraph://status" => {
-                let store = self.store.lock().unwrap_or_else(|e| e.into_inner());
+                let store = self.active_store();
+                let store = store.lock().unwrap_or_else(|e| e.into_inner());
                 match store.get_stats() {
                     Ok(stats) => {
                         let unresolved = store.get_unresolved_ref_count().unwrap_or(0);
@@ -1138,7 +3397,8 @@ impl ServerHandler for CodeGraphServer {
                 }
             }
             "codegraph://overview" => {
-                let store = self.store.lock().unwrap_or_else(|e| e.into_inner());
+                let store = self.active_store();
+                let store = store.lock().unwrap_or_else(|e| e.into_inner());
                 let stats = match store.get_stats() {
                     Ok(s) => s,
                     Err(e) => {
@@ -1148,18 +3408,17 @@ impl ServerHandler for CodeGraphServer {
                         )));
                     }
                 };
-                let all_nodes = store.get_all_nodes().unwrap_or_default();
-                let file_count = all_nodes
-                    .iter()
-                    .map(|n| &n.file_path)
-                    .collect::<HashSet<_>>()
-                    .len();
-
-                // Language breakdown
+                // File count and language breakdown, streamed so large
+                // graphs are never held in memory at once.
+                let mut files: HashSet<String> = HashSet::new();
                 let mut lang_counts: HashMap<String, usize> = HashMap::new();
-                for node in &all_nodes {
-                    *lang_counts.entry(node.language.to_string()).or_default() += 1;
+                for batch in store.iter_nodes(STREAM_BATCH_SIZE).flatten() {
+                    for node in batch {
+                        *lang_counts.entry(node.language.to_string()).or_default() += 1;
+                        files.insert(node.file_path);
+                    }
                 }
+                let file_count = files.len();
                 let mut langs: Vec<_> = lang_counts.into_iter().collect();
                 langs.sort_by(|a, b| b.1.cmp(&a.1));
 
@@ -1239,6 +3498,26 @@ impl ServerHandler for CodeGraphServer {
                     required: Some(true),
                 }]),
             ),
+            Prompt::new(
+                "codebase-onboarding",
+                Some("Guided tour of the codebase for a new contributor — layout, languages, frameworks, churn hotspots, and the most central symbols."),
+                Some(vec![PromptArgument {
+                    name: "path".into(),
+                    title: None,
+                    description: Some("Directory to focus the tour on (default: the whole project)".into()),
+                    required: Some(false),
+                }]),
+            ),
+            Prompt::new(
+                "locate-bug",
+                Some("Localize a bug from an error message or stack trace — finds the frame symbols, their callers, and recent changes to the implicated files, ending with a ranked suspect list."),
+                Some(vec![PromptArgument {
+                    name: "error".into(),
+                    title: None,
+                    description: Some("Error message or stack trace of the failure".into()),
+                    required: Some(true),
+                }]),
+            ),
         ];
         std::future::ready(Ok(ListPromptsResult {
             meta: None,
@@ -1331,6 +3610,66 @@ impl ServerHandler for CodeGraphServer {
                     ],
                 })
             }
+            "codebase-onboarding" => {
+                let path = args.get("path").and_then(|v| v.as_str());
+                let (scope, path_arg) = match path {
+                    Some(p) => (
+                        format!("the `{p}` part of this codebase"),
+                        format!(" with path=\"{p}\""),
+                    ),
+                    None => ("this codebase".to_string(), String::new()),
+                };
+                Ok(GetPromptResult {
+                    description: Some("Guided onboarding walkthrough".into()),
+                    messages: vec![
+                        PromptMessage::new_text(
+                            PromptMessageRole::User,
+                            format!(
+                                "Give me a guided tour of {scope} as a new contributor. Gather the facts first:\n\n\
+                                 1. Call `codegraph_structure`{path_arg} for the module layout, symbol counts, and the top symbols by PageRank.\n\
+                                 2. Call `codegraph_languages` for the language mix and code size.\n\
+                                 3. Call `codegraph_frameworks` to identify the frameworks and libraries in use.\n\
+                                 4. Call `codegraph_hotspots` with limit=10 to find the files that change most often (skip this if the project has no git history).\n\
+                                 5. For the 5 highest-ranked symbols from step 1, call `codegraph_node` with include_relations=true to see what they do and how they connect.\n\n\
+                                 Then walk me through it in stages, pausing after each one:\n\
+                                    - The big picture: what the project does, its languages and frameworks\n\
+                                    - The map: main modules and how they depend on each other\n\
+                                    - The core: the central symbols, what they do, and who calls them\n\
+                                    - Where the action is: hotspots, and what to read before touching them\n\
+                                    - A suggested reading order and a good first area to contribute to"
+                            ),
+                        ),
+                    ],
+                })
+            }
+            "locate-bug" => {
+                let error = args
+                    .get("error")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("unknown error");
+                Ok(GetPromptResult {
+                    description: Some("Bug localization workflow".into()),
+                    messages: vec![
+                        PromptMessage::new_text(
+                            PromptMessageRole::User,
+                            format!(
+                                "Find the most likely cause of this failure:\n\n```\n{error}\n```\n\n\
+                                 Follow these steps:\n\n\
+                                 1. Extract the function, method, and type names from the error and its stack frames, innermost frame first. Ignore frames from the standard library and third-party dependencies.\n\
+                                 2. Call `codegraph_search` for each frame symbol to find its definition in the graph, and note its file.\n\
+                                 3. Call `codegraph_callers` with max_depth=2 for the innermost project frames to see how execution reaches them and which inputs they receive.\n\
+                                 4. Call `codegraph_recent_changes` with limit=30, and `codegraph_file_history` for each implicated file, to find commits that recently touched the failing code path.\n\
+                                 5. Call `codegraph_node` with include_relations=true for the strongest candidates to read their source.\n\
+                                 6. End with a ranked suspect list. For each suspect give:\n\
+                                    - The symbol and file:line\n\
+                                    - Why it is implicated (stack frame, caller path, recent change)\n\
+                                    - The specific line or condition you suspect\n\
+                                    - How to confirm it (a test, a log line, or an input to try)"
+                            ),
+                        ),
+                    ],
+                })
+            }
             name => Err(McpError::invalid_params(
                 format!("Unknown prompt: {name}"),
                 None,
@@ -1351,10 +3690,29 @@ impl ServerHandler for CodeGraphServer {
         // Build the set of enabled tool names from config + registry
         let enabled = super::registry::enabled_tool_names(&self.config);
 
-        // Filter: keep only tools whose name is in the enabled set
+        // Filter: keep only tools whose name is in the enabled set, and
+        // advertise the paging arguments of paged tools, the output
+        // schemas of structured ones and the `workspace` argument of a
+        // multi-root server
+        let root_names: Vec<&str> = self.roots.iter().map(|r| r.name.as_str()).collect();
         let filtered = all_tools
             .into_iter()
             .filter(|t| enabled.contains(t.name.as_ref()))
+            .map(|mut t| {
+                if super::pagination::paged_field(&t.name).is_some() {
+                    super::pagination::add_page_params(Arc::make_mut(&mut t.input_schema));
+                }
+                if let Some(schema) = super::structured::output_schema(&t.name) {
+                    t.output_schema = Some(Arc::new(schema));
+                }
+                if !root_names.is_empty() {
+                    super::roots::add_workspace_param(
+                        Arc::make_mut(&mut t.input_schema),
+                        &root_names,
+                    );
+                }
+                t
+            })
             .collect();
 
         std::future::ready(Ok(ListToolsResult {
@@ -1366,25 +3724,220 @@ impl ServerHandler for CodeGraphServer {
 
     async fn call_tool(
         &self,
+        mut request: CallToolRequestParams,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, McpError> {
+        let tool = request.name.to_string();
+        let started = std::time::Instant::now();
+        // Multi-root servers: run against the root the call names
+        let result = match super::roots::take_workspace(&mut request.arguments) {
+            Some(workspace) => self.run_in_workspace(&workspace, request, context).await,
+            None => self.run_tool_call(request, context).await,
+        };
+        // Not awaited: recording must never hold up the response.
+        drop(self.record_usage(&tool, started.elapsed(), &result));
+        result
+    }
+}
+
+impl CodeGraphServer {
+    /// Run a tool call against the root `workspace`, see
+    /// [`roots`](super::roots).
+    async fn run_in_workspace(
+        &self,
+        workspace: &str,
         request: CallToolRequestParams,
         context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, McpError> {
+        let server = match self.for_workspace(workspace) {
+            Some(_) if request.name == "codegraph_pin_snapshot" => {
+                Err("Snapshots can only be pinned in the default project".to_string())
+            }
+            Some(server) => Ok(server),
+            None => Err(format!(
+                "Unknown workspace '{workspace}'. Available: {}",
+                self.roots
+                    .iter()
+                    .map(|r| r.name.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )),
+        };
+        match server {
+            Ok(server) => server.run_tool_call(request, context).await,
+            Err(message) => Ok(CallToolResult::error(vec![rmcp::model::Content::text(
+                json_text(&serde_json::json!({ "error": message })),
+            )])),
+        }
+    }
+
+    /// Record a finished tool call in the usage analytics of the default
+    /// project, see [`usage`](crate::db::usage).
+    ///
+    /// The write runs on the blocking pool: a call cancelled by its timeout
+    /// may leave a worker holding the store, and the async caller must not
+    /// wait for it.
+    fn record_usage(
+        &self,
+        tool: &str,
+        duration: std::time::Duration,
+        result: &Result<CallToolResult, McpError>,
+    ) -> tokio::task::JoinHandle<()> {
+        let (text, error) = match result {
+            Ok(result) => {
+                let text = result
+                    .content
+                    .iter()
+                    .filter_map(|c| c.as_text())
+                    .map(|t| t.text.as_str())
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                let failed = result.is_error == Some(true)
+                    || serde_json::from_str::<serde_json::Value>(&text)
+                        .is_ok_and(|v| v.get("error").is_some_and(|e| e.is_string()));
+                let error = failed.then(|| tool_error_message(&text));
+                (text, error)
+            }
+            Err(e) => (String::new(), Some(e.message.to_string())),
+        };
+        let store = Arc::clone(&self.store);
+        let session = self.session_id.clone();
+        let tool = tool.to_string();
+        let result_bytes = text.len();
+        tokio::task::spawn_blocking(move || {
+            let call = ToolInvocation {
+                session: &session,
+                tool: &tool,
+                duration,
+                result_bytes,
+                error: error.as_deref(),
+            };
+            let store = store.lock().unwrap_or_else(|e| e.into_inner());
+            if let Err(e) = record_invocation(&store.conn, &call) {
+                tracing::warn!("Failed to record {} call: {}", tool, e);
+            }
+        })
+    }
+
+    /// Run a tool call against this server's project.
+    async fn run_tool_call(
+        &self,
+        mut request: CallToolRequestParams,
+        context: RequestContext<RoleServer>,
     ) -> Result<CallToolResult, McpError> {
         // Check if the tool is enabled before dispatching
         let enabled = super::registry::enabled_tool_names(&self.config);
         if !enabled.contains(request.name.as_ref()) {
+            let message = format!(
+                "Tool '{}' is not available in the current preset ({}). \
+                 Change the preset in .codegraph.yaml or set CODEGRAPH_PRESET=full to enable all tools.",
+                request.name, self.config.preset
+            );
             return Ok(CallToolResult::error(vec![rmcp::model::Content::text(
-                format!(
-                    "Tool '{}' is not available in the current preset ({}). \
-                     Change the preset in .codegraph.yaml or set CODEGRAPH_PRESET=full to enable all tools.",
-                    request.name, self.config.preset
-                ),
+                self.localizer.localize(&request.name, &message),
             )]));
         }
 
+        // Clamp depth/limit arguments to the active response profile
+        let spec = (self.shaper.is_active() || self.shaper.has_budget())
+            .then(|| {
+                Self::tool_router()
+                    .list_all()
+                    .into_iter()
+                    .find(|t| t.name == request.name)
+            })
+            .flatten();
+        if let Some(spec) = spec.as_ref().filter(|_| self.shaper.is_active()) {
+            let args = request.arguments.get_or_insert_with(Default::default);
+            self.shaper.cap_arguments(&spec.input_schema, args);
+        }
+
+        // Paged tools: take the paging arguments out before dispatch
+        let paging = super::pagination::paged_field(&request.name).map(|field| {
+            let args = request.arguments.get_or_insert_with(Default::default);
+            (field, super::pagination::PageRequest::take(args))
+        });
+
         // Dispatch to the macro-generated tool handler
-        let tool_context =
-            rmcp::handler::server::tool::ToolCallContext::new(self, request, context);
-        Self::tool_router().call(tool_context).await
+        let tool = request.name.clone();
+        let logged_args = self
+            .config
+            .performance
+            .query_log
+            .then(|| serde_json::to_string(&request.arguments).unwrap_or_default());
+        let started = std::time::Instant::now();
+        let server = self.for_call(&tool);
+        let runtime = tokio::runtime::Handle::current();
+        let name = tool.clone();
+        // Over the token budget, re-run at a lower detail level while the
+        // tool has one, see [`profile`](super::profile)
+        let call = self.limiter.run(&tool, move || {
+            runtime.block_on(async {
+                let mut degraded_to = None;
+                loop {
+                    let tool_context = rmcp::handler::server::tool::ToolCallContext::new(
+                        &server,
+                        request.clone(),
+                        context.clone(),
+                    );
+                    let mut result = Self::tool_router().call(tool_context).await?;
+                    if let Some((field, page)) = &paging {
+                        server.paginate_result(&name, field, page, &mut result);
+                    }
+                    let lowered = spec.as_ref().and_then(|spec| {
+                        let text = result.content.iter().find_map(|c| c.as_text())?;
+                        let args = request.arguments.get_or_insert_with(Default::default);
+                        server
+                            .shaper
+                            .lower_detail(&spec.input_schema, args, &text.text)
+                    });
+                    match lowered {
+                        Some(level) => degraded_to = Some(level),
+                        None => return Ok((result, degraded_to)),
+                    }
+                }
+            })
+        });
+        let (mut result, degraded_to) = match call.await {
+            Ok(result) => result?,
+            Err(e) => {
+                return Ok(CallToolResult::error(vec![rmcp::model::Content::text(
+                    json_text(&e.to_json(&tool)),
+                )]))
+            }
+        };
+        if !super::annotations::SELF_ANNOTATED_TOOLS.contains(&tool.as_ref()) {
+            self.attach_annotations(&mut result);
+        }
+        if let Some(args) = logged_args {
+            let store = self.store.lock().unwrap_or_else(|e| e.into_inner());
+            if let Err(e) = log_query(&store.conn, &tool, &args, started.elapsed()) {
+                tracing::warn!("Failed to log {} call: {}", tool, e);
+            }
+        }
+        if self.localizer.locale_for(&tool).is_some() {
+            for content in &mut result.content {
+                if let RawContent::Text(text) = &mut content.raw {
+                    text.text = self.localizer.localize(&tool, &text.text);
+                }
+            }
+        }
+        if self.shaper.is_active() {
+            for content in &mut result.content {
+                if let RawContent::Text(text) = &mut content.raw {
+                    text.text = self.shaper.shape(&text.text);
+                }
+            }
+        }
+        if self.shaper.has_budget() {
+            for content in &mut result.content {
+                if let RawContent::Text(text) = &mut content.raw {
+                    text.text = self.shaper.fit_budget(&text.text, degraded_to);
+                }
+            }
+        }
+        self.structure_result(&tool, &mut result);
+        Ok(result)
     }
 }
 
@@ -1395,10 +3948,16 @@ impl ServerHandler for CodeGraphServer {
 /// Start the MCP server on stdin/stdout.
 ///
 /// This blocks until the client disconnects or a shutdown signal is received.
-pub async fn run_server(store: GraphStore) -> Result<(), Box<dyn std::error::Error>> {
+///
+/// Tools take a `workspace` argument selecting one of `roots` instead of the
+/// project in the current directory, see [`roots`](super::roots).
+pub async fn run_server(
+    store: GraphStore,
+    roots: Vec<ProjectRoot>,
+) -> Result<(), Box<dyn std::error::Error>> {
     let project_root = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
     let config = crate::config::loader::load_config(None, Some(&project_root)).unwrap_or_default();
-    let server = CodeGraphServer::with_config(store, project_root, config);
+    let server = CodeGraphServer::with_config(store, project_root, config).with_roots(roots);
     let transport = rmcp::transport::io::stdio();
     let running = server.serve(transport).await.inspect_err(|e| {
         tracing::error!("MCP server error: {}", e);
@@ -1611,6 +4170,50 @@ mod tests {
         assert_eq!(callees[0]["name"].as_str().unwrap(), "callee");
     }
 
+    #[tokio::test]
+    async fn node_includes_git_metadata_when_recorded() {
+        let server = setup_server();
+        {
+            let store = server.store.lock().unwrap();
+            store
+                .upsert_node(&make_node(
+                    "n1",
+                    "target",
+                    "src/a.ts",
+                    NodeKind::Function,
+                    1,
+                    None,
+                ))
+                .unwrap();
+            store
+                .replace_node_git_meta_for_file(
+                    "src/a.ts",
+                    &[crate::git::NodeGitMeta {
+                        node_id: "n1".to_string(),
+                        file_path: "src/a.ts".to_string(),
+                        commit_hash: "deadbeef".to_string(),
+                        author: "Alice".to_string(),
+                        email: "alice@example.com".to_string(),
+                        committed_at: chrono::Utc::now().timestamp() - 3 * 86_400,
+                        age_days: 3,
+                    }],
+                )
+                .unwrap();
+        }
+
+        let result = server
+            .codegraph_node(Parameters(NodeParams {
+                symbol: "target".to_string(),
+                include_relations: None,
+                detail_level: None,
+            }))
+            .await;
+        let json: serde_json::Value = serde_json::from_str(&result).unwrap();
+        assert_eq!(json["git"]["lastCommit"].as_str().unwrap(), "deadbeef");
+        assert_eq!(json["git"]["author"].as_str().unwrap(), "Alice");
+        assert_eq!(json["git"]["ageDays"].as_i64().unwrap(), 3);
+    }
+
     #[tokio::test]
     async fn node_not_found_with_suggestions() {
         let server = setup_server();
@@ -1647,7 +4250,7 @@ mod tests {
     // -- codegraph_dead_code --------------------------------------------------
 
     #[tokio::test]
-    async fn dead_code_finds_unreferenced_symbols() {
+    async fn dead_code_finds_unreachable_symbols() {
         let server = setup_server();
         {
             let store = server.store.lock().unwrap();
@@ -1667,6 +4270,7 @@ mod tests {
             .codegraph_dead_code(Parameters(DeadCodeParams {
                 kinds: None,
                 include_exported: None,
+                history: None,
             }))
             .await;
         let json: serde_json::Value = serde_json::from_str(&result).unwrap();
@@ -1685,7 +4289,13 @@ mod tests {
             .collect();
         assert!(all_names.contains(&"unusedFunc"));
         assert!(all_names.contains(&"caller"));
-        assert!(!all_names.contains(&"usedFunc"));
+        // Called, but only by dead code.
+        let used = files
+            .iter()
+            .flat_map(|f| f["symbols"].as_array().unwrap())
+            .find(|s| s["name"] == "usedFunc")
+            .unwrap();
+        assert_eq!(used["referencedBy"], serde_json::json!(["caller"]));
     }
 
     #[tokio::test]
@@ -1705,6 +4315,7 @@ mod tests {
             .codegraph_dead_code(Parameters(DeadCodeParams {
                 kinds: Some("function".to_string()),
                 include_exported: None,
+                history: Some(true),
             }))
             .await;
         let json: serde_json::Value = serde_json::from_str(&result).unwrap();
@@ -1713,6 +4324,8 @@ mod tests {
         let files = json["files"].as_array().unwrap();
         let name = files[0]["symbols"][0]["name"].as_str().unwrap();
         assert_eq!(name, "unusedFunc");
+        assert_eq!(json["historyChecked"], 1);
+        assert_eq!(files[0]["symbols"][0]["history"]["status"], "never_used");
     }
 
     #[tokio::test]
@@ -1722,6 +4335,7 @@ mod tests {
             .codegraph_dead_code(Parameters(DeadCodeParams {
                 kinds: None,
                 include_exported: None,
+                history: None,
             }))
             .await;
         let json: serde_json::Value = serde_json::from_str(&result).unwrap();
@@ -1730,50 +4344,328 @@ mod tests {
         assert!(json["message"].as_str().is_some());
     }
 
-    // -- codegraph_frameworks -------------------------------------------------
-
     #[tokio::test]
-    async fn frameworks_with_explicit_dir() {
-        let dir = tempfile::tempdir().unwrap();
-        std::fs::write(
-            dir.path().join("package.json"),
-            r#"{"dependencies": {"react": "^18.0.0"}}"#,
-        )
-        .unwrap();
-
+    async fn dead_code_keeps_finding_ids_across_runs() {
         let server = setup_server();
-        let result = server
-            .codegraph_frameworks(Parameters(FrameworksParams {
-                project_dir: Some(dir.path().to_str().unwrap().to_string()),
-            }))
-            .await;
-        let json: serde_json::Value = serde_json::from_str(&result).unwrap();
+        {
+            let store = server.store.lock().unwrap();
+            store
+                .upsert_nodes(&[
+                    make_node("n1", "unusedA", "src/a.ts", NodeKind::Function, 1, None),
+                    make_node("n2", "unusedB", "src/b.ts", NodeKind::Function, 1, None),
+                ])
+                .unwrap();
+        }
+        let run = || async {
+            let result = server
+                .codegraph_dead_code(Parameters(DeadCodeParams {
+                    kinds: None,
+                    include_exported: None,
+                    history: None,
+                }))
+                .await;
+            serde_json::from_str::<serde_json::Value>(&result).unwrap()
+        };
 
-        assert_eq!(json["frameworkCount"].as_u64().unwrap(), 1);
-        let frameworks = json["frameworks"].as_array().unwrap();
-        assert_eq!(frameworks[0]["name"].as_str().unwrap(), "React");
-        assert_eq!(frameworks[0]["language"].as_str().unwrap(), "javascript");
-        assert_eq!(frameworks[0]["category"].as_str().unwrap(), "web");
+        let first = run().await;
+        assert_eq!(first["newCount"], 2);
+        let id = first["files"][0]["symbols"][0]["findingId"].clone();
+        assert!(id.is_string());
+
+        // unusedB gains a caller, so only unusedA remains and keeps its ID.
+        {
+            let store = server.store.lock().unwrap();
+            store
+                .upsert_nodes(&[make_node(
+                    "n3",
+                    "main",
+                    "src/b.ts",
+                    NodeKind::Function,
+                    5,
+                    None,
+                )])
+                .unwrap();
+            store
+                .upsert_edge(&make_edge("n3", "n2", EdgeKind::Calls, "src/b.ts", 6))
+                .unwrap();
+        }
+        let second = run().await;
+        let symbol = &second["files"][0]["symbols"][0];
+        assert_eq!(symbol["name"], "unusedA");
+        assert_eq!(symbol["findingId"], id);
+        assert_eq!(symbol["new"], false);
+        assert_eq!(second["resolvedCount"], 1);
     }
 
+    // -- codegraph_security_summary -------------------------------------------
+
     #[tokio::test]
-    async fn frameworks_empty_project() {
-        let dir = tempfile::tempdir().unwrap();
-        let server = setup_server();
-        let result = server
-            .codegraph_frameworks(Parameters(FrameworksParams {
-                project_dir: Some(dir.path().to_str().unwrap().to_string()),
-            }))
-            .await;
-        let json: serde_json::Value = serde_json::from_str(&result).unwrap();
+    async fn security_summary_merges_ranks_and_tracks_directory_risk() {
+        let tmp = tempfile::tempdir().unwrap();
+        std::fs::create_dir(tmp.path().join("src")).unwrap();
+        std::fs::write(
+            tmp.path().join("src/app.py"),
+            "username = request.args.get('name')\nquery = \"SELECT * FROM users WHERE name = '\" + username + \"'\"\ncursor.execute(query)\n",
+        )
+        .unwrap();
+        let server = CodeGraphServer::with_project_root(
+            GraphStore::from_connection(initialize_database(":memory:").unwrap()),
+            tmp.path().into(),
+        );
+        let summary = || async {
+            let result = server
+                .codegraph_security_summary(Parameters(OptionalDirParams { directory: None }))
+                .await;
+            serde_json::from_str::<serde_json::Value>(&result).unwrap()
+        };
 
-        assert_eq!(json["frameworkCount"].as_u64().unwrap(), 0);
-        assert!(json["message"].as_str().is_some());
+        let first = summary().await;
+        assert!(first["taintFlows"].as_u64().unwrap() >= 1);
+        let top = &first["findings"][0];
+        assert_eq!(top["taintConfirmed"], true);
+        assert!(top["ruleIds"]
+            .as_array()
+            .unwrap()
+            .contains(&serde_json::json!("TAINT-SQL-INJECTION")));
+        assert_eq!(top["rating"], "critical");
+        assert_eq!(first["riskLevel"], "CRITICAL");
+        assert_eq!(first["directories"][0]["directory"], "src");
+        assert_eq!(first["directories"][0]["trend"], "new");
+
+        std::fs::write(tmp.path().join("src/app.py"), "print('fixed')\n").unwrap();
+        let second = summary().await;
+        assert_eq!(second["uniqueFindings"], 0);
+        assert_eq!(second["directories"][0]["directory"], "src");
+        assert_eq!(second["directories"][0]["trend"], "down");
+        assert_eq!(
+            second["directories"][0]["previousScore"],
+            first["directories"][0]["score"]
+        );
     }
 
+    // -- codegraph_scan_security ----------------------------------------------
+
     #[tokio::test]
-    async fn frameworks_no_dir_with_empty_store() {
-        let server = setup_server();
+    async fn scan_security_reports_stable_findings() {
+        let tmp = tempfile::tempdir().unwrap();
+        std::fs::create_dir(tmp.path().join("src")).unwrap();
+        std::fs::write(
+            tmp.path().join("src/run.js"),
+            "function run(code) {\n  return eval(code);\n}\n",
+        )
+        .unwrap();
+        let server = CodeGraphServer::with_project_root(
+            GraphStore::from_connection(initialize_database(":memory:").unwrap()),
+            tmp.path().into(),
+        );
+        let scan = || async {
+            let result = server
+                .codegraph_scan_security(Parameters(ScanSecurityParams {
+                    directory: None,
+                    exclude_tests: None,
+                    include_baselined: None,
+                }))
+                .await;
+            serde_json::from_str::<serde_json::Value>(&result).unwrap()
+        };
+
+        let first = scan().await;
+        assert!(first["totalFindings"].as_u64().unwrap() >= 1);
+        assert!(first["newFindings"].as_u64().unwrap() >= 1);
+        assert_eq!(first["findings"][0]["new"], true);
+
+        // A blank line above shifts the finding; it is still the same one.
+        std::fs::write(
+            tmp.path().join("src/run.js"),
+            "\nfunction run(code) {\n  return eval(code);\n}\n",
+        )
+        .unwrap();
+        let second = scan().await;
+        assert_eq!(second["newFindings"], 0);
+        assert_eq!(
+            second["findings"][0]["findingId"],
+            first["findings"][0]["findingId"]
+        );
+        assert_eq!(second["findings"][0]["new"], false);
+        assert_eq!(
+            second["findings"][0]["firstSeen"],
+            first["findings"][0]["firstSeen"]
+        );
+    }
+
+    // -- codegraph_audit_dependencies -----------------------------------------
+
+    #[tokio::test]
+    async fn audit_dependencies_offline_reports_cached_vulns_with_importers() {
+        use crate::resolution::deps_audit::{CachedQuery, OsvCache, Vulnerability};
+
+        let tmp = tempfile::tempdir().unwrap();
+        std::fs::write(
+            tmp.path().join("package-lock.json"),
+            r#"{"packages": {"node_modules/lodash": {"version": "4.17.20"}, "node_modules/react": {"version": "18.2.0"}}}"#,
+        )
+        .unwrap();
+        let mut cache = OsvCache::default();
+        cache.queries.insert(
+            "npm:lodash@4.17.20".to_string(),
+            CachedQuery {
+                fetched_at: 0,
+                ids: vec!["GHSA-p6mc".to_string()],
+            },
+        );
+        cache.vulns.insert(
+            "GHSA-p6mc".to_string(),
+            Vulnerability::from_osv(&serde_json::json!({
+                "id": "GHSA-p6mc",
+                "summary": "Prototype pollution in lodash",
+                "database_specific": {"severity": "HIGH"},
+                "affected": [{
+                    "package": {"ecosystem": "npm", "name": "lodash"},
+                    "ranges": [{"type": "SEMVER", "events": [{"introduced": "0"}, {"fixed": "4.17.21"}]}],
+                }],
+            }))
+            .unwrap(),
+        );
+        cache.save(tmp.path()).unwrap();
+
+        let server = CodeGraphServer::with_project_root(
+            GraphStore::from_connection(initialize_database(":memory:").unwrap()),
+            tmp.path().into(),
+        );
+        {
+            let store = server.store.lock().unwrap();
+            let mut edge = make_edge(
+                "file:src/app.ts",
+                "module:lodash/merge",
+                EdgeKind::Imports,
+                "src/app.ts",
+                3,
+            );
+            edge.metadata = Some(HashMap::from([("names".to_string(), "merge".to_string())]));
+            store.upsert_edges(&[edge]).unwrap();
+        }
+
+        let result = server
+            .codegraph_audit_dependencies(Parameters(AuditDependenciesParams {
+                offline: Some(true),
+                max_cache_age_hours: None,
+            }))
+            .await;
+        let json: serde_json::Value = serde_json::from_str(&result).unwrap();
+        assert_eq!(json["packagesScanned"], 2);
+        assert_eq!(json["unchecked"], 1);
+        assert_eq!(json["vulnerablePackageCount"], 1);
+        let lodash = &json["vulnerablePackages"][0];
+        assert_eq!(lodash["name"], "lodash");
+        assert_eq!(lodash["vulnerabilities"][0]["severity"], "HIGH");
+        assert_eq!(lodash["vulnerabilities"][0]["fixedIn"][0], "4.17.21");
+        assert_eq!(lodash["importedBy"][0]["file"], "src/app.ts");
+        assert_eq!(lodash["importedBy"][0]["names"][0], "merge");
+    }
+
+    // -- codegraph_find_secrets -----------------------------------------------
+
+    #[tokio::test]
+    async fn find_secrets_scans_indexed_files_with_allowlist() {
+        let tmp = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(tmp.path().join("src")).unwrap();
+        std::fs::create_dir_all(tmp.path().join("fixtures")).unwrap();
+        let leaky = "const dbPassword = \"hunter2hunter2\";\nconst signing = \"q8Zr2LmX0vT7nKp4Wb9sYc1Hd6Fj3\";\n";
+        std::fs::write(tmp.path().join("src/config.ts"), leaky).unwrap();
+        std::fs::write(tmp.path().join("fixtures/keys.ts"), leaky).unwrap();
+        std::fs::write(tmp.path().join("unindexed.ts"), leaky).unwrap();
+
+        let mut config = CodeGraphConfig::default();
+        config.secrets.allowlist = HashMap::from([("fixtures".to_string(), vec!["*".to_string()])]);
+        let server = CodeGraphServer::with_config(
+            GraphStore::from_connection(initialize_database(":memory:").unwrap()),
+            tmp.path().into(),
+            config,
+        );
+        {
+            let store = server.store.lock().unwrap();
+            store
+                .upsert_nodes(&[
+                    make_node("a", "a", "src/config.ts", NodeKind::Variable, 1, None),
+                    make_node("b", "b", "fixtures/keys.ts", NodeKind::Variable, 1, None),
+                ])
+                .unwrap();
+        }
+        let find = |min_severity: Option<&str>| {
+            let server = server.clone();
+            let min_severity = min_severity.map(str::to_string);
+            async move {
+                let text = server
+                    .codegraph_find_secrets(Parameters(FindSecretsParams {
+                        path: None,
+                        include_tests: None,
+                        min_severity,
+                        limit: None,
+                    }))
+                    .await;
+                serde_json::from_str::<serde_json::Value>(&text).unwrap()
+            }
+        };
+
+        let all = find(None).await;
+        assert_eq!(all["filesScanned"], 2);
+        assert_eq!(all["totalFindings"], 2);
+        assert_eq!(all["allowlisted"], 2);
+        assert_eq!(all["findings"][0]["file"], "src/config.ts");
+        assert_eq!(all["findings"][0]["ruleId"], "OWASP-A07-001");
+        assert_eq!(all["findings"][0]["severity"], "Critical");
+        assert!(!all.to_string().contains("hunter2hunter2"));
+        assert_eq!(all["findings"][1]["ruleId"], "SECRET-ENTROPY");
+
+        let high = find(Some("high")).await;
+        assert_eq!(high["totalFindings"], 1);
+        assert!(find(Some("severe")).await["error"].is_string());
+    }
+
+    // -- codegraph_frameworks -------------------------------------------------
+
+    #[tokio::test]
+    async fn frameworks_with_explicit_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("package.json"),
+            r#"{"dependencies": {"react": "^18.0.0"}}"#,
+        )
+        .unwrap();
+
+        let server = setup_server();
+        let result = server
+            .codegraph_frameworks(Parameters(FrameworksParams {
+                project_dir: Some(dir.path().to_str().unwrap().to_string()),
+            }))
+            .await;
+        let json: serde_json::Value = serde_json::from_str(&result).unwrap();
+
+        assert_eq!(json["frameworkCount"].as_u64().unwrap(), 1);
+        let frameworks = json["frameworks"].as_array().unwrap();
+        assert_eq!(frameworks[0]["name"].as_str().unwrap(), "React");
+        assert_eq!(frameworks[0]["language"].as_str().unwrap(), "javascript");
+        assert_eq!(frameworks[0]["category"].as_str().unwrap(), "web");
+    }
+
+    #[tokio::test]
+    async fn frameworks_empty_project() {
+        let dir = tempfile::tempdir().unwrap();
+        let server = setup_server();
+        let result = server
+            .codegraph_frameworks(Parameters(FrameworksParams {
+                project_dir: Some(dir.path().to_str().unwrap().to_string()),
+            }))
+            .await;
+        let json: serde_json::Value = serde_json::from_str(&result).unwrap();
+
+        assert_eq!(json["frameworkCount"].as_u64().unwrap(), 0);
+        assert!(json["message"].as_str().is_some());
+    }
+
+    #[tokio::test]
+    async fn frameworks_no_dir_with_empty_store() {
+        let server = setup_server();
         let result = server
             .codegraph_frameworks(Parameters(FrameworksParams { project_dir: None }))
             .await;
@@ -1784,6 +4676,269 @@ mod tests {
         assert!(json["frameworkCount"].is_number());
     }
 
+    // -- codegraph_complete ---------------------------------------------------
+
+    #[tokio::test]
+    async fn complete_ranks_same_file_before_project() {
+        let server = setup_server();
+        {
+            let store = server.store.lock().unwrap();
+            store
+                .upsert_nodes(&[
+                    make_node("n1", "parseArgs", "src/a.ts", NodeKind::Function, 1, None),
+                    make_node(
+                        "n2",
+                        "parseConfig",
+                        "src/b.ts",
+                        NodeKind::Function,
+                        1,
+                        Some(true),
+                    ),
+                    make_node(
+                        "n3",
+                        "render",
+                        "src/b.ts",
+                        NodeKind::Function,
+                        20,
+                        Some(true),
+                    ),
+                ])
+                .unwrap();
+        }
+        let result = server
+            .codegraph_complete(Parameters(CompleteParams {
+                file_path: "./src/a.ts".to_string(),
+                line: 30,
+                prefix: "parse".to_string(),
+                limit: None,
+            }))
+            .await;
+        let json: serde_json::Value = serde_json::from_str(&result).unwrap();
+        assert_eq!(json["filePath"], "src/a.ts");
+        assert_eq!(json["count"], 2);
+        assert_eq!(json["completions"][0]["name"], "parseArgs");
+        assert_eq!(json["completions"][0]["source"], "file");
+        assert_eq!(json["completions"][1]["name"], "parseConfig");
+        assert_eq!(json["completions"][1]["source"], "project");
+        assert!(json["completions"][1]["signature"].is_string());
+    }
+
+    // -- codegraph_examples ---------------------------------------------------
+
+    #[tokio::test]
+    async fn api_surface_groups_exports_by_module() {
+        let server = setup_server();
+        {
+            let store = server.store.lock().unwrap();
+            let mut old = make_node("o", "oldApi", "sdk/api.ts", NodeKind::Function, 1, None);
+            old.exported = Some(true);
+            old.documentation = Some("@deprecated use newApi".to_string());
+            let mut new = make_node("n", "newApi", "sdk/api.ts", NodeKind::Function, 5, None);
+            new.exported = Some(true);
+            let mut app = make_node("a", "main", "app/main.ts", NodeKind::Function, 1, None);
+            app.exported = Some(true);
+            store.upsert_nodes(&[old, new, app]).unwrap();
+        }
+        let result = server
+            .codegraph_api_surface(Parameters(ApiSurfaceParams {
+                path: Some("sdk/".to_string()),
+                max_examples: None,
+            }))
+            .await;
+        let json: serde_json::Value = serde_json::from_str(&result).unwrap();
+        assert_eq!(json["moduleCount"], 1);
+        assert_eq!(json["symbolCount"], 2);
+        assert_eq!(json["deprecatedCount"], 1);
+        let symbols = &json["modules"][0]["symbols"];
+        assert_eq!(symbols[0]["stability"]["level"], "deprecated");
+        assert_eq!(symbols[0]["stability"]["note"], "use newApi");
+        assert!(symbols[1].get("stability").is_none());
+    }
+
+    #[tokio::test]
+    async fn callgraph_export_returns_the_scoped_subgraph() {
+        let server = setup_server();
+        {
+            let store = server.store.lock().unwrap();
+            store
+                .upsert_nodes(&[
+                    make_node("a", "handle", "src/a.ts", NodeKind::Function, 1, None),
+                    make_node("b", "validate", "src/b.ts", NodeKind::Function, 1, None),
+                    make_node("c", "save", "src/c.ts", NodeKind::Function, 1, None),
+                    make_node("d", "route", "src/d.ts", NodeKind::Function, 1, None),
+                ])
+                .unwrap();
+            store
+                .upsert_edges(&[
+                    make_edge("a", "b", EdgeKind::Calls, "src/a.ts", 2),
+                    make_edge("b", "c", EdgeKind::Calls, "src/b.ts", 2),
+                    make_edge("d", "a", EdgeKind::Calls, "src/d.ts", 2),
+                    make_edge("a", "c", EdgeKind::References, "src/a.ts", 3),
+                ])
+                .unwrap();
+        }
+        let export = |direction: Option<&str>, edge_kinds: Option<Vec<String>>| {
+            server.codegraph_callgraph_export(Parameters(CallgraphExportParams {
+                symbol: "handle".to_string(),
+                direction: direction.map(str::to_string),
+                depth: None,
+                edge_kinds,
+                max_nodes: None,
+            }))
+        };
+
+        let json: serde_json::Value = serde_json::from_str(&export(None, None).await).unwrap();
+        let ids: Vec<&str> = json["nodes"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|n| n["id"].as_str().unwrap())
+            .collect();
+        assert_eq!(ids, vec!["a", "b", "c"]);
+        assert_eq!(json["edgeCount"], 2);
+        assert_eq!(json["nodes"][0]["file_path"], "src/a.ts");
+        assert_eq!(
+            json["edges"][0],
+            serde_json::json!({"source": "a", "target": "b", "kind": "calls"})
+        );
+
+        let both: serde_json::Value = serde_json::from_str(
+            &export(
+                Some("both"),
+                Some(vec!["calls".to_string(), "references".to_string()]),
+            )
+            .await,
+        )
+        .unwrap();
+        assert_eq!(both["nodeCount"], 4);
+        assert_eq!(both["edgeCount"], 4);
+
+        let bad = export(Some("sideways"), None).await;
+        assert!(bad.contains("Unknown direction"));
+    }
+
+    #[tokio::test]
+    async fn rename_impact_returns_an_edit_plan() {
+        let tmp = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(tmp.path().join("src")).unwrap();
+        std::fs::write(tmp.path().join("src/config.ts"), "function load() {}\n").unwrap();
+        std::fs::write(
+            tmp.path().join("src/app.ts"),
+            "function main() {\n  load(); load();\n  registry[\"load\"]();\n}\n",
+        )
+        .unwrap();
+        let conn = initialize_database(":memory:").unwrap();
+        let server = CodeGraphServer::with_project_root(
+            GraphStore::from_connection(conn),
+            tmp.path().into(),
+        );
+        {
+            let store = server.store.lock().unwrap();
+            store
+                .upsert_nodes(&[
+                    make_node("load", "load", "src/config.ts", NodeKind::Function, 1, None),
+                    make_node("main", "main", "src/app.ts", NodeKind::Function, 1, None),
+                ])
+                .unwrap();
+            store
+                .upsert_edge(&make_edge("main", "load", EdgeKind::Calls, "src/app.ts", 2))
+                .unwrap();
+            store
+                .upsert_edge(&make_edge(
+                    "main",
+                    "load",
+                    EdgeKind::References,
+                    "src/app.ts",
+                    3,
+                ))
+                .unwrap();
+        }
+        let result = server
+            .codegraph_rename_impact(Parameters(RenameImpactParams {
+                symbol: "load".to_string(),
+                new_name: "loadConfig".to_string(),
+            }))
+            .await;
+        let json: serde_json::Value = serde_json::from_str(&result).unwrap();
+        assert_eq!(json["editCount"], 3);
+        assert_eq!(json["fileCount"], 2);
+        let app = &json["editPlan"][0];
+        assert_eq!(app["file"], "src/app.ts");
+        assert_eq!(app["edits"][0]["column"], 11);
+        assert_eq!(app["edits"][1]["column"], 3);
+        assert_eq!(app["edits"][0]["newText"], "loadConfig");
+        assert_eq!(json["unverified"][0]["line"], 3);
+        assert_eq!(json["unverified"][0]["reason"], "string_literal");
+
+        let invalid = server
+            .codegraph_rename_impact(Parameters(RenameImpactParams {
+                symbol: "load".to_string(),
+                new_name: "load config".to_string(),
+            }))
+            .await;
+        assert!(invalid.contains("not a valid identifier"));
+    }
+
+    #[tokio::test]
+    async fn examples_show_call_expression_and_snippet() {
+        let tmp = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(tmp.path().join("src")).unwrap();
+        std::fs::write(
+            tmp.path().join("src/app.ts"),
+            "function main() {\n  const cfg = load(\"app.yaml\", { strict: true });\n}\n",
+        )
+        .unwrap();
+        let conn = initialize_database(":memory:").unwrap();
+        let server = CodeGraphServer::with_project_root(
+            GraphStore::from_connection(conn),
+            tmp.path().into(),
+        );
+        {
+            let store = server.store.lock().unwrap();
+            store
+                .upsert_nodes(&[
+                    make_node("load", "load", "src/config.ts", NodeKind::Function, 1, None),
+                    make_node("main", "main", "src/app.ts", NodeKind::Function, 1, None),
+                ])
+                .unwrap();
+            store
+                .upsert_edge(&make_edge("main", "load", EdgeKind::Calls, "src/app.ts", 2))
+                .unwrap();
+        }
+        let result = server
+            .codegraph_examples(Parameters(ExamplesParams {
+                symbol: "load".to_string(),
+                limit: None,
+                context_lines: Some(0),
+            }))
+            .await;
+        let json: serde_json::Value = serde_json::from_str(&result).unwrap();
+        assert_eq!(json["exampleCount"], 1);
+        let example = &json["examples"][0];
+        assert_eq!(example["caller"], "main");
+        assert_eq!(example["call"], "load(\"app.yaml\", { strict: true })");
+        assert_eq!(
+            example["arguments"],
+            serde_json::json!(["\"app.yaml\"", "{ strict: true }"])
+        );
+        assert_eq!(example["snippetStartLine"], 2);
+        assert_eq!(
+            example["snippet"],
+            "  const cfg = load(\"app.yaml\", { strict: true });"
+        );
+
+        let result = server
+            .codegraph_examples(Parameters(ExamplesParams {
+                symbol: "main".to_string(),
+                limit: None,
+                context_lines: None,
+            }))
+            .await;
+        let json: serde_json::Value = serde_json::from_str(&result).unwrap();
+        assert_eq!(json["exampleCount"], 0);
+        assert!(json["message"].is_string());
+    }
+
     // -- codegraph_languages --------------------------------------------------
 
     #[tokio::test]
@@ -1848,6 +5003,46 @@ mod tests {
         assert_eq!(py["files"].as_u64().unwrap(), 1);
         assert_eq!(py["symbols"].as_u64().unwrap(), 1);
         assert_eq!(py["percentage"].as_str().unwrap(), "33.3%");
+        // No line counts stored for these files.
+        assert_eq!(py["lines"]["code"].as_u64().unwrap(), 0);
+        assert_eq!(json["totalLines"]["physical"].as_u64().unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn languages_reports_line_counts_and_symbol_size() {
+        let server = setup_server();
+        {
+            let store = server.store.lock().unwrap();
+            let mut node = make_node_with_lang(
+                "n1",
+                "foo",
+                "src/a.rs",
+                NodeKind::Function,
+                1,
+                Language::Rust,
+            );
+            node.end_line = 10;
+            store.upsert_node(&node).unwrap();
+            store
+                .conn
+                .execute(
+                    "INSERT INTO file_hashes (file_path, content_hash, language, total_lines,
+                       code_lines, comment_lines, blank_lines, logical_lines)
+                     VALUES ('src/a.rs', 'h', 'rust', 20, 12, 4, 4, 10)",
+                    [],
+                )
+                .unwrap();
+        }
+
+        let json: serde_json::Value =
+            serde_json::from_str(&server.codegraph_languages().await).unwrap();
+        let rust = &json["languages"][0];
+        assert_eq!(rust["lines"]["physical"], 20);
+        assert_eq!(rust["lines"]["code"], 12);
+        assert_eq!(rust["lines"]["logical"], 10);
+        assert_eq!(rust["lines"]["commentRatio"], 0.25);
+        assert_eq!(rust["avgSymbolLines"], 10.0);
+        assert_eq!(json["totalLines"]["blank"], 4);
     }
 
     #[tokio::test]
@@ -1987,22 +5182,575 @@ mod tests {
         assert_eq!(cycles[0]["size"].as_u64().unwrap(), 2);
     }
 
-    // -- codegraph_project_tree -------------------------------------------
+    // -- tasks -----------------------------------------------------------
 
-    #[tokio::test]
-    async fn project_tree_empty() {
-        let server = setup_server();
-        let result = server
-            .codegraph_project_tree(Parameters(MaxDepthParams { max_depth: None }))
-            .await;
-        let json: serde_json::Value = serde_json::from_str(&result).unwrap();
-        assert!(json["tree"].is_array() || json["message"].is_string() || json.is_object());
+    fn task_call(tool: &str, arguments: serde_json::Value) -> CallToolRequestParams {
+        CallToolRequestParams {
+            meta: None,
+            name: tool.to_string().into(),
+            arguments: arguments.as_object().cloned(),
+            task: Some(Default::default()),
+        }
     }
 
-    #[tokio::test]
-    async fn project_tree_with_files() {
-        let server = setup_server();
-        {
+    async fn finished_task(
+        server: &CodeGraphServer,
+        task_id: &str,
+    ) -> super::super::tasks::TaskEntry {
+        for _ in 0..200 {
+            let entry = server.tasks.get_task(task_id).unwrap();
+            if entry.task.status != rmcp::model::TaskStatus::Working {
+                return entry;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        panic!("task {task_id} did not finish");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn long_running_tools_run_as_tasks() {
+        use rmcp::model::TaskStatus;
+
+        let server = setup_server();
+        {
+            let store = server.store.lock().unwrap();
+            store
+                .upsert_nodes(&[
+                    make_node("n1", "main", "a.ts", NodeKind::Function, 1, None),
+                    make_node("n2", "util", "b.ts", NodeKind::Function, 1, None),
+                ])
+                .unwrap();
+            store
+                .upsert_edges(&[make_edge("n1", "n2", EdgeKind::Calls, "a.ts", 2)])
+                .unwrap();
+        }
+
+        let created = server
+            .start_task(task_call(
+                "codegraph_recompute_pagerank",
+                serde_json::json!({"top": 1}),
+            ))
+            .unwrap();
+        let entry = finished_task(&server, &created.task.task_id).await;
+        assert_eq!(entry.task.status, TaskStatus::Completed);
+        let result = super::super::tasks::make_task_result(
+            &server.tasks,
+            GetTaskResultParams {
+                meta: None,
+                task_id: created.task.task_id.clone(),
+            },
+        )
+        .unwrap();
+        let text = result.value["content"][0]["text"].as_str().unwrap();
+        let json: serde_json::Value = serde_json::from_str(text).unwrap();
+        assert_eq!(json["rankedNodes"], 2);
+        assert_eq!(json["top"][0]["name"], "util");
+
+        // Nothing to embed, so no model is loaded.
+        let created = server
+            .start_task(task_call(
+                "codegraph_backfill_embeddings",
+                serde_json::json!({"limit": 0}),
+            ))
+            .unwrap();
+        let entry = finished_task(&server, &created.task.task_id).await;
+        assert_eq!(entry.task.status, TaskStatus::Completed);
+
+        let created = server
+            .start_task(task_call(
+                "codegraph_recompute_pagerank",
+                serde_json::json!({"top": "ten"}),
+            ))
+            .unwrap();
+        let entry = finished_task(&server, &created.task.task_id).await;
+        assert_eq!(entry.task.status, TaskStatus::Failed);
+        assert!(entry.error.unwrap().starts_with("invalid arguments"));
+
+        let err = server
+            .start_task(task_call("codegraph_query", serde_json::json!({})))
+            .unwrap_err();
+        assert!(err.message.contains("cannot run as a task"));
+        assert_eq!(server.for_session().tasks.list_tasks(None).1, 0);
+    }
+
+    // -- resources -------------------------------------------------------
+
+    #[test]
+    fn templated_resources_read_from_the_index() {
+        use super::super::resources::ResourceRef;
+
+        let server = setup_server();
+        {
+            let store = server.store.lock().unwrap();
+            store
+                .upsert_nodes(&[make_node(
+                    "fn:src/a.ts:pay:1",
+                    "pay",
+                    "src/a.ts",
+                    NodeKind::Function,
+                    1,
+                    Some(true),
+                )])
+                .unwrap();
+        }
+        let read = |uri: &str| {
+            server.read_templated_resource(&ResourceRef::parse(uri).expect("template URI"))
+        };
+        let outline = read("codegraph://file/src/a.ts").unwrap();
+        let ResourceContents::TextResourceContents { text, uri, .. } = &outline.contents[0] else {
+            panic!("expected text contents");
+        };
+        assert_eq!(uri, "codegraph://file/src/a.ts");
+        assert!(text.contains("codegraph://symbol/fn%3Asrc%2Fa.ts%3Apay%3A1"));
+
+        let symbol = read("codegraph://symbol/fn%3Asrc%2Fa.ts%3Apay%3A1").unwrap();
+        let ResourceContents::TextResourceContents { text, .. } = &symbol.contents[0] else {
+            panic!("expected text contents");
+        };
+        assert!(text.contains("\"name\": \"pay\""));
+        assert!(read("codegraph://file/src/missing.ts").is_err());
+    }
+
+    #[test]
+    fn subscriptions_track_the_file_a_resource_depends_on() {
+        let server = setup_server();
+        {
+            let store = server.store.lock().unwrap();
+            store
+                .upsert_nodes(&[make_node(
+                    "fn:pay",
+                    "pay",
+                    "src/a.ts",
+                    NodeKind::Function,
+                    1,
+                    None,
+                )])
+                .unwrap();
+        }
+        assert_eq!(
+            server.subscription_file("codegraph://status").unwrap(),
+            None
+        );
+        assert_eq!(
+            server
+                .subscription_file("codegraph://symbol/fn%3Apay")
+                .unwrap()
+                .as_deref(),
+            Some("src/a.ts")
+        );
+        assert_eq!(
+            server
+                .subscription_file("codegraph://file/src/new.ts")
+                .unwrap()
+                .as_deref(),
+            Some("src/new.ts")
+        );
+        assert!(server.subscription_file("codegraph://symbol/gone").is_err());
+        assert!(server.subscription_file("codegraph://nope").is_err());
+
+        server.subscriptions.subscribe("codegraph://status", None);
+        assert!(server.for_session().subscriptions.is_empty());
+    }
+
+    // -- codegraph_summarize_module --------------------------------------
+
+    #[test]
+    fn module_summaries_are_cached_by_content() {
+        let server = setup_server();
+        {
+            let store = server.store.lock().unwrap();
+            store
+                .upsert_nodes(&[make_node(
+                    "fn:login",
+                    "login",
+                    "src/auth/login.ts",
+                    NodeKind::Function,
+                    1,
+                    Some(true),
+                )])
+                .unwrap();
+            store
+                .conn
+                .execute(
+                    "INSERT INTO file_hashes (file_path, content_hash, language) \
+                     VALUES ('src/auth/login.ts', 'h1', 'typescript')",
+                    [],
+                )
+                .unwrap();
+        }
+        let params = |refresh| SummarizeModuleParams {
+            path: "src/auth".to_string(),
+            max_symbols: None,
+            refresh: Some(refresh),
+        };
+        let (context, cached) = server.module_summary_context(&params(false)).unwrap();
+        assert!(cached.is_none());
+        assert!(context.prompt.contains("function login()"));
+
+        {
+            let store = server.store.lock().unwrap();
+            crate::db::summaries::save_summary(
+                &store.conn,
+                &context.directory,
+                &context.content_hash,
+                "Signs users in.",
+                Some("model"),
+            )
+            .unwrap();
+        }
+        let (_, cached) = server.module_summary_context(&params(false)).unwrap();
+        assert_eq!(cached.unwrap().summary, "Signs users in.");
+        let (_, cached) = server.module_summary_context(&params(true)).unwrap();
+        assert!(cached.is_none());
+
+        let missing = SummarizeModuleParams {
+            path: "src/none".to_string(),
+            max_symbols: None,
+            refresh: None,
+        };
+        assert!(server.module_summary_context(&missing).is_err());
+    }
+
+    // -- codegraph_architecture_check ------------------------------------
+
+    #[tokio::test]
+    async fn architecture_check_reports_layer_violations() {
+        use crate::config::schema::{ArchitectureConfig, CodeGraphConfig};
+        use crate::graph::architecture::LayerRule;
+
+        let config = CodeGraphConfig {
+            architecture: ArchitectureConfig {
+                layers: std::collections::HashMap::from([
+                    ("ui".to_string(), vec!["src/ui".to_string()]),
+                    ("db".to_string(), vec!["src/db".to_string()]),
+                ]),
+                rules: vec![LayerRule {
+                    from: "ui".to_string(),
+                    deny: vec!["db".to_string()],
+                    ..Default::default()
+                }],
+                edge_kinds: None,
+                coupling: Default::default(),
+            },
+            ..Default::default()
+        };
+        let conn = initialize_database(":memory:").unwrap();
+        let server =
+            CodeGraphServer::with_config(GraphStore::from_connection(conn), ".".into(), config);
+        {
+            let store = server.store.lock().unwrap();
+            store
+                .upsert_nodes(&[
+                    make_node(
+                        "n1",
+                        "render",
+                        "src/ui/page.ts",
+                        NodeKind::Function,
+                        1,
+                        None,
+                    ),
+                    make_node("n2", "query", "src/db/conn.ts", NodeKind::Function, 1, None),
+                ])
+                .unwrap();
+            store
+                .upsert_edges(&[
+                    make_edge("n1", "n2", EdgeKind::Calls, "src/ui/page.ts", 4),
+                    make_edge("n2", "n1", EdgeKind::Calls, "src/db/conn.ts", 2),
+                ])
+                .unwrap();
+        }
+        let result = server
+            .codegraph_architecture_check(Parameters(ArchitectureCheckParams {
+                layer: None,
+                limit: None,
+            }))
+            .await;
+        let json: serde_json::Value = serde_json::from_str(&result).unwrap();
+        assert_eq!(json["edgesChecked"], 2);
+        assert_eq!(json["violationCount"], 1, "{json}");
+        assert_eq!(json["violations"][0]["filePath"], "src/ui/page.ts");
+        assert_eq!(json["violations"][0]["line"], 4);
+        assert_eq!(json["byLayerPair"]["ui -> db"], 1);
+
+        let unconfigured = setup_server()
+            .codegraph_architecture_check(Parameters(ArchitectureCheckParams {
+                layer: None,
+                limit: None,
+            }))
+            .await;
+        assert!(unconfigured.contains("error"));
+    }
+
+    // -- codegraph_module_coupling ----------------------------------------
+
+    #[tokio::test]
+    async fn module_coupling_sorts_and_flags_modules() {
+        let server = setup_server();
+        {
+            let store = server.store.lock().unwrap();
+            store
+                .upsert_nodes(&[
+                    make_node("a", "a", "src/a/a.ts", NodeKind::Function, 1, None),
+                    make_node("b", "b", "src/b/b.ts", NodeKind::Function, 1, None),
+                    make_node("c", "c", "src/c/c.ts", NodeKind::Function, 1, None),
+                ])
+                .unwrap();
+            store
+                .upsert_edges(&[
+                    make_edge("a", "b", EdgeKind::Imports, "src/a/a.ts", 1),
+                    make_edge("a", "c", EdgeKind::Imports, "src/a/a.ts", 2),
+                    make_edge("b", "c", EdgeKind::Imports, "src/b/b.ts", 1),
+                ])
+                .unwrap();
+        }
+        let coupling = |sort_by: &str, flagged_only| {
+            let server = server.clone();
+            let sort_by = sort_by.to_string();
+            async move {
+                let text = server
+                    .codegraph_module_coupling(Parameters(ModuleCouplingParams {
+                        depth: None,
+                        path: Some("src".to_string()),
+                        sort_by: Some(sort_by),
+                        flagged_only: Some(flagged_only),
+                        limit: None,
+                    }))
+                    .await;
+                serde_json::from_str::<serde_json::Value>(&text).unwrap()
+            }
+        };
+
+        let by_efferent = coupling("efferent", false).await;
+        assert_eq!(by_efferent["moduleCount"], 3);
+        assert_eq!(by_efferent["modules"][0]["module"], "src/a");
+        assert_eq!(by_efferent["modules"][0]["efferent"], 2);
+        assert_eq!(by_efferent["modules"][2]["module"], "src/c");
+        assert_eq!(by_efferent["modules"][2]["afferent"], 2);
+
+        // Concrete modules that only others depend on sit far from the
+        // main sequence.
+        let flagged = coupling("distance", true).await;
+        assert_eq!(flagged["flaggedCount"], 1);
+        assert_eq!(flagged["modules"][0]["module"], "src/c");
+        assert_eq!(flagged["modules"][0]["distance"], 1.0);
+        assert_eq!(flagged["modules"][0]["exceeds"][0], "distance > 0.7");
+
+        assert!(coupling("size", false).await["error"].is_string());
+    }
+
+    #[tokio::test]
+    async fn entrypoints_list_mains_with_callee_trees() {
+        let server = setup_server();
+        {
+            let store = server.store.lock().unwrap();
+            store
+                .upsert_nodes(&[
+                    make_node("main", "main", "app/main.ts", NodeKind::Function, 1, None),
+                    make_node("run", "run", "app/run.ts", NodeKind::Function, 1, None),
+                    make_node("load", "load", "app/load.ts", NodeKind::Function, 1, None),
+                    make_node(
+                        "parse",
+                        "parse",
+                        "app/parse.ts",
+                        NodeKind::Function,
+                        1,
+                        None,
+                    ),
+                    make_node(
+                        "api",
+                        "api",
+                        "lib/index.ts",
+                        NodeKind::Function,
+                        1,
+                        Some(true),
+                    ),
+                ])
+                .unwrap();
+            store
+                .upsert_edges(&[
+                    make_edge("main", "run", EdgeKind::Calls, "app/main.ts", 2),
+                    make_edge("run", "load", EdgeKind::Calls, "app/run.ts", 2),
+                    make_edge("load", "parse", EdgeKind::Calls, "app/load.ts", 2),
+                    make_edge("load", "main", EdgeKind::Calls, "app/load.ts", 3),
+                ])
+                .unwrap();
+        }
+        let entrypoints = |kind: Option<&str>| {
+            let server = server.clone();
+            let kind = kind.map(str::to_string);
+            async move {
+                let text = server
+                    .codegraph_entrypoints(Parameters(EntrypointsParams {
+                        kind,
+                        path: None,
+                        depth: None,
+                        limit: None,
+                    }))
+                    .await;
+                serde_json::from_str::<serde_json::Value>(&text).unwrap()
+            }
+        };
+
+        let all = entrypoints(None).await;
+        assert_eq!(all["entryPointCount"], 2);
+        assert_eq!(
+            all["byKind"],
+            serde_json::json!({"main": 1, "package_export": 1})
+        );
+
+        let mains = entrypoints(Some("main")).await;
+        let main = &mains["entryPoints"][0];
+        assert_eq!(main["name"], "main");
+        assert_eq!(main["callees"][0]["name"], "run");
+        // Two levels by default, and no way back into the entry point
+        assert_eq!(main["callees"][0]["callees"][0]["name"], "load");
+        assert!(main["callees"][0]["callees"][0].get("callees").is_none());
+
+        let bad = entrypoints(Some("cron")).await;
+        assert!(bad["error"].as_str().unwrap().contains("Unknown kind"));
+    }
+
+    #[tokio::test]
+    async fn routes_list_method_path_and_handler() {
+        let server = setup_server();
+        {
+            let store = server.store.lock().unwrap();
+            let route = |id: &str, name: &str, line: u32| CodeNode {
+                qualified_name: Some(format!("express {name}")),
+                ..make_node(id, name, "src/app.ts", NodeKind::Route, line, None)
+            };
+            store
+                .upsert_nodes(&[
+                    route("r1", "POST /users", 3),
+                    route("r2", "GET /users", 2),
+                    route("r3", "GET /health", 4),
+                    make_node(
+                        "h",
+                        "listUsers",
+                        "src/users.ts",
+                        NodeKind::Function,
+                        1,
+                        None,
+                    ),
+                ])
+                .unwrap();
+            store
+                .upsert_edges(&[
+                    make_edge("r2", "h", EdgeKind::Handles, "src/app.ts", 2),
+                    make_edge(
+                        "r1",
+                        "unresolved:createUser",
+                        EdgeKind::Handles,
+                        "src/app.ts",
+                        3,
+                    ),
+                ])
+                .unwrap();
+        }
+        let routes = |method: Option<&str>, path: Option<&str>| {
+            let server = server.clone();
+            let method = method.map(str::to_string);
+            let path = path.map(str::to_string);
+            async move {
+                let text = server
+                    .codegraph_routes(Parameters(RoutesParams {
+                        method,
+                        path,
+                        framework: None,
+                        limit: None,
+                    }))
+                    .await;
+                serde_json::from_str::<serde_json::Value>(&text).unwrap()
+            }
+        };
+
+        let all = routes(None, None).await;
+        assert_eq!(all["routeCount"], 3);
+        assert_eq!(all["byMethod"], serde_json::json!({"GET": 2, "POST": 1}));
+        assert_eq!(all["routes"][0]["path"], "/health");
+        assert!(all["routes"][0]["handler"].is_null());
+
+        let users = routes(None, Some("/users")).await;
+        let get = &users["routes"][0];
+        assert_eq!(get["method"], "GET");
+        assert_eq!(get["framework"], "express");
+        assert_eq!(get["handler"]["name"], "listUsers");
+        assert_eq!(get["handler"]["file"], "src/users.ts");
+        assert_eq!(users["routes"][1]["handler"]["name"], "createUser");
+        assert_eq!(users["routes"][1]["handler"]["unresolved"], true);
+
+        let posts = routes(Some("post"), None).await;
+        assert_eq!(posts["routeCount"], 1);
+    }
+
+    #[tokio::test]
+    async fn find_injections_follows_calls_without_source() {
+        let server = setup_server();
+        {
+            let mut handler =
+                make_node("h", "handler", "src/routes.js", NodeKind::Function, 1, None);
+            handler.body = Some(
+                "function handler(req, res) {\n  const id = req.query.id;\n  lookup(id);\n}"
+                    .to_string(),
+            );
+            let mut lookup = make_node("l", "lookup", "src/db.js", NodeKind::Function, 1, None);
+            lookup.body = Some(
+                "function lookup(key) {\n  db.query(\"SELECT * FROM t WHERE id = \" + key);\n}"
+                    .to_string(),
+            );
+            let store = server.store.lock().unwrap();
+            store.upsert_nodes(&[handler, lookup]).unwrap();
+            store
+                .upsert_edges(&[make_edge("h", "l", EdgeKind::Calls, "src/routes.js", 3)])
+                .unwrap();
+        }
+        let find = |source: Option<&str>, language: Option<&str>| {
+            let server = server.clone();
+            let params = FindInjectionsParams {
+                source: source.map(str::to_string),
+                language: language.map(str::to_string),
+                path: None,
+                max_hops: None,
+                limit: None,
+            };
+            async move {
+                let text = server.codegraph_find_injections(Parameters(params)).await;
+                serde_json::from_str::<serde_json::Value>(&text).unwrap()
+            }
+        };
+
+        let graph = find(None, None).await;
+        assert_eq!(graph["vulnerabilityCount"], 1);
+        assert_eq!(graph["crossFunction"], 1);
+        let flow = &graph["flows"][0];
+        assert_eq!(flow["type"], "SQL Injection");
+        assert_eq!(flow["source"]["file"], "src/routes.js");
+        assert_eq!(flow["sink"]["file"], "src/db.js");
+        assert_eq!(flow["sink"]["line"], 2);
+        assert_eq!(flow["hops"], 1);
+
+        let text = find(Some("x = req.query.id\ndb.query(x)"), Some("javascript")).await;
+        assert_eq!(text["vulnerabilityCount"], 1);
+        let missing = find(Some("x = 1"), None).await;
+        assert!(missing["error"].as_str().unwrap().contains("language"));
+    }
+
+    // -- codegraph_project_tree -------------------------------------------
+
+    #[tokio::test]
+    async fn project_tree_empty() {
+        let server = setup_server();
+        let result = server
+            .codegraph_project_tree(Parameters(MaxDepthParams { max_depth: None }))
+            .await;
+        let json: serde_json::Value = serde_json::from_str(&result).unwrap();
+        assert!(json["tree"].is_array() || json["message"].is_string() || json.is_object());
+    }
+
+    #[tokio::test]
+    async fn project_tree_with_files() {
+        let server = setup_server();
+        {
             let store = server.store.lock().unwrap();
             store
                 .upsert_nodes(&[
@@ -2110,23 +5858,435 @@ mod tests {
         assert!(json.is_object());
     }
 
-    // -- codegraph_find_path ----------------------------------------------
+    // -- codegraph_config_usage -----------------------------------------------
 
     #[tokio::test]
-    async fn find_path_existing() {
+    async fn config_usage_links_key_to_readers() {
+        use crate::resolution::config_keys::{ConfigFormat, ConfigKey, ConfigKeyRef};
         let server = setup_server();
         {
             let store = server.store.lock().unwrap();
             store
-                .upsert_nodes(&[
-                    make_node("n1", "start", "src/a.ts", NodeKind::Function, 1, None),
-                    make_node("n2", "middle", "src/b.ts", NodeKind::Function, 1, None),
-                    make_node("n3", "end", "src/c.ts", NodeKind::Function, 1, None),
-                ])
+                .upsert_nodes(&[make_node(
+                    "n1",
+                    "connect",
+                    "src/db.ts",
+                    NodeKind::Function,
+                    1,
+                    None,
+                )])
                 .unwrap();
             store
-                .upsert_edges(&[
-                    make_edge("n1", "n2", EdgeKind::Calls, "src/a.ts", 5),
+                .replace_config_keys(&[ConfigKey {
+                    key: "database.host".to_string(),
+                    file_path: "config.yaml".to_string(),
+                    line: 2,
+                    format: ConfigFormat::Yaml,
+                }])
+                .unwrap();
+            store
+                .replace_config_refs_for_file(
+                    "src/db.ts",
+                    &[ConfigKeyRef {
+                        key: "database.host".to_string(),
+                        node_id: "n1".to_string(),
+                        file_path: "src/db.ts".to_string(),
+                        line: 3,
+                    }],
+                )
+                .unwrap();
+        }
+
+        let result = server
+            .codegraph_config_usage(Parameters(ConfigUsageParams {
+                key: Some("database.host".to_string()),
+                file_path: None,
+            }))
+            .await;
+        let json: serde_json::Value = serde_json::from_str(&result).unwrap();
+        assert_eq!(json["usageCount"].as_u64().unwrap(), 1);
+        assert_eq!(json["usages"][0]["symbol"].as_str().unwrap(), "connect");
+        assert_eq!(
+            json["definitions"][0]["file"].as_str().unwrap(),
+            "config.yaml"
+        );
+
+        let listing = server
+            .codegraph_config_usage(Parameters(ConfigUsageParams {
+                key: None,
+                file_path: None,
+            }))
+            .await;
+        let json: serde_json::Value = serde_json::from_str(&listing).unwrap();
+        assert_eq!(json["keys"][0]["readCount"].as_u64().unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn config_usage_unknown_key() {
+        let server = setup_server();
+        let result = server
+            .codegraph_config_usage(Parameters(ConfigUsageParams {
+                key: Some("missing.key".to_string()),
+                file_path: None,
+            }))
+            .await;
+        let json: serde_json::Value = serde_json::from_str(&result).unwrap();
+        assert!(json["error"].as_str().unwrap().contains("missing.key"));
+    }
+
+    // -- codegraph_field_usages -----------------------------------------------
+
+    #[tokio::test]
+    async fn field_usages_splits_readers_and_writers() {
+        let server = setup_server();
+        {
+            let store = server.store.lock().unwrap();
+            let mut field = make_node("f1", "status", "src/order.ts", NodeKind::Property, 2, None);
+            field.qualified_name = Some("Order.status".to_string());
+            store
+                .upsert_nodes(&[
+                    field,
+                    make_node("m1", "ship", "src/order.ts", NodeKind::Method, 5, None),
+                    make_node("m2", "describe", "src/order.ts", NodeKind::Method, 9, None),
+                ])
+                .unwrap();
+            store
+                .upsert_edges(&[
+                    make_edge("m1", "f1", EdgeKind::Writes, "src/order.ts", 6),
+                    make_edge("m2", "f1", EdgeKind::Reads, "src/order.ts", 10),
+                ])
+                .unwrap();
+        }
+
+        let result = server
+            .codegraph_field_usages(Parameters(FieldUsagesParams {
+                field: "Order.status".to_string(),
+                access: None,
+            }))
+            .await;
+        let json: serde_json::Value = serde_json::from_str(&result).unwrap();
+        assert_eq!(json["matchCount"].as_u64().unwrap(), 1);
+        let f = &json["fields"][0];
+        assert_eq!(f["writers"][0]["symbol"].as_str().unwrap(), "ship");
+        assert_eq!(f["readers"][0]["symbol"].as_str().unwrap(), "describe");
+
+        let writes_only = server
+            .codegraph_field_usages(Parameters(FieldUsagesParams {
+                field: "status".to_string(),
+                access: Some("write".to_string()),
+            }))
+            .await;
+        let json: serde_json::Value = serde_json::from_str(&writes_only).unwrap();
+        assert_eq!(json["fields"][0]["writerCount"].as_u64().unwrap(), 1);
+        assert_eq!(json["fields"][0]["readerCount"].as_u64().unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn field_usages_unknown_field() {
+        let server = setup_server();
+        let result = server
+            .codegraph_field_usages(Parameters(FieldUsagesParams {
+                field: "Nope.missing".to_string(),
+                access: None,
+            }))
+            .await;
+        let json: serde_json::Value = serde_json::from_str(&result).unwrap();
+        assert!(json["error"].as_str().unwrap().contains("not found"));
+    }
+
+    // -- codegraph_structure --------------------------------------------------
+
+    #[tokio::test]
+    async fn structure_rolls_up_monorepo_projects() {
+        let tmp = tempfile::tempdir().unwrap();
+        for (dir, name) in [("packages/web", "web"), ("packages/api", "api")] {
+            std::fs::create_dir_all(tmp.path().join(dir)).unwrap();
+            std::fs::write(
+                tmp.path().join(dir).join("package.json"),
+                format!("{{\"name\": \"{name}\"}}"),
+            )
+            .unwrap();
+        }
+        let conn = initialize_database(":memory:").unwrap();
+        let server = CodeGraphServer::with_project_root(
+            GraphStore::from_connection(conn),
+            tmp.path().into(),
+        );
+        {
+            let store = server.store.lock().unwrap();
+            store
+                .upsert_nodes(&[
+                    make_node(
+                        "w1",
+                        "render",
+                        "packages/web/app.ts",
+                        NodeKind::Function,
+                        1,
+                        None,
+                    ),
+                    make_node(
+                        "a1",
+                        "fetchUser",
+                        "packages/api/user.ts",
+                        NodeKind::Function,
+                        1,
+                        None,
+                    ),
+                    make_node(
+                        "a2",
+                        "query",
+                        "packages/api/db.ts",
+                        NodeKind::Function,
+                        1,
+                        None,
+                    ),
+                ])
+                .unwrap();
+            store
+                .upsert_edges(&[
+                    make_edge("w1", "a1", EdgeKind::Calls, "packages/web/app.ts", 2),
+                    make_edge("a1", "a2", EdgeKind::Calls, "packages/api/user.ts", 2),
+                ])
+                .unwrap();
+        }
+
+        let result = server
+            .codegraph_structure(Parameters(StructureParams {
+                path: None,
+                depth: None,
+            }))
+            .await;
+        let json: serde_json::Value = serde_json::from_str(&result).unwrap();
+        let projects = json["projects"].as_array().unwrap();
+        assert_eq!(projects.len(), 2);
+        let api = projects.iter().find(|p| p["name"] == "api").unwrap();
+        assert_eq!(api["fileCount"].as_u64().unwrap(), 2);
+        assert_eq!(api["dependedOnBy"][0].as_str().unwrap(), "web");
+        let deps = json["projectDependencies"].as_array().unwrap();
+        assert_eq!(deps.len(), 1);
+        assert_eq!(deps[0]["from"].as_str().unwrap(), "web");
+        assert_eq!(deps[0]["edgeCount"].as_u64().unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn structure_omits_projects_for_single_package() {
+        let tmp = tempfile::tempdir().unwrap();
+        std::fs::write(tmp.path().join("package.json"), "{\"name\": \"app\"}").unwrap();
+        let conn = initialize_database(":memory:").unwrap();
+        let server = CodeGraphServer::with_project_root(
+            GraphStore::from_connection(conn),
+            tmp.path().into(),
+        );
+        {
+            let store = server.store.lock().unwrap();
+            store
+                .upsert_node(&make_node(
+                    "n1",
+                    "main",
+                    "src/main.ts",
+                    NodeKind::Function,
+                    1,
+                    None,
+                ))
+                .unwrap();
+        }
+        let result = server
+            .codegraph_structure(Parameters(StructureParams {
+                path: None,
+                depth: None,
+            }))
+            .await;
+        let json: serde_json::Value = serde_json::from_str(&result).unwrap();
+        assert!(json.get("projects").is_none());
+    }
+
+    // -- codegraph_wire_contract ----------------------------------------------
+
+    #[tokio::test]
+    async fn wire_contract_shows_wire_names_and_parsers() {
+        use crate::resolution::wire_contract::{WireField, WireFramework};
+
+        let server = setup_server();
+        {
+            let store = server.store.lock().unwrap();
+            let mut parse = make_node(
+                "p1",
+                "load_order",
+                "src/api.rs",
+                NodeKind::Function,
+                3,
+                None,
+            );
+            parse.body = Some("let o: Order = serde_json::from_str(&raw)?;".to_string());
+            store
+                .upsert_nodes(&[
+                    make_node(
+                        "t1",
+                        "Order",
+                        "src/order.rs",
+                        NodeKind::Struct,
+                        1,
+                        Some(true),
+                    ),
+                    make_node(
+                        "f1",
+                        "order_id",
+                        "src/order.rs",
+                        NodeKind::Property,
+                        3,
+                        None,
+                    ),
+                    parse,
+                    make_node("u1", "total", "src/bill.rs", NodeKind::Function, 8, None),
+                ])
+                .unwrap();
+            store
+                .upsert_edges(&[
+                    make_edge("p1", "t1", EdgeKind::References, "src/api.rs", 4),
+                    make_edge("u1", "f1", EdgeKind::Reads, "src/bill.rs", 9),
+                ])
+                .unwrap();
+            store
+                .replace_wire_fields_for_file(
+                    "src/order.rs",
+                    &[WireField {
+                        node_id: "f1".to_string(),
+                        owner_id: "t1".to_string(),
+                        owner: "Order".to_string(),
+                        field_name: "order_id".to_string(),
+                        wire_name: "orderId".to_string(),
+                        skipped: false,
+                        optional: false,
+                        framework: WireFramework::Serde,
+                        file_path: "src/order.rs".to_string(),
+                        line: 3,
+                    }],
+                )
+                .unwrap();
+        }
+
+        let result = server
+            .codegraph_wire_contract(Parameters(WireContractParams {
+                name: "Order".to_string(),
+            }))
+            .await;
+        let json: serde_json::Value = serde_json::from_str(&result).unwrap();
+        assert!(json["serializable"].as_bool().unwrap());
+        assert_eq!(json["framework"].as_str().unwrap(), "serde");
+        assert_eq!(json["fields"][0]["wireName"].as_str().unwrap(), "orderId");
+        assert!(json["fields"][0]["renamed"].as_bool().unwrap());
+        assert!(json["shape"].get("orderId").is_some());
+        assert_eq!(json["consumerCount"].as_u64().unwrap(), 2);
+        assert_eq!(
+            json["consumers"][0]["symbol"].as_str().unwrap(),
+            "load_order"
+        );
+        assert_eq!(json["consumers"][0]["role"].as_str().unwrap(), "parses");
+        assert_eq!(json["consumers"][1]["role"].as_str().unwrap(), "uses");
+    }
+
+    #[tokio::test]
+    async fn wire_contract_unknown_type() {
+        let server = setup_server();
+        let result = server
+            .codegraph_wire_contract(Parameters(WireContractParams {
+                name: "Missing".to_string(),
+            }))
+            .await;
+        let json: serde_json::Value = serde_json::from_str(&result).unwrap();
+        assert!(json["error"].as_str().unwrap().contains("not found"));
+    }
+
+    // -- codegraph_diff_index -------------------------------------------------
+
+    #[tokio::test]
+    async fn diff_index_reports_added_symbol_since_snapshot() {
+        let server = setup_server();
+        {
+            let store = server.store.lock().unwrap();
+            store
+                .upsert_node(&make_node(
+                    "n1",
+                    "alpha",
+                    "src/a.ts",
+                    NodeKind::Function,
+                    1,
+                    None,
+                ))
+                .unwrap();
+        }
+
+        let saved = server
+            .codegraph_diff_index(Parameters(DiffIndexParams {
+                snapshot: None,
+                against: None,
+                save: Some("base".to_string()),
+            }))
+            .await;
+        let json: serde_json::Value = serde_json::from_str(&saved).unwrap();
+        assert_eq!(json["saved"]["nodeCount"].as_u64().unwrap(), 1);
+
+        {
+            let store = server.store.lock().unwrap();
+            store
+                .upsert_node(&make_node(
+                    "n2",
+                    "beta",
+                    "src/a.ts",
+                    NodeKind::Function,
+                    9,
+                    None,
+                ))
+                .unwrap();
+        }
+
+        let result = server
+            .codegraph_diff_index(Parameters(DiffIndexParams {
+                snapshot: Some("base".to_string()),
+                against: None,
+                save: None,
+            }))
+            .await;
+        let json: serde_json::Value = serde_json::from_str(&result).unwrap();
+        assert_eq!(json["summary"]["addedSymbols"].as_u64().unwrap(), 1);
+        assert_eq!(
+            json["diff"]["addedNodes"][0]["name"].as_str().unwrap(),
+            "beta"
+        );
+        assert_eq!(json["summary"]["removedSymbols"].as_u64().unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn diff_index_unknown_snapshot_errors() {
+        let server = setup_server();
+        let result = server
+            .codegraph_diff_index(Parameters(DiffIndexParams {
+                snapshot: Some("nope".to_string()),
+                against: None,
+                save: None,
+            }))
+            .await;
+        let json: serde_json::Value = serde_json::from_str(&result).unwrap();
+        assert!(json["error"].as_str().unwrap().contains("nope"));
+    }
+
+    // -- codegraph_find_path ----------------------------------------------
+
+    #[tokio::test]
+    async fn find_path_existing() {
+        let server = setup_server();
+        {
+            let store = server.store.lock().unwrap();
+            store
+                .upsert_nodes(&[
+                    make_node("n1", "start", "src/a.ts", NodeKind::Function, 1, None),
+                    make_node("n2", "middle", "src/b.ts", NodeKind::Function, 1, None),
+                    make_node("n3", "end", "src/c.ts", NodeKind::Function, 1, None),
+                ])
+                .unwrap();
+            store
+                .upsert_edges(&[
+                    make_edge("n1", "n2", EdgeKind::Calls, "src/a.ts", 5),
                     make_edge("n2", "n3", EdgeKind::Calls, "src/b.ts", 3),
                 ])
                 .unwrap();
@@ -2144,274 +6304,1201 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn find_path_no_route() {
+    async fn find_path_no_route() {
+        let server = setup_server();
+        {
+            let store = server.store.lock().unwrap();
+            store
+                .upsert_nodes(&[
+                    make_node("n1", "isolated_a", "src/a.ts", NodeKind::Function, 1, None),
+                    make_node("n2", "isolated_b", "src/b.ts", NodeKind::Function, 1, None),
+                ])
+                .unwrap();
+        }
+        let result = server
+            .codegraph_find_path(Parameters(FindPathParams {
+                from: "isolated_a".to_string(),
+                to: "isolated_b".to_string(),
+                max_depth: None,
+            }))
+            .await;
+        let json: serde_json::Value = serde_json::from_str(&result).unwrap();
+        assert!(json["message"].as_str().unwrap().contains("No call path"));
+    }
+
+    // -- codegraph_complexity ---------------------------------------------
+
+    #[tokio::test]
+    async fn complexity_analysis() {
+        let server = setup_server();
+        {
+            let store = server.store.lock().unwrap();
+            let meta = serde_json::json!({
+                "body": "function process(x) {\n  if (x > 0) {\n    return true;\n  }\n  return false;\n}"
+            });
+            store.conn.execute(
+                "INSERT INTO nodes (id, type, name, file_path, start_line, end_line, language, source_hash, metadata) \
+                 VALUES ('fn:a:1', 'function', 'process', 'src/a.ts', 1, 6, 'typescript', 'h1', ?1)",
+                [meta.to_string()],
+            ).unwrap();
+        }
+        let result = server
+            .codegraph_complexity(Parameters(ComplexityParams {
+                min_complexity: None,
+            }))
+            .await;
+        let json: serde_json::Value = serde_json::from_str(&result).unwrap();
+        assert!(json["functions"].is_array());
+    }
+
+    #[tokio::test]
+    async fn complexity_empty_graph() {
+        let server = setup_server();
+        let result = server
+            .codegraph_complexity(Parameters(ComplexityParams {
+                min_complexity: None,
+            }))
+            .await;
+        let json: serde_json::Value = serde_json::from_str(&result).unwrap();
+        assert!(json.is_object());
+    }
+
+    // -- codegraph_cfg ----------------------------------------------------
+
+    #[tokio::test]
+    async fn cfg_builds_blocks_and_renders_mermaid() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        std::fs::write(
+            tmp.path().join("pay.ts"),
+            "function total(items) {\n  let sum = 0;\n  for (const i of items) {\n    if (i.free) {\n      continue;\n    }\n    sum += i.price;\n  }\n  return sum;\n}\n",
+        )
+        .unwrap();
+        let server = CodeGraphServer::with_project_root(
+            GraphStore::from_connection(initialize_database(":memory:").unwrap()),
+            tmp.path().into(),
+        );
+        {
+            let store = server.store.lock().unwrap();
+            let mut total = make_node("total", "total", "pay.ts", NodeKind::Function, 1, None);
+            total.end_line = 10;
+            let class = make_node("Cart", "Cart", "pay.ts", NodeKind::Class, 1, None);
+            store.upsert_nodes(&[total, class]).unwrap();
+        }
+        let params = |symbol: &str, format: Option<&str>| {
+            Parameters(CfgParams {
+                symbol: symbol.to_string(),
+                format: format.map(str::to_string),
+            })
+        };
+
+        let json: serde_json::Value =
+            serde_json::from_str(&server.codegraph_cfg(params("total", None)).await).unwrap();
+        assert_eq!(json["cyclomatic"], 3);
+        assert_eq!(json["unreachableBlocks"], serde_json::json!([]));
+        let kinds: Vec<&str> = json["blocks"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|b| b["kind"].as_str().unwrap())
+            .collect();
+        assert!(kinds.contains(&"loop") && kinds.contains(&"condition"));
+        assert!(json["edges"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|e| e["kind"] == "continue"));
+
+        let mermaid = server.codegraph_cfg(params("total", Some("mermaid"))).await;
+        assert!(mermaid.starts_with("```mermaid\nflowchart TD"));
+        assert!(mermaid.contains("-.->|back|"));
+
+        let not_fn: serde_json::Value =
+            serde_json::from_str(&server.codegraph_cfg(params("Cart", None)).await).unwrap();
+        assert!(not_fn["error"].as_str().unwrap().contains("not a function"));
+        let bad: serde_json::Value =
+            serde_json::from_str(&server.codegraph_cfg(params("total", Some("dot"))).await)
+                .unwrap();
+        assert!(bad["error"].as_str().unwrap().contains("Unknown format"));
+    }
+
+    // -- codegraph_null_checks --------------------------------------------
+
+    #[tokio::test]
+    async fn null_checks_flags_unguarded_dereference() {
+        let server = setup_server();
+        let params = |language: &str| {
+            Parameters(DataFlowParams {
+                file_path: None,
+                source: Some(
+                    "def area(shape):\n    size = shape.get('size')\n    if size is None:\n        return 0\n    side = shape.get('side')\n    return size.w * side.h\n"
+                        .to_string(),
+                ),
+                language: Some(language.to_string()),
+            })
+        };
+        let json: serde_json::Value =
+            serde_json::from_str(&server.codegraph_null_checks(params("python")).await).unwrap();
+        assert_eq!(json["nullDerefCount"], 1);
+        assert_eq!(json["derefs"][0]["variable"], "side");
+        assert_eq!(json["derefs"][0]["line"], 6);
+        assert_eq!(json["derefs"][0]["nullLine"], 5);
+
+        let json: serde_json::Value =
+            serde_json::from_str(&server.codegraph_null_checks(params("ruby")).await).unwrap();
+        assert!(json["error"].as_str().unwrap().contains("ruby"));
+    }
+
+    // -- codegraph_recursion ----------------------------------------------
+
+    #[tokio::test]
+    async fn recursion_reports_members_and_entry_edges() {
+        let server = setup_server();
+        {
+            let store = server.store.lock().unwrap();
+            store
+                .upsert_nodes(&[
+                    make_node("run", "run", "src/run.ts", NodeKind::Function, 1, None),
+                    make_node(
+                        "even",
+                        "isEven",
+                        "src/parity.ts",
+                        NodeKind::Function,
+                        1,
+                        None,
+                    ),
+                    make_node(
+                        "odd",
+                        "isOdd",
+                        "src/parity.ts",
+                        NodeKind::Function,
+                        10,
+                        None,
+                    ),
+                ])
+                .unwrap();
+            store
+                .upsert_edges(&[
+                    make_edge("run", "even", EdgeKind::Calls, "src/run.ts", 3),
+                    make_edge("even", "odd", EdgeKind::Calls, "src/parity.ts", 2),
+                    make_edge("odd", "even", EdgeKind::Calls, "src/parity.ts", 11),
+                ])
+                .unwrap();
+        }
+        let result = server
+            .codegraph_recursion(Parameters(RecursionParams {
+                file_path: None,
+                include_tests: None,
+                limit: None,
+            }))
+            .await;
+        let json: serde_json::Value = serde_json::from_str(&result).unwrap();
+        assert_eq!(json["mutualCount"], 1);
+        let cycle = &json["cycles"][0];
+        assert_eq!(cycle["size"], 2);
+        assert_eq!(cycle["entryEdgeCount"], 1);
+        assert_eq!(cycle["entryEdges"][0]["caller"], "run");
+        assert_eq!(cycle["entryEdges"][0]["callee"], "isEven");
+        assert_eq!(cycle["entryEdges"][0]["file"], "src/run.ts");
+        assert_eq!(cycle["entryEdges"][0]["line"], 3);
+    }
+
+    // -- codegraph_test_gaps ----------------------------------------------
+
+    #[tokio::test]
+    async fn test_gaps_lists_untested_complex_functions() {
+        let server = setup_server();
+        {
+            let store = server.store.lock().unwrap();
+            let branchy = "function f(x) {\n  if (x > 0 && x < 9) { a(); }\n  \
+                           for (const y of x) { if (y) { b(); } }\n  while (x) { c(); }\n}";
+            let with_body = |id: &str, name: &str, file: &str| CodeNode {
+                body: Some(branchy.to_string()),
+                ..make_node(id, name, file, NodeKind::Function, 1, None)
+            };
+            store
+                .upsert_nodes(&[
+                    with_body("fn:pay", "charge", "src/pay.ts"),
+                    with_body("fn:cart", "total", "src/cart.ts"),
+                    with_body("fn:test", "testTotal", "test/cart.test.ts"),
+                ])
+                .unwrap();
+            store
+                .upsert_edges(&[make_edge(
+                    "fn:test",
+                    "fn:cart",
+                    EdgeKind::Calls,
+                    "test/cart.test.ts",
+                    2,
+                )])
+                .unwrap();
+        }
+        let result = server
+            .codegraph_test_gaps(Parameters(TestGapsParams {
+                min_complexity: None,
+                path: None,
+                max_depth: None,
+                limit: None,
+            }))
+            .await;
+        let json: serde_json::Value = serde_json::from_str(&result).unwrap();
+        assert_eq!(json["candidateCount"], 2, "{json}");
+        assert_eq!(json["coveredCount"], 1);
+        assert_eq!(json["gaps"][0]["name"], "charge");
+        assert_eq!(json["gaps"].as_array().unwrap().len(), 1);
+    }
+
+    // -- codegraph_data_flow ----------------------------------------------
+
+    #[tokio::test]
+    async fn data_flow_analysis() {
+        let server = setup_server();
+        let result = server
+            .codegraph_data_flow(Parameters(DataFlowParams {
+                file_path: None,
+                source: Some("let x = 10;\nlet y = x + 5;".to_string()),
+                language: Some("javascript".to_string()),
+            }))
+            .await;
+        let json: serde_json::Value = serde_json::from_str(&result).unwrap();
+        assert!(json["chains"].is_array());
+    }
+
+    #[tokio::test]
+    async fn data_flow_empty_source() {
+        let server = setup_server();
+        let result = server
+            .codegraph_data_flow(Parameters(DataFlowParams {
+                file_path: None,
+                source: Some("".to_string()),
+                language: Some("javascript".to_string()),
+            }))
+            .await;
+        let json: serde_json::Value = serde_json::from_str(&result).unwrap();
+        assert!(json["chains"].is_array());
+    }
+
+    #[tokio::test]
+    async fn data_flow_missing_params() {
+        let server = setup_server();
+        let result = server
+            .codegraph_data_flow(Parameters(DataFlowParams {
+                file_path: None,
+                source: None,
+                language: None,
+            }))
+            .await;
+        let json: serde_json::Value = serde_json::from_str(&result).unwrap();
+        assert!(json["error"].is_string());
+    }
+
+    // -- codegraph_dead_stores --------------------------------------------
+
+    #[tokio::test]
+    async fn dead_stores_detection() {
+        let server = setup_server();
+        let result = server
+            .codegraph_dead_stores(Parameters(DataFlowParams {
+                file_path: None,
+                source: Some("let x = 10;\nlet y = 20;\nconsole.log(y);".to_string()),
+                language: Some("javascript".to_string()),
+            }))
+            .await;
+        let json: serde_json::Value = serde_json::from_str(&result).unwrap();
+        assert!(json["stores"].is_array());
+        let stores = json["stores"].as_array().unwrap();
+        assert!(stores
+            .iter()
+            .any(|s| s["variable"].as_str().unwrap() == "x"));
+    }
+
+    #[tokio::test]
+    async fn dead_stores_missing_params() {
+        let server = setup_server();
+        let result = server
+            .codegraph_dead_stores(Parameters(DataFlowParams {
+                file_path: None,
+                source: None,
+                language: None,
+            }))
+            .await;
+        let json: serde_json::Value = serde_json::from_str(&result).unwrap();
+        assert!(json["error"].is_string());
+    }
+
+    // -- codegraph_find_uninitialized -------------------------------------
+
+    #[tokio::test]
+    async fn find_uninitialized_vars() {
+        let server = setup_server();
+        let result = server
+            .codegraph_find_uninitialized(Parameters(DataFlowParams {
+                file_path: None,
+                source: Some("console.log(result);\nlet result = compute();".to_string()),
+                language: Some("javascript".to_string()),
+            }))
+            .await;
+        let json: serde_json::Value = serde_json::from_str(&result).unwrap();
+        assert!(json["locations"].is_array());
+    }
+
+    #[tokio::test]
+    async fn find_uninitialized_missing_params() {
+        let server = setup_server();
+        let result = server
+            .codegraph_find_uninitialized(Parameters(DataFlowParams {
+                file_path: None,
+                source: None,
+                language: None,
+            }))
+            .await;
+        let json: serde_json::Value = serde_json::from_str(&result).unwrap();
+        assert!(json["error"].is_string());
+    }
+
+    // -- codegraph_reaching_defs ------------------------------------------
+
+    #[tokio::test]
+    async fn reaching_defs_analysis() {
+        let server = setup_server();
+        let result = server
+            .codegraph_reaching_defs(Parameters(ReachingDefsParams {
+                file_path: None,
+                source: Some("let x = 10;\nlet y = 20;\nlet z = x + y;".to_string()),
+                language: Some("javascript".to_string()),
+                target_line: 3,
+            }))
+            .await;
+        let json: serde_json::Value = serde_json::from_str(&result).unwrap();
+        assert!(json["reachingDefinitions"].is_array());
+    }
+
+    #[tokio::test]
+    async fn reaching_defs_missing_params() {
+        let server = setup_server();
+        let result = server
+            .codegraph_reaching_defs(Parameters(ReachingDefsParams {
+                file_path: None,
+                source: None,
+                language: None,
+                target_line: 1,
+            }))
+            .await;
+        let json: serde_json::Value = serde_json::from_str(&result).unwrap();
+        assert!(json["error"].is_string());
+    }
+
+    // -- codegraph_query --------------------------------------------------
+
+    #[tokio::test]
+    async fn query_with_results() {
+        let server = setup_server();
+        {
+            let store = server.store.lock().unwrap();
+            store
+                .upsert_node(&make_node(
+                    "n1",
+                    "searchable",
+                    "src/a.ts",
+                    NodeKind::Function,
+                    1,
+                    None,
+                ))
+                .unwrap();
+        }
+        let result = server
+            .codegraph_query(Parameters(QueryParams {
+                query: "searchable".to_string(),
+                limit: Some(5),
+                offset: None,
+                cursor: None,
+                language: None,
+                path: None,
+                tags: None,
+                exclude_languages: None,
+                exclude_kinds: None,
+                exclude_paths: None,
+                debug: None,
+            }))
+            .await;
+        let json: serde_json::Value = serde_json::from_str(&result).unwrap();
+        assert!(json.is_array());
+    }
+
+    #[tokio::test]
+    async fn query_empty_results() {
+        let server = setup_server();
+        let result = server
+            .codegraph_query(Parameters(QueryParams {
+                query: "nonexistent".to_string(),
+                limit: None,
+                offset: None,
+                cursor: None,
+                language: None,
+                path: None,
+                tags: None,
+                exclude_languages: None,
+                exclude_kinds: None,
+                exclude_paths: None,
+                debug: None,
+            }))
+            .await;
+        let json: serde_json::Value = serde_json::from_str(&result).unwrap();
+        assert!(json.is_array());
+        assert!(json.as_array().unwrap().is_empty());
+    }
+
+    fn saved_query_params(action: &str, name: Option<&str>) -> SavedQueryParams {
+        SavedQueryParams {
+            action: Some(action.to_string()),
+            name: name.map(String::from),
+            params: None,
+            query: None,
+            description: None,
+            language: None,
+            kind: None,
+            path: None,
+            tags: None,
+            exclude_languages: None,
+            exclude_kinds: None,
+            exclude_paths: None,
+            limit: None,
+            detail_level: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn saved_query_binds_params_and_applies_detail_level() {
+        let server = setup_server();
+        {
+            let store = server.store.lock().unwrap();
+            store
+                .upsert_nodes(&[
+                    make_node("n1", "searchable", "src/a.ts", NodeKind::Function, 1, None),
+                    make_node("n2", "searchable", "lib/b.ts", NodeKind::Function, 1, None),
+                ])
+                .unwrap();
+        }
+        let saved = server
+            .codegraph_saved_query(Parameters(SavedQueryParams {
+                query: Some("searchable".to_string()),
+                path: Some("{dir}/**".to_string()),
+                detail_level: Some("summary".to_string()),
+                ..saved_query_params("save", Some("in-dir"))
+            }))
+            .await;
+        let json: serde_json::Value = serde_json::from_str(&saved).unwrap();
+        assert_eq!(json["parameters"], serde_json::json!(["dir"]));
+
+        let missing = server
+            .codegraph_saved_query(Parameters(saved_query_params("run", Some("in-dir"))))
+            .await;
+        let json: serde_json::Value = serde_json::from_str(&missing).unwrap();
+        assert!(json["error"].as_str().unwrap().contains("dir"), "{json}");
+
+        let result = server
+            .codegraph_saved_query(Parameters(SavedQueryParams {
+                params: Some(vec!["dir=src".to_string()]),
+                ..saved_query_params("run", Some("in-dir"))
+            }))
+            .await;
+        let json: serde_json::Value = serde_json::from_str(&result).unwrap();
+        let results = json.as_array().unwrap();
+        assert_eq!(results.len(), 1, "{json}");
+        assert_eq!(results[0]["file_path"], "src/a.ts");
+        assert!(results[0].get("score").is_none());
+
+        let listed = server
+            .codegraph_saved_query(Parameters(saved_query_params("list", None)))
+            .await;
+        let json: serde_json::Value = serde_json::from_str(&listed).unwrap();
+        assert_eq!(json["savedQueries"][0]["name"], "in-dir");
+        assert_eq!(json["savedQueries"][0]["source"], "local");
+    }
+
+    #[tokio::test]
+    async fn paged_tools_return_pages_with_a_cursor() {
+        let conn = initialize_database(":memory:").expect("schema init");
+        let mut config = CodeGraphConfig::default();
+        config.response.page_size = Some(2);
+        let server = CodeGraphServer::with_config(
+            GraphStore::from_connection(conn),
+            PathBuf::from("/repo"),
+            config,
+        );
+        {
+            let store = server.store.lock().unwrap();
+            let mut nodes = vec![make_node(
+                "t",
+                "target",
+                "src/t.ts",
+                NodeKind::Function,
+                1,
+                None,
+            )];
+            for i in 0..5 {
+                nodes.push(make_node(
+                    &format!("c{i}"),
+                    &format!("caller{i}"),
+                    &format!("src/c{i}.ts"),
+                    NodeKind::Function,
+                    1,
+                    None,
+                ));
+            }
+            store.upsert_nodes(&nodes).unwrap();
+            for i in 0..5 {
+                store
+                    .upsert_edge(&make_edge(
+                        &format!("c{i}"),
+                        "t",
+                        EdgeKind::Calls,
+                        &format!("src/c{i}.ts"),
+                        2,
+                    ))
+                    .unwrap();
+            }
+        }
+
+        let field = super::super::pagination::paged_field("codegraph_callers").unwrap();
+        let mut cursor: Option<String> = None;
+        let mut seen = Vec::new();
+        loop {
+            let mut args = serde_json::Map::new();
+            args.insert("symbol".to_string(), serde_json::json!("target"));
+            if let Some(c) = &cursor {
+                args.insert("cursor".to_string(), serde_json::json!(c));
+            }
+            let page = super::super::pagination::PageRequest::take(&mut args);
+            let text = server
+                .codegraph_callers(Parameters(SymbolDepthDetailParams {
+                    symbol: "target".to_string(),
+                    max_depth: None,
+                    detail_level: None,
+                }))
+                .await;
+            let mut result = CallToolResult::success(vec![rmcp::model::Content::text(text)]);
+            server.paginate_result("codegraph_callers", field, &page, &mut result);
+            let RawContent::Text(text) = &result.content[0].raw else {
+                panic!("expected text content");
+            };
+            let json: serde_json::Value = serde_json::from_str(&text.text).unwrap();
+            assert!(json["callers"].as_array().unwrap().len() <= 2);
+            assert_eq!(json["pagination"]["total"], 5);
+            for caller in json["callers"].as_array().unwrap() {
+                seen.push(caller["name"].as_str().unwrap().to_string());
+            }
+            match json["pagination"]["nextCursor"].as_str() {
+                Some(c) => cursor = Some(c.to_string()),
+                None => break,
+            }
+        }
+        seen.sort();
+        assert_eq!(
+            seen,
+            vec!["caller0", "caller1", "caller2", "caller3", "caller4"]
+        );
+    }
+
+    #[tokio::test]
+    async fn annotations_are_attached_to_every_response() {
+        let server = setup_server();
+        {
+            let store = server.store.lock().unwrap();
+            store
+                .upsert_nodes(&[
+                    make_node("n1", "charge", "src/pay.ts", NodeKind::Function, 1, None),
+                    make_node("n2", "checkout", "src/cart.ts", NodeKind::Function, 1, None),
+                ])
+                .unwrap();
+            store
+                .upsert_edge(&make_edge("n2", "n1", EdgeKind::Calls, "src/cart.ts", 3))
+                .unwrap();
+        }
+        let annotated = server
+            .codegraph_annotate(Parameters(AnnotateParams {
+                symbol: "charge".to_string(),
+                note: Some("do not touch: billing export parses this".to_string()),
+                author: Some("agent".to_string()),
+                remove: None,
+            }))
+            .await;
+        let json: serde_json::Value = serde_json::from_str(&annotated).unwrap();
+        let note_id = json["added"].as_i64().unwrap();
+        assert_eq!(json["annotations"]["notes"][0]["author"], "agent");
+        server
+            .codegraph_tag(Parameters(TagParams {
+                symbol: "charge".to_string(),
+                set: Some(vec!["perf=critical".to_string()]),
+                remove: None,
+            }))
+            .await;
+
+        let text = server
+            .codegraph_callees(Parameters(SymbolDepthDetailParams {
+                symbol: "checkout".to_string(),
+                max_depth: None,
+                detail_level: None,
+            }))
+            .await;
+        let mut result = CallToolResult::success(vec![rmcp::model::Content::text(text)]);
+        server.attach_annotations(&mut result);
+        let RawContent::Text(text) = &result.content[0].raw else {
+            panic!("expected text content");
+        };
+        let json: serde_json::Value = serde_json::from_str(&text.text).unwrap();
+        let callee = &json["callees"][0];
+        assert_eq!(callee["annotations"]["tags"]["perf"], "critical");
+        assert_eq!(
+            callee["annotations"]["notes"][0]["note"],
+            "do not touch: billing export parses this"
+        );
+        assert!(json["source"].get("annotations").is_none());
+
+        let removed = server
+            .codegraph_annotate(Parameters(AnnotateParams {
+                symbol: "charge".to_string(),
+                note: None,
+                author: None,
+                remove: Some(vec![note_id]),
+            }))
+            .await;
+        let json: serde_json::Value = serde_json::from_str(&removed).unwrap();
+        assert_eq!(json["removed"], serde_json::json!([note_id]));
+        assert!(json["annotations"].get("notes").is_none());
+    }
+
+    #[tokio::test]
+    async fn federated_query_tags_results_with_their_index() {
+        let tmp = tempfile::tempdir().unwrap();
+        {
+            let shared = GraphStore::new(&tmp.path().join("shared.db").to_string_lossy()).unwrap();
+            shared
+                .upsert_node(&make_node(
+                    "s1",
+                    "retryPolicy",
+                    "lib/retry.ts",
+                    NodeKind::Function,
+                    1,
+                    None,
+                ))
+                .unwrap();
+        }
+        let mut config = CodeGraphConfig::default();
+        config.federation.indexes = vec![crate::graph::federation::FederatedIndex {
+            name: "shared".to_string(),
+            path: "shared.db".into(),
+            weight: None,
+        }];
+        let store = GraphStore::from_connection(initialize_database(":memory:").unwrap());
+        store
+            .upsert_node(&make_node(
+                "l1",
+                "retryPolicy",
+                "src/retry.ts",
+                NodeKind::Function,
+                1,
+                None,
+            ))
+            .unwrap();
+        let server = CodeGraphServer::with_config(store, tmp.path().to_path_buf(), config);
+
+        let result = server
+            .codegraph_federated_query(Parameters(FederatedQueryParams {
+                query: "retryPolicy".to_string(),
+                limit: None,
+                language: None,
+                indexes: None,
+            }))
+            .await;
+        let json: serde_json::Value = serde_json::from_str(&result).unwrap();
+        assert_eq!(json["indexes"], serde_json::json!(["local", "shared"]));
+        let mut found: Vec<(String, String)> = json["results"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|r| {
+                (
+                    r["index"].as_str().unwrap().to_string(),
+                    r["file_path"].as_str().unwrap().to_string(),
+                )
+            })
+            .collect();
+        found.sort();
+        assert_eq!(
+            found,
+            vec![
+                ("local".to_string(), "src/retry.ts".to_string()),
+                ("shared".to_string(), "lib/retry.ts".to_string()),
+            ]
+        );
+        assert!(json.get("unavailable").is_none());
+    }
+
+    #[tokio::test]
+    async fn similar_returns_nearest_stored_embeddings() {
+        let server = setup_server();
+        {
+            let store = server.store.lock().unwrap();
+            store
+                .upsert_nodes(&[
+                    make_node("n1", "parseJson", "src/a.ts", NodeKind::Function, 1, None),
+                    make_node("n2", "parseYaml", "src/b.ts", NodeKind::Function, 1, None),
+                    make_node("n3", "render", "src/c.ts", NodeKind::Function, 1, None),
+                ])
+                .unwrap();
+            for (id, vector) in [
+                ("n1", [1.0f32, 0.1]),
+                ("n2", [0.9, 0.2]),
+                ("n3", [0.0, 1.0]),
+            ] {
+                store
+                    .conn
+                    .execute(
+                        "INSERT INTO embedding_cache (node_id, embedding) VALUES (?1, ?2)",
+                        rusqlite::params![id, crate::db::findings::to_blob(&vector)],
+                    )
+                    .unwrap();
+            }
+        }
+        let result = server
+            .codegraph_similar(Parameters(SimilarParams {
+                symbol: "parseJson".to_string(),
+                limit: Some(1),
+            }))
+            .await;
+        let json: serde_json::Value = serde_json::from_str(&result).unwrap();
+        assert_eq!(json["source"]["id"], "n1");
+        assert_eq!(json["count"], 1);
+        assert_eq!(json["similar"][0]["name"], "parseYaml");
+
+        let result = server
+            .codegraph_similar(Parameters(SimilarParams {
+                symbol: "missing".to_string(),
+                limit: None,
+            }))
+            .await;
+        assert!(result.contains("not found"));
+    }
+
+    #[tokio::test]
+    async fn query_debug_explains_ranking() {
+        let server = setup_server();
+        {
+            let store = server.store.lock().unwrap();
+            store
+                .upsert_node(&make_node(
+                    "n1",
+                    "searchable",
+                    "src/a.ts",
+                    NodeKind::Function,
+                    1,
+                    None,
+                ))
+                .unwrap();
+        }
+        let result = server
+            .codegraph_query(Parameters(QueryParams {
+                query: "searchable".to_string(),
+                limit: None,
+                offset: None,
+                cursor: None,
+                language: None,
+                path: None,
+                tags: None,
+                exclude_languages: None,
+                exclude_kinds: None,
+                exclude_paths: None,
+                debug: Some(true),
+            }))
+            .await;
+        let json: serde_json::Value = serde_json::from_str(&result).unwrap();
+        assert_eq!(json["debug"]["intent"], "hybrid");
+        assert_eq!(json["debug"]["blendWeights"]["ftsWeight"], 1.0);
+        let explain = &json["results"][0]["explain"];
+        assert_eq!(explain["ftsRank"], 1);
+        assert!(explain["vecRank"].is_null());
+        assert_eq!(explain["priorMultiplier"], 1.0);
+    }
+
+    #[tokio::test]
+    async fn query_with_language_filter() {
         let server = setup_server();
         {
             let store = server.store.lock().unwrap();
             store
-                .upsert_nodes(&[
-                    make_node("n1", "isolated_a", "src/a.ts", NodeKind::Function, 1, None),
-                    make_node("n2", "isolated_b", "src/b.ts", NodeKind::Function, 1, None),
-                ])
+                .upsert_node(&make_node(
+                    "n1",
+                    "compute",
+                    "src/a.ts",
+                    NodeKind::Function,
+                    1,
+                    None,
+                ))
                 .unwrap();
         }
         let result = server
-            .codegraph_find_path(Parameters(FindPathParams {
-                from: "isolated_a".to_string(),
-                to: "isolated_b".to_string(),
-                max_depth: None,
+            .codegraph_query(Parameters(QueryParams {
+                query: "compute".to_string(),
+                limit: None,
+                offset: None,
+                cursor: None,
+                language: Some("python".to_string()),
+                path: None,
+                tags: None,
+                exclude_languages: None,
+                exclude_kinds: None,
+                exclude_paths: None,
+                debug: None,
             }))
             .await;
         let json: serde_json::Value = serde_json::from_str(&result).unwrap();
-        assert!(json["message"].as_str().unwrap().contains("No call path"));
+        assert!(json.as_array().unwrap().is_empty(), "no Python nodes exist");
     }
 
-    // -- codegraph_complexity ---------------------------------------------
+    // -- codegraph_deep_file_query ----------------------------------------
 
     #[tokio::test]
-    async fn complexity_analysis() {
+    async fn deep_file_query_returns_files() {
         let server = setup_server();
         {
             let store = server.store.lock().unwrap();
-            let meta = serde_json::json!({
-                "body": "function process(x) {\n  if (x > 0) {\n    return true;\n  }\n  return false;\n}"
-            });
-            store.conn.execute(
-                "INSERT INTO nodes (id, type, name, file_path, start_line, end_line, language, source_hash, metadata) \
-                 VALUES ('fn:a:1', 'function', 'process', 'src/a.ts', 1, 6, 'typescript', 'h1', ?1)",
-                [meta.to_string()],
-            ).unwrap();
+            store
+                .upsert_nodes(&[
+                    make_node(
+                        "n1",
+                        "parseInvoice",
+                        "src/billing.ts",
+                        NodeKind::Function,
+                        1,
+                        None,
+                    ),
+                    make_node(
+                        "n2",
+                        "parseRefund",
+                        "src/billing.ts",
+                        NodeKind::Function,
+                        9,
+                        None,
+                    ),
+                    make_node(
+                        "n3",
+                        "parseConfig",
+                        "src/config.ts",
+                        NodeKind::Function,
+                        1,
+                        None,
+                    ),
+                ])
+                .unwrap();
         }
         let result = server
-            .codegraph_complexity(Parameters(ComplexityParams {
-                min_complexity: None,
+            .codegraph_deep_file_query(Parameters(DeepFileQueryParams {
+                query: "parse".to_string(),
+                limit: Some(5),
             }))
             .await;
         let json: serde_json::Value = serde_json::from_str(&result).unwrap();
-        assert!(json["functions"].is_array());
+        let mut files: Vec<&str> = json
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|f| f["file_path"].as_str().unwrap())
+            .collect();
+        files.sort();
+        assert_eq!(files, vec!["src/billing.ts", "src/config.ts"]);
     }
 
+    // -- codegraph_tag / codegraph_tagged ---------------------------------
+
     #[tokio::test]
-    async fn complexity_empty_graph() {
+    async fn tag_and_filter_by_tags() {
         let server = setup_server();
+        {
+            let store = server.store.lock().unwrap();
+            store
+                .upsert_nodes(&[
+                    make_node(
+                        "n1",
+                        "chargeCard",
+                        "src/pay.ts",
+                        NodeKind::Function,
+                        1,
+                        None,
+                    ),
+                    make_node("n2", "chargeFee", "src/fee.ts", NodeKind::Function, 1, None),
+                ])
+                .unwrap();
+        }
         let result = server
-            .codegraph_complexity(Parameters(ComplexityParams {
-                min_complexity: None,
+            .codegraph_tag(Parameters(TagParams {
+                symbol: "chargeCard".to_string(),
+                set: Some(vec!["layer=api".to_string(), "team=payments".to_string()]),
+                remove: None,
             }))
             .await;
         let json: serde_json::Value = serde_json::from_str(&result).unwrap();
-        assert!(json.is_object());
-    }
+        assert_eq!(json["tags"]["layer"], "api");
+        assert_eq!(json["tags"]["team"], "payments");
 
-    // -- codegraph_data_flow ----------------------------------------------
-
-    #[tokio::test]
-    async fn data_flow_analysis() {
-        let server = setup_server();
         let result = server
-            .codegraph_data_flow(Parameters(DataFlowParams {
-                file_path: None,
-                source: Some("let x = 10;\nlet y = x + 5;".to_string()),
-                language: Some("javascript".to_string()),
+            .codegraph_tag(Parameters(TagParams {
+                symbol: "chargeCard".to_string(),
+                set: Some(vec!["layer".to_string()]),
+                remove: None,
             }))
             .await;
         let json: serde_json::Value = serde_json::from_str(&result).unwrap();
-        assert!(json["chains"].is_array());
-    }
+        assert!(json["error"].as_str().unwrap().contains("key=value"));
 
-    #[tokio::test]
-    async fn data_flow_empty_source() {
-        let server = setup_server();
         let result = server
-            .codegraph_data_flow(Parameters(DataFlowParams {
-                file_path: None,
-                source: Some("".to_string()),
-                language: Some("javascript".to_string()),
+            .codegraph_tagged(Parameters(TaggedParams {
+                tags: Some(vec!["team".to_string()]),
+                kind: None,
+                limit: None,
             }))
             .await;
         let json: serde_json::Value = serde_json::from_str(&result).unwrap();
-        assert!(json["chains"].is_array());
-    }
+        assert_eq!(json["symbolCount"], 1);
+        assert_eq!(json["symbols"][0]["name"], "chargeCard");
 
-    #[tokio::test]
-    async fn data_flow_missing_params() {
-        let server = setup_server();
         let result = server
-            .codegraph_data_flow(Parameters(DataFlowParams {
-                file_path: None,
-                source: None,
-                language: None,
+            .codegraph_tagged(Parameters(TaggedParams {
+                tags: None,
+                kind: None,
+                limit: None,
             }))
             .await;
         let json: serde_json::Value = serde_json::from_str(&result).unwrap();
-        assert!(json["error"].is_string());
-    }
-
-    // -- codegraph_dead_stores --------------------------------------------
+        assert_eq!(json["tagCount"], 2);
 
-    #[tokio::test]
-    async fn dead_stores_detection() {
-        let server = setup_server();
         let result = server
-            .codegraph_dead_stores(Parameters(DataFlowParams {
-                file_path: None,
-                source: Some("let x = 10;\nlet y = 20;\nconsole.log(y);".to_string()),
-                language: Some("javascript".to_string()),
+            .codegraph_search(Parameters(SearchParams {
+                query: "charge".to_string(),
+                limit: None,
+                offset: None,
+                cursor: None,
+                kind: None,
+                path: None,
+                tags: Some(vec!["layer=api".to_string()]),
+                exclude_languages: None,
+                exclude_kinds: None,
+                exclude_paths: None,
             }))
             .await;
         let json: serde_json::Value = serde_json::from_str(&result).unwrap();
-        assert!(json["stores"].is_array());
-        let stores = json["stores"].as_array().unwrap();
-        assert!(stores
+        let names: Vec<&str> = json
+            .as_array()
+            .unwrap()
             .iter()
-            .any(|s| s["variable"].as_str().unwrap() == "x"));
-    }
+            .map(|r| r["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(names, vec!["chargeCard"]);
 
-    #[tokio::test]
-    async fn dead_stores_missing_params() {
-        let server = setup_server();
-        let result = server
-            .codegraph_dead_stores(Parameters(DataFlowParams {
-                file_path: None,
-                source: None,
-                language: None,
+        server
+            .codegraph_tag(Parameters(TagParams {
+                symbol: "chargeCard".to_string(),
+                set: None,
+                remove: Some(vec!["layer".to_string()]),
             }))
             .await;
-        let json: serde_json::Value = serde_json::from_str(&result).unwrap();
-        assert!(json["error"].is_string());
-    }
-
-    // -- codegraph_find_uninitialized -------------------------------------
-
-    #[tokio::test]
-    async fn find_uninitialized_vars() {
-        let server = setup_server();
         let result = server
-            .codegraph_find_uninitialized(Parameters(DataFlowParams {
-                file_path: None,
-                source: Some("console.log(result);\nlet result = compute();".to_string()),
-                language: Some("javascript".to_string()),
+            .codegraph_tagged(Parameters(TaggedParams {
+                tags: Some(vec!["layer=api".to_string()]),
+                kind: None,
+                limit: None,
             }))
             .await;
         let json: serde_json::Value = serde_json::from_str(&result).unwrap();
-        assert!(json["locations"].is_array());
+        assert_eq!(json["symbolCount"], 0);
     }
 
     #[tokio::test]
-    async fn find_uninitialized_missing_params() {
+    async fn search_suggests_names_for_typos() {
         let server = setup_server();
+        {
+            let store = server.store.lock().unwrap();
+            store
+                .upsert_nodes(&[
+                    make_node(
+                        "n1",
+                        "HybridSearch",
+                        "src/search.rs",
+                        NodeKind::Class,
+                        1,
+                        None,
+                    ),
+                    make_node("n2", "GraphStore", "src/store.rs", NodeKind::Class, 1, None),
+                    make_node("n3", "Tokenizer", "src/lex.rs", NodeKind::Class, 1, None),
+                ])
+                .unwrap();
+        }
         let result = server
-            .codegraph_find_uninitialized(Parameters(DataFlowParams {
-                file_path: None,
-                source: None,
-                language: None,
+            .codegraph_search(Parameters(SearchParams {
+                query: "HybirdSearch".to_string(),
+                limit: None,
+                offset: None,
+                cursor: None,
+                kind: None,
+                path: None,
+                tags: None,
+                exclude_languages: None,
+                exclude_kinds: None,
+                exclude_paths: None,
             }))
             .await;
         let json: serde_json::Value = serde_json::from_str(&result).unwrap();
-        assert!(json["error"].is_string());
-    }
-
-    // -- codegraph_reaching_defs ------------------------------------------
+        assert_eq!(json["didYouMean"], serde_json::json!(["HybridSearch"]));
+        assert_eq!(json["results"][0]["node_id"], "n1");
 
-    #[tokio::test]
-    async fn reaching_defs_analysis() {
-        let server = setup_server();
         let result = server
-            .codegraph_reaching_defs(Parameters(ReachingDefsParams {
-                file_path: None,
-                source: Some("let x = 10;\nlet y = 20;\nlet z = x + y;".to_string()),
-                language: Some("javascript".to_string()),
-                target_line: 3,
+            .codegraph_query(Parameters(QueryParams {
+                query: "Tokenzier".to_string(),
+                limit: None,
+                offset: None,
+                cursor: None,
+                language: None,
+                path: None,
+                tags: None,
+                exclude_languages: None,
+                exclude_kinds: None,
+                exclude_paths: None,
+                debug: None,
             }))
             .await;
         let json: serde_json::Value = serde_json::from_str(&result).unwrap();
-        assert!(json["reachingDefinitions"].is_array());
-    }
+        assert_eq!(json["didYouMean"][0], "Tokenizer");
+        assert_eq!(json["results"][0]["name"], "Tokenizer");
 
-    #[tokio::test]
-    async fn reaching_defs_missing_params() {
-        let server = setup_server();
+        // Exact hits keep the plain result list.
         let result = server
-            .codegraph_reaching_defs(Parameters(ReachingDefsParams {
-                file_path: None,
-                source: None,
-                language: None,
-                target_line: 1,
+            .codegraph_search(Parameters(SearchParams {
+                query: "GraphStore".to_string(),
+                limit: None,
+                offset: None,
+                cursor: None,
+                kind: None,
+                path: None,
+                tags: None,
+                exclude_languages: None,
+                exclude_kinds: None,
+                exclude_paths: None,
             }))
             .await;
         let json: serde_json::Value = serde_json::from_str(&result).unwrap();
-        assert!(json["error"].is_string());
+        assert_eq!(json[0]["name"], "GraphStore");
     }
 
-    // -- codegraph_query --------------------------------------------------
-
     #[tokio::test]
-    async fn query_with_results() {
+    async fn search_excludes_kinds_and_paths() {
         let server = setup_server();
         {
             let store = server.store.lock().unwrap();
             store
-                .upsert_node(&make_node(
-                    "n1",
-                    "searchable",
-                    "src/a.ts",
-                    NodeKind::Function,
-                    1,
-                    None,
-                ))
+                .upsert_nodes(&[
+                    make_node(
+                        "n1",
+                        "retryPolicy",
+                        "src/net.ts",
+                        NodeKind::Function,
+                        1,
+                        None,
+                    ),
+                    make_node(
+                        "n2",
+                        "retryPolicy",
+                        "src/net.ts",
+                        NodeKind::Variable,
+                        9,
+                        None,
+                    ),
+                    make_node(
+                        "n3",
+                        "retryPolicy",
+                        "vendor/net.ts",
+                        NodeKind::Function,
+                        1,
+                        None,
+                    ),
+                ])
                 .unwrap();
         }
         let result = server
-            .codegraph_query(Parameters(QueryParams {
-                query: "searchable".to_string(),
-                limit: Some(5),
-                language: None,
-            }))
-            .await;
-        let json: serde_json::Value = serde_json::from_str(&result).unwrap();
-        assert!(json.is_array());
-    }
-
-    #[tokio::test]
-    async fn query_empty_results() {
-        let server = setup_server();
-        let result = server
-            .codegraph_query(Parameters(QueryParams {
-                query: "nonexistent".to_string(),
+            .codegraph_search(Parameters(SearchParams {
+                query: "retryPolicy".to_string(),
                 limit: None,
-                language: None,
+                offset: None,
+                cursor: None,
+                kind: None,
+                path: None,
+                tags: None,
+                exclude_languages: None,
+                exclude_kinds: Some(vec!["variable".to_string()]),
+                exclude_paths: Some(vec!["vendor/".to_string()]),
             }))
             .await;
         let json: serde_json::Value = serde_json::from_str(&result).unwrap();
-        assert!(json.is_array());
-        assert!(json.as_array().unwrap().is_empty());
+        let ids: Vec<&str> = json
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|r| r["node_id"].as_str().unwrap())
+            .collect();
+        assert_eq!(ids, vec!["n1"]);
     }
 
     #[tokio::test]
-    async fn query_with_language_filter() {
+    async fn search_pages_with_cursor() {
         let server = setup_server();
         {
             let store = server.store.lock().unwrap();
-            store
-                .upsert_node(&make_node(
-                    "n1",
-                    "compute",
-                    "src/a.ts",
-                    NodeKind::Function,
-                    1,
-                    None,
-                ))
-                .unwrap();
+            let nodes: Vec<_> = (0..5)
+                .map(|i| {
+                    make_node(
+                        &format!("n{}", i),
+                        &format!("route{}", i),
+                        "src/routes.ts",
+                        NodeKind::Function,
+                        i * 10 + 1,
+                        None,
+                    )
+                })
+                .collect();
+            store.upsert_nodes(&nodes).unwrap();
         }
-        let result = server
-            .codegraph_query(Parameters(QueryParams {
-                query: "compute".to_string(),
-                limit: None,
-                language: Some("python".to_string()),
+        let page = |cursor: Option<String>| {
+            server.codegraph_search(Parameters(SearchParams {
+                query: "route0 route1 route2 route3 route4".to_string(),
+                limit: Some(2),
+                offset: None,
+                cursor,
+                kind: None,
+                path: None,
+                tags: None,
+                exclude_languages: None,
+                exclude_kinds: None,
+                exclude_paths: None,
             }))
-            .await;
-        let json: serde_json::Value = serde_json::from_str(&result).unwrap();
-        assert!(json.as_array().unwrap().is_empty(), "no Python nodes exist");
+        };
+
+        let mut seen = Vec::new();
+        let mut json: serde_json::Value = serde_json::from_str(&page(None).await).unwrap();
+        loop {
+            let results = json.get("results").unwrap_or(&json).as_array().unwrap();
+            seen.extend(
+                results
+                    .iter()
+                    .map(|r| r["node_id"].as_str().unwrap().to_string()),
+            );
+            let Some(cursor) = json["nextCursor"].as_str() else {
+                break;
+            };
+            json = serde_json::from_str(&page(Some(cursor.to_string())).await).unwrap();
+        }
+        seen.sort();
+        assert_eq!(seen, vec!["n0", "n1", "n2", "n3", "n4"]);
+
+        let bad = page(Some("2.0123456789ab".to_string())).await;
+        assert!(bad.contains("invalid cursor"));
     }
 
     // -- codegraph_dependencies -------------------------------------------
@@ -2482,6 +7569,47 @@ mod tests {
         assert!(json["callerCount"].as_u64().unwrap() >= 1);
     }
 
+    #[tokio::test]
+    async fn callers_include_callers_through_the_interface() {
+        let server = setup_server();
+        {
+            let store = server.store.lock().unwrap();
+            store
+                .upsert_nodes(&[
+                    make_node("i", "Store", "src/store.ts", NodeKind::Interface, 1, None),
+                    make_node("i.save", "save", "src/store.ts", NodeKind::Method, 2, None),
+                    make_node("c", "DiskStore", "src/disk.ts", NodeKind::Class, 1, None),
+                    make_node("c.save", "save", "src/disk.ts", NodeKind::Method, 2, None),
+                    make_node("app", "persist", "src/app.ts", NodeKind::Function, 1, None),
+                ])
+                .unwrap();
+            store
+                .upsert_edges(&[
+                    make_edge("i", "i.save", EdgeKind::Contains, "src/store.ts", 2),
+                    make_edge("c", "c.save", EdgeKind::Contains, "src/disk.ts", 2),
+                    make_edge("c", "i", EdgeKind::Implements, "src/disk.ts", 1),
+                    make_edge("app", "i.save", EdgeKind::Calls, "src/app.ts", 3),
+                ])
+                .unwrap();
+        }
+        let result = server
+            .codegraph_callers(Parameters(SymbolDepthDetailParams {
+                symbol: "c.save".to_string(),
+                max_depth: Some(1),
+                detail_level: None,
+            }))
+            .await;
+        let json: serde_json::Value = serde_json::from_str(&result).unwrap();
+
+        assert_eq!(json["callerCount"], 1);
+        assert_eq!(json["dispatchCallerCount"], 1);
+        let caller = &json["callers"][0];
+        assert_eq!(caller["name"], "persist");
+        assert_eq!(caller["via"]["declaration"], "i.save");
+        assert_eq!(caller["via"]["type"], "Store");
+        assert_eq!(caller["via"]["kind"], "override");
+    }
+
     #[tokio::test]
     async fn callers_not_found() {
         let server = setup_server();
@@ -2496,6 +7624,283 @@ mod tests {
         assert!(json["error"].is_string());
     }
 
+    // -- codegraph_reviewers ----------------------------------------------
+
+    #[tokio::test]
+    async fn reviewers_combines_blame_codeowners_and_impact() {
+        let tmp = tempfile::tempdir().unwrap();
+        let git = |args: &[&str], author: &str| {
+            std::process::Command::new("git")
+                .args(args)
+                .current_dir(tmp.path())
+                .env("GIT_AUTHOR_NAME", author)
+                .env(
+                    "GIT_AUTHOR_EMAIL",
+                    format!("{}@example.com", author.to_lowercase()),
+                )
+                .env("GIT_COMMITTER_NAME", author)
+                .env("GIT_COMMITTER_EMAIL", "ci@example.com")
+                .output()
+                .unwrap()
+        };
+        git(&["init"], "Me");
+        git(&["config", "user.email", "me@example.com"], "Me");
+        git(&["config", "user.name", "Me"], "Me");
+        std::fs::write(tmp.path().join("CODEOWNERS"), "/src/ @org/core\n").unwrap();
+        std::fs::create_dir_all(tmp.path().join("src")).unwrap();
+        std::fs::write(
+            tmp.path().join("src/a.ts"),
+            "export function a() {\n  b();\n}\n",
+        )
+        .unwrap();
+        git(&["add", "."], "Alice");
+        git(&["commit", "-m", "add a"], "Alice");
+
+        let conn = initialize_database(":memory:").unwrap();
+        let server = CodeGraphServer::with_project_root(
+            GraphStore::from_connection(conn),
+            tmp.path().into(),
+        );
+        {
+            let store = server.store.lock().unwrap();
+            store
+                .upsert_nodes(&[
+                    make_node("n1", "a", "src/a.ts", NodeKind::Function, 1, None),
+                    make_node("n2", "b", "lib/b.ts", NodeKind::Function, 1, None),
+                ])
+                .unwrap();
+            store
+                .upsert_edge(&make_edge("n1", "n2", EdgeKind::Calls, "src/a.ts", 2))
+                .unwrap();
+            store
+                .replace_node_git_meta_for_file(
+                    "lib/b.ts",
+                    &[crate::git::NodeGitMeta {
+                        node_id: "n2".to_string(),
+                        file_path: "lib/b.ts".to_string(),
+                        commit_hash: "deadbeef".to_string(),
+                        author: "Bob".to_string(),
+                        email: "bob@example.com".to_string(),
+                        committed_at: chrono::Utc::now().timestamp() - 5 * 86_400,
+                        age_days: 5,
+                    }],
+                )
+                .unwrap();
+        }
+
+        let result = server
+            .codegraph_reviewers(Parameters(ReviewersParams {
+                files: Some(vec!["src/a.ts".to_string()]),
+                limit: None,
+            }))
+            .await;
+        let json: serde_json::Value = serde_json::from_str(&result).unwrap();
+        assert_eq!(json["impactedSymbolCount"], 1);
+        let names: Vec<&str> = json["reviewers"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|r| r["reviewer"].as_str().unwrap())
+            .collect();
+        assert_eq!(names, ["Alice", "@org/core", "Bob"]);
+        assert_eq!(
+            json["reviewers"][0]["rationale"][0],
+            "owns 100% of affected lines (3/3)"
+        );
+        assert_eq!(
+            json["reviewers"][2]["rationale"][0],
+            "authored the callee b 5 days ago"
+        );
+    }
+
+    // -- codegraph_search_commits -----------------------------------------
+
+    #[tokio::test]
+    async fn search_commits_falls_back_to_pickaxe_without_index() {
+        let tmp = tempfile::tempdir().unwrap();
+        let git = |args: &[&str]| {
+            std::process::Command::new("git")
+                .args(args)
+                .current_dir(tmp.path())
+                .env("GIT_AUTHOR_NAME", "Me")
+                .env("GIT_AUTHOR_EMAIL", "me@example.com")
+                .env("GIT_COMMITTER_NAME", "Me")
+                .env("GIT_COMMITTER_EMAIL", "me@example.com")
+                .output()
+                .unwrap()
+        };
+        git(&["init"]);
+        std::fs::write(tmp.path().join("retry.ts"), "const backoffMs = 200;\n").unwrap();
+        git(&["add", "."]);
+        git(&["commit", "-m", "Tune reconnects"]);
+
+        let conn = initialize_database(":memory:").unwrap();
+        let server = CodeGraphServer::with_project_root(
+            GraphStore::from_connection(conn),
+            tmp.path().into(),
+        );
+        let result = server
+            .codegraph_search_commits(Parameters(SearchCommitsParams {
+                query: "when did we change backoffMs".to_string(),
+                limit: None,
+            }))
+            .await;
+        let json: serde_json::Value = serde_json::from_str(&result).unwrap();
+        assert_eq!(json["indexedCommits"], 0);
+        assert!(json["hint"].is_string());
+        assert_eq!(json["commits"][0]["message"], "Tune reconnects");
+        assert_eq!(json["commits"][0]["pickaxeTerms"][0], "backoffMs");
+    }
+
+    #[tokio::test]
+    async fn reviewers_without_changes_is_an_error() {
+        let tmp = tempfile::tempdir().unwrap();
+        let conn = initialize_database(":memory:").unwrap();
+        let server = CodeGraphServer::with_project_root(
+            GraphStore::from_connection(conn),
+            tmp.path().into(),
+        );
+        let result = server
+            .codegraph_reviewers(Parameters(ReviewersParams {
+                files: None,
+                limit: None,
+            }))
+            .await;
+        let json: serde_json::Value = serde_json::from_str(&result).unwrap();
+        assert!(json["error"].is_string());
+    }
+
+    // -- codegraph_symbol_history -----------------------------------------
+
+    #[tokio::test]
+    async fn symbol_history_reports_signature_changes() {
+        let tmp = tempfile::tempdir().unwrap();
+        let commit = |contents: &str, message: &str| {
+            std::fs::write(tmp.path().join("fee.py"), contents).unwrap();
+            for args in [&["add", "."][..], &["commit", "-qm", message][..]] {
+                std::process::Command::new("git")
+                    .args(args)
+                    .current_dir(tmp.path())
+                    .env("GIT_AUTHOR_NAME", "Me")
+                    .env("GIT_AUTHOR_EMAIL", "me@example.com")
+                    .env("GIT_COMMITTER_NAME", "Me")
+                    .env("GIT_COMMITTER_EMAIL", "me@example.com")
+                    .output()
+                    .unwrap();
+            }
+        };
+        std::process::Command::new("git")
+            .args(["init", "-q"])
+            .current_dir(tmp.path())
+            .output()
+            .unwrap();
+        commit("def fee(x):\n    return x\n", "Add fee");
+        commit("def fee(x, rate):\n    return x * rate\n", "Add a rate");
+
+        let server = CodeGraphServer::with_project_root(
+            GraphStore::from_connection(initialize_database(":memory:").unwrap()),
+            tmp.path().into(),
+        );
+        let result = server
+            .codegraph_symbol_history(Parameters(SymbolHistoryParams {
+                symbol: "fee".to_string(),
+                limit: None,
+                include_bodies: Some(false),
+            }))
+            .await;
+        let json: serde_json::Value = serde_json::from_str(&result).unwrap();
+        assert_eq!(json["commitCount"], 2, "{json}");
+        assert_eq!(json["signatureChanges"], 1);
+        let latest = &json["commits"][0];
+        assert_eq!(latest["message"], "Add a rate");
+        assert_eq!(latest["change"], "signature_changed");
+        assert_eq!(
+            latest["definitions"][0]["before"]["signature"],
+            "def fee(x):"
+        );
+        assert!(latest["definitions"][0]["after"].get("body").is_none());
+        assert_eq!(json["commits"][1]["change"], "added");
+    }
+
+    // -- codegraph_diff_impact --------------------------------------------
+
+    #[tokio::test]
+    async fn diff_impact_maps_working_tree_changes_to_symbols() {
+        let tmp = tempfile::tempdir().unwrap();
+        let git = |args: &[&str]| {
+            std::process::Command::new("git")
+                .args(args)
+                .current_dir(tmp.path())
+                .env("GIT_AUTHOR_NAME", "Me")
+                .env("GIT_AUTHOR_EMAIL", "me@example.com")
+                .env("GIT_COMMITTER_NAME", "Me")
+                .env("GIT_COMMITTER_EMAIL", "me@example.com")
+                .output()
+                .unwrap()
+        };
+        git(&["init"]);
+        std::fs::write(
+            tmp.path().join("pay.ts"),
+            "function fee(x) {\n  return x * 2;\n}\n\nfunction charge(x) {\n  return fee(x);\n}\n",
+        )
+        .unwrap();
+        git(&["add", "."]);
+        git(&["commit", "-m", "init"]);
+
+        let conn = initialize_database(":memory:").unwrap();
+        let server = CodeGraphServer::with_project_root(
+            GraphStore::from_connection(conn),
+            tmp.path().into(),
+        );
+        {
+            let store = server.store.lock().unwrap();
+            let mut fee = make_node("fee", "fee", "pay.ts", NodeKind::Function, 1, None);
+            fee.end_line = 3;
+            let mut charge = make_node("charge", "charge", "pay.ts", NodeKind::Function, 5, None);
+            charge.end_line = 7;
+            let test = make_node(
+                "t",
+                "testCharge",
+                "pay.test.ts",
+                NodeKind::Function,
+                1,
+                None,
+            );
+            store.upsert_nodes(&[fee, charge, test]).unwrap();
+            store
+                .upsert_edges(&[
+                    make_edge("charge", "fee", EdgeKind::Calls, "pay.ts", 6),
+                    make_edge("t", "charge", EdgeKind::Calls, "pay.test.ts", 2),
+                ])
+                .unwrap();
+        }
+        let params = || {
+            Parameters(DiffImpactParams {
+                base: None,
+                max_depth: None,
+                limit: None,
+            })
+        };
+
+        let clean: serde_json::Value =
+            serde_json::from_str(&server.codegraph_diff_impact(params()).await).unwrap();
+        assert_eq!(clean["changedFiles"], 0);
+
+        let source = std::fs::read_to_string(tmp.path().join("pay.ts")).unwrap();
+        std::fs::write(tmp.path().join("pay.ts"), source.replace("x * 2", "x * 3")).unwrap();
+        let json: serde_json::Value =
+            serde_json::from_str(&server.codegraph_diff_impact(params()).await).unwrap();
+        assert_eq!(json["changedSymbolCount"], 1);
+        assert_eq!(json["changedSymbols"][0]["name"], "fee");
+        assert_eq!(
+            json["changedSymbols"][0]["changedLines"],
+            serde_json::json!([[2, 2]])
+        );
+        assert_eq!(json["affectedCallers"][0]["name"], "charge");
+        assert_eq!(json["affectedCallers"][0]["risk"], "high");
+        assert_eq!(json["suggestedTests"][0]["name"], "testCharge");
+    }
+
     // -- codegraph_impact -------------------------------------------------
 
     #[tokio::test]
@@ -2588,6 +7993,150 @@ mod tests {
         assert!(node.is_none());
     }
 
+    #[test]
+    fn pinned_session_reads_one_snapshot() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let db = tmp.path().join("graph.db");
+        let server = CodeGraphServer::new(GraphStore::new(db.to_str().unwrap()).unwrap());
+        let node = |id: &str| make_node(id, id, "a.ts", NodeKind::Function, 1, None);
+        server
+            .store
+            .lock()
+            .unwrap()
+            .upsert_node(&node("n1"))
+            .unwrap();
+
+        let pin: serde_json::Value = serde_json::from_str(&server.pin_snapshot(false)).unwrap();
+        assert_eq!(pin["pinned"], true);
+        assert_eq!(pin["nodes"], 1);
+
+        let other = server.for_session();
+        server
+            .store
+            .lock()
+            .unwrap()
+            .upsert_node(&node("n2"))
+            .unwrap();
+
+        let count =
+            |s: &CodeGraphServer| s.active_store().lock().unwrap().get_node_count().unwrap();
+        assert_eq!(
+            count(&server),
+            1,
+            "pinned session must not see the new commit"
+        );
+        assert_eq!(count(&other), 2, "other sessions read the live graph");
+
+        let released: serde_json::Value = serde_json::from_str(&server.pin_snapshot(true)).unwrap();
+        assert_eq!(released["released"], true);
+        assert_eq!(count(&server), 2);
+    }
+
+    #[test]
+    fn pooled_tools_read_while_the_store_is_locked() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let db = tmp.path().join("graph.db");
+        let server = CodeGraphServer::new(GraphStore::new(db.to_str().unwrap()).unwrap());
+        server
+            .store
+            .lock()
+            .unwrap()
+            .upsert_node(&make_node("n1", "n1", "a.ts", NodeKind::Function, 1, None))
+            .unwrap();
+
+        let call = server.for_call("codegraph_callers");
+        {
+            let _writer = server.store.lock().unwrap();
+            let store = call.active_store();
+            let reader = store
+                .try_lock()
+                .expect("pooled reader is not the shared store");
+            assert_eq!(reader.get_node_count().unwrap(), 1);
+        }
+        drop(call);
+        let pool = server.readers.as_ref().unwrap();
+        assert_eq!(pool.idle_count(), 1, "the connection goes back to the pool");
+
+        // Tools that may write keep the shared store.
+        let call = server.for_call("codegraph_scan_security");
+        assert!(Arc::ptr_eq(&call.active_store(), &server.store));
+    }
+
+    #[test]
+    fn expired_pins_are_released_by_any_session() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let db = tmp.path().join("graph.db");
+        let server = CodeGraphServer::new(GraphStore::new(db.to_str().unwrap()).unwrap());
+        let pinning = server.for_session();
+        let pin: serde_json::Value = serde_json::from_str(&pinning.pin_snapshot(false)).unwrap();
+        assert_eq!(pin["expiresInSecs"], MAX_PIN_AGE.as_secs());
+
+        if let Some(pin) = pinning.pinned.lock().unwrap().as_mut() {
+            pin.pinned_at = Instant::now().checked_sub(MAX_PIN_AGE).unwrap();
+        }
+        // A call from another session sweeps the abandoned pin.
+        server.for_session().active_store();
+        assert!(pinning.pinned.lock().unwrap().is_none());
+        assert!(Arc::ptr_eq(&pinning.active_store(), &pinning.store));
+    }
+
+    #[test]
+    fn live_pins_are_capped_across_sessions() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let db = tmp.path().join("graph.db");
+        let server = CodeGraphServer::new(GraphStore::new(db.to_str().unwrap()).unwrap());
+        let pin = |s: &CodeGraphServer| -> serde_json::Value {
+            serde_json::from_str(&s.pin_snapshot(false)).unwrap()
+        };
+        let sessions: Vec<_> = (0..MAX_LIVE_PINS).map(|_| server.for_session()).collect();
+        for session in &sessions {
+            assert_eq!(pin(session)["pinned"], true);
+        }
+        // Re-pinning replaces a session's own pin.
+        assert_eq!(pin(&sessions[0])["replacedPrevious"], true);
+
+        let late = server.for_session();
+        assert!(pin(&late)["error"].is_string());
+        sessions[0].pin_snapshot(true);
+        assert_eq!(pin(&late)["pinned"], true);
+    }
+
+    #[test]
+    fn pin_snapshot_on_memory_db_reports_error() {
+        let server = setup_server();
+        let v: serde_json::Value = serde_json::from_str(&server.pin_snapshot(false)).unwrap();
+        assert!(v["error"].is_string());
+    }
+
+    #[test]
+    fn symbol_not_found_reports_removed_symbol() {
+        let server = setup_server();
+        {
+            let store = server.store.lock().unwrap();
+            store
+                .upsert_node(&make_node(
+                    "fn:a.ts:gone:1",
+                    "gone",
+                    "a.ts",
+                    NodeKind::Function,
+                    1,
+                    None,
+                ))
+                .unwrap();
+            store.replace_file_data("a.ts", &[], &[]).unwrap();
+        }
+
+        let v: serde_json::Value =
+            serde_json::from_str(&symbol_not_found(&server.store, "gone")).unwrap();
+        assert!(v["error"].as_str().unwrap().contains("not found"));
+        assert_eq!(v["removed"]["filePath"], "a.ts");
+        assert_eq!(v["removed"]["startLine"], 1);
+
+        let v: serde_json::Value =
+            serde_json::from_str(&symbol_not_found(&server.store, "never")).unwrap();
+        assert!(v.get("removed").is_none());
+    }
+
     // -- helper function tests --------------------------------------------
 
     #[test]
@@ -2646,7 +8195,15 @@ mod tests {
             .codegraph_query(Parameters(QueryParams {
                 query: "old_handler".to_string(),
                 limit: Some(5),
+                offset: None,
+                cursor: None,
                 language: None,
+                path: None,
+                tags: None,
+                exclude_languages: None,
+                exclude_kinds: None,
+                exclude_paths: None,
+                debug: None,
             }))
             .await;
         let json: serde_json::Value = serde_json::from_str(&result).unwrap();
@@ -2685,7 +8242,15 @@ mod tests {
             .codegraph_query(Parameters(QueryParams {
                 query: "new_handler".to_string(),
                 limit: Some(5),
+                offset: None,
+                cursor: None,
                 language: None,
+                path: None,
+                tags: None,
+                exclude_languages: None,
+                exclude_kinds: None,
+                exclude_paths: None,
+                debug: None,
             }))
             .await;
         let json: serde_json::Value = serde_json::from_str(&result).unwrap();
@@ -2831,4 +8396,217 @@ mod tests {
             tools.iter().map(|t| t.name.as_ref()).collect::<Vec<_>>()
         );
     }
+
+    #[tokio::test]
+    async fn core_tools_return_structured_content() {
+        let server = setup_server();
+        {
+            let store = server.store.lock().unwrap();
+            store
+                .upsert_nodes(&[
+                    make_node("n1", "main", "a.ts", NodeKind::Function, 1, None),
+                    make_node("n2", "util", "b.ts", NodeKind::Function, 1, None),
+                ])
+                .unwrap();
+            store
+                .upsert_edges(&[make_edge("n1", "n2", EdgeKind::Calls, "a.ts", 2)])
+                .unwrap();
+        }
+        let tools = CodeGraphServer::tool_router().list_all();
+        for (name, _) in super::super::structured::STRUCTURED_TOOLS {
+            assert!(
+                tools.iter().any(|t| t.name == *name),
+                "{name} is not a tool"
+            );
+        }
+
+        let text = server
+            .codegraph_callers(Parameters(SymbolDepthDetailParams {
+                symbol: "util".to_string(),
+                max_depth: None,
+                detail_level: None,
+            }))
+            .await;
+        let mut result = CallToolResult::success(vec![rmcp::model::Content::text(text)]);
+        server.structure_result("codegraph_callers", &mut result);
+        let structured = result.structured_content.unwrap();
+        assert_eq!(structured["callerCount"], 1);
+        assert_eq!(structured["callers"][0]["name"], "main");
+        assert_eq!(result.is_error, Some(false));
+
+        let text = server
+            .codegraph_callers(Parameters(SymbolDepthDetailParams {
+                symbol: "missing".to_string(),
+                max_depth: None,
+                detail_level: None,
+            }))
+            .await;
+        let mut result = CallToolResult::success(vec![rmcp::model::Content::text(text)]);
+        server.structure_result("codegraph_callers", &mut result);
+        assert!(result.structured_content.is_none());
+        assert_eq!(result.is_error, Some(true));
+    }
+
+    #[tokio::test]
+    async fn grep_reports_each_line_under_its_innermost_symbol() {
+        let server = setup_server();
+        {
+            let mut class = make_node("c1", "Client", "src/client.ts", NodeKind::Class, 1, None);
+            class.end_line = 10;
+            class.body = Some(
+                "class Client {\n  fetch() {\n    retry(3);\n  }\n  // RETRY later\n}".to_string(),
+            );
+            let mut method = make_node("m1", "fetch", "src/client.ts", NodeKind::Method, 2, None);
+            method.end_line = 4;
+            method.body = Some("fetch() {\n    retry(3);\n  }".to_string());
+            let mut test = make_node(
+                "t1",
+                "testRetry",
+                "src/client.test.ts",
+                NodeKind::Function,
+                1,
+                None,
+            );
+            test.body = Some("retry(1)".to_string());
+            let store = server.store.lock().unwrap();
+            store.upsert_nodes(&[class, method, test]).unwrap();
+        }
+
+        let grep = |pattern: &str, kind: Option<&str>, case_sensitive: bool| {
+            let json: serde_json::Value =
+                serde_json::from_str(&super::super::tools_analysis::handle_grep(
+                    &server.store,
+                    pattern,
+                    None,
+                    kind.map(str::to_string),
+                    case_sensitive,
+                    false,
+                    None,
+                ))
+                .unwrap();
+            json
+        };
+
+        let json = grep(r"retry\(\d\)", None, false);
+        assert_eq!(json["matchCount"], 1);
+        assert_eq!(json["symbols"][0]["name"], "fetch");
+        assert_eq!(json["symbols"][0]["matches"][0]["line"], 3);
+        assert_eq!(json["symbols"][0]["matches"][0]["text"], "retry(3);");
+
+        let json = grep("retry", None, false);
+        assert_eq!(json["matchCount"], 2);
+        assert_eq!(json["symbols"][1]["name"], "Client");
+        assert_eq!(json["symbols"][1]["matches"][0]["line"], 5);
+        assert_eq!(grep("retry", None, true)["matchCount"], 1);
+        assert_eq!(grep("retry", Some("class"), false)["matchCount"], 2);
+        assert!(grep("retry(", None, false)["error"]
+            .as_str()
+            .unwrap()
+            .starts_with("Invalid regex"));
+        assert!(grep("retry", Some("gadget"), false)["error"].is_string());
+    }
+
+    #[tokio::test]
+    async fn workspace_argument_selects_a_root() {
+        let api = GraphStore::from_connection(initialize_database(":memory:").unwrap());
+        api.upsert_nodes(&[make_node(
+            "a1",
+            "login",
+            "auth.ts",
+            NodeKind::Function,
+            1,
+            None,
+        )])
+        .unwrap();
+        let server = setup_server().with_roots(vec![ProjectRoot::new(
+            "api",
+            PathBuf::from("services/api"),
+            api,
+        )]);
+
+        let search = |server: CodeGraphServer| async move {
+            let text = server
+                .codegraph_search(Parameters(
+                    serde_json::from_value(serde_json::json!({"query": "login"})).unwrap(),
+                ))
+                .await;
+            serde_json::from_str::<serde_json::Value>(&text).unwrap()
+        };
+        assert_eq!(search(server.clone()).await, serde_json::json!([]));
+        let api = server.for_workspace("api").unwrap();
+        assert_eq!(api.project_root, PathBuf::from("services/api"));
+        assert_eq!(search(api).await[0]["name"], "login");
+        assert!(server.for_workspace("web").is_none());
+
+        let resource = server.read_workspace_resource("api").unwrap();
+        let ResourceContents::TextResourceContents { uri, text, .. } = &resource.contents[0] else {
+            panic!("expected a text resource");
+        };
+        assert_eq!(uri, "codegraph://workspaces/api");
+        let status: serde_json::Value = serde_json::from_str(text).unwrap();
+        assert_eq!(status["nodes"], 1);
+        assert!(server.read_workspace_resource("web").is_err());
+    }
+
+    #[tokio::test]
+    async fn recording_usage_does_not_wait_for_a_busy_store() {
+        let server = setup_server();
+        let ok = Ok(CallToolResult::success(vec![rmcp::model::Content::text(
+            "{\"results\": []}",
+        )]));
+        // A worker of a timed-out call still holds the store.
+        let busy = server.store.lock().unwrap();
+        let recorded =
+            server.record_usage("codegraph_search", std::time::Duration::from_millis(1), &ok);
+        assert!(!recorded.is_finished());
+        drop(busy);
+        recorded.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn tool_calls_are_recorded_for_usage_analytics() {
+        let server = setup_server();
+        let other = server.for_session();
+        let ok = Ok(CallToolResult::success(vec![rmcp::model::Content::text(
+            "{\"results\": []}",
+        )]));
+        let failed = Ok(CallToolResult::success(vec![rmcp::model::Content::text(
+            "{\"error\": \"Symbol not found: foo\"}",
+        )]));
+        let duration = std::time::Duration::from_millis(12);
+        for recorded in [
+            server.record_usage("codegraph_search", duration, &ok),
+            server.record_usage("codegraph_callers", duration, &failed),
+            other.record_usage("codegraph_search", duration, &ok),
+        ] {
+            recorded.await.unwrap();
+        }
+
+        let usage = |current_session| {
+            let server = server.clone();
+            async move {
+                let text = server
+                    .codegraph_usage(Parameters(UsageParams {
+                        days: Some(1),
+                        current_session: Some(current_session),
+                    }))
+                    .await;
+                serde_json::from_str::<serde_json::Value>(&text).unwrap()
+            }
+        };
+        let all = usage(false).await;
+        assert_eq!(all["totalCalls"], 3);
+        assert_eq!(all["sessions"], 2);
+        assert_eq!(all["tools"][0]["tool"], "codegraph_search");
+        assert_eq!(all["tools"][0]["calls"], 2);
+        assert_eq!(all["tools"][1]["errors"], 1);
+        assert_eq!(all["tools"][1]["lastError"], "Symbol not found: foo");
+        let unused = all["unusedEnabledTools"].as_array().unwrap();
+        assert!(unused.iter().any(|t| t == "codegraph_usage"));
+        assert!(!unused.iter().any(|t| t == "codegraph_search"));
+
+        let mine = usage(true).await;
+        assert_eq!(mine["totalCalls"], 2);
+        assert_eq!(mine["sessions"], 1);
+    }
 }
@@ -0,0 +1,206 @@
+//! Structured output of the core tools.
+//!
+//! Every tool answers with pretty-printed JSON text. For the tools in
+//! [`STRUCTURED_TOOLS`] the server also advertises an `outputSchema` in
+//! `tools/list` and returns the same JSON as `structuredContent`, so clients
+//! that support structured output read the fields directly instead of
+//! re-parsing the text:
+//!
+//! ```json
+//! { "content": [{ "type": "text", "text": "{\n  \"target\": …" }],
+//!   "structuredContent": { "target": {…}, "callerCount": 3, "callers": [...] } }
+//! ```
+//!
+//! The schemas list the fields a response always has; anything else a
+//! response carries (pagination, annotations, per-result details) is
+//! allowed but not described. Tools that answer with a bare list, such as
+//! `codegraph_query`, get it wrapped as `{"results": [...]}`, and a
+//! response with an `error` field is flagged as a tool error instead.
+
+use serde_json::{Map, Value};
+
+/// Tools with structured output, and the fields (with their JSON type)
+/// their responses always have.
+pub const STRUCTURED_TOOLS: &[(&str, &[(&str, &str)])] = &[
+    ("codegraph_query", &[("results", "array")]),
+    ("codegraph_search", &[("results", "array")]),
+    (
+        "codegraph_dependencies",
+        &[
+            ("source", "object"),
+            ("dependencyCount", "integer"),
+            ("dependencies", "array"),
+        ],
+    ),
+    (
+        "codegraph_callers",
+        &[
+            ("target", "object"),
+            ("callerCount", "integer"),
+            ("callers", "array"),
+        ],
+    ),
+    (
+        "codegraph_callees",
+        &[
+            ("source", "object"),
+            ("calleeCount", "integer"),
+            ("callees", "array"),
+        ],
+    ),
+    (
+        "codegraph_impact",
+        &[
+            ("analyzedSymbols", "array"),
+            ("totalAffected", "integer"),
+            ("affectedFiles", "array"),
+            ("affectedFileCount", "integer"),
+            ("riskGroups", "array"),
+        ],
+    ),
+    (
+        "codegraph_structure",
+        &[
+            ("stats", "object"),
+            ("symbolsByKind", "object"),
+            ("topSymbols", "array"),
+            ("modules", "array"),
+        ],
+    ),
+    (
+        "codegraph_tests",
+        &[("symbol", "object"), ("testCount", "integer")],
+    ),
+    (
+        "codegraph_node",
+        &[
+            ("name", "string"),
+            ("kind", "string"),
+            ("filePath", "string"),
+            ("startLine", "integer"),
+        ],
+    ),
+    (
+        "codegraph_dead_code",
+        &[("deadCodeCount", "integer"), ("resolvedCount", "integer")],
+    ),
+    ("codegraph_frameworks", &[("frameworkCount", "integer")]),
+    ("codegraph_languages", &[("languageCount", "integer")]),
+    (
+        "codegraph_complete",
+        &[("count", "integer"), ("completions", "array")],
+    ),
+    (
+        "codegraph_examples",
+        &[("target", "object"), ("exampleCount", "integer")],
+    ),
+];
+
+fn fields(tool: &str) -> Option<&'static [(&'static str, &'static str)]> {
+    STRUCTURED_TOOLS
+        .iter()
+        .find(|(name, _)| *name == tool)
+        .map(|(_, fields)| *fields)
+}
+
+/// The `outputSchema` of `tool`, if it has structured output.
+pub fn output_schema(tool: &str) -> Option<Map<String, Value>> {
+    let fields = fields(tool)?;
+    let properties: Map<String, Value> = fields
+        .iter()
+        .map(|(name, ty)| (name.to_string(), serde_json::json!({ "type": ty })))
+        .collect();
+    let required: Vec<&str> = fields.iter().map(|(name, _)| *name).collect();
+    match serde_json::json!({
+        "type": "object",
+        "properties": properties,
+        "required": required,
+    }) {
+        Value::Object(schema) => Some(schema),
+        _ => None,
+    }
+}
+
+/// What a structured tool's final response text amounts to.
+#[derive(Debug, PartialEq)]
+pub enum Structured {
+    /// The response as `structuredContent`.
+    Content(Value),
+    /// The response reports an error.
+    Error,
+    /// Not JSON, or not shaped like the tool's schema.
+    Unstructured,
+}
+
+/// Parse `tool`'s response `text` into its structured form.
+pub fn structure(tool: &str, text: &str) -> Structured {
+    let Some(fields) = fields(tool) else {
+        return Structured::Unstructured;
+    };
+    let value = match serde_json::from_str::<Value>(text) {
+        Ok(Value::Array(results)) => serde_json::json!({ "results": results }),
+        Ok(value @ Value::Object(_)) => value,
+        _ => return Structured::Unstructured,
+    };
+    if value.get("error").is_some() {
+        return Structured::Error;
+    }
+    if fields.iter().all(|(name, ty)| has_type(&value[*name], ty)) {
+        Structured::Content(value)
+    } else {
+        Structured::Unstructured
+    }
+}
+
+fn has_type(value: &Value, ty: &str) -> bool {
+    match ty {
+        "array" => value.is_array(),
+        "object" => value.is_object(),
+        "integer" => value.is_u64() || value.is_i64(),
+        "string" => value.is_string(),
+        _ => !value.is_null(),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn responses_are_checked_against_the_schema() {
+        let schema = output_schema("codegraph_callers").unwrap();
+        assert_eq!(schema["type"], "object");
+        assert_eq!(
+            schema["required"],
+            serde_json::json!(["target", "callerCount", "callers"])
+        );
+        assert_eq!(schema["properties"]["callerCount"]["type"], "integer");
+        assert!(output_schema("codegraph_diagram").is_none());
+
+        let text = r#"{"target": {"id": "a"}, "callerCount": 0, "callers": [], "extra": 1}"#;
+        match structure("codegraph_callers", text) {
+            Structured::Content(value) => assert_eq!(value["extra"], 1),
+            other => panic!("expected content, got {other:?}"),
+        }
+        assert_eq!(
+            structure("codegraph_callers", r#"{"error": "not found"}"#),
+            Structured::Error
+        );
+        assert_eq!(
+            structure("codegraph_callers", r#"{"callerCount": "3"}"#),
+            Structured::Unstructured
+        );
+        assert_eq!(
+            structure("codegraph_query", r#"[{"name": "a"}]"#),
+            Structured::Content(serde_json::json!({"results": [{"name": "a"}]}))
+        );
+        assert_eq!(
+            structure("codegraph_diagram", "graph TD"),
+            Structured::Unstructured
+        );
+    }
+}
@@ -0,0 +1,202 @@
+//! Resource subscriptions and index-change notifications.
+//!
+//! Clients subscribe to `codegraph://status`, `codegraph://overview`, or a
+//! file or symbol resource (see [`resources`](super::resources)). The
+//! index is written by `codegraph watch`, the daemon or a re-index,
+//! usually from another process, so a session with subscriptions polls
+//! the store instead: when SQLite's `data_version` or the connection's
+//! change count moves, the `file_hashes` table is compared with the last
+//! poll. Each added, changed or removed file updates the status and
+//! overview resources, its own outline, and the symbols defined in it.
+
+use std::collections::{BTreeSet, HashMap};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::error::Result;
+use crate::graph::store::GraphStore;
+
+/// How often a session with subscriptions checks the index.
+pub const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+const FILE_HASHES_SQL: &str = "SELECT file_path, content_hash FROM file_hashes";
+
+// ---------------------------------------------------------------------------
+// Change detection
+// ---------------------------------------------------------------------------
+
+/// The indexed files and their content hashes at the last poll.
+#[derive(Debug, Default)]
+pub struct IndexWatch {
+    /// `(PRAGMA data_version, total_changes)` at the last poll.
+    version: (i64, u64),
+    hashes: HashMap<String, String>,
+}
+
+impl IndexWatch {
+    /// Start watching from the current state of the index.
+    pub fn new(store: &GraphStore) -> Result<Self> {
+        Ok(Self {
+            version: version(store)?,
+            hashes: file_hashes(store)?,
+        })
+    }
+
+    /// Files added, re-indexed with new content, or removed since the last
+    /// poll.
+    pub fn poll(&mut self, store: &GraphStore) -> Result<BTreeSet<String>> {
+        let version = version(store)?;
+        if version == self.version {
+            return Ok(BTreeSet::new());
+        }
+        let hashes = file_hashes(store)?;
+        let mut changed: BTreeSet<String> = hashes
+            .iter()
+            .filter(|(path, hash)| self.hashes.get(*path) != Some(hash))
+            .map(|(path, _)| path.clone())
+            .collect();
+        changed.extend(
+            self.hashes
+                .keys()
+                .filter(|path| !hashes.contains_key(*path))
+                .cloned(),
+        );
+        self.version = version;
+        self.hashes = hashes;
+        Ok(changed)
+    }
+}
+
+fn version(store: &GraphStore) -> Result<(i64, u64)> {
+    let data_version = store
+        .conn
+        .pragma_query_value(None, "data_version", |row| row.get(0))?;
+    Ok((data_version, store.conn.total_changes()))
+}
+
+fn file_hashes(store: &GraphStore) -> Result<HashMap<String, String>> {
+    let mut stmt = store.conn.prepare_cached(FILE_HASHES_SQL)?;
+    let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+    Ok(rows.collect::<std::result::Result<_, _>>()?)
+}
+
+// ---------------------------------------------------------------------------
+// Subscriptions
+// ---------------------------------------------------------------------------
+
+/// The resources one session subscribed to.
+#[derive(Debug, Default)]
+pub struct Subscriptions {
+    /// URI → the file it depends on; `None` for whole-index resources.
+    uris: Mutex<HashMap<String, Option<String>>>,
+    polling: AtomicBool,
+}
+
+impl Subscriptions {
+    /// Subscribe to `uri`, which changes with `file` (or with any file).
+    pub fn subscribe(&self, uri: &str, file: Option<String>) {
+        self.lock().insert(uri.to_string(), file);
+    }
+
+    /// Whether `uri` was subscribed.
+    pub fn unsubscribe(&self, uri: &str) -> bool {
+        self.lock().remove(uri).is_some()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    /// Claim the session's poller; `true` for the first caller only.
+    pub fn start_polling(&self) -> bool {
+        !self.polling.swap(true, Ordering::SeqCst)
+    }
+
+    /// Release the poller once its connection is gone, so a resumed
+    /// session (see [`websocket`](super::websocket)) can start another.
+    pub fn stop_polling(&self) {
+        self.polling.store(false, Ordering::SeqCst);
+    }
+
+    /// Subscribed URIs updated by a change to `files`, in order.
+    pub fn affected(&self, files: &BTreeSet<String>) -> Vec<String> {
+        if files.is_empty() {
+            return Vec::new();
+        }
+        let mut uris: Vec<String> = self
+            .lock()
+            .iter()
+            .filter(|(_, file)| file.as_ref().is_none_or(|f| files.contains(f)))
+            .map(|(uri, _)| uri.clone())
+            .collect();
+        uris.sort();
+        uris
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, Option<String>>> {
+        self.uris.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::schema::initialize_database;
+
+    fn set_hash(store: &GraphStore, path: &str, hash: &str) {
+        store
+            .conn
+            .execute(
+                "INSERT OR REPLACE INTO file_hashes (file_path, content_hash, language) \
+                 VALUES (?1, ?2, 'typescript')",
+                [path, hash],
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn changed_files_update_the_resources_that_depend_on_them() {
+        let store = GraphStore::from_connection(initialize_database(":memory:").unwrap());
+        set_hash(&store, "src/a.ts", "1");
+        set_hash(&store, "src/b.ts", "1");
+        let mut watch = IndexWatch::new(&store).unwrap();
+        assert!(watch.poll(&store).unwrap().is_empty());
+
+        // Re-indexing unchanged content is not a change.
+        set_hash(&store, "src/a.ts", "1");
+        assert!(watch.poll(&store).unwrap().is_empty());
+
+        set_hash(&store, "src/a.ts", "2");
+        set_hash(&store, "src/c.ts", "1");
+        store
+            .conn
+            .execute("DELETE FROM file_hashes WHERE file_path = 'src/b.ts'", [])
+            .unwrap();
+        let changed = watch.poll(&store).unwrap();
+        assert_eq!(
+            changed.iter().map(String::as_str).collect::<Vec<_>>(),
+            vec!["src/a.ts", "src/b.ts", "src/c.ts"]
+        );
+
+        let subs = Subscriptions::default();
+        subs.subscribe("codegraph://status", None);
+        subs.subscribe("codegraph://file/src/a.ts", Some("src/a.ts".into()));
+        subs.subscribe("codegraph://symbol/pay", Some("src/d.ts".into()));
+        assert_eq!(
+            subs.affected(&changed),
+            vec!["codegraph://file/src/a.ts", "codegraph://status"]
+        );
+        assert!(subs.affected(&BTreeSet::new()).is_empty());
+        assert!(subs.unsubscribe("codegraph://status"));
+        assert!(!subs.unsubscribe("codegraph://status"));
+        assert!(subs.start_polling());
+        assert!(!subs.start_polling());
+        subs.stop_polling();
+        assert!(subs.start_polling());
+    }
+}
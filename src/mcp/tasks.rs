@@ -1,8 +1,12 @@
 //! MCP Tasks — asynchronous task management for long-running operations.
 //!
 //! Implements the MCP Tasks protocol (SEP-1686) using rmcp's built-in
-//! `OperationProcessor`. Clients can enqueue long-running operations (like
-//! directory indexing) and poll for completion instead of blocking.
+//! `OperationProcessor`. Clients can enqueue long-running operations and
+//! poll for completion instead of blocking. The tools that can run as tasks
+//! are listed in [`TASK_TOOLS`]: a security scan, the embedding backfill
+//! and the PageRank recompute, each of which can take minutes on a large
+//! repository. Their work reports through a [`TaskProgress`], which shows
+//! up as the task's status message.
 //!
 //! # Flow
 //!
@@ -16,11 +20,21 @@ use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 
 use rmcp::model::{
-    self as mcp, CallToolResult, CreateTaskResult, GetTaskInfoParams, GetTaskInfoResult,
-    GetTaskResultParams, ListTasksResult, PaginatedRequestParams, Task, TaskStatus,
+    self as mcp, CallToolResult, CancelTaskParams, CreateTaskResult, CustomRequest,
+    GetTaskInfoParams, GetTaskInfoResult, GetTaskResultParams, ListTasksResult,
+    PaginatedRequestParams, Task, TaskStatus,
 };
 use rmcp::task_manager::current_timestamp;
 use rmcp::ErrorData as McpError;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+/// Tools that may be called with a `task` field.
+pub const TASK_TOOLS: &[&str] = &[
+    "codegraph_scan_security",
+    "codegraph_backfill_embeddings",
+    "codegraph_recompute_pagerank",
+];
 
 // ---------------------------------------------------------------------------
 // Task state — our internal bookkeeping beyond what rmcp tracks
@@ -115,10 +129,15 @@ impl TaskManager {
         }
     }
 
-    /// Mark a task as completed with a result.
+    /// Mark a task as completed with a result. A task cancelled meanwhile
+    /// stays cancelled and the result is dropped.
     pub fn complete_task(&self, task_id: &str, result: CallToolResult) {
         let mut inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
-        if let Some(entry) = inner.tasks.get_mut(task_id) {
+        if let Some(entry) = inner
+            .tasks
+            .get_mut(task_id)
+            .filter(|e| e.task.status == TaskStatus::Working)
+        {
             entry.task.status = TaskStatus::Completed;
             entry.task.status_message = Some("Completed successfully".to_string());
             entry.task.last_updated_at = Some(current_timestamp());
@@ -127,10 +146,14 @@ impl TaskManager {
         }
     }
 
-    /// Mark a task as failed.
+    /// Mark a running task as failed.
     pub fn fail_task(&self, task_id: &str, error: &str) {
         let mut inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
-        if let Some(entry) = inner.tasks.get_mut(task_id) {
+        if let Some(entry) = inner
+            .tasks
+            .get_mut(task_id)
+            .filter(|e| e.task.status == TaskStatus::Working)
+        {
             entry.task.status = TaskStatus::Failed;
             entry.task.status_message = Some(format!("Failed: {error}"));
             entry.task.last_updated_at = Some(current_timestamp());
@@ -208,26 +231,74 @@ impl TaskManager {
     /// are never removed.
     pub fn evict_expired(&self) {
         let mut inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
-        let now_ms = current_timestamp().parse::<u64>().unwrap_or(0);
+        let now = chrono::Utc::now();
 
         inner.tasks.retain(|_, entry| {
             // Never evict working tasks
             if entry.task.status == TaskStatus::Working {
                 return true;
             }
-            // Keep tasks within their TTL
+            // Keep tasks within their TTL (timestamps are RFC 3339)
             let ttl_ms = entry.task.ttl.unwrap_or(300_000);
-            let updated_ms = entry
+            let Some(updated) = entry
                 .task
                 .last_updated_at
-                .as_ref()
-                .and_then(|ts| ts.parse::<u64>().ok())
-                .unwrap_or(0);
-            now_ms.saturating_sub(updated_ms) < ttl_ms
+                .as_deref()
+                .and_then(|ts| chrono::DateTime::parse_from_rfc3339(ts).ok())
+            else {
+                return true;
+            };
+            let age_ms = (now - updated.with_timezone(&chrono::Utc)).num_milliseconds();
+            age_ms < ttl_ms as i64
         });
     }
 }
 
+// ---------------------------------------------------------------------------
+// TaskProgress — what a task's work reports through
+// ---------------------------------------------------------------------------
+
+/// Progress reporting and cancellation checks for the work of one task.
+#[derive(Clone)]
+pub struct TaskProgress {
+    manager: TaskManager,
+    task_id: String,
+}
+
+impl TaskProgress {
+    pub fn new(manager: &TaskManager, task_id: &str) -> Self {
+        Self {
+            manager: manager.clone(),
+            task_id: task_id.to_string(),
+        }
+    }
+
+    /// Record `done` of `total` units, e.g. "Embedded 640/12000 nodes (5%)".
+    pub fn step(&self, done: usize, total: usize, what: &str) {
+        let percent = (done * 100).checked_div(total).unwrap_or(100).min(100) as u8;
+        self.manager.update_progress(
+            &self.task_id,
+            percent,
+            Some(&format!("{what} {done}/{total} ({percent}%)")),
+        );
+    }
+
+    /// Update the status message of work whose total is unknown.
+    pub fn message(&self, message: &str) {
+        let progress = self
+            .manager
+            .get_task(&self.task_id)
+            .map_or(0, |e| e.progress);
+        self.manager
+            .update_progress(&self.task_id, progress, Some(message));
+    }
+
+    /// Whether the client cancelled the task; work should stop early.
+    pub fn is_cancelled(&self) -> bool {
+        self.manager.is_cancelled(&self.task_id)
+    }
+}
+
 // ---------------------------------------------------------------------------
 // ServerHandler helpers — convert TaskManager state into rmcp response types
 // ---------------------------------------------------------------------------
@@ -332,6 +403,42 @@ pub fn handle_cancel(manager: &TaskManager, task_id: &str) -> Result<(), McpErro
     }
 }
 
+/// Answer a `tasks/*` request that rmcp delivered as a custom request, or
+/// `None` for any other method.
+///
+/// rmcp tries `CustomRequest` before the task requests when it parses a
+/// client request, so `tasks/get`, `tasks/list`, `tasks/result` and
+/// `tasks/cancel` reach `on_custom_request` instead of their own
+/// `ServerHandler` methods.
+pub fn handle_task_request(
+    manager: &TaskManager,
+    request: &CustomRequest,
+) -> Option<Result<serde_json::Value, McpError>> {
+    fn params<T: DeserializeOwned>(request: &CustomRequest) -> Result<T, McpError> {
+        request
+            .params_as()
+            .map_err(|e| McpError::invalid_params(e.to_string(), None))?
+            .ok_or_else(|| McpError::invalid_params("missing params".to_string(), None))
+    }
+    fn json<T: Serialize>(value: T) -> Result<serde_json::Value, McpError> {
+        serde_json::to_value(value).map_err(|e| McpError::internal_error(e.to_string(), None))
+    }
+
+    let result = match request.method.as_str() {
+        "tasks/get" => params(request).and_then(|p| json(make_task_info(manager, p)?)),
+        "tasks/list" => request
+            .params_as()
+            .map_err(|e| McpError::invalid_params(e.to_string(), None))
+            .and_then(|p| json(make_list_result(manager, p)?)),
+        "tasks/result" => params(request).and_then(|p| json(make_task_result(manager, p)?)),
+        "tasks/cancel" => params::<CancelTaskParams>(request)
+            .and_then(|p| handle_cancel(manager, &p.task_id))
+            .map(|()| serde_json::json!({})),
+        _ => return None,
+    };
+    Some(result)
+}
+
 // ---------------------------------------------------------------------------
 // Tests
 // ---------------------------------------------------------------------------
@@ -572,4 +679,87 @@ mod tests {
         // list_tasks caps at 50 per page
         assert_eq!(tasks.len(), 50);
     }
+
+    #[test]
+    fn progress_reports_and_cancellation_reach_the_task() {
+        let mgr = TaskManager::new();
+        let id = mgr.create_task("codegraph_backfill_embeddings");
+        let progress = TaskProgress::new(&mgr, &id);
+
+        progress.step(640, 12_800, "Embedded nodes");
+        let entry = mgr.get_task(&id).unwrap();
+        assert_eq!(entry.progress, 5);
+        assert_eq!(
+            entry.task.status_message.as_deref(),
+            Some("Embedded nodes 640/12800 (5%)")
+        );
+        progress.message("Refreshing the ANN index");
+        let entry = mgr.get_task(&id).unwrap();
+        assert_eq!(entry.progress, 5);
+        assert_eq!(
+            entry.task.status_message.as_deref(),
+            Some("Refreshing the ANN index")
+        );
+
+        // A result arriving after cancellation does not revive the task.
+        assert!(!progress.is_cancelled());
+        mgr.cancel_task(&id);
+        assert!(progress.is_cancelled());
+        mgr.complete_task(&id, CallToolResult::success(vec![Content::text("late")]));
+        mgr.fail_task(&id, "late");
+        let entry = mgr.get_task(&id).unwrap();
+        assert_eq!(entry.task.status, TaskStatus::Cancelled);
+        assert!(entry.result.is_none());
+    }
+
+    #[test]
+    fn finished_tasks_are_evicted_after_their_ttl() {
+        let mgr = TaskManager::new();
+        let done = mgr.create_task("done");
+        let running = mgr.create_task("running");
+        mgr.fail_task(&done, "boom");
+        mgr.evict_expired();
+        assert!(mgr.get_task(&done).is_some());
+
+        {
+            let mut inner = mgr.inner.lock().unwrap();
+            for entry in inner.tasks.values_mut() {
+                entry.task.last_updated_at = Some("2020-01-01T00:00:00+00:00".to_string());
+            }
+        }
+        mgr.evict_expired();
+        assert!(mgr.get_task(&done).is_none());
+        assert!(mgr.get_task(&running).is_some());
+    }
+
+    #[test]
+    fn task_requests_are_answered_from_custom_requests() {
+        let mgr = TaskManager::new();
+        let id = mgr.create_task("codegraph_recompute_pagerank");
+        let request =
+            |method: &str| CustomRequest::new(method, Some(serde_json::json!({"taskId": id})));
+
+        let info = handle_task_request(&mgr, &request("tasks/get"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(info["task"]["status"], "working");
+        let list = handle_task_request(&mgr, &CustomRequest::new("tasks/list", None))
+            .unwrap()
+            .unwrap();
+        assert_eq!(list["tasks"][0]["taskId"], id.as_str());
+        assert!(handle_task_request(&mgr, &request("tasks/result"))
+            .unwrap()
+            .is_err());
+        handle_task_request(&mgr, &request("tasks/cancel"))
+            .unwrap()
+            .unwrap();
+        assert!(mgr.is_cancelled(&id));
+
+        assert!(
+            handle_task_request(&mgr, &CustomRequest::new("tasks/get", None))
+                .unwrap()
+                .is_err()
+        );
+        assert!(handle_task_request(&mgr, &request("custom/other")).is_none());
+    }
 }
@@ -0,0 +1,728 @@
+//! Multi-tenant HTTP hosting with per-project quotas.
+//!
+//! `codegraph serve --http <addr> --workspace <dir>` serves every indexed
+//! repository of a workspace from one process, each at
+//! `/projects/<name>/mcp` with its own store, connection and lock, so a slow
+//! query in one project never holds another project's database.
+//!
+//! Each project is limited by its [`ProjectQuota`]:
+//!
+//! - **concurrent queries** — tool calls beyond the limit are rejected with
+//!   `429` instead of queueing behind the noisy project's blocked workers;
+//! - **cache memory** — the SQLite page cache of the project's connection;
+//! - **embedding rate** — a token bucket over the tool calls that run the
+//!   embedding model ([`EMBEDDING_TOOLS`]); a federated query takes one
+//!   token per index it searches.
+//!
+//! When an admin token is configured, `/admin/projects` lists usage and
+//! `/admin/projects/<name>/quota` reads or updates a quota at runtime (the
+//! change is written back to the workspace config).
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use axum::body::{Body, Bytes};
+use axum::extract::{Path as UrlPath, Request, State};
+use axum::http::{header, HeaderMap, Method, StatusCode};
+use axum::middleware::{from_fn_with_state, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use futures::StreamExt;
+use serde::Deserialize;
+
+use crate::config::loader::load_config;
+use crate::error::{CodeGraphError, Result};
+use crate::graph::store::GraphStore;
+use crate::workspace::{resolve_path, ProjectQuota, Workspace};
+
+use super::http::{constant_time_eq, require_api_key, HttpAuth};
+use super::server::CodeGraphServer;
+
+/// Tools that run the embedding model and so count against
+/// [`ProjectQuota::embeddings_per_minute`], one unit per call except for
+/// `codegraph_federated_query` (see [`Tenant::embedding_units`]).
+pub const EMBEDDING_TOOLS: &[&str] = &[
+    "codegraph_query",
+    "codegraph_context",
+    "codegraph_saved_query",
+    "codegraph_deep_query",
+    "codegraph_deep_file_query",
+    "codegraph_search_commits",
+    "codegraph_federated_query",
+    "codegraph_dead_code",
+    "codegraph_complexity",
+    "codegraph_scan_security",
+];
+
+/// Largest request body inspected for tool calls.
+pub(crate) const MAX_REQUEST_BYTES: usize = 4 * 1024 * 1024;
+
+// ---------------------------------------------------------------------------
+// Rate limiting
+// ---------------------------------------------------------------------------
+
+/// Token bucket holding up to one minute's worth of calls.
+#[derive(Debug)]
+struct TokenBucket {
+    per_minute: u32,
+    tokens: f64,
+    last: Instant,
+}
+
+impl TokenBucket {
+    fn new(per_minute: u32, now: Instant) -> Self {
+        Self {
+            per_minute,
+            tokens: f64::from(per_minute),
+            last: now,
+        }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.last).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * f64::from(self.per_minute) / 60.0)
+            .min(f64::from(self.per_minute));
+        self.last = now;
+    }
+
+    /// Take `n` tokens, or return the seconds until they are available.
+    fn try_take(&mut self, n: u32, now: Instant) -> std::result::Result<(), u64> {
+        self.refill(now);
+        let n = f64::from(n);
+        if self.tokens >= n {
+            self.tokens -= n;
+            return Ok(());
+        }
+        if self.per_minute == 0 {
+            return Err(60);
+        }
+        let missing = n - self.tokens;
+        Err((missing * 60.0 / f64::from(self.per_minute)).ceil() as u64)
+    }
+
+    fn set_rate(&mut self, per_minute: u32, now: Instant) {
+        self.refill(now);
+        self.per_minute = per_minute;
+        self.tokens = self.tokens.min(f64::from(per_minute));
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tenants
+// ---------------------------------------------------------------------------
+
+/// Why a request was turned away.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Rejection {
+    Concurrency { limit: u32 },
+    EmbeddingRate { retry_after_secs: u64 },
+}
+
+/// One hosted project.
+pub struct Tenant {
+    name: String,
+    server: CodeGraphServer,
+    quota: Mutex<ProjectQuota>,
+    embed_bucket: Mutex<TokenBucket>,
+    in_flight: AtomicU32,
+    served: AtomicU64,
+    rejected: AtomicU64,
+}
+
+/// Holds a concurrency slot until dropped.
+pub struct InFlight(Arc<Tenant>);
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        self.0.in_flight.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+impl Tenant {
+    pub fn new(name: String, server: CodeGraphServer, quota: ProjectQuota) -> Self {
+        apply_cache_size(&server, quota.cache_memory_mb);
+        Self {
+            name,
+            server,
+            embed_bucket: Mutex::new(TokenBucket::new(
+                quota.embeddings_per_minute,
+                Instant::now(),
+            )),
+            quota: Mutex::new(quota),
+            in_flight: AtomicU32::new(0),
+            served: AtomicU64::new(0),
+            rejected: AtomicU64::new(0),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn quota(&self) -> ProjectQuota {
+        *self.quota.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Replace the quota; takes effect for the next request.
+    pub fn set_quota(&self, quota: ProjectQuota) {
+        apply_cache_size(&self.server, quota.cache_memory_mb);
+        self.embed_bucket
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .set_rate(quota.embeddings_per_minute, Instant::now());
+        *self.quota.lock().unwrap_or_else(|e| e.into_inner()) = quota;
+    }
+
+    /// Admit a request carrying the given tool calls. Requests without tool
+    /// calls (handshakes, notifications, event streams) are not limited.
+    pub fn admit(
+        self: &Arc<Self>,
+        tools: &[ToolCall],
+    ) -> std::result::Result<Option<InFlight>, Rejection> {
+        if tools.is_empty() {
+            return Ok(None);
+        }
+        let quota = self.quota();
+        let previous = self.in_flight.fetch_add(1, Ordering::SeqCst);
+        let slot = InFlight(Arc::clone(self));
+        if previous >= quota.max_concurrent_queries {
+            self.rejected.fetch_add(1, Ordering::Relaxed);
+            return Err(Rejection::Concurrency {
+                limit: quota.max_concurrent_queries,
+            });
+        }
+
+        let embedding_units: u32 = tools.iter().map(|t| self.embedding_units(t)).sum();
+        if embedding_units > 0 {
+            let taken = self
+                .embed_bucket
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .try_take(embedding_units, Instant::now());
+            if let Err(retry_after_secs) = taken {
+                self.rejected.fetch_add(1, Ordering::Relaxed);
+                return Err(Rejection::EmbeddingRate { retry_after_secs });
+            }
+        }
+        self.served.fetch_add(1, Ordering::Relaxed);
+        Ok(Some(slot))
+    }
+
+    /// Embedding quota taken by one call: none for tools that do not embed,
+    /// one per index searched (the local one included) for a federated
+    /// query, one otherwise.
+    pub fn embedding_units(&self, call: &ToolCall) -> u32 {
+        if !EMBEDDING_TOOLS.contains(&call.name.as_str()) {
+            return 0;
+        }
+        if call.name != "codegraph_federated_query" {
+            return 1;
+        }
+        let configured = &self.server.config().federation.indexes;
+        let remote = match call.arguments["indexes"].as_array() {
+            Some(names) => configured
+                .iter()
+                .filter(|index| names.iter().any(|n| n.as_str() == Some(&index.name)))
+                .count(),
+            None => configured.len(),
+        };
+        1 + remote as u32
+    }
+
+    fn usage_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "name": self.name,
+            "quota": quota_json(&self.quota()),
+            "inFlight": self.in_flight.load(Ordering::SeqCst),
+            "served": self.served.load(Ordering::Relaxed),
+            "rejected": self.rejected.load(Ordering::Relaxed),
+        })
+    }
+}
+
+/// Size the project connection's page cache (negative = KiB).
+fn apply_cache_size(server: &CodeGraphServer, cache_memory_mb: u32) {
+    let store = server.store().lock().unwrap_or_else(|e| e.into_inner());
+    let kib = -(i64::from(cache_memory_mb) * 1024);
+    if let Err(e) = store.conn.pragma_update(None, "cache_size", kib) {
+        tracing::warn!("could not set cache_size: {}", e);
+    }
+}
+
+fn quota_json(quota: &ProjectQuota) -> serde_json::Value {
+    serde_json::json!({
+        "maxConcurrentQueries": quota.max_concurrent_queries,
+        "cacheMemoryMb": quota.cache_memory_mb,
+        "embeddingsPerMinute": quota.embeddings_per_minute,
+    })
+}
+
+/// A tool invoked by a JSON-RPC message.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ToolCall {
+    pub name: String,
+    /// The call's `arguments`, `null` when absent.
+    pub arguments: serde_json::Value,
+}
+
+/// The tools invoked by a JSON-RPC message or batch, with their arguments.
+pub fn tool_invocations(body: &[u8]) -> Vec<ToolCall> {
+    let Ok(value) = serde_json::from_slice::<serde_json::Value>(body) else {
+        return Vec::new();
+    };
+    let messages = match value {
+        serde_json::Value::Array(items) => items,
+        single => vec![single],
+    };
+    messages
+        .into_iter()
+        .filter(|m| m["method"] == "tools/call")
+        .map(|mut m| ToolCall {
+            name: m["params"]["name"].as_str().unwrap_or_default().to_string(),
+            arguments: m["params"]["arguments"].take(),
+        })
+        .collect()
+}
+
+/// Names of the tools invoked by a JSON-RPC message or batch.
+pub fn tool_calls(body: &[u8]) -> Vec<String> {
+    tool_invocations(body)
+        .into_iter()
+        .map(|call| call.name)
+        .collect()
+}
+
+// ---------------------------------------------------------------------------
+// Registry
+// ---------------------------------------------------------------------------
+
+/// Every hosted project, plus what the admin endpoints need.
+pub struct TenantRegistry {
+    workspace_dir: PathBuf,
+    tenants: BTreeMap<String, Arc<Tenant>>,
+    admin_token: Option<String>,
+    /// Serialises workspace config rewrites from concurrent admin calls.
+    config_lock: Mutex<()>,
+}
+
+impl TenantRegistry {
+    /// Open every indexed repository of the workspace in `workspace_dir`.
+    /// Repositories without an index, or whose name is not URL-safe, are
+    /// skipped with a warning.
+    pub fn load(workspace_dir: &Path, admin_token: Option<String>) -> Result<Self> {
+        let workspace = Workspace::load(workspace_dir)?;
+        let mut tenants = BTreeMap::new();
+        for repo in &workspace.repos {
+            if !is_url_safe(&repo.name) {
+                tracing::warn!("skipping repo '{}': name must be [A-Za-z0-9._-]", repo.name);
+                continue;
+            }
+            let db_path = resolve_path(&repo.db_path, workspace_dir);
+            if !db_path.exists() {
+                tracing::warn!("skipping repo '{}': not indexed", repo.name);
+                continue;
+            }
+            let store = GraphStore::new(&db_path.to_string_lossy())?;
+            let root = resolve_path(&repo.path, workspace_dir);
+            let config = load_config(None, Some(&root)).unwrap_or_default();
+            let server = CodeGraphServer::with_config(store, root, config);
+            let tenant = Tenant::new(repo.name.clone(), server, repo.quota.unwrap_or_default());
+            tenants.insert(repo.name.clone(), Arc::new(tenant));
+        }
+        Ok(Self {
+            workspace_dir: workspace_dir.to_path_buf(),
+            tenants,
+            admin_token: admin_token.filter(|t| !t.is_empty()),
+            config_lock: Mutex::new(()),
+        })
+    }
+
+    pub fn tenants(&self) -> impl Iterator<Item = &Arc<Tenant>> {
+        self.tenants.values()
+    }
+
+    /// Persist a quota change to the workspace config.
+    fn save_quota(&self, name: &str, quota: ProjectQuota) -> Result<()> {
+        let _guard = self.config_lock.lock().unwrap_or_else(|e| e.into_inner());
+        let mut workspace = Workspace::load(&self.workspace_dir)?;
+        let repo = workspace
+            .repos
+            .iter_mut()
+            .find(|r| r.name == name)
+            .ok_or_else(|| CodeGraphError::Other(format!("repo '{name}' left the workspace")))?;
+        repo.quota = Some(quota);
+        workspace.save(&self.workspace_dir)
+    }
+
+    fn authorized(&self, headers: &HeaderMap) -> bool {
+        let Some(ref token) = self.admin_token else {
+            return false;
+        };
+        let presented = headers
+            .get(header::AUTHORIZATION)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.strip_prefix("Bearer "))
+            .unwrap_or_default();
+        constant_time_eq(presented, token)
+    }
+}
+
+fn is_url_safe(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-'))
+}
+
+// ---------------------------------------------------------------------------
+// HTTP
+// ---------------------------------------------------------------------------
+
+/// Build the multi-tenant router: one MCP endpoint per project behind its
+/// quota and `auth`, plus the admin API when a token is configured.
+pub fn router(registry: Arc<TenantRegistry>, auth: Arc<HttpAuth>) -> Router {
+    use rmcp::transport::streamable_http_server::{
+        session::local::LocalSessionManager, StreamableHttpService,
+    };
+
+    let mut router = Router::new();
+    for tenant in registry.tenants() {
+        let server = tenant.server.clone();
+        let service = StreamableHttpService::new(
+            move || Ok(server.for_session()),
+            LocalSessionManager::default().into(),
+            Default::default(),
+        );
+        let project = Router::new()
+            .nest_service("/mcp", service)
+            .layer(from_fn_with_state(Arc::clone(tenant), enforce_quota))
+            .layer(from_fn_with_state(Arc::clone(&auth), require_api_key));
+        router = router.nest(&format!("/projects/{}", tenant.name), project);
+    }
+
+    if registry.admin_token.is_some() {
+        let admin = Router::new()
+            .route("/admin/projects", get(list_projects))
+            .route(
+                "/admin/projects/{name}/quota",
+                get(get_quota).put(put_quota),
+            )
+            .with_state(registry);
+        router = router.merge(admin);
+    }
+    router
+}
+
+async fn enforce_quota(State(tenant): State<Arc<Tenant>>, req: Request, next: Next) -> Response {
+    if req.method() != Method::POST {
+        return next.run(req).await;
+    }
+    let (parts, body) = req.into_parts();
+    let bytes: Bytes = match axum::body::to_bytes(body, MAX_REQUEST_BYTES).await {
+        Ok(b) => b,
+        Err(_) => return error_response(StatusCode::PAYLOAD_TOO_LARGE, "request body too large"),
+    };
+
+    let slot = match tenant.admit(&tool_invocations(&bytes)) {
+        Ok(slot) => slot,
+        Err(rejection) => return rejection_response(&tenant.name, rejection),
+    };
+    let response = next
+        .run(Request::from_parts(parts, Body::from(bytes)))
+        .await;
+    let Some(slot) = slot else {
+        return response;
+    };
+
+    // Results stream back as SSE, so the slot is held until the body ends.
+    let (parts, body) = response.into_parts();
+    let stream = body.into_data_stream().map(move |chunk| {
+        let _held = &slot;
+        chunk
+    });
+    Response::from_parts(parts, Body::from_stream(stream))
+}
+
+fn rejection_response(project: &str, rejection: Rejection) -> Response {
+    let (message, retry_after) = match rejection {
+        Rejection::Concurrency { limit } => (
+            format!("project '{project}' is at its limit of {limit} concurrent queries"),
+            1,
+        ),
+        Rejection::EmbeddingRate { retry_after_secs } => (
+            format!("project '{project}' exceeded its embedding rate"),
+            retry_after_secs,
+        ),
+    };
+    let mut response = error_response(StatusCode::TOO_MANY_REQUESTS, &message);
+    response
+        .headers_mut()
+        .insert(header::RETRY_AFTER, retry_after.into());
+    response
+}
+
+pub(crate) fn error_response(status: StatusCode, message: &str) -> Response {
+    (status, Json(serde_json::json!({"error": message}))).into_response()
+}
+
+/// Partial quota update; omitted fields keep their value.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct QuotaUpdate {
+    max_concurrent_queries: Option<u32>,
+    cache_memory_mb: Option<u32>,
+    embeddings_per_minute: Option<u32>,
+}
+
+async fn list_projects(
+    State(registry): State<Arc<TenantRegistry>>,
+    headers: HeaderMap,
+) -> Response {
+    if !registry.authorized(&headers) {
+        return error_response(StatusCode::UNAUTHORIZED, "invalid admin token");
+    }
+    let projects: Vec<_> = registry.tenants().map(|t| t.usage_json()).collect();
+    Json(serde_json::json!({ "projects": projects })).into_response()
+}
+
+async fn get_quota(
+    State(registry): State<Arc<TenantRegistry>>,
+    UrlPath(name): UrlPath<String>,
+    headers: HeaderMap,
+) -> Response {
+    if !registry.authorized(&headers) {
+        return error_response(StatusCode::UNAUTHORIZED, "invalid admin token");
+    }
+    match registry.tenants.get(&name) {
+        Some(tenant) => Json(tenant.usage_json()).into_response(),
+        None => error_response(StatusCode::NOT_FOUND, &format!("unknown project '{name}'")),
+    }
+}
+
+async fn put_quota(
+    State(registry): State<Arc<TenantRegistry>>,
+    UrlPath(name): UrlPath<String>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    if !registry.authorized(&headers) {
+        return error_response(StatusCode::UNAUTHORIZED, "invalid admin token");
+    }
+    let Some(tenant) = registry.tenants.get(&name) else {
+        return error_response(StatusCode::NOT_FOUND, &format!("unknown project '{name}'"));
+    };
+    let update: QuotaUpdate = match serde_json::from_slice(&body) {
+        Ok(u) => u,
+        Err(e) => return error_response(StatusCode::BAD_REQUEST, &format!("invalid quota: {e}")),
+    };
+
+    let mut quota = tenant.quota();
+    if let Some(v) = update.max_concurrent_queries {
+        quota.max_concurrent_queries = v;
+    }
+    if let Some(v) = update.cache_memory_mb {
+        quota.cache_memory_mb = v;
+    }
+    if let Some(v) = update.embeddings_per_minute {
+        quota.embeddings_per_minute = v;
+    }
+    if quota.max_concurrent_queries == 0 || quota.cache_memory_mb == 0 {
+        return error_response(
+            StatusCode::BAD_REQUEST,
+            "maxConcurrentQueries and cacheMemoryMb must be at least 1",
+        );
+    }
+
+    tenant.set_quota(quota);
+    if let Err(e) = registry.save_quota(&name, quota) {
+        tracing::warn!("quota for '{}' applied but not saved: {}", name, e);
+    }
+    Json(tenant.usage_json()).into_response()
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Read, Write};
+    use std::time::Duration;
+
+    use crate::db::schema::initialize_database;
+
+    fn tenant(quota: ProjectQuota) -> Arc<Tenant> {
+        let store = GraphStore::from_connection(initialize_database(":memory:").unwrap());
+        let server = CodeGraphServer::with_project_root(store, PathBuf::from("."));
+        Arc::new(Tenant::new("app".into(), server, quota))
+    }
+
+    fn calls(names: &[&str]) -> Vec<ToolCall> {
+        names
+            .iter()
+            .map(|s| ToolCall {
+                name: s.to_string(),
+                arguments: serde_json::Value::Null,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn token_bucket_refills_over_time() {
+        let start = Instant::now();
+        let mut bucket = TokenBucket::new(60, start);
+        assert!(bucket.try_take(60, start).is_ok());
+        assert_eq!(bucket.try_take(1, start), Err(1));
+        assert!(bucket.try_take(2, start + Duration::from_secs(2)).is_ok());
+        bucket.set_rate(0, start + Duration::from_secs(2));
+        assert_eq!(
+            bucket.try_take(1, start + Duration::from_secs(100)),
+            Err(60)
+        );
+    }
+
+    #[test]
+    fn tool_calls_are_read_from_single_and_batched_messages() {
+        let single = br#"{"jsonrpc":"2.0","id":1,"method":"tools/call","params":{"name":"codegraph_query"}}"#;
+        assert_eq!(tool_invocations(single), calls(&["codegraph_query"]));
+        assert_eq!(tool_calls(single), ["codegraph_query"]);
+        let batch = br#"[{"method":"tools/call","params":{"name":"a","arguments":{"q":1}}},{"method":"ping"}]"#;
+        assert_eq!(tool_invocations(batch)[0].arguments["q"], 1);
+        assert_eq!(tool_calls(batch), ["a"]);
+        assert!(tool_calls(br#"{"method":"initialize"}"#).is_empty());
+        assert!(tool_calls(b"not json").is_empty());
+    }
+
+    #[test]
+    fn admission_enforces_concurrency_and_embedding_rate() {
+        let t = tenant(ProjectQuota {
+            max_concurrent_queries: 1,
+            cache_memory_mb: 8,
+            embeddings_per_minute: 1,
+        });
+
+        // Non-tool traffic is never limited.
+        assert!(t.admit(&[]).unwrap().is_none());
+
+        let slot = t.admit(&calls(&["codegraph_callers"])).unwrap();
+        assert_eq!(
+            t.admit(&calls(&["codegraph_callers"])).err(),
+            Some(Rejection::Concurrency { limit: 1 })
+        );
+        drop(slot);
+
+        assert!(t.admit(&calls(&["codegraph_query"])).is_ok());
+        assert!(matches!(
+            t.admit(&calls(&["codegraph_query"])),
+            Err(Rejection::EmbeddingRate { .. })
+        ));
+        // Non-embedding tools are unaffected by the exhausted bucket.
+        assert!(t.admit(&calls(&["codegraph_callers"])).is_ok());
+        assert_eq!(t.in_flight.load(Ordering::SeqCst), 0);
+        assert_eq!(t.rejected.load(Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn federated_query_takes_one_embedding_unit_per_index() {
+        let mut config = crate::config::schema::CodeGraphConfig::default();
+        config.federation.indexes = ["shared", "vendor"]
+            .iter()
+            .map(|name| crate::graph::federation::FederatedIndex {
+                name: name.to_string(),
+                path: PathBuf::from(format!("{name}.db")),
+                weight: None,
+            })
+            .collect();
+        let store = GraphStore::from_connection(initialize_database(":memory:").unwrap());
+        let server = CodeGraphServer::with_config(store, PathBuf::from("."), config);
+        let t = Arc::new(Tenant::new(
+            "app".into(),
+            server,
+            ProjectQuota {
+                max_concurrent_queries: 4,
+                cache_memory_mb: 8,
+                embeddings_per_minute: 5,
+            },
+        ));
+
+        let all = calls(&["codegraph_federated_query"]);
+        let mut one = all.clone();
+        one[0].arguments = serde_json::json!({"indexes": ["vendor"]});
+        assert_eq!(t.embedding_units(&all[0]), 3);
+        assert_eq!(t.embedding_units(&one[0]), 2);
+        assert_eq!(t.embedding_units(&calls(&["codegraph_callers"])[0]), 0);
+
+        assert!(t.admit(&all).is_ok());
+        assert!(t.admit(&one).is_ok());
+        assert!(matches!(
+            t.admit(&calls(&["codegraph_query"])),
+            Err(Rejection::EmbeddingRate { .. })
+        ));
+    }
+
+    #[test]
+    fn set_quota_resizes_page_cache() {
+        let t = tenant(ProjectQuota::default());
+        t.set_quota(ProjectQuota {
+            cache_memory_mb: 16,
+            ..ProjectQuota::default()
+        });
+        let store = t.server.store().lock().unwrap();
+        let cache: i64 = store
+            .conn
+            .pragma_query_value(None, "cache_size", |r| r.get(0))
+            .unwrap();
+        assert_eq!(cache, -16 * 1024);
+    }
+
+    fn http(addr: std::net::SocketAddr, request: String) -> String {
+        let mut stream = std::net::TcpStream::connect(addr).unwrap();
+        stream.write_all(request.as_bytes()).unwrap();
+        let mut out = String::new();
+        stream.read_to_string(&mut out).unwrap();
+        out
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn admin_api_updates_and_persists_quotas() {
+        let tmp = tempfile::tempdir().unwrap();
+        let repo = tmp.path().join("app");
+        std::fs::create_dir_all(repo.join(".codegraph")).unwrap();
+        initialize_database(repo.join(".codegraph/codegraph.db").to_str().unwrap()).unwrap();
+        let mut ws = Workspace::init(tmp.path()).unwrap();
+        ws.add_repo("app", &repo, tmp.path()).unwrap();
+        ws.save(tmp.path()).unwrap();
+
+        let registry = Arc::new(TenantRegistry::load(tmp.path(), Some("s3cret".into())).unwrap());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, router(registry, Arc::default())).await });
+
+        let put = |token: &str| {
+            let body = r#"{"maxConcurrentQueries":9}"#;
+            format!(
+                "PUT /admin/projects/app/quota HTTP/1.1\r\nHost: x\r\nAuthorization: Bearer {token}\r\n\
+                 Content-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            )
+        };
+        let denied = tokio::task::spawn_blocking(move || http(addr, put("wrong")))
+            .await
+            .unwrap();
+        assert!(denied.starts_with("HTTP/1.1 401"), "{denied}");
+
+        let ok = tokio::task::spawn_blocking(move || http(addr, put("s3cret")))
+            .await
+            .unwrap();
+        assert!(ok.starts_with("HTTP/1.1 200"), "{ok}");
+        assert!(ok.contains(r#""maxConcurrentQueries":9"#), "{ok}");
+
+        let saved = Workspace::load(tmp.path()).unwrap();
+        assert_eq!(saved.repos[0].quota.unwrap().max_concurrent_queries, 9);
+    }
+}
@@ -1,16 +1,41 @@
-//! Analysis MCP tool handler implementations (7 tools).
+//! Analysis MCP tool handler implementations (27 tools).
 //!
-//! Contains the business logic for: stats, circular_imports, project_tree,
-//! find_references, export_map, import_graph, and file.
+//! Contains the business logic for: stats, circular_imports, architecture_check,
+//! module_coupling, project_tree, find_references, rename_impact, export_map, api_surface,
+//! import_graph, file, config_usage, diff_index, field_usages, wire_contract, import_side_effects,
+//! global_state, find_literal, grep, usage, tag, annotate, tagged,
+//! duplicate_definitions, find_clones, backfill_embeddings, and
+//! recompute_pagerank.
 
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::Path;
 use std::sync::{Arc, Mutex};
 
-use crate::graph::store::GraphStore;
+use crate::db::snapshot;
+use crate::db::usage::usage_report;
+use crate::graph::api_docs::{build_api_docs, ApiDocsOptions};
+use crate::graph::api_guard::in_directory;
+use crate::graph::architecture::{check_architecture, ArchitecturePolicy};
+use crate::graph::clones::{find_clones, CloneOptions};
+use crate::graph::coupling::{
+    module_coupling, CouplingThresholds, ModuleCoupling, DEFAULT_MAX_DISTANCE, DEFAULT_MAX_EFFERENT,
+};
+use crate::graph::duplicates::{self, DuplicateClass};
+use crate::graph::global_state::{self, GlobalStateKind, StateAccessor};
+use crate::graph::ranking::refresh_node_rank;
+use crate::graph::rename::plan_rename;
+use crate::graph::store::{GraphStore, NodeAnnotations, TagFilter};
 use crate::graph::traversal::GraphTraversal;
-use crate::types::CodeNode;
+use crate::resolution::config_keys::key_matches;
+use crate::resolution::literals::{find_constant_uses, LiteralKind};
+use crate::resolution::side_effects::SideEffectKind;
+use crate::resolution::wire_contract::classify_wire_role;
+use crate::security::rules::is_test_file;
+use crate::types::{CodeNode, EdgeKind, NodeKind};
 
-use super::server::{json_text, mermaid_id, mermaid_safe, resolve_symbol};
+use super::annotations::annotations_json;
+use super::server::{json_text, mermaid_id, mermaid_safe, resolve_symbol, symbol_not_found};
+use super::tasks::TaskProgress;
 
 // 32. codegraph_stats
 pub fn handle_stats(store_arc: &Arc<Mutex<GraphStore>>) -> String {
@@ -53,6 +78,120 @@ pub fn handle_circular_imports(store_arc: &Arc<Mutex<GraphStore>>) -> String {
     }
 }
 
+// 75. codegraph_architecture_check
+pub fn handle_architecture_check(
+    store_arc: &Arc<Mutex<GraphStore>>,
+    policy: &ArchitecturePolicy,
+    layer: Option<&str>,
+    limit: Option<usize>,
+) -> String {
+    if policy.rules.is_empty() {
+        return json_text(&serde_json::json!({
+            "error": "No architecture rules configured. Add `architecture:` layers and rules to .codegraph.yaml.",
+        }));
+    }
+    let store = store_arc.lock().unwrap_or_else(|e| e.into_inner());
+    let mut report = match check_architecture(&store, policy) {
+        Ok(r) => r,
+        Err(e) => return json_text(&serde_json::json!({"error": e.to_string()})),
+    };
+    if let Some(layer) = layer {
+        report.violations.retain(|v| v.from_layer == layer);
+    }
+
+    let mut by_rule: BTreeMap<String, usize> = BTreeMap::new();
+    for v in &report.violations {
+        *by_rule
+            .entry(format!("{} -> {}", v.from_layer, v.to_layer))
+            .or_default() += 1;
+    }
+    let limit = limit.unwrap_or(100);
+    json_text(&serde_json::json!({
+        "layers": policy.layers.iter().map(|(name, dirs)| (name.clone(), dirs.clone())).collect::<BTreeMap<_, _>>(),
+        "edgesChecked": report.edges_checked,
+        "violationCount": report.violations.len(),
+        "byLayerPair": by_rule,
+        "violations": report.violations.iter().take(limit).collect::<Vec<_>>(),
+        "unknownLayers": report.unknown_layers,
+    }))
+}
+
+// 81. codegraph_module_coupling
+/// Coupling metrics of the modules under `path`, sorted by `sort_by`
+/// (largest first, except by module name), each with the thresholds it
+/// exceeds.
+pub fn handle_module_coupling(
+    store_arc: &Arc<Mutex<GraphStore>>,
+    thresholds: &CouplingThresholds,
+    depth: Option<usize>,
+    path: Option<&str>,
+    sort_by: Option<&str>,
+    flagged_only: bool,
+    limit: Option<usize>,
+) -> String {
+    let sort_by = sort_by.unwrap_or("distance");
+    let metric: fn(&ModuleCoupling) -> f64 = match sort_by {
+        "distance" => |m| m.distance,
+        "instability" => |m| m.instability,
+        "abstractness" => |m| m.abstractness,
+        "afferent" => |m| m.afferent as f64,
+        "efferent" => |m| m.efferent as f64,
+        "module" => |_| 0.0,
+        other => {
+            return json_text(&serde_json::json!({
+                "error": format!(
+                    "Unknown sort_by '{other}'. Use distance, instability, abstractness, afferent, efferent or module."
+                ),
+            }))
+        }
+    };
+    let store = store_arc.lock().unwrap_or_else(|e| e.into_inner());
+    let modules = match module_coupling(&store, depth) {
+        Ok(modules) => modules,
+        Err(e) => return json_text(&serde_json::json!({"error": e.to_string()})),
+    };
+    drop(store);
+
+    let mut rows: Vec<(ModuleCoupling, Vec<String>)> = modules
+        .into_iter()
+        .filter(|m| path.is_none_or(|p| in_directory(&m.module, p)))
+        .map(|m| {
+            let exceeded = thresholds.exceeded(&m);
+            (m, exceeded)
+        })
+        .collect();
+    let flagged = rows.iter().filter(|(_, e)| !e.is_empty()).count();
+    if flagged_only {
+        rows.retain(|(_, e)| !e.is_empty());
+    }
+    // Modules come in name order, which the stable sort keeps for ties.
+    rows.sort_by(|(a, _), (b, _)| metric(b).total_cmp(&metric(a)));
+
+    let limit = limit.unwrap_or(100);
+    let table: Vec<serde_json::Value> = rows
+        .iter()
+        .take(limit)
+        .map(|(m, exceeded)| {
+            let mut row = serde_json::to_value(m).unwrap_or_default();
+            row["exceeds"] = serde_json::json!(exceeded);
+            row
+        })
+        .collect();
+    json_text(&serde_json::json!({
+        "sortBy": sort_by,
+        "thresholds": {
+            "maxAfferent": thresholds.max_afferent,
+            "maxEfferent": thresholds.max_efferent.unwrap_or(DEFAULT_MAX_EFFERENT),
+            "maxInstability": thresholds.max_instability,
+            "maxDistance": thresholds.max_distance.unwrap_or(DEFAULT_MAX_DISTANCE),
+        },
+        "moduleCount": rows.len(),
+        "flaggedCount": flagged,
+        "truncated": rows.len() > limit,
+        "modules": table,
+    }))
+}
+
 // 34. codegraph_project_tree
 pub fn handle_project_tree(store_arc: &Arc<Mutex<GraphStore>>, max_depth: Option<usize>) -> String {
     let store = store_arc.lock().unwrap_or_else(|e| e.into_inner());
@@ -144,6 +283,61 @@ pub fn handle_find_references(store_arc: &Arc<Mutex<GraphStore>>, symbol: &str)
     }))
 }
 
+// 70. codegraph_rename_impact
+/// Every edit renaming `symbol` to `new_name` needs, the sites that may
+/// refer to it dynamically, and the symbols the new name collides with.
+pub fn handle_rename_impact(
+    store_arc: &Arc<Mutex<GraphStore>>,
+    project_root: &Path,
+    symbol: &str,
+    new_name: &str,
+) -> String {
+    let node = match resolve_symbol(store_arc, symbol) {
+        Some(n) => n,
+        None => return symbol_not_found(store_arc, symbol),
+    };
+    let store = store_arc.lock().unwrap_or_else(|e| e.into_inner());
+    let plan = match plan_rename(&store, &node, new_name.trim(), project_root) {
+        Ok(p) => p,
+        Err(e) => return json_text(&serde_json::json!({"error": e.to_string()})),
+    };
+    let by_file = plan.edits_by_file();
+    let files: Vec<serde_json::Value> = by_file
+        .iter()
+        .map(|(file, edits)| {
+            serde_json::json!({
+                "file": file,
+                "edits": edits.iter().map(|e| serde_json::json!({
+                    "line": e.line, "column": e.column, "endColumn": e.end_column,
+                    "oldText": plan.old_name, "newText": plan.new_name,
+                })).collect::<Vec<_>>(),
+            })
+        })
+        .collect();
+    json_text(&serde_json::json!({
+        "symbol": {
+            "nodeId": node.id, "name": node.name, "kind": node.kind.as_str(),
+            "file": node.file_path, "line": node.start_line,
+        },
+        "newName": plan.new_name,
+        "editCount": plan.edits.len(),
+        "fileCount": files.len(),
+        "references": plan.edits.iter().map(|e| serde_json::json!({
+            "file": e.file_path, "line": e.line, "column": e.column, "site": e.site,
+        })).collect::<Vec<_>>(),
+        "unverified": plan.unverified.iter().map(|u| serde_json::json!({
+            "file": u.file_path, "line": u.line, "reason": u.kind.as_str(), "detail": u.detail,
+        })).collect::<Vec<_>>(),
+        "conflicts": plan.conflicts.iter().map(|n| serde_json::json!({
+            "nodeId": n.id, "name": n.name, "kind": n.kind.as_str(),
+            "file": n.file_path, "line": n.start_line,
+        })).collect::<Vec<_>>(),
+        // Edits per file, last to first: applied in order, earlier columns
+        // stay valid.
+        "editPlan": files,
+    }))
+}
+
 // 36. codegraph_export_map
 pub fn handle_export_map(store_arc: &Arc<Mutex<GraphStore>>) -> String {
     let store = store_arc.lock().unwrap_or_else(|e| e.into_inner());
@@ -182,6 +376,27 @@ pub fn handle_export_map(store_arc: &Arc<Mutex<GraphStore>>) -> String {
     }))
 }
 
+// 72. codegraph_api_surface
+/// The exported symbols under `path` grouped by module, with signatures,
+/// docs and stability markers.
+pub fn handle_api_surface(
+    store_arc: &Arc<Mutex<GraphStore>>,
+    project_root: &Path,
+    path: Option<String>,
+    max_examples: usize,
+) -> String {
+    let store = store_arc.lock().unwrap_or_else(|e| e.into_inner());
+    let options = ApiDocsOptions {
+        path_prefix: path.map(|p| p.trim_start_matches("./").to_string()),
+        max_examples,
+        root: Some(project_root.to_path_buf()),
+    };
+    match build_api_docs(&store, &options) {
+        Ok(docs) => json_text(&serde_json::json!(docs)),
+        Err(e) => json_text(&serde_json::json!({"error": e.to_string()})),
+    }
+}
+
 // 37. codegraph_import_graph
 pub fn handle_import_graph(store_arc: &Arc<Mutex<GraphStore>>, scope: Option<String>) -> String {
     let store = store_arc.lock().unwrap_or_else(|e| e.into_inner());
@@ -271,3 +486,1167 @@ pub fn handle_file(store_arc: &Arc<Mutex<GraphStore>>, file_path: &str) -> Strin
         Err(e) => json_text(&serde_json::json!({"error": e.to_string()})),
     }
 }
+
+// 47. codegraph_config_usage
+pub fn handle_config_usage(
+    store_arc: &Arc<Mutex<GraphStore>>,
+    key: Option<String>,
+    file_path: Option<String>,
+) -> String {
+    let store = store_arc.lock().unwrap_or_else(|e| e.into_inner());
+    let mut keys = match store.get_config_keys() {
+        Ok(k) => k,
+        Err(e) => return json_text(&serde_json::json!({"error": e.to_string()})),
+    };
+    let refs = match store.get_config_refs() {
+        Ok(r) => r,
+        Err(e) => return json_text(&serde_json::json!({"error": e.to_string()})),
+    };
+    if let Some(ref fp) = file_path {
+        keys.retain(|k| k.file_path == *fp);
+    }
+
+    // Without a key: list every config key with its read count.
+    let Some(key) = key else {
+        let listed: Vec<serde_json::Value> = keys
+            .iter()
+            .map(|k| {
+                let reads = refs.iter().filter(|r| key_matches(&k.key, &r.key)).count();
+                serde_json::json!({
+                    "key": k.key, "file": k.file_path, "line": k.line,
+                    "format": k.format.as_str(), "readCount": reads,
+                })
+            })
+            .collect();
+        let mut undefined: Vec<&str> = refs
+            .iter()
+            .filter(|r| !keys.iter().any(|k| key_matches(&k.key, &r.key)))
+            .map(|r| r.key.as_str())
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect();
+        undefined.sort_unstable();
+        return json_text(&serde_json::json!({
+            "keyCount": listed.len(),
+            "keys": listed,
+            "undefinedKeys": if file_path.is_none() { undefined } else { Vec::new() },
+        }));
+    };
+
+    let definitions: Vec<serde_json::Value> = keys
+        .iter()
+        .filter(|k| k.key == key || key_matches(&k.key, &key))
+        .map(|k| {
+            serde_json::json!({
+                "key": k.key, "file": k.file_path, "line": k.line,
+                "format": k.format.as_str(),
+            })
+        })
+        .collect();
+
+    let mut affected_files: HashSet<&str> = HashSet::new();
+    let usages: Vec<serde_json::Value> = refs
+        .iter()
+        .filter(|r| r.key == key || key_matches(&key, &r.key))
+        .map(|r| {
+            affected_files.insert(r.file_path.as_str());
+            let node = store.get_node(&r.node_id).ok().flatten();
+            serde_json::json!({
+                "key": r.key,
+                "symbol": node.as_ref().map(|n| n.name.as_str()),
+                "kind": node.as_ref().map(|n| n.kind.as_str()),
+                "nodeId": r.node_id,
+                "file": r.file_path,
+                "line": r.line,
+            })
+        })
+        .collect();
+
+    if definitions.is_empty() && usages.is_empty() {
+        return json_text(&serde_json::json!({
+            "error": format!("Config key \"{}\" is not defined in any config file and is never read.", key),
+        }));
+    }
+
+    let mut affected: Vec<&str> = affected_files.into_iter().collect();
+    affected.sort_unstable();
+    json_text(&serde_json::json!({
+        "key": key,
+        "definitions": definitions,
+        "usageCount": usages.len(),
+        "usages": usages,
+        "affectedFiles": affected,
+    }))
+}
+
+// 48. codegraph_diff_index
+pub fn handle_diff_index(
+    store_arc: &Arc<Mutex<GraphStore>>,
+    snapshot_label: Option<String>,
+    against: Option<String>,
+    save: Option<String>,
+) -> String {
+    let store = store_arc.lock().unwrap_or_else(|e| e.into_inner());
+
+    if let Some(label) = save {
+        return match snapshot::create_snapshot(&store.conn, &label) {
+            Ok(info) => json_text(&serde_json::json!({"saved": info})),
+            Err(e) => json_text(&serde_json::json!({"error": e.to_string()})),
+        };
+    }
+
+    // Without a snapshot: list what is available to diff against.
+    let Some(label) = snapshot_label else {
+        return match snapshot::list_snapshots(&store.conn) {
+            Ok(list) => json_text(&serde_json::json!({
+                "snapshotCount": list.len(),
+                "snapshots": list,
+            })),
+            Err(e) => json_text(&serde_json::json!({"error": e.to_string()})),
+        };
+    };
+
+    let diff = match against {
+        Some(ref to) => snapshot::diff_snapshots(&store.conn, &label, to),
+        None => snapshot::diff_with_current(&store.conn, &label),
+    };
+    match diff {
+        Ok(diff) => json_text(&serde_json::json!({
+            "from": label,
+            "to": against.as_deref().unwrap_or("current"),
+            "summary": {
+                "addedSymbols": diff.added_nodes.len(),
+                "removedSymbols": diff.removed_nodes.len(),
+                "changedSymbols": diff.changed_nodes.len(),
+                "addedEdges": diff.added_edges.len(),
+                "removedEdges": diff.removed_edges.len(),
+            },
+            "diff": diff,
+        })),
+        Err(e) => json_text(&serde_json::json!({"error": e.to_string()})),
+    }
+}
+
+// 49. codegraph_field_usages
+pub fn handle_field_usages(
+    store_arc: &Arc<Mutex<GraphStore>>,
+    field: &str,
+    access: Option<String>,
+) -> String {
+    let store = store_arc.lock().unwrap_or_else(|e| e.into_inner());
+
+    // Accept a node ID, a qualified name (`Order.status`), or a bare name.
+    let fields: Vec<CodeNode> = match store.get_node(field) {
+        Ok(Some(n)) => vec![n],
+        _ => {
+            let name = field.rsplit(['.', ':']).next().unwrap_or(field);
+            store
+                .get_nodes_by_name(name)
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|n| n.kind == NodeKind::Property)
+                .filter(|n| {
+                    name == field
+                        || n.qualified_name
+                            .as_deref()
+                            .is_some_and(|q| q == field || q.ends_with(&format!(".{}", field)))
+                })
+                .collect()
+        }
+    };
+    if fields.is_empty() {
+        return json_text(&serde_json::json!({"error": format!("Field \"{}\" not found.", field)}));
+    }
+
+    let want_reads = access.as_deref() != Some("write");
+    let want_writes = access.as_deref() != Some("read");
+    let usages_of = |node_id: &str, edge_type: &str| -> Vec<serde_json::Value> {
+        store
+            .get_in_edges(node_id, Some(edge_type))
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|edge| {
+                let src = store.get_node(&edge.source).ok().flatten()?;
+                Some(serde_json::json!({
+                    "symbol": src.qualified_name.as_deref().unwrap_or(&src.name),
+                    "kind": src.kind.as_str(), "nodeId": src.id,
+                    "file": edge.file_path, "line": edge.line,
+                }))
+            })
+            .collect()
+    };
+
+    let results: Vec<serde_json::Value> = fields
+        .iter()
+        .map(|f| {
+            let writers = if want_writes {
+                usages_of(&f.id, "writes")
+            } else {
+                Vec::new()
+            };
+            let readers = if want_reads {
+                usages_of(&f.id, "reads")
+            } else {
+                Vec::new()
+            };
+            serde_json::json!({
+                "field": {
+                    "name": f.name, "qualifiedName": f.qualified_name,
+                    "nodeId": f.id, "file": f.file_path, "line": f.start_line,
+                },
+                "writerCount": writers.len(),
+                "writers": writers,
+                "readerCount": readers.len(),
+                "readers": readers,
+            })
+        })
+        .collect();
+
+    json_text(&serde_json::json!({
+        "query": field,
+        "matchCount": results.len(),
+        "fields": results,
+    }))
+}
+
+// 50. codegraph_wire_contract
+pub fn handle_wire_contract(store_arc: &Arc<Mutex<GraphStore>>, type_ref: &str) -> String {
+    let store = store_arc.lock().unwrap_or_else(|e| e.into_inner());
+
+    let is_type = |n: &CodeNode| {
+        matches!(
+            n.kind,
+            NodeKind::Class | NodeKind::Struct | NodeKind::Interface
+        )
+    };
+    let candidates: Vec<CodeNode> = match store.get_node(type_ref) {
+        Ok(Some(n)) => vec![n],
+        _ => store
+            .get_nodes_by_name(type_ref)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(is_type)
+            .collect(),
+    };
+    // Prefer the candidate that actually has a recorded serialization contract.
+    let Some((target, fields)) = candidates
+        .iter()
+        .map(|n| (n, store.get_wire_fields(&n.id).unwrap_or_default()))
+        .max_by_key(|(_, fields)| !fields.is_empty())
+    else {
+        return json_text(
+            &serde_json::json!({"error": format!("Type \"{}\" not found.", type_ref)}),
+        );
+    };
+
+    let serializable = !fields.is_empty();
+    let field_rows: Vec<serde_json::Value> = if serializable {
+        fields
+            .iter()
+            .map(|f| {
+                serde_json::json!({
+                    "field": f.field_name, "wireName": f.wire_name,
+                    "renamed": f.field_name != f.wire_name,
+                    "skipped": f.skipped, "optional": f.optional,
+                    "nodeId": f.node_id, "line": f.line,
+                })
+            })
+            .collect()
+    } else {
+        // No serialization annotations: the wire shape is unknown, list plain fields.
+        store
+            .get_out_edges(&target.id, Some("contains"))
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|e| store.get_node(&e.target).ok().flatten())
+            .filter(|n| n.kind == NodeKind::Property)
+            .map(|n| {
+                serde_json::json!({
+                    "field": n.name, "wireName": n.name, "renamed": false,
+                    "skipped": false, "optional": false,
+                    "nodeId": n.id, "line": n.start_line,
+                })
+            })
+            .collect()
+    };
+    let shape: serde_json::Map<String, serde_json::Value> = fields
+        .iter()
+        .filter(|f| !f.skipped)
+        .map(|f| {
+            let ty = if f.optional { "optional" } else { "required" };
+            (f.wire_name.clone(), serde_json::Value::from(ty))
+        })
+        .collect();
+
+    // Consumers: anything referencing the type, plus readers/writers of its fields.
+    let mut seen: HashSet<String> = HashSet::new();
+    let mut consumers: Vec<serde_json::Value> = Vec::new();
+    let mut in_edges = store.get_in_edges(&target.id, None).unwrap_or_default();
+    for f in &fields {
+        in_edges.extend(store.get_in_edges(&f.node_id, None).unwrap_or_default());
+    }
+    for edge in in_edges {
+        if edge.kind == EdgeKind::Contains || !seen.insert(edge.source.clone()) {
+            continue;
+        }
+        let Some(src) = store.get_node(&edge.source).ok().flatten() else {
+            continue;
+        };
+        let role = classify_wire_role(src.body.as_deref().unwrap_or(""));
+        consumers.push(serde_json::json!({
+            "symbol": src.qualified_name.as_deref().unwrap_or(&src.name),
+            "kind": src.kind.as_str(), "nodeId": src.id,
+            "file": edge.file_path, "line": edge.line,
+            "role": role.as_str(),
+        }));
+    }
+    consumers.sort_by_key(|c| c["role"] != "parses");
+
+    json_text(&serde_json::json!({
+        "type": {
+            "name": target.name, "kind": target.kind.as_str(), "nodeId": target.id,
+            "file": target.file_path, "line": target.start_line,
+        },
+        "serializable": serializable,
+        "framework": fields.first().map(|f| f.framework.as_str()),
+        "shape": shape,
+        "fields": field_rows,
+        "consumerCount": consumers.len(),
+        "consumers": consumers,
+    }))
+}
+
+// 53. codegraph_import_side_effects
+pub fn handle_import_side_effects(
+    store_arc: &Arc<Mutex<GraphStore>>,
+    file_path: Option<String>,
+    kind: Option<String>,
+    include_tests: bool,
+) -> String {
+    let store = store_arc.lock().unwrap_or_else(|e| e.into_inner());
+    let kind = match kind
+        .as_deref()
+        .map(|k| (k, SideEffectKind::from_str_loose(k)))
+    {
+        None => None,
+        Some((_, Some(k))) => Some(k),
+        Some((k, None)) => {
+            return json_text(&serde_json::json!({
+                "error": format!("Unknown side-effect kind \"{}\". Use call, initializer, control_flow, or static_block.", k),
+            }));
+        }
+    };
+    let mut effects = match store.get_side_effects(file_path.as_deref()) {
+        Ok(e) => e,
+        Err(e) => return json_text(&serde_json::json!({"error": e.to_string()})),
+    };
+    effects.retain(|e| {
+        kind.is_none_or(|k| e.kind == k) && (include_tests || !is_test_file(&e.file_path))
+    });
+
+    // Effects arrive ordered by file, so modules are contiguous runs.
+    let mut modules: Vec<serde_json::Value> = Vec::new();
+    for run in effects.chunk_by(|a, b| a.file_path == b.file_path) {
+        modules.push(serde_json::json!({
+            "file": run[0].file_path,
+            "effectCount": run.len(),
+            "effects": run.iter().map(|e| serde_json::json!({
+                "line": e.line, "kind": e.kind.as_str(), "statement": e.statement,
+            })).collect::<Vec<_>>(),
+        }));
+    }
+    modules.sort_by_key(|m| std::cmp::Reverse(m["effectCount"].as_u64().unwrap_or(0)));
+
+    if modules.is_empty() {
+        return json_text(&serde_json::json!({
+            "moduleCount": 0,
+            "message": "No import-time side effects found.",
+        }));
+    }
+    json_text(&serde_json::json!({
+        "moduleCount": modules.len(),
+        "effectCount": effects.len(),
+        "modules": modules,
+    }))
+}
+
+// 54. codegraph_global_state
+pub fn handle_global_state(
+    store_arc: &Arc<Mutex<GraphStore>>,
+    file_path: Option<String>,
+    kind: Option<String>,
+    include_tests: bool,
+    limit: Option<usize>,
+) -> String {
+    let store = store_arc.lock().unwrap_or_else(|e| e.into_inner());
+    let kind = match kind
+        .as_deref()
+        .map(|k| (k, GlobalStateKind::from_str_loose(k)))
+    {
+        None => None,
+        Some((_, Some(k))) => Some(k),
+        Some((k, None)) => {
+            return json_text(&serde_json::json!({
+                "error": format!("Unknown state kind \"{}\". Use module_variable, static_field, or singleton.", k),
+            }));
+        }
+    };
+    let mut states = match global_state::inventory_global_state(&store) {
+        Ok(s) => s,
+        Err(e) => return json_text(&serde_json::json!({"error": e.to_string()})),
+    };
+    states.retain(|s| {
+        kind.is_none_or(|k| s.kind == k)
+            && file_path
+                .as_deref()
+                .is_none_or(|p| s.node.file_path.starts_with(p))
+            && (include_tests || !is_test_file(&s.node.file_path))
+    });
+
+    if states.is_empty() {
+        return json_text(&serde_json::json!({
+            "stateCount": 0,
+            "message": "No global mutable state found.",
+        }));
+    }
+    let accessors = |list: &[StateAccessor]| -> Vec<serde_json::Value> {
+        list.iter()
+            .map(|a| {
+                serde_json::json!({
+                    "symbol": a.name, "kind": a.kind.as_str(), "nodeId": a.node_id,
+                    "file": a.file_path, "line": a.line,
+                })
+            })
+            .collect()
+    };
+    let total = states.len();
+    let entries: Vec<serde_json::Value> = states
+        .iter()
+        .take(limit.unwrap_or(50))
+        .map(|s| {
+            serde_json::json!({
+                "name": s.node.qualified_name.as_deref().unwrap_or(&s.node.name),
+                "kind": s.kind.as_str(), "nodeId": s.node.id,
+                "file": s.node.file_path, "line": s.node.start_line,
+                "declaration": s.declaration,
+                "writerCount": s.writers.len(),
+                "writers": accessors(&s.writers),
+                "readerCount": s.readers.len(),
+                "readers": accessors(&s.readers),
+            })
+        })
+        .collect();
+    let count_of = |k: GlobalStateKind| states.iter().filter(|s| s.kind == k).count();
+
+    json_text(&serde_json::json!({
+        "stateCount": total,
+        "byKind": {
+            "moduleVariable": count_of(GlobalStateKind::ModuleVariable),
+            "staticField": count_of(GlobalStateKind::StaticField),
+            "singleton": count_of(GlobalStateKind::Singleton),
+        },
+        "states": entries,
+    }))
+}
+
+// 57. codegraph_find_literal
+pub fn handle_find_literal(
+    store_arc: &Arc<Mutex<GraphStore>>,
+    query: &str,
+    exact: bool,
+    kind: Option<String>,
+    file_path: Option<String>,
+    include_tests: bool,
+    limit: Option<usize>,
+) -> String {
+    let store = store_arc.lock().unwrap_or_else(|e| e.into_inner());
+    if query.is_empty() {
+        return json_text(&serde_json::json!({"error": "query must not be empty"}));
+    }
+    let kind = match kind.as_deref().map(|k| (k, LiteralKind::from_str_loose(k))) {
+        None => None,
+        Some((_, Some(k))) => Some(k),
+        Some((k, None)) => {
+            return json_text(&serde_json::json!({
+                "error": format!("Unknown literal kind \"{}\". Use string or constant.", k),
+            }));
+        }
+    };
+    let mut literals = match store.find_literals(query, exact, kind) {
+        Ok(l) => l,
+        Err(e) => return json_text(&serde_json::json!({"error": e.to_string()})),
+    };
+    literals.retain(|l| {
+        file_path
+            .as_deref()
+            .is_none_or(|p| l.file_path.starts_with(p))
+            && (include_tests || !is_test_file(&l.file_path))
+    });
+    let limit = limit.unwrap_or(50);
+
+    let mut symbols: HashMap<String, Option<CodeNode>> = HashMap::new();
+    let mut symbol_name = |id: &Option<String>| -> Option<String> {
+        let id = id.as_ref()?;
+        symbols
+            .entry(id.clone())
+            .or_insert_with(|| store.get_node(id).ok().flatten())
+            .as_ref()
+            .map(|n| n.qualified_name.clone().unwrap_or_else(|| n.name.clone()))
+    };
+
+    // Constants with the functions that use them: the declaring file and
+    // the files importing it.
+    let (constant_rows, string_rows): (Vec<_>, Vec<_>) = literals
+        .iter()
+        .partition(|l| l.kind == LiteralKind::Constant);
+    let mut constants = Vec::new();
+    for c in constant_rows.iter().take(limit) {
+        let name = c.name.as_deref().unwrap_or_default();
+        let mut files = vec![c.file_path.clone()];
+        files.extend(store.get_importing_files(&c.file_path).unwrap_or_default());
+        let nodes: Vec<CodeNode> = files
+            .iter()
+            .filter(|f| include_tests || !is_test_file(f))
+            .flat_map(|f| store.get_nodes_by_file(f).unwrap_or_default())
+            .filter(|n| matches!(n.kind, NodeKind::Function | NodeKind::Method))
+            .collect();
+        let uses = find_constant_uses(name, (&c.file_path, c.line), &nodes);
+        constants.push(serde_json::json!({
+            "name": name, "value": c.value, "file": c.file_path, "line": c.line,
+            "symbol": symbol_name(&c.node_id),
+            "useCount": uses.len(),
+            "uses": uses.iter().take(limit).map(|u| serde_json::json!({
+                "symbol": u.symbol, "nodeId": u.node_id, "file": u.file_path, "line": u.line,
+            })).collect::<Vec<_>>(),
+        }));
+    }
+
+    // Strings grouped by value, most frequent first.
+    let mut by_value: Vec<(&str, Vec<_>)> = Vec::new();
+    for l in &string_rows {
+        match by_value.iter_mut().find(|(v, _)| *v == l.value) {
+            Some((_, sites)) => sites.push(*l),
+            None => by_value.push((l.value.as_str(), vec![*l])),
+        }
+    }
+    by_value.sort_by(|a, b| b.1.len().cmp(&a.1.len()).then(a.0.cmp(b.0)));
+    let strings: Vec<serde_json::Value> = by_value
+        .iter()
+        .take(limit)
+        .map(|(value, sites)| {
+            serde_json::json!({
+                "value": value,
+                "occurrenceCount": sites.len(),
+                "sites": sites.iter().take(limit).map(|s| serde_json::json!({
+                    "file": s.file_path, "line": s.line,
+                    "symbol": symbol_name(&s.node_id), "nodeId": s.node_id,
+                })).collect::<Vec<_>>(),
+            })
+        })
+        .collect();
+
+    if constants.is_empty() && strings.is_empty() {
+        return json_text(&serde_json::json!({
+            "query": query,
+            "exact": exact,
+            "message": format!("No string literals or constants match \"{}\".", query),
+        }));
+    }
+    json_text(&serde_json::json!({
+        "query": query,
+        "exact": exact,
+        "constantCount": constant_rows.len(),
+        "constants": constants,
+        "stringCount": by_value.len(),
+        "strings": strings,
+    }))
+}
+
+/// Compiled size cap of a `codegraph_grep` pattern, against patterns
+/// that blow up into huge automata.
+const GREP_REGEX_SIZE_LIMIT: usize = 1 << 20;
+
+// 79. codegraph_grep
+/// Regex matches, line by line, in the stored bodies of indexed symbols. A
+/// line inside nested symbols (a method in a class) is reported once, under
+/// the innermost one.
+pub fn handle_grep(
+    store_arc: &Arc<Mutex<GraphStore>>,
+    pattern: &str,
+    path: Option<String>,
+    kind: Option<String>,
+    case_sensitive: bool,
+    include_tests: bool,
+    limit: Option<usize>,
+) -> String {
+    let store = store_arc.lock().unwrap_or_else(|e| e.into_inner());
+    let regex = match regex::RegexBuilder::new(pattern)
+        .case_insensitive(!case_sensitive)
+        .size_limit(GREP_REGEX_SIZE_LIMIT)
+        .build()
+    {
+        Ok(r) => r,
+        Err(e) => {
+            return json_text(&serde_json::json!({"error": format!("Invalid regex: {e}")}));
+        }
+    };
+    let kind = match kind.as_deref().map(|k| (k, NodeKind::from_str_loose(k))) {
+        None => None,
+        Some((_, Some(k))) => Some(k),
+        Some((k, None)) => {
+            return json_text(&serde_json::json!({
+                "error": format!("Unknown symbol kind \"{}\".", k),
+            }));
+        }
+    };
+    let limit = limit.unwrap_or(100).clamp(1, 1000);
+
+    // (file, line) -> (span of the symbol, index into `symbols`, line text)
+    let mut hits: HashMap<(String, u32), (u32, usize, String)> = HashMap::new();
+    let mut symbols: Vec<CodeNode> = Vec::new();
+    let mut searched = 0;
+    for batch in store.iter_nodes(500) {
+        let batch = match batch {
+            Ok(b) => b,
+            Err(e) => return json_text(&serde_json::json!({"error": e.to_string()})),
+        };
+        for mut node in batch {
+            if kind.is_some_and(|k| node.kind != k)
+                || path
+                    .as_deref()
+                    .is_some_and(|p| !node.file_path.starts_with(p))
+                || (!include_tests && is_test_file(&node.file_path))
+            {
+                continue;
+            }
+            let Some(body) = node.body.take() else {
+                continue;
+            };
+            searched += 1;
+            let span = node.end_line.saturating_sub(node.start_line);
+            let mut matched = false;
+            for (i, line) in body.lines().enumerate() {
+                if !regex.is_match(line) {
+                    continue;
+                }
+                let at = (node.file_path.clone(), node.start_line + i as u32);
+                if hits.get(&at).is_some_and(|(inner, _, _)| *inner <= span) {
+                    continue;
+                }
+                let text: String = line.trim().chars().take(200).collect();
+                hits.insert(at, (span, symbols.len(), text));
+                matched = true;
+            }
+            if matched {
+                symbols.push(node);
+            }
+        }
+    }
+
+    if hits.is_empty() {
+        return json_text(&serde_json::json!({
+            "pattern": pattern,
+            "symbolsSearched": searched,
+            "matchCount": 0,
+            "message": format!("No indexed symbol body matches /{}/.", pattern),
+        }));
+    }
+
+    let match_count = hits.len();
+    let mut hits: Vec<_> = hits.into_iter().collect();
+    hits.sort_by(|a, b| a.0.cmp(&b.0));
+    let mut order: Vec<usize> = Vec::new();
+    let mut by_symbol: HashMap<usize, Vec<serde_json::Value>> = HashMap::new();
+    for ((_, line), (_, index, text)) in hits.into_iter().take(limit) {
+        by_symbol
+            .entry(index)
+            .or_insert_with(|| {
+                order.push(index);
+                Vec::new()
+            })
+            .push(serde_json::json!({"line": line, "text": text}));
+    }
+    let results: Vec<serde_json::Value> = order
+        .iter()
+        .map(|index| {
+            let n = &symbols[*index];
+            serde_json::json!({
+                "id": n.id, "name": n.name, "qualifiedName": n.qualified_name,
+                "kind": n.kind.as_str(), "filePath": n.file_path,
+                "startLine": n.start_line, "endLine": n.end_line,
+                "matches": by_symbol.remove(index).unwrap_or_default(),
+            })
+        })
+        .collect();
+
+    json_text(&serde_json::json!({
+        "pattern": pattern,
+        "symbolsSearched": searched,
+        "matchCount": match_count,
+        "truncated": match_count > limit,
+        "symbolCount": results.len(),
+        "symbols": results,
+    }))
+}
+
+// 80. codegraph_usage
+/// Recorded tool calls per tool over the last `days` days (all recorded
+/// calls without it), plus the enabled tools nobody called.
+pub fn handle_usage(
+    store_arc: &Arc<Mutex<GraphStore>>,
+    enabled: &HashSet<String>,
+    days: Option<u32>,
+    session: Option<&str>,
+) -> String {
+    let store = store_arc.lock().unwrap_or_else(|e| e.into_inner());
+    let since = days.map(|d| chrono::Utc::now().timestamp() - i64::from(d) * 86_400);
+    let report = match usage_report(&store.conn, since, session) {
+        Ok(report) => report,
+        Err(e) => return json_text(&serde_json::json!({ "error": e.to_string() })),
+    };
+    let mut unused: Vec<&str> = enabled
+        .iter()
+        .map(String::as_str)
+        .filter(|name| !report.tools.iter().any(|t| t.tool == *name))
+        .collect();
+    unused.sort_unstable();
+    json_text(&serde_json::json!({
+        "days": days,
+        "session": session,
+        "totalCalls": report.total_calls,
+        "errorCount": report.error_count,
+        "sessions": report.sessions,
+        "toolCount": report.tools.len(),
+        "tools": report.tools,
+        "unusedEnabledTools": unused,
+    }))
+}
+
+// 58. codegraph_tag
+pub fn handle_tag(
+    store_arc: &Arc<Mutex<GraphStore>>,
+    symbol: &str,
+    set: Vec<String>,
+    remove: Vec<String>,
+) -> String {
+    let node = match resolve_symbol(store_arc, symbol) {
+        Some(n) => n,
+        None => return symbol_not_found(store_arc, symbol),
+    };
+    let mut to_set = Vec::new();
+    for tag in &set {
+        match TagFilter::parse(tag) {
+            Some(TagFilter {
+                key,
+                value: Some(value),
+            }) => to_set.push((key, value)),
+            _ => {
+                return json_text(&serde_json::json!({
+                    "error": format!("Invalid tag \"{}\". Use key=value.", tag),
+                }));
+            }
+        }
+    }
+
+    let store = store_arc.lock().unwrap_or_else(|e| e.into_inner());
+    let mut removed = Vec::new();
+    for key in &remove {
+        match store.remove_node_tag(&node.id, key.trim()) {
+            Ok(true) => removed.push(key.trim()),
+            Ok(false) => {}
+            Err(e) => return json_text(&serde_json::json!({"error": e.to_string()})),
+        }
+    }
+    for (key, value) in &to_set {
+        if let Err(e) = store.set_node_tag(&node.id, key, value) {
+            return json_text(&serde_json::json!({"error": e.to_string()}));
+        }
+    }
+    let tags = store.get_node_tags(&node.id).unwrap_or_default();
+    json_text(&serde_json::json!({
+        "nodeId": node.id, "name": node.name, "kind": node.kind.as_str(),
+        "file": node.file_path, "line": node.start_line,
+        "set": to_set.len(),
+        "removed": removed,
+        "tags": tags.iter().map(|t| (t.key.clone(), t.value.clone())).collect::<BTreeMap<_, _>>(),
+    }))
+}
+
+// 69. codegraph_annotate
+/// Add a note to `symbol` and/or delete notes by ID, returning all of the
+/// symbol's annotations.
+pub fn handle_annotate(
+    store_arc: &Arc<Mutex<GraphStore>>,
+    symbol: &str,
+    note: Option<String>,
+    author: Option<String>,
+    remove: Vec<i64>,
+) -> String {
+    let node = match resolve_symbol(store_arc, symbol) {
+        Some(n) => n,
+        None => return symbol_not_found(store_arc, symbol),
+    };
+    let store = store_arc.lock().unwrap_or_else(|e| e.into_inner());
+    let mut removed = Vec::new();
+    for id in remove {
+        match store.remove_node_note(&node.id, id) {
+            Ok(true) => removed.push(id),
+            Ok(false) => {}
+            Err(e) => return json_text(&serde_json::json!({"error": e.to_string()})),
+        }
+    }
+    let mut added = None;
+    if let Some(note) = note.as_deref().map(str::trim).filter(|n| !n.is_empty()) {
+        let author = author.as_deref().map(str::trim).filter(|a| !a.is_empty());
+        match store.add_node_note(&node.id, note, author) {
+            Ok(id) => added = id,
+            Err(e) => return json_text(&serde_json::json!({"error": e.to_string()})),
+        }
+    }
+    let annotations = NodeAnnotations {
+        tags: store.get_node_tags(&node.id).unwrap_or_default(),
+        notes: store.get_node_notes(&node.id).unwrap_or_default(),
+    };
+    json_text(&serde_json::json!({
+        "nodeId": node.id, "name": node.name, "kind": node.kind.as_str(),
+        "file": node.file_path, "line": node.start_line,
+        "added": added,
+        "removed": removed,
+        "annotations": annotations_json(&annotations),
+    }))
+}
+
+// 59. codegraph_tagged
+pub fn handle_tagged(
+    store_arc: &Arc<Mutex<GraphStore>>,
+    tags: Vec<String>,
+    kind: Option<String>,
+    limit: Option<usize>,
+) -> String {
+    let store = store_arc.lock().unwrap_or_else(|e| e.into_inner());
+    let filters: Vec<TagFilter> = tags.iter().filter_map(|t| TagFilter::parse(t)).collect();
+    if filters.is_empty() {
+        return match store.get_tag_counts() {
+            Ok(counts) if counts.is_empty() => json_text(&serde_json::json!({
+                "tagCount": 0,
+                "message": "No tags set. Use codegraph_tag to label symbols.",
+            })),
+            Ok(counts) => json_text(&serde_json::json!({
+                "tagCount": counts.len(),
+                "tags": counts.iter().map(|(key, value, nodes)| serde_json::json!({
+                    "key": key, "value": value, "symbolCount": nodes,
+                })).collect::<Vec<_>>(),
+            })),
+            Err(e) => json_text(&serde_json::json!({"error": e.to_string()})),
+        };
+    }
+
+    let ids = match store.find_tagged_nodes(&filters) {
+        Ok(ids) => ids,
+        Err(e) => return json_text(&serde_json::json!({"error": e.to_string()})),
+    };
+    // Tags outlive their node until a re-index brings the symbol back.
+    let mut symbols: Vec<serde_json::Value> = Vec::new();
+    let mut total = 0;
+    for id in &ids {
+        let Ok(Some(node)) = store.get_node(id) else {
+            continue;
+        };
+        if kind.as_deref().is_some_and(|k| node.kind.as_str() != k) {
+            continue;
+        }
+        total += 1;
+        if symbols.len() >= limit.unwrap_or(50) {
+            continue;
+        }
+        let tags = store.get_node_tags(id).unwrap_or_default();
+        symbols.push(serde_json::json!({
+            "nodeId": node.id, "name": node.name, "kind": node.kind.as_str(),
+            "file": node.file_path, "line": node.start_line,
+            "tags": tags.iter().map(|t| (t.key.clone(), t.value.clone())).collect::<BTreeMap<_, _>>(),
+        }));
+    }
+    json_text(&serde_json::json!({
+        "filter": tags,
+        "symbolCount": total,
+        "symbols": symbols,
+    }))
+}
+
+// 62. codegraph_duplicate_definitions
+pub fn handle_duplicate_definitions(
+    store_arc: &Arc<Mutex<GraphStore>>,
+    file_path: Option<String>,
+    class: Option<String>,
+    min_similarity: Option<f64>,
+    include_tests: bool,
+    limit: Option<usize>,
+) -> String {
+    let store = store_arc.lock().unwrap_or_else(|e| e.into_inner());
+    let class = match class
+        .as_deref()
+        .map(|c| (c, DuplicateClass::from_str_loose(c)))
+    {
+        None => None,
+        Some((_, Some(c))) => Some(c),
+        Some((c, None)) => {
+            return json_text(&serde_json::json!({
+                "error": format!("Unknown duplicate class \"{}\". Use identical, near_duplicate, or divergent.", c),
+            }));
+        }
+    };
+    let mut groups = match duplicates::find_duplicate_definitions(&store, include_tests) {
+        Ok(g) => g,
+        Err(e) => return json_text(&serde_json::json!({"error": e.to_string()})),
+    };
+    let min_similarity = min_similarity.unwrap_or(0.0);
+    groups.retain(|g| {
+        class.is_none_or(|c| g.class == c)
+            && g.max_similarity >= min_similarity
+            && file_path
+                .as_deref()
+                .is_none_or(|p| g.members.iter().any(|m| m.file_path.starts_with(p)))
+    });
+
+    if groups.is_empty() {
+        return json_text(&serde_json::json!({
+            "groupCount": 0,
+            "message": "No duplicate definitions found.",
+        }));
+    }
+    let round = |x: f64| (x * 100.0).round() / 100.0;
+    let consolidatable = groups
+        .iter()
+        .filter(|g| g.class != DuplicateClass::Divergent)
+        .count();
+    let total = groups.len();
+    let entries: Vec<serde_json::Value> = groups
+        .iter()
+        .take(limit.unwrap_or(50))
+        .map(|g| {
+            serde_json::json!({
+                "name": g.name,
+                "class": g.class.as_str(),
+                "definitionCount": g.members.len(),
+                "maxSimilarity": round(g.max_similarity),
+                "minSimilarity": round(g.min_similarity),
+                "definitions": g.members.iter().map(|m| serde_json::json!({
+                    "nodeId": m.id, "kind": m.kind.as_str(),
+                    "file": m.file_path, "line": m.start_line,
+                    "lines": m.end_line.saturating_sub(m.start_line) + 1,
+                })).collect::<Vec<_>>(),
+                "pairs": g.pairs.iter().take(10).map(|p| serde_json::json!({
+                    "a": g.members[p.a].id, "b": g.members[p.b].id,
+                    "similarity": round(p.similarity),
+                })).collect::<Vec<_>>(),
+            })
+        })
+        .collect();
+    json_text(&serde_json::json!({
+        "groupCount": total,
+        "consolidatableCount": consolidatable,
+        "groups": entries,
+    }))
+}
+
+// 66. codegraph_find_clones
+pub fn handle_find_clones(
+    store_arc: &Arc<Mutex<GraphStore>>,
+    opts: CloneOptions,
+    limit: Option<usize>,
+) -> String {
+    let store = store_arc.lock().unwrap_or_else(|e| e.into_inner());
+    let report = match find_clones(&store, &opts) {
+        Ok(r) => r,
+        Err(e) => return json_text(&serde_json::json!({"error": e.to_string()})),
+    };
+    if report.clusters.is_empty() {
+        return json_text(&serde_json::json!({
+            "clusterCount": 0,
+            "candidates": report.candidates,
+            "vectors": report.source.as_str(),
+            "message": format!("No clones above similarity {}.", opts.threshold),
+        }));
+    }
+    let round = |x: f32| (x * 1000.0).round() / 1000.0;
+    let total = report.clusters.len();
+    let clusters: Vec<serde_json::Value> = report
+        .clusters
+        .iter()
+        .take(limit.unwrap_or(50))
+        .map(|c| {
+            serde_json::json!({
+                "size": c.members.len(),
+                "maxSimilarity": round(c.max_similarity),
+                "members": c.members.iter().map(|m| serde_json::json!({
+                    "nodeId": m.id, "name": m.name, "kind": m.kind.as_str(),
+                    "file": m.file_path, "line": m.start_line,
+                    "lines": m.end_line.saturating_sub(m.start_line) + 1,
+                })).collect::<Vec<_>>(),
+                "pairs": c.pairs.iter().take(10).map(|p| serde_json::json!({
+                    "a": c.members[p.a].id, "b": c.members[p.b].id,
+                    "similarity": round(p.similarity),
+                })).collect::<Vec<_>>(),
+            })
+        })
+        .collect();
+    json_text(&serde_json::json!({
+        "clusterCount": total,
+        "candidates": report.candidates,
+        "vectors": report.source.as_str(),
+        "clusters": clusters,
+    }))
+}
+
+const MISSING_EMBEDDINGS_SQL: &str = "\
+SELECT id FROM nodes
+WHERE id NOT IN (SELECT node_id FROM embedding_cache)
+ORDER BY file_path, start_line";
+const TOP_RANKED_SQL: &str = "\
+SELECT n.id, n.name, n.type, n.file_path, r.page_rank
+FROM node_rank r JOIN nodes n ON n.id = r.node_id
+ORDER BY r.page_rank DESC LIMIT ?1";
+
+/// Nodes embedded per model call.
+#[cfg(feature = "embedding")]
+const EMBEDDING_BATCH: usize = 64;
+
+// 77. codegraph_backfill_embeddings
+pub fn handle_backfill_embeddings(
+    store_arc: &Arc<Mutex<GraphStore>>,
+    limit: Option<usize>,
+    ann_index: Option<bool>,
+    progress: Option<&TaskProgress>,
+) -> String {
+    let missing: Vec<String> = {
+        let store = store_arc.lock().unwrap_or_else(|e| e.into_inner());
+        let ids = store
+            .conn
+            .prepare(MISSING_EMBEDDINGS_SQL)
+            .and_then(|mut stmt| {
+                stmt.query_map([], |row| row.get(0))?
+                    .collect::<rusqlite::Result<Vec<String>>>()
+            });
+        match ids {
+            Ok(ids) => ids,
+            Err(e) => return json_text(&serde_json::json!({"error": e.to_string()})),
+        }
+    };
+    let targets = &missing[..limit.unwrap_or(missing.len()).min(missing.len())];
+    let (embedded, cancelled) = if targets.is_empty() {
+        (0, false)
+    } else {
+        match embed_batches(store_arc, targets, ann_index, progress) {
+            Ok(done) => done,
+            Err(e) => {
+                return json_text(&serde_json::json!({
+                    "error": e,
+                    "missing": missing.len(),
+                }))
+            }
+        }
+    };
+    json_text(&serde_json::json!({
+        "missing": missing.len(),
+        "embedded": embedded,
+        "remaining": missing.len() - embedded,
+        "cancelled": cancelled,
+    }))
+}
+
+/// Embed `ids` batch by batch, holding the store lock only to read and
+/// write. Returns the number embedded and whether the task was cancelled.
+#[cfg(feature = "embedding")]
+fn embed_batches(
+    store_arc: &Arc<Mutex<GraphStore>>,
+    ids: &[String],
+    ann_index: Option<bool>,
+    progress: Option<&TaskProgress>,
+) -> std::result::Result<(usize, bool), String> {
+    use crate::indexer::embedder::{store_embeddings, EmbeddingEngine};
+
+    let engine = EmbeddingEngine::try_new().map_err(|e| e.to_string())?;
+    let mut embedded: Vec<String> = Vec::new();
+    let mut cancelled = false;
+    for batch in ids.chunks(EMBEDDING_BATCH) {
+        if progress.is_some_and(TaskProgress::is_cancelled) {
+            cancelled = true;
+            break;
+        }
+        let nodes: Vec<CodeNode> = {
+            let store = store_arc.lock().unwrap_or_else(|e| e.into_inner());
+            batch
+                .iter()
+                .filter_map(|id| store.get_node(id).ok().flatten())
+                .collect()
+        };
+        let embeddings = engine.embed_nodes(&nodes).map_err(|e| e.to_string())?;
+        {
+            let store = store_arc.lock().unwrap_or_else(|e| e.into_inner());
+            store_embeddings(&store.conn, &nodes, &embeddings).map_err(|e| e.to_string())?;
+        }
+        embedded.extend(nodes.into_iter().map(|n| n.id));
+        if let Some(progress) = progress {
+            progress.step(embedded.len(), ids.len(), "Embedded nodes");
+        }
+    }
+    if !embedded.is_empty() {
+        if let Some(progress) = progress {
+            progress.message("Updating the ANN index");
+        }
+        let store = store_arc.lock().unwrap_or_else(|e| e.into_inner());
+        if let Err(e) = crate::graph::ann::refresh_sidecar(&store.conn, &embedded, ann_index) {
+            tracing::warn!("ANN index update failed: {e}");
+        }
+    }
+    Ok((embedded.len(), cancelled))
+}
+
+#[cfg(not(feature = "embedding"))]
+fn embed_batches(
+    _store_arc: &Arc<Mutex<GraphStore>>,
+    _ids: &[String],
+    _ann_index: Option<bool>,
+    _progress: Option<&TaskProgress>,
+) -> std::result::Result<(usize, bool), String> {
+    Err("CodeGraph was built without the embedding feature".to_string())
+}
+
+// 78. codegraph_recompute_pagerank
+pub fn handle_recompute_pagerank(
+    store_arc: &Arc<Mutex<GraphStore>>,
+    top: Option<usize>,
+    progress: Option<&TaskProgress>,
+) -> String {
+    if let Some(progress) = progress {
+        progress.message("Computing PageRank");
+    }
+    let started = std::time::Instant::now();
+    let store = store_arc.lock().unwrap_or_else(|e| e.into_inner());
+    let ranked = match refresh_node_rank(&store) {
+        Ok(n) => n,
+        Err(e) => return json_text(&serde_json::json!({"error": e.to_string()})),
+    };
+    let duration_ms = started.elapsed().as_millis() as u64;
+    let top = store
+        .conn
+        .prepare(TOP_RANKED_SQL)
+        .and_then(|mut stmt| {
+            stmt.query_map([top.unwrap_or(10) as i64], |row| {
+                Ok(serde_json::json!({
+                    "id": row.get::<_, String>(0)?,
+                    "name": row.get::<_, String>(1)?,
+                    "kind": row.get::<_, String>(2)?,
+                    "filePath": row.get::<_, String>(3)?,
+                    "pageRank": row.get::<_, f64>(4)?,
+                }))
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()
+        })
+        .unwrap_or_default();
+    json_text(&serde_json::json!({
+        "rankedNodes": ranked,
+        "durationMs": duration_ms,
+        "top": top,
+    }))
+}
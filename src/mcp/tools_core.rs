@@ -1,100 +1,249 @@
-//! Core MCP tool handler implementations (14 tools).
+//! Core MCP tool handler implementations (19 tools).
 //!
 //! Contains the business logic for: query, search, dependencies, callers,
 //! callees, impact, structure, tests, context, diagram, node, dead_code,
-//! frameworks, and languages.
+//! frameworks, languages, complete, examples, saved_query, similar, and
+//! federated_query.
 
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::Path;
 use std::sync::{Arc, Mutex};
 
 use crate::config::schema::CodeGraphConfig;
 use crate::context::assembler::ContextAssembler;
-use crate::graph::ranking::GraphRanking;
-use crate::graph::search::{HybridSearch, SearchOptions};
-use crate::graph::store::GraphStore;
+use crate::db::findings::{FindingCategory, NewFinding};
+use crate::db::saved_queries::{
+    delete_saved_query, find_saved_query, list_saved_queries, save_query, SavedQuery,
+};
+use crate::git::node_meta::age_in_days;
+use crate::graph::complete::complete;
+use crate::graph::examples::find_usage_examples;
+use crate::graph::ranking::{GraphRanking, RankedNode};
+use crate::graph::search::{
+    decode_cursor, encode_cursor, HybridSearch, SearchExplanation, SearchOptions, SearchResult,
+};
+use crate::graph::stats::{load_dir_stats, load_language_lines, rollup, RollupKey};
+use crate::graph::store::{GraphStore, TagFilter};
 use crate::graph::traversal::GraphTraversal;
-use crate::resolution::dead_code::find_dead_code;
+use crate::indexer::loc::LineCounts;
+use crate::resolution::dead_code::{find_unreachable_code, orphan_evidence, DeadCodeRoots};
+use crate::resolution::dispatch::{DispatchIndex, DispatchLink};
 use crate::resolution::frameworks::detect_frameworks;
-use crate::types::{CodeNode, NodeKind};
+use crate::resolution::projects::{detect_projects, project_for_file, Project};
+use crate::types::{CodeNode, EdgeKind, NodeKind};
 
 use super::server::{
     format_traversal_node, generate_graph_diagram, json_text, mermaid_id, mermaid_safe,
-    parse_detail_level, resolve_symbol, DetailLevel,
+    parse_detail_level, resolve_symbol, symbol_not_found, track_findings, DetailLevel,
 };
 
+/// Node and edge batch size when reading the graph.
+const BATCH_SIZE: usize = 500;
+
 // 1. codegraph_query
+#[allow(clippy::too_many_arguments)]
 pub fn handle_query(
     store: &Arc<Mutex<GraphStore>>,
     query: &str,
     limit: Option<usize>,
+    offset: Option<usize>,
+    cursor: Option<String>,
     language: Option<String>,
+    path: Option<String>,
+    tags: Vec<String>,
+    exclude_languages: Vec<String>,
+    exclude_kinds: Vec<String>,
+    exclude_paths: Vec<String>,
+    debug: bool,
     config: &CodeGraphConfig,
 ) -> String {
     let store = store.lock().unwrap_or_else(|e| e.into_inner());
-    let search = HybridSearch::new(&store.conn);
     let opts = SearchOptions {
         limit: Some(limit.unwrap_or(20)),
         language,
+        tags: tags.iter().filter_map(|t| TagFilter::parse(t)).collect(),
+        offset: offset.unwrap_or(0),
+        cursor,
+        path,
+        exclude_languages,
+        exclude_kinds,
+        exclude_paths,
+        debug,
         ..Default::default()
     };
-    match search.search(query, &opts) {
-        Ok(results) => {
-            if config.contexts.is_empty() {
-                json_text(&results)
-            } else {
-                let enriched: Vec<serde_json::Value> = results
-                    .iter()
-                    .map(|r| {
-                        let mut v = serde_json::to_value(r).unwrap_or_default();
-                        if let Some(ctx) = config.get_context_for_path(&r.file_path) {
-                            v["context"] = serde_json::json!(ctx);
-                        }
-                        v
-                    })
-                    .collect();
-                json_text(&enriched)
-            }
-        }
+    hybrid_search_response(&store, query, &opts, DetailLevel::Standard, config)
+}
+
+/// Run a hybrid search and render it with [`search_response`].
+fn hybrid_search_response(
+    store: &GraphStore,
+    query: &str,
+    opts: &SearchOptions,
+    level: DetailLevel,
+    config: &CodeGraphConfig,
+) -> String {
+    let search = HybridSearch::new(&store.conn)
+        .with_priors(config.ranking.prior_weights())
+        .with_synonyms(config.synonym_map());
+    match search.search_with_suggestions(query, opts) {
+        Ok(found) => search_response(
+            store,
+            &found.results,
+            found.did_you_mean,
+            found.next_cursor,
+            found.explanation.as_ref(),
+            level,
+            config,
+        ),
         Err(e) => json_text(&serde_json::json!({"error": e.to_string()})),
     }
 }
 
 // 1b. codegraph_search
+#[allow(clippy::too_many_arguments)]
 pub fn handle_search(
     store: &Arc<Mutex<GraphStore>>,
     query: &str,
     limit: Option<usize>,
+    offset: Option<usize>,
+    cursor: Option<String>,
     kind: Option<String>,
+    path: Option<String>,
+    tags: Vec<String>,
+    exclude_languages: Vec<String>,
+    exclude_kinds: Vec<String>,
+    exclude_paths: Vec<String>,
     config: &CodeGraphConfig,
 ) -> String {
     let store = store.lock().unwrap_or_else(|e| e.into_inner());
     let search = HybridSearch::new(&store.conn);
     let limit = limit.unwrap_or(10);
-    match search.search_by_keyword(query, limit) {
+    let tags: Vec<TagFilter> = tags.iter().filter_map(|t| TagFilter::parse(t)).collect();
+    let filters = SearchOptions {
+        path,
+        exclude_languages,
+        exclude_kinds,
+        exclude_paths,
+        ..Default::default()
+    };
+    let scope = format!(
+        "keyword|{:?}|{:?}|{}",
+        kind,
+        tags,
+        filters.candidate_scope()
+    );
+    let start = match cursor {
+        Some(ref c) => match decode_cursor(c, query, &scope) {
+            Ok(start) => start,
+            Err(e) => return json_text(&serde_json::json!({"error": e.to_string()})),
+        },
+        None => offset.unwrap_or(0),
+    };
+    // One extra result tells whether another page follows.
+    let fetch = start + limit + 1;
+    match search.search_by_keyword_in(query, fetch, &filters) {
         Ok(mut results) => {
+            let mut did_you_mean = Vec::new();
+            if results.is_empty() {
+                if let Ok(fuzzy) = search.search_fuzzy(query, fetch, &filters) {
+                    did_you_mean = fuzzy.did_you_mean;
+                    results = fuzzy.results;
+                }
+            }
             if let Some(ref kind_filter) = kind {
                 results.retain(|r| r.kind == *kind_filter);
             }
-            if config.contexts.is_empty() {
-                json_text(&results)
-            } else {
-                let enriched: Vec<serde_json::Value> = results
-                    .iter()
-                    .map(|r| {
-                        let mut v = serde_json::to_value(r).unwrap_or_default();
-                        if let Some(ctx) = config.get_context_for_path(&r.file_path) {
-                            v["context"] = serde_json::json!(ctx);
-                        }
-                        v
-                    })
+            if !tags.is_empty() {
+                let tagged: HashSet<String> = store
+                    .find_tagged_nodes(&tags)
+                    .unwrap_or_default()
+                    .into_iter()
                     .collect();
-                json_text(&enriched)
+                results.retain(|r| tagged.contains(&r.node_id));
             }
+            let next_cursor = (results.len() > start + limit)
+                .then(|| encode_cursor(query, &scope, start + limit));
+            let results: Vec<SearchResult> = results.into_iter().skip(start).take(limit).collect();
+            did_you_mean.retain(|name| results.iter().any(|r| r.name == *name));
+            search_response(
+                &store,
+                &results,
+                did_you_mean,
+                next_cursor,
+                None,
+                DetailLevel::Standard,
+                config,
+            )
         }
         Err(e) => json_text(&serde_json::json!({"error": e.to_string()})),
     }
 }
 
+/// Search results as JSON, each with its directory context when one is
+/// configured. When the results came from a fuzzy fallback, or more pages
+/// follow, they are wrapped with the names matched instead as
+/// `didYouMean` and the following page's `nextCursor`.
+///
+/// `level` trims results to name, kind and file (`Summary`) or adds each
+/// symbol's lines, body and documentation (`Full`).
+fn search_response(
+    store: &GraphStore,
+    results: &[SearchResult],
+    did_you_mean: Vec<String>,
+    next_cursor: Option<String>,
+    explanation: Option<&SearchExplanation>,
+    level: DetailLevel,
+    config: &CodeGraphConfig,
+) -> String {
+    let results: Vec<serde_json::Value> = results
+        .iter()
+        .enumerate()
+        .map(|(i, r)| {
+            let mut v = match level {
+                DetailLevel::Summary => serde_json::json!({
+                    "name": r.name, "kind": r.kind, "file_path": r.file_path,
+                }),
+                _ => serde_json::to_value(r).unwrap_or_default(),
+            };
+            if level == DetailLevel::Full {
+                if let Ok(Some(node)) = store.get_node(&r.node_id) {
+                    v["start_line"] = serde_json::json!(node.start_line);
+                    v["end_line"] = serde_json::json!(node.end_line);
+                    v["body"] = serde_json::json!(node.body);
+                    v["documentation"] = serde_json::json!(node.documentation);
+                }
+            }
+            if let Some(ctx) = config.get_context_for_path(&r.file_path) {
+                v["context"] = serde_json::json!(ctx);
+            }
+            if let Some(why) = explanation.and_then(|e| e.results.get(i)) {
+                v["explain"] = serde_json::json!(why);
+            }
+            v
+        })
+        .collect();
+    if did_you_mean.is_empty() && next_cursor.is_none() && explanation.is_none() {
+        return json_text(&results);
+    }
+    let mut body = serde_json::json!({ "results": results });
+    if !did_you_mean.is_empty() {
+        body["didYouMean"] = serde_json::json!(did_you_mean);
+    }
+    if let Some(cursor) = next_cursor {
+        body["nextCursor"] = serde_json::json!(cursor);
+    }
+    if let Some(e) = explanation {
+        body["debug"] = serde_json::json!({
+            "intent": e.intent,
+            "blendWeights": e.blend_weights,
+            "expansions": e.expansions,
+            "fuzzyFallback": e.fuzzy_fallback,
+            "priorWeights": e.prior_weights,
+        });
+    }
+    json_text(&body)
+}
+
 // 2. codegraph_dependencies
 pub fn handle_dependencies(
     store_arc: &Arc<Mutex<GraphStore>>,
@@ -103,11 +252,7 @@ pub fn handle_dependencies(
 ) -> String {
     let node = match resolve_symbol(store_arc, symbol) {
         Some(n) => n,
-        None => {
-            return json_text(
-                &serde_json::json!({"error": format!("Symbol \"{}\" not found in the graph.", symbol)}),
-            )
-        }
+        None => return symbol_not_found(store_arc, symbol),
     };
     let store = store_arc.lock().unwrap_or_else(|e| e.into_inner());
     let traversal = GraphTraversal::new(&store);
@@ -135,23 +280,49 @@ pub fn handle_callers(
     let level = parse_detail_level(detail_level.as_deref());
     let node = match resolve_symbol(store_arc, symbol) {
         Some(n) => n,
-        None => {
-            return json_text(
-                &serde_json::json!({"error": format!("Symbol \"{}\" not found in the graph.", symbol)}),
-            )
-        }
+        None => return symbol_not_found(store_arc, symbol),
     };
     let store = store_arc.lock().unwrap_or_else(|e| e.into_inner());
     let traversal = GraphTraversal::new(&store);
     let depth = max_depth.unwrap_or(5).min(50);
-    match traversal.find_callers(&node.id, depth) {
-        Ok(callers) => json_text(&serde_json::json!({
-            "target": {"id": node.id, "name": node.name, "kind": node.kind.as_str(), "filePath": node.file_path},
-            "callerCount": callers.len(),
-            "callers": callers.iter().map(|c| format_traversal_node(c, level)).collect::<Vec<_>>(),
-        })),
-        Err(e) => json_text(&serde_json::json!({"error": e.to_string()})),
+    let mut callers = match traversal.find_callers(&node.id, depth) {
+        Ok(c) => c,
+        Err(e) => return json_text(&serde_json::json!({"error": e.to_string()})),
+    };
+
+    // Callers of the interface and base-class methods this one implements
+    let dispatch = DispatchIndex::around(&store, &node.id).unwrap_or_default();
+    let mut seen: HashSet<String> = callers.iter().map(|c| c.node.id.clone()).collect();
+    let mut via: Vec<Option<&DispatchLink>> = vec![None; callers.len()];
+    for link in dispatch.declarations(&node.id) {
+        for caller in traversal
+            .find_callers(&link.declaration, depth)
+            .unwrap_or_default()
+        {
+            if seen.insert(caller.node.id.clone()) {
+                callers.push(caller);
+                via.push(Some(link));
+            }
+        }
     }
+
+    let dispatch_count = via.iter().flatten().count();
+    json_text(&serde_json::json!({
+        "target": {"id": node.id, "name": node.name, "kind": node.kind.as_str(), "filePath": node.file_path},
+        "callerCount": callers.len(),
+        "dispatchCallerCount": dispatch_count,
+        "callers": callers.iter().zip(&via).map(|(c, link)| {
+            let mut v = format_traversal_node(c, level);
+            if let Some(link) = link {
+                v["via"] = serde_json::json!({
+                    "declaration": link.declaration,
+                    "type": link.via,
+                    "kind": link.kind.as_str(),
+                });
+            }
+            v
+        }).collect::<Vec<_>>(),
+    }))
 }
 
 // 4. codegraph_callees
@@ -164,11 +335,7 @@ pub fn handle_callees(
     let level = parse_detail_level(detail_level.as_deref());
     let node = match resolve_symbol(store_arc, symbol) {
         Some(n) => n,
-        None => {
-            return json_text(
-                &serde_json::json!({"error": format!("Symbol \"{}\" not found in the graph.", symbol)}),
-            )
-        }
+        None => return symbol_not_found(store_arc, symbol),
     };
     let store = store_arc.lock().unwrap_or_else(|e| e.into_inner());
     let traversal = GraphTraversal::new(&store);
@@ -192,11 +359,7 @@ pub fn handle_impact(
     let targets: Vec<CodeNode> = if let Some(ref sym) = symbol {
         match resolve_symbol(store_arc, sym) {
             Some(n) => vec![n],
-            None => {
-                return json_text(
-                    &serde_json::json!({"error": format!("Symbol \"{}\" not found in the graph.", sym)}),
-                )
-            }
+            None => return symbol_not_found(store_arc, sym),
         }
     } else if let Some(ref fp) = file_path {
         let store = store_arc.lock().unwrap_or_else(|e| e.into_inner());
@@ -282,6 +445,7 @@ pub fn handle_impact(
 // 6. codegraph_structure
 pub fn handle_structure(
     store_arc: &Arc<Mutex<GraphStore>>,
+    project_root: &Path,
     path: Option<String>,
     depth: Option<usize>,
 ) -> String {
@@ -293,19 +457,26 @@ pub fn handle_structure(
         Err(e) => return json_text(&serde_json::json!({"error": e.to_string()})),
     };
 
-    let all_nodes = match store.get_all_nodes() {
-        Ok(nodes) => nodes,
-        Err(e) => return json_text(&serde_json::json!({"error": e.to_string()})),
-    };
-
-    let scoped_nodes: Vec<&CodeNode> = if let Some(ref p) = path {
-        all_nodes
-            .iter()
-            .filter(|n| n.file_path.starts_with(p))
-            .collect()
-    } else {
-        all_nodes.iter().collect()
-    };
+    // Only names, kinds and locations are reported, so bodies are dropped
+    // as the nodes stream in.
+    let mut scoped_nodes: Vec<CodeNode> = Vec::new();
+    for batch in store.iter_nodes(BATCH_SIZE) {
+        let batch = match batch {
+            Ok(b) => b,
+            Err(e) => return json_text(&serde_json::json!({"error": e.to_string()})),
+        };
+        for mut node in batch {
+            if path
+                .as_deref()
+                .is_some_and(|p| !node.file_path.starts_with(p))
+            {
+                continue;
+            }
+            node.body = None;
+            node.documentation = None;
+            scoped_nodes.push(node);
+        }
+    }
 
     if scoped_nodes.is_empty() {
         return json_text(&serde_json::json!({
@@ -364,19 +535,44 @@ pub fn handle_structure(
         *kind_counts.entry(node.kind.as_str()).or_insert(0) += 1;
     }
 
-    let mut modules: Vec<serde_json::Value> = files_by_dir
-        .iter()
-        .map(|(dir, files)| serde_json::json!({"directory": dir, "fileCount": files.len()}))
+    // Materialized rollups add size and complexity per directory in scope;
+    // an index that predates them only gets file counts.
+    let dir_rows: Vec<_> = load_dir_stats(&store.conn)
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|d| files_by_dir.contains_key(&d.directory))
         .collect();
+    let mut modules: Vec<serde_json::Value> = if dir_rows.is_empty() {
+        files_by_dir
+            .iter()
+            .map(|(dir, files)| serde_json::json!({"directory": dir, "fileCount": files.len()}))
+            .collect()
+    } else {
+        rollup(&dir_rows, RollupKey::Directory)
+            .iter()
+            .map(|d| {
+                serde_json::json!({
+                    "directory": d.directory,
+                    "fileCount": d.files,
+                    "nodeCount": d.nodes,
+                    "edgeCount": d.edges,
+                    "languages": d.language.split(',').collect::<Vec<_>>(),
+                    "avgComplexity": (d.avg_complexity() * 10.0).round() / 10.0,
+                    "maxComplexity": d.complexity_max,
+                })
+            })
+            .collect()
+    };
     modules.sort_by(|a, b| {
         b["fileCount"]
             .as_u64()
             .unwrap_or(0)
             .cmp(&a["fileCount"].as_u64().unwrap_or(0))
+            .then_with(|| a["directory"].as_str().cmp(&b["directory"].as_str()))
     });
     modules.truncate(limit);
 
-    json_text(&serde_json::json!({
+    let mut result = serde_json::json!({
         "stats": {
             "totalNodes": stats.nodes,
             "totalEdges": stats.edges,
@@ -387,18 +583,126 @@ pub fn handle_structure(
         "symbolsByKind": kind_counts,
         "topSymbols": top_symbols,
         "modules": modules,
-    }))
+    });
+
+    // Monorepo roll-up: only worth reporting when several packages exist.
+    let projects = detect_projects(project_root);
+    if projects.len() > 1 {
+        let (project_rows, dependencies) =
+            project_rollup(&store, &projects, &scoped_nodes, &page_rank, limit.min(5));
+        result["projects"] = serde_json::json!(project_rows);
+        result["projectDependencies"] = serde_json::json!(dependencies);
+    }
+
+    json_text(&result)
+}
+
+/// Group scoped nodes by detected project: per-project stats, top symbols,
+/// and the cross-project dependency edges between them.
+fn project_rollup(
+    store: &GraphStore,
+    projects: &[Project],
+    scoped_nodes: &[CodeNode],
+    page_rank: &[RankedNode],
+    top_per_project: usize,
+) -> (Vec<serde_json::Value>, Vec<serde_json::Value>) {
+    let mut project_of: HashMap<&str, &Project> = HashMap::new();
+    let mut members: BTreeMap<&str, Vec<&CodeNode>> = BTreeMap::new();
+    for node in scoped_nodes {
+        if let Some(project) = project_for_file(projects, &node.file_path) {
+            project_of.insert(node.id.as_str(), project);
+            members.entry(project.root.as_str()).or_default().push(node);
+        }
+    }
+
+    // Cross-project edges, counted per (from, to) project pair.
+    let mut deps: BTreeMap<(&str, &str), usize> = BTreeMap::new();
+    for batch in store.iter_edges(BATCH_SIZE).flatten() {
+        for edge in batch {
+            if edge.kind == EdgeKind::Contains {
+                continue;
+            }
+            let (Some(from), Some(to)) = (
+                project_of.get(edge.source.as_str()),
+                project_of.get(edge.target.as_str()),
+            ) else {
+                continue;
+            };
+            if from.root != to.root {
+                *deps
+                    .entry((from.name.as_str(), to.name.as_str()))
+                    .or_insert(0) += 1;
+            }
+        }
+    }
+
+    let score_of: HashMap<&str, f64> = page_rank
+        .iter()
+        .map(|r| (r.node_id.as_str(), r.score))
+        .collect();
+
+    let rows = projects
+        .iter()
+        .filter_map(|project| {
+            let nodes = members.get(project.root.as_str())?;
+            let files: HashSet<&str> = nodes.iter().map(|n| n.file_path.as_str()).collect();
+            let mut kinds: HashMap<&str, usize> = HashMap::new();
+            for n in nodes {
+                *kinds.entry(n.kind.as_str()).or_insert(0) += 1;
+            }
+            let mut ranked: Vec<(&CodeNode, f64)> = nodes
+                .iter()
+                .map(|n| (*n, score_of.get(n.id.as_str()).copied().unwrap_or(0.0)))
+                .collect();
+            ranked.sort_by(|a, b| b.1.total_cmp(&a.1));
+            let top: Vec<serde_json::Value> = ranked
+                .iter()
+                .take(top_per_project)
+                .map(|(n, score)| {
+                    serde_json::json!({
+                        "id": n.id, "name": n.name, "kind": n.kind.as_str(),
+                        "filePath": n.file_path, "score": score,
+                    })
+                })
+                .collect();
+            let depends_on: Vec<&str> = deps
+                .keys()
+                .filter(|(from, _)| *from == project.name)
+                .map(|(_, to)| *to)
+                .collect();
+            let depended_on_by: Vec<&str> = deps
+                .keys()
+                .filter(|(_, to)| *to == project.name)
+                .map(|(from, _)| *from)
+                .collect();
+            Some(serde_json::json!({
+                "name": project.name,
+                "root": project.root,
+                "ecosystem": project.ecosystem,
+                "manifest": project.manifest,
+                "fileCount": files.len(),
+                "nodeCount": nodes.len(),
+                "symbolsByKind": kinds,
+                "topSymbols": top,
+                "dependsOn": depends_on,
+                "dependedOnBy": depended_on_by,
+            }))
+        })
+        .collect();
+
+    let dependencies = deps
+        .iter()
+        .map(|((from, to), count)| serde_json::json!({"from": from, "to": to, "edgeCount": count}))
+        .collect();
+
+    (rows, dependencies)
 }
 
 // 7. codegraph_tests
 pub fn handle_tests(store_arc: &Arc<Mutex<GraphStore>>, symbol: &str) -> String {
     let node = match resolve_symbol(store_arc, symbol) {
         Some(n) => n,
-        None => {
-            return json_text(
-                &serde_json::json!({"error": format!("Symbol \"{}\" not found in the graph.", symbol)}),
-            )
-        }
+        None => return symbol_not_found(store_arc, symbol),
     };
 
     let store = store_arc.lock().unwrap_or_else(|e| e.into_inner());
@@ -473,9 +777,10 @@ WITH RECURSIVE callers(id, depth, path) AS (
     WHERE c.depth < 5
       AND instr(c.path, e.source_id) = 0
 )
-SELECT DISTINCT n.*
+SELECT DISTINCT n.*, b.encoding AS body_encoding, b.body AS body_data
 FROM callers c
 JOIN nodes n ON n.id = c.id
+LEFT JOIN node_bodies b ON b.node_id = n.id
 WHERE n.is_test = 1
 ORDER BY n.file_path ASC, n.start_line ASC";
 
@@ -593,11 +898,7 @@ pub fn handle_diagram(
 
     let node = match resolve_symbol(store_arc, sym) {
         Some(n) => n,
-        None => {
-            return json_text(
-                &serde_json::json!({"error": format!("Symbol \"{}\" not found in the graph.", sym)}),
-            )
-        }
+        None => return symbol_not_found(store_arc, sym),
     };
 
     let store = store_arc.lock().unwrap_or_else(|e| e.into_inner());
@@ -698,6 +999,23 @@ pub fn handle_node(
     if let Some(ref body) = node.body {
         result["body"] = serde_json::json!(body);
     }
+    {
+        let store = store_arc.lock().unwrap_or_else(|e| e.into_inner());
+        if let Ok(Some(parser)) = store.get_node_parser(&node.id) {
+            result["parser"] = serde_json::json!(parser);
+        }
+        if let Ok(Some(meta)) = store.get_node_git_meta(&node.id) {
+            let now = chrono::Utc::now().timestamp();
+            result["git"] = serde_json::json!({
+                "lastCommit": meta.commit_hash,
+                "author": meta.author,
+                "email": meta.email,
+                "lastModified": chrono::DateTime::from_timestamp(meta.committed_at, 0)
+                    .map(|dt| dt.format("%Y-%m-%d %H:%M:%S").to_string()),
+                "ageDays": age_in_days(meta.committed_at, now),
+            });
+        }
+    }
 
     let show_relations = include_relations.unwrap_or(false) || level == DetailLevel::Full;
     if show_relations {
@@ -733,11 +1051,17 @@ pub fn handle_node(
     json_text(&result)
 }
 
+/// Dead-code results that get git history; each costs a pickaxe search.
+const DEAD_CODE_HISTORY_LIMIT: usize = 20;
+
 // 11. codegraph_dead_code
 pub fn handle_dead_code(
     store_arc: &Arc<Mutex<GraphStore>>,
+    project_root: &Path,
+    roots: &DeadCodeRoots,
     kinds: Option<String>,
     include_exported: Option<bool>,
+    history: Option<bool>,
 ) -> String {
     let kind_filter: Vec<NodeKind> = kinds
         .as_deref()
@@ -748,25 +1072,62 @@ pub fn handle_dead_code(
         .filter_map(NodeKind::from_str_loose)
         .collect();
 
+    // Reporting exports means not treating the public API as a root.
+    let mut roots = roots.clone();
+    if include_exported.unwrap_or(false) {
+        roots.exports = false;
+    }
+
     let store = store_arc.lock().unwrap_or_else(|e| e.into_inner());
-    let results = find_dead_code(&store.conn, &kind_filter);
-    let _ = include_exported;
+    let results = match find_unreachable_code(&store, &kind_filter, &roots, Some(project_root)) {
+        Ok(r) => r,
+        Err(e) => return json_text(&serde_json::json!({"error": e.to_string()})),
+    };
+
+    // A kind-filtered run only sees part of the dead code, so it must not
+    // resolve findings of the other kinds.
+    let findings: Vec<NewFinding> = results
+        .iter()
+        .map(|r| NewFinding {
+            rule: format!("unused_{}", r.kind),
+            file_path: r.file_path.clone(),
+            symbol: Some(r.name.clone()),
+            line: r.start_line,
+            description: format!("Unused {} {}", r.kind, r.name),
+        })
+        .collect();
+    let scope = kind_filter.is_empty().then_some("");
+    let record = track_findings(&store, FindingCategory::DeadCode, scope, &findings);
 
     if results.is_empty() {
         return json_text(&serde_json::json!({
             "deadCodeCount": 0,
-            "message": "No dead code found. All symbols have incoming references (or are excluded as exports/tests/entry points).",
+            "resolvedCount": record.map_or(0, |r| r.resolved.len()),
+            "message": "No dead code found. Every symbol is reachable from an entry point, export, test or handler root.",
         }));
     }
 
+    let history = history.unwrap_or(false);
+    let now = chrono::Utc::now().timestamp();
     let mut by_file: HashMap<String, Vec<serde_json::Value>> = HashMap::new();
-    for r in &results {
-        by_file
-            .entry(r.file_path.clone())
-            .or_default()
-            .push(serde_json::json!({
-                "id": r.id, "name": r.name, "kind": r.kind, "line": r.start_line,
-            }));
+    for (i, r) in results.iter().enumerate() {
+        let mut entry = serde_json::json!({
+            "id": r.id, "name": r.name, "kind": r.kind, "line": r.start_line,
+        });
+        if !r.referenced_by.is_empty() {
+            entry["referencedBy"] = serde_json::json!(r.referenced_by);
+        }
+        if history && i < DEAD_CODE_HISTORY_LIMIT {
+            let evidence = orphan_evidence(&store, Some(project_root), r, now);
+            entry["history"] = serde_json::json!(evidence);
+        }
+        if let Some(tracked) = record.as_ref().map(|rec| &rec.findings[i]) {
+            entry["findingId"] = serde_json::json!(tracked.id);
+            entry["firstSeen"] = serde_json::json!(tracked.first_seen);
+            entry["lastSeen"] = serde_json::json!(tracked.last_seen);
+            entry["new"] = serde_json::json!(tracked.is_new);
+        }
+        by_file.entry(r.file_path.clone()).or_default().push(entry);
     }
 
     let mut files: Vec<serde_json::Value> = by_file
@@ -777,6 +1138,9 @@ pub fn handle_dead_code(
 
     json_text(&serde_json::json!({
         "deadCodeCount": results.len(),
+        "newCount": record.as_ref().map_or(0, |r| r.new_count()),
+        "resolvedCount": record.as_ref().map_or(0, |r| r.resolved.len()),
+        "historyChecked": if history { results.len().min(DEAD_CODE_HISTORY_LIMIT) } else { 0 },
         "files": files,
     }))
 }
@@ -840,6 +1204,9 @@ pub fn handle_languages(store_arc: &Arc<Mutex<GraphStore>>) -> String {
     let edge_count_query = "\
         SELECT n.language, COUNT(*) as edge_count \
         FROM edges e JOIN nodes n ON n.id = e.source_id GROUP BY n.language";
+    let symbol_size_query = "\
+        SELECT language, AVG(end_line - start_line + 1) FROM nodes \
+        WHERE type NOT IN ('module', 'namespace') GROUP BY language";
 
     let lang_stats: Vec<(String, i64, i64)> = match store.conn.prepare(lang_query) {
         Ok(mut stmt) => {
@@ -876,6 +1243,23 @@ pub fn handle_languages(store_arc: &Arc<Mutex<GraphStore>>) -> String {
         }
     }
 
+    let mut symbol_sizes: HashMap<String, f64> = HashMap::new();
+    if let Ok(mut stmt) = store.conn.prepare(symbol_size_query) {
+        if let Ok(rows) = stmt.query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, f64>(1)?))
+        }) {
+            symbol_sizes.extend(rows.flatten());
+        }
+    }
+    let lines = load_language_lines(&store.conn).unwrap_or_default();
+    let lines_json = |c: &LineCounts| {
+        serde_json::json!({
+            "physical": c.total, "code": c.code, "logical": c.logical,
+            "comment": c.comment, "blank": c.blank,
+            "commentRatio": (c.comment_ratio() * 1000.0).round() / 1000.0,
+        })
+    };
+
     let total_symbols: i64 = lang_stats.iter().map(|(_, _, s)| s).sum();
     let languages: Vec<serde_json::Value> = lang_stats
         .iter()
@@ -889,18 +1273,296 @@ pub fn handle_languages(store_arc: &Arc<Mutex<GraphStore>>) -> String {
                 "language": lang, "files": files, "symbols": symbols,
                 "edges": edge_counts.get(lang).copied().unwrap_or(0),
                 "percentage": format!("{:.1}%", pct),
+                "lines": lines_json(&lines.get(lang).copied().unwrap_or_default()),
+                "avgSymbolLines": symbol_sizes
+                    .get(lang)
+                    .map_or(0.0, |avg| (avg * 10.0).round() / 10.0),
             })
         })
         .collect();
 
     let total_files: i64 = lang_stats.iter().map(|(_, f, _)| f).sum();
     let total_edges: i64 = edge_counts.values().sum();
+    let mut total_lines = LineCounts::default();
+    for c in lines.values() {
+        total_lines.add(c);
+    }
 
     json_text(&serde_json::json!({
         "languageCount": lang_stats.len(),
         "totalFiles": total_files,
         "totalSymbols": total_symbols,
         "totalEdges": total_edges,
+        "totalLines": lines_json(&total_lines),
         "languages": languages,
     }))
 }
+
+// 14. codegraph_complete
+pub fn handle_complete(
+    store_arc: &Arc<Mutex<GraphStore>>,
+    file_path: &str,
+    line: u32,
+    prefix: &str,
+    limit: Option<usize>,
+) -> String {
+    let store = store_arc.lock().unwrap_or_else(|e| e.into_inner());
+    let file_path = file_path.trim_start_matches("./");
+    let completions = match complete(&store.conn, file_path, line, prefix, limit.unwrap_or(20)) {
+        Ok(c) => c,
+        Err(e) => return json_text(&serde_json::json!({"error": e.to_string()})),
+    };
+    let items: Vec<serde_json::Value> = completions
+        .iter()
+        .map(|c| {
+            let mut item = serde_json::json!({
+                "name": c.name,
+                "kind": c.kind,
+                "id": c.id,
+                "filePath": c.file_path,
+                "line": c.line,
+                "source": c.source.as_str(),
+                "score": (c.score * 1000.0).round() / 1000.0,
+            });
+            if let Some(ref sig) = c.signature {
+                item["signature"] = serde_json::json!(sig);
+            }
+            item
+        })
+        .collect();
+    json_text(&serde_json::json!({
+        "filePath": file_path,
+        "line": line,
+        "prefix": prefix,
+        "count": items.len(),
+        "completions": items,
+    }))
+}
+
+// 15. codegraph_examples
+pub fn handle_examples(
+    store_arc: &Arc<Mutex<GraphStore>>,
+    project_root: &Path,
+    symbol: &str,
+    limit: Option<usize>,
+    context_lines: Option<usize>,
+) -> String {
+    let node = match resolve_symbol(store_arc, symbol) {
+        Some(n) => n,
+        None => return symbol_not_found(store_arc, symbol),
+    };
+    let store = store_arc.lock().unwrap_or_else(|e| e.into_inner());
+    let limit = limit.unwrap_or(5).min(50);
+    let context = context_lines.unwrap_or(2).min(20);
+    let (examples, total) = match find_usage_examples(&store, &node, project_root, limit, context) {
+        Ok(found) => found,
+        Err(e) => return json_text(&serde_json::json!({"error": e.to_string()})),
+    };
+    let target = serde_json::json!({
+        "id": node.id, "name": node.name, "kind": node.kind.as_str(),
+        "filePath": node.file_path, "line": node.start_line,
+    });
+    if examples.is_empty() {
+        return json_text(&serde_json::json!({
+            "target": target,
+            "exampleCount": 0,
+            "message": format!("No call sites of \"{}\" found.", node.name),
+        }));
+    }
+    json_text(&serde_json::json!({
+        "target": target,
+        "callSiteCount": total,
+        "exampleCount": examples.len(),
+        "examples": examples.iter().map(|e| {
+            let mut item = serde_json::json!({
+                "filePath": e.file_path,
+                "line": e.line,
+                "isTest": e.is_test,
+                "arguments": e.arguments,
+                "score": e.score,
+            });
+            if let Some(ref caller) = e.caller {
+                item["caller"] = serde_json::json!(caller.name);
+                item["callerKind"] = serde_json::json!(caller.kind.as_str());
+            }
+            if let Some(ref call) = e.call {
+                item["call"] = serde_json::json!(call);
+            }
+            if let Some(ref snippet) = e.snippet {
+                item["snippet"] = serde_json::json!(snippet);
+                item["snippetStartLine"] = serde_json::json!(e.snippet_start);
+            }
+            item
+        }).collect::<Vec<_>>(),
+    }))
+}
+
+// 16. codegraph_saved_query
+/// Run, list, save or delete a saved search. `definition` is only used by
+/// `save`; `args` fills the `{param}` placeholders of a `run`.
+pub fn handle_saved_query(
+    store_arc: &Arc<Mutex<GraphStore>>,
+    config: &CodeGraphConfig,
+    action: Option<&str>,
+    name: Option<&str>,
+    args: HashMap<String, String>,
+    definition: Option<SavedQuery>,
+) -> String {
+    let store = store_arc.lock().unwrap_or_else(|e| e.into_inner());
+    let action = action.unwrap_or("run").to_lowercase();
+    if action == "list" {
+        return match list_saved_queries(&store.conn, &config.saved_queries) {
+            Ok(queries) => json_text(&serde_json::json!({
+                "count": queries.len(),
+                "savedQueries": queries,
+            })),
+            Err(e) => json_text(&serde_json::json!({"error": e.to_string()})),
+        };
+    }
+    let Some(name) = name.map(str::trim).filter(|n| !n.is_empty()) else {
+        return json_text(&serde_json::json!({
+            "error": format!("\"name\" is required for action \"{action}\""),
+        }));
+    };
+    match action.as_str() {
+        "run" => {
+            let saved = match find_saved_query(&store.conn, &config.saved_queries, name) {
+                Ok(Some(saved)) => saved,
+                Ok(None) => {
+                    return json_text(&serde_json::json!({
+                        "error": format!("No saved query named \"{name}\""),
+                    }))
+                }
+                Err(e) => return json_text(&serde_json::json!({"error": e.to_string()})),
+            };
+            let bound = match saved.definition.bind(&args) {
+                Ok(bound) => bound,
+                Err(e) => {
+                    return json_text(&serde_json::json!({
+                        "error": e.to_string(),
+                        "parameters": saved.parameters,
+                    }))
+                }
+            };
+            let level = parse_detail_level(bound.detail_level.as_deref());
+            hybrid_search_response(&store, &bound.query, &bound.search_options(), level, config)
+        }
+        "save" => {
+            let Some(definition) = definition.filter(|d| !d.query.trim().is_empty()) else {
+                return json_text(&serde_json::json!({
+                    "error": "\"query\" is required to save a query",
+                }));
+            };
+            match save_query(&store.conn, name, &definition) {
+                Ok(()) => json_text(&serde_json::json!({
+                    "saved": name,
+                    "parameters": definition.parameters(),
+                })),
+                Err(e) => json_text(&serde_json::json!({"error": e.to_string()})),
+            }
+        }
+        "delete" => match delete_saved_query(&store.conn, name) {
+            Ok(deleted) => json_text(&serde_json::json!({
+                "deleted": deleted,
+                "name": name,
+                "sharedInConfig": config.saved_queries.contains_key(name),
+            })),
+            Err(e) => json_text(&serde_json::json!({"error": e.to_string()})),
+        },
+        other => json_text(&serde_json::json!({
+            "error": format!("Unknown action \"{other}\"; expected run, list, save or delete"),
+        })),
+    }
+}
+
+// 17. codegraph_similar
+/// The symbols whose stored embeddings are nearest to `symbol`'s.
+pub fn handle_similar(
+    store_arc: &Arc<Mutex<GraphStore>>,
+    symbol: &str,
+    limit: Option<usize>,
+) -> String {
+    let node = match resolve_symbol(store_arc, symbol) {
+        Some(n) => n,
+        None => return symbol_not_found(store_arc, symbol),
+    };
+    let store = store_arc.lock().unwrap_or_else(|e| e.into_inner());
+    let k = limit.unwrap_or(10).clamp(1, 100);
+    let similar = match HybridSearch::new(&store.conn).similar_to(&node.id, k) {
+        Ok(similar) => similar,
+        Err(e) => {
+            return json_text(&serde_json::json!({
+                "error": e.to_string(),
+                "hint": "Similarity needs stored embeddings; re-index with the embedding feature enabled.",
+            }))
+        }
+    };
+    json_text(&serde_json::json!({
+        "source": {"id": node.id, "name": node.name, "kind": node.kind.as_str(), "filePath": node.file_path},
+        "count": similar.len(),
+        "similar": similar.iter().map(|r| serde_json::json!({
+            "id": r.node_id, "name": r.name, "kind": r.kind, "filePath": r.file_path,
+            "startLine": store.get_node(&r.node_id).ok().flatten().map(|n| n.start_line),
+            "similarity": (r.score * 1000.0).round() / 1000.0,
+        })).collect::<Vec<_>>(),
+    }))
+}
+
+// 18. codegraph_federated_query
+/// Hybrid search over the local index and the configured federation
+/// indexes (or the subset named in `indexes`), fused by weighted rank.
+pub fn handle_federated_query(
+    store_arc: &Arc<Mutex<GraphStore>>,
+    config: &CodeGraphConfig,
+    project_root: &Path,
+    query: &str,
+    limit: Option<usize>,
+    language: Option<String>,
+    indexes: Option<Vec<String>>,
+) -> String {
+    let mut federation = config
+        .federation
+        .open(project_root)
+        .with_priors(config.ranking.prior_weights())
+        .with_synonyms(config.synonym_map());
+    if let Some(names) = &indexes {
+        federation.retain(names);
+    }
+    let searched = federation
+        .index_names()
+        .iter()
+        .map(|n| n.to_string())
+        .collect::<Vec<_>>();
+    let opts = SearchOptions {
+        limit: Some(limit.unwrap_or(20)),
+        language,
+        ..Default::default()
+    };
+    let store = store_arc.lock().unwrap_or_else(|e| e.into_inner());
+    match federation.search(Some(&store), query, &opts) {
+        Ok(found) => {
+            let mut body = serde_json::json!({
+                "query": query,
+                "indexes": std::iter::once(crate::graph::federation::LOCAL_INDEX.to_string())
+                    .chain(searched)
+                    .collect::<Vec<_>>(),
+                "count": found.results.len(),
+                "results": found.results,
+            });
+            if !found.errors.is_empty() {
+                body["unavailable"] = found
+                    .errors
+                    .iter()
+                    .map(|(index, error)| serde_json::json!({"index": index, "error": error}))
+                    .collect();
+            }
+            if config.federation.indexes.is_empty() {
+                body["hint"] = serde_json::json!(
+                    "No federation indexes configured; add them under federation.indexes in .codegraph.yaml."
+                );
+            }
+            json_text(&body)
+        }
+        Err(e) => json_text(&serde_json::json!({"error": e.to_string()})),
+    }
+}
@@ -1,18 +1,30 @@
-//! Data flow MCP tool handler implementations (6 tools).
+//! Data flow MCP tool handler implementations (13 tools).
 //!
-//! Contains the business logic for: find_path, complexity, data_flow,
-//! dead_stores, find_uninitialized, and reaching_defs.
+//! Contains the business logic for: find_path, complexity, test_gaps, data_flow,
+//! dead_stores, find_uninitialized, reaching_defs, recursion,
+//! unused_params, callgraph_export, entrypoints, cfg, and null_checks.
 
+use std::collections::HashMap;
 use std::path::Path;
 use std::sync::{Arc, Mutex};
 
+use crate::db::findings::{FindingCategory, NewFinding};
+use crate::graph::cfg::{self, BlockKind, CfgEdgeKind};
 use crate::graph::complexity;
 use crate::graph::dataflow;
+use crate::graph::entrypoints::{find_entrypoints, EntryKind, EntryPoint};
+use crate::graph::export::{collect_subgraph, ExportDirection, ExportScope};
 use crate::graph::store::GraphStore;
+use crate::graph::test_gaps::{find_test_gaps, TestGapOptions};
 use crate::graph::traversal::GraphTraversal;
 use crate::indexer::parser::CodeParser;
+use crate::security::rules::is_test_file;
+use crate::types::{CodeNode, EdgeKind, NodeKind};
 
-use super::server::{json_text, resolve_symbol};
+use super::server::{json_text, mermaid_safe, resolve_symbol, symbol_not_found, track_findings};
+
+/// Node and edge batch size when reading the graph.
+const BATCH_SIZE: usize = 500;
 
 /// Resolve source code and language from either a file path or explicit parameters.
 ///
@@ -92,13 +104,83 @@ pub fn handle_complexity(
     results.retain(|r| r.cyclomatic >= threshold);
     results.sort_by(|a, b| b.cyclomatic.cmp(&a.cyclomatic));
 
+    // Every result is recorded, not just the 50 shown. A custom threshold
+    // hides part of the set, so only default-threshold runs resolve findings.
+    let findings: Vec<NewFinding> = results
+        .iter()
+        .map(|r| NewFinding {
+            rule: "high_complexity".to_string(),
+            file_path: r.file_path.clone(),
+            symbol: Some(r.name.clone()),
+            line: 0,
+            description: format!(
+                "High complexity in {}: cyclomatic {}, cognitive {}",
+                r.name, r.cyclomatic, r.cognitive
+            ),
+        })
+        .collect();
+    let scope = min_complexity.is_none().then_some("");
+    let record = track_findings(&store, FindingCategory::Complexity, scope, &findings);
+
     json_text(&serde_json::json!({
         "threshold": threshold,
         "functionCount": results.len(),
-        "functions": results.iter().take(50).map(|r| serde_json::json!({
-            "name": r.name, "file": r.file_path,
-            "cyclomatic": r.cyclomatic, "cognitive": r.cognitive,
-            "lineCount": r.line_count,
+        "newCount": record.as_ref().map_or(0, |r| r.new_count()),
+        "resolvedCount": record.as_ref().map_or(0, |r| r.resolved.len()),
+        "functions": results.iter().enumerate().take(50).map(|(i, r)| {
+            let mut entry = serde_json::json!({
+                "name": r.name, "file": r.file_path,
+                "cyclomatic": r.cyclomatic, "cognitive": r.cognitive,
+                "lineCount": r.line_count,
+            });
+            if let Some(tracked) = record.as_ref().map(|rec| &rec.findings[i]) {
+                entry["findingId"] = serde_json::json!(tracked.id);
+                entry["firstSeen"] = serde_json::json!(tracked.first_seen);
+                entry["lastSeen"] = serde_json::json!(tracked.last_seen);
+                entry["new"] = serde_json::json!(tracked.is_new);
+            }
+            entry
+        }).collect::<Vec<_>>(),
+    }))
+}
+
+// 74. codegraph_test_gaps
+pub fn handle_test_gaps(
+    store_arc: &Arc<Mutex<GraphStore>>,
+    min_complexity: Option<u32>,
+    path: Option<&str>,
+    max_depth: Option<u32>,
+    limit: Option<usize>,
+) -> String {
+    let store = store_arc.lock().unwrap_or_else(|e| e.into_inner());
+    let defaults = TestGapOptions::default();
+    let opts = TestGapOptions {
+        min_complexity: min_complexity.unwrap_or(defaults.min_complexity),
+        path_prefix: path.map(str::to_string),
+        max_depth: max_depth.unwrap_or(defaults.max_depth).clamp(1, 10),
+    };
+    let report = match find_test_gaps(&store, &opts) {
+        Ok(r) => r,
+        Err(e) => return json_text(&serde_json::json!({"error": e.to_string()})),
+    };
+    let limit = limit.unwrap_or(30);
+
+    json_text(&serde_json::json!({
+        "threshold": opts.min_complexity,
+        "maxDepth": opts.max_depth,
+        "candidateCount": report.candidates,
+        "coveredCount": report.covered,
+        "gapCount": report.gaps.len(),
+        "gaps": report.gaps.iter().take(limit).map(|g| serde_json::json!({
+            "id": g.complexity.node_id,
+            "name": g.complexity.name,
+            "file": g.complexity.file_path,
+            "line": g.start_line,
+            "cyclomatic": g.complexity.cyclomatic,
+            "cognitive": g.complexity.cognitive,
+            "lineCount": g.complexity.line_count,
+            "pageRank": (g.page_rank * 1000.0).round() / 1000.0,
+            "score": (g.score * 1000.0).round() / 1000.0,
         })).collect::<Vec<_>>(),
     }))
 }
@@ -188,6 +270,185 @@ pub fn handle_reaching_defs(
     }))
 }
 
+// 55. codegraph_recursion
+pub fn handle_recursion(
+    store_arc: &Arc<Mutex<GraphStore>>,
+    file_path: Option<String>,
+    include_tests: bool,
+    limit: Option<usize>,
+) -> String {
+    let store = store_arc.lock().unwrap_or_else(|e| e.into_inner());
+    let cycles = match GraphTraversal::new(&store).find_recursion() {
+        Ok(c) => c,
+        Err(e) => return json_text(&serde_json::json!({"error": e.to_string()})),
+    };
+
+    let symbol = |id: &str| -> serde_json::Value {
+        match store.get_node(id).ok().flatten() {
+            Some(n) => serde_json::json!({
+                "symbol": n.qualified_name.as_deref().unwrap_or(&n.name),
+                "kind": n.kind.as_str(), "nodeId": n.id,
+                "file": n.file_path, "line": n.start_line,
+            }),
+            None => serde_json::json!({"nodeId": id}),
+        }
+    };
+    let mut results: Vec<serde_json::Value> = Vec::new();
+    let mut direct = 0;
+    for cycle in &cycles {
+        let members: Vec<serde_json::Value> = cycle.node_ids.iter().map(|id| symbol(id)).collect();
+        let files: Vec<&str> = members.iter().filter_map(|m| m["file"].as_str()).collect();
+        if file_path
+            .as_deref()
+            .is_some_and(|p| !files.iter().any(|f| f.starts_with(p)))
+        {
+            continue;
+        }
+        if !include_tests && !files.is_empty() && files.iter().all(|f| is_test_file(f)) {
+            continue;
+        }
+        direct += usize::from(cycle.direct);
+        results.push(serde_json::json!({
+            "kind": if cycle.direct { "direct" } else { "mutual" },
+            "size": cycle.node_ids.len(),
+            "members": members,
+            "cycleLength": cycle.cycle_length,
+            "maxDepthHint": cycle.max_depth_hint,
+            "entryDepth": cycle.entry_depth,
+            "entryPoints": cycle.entry_points.iter().take(10).map(|id| symbol(id)).collect::<Vec<_>>(),
+            "entryPointCount": cycle.entry_points.len(),
+            "entryEdges": cycle.entry_edges.iter().take(20).map(|e| serde_json::json!({
+                "caller": symbol(&e.caller)["symbol"], "callerId": e.caller,
+                "callee": symbol(&e.callee)["symbol"], "calleeId": e.callee,
+                "file": e.file_path, "line": e.line,
+            })).collect::<Vec<_>>(),
+            "entryEdgeCount": cycle.entry_edges.len(),
+        }));
+    }
+
+    if results.is_empty() {
+        return json_text(&serde_json::json!({
+            "cycleCount": 0,
+            "message": "No recursion found in the call graph.",
+        }));
+    }
+    json_text(&serde_json::json!({
+        "cycleCount": results.len(),
+        "directCount": direct,
+        "mutualCount": results.len() - direct,
+        "cycles": results.into_iter().take(limit.unwrap_or(50)).collect::<Vec<_>>(),
+    }))
+}
+
+// 56. codegraph_unused_params
+pub fn handle_unused_params(
+    store_arc: &Arc<Mutex<GraphStore>>,
+    file_path: Option<String>,
+    include_tests: bool,
+    limit: Option<usize>,
+) -> String {
+    let store = store_arc.lock().unwrap_or_else(|e| e.into_inner());
+    let in_scope = |n: &CodeNode| {
+        file_path
+            .as_deref()
+            .is_none_or(|p| n.file_path.starts_with(p))
+            && (include_tests || !is_test_file(&n.file_path))
+    };
+    // Bodies cut off by the extractor end in `...` and cannot prove a
+    // parameter unused.
+    let mut functions: HashMap<String, (CodeNode, String)> = HashMap::new();
+    for batch in store.iter_nodes(BATCH_SIZE) {
+        let batch = match batch {
+            Ok(b) => b,
+            Err(e) => return json_text(&serde_json::json!({"error": e.to_string()})),
+        };
+        for mut node in batch {
+            if !matches!(node.kind, NodeKind::Function | NodeKind::Method) {
+                continue;
+            }
+            let Some(body) = node.body.take().filter(|b| !b.ends_with("...")) else {
+                continue;
+            };
+            node.documentation = None;
+            functions.insert(node.id.clone(), (node, body));
+        }
+    }
+    let limit = limit.unwrap_or(50);
+
+    let mut unused: Vec<serde_json::Value> = functions
+        .values()
+        .filter(|(n, _)| in_scope(n))
+        .filter_map(|(n, body)| {
+            let params = dataflow::find_unused_params(body, &n.name, n.language.as_str());
+            (!params.is_empty()).then(|| {
+                serde_json::json!({
+                    "function": n.qualified_name.as_deref().unwrap_or(&n.name),
+                    "nodeId": n.id, "file": n.file_path, "line": n.start_line,
+                    "params": params,
+                })
+            })
+        })
+        .collect();
+    unused.sort_by(|a, b| {
+        (a["file"].as_str(), a["line"].as_u64()).cmp(&(b["file"].as_str(), b["line"].as_u64()))
+    });
+
+    let mut returns: HashMap<&str, Option<String>> = HashMap::new();
+    let mut ignored: Vec<serde_json::Value> = Vec::new();
+    for batch in store.iter_edges(BATCH_SIZE) {
+        let batch = match batch {
+            Ok(b) => b,
+            Err(e) => return json_text(&serde_json::json!({"error": e.to_string()})),
+        };
+        for edge in batch.iter().filter(|e| e.kind == EdgeKind::Calls) {
+            let (Some((caller, caller_body)), Some((callee, callee_body))) = (
+                functions.get(edge.source.as_str()),
+                functions.get(edge.target.as_str()),
+            ) else {
+                continue;
+            };
+            if !in_scope(caller) {
+                continue;
+            }
+            let Some(ty) = returns
+                .entry(callee.id.as_str())
+                .or_insert_with(|| {
+                    dataflow::return_value_type(callee_body, &callee.name, callee.language.as_str())
+                })
+                .clone()
+            else {
+                continue;
+            };
+            let Some(line) = edge
+                .line
+                .checked_sub(caller.start_line)
+                .and_then(|offset| caller_body.lines().nth(offset as usize))
+            else {
+                continue;
+            };
+            if dataflow::discards_call_result(line, &callee.name) {
+                ignored.push(serde_json::json!({
+                    "callee": callee.qualified_name.as_deref().unwrap_or(&callee.name),
+                    "calleeId": callee.id, "returns": ty,
+                    "caller": caller.qualified_name.as_deref().unwrap_or(&caller.name),
+                    "file": edge.file_path, "line": edge.line,
+                    "statement": line.trim(),
+                }));
+            }
+        }
+    }
+    ignored.sort_by(|a, b| {
+        (a["file"].as_str(), a["line"].as_u64()).cmp(&(b["file"].as_str(), b["line"].as_u64()))
+    });
+
+    json_text(&serde_json::json!({
+        "unusedParamCount": unused.len(),
+        "unusedParams": unused.into_iter().take(limit).collect::<Vec<_>>(),
+        "ignoredReturnCount": ignored.len(),
+        "ignoredReturns": ignored.into_iter().take(limit).collect::<Vec<_>>(),
+    }))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -382,3 +643,402 @@ mod tests {
         assert!(err.contains("Path not found") || err.contains("Path traversal"));
     }
 }
+
+// 71. codegraph_callgraph_export
+/// The subgraph around `symbol` as nodes and edges, shaped like the viz
+/// API's `/api/nodes` and `/api/edges` so clients can render it directly.
+pub fn handle_callgraph_export(
+    store_arc: &Arc<Mutex<GraphStore>>,
+    symbol: &str,
+    direction: Option<String>,
+    depth: Option<usize>,
+    edge_kinds: Option<Vec<String>>,
+    max_nodes: Option<usize>,
+) -> String {
+    let direction = match direction.as_deref() {
+        None => ExportDirection::Outgoing,
+        Some(d) => match ExportDirection::from_str_loose(d) {
+            Some(d) => d,
+            None => {
+                return json_text(&serde_json::json!({
+                    "error": format!("Unknown direction \"{d}\". Use outgoing, incoming or both."),
+                }))
+            }
+        },
+    };
+    let mut kinds = Vec::new();
+    for kind in edge_kinds.unwrap_or_else(|| vec![EdgeKind::Calls.as_str().to_string()]) {
+        match EdgeKind::from_str_loose(&kind) {
+            Some(k) => kinds.push(k.as_str().to_string()),
+            None => {
+                return json_text(&serde_json::json!({
+                    "error": format!("Unknown edge kind \"{kind}\"."),
+                }))
+            }
+        }
+    }
+    let node = match resolve_symbol(store_arc, symbol) {
+        Some(n) => n,
+        None => {
+            return json_text(
+                &serde_json::json!({"error": format!("Symbol \"{}\" not found.", symbol)}),
+            )
+        }
+    };
+    let scope = ExportScope {
+        symbol: Some(node.id.clone()),
+        depth: depth.unwrap_or(2).min(10),
+        direction,
+        edge_kinds: kinds,
+        max_nodes: Some(max_nodes.unwrap_or(200).max(1)),
+        ..ExportScope::default()
+    };
+    let store = store_arc.lock().unwrap_or_else(|e| e.into_inner());
+    let graph = match collect_subgraph(&*store, &scope) {
+        Ok(g) => g,
+        Err(e) => return json_text(&serde_json::json!({"error": e.to_string()})),
+    };
+    json_text(&serde_json::json!({
+        "root": node.id,
+        "direction": scope.direction.as_str(),
+        "depth": scope.depth,
+        "edgeKinds": scope.edge_kinds,
+        "nodeCount": graph.nodes.len(),
+        "edgeCount": graph.edges.len(),
+        "truncated": graph.truncated,
+        "nodes": graph.nodes.iter().map(|n| serde_json::json!({
+            "id": n.id, "name": n.name, "kind": n.kind.as_str(),
+            "file_path": n.file_path, "start_line": n.start_line, "end_line": n.end_line,
+            "language": n.language.as_str(),
+        })).collect::<Vec<_>>(),
+        "edges": graph.edges.iter().map(|e| serde_json::json!({
+            "source": e.source, "target": e.target, "kind": e.kind.as_str(),
+        })).collect::<Vec<_>>(),
+    }))
+}
+
+// 82. codegraph_entrypoints
+/// Entry points of the project, each with the first levels of its callee
+/// tree, so an agent can start reading where execution starts.
+pub fn handle_entrypoints(
+    store_arc: &Arc<Mutex<GraphStore>>,
+    project_root: &Path,
+    kind: Option<&str>,
+    path: Option<&str>,
+    depth: Option<usize>,
+    limit: Option<usize>,
+) -> String {
+    let kind = match kind {
+        None => None,
+        Some(k) => match EntryKind::from_str_loose(k) {
+            Some(k) => Some(k),
+            None => {
+                return json_text(&serde_json::json!({
+                    "error": format!("Unknown kind \"{k}\". Use main, route, command or package_export."),
+                }))
+            }
+        },
+    };
+    let store = store_arc.lock().unwrap_or_else(|e| e.into_inner());
+    let entries = match find_entrypoints(&store, Some(project_root.to_path_buf())) {
+        Ok(e) => e,
+        Err(e) => return json_text(&serde_json::json!({"error": e.to_string()})),
+    };
+    let entries: Vec<&EntryPoint> = entries
+        .iter()
+        .filter(|e| kind.is_none_or(|k| e.kind == k))
+        .filter(|e| path.is_none_or(|p| e.file_path.starts_with(p)))
+        .collect();
+    let mut by_kind: std::collections::BTreeMap<&str, usize> = std::collections::BTreeMap::new();
+    for entry in &entries {
+        *by_kind.entry(entry.kind.as_str()).or_default() += 1;
+    }
+
+    let depth = depth.unwrap_or(2).clamp(1, 3);
+    let limit = limit.unwrap_or(50);
+    let results: Vec<serde_json::Value> = entries
+        .iter()
+        .take(limit)
+        .map(|entry| {
+            let mut value = serde_json::to_value(entry).unwrap_or_default();
+            let mut path = vec![entry.id.clone()];
+            value["callees"] = callee_tree(&store, &entry.id, depth, &mut path).into();
+            value
+        })
+        .collect();
+    json_text(&serde_json::json!({
+        "entryPointCount": entries.len(),
+        "byKind": by_kind,
+        "truncated": entries.len() > limit,
+        "entryPoints": results,
+    }))
+}
+
+// 87. codegraph_routes
+/// The HTTP surface of the project: every route a web framework registers,
+/// with the handler serving it, in path and method order.
+pub fn handle_routes(
+    store_arc: &Arc<Mutex<GraphStore>>,
+    method: Option<&str>,
+    path: Option<&str>,
+    framework: Option<&str>,
+    limit: Option<usize>,
+) -> String {
+    let store = store_arc.lock().unwrap_or_else(|e| e.into_inner());
+    let nodes = match store.get_nodes_by_type(NodeKind::Route.as_str()) {
+        Ok(n) => n,
+        Err(e) => return json_text(&serde_json::json!({"error": e.to_string()})),
+    };
+    // Route nodes are named `GET /users`; the framework leads the qualified name.
+    let mut routes: Vec<(&str, &str, &str, &CodeNode)> = nodes
+        .iter()
+        .filter_map(|n| {
+            let (m, p) = n.name.split_once(' ')?;
+            let fw = n
+                .qualified_name
+                .as_deref()
+                .and_then(|q| q.split_once(' '))
+                .map_or("", |(fw, _)| fw);
+            Some((m, p, fw, n))
+        })
+        .filter(|(m, _, _, _)| method.is_none_or(|want| m.eq_ignore_ascii_case(want)))
+        .filter(|(_, p, _, _)| path.is_none_or(|prefix| p.starts_with(prefix)))
+        .filter(|(_, _, fw, _)| framework.is_none_or(|want| fw.eq_ignore_ascii_case(want)))
+        .collect();
+    routes.sort_by(|a, b| {
+        (a.1, a.0, &a.3.file_path, a.3.start_line).cmp(&(b.1, b.0, &b.3.file_path, b.3.start_line))
+    });
+    let mut by_method: std::collections::BTreeMap<&str, usize> = std::collections::BTreeMap::new();
+    for (m, _, _, _) in &routes {
+        *by_method.entry(m).or_default() += 1;
+    }
+
+    let limit = limit.unwrap_or(100);
+    let results: Vec<serde_json::Value> = routes
+        .iter()
+        .take(limit)
+        .map(|(m, p, fw, node)| {
+            let handler = store
+                .get_out_edges(&node.id, Some(EdgeKind::Handles.as_str()))
+                .unwrap_or_default()
+                .into_iter()
+                .next()
+                .map(|edge| match store.get_node(&edge.target).ok().flatten() {
+                    Some(h) => serde_json::json!({
+                        "name": h.qualified_name.as_deref().unwrap_or(&h.name),
+                        "nodeId": h.id,
+                        "file": h.file_path,
+                        "line": h.start_line,
+                    }),
+                    None => serde_json::json!({
+                        "name": edge.target.strip_prefix("unresolved:").unwrap_or(&edge.target),
+                        "unresolved": true,
+                    }),
+                });
+            serde_json::json!({
+                "method": m,
+                "path": p,
+                "framework": fw,
+                "nodeId": node.id,
+                "file": node.file_path,
+                "line": node.start_line,
+                "handler": handler,
+            })
+        })
+        .collect();
+    json_text(&serde_json::json!({
+        "routeCount": routes.len(),
+        "byMethod": by_method,
+        "truncated": routes.len() > limit,
+        "routes": results,
+    }))
+}
+
+/// Callees reached from `id`, `depth` levels deep, skipping calls back into
+/// the current path.
+fn callee_tree(
+    store: &GraphStore,
+    id: &str,
+    depth: usize,
+    path: &mut Vec<String>,
+) -> Vec<serde_json::Value> {
+    const MAX_CALLEES: usize = 10;
+    if depth == 0 {
+        return Vec::new();
+    }
+    let edges = store
+        .get_out_edges(id, Some(EdgeKind::Calls.as_str()))
+        .unwrap_or_default();
+    let mut seen = std::collections::HashSet::new();
+    let mut callees = Vec::new();
+    for edge in edges {
+        if path.contains(&edge.target) || !seen.insert(edge.target.clone()) {
+            continue;
+        }
+        if callees.len() == MAX_CALLEES {
+            callees.push(serde_json::json!({"more": true}));
+            break;
+        }
+        let Some(node) = store.get_node(&edge.target).ok().flatten() else {
+            continue;
+        };
+        path.push(node.id.clone());
+        let children = callee_tree(store, &node.id, depth - 1, path);
+        path.pop();
+        let mut callee = serde_json::json!({
+            "name": node.qualified_name.as_deref().unwrap_or(&node.name),
+            "kind": node.kind.as_str(),
+            "nodeId": node.id,
+            "file": node.file_path,
+            "line": node.start_line,
+        });
+        if !children.is_empty() {
+            callee["callees"] = children.into();
+        }
+        callees.push(callee);
+    }
+    callees
+}
+
+// 85. codegraph_cfg
+/// The control-flow graph of a function: basic blocks and the branch, loop
+/// and jump edges between them, as JSON or a Mermaid flowchart.
+pub fn handle_cfg(
+    store_arc: &Arc<Mutex<GraphStore>>,
+    project_root: &Path,
+    symbol: &str,
+    format: Option<&str>,
+) -> String {
+    let mermaid = match format.map(str::to_ascii_lowercase).as_deref() {
+        None | Some("json") => false,
+        Some("mermaid") => true,
+        Some(other) => {
+            return json_text(&serde_json::json!({
+                "error": format!("Unknown format \"{other}\". Use json or mermaid."),
+            }))
+        }
+    };
+    let node = match resolve_symbol(store_arc, symbol) {
+        Some(n) => n,
+        None => return symbol_not_found(store_arc, symbol),
+    };
+    if !matches!(
+        node.kind,
+        NodeKind::Function
+            | NodeKind::Method
+            | NodeKind::Variable
+            | NodeKind::Constant
+            | NodeKind::Property
+    ) {
+        return json_text(&serde_json::json!({
+            "error": format!("\"{}\" is a {}, not a function.", node.name, node.kind.as_str()),
+        }));
+    }
+    let source = match crate::observability::validate_path(&node.file_path, project_root)
+        .and_then(|p| std::fs::read_to_string(p).map_err(|e| e.to_string()))
+    {
+        Ok(s) => s,
+        Err(e) => return json_text(&serde_json::json!({"error": e})),
+    };
+    let graph = match cfg::function_cfg(&source, node.language, node.start_line, node.end_line) {
+        Ok(Some(g)) => g,
+        Ok(None) => {
+            return json_text(&serde_json::json!({
+                "error": format!(
+                    "No function body found in {} lines {}-{}; the file may have changed since it was indexed.",
+                    node.file_path, node.start_line, node.end_line
+                ),
+            }))
+        }
+        Err(e) => return json_text(&serde_json::json!({"error": e.to_string()})),
+    };
+    if mermaid {
+        return cfg_mermaid(&graph);
+    }
+    json_text(&serde_json::json!({
+        "symbol": node.qualified_name.as_deref().unwrap_or(&node.name),
+        "nodeId": node.id,
+        "file": node.file_path,
+        "language": node.language.as_str(),
+        "startLine": node.start_line,
+        "endLine": node.end_line,
+        "blockCount": graph.blocks.len(),
+        "edgeCount": graph.edges.len(),
+        "cyclomatic": graph.cyclomatic(),
+        "unreachableBlocks": graph.unreachable(),
+        "entry": graph.entry,
+        "exit": graph.exit,
+        "blocks": graph.blocks,
+        "edges": graph.edges,
+    }))
+}
+
+/// `graph` as a Mermaid flowchart. Unreachable blocks are dashed.
+fn cfg_mermaid(graph: &cfg::ControlFlowGraph) -> String {
+    let mut lines = vec!["```mermaid".to_string(), "flowchart TD".to_string()];
+    for block in &graph.blocks {
+        let text = match block.kind {
+            BlockKind::Entry | BlockKind::Exit => block.kind.as_str().to_string(),
+            _ => block
+                .statements
+                .iter()
+                .map(|s| mermaid_safe(s))
+                .collect::<Vec<_>>()
+                .join("<br/>"),
+        };
+        let id = block.id;
+        lines.push(match block.kind {
+            BlockKind::Entry | BlockKind::Exit => format!("  b{id}([\"{text}\"])"),
+            BlockKind::Condition | BlockKind::Switch => format!("  b{id}{{\"{text}\"}}"),
+            BlockKind::Loop => format!("  b{id}{{{{\"{text}\"}}}}"),
+            BlockKind::Handler => format!("  b{id}[/\"{text}\"/]"),
+            BlockKind::Basic => format!("  b{id}[\"{text}\"]"),
+        });
+    }
+    for edge in &graph.edges {
+        let label = edge
+            .label
+            .as_deref()
+            .map(mermaid_safe)
+            .unwrap_or_else(|| edge.kind.as_str().to_string());
+        let arrow = match edge.kind {
+            CfgEdgeKind::Next => format!("  b{} --> b{}", edge.from, edge.to),
+            CfgEdgeKind::Back | CfgEdgeKind::Exception => {
+                format!("  b{} -.->|{label}| b{}", edge.from, edge.to)
+            }
+            _ => format!("  b{} -->|{label}| b{}", edge.from, edge.to),
+        };
+        lines.push(arrow);
+    }
+    for b in graph.unreachable() {
+        lines.push(format!("  style b{b} stroke-dasharray: 5 5"));
+    }
+    lines.push("```".to_string());
+    lines.join("\n")
+}
+
+// 86. codegraph_null_checks
+pub fn handle_null_checks(
+    file_path: Option<&str>,
+    source: Option<&str>,
+    language: Option<&str>,
+    project_root: &Path,
+) -> String {
+    let (src, lang) = match resolve_source_input(file_path, source, language, project_root) {
+        Ok(v) => v,
+        Err(e) => return json_text(&serde_json::json!({"error": e})),
+    };
+    let Some(derefs) = dataflow::find_null_derefs(&src, &lang) else {
+        return json_text(&serde_json::json!({
+            "error": format!("Null checks support Python, JavaScript, TypeScript and Go, not \"{lang}\"."),
+        }));
+    };
+    json_text(&serde_json::json!({
+        "language": lang,
+        "nullDerefCount": derefs.len(),
+        "derefs": derefs.iter().map(|d| serde_json::json!({
+            "variable": d.variable, "line": d.line, "expression": d.expression,
+            "nullLine": d.null_line, "nullSource": d.null_source,
+        })).collect::<Vec<_>>(),
+    }))
+}
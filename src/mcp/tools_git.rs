@@ -1,15 +1,26 @@
-//! Git MCP tool handler implementations (9 tools).
+//! Git MCP tool handler implementations (12 tools).
 //!
 //! Contains the business logic for: blame, file_history, recent_changes,
-//! commit_diff, symbol_history, branch_info, modified_files, hotspots,
-//! and contributors.
+//! commit_diff, symbol_history, branch_info, modified_files, diff_impact,
+//! hotspots, contributors, reviewers, and search_commits.
 
+use std::collections::HashSet;
 use std::path::Path;
+use std::sync::{Arc, Mutex};
 
+use crate::context::budget::truncate_to_fit;
 use crate::git;
+use crate::git::commit_search::{commit_index_status, engine_for, search_commits, CommitEmbedder};
+use crate::git::evolution::{DefinitionChange, SymbolVersion};
+use crate::git::reviewers::ImpactedSymbol;
+use crate::graph::change_impact::change_impact;
+use crate::graph::store::GraphStore;
 
 use super::server::json_text;
 
+/// Token budget of each definition body in `codegraph_symbol_history`.
+const MAX_BODY_TOKENS: usize = 400;
+
 // 14. codegraph_blame
 pub fn handle_blame(project_root: &Path, file_path: &str) -> String {
     match git::blame::git_blame(project_root, file_path) {
@@ -70,18 +81,56 @@ pub fn handle_commit_diff(project_root: &Path, commit: &str) -> String {
 }
 
 // 18. codegraph_symbol_history
-pub fn handle_symbol_history(project_root: &Path, symbol: &str) -> String {
-    match git::history::symbol_history(project_root, symbol) {
-        Ok(commits) => json_text(&serde_json::json!({
-            "symbol": symbol,
-            "commitCount": commits.len(),
-            "commits": commits.iter().map(|c| serde_json::json!({
-                "hash": c.hash, "author": c.author, "email": c.email,
-                "date": c.date, "message": c.message,
+/// Commits touching `symbol`, each with the definition before and after it
+/// and whether its signature changed.
+pub fn handle_symbol_history(
+    project_root: &Path,
+    symbol: &str,
+    limit: Option<usize>,
+    include_bodies: Option<bool>,
+) -> String {
+    let limit = limit.unwrap_or(20).clamp(1, 100);
+    let include_bodies = include_bodies.unwrap_or(true);
+    let history = match git::evolution::symbol_evolution(project_root, symbol, limit) {
+        Ok(h) => h,
+        Err(e) => return json_text(&serde_json::json!({"error": e.to_string()})),
+    };
+    let version = |v: &Option<SymbolVersion>| {
+        v.as_ref().map(|v| {
+            let mut out = serde_json::json!({
+                "kind": v.kind.as_str(),
+                "signature": v.signature,
+                "startLine": v.start_line,
+                "endLine": v.end_line,
+            });
+            if include_bodies {
+                out["body"] = serde_json::json!(truncate_to_fit(&v.body, MAX_BODY_TOKENS));
+            }
+            out
+        })
+    };
+    let signature_changes = history
+        .iter()
+        .filter(|e| e.change() == DefinitionChange::SignatureChanged)
+        .count();
+
+    json_text(&serde_json::json!({
+        "symbol": symbol,
+        "commitCount": history.len(),
+        "signatureChanges": signature_changes,
+        "commits": history.iter().map(|e| serde_json::json!({
+            "hash": e.commit.hash, "author": e.commit.author, "email": e.commit.email,
+            "date": e.commit.date, "message": e.commit.message,
+            "change": e.change(),
+            "signatureChanged": e.change() == DefinitionChange::SignatureChanged,
+            "definitions": e.definitions.iter().map(|d| serde_json::json!({
+                "file": d.file_path,
+                "change": d.change,
+                "before": version(&d.before),
+                "after": version(&d.after),
             })).collect::<Vec<_>>(),
-        })),
-        Err(e) => json_text(&serde_json::json!({"error": e.to_string()})),
-    }
+        })).collect::<Vec<_>>(),
+    }))
 }
 
 // 19. codegraph_branch_info
@@ -106,6 +155,66 @@ pub fn handle_modified_files(project_root: &Path) -> String {
     }
 }
 
+// 73. codegraph_diff_impact
+/// Impact of the working-tree changes against `base` (default `HEAD`): the
+/// symbols the changed lines fall in, their callers, and tests to run.
+pub fn handle_diff_impact(
+    store_arc: &Arc<Mutex<GraphStore>>,
+    project_root: &Path,
+    base: Option<&str>,
+    max_depth: Option<u32>,
+    limit: Option<usize>,
+) -> String {
+    let changes = match git::changed_lines(project_root, base) {
+        Ok(c) => c,
+        Err(e) => return json_text(&serde_json::json!({"error": e.to_string()})),
+    };
+    if changes.is_empty() {
+        return json_text(&serde_json::json!({
+            "base": base.unwrap_or("HEAD"),
+            "changedFiles": 0,
+            "message": "No changes in the working tree.",
+        }));
+    }
+    let store = store_arc.lock().unwrap_or_else(|e| e.into_inner());
+    let impact = match change_impact(&store, &changes, max_depth.unwrap_or(3).min(10)) {
+        Ok(i) => i,
+        Err(e) => return json_text(&serde_json::json!({"error": e.to_string()})),
+    };
+    let limit = limit.unwrap_or(100);
+    let range_json = |ranges: &[(u32, u32)]| {
+        ranges
+            .iter()
+            .map(|(from, to)| serde_json::json!([from, to]))
+            .collect::<Vec<_>>()
+    };
+    json_text(&serde_json::json!({
+        "base": base.unwrap_or("HEAD"),
+        "changedFiles": changes.iter().map(|c| serde_json::json!({
+            "file": c.path,
+            "lines": if c.whole_file { serde_json::json!("all") } else { serde_json::json!(range_json(&c.ranges)) },
+        })).collect::<Vec<_>>(),
+        "changedSymbolCount": impact.changed.len(),
+        "changedSymbols": impact.changed.iter().map(|c| serde_json::json!({
+            "id": c.node.id, "name": c.node.name, "kind": c.node.kind.as_str(),
+            "file": c.node.file_path, "line": c.node.start_line,
+            "changedLines": range_json(&c.ranges),
+        })).collect::<Vec<_>>(),
+        "affectedCallerCount": impact.callers.len(),
+        "affectedCallers": impact.callers.iter().take(limit).map(|c| serde_json::json!({
+            "id": c.node.id, "name": c.node.name, "kind": c.node.kind.as_str(),
+            "file": c.node.file_path, "line": c.node.start_line,
+            "depth": c.depth, "via": c.via,
+            "risk": if c.depth <= 1 { "high" } else if c.depth <= 3 { "medium" } else { "low" },
+        })).collect::<Vec<_>>(),
+        "affectedFiles": impact.affected_files(),
+        "suggestedTests": impact.tests.iter().map(|t| serde_json::json!({
+            "id": t.id, "name": t.name, "file": t.file_path, "line": t.start_line,
+        })).collect::<Vec<_>>(),
+        "unindexedFiles": impact.unindexed_files,
+    }))
+}
+
 // 21. codegraph_hotspots
 pub fn handle_hotspots(project_root: &Path, limit: Option<usize>) -> String {
     match git::analysis::hotspots(project_root, limit.unwrap_or(20)) {
@@ -133,3 +242,125 @@ pub fn handle_contributors(project_root: &Path, file_path: Option<&str>) -> Stri
         Err(e) => json_text(&serde_json::json!({"error": e.to_string()})),
     }
 }
+
+// 65. codegraph_search_commits
+pub fn handle_search_commits(
+    store_arc: &Arc<Mutex<GraphStore>>,
+    project_root: &Path,
+    query: &str,
+    limit: Option<usize>,
+) -> String {
+    let store = store_arc.lock().unwrap_or_else(|e| e.into_inner());
+    let (indexed, model) = commit_index_status(&store.conn).unwrap_or((0, None));
+    let engine = engine_for(model.as_deref());
+    let embedder = match engine.as_ref() {
+        Some(engine) => CommitEmbedder::Model(engine),
+        None => CommitEmbedder::Lexical,
+    };
+    let limit = limit.unwrap_or(10).min(50);
+    match search_commits(&store.conn, project_root, &embedder, query, limit) {
+        Ok(commits) => {
+            let mut response = serde_json::json!({
+                "query": query,
+                "indexedCommits": indexed,
+                "model": embedder.model_name(),
+                "commitCount": commits.len(),
+                "commits": commits,
+            });
+            if indexed == 0 {
+                response["hint"] = serde_json::json!(
+                    "No commit messages are embedded yet; run `codegraph index` in a git repository. Showing `git log -S` matches only."
+                );
+            }
+            json_text(&response)
+        }
+        Err(e) => json_text(&serde_json::json!({"error": e.to_string()})),
+    }
+}
+
+// 51. codegraph_reviewers
+pub fn handle_reviewers(
+    store_arc: &Arc<Mutex<GraphStore>>,
+    project_root: &Path,
+    files: Option<Vec<String>>,
+    limit: Option<usize>,
+) -> String {
+    let files = match files {
+        Some(files) if !files.is_empty() => files,
+        _ => match git::history::modified_files(project_root) {
+            Ok(mf) => {
+                let mut seen = HashSet::new();
+                mf.staged
+                    .into_iter()
+                    .chain(mf.unstaged)
+                    .filter(|f| seen.insert(f.clone()))
+                    .collect()
+            }
+            Err(e) => return json_text(&serde_json::json!({"error": e.to_string()})),
+        },
+    };
+    if files.is_empty() {
+        return json_text(&serde_json::json!({
+            "error": "No changed files. Pass 'files' or stage some changes."
+        }));
+    }
+
+    let impacted = {
+        let store = store_arc.lock().unwrap_or_else(|e| e.into_inner());
+        impacted_symbols(&store, &files)
+    };
+    match git::reviewers::suggest_reviewers(project_root, &files, &impacted, limit.unwrap_or(5)) {
+        Ok(reviewers) => json_text(&serde_json::json!({
+            "files": files,
+            "impactedSymbolCount": impacted.len(),
+            "reviewerCount": reviewers.len(),
+            "reviewers": reviewers,
+        })),
+        Err(e) => json_text(&serde_json::json!({"error": e.to_string()})),
+    }
+}
+
+/// Callers and callees of the changed files' symbols that live outside the
+/// change set, with their last author from `node_git_meta`.
+fn impacted_symbols(store: &GraphStore, files: &[String]) -> Vec<ImpactedSymbol> {
+    let changed: HashSet<&str> = files.iter().map(String::as_str).collect();
+    let mut seen = HashSet::new();
+    let mut impacted = Vec::new();
+
+    for file in files {
+        for node in store.get_nodes_by_file(file).unwrap_or_default() {
+            let callers = store
+                .get_in_edges(&node.id, Some("calls"))
+                .unwrap_or_default();
+            let callees = store
+                .get_out_edges(&node.id, Some("calls"))
+                .unwrap_or_default();
+            let neighbours = callers
+                .into_iter()
+                .map(|e| (e.source, "caller"))
+                .chain(callees.into_iter().map(|e| (e.target, "callee")));
+            for (id, relation) in neighbours {
+                if !seen.insert(id.clone()) {
+                    continue;
+                }
+                let Ok(Some(other)) = store.get_node(&id) else {
+                    continue;
+                };
+                if changed.contains(other.file_path.as_str()) {
+                    continue;
+                }
+                if let Ok(Some(meta)) = store.get_node_git_meta(&id) {
+                    impacted.push(ImpactedSymbol {
+                        name: other.name,
+                        file_path: other.file_path,
+                        relation,
+                        author: meta.author,
+                        email: meta.email,
+                        age_days: meta.age_days,
+                    });
+                }
+            }
+        }
+    }
+    impacted
+}
@@ -1,20 +1,34 @@
-//! Security MCP tool handler implementations (9 tools).
+//! Security MCP tool handler implementations (11 tools).
 //!
 //! Contains the business logic for: scan_security, check_owasp, check_cwe,
 //! explain_vulnerability, suggest_fix, find_injections, taint_sources,
-//! security_summary, and trace_taint.
+//! security_summary, trace_taint, audit_dependencies, and find_secrets.
 
 use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
+use crate::db::findings::{FindingCategory, NewFinding};
+use crate::db::risk_history::{self, RiskSample};
+use crate::graph::store::GraphStore;
+use crate::resolution::deps_audit;
 use crate::security;
+use crate::types::{CodeEdge, EdgeKind};
 
-use super::server::json_text;
+use super::server::{json_text, track_findings};
+use super::tasks::TaskProgress;
+
+/// Edge batch size when reading the graph.
+const BATCH_SIZE: usize = 500;
 
 // 23. codegraph_scan_security
 pub fn handle_scan_security(
+    store_arc: &Arc<Mutex<GraphStore>>,
     project_root: &Path,
     directory: Option<String>,
     exclude_tests: Option<bool>,
+    include_baselined: bool,
+    progress: Option<&TaskProgress>,
 ) -> String {
     let dir = match directory {
         Some(ref d) => match crate::observability::validate_path(d, project_root) {
@@ -23,20 +37,81 @@ pub fn handle_scan_security(
         },
         None => project_root.to_path_buf(),
     };
-    let rules = security::rules::load_bundled_rules();
-    let summary = security::scanner::scan_directory(&dir, &rules, exclude_tests.unwrap_or(true));
+    let rules = security::packs::project_rules(project_root);
+    let mut summary = security::scanner::scan_directory_with_progress(
+        &dir,
+        &rules,
+        exclude_tests.unwrap_or(true),
+        &|files| {
+            if let Some(progress) = progress.filter(|_| files % 100 == 0) {
+                progress.message(&format!("Scanned {files} files"));
+            }
+        },
+    );
+
+    let baseline = match include_baselined {
+        true => Ok(None),
+        false => security::baseline::Baseline::load(project_root),
+    };
+    if let Ok(Some(baseline)) = &baseline {
+        summary.apply_baseline(baseline, project_root);
+    }
+
+    // History is keyed on project-relative paths so scans of a subdirectory
+    // and of the whole project agree on identities.
+    let relative = |path: &Path| -> String {
+        path.strip_prefix(project_root)
+            .unwrap_or(path)
+            .to_string_lossy()
+            .replace('\\', "/")
+    };
+    let findings: Vec<NewFinding> = summary
+        .findings
+        .iter()
+        .map(|f| NewFinding {
+            rule: f.rule_id.clone(),
+            file_path: relative(Path::new(&f.file_path)),
+            symbol: None,
+            line: f.line_number as u32,
+            description: format!("{} {}", f.message, f.matched_text.trim()),
+        })
+        .collect();
+    let scope = match relative(&dir) {
+        r if r.is_empty() => r,
+        r => format!("{}/", r.trim_end_matches('/')),
+    };
+    let record = {
+        let store = store_arc.lock().unwrap_or_else(|e| e.into_inner());
+        track_findings(&store, FindingCategory::Security, Some(&scope), &findings)
+    };
+
     json_text(&serde_json::json!({
         "totalFindings": summary.total_findings,
         "critical": summary.critical, "high": summary.high,
         "medium": summary.medium, "low": summary.low,
         "filesScanned": summary.files_scanned,
         "rulesApplied": summary.rules_applied,
+        "suppressed": summary.suppressed,
+        "baselined": summary.baselined,
+        "baselineError": baseline.err(),
+        "newFindings": record.as_ref().map_or(0, |r| r.new_count()),
+        "duplicates": record.as_ref().map_or(0, |r| r.duplicates),
+        "resolvedFindings": record.as_ref().map_or(0, |r| r.resolved.len()),
         "topIssues": summary.top_issues.iter().map(|(name, count)| serde_json::json!({"rule": name, "count": count})).collect::<Vec<_>>(),
-        "findings": summary.findings.iter().take(50).map(|f| serde_json::json!({
-            "ruleId": f.rule_id, "ruleName": f.rule_name, "severity": format!("{:?}", f.severity),
-            "file": f.file_path, "line": f.line_number, "message": f.message,
-            "fix": f.fix, "cwe": f.cwe, "owasp": f.owasp,
-        })).collect::<Vec<_>>(),
+        "findings": summary.findings.iter().enumerate().take(50).map(|(i, f)| {
+            let mut entry = serde_json::json!({
+                "ruleId": f.rule_id, "ruleName": f.rule_name, "severity": format!("{:?}", f.severity),
+                "file": f.file_path, "line": f.line_number, "message": f.message,
+                "fix": f.fix, "cwe": f.cwe, "owasp": f.owasp,
+            });
+            if let Some(tracked) = record.as_ref().map(|rec| &rec.findings[i]) {
+                entry["findingId"] = serde_json::json!(tracked.id);
+                entry["firstSeen"] = serde_json::json!(tracked.first_seen);
+                entry["lastSeen"] = serde_json::json!(tracked.last_seen);
+                entry["new"] = serde_json::json!(tracked.is_new);
+            }
+            entry
+        }).collect::<Vec<_>>(),
     }))
 }
 
@@ -49,7 +124,10 @@ pub fn handle_check_owasp(project_root: &Path, directory: Option<String>) -> Str
         },
         None => project_root.to_path_buf(),
     };
-    let summary = security::scanner::check_owasp_top10(&dir);
+    let summary = security::scanner::check_owasp_top10_with(
+        &dir,
+        security::packs::project_rules(project_root),
+    );
     json_text(&serde_json::json!({
         "standard": "OWASP Top 10 2021",
         "totalFindings": summary.total_findings,
@@ -72,7 +150,8 @@ pub fn handle_check_cwe(project_root: &Path, directory: Option<String>) -> Strin
         },
         None => project_root.to_path_buf(),
     };
-    let summary = security::scanner::check_cwe_top25(&dir);
+    let summary =
+        security::scanner::check_cwe_top25_with(&dir, security::packs::project_rules(project_root));
     json_text(&serde_json::json!({
         "standard": "CWE Top 25",
         "totalFindings": summary.total_findings,
@@ -139,6 +218,40 @@ pub fn handle_find_injections(source: &str, language: &str) -> String {
     }))
 }
 
+/// `codegraph_find_injections` without a source text: taint followed
+/// through the indexed call graph, so sinks in helpers are found too.
+pub fn handle_graph_injections(
+    store_arc: &Arc<Mutex<GraphStore>>,
+    path: Option<&str>,
+    max_hops: Option<usize>,
+    limit: Option<usize>,
+) -> String {
+    let max_hops = max_hops.unwrap_or(security::interprocedural::DEFAULT_MAX_HOPS);
+    let flows = {
+        let store = store_arc.lock().unwrap_or_else(|e| e.into_inner());
+        match security::interprocedural::find_interprocedural_flows(&store, path, max_hops) {
+            Ok(f) => f,
+            Err(e) => return json_text(&serde_json::json!({"error": e.to_string()})),
+        }
+    };
+    let hops = |f: &security::TaintFlow| f.path.iter().filter(|s| s.operation == "call").count();
+    json_text(&serde_json::json!({
+        "vulnerabilityCount": flows.len(),
+        "crossFunction": flows.iter().filter(|f| hops(f) > 0).count(),
+        "maxHops": max_hops,
+        "flows": flows.iter().take(limit.unwrap_or(50)).map(|f| serde_json::json!({
+            "type": f.vulnerability_type,
+            "source": { "kind": format!("{:?}", f.source.kind), "file": f.source.file_path, "line": f.source.line_number, "expression": f.source.expression },
+            "sink": { "kind": f.sink.kind, "file": f.sink.file_path, "line": f.sink.line_number, "expression": f.sink.expression },
+            "hops": hops(f),
+            "steps": f.path.iter().map(|s| serde_json::json!({
+                "file": s.file_path, "line": s.line_number, "code": s.code,
+                "variable": s.variable, "operation": s.operation,
+            })).collect::<Vec<_>>(),
+        })).collect::<Vec<_>>(),
+    }))
+}
+
 // 29. codegraph_taint_sources
 pub fn handle_taint_sources(source: &str, language: &str) -> String {
     let sources = security::taint::find_taint_sources(source, language);
@@ -153,7 +266,11 @@ pub fn handle_taint_sources(source: &str, language: &str) -> String {
 }
 
 // 30. codegraph_security_summary
-pub fn handle_security_summary(project_root: &Path, directory: Option<String>) -> String {
+pub fn handle_security_summary(
+    store_arc: &Arc<Mutex<GraphStore>>,
+    project_root: &Path,
+    directory: Option<String>,
+) -> String {
     let dir = match directory {
         Some(ref d) => match crate::observability::validate_path(d, project_root) {
             Ok(p) => p,
@@ -161,15 +278,109 @@ pub fn handle_security_summary(project_root: &Path, directory: Option<String>) -
         },
         None => project_root.to_path_buf(),
     };
-    let rules = security::rules::load_bundled_rules();
-    let summary = security::scanner::scan_directory(&dir, &rules, true);
+    let rules = security::packs::project_rules(project_root);
+    let (summary, taint) = security::risk::scan_with_taint(&dir, &rules, true);
+    let taint_flows = taint.len();
+    let report = security::risk::aggregate(
+        summary.findings.iter().cloned().chain(taint).collect(),
+        project_root,
+    );
+
+    // Directories with risk in the previous scan and no findings now are
+    // recorded at zero, so their trend shows the drop.
+    let scope = dir
+        .strip_prefix(project_root)
+        .unwrap_or(&dir)
+        .to_string_lossy()
+        .replace('\\', "/");
+    let now = chrono::Utc::now().timestamp();
+    let previous = {
+        let store = store_arc.lock().unwrap_or_else(|e| e.into_inner());
+        risk_history::latest_risk(&store.conn, &scope).unwrap_or_default()
+    };
+    let mut cleared: Vec<&RiskSample> = previous
+        .values()
+        .filter(|p| {
+            p.score > 0.0
+                && !report
+                    .directories
+                    .iter()
+                    .any(|d| d.directory == p.directory)
+        })
+        .collect();
+    cleared.sort_by(|a, b| a.directory.cmp(&b.directory));
+    let samples: Vec<RiskSample> = report
+        .directories
+        .iter()
+        .map(|d| (d.directory.as_str(), d.score, d.findings))
+        .chain(cleared.iter().map(|p| (p.directory.as_str(), 0.0, 0)))
+        .map(|(directory, score, findings)| RiskSample {
+            directory: directory.to_string(),
+            score,
+            findings,
+            scanned_at: now,
+        })
+        .collect();
+    {
+        let store = store_arc.lock().unwrap_or_else(|e| e.into_inner());
+        if let Err(e) = risk_history::record_risk(&store.conn, &samples) {
+            tracing::warn!("Failed to record security risk: {}", e);
+        }
+    }
+
+    let trend = |score: f64, before: Option<f64>| match before {
+        None => "new",
+        Some(b) if score > b => "up",
+        Some(b) if score < b => "down",
+        Some(_) => "unchanged",
+    };
+    let directories: Vec<serde_json::Value> = report
+        .directories
+        .iter()
+        .map(|d| {
+            let before = previous.get(&d.directory).map(|p| p.score);
+            serde_json::json!({
+                "directory": d.directory, "score": d.score, "findings": d.findings,
+                "critical": d.critical, "high": d.high,
+                "previousScore": before, "trend": trend(d.score, before),
+            })
+        })
+        .chain(cleared.iter().map(|p| {
+            serde_json::json!({
+                "directory": p.directory, "score": 0.0, "findings": 0, "critical": 0, "high": 0,
+                "previousScore": p.score, "trend": "down",
+            })
+        }))
+        .collect();
+
+    let risk_level = match security::risk::rating(report.score) {
+        security::rules::Severity::Critical => "CRITICAL",
+        security::rules::Severity::High => "HIGH",
+        security::rules::Severity::Medium => "MEDIUM",
+        _ => "LOW",
+    };
     json_text(&serde_json::json!({
-        "riskLevel": if summary.critical > 0 { "CRITICAL" } else if summary.high > 0 { "HIGH" } else if summary.medium > 0 { "MEDIUM" } else { "LOW" },
+        "riskLevel": risk_level,
+        "riskScore": report.score,
         "totalFindings": summary.total_findings,
+        "taintFlows": taint_flows,
+        "uniqueFindings": report.findings.len(),
+        "mergedDuplicates": report.merged,
         "bySeverity": { "critical": summary.critical, "high": summary.high, "medium": summary.medium, "low": summary.low },
         "filesScanned": summary.files_scanned,
         "rulesApplied": summary.rules_applied,
+        "suppressed": summary.suppressed,
         "topIssues": summary.top_issues,
+        "directories": directories,
+        "findings": report.findings.iter().take(50).map(|r| serde_json::json!({
+            "score": r.score, "rating": security::risk::rating(r.score).to_string(),
+            "ruleIds": r.rule_ids, "ruleName": r.finding.rule_name,
+            "severity": format!("{:?}", r.finding.severity),
+            "file": r.finding.file_path, "line": r.finding.line_number,
+            "message": r.finding.message, "fix": r.finding.fix,
+            "cwe": r.finding.cwe, "owasp": r.finding.owasp,
+            "taintConfirmed": r.taint_confirmed,
+        })).collect::<Vec<_>>(),
     }))
 }
 
@@ -189,3 +400,167 @@ pub fn handle_trace_taint(source: &str, language: &str, from_line: usize) -> Str
         })).collect::<Vec<_>>(),
     }))
 }
+
+// 83. codegraph_audit_dependencies
+pub fn handle_audit_dependencies(
+    store_arc: &Arc<Mutex<GraphStore>>,
+    project_root: &Path,
+    offline: bool,
+    max_cache_age_hours: Option<u64>,
+) -> String {
+    let scan = deps_audit::locked_packages(project_root);
+    if scan.lockfiles.is_empty() {
+        return json_text(&serde_json::json!({
+            "error": "No Cargo.lock, package-lock.json or poetry.lock found in the project"
+        }));
+    }
+    let max_cache_age = max_cache_age_hours
+        .map(|h| Duration::from_secs(h * 60 * 60))
+        .unwrap_or(deps_audit::DEFAULT_MAX_CACHE_AGE);
+
+    let mut cache = deps_audit::OsvCache::load(project_root);
+    let api = deps_audit::OsvApi::default();
+    let source: Option<&dyn deps_audit::OsvSource> = match offline {
+        true => None,
+        false => Some(&api),
+    };
+    let mut report = deps_audit::audit(
+        &scan.packages,
+        &mut cache,
+        source,
+        max_cache_age,
+        chrono::Utc::now().timestamp(),
+    );
+    if !offline {
+        if let Err(e) = cache.save(project_root) {
+            report.errors.push(e);
+        }
+    }
+
+    // Only imports of external modules can name a package.
+    let edges: Vec<CodeEdge> = {
+        let store = store_arc.lock().unwrap_or_else(|e| e.into_inner());
+        store
+            .iter_edges(BATCH_SIZE)
+            .flatten()
+            .flatten()
+            .filter(|e| e.kind == EdgeKind::Imports && e.target.starts_with("module:"))
+            .collect()
+    };
+    let index = deps_audit::import_index(&edges, project_root);
+    let mut vulnerable: Vec<_> = report
+        .vulnerable
+        .iter()
+        .map(|v| (v, deps_audit::importers_of(&index, &v.package)))
+        .collect();
+    // Packages the code imports directly come first.
+    vulnerable.sort_by_key(|(v, importers)| (importers.is_empty(), v.package.name.clone()));
+
+    let errors: Vec<&String> = scan.errors.iter().chain(&report.errors).collect();
+    json_text(&serde_json::json!({
+        "lockfiles": scan.lockfiles,
+        "packagesScanned": scan.packages.len(),
+        "vulnerablePackageCount": vulnerable.len(),
+        "vulnerabilityCount": vulnerable.iter().map(|(v, _)| v.vulnerabilities.len()).sum::<usize>(),
+        "importedVulnerablePackages": vulnerable.iter().filter(|(_, i)| !i.is_empty()).count(),
+        "unchecked": report.unchecked,
+        "fromCache": report.from_cache,
+        "offline": offline,
+        "errors": errors,
+        "vulnerablePackages": vulnerable.iter().map(|(v, importers)| serde_json::json!({
+            "name": v.package.name, "version": v.package.version,
+            "ecosystem": v.package.ecosystem.as_str(), "lockfile": v.package.lockfile,
+            "vulnerabilities": v.vulnerabilities,
+            "importedBy": importers,
+        })).collect::<Vec<_>>(),
+    }))
+}
+
+// 84. codegraph_find_secrets
+pub fn handle_find_secrets(
+    store_arc: &Arc<Mutex<GraphStore>>,
+    project_root: &Path,
+    options: &security::secrets::SecretScanOptions,
+    path: Option<&str>,
+    include_tests: bool,
+    min_severity: Option<&str>,
+    limit: Option<usize>,
+) -> String {
+    let min_severity = match min_severity {
+        Some(s) => match security::rules::Severity::from_str_loose(s) {
+            Some(severity) => severity,
+            None => {
+                return json_text(&serde_json::json!({
+                    "error": format!("Unknown severity '{s}': use info, low, medium, high or critical")
+                }))
+            }
+        },
+        None => security::rules::Severity::Info,
+    };
+    let files = {
+        let store = store_arc.lock().unwrap_or_else(|e| e.into_inner());
+        match store.get_file_paths() {
+            Ok(files) => files,
+            Err(e) => return json_text(&serde_json::json!({"error": e.to_string()})),
+        }
+    };
+
+    let rules = security::packs::project_rules(project_root);
+    let policy = crate::sandbox::current();
+    let mut summary = security::scanner::SecuritySummary::new();
+    summary.rules_applied = security::secrets::SECRET_PATTERNS.len()
+        + rules
+            .iter()
+            .filter(|r| r.category == security::rules::RuleCategory::Secrets)
+            .count();
+    let mut allowlisted = 0;
+    for file in files {
+        if path.is_some_and(|p| !crate::graph::api_guard::in_directory(&file, p)) {
+            continue;
+        }
+        if !include_tests && security::rules::is_test_file(&file) {
+            continue;
+        }
+        let full = project_root.join(&file);
+        if !policy.allows_file(&full) {
+            continue;
+        }
+        let Ok(source) = std::fs::read_to_string(&full) else {
+            continue;
+        };
+        let language = full
+            .extension()
+            .and_then(|e| {
+                crate::types::Language::from_extension(&format!(".{}", e.to_string_lossy()))
+            })
+            .map(|l| l.as_str().to_string())
+            .unwrap_or_default();
+        let scan = security::secrets::scan_secrets(&file, &source, &language, &rules, options);
+        summary.suppressed += scan.suppressed;
+        allowlisted += scan.allowlisted;
+        for finding in scan.findings {
+            if finding.severity >= min_severity {
+                summary.add_finding(finding);
+            }
+        }
+        summary.files_scanned += 1;
+    }
+    summary.finalize();
+
+    json_text(&serde_json::json!({
+        "totalFindings": summary.total_findings,
+        "critical": summary.critical, "high": summary.high,
+        "medium": summary.medium, "low": summary.low,
+        "filesScanned": summary.files_scanned,
+        "rulesApplied": summary.rules_applied,
+        "suppressed": summary.suppressed,
+        "allowlisted": allowlisted,
+        "entropy": options.entropy,
+        "topIssues": summary.top_issues.iter().map(|(name, count)| serde_json::json!({"rule": name, "count": count})).collect::<Vec<_>>(),
+        "findings": summary.findings.iter().take(limit.unwrap_or(50)).map(|f| serde_json::json!({
+            "ruleId": f.rule_id, "ruleName": f.rule_name, "severity": format!("{:?}", f.severity),
+            "file": f.file_path, "line": f.line_number, "message": f.message,
+            "match": f.matched_text, "fix": f.fix, "cwe": f.cwe, "owasp": f.owasp,
+        })).collect::<Vec<_>>(),
+    }))
+}
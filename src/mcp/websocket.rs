@@ -0,0 +1,335 @@
+//! WebSocket transport for browser-based MCP clients.
+//!
+//! `codegraph serve --http <addr>` also accepts WebSocket upgrades at
+//! `/ws`. Each text frame carries one JSON-RPC message in either
+//! direction, and every connection gets its own MCP session, the same way
+//! a streamable HTTP session does.
+//!
+//! A connection opened as `/ws?session=<id>` (a client-chosen random ID of
+//! 16 to 128 letters, digits, `-` or `_`) can be resumed: once it drops,
+//! its session state — pinned snapshot, resource subscriptions and MCP
+//! tasks — is kept for [`RESUME_GRACE`], and a reconnect with the same ID
+//! and API key picks it up again after a fresh `initialize`. Notifications
+//! sent while no connection was open are lost; subscriptions notice the
+//! index changes they missed at their next poll. Connecting with the ID
+//! of a session that is still connected is refused with `409`.
+//!
+//! API keys and tool allowlists apply as for HTTP requests, see
+//! [`http`](super::http): the upgrade request must carry the key, and a
+//! `tools/call` outside the key's allowlist gets a JSON-RPC error instead
+//! of reaching the server.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::{Query, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::Extension;
+use futures::stream::{SplitSink, SplitStream};
+use futures::{SinkExt, StreamExt};
+use rmcp::service::{RoleServer, RxJsonRpcMessage, TxJsonRpcMessage};
+use rmcp::transport::Transport;
+use rmcp::ServiceExt;
+use serde::Deserialize;
+
+use super::http::ApiKey;
+use super::server::CodeGraphServer;
+use super::tenants::{error_response, tool_calls};
+
+/// How long the state of a dropped, resumable connection is kept.
+pub const RESUME_GRACE: Duration = Duration::from_secs(300);
+
+// ---------------------------------------------------------------------------
+// Sessions
+// ---------------------------------------------------------------------------
+
+struct WsSession {
+    server: CodeGraphServer,
+    /// Name of the API key the session was opened with.
+    key: Option<String>,
+    /// When the connection dropped; `None` while connected.
+    detached_at: Option<Instant>,
+}
+
+/// Resumable sessions of WebSocket connections.
+pub struct WsSessions {
+    server: CodeGraphServer,
+    sessions: Mutex<HashMap<String, WsSession>>,
+}
+
+/// Why a connection cannot take a session.
+#[derive(Debug, PartialEq, Eq)]
+pub enum AttachError {
+    /// Not a valid session ID.
+    InvalidId,
+    /// The session is still connected.
+    InUse,
+    /// The session was opened with another API key.
+    OtherKey,
+}
+
+impl WsSessions {
+    pub fn new(server: CodeGraphServer) -> Self {
+        Self {
+            server,
+            sessions: Mutex::default(),
+        }
+    }
+
+    /// Session state for a new connection: a fresh session without `id`,
+    /// otherwise the session `id`, created on first use. Returns whether
+    /// an existing session was resumed.
+    pub fn attach(
+        &self,
+        id: Option<&str>,
+        key: Option<&str>,
+    ) -> Result<(CodeGraphServer, bool), AttachError> {
+        let Some(id) = id else {
+            return Ok((self.server.for_session(), false));
+        };
+        if !valid_session_id(id) {
+            return Err(AttachError::InvalidId);
+        }
+        let mut sessions = self.sessions.lock().unwrap_or_else(|e| e.into_inner());
+        sessions.retain(|_, s| s.detached_at.is_none_or(|at| at.elapsed() < RESUME_GRACE));
+        match sessions.get_mut(id) {
+            Some(session) if session.detached_at.is_none() => Err(AttachError::InUse),
+            Some(session) if session.key.as_deref() != key => Err(AttachError::OtherKey),
+            Some(session) => {
+                session.detached_at = None;
+                Ok((session.server.clone(), true))
+            }
+            None => {
+                let server = self.server.for_session();
+                sessions.insert(
+                    id.to_string(),
+                    WsSession {
+                        server: server.clone(),
+                        key: key.map(str::to_string),
+                        detached_at: None,
+                    },
+                );
+                Ok((server, false))
+            }
+        }
+    }
+
+    /// Keep the session `id` for [`RESUME_GRACE`] after its connection
+    /// closed.
+    pub fn detach(&self, id: &str) {
+        let mut sessions = self.sessions.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(session) = sessions.get_mut(id) {
+            session.detached_at = Some(Instant::now());
+        }
+    }
+}
+
+fn valid_session_id(id: &str) -> bool {
+    (16..=128).contains(&id.len())
+        && id
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_')
+}
+
+// ---------------------------------------------------------------------------
+// Handler
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Deserialize)]
+pub struct WsParams {
+    session: Option<String>,
+}
+
+/// `GET /ws`: upgrade to a WebSocket carrying an MCP session.
+pub async fn ws_handler(
+    State(sessions): State<Arc<WsSessions>>,
+    Query(params): Query<WsParams>,
+    key: Option<Extension<ApiKey>>,
+    upgrade: WebSocketUpgrade,
+) -> Response {
+    let key = key.map(|Extension(key)| key);
+    let id = params.session;
+    let (server, resumed) =
+        match sessions.attach(id.as_deref(), key.as_ref().map(|k| k.name.as_str())) {
+            Ok(attached) => attached,
+            Err(AttachError::InvalidId) => {
+                return error_response(
+                    StatusCode::BAD_REQUEST,
+                    "session must be 16-128 letters, digits, '-' or '_'",
+                )
+            }
+            Err(AttachError::InUse) => {
+                return error_response(StatusCode::CONFLICT, "session is still connected")
+            }
+            Err(AttachError::OtherKey) => {
+                return error_response(StatusCode::FORBIDDEN, "session belongs to another API key")
+            }
+        };
+    if resumed {
+        tracing::info!("resuming WebSocket session");
+    }
+    let failed = (Arc::clone(&sessions), id.clone());
+    upgrade
+        .on_failed_upgrade(move |e| {
+            tracing::warn!("WebSocket upgrade failed: {e}");
+            if let (sessions, Some(id)) = failed {
+                sessions.detach(&id);
+            }
+        })
+        .on_upgrade(move |socket| async move {
+            let transport = WsTransport::new(socket, key);
+            match server.serve(transport).await {
+                Ok(running) => {
+                    let _ = running.waiting().await;
+                }
+                Err(e) => tracing::warn!("WebSocket session failed to start: {e}"),
+            }
+            if let Some(id) = id {
+                sessions.detach(&id);
+            }
+        })
+        .into_response()
+}
+
+// ---------------------------------------------------------------------------
+// Transport
+// ---------------------------------------------------------------------------
+
+/// One JSON-RPC message per text frame.
+struct WsTransport {
+    sink: Arc<tokio::sync::Mutex<SplitSink<WebSocket, Message>>>,
+    stream: SplitStream<WebSocket>,
+    key: Option<ApiKey>,
+}
+
+impl WsTransport {
+    fn new(socket: WebSocket, key: Option<ApiKey>) -> Self {
+        let (sink, stream) = socket.split();
+        Self {
+            sink: Arc::new(tokio::sync::Mutex::new(sink)),
+            stream,
+            key,
+        }
+    }
+
+    /// Answer a message directly, without the server seeing it.
+    async fn reply(&self, message: serde_json::Value) {
+        let text = message.to_string();
+        let _ = self
+            .sink
+            .lock()
+            .await
+            .send(Message::Text(text.into()))
+            .await;
+    }
+}
+
+impl Transport<RoleServer> for WsTransport {
+    type Error = axum::Error;
+
+    fn send(
+        &mut self,
+        item: TxJsonRpcMessage<RoleServer>,
+    ) -> impl std::future::Future<Output = Result<(), Self::Error>> + Send + 'static {
+        let sink = Arc::clone(&self.sink);
+        async move {
+            let text = serde_json::to_string(&item).map_err(axum::Error::new)?;
+            sink.lock().await.send(Message::Text(text.into())).await
+        }
+    }
+
+    async fn receive(&mut self) -> Option<RxJsonRpcMessage<RoleServer>> {
+        loop {
+            let text = match self.stream.next().await? {
+                Ok(Message::Text(text)) => text,
+                Ok(Message::Close(_)) | Err(_) => return None,
+                // Pings are answered by axum; binary frames are not MCP.
+                Ok(_) => continue,
+            };
+            if let Some(denied) = denied_call(self.key.as_ref(), text.as_str()) {
+                self.reply(denied).await;
+                continue;
+            }
+            match serde_json::from_str(text.as_str()) {
+                Ok(message) => return Some(message),
+                Err(e) => {
+                    self.reply(serde_json::json!({
+                        "jsonrpc": "2.0",
+                        "id": null,
+                        "error": {"code": -32700, "message": format!("Parse error: {e}")},
+                    }))
+                    .await;
+                }
+            }
+        }
+    }
+
+    async fn close(&mut self) -> Result<(), Self::Error> {
+        self.sink.lock().await.close().await
+    }
+}
+
+/// The error answering a `tools/call` the key may not make.
+fn denied_call(key: Option<&ApiKey>, text: &str) -> Option<serde_json::Value> {
+    let key = key?;
+    let tool = tool_calls(text.as_bytes())
+        .into_iter()
+        .find(|t| !key.allows(t))?;
+    let id = serde_json::from_str::<serde_json::Value>(text)
+        .ok()
+        .map(|m| m["id"].clone())
+        .unwrap_or_default();
+    Some(serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": {
+            "code": -32600,
+            "message": format!("API key '{}' may not call {tool}", key.name),
+        },
+    }))
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::schema::initialize_database;
+    use crate::graph::store::GraphStore;
+
+    const ID: &str = "0123456789abcdef-session";
+
+    #[test]
+    fn dropped_sessions_can_be_resumed_by_their_key() {
+        let store = GraphStore::from_connection(initialize_database(":memory:").unwrap());
+        let sessions = WsSessions::new(CodeGraphServer::new(store));
+
+        assert_eq!(
+            sessions.attach(Some("short"), None).err(),
+            Some(AttachError::InvalidId)
+        );
+
+        let (fresh, _) = sessions.attach(None, Some("ci")).unwrap();
+        let (first, resumed) = sessions.attach(Some(ID), Some("ci")).unwrap();
+        assert!(!resumed);
+        assert_eq!(
+            sessions.attach(Some(ID), Some("ci")).err(),
+            Some(AttachError::InUse)
+        );
+
+        sessions.detach(ID);
+        assert_eq!(
+            sessions.attach(Some(ID), Some("other")).err(),
+            Some(AttachError::OtherKey)
+        );
+        let (second, resumed) = sessions.attach(Some(ID), Some("ci")).unwrap();
+        assert!(resumed);
+        assert!(first.same_session(&second));
+        assert!(!first.same_session(&fresh));
+    }
+}
@@ -8,7 +8,6 @@
 
 use std::path::{Path, PathBuf};
 
-use regex::Regex;
 use tracing_subscriber::EnvFilter;
 
 /// Initialize structured logging with `RUST_LOG` environment variable support.
@@ -57,46 +56,11 @@ pub fn validate_path(path: &str, project_root: &Path) -> Result<PathBuf, String>
 /// Redact potential secrets from text.
 ///
 /// Replaces patterns that look like API keys, tokens, passwords, AWS
-/// credentials, and Bearer tokens with `***REDACTED***`.
+/// credentials, and Bearer tokens with `***REDACTED***`. The patterns are
+/// [`crate::security::secrets::SECRET_PATTERNS`], which
+/// `codegraph_find_secrets` scans the code for.
 pub fn redact_secrets(text: &str) -> String {
-    let patterns: &[(&str, &str)] = &[
-        (
-            r#"(?i)(api[_-]?key|apikey)\s*[:=]\s*['"]?([a-zA-Z0-9_\-]{20,})['"]?"#,
-            "$1=***REDACTED***",
-        ),
-        (
-            r#"(?i)(password|passwd|pwd)\s*[:=]\s*['"]?([^\s'"]{8,})['"]?"#,
-            "$1=***REDACTED***",
-        ),
-        (
-            r#"(?i)(secret|token)\s*[:=]\s*['"]?([a-zA-Z0-9_\-]{20,})['"]?"#,
-            "$1=***REDACTED***",
-        ),
-        (
-            r#"(?i)(aws_access_key_id)\s*[:=]\s*['"]?(AKIA[0-9A-Z]{16})['"]?"#,
-            "$1=***REDACTED***",
-        ),
-        (
-            r#"(?i)(aws_secret_access_key)\s*[:=]\s*['"]?([a-zA-Z0-9/+]{40})['"]?"#,
-            "$1=***REDACTED***",
-        ),
-        (
-            r"(?i)Bearer\s+[a-zA-Z0-9_\-\.]{20,}",
-            "Bearer ***REDACTED***",
-        ),
-        (
-            r#"(?i)(connection_string|conn_str)\s*[:=]\s*['"]?([^\s'"]{20,})['"]?"#,
-            "$1=***REDACTED***",
-        ),
-    ];
-
-    let mut result = text.to_string();
-    for (pattern, replacement) in patterns {
-        if let Ok(re) = Regex::new(pattern) {
-            result = re.replace_all(&result, *replacement).to_string();
-        }
-    }
-    result
+    crate::security::secrets::redact(text)
 }
 
 /// Lightweight performance metrics collector.
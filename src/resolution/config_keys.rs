@@ -0,0 +1,645 @@
+//! Configuration key cross-referencing.
+//!
+//! Extracts keys from configuration files (YAML, TOML, JSON, `.env`) and
+//! finds code that reads those keys through common accessor calls such as
+//! `os.getenv("DB_HOST")`, `process.env.DB_HOST`, `env::var("DB_HOST")`,
+//! or `config.get("database.host")`. Matching is purely string-based: a
+//! usage links to a key when the accessor's literal argument equals the
+//! full dotted key or its final segment. Keys that code reads also enter the
+//! graph as config key nodes, linked from their readers by `Reads` edges.
+
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use ignore::WalkBuilder;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::indexer::parser::CodeParser;
+use crate::types::{make_node_id, CodeEdge, CodeNode, EdgeKind, NodeKind};
+
+// ---------------------------------------------------------------------------
+// Constants
+// ---------------------------------------------------------------------------
+
+/// Skip config files larger than 256 KB (fixtures, generated manifests).
+const MAX_CONFIG_FILE_SIZE: u64 = 256 * 1024;
+
+/// Config-shaped files that are machine-generated and never read by key.
+const IGNORED_CONFIG_FILES: &[&str] = &[
+    "package-lock.json",
+    "composer.lock",
+    "yarn.lock",
+    "pnpm-lock.yaml",
+    "Cargo.lock",
+    "poetry.lock",
+];
+
+/// Regexes capturing the literal key passed to a config accessor.
+///
+/// Each pattern has exactly one capture group holding the key.
+const ACCESSOR_PATTERNS: &[&str] = &[
+    // Environment variables
+    r#"(?:getenv|env::var|env::var_os|os\.environ\.get|System\.getenv|Environment\.GetEnvironmentVariable)\s*\(\s*["']([^"']+)["']"#,
+    r#"(?:os\.environ|ENV|\$_ENV|\$_SERVER)\s*\[\s*["']([^"']+)["']\s*\]"#,
+    r"process\.env\.([A-Za-z_][A-Za-z0-9_]*)",
+    r#"process\.env\[\s*["']([^"']+)["']\s*\]"#,
+    // Generic config objects
+    r#"(?i)(?:config|conf|cfg|settings|viper|properties|env)\s*(?:\.|::)\s*(?:get|get_?string|get_?int|get_?bool|get_?float|get_?property|get_?value|require|fetch|lookup)\w*\s*\(\s*["']([^"']+)["']"#,
+    r#"(?:getProperty|getString|getInt|getBoolean|GetValue|GetSection)\s*\(\s*["']([^"']+)["']"#,
+    r#"(?:config|settings|conf|cfg)\s*\[\s*["']([^"']+)["']\s*\]"#,
+    r#"@Value\s*\(\s*["']\$\{([^}:"']+)"#,
+];
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// Supported configuration file formats.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConfigFormat {
+    Yaml,
+    Toml,
+    Json,
+    Env,
+}
+
+impl ConfigFormat {
+    /// Detect the format from a file path, or `None` for non-config files.
+    pub fn from_path(path: &str) -> Option<Self> {
+        let file_name = path.rsplit(['/', '\\']).next().unwrap_or(path);
+        if IGNORED_CONFIG_FILES.contains(&file_name) {
+            return None;
+        }
+        if file_name == ".env" || file_name.starts_with(".env.") {
+            return Some(Self::Env);
+        }
+        let ext = file_name.rsplit_once('.').map(|(_, e)| e)?;
+        match ext.to_ascii_lowercase().as_str() {
+            "yaml" | "yml" => Some(Self::Yaml),
+            "toml" => Some(Self::Toml),
+            "json" => Some(Self::Json),
+            "env" => Some(Self::Env),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Yaml => "yaml",
+            Self::Toml => "toml",
+            Self::Json => "json",
+            Self::Env => "env",
+        }
+    }
+
+    pub fn from_str_loose(s: &str) -> Option<Self> {
+        match s {
+            "yaml" | "yml" => Some(Self::Yaml),
+            "toml" => Some(Self::Toml),
+            "json" => Some(Self::Json),
+            "env" | "dotenv" => Some(Self::Env),
+            _ => None,
+        }
+    }
+}
+
+/// A key defined in a configuration file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConfigKey {
+    /// Dotted key path (`database.host`) or the bare variable name for `.env`.
+    pub key: String,
+    pub file_path: String,
+    pub line: u32,
+    pub format: ConfigFormat,
+}
+
+/// A code location that reads a configuration key.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConfigKeyRef {
+    /// The literal key passed to the accessor.
+    pub key: String,
+    /// ID of the innermost node whose body contains the accessor call.
+    pub node_id: String,
+    pub file_path: String,
+    pub line: u32,
+}
+
+// ---------------------------------------------------------------------------
+// Key extraction
+// ---------------------------------------------------------------------------
+
+/// Extract all keys from a configuration file's contents.
+pub fn extract_config_keys(file_path: &str, content: &str) -> Vec<ConfigKey> {
+    let format = match ConfigFormat::from_path(file_path) {
+        Some(f) => f,
+        None => return Vec::new(),
+    };
+    let raw = match format {
+        ConfigFormat::Yaml => extract_yaml_keys(content),
+        ConfigFormat::Toml => extract_toml_keys(content),
+        ConfigFormat::Json => extract_json_keys(content),
+        ConfigFormat::Env => extract_env_keys(content),
+    };
+    raw.into_iter()
+        .map(|(key, line)| ConfigKey {
+            key,
+            file_path: file_path.to_string(),
+            line,
+            format,
+        })
+        .collect()
+}
+
+/// Indentation-based YAML key walker. Sequence items are skipped — only
+/// mapping keys produce dotted paths.
+fn extract_yaml_keys(content: &str) -> Vec<(String, u32)> {
+    let mut keys = Vec::new();
+    let mut stack: Vec<(usize, String)> = Vec::new();
+
+    for (idx, line) in content.lines().enumerate() {
+        let trimmed = line.trim_start();
+        if trimmed.is_empty()
+            || trimmed.starts_with('#')
+            || trimmed.starts_with("---")
+            || trimmed.starts_with('-')
+        {
+            continue;
+        }
+        let indent = line.len() - trimmed.len();
+        let Some((raw_key, _)) = trimmed.split_once(':') else {
+            continue;
+        };
+        let key = raw_key.trim().trim_matches(|c| c == '"' || c == '\'');
+        if key.is_empty() || key.contains(' ') {
+            continue;
+        }
+        while stack.last().is_some_and(|(i, _)| *i >= indent) {
+            stack.pop();
+        }
+        let full = stack
+            .iter()
+            .map(|(_, k)| k.as_str())
+            .chain(std::iter::once(key))
+            .collect::<Vec<_>>()
+            .join(".");
+        keys.push((full, idx as u32 + 1));
+        stack.push((indent, key.to_string()));
+    }
+    keys
+}
+
+/// Line-based TOML key walker supporting `[table]`, `[[array]]`, and
+/// dotted `a.b = 1` keys.
+fn extract_toml_keys(content: &str) -> Vec<(String, u32)> {
+    let mut keys = Vec::new();
+    let mut table = String::new();
+
+    for (idx, line) in content.lines().enumerate() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        if trimmed.starts_with('[') {
+            table = trimmed
+                .trim_start_matches('[')
+                .trim_end_matches(']')
+                .trim()
+                .to_string();
+            continue;
+        }
+        let Some((raw_key, _)) = trimmed.split_once('=') else {
+            continue;
+        };
+        let key = raw_key.trim().trim_matches('"');
+        if key.is_empty() || key.contains(' ') {
+            continue;
+        }
+        let full = if table.is_empty() {
+            key.to_string()
+        } else {
+            format!("{table}.{key}")
+        };
+        keys.push((full, idx as u32 + 1));
+    }
+    keys
+}
+
+/// JSON key walker. Keys are collected via `serde_json`, and each key's
+/// line is the first line declaring `"leaf":`.
+fn extract_json_keys(content: &str) -> Vec<(String, u32)> {
+    let value: serde_json::Value = match serde_json::from_str(content) {
+        Ok(v) => v,
+        Err(_) => return Vec::new(),
+    };
+    let mut paths = Vec::new();
+    collect_json_paths(&value, "", &mut paths);
+
+    let lines: Vec<&str> = content.lines().collect();
+    paths
+        .into_iter()
+        .map(|path| {
+            let leaf = path.rsplit('.').next().unwrap_or(&path);
+            let needle = format!("\"{leaf}\"");
+            let line = lines
+                .iter()
+                .position(|l| {
+                    l.find(&needle)
+                        .is_some_and(|pos| l[pos + needle.len()..].trim_start().starts_with(':'))
+                })
+                .map(|i| i as u32 + 1)
+                .unwrap_or(1);
+            (path, line)
+        })
+        .collect()
+}
+
+fn collect_json_paths(value: &serde_json::Value, prefix: &str, out: &mut Vec<String>) {
+    if let serde_json::Value::Object(map) = value {
+        for (k, v) in map {
+            let path = if prefix.is_empty() {
+                k.clone()
+            } else {
+                format!("{prefix}.{k}")
+            };
+            out.push(path.clone());
+            collect_json_paths(v, &path, out);
+        }
+    }
+}
+
+/// `.env` parser: `KEY=value`, optionally prefixed by `export`.
+fn extract_env_keys(content: &str) -> Vec<(String, u32)> {
+    content
+        .lines()
+        .enumerate()
+        .filter_map(|(idx, line)| {
+            let trimmed = line.trim();
+            if trimmed.starts_with('#') {
+                return None;
+            }
+            let trimmed = trimmed.strip_prefix("export ").unwrap_or(trimmed);
+            let (key, _) = trimmed.split_once('=')?;
+            let key = key.trim();
+            let valid = !key.is_empty()
+                && key
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.');
+            valid.then(|| (key.to_string(), idx as u32 + 1))
+        })
+        .collect()
+}
+
+// ---------------------------------------------------------------------------
+// File collection
+// ---------------------------------------------------------------------------
+
+/// Collect configuration files under `root`, respecting `.gitignore`.
+///
+/// Hidden directories are skipped, but hidden `.env*` files are kept.
+pub fn collect_config_files(root: &Path) -> Vec<PathBuf> {
+    let walker = WalkBuilder::new(root)
+        .standard_filters(true)
+        .hidden(false)
+        .filter_entry(|entry| {
+            let name = entry.file_name().to_str().unwrap_or("");
+            if entry.file_type().is_some_and(|ft| ft.is_dir()) {
+                return entry.depth() == 0
+                    || (!name.starts_with('.')
+                        && !crate::indexer::pipeline::ALWAYS_SKIP_DIRS.contains(&name));
+            }
+            !name.starts_with('.') || name.starts_with(".env")
+        })
+        .build();
+
+    walker
+        .flatten()
+        .filter(|entry| entry.file_type().is_some_and(|ft| ft.is_file()))
+        .filter(|entry| ConfigFormat::from_path(&entry.path().to_string_lossy()).is_some())
+        .filter(|entry| {
+            entry
+                .metadata()
+                .is_ok_and(|m| m.len() <= MAX_CONFIG_FILE_SIZE)
+        })
+        .map(|entry| entry.path().to_path_buf())
+        .collect()
+}
+
+/// Collect and parse every configuration file under `root`.
+///
+/// Returned keys carry paths relative to `root`.
+pub fn scan_config_keys(root: &Path) -> Vec<ConfigKey> {
+    collect_config_files(root)
+        .iter()
+        .flat_map(|path| read_config_keys(root, path))
+        .collect()
+}
+
+/// Parse the configuration file at `path`, with its path relative to
+/// `root`. Empty for files that are not config, too large or unreadable.
+pub fn read_config_keys(root: &Path, path: &Path) -> Vec<ConfigKey> {
+    let Ok(rel) = path.strip_prefix(root) else {
+        return Vec::new();
+    };
+    if std::fs::metadata(path).map_or(true, |m| m.len() > MAX_CONFIG_FILE_SIZE) {
+        return Vec::new();
+    }
+    match std::fs::read_to_string(path) {
+        Ok(content) => extract_config_keys(&rel.to_string_lossy(), &content),
+        Err(_) => Vec::new(),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Usage detection
+// ---------------------------------------------------------------------------
+
+/// Compiled accessor patterns; [`shared`](Self::shared) compiles them once
+/// per process.
+pub struct ConfigAccessorMatcher {
+    patterns: Vec<Regex>,
+}
+
+impl Default for ConfigAccessorMatcher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ConfigAccessorMatcher {
+    pub fn new() -> Self {
+        Self {
+            patterns: ACCESSOR_PATTERNS
+                .iter()
+                .filter_map(|p| Regex::new(p).ok())
+                .collect(),
+        }
+    }
+
+    /// The process-wide matcher.
+    pub fn shared() -> &'static Self {
+        static MATCHER: OnceLock<ConfigAccessorMatcher> = OnceLock::new();
+        MATCHER.get_or_init(Self::new)
+    }
+
+    /// Return `(key, line offset)` pairs for every accessor call in `text`.
+    pub fn find_keys(&self, text: &str) -> Vec<(String, u32)> {
+        let mut found = Vec::new();
+        for (offset, line) in text.lines().enumerate() {
+            for re in &self.patterns {
+                for cap in re.captures_iter(line) {
+                    if let Some(m) = cap.get(1) {
+                        found.push((m.as_str().to_string(), offset as u32));
+                    }
+                }
+            }
+        }
+        found.sort();
+        found.dedup();
+        found
+    }
+
+    /// Find config key reads inside the given nodes.
+    ///
+    /// When nodes nest (a method inside a class), each read is attributed
+    /// to the innermost node only.
+    pub fn find_refs(&self, nodes: &[CodeNode]) -> Vec<ConfigKeyRef> {
+        let mut best: std::collections::HashMap<(String, String, u32), (u32, &CodeNode)> =
+            std::collections::HashMap::new();
+        for node in nodes {
+            let Some(body) = node.body.as_deref() else {
+                continue;
+            };
+            let span = node.end_line.saturating_sub(node.start_line);
+            for (key, offset) in self.find_keys(body) {
+                let line = node.start_line + offset;
+                let slot = (key, node.file_path.clone(), line);
+                match best.get(&slot) {
+                    Some((existing_span, _)) if *existing_span <= span => {}
+                    _ => {
+                        best.insert(slot, (span, node));
+                    }
+                }
+            }
+        }
+        let mut refs: Vec<ConfigKeyRef> = best
+            .into_iter()
+            .map(|((key, file_path, line), (_, node))| ConfigKeyRef {
+                key,
+                node_id: node.id.clone(),
+                file_path,
+                line,
+            })
+            .collect();
+        refs.sort_by(|a, b| (&a.file_path, a.line, &a.key).cmp(&(&b.file_path, b.line, &b.key)));
+        refs
+    }
+}
+
+/// Whether an accessor key refers to the given config key.
+///
+/// Matches the full dotted path, or the final segment (so that
+/// `getenv("PORT")` matches `server.PORT`), case-sensitively.
+pub fn key_matches(config_key: &str, accessor_key: &str) -> bool {
+    config_key == accessor_key
+        || config_key
+            .rsplit('.')
+            .next()
+            .is_some_and(|leaf| leaf == accessor_key)
+        || accessor_key
+            .strip_prefix(config_key)
+            .is_some_and(|rest| rest.starts_with('.'))
+}
+
+// ---------------------------------------------------------------------------
+// Graph
+// ---------------------------------------------------------------------------
+
+/// Config key nodes, and the `Reads` edges from each accessor to the keys
+/// it matches (see [`key_matches`]).
+///
+/// Only keys some code reads become nodes: a config file has no language
+/// of its own, so a key node takes the language of its first reader.
+pub fn config_key_graph(
+    keys: &[ConfigKey],
+    refs: &[ConfigKeyRef],
+) -> (Vec<CodeNode>, Vec<CodeEdge>) {
+    let mut nodes = Vec::new();
+    let mut edges = Vec::new();
+    for key in keys {
+        let readers: Vec<&ConfigKeyRef> = refs
+            .iter()
+            .filter(|r| key_matches(&key.key, &r.key))
+            .collect();
+        let Some(language) = readers
+            .iter()
+            .find_map(|r| CodeParser::detect_language(&r.file_path))
+        else {
+            continue;
+        };
+        let id = make_node_id(NodeKind::ConfigKey, &key.file_path, &key.key, key.line);
+        edges.extend(readers.into_iter().map(|r| CodeEdge {
+            source: r.node_id.clone(),
+            target: id.clone(),
+            kind: EdgeKind::Reads,
+            file_path: r.file_path.clone(),
+            line: r.line,
+            metadata: None,
+        }));
+        nodes.push(CodeNode {
+            id,
+            name: key.key.clone(),
+            qualified_name: None,
+            kind: NodeKind::ConfigKey,
+            file_path: key.file_path.clone(),
+            start_line: key.line,
+            end_line: key.line,
+            start_column: 0,
+            end_column: 0,
+            language,
+            body: None,
+            documentation: None,
+            exported: None,
+        });
+    }
+    (nodes, edges)
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{Language, NodeKind};
+
+    fn make_node(id: &str, file: &str, start: u32, end: u32, body: &str) -> CodeNode {
+        CodeNode {
+            id: id.to_string(),
+            name: id.to_string(),
+            qualified_name: None,
+            kind: NodeKind::Function,
+            file_path: file.to_string(),
+            start_line: start,
+            end_line: end,
+            start_column: 0,
+            end_column: 0,
+            language: Language::Python,
+            body: Some(body.to_string()),
+            documentation: None,
+            exported: None,
+        }
+    }
+
+    #[test]
+    fn format_detection() {
+        assert_eq!(
+            ConfigFormat::from_path("config.yaml"),
+            Some(ConfigFormat::Yaml)
+        );
+        assert_eq!(ConfigFormat::from_path("a/b.yml"), Some(ConfigFormat::Yaml));
+        assert_eq!(
+            ConfigFormat::from_path("pyproject.toml"),
+            Some(ConfigFormat::Toml)
+        );
+        assert_eq!(
+            ConfigFormat::from_path("settings.json"),
+            Some(ConfigFormat::Json)
+        );
+        assert_eq!(ConfigFormat::from_path(".env"), Some(ConfigFormat::Env));
+        assert_eq!(
+            ConfigFormat::from_path("app/.env.local"),
+            Some(ConfigFormat::Env)
+        );
+        assert_eq!(ConfigFormat::from_path("package-lock.json"), None);
+        assert_eq!(ConfigFormat::from_path("main.rs"), None);
+    }
+
+    #[test]
+    fn yaml_keys_are_dotted() {
+        let yaml = "database:\n  host: localhost\n  port: 5432\n# comment\nfeatures:\n  - a\nlog_level: info\n";
+        let keys = extract_config_keys("config.yaml", yaml);
+        let names: Vec<(&str, u32)> = keys.iter().map(|k| (k.key.as_str(), k.line)).collect();
+        assert_eq!(
+            names,
+            vec![
+                ("database", 1),
+                ("database.host", 2),
+                ("database.port", 3),
+                ("features", 5),
+                ("log_level", 7),
+            ]
+        );
+    }
+
+    #[test]
+    fn toml_keys_include_table() {
+        let toml = "name = \"x\"\n[server]\nport = 8080\n[[workers]]\ncount = 2\n";
+        let keys = extract_config_keys("app.toml", toml);
+        let names: Vec<&str> = keys.iter().map(|k| k.key.as_str()).collect();
+        assert_eq!(names, vec!["name", "server.port", "workers.count"]);
+        assert_eq!(keys[1].line, 3);
+    }
+
+    #[test]
+    fn json_keys_with_lines() {
+        let json = "{\n  \"api\": {\n    \"timeout\": 30\n  }\n}\n";
+        let keys = extract_config_keys("settings.json", json);
+        let timeout = keys.iter().find(|k| k.key == "api.timeout").unwrap();
+        assert_eq!(timeout.line, 3);
+        assert_eq!(timeout.format, ConfigFormat::Json);
+    }
+
+    #[test]
+    fn env_keys_handle_export_and_comments() {
+        let env = "# secrets\nDB_HOST=localhost\nexport API_KEY=abc\n\nnot a key\n";
+        let keys = extract_config_keys(".env", env);
+        let names: Vec<&str> = keys.iter().map(|k| k.key.as_str()).collect();
+        assert_eq!(names, vec!["DB_HOST", "API_KEY"]);
+    }
+
+    #[test]
+    fn accessor_patterns_capture_keys() {
+        let matcher = ConfigAccessorMatcher::new();
+        let src = "host = os.getenv(\"DB_HOST\")\nport = process.env.PORT\nt = config.get('api.timeout')\nv = std::env::var(\"RUST_LOG\")\n";
+        let keys: Vec<String> = matcher.find_keys(src).into_iter().map(|(k, _)| k).collect();
+        assert!(keys.contains(&"DB_HOST".to_string()));
+        assert!(keys.contains(&"PORT".to_string()));
+        assert!(keys.contains(&"api.timeout".to_string()));
+        assert!(keys.contains(&"RUST_LOG".to_string()));
+    }
+
+    #[test]
+    fn refs_attributed_to_innermost_node() {
+        let matcher = ConfigAccessorMatcher::new();
+        let outer = make_node(
+            "outer",
+            "app.py",
+            1,
+            10,
+            "class A:\n  def f():\n    os.getenv(\"DB_HOST\")\n",
+        );
+        let inner = make_node(
+            "inner",
+            "app.py",
+            2,
+            3,
+            "def f():\n    os.getenv(\"DB_HOST\")\n",
+        );
+        let refs = matcher.find_refs(&[outer, inner]);
+        assert_eq!(refs.len(), 1);
+        assert_eq!(refs[0].node_id, "inner");
+        assert_eq!(refs[0].line, 3);
+    }
+
+    #[test]
+    fn key_matching_rules() {
+        assert!(key_matches("database.host", "database.host"));
+        assert!(key_matches("server.PORT", "PORT"));
+        assert!(key_matches("database", "database.host"));
+        assert!(!key_matches("database.host", "host.name"));
+        assert!(!key_matches("DB_HOST", "db_host"));
+    }
+}
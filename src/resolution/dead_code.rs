@@ -3,11 +3,38 @@
 //! A symbol is considered "dead" if no other node in the graph references,
 //! calls, imports, extends, or implements it. Exported symbols, entry points,
 //! and test functions are excluded by default.
+//!
+//! [`find_unreachable_code`] goes further and walks the whole graph from a
+//! set of [`DeadCodeRoots`] — mains, the public API, tests and handlers a
+//! framework registers — so a helper only used by other dead code is dead
+//! as well. Implementations reached through interfaces, base classes and
+//! dependency-injection registrations count as used.
+//!
+//! [`orphan_evidence`] adds history to a result — the edges the index has
+//! dropped and the commit that last removed references — so a symbol that
+//! was never used can be told apart from one orphaned last week.
+
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
 
 use rusqlite::Connection;
 use serde::{Deserialize, Serialize};
 
-use crate::types::NodeKind;
+use crate::git::ReferenceRemoval;
+use crate::graph::api_guard::in_directory;
+use crate::graph::entrypoints::{find_entrypoints, EntryKind};
+use crate::graph::store::GraphStore;
+use crate::resolution::dispatch::DispatchIndex;
+use crate::types::{CodeEdge, CodeNode, EdgeKind, NodeKind};
+
+/// Orphaned within this many days counts as recent.
+pub const RECENTLY_ORPHANED_DAYS: i64 = 90;
+
+/// Commits that changed a symbol's occurrence count searched for removals.
+const REMOVAL_SEARCH_DEPTH: usize = 50;
+
+/// Node and edge batch size when reading the graph.
+const BATCH_SIZE: usize = 500;
 
 // ---------------------------------------------------------------------------
 // Types
@@ -21,6 +48,106 @@ pub struct DeadCodeResult {
     pub kind: String,
     pub file_path: String,
     pub start_line: u32,
+    /// Dead symbols that still use this one (reachability mode only).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub referenced_by: Vec<String>,
+}
+
+/// The symbols [`find_unreachable_code`] starts from; everything they
+/// reach is live.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeadCodeRoots {
+    /// Functions named `main` and other program entry points.
+    pub mains: bool,
+    /// Exported symbols and package exports, the public API.
+    pub exports: bool,
+    /// Test functions and symbols in test files.
+    pub tests: bool,
+    /// Route handlers and CLI commands registered with a framework.
+    pub handlers: bool,
+    /// Extra roots by name or qualified name; a trailing `*` matches a
+    /// prefix.
+    pub symbols: Vec<String>,
+    /// Directories whose symbols are all roots, e.g. scripts or migrations.
+    pub paths: Vec<String>,
+}
+
+impl Default for DeadCodeRoots {
+    fn default() -> Self {
+        Self {
+            mains: true,
+            exports: true,
+            tests: true,
+            handlers: true,
+            symbols: Vec::new(),
+            paths: Vec::new(),
+        }
+    }
+}
+
+impl DeadCodeRoots {
+    fn is_root(&self, node: &CodeNode, entry_points: &HashSet<String>) -> bool {
+        (self.mains && node.name == "main")
+            || (self.exports && node.exported == Some(true))
+            || (self.tests && is_test_symbol(node))
+            || (self.handlers && node.kind == NodeKind::Route)
+            || entry_points.contains(&node.id)
+            || self.matches_symbol(node)
+            || self
+                .paths
+                .iter()
+                .any(|dir| in_directory(&node.file_path, dir))
+    }
+
+    fn matches_symbol(&self, node: &CodeNode) -> bool {
+        let qualified = node.qualified_name.as_deref();
+        self.symbols
+            .iter()
+            .any(|pattern| match pattern.strip_suffix('*') {
+                Some(prefix) => {
+                    node.name.starts_with(prefix)
+                        || qualified.is_some_and(|q| q.starts_with(prefix))
+                }
+                None => node.name == *pattern || qualified == Some(pattern.as_str()),
+            })
+    }
+}
+
+/// Whether a symbol is a test or lives in a test file; the same check as
+/// the `test` patterns of [`find_dead_code`].
+fn is_test_symbol(node: &CodeNode) -> bool {
+    let path = node.file_path.to_ascii_lowercase();
+    node.name.to_ascii_lowercase().starts_with("test")
+        || path.contains("test")
+        || path.contains("spec")
+}
+
+/// Whether a dead symbol was ever used.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OrphanStatus {
+    /// No removed reference on record.
+    NeverUsed,
+    /// Lost its last reference within [`RECENTLY_ORPHANED_DAYS`].
+    RecentlyOrphaned,
+    /// Lost its last reference longer ago.
+    Orphaned,
+}
+
+/// History behind a dead-code result.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrphanEvidence {
+    pub status: OrphanStatus,
+    /// Symbol whose reference the index dropped most recently.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_caller: Option<String>,
+    /// When the index last saw that reference (Unix seconds).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_caller_seen_at: Option<i64>,
+    /// The newest commit that removed references to the symbol.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub removing_commit: Option<ReferenceRemoval>,
 }
 
 // ---------------------------------------------------------------------------
@@ -33,7 +160,7 @@ pub struct DeadCodeResult {
 /// 3. Test functions (name starts with 'test' or file in test paths)
 /// 4. Module nodes (modules are structural, not callable)
 ///
-/// Kind filtering is applied in Rust after the query for simplicity,
+/// Kind filtering is applied in Rust as rows stream off the cursor,
 /// since rusqlite doesn't support rarray out of the box.
 const DEAD_CODE_ALL_SQL: &str = "\
 SELECT n.id, n.name, n.type, n.file_path, n.start_line
@@ -47,6 +174,7 @@ WHERE e.id IS NULL
   AND n.file_path NOT LIKE '%spec%'
   AND n.file_path NOT LIKE '%__tests__%'
   AND n.type != 'module'
+  AND n.type != 'route'
 ORDER BY n.file_path ASC, n.start_line ASC";
 
 // ---------------------------------------------------------------------------
@@ -65,24 +193,16 @@ ORDER BY n.file_path ASC, n.start_line ASC";
 /// - Symbols in test directories
 /// - Module nodes
 pub fn find_dead_code(conn: &Connection, kinds: &[NodeKind]) -> Vec<DeadCodeResult> {
-    let all_results = match find_dead_code_inner(conn) {
-        Ok(results) => results,
-        Err(_) => return Vec::new(),
-    };
-
-    if kinds.is_empty() {
-        return all_results;
-    }
-
-    // Filter by requested kinds
     let kind_strs: Vec<&str> = kinds.iter().map(|k| k.as_str()).collect();
-    all_results
-        .into_iter()
-        .filter(|r| kind_strs.contains(&r.kind.as_str()))
-        .collect()
+    find_dead_code_inner(conn, &kind_strs).unwrap_or_default()
 }
 
-fn find_dead_code_inner(conn: &Connection) -> crate::error::Result<Vec<DeadCodeResult>> {
+/// Rows are filtered as they stream off the cursor, so only matching
+/// results are ever held in memory.
+fn find_dead_code_inner(
+    conn: &Connection,
+    kinds: &[&str],
+) -> crate::error::Result<Vec<DeadCodeResult>> {
     let mut stmt = conn.prepare_cached(DEAD_CODE_ALL_SQL)?;
     let rows = stmt.query_map([], |row| {
         Ok(DeadCodeResult {
@@ -91,16 +211,225 @@ fn find_dead_code_inner(conn: &Connection) -> crate::error::Result<Vec<DeadCodeR
             kind: row.get(2)?,
             file_path: row.get(3)?,
             start_line: row.get(4)?,
+            referenced_by: Vec::new(),
         })
     })?;
 
-    let mut results = Vec::new();
-    for r in rows.flatten() {
-        results.push(r);
+    Ok(rows
+        .flatten()
+        .filter(|r| kinds.is_empty() || kinds.contains(&r.kind.as_str()))
+        .collect())
+}
+
+/// Find symbols that no root reaches (potentially dead code), including
+/// helpers whose only users are dead themselves.
+///
+/// Reachability follows every edge except imports — importing a symbol
+/// does not use it — and the [`DispatchIndex`] links from interface and
+/// base-class methods to their implementations and from dependency-injection
+/// registrations to the classes they register. Top-level code and callers
+/// missing from the index are roots as well, and a live member keeps its
+/// containers alive. Members of
+/// a dead container are folded into it, and module nodes and tests are
+/// never reported. `project_root` lets the entry-point scan read sources
+/// for framework registrations.
+pub fn find_unreachable_code(
+    store: &GraphStore,
+    kinds: &[NodeKind],
+    roots: &DeadCodeRoots,
+    project_root: Option<&Path>,
+) -> crate::error::Result<Vec<DeadCodeResult>> {
+    // Bodies and docs are never read here; dropping them while streaming
+    // keeps the node list small.
+    let mut nodes: Vec<CodeNode> = Vec::new();
+    for batch in store.iter_nodes(BATCH_SIZE) {
+        nodes.extend(batch?.into_iter().map(|mut n| {
+            n.body = None;
+            n.documentation = None;
+            n
+        }));
+    }
+    let indexed: HashSet<&str> = nodes.iter().map(|n| n.id.as_str()).collect();
+    let names: HashMap<&str, &str> = nodes
+        .iter()
+        .map(|n| (n.id.as_str(), n.name.as_str()))
+        .collect();
+
+    let mut entry_points = HashSet::new();
+    if roots.mains || roots.exports || roots.handlers {
+        for entry in find_entrypoints(store, project_root.map(Path::to_path_buf))? {
+            let wanted = match entry.kind {
+                EntryKind::Main => roots.mains,
+                EntryKind::Route | EntryKind::Command => roots.handlers,
+                EntryKind::PackageExport => roots.exports,
+            };
+            if wanted {
+                entry_points.insert(entry.id);
+            }
+        }
+    }
+
+    // Of the edges only the adjacency is kept, plus the membership and
+    // supertype edges dispatch is resolved over.
+    let mut uses: HashMap<String, Vec<String>> = HashMap::new();
+    let mut users: HashMap<String, Vec<&str>> = HashMap::new();
+    let mut containers: HashMap<String, String> = HashMap::new();
+    let mut dispatch_edges: Vec<CodeEdge> = Vec::new();
+    let mut stack: Vec<String> = Vec::new();
+    for batch in store.iter_edges(BATCH_SIZE) {
+        for edge in batch? {
+            if edge.kind == EdgeKind::Imports {
+                continue;
+            }
+            if edge.kind == EdgeKind::Contains {
+                containers.insert(edge.target.clone(), edge.source.clone());
+            } else if let Some(name) = names.get(edge.source.as_str()) {
+                users.entry(edge.target.clone()).or_default().push(name);
+            }
+            // `file:` sources are top-level code, which runs when its file loads.
+            if !indexed.contains(edge.source.as_str()) {
+                stack.push(edge.source.clone());
+            }
+            uses.entry(edge.source.clone())
+                .or_default()
+                .push(edge.target.clone());
+            if matches!(
+                edge.kind,
+                EdgeKind::Contains | EdgeKind::Implements | EdgeKind::Extends
+            ) {
+                dispatch_edges.push(edge);
+            }
+        }
+    }
+
+    // A call through an interface or base class may run any implementation,
+    // and a container constructs the classes registered with it.
+    let dispatch = DispatchIndex::from_graph(&nodes, &dispatch_edges, project_root);
+    drop(dispatch_edges);
+    for link in dispatch.links() {
+        uses.entry(link.declaration.clone())
+            .or_default()
+            .push(link.implementation.clone());
+    }
+    for registration in dispatch.registrations() {
+        uses.entry(registration.site.clone())
+            .or_default()
+            .push(registration.class.clone());
+        if !indexed.contains(registration.site.as_str()) {
+            stack.push(registration.site.clone());
+        }
+    }
+    stack.extend(
+        nodes
+            .iter()
+            .filter(|n| roots.is_root(n, &entry_points))
+            .map(|n| n.id.clone()),
+    );
+
+    let mut live: HashSet<String> = HashSet::new();
+    while let Some(id) = stack.pop() {
+        if !live.contains(&id) {
+            stack.extend(uses.get(&id).into_iter().flatten().cloned());
+            live.insert(id);
+        }
+    }
+    for member in live.clone() {
+        let mut id = member;
+        while let Some(container) = containers.get(&id) {
+            if !live.insert(container.clone()) {
+                break;
+            }
+            id = container.clone();
+        }
     }
+
+    let reported = |n: &CodeNode| {
+        !live.contains(n.id.as_str())
+            && !matches!(
+                n.kind,
+                NodeKind::Module | NodeKind::Route | NodeKind::ConfigKey
+            )
+            && !is_test_symbol(n)
+    };
+    let reported_ids: HashSet<&str> = nodes
+        .iter()
+        .filter(|n| reported(n))
+        .map(|n| n.id.as_str())
+        .collect();
+    let mut results: Vec<DeadCodeResult> = nodes
+        .iter()
+        .filter(|n| reported_ids.contains(n.id.as_str()))
+        .filter(|n| {
+            containers
+                .get(n.id.as_str())
+                .is_none_or(|c| !reported_ids.contains(c.as_str()))
+        })
+        .filter(|n| kinds.is_empty() || kinds.contains(&n.kind))
+        .map(|n| {
+            let mut referenced_by: Vec<String> = users
+                .get(n.id.as_str())
+                .into_iter()
+                .flatten()
+                .map(|name| name.to_string())
+                .collect();
+            referenced_by.sort();
+            referenced_by.dedup();
+            DeadCodeResult {
+                id: n.id.clone(),
+                name: n.name.clone(),
+                kind: n.kind.as_str().to_string(),
+                file_path: n.file_path.clone(),
+                start_line: n.start_line,
+                referenced_by,
+            }
+        })
+        .collect();
+    results.sort_by(|a, b| {
+        (a.file_path.as_str(), a.start_line).cmp(&(b.file_path.as_str(), b.start_line))
+    });
     Ok(results)
 }
 
+/// Gather evidence on when `result` lost its references: edge tombstones
+/// from the index and, when `repo_path` is given, the git commit that last
+/// removed occurrences of its name. `now` is in Unix seconds.
+pub fn orphan_evidence(
+    store: &GraphStore,
+    repo_path: Option<&Path>,
+    result: &DeadCodeResult,
+    now: i64,
+) -> OrphanEvidence {
+    let last_edge = store
+        .get_edge_tombstones_to(&result.id)
+        .unwrap_or_default()
+        .into_iter()
+        .next();
+    let removing_commit = repo_path.and_then(|repo| {
+        crate::git::last_reference_removal(repo, &result.name, REMOVAL_SEARCH_DEPTH)
+            .ok()
+            .flatten()
+    });
+
+    let orphaned_at = last_edge
+        .as_ref()
+        .map(|e| e.deleted_at)
+        .into_iter()
+        .chain(removing_commit.as_ref().map(|c| c.timestamp))
+        .max();
+    let status = match orphaned_at {
+        None => OrphanStatus::NeverUsed,
+        Some(at) if now - at <= RECENTLY_ORPHANED_DAYS * 86_400 => OrphanStatus::RecentlyOrphaned,
+        Some(_) => OrphanStatus::Orphaned,
+    };
+
+    OrphanEvidence {
+        status,
+        last_caller: last_edge.as_ref().map(|e| e.source_id.clone()),
+        last_caller_seen_at: last_edge.map(|e| e.deleted_at),
+        removing_commit,
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Tests
 // ---------------------------------------------------------------------------
@@ -391,6 +720,7 @@ mod tests {
             kind: "function".to_string(),
             file_path: "src/a.ts".to_string(),
             start_line: 1,
+            referenced_by: Vec::new(),
         };
 
         let json = serde_json::to_string(&result).unwrap();
@@ -870,4 +1200,233 @@ mod tests {
             "functions in test/spec directories should be excluded"
         );
     }
+
+    #[test]
+    fn orphan_evidence_tells_never_used_from_orphaned() {
+        let store = setup();
+        let caller = make_node("n1", "caller", "src/a.ts", NodeKind::Function, 1, None);
+        let target = make_node("n2", "target", "src/b.ts", NodeKind::Function, 1, None);
+        let lonely = make_node("n3", "lonely", "src/b.ts", NodeKind::Function, 9, None);
+        store
+            .replace_file_data("src/b.ts", &[target.clone(), lonely.clone()], &[])
+            .unwrap();
+        store
+            .replace_file_data(
+                "src/a.ts",
+                &[caller.clone()],
+                &[make_edge("n1", "n2", EdgeKind::Calls, "src/a.ts", 3)],
+            )
+            .unwrap();
+        // The call goes away on the next index of a.ts.
+        store.replace_file_data("src/a.ts", &[caller], &[]).unwrap();
+
+        let dead = find_dead_code(&store.conn, &[]);
+        let result = |name: &str| dead.iter().find(|d| d.name == name).unwrap();
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs() as i64;
+
+        let orphaned = orphan_evidence(&store, None, result("target"), now);
+        assert_eq!(orphaned.status, OrphanStatus::RecentlyOrphaned);
+        assert_eq!(orphaned.last_caller.as_deref(), Some("n1"));
+
+        let later = now + (RECENTLY_ORPHANED_DAYS + 1) * 86_400;
+        let old = orphan_evidence(&store, None, result("target"), later);
+        assert_eq!(old.status, OrphanStatus::Orphaned);
+
+        let never = orphan_evidence(&store, None, result("lonely"), now);
+        assert_eq!(never.status, OrphanStatus::NeverUsed);
+        assert!(never.last_caller.is_none());
+    }
+
+    #[test]
+    fn unreachable_code_includes_transitively_unused_helpers() {
+        let store = setup();
+        store
+            .upsert_nodes(&[
+                make_node("main", "main", "src/main.ts", NodeKind::Function, 1, None),
+                make_node(
+                    "live",
+                    "liveHelper",
+                    "src/a.ts",
+                    NodeKind::Function,
+                    1,
+                    None,
+                ),
+                make_node("dead", "oldFlow", "src/b.ts", NodeKind::Function, 1, None),
+                make_node(
+                    "helper",
+                    "oldHelper",
+                    "src/b.ts",
+                    NodeKind::Function,
+                    10,
+                    None,
+                ),
+                make_node("cls", "Legacy", "src/c.ts", NodeKind::Class, 1, None),
+                make_node("m", "run", "src/c.ts", NodeKind::Method, 2, None),
+                make_node(
+                    "api",
+                    "publicApi",
+                    "src/d.ts",
+                    NodeKind::Function,
+                    1,
+                    Some(true),
+                ),
+                make_node("boot", "boot", "src/e.ts", NodeKind::Function, 1, None),
+            ])
+            .unwrap();
+        store
+            .upsert_edges(&[
+                make_edge("main", "live", EdgeKind::Calls, "src/main.ts", 2),
+                make_edge("dead", "helper", EdgeKind::Calls, "src/b.ts", 2),
+                make_edge("cls", "m", EdgeKind::Contains, "src/c.ts", 2),
+                make_edge("file:src/e.ts", "boot", EdgeKind::Calls, "src/e.ts", 9),
+                make_edge("file:src/f.ts", "dead", EdgeKind::Imports, "src/f.ts", 1),
+            ])
+            .unwrap();
+
+        let dead = find_unreachable_code(&store, &[], &DeadCodeRoots::default(), None).unwrap();
+        let names: Vec<&str> = dead.iter().map(|d| d.name.as_str()).collect();
+
+        // The helper is referenced, but only by dead code; the method is
+        // folded into its dead class; the import alone keeps nothing alive.
+        assert_eq!(names, vec!["oldFlow", "oldHelper", "Legacy"]);
+        assert_eq!(dead[1].referenced_by, vec!["oldFlow"]);
+        assert!(dead[0].referenced_by.is_empty());
+    }
+
+    #[test]
+    fn unreachable_code_roots_are_configurable() {
+        let store = setup();
+        store
+            .upsert_nodes(&[
+                make_node(
+                    "api",
+                    "publicApi",
+                    "src/a.ts",
+                    NodeKind::Function,
+                    1,
+                    Some(true),
+                ),
+                make_node("util", "format", "src/a.ts", NodeKind::Function, 10, None),
+                make_node(
+                    "t",
+                    "testFormat",
+                    "src/a.test.ts",
+                    NodeKind::Function,
+                    1,
+                    None,
+                ),
+                make_node(
+                    "fixture",
+                    "fixtureData",
+                    "src/b.ts",
+                    NodeKind::Function,
+                    1,
+                    None,
+                ),
+                make_node(
+                    "plugin",
+                    "registerPlugin",
+                    "src/c.ts",
+                    NodeKind::Function,
+                    1,
+                    None,
+                ),
+                make_node(
+                    "script",
+                    "seed",
+                    "scripts/seed.ts",
+                    NodeKind::Function,
+                    1,
+                    None,
+                ),
+            ])
+            .unwrap();
+        store
+            .upsert_edges(&[
+                make_edge("api", "util", EdgeKind::Calls, "src/a.ts", 2),
+                make_edge("t", "fixture", EdgeKind::Calls, "src/a.test.ts", 2),
+            ])
+            .unwrap();
+        let names = |roots: &DeadCodeRoots| -> Vec<String> {
+            find_unreachable_code(&store, &[], roots, None)
+                .unwrap()
+                .into_iter()
+                .map(|d| d.name)
+                .collect()
+        };
+
+        assert_eq!(
+            names(&DeadCodeRoots::default()),
+            vec!["seed", "registerPlugin"]
+        );
+
+        let roots = DeadCodeRoots {
+            exports: false,
+            tests: false,
+            symbols: vec!["register*".to_string()],
+            paths: vec!["scripts".to_string()],
+            ..Default::default()
+        };
+        // Tests are never reported, but no longer keep their fixtures alive.
+        assert_eq!(names(&roots), vec!["publicApi", "format", "fixtureData"]);
+    }
+
+    #[test]
+    fn implementations_called_through_an_interface_are_reachable() {
+        let store = setup();
+        store
+            .upsert_nodes(&[
+                make_node("main", "main", "src/main.ts", NodeKind::Function, 1, None),
+                make_node("i", "Store", "src/store.ts", NodeKind::Interface, 1, None),
+                make_node("i.save", "save", "src/store.ts", NodeKind::Method, 2, None),
+                make_node("c", "DiskStore", "src/disk.ts", NodeKind::Class, 1, None),
+                make_node("c.save", "save", "src/disk.ts", NodeKind::Method, 2, None),
+                make_node("c.flush", "flush", "src/disk.ts", NodeKind::Method, 4, None),
+            ])
+            .unwrap();
+        store
+            .upsert_edges(&[
+                make_edge("main", "i.save", EdgeKind::Calls, "src/main.ts", 2),
+                make_edge("i", "i.save", EdgeKind::Contains, "src/store.ts", 2),
+                make_edge("c", "c.save", EdgeKind::Contains, "src/disk.ts", 2),
+                make_edge("c", "c.flush", EdgeKind::Contains, "src/disk.ts", 4),
+                make_edge("c", "i", EdgeKind::Implements, "src/disk.ts", 1),
+            ])
+            .unwrap();
+
+        let dead = find_unreachable_code(&store, &[], &DeadCodeRoots::default(), None).unwrap();
+        let names: Vec<&str> = dead.iter().map(|d| d.name.as_str()).collect();
+
+        // save runs through Store; the class is kept alive by its method.
+        assert_eq!(names, vec!["flush"]);
+    }
+
+    #[test]
+    fn route_handlers_are_reachable_unless_handlers_are_off() {
+        let store = setup();
+        store
+            .upsert_nodes(&[
+                make_node("r", "GET /users", "src/app.js", NodeKind::Route, 3, None),
+                make_node("h", "listUsers", "src/app.js", NodeKind::Function, 5, None),
+            ])
+            .unwrap();
+        store
+            .upsert_edges(&[make_edge("r", "h", EdgeKind::Handles, "src/app.js", 3)])
+            .unwrap();
+
+        let dead = find_unreachable_code(&store, &[], &DeadCodeRoots::default(), None).unwrap();
+        assert!(dead.is_empty());
+
+        let roots = DeadCodeRoots {
+            handlers: false,
+            ..DeadCodeRoots::default()
+        };
+        let dead = find_unreachable_code(&store, &[], &roots, None).unwrap();
+        let names: Vec<&str> = dead.iter().map(|d| d.name.as_str()).collect();
+        // The route itself is never reported.
+        assert_eq!(names, vec!["listUsers"]);
+    }
 }
@@ -0,0 +1,1072 @@
+//! Dependency vulnerability audit from lockfiles.
+//!
+//! Reads the packages pinned by `Cargo.lock`, `package-lock.json` and
+//! `poetry.lock` files under the project, asks the [OSV](https://osv.dev)
+//! database which of those versions have known vulnerabilities, and maps
+//! each vulnerable package to the indexed files that import it.
+//!
+//! OSV answers are cached in `.codegraph/osv-cache.json`. Answers younger
+//! than the maximum cache age are reused as-is. Offline, or when the API
+//! cannot be reached, every cached answer is used whatever its age and
+//! packages without one are counted as unchecked.
+//!
+//! Imports are matched on package names: the first segment of a JS/TS
+//! specifier (two for `@scope/` packages), the crate of a Rust `use` or
+//! `extern crate` line, and the top-level module of a Python import.
+//! Python distributions whose module is named differently (`PyYAML` is
+//! imported as `yaml`) are not matched.
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::types::{CodeEdge, EdgeKind};
+
+// ---------------------------------------------------------------------------
+// Constants
+// ---------------------------------------------------------------------------
+
+/// Base URL of the OSV API.
+pub const OSV_API_URL: &str = "https://api.osv.dev/v1";
+
+/// Cache of OSV answers, relative to the project root.
+pub const CACHE_FILE: &str = ".codegraph/osv-cache.json";
+
+/// How long a cached OSV answer is reused before it is asked again.
+pub const DEFAULT_MAX_CACHE_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+
+const CACHE_VERSION: u32 = 1;
+
+/// Most packages OSV accepts in one batch query.
+const QUERY_BATCH_SIZE: usize = 1000;
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Directories never searched for lockfiles: installed packages and build
+/// output carry lockfiles of their own.
+const SKIPPED_DIRS: &[&str] = &["node_modules", "target", ".git", ".venv", "venv"];
+
+const JS_EXTENSIONS: &[&str] = &[
+    "js", "jsx", "mjs", "cjs", "ts", "tsx", "mts", "cts", "vue", "svelte",
+];
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// A package registry, named as OSV names it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Ecosystem {
+    #[serde(rename = "crates.io")]
+    CratesIo,
+    #[serde(rename = "npm")]
+    Npm,
+    #[serde(rename = "PyPI")]
+    PyPi,
+}
+
+impl Ecosystem {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::CratesIo => "crates.io",
+            Self::Npm => "npm",
+            Self::PyPi => "PyPI",
+        }
+    }
+
+    /// The ecosystem a lockfile pins packages of, by file name.
+    pub fn of_lockfile(file_name: &str) -> Option<Self> {
+        match file_name {
+            "Cargo.lock" => Some(Self::CratesIo),
+            "package-lock.json" => Some(Self::Npm),
+            "poetry.lock" => Some(Self::PyPi),
+            _ => None,
+        }
+    }
+
+    /// A package name as compared with import roots: crate and Python
+    /// names are case- and separator-insensitive.
+    fn normalize(&self, name: &str) -> String {
+        match self {
+            Self::Npm => name.to_string(),
+            Self::CratesIo | Self::PyPi => name.to_ascii_lowercase().replace(['-', '.'], "_"),
+        }
+    }
+}
+
+/// A package version pinned by a lockfile.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LockedPackage {
+    pub ecosystem: Ecosystem,
+    pub name: String,
+    pub version: String,
+    /// Project-relative lockfile path.
+    pub lockfile: String,
+}
+
+impl LockedPackage {
+    fn cache_key(&self) -> String {
+        format!("{}:{}@{}", self.ecosystem.as_str(), self.name, self.version)
+    }
+}
+
+/// The lockfiles found under a project and the packages they pin.
+#[derive(Debug, Clone, Default)]
+pub struct LockfileScan {
+    /// Project-relative paths.
+    pub lockfiles: Vec<String>,
+    pub packages: Vec<LockedPackage>,
+    /// Lockfiles that could not be read or parsed.
+    pub errors: Vec<String>,
+}
+
+/// A package an OSV vulnerability affects, with the versions fixing it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AffectedPackage {
+    pub ecosystem: String,
+    pub name: String,
+    pub fixed: Vec<String>,
+}
+
+/// The parts of an OSV vulnerability record the audit reports.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Vulnerability {
+    pub id: String,
+    pub summary: Option<String>,
+    pub aliases: Vec<String>,
+    /// Database severity (`HIGH`) or, failing that, a CVSS vector.
+    pub severity: Option<String>,
+    pub affected: Vec<AffectedPackage>,
+}
+
+impl Vulnerability {
+    /// Parse an OSV record (`GET /v1/vulns/{id}`).
+    pub fn from_osv(record: &Value) -> Option<Self> {
+        let strings = |v: &Value| -> Vec<String> {
+            v.as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|s| s.as_str().map(str::to_string))
+                .collect()
+        };
+        let severity = record["database_specific"]["severity"]
+            .as_str()
+            .or_else(|| record["severity"][0]["score"].as_str())
+            .map(str::to_string);
+        let affected = record["affected"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .map(|a| AffectedPackage {
+                ecosystem: a["package"]["ecosystem"].as_str().unwrap_or("").to_string(),
+                name: a["package"]["name"].as_str().unwrap_or("").to_string(),
+                fixed: a["ranges"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .flat_map(|r| r["events"].as_array().into_iter().flatten())
+                    .filter_map(|e| e["fixed"].as_str().map(str::to_string))
+                    .collect(),
+            })
+            .collect();
+        Some(Self {
+            id: record["id"].as_str()?.to_string(),
+            summary: record["summary"].as_str().map(str::to_string),
+            aliases: strings(&record["aliases"]),
+            severity,
+            affected,
+        })
+    }
+
+    /// Versions fixing the vulnerability in `package`.
+    pub fn fixed_in(&self, package: &LockedPackage) -> Vec<String> {
+        let mut fixed: Vec<String> = self
+            .affected
+            .iter()
+            .filter(|a| a.ecosystem == package.ecosystem.as_str() && a.name == package.name)
+            .flat_map(|a| a.fixed.iter().cloned())
+            .collect();
+        fixed.sort();
+        fixed.dedup();
+        fixed
+    }
+}
+
+/// An OSV answer for one package version.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CachedQuery {
+    /// Unix seconds.
+    pub fetched_at: i64,
+    pub ids: Vec<String>,
+}
+
+/// OSV answers, as stored in [`CACHE_FILE`].
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct OsvCache {
+    pub version: u32,
+    /// Vulnerability IDs per `ecosystem:name@version`.
+    pub queries: BTreeMap<String, CachedQuery>,
+    pub vulns: BTreeMap<String, Vulnerability>,
+}
+
+impl OsvCache {
+    /// The project's cache; empty when missing or unreadable.
+    pub fn load(project_root: &Path) -> Self {
+        std::fs::read_to_string(project_root.join(CACHE_FILE))
+            .ok()
+            .and_then(|text| serde_json::from_str::<Self>(&text).ok())
+            .filter(|cache| cache.version == CACHE_VERSION)
+            .unwrap_or_default()
+    }
+
+    /// Write the cache to the project's [`CACHE_FILE`].
+    pub fn save(&self, project_root: &Path) -> Result<(), String> {
+        let path = project_root.join(CACHE_FILE);
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .map_err(|e| format!("Failed to create {}: {e}", dir.display()))?;
+        }
+        let cache = Self {
+            version: CACHE_VERSION,
+            ..self.clone()
+        };
+        let text = serde_json::to_string(&cache).map_err(|e| e.to_string())?;
+        std::fs::write(&path, text).map_err(|e| format!("Failed to write {}: {e}", path.display()))
+    }
+}
+
+/// A vulnerability of one package version.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PackageVulnerability {
+    pub id: String,
+    pub summary: Option<String>,
+    pub aliases: Vec<String>,
+    pub severity: Option<String>,
+    pub fixed_in: Vec<String>,
+}
+
+/// A locked package with known vulnerabilities.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct VulnerablePackage {
+    pub package: LockedPackage,
+    pub vulnerabilities: Vec<PackageVulnerability>,
+}
+
+/// Outcome of [`audit`].
+#[derive(Debug, Clone, Default)]
+pub struct AuditReport {
+    pub vulnerable: Vec<VulnerablePackage>,
+    /// Packages with no OSV answer, fresh or cached.
+    pub unchecked: usize,
+    /// Packages answered from the cache without asking OSV.
+    pub from_cache: usize,
+    pub errors: Vec<String>,
+}
+
+/// An indexed file importing a package.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Importer {
+    pub file: String,
+    pub line: u32,
+    /// Names imported from the package, when the import lists them.
+    pub names: Vec<String>,
+}
+
+// ---------------------------------------------------------------------------
+// Lockfiles
+// ---------------------------------------------------------------------------
+
+/// Strip the quotes of a TOML string value.
+fn toml_string(value: &str) -> String {
+    value.trim().trim_matches('"').to_string()
+}
+
+/// `[[package]]` tables of a TOML lockfile as (name, version, source),
+/// where `source` is the `source` key or, for a `[package.source]`
+/// sub-table, its `type`.
+fn toml_packages(text: &str) -> Vec<(String, String, Option<String>)> {
+    let mut packages = Vec::new();
+    let mut current: Option<(Option<String>, Option<String>, Option<String>)> = None;
+    let mut section = "";
+    for line in text.lines().map(str::trim) {
+        if line.starts_with('[') {
+            if line == "[[package]]" {
+                packages.extend(current.take());
+                current = Some((None, None, None));
+            }
+            section = line;
+            continue;
+        }
+        let (Some(entry), Some((key, value))) = (current.as_mut(), line.split_once('=')) else {
+            continue;
+        };
+        match (section, key.trim()) {
+            ("[[package]]", "name") => entry.0 = Some(toml_string(value)),
+            ("[[package]]", "version") => entry.1 = Some(toml_string(value)),
+            ("[[package]]", "source") | ("[package.source]", "type") => {
+                entry.2 = Some(toml_string(value))
+            }
+            _ => {}
+        }
+    }
+    packages.extend(current);
+    packages
+        .into_iter()
+        .filter_map(|(name, version, source)| Some((name?, version?, source)))
+        .collect()
+}
+
+/// Registry packages pinned by a `Cargo.lock`. Workspace members (no
+/// `source`) and git dependencies are left out.
+pub fn parse_cargo_lock(text: &str) -> Vec<(String, String)> {
+    toml_packages(text)
+        .into_iter()
+        .filter(|(_, _, source)| {
+            source
+                .as_deref()
+                .is_some_and(|s| s.starts_with("registry+") || s.starts_with("sparse+"))
+        })
+        .map(|(name, version, _)| (name, version))
+        .collect()
+}
+
+/// PyPI packages pinned by a `poetry.lock`. Packages from git, a URL, a
+/// path or another index (a `[package.source]` table) are left out.
+pub fn parse_poetry_lock(text: &str) -> Vec<(String, String)> {
+    toml_packages(text)
+        .into_iter()
+        .filter(|(_, _, source)| source.is_none())
+        .map(|(name, version, _)| (name, version))
+        .collect()
+}
+
+/// Packages pinned by a `package-lock.json`, from its `packages` map
+/// (lockfile versions 2 and 3) or nested `dependencies` (version 1).
+/// Linked workspace packages and non-registry versions are left out.
+pub fn parse_package_lock(text: &str) -> Result<Vec<(String, String)>, String> {
+    fn collect_v1(deps: &serde_json::Map<String, Value>, out: &mut Vec<(String, String)>) {
+        for (name, entry) in deps {
+            if let Some(version) = entry["version"].as_str() {
+                out.push((name.clone(), version.to_string()));
+            }
+            if let Some(nested) = entry["dependencies"].as_object() {
+                collect_v1(nested, out);
+            }
+        }
+    }
+
+    const NODE_MODULES: &str = "node_modules/";
+    let json: Value = serde_json::from_str(text).map_err(|e| e.to_string())?;
+    let mut packages = Vec::new();
+    if let Some(map) = json["packages"].as_object() {
+        for (path, entry) in map {
+            let Some(pos) = path.rfind(NODE_MODULES) else {
+                continue;
+            };
+            if entry["link"].as_bool() == Some(true) {
+                continue;
+            }
+            let name = entry["name"]
+                .as_str()
+                .unwrap_or(&path[pos + NODE_MODULES.len()..]);
+            if let Some(version) = entry["version"].as_str() {
+                packages.push((name.to_string(), version.to_string()));
+            }
+        }
+    } else if let Some(deps) = json["dependencies"].as_object() {
+        collect_v1(deps, &mut packages);
+    }
+    packages.retain(|(_, version)| !version.contains(':'));
+    Ok(packages)
+}
+
+/// Lockfiles under `project_root`, skipping installed packages and build
+/// output.
+pub fn find_lockfiles(project_root: &Path) -> Vec<PathBuf> {
+    let mut found: Vec<PathBuf> = walkdir::WalkDir::new(project_root)
+        .into_iter()
+        .filter_entry(|e| {
+            e.depth() == 0
+                || !e.file_type().is_dir()
+                || !SKIPPED_DIRS.contains(&e.file_name().to_string_lossy().as_ref())
+        })
+        .filter_map(|e| e.ok())
+        .filter(|e| {
+            e.file_type().is_file()
+                && Ecosystem::of_lockfile(&e.file_name().to_string_lossy()).is_some()
+        })
+        .map(|e| e.into_path())
+        .collect();
+    found.sort();
+    found
+}
+
+/// Every package the project's lockfiles pin.
+pub fn locked_packages(project_root: &Path) -> LockfileScan {
+    let mut scan = LockfileScan::default();
+    for path in find_lockfiles(project_root) {
+        let Some(ecosystem) = path
+            .file_name()
+            .and_then(|n| Ecosystem::of_lockfile(&n.to_string_lossy()))
+        else {
+            continue;
+        };
+        let lockfile = path
+            .strip_prefix(project_root)
+            .unwrap_or(&path)
+            .to_string_lossy()
+            .replace('\\', "/");
+        let pinned = std::fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|text| match ecosystem {
+                Ecosystem::CratesIo => Ok(parse_cargo_lock(&text)),
+                Ecosystem::Npm => parse_package_lock(&text),
+                Ecosystem::PyPi => Ok(parse_poetry_lock(&text)),
+            });
+        match pinned {
+            Ok(pinned) => scan
+                .packages
+                .extend(pinned.into_iter().map(|(name, version)| LockedPackage {
+                    ecosystem,
+                    name,
+                    version,
+                    lockfile: lockfile.clone(),
+                })),
+            Err(e) => scan.errors.push(format!("{lockfile}: {e}")),
+        }
+        scan.lockfiles.push(lockfile);
+    }
+    scan.packages.sort();
+    scan.packages.dedup();
+    scan
+}
+
+// ---------------------------------------------------------------------------
+// OSV
+// ---------------------------------------------------------------------------
+
+/// Where vulnerability data comes from: [`OsvApi`] in practice.
+pub trait OsvSource {
+    /// The IDs of the vulnerabilities affecting each package, in order.
+    fn query_batch(&self, packages: &[&LockedPackage]) -> Result<Vec<Vec<String>>, String>;
+
+    /// The record of one vulnerability.
+    fn vulnerability(&self, id: &str) -> Result<Vulnerability, String>;
+}
+
+/// The OSV HTTP API.
+pub struct OsvApi {
+    agent: ureq::Agent,
+    base_url: String,
+}
+
+impl OsvApi {
+    pub fn new(base_url: &str) -> Self {
+        Self {
+            agent: ureq::AgentBuilder::new().timeout(REQUEST_TIMEOUT).build(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+        }
+    }
+}
+
+impl Default for OsvApi {
+    fn default() -> Self {
+        Self::new(OSV_API_URL)
+    }
+}
+
+impl OsvSource for OsvApi {
+    fn query_batch(&self, packages: &[&LockedPackage]) -> Result<Vec<Vec<String>>, String> {
+        let queries: Vec<Value> = packages
+            .iter()
+            .map(|p| {
+                serde_json::json!({
+                    "package": {"name": p.name, "ecosystem": p.ecosystem.as_str()},
+                    "version": p.version,
+                })
+            })
+            .collect();
+        let body = serde_json::json!({ "queries": queries }).to_string();
+        let text = self
+            .agent
+            .post(&format!("{}/querybatch", self.base_url))
+            .set("Content-Type", "application/json")
+            .send_string(&body)
+            .map_err(|e| e.to_string())?
+            .into_string()
+            .map_err(|e| e.to_string())?;
+        let json: Value = serde_json::from_str(&text).map_err(|e| e.to_string())?;
+        let results = json["results"]
+            .as_array()
+            .ok_or("OSV batch response has no results")?;
+        if results.len() != packages.len() {
+            return Err(format!(
+                "OSV answered {} of {} queries",
+                results.len(),
+                packages.len()
+            ));
+        }
+        Ok(results
+            .iter()
+            .map(|r| {
+                r["vulns"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|v| v["id"].as_str().map(str::to_string))
+                    .collect()
+            })
+            .collect())
+    }
+
+    fn vulnerability(&self, id: &str) -> Result<Vulnerability, String> {
+        let text = self
+            .agent
+            .get(&format!("{}/vulns/{id}", self.base_url))
+            .call()
+            .map_err(|e| e.to_string())?
+            .into_string()
+            .map_err(|e| e.to_string())?;
+        let json: Value = serde_json::from_str(&text).map_err(|e| e.to_string())?;
+        Vulnerability::from_osv(&json).ok_or_else(|| format!("Invalid OSV record for {id}"))
+    }
+}
+
+/// Look `packages` up in OSV, through `cache`. Without a `source`
+/// (offline) only cached answers are used; answers older than
+/// `max_cache_age` are asked again when there is one. `now` is in Unix
+/// seconds.
+pub fn audit(
+    packages: &[LockedPackage],
+    cache: &mut OsvCache,
+    source: Option<&dyn OsvSource>,
+    max_cache_age: Duration,
+    now: i64,
+) -> AuditReport {
+    let mut report = AuditReport::default();
+    let max_age = max_cache_age.as_secs() as i64;
+
+    let mut stale: Vec<&LockedPackage> = Vec::new();
+    let mut seen = std::collections::HashSet::new();
+    for package in packages {
+        let key = package.cache_key();
+        let fresh = cache
+            .queries
+            .get(&key)
+            .is_some_and(|q| now - q.fetched_at <= max_age);
+        if !fresh && seen.insert(key) {
+            stale.push(package);
+        }
+    }
+
+    if let Some(source) = source {
+        for batch in stale.chunks(QUERY_BATCH_SIZE) {
+            match source.query_batch(batch) {
+                Ok(results) => {
+                    for (package, ids) in batch.iter().zip(results) {
+                        cache.queries.insert(
+                            package.cache_key(),
+                            CachedQuery {
+                                fetched_at: now,
+                                ids,
+                            },
+                        );
+                    }
+                }
+                Err(e) => {
+                    report.errors.push(format!("OSV query failed: {e}"));
+                    break;
+                }
+            }
+        }
+
+        let mut missing: Vec<String> = packages
+            .iter()
+            .filter_map(|p| cache.queries.get(&p.cache_key()))
+            .flat_map(|q| q.ids.iter())
+            .filter(|id| !cache.vulns.contains_key(*id))
+            .cloned()
+            .collect();
+        missing.sort();
+        missing.dedup();
+        for id in missing {
+            match source.vulnerability(&id) {
+                Ok(vuln) => {
+                    cache.vulns.insert(id, vuln);
+                }
+                Err(e) => {
+                    report
+                        .errors
+                        .push(format!("OSV lookup of {id} failed: {e}"));
+                    break;
+                }
+            }
+        }
+    }
+
+    for package in packages {
+        let Some(query) = cache.queries.get(&package.cache_key()) else {
+            report.unchecked += 1;
+            continue;
+        };
+        if query.fetched_at != now {
+            report.from_cache += 1;
+        }
+        if query.ids.is_empty() {
+            continue;
+        }
+        let vulnerabilities = query
+            .ids
+            .iter()
+            .map(|id| match cache.vulns.get(id) {
+                Some(v) => PackageVulnerability {
+                    id: id.clone(),
+                    summary: v.summary.clone(),
+                    aliases: v.aliases.clone(),
+                    severity: v.severity.clone(),
+                    fixed_in: v.fixed_in(package),
+                },
+                None => PackageVulnerability {
+                    id: id.clone(),
+                    summary: None,
+                    aliases: Vec::new(),
+                    severity: None,
+                    fixed_in: Vec::new(),
+                },
+            })
+            .collect();
+        report.vulnerable.push(VulnerablePackage {
+            package: package.clone(),
+            vulnerabilities,
+        });
+    }
+    report
+}
+
+// ---------------------------------------------------------------------------
+// Importers
+// ---------------------------------------------------------------------------
+
+/// The npm package a JS/TS specifier names; `None` for relative paths,
+/// URLs and `node:` builtins.
+fn npm_package(specifier: &str) -> Option<String> {
+    if specifier.is_empty() || specifier.starts_with(['.', '/']) || specifier.contains(':') {
+        return None;
+    }
+    let mut segments = specifier.split('/');
+    let first = segments.next()?;
+    match first.starts_with('@') {
+        true => Some(format!("{first}/{}", segments.next()?)),
+        false => Some(first.to_string()),
+    }
+}
+
+/// The crate a Rust `use` or `extern crate` line imports from; `None` for
+/// paths within the crate and the standard library.
+fn rust_crate(line: &str) -> Option<String> {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    let re = RE.get_or_init(|| {
+        Regex::new(
+            r"^\s*(?:pub(?:\([^)]*\))?\s+)?(?:use\s+(?:::)?|extern\s+crate\s+)([A-Za-z_]\w*)",
+        )
+        .unwrap()
+    });
+    let root = re.captures(line)?.get(1)?.as_str();
+    match root {
+        "crate" | "self" | "super" | "std" | "core" | "alloc" => None,
+        _ => Some(root.to_string()),
+    }
+}
+
+/// Indexed files importing each package, keyed by ecosystem and
+/// normalized package name, from the `Imports` edges to external modules.
+/// Rust edges only name the imported item, so the crate is read from the
+/// source line under `project_root`.
+pub fn import_index(
+    edges: &[CodeEdge],
+    project_root: &Path,
+) -> HashMap<(Ecosystem, String), Vec<Importer>> {
+    let mut sources: HashMap<&str, Option<Vec<String>>> = HashMap::new();
+    let mut index: HashMap<(Ecosystem, String), Vec<Importer>> = HashMap::new();
+    for edge in edges.iter().filter(|e| e.kind == EdgeKind::Imports) {
+        let Some(specifier) = edge.target.strip_prefix("module:") else {
+            continue;
+        };
+        let extension = Path::new(&edge.file_path)
+            .extension()
+            .map(|e| e.to_string_lossy().to_ascii_lowercase())
+            .unwrap_or_default();
+        let mut names: Vec<String> = edge
+            .metadata
+            .as_ref()
+            .and_then(|m| m.get("names"))
+            .map(|n| n.split(',').map(|s| s.trim().to_string()).collect())
+            .unwrap_or_default();
+        let (ecosystem, package) = match extension.as_str() {
+            ext if JS_EXTENSIONS.contains(&ext) => (Ecosystem::Npm, npm_package(specifier)),
+            "py" | "pyi" => (
+                Ecosystem::PyPi,
+                specifier
+                    .split('.')
+                    .next()
+                    .filter(|root| !root.is_empty())
+                    .map(str::to_string),
+            ),
+            "rs" => {
+                let lines = sources.entry(edge.file_path.as_str()).or_insert_with(|| {
+                    std::fs::read_to_string(project_root.join(&edge.file_path))
+                        .ok()
+                        .map(|s| s.lines().map(str::to_string).collect())
+                });
+                let line = lines
+                    .as_ref()
+                    .and_then(|l| l.get((edge.line as usize).saturating_sub(1)));
+                names = vec![specifier.to_string()];
+                (Ecosystem::CratesIo, line.and_then(|l| rust_crate(l)))
+            }
+            _ => continue,
+        };
+        let Some(package) = package else {
+            continue;
+        };
+        let importers = index
+            .entry((ecosystem, ecosystem.normalize(&package)))
+            .or_default();
+        match importers
+            .iter_mut()
+            .find(|i| i.file == edge.file_path && i.line == edge.line)
+        {
+            Some(importer) => {
+                for name in names {
+                    if !importer.names.contains(&name) {
+                        importer.names.push(name);
+                    }
+                }
+            }
+            None => importers.push(Importer {
+                file: edge.file_path.clone(),
+                line: edge.line,
+                names,
+            }),
+        }
+    }
+    for importers in index.values_mut() {
+        importers.sort_by(|a, b| (&a.file, a.line).cmp(&(&b.file, b.line)));
+    }
+    index
+}
+
+/// The importers of `package` in an [`import_index`].
+pub fn importers_of<'a>(
+    index: &'a HashMap<(Ecosystem, String), Vec<Importer>>,
+    package: &LockedPackage,
+) -> &'a [Importer] {
+    index
+        .get(&(
+            package.ecosystem,
+            package.ecosystem.normalize(&package.name),
+        ))
+        .map(Vec::as_slice)
+        .unwrap_or_default()
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    fn package(ecosystem: Ecosystem, name: &str, version: &str) -> LockedPackage {
+        LockedPackage {
+            ecosystem,
+            name: name.to_string(),
+            version: version.to_string(),
+            lockfile: "Cargo.lock".to_string(),
+        }
+    }
+
+    fn import_edge(file: &str, line: u32, specifier: &str, names: Option<&str>) -> CodeEdge {
+        CodeEdge {
+            source: format!("file:{file}"),
+            target: format!("module:{specifier}"),
+            kind: EdgeKind::Imports,
+            file_path: file.to_string(),
+            line,
+            metadata: names.map(|n| HashMap::from([("names".to_string(), n.to_string())])),
+        }
+    }
+
+    /// Answers from fixed data, counting the batch queries asked.
+    struct FakeOsv {
+        vulnerable: &'static str,
+        queries: Cell<usize>,
+        fail: bool,
+    }
+
+    impl OsvSource for FakeOsv {
+        fn query_batch(&self, packages: &[&LockedPackage]) -> Result<Vec<Vec<String>>, String> {
+            if self.fail {
+                return Err("connection refused".to_string());
+            }
+            self.queries.set(self.queries.get() + packages.len());
+            Ok(packages
+                .iter()
+                .map(|p| match p.name == self.vulnerable {
+                    true => vec!["GHSA-1".to_string()],
+                    false => Vec::new(),
+                })
+                .collect())
+        }
+
+        fn vulnerability(&self, id: &str) -> Result<Vulnerability, String> {
+            Ok(Vulnerability::from_osv(&serde_json::json!({
+                "id": id,
+                "summary": "Prototype pollution",
+                "aliases": ["CVE-2024-1"],
+                "database_specific": {"severity": "HIGH"},
+                "affected": [{
+                    "package": {"ecosystem": "npm", "name": self.vulnerable},
+                    "ranges": [{"type": "SEMVER", "events": [{"introduced": "0"}, {"fixed": "4.17.21"}]}],
+                }],
+            }))
+            .unwrap())
+        }
+    }
+
+    #[test]
+    fn parses_cargo_and_poetry_lockfiles() {
+        let cargo = r#"
+version = 3
+
+[[package]]
+name = "app"
+version = "0.1.0"
+dependencies = ["serde"]
+
+[[package]]
+name = "serde"
+version = "1.0.200"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+checksum = "abc"
+
+[[package]]
+name = "forked"
+version = "0.2.0"
+source = "git+https://example.com/forked#abc"
+"#;
+        assert_eq!(
+            parse_cargo_lock(cargo),
+            vec![("serde".to_string(), "1.0.200".to_string())]
+        );
+
+        let poetry = r#"
+[[package]]
+name = "requests"
+version = "2.31.0"
+description = "HTTP"
+
+[package.dependencies]
+urllib3 = ">=1.21.1"
+
+[[package]]
+name = "internal"
+version = "1.0.0"
+
+[package.source]
+type = "git"
+url = "https://example.com/internal.git"
+
+[metadata]
+lock-version = "2.0"
+"#;
+        assert_eq!(
+            parse_poetry_lock(poetry),
+            vec![("requests".to_string(), "2.31.0".to_string())]
+        );
+    }
+
+    #[test]
+    fn parses_package_lock_v1_and_v3() {
+        let v3 = r#"{
+            "lockfileVersion": 3,
+            "packages": {
+                "": {"name": "app", "version": "1.0.0"},
+                "node_modules/lodash": {"version": "4.17.20"},
+                "node_modules/@scope/util": {"version": "2.0.0"},
+                "node_modules/a/node_modules/lodash": {"version": "3.10.1"},
+                "node_modules/local": {"resolved": "packages/local", "link": true},
+                "node_modules/git-dep": {"version": "git+https://example.com/x.git"}
+            }
+        }"#;
+        let mut pinned = parse_package_lock(v3).unwrap();
+        pinned.sort();
+        assert_eq!(
+            pinned,
+            vec![
+                ("@scope/util".to_string(), "2.0.0".to_string()),
+                ("lodash".to_string(), "3.10.1".to_string()),
+                ("lodash".to_string(), "4.17.20".to_string()),
+            ]
+        );
+
+        let v1 = r#"{
+            "lockfileVersion": 1,
+            "dependencies": {
+                "a": {"version": "1.0.0", "dependencies": {"b": {"version": "2.0.0"}}}
+            }
+        }"#;
+        assert_eq!(parse_package_lock(v1).unwrap().len(), 2);
+        assert!(parse_package_lock("{").is_err());
+    }
+
+    #[test]
+    fn locked_packages_skip_installed_packages() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::create_dir_all(root.join("web/node_modules/x")).unwrap();
+        std::fs::write(
+            root.join("web/package-lock.json"),
+            r#"{"packages": {"node_modules/lodash": {"version": "4.17.20"}}}"#,
+        )
+        .unwrap();
+        std::fs::write(
+            root.join("web/node_modules/x/package-lock.json"),
+            r#"{"packages": {"node_modules/y": {"version": "1.0.0"}}}"#,
+        )
+        .unwrap();
+        std::fs::write(
+            root.join("poetry.lock"),
+            "[[package]]\nname = \"requests\"\nversion = \"2.0.0\"\n",
+        )
+        .unwrap();
+
+        let scan = locked_packages(root);
+        assert_eq!(scan.lockfiles, vec!["poetry.lock", "web/package-lock.json"]);
+        assert_eq!(scan.packages.len(), 2);
+        assert_eq!(scan.packages[0].ecosystem, Ecosystem::Npm);
+        assert_eq!(scan.packages[0].lockfile, "web/package-lock.json");
+        assert!(scan.errors.is_empty());
+    }
+
+    #[test]
+    fn audit_uses_fresh_cache_and_falls_back_offline() {
+        let packages = vec![
+            package(Ecosystem::Npm, "lodash", "4.17.20"),
+            package(Ecosystem::Npm, "react", "18.2.0"),
+        ];
+        let osv = FakeOsv {
+            vulnerable: "lodash",
+            queries: Cell::new(0),
+            fail: false,
+        };
+        let mut cache = OsvCache::default();
+        let report = audit(
+            &packages,
+            &mut cache,
+            Some(&osv),
+            DEFAULT_MAX_CACHE_AGE,
+            1000,
+        );
+        assert_eq!(osv.queries.get(), 2);
+        assert_eq!((report.unchecked, report.from_cache), (0, 0));
+        assert_eq!(report.vulnerable.len(), 1);
+        let vuln = &report.vulnerable[0];
+        assert_eq!(vuln.package.name, "lodash");
+        assert_eq!(vuln.vulnerabilities[0].severity.as_deref(), Some("HIGH"));
+        assert_eq!(vuln.vulnerabilities[0].fixed_in, vec!["4.17.21"]);
+
+        // Fresh answers are not asked again.
+        let report = audit(
+            &packages,
+            &mut cache,
+            Some(&osv),
+            DEFAULT_MAX_CACHE_AGE,
+            2000,
+        );
+        assert_eq!((osv.queries.get(), report.from_cache), (2, 2));
+
+        // Offline, or with OSV unreachable, stale answers still count.
+        let later = 1000 + DEFAULT_MAX_CACHE_AGE.as_secs() as i64 + 1;
+        let mut extra = packages.clone();
+        extra.push(package(Ecosystem::Npm, "vue", "3.0.0"));
+        let report = audit(&extra, &mut cache, None, DEFAULT_MAX_CACHE_AGE, later);
+        assert_eq!((report.vulnerable.len(), report.unchecked), (1, 1));
+        let down = FakeOsv { fail: true, ..osv };
+        let report = audit(
+            &extra,
+            &mut cache,
+            Some(&down),
+            DEFAULT_MAX_CACHE_AGE,
+            later,
+        );
+        assert_eq!((report.vulnerable.len(), report.unchecked), (1, 1));
+        assert!(report.errors[0].contains("connection refused"));
+    }
+
+    #[test]
+    fn cache_round_trips() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(OsvCache::load(dir.path()), OsvCache::default());
+        let mut cache = OsvCache::default();
+        cache.queries.insert(
+            "npm:lodash@4.17.20".to_string(),
+            CachedQuery {
+                fetched_at: 5,
+                ids: vec!["GHSA-1".to_string()],
+            },
+        );
+        cache.save(dir.path()).unwrap();
+        let loaded = OsvCache::load(dir.path());
+        assert_eq!(loaded.version, CACHE_VERSION);
+        assert_eq!(loaded.queries, cache.queries);
+    }
+
+    #[test]
+    fn import_index_maps_imports_to_packages() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("src")).unwrap();
+        std::fs::write(
+            dir.path().join("src/main.rs"),
+            "use serde_json::{json, Value};\nuse crate::util::helper;\n",
+        )
+        .unwrap();
+        let edges = vec![
+            import_edge("web/app.ts", 1, "lodash/fp", Some("merge")),
+            import_edge("web/app.ts", 2, "@scope/util", None),
+            import_edge("web/app.ts", 3, "./local", None),
+            import_edge("web/app.ts", 4, "node:fs", None),
+            import_edge("tool/run.py", 1, "PyYAML_compat.loader", Some("load")),
+            import_edge("src/main.rs", 1, "json", None),
+            import_edge("src/main.rs", 1, "Value", None),
+            import_edge("src/main.rs", 2, "helper", None),
+        ];
+        let index = import_index(&edges, dir.path());
+        assert_eq!(index.len(), 4);
+
+        let lodash = importers_of(&index, &package(Ecosystem::Npm, "lodash", "4.17.20"));
+        assert_eq!(lodash[0].file, "web/app.ts");
+        assert_eq!(lodash[0].names, vec!["merge"]);
+        assert_eq!(
+            importers_of(&index, &package(Ecosystem::Npm, "@scope/util", "1.0.0")).len(),
+            1
+        );
+        assert_eq!(
+            importers_of(&index, &package(Ecosystem::PyPi, "pyyaml-compat", "1.0")).len(),
+            1
+        );
+        let serde = importers_of(&index, &package(Ecosystem::CratesIo, "serde-json", "1.0.0"));
+        assert_eq!(serde.len(), 1);
+        assert_eq!(serde[0].names, vec!["json", "Value"]);
+        assert!(importers_of(&index, &package(Ecosystem::CratesIo, "serde", "1.0.0")).is_empty());
+    }
+}
@@ -0,0 +1,648 @@
+//! Dynamic dispatch: the implementations a call through an interface,
+//! trait or base class may run.
+//!
+//! Calls are resolved by name to a single node, so a call through an
+//! interface ends at the interface method — or at whichever implementation
+//! the name happened to resolve to — and every other implementation looks
+//! unused. [`DispatchIndex`] links a method to the methods a call to it may
+//! run instead:
+//!
+//! - `override`: a method of the same name in a type that implements or
+//!   extends the method's type, directly or transitively,
+//! - `peer`: methods of the same name in types implementing an interface
+//!   whose members are not indexed (TypeScript interface signatures),
+//! - `registration`: an override through a dependency-injection binding of
+//!   a class to an interface — ASP.NET `AddScoped<IRepo, SqlRepo>()`,
+//!   InversifyJS and Guice `bind(IRepo).to(SqlRepo)`, Angular and NestJS
+//!   `{ provide: IRepo, useClass: SqlRepo }`, `injector`
+//!   `binder.bind(IRepo, to=SqlRepo)`.
+//!
+//! Registrations are read from the source files. They also record the
+//! classes a container constructs on its own: those registered without an
+//! interface (`AddSingleton<Clock>()`) and those annotated `@Service`,
+//! `@Component`, `@Injectable()` and the like.
+
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::sync::OnceLock;
+
+use regex::Regex;
+use serde::Serialize;
+
+use crate::error::Result;
+use crate::graph::api_docs::SourceCache;
+use crate::graph::store::GraphStore;
+use crate::security::rules::is_test_file;
+use crate::types::{CodeEdge, CodeNode, EdgeKind, NodeKind};
+
+/// Lines between an annotation and the start of the class it annotates.
+const ANNOTATION_REACH: u32 = 5;
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// How a call to one method may run another.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DispatchKind {
+    Override,
+    Peer,
+    Registration,
+}
+
+impl DispatchKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Override => "override",
+            Self::Peer => "peer",
+            Self::Registration => "registration",
+        }
+    }
+}
+
+/// A call to `declaration` may run `implementation`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DispatchLink {
+    pub declaration: String,
+    pub implementation: String,
+    pub kind: DispatchKind,
+    /// The interface, trait or base class the two methods meet at.
+    pub via: String,
+}
+
+/// A class a dependency-injection container constructs.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Registration {
+    /// The symbol registering it, or `file:<path>` for top-level code and
+    /// annotations.
+    pub site: String,
+    pub class: String,
+    /// The interface it is bound to, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub interface: Option<String>,
+    pub file_path: String,
+    pub line: u32,
+}
+
+/// Dispatch links and registrations, looked up from either end.
+#[derive(Debug, Default)]
+pub struct DispatchIndex {
+    links: Vec<DispatchLink>,
+    registrations: Vec<Registration>,
+    by_declaration: HashMap<String, Vec<usize>>,
+    by_implementation: HashMap<String, Vec<usize>>,
+}
+
+fn is_type(kind: NodeKind) -> bool {
+    matches!(
+        kind,
+        NodeKind::Class | NodeKind::Interface | NodeKind::Struct | NodeKind::Trait | NodeKind::Enum
+    )
+}
+
+fn is_supertype_edge(kind: EdgeKind) -> bool {
+    matches!(kind, EdgeKind::Implements | EdgeKind::Extends)
+}
+
+// ---------------------------------------------------------------------------
+// Index
+// ---------------------------------------------------------------------------
+
+impl DispatchIndex {
+    /// Index a graph. Registrations are read from the sources under `root`;
+    /// without one there are none.
+    pub fn from_graph(nodes: &[CodeNode], edges: &[CodeEdge], root: Option<&Path>) -> Self {
+        let registrations = find_registrations(nodes, root);
+        let by_id: HashMap<&str, &CodeNode> = nodes.iter().map(|n| (n.id.as_str(), n)).collect();
+        let node = |id: &str| by_id.get(id).copied();
+
+        let mut methods: HashMap<&str, Vec<&CodeNode>> = HashMap::new();
+        let mut supertypes: HashMap<&str, Vec<(&str, DispatchKind)>> = HashMap::new();
+        for edge in edges {
+            let (Some(source), Some(target)) = (node(&edge.source), node(&edge.target)) else {
+                continue;
+            };
+            if edge.kind == EdgeKind::Contains && target.kind == NodeKind::Method {
+                methods.entry(&edge.source).or_default().push(target);
+            } else if is_supertype_edge(edge.kind) && is_type(source.kind) && is_type(target.kind) {
+                supertypes
+                    .entry(&edge.source)
+                    .or_default()
+                    .push((&edge.target, DispatchKind::Override));
+            }
+        }
+        for r in &registrations {
+            if let Some(interface) = &r.interface {
+                supertypes
+                    .entry(&r.class)
+                    .or_default()
+                    .push((interface, DispatchKind::Registration));
+            }
+        }
+
+        let mut links: Vec<DispatchLink> = Vec::new();
+        let mut peers: HashMap<(&str, &str), Vec<&CodeNode>> = HashMap::new();
+        let mut types: Vec<&&str> = methods.keys().collect();
+        types.sort();
+        for ty in types {
+            let own = &methods[*ty];
+            let mut seen: HashSet<&str> = HashSet::new();
+            let mut queue: Vec<(&str, DispatchKind)> =
+                supertypes.get(*ty).cloned().unwrap_or_default();
+            while let Some((ancestor, kind)) = queue.pop() {
+                if !seen.insert(ancestor) {
+                    continue;
+                }
+                let Some(ancestor_node) = node(ancestor) else {
+                    continue;
+                };
+                let declared = methods.get(ancestor);
+                for method in own {
+                    match declared.and_then(|d| d.iter().find(|m| m.name == method.name)) {
+                        Some(declaration) => links.push(DispatchLink {
+                            declaration: declaration.id.clone(),
+                            implementation: method.id.clone(),
+                            kind,
+                            via: ancestor_node.name.clone(),
+                        }),
+                        None if declared.is_none()
+                            && matches!(
+                                ancestor_node.kind,
+                                NodeKind::Interface | NodeKind::Trait
+                            ) =>
+                        {
+                            peers
+                                .entry((ancestor, method.name.as_str()))
+                                .or_default()
+                                .push(method);
+                        }
+                        None => {}
+                    }
+                }
+                for &(next, next_kind) in supertypes.get(ancestor).into_iter().flatten() {
+                    let kind = if kind == DispatchKind::Registration {
+                        kind
+                    } else {
+                        next_kind
+                    };
+                    queue.push((next, kind));
+                }
+            }
+        }
+
+        let mut groups: Vec<_> = peers.into_iter().collect();
+        groups.sort_by(|a, b| a.0.cmp(&b.0));
+        for ((interface, _), mut group) in groups {
+            group.sort_by(|a, b| a.id.cmp(&b.id));
+            group.dedup_by(|a, b| a.id == b.id);
+            let via = node(interface).map_or(interface, |n| n.name.as_str());
+            for a in &group {
+                for b in group.iter().filter(|b| b.id != a.id) {
+                    links.push(DispatchLink {
+                        declaration: a.id.clone(),
+                        implementation: b.id.clone(),
+                        kind: DispatchKind::Peer,
+                        via: via.to_string(),
+                    });
+                }
+            }
+        }
+
+        let mut seen: HashSet<(String, String)> = HashSet::new();
+        links.retain(|l| seen.insert((l.declaration.clone(), l.implementation.clone())));
+        let mut index = Self {
+            registrations,
+            ..Self::default()
+        };
+        for (i, link) in links.iter().enumerate() {
+            index
+                .by_declaration
+                .entry(link.declaration.clone())
+                .or_default()
+                .push(i);
+            index
+                .by_implementation
+                .entry(link.implementation.clone())
+                .or_default()
+                .push(i);
+        }
+        index.links = links;
+        index
+    }
+
+    /// Index the part of the stored graph dispatch to and from `method_id`
+    /// can go through: the types above the method's type with their
+    /// methods, and the same-named peers below interfaces without indexed
+    /// members. Registrations are not read.
+    pub fn around(store: &GraphStore, method_id: &str) -> Result<Self> {
+        let Some(method) = store.get_node(method_id)? else {
+            return Ok(Self::default());
+        };
+        let mut nodes: HashMap<String, CodeNode> = HashMap::new();
+        let mut edges: Vec<CodeEdge> = Vec::new();
+
+        let mut queue: Vec<String> = store
+            .get_in_edges(method_id, Some(EdgeKind::Contains.as_str()))?
+            .into_iter()
+            .map(|e| e.source)
+            .collect();
+        let mut seen: HashSet<String> = HashSet::new();
+        let mut below_bare_interfaces: Vec<String> = Vec::new();
+        while let Some(ty) = queue.pop() {
+            if !seen.insert(ty.clone()) {
+                continue;
+            }
+            let Some(ty_node) = store.get_node(&ty)? else {
+                continue;
+            };
+            let mut has_methods = false;
+            for edge in store.get_out_edges(&ty, Some(EdgeKind::Contains.as_str()))? {
+                if let Some(member) = store.get_node(&edge.target)? {
+                    if member.kind == NodeKind::Method {
+                        has_methods = true;
+                        nodes.insert(member.id.clone(), member);
+                        edges.push(edge);
+                    }
+                }
+            }
+            if !has_methods && matches!(ty_node.kind, NodeKind::Interface | NodeKind::Trait) {
+                below_bare_interfaces.push(ty.clone());
+            }
+            for kind in [EdgeKind::Implements, EdgeKind::Extends] {
+                for edge in store.get_out_edges(&ty, Some(kind.as_str()))? {
+                    queue.push(edge.target.clone());
+                    edges.push(edge);
+                }
+            }
+            nodes.insert(ty, ty_node);
+        }
+
+        // Peers: the other types under an interface without members
+        for interface in below_bare_interfaces {
+            for kind in [EdgeKind::Implements, EdgeKind::Extends] {
+                for edge in store.get_in_edges(&interface, Some(kind.as_str()))? {
+                    if seen.insert(edge.source.clone()) {
+                        if let Some(peer) = store.get_node(&edge.source)? {
+                            for member in
+                                store.get_out_edges(&peer.id, Some(EdgeKind::Contains.as_str()))?
+                            {
+                                if let Some(m) = store.get_node(&member.target)? {
+                                    if m.kind == NodeKind::Method && m.name == method.name {
+                                        nodes.insert(m.id.clone(), m);
+                                        edges.push(member);
+                                    }
+                                }
+                            }
+                            nodes.insert(peer.id.clone(), peer);
+                        }
+                    }
+                    edges.push(edge);
+                }
+            }
+        }
+
+        let nodes: Vec<CodeNode> = nodes.into_values().collect();
+        Ok(Self::from_graph(&nodes, &edges, None))
+    }
+
+    pub fn links(&self) -> &[DispatchLink] {
+        &self.links
+    }
+
+    pub fn registrations(&self) -> &[Registration] {
+        &self.registrations
+    }
+
+    /// The links from `declaration` to the methods a call to it may run.
+    pub fn implementations<'a>(
+        &'a self,
+        declaration: &str,
+    ) -> impl Iterator<Item = &'a DispatchLink> + 'a {
+        self.lookup(&self.by_declaration, declaration)
+    }
+
+    /// The links from methods whose calls may run `implementation`.
+    pub fn declarations<'a>(
+        &'a self,
+        implementation: &str,
+    ) -> impl Iterator<Item = &'a DispatchLink> + 'a {
+        self.lookup(&self.by_implementation, implementation)
+    }
+
+    fn lookup<'a>(
+        &'a self,
+        map: &'a HashMap<String, Vec<usize>>,
+        id: &str,
+    ) -> impl Iterator<Item = &'a DispatchLink> + 'a {
+        map.get(id).into_iter().flatten().map(|&i| &self.links[i])
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Registrations
+// ---------------------------------------------------------------------------
+
+/// Bindings of a class to an interface: group 1 the interface, 2 the class.
+fn binding_patterns() -> &'static [Regex] {
+    static RE: OnceLock<Vec<Regex>> = OnceLock::new();
+    RE.get_or_init(|| {
+        [
+            // ASP.NET Core: services.AddScoped<IRepo, SqlRepo>()
+            r"\bAdd(?:Scoped|Transient|Singleton)<\s*([\w.]+)\s*,\s*([\w.]+)\s*>",
+            // InversifyJS, Guice: bind<IRepo>(TYPES.Repo).to(SqlRepo), bind(IRepo.class).to(SqlRepo.class)
+            r"\bbind(?:<[^>]*>)?\(\s*([\w.]+?)(?:\.class)?\s*\)\s*\.to\(\s*([\w.]+?)(?:\.class)?\s*\)",
+            // Angular, NestJS: { provide: IRepo, useClass: SqlRepo }
+            r"\bprovide\s*:\s*([\w.]+)\s*,\s*use(?:Class|Existing)\s*:\s*([\w.]+)",
+            // injector: binder.bind(IRepo, to=SqlRepo)
+            r"\.bind\(\s*([\w.]+)\s*,\s*to\s*=\s*([\w.]+)",
+        ]
+        .iter()
+        .map(|p| Regex::new(p).expect("valid regex"))
+        .collect()
+    })
+}
+
+/// Classes registered on their own: group 1 the class.
+fn self_registration() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(r"\bAdd(?:Scoped|Transient|Singleton)<\s*([\w.]+)\s*>\s*\(")
+            .expect("valid regex")
+    })
+}
+
+/// Annotations and decorators handing a class to a container.
+fn component_annotation() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(
+            r"^@(?:Service|Component|Repository|Controller|RestController|Configuration|Injectable|Named|Singleton|ApplicationScoped|RequestScoped)\b",
+        )
+        .expect("valid regex")
+    })
+}
+
+/// Registrations in the non-test source files of `nodes`, in file and line
+/// order.
+fn find_registrations(nodes: &[CodeNode], root: Option<&Path>) -> Vec<Registration> {
+    let Some(root) = root else {
+        return Vec::new();
+    };
+    let mut sources = SourceCache::new(Some(root.to_path_buf()));
+    let mut by_file: HashMap<&str, Vec<&CodeNode>> = HashMap::new();
+    let mut types: HashMap<&str, Vec<&CodeNode>> = HashMap::new();
+    for node in nodes {
+        by_file.entry(&node.file_path).or_default().push(node);
+        if is_type(node.kind) {
+            types.entry(&node.name).or_default().push(node);
+        }
+    }
+    let resolve = |name: &str, file: &str| -> Option<&CodeNode> {
+        let name = name.rsplit('.').next().unwrap_or(name);
+        let candidates = types.get(name)?;
+        candidates
+            .iter()
+            .find(|n| n.file_path == file)
+            .or_else(|| (candidates.len() == 1).then(|| &candidates[0]))
+            .copied()
+    };
+
+    let mut files: Vec<&str> = by_file.keys().copied().collect();
+    files.sort();
+    let mut registrations = Vec::new();
+    for file in files {
+        if is_test_file(file) {
+            continue;
+        }
+        let Some(lines) = sources.lines(file) else {
+            continue;
+        };
+        let file_nodes = &by_file[file];
+        for (i, text) in lines.iter().enumerate() {
+            let line = i as u32 + 1;
+            let mut register = |class: &CodeNode, interface: Option<&CodeNode>, site: String| {
+                registrations.push(Registration {
+                    site,
+                    class: class.id.clone(),
+                    interface: interface.map(|n| n.id.clone()),
+                    file_path: file.to_string(),
+                    line,
+                });
+            };
+            for re in binding_patterns() {
+                for caps in re.captures_iter(text) {
+                    if let Some(class) = resolve(&caps[2], file) {
+                        register(class, resolve(&caps[1], file), site(file, file_nodes, line));
+                    }
+                }
+            }
+            for caps in self_registration().captures_iter(text) {
+                if let Some(class) = resolve(&caps[1], file) {
+                    register(class, None, site(file, file_nodes, line));
+                }
+            }
+            if component_annotation().is_match(text.trim_start()) {
+                if let Some(class) = annotated_class(file_nodes, line) {
+                    register(class, None, format!("file:{file}"));
+                }
+            }
+        }
+    }
+    registrations
+}
+
+/// The innermost function or method around `line`, else the file itself.
+fn site(file: &str, file_nodes: &[&CodeNode], line: u32) -> String {
+    file_nodes
+        .iter()
+        .filter(|n| matches!(n.kind, NodeKind::Function | NodeKind::Method))
+        .filter(|n| n.start_line <= line && n.end_line >= line)
+        .min_by_key(|n| n.end_line - n.start_line)
+        .map_or_else(|| format!("file:{file}"), |n| n.id.clone())
+}
+
+/// The class an annotation on `line` belongs to: the one starting nearest
+/// to it, within [`ANNOTATION_REACH`] lines either way (Java and C# put
+/// annotations inside the declaration's range).
+fn annotated_class<'a>(file_nodes: &[&'a CodeNode], line: u32) -> Option<&'a CodeNode> {
+    file_nodes
+        .iter()
+        .filter(|n| n.kind == NodeKind::Class && n.end_line >= line)
+        .filter(|n| n.start_line.abs_diff(line) <= ANNOTATION_REACH)
+        .min_by_key(|n| n.start_line.abs_diff(line))
+        .copied()
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::schema::initialize_database;
+    use crate::types::Language;
+
+    fn node(kind: NodeKind, name: &str, file: &str, line: u32) -> CodeNode {
+        CodeNode {
+            id: format!("{}:{file}:{name}:{line}", kind.as_str()),
+            name: name.to_string(),
+            qualified_name: None,
+            kind,
+            file_path: file.to_string(),
+            start_line: line,
+            end_line: line + 3,
+            start_column: 0,
+            end_column: 1,
+            language: Language::TypeScript,
+            body: None,
+            documentation: None,
+            exported: None,
+        }
+    }
+
+    fn edge(source: &CodeNode, target: &CodeNode, kind: EdgeKind) -> CodeEdge {
+        CodeEdge {
+            source: source.id.clone(),
+            target: target.id.clone(),
+            kind,
+            file_path: source.file_path.clone(),
+            line: source.start_line,
+            metadata: None,
+        }
+    }
+
+    #[test]
+    fn links_overrides_peers_and_registrations() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("Startup.cs"),
+            "class Startup {\n  void Configure(IServiceCollection services) {\n    services.AddScoped<IRepo, SqlRepo>();\n  }\n}\n",
+        )
+        .unwrap();
+
+        // Java-style: the interface declares `save`, two levels implement it.
+        let store_i = node(NodeKind::Interface, "Store", "Store.java", 1);
+        let store_save = node(NodeKind::Method, "save", "Store.java", 2);
+        let base = node(NodeKind::Class, "BaseStore", "Base.java", 1);
+        let base_save = node(NodeKind::Method, "save", "Base.java", 2);
+        let disk = node(NodeKind::Class, "DiskStore", "Disk.java", 1);
+        let disk_save = node(NodeKind::Method, "save", "Disk.java", 2);
+        // TypeScript-style: the interface's signatures are not indexed.
+        let handler = node(NodeKind::Interface, "Handler", "h.ts", 1);
+        let a = node(NodeKind::Class, "A", "a.ts", 1);
+        let a_run = node(NodeKind::Method, "run", "a.ts", 2);
+        let b = node(NodeKind::Class, "B", "b.ts", 1);
+        let b_run = node(NodeKind::Method, "run", "b.ts", 2);
+        // Bound in Startup.cs without an implements clause in the graph.
+        let repo = node(NodeKind::Interface, "IRepo", "IRepo.cs", 1);
+        let repo_get = node(NodeKind::Method, "Get", "IRepo.cs", 2);
+        let sql = node(NodeKind::Class, "SqlRepo", "SqlRepo.cs", 1);
+        let sql_get = node(NodeKind::Method, "Get", "SqlRepo.cs", 2);
+        let mut configure = node(NodeKind::Method, "Configure", "Startup.cs", 2);
+        configure.end_line = 4;
+
+        let nodes = vec![
+            store_i.clone(),
+            store_save.clone(),
+            base.clone(),
+            base_save.clone(),
+            disk.clone(),
+            disk_save.clone(),
+            handler.clone(),
+            a.clone(),
+            a_run.clone(),
+            b.clone(),
+            b_run.clone(),
+            repo.clone(),
+            repo_get.clone(),
+            sql.clone(),
+            sql_get.clone(),
+            configure.clone(),
+        ];
+        let edges = vec![
+            edge(&store_i, &store_save, EdgeKind::Contains),
+            edge(&base, &base_save, EdgeKind::Contains),
+            edge(&disk, &disk_save, EdgeKind::Contains),
+            edge(&base, &store_i, EdgeKind::Implements),
+            edge(&disk, &base, EdgeKind::Extends),
+            edge(&a, &a_run, EdgeKind::Contains),
+            edge(&b, &b_run, EdgeKind::Contains),
+            edge(&a, &handler, EdgeKind::Implements),
+            edge(&b, &handler, EdgeKind::Implements),
+            edge(&repo, &repo_get, EdgeKind::Contains),
+            edge(&sql, &sql_get, EdgeKind::Contains),
+        ];
+        let index = DispatchIndex::from_graph(&nodes, &edges, Some(dir.path()));
+
+        let mut from_store: Vec<&str> = index
+            .implementations(&store_save.id)
+            .map(|l| l.implementation.as_str())
+            .collect();
+        from_store.sort();
+        assert_eq!(
+            from_store,
+            vec![base_save.id.as_str(), disk_save.id.as_str()]
+        );
+        let to_disk: Vec<(&str, &str)> = index
+            .declarations(&disk_save.id)
+            .map(|l| (l.declaration.as_str(), l.via.as_str()))
+            .collect();
+        assert!(to_disk.contains(&(base_save.id.as_str(), "BaseStore")));
+        assert!(to_disk.contains(&(store_save.id.as_str(), "Store")));
+
+        let peer = index.implementations(&a_run.id).next().unwrap();
+        assert_eq!(
+            (peer.implementation.as_str(), peer.kind, peer.via.as_str()),
+            (b_run.id.as_str(), DispatchKind::Peer, "Handler")
+        );
+
+        let bound = index.declarations(&sql_get.id).next().unwrap();
+        assert_eq!(bound.declaration, repo_get.id);
+        assert_eq!(bound.kind, DispatchKind::Registration);
+        assert_eq!(index.registrations().len(), 1);
+        assert_eq!(index.registrations()[0].site, configure.id);
+        assert_eq!(index.registrations()[0].interface, Some(repo.id.clone()));
+    }
+
+    #[test]
+    fn around_finds_the_same_links_from_the_store() {
+        let store = GraphStore::from_connection(initialize_database(":memory:").unwrap());
+        let handler = node(NodeKind::Interface, "Handler", "h.ts", 1);
+        let a = node(NodeKind::Class, "A", "a.ts", 1);
+        let a_run = node(NodeKind::Method, "run", "a.ts", 2);
+        let b = node(NodeKind::Class, "B", "b.ts", 1);
+        let b_run = node(NodeKind::Method, "run", "b.ts", 2);
+        let b_stop = node(NodeKind::Method, "stop", "b.ts", 3);
+        store
+            .upsert_nodes(&[
+                handler.clone(),
+                a.clone(),
+                a_run.clone(),
+                b.clone(),
+                b_run.clone(),
+                b_stop.clone(),
+            ])
+            .unwrap();
+        store
+            .upsert_edges(&[
+                edge(&a, &a_run, EdgeKind::Contains),
+                edge(&b, &b_run, EdgeKind::Contains),
+                edge(&b, &b_stop, EdgeKind::Contains),
+                edge(&a, &handler, EdgeKind::Implements),
+                edge(&b, &handler, EdgeKind::Implements),
+            ])
+            .unwrap();
+
+        let index = DispatchIndex::around(&store, &b_run.id).unwrap();
+        let declarations: Vec<&str> = index
+            .declarations(&b_run.id)
+            .map(|l| l.declaration.as_str())
+            .collect();
+        assert_eq!(declarations, vec![a_run.id.as_str()]);
+        assert_eq!(index.implementations(&b_stop.id).count(), 0);
+    }
+}
@@ -3,10 +3,18 @@
 //! Inspects dependency manifests (package.json, Cargo.toml, go.mod, etc.)
 //! to detect which frameworks a project uses. Returns structured results
 //! with name, version, confidence, language, and category.
+//!
+//! [`find_routes`] reads the HTTP routes a source file declares with
+//! Express, FastAPI (and Flask), Axum or Spring; the extractor turns them
+//! into `route` nodes.
 
+use regex::Regex;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::Path;
+use std::sync::OnceLock;
+
+use crate::types::Language;
 
 // ---------------------------------------------------------------------------
 // Types
@@ -663,6 +671,248 @@ fn detect_from_build_gradle(path: &Path) -> Option<Vec<DetectedFramework>> {
     }
 }
 
+// ---------------------------------------------------------------------------
+// Routes
+// ---------------------------------------------------------------------------
+
+/// An HTTP route declared in a source file.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RouteDecl {
+    /// `express`, `fastapi`, `flask`, `axum` or `spring`.
+    pub framework: &'static str,
+    /// Upper-case HTTP method, `ANY` when the route takes every method.
+    pub method: String,
+    pub path: String,
+    /// 1-based line of the registration, decorator or annotation.
+    pub line: u32,
+    /// The handler a registration names. Decorated and annotated routes
+    /// leave it to the function that follows.
+    pub handler: Option<String>,
+}
+
+impl RouteDecl {
+    /// `GET /users/:id`, the name of the route's node.
+    pub fn name(&self) -> String {
+        format!("{} {}", self.method, self.path)
+    }
+}
+
+/// Express-style registrations on an app or router: group 1 the verb,
+/// 2 the path, 3 the arguments after it.
+fn express_route() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(
+            r#"\b(?:app|server|api|[\w$]*[Rr]outer|[\w$]*[Aa]pp)\.(get|post|put|delete|patch|all|head|options)\(\s*['"`](/[^'"`]*)['"`]\s*,(.*)"#,
+        )
+        .expect("valid regex")
+    })
+}
+
+/// The named handler closing an argument list: the last of
+/// `auth, users.list)`.
+fn trailing_handler() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(r"^\s*(?:[\w$.]+\s*,\s*)*([A-Za-z_$][\w$]*(?:\.[A-Za-z_$][\w$]*)*)\s*\)")
+            .expect("valid regex")
+    })
+}
+
+/// FastAPI and Flask decorators: group 1 the verb, 2 the path.
+fn python_route() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(
+            r#"^@\w+(?:\.\w+)*\.(get|post|put|delete|patch|head|options|route|api_route|websocket)\(\s*(?:path\s*=\s*)?['"]([^'"]*)['"]"#,
+        )
+        .expect("valid regex")
+    })
+}
+
+/// `methods=["GET", "POST"]` of a Flask `route` decorator.
+fn python_methods() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"methods\s*=\s*\[([^\]]*)\]").expect("valid regex"))
+}
+
+/// Axum `.route("/x", ...)`: group 1 the path, 2 the method router.
+fn axum_route() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r#"\.route\(\s*"([^"]*)"\s*,(.*)"#).expect("valid regex"))
+}
+
+/// One `get(handler)` of an Axum method router.
+fn axum_method() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(r"\b(get|post|put|delete|patch|head|options|any)\(\s*([\w:]+)\s*\)")
+            .expect("valid regex")
+    })
+}
+
+/// Spring mapping annotations: group 1 the verb, 2 the path.
+fn spring_mapping() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(
+            r#"^@(Get|Post|Put|Delete|Patch|Request)Mapping\b(?:\(\s*(?:(?:value|path)\s*=\s*)?\{?\s*"([^"]*)")?"#,
+        )
+        .expect("valid regex")
+    })
+}
+
+/// `method = RequestMethod.POST` of a `@RequestMapping`.
+fn spring_request_method() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"RequestMethod\.(\w+)").expect("valid regex"))
+}
+
+/// The HTTP routes `source` declares, in line order. Only the frameworks
+/// of `language` are looked for.
+pub fn find_routes(language: Language, source: &str) -> Vec<RouteDecl> {
+    let lines: Vec<&str> = source.lines().collect();
+    match language {
+        Language::TypeScript | Language::Tsx | Language::JavaScript | Language::Jsx => {
+            express_routes(&lines)
+        }
+        Language::Python => python_routes(&lines),
+        Language::Rust => axum_routes(&lines),
+        Language::Java | Language::Kotlin => spring_routes(&lines),
+        _ => Vec::new(),
+    }
+}
+
+fn express_routes(lines: &[&str]) -> Vec<RouteDecl> {
+    let mut routes = Vec::new();
+    for (i, line) in lines.iter().enumerate() {
+        for caps in express_route().captures_iter(line) {
+            let method = match caps[1].to_ascii_uppercase().as_str() {
+                "ALL" => "ANY".to_string(),
+                other => other.to_string(),
+            };
+            routes.push(RouteDecl {
+                framework: "express",
+                method,
+                path: caps[2].to_string(),
+                line: i as u32 + 1,
+                handler: trailing_handler()
+                    .captures(&caps[3])
+                    .map(|h| h[1].to_string()),
+            });
+        }
+    }
+    routes
+}
+
+fn python_routes(lines: &[&str]) -> Vec<RouteDecl> {
+    let mut routes = Vec::new();
+    for (i, line) in lines.iter().enumerate() {
+        let line = line.trim_start();
+        let Some(caps) = python_route().captures(line) else {
+            continue;
+        };
+        let verb = caps[1].to_ascii_uppercase();
+        let (framework, methods) = match verb.as_str() {
+            "ROUTE" => {
+                let listed: Vec<String> = python_methods()
+                    .captures(line)
+                    .map(|m| {
+                        m[1].split(',')
+                            .map(|v| v.trim().trim_matches(['"', '\'']).to_ascii_uppercase())
+                            .filter(|v| !v.is_empty())
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                let methods = if listed.is_empty() {
+                    vec!["GET".to_string()]
+                } else {
+                    listed
+                };
+                ("flask", methods)
+            }
+            "API_ROUTE" => ("fastapi", vec!["ANY".to_string()]),
+            "WEBSOCKET" => ("fastapi", vec!["WS".to_string()]),
+            _ => ("fastapi", vec![verb]),
+        };
+        for method in methods {
+            routes.push(RouteDecl {
+                framework,
+                method,
+                path: caps[2].to_string(),
+                line: i as u32 + 1,
+                handler: None,
+            });
+        }
+    }
+    routes
+}
+
+fn axum_routes(lines: &[&str]) -> Vec<RouteDecl> {
+    let mut routes = Vec::new();
+    for (i, line) in lines.iter().enumerate() {
+        for caps in axum_route().captures_iter(line) {
+            for method in axum_method().captures_iter(&caps[2]) {
+                routes.push(RouteDecl {
+                    framework: "axum",
+                    method: method[1].to_ascii_uppercase(),
+                    path: caps[1].to_string(),
+                    line: i as u32 + 1,
+                    handler: Some(method[2].to_string()),
+                });
+            }
+        }
+    }
+    routes
+}
+
+/// Spring routes, prefixed with the class-level `@RequestMapping` path.
+fn spring_routes(lines: &[&str]) -> Vec<RouteDecl> {
+    let mut routes = Vec::new();
+    let mut prefix = String::new();
+    for (i, line) in lines.iter().enumerate() {
+        let line = line.trim_start();
+        let Some(caps) = spring_mapping().captures(line) else {
+            continue;
+        };
+        let path = caps.get(2).map_or("", |p| p.as_str());
+        // The declaration the annotation belongs to: the next line that is
+        // not another annotation.
+        let on_class = lines[i + 1..]
+            .iter()
+            .map(|l| l.trim_start())
+            .find(|l| !l.is_empty() && !l.starts_with('@'))
+            .is_some_and(|l| {
+                l.split_whitespace()
+                    .any(|w| w == "class" || w == "interface")
+            });
+        if on_class {
+            prefix = path.trim_end_matches('/').to_string();
+            continue;
+        }
+        let method = match &caps[1] {
+            "Request" => spring_request_method()
+                .captures(line)
+                .map_or("ANY".to_string(), |m| m[1].to_ascii_uppercase()),
+            verb => verb.to_ascii_uppercase(),
+        };
+        let path = match (prefix.is_empty(), path.is_empty()) {
+            (_, true) if !prefix.is_empty() => prefix.clone(),
+            (true, true) => "/".to_string(),
+            _ if path.starts_with('/') => format!("{prefix}{path}"),
+            _ => format!("{prefix}/{path}"),
+        };
+        routes.push(RouteDecl {
+            framework: "spring",
+            method,
+            path,
+            line: i as u32 + 1,
+            handler: None,
+        });
+    }
+    routes
+}
+
 // ---------------------------------------------------------------------------
 // Tests
 // ---------------------------------------------------------------------------
@@ -1033,4 +1283,75 @@ tokio = "1"
         assert_eq!(back.name, "React");
         assert_eq!(back.confidence, 0.95);
     }
+
+    // -- routes ---------------------------------------------------------------
+
+    fn route_summary(language: Language, source: &str) -> Vec<(String, u32, Option<String>)> {
+        find_routes(language, source)
+            .into_iter()
+            .map(|r| (format!("{} {}", r.framework, r.name()), r.line, r.handler))
+            .collect()
+    }
+
+    #[test]
+    fn finds_express_and_fastapi_routes() {
+        let express = "const router = express.Router();\n\
+                       router.get('/users/:id', auth, users.show);\n\
+                       app.post(\"/login\", (req, res) => res.send());\n\
+                       axios.get('/api/users', config);\n";
+        assert_eq!(
+            route_summary(Language::TypeScript, express),
+            vec![
+                (
+                    "express GET /users/:id".to_string(),
+                    2,
+                    Some("users.show".to_string())
+                ),
+                ("express POST /login".to_string(), 3, None),
+            ]
+        );
+
+        let python = "@app.get(\"/items/{id}\")\nasync def read_item(id: int):\n    ...\n\n\
+                      @bp.route('/upload', methods=['POST', 'PUT'])\ndef upload():\n    ...\n";
+        assert_eq!(
+            route_summary(Language::Python, python),
+            vec![
+                ("fastapi GET /items/{id}".to_string(), 1, None),
+                ("flask POST /upload".to_string(), 5, None),
+                ("flask PUT /upload".to_string(), 5, None),
+            ]
+        );
+    }
+
+    #[test]
+    fn finds_axum_and_spring_routes() {
+        let rust =
+            "Router::new()\n    .route(\"/users\", get(list_users).post(handlers::create_user))\n";
+        assert_eq!(
+            route_summary(Language::Rust, rust),
+            vec![
+                (
+                    "axum GET /users".to_string(),
+                    2,
+                    Some("list_users".to_string())
+                ),
+                (
+                    "axum POST /users".to_string(),
+                    2,
+                    Some("handlers::create_user".to_string())
+                ),
+            ]
+        );
+
+        let java = "@RestController\n@RequestMapping(\"/api/orders\")\npublic class OrderController {\n\
+                    \x20   @GetMapping(\"/{id}\")\n    public Order get(@PathVariable long id) { return null; }\n\
+                    \x20   @RequestMapping(method = RequestMethod.DELETE)\n    public void clear() {}\n}\n";
+        assert_eq!(
+            route_summary(Language::Java, java),
+            vec![
+                ("spring GET /api/orders/{id}".to_string(), 4, None),
+                ("spring DELETE /api/orders".to_string(), 6, None),
+            ]
+        );
+    }
 }
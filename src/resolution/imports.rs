@@ -14,10 +14,15 @@
 //!    - Create direct `Imports` edges from the importing file to each named symbol
 //! 3. When no names are specified (e.g., `import * as utils from './utils'`):
 //!    - Create an `Imports` edge from the file to all exported symbols in the target
+//!
+//! In a monorepo, imports of sibling packages are resolved the same way
+//! through the [`WorkspaceLayout`] read from the workspace manifests:
+//! tsconfig `paths`, Cargo workspace members and the modules of `go.work`.
 
 use std::collections::{HashMap, HashSet};
-use std::path::{Component, PathBuf};
+use std::path::{Component, Path, PathBuf};
 
+use crate::resolution::projects::toml_section_value;
 use crate::types::{CodeEdge, CodeNode, EdgeKind, UnresolvedRef};
 
 /// Result of import resolution: both successfully resolved edges and
@@ -62,47 +67,59 @@ pub fn resolve_imports(
     indexed_files: &HashSet<String>,
     node_index: &HashMap<String, Vec<CodeNode>>,
     nodes_by_file: &HashMap<String, Vec<CodeNode>>,
+) -> ImportResolutionResult {
+    resolve_workspace_imports(
+        edges,
+        indexed_files,
+        node_index,
+        nodes_by_file,
+        &WorkspaceLayout::default(),
+    )
+}
+
+/// [`resolve_imports`], also resolving imports of the other packages of a
+/// monorepo through its `layout`.
+pub fn resolve_workspace_imports(
+    edges: &[CodeEdge],
+    indexed_files: &HashSet<String>,
+    node_index: &HashMap<String, Vec<CodeNode>>,
+    nodes_by_file: &HashMap<String, Vec<CodeNode>>,
+    layout: &WorkspaceLayout,
 ) -> ImportResolutionResult {
     let mut resolved_edges = Vec::new();
     let mut unresolved_refs = Vec::new();
 
+    // A Go import names a package, which is every file of its directory.
+    let mut go_packages: HashMap<&str, Vec<String>> = HashMap::new();
+    if !layout.go_modules.is_empty() {
+        for file in indexed_files {
+            if file.ends_with(".go") && !file.ends_with("_test.go") {
+                go_packages
+                    .entry(parent_dir(file))
+                    .or_default()
+                    .push(file.clone());
+            }
+        }
+        for files in go_packages.values_mut() {
+            files.sort();
+        }
+    }
+
     for edge in edges {
         if edge.kind != EdgeKind::Imports {
             continue;
         }
 
-        // Only process module:<specifier> targets with relative paths
+        // Only process module:<specifier> targets
         let specifier = match edge.target.strip_prefix("module:") {
             Some(s) => s,
             None => continue,
         };
 
-        // Determine if this is a resolvable import
-        let importing_file = edge.file_path.as_str();
-        let resolved_path;
-
-        if is_relative_import(specifier) {
-            // Resolve relative imports (./  ../)
-            resolved_path = match resolve_specifier(importing_file, specifier, indexed_files) {
-                Some(p) => p,
-                None => {
-                    unresolved_refs.push(UnresolvedRef {
-                        id: 0,
-                        source_id: edge.source.clone(),
-                        specifier: specifier.to_string(),
-                        ref_type: "import".to_string(),
-                        file_path: edge.file_path.clone(),
-                        line: edge.line,
-                    });
-                    continue;
-                }
-            };
-        } else if is_path_alias(specifier) {
-            // Resolve path aliases (@/  ~/) by mapping to src/ prefix
-            let alias_path = resolve_path_alias(specifier);
-            resolved_path = match resolve_alias_path(&alias_path, indexed_files) {
-                Some(p) => p,
-                None => {
+        let (resolved_paths, imported_name) =
+            match import_target(edge, specifier, indexed_files, &go_packages, layout) {
+                ImportTarget::External => continue,
+                ImportTarget::Missing => {
                     unresolved_refs.push(UnresolvedRef {
                         id: 0,
                         source_id: edge.source.clone(),
@@ -113,75 +130,31 @@ pub fn resolve_imports(
                     });
                     continue;
                 }
+                ImportTarget::Files(paths, name) => (paths, name),
             };
-        } else {
-            // Package/absolute imports — skip
-            continue;
-        };
 
         // Extract imported names from metadata
-        let imported_names: Vec<&str> = edge
-            .metadata
-            .as_ref()
-            .and_then(|m| m.get("names"))
-            .map(|names| names.split(',').map(|s| s.trim()).collect())
-            .unwrap_or_default();
+        let imported_names: Vec<&str> = match &imported_name {
+            Some(name) => vec![name.as_str()],
+            None => edge
+                .metadata
+                .as_ref()
+                .and_then(|m| m.get("names"))
+                .map(|names| names.split(',').map(|s| s.trim()).collect())
+                .unwrap_or_default(),
+        };
 
-        let target_nodes = nodes_by_file.get(&resolved_path);
+        for resolved_path in resolved_paths {
+            let target_nodes = nodes_by_file.get(&resolved_path);
 
-        if imported_names.is_empty() {
-            // Wildcard/default import: link to all exported symbols in target file
-            if let Some(target_file_nodes) = target_nodes {
-                for target_node in target_file_nodes {
-                    if target_node.exported == Some(true) {
-                        resolved_edges.push(CodeEdge {
-                            source: edge.source.clone(),
-                            target: target_node.id.clone(),
-                            kind: EdgeKind::Imports,
-                            file_path: edge.file_path.clone(),
-                            line: edge.line,
-                            metadata: Some(
-                                [("resolved".to_string(), resolved_path.clone())]
-                                    .into_iter()
-                                    .collect(),
-                            ),
-                        });
-                    }
-                }
-            }
-        } else {
-            // Named imports: link to specific symbols
-            for name in &imported_names {
-                // First try: find by name in the target file
-                let target_node =
-                    target_nodes.and_then(|nodes| nodes.iter().find(|n| n.name == *name));
-
-                if let Some(target) = target_node {
-                    resolved_edges.push(CodeEdge {
-                        source: edge.source.clone(),
-                        target: target.id.clone(),
-                        kind: EdgeKind::Imports,
-                        file_path: edge.file_path.clone(),
-                        line: edge.line,
-                        metadata: Some(
-                            [("resolved".to_string(), resolved_path.clone())]
-                                .into_iter()
-                                .collect(),
-                        ),
-                    });
-                } else {
-                    // Second try: look up in global node index
-                    if let Some(candidates) = node_index.get(*name) {
-                        // Prefer the candidate from the resolved file
-                        let best = candidates
-                            .iter()
-                            .find(|n| n.file_path == resolved_path)
-                            .or_else(|| candidates.first());
-
-                        if let Some(target) = best {
+            if imported_names.is_empty() {
+                // Wildcard/default import: link to all exported symbols in target file
+                if let Some(target_file_nodes) = target_nodes {
+                    for target_node in target_file_nodes {
+                        if target_node.exported == Some(true) {
                             resolved_edges.push(CodeEdge {
                                 source: edge.source.clone(),
-                                target: target.id.clone(),
+                                target: target_node.id.clone(),
                                 kind: EdgeKind::Imports,
                                 file_path: edge.file_path.clone(),
                                 line: edge.line,
@@ -194,6 +167,52 @@ pub fn resolve_imports(
                         }
                     }
                 }
+            } else {
+                // Named imports: link to specific symbols
+                for name in &imported_names {
+                    // First try: find by name in the target file
+                    let target_node =
+                        target_nodes.and_then(|nodes| nodes.iter().find(|n| n.name == *name));
+
+                    if let Some(target) = target_node {
+                        resolved_edges.push(CodeEdge {
+                            source: edge.source.clone(),
+                            target: target.id.clone(),
+                            kind: EdgeKind::Imports,
+                            file_path: edge.file_path.clone(),
+                            line: edge.line,
+                            metadata: Some(
+                                [("resolved".to_string(), resolved_path.clone())]
+                                    .into_iter()
+                                    .collect(),
+                            ),
+                        });
+                    } else {
+                        // Second try: look up in global node index
+                        if let Some(candidates) = node_index.get(*name) {
+                            // Prefer the candidate from the resolved file
+                            let best = candidates
+                                .iter()
+                                .find(|n| n.file_path == resolved_path)
+                                .or_else(|| candidates.first());
+
+                            if let Some(target) = best {
+                                resolved_edges.push(CodeEdge {
+                                    source: edge.source.clone(),
+                                    target: target.id.clone(),
+                                    kind: EdgeKind::Imports,
+                                    file_path: edge.file_path.clone(),
+                                    line: edge.line,
+                                    metadata: Some(
+                                        [("resolved".to_string(), resolved_path.clone())]
+                                            .into_iter()
+                                            .collect(),
+                                    ),
+                                });
+                            }
+                        }
+                    }
+                }
             }
         }
     }
@@ -204,6 +223,76 @@ pub fn resolve_imports(
     }
 }
 
+/// Where an import specifier points.
+enum ImportTarget {
+    /// A package outside the project, left alone.
+    External,
+    /// Inside the project, but no indexed file matches.
+    Missing,
+    /// Indexed files, with the one name imported from them when the import
+    /// path itself names it (a Rust `use`) rather than the edge metadata.
+    Files(Vec<String>, Option<String>),
+}
+
+fn import_target(
+    edge: &CodeEdge,
+    specifier: &str,
+    indexed_files: &HashSet<String>,
+    go_packages: &HashMap<&str, Vec<String>>,
+    layout: &WorkspaceLayout,
+) -> ImportTarget {
+    let single = |path: Option<String>| match path {
+        Some(p) => ImportTarget::Files(vec![p], None),
+        None => ImportTarget::Missing,
+    };
+    let file = edge.file_path.as_str();
+    if is_relative_import(specifier) {
+        // Resolve relative imports (./  ../)
+        return single(resolve_specifier(file, specifier, indexed_files));
+    }
+    if is_script_file(file) {
+        if let Some(candidates) = layout.ts_path_candidates(specifier) {
+            return single(
+                candidates
+                    .iter()
+                    .find_map(|c| resolve_alias_path(c, indexed_files)),
+            );
+        }
+    }
+    if is_path_alias(specifier) {
+        // Resolve path aliases (@/  ~/) by mapping to src/ prefix
+        return single(resolve_alias_path(
+            &resolve_path_alias(specifier),
+            indexed_files,
+        ));
+    }
+    if file.ends_with(".rs") {
+        let path = edge.metadata.as_ref().and_then(|m| m.get("path"));
+        if let Some(target) =
+            layout.crate_target(path.map(String::as_str), specifier, indexed_files)
+        {
+            return target;
+        }
+    }
+    if file.ends_with(".go") {
+        if let Some(dir) = layout.go_package_dir(specifier) {
+            return match go_packages.get(dir.as_str()) {
+                Some(files) => ImportTarget::Files(files.clone(), None),
+                None => ImportTarget::Missing,
+            };
+        }
+    }
+    // Package/absolute imports — skip
+    ImportTarget::External
+}
+
+/// JavaScript and TypeScript files, the ones tsconfig `paths` apply to.
+fn is_script_file(file_path: &str) -> bool {
+    [".ts", ".tsx", ".mts", ".cts", ".js", ".jsx", ".mjs", ".cjs"]
+        .iter()
+        .any(|ext| file_path.ends_with(ext))
+}
+
 /// Check if an import specifier is a relative path.
 fn is_relative_import(specifier: &str) -> bool {
     specifier.starts_with("./") || specifier.starts_with("../")
@@ -407,6 +496,335 @@ fn normalize_path(path: &str) -> String {
     components.join("/")
 }
 
+/// `dir/rest` for root-relative paths, where the root itself is `""`.
+fn join_path(dir: &str, rest: &str) -> String {
+    normalize_path(&format!("{}/{}", dir, rest))
+}
+
+/// The directory part of a root-relative file path, `""` at the root.
+fn parent_dir(file_path: &str) -> &str {
+    file_path.rsplit_once('/').map_or("", |(dir, _)| dir)
+}
+
+// ---------------------------------------------------------------------------
+// Monorepo layout
+// ---------------------------------------------------------------------------
+
+/// Package boundaries of a monorepo, read from its workspace manifests at
+/// the repository root:
+///
+/// - tsconfig `compilerOptions.paths` (against `baseUrl`) from
+///   `tsconfig.json` and `tsconfig.base.json`,
+/// - Cargo workspace members, keyed by crate name,
+/// - Go modules listed in `go.work`, plus the root `go.mod`.
+///
+/// All paths are relative to the repository root. An empty layout resolves
+/// nothing beyond relative imports and `@/` aliases.
+#[derive(Debug, Clone, Default)]
+pub struct WorkspaceLayout {
+    /// tsconfig path patterns with their targets, longest prefix first.
+    ts_paths: Vec<(String, Vec<String>)>,
+    /// Crate name as written in `use` paths → its library root file.
+    crates: HashMap<String, String>,
+    /// Go module path → module directory, longest module path first.
+    go_modules: Vec<(String, String)>,
+}
+
+impl WorkspaceLayout {
+    /// Read the layout of the repository at `root`. Missing or malformed
+    /// manifests are skipped.
+    pub fn load(root: &Path) -> Self {
+        let mut layout = Self::default();
+        for name in ["tsconfig.json", "tsconfig.base.json"] {
+            layout.read_tsconfig(root, name);
+        }
+        layout.ts_paths.sort_by_key(|(pattern, _)| {
+            std::cmp::Reverse(pattern.find('*').unwrap_or(pattern.len()))
+        });
+
+        let cargo = std::fs::read_to_string(root.join("Cargo.toml")).unwrap_or_default();
+        let mut members = toml_array(&cargo, "workspace", "members");
+        if toml_section_value(&cargo, &["package"], "name").is_some() {
+            members.push(".".to_string());
+        }
+        for member in members {
+            for dir in expand_member(root, &member) {
+                layout.read_crate(root, &dir);
+            }
+        }
+
+        let go_work = std::fs::read_to_string(root.join("go.work")).unwrap_or_default();
+        let mut go_dirs = go_work_dirs(&go_work);
+        go_dirs.push(String::new());
+        for dir in go_dirs {
+            let go_mod =
+                std::fs::read_to_string(root.join(&dir).join("go.mod")).unwrap_or_default();
+            let module = go_mod
+                .lines()
+                .find_map(|l| l.trim().strip_prefix("module "))
+                .map(|m| m.trim().trim_matches('"').to_string());
+            if let Some(module) = module {
+                if !layout.go_modules.iter().any(|(m, _)| *m == module) {
+                    layout.go_modules.push((module, dir));
+                }
+            }
+        }
+        layout
+            .go_modules
+            .sort_by_key(|(module, _)| std::cmp::Reverse(module.len()));
+        layout
+    }
+
+    fn read_tsconfig(&mut self, root: &Path, file_name: &str) {
+        let Ok(content) = std::fs::read_to_string(root.join(file_name)) else {
+            return;
+        };
+        let Ok(config) = serde_json::from_str::<serde_json::Value>(&strip_jsonc(&content)) else {
+            return;
+        };
+        let options = &config["compilerOptions"];
+        let base = options["baseUrl"].as_str().unwrap_or(".");
+        let Some(paths) = options["paths"].as_object() else {
+            return;
+        };
+        for (pattern, targets) in paths {
+            if self.ts_paths.iter().any(|(p, _)| p == pattern) {
+                continue;
+            }
+            let targets = targets
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|t| t.as_str())
+                .map(|t| join_path(base, t))
+                .collect();
+            self.ts_paths.push((pattern.clone(), targets));
+        }
+    }
+
+    fn read_crate(&mut self, root: &Path, dir: &str) {
+        let Ok(manifest) = std::fs::read_to_string(root.join(dir).join("Cargo.toml")) else {
+            return;
+        };
+        let Some(name) = toml_section_value(&manifest, &["lib"], "name")
+            .or_else(|| toml_section_value(&manifest, &["package"], "name"))
+        else {
+            return;
+        };
+        let lib = toml_section_value(&manifest, &["lib"], "path")
+            .unwrap_or_else(|| "src/lib.rs".to_string());
+        self.crates
+            .entry(name.replace('-', "_"))
+            .or_insert_with(|| join_path(dir, &lib));
+    }
+
+    /// Paths a tsconfig `paths` pattern maps `specifier` to, or `None` when
+    /// no pattern matches.
+    fn ts_path_candidates(&self, specifier: &str) -> Option<Vec<String>> {
+        self.ts_paths.iter().find_map(|(pattern, targets)| {
+            let star = match pattern.split_once('*') {
+                Some((prefix, suffix)) => specifier.strip_prefix(prefix)?.strip_suffix(suffix)?,
+                None if pattern == specifier => "",
+                None => return None,
+            };
+            Some(targets.iter().map(|t| t.replacen('*', star, 1)).collect())
+        })
+    }
+
+    /// The file a Rust `use` of a workspace crate points at. `path` is the
+    /// `use` path before the imported `name`: `shared::util` in
+    /// `use shared::util::helper`. A module is imported whole, an item is
+    /// looked up in the innermost module file that is indexed.
+    fn crate_target(
+        &self,
+        path: Option<&str>,
+        name: &str,
+        indexed_files: &HashSet<String>,
+    ) -> Option<ImportTarget> {
+        let mut segments: Vec<&str> = path
+            .map(|p| p.split("::").filter(|s| !s.is_empty()).collect())
+            .unwrap_or_default();
+        segments.push(name);
+        let lib = self.crates.get(segments[0])?;
+        let modules = &segments[1..];
+        let module_file = |depth: usize| -> Option<String> {
+            if depth == 0 {
+                return indexed_files.contains(lib).then(|| lib.clone());
+            }
+            let base = join_path(parent_dir(lib), &modules[..depth].join("/"));
+            [format!("{}.rs", base), format!("{}/mod.rs", base)]
+                .into_iter()
+                .find(|c| indexed_files.contains(c))
+        };
+        if let Some(file) = module_file(modules.len()) {
+            return Some(ImportTarget::Files(vec![file], None));
+        }
+        let target = (0..modules.len())
+            .rev()
+            .find_map(module_file)
+            .map_or(ImportTarget::Missing, |file| {
+                ImportTarget::Files(vec![file], Some(name.to_string()))
+            });
+        Some(target)
+    }
+
+    /// The package directory a Go import of one of the workspace's modules
+    /// points at.
+    fn go_package_dir(&self, specifier: &str) -> Option<String> {
+        self.go_modules.iter().find_map(|(module, dir)| {
+            let rest = specifier.strip_prefix(module.as_str())?;
+            if !rest.is_empty() && !rest.starts_with('/') {
+                return None;
+            }
+            Some(join_path(dir, rest))
+        })
+    }
+}
+
+/// The quoted strings of a TOML array `key = [...]` in `[section]`, which
+/// may span several lines.
+fn toml_array(content: &str, section: &str, key: &str) -> Vec<String> {
+    let mut in_section = false;
+    let mut lines = content.lines();
+    while let Some(line) = lines.next() {
+        let t = line.trim();
+        if t.starts_with('[') && !t.starts_with("[[") && t.ends_with(']') {
+            in_section = t.trim_matches(['[', ']']).trim() == section;
+            continue;
+        }
+        let Some((k, v)) = t.split_once('=') else {
+            continue;
+        };
+        if !in_section || k.trim() != key {
+            continue;
+        }
+        let mut array = v.to_string();
+        while !array.contains(']') {
+            match lines.next() {
+                Some(next) => array.push_str(next),
+                None => break,
+            }
+        }
+        return array
+            .split(['"', '\''])
+            .skip(1)
+            .step_by(2)
+            .map(String::from)
+            .collect();
+    }
+    Vec::new()
+}
+
+/// Directories of a Cargo workspace member, expanding a trailing `/*` to
+/// the subdirectories that hold a `Cargo.toml`.
+fn expand_member(root: &Path, member: &str) -> Vec<String> {
+    let Some(parent) = member.strip_suffix("/*") else {
+        return vec![normalize_path(member)];
+    };
+    let mut dirs: Vec<String> = std::fs::read_dir(root.join(parent))
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|e| e.path().join("Cargo.toml").is_file())
+        .map(|e| join_path(parent, &e.file_name().to_string_lossy()))
+        .collect();
+    dirs.sort();
+    dirs
+}
+
+/// Module directories of a `go.work` file, from `use ./dir` lines and
+/// `use ( ... )` blocks.
+fn go_work_dirs(content: &str) -> Vec<String> {
+    let mut dirs = Vec::new();
+    let mut in_block = false;
+    for line in content.lines() {
+        let t = line.split("//").next().unwrap_or_default().trim();
+        let dir = if in_block {
+            if t == ")" {
+                in_block = false;
+                continue;
+            }
+            t
+        } else if let Some(rest) = t.strip_prefix("use") {
+            let rest = rest.trim();
+            if rest == "(" {
+                in_block = true;
+                continue;
+            }
+            rest
+        } else {
+            continue;
+        };
+        if !dir.is_empty() {
+            dirs.push(normalize_path(dir.trim_matches('"')));
+        }
+    }
+    dirs
+}
+
+/// JSON with comments and trailing commas, as tsconfig files allow, made
+/// plain JSON.
+fn strip_jsonc(content: &str) -> String {
+    let mut out = String::with_capacity(content.len());
+    let mut chars = content.chars().peekable();
+    let mut in_string = false;
+    while let Some(c) = chars.next() {
+        if in_string {
+            out.push(c);
+            match c {
+                '\\' => out.extend(chars.next()),
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match (c, chars.peek()) {
+            ('"', _) => {
+                in_string = true;
+                out.push(c);
+            }
+            ('/', Some('/')) => {
+                for next in chars.by_ref() {
+                    if next == '\n' {
+                        out.push(next);
+                        break;
+                    }
+                }
+            }
+            ('/', Some('*')) => {
+                chars.next();
+                let mut prev = ' ';
+                for next in chars.by_ref() {
+                    if prev == '*' && next == '/' {
+                        break;
+                    }
+                    prev = next;
+                }
+            }
+            _ => out.push(c),
+        }
+    }
+    // Trailing commas: a comma followed only by whitespace before `}` or `]`.
+    let mut plain = String::with_capacity(out.len());
+    let mut in_string = false;
+    let bytes: Vec<char> = out.chars().collect();
+    for (i, &c) in bytes.iter().enumerate() {
+        if in_string {
+            in_string =
+                !(c == '"' && bytes[..i].iter().rev().take_while(|&&b| b == '\\').count() % 2 == 0);
+        } else if c == '"' {
+            in_string = true;
+        } else if c == ',' {
+            let next = bytes[i + 1..].iter().find(|b| !b.is_whitespace());
+            if matches!(next, Some('}') | Some(']')) {
+                continue;
+            }
+        }
+        plain.push(c);
+    }
+    plain
+}
+
 // ---------------------------------------------------------------------------
 // Tests
 // ---------------------------------------------------------------------------
@@ -1113,4 +1531,231 @@ mod tests {
             .iter()
             .any(|e| e.target == "fn:src/lib/impl.ts:baz:20"));
     }
+
+    // -- monorepo layout ------------------------------------------------------
+
+    fn write(root: &Path, rel: &str, content: &str) {
+        let path = root.join(rel);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    }
+
+    /// Index and node lookups over `nodes`, with `extra_files` indexed too.
+    fn index(
+        nodes: &[CodeNode],
+        extra_files: &[&str],
+    ) -> (
+        HashSet<String>,
+        HashMap<String, Vec<CodeNode>>,
+        HashMap<String, Vec<CodeNode>>,
+    ) {
+        let mut indexed_files: HashSet<String> =
+            extra_files.iter().map(|f| f.to_string()).collect();
+        let mut node_index: HashMap<String, Vec<CodeNode>> = HashMap::new();
+        let mut nodes_by_file: HashMap<String, Vec<CodeNode>> = HashMap::new();
+        for node in nodes {
+            indexed_files.insert(node.file_path.clone());
+            node_index
+                .entry(node.name.clone())
+                .or_default()
+                .push(node.clone());
+            nodes_by_file
+                .entry(node.file_path.clone())
+                .or_default()
+                .push(node.clone());
+        }
+        (indexed_files, node_index, nodes_by_file)
+    }
+
+    #[test]
+    fn resolves_tsconfig_paths() {
+        let tmp = tempfile::tempdir().unwrap();
+        write(
+            tmp.path(),
+            "tsconfig.base.json",
+            r#"{
+  // Shared by every package
+  "compilerOptions": {
+    "baseUrl": ".",
+    "paths": {
+      "@acme/shared": ["packages/shared/src/index.ts"],
+      "@app/*": ["apps/web/src/*"], /* app-local */
+    },
+  },
+}"#,
+        );
+        let layout = WorkspaceLayout::load(tmp.path());
+
+        let nodes = [
+            make_node(
+                "fn:packages/shared/src/index.ts:format:1",
+                "format",
+                "packages/shared/src/index.ts",
+                NodeKind::Function,
+                Some(true),
+            ),
+            make_node(
+                "fn:apps/web/src/utils/date.ts:parse:1",
+                "parse",
+                "apps/web/src/utils/date.ts",
+                NodeKind::Function,
+                Some(true),
+            ),
+        ];
+        let (indexed_files, node_index, nodes_by_file) = index(&nodes, &["apps/web/src/main.ts"]);
+        let edges = vec![
+            make_import_edge("apps/web/src/main.ts", "@acme/shared", 1, Some("format")),
+            make_import_edge("apps/web/src/main.ts", "@app/utils/date", 2, Some("parse")),
+            make_import_edge("apps/web/src/main.ts", "@app/missing", 3, Some("x")),
+            make_import_edge("apps/web/src/main.ts", "react", 4, Some("useState")),
+        ];
+
+        let result =
+            resolve_workspace_imports(&edges, &indexed_files, &node_index, &nodes_by_file, &layout);
+        let targets: Vec<&str> = result
+            .resolved_edges
+            .iter()
+            .map(|e| e.target.as_str())
+            .collect();
+        assert_eq!(
+            targets,
+            vec![
+                "fn:packages/shared/src/index.ts:format:1",
+                "fn:apps/web/src/utils/date.ts:parse:1",
+            ]
+        );
+        assert_eq!(result.unresolved_refs.len(), 1);
+        assert_eq!(result.unresolved_refs[0].specifier, "@app/missing");
+    }
+
+    #[test]
+    fn resolves_cargo_workspace_crates() {
+        let tmp = tempfile::tempdir().unwrap();
+        write(
+            tmp.path(),
+            "Cargo.toml",
+            "[workspace]\nmembers = [\n    \"crates/*\",\n]\n",
+        );
+        write(
+            tmp.path(),
+            "crates/shared-lib/Cargo.toml",
+            "[package]\nname = \"shared-lib\"\n",
+        );
+        write(
+            tmp.path(),
+            "crates/app/Cargo.toml",
+            "[package]\nname = \"app\"\n",
+        );
+        let layout = WorkspaceLayout::load(tmp.path());
+
+        let nodes = [
+            make_node(
+                "struct:crates/shared-lib/src/lib.rs:Config:1",
+                "Config",
+                "crates/shared-lib/src/lib.rs",
+                NodeKind::Struct,
+                Some(true),
+            ),
+            make_node(
+                "fn:crates/shared-lib/src/util.rs:helper:1",
+                "helper",
+                "crates/shared-lib/src/util.rs",
+                NodeKind::Function,
+                Some(true),
+            ),
+        ];
+        let (indexed_files, node_index, nodes_by_file) = index(&nodes, &["crates/app/src/main.rs"]);
+        let use_edge = |path: &str, name: &str| CodeEdge {
+            metadata: Some(
+                [("path".to_string(), path.to_string())]
+                    .into_iter()
+                    .collect(),
+            ),
+            ..make_import_edge("crates/app/src/main.rs", name, 1, None)
+        };
+        let edges = vec![
+            use_edge("shared_lib::util", "helper"),
+            use_edge("shared_lib", "Config"),
+            use_edge("shared_lib", "util"),
+            use_edge("serde", "Deserialize"),
+        ];
+
+        let result =
+            resolve_workspace_imports(&edges, &indexed_files, &node_index, &nodes_by_file, &layout);
+        let targets: Vec<&str> = result
+            .resolved_edges
+            .iter()
+            .map(|e| e.target.as_str())
+            .collect();
+        // `use shared_lib::util` imports the whole module.
+        assert_eq!(
+            targets,
+            vec![
+                "fn:crates/shared-lib/src/util.rs:helper:1",
+                "struct:crates/shared-lib/src/lib.rs:Config:1",
+                "fn:crates/shared-lib/src/util.rs:helper:1",
+            ]
+        );
+        assert!(result.unresolved_refs.is_empty());
+    }
+
+    #[test]
+    fn resolves_go_work_modules() {
+        let tmp = tempfile::tempdir().unwrap();
+        write(
+            tmp.path(),
+            "go.work",
+            "go 1.22\n\nuse (\n\t./svc\n\t./libs/util // shared helpers\n)\n",
+        );
+        write(tmp.path(), "svc/go.mod", "module example.com/svc\n");
+        write(tmp.path(), "libs/util/go.mod", "module example.com/util\n");
+        let layout = WorkspaceLayout::load(tmp.path());
+
+        let nodes = [
+            make_node(
+                "fn:libs/util/strs/pad.go:Pad:1",
+                "Pad",
+                "libs/util/strs/pad.go",
+                NodeKind::Function,
+                Some(true),
+            ),
+            make_node(
+                "fn:libs/util/strs/trim.go:Trim:1",
+                "Trim",
+                "libs/util/strs/trim.go",
+                NodeKind::Function,
+                Some(true),
+            ),
+            make_node(
+                "fn:libs/util/strs/pad_test.go:TestPad:1",
+                "TestPad",
+                "libs/util/strs/pad_test.go",
+                NodeKind::Function,
+                Some(true),
+            ),
+        ];
+        let (indexed_files, node_index, nodes_by_file) = index(&nodes, &["svc/main.go"]);
+        let edges = vec![
+            make_import_edge("svc/main.go", "example.com/util/strs", 3, None),
+            make_import_edge("svc/main.go", "example.com/util/nope", 4, None),
+            make_import_edge("svc/main.go", "fmt", 5, None),
+        ];
+
+        let result =
+            resolve_workspace_imports(&edges, &indexed_files, &node_index, &nodes_by_file, &layout);
+        let targets: Vec<&str> = result
+            .resolved_edges
+            .iter()
+            .map(|e| e.target.as_str())
+            .collect();
+        assert_eq!(
+            targets,
+            vec![
+                "fn:libs/util/strs/pad.go:Pad:1",
+                "fn:libs/util/strs/trim.go:Trim:1",
+            ]
+        );
+        assert_eq!(result.unresolved_refs.len(), 1);
+        assert_eq!(result.unresolved_refs[0].specifier, "example.com/util/nope");
+    }
 }
@@ -0,0 +1,659 @@
+//! String literal and named constant cross-reference.
+//!
+//! Records every string literal in a file with the symbol it appears in,
+//! and every named constant with a literal value (`const TIMEOUT = 30`,
+//! `static final String CODE = "E42"`, `MAX_RETRIES = 3`, `#define LIMIT
+//! 64`). Agents can then trace a user-visible message, error code or magic
+//! value back to the code without grepping.
+//!
+//! Extraction is lexical: a small scanner skips comments and finds quoted
+//! strings per language family; constants are recognized line by line.
+//! Import specifiers, docstrings and multi-line strings are not recorded,
+//! and only constants outside functions count as named constants.
+
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::types::{CodeNode, Language, NodeKind};
+
+// ---------------------------------------------------------------------------
+// Constants
+// ---------------------------------------------------------------------------
+
+/// Longest literal value recorded; longer strings are templates and prose
+/// rather than something anyone searches for verbatim.
+const MAX_VALUE_LEN: usize = 200;
+
+/// Lines whose strings name modules rather than carry values.
+const IMPORT_LINE_PATTERN: &str = r#"^\s*(?:import\b|from\s+\S+\s+import\b|export\s.*\bfrom\s*['"]|#\s*include\b|use\s|package\s|require(?:_relative)?\b|load\s*\(|using\s)|\brequire\s*\(|\bimport\s*\("#;
+
+/// A literal value: a quoted string or a number.
+const LITERAL: &str = r#""(?:[^"\\]|\\.)*"|'(?:[^'\\]|\\.)*'|-?(?:0[xX][0-9a-fA-F_]+|\d[\d_]*(?:\.\d+)?(?:[eE][+-]?\d+)?)[a-zA-Z]*"#;
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// What a recorded literal is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LiteralKind {
+    /// A string literal at its use site.
+    String,
+    /// A named constant initialized with a literal.
+    Constant,
+}
+
+impl LiteralKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::String => "string",
+            Self::Constant => "constant",
+        }
+    }
+
+    pub fn from_str_loose(s: &str) -> Option<Self> {
+        match s {
+            "string" => Some(Self::String),
+            "constant" => Some(Self::Constant),
+            _ => None,
+        }
+    }
+}
+
+/// A string literal or named constant.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Literal {
+    pub file_path: String,
+    pub line: u32,
+    pub kind: LiteralKind,
+    /// Text between the quotes (escapes kept as written), or the literal
+    /// text of a numeric constant.
+    pub value: String,
+    /// Constant name; `None` for string literals.
+    pub name: Option<String>,
+    /// Innermost symbol whose span contains the line.
+    pub node_id: Option<String>,
+}
+
+/// A line in a function body that mentions a constant by name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConstantUse {
+    pub node_id: String,
+    pub symbol: String,
+    pub file_path: String,
+    pub line: u32,
+}
+
+// ---------------------------------------------------------------------------
+// Extraction
+// ---------------------------------------------------------------------------
+
+/// Find the string literals and named constants in one file. `nodes` are
+/// the file's extracted symbols, used to attribute each literal.
+pub fn extract_literals(
+    file_path: &str,
+    language: Language,
+    source: &str,
+    nodes: &[CodeNode],
+) -> Vec<Literal> {
+    let Some(syntax) = Syntax::of(language) else {
+        return Vec::new();
+    };
+    let lines: Vec<&str> = source.lines().collect();
+    static IMPORT_LINE: OnceLock<Regex> = OnceLock::new();
+    let import_line =
+        IMPORT_LINE.get_or_init(|| Regex::new(IMPORT_LINE_PATTERN).expect("valid regex"));
+    let constants = ConstantMatcher::for_language(language);
+
+    let enclosing = |line: u32| {
+        nodes
+            .iter()
+            .filter(|n| n.start_line <= line && line <= n.end_line)
+            .min_by_key(|n| n.end_line - n.start_line)
+    };
+    let in_function = |line: u32| {
+        nodes.iter().any(|n| {
+            matches!(n.kind, NodeKind::Function | NodeKind::Method)
+                && n.start_line < line
+                && line <= n.end_line
+        })
+    };
+
+    let mut out = Vec::new();
+    let mut go_const_block = false;
+    for (i, raw) in lines.iter().enumerate() {
+        let line = i as u32 + 1;
+        let trimmed = raw.trim();
+        if language == Language::Go {
+            if trimmed.starts_with("const (") {
+                go_const_block = true;
+                continue;
+            }
+            if go_const_block && trimmed.starts_with(')') {
+                go_const_block = false;
+            }
+        }
+        if let Some((name, value)) = constants.matches(raw, go_const_block) {
+            if !in_function(line) {
+                out.push(Literal {
+                    file_path: file_path.to_string(),
+                    line,
+                    kind: LiteralKind::Constant,
+                    value,
+                    name: Some(name),
+                    node_id: enclosing(line).map(|n| n.id.clone()),
+                });
+            }
+        }
+    }
+
+    for (line, value) in scan_strings(source, syntax) {
+        let text = lines.get(line as usize - 1).copied().unwrap_or("");
+        let recorded_as_constant = out.iter().any(|l| l.line == line && l.value == value);
+        if import_line.is_match(text)
+            || recorded_as_constant
+            || value.len() > MAX_VALUE_LEN
+            || !value.chars().any(char::is_alphanumeric)
+        {
+            continue;
+        }
+        out.push(Literal {
+            file_path: file_path.to_string(),
+            line,
+            kind: LiteralKind::String,
+            value,
+            name: None,
+            node_id: enclosing(line).map(|n| n.id.clone()),
+        });
+    }
+    out.sort_by_key(|l| l.line);
+    out
+}
+
+/// Lines in the bodies of `functions` that mention constant `name` as a
+/// whole word, skipping the constant's own declaration.
+pub fn find_constant_uses<'a>(
+    name: &str,
+    declaration: (&str, u32),
+    functions: impl IntoIterator<Item = &'a CodeNode>,
+) -> Vec<ConstantUse> {
+    let mut uses = Vec::new();
+    for f in functions {
+        let Some(body) = f.body.as_deref() else {
+            continue;
+        };
+        if !body.contains(name) {
+            continue;
+        }
+        for (offset, text) in body.lines().enumerate() {
+            let line = f.start_line + offset as u32;
+            if (f.file_path.as_str(), line) != declaration && mentions(text, name) {
+                uses.push(ConstantUse {
+                    node_id: f.id.clone(),
+                    symbol: f.qualified_name.clone().unwrap_or_else(|| f.name.clone()),
+                    file_path: f.file_path.clone(),
+                    line,
+                });
+            }
+        }
+    }
+    uses
+}
+
+/// Whether `text` contains `name` with no identifier characters around it.
+fn mentions(text: &str, name: &str) -> bool {
+    let is_ident = |c: char| c.is_alphanumeric() || c == '_' || c == '$';
+    text.match_indices(name).any(|(at, _)| {
+        !text[..at].chars().next_back().is_some_and(is_ident)
+            && !text[at + name.len()..].chars().next().is_some_and(is_ident)
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Internal: string scanning
+// ---------------------------------------------------------------------------
+
+/// Comment and quote rules of a language family.
+#[derive(Debug, Clone, Copy)]
+struct Syntax {
+    /// `//` and `/* */` comments.
+    slash_comments: bool,
+    /// `#` line comments.
+    hash_comments: bool,
+    /// `'...'` is a string rather than a character literal.
+    single_quote_strings: bool,
+    /// Backtick strings (JS templates, Go raw strings).
+    backticks: bool,
+    /// Triple-quoted strings (docstrings) exist and are skipped.
+    triple_quotes: bool,
+}
+
+impl Syntax {
+    fn of(language: Language) -> Option<Self> {
+        let c_like = Syntax {
+            slash_comments: true,
+            hash_comments: false,
+            single_quote_strings: false,
+            backticks: false,
+            triple_quotes: false,
+        };
+        let hash = Syntax {
+            slash_comments: false,
+            hash_comments: true,
+            single_quote_strings: true,
+            backticks: false,
+            triple_quotes: false,
+        };
+        Some(match language {
+            Language::TypeScript | Language::Tsx | Language::JavaScript | Language::Jsx => Syntax {
+                single_quote_strings: true,
+                backticks: true,
+                ..c_like
+            },
+            Language::Go => Syntax {
+                backticks: true,
+                ..c_like
+            },
+            Language::Php => Syntax {
+                hash_comments: true,
+                single_quote_strings: true,
+                ..c_like
+            },
+            Language::Dart | Language::Groovy => Syntax {
+                single_quote_strings: true,
+                triple_quotes: true,
+                ..c_like
+            },
+            Language::Kotlin | Language::Scala | Language::Swift => Syntax {
+                triple_quotes: true,
+                ..c_like
+            },
+            Language::Rust
+            | Language::Java
+            | Language::C
+            | Language::Cpp
+            | Language::CSharp
+            | Language::Zig => c_like,
+            Language::Python | Language::Julia => Syntax {
+                triple_quotes: true,
+                ..hash
+            },
+            Language::Ruby | Language::Bash | Language::Elixir | Language::R | Language::Nix => {
+                hash
+            }
+            _ => return None,
+        })
+    }
+}
+
+/// Single-line string literals as `(line, contents)`. Strings spanning
+/// lines are skipped but still lexed so that what follows stays in sync.
+fn scan_strings(source: &str, syntax: Syntax) -> Vec<(u32, String)> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut out = Vec::new();
+    let mut line = 1u32;
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+        match c {
+            '\n' => line += 1,
+            '/' if syntax.slash_comments && next == Some('/') => {
+                i = skip_to_line_end(&chars, i);
+                continue;
+            }
+            '#' if syntax.hash_comments => {
+                i = skip_to_line_end(&chars, i);
+                continue;
+            }
+            '/' if syntax.slash_comments && next == Some('*') => {
+                i += 2;
+                while i < chars.len() && !(chars[i] == '*' && chars.get(i + 1) == Some(&'/')) {
+                    if chars[i] == '\n' {
+                        line += 1;
+                    }
+                    i += 1;
+                }
+                i += 2;
+                continue;
+            }
+            '"' | '\''
+                if syntax.triple_quotes && next == Some(c) && chars.get(i + 2) == Some(&c) =>
+            {
+                i += 3;
+                while i < chars.len()
+                    && !(chars[i] == c
+                        && chars.get(i + 1) == Some(&c)
+                        && chars.get(i + 2) == Some(&c))
+                {
+                    if chars[i] == '\n' {
+                        line += 1;
+                    }
+                    i += 1;
+                }
+                i += 3;
+                continue;
+            }
+            '\'' if !syntax.single_quote_strings => {
+                // A character literal ('a', '\n'); a lone quote is a Rust
+                // lifetime or label and is passed over.
+                if let Some(len) = char_literal_len(&chars[i..]) {
+                    i += len;
+                    continue;
+                }
+            }
+            '"' | '\'' | '`' if c != '`' || syntax.backticks => {
+                let start_line = line;
+                let mut value = String::new();
+                let mut single_line = true;
+                let mut interpolated = false;
+                i += 1;
+                while i < chars.len() && chars[i] != c {
+                    match chars[i] {
+                        '\\' => {
+                            value.push('\\');
+                            if let Some(&escaped) = chars.get(i + 1) {
+                                value.push(escaped);
+                                if escaped == '\n' {
+                                    line += 1;
+                                    single_line = false;
+                                }
+                            }
+                            i += 2;
+                            continue;
+                        }
+                        '\n' if c != '`' => break,
+                        '\n' => {
+                            line += 1;
+                            single_line = false;
+                        }
+                        '$' if c == '`' && chars.get(i + 1) == Some(&'{') => interpolated = true,
+                        ch => value.push(ch),
+                    }
+                    i += 1;
+                }
+                if single_line && !interpolated && i < chars.len() && chars[i] == c {
+                    out.push((start_line, value));
+                }
+                i += 1;
+                continue;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    out
+}
+
+fn skip_to_line_end(chars: &[char], mut i: usize) -> usize {
+    while i < chars.len() && chars[i] != '\n' {
+        i += 1;
+    }
+    i
+}
+
+/// Length of a character literal starting at `chars[0] == '\''`, if any.
+fn char_literal_len(chars: &[char]) -> Option<usize> {
+    match chars.get(1)? {
+        '\\' => (3..chars.len().min(12))
+            .find(|&end| chars[end] == '\'')
+            .map(|end| end + 1),
+        _ if chars.get(2) == Some(&'\'') => Some(3),
+        _ => None,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Internal: constants
+// ---------------------------------------------------------------------------
+
+/// Recognizes single-line constant declarations with a literal value.
+struct ConstantMatcher {
+    patterns: Vec<Regex>,
+    /// Bare `Name = value` lines inside a Go `const ( ... )` block.
+    go_block: Option<Regex>,
+}
+
+/// Languages with constant declaration forms in [`ConstantMatcher`].
+const CONSTANT_LANGUAGES: &[Language] = &[
+    Language::TypeScript,
+    Language::Tsx,
+    Language::JavaScript,
+    Language::Jsx,
+    Language::Rust,
+    Language::Go,
+    Language::Java,
+    Language::CSharp,
+    Language::Groovy,
+    Language::Dart,
+    Language::Kotlin,
+    Language::Scala,
+    Language::Swift,
+    Language::C,
+    Language::Cpp,
+    Language::Php,
+    Language::Python,
+    Language::Ruby,
+    Language::Elixir,
+];
+
+impl ConstantMatcher {
+    /// The matcher for `language`, compiled once per process.
+    fn for_language(language: Language) -> &'static Self {
+        static MATCHERS: OnceLock<HashMap<Language, ConstantMatcher>> = OnceLock::new();
+        static NONE: ConstantMatcher = ConstantMatcher {
+            patterns: Vec::new(),
+            go_block: None,
+        };
+        MATCHERS
+            .get_or_init(|| {
+                CONSTANT_LANGUAGES
+                    .iter()
+                    .map(|&l| (l, Self::new(l)))
+                    .collect()
+            })
+            .get(&language)
+            .unwrap_or(&NONE)
+    }
+
+    fn new(language: Language) -> Self {
+        let forms: Vec<String> = match language {
+            Language::TypeScript | Language::Tsx | Language::JavaScript | Language::Jsx => {
+                vec![format!(
+                    r"^\s*(?:export\s+)?const\s+([A-Za-z_$][\w$]*)\s*(?::\s*[^=]+?)?\s*=\s*({LITERAL})\s*(?:as\s+const\s*)?;?\s*$"
+                )]
+            }
+            Language::Rust => vec![format!(
+                r"^\s*(?:pub(?:\([^)]*\))?\s+)?(?:const|static)\s+([A-Z_][A-Z0-9_]*)\s*:\s*[^=]+?=\s*({LITERAL})\s*;"
+            )],
+            Language::Go => vec![format!(
+                r"^\s*const\s+([A-Za-z_]\w*)\s*(?:[\w.]+\s*)?=\s*({LITERAL})\s*$"
+            )],
+            Language::Java | Language::CSharp | Language::Groovy | Language::Dart => vec![format!(
+                r"\b(?:static\s+final|final\s+static|const|static\s+readonly|readonly\s+static)\s+(?:[\w<>\[\],.?]+\s+)?([A-Za-z_]\w*)\s*=\s*({LITERAL})\s*;"
+            )],
+            Language::Kotlin | Language::Scala => vec![format!(
+                r"\b(?:const\s+val|final\s+val)\s+([A-Za-z_]\w*)\s*(?::\s*[\w.?]+)?\s*=\s*({LITERAL})\s*;?\s*$"
+            )],
+            Language::Swift => vec![format!(
+                r"^\s*(?:(?:public|private|internal|static|fileprivate)\s+)*let\s+([A-Za-z_]\w*)\s*(?::\s*[\w.?]+)?\s*=\s*({LITERAL})\s*$"
+            )],
+            Language::C | Language::Cpp => vec![
+                format!(r"^\s*#\s*define\s+([A-Za-z_]\w*)\s+({LITERAL})\s*$"),
+                format!(
+                    r"^\s*(?:static\s+)?(?:constexpr|const)\s+[\w:<>\s*]+?\s+\*?\s*([A-Za-z_]\w*)(?:\[\])?\s*=\s*({LITERAL})\s*;"
+                ),
+            ],
+            Language::Php => vec![
+                format!(
+                    r"^\s*(?:(?:public|private|protected)\s+)?const\s+([A-Za-z_]\w*)\s*=\s*({LITERAL})\s*;"
+                ),
+                format!(r#"^\s*define\s*\(\s*['"]([A-Za-z_]\w*)['"]\s*,\s*({LITERAL})\s*\)"#),
+            ],
+            Language::Python => vec![format!(
+                r"^([A-Z][A-Z0-9_]*)\s*(?::\s*[^=]+?)?\s*=\s*({LITERAL})\s*$"
+            )],
+            Language::Ruby => vec![format!(
+                r"^\s*([A-Z][A-Z0-9_]*)\s*=\s*({LITERAL})(?:\.freeze)?\s*$"
+            )],
+            Language::Elixir => vec![format!(r"^\s*@([a-z_]\w*)\s+({LITERAL})\s*$")],
+            _ => Vec::new(),
+        };
+        Self {
+            patterns: forms
+                .iter()
+                .map(|f| Regex::new(f).expect("valid regex"))
+                .collect(),
+            go_block: (language == Language::Go).then(|| {
+                Regex::new(&format!(
+                    r"^\s*([A-Za-z_]\w*)\s*(?:[\w.]+\s*)?=\s*({LITERAL})\s*$"
+                ))
+                .expect("valid regex")
+            }),
+        }
+    }
+
+    /// `(name, value)` when `line` declares a constant, with string values
+    /// unquoted.
+    fn matches(&self, line: &str, in_go_const_block: bool) -> Option<(String, String)> {
+        let block = self.go_block.as_ref().filter(|_| in_go_const_block);
+        let caps = self
+            .patterns
+            .iter()
+            .chain(block)
+            .find_map(|p| p.captures(line))?;
+        let value = &caps[2];
+        let value = match value.chars().next() {
+            Some(q @ ('"' | '\'')) if value.len() >= 2 && value.ends_with(q) => {
+                &value[1..value.len() - 1]
+            }
+            _ => value,
+        };
+        Some((caps[1].to_string(), value.to_string()))
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(id: &str, kind: NodeKind, start: u32, end: u32, body: &str) -> CodeNode {
+        CodeNode {
+            id: id.into(),
+            name: id.into(),
+            qualified_name: None,
+            kind,
+            file_path: "app.ts".into(),
+            start_line: start,
+            end_line: end,
+            start_column: 0,
+            end_column: 0,
+            language: Language::TypeScript,
+            body: Some(body.into()),
+            documentation: None,
+            exported: None,
+        }
+    }
+
+    fn rows(literals: &[Literal]) -> Vec<(u32, &str, &str, Option<&str>)> {
+        literals
+            .iter()
+            .map(|l| (l.line, l.kind.as_str(), l.value.as_str(), l.name.as_deref()))
+            .collect()
+    }
+
+    #[test]
+    fn typescript_strings_and_constants() {
+        let source = r#"import { readFile } from "fs";
+export const ERR_NOT_FOUND = "E404";
+const MAX_RETRIES = 3;
+// "commented out"
+function load(path: string) {
+  const local = "inner";
+  if (!path) throw new Error(`Missing path`);
+  return readFile(`${path}.json`, 'utf8') + ERR_NOT_FOUND;
+}
+"#;
+        let load = node("fn:load", NodeKind::Function, 5, 9, "");
+        let found = extract_literals("app.ts", Language::TypeScript, source, &[load]);
+        assert_eq!(
+            rows(&found),
+            vec![
+                (2, "constant", "E404", Some("ERR_NOT_FOUND")),
+                (3, "constant", "3", Some("MAX_RETRIES")),
+                (6, "string", "inner", None),
+                (7, "string", "Missing path", None),
+                (8, "string", "utf8", None),
+            ]
+        );
+        assert_eq!(found[2].node_id.as_deref(), Some("fn:load"));
+        assert_eq!(found[0].node_id, None);
+    }
+
+    #[test]
+    fn other_language_families() {
+        let python = "\"\"\"Docs with \"quotes\".\"\"\"\nimport os\nDEFAULT_REGION = \"eu-west-1\"\n\ndef f():\n    # 'comment'\n    return os.getenv('REGION', DEFAULT_REGION)\n";
+        assert_eq!(
+            rows(&extract_literals("a.py", Language::Python, python, &[])),
+            vec![
+                (3, "constant", "eu-west-1", Some("DEFAULT_REGION")),
+                (7, "string", "REGION", None),
+            ]
+        );
+
+        let rust = "pub const LIMIT: usize = 64;\nfn f<'a>(s: &'a str) -> char {\n    log(\"limit hit: {}\", '\\n');\n    'x'\n}\n";
+        assert_eq!(
+            rows(&extract_literals("a.rs", Language::Rust, rust, &[])),
+            vec![
+                (1, "constant", "64", Some("LIMIT")),
+                (3, "string", "limit hit: {}", None),
+            ]
+        );
+
+        let go = "package main\nimport \"fmt\"\nconst (\n\tStatusOK = \"ok\"\n\tRetries int = 5\n)\nfunc f() { fmt.Println(`raw`) }\n";
+        assert_eq!(
+            rows(&extract_literals("a.go", Language::Go, go, &[])),
+            vec![
+                (4, "constant", "ok", Some("StatusOK")),
+                (5, "constant", "5", Some("Retries")),
+                (7, "string", "raw", None),
+            ]
+        );
+
+        let java = "class A {\n  public static final String CODE = \"E42\";\n  char c = 'q';\n}\n";
+        assert_eq!(
+            rows(&extract_literals("A.java", Language::Java, java, &[])),
+            vec![(2, "constant", "E42", Some("CODE"))]
+        );
+    }
+
+    #[test]
+    fn constant_uses_skip_declaration_and_partial_words() {
+        let f = node(
+            "fn:load",
+            NodeKind::Function,
+            5,
+            8,
+            "function load() {\n  retry(MAX_RETRIES);\n  return MAX_RETRIES_TOTAL;\n}",
+        );
+        let decl = node(
+            "MAX_RETRIES",
+            NodeKind::Variable,
+            3,
+            3,
+            "const MAX_RETRIES = 3;",
+        );
+        let uses = find_constant_uses("MAX_RETRIES", ("app.ts", 3), [&f, &decl]);
+        assert_eq!(uses.len(), 1);
+        assert_eq!((uses[0].node_id.as_str(), uses[0].line), ("fn:load", 6));
+    }
+}
@@ -1,6 +1,17 @@
-//! Resolution module — framework detection, dead code analysis, and import resolution.
+//! Resolution module — framework and project detection, dead code analysis,
+//! dynamic dispatch, import resolution, configuration key cross-referencing, serialization
+//! contract tracking, import-time side-effect detection, the string
+//! literal and constant cross-reference, and the dependency vulnerability
+//! audit.
 
+pub mod config_keys;
 pub mod dead_code;
+pub mod deps_audit;
+pub mod dispatch;
 pub mod frameworks;
 pub mod imports;
+pub mod literals;
+pub mod projects;
 pub mod routes;
+pub mod side_effects;
+pub mod wire_contract;
@@ -0,0 +1,330 @@
+//! Project detection — finds the packages of a monorepo from their manifests.
+//!
+//! Walks the repository for package manifests (package.json, Cargo.toml,
+//! go.mod, pyproject.toml, pom.xml, ...) and treats each manifest directory
+//! as a project root. Indexed files are assigned to the innermost enclosing
+//! project, so nested packages win over the repository root.
+
+use std::path::Path;
+
+use ignore::WalkBuilder;
+use serde::Serialize;
+
+// ---------------------------------------------------------------------------
+// Constants
+// ---------------------------------------------------------------------------
+
+/// How deep below the repository root manifests are searched for.
+const MAX_MANIFEST_DEPTH: usize = 8;
+
+/// Directories that hold dependencies or build output, never projects.
+const SKIP_DIRS: &[&str] = &[
+    "node_modules",
+    "target",
+    "vendor",
+    "dist",
+    "build",
+    ".git",
+    ".codegraph",
+    "__pycache__",
+    ".venv",
+];
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// A package/project detected from a manifest file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Project {
+    /// Package name from the manifest, or the directory name as a fallback.
+    pub name: String,
+    /// Directory relative to the repository root, `"."` for the root itself.
+    pub root: String,
+    /// Manifest file name, e.g. `package.json`.
+    pub manifest: String,
+    /// Package ecosystem: `npm`, `cargo`, `go`, `python`, `maven`, ...
+    pub ecosystem: String,
+}
+
+// ---------------------------------------------------------------------------
+// Detection
+// ---------------------------------------------------------------------------
+
+/// Detect every project under `root`, sorted by root path.
+///
+/// When a directory has several manifests the first recognised one (in the
+/// order of [`manifest_ecosystem`]) wins. Cargo workspace manifests without
+/// a `[package]` section are not projects themselves.
+pub fn detect_projects(root: &Path) -> Vec<Project> {
+    let walker = WalkBuilder::new(root)
+        .max_depth(Some(MAX_MANIFEST_DEPTH))
+        .hidden(true)
+        .filter_entry(|e| {
+            !e.file_type().is_some_and(|t| t.is_dir())
+                || !SKIP_DIRS.contains(&e.file_name().to_string_lossy().as_ref())
+        })
+        .build();
+
+    let mut projects: Vec<(usize, Project)> = Vec::new();
+    for entry in walker.flatten() {
+        if !entry.file_type().is_some_and(|t| t.is_file()) {
+            continue;
+        }
+        let file_name = entry.file_name().to_string_lossy().to_string();
+        let Some((rank, ecosystem)) = manifest_ecosystem(&file_name) else {
+            continue;
+        };
+        let dir = entry.path().parent().unwrap_or(root);
+        let rel = dir
+            .strip_prefix(root)
+            .map(|p| p.to_string_lossy().replace('\\', "/"))
+            .unwrap_or_default();
+        let rel = if rel.is_empty() { ".".to_string() } else { rel };
+
+        let content = std::fs::read_to_string(entry.path()).unwrap_or_default();
+        let Some(name) = manifest_name(&file_name, &content).or_else(|| {
+            // Workspace-only Cargo manifests describe no package of their own.
+            if file_name == "Cargo.toml" {
+                None
+            } else {
+                Some(fallback_name(dir, &file_name))
+            }
+        }) else {
+            continue;
+        };
+
+        let project = Project {
+            name,
+            root: rel,
+            manifest: file_name,
+            ecosystem: ecosystem.to_string(),
+        };
+        match projects.iter_mut().find(|(_, p)| p.root == project.root) {
+            Some(existing) if existing.0 <= rank => {}
+            Some(existing) => *existing = (rank, project),
+            None => projects.push((rank, project)),
+        }
+    }
+
+    let mut projects: Vec<Project> = projects.into_iter().map(|(_, p)| p).collect();
+    projects.sort_by(|a, b| a.root.cmp(&b.root));
+    projects
+}
+
+/// The innermost project containing `file_path` (relative to the repo root).
+pub fn project_for_file<'a>(projects: &'a [Project], file_path: &str) -> Option<&'a Project> {
+    projects
+        .iter()
+        .filter(|p| {
+            p.root == "."
+                || file_path
+                    .strip_prefix(p.root.as_str())
+                    .is_some_and(|rest| rest.starts_with('/'))
+        })
+        .max_by_key(|p| if p.root == "." { 0 } else { p.root.len() })
+}
+
+// ---------------------------------------------------------------------------
+// Manifest parsing
+// ---------------------------------------------------------------------------
+
+/// Recognised manifest files with their precedence and ecosystem.
+fn manifest_ecosystem(file_name: &str) -> Option<(usize, &'static str)> {
+    let known = [
+        ("package.json", "npm"),
+        ("Cargo.toml", "cargo"),
+        ("go.mod", "go"),
+        ("pyproject.toml", "python"),
+        ("setup.py", "python"),
+        ("pom.xml", "maven"),
+        ("build.gradle.kts", "gradle"),
+        ("build.gradle", "gradle"),
+        ("composer.json", "composer"),
+        ("pubspec.yaml", "pub"),
+        ("mix.exs", "hex"),
+    ];
+    if let Some(rank) = known.iter().position(|(f, _)| *f == file_name) {
+        return Some((rank, known[rank].1));
+    }
+    if file_name.ends_with(".csproj") {
+        return Some((known.len(), "nuget"));
+    }
+    None
+}
+
+/// Package name declared in a manifest, if it declares one.
+fn manifest_name(file_name: &str, content: &str) -> Option<String> {
+    match file_name {
+        "package.json" | "composer.json" => serde_json::from_str::<serde_json::Value>(content)
+            .ok()?
+            .get("name")?
+            .as_str()
+            .map(String::from),
+        "Cargo.toml" => toml_section_value(content, &["package"], "name"),
+        "pyproject.toml" => toml_section_value(content, &["project", "tool.poetry"], "name"),
+        "go.mod" => content
+            .lines()
+            .find_map(|l| l.trim().strip_prefix("module "))
+            .map(|m| m.trim().to_string()),
+        "pom.xml" => {
+            // The project's own artifactId follows the (optional) <parent> block.
+            let body = match content.find("</parent>") {
+                Some(end) => &content[end..],
+                None => content,
+            };
+            let start = body.find("<artifactId>")? + "<artifactId>".len();
+            let end = body[start..].find("</artifactId>")?;
+            Some(body[start..start + end].trim().to_string())
+        }
+        "pubspec.yaml" => content
+            .lines()
+            .find_map(|l| l.strip_prefix("name:"))
+            .map(|n| n.trim().trim_matches(['"', '\'']).to_string()),
+        _ => None,
+    }
+    .filter(|n| !n.is_empty())
+}
+
+/// `key = "value"` inside one of the named TOML `sections`.
+pub(crate) fn toml_section_value(content: &str, sections: &[&str], key: &str) -> Option<String> {
+    let mut in_section = false;
+    for line in content.lines() {
+        let t = line.trim();
+        if t.starts_with('[') {
+            let name = t.trim_matches(['[', ']']).trim();
+            in_section = sections.contains(&name);
+            continue;
+        }
+        if !in_section {
+            continue;
+        }
+        if let Some((k, v)) = t.split_once('=') {
+            if k.trim() == key {
+                return Some(v.trim().trim_matches(['"', '\'']).to_string());
+            }
+        }
+    }
+    None
+}
+
+fn fallback_name(dir: &Path, file_name: &str) -> String {
+    if let Some(stem) = file_name.strip_suffix(".csproj") {
+        return stem.to_string();
+    }
+    dir.file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| ".".to_string())
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn write(root: &Path, rel: &str, content: &str) {
+        let path = root.join(rel);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    #[test]
+    fn detects_packages_across_ecosystems() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        write(
+            root,
+            "package.json",
+            r#"{"name": "monorepo", "private": true}"#,
+        );
+        write(
+            root,
+            "packages/web/package.json",
+            r#"{"name": "@acme/web"}"#,
+        );
+        write(
+            root,
+            "packages/web/node_modules/dep/package.json",
+            r#"{"name": "dep"}"#,
+        );
+        write(
+            root,
+            "crates/core/Cargo.toml",
+            "[package]\nname = \"acme-core\"\n",
+        );
+        write(
+            root,
+            "Cargo.toml",
+            "[workspace]\nmembers = [\"crates/*\"]\n",
+        );
+        write(
+            root,
+            "services/api/go.mod",
+            "module github.com/acme/api\n\ngo 1.22\n",
+        );
+        write(
+            root,
+            "tools/pyproject.toml",
+            "[project]\nname = \"acme-tools\"\n",
+        );
+
+        let projects = detect_projects(root);
+        let names: Vec<(&str, &str)> = projects
+            .iter()
+            .map(|p| (p.root.as_str(), p.name.as_str()))
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                (".", "monorepo"),
+                ("crates/core", "acme-core"),
+                ("packages/web", "@acme/web"),
+                ("services/api", "github.com/acme/api"),
+                ("tools", "acme-tools"),
+            ]
+        );
+    }
+
+    #[test]
+    fn files_map_to_innermost_project() {
+        let projects = vec![
+            Project {
+                name: "root".into(),
+                root: ".".into(),
+                manifest: "package.json".into(),
+                ecosystem: "npm".into(),
+            },
+            Project {
+                name: "web".into(),
+                root: "packages/web".into(),
+                manifest: "package.json".into(),
+                ecosystem: "npm".into(),
+            },
+        ];
+        assert_eq!(
+            project_for_file(&projects, "packages/web/src/app.ts")
+                .unwrap()
+                .name,
+            "web"
+        );
+        assert_eq!(
+            project_for_file(&projects, "packages/website/x.ts")
+                .unwrap()
+                .name,
+            "root"
+        );
+        assert!(project_for_file(&projects[1..], "scripts/x.ts").is_none());
+    }
+
+    #[test]
+    fn pom_artifact_skips_parent() {
+        let pom = "<project><parent><artifactId>parent</artifactId></parent>\
+                   <artifactId>child</artifactId></project>";
+        assert_eq!(manifest_name("pom.xml", pom).as_deref(), Some("child"));
+    }
+}
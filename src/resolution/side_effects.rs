@@ -0,0 +1,482 @@
+//! Import-time side-effect detection.
+//!
+//! Finds module-level code that runs as soon as a module is imported:
+//! top-level calls, control flow and computed initializers in Python and
+//! JavaScript/TypeScript, and static initializer blocks and computed static
+//! fields in Java. Such code is a common source of slow startup and of
+//! tests that pass or fail depending on import order.
+//!
+//! Detection is line-based over the source text: a statement is top-level
+//! when it starts at column 0 (Python) or at brace depth 0 (JS/TS).
+//! Definitions, imports and literal constants are ignored, as are a few
+//! idiomatic calls such as `logging.getLogger` and `require`.
+
+use std::sync::OnceLock;
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::types::Language;
+
+// ---------------------------------------------------------------------------
+// Constants
+// ---------------------------------------------------------------------------
+
+/// Calls that are conventionally made at module level and do no real work.
+const BENIGN_CALL_PATTERN: &str = r"^(?:logging\.getLogger|getLogger|LoggerFactory\.getLogger|Logger\.getLogger|LogManager\.getLogger|TypeVar|NewType|ParamSpec|namedtuple|NamedTuple|TypedDict|field|frozenset|set|dict|list|tuple|object|require|Symbol|Object\.freeze|createContext|React\.createContext|styled\.[\w.]+|debug)$";
+
+/// Longest statement text kept per side effect.
+const MAX_STATEMENT_LEN: usize = 120;
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// What a module-level statement does when the module is loaded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SideEffectKind {
+    /// A bare call (`app.listen(3000)`, `main()`, an IIFE).
+    Call,
+    /// A variable whose value is computed by a call (`db = connect()`).
+    Initializer,
+    /// A loop, `with`, `if` or `try` block executed at import time.
+    ControlFlow,
+    /// A Java `static { ... }` block.
+    StaticBlock,
+}
+
+impl SideEffectKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Call => "call",
+            Self::Initializer => "initializer",
+            Self::ControlFlow => "control_flow",
+            Self::StaticBlock => "static_block",
+        }
+    }
+
+    pub fn from_str_loose(s: &str) -> Option<Self> {
+        match s {
+            "call" => Some(Self::Call),
+            "initializer" => Some(Self::Initializer),
+            "control_flow" => Some(Self::ControlFlow),
+            "static_block" => Some(Self::StaticBlock),
+            _ => None,
+        }
+    }
+}
+
+/// A module-level statement that runs at import time.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SideEffect {
+    pub file_path: String,
+    pub line: u32,
+    pub kind: SideEffectKind,
+    /// First line of the statement, trimmed.
+    pub statement: String,
+}
+
+// ---------------------------------------------------------------------------
+// Extraction
+// ---------------------------------------------------------------------------
+
+/// Find import-time side effects in one file. Languages without module-level
+/// execution semantics handled here yield nothing.
+pub fn extract_side_effects(file_path: &str, language: Language, source: &str) -> Vec<SideEffect> {
+    let calls = CallMatcher::shared();
+    let found = match language {
+        Language::Python => python_side_effects(source, calls),
+        Language::JavaScript | Language::Jsx | Language::TypeScript | Language::Tsx => {
+            js_side_effects(source, calls)
+        }
+        Language::Java => java_side_effects(source, calls),
+        _ => Vec::new(),
+    };
+    found
+        .into_iter()
+        .map(|(line, kind, text)| SideEffect {
+            file_path: file_path.to_string(),
+            line,
+            kind,
+            statement: truncate(text.trim()),
+        })
+        .collect()
+}
+
+/// Recognizes calls in initializer expressions, ignoring benign ones.
+struct CallMatcher {
+    callee: Regex,
+    benign: Regex,
+}
+
+impl CallMatcher {
+    fn shared() -> &'static Self {
+        static MATCHER: OnceLock<CallMatcher> = OnceLock::new();
+        MATCHER.get_or_init(|| Self {
+            callee: Regex::new(r"(?:\bnew\s+)?([A-Za-z_$][\w$.]*)\s*(?:<[^>]*>)?\s*\(")
+                .expect("valid regex"),
+            benign: Regex::new(BENIGN_CALL_PATTERN).expect("valid regex"),
+        })
+    }
+
+    fn is_benign(&self, callee: &str) -> bool {
+        self.benign.is_match(callee)
+    }
+
+    /// Whether `expr` calls something other than a benign factory.
+    /// Constructor calls (`new X()`) always count.
+    fn computes(&self, expr: &str) -> bool {
+        self.callee.captures_iter(expr).any(|c| {
+            let whole = c.get(0).map_or("", |m| m.as_str());
+            whole.starts_with("new ") || !self.is_benign(&c[1])
+        })
+    }
+}
+
+/// Line patterns for Python module-level statements.
+struct PythonPatterns {
+    skip: Regex,
+    control: Regex,
+    assign: Regex,
+    call: Regex,
+}
+
+fn python_patterns() -> &'static PythonPatterns {
+    static PATTERNS: OnceLock<PythonPatterns> = OnceLock::new();
+    PATTERNS.get_or_init(|| PythonPatterns {
+        skip: Regex::new(
+            r"^(?:import\s|from\s|def\s|async\s+def\s|class\s|@|if\s+__name__\s*==|if\s+(?:typing\.)?TYPE_CHECKING|__all__\b|try\s*:|except\b|else\s*:|elif\s|finally\s*:|pass\b|raise\s)",
+        )
+        .expect("valid regex"),
+        control: Regex::new(r"^(?:for|while|with|async\s+for|async\s+with)\b")
+            .expect("valid regex"),
+        assign: Regex::new(r"^[A-Za-z_][\w.]*(?:\s*:\s*[^=]+)?\s*=\s*([^=].*)$")
+            .expect("valid regex"),
+        call: Regex::new(r"^(?:await\s+)?([A-Za-z_][\w.]*)\s*\(").expect("valid regex"),
+    })
+}
+
+fn python_side_effects<'a>(
+    source: &'a str,
+    calls: &CallMatcher,
+) -> Vec<(u32, SideEffectKind, &'a str)> {
+    let PythonPatterns {
+        skip,
+        control,
+        assign,
+        call,
+    } = python_patterns();
+
+    let mut out = Vec::new();
+    let mut in_string: Option<&str> = None;
+    for (i, raw) in source.lines().enumerate() {
+        let line = raw.trim_end();
+        if let Some(quote) = in_string {
+            if line.contains(quote) {
+                in_string = None;
+            }
+            continue;
+        }
+        if let Some(quote) = opens_triple_quote(line) {
+            in_string = Some(quote);
+            continue;
+        }
+        if line.is_empty() || line.starts_with(char::is_whitespace) || line.starts_with('#') {
+            continue;
+        }
+        if line.starts_with([')', ']', '}', '"', '\'']) || skip.is_match(line) {
+            continue;
+        }
+
+        let lineno = i as u32 + 1;
+        if control.is_match(line) || line.starts_with("if ") {
+            out.push((lineno, SideEffectKind::ControlFlow, line));
+        } else if let Some(c) = call.captures(line) {
+            if !calls.is_benign(&c[1]) {
+                out.push((lineno, SideEffectKind::Call, line));
+            }
+        } else if let Some(c) = assign.captures(line) {
+            if calls.computes(&c[1]) {
+                out.push((lineno, SideEffectKind::Initializer, line));
+            }
+        }
+    }
+    out
+}
+
+/// The triple quote a line opens without closing, if any.
+fn opens_triple_quote(line: &str) -> Option<&'static str> {
+    ["\"\"\"", "'''"]
+        .into_iter()
+        .find(|quote| line.matches(quote).count() % 2 == 1)
+}
+
+/// Line patterns for JavaScript/TypeScript module-level statements.
+struct JsPatterns {
+    skip: Regex,
+    control: Regex,
+    decl: Regex,
+    call: Regex,
+    function_value: Regex,
+    iife: Regex,
+}
+
+fn js_patterns() -> &'static JsPatterns {
+    static PATTERNS: OnceLock<JsPatterns> = OnceLock::new();
+    PATTERNS.get_or_init(|| JsPatterns {
+        skip: Regex::new(
+        r"^(?:import\b|export\s+(?:\*|\{|default\s+(?:async\s+)?(?:function|class)\b|(?:async\s+)?function\b|(?:abstract\s+)?class\b|interface\b|type\b|enum\b|declare\b)|(?:async\s+)?function\b|(?:abstract\s+)?class\b|interface\b|type\s+\w|enum\s|declare\s|namespace\s|module\.exports\b|exports\.\w+\s*=\s*\w+\s*;?$|['\x22]use strict)",
+    )
+        .expect("valid regex"),
+        control: Regex::new(r"^(?:for|while|if|try|switch|do)\b").expect("valid regex"),
+        decl: Regex::new(r"^(?:export\s+)?(?:const|let|var)\s+[^=]+?=\s*(.+)$")
+            .expect("valid regex"),
+        call: Regex::new(r"^(?:await\s+)?([A-Za-z_$][\w$.]*)\s*(?:<[^>]*>)?\s*\(")
+            .expect("valid regex"),
+        function_value: Regex::new(
+            r"^(?:async\s+)?(?:function\b|\([^)]*\)\s*(?::[^=]+)?=>|[\w$]+\s*=>)",
+        )
+        .expect("valid regex"),
+        iife: Regex::new(r"^;?\(\s*(?:async\s+)?(?:function\b|\([^)]*\)\s*=>)")
+            .expect("valid regex"),
+    })
+}
+
+fn js_side_effects<'a>(
+    source: &'a str,
+    calls: &CallMatcher,
+) -> Vec<(u32, SideEffectKind, &'a str)> {
+    let JsPatterns {
+        skip,
+        control,
+        decl,
+        call,
+        function_value,
+        iife,
+    } = js_patterns();
+
+    let mut out = Vec::new();
+    let mut depth = 0i32;
+    let mut in_comment = false;
+    for (i, raw) in source.lines().enumerate() {
+        let (code, still_in_comment) = strip_js_line(raw, in_comment);
+        in_comment = still_in_comment;
+        let line = code.trim();
+        let top_level = depth == 0 && !raw.starts_with(char::is_whitespace);
+        depth = (depth + bracket_delta(&code)).max(0);
+        if !top_level || line.is_empty() {
+            continue;
+        }
+        if line.starts_with(['.', ')', ']', '}', '+', '-', '*', '?', ':', '|', '&'])
+            || skip.is_match(line)
+        {
+            continue;
+        }
+
+        let lineno = i as u32 + 1;
+        let text = raw.trim();
+        if control.is_match(line) {
+            out.push((lineno, SideEffectKind::ControlFlow, text));
+        } else if iife.is_match(line) {
+            out.push((lineno, SideEffectKind::Call, text));
+        } else if let Some(c) = decl.captures(line) {
+            let rhs = c[1].trim();
+            if !function_value.is_match(rhs) && calls.computes(rhs) {
+                out.push((lineno, SideEffectKind::Initializer, text));
+            }
+        } else if let Some(c) = call.captures(line) {
+            if !calls.is_benign(&c[1]) {
+                out.push((lineno, SideEffectKind::Call, text));
+            }
+        }
+    }
+    out
+}
+
+/// Remove string contents and comments from one JS line so brackets can be
+/// counted. Returns the stripped code and whether a block comment is still
+/// open at the end of the line.
+fn strip_js_line(line: &str, mut in_comment: bool) -> (String, bool) {
+    let mut out = String::with_capacity(line.len());
+    let mut chars = line.chars().peekable();
+    let mut quote: Option<char> = None;
+    while let Some(c) = chars.next() {
+        if in_comment {
+            if c == '*' && chars.peek() == Some(&'/') {
+                chars.next();
+                in_comment = false;
+            }
+            continue;
+        }
+        if let Some(q) = quote {
+            if c == '\\' {
+                chars.next();
+            } else if c == q {
+                quote = None;
+                out.push(c);
+            }
+            continue;
+        }
+        match c {
+            '"' | '\'' | '`' => {
+                quote = Some(c);
+                out.push(c);
+            }
+            '/' if chars.peek() == Some(&'/') => break,
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                in_comment = true;
+            }
+            _ => out.push(c),
+        }
+    }
+    (out, in_comment)
+}
+
+fn bracket_delta(code: &str) -> i32 {
+    code.chars()
+        .map(|c| match c {
+            '{' | '(' | '[' => 1,
+            '}' | ')' | ']' => -1,
+            _ => 0,
+        })
+        .sum()
+}
+
+/// Line patterns for Java static initializers.
+struct JavaPatterns {
+    block: Regex,
+    field: Regex,
+}
+
+fn java_patterns() -> &'static JavaPatterns {
+    static PATTERNS: OnceLock<JavaPatterns> = OnceLock::new();
+    PATTERNS.get_or_init(|| JavaPatterns {
+        block: Regex::new(r"^\s*static\s*\{").expect("valid regex"),
+        field: Regex::new(
+            r"^\s*(?:(?:public|private|protected|final)\s+)*static\s+(?:final\s+)?[\w<>\[\],.? ]+?\s+\w+\s*=\s*(.+)$",
+        )
+        .expect("valid regex"),
+    })
+}
+
+fn java_side_effects<'a>(
+    source: &'a str,
+    calls: &CallMatcher,
+) -> Vec<(u32, SideEffectKind, &'a str)> {
+    let JavaPatterns { block, field } = java_patterns();
+
+    let mut out = Vec::new();
+    for (i, line) in source.lines().enumerate() {
+        let lineno = i as u32 + 1;
+        if block.is_match(line) {
+            out.push((lineno, SideEffectKind::StaticBlock, line));
+        } else if let Some(c) = field.captures(line) {
+            if calls.computes(&c[1]) {
+                out.push((lineno, SideEffectKind::Initializer, line));
+            }
+        }
+    }
+    out
+}
+
+fn truncate(text: &str) -> String {
+    if text.len() <= MAX_STATEMENT_LEN {
+        return text.to_string();
+    }
+    let mut end = MAX_STATEMENT_LEN;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    format!("{}...", &text[..end])
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(file: &str, language: Language, source: &str) -> Vec<(u32, &'static str)> {
+        extract_side_effects(file, language, source)
+            .into_iter()
+            .map(|e| (e.line, e.kind.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn python_module_level_work() {
+        let source = r#""""Module docstring.
+
+connect()
+"""
+import os
+import logging
+
+logger = logging.getLogger(__name__)
+TIMEOUT = 30
+db = connect(os.environ["DB_URL"])
+register_plugins()
+
+for name in PLUGINS:
+    load(name)
+
+def handler():
+    run()
+
+class Service:
+    cache = build()
+
+if __name__ == "__main__":
+    main()
+"#;
+        assert_eq!(
+            kinds("app.py", Language::Python, source),
+            vec![(10, "initializer"), (11, "call"), (13, "control_flow")]
+        );
+    }
+
+    #[test]
+    fn javascript_and_java_side_effects() {
+        let js = r#"import express from "express";
+const fs = require("fs");
+const app = express();
+export const handler = async (req) => {
+  start();
+};
+/* init()
+   still a comment */
+app.listen(3000);
+(async () => {
+  await warm();
+})();
+export function main() {
+  run();
+}
+const LIMIT = { max: 3 };
+"#;
+        assert_eq!(
+            kinds("server.ts", Language::TypeScript, js),
+            vec![(3, "initializer"), (9, "call"), (10, "call")]
+        );
+
+        let java = r#"public class Registry {
+    private static final Logger LOG = LoggerFactory.getLogger(Registry.class);
+    static final Map<String, Handler> HANDLERS = loadHandlers();
+    private static int count = 0;
+    static {
+        init();
+    }
+}
+"#;
+        assert_eq!(
+            kinds("Registry.java", Language::Java, java),
+            vec![(3, "initializer"), (5, "static_block")]
+        );
+        assert!(kinds("lib.rs", Language::Rust, "fn main() {}").is_empty());
+    }
+}
@@ -0,0 +1,589 @@
+//! Serialization contract tracking.
+//!
+//! Works out the external (wire) name of every field on a serializable type
+//! by reading the serialization annotations around it: serde attributes in
+//! Rust, Jackson annotations in Java/Kotlin, pydantic aliases in Python,
+//! `json:"..."` struct tags in Go, and `System.Text.Json` / Newtonsoft
+//! attributes in C#. Container-level naming rules (`rename_all`,
+//! `@JsonNaming`, `alias_generator`) are applied to fields without an
+//! explicit rename.
+//!
+//! Detection is line-based over the source text, using the field and type
+//! nodes already produced by the extractor for positions.
+
+use std::sync::OnceLock;
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::types::{CodeNode, Language, NodeKind};
+
+// ---------------------------------------------------------------------------
+// Constants
+// ---------------------------------------------------------------------------
+
+/// Calls that turn wire data into a typed value.
+const DESERIALIZE_PATTERN: &str = r"\b(?:from_str|from_slice|from_value|from_reader|readValue|treeToValue|convertValue|Unmarshal|NewDecoder|model_validate(?:_json)?|parse_obj|parse_raw|JSON\.parse|json\.loads|JsonSerializer\.Deserialize|DeserializeObject)\b";
+
+/// Calls that turn a typed value into wire data.
+const SERIALIZE_PATTERN: &str = r"\b(?:to_string|to_string_pretty|to_vec|to_value|to_writer|writeValueAsString|writeValueAsBytes|Marshal|MarshalIndent|NewEncoder|model_dump(?:_json)?|JSON\.stringify|json\.dumps|JsonSerializer\.Serialize|SerializeObject)\b";
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// Serialization framework whose conventions determined a wire name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WireFramework {
+    Serde,
+    Jackson,
+    Pydantic,
+    GoJson,
+    DotNet,
+}
+
+impl WireFramework {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Serde => "serde",
+            Self::Jackson => "jackson",
+            Self::Pydantic => "pydantic",
+            Self::GoJson => "go_json",
+            Self::DotNet => "dotnet",
+        }
+    }
+
+    pub fn from_str_loose(s: &str) -> Option<Self> {
+        match s {
+            "serde" => Some(Self::Serde),
+            "jackson" => Some(Self::Jackson),
+            "pydantic" => Some(Self::Pydantic),
+            "go_json" => Some(Self::GoJson),
+            "dotnet" => Some(Self::DotNet),
+            _ => None,
+        }
+    }
+}
+
+/// A field of a serializable type together with its wire name.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WireField {
+    /// Node ID of the field (`Property` node).
+    pub node_id: String,
+    /// Node ID of the owning type.
+    pub owner_id: String,
+    /// Name of the owning type.
+    pub owner: String,
+    /// Field name in source.
+    pub field_name: String,
+    /// Name the field has on the wire.
+    pub wire_name: String,
+    /// Field is excluded from the serialized form.
+    pub skipped: bool,
+    /// Field may be absent on the wire (default / omitempty / Option).
+    pub optional: bool,
+    pub framework: WireFramework,
+    pub file_path: String,
+    pub line: u32,
+}
+
+/// How a consumer function interacts with wire data, judged from its body.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WireRole {
+    Parses,
+    Produces,
+    Uses,
+}
+
+impl WireRole {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Parses => "parses",
+            Self::Produces => "produces",
+            Self::Uses => "uses",
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Extraction
+// ---------------------------------------------------------------------------
+
+/// Field-naming annotations and tags of the serialization frameworks.
+struct WirePatterns {
+    serde_rename: Regex,
+    serde_rename_all: Regex,
+    serde_skip: Regex,
+    serde_optional: Regex,
+    jackson_property: Regex,
+    jackson_naming: Regex,
+    pydantic_alias: Regex,
+    pydantic_generator: Regex,
+    go_tag: Regex,
+    dotnet_name: Regex,
+}
+
+fn wire_patterns() -> &'static WirePatterns {
+    static PATTERNS: OnceLock<WirePatterns> = OnceLock::new();
+    PATTERNS.get_or_init(|| WirePatterns {
+        serde_rename: Regex::new(r#"serde\s*\([^)]*\brename\s*=\s*"([^"]+)""#)
+            .expect("valid regex"),
+        serde_rename_all: Regex::new(r#"serde\s*\([^)]*\brename_all\s*=\s*"([^"]+)""#)
+            .expect("valid regex"),
+        serde_skip: Regex::new(r"serde\s*\([^)]*\bskip\s*[,)]").expect("valid regex"),
+        serde_optional: Regex::new(r"serde\s*\([^)]*\b(?:default|skip_serializing_if)\b")
+            .expect("valid regex"),
+        jackson_property: Regex::new(r#"@JsonProperty\s*\(\s*(?:value\s*=\s*)?"([^"]+)""#)
+            .expect("valid regex"),
+        jackson_naming: Regex::new(
+            r"@JsonNaming\s*\(\s*(?:\w+\.)*?(\w+?)(?:Strategy)?(?:\.class)?\s*\)",
+        )
+        .expect("valid regex"),
+        pydantic_alias: Regex::new(
+            r#"Field\s*\([^)]*\b(?:serialization_)?alias\s*=\s*["']([^"']+)["']"#,
+        )
+        .expect("valid regex"),
+        pydantic_generator: Regex::new(r"alias_generator\s*=\s*(?:\w+\.)*(\w+)")
+            .expect("valid regex"),
+        go_tag: Regex::new(r#"json:"([^"]*)""#).expect("valid regex"),
+        dotnet_name: Regex::new(
+            r#"\[(?:JsonProperty(?:Name)?|DataMember)\s*\(\s*(?:Name\s*=\s*)?"([^"]+)""#,
+        )
+        .expect("valid regex"),
+    })
+}
+
+/// Compute wire fields for every serializable type in one file.
+///
+/// `nodes` are the extractor's nodes for the file whose text is `source`.
+pub fn extract_wire_fields(source: &str, nodes: &[CodeNode]) -> Vec<WireField> {
+    let lines: Vec<&str> = source.lines().collect();
+    let containers: Vec<&CodeNode> = nodes
+        .iter()
+        .filter(|n| {
+            matches!(
+                n.kind,
+                NodeKind::Class | NodeKind::Struct | NodeKind::Interface
+            )
+        })
+        .collect();
+
+    let WirePatterns {
+        serde_rename,
+        serde_rename_all,
+        serde_skip,
+        serde_optional,
+        jackson_property,
+        jackson_naming,
+        pydantic_alias,
+        pydantic_generator,
+        go_tag,
+        dotnet_name,
+    } = wire_patterns();
+
+    let mut out = Vec::new();
+    for container in &containers {
+        let fields: Vec<&CodeNode> = nodes
+            .iter()
+            .filter(|n| n.kind == NodeKind::Property)
+            .filter(|n| tightest_container(&containers, n).is_some_and(|c| c.id == container.id))
+            .collect();
+        if fields.is_empty() {
+            continue;
+        }
+
+        let header = format!(
+            "{}\n{}",
+            preceding_decorations(&lines, container.start_line),
+            line_at(&lines, container.start_line)
+        );
+        let body = span(&lines, container.start_line, container.end_line);
+        let field_texts: Vec<String> = fields
+            .iter()
+            .map(|f| {
+                format!(
+                    "{}\n{}",
+                    preceding_decorations(&lines, f.start_line),
+                    span(&lines, f.start_line, f.end_line)
+                )
+            })
+            .collect();
+
+        let framework = match container.language {
+            Language::Rust
+                if header.contains("Serialize")
+                    || header.contains("Deserialize")
+                    || header.contains("serde(") =>
+            {
+                WireFramework::Serde
+            }
+            Language::Java | Language::Kotlin if body.contains("@Json") => WireFramework::Jackson,
+            Language::Python if header.contains("BaseModel") => WireFramework::Pydantic,
+            Language::Go if field_texts.iter().any(|t| t.contains("json:\"")) => {
+                WireFramework::GoJson
+            }
+            Language::CSharp
+                if body.contains("[JsonProperty")
+                    || body.contains("[DataContract")
+                    || header.contains("[DataContract") =>
+            {
+                WireFramework::DotNet
+            }
+            _ => continue,
+        };
+
+        let rule: Option<String> = match framework {
+            WireFramework::Serde => capture(serde_rename_all, &header),
+            WireFramework::Jackson => {
+                capture(jackson_naming, &body).and_then(|s| jackson_rule(&s).map(String::from))
+            }
+            WireFramework::Pydantic => {
+                capture(pydantic_generator, &body).and_then(|s| pydantic_rule(&s).map(String::from))
+            }
+            WireFramework::GoJson | WireFramework::DotNet => None,
+        };
+
+        for (field, text) in fields.iter().zip(&field_texts) {
+            let mut skipped: bool;
+            let mut optional = false;
+            let explicit = match framework {
+                WireFramework::Serde => {
+                    skipped = serde_skip.is_match(text);
+                    optional = serde_optional.is_match(text) || text.contains("Option<");
+                    capture(serde_rename, text)
+                }
+                WireFramework::Jackson => {
+                    skipped = text.contains("@JsonIgnore");
+                    capture(jackson_property, text)
+                }
+                WireFramework::Pydantic => {
+                    skipped = text.contains("exclude=True") || text.contains("exclude = True");
+                    optional = text.contains("Optional[") || text.contains("| None");
+                    capture(pydantic_alias, text)
+                }
+                WireFramework::GoJson => {
+                    // Unexported fields never reach the encoder.
+                    skipped = !field.name.starts_with(|c: char| c.is_ascii_uppercase());
+                    match capture(go_tag, text) {
+                        Some(tag) => {
+                            let mut parts = tag.split(',');
+                            let name = parts.next().unwrap_or("").to_string();
+                            optional = parts.any(|p| p == "omitempty" || p == "omitzero");
+                            if name == "-" {
+                                skipped = true;
+                                None
+                            } else if name.is_empty() {
+                                None
+                            } else {
+                                Some(name)
+                            }
+                        }
+                        None => None,
+                    }
+                }
+                WireFramework::DotNet => {
+                    skipped = text.contains("[JsonIgnore") || text.contains("[IgnoreDataMember");
+                    capture(dotnet_name, text)
+                }
+            };
+
+            let wire_name = explicit.unwrap_or_else(|| {
+                rule.as_deref()
+                    .and_then(|r| apply_rename_rule(&field.name, r))
+                    .unwrap_or_else(|| field.name.clone())
+            });
+
+            out.push(WireField {
+                node_id: field.id.clone(),
+                owner_id: container.id.clone(),
+                owner: container.name.clone(),
+                field_name: field.name.clone(),
+                wire_name,
+                skipped,
+                optional,
+                framework,
+                file_path: field.file_path.clone(),
+                line: field.start_line,
+            });
+        }
+    }
+    out
+}
+
+/// Classify a consumer's body: does it parse, produce, or merely use wire data?
+pub fn classify_wire_role(body: &str) -> WireRole {
+    static DESERIALIZE: OnceLock<Regex> = OnceLock::new();
+    static SERIALIZE: OnceLock<Regex> = OnceLock::new();
+    let deserialize =
+        DESERIALIZE.get_or_init(|| Regex::new(DESERIALIZE_PATTERN).expect("valid regex"));
+    let serialize = SERIALIZE.get_or_init(|| Regex::new(SERIALIZE_PATTERN).expect("valid regex"));
+    if deserialize.is_match(body) {
+        WireRole::Parses
+    } else if serialize.is_match(body) {
+        WireRole::Produces
+    } else {
+        WireRole::Uses
+    }
+}
+
+/// Apply a serde-style `rename_all` rule to a field name.
+///
+/// Returns `None` for unknown rules.
+pub fn apply_rename_rule(name: &str, rule: &str) -> Option<String> {
+    let words = split_words(name);
+    let lower: Vec<String> = words.iter().map(|w| w.to_lowercase()).collect();
+    let capitalize = |w: &String| {
+        let mut c = w.chars();
+        match c.next() {
+            Some(f) => f.to_uppercase().collect::<String>() + c.as_str(),
+            None => String::new(),
+        }
+    };
+    Some(match rule {
+        "lowercase" => lower.concat(),
+        "UPPERCASE" => lower.concat().to_uppercase(),
+        "PascalCase" => lower.iter().map(capitalize).collect(),
+        "camelCase" => lower
+            .iter()
+            .enumerate()
+            .map(|(i, w)| if i == 0 { w.clone() } else { capitalize(w) })
+            .collect(),
+        "snake_case" => lower.join("_"),
+        "SCREAMING_SNAKE_CASE" => lower.join("_").to_uppercase(),
+        "kebab-case" => lower.join("-"),
+        "SCREAMING-KEBAB-CASE" => lower.join("-").to_uppercase(),
+        _ => return None,
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Helpers
+// ---------------------------------------------------------------------------
+
+/// Map a Jackson naming strategy class name to a rename rule.
+fn jackson_rule(strategy: &str) -> Option<&'static str> {
+    match strategy {
+        "SnakeCase" => Some("snake_case"),
+        "UpperSnakeCase" => Some("SCREAMING_SNAKE_CASE"),
+        "KebabCase" => Some("kebab-case"),
+        "LowerCamelCase" => Some("camelCase"),
+        "UpperCamelCase" => Some("PascalCase"),
+        "LowerCase" => Some("lowercase"),
+        _ => None,
+    }
+}
+
+/// Map a pydantic alias generator to a rename rule.
+fn pydantic_rule(generator: &str) -> Option<&'static str> {
+    match generator {
+        "to_camel" | "to_lower_camel" => Some("camelCase"),
+        "to_pascal" => Some("PascalCase"),
+        "to_snake" => Some("snake_case"),
+        _ => None,
+    }
+}
+
+fn capture(re: &Regex, text: &str) -> Option<String> {
+    re.captures(text)
+        .and_then(|c| c.get(1))
+        .map(|m| m.as_str().to_string())
+}
+
+fn line_at<'a>(lines: &[&'a str], line: u32) -> &'a str {
+    lines
+        .get((line as usize).wrapping_sub(1))
+        .copied()
+        .unwrap_or("")
+}
+
+fn span(lines: &[&str], start: u32, end: u32) -> String {
+    let start = (start as usize).saturating_sub(1).min(lines.len());
+    let end = (end as usize).clamp(start, lines.len());
+    lines[start..end].join("\n")
+}
+
+/// Attribute / annotation / doc lines directly above a 1-based `line`.
+fn preceding_decorations(lines: &[&str], line: u32) -> String {
+    let mut collected = Vec::new();
+    let mut idx = (line as usize).saturating_sub(1);
+    while idx > 0 {
+        idx -= 1;
+        let t = lines[idx].trim();
+        let is_decoration = t.starts_with("#[")
+            || t.starts_with('@')
+            || (t.starts_with('[') && t.ends_with(']'))
+            || t.starts_with("//");
+        if !is_decoration {
+            break;
+        }
+        collected.push(t);
+    }
+    collected.reverse();
+    collected.join("\n")
+}
+
+/// The innermost container whose line range encloses `node`.
+fn tightest_container<'a>(containers: &[&'a CodeNode], node: &CodeNode) -> Option<&'a CodeNode> {
+    containers
+        .iter()
+        .filter(|c| c.start_line <= node.start_line && c.end_line >= node.end_line)
+        .min_by_key(|c| c.end_line - c.start_line)
+        .copied()
+}
+
+/// Split a snake_case or camelCase identifier into words.
+fn split_words(name: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    for part in name.split(['_', '-']).filter(|p| !p.is_empty()) {
+        let mut current = String::new();
+        let chars: Vec<char> = part.chars().collect();
+        for (i, &c) in chars.iter().enumerate() {
+            let boundary = c.is_uppercase()
+                && i > 0
+                && (chars[i - 1].is_lowercase()
+                    || chars.get(i + 1).is_some_and(|n| n.is_lowercase()));
+            if boundary && !current.is_empty() {
+                words.push(std::mem::take(&mut current));
+            }
+            current.push(c);
+        }
+        if !current.is_empty() {
+            words.push(current);
+        }
+    }
+    words
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indexer::extractor::Extractor;
+    use crate::indexer::parser::CodeParser;
+
+    fn wire_fields(source: &str, language: Language, file: &str) -> Vec<WireField> {
+        let parser = CodeParser::new();
+        let tree = parser.parse(source, language).unwrap();
+        let nodes = Extractor::extract_nodes(&tree, file, language, source).unwrap();
+        extract_wire_fields(source, &nodes)
+    }
+
+    fn wire_name<'a>(fields: &'a [WireField], name: &str) -> &'a WireField {
+        fields.iter().find(|f| f.field_name == name).unwrap()
+    }
+
+    #[test]
+    fn rename_rules() {
+        assert_eq!(
+            apply_rename_rule("created_at", "camelCase").unwrap(),
+            "createdAt"
+        );
+        assert_eq!(
+            apply_rename_rule("created_at", "PascalCase").unwrap(),
+            "CreatedAt"
+        );
+        assert_eq!(
+            apply_rename_rule("createdAt", "snake_case").unwrap(),
+            "created_at"
+        );
+        assert_eq!(
+            apply_rename_rule("created_at", "SCREAMING-KEBAB-CASE").unwrap(),
+            "CREATED-AT"
+        );
+        assert!(apply_rename_rule("x", "weird").is_none());
+    }
+
+    #[test]
+    fn serde_rename_all_rename_and_skip() {
+        let source = r#"
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Order {
+    order_id: u64,
+    #[serde(rename = "state")]
+    status: String,
+    #[serde(skip)]
+    cache: Vec<u8>,
+    note: Option<String>,
+}
+
+pub struct Internal {
+    value: u32,
+}
+"#;
+        let fields = wire_fields(source, Language::Rust, "order.rs");
+        assert_eq!(wire_name(&fields, "order_id").wire_name, "orderId");
+        assert_eq!(wire_name(&fields, "status").wire_name, "state");
+        assert!(wire_name(&fields, "cache").skipped);
+        assert!(wire_name(&fields, "note").optional);
+        assert!(fields.iter().all(|f| f.owner == "Order"));
+        assert_eq!(fields[0].framework, WireFramework::Serde);
+    }
+
+    #[test]
+    fn go_json_tags() {
+        let source = r#"
+package model
+
+type Order struct {
+	ID     int    `json:"id"`
+	Status string `json:"status,omitempty"`
+	Secret string `json:"-"`
+	cache  string
+}
+"#;
+        let fields = wire_fields(source, Language::Go, "order.go");
+        assert_eq!(wire_name(&fields, "ID").wire_name, "id");
+        assert!(wire_name(&fields, "Status").optional);
+        assert!(wire_name(&fields, "Secret").skipped);
+        assert!(wire_name(&fields, "cache").skipped);
+    }
+
+    #[test]
+    fn jackson_property_and_ignore() {
+        let source = r#"
+public class Order {
+    @JsonProperty("order_status")
+    private String status;
+
+    @JsonIgnore
+    private String internal;
+}
+"#;
+        let fields = wire_fields(source, Language::Java, "Order.java");
+        assert_eq!(wire_name(&fields, "status").wire_name, "order_status");
+        assert!(wire_name(&fields, "internal").skipped);
+    }
+
+    #[test]
+    fn pydantic_alias_and_generator() {
+        let source = r#"
+class Order(BaseModel):
+    model_config = ConfigDict(alias_generator=to_camel)
+    order_id: int
+    status: str = Field(alias="state")
+"#;
+        let fields = wire_fields(source, Language::Python, "order.py");
+        assert_eq!(wire_name(&fields, "order_id").wire_name, "orderId");
+        assert_eq!(wire_name(&fields, "status").wire_name, "state");
+    }
+
+    #[test]
+    fn classify_roles() {
+        assert_eq!(
+            classify_wire_role("let o: Order = serde_json::from_str(&s)?;"),
+            WireRole::Parses
+        );
+        assert_eq!(
+            classify_wire_role("return JSON.stringify(order);"),
+            WireRole::Produces
+        );
+        assert_eq!(classify_wire_role("order.total()"), WireRole::Uses);
+    }
+}
@@ -0,0 +1,561 @@
+//! Workspace trust and the sandbox for external commands.
+//!
+//! CodeGraph shells out to `git` and reads every file a scanner walks. On a
+//! developer's own checkout that is harmless; a server indexing repos it
+//! does not control has to assume the repo is hostile. A hostile repo can
+//! point `core.fsmonitor`, `core.pager` or a filter, textconv or external
+//! diff driver in its `.git/config` at a program, symlink its way out of
+//! the project, or hand a scanner a file that never ends.
+//!
+//! [`SandboxPolicy`] bounds all of that:
+//!
+//! - only binaries on the allow list can run (`git` by default);
+//! - every command has a timeout and a cap on captured output;
+//! - in an [`Trust::Untrusted`] workspace, commands only run inside the
+//!   registered project roots (the jail), git runs with the config keys
+//!   that execute programs overridden (including those of every filter,
+//!   diff and merge driver the repo configures) and without textconv or
+//!   external diffs, and scanners skip files that resolve outside the jail
+//!   or exceed the file size cap.
+//!
+//! The policy is process-wide, since commands are run from deep inside
+//! analysis code. Each server (or CLI run) [`register`]s its project root
+//! and policy; with several projects, the strictest setting wins.
+
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{CodeGraphError, Result};
+
+/// Default command timeout.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(120);
+/// Default cap on a command's stdout (and, separately, stderr).
+pub const DEFAULT_MAX_OUTPUT_BYTES: usize = 64 * 1024 * 1024;
+/// Default cap on a file read by a scanner in an untrusted workspace.
+pub const DEFAULT_MAX_FILE_BYTES: u64 = 2 * 1024 * 1024;
+
+/// Git config overrides for untrusted repos. Command-line `-c` beats the
+/// repo's own config, so none of these can be pointed at a program.
+const UNTRUSTED_GIT_CONFIG: &[&str] = &[
+    "core.fsmonitor=false",
+    "core.hooksPath=/dev/null",
+    "core.pager=cat",
+    "core.sshCommand=false",
+    "diff.external=",
+    "protocol.allow=never",
+];
+
+/// Keys of named drivers that run a program. Driver names are picked by the
+/// repo, so untrusted git looks up the ones it configures and blanks these
+/// keys for each; an empty command runs nothing.
+const DRIVER_PROGRAM_KEYS: &[(&str, &[&str])] = &[
+    ("filter", &["clean", "smudge", "process"]),
+    ("diff", &["textconv", "command"]),
+    ("merge", &["driver"]),
+];
+
+/// Options untrusted git adds after these subcommands, so blobs are shown
+/// and blamed as stored rather than through a repo-chosen program.
+const UNTRUSTED_GIT_OPTIONS: &[(&str, &[&str])] = &[
+    ("blame", &["--no-textconv"]),
+    ("diff", &["--no-ext-diff", "--no-textconv"]),
+    ("log", &["--no-ext-diff", "--no-textconv"]),
+    ("show", &["--no-ext-diff", "--no-textconv"]),
+];
+
+/// How far a workspace is trusted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Trust {
+    /// The user's own code: commands run as-is.
+    #[default]
+    Trusted,
+    /// Possibly hostile code: jail, hardened git, capped file reads.
+    Untrusted,
+}
+
+impl Trust {
+    pub fn from_str_loose(s: &str) -> Option<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "trusted" | "true" | "yes" => Some(Self::Trusted),
+            "untrusted" | "false" | "no" => Some(Self::Untrusted),
+            _ => None,
+        }
+    }
+}
+
+/// Limits applied to external commands and scanned files.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SandboxPolicy {
+    pub trust: Trust,
+    /// Binary names that may be run.
+    pub allowed_binaries: Vec<String>,
+    /// Directories commands may run in when untrusted (canonical paths).
+    pub jail: Vec<PathBuf>,
+    pub timeout: Duration,
+    pub max_output_bytes: usize,
+    pub max_file_bytes: u64,
+}
+
+impl Default for SandboxPolicy {
+    fn default() -> Self {
+        Self {
+            trust: Trust::Trusted,
+            allowed_binaries: vec!["git".to_string()],
+            jail: Vec::new(),
+            timeout: DEFAULT_TIMEOUT,
+            max_output_bytes: DEFAULT_MAX_OUTPUT_BYTES,
+            max_file_bytes: DEFAULT_MAX_FILE_BYTES,
+        }
+    }
+}
+
+static POLICY: RwLock<Option<SandboxPolicy>> = RwLock::new(None);
+
+/// Add `root` to the jail and combine `policy` with the one in effect,
+/// keeping the stricter of each setting.
+pub fn register(policy: SandboxPolicy, root: &Path) {
+    let mut guard = POLICY.write().unwrap_or_else(|e| e.into_inner());
+    let mut merged = match guard.take() {
+        Some(current) => current.strictest(policy),
+        None => policy,
+    };
+    let root = root.canonicalize().unwrap_or_else(|_| root.to_path_buf());
+    if !merged.jail.contains(&root) {
+        merged.jail.push(root);
+    }
+    *guard = Some(merged);
+}
+
+/// The policy in effect (the default one until something is registered).
+pub fn current() -> SandboxPolicy {
+    POLICY
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone()
+        .unwrap_or_default()
+}
+
+/// Run `binary` with `args` in `cwd` under the current policy.
+pub fn run(binary: &str, args: &[&str], cwd: &Path) -> Result<Output> {
+    current().run(binary, args, cwd)
+}
+
+impl SandboxPolicy {
+    /// Each setting of `self` and `other`, whichever is stricter. Jails are
+    /// combined, since every registered project is a legitimate place to
+    /// run in.
+    pub fn strictest(mut self, other: SandboxPolicy) -> SandboxPolicy {
+        if other.trust == Trust::Untrusted {
+            self.trust = Trust::Untrusted;
+        }
+        self.allowed_binaries
+            .retain(|b| other.allowed_binaries.contains(b));
+        for root in other.jail {
+            if !self.jail.contains(&root) {
+                self.jail.push(root);
+            }
+        }
+        self.timeout = self.timeout.min(other.timeout);
+        self.max_output_bytes = self.max_output_bytes.min(other.max_output_bytes);
+        self.max_file_bytes = self.max_file_bytes.min(other.max_file_bytes);
+        self
+    }
+
+    /// Run `binary` with `args` in `cwd`, failing if the binary is not
+    /// allowed, `cwd` is outside the jail (untrusted only), the command
+    /// outlives the timeout, or it prints more than the output cap.
+    pub fn run(&self, binary: &str, args: &[&str], cwd: &Path) -> Result<Output> {
+        if !self.allowed_binaries.iter().any(|b| b == binary) {
+            return Err(CodeGraphError::Other(format!(
+                "Sandbox: '{binary}' is not an allowed binary"
+            )));
+        }
+        let cwd = self.check_dir(cwd)?;
+
+        if self.trust == Trust::Untrusted && binary == "git" {
+            let mut command = hardened_git(&cwd);
+            for setting in self.driver_overrides(&cwd)? {
+                command.args(["-c", &setting]);
+            }
+            let subcommand = args.iter().position(|a| !a.starts_with('-'));
+            let extra = subcommand.and_then(|i| {
+                UNTRUSTED_GIT_OPTIONS
+                    .iter()
+                    .find(|(name, _)| *name == args[i])
+                    .map(|(_, options)| (i, *options))
+            });
+            match extra {
+                Some((i, options)) => command.args(&args[..=i]).args(options).args(&args[i + 1..]),
+                None => command.args(args),
+            };
+            return self.execute(binary, command);
+        }
+        let mut command = Command::new(binary);
+        command.args(args).current_dir(&cwd);
+        self.execute(binary, command)
+    }
+
+    /// `-c` settings blanking the program keys of every driver the repo's
+    /// config names, see [`DRIVER_PROGRAM_KEYS`]. Reading config runs
+    /// nothing.
+    fn driver_overrides(&self, cwd: &Path) -> Result<Vec<String>> {
+        let mut command = hardened_git(cwd);
+        command.args([
+            "config",
+            "--name-only",
+            "--get-regexp",
+            r"^(filter|diff|merge)\.",
+        ]);
+        // Exits 1 when nothing matches, which is fine.
+        let output = self.execute("git", command)?;
+        let names = String::from_utf8_lossy(&output.stdout);
+        let mut overrides = Vec::new();
+        for name in names.lines() {
+            // section.<driver>.key; the driver name may contain dots.
+            let Some((section, rest)) = name.split_once('.') else {
+                continue;
+            };
+            let Some((driver, _)) = rest.rsplit_once('.') else {
+                continue;
+            };
+            let Some((_, keys)) = DRIVER_PROGRAM_KEYS.iter().find(|(s, _)| *s == section) else {
+                continue;
+            };
+            for key in *keys {
+                let setting = format!("{section}.{driver}.{key}=");
+                if !overrides.contains(&setting) {
+                    overrides.push(setting);
+                }
+            }
+        }
+        Ok(overrides)
+    }
+
+    /// Spawn `command` with captured output, enforcing the timeout and the
+    /// output cap.
+    fn execute(&self, binary: &str, mut command: Command) -> Result<Output> {
+        let mut child = command
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+
+        let overflow = Arc::new(AtomicBool::new(false));
+        let stdout = capture(child.stdout.take(), self.max_output_bytes, &overflow);
+        let stderr = capture(child.stderr.take(), self.max_output_bytes, &overflow);
+
+        let deadline = Instant::now() + self.timeout;
+        // Most git calls finish in a few milliseconds; back off from there.
+        let mut pause = Duration::from_millis(1);
+        let status = loop {
+            if let Some(status) = child.try_wait()? {
+                break status;
+            }
+            if overflow.load(Ordering::Relaxed) {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(CodeGraphError::Other(format!(
+                    "Sandbox: {binary} produced more than {} bytes of output",
+                    self.max_output_bytes
+                )));
+            }
+            if Instant::now() >= deadline {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(CodeGraphError::Other(format!(
+                    "Sandbox: {binary} timed out after {}s",
+                    self.timeout.as_secs_f64()
+                )));
+            }
+            std::thread::sleep(pause);
+            pause = (pause * 2).min(Duration::from_millis(25));
+        };
+
+        let stdout = stdout.join().unwrap_or_default();
+        let stderr = stderr.join().unwrap_or_default();
+        if overflow.load(Ordering::Relaxed) {
+            return Err(CodeGraphError::Other(format!(
+                "Sandbox: {binary} produced more than {} bytes of output",
+                self.max_output_bytes
+            )));
+        }
+        Ok(Output {
+            status,
+            stdout,
+            stderr,
+        })
+    }
+
+    /// Canonical `dir`, if commands may run there.
+    pub fn check_dir(&self, dir: &Path) -> Result<PathBuf> {
+        let canonical = dir.canonicalize()?;
+        if self.trust == Trust::Untrusted && !self.in_jail(&canonical) {
+            return Err(CodeGraphError::Other(format!(
+                "Sandbox: {} is outside the workspace",
+                dir.display()
+            )));
+        }
+        Ok(canonical)
+    }
+
+    /// Whether a scanner may read `path`. Trusted workspaces read anything;
+    /// untrusted ones only files that resolve inside the jail and fit the
+    /// file size cap.
+    pub fn allows_file(&self, path: &Path) -> bool {
+        if self.trust == Trust::Trusted {
+            return true;
+        }
+        let Ok(canonical) = path.canonicalize() else {
+            return false;
+        };
+        if !self.in_jail(&canonical) {
+            return false;
+        }
+        std::fs::metadata(&canonical).is_ok_and(|m| m.is_dir() || m.len() <= self.max_file_bytes)
+    }
+
+    fn in_jail(&self, canonical: &Path) -> bool {
+        self.jail.iter().any(|root| canonical.starts_with(root))
+    }
+}
+
+/// `git` in `cwd` with the fixed untrusted overrides and environment.
+fn hardened_git(cwd: &Path) -> Command {
+    let mut command = Command::new("git");
+    for setting in UNTRUSTED_GIT_CONFIG {
+        command.args(["-c", setting]);
+    }
+    command
+        .current_dir(cwd)
+        .env("GIT_CONFIG_NOSYSTEM", "1")
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .env_remove("GIT_EXTERNAL_DIFF");
+    command
+}
+
+/// Read `pipe` to the end on a separate thread, keeping at most `cap`
+/// bytes and raising `overflow` past that.
+fn capture<R: Read + Send + 'static>(
+    pipe: Option<R>,
+    cap: usize,
+    overflow: &Arc<AtomicBool>,
+) -> std::thread::JoinHandle<Vec<u8>> {
+    let overflow = Arc::clone(overflow);
+    std::thread::spawn(move || {
+        let mut buf = Vec::new();
+        let Some(pipe) = pipe else {
+            return buf;
+        };
+        let mut limited = pipe.take(cap as u64 + 1);
+        let _ = limited.read_to_end(&mut buf);
+        if buf.len() > cap {
+            buf.truncate(cap);
+            overflow.store(true, Ordering::Relaxed);
+        }
+        buf
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn untrusted(root: &Path) -> SandboxPolicy {
+        SandboxPolicy {
+            trust: Trust::Untrusted,
+            jail: vec![root.canonicalize().unwrap()],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn only_allowed_binaries_run() {
+        let dir = tempfile::tempdir().unwrap();
+        let err = SandboxPolicy::default()
+            .run("sh", &["-c", "true"], dir.path())
+            .unwrap_err();
+        assert!(err.to_string().contains("not an allowed binary"));
+        let out = SandboxPolicy::default()
+            .run("git", &["--version"], dir.path())
+            .unwrap();
+        assert!(out.status.success());
+    }
+
+    #[test]
+    fn untrusted_commands_stay_in_the_jail() {
+        let jail = tempfile::tempdir().unwrap();
+        let outside = tempfile::tempdir().unwrap();
+        let policy = untrusted(jail.path());
+        assert!(policy.run("git", &["--version"], jail.path()).is_ok());
+        let err = policy
+            .run("git", &["--version"], outside.path())
+            .unwrap_err();
+        assert!(err.to_string().contains("outside the workspace"));
+        // Trusted workspaces are not jailed.
+        assert!(SandboxPolicy::default()
+            .run("git", &["--version"], outside.path())
+            .is_ok());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn timeouts_and_output_caps_kill_the_command() {
+        let dir = tempfile::tempdir().unwrap();
+        let slow = SandboxPolicy {
+            allowed_binaries: vec!["sleep".to_string()],
+            timeout: Duration::from_millis(100),
+            ..Default::default()
+        };
+        let started = Instant::now();
+        let err = slow.run("sleep", &["5"], dir.path()).unwrap_err();
+        assert!(err.to_string().contains("timed out"));
+        assert!(started.elapsed() < Duration::from_secs(4));
+
+        let chatty = SandboxPolicy {
+            allowed_binaries: vec!["yes".to_string()],
+            max_output_bytes: 1024,
+            ..Default::default()
+        };
+        let err = chatty.run("yes", &[], dir.path()).unwrap_err();
+        assert!(err.to_string().contains("more than 1024 bytes"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn untrusted_git_ignores_repo_configured_programs() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let git = |args: &[&str]| {
+            Command::new("git")
+                .args(args)
+                .current_dir(root)
+                .output()
+                .unwrap()
+        };
+        git(&["init", "-q"]);
+        let marker = root.join("pwned");
+        git(&[
+            "config",
+            "core.fsmonitor",
+            &format!("touch {}; false", marker.display()),
+        ]);
+        let out = untrusted(root).run("git", &["status"], root).unwrap();
+        assert!(out.status.success());
+        assert!(!marker.exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn untrusted_git_never_runs_repo_drivers() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let git = |args: &[&str]| {
+            Command::new("git")
+                .args(args)
+                .current_dir(root)
+                .env("GIT_AUTHOR_NAME", "a")
+                .env("GIT_AUTHOR_EMAIL", "a@example.com")
+                .env("GIT_COMMITTER_NAME", "a")
+                .env("GIT_COMMITTER_EMAIL", "a@example.com")
+                .output()
+                .unwrap()
+        };
+        git(&["init", "-q"]);
+        std::fs::write(
+            root.join(".gitattributes"),
+            "*.txt filter=evil diff=evil.x\n",
+        )
+        .unwrap();
+        std::fs::write(root.join("a.txt"), "one\n").unwrap();
+        git(&["add", "."]);
+        git(&["commit", "-qm", "initial"]);
+        let marker = root.join("pwned");
+        let payload = format!("touch {}; cat", marker.display());
+        for (key, value) in [
+            ("filter.evil.clean", payload.as_str()),
+            ("filter.evil.smudge", payload.as_str()),
+            ("filter.evil.process", payload.as_str()),
+            ("diff.evil.x.textconv", payload.as_str()),
+            ("diff.evil.x.command", payload.as_str()),
+            ("diff.external", payload.as_str()),
+        ] {
+            git(&["config", key, value]);
+        }
+        // A dirty, racy worktree file makes git re-run the clean filter.
+        std::fs::write(root.join("a.txt"), "one\ntwo\n").unwrap();
+
+        // The planted drivers do run for trusted git.
+        let trusted = SandboxPolicy::default();
+        trusted.run("git", &["diff", "HEAD", "--"], root).unwrap();
+        assert!(marker.exists());
+        std::fs::remove_file(&marker).unwrap();
+
+        let policy = untrusted(root);
+        for args in [
+            &["diff", "HEAD", "--"][..],
+            &["diff", "--name-status", "HEAD", "--"],
+            &["blame", "--porcelain", "--", "a.txt"],
+            &["log", "-p"],
+            &["show", "HEAD"],
+            &["status"],
+        ] {
+            let out = policy.run("git", args, root).unwrap();
+            assert!(out.status.success(), "git {args:?} failed");
+            assert!(!marker.exists(), "git {args:?} ran a repo driver");
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn untrusted_scans_skip_escaping_and_oversized_files() {
+        let jail = tempfile::tempdir().unwrap();
+        let outside = tempfile::tempdir().unwrap();
+        let secret = outside.path().join("secret.txt");
+        std::fs::write(&secret, "token").unwrap();
+        std::os::unix::fs::symlink(&secret, jail.path().join("link.txt")).unwrap();
+        std::fs::write(jail.path().join("small.rs"), "fn a() {}").unwrap();
+        std::fs::write(jail.path().join("big.rs"), vec![b'a'; 4096]).unwrap();
+
+        let policy = SandboxPolicy {
+            max_file_bytes: 1024,
+            ..untrusted(jail.path())
+        };
+        assert!(policy.allows_file(&jail.path().join("small.rs")));
+        assert!(!policy.allows_file(&jail.path().join("big.rs")));
+        assert!(!policy.allows_file(&jail.path().join("link.txt")));
+        assert!(SandboxPolicy::default().allows_file(&jail.path().join("link.txt")));
+    }
+
+    #[test]
+    fn strictest_combines_policies() {
+        let a = SandboxPolicy {
+            allowed_binaries: vec!["git".to_string(), "rg".to_string()],
+            timeout: Duration::from_secs(10),
+            jail: vec![PathBuf::from("/a")],
+            ..Default::default()
+        };
+        let b = SandboxPolicy {
+            trust: Trust::Untrusted,
+            max_file_bytes: 10,
+            jail: vec![PathBuf::from("/b")],
+            ..Default::default()
+        };
+        let merged = a.strictest(b);
+        assert_eq!(merged.trust, Trust::Untrusted);
+        assert_eq!(merged.allowed_binaries, vec!["git"]);
+        assert_eq!(merged.timeout, Duration::from_secs(10));
+        assert_eq!(merged.max_file_bytes, 10);
+        assert_eq!(merged.jail.len(), 2);
+    }
+}
@@ -0,0 +1,256 @@
+//! Suppressing accepted security findings.
+//!
+//! Two mechanisms keep findings that were reviewed and accepted out of scan
+//! results:
+//!
+//! - **Inline suppressions**: a `codegraph-ignore: <rule-id>[, <rule-id>...]`
+//!   comment on the finding's line, or alone on the line above it,
+//!   suppresses those rules there. Rule IDs are matched case-insensitively;
+//!   pack rules are named with their namespace (`acme/NO-EVAL`).
+//! - **Baseline**: `.codegraph/security-baseline.json`, meant to be
+//!   committed, records accepted findings by rule, project-relative file and
+//!   a hash of the line they are on. Line numbers are not part of the key,
+//!   so a baselined finding stays accepted when code above it moves; editing
+//!   the line makes it new again. `codegraph scan-security
+//!   --write-baseline` records the current findings.
+
+use std::collections::{BTreeSet, HashMap};
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use super::scanner::SecurityFinding;
+
+/// Comment marker of inline suppressions.
+pub const SUPPRESSION_MARKER: &str = "codegraph-ignore:";
+
+/// Baseline file, relative to the project root.
+pub const BASELINE_FILE: &str = ".codegraph/security-baseline.json";
+
+const BASELINE_VERSION: u32 = 1;
+
+// ---------------------------------------------------------------------------
+// Inline suppressions
+// ---------------------------------------------------------------------------
+
+/// The rule IDs a `codegraph-ignore:` comment on `line` names.
+fn suppressed_rules(line: &str) -> impl Iterator<Item = &str> {
+    line.find(SUPPRESSION_MARKER)
+        .map(|pos| &line[pos + SUPPRESSION_MARKER.len()..])
+        .unwrap_or_default()
+        .split([',', ' ', '\t'])
+        .map(|id| id.trim_end_matches(['*', '/', '-', '>']).trim())
+        .filter(|id| !id.is_empty())
+}
+
+/// Whether a comment-only line: nothing before the marker but a comment
+/// opener.
+fn is_comment_line(line: &str) -> bool {
+    let trimmed = line.trim_start();
+    ["//", "#", "--", "/*", "*", "<!--", ";", "%", "'"]
+        .iter()
+        .any(|opener| trimmed.starts_with(opener))
+}
+
+/// Whether `rule_id` is suppressed at `line_number` (1-based) of `lines`:
+/// named by a marker on that line, or on a comment-only line right above.
+pub fn is_suppressed(lines: &[&str], line_number: usize, rule_id: &str) -> bool {
+    let names = |line: &str| suppressed_rules(line).any(|id| id.eq_ignore_ascii_case(rule_id));
+    let on_line = line_number
+        .checked_sub(1)
+        .and_then(|i| lines.get(i))
+        .is_some_and(|line| names(line));
+    let above = line_number
+        .checked_sub(2)
+        .and_then(|i| lines.get(i))
+        .is_some_and(|line| is_comment_line(line) && names(line));
+    on_line || above
+}
+
+// ---------------------------------------------------------------------------
+// Baseline
+// ---------------------------------------------------------------------------
+
+/// One accepted finding.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct BaselineEntry {
+    pub rule: String,
+    /// Project-relative path with `/` separators.
+    pub file: String,
+    /// SHA-256 of the finding's source line, whitespace-normalized.
+    pub hash: String,
+}
+
+/// Accepted findings, as stored in [`BASELINE_FILE`].
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Baseline {
+    pub version: u32,
+    pub findings: BTreeSet<BaselineEntry>,
+}
+
+fn code_hash(matched: &str) -> String {
+    let normalized = matched.split_whitespace().collect::<Vec<_>>().join(" ");
+    hex::encode(Sha256::digest(normalized.as_bytes()))
+}
+
+fn relative_path(file: &str, project_root: &Path) -> String {
+    Path::new(file)
+        .strip_prefix(project_root)
+        .unwrap_or(Path::new(file))
+        .to_string_lossy()
+        .replace('\\', "/")
+        .trim_start_matches("./")
+        .to_string()
+}
+
+/// Baseline entries for `findings`, hashing the source line each finding
+/// is on (the matched text when the file cannot be read).
+fn entries(findings: &[SecurityFinding], project_root: &Path) -> Vec<BaselineEntry> {
+    let mut sources: HashMap<&str, Option<String>> = HashMap::new();
+    findings
+        .iter()
+        .map(|f| {
+            let source = sources
+                .entry(f.file_path.as_str())
+                .or_insert_with(|| std::fs::read_to_string(&f.file_path).ok());
+            let line = source
+                .as_deref()
+                .and_then(|s| s.lines().nth(f.line_number.saturating_sub(1)))
+                .unwrap_or(&f.matched_text);
+            BaselineEntry {
+                rule: f.rule_id.clone(),
+                file: relative_path(&f.file_path, project_root),
+                hash: code_hash(line),
+            }
+        })
+        .collect()
+}
+
+impl Baseline {
+    /// A baseline accepting `findings`.
+    pub fn from_findings(findings: &[SecurityFinding], project_root: &Path) -> Self {
+        Self {
+            version: BASELINE_VERSION,
+            findings: entries(findings, project_root).into_iter().collect(),
+        }
+    }
+
+    /// The project's baseline; `Ok(None)` without a baseline file.
+    pub fn load(project_root: &Path) -> Result<Option<Self>, String> {
+        let path = project_root.join(BASELINE_FILE);
+        let text = match std::fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(format!("Failed to read {}: {e}", path.display())),
+        };
+        serde_json::from_str(&text)
+            .map(Some)
+            .map_err(|e| format!("Invalid baseline {}: {e}", path.display()))
+    }
+
+    /// Write the baseline to the project's [`BASELINE_FILE`].
+    pub fn save(&self, project_root: &Path) -> Result<(), String> {
+        let path = project_root.join(BASELINE_FILE);
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .map_err(|e| format!("Failed to create {}: {e}", dir.display()))?;
+        }
+        let text = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        std::fs::write(&path, text + "\n")
+            .map_err(|e| format!("Failed to write {}: {e}", path.display()))
+    }
+
+    /// Which of `findings` the baseline accepts, in order.
+    pub fn accepts(&self, findings: &[SecurityFinding], project_root: &Path) -> Vec<bool> {
+        entries(findings, project_root)
+            .iter()
+            .map(|e| self.findings.contains(e))
+            .collect()
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::security::rules::{RuleCategory, Severity};
+
+    fn finding(file: &str, line: usize, matched: &str) -> SecurityFinding {
+        SecurityFinding {
+            rule_id: "SEC-001".to_string(),
+            rule_name: "Eval".to_string(),
+            severity: Severity::High,
+            file_path: file.to_string(),
+            line_number: line,
+            column: 1,
+            matched_text: matched.to_string(),
+            message: "eval".to_string(),
+            fix: None,
+            cwe: None,
+            owasp: None,
+            category: RuleCategory::Injection,
+        }
+    }
+
+    #[test]
+    fn inline_suppressions_name_rules_on_the_line_or_above() {
+        let lines = [
+            "x = eval(a)  # codegraph-ignore: sec-001, acme/NO-EVAL",
+            "# codegraph-ignore: SEC-002",
+            "y = eval(b)",
+            "z = eval(c)  # codegraph-ignore: SEC-003",
+            "w = eval(d)",
+        ];
+        assert!(is_suppressed(&lines, 1, "SEC-001"));
+        assert!(is_suppressed(&lines, 1, "acme/NO-EVAL"));
+        assert!(!is_suppressed(&lines, 1, "SEC-002"));
+        assert!(is_suppressed(&lines, 3, "SEC-002"));
+        assert!(!is_suppressed(&lines, 3, "SEC-001"));
+        // A trailing marker only covers its own line.
+        assert!(is_suppressed(&lines, 4, "SEC-003"));
+        assert!(!is_suppressed(&lines, 5, "SEC-003"));
+        assert!(!is_suppressed(&lines, 9, "SEC-001"));
+    }
+
+    #[test]
+    fn baseline_matches_by_rule_file_and_line() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        assert_eq!(Baseline::load(root).unwrap(), None);
+
+        let file = root.join("app.py").to_string_lossy().into_owned();
+        std::fs::write(&file, "x = eval(a)\ny = eval(b)\n").unwrap();
+        let baseline = Baseline::from_findings(&[finding(&file, 1, "eval(")], root);
+        baseline.save(root).unwrap();
+        let loaded = Baseline::load(root).unwrap().unwrap();
+        assert_eq!(loaded, baseline);
+        assert_eq!(loaded.findings.first().unwrap().file, "app.py");
+        assert_eq!(
+            loaded.accepts(
+                &[finding(&file, 1, "eval("), finding(&file, 2, "eval(")],
+                root
+            ),
+            vec![true, false]
+        );
+
+        // Moved down and re-indented: still accepted.
+        std::fs::write(&file, "import os\nif ok:\n    x =  eval(a)\n").unwrap();
+        assert_eq!(
+            loaded.accepts(&[finding(&file, 3, "eval(")], root),
+            vec![true]
+        );
+        let other = root.join("lib.py").to_string_lossy().into_owned();
+        std::fs::write(&other, "x = eval(a)\n").unwrap();
+        assert_eq!(
+            loaded.accepts(&[finding(&other, 1, "eval(")], root),
+            vec![false]
+        );
+
+        std::fs::write(root.join(BASELINE_FILE), "not json").unwrap();
+        assert!(Baseline::load(root).is_err());
+    }
+}
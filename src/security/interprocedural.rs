@@ -0,0 +1,393 @@
+//! Interprocedural taint analysis over the indexed call graph.
+//!
+//! [`taint`](super::taint) follows untrusted data within one source text.
+//! Here the same line-based propagation runs over each function body in the
+//! index, and when a tainted value is passed to a function the graph has a
+//! `calls` edge to, the argument is mapped onto the callee's parameter (see
+//! [`positional_params`]) and the callee is analyzed with that parameter
+//! tainted. Sinks reached through helpers in other files are reported with
+//! the whole chain: the source, each call, and the sink.
+//!
+//! Sanitizers count anywhere along the chain before the sink. Return values
+//! are approximated the way `taint` does: assigning the result of a call
+//! with a tainted argument taints the target.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use crate::error::Result;
+use crate::graph::dataflow::{call_arguments, positional_params};
+use crate::graph::store::GraphStore;
+use crate::security::rules::is_test_file;
+use crate::security::taint::{
+    check_sanitization, find_sinks, find_taint_sources, parse_assignment, propagate_method_taint,
+    sink_to_vuln_type, TaintFlow, TaintSink, TaintSource, TaintStep,
+};
+use crate::types::{CodeNode, EdgeKind, Language, NodeKind};
+
+/// Calls followed from a source before giving up.
+pub const DEFAULT_MAX_HOPS: usize = 4;
+
+/// Node batch size when loading function bodies.
+const BATCH_SIZE: usize = 500;
+
+/// A function body with what the analysis needs from it.
+struct Function {
+    node: CodeNode,
+    language: &'static str,
+    params: Vec<Option<String>>,
+    callees: Vec<String>,
+    /// Sinks, with line numbers relative to the body.
+    sinks: Vec<TaintSink>,
+}
+
+impl Function {
+    fn body(&self) -> &str {
+        self.node.body.as_deref().unwrap_or_default()
+    }
+
+    /// The file line of body line `line`.
+    fn file_line(&self, line: usize) -> usize {
+        self.node.start_line as usize + line - 1
+    }
+}
+
+/// One function to analyze with some of its variables tainted.
+struct Visit {
+    function: String,
+    tainted: HashSet<String>,
+    /// Body line the analysis starts at.
+    from_line: usize,
+    source: TaintSource,
+    steps: Vec<TaintStep>,
+    /// Lines passed in the callers, checked for sanitizers.
+    passed: Vec<String>,
+}
+
+/// The pattern set covering `language`; TypeScript and JSX share the
+/// JavaScript sources and sinks.
+fn taint_language(language: Language) -> &'static str {
+    match language {
+        Language::TypeScript | Language::Tsx | Language::Jsx => "javascript",
+        other => other.as_str(),
+    }
+}
+
+/// Unsanitized flows from taint sources to sinks in the indexed project,
+/// following up to `max_hops` calls. With `scope`, only flows whose source
+/// or sink lies in a file under it.
+pub fn find_interprocedural_flows(
+    store: &GraphStore,
+    scope: Option<&str>,
+    max_hops: usize,
+) -> Result<Vec<TaintFlow>> {
+    let mut functions: HashMap<String, Function> = HashMap::new();
+    for batch in store.iter_nodes(BATCH_SIZE) {
+        for node in batch? {
+            if !matches!(node.kind, NodeKind::Function | NodeKind::Method)
+                || is_test_file(&node.file_path)
+            {
+                continue;
+            }
+            let Some(body) = node.body.as_deref() else {
+                continue;
+            };
+            let language = taint_language(node.language);
+            functions.insert(
+                node.id.clone(),
+                Function {
+                    params: positional_params(body, &node.name, node.language.as_str()),
+                    sinks: find_sinks(body, language),
+                    callees: Vec::new(),
+                    language,
+                    node,
+                },
+            );
+        }
+    }
+    let ids: Vec<String> = functions.keys().cloned().collect();
+    for id in &ids {
+        let mut callees: Vec<String> = store
+            .get_out_edges(id, Some(EdgeKind::Calls.as_str()))?
+            .into_iter()
+            .map(|e| e.target)
+            .filter(|t| functions.contains_key(t))
+            .collect();
+        callees.sort();
+        callees.dedup();
+        if let Some(function) = functions.get_mut(id) {
+            function.callees = callees;
+        }
+    }
+
+    let mut queue: VecDeque<Visit> = VecDeque::new();
+    let mut sorted_ids = ids;
+    sorted_ids.sort();
+    for id in &sorted_ids {
+        let function = &functions[id];
+        for mut source in find_taint_sources(function.body(), function.language) {
+            let from_line = source.line_number;
+            source.file_path = function.node.file_path.clone();
+            source.line_number = function.file_line(from_line);
+            queue.push_back(Visit {
+                function: id.clone(),
+                tainted: HashSet::from([source.variable.clone()]),
+                from_line,
+                steps: vec![TaintStep {
+                    file_path: source.file_path.clone(),
+                    line_number: source.line_number,
+                    code: source.expression.clone(),
+                    variable: source.variable.clone(),
+                    operation: "source".to_string(),
+                }],
+                passed: Vec::new(),
+                source,
+            });
+        }
+    }
+
+    let mut visited: HashSet<(String, String, String, usize)> = HashSet::new();
+    let mut reported: HashSet<(String, usize, String, usize)> = HashSet::new();
+    let mut flows = Vec::new();
+    while let Some(visit) = queue.pop_front() {
+        let function = &functions[&visit.function];
+        let lines: Vec<&str> = function.body().lines().collect();
+        let passed: Vec<&str> = visit.passed.iter().map(String::as_str).collect();
+        let mut tainted = visit.tainted.clone();
+        let tainted_in = |tainted: &HashSet<String>, text: &str| -> Option<String> {
+            tainted
+                .iter()
+                .filter(|tv| text.contains(tv.as_str()))
+                .min()
+                .cloned()
+        };
+
+        for line_num in visit.from_line..=lines.len() {
+            let line = lines[line_num - 1];
+            if let Some((lhs, rhs)) = parse_assignment(line) {
+                if tainted_in(&tainted, &rhs).is_some() {
+                    tainted.insert(lhs);
+                }
+            }
+            propagate_method_taint(line, &mut tainted);
+
+            for sink in function.sinks.iter().filter(|s| s.line_number == line_num) {
+                let Some(variable) = tainted_in(&tainted, &sink.expression) else {
+                    continue;
+                };
+                if check_sanitization(&lines, visit.from_line, line_num, &sink.kind)
+                    || check_sanitization(&passed, 1, passed.len() + 1, &sink.kind)
+                {
+                    continue;
+                }
+                let sink = TaintSink {
+                    file_path: function.node.file_path.clone(),
+                    line_number: function.file_line(line_num),
+                    ..sink.clone()
+                };
+                if !reported.insert((
+                    visit.source.file_path.clone(),
+                    visit.source.line_number,
+                    sink.file_path.clone(),
+                    sink.line_number,
+                )) {
+                    continue;
+                }
+                let mut path = visit.steps.clone();
+                path.push(TaintStep {
+                    file_path: sink.file_path.clone(),
+                    line_number: sink.line_number,
+                    code: sink.expression.clone(),
+                    variable,
+                    operation: "sink".to_string(),
+                });
+                flows.push(TaintFlow {
+                    source: visit.source.clone(),
+                    vulnerability_type: sink_to_vuln_type(&sink.kind),
+                    sink,
+                    path,
+                    is_sanitized: false,
+                });
+            }
+
+            // The first step is the source; every later one is a call.
+            if visit.steps.len() > max_hops {
+                continue;
+            }
+            for callee_id in &function.callees {
+                let callee = &functions[callee_id];
+                let Some(args) = call_arguments(line, &callee.node.name) else {
+                    continue;
+                };
+                for (arg, param) in args.iter().zip(&callee.params) {
+                    let Some(param) = param else {
+                        continue;
+                    };
+                    if tainted_in(&tainted, arg).is_none()
+                        || !visited.insert((
+                            callee_id.clone(),
+                            param.clone(),
+                            visit.source.file_path.clone(),
+                            visit.source.line_number,
+                        ))
+                    {
+                        continue;
+                    }
+                    let mut steps = visit.steps.clone();
+                    steps.push(TaintStep {
+                        file_path: function.node.file_path.clone(),
+                        line_number: function.file_line(line_num),
+                        code: line.trim().to_string(),
+                        variable: param.clone(),
+                        operation: "call".to_string(),
+                    });
+                    let mut passed = visit.passed.clone();
+                    passed.extend(
+                        lines[visit.from_line - 1..line_num]
+                            .iter()
+                            .map(|l| l.to_string()),
+                    );
+                    queue.push_back(Visit {
+                        function: callee_id.clone(),
+                        tainted: HashSet::from([param.clone()]),
+                        from_line: 1,
+                        source: visit.source.clone(),
+                        steps,
+                        passed,
+                    });
+                }
+            }
+        }
+    }
+
+    if let Some(scope) = scope {
+        flows.retain(|f| {
+            f.source.file_path.starts_with(scope) || f.sink.file_path.starts_with(scope)
+        });
+    }
+    flows.sort_by(|a, b| {
+        (
+            &a.source.file_path,
+            a.source.line_number,
+            &a.sink.file_path,
+            a.sink.line_number,
+        )
+            .cmp(&(
+                &b.source.file_path,
+                b.source.line_number,
+                &b.sink.file_path,
+                b.sink.line_number,
+            ))
+    });
+    Ok(flows)
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::schema::initialize_database;
+    use crate::types::CodeEdge;
+
+    fn function(id: &str, file: &str, line: u32, body: &str) -> CodeNode {
+        let name = body
+            .split_once("def ")
+            .and_then(|(_, rest)| rest.split_once('('))
+            .map_or(id, |(name, _)| name);
+        CodeNode {
+            id: id.to_string(),
+            name: name.to_string(),
+            qualified_name: None,
+            kind: NodeKind::Function,
+            file_path: file.to_string(),
+            start_line: line,
+            end_line: line + body.lines().count() as u32 - 1,
+            start_column: 0,
+            end_column: 0,
+            language: Language::Python,
+            body: Some(body.to_string()),
+            documentation: None,
+            exported: None,
+        }
+    }
+
+    fn calls(source: &str, target: &str, file: &str) -> CodeEdge {
+        CodeEdge {
+            source: source.to_string(),
+            target: target.to_string(),
+            kind: EdgeKind::Calls,
+            file_path: file.to_string(),
+            line: 1,
+            metadata: None,
+        }
+    }
+
+    #[test]
+    fn taint_follows_arguments_into_helpers_in_other_files() {
+        let store = GraphStore::from_connection(initialize_database(":memory:").unwrap());
+        store
+            .upsert_nodes(&[
+                function(
+                    "view",
+                    "app/views.py",
+                    10,
+                    "def view():\n    name = request.args.get(\"name\")\n    greet(name, 1)\n    archive(shlex.quote(name))",
+                ),
+                function(
+                    "greet",
+                    "app/util.py",
+                    3,
+                    "def greet(who, times):\n    msg = \"hi \" + who\n    run(msg)",
+                ),
+                function("run", "app/shell.py", 1, "def run(cmd):\n    os.system(cmd)"),
+                function("archive", "app/shell.py", 5, "def archive(path):\n    os.system(path)"),
+            ])
+            .unwrap();
+        store
+            .upsert_edges(&[
+                calls("view", "greet", "app/views.py"),
+                calls("view", "archive", "app/views.py"),
+                calls("greet", "run", "app/util.py"),
+            ])
+            .unwrap();
+
+        let flows = find_interprocedural_flows(&store, None, DEFAULT_MAX_HOPS).unwrap();
+        // The archive call is sanitized at the call site.
+        assert_eq!(flows.len(), 1);
+        let flow = &flows[0];
+        assert_eq!(flow.vulnerability_type, "Command Injection");
+        assert_eq!(
+            (flow.source.file_path.as_str(), flow.source.line_number),
+            ("app/views.py", 11)
+        );
+        assert_eq!(
+            (flow.sink.file_path.as_str(), flow.sink.line_number),
+            ("app/shell.py", 2)
+        );
+        let steps: Vec<(&str, &str, usize)> = flow
+            .path
+            .iter()
+            .map(|s| (s.operation.as_str(), s.file_path.as_str(), s.line_number))
+            .collect();
+        assert_eq!(
+            steps,
+            vec![
+                ("source", "app/views.py", 11),
+                ("call", "app/views.py", 12),
+                ("call", "app/util.py", 5),
+                ("sink", "app/shell.py", 2),
+            ]
+        );
+
+        assert!(find_interprocedural_flows(&store, None, 1)
+            .unwrap()
+            .is_empty());
+        assert!(
+            find_interprocedural_flows(&store, Some("lib/"), DEFAULT_MAX_HOPS)
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
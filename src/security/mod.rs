@@ -2,12 +2,22 @@
 //!
 //! This module provides:
 //! - YAML-based security rule definitions with pattern, crypto, secret, and taint rule types
+//! - Custom rule packs from `.codegraph/rules/`, validated and namespaced
 //! - Code scanning engine for OWASP Top 10 and CWE Top 25 coverage
-//! - Taint analysis: source→sink tracking with sanitizer awareness
+//! - Inline suppressions and a committed baseline of accepted findings
+//! - Taint analysis: source→sink tracking with sanitizer awareness, within one
+//!   source text or across the call graph of the index
+//! - CVSS-like risk scores, deduplication of findings across rules and taint
+//!   flows, and per-directory risk
 //! - Vulnerability explanation and fix suggestion
 
+pub mod baseline;
+pub mod interprocedural;
+pub mod packs;
+pub mod risk;
 pub mod rules;
 pub mod scanner;
+pub mod secrets;
 pub mod taint;
 
 // Re-export the primary public API so callers can use `security::*` directly.
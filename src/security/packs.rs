@@ -0,0 +1,312 @@
+//! Custom rule packs: org-specific rules next to the bundled ones.
+//!
+//! Every `*.yaml` or `*.yml` file in `.codegraph/rules/` is a rule pack in
+//! the format of the bundled rulesets (`name`, `description`, `rules`).
+//! Packs are validated before use: unknown keys, missing or duplicate rule
+//! IDs, patterns that do not compile and unknown languages are errors, and
+//! a pack with errors is skipped as a whole. Rule IDs are namespaced with
+//! the pack's file stem, so `NO-EVAL` in `acme.yaml` is reported as
+//! `acme/NO-EVAL` and never collides with a bundled rule or another pack.
+//!
+//! `codegraph rules list` shows every rule in effect and `codegraph rules
+//! validate` checks the packs, exiting non-zero on errors.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use regex::Regex;
+
+use super::rules::{load_bundled_rules, Ruleset, SecurityRule};
+use crate::types::Language;
+
+/// Directory of rule packs, relative to the project root.
+pub const RULES_DIR: &str = ".codegraph/rules";
+
+const RULESET_KEYS: &[&str] = &["name", "version", "description", "rules"];
+const RULE_KEYS: &[&str] = &[
+    "id",
+    "name",
+    "severity",
+    "cwe",
+    "owasp",
+    "languages",
+    "pattern",
+    "message",
+    "fix",
+    "category",
+];
+
+/// A validated rule pack, its rule IDs namespaced.
+#[derive(Debug, Clone)]
+pub struct RulePack {
+    /// The file stem, used as the rule ID namespace.
+    pub name: String,
+    pub path: PathBuf,
+    pub description: String,
+    pub rules: Vec<SecurityRule>,
+}
+
+/// A problem that keeps a pack from loading.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PackError {
+    pub path: PathBuf,
+    /// The rule the problem is in, by its ID or position.
+    pub rule: Option<String>,
+    pub message: String,
+}
+
+impl std::fmt::Display for PackError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.rule {
+            Some(rule) => write!(
+                f,
+                "{}: rule {}: {}",
+                self.path.display(),
+                rule,
+                self.message
+            ),
+            None => write!(f, "{}: {}", self.path.display(), self.message),
+        }
+    }
+}
+
+fn valid_id(id: &str) -> bool {
+    !id.is_empty()
+        && id
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_' | b'.'))
+}
+
+/// Load and validate one pack file.
+pub fn load_pack(path: &Path) -> Result<RulePack, Vec<PackError>> {
+    let error = |rule: Option<String>, message: String| PackError {
+        path: path.to_path_buf(),
+        rule,
+        message,
+    };
+    let name = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    if !valid_id(&name) {
+        return Err(vec![error(
+            None,
+            "file name must be letters, digits, '-', '_' or '.'".to_string(),
+        )]);
+    }
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| vec![error(None, format!("cannot read: {e}"))])?;
+    let value: serde_yaml::Value = serde_yaml::from_str(&content)
+        .map_err(|e| vec![error(None, format!("YAML parse error: {e}"))])?;
+
+    // Unknown keys are most often typos that would silently drop a field.
+    let mut errors = Vec::new();
+    let unknown = |map: &serde_yaml::Mapping, known: &[&str]| -> Vec<String> {
+        map.keys()
+            .filter_map(|k| k.as_str())
+            .filter(|k| !known.contains(k))
+            .map(str::to_string)
+            .collect()
+    };
+    let Some(top) = value.as_mapping() else {
+        return Err(vec![error(
+            None,
+            "expected a mapping with a `rules` list".to_string(),
+        )]);
+    };
+    for key in unknown(top, RULESET_KEYS) {
+        errors.push(error(None, format!("unknown key `{key}`")));
+    }
+    if let Some(rules) = value.get("rules").and_then(|r| r.as_sequence()) {
+        for (i, rule) in rules.iter().enumerate() {
+            let label = rule
+                .get("id")
+                .and_then(|id| id.as_str())
+                .map_or_else(|| format!("#{}", i + 1), str::to_string);
+            match rule.as_mapping() {
+                Some(map) => {
+                    for key in unknown(map, RULE_KEYS) {
+                        errors.push(error(Some(label.clone()), format!("unknown key `{key}`")));
+                    }
+                }
+                None => errors.push(error(Some(label), "expected a mapping".to_string())),
+            }
+        }
+    }
+    let ruleset: Ruleset = match serde_yaml::from_value(value) {
+        Ok(r) => r,
+        Err(e) => {
+            errors.push(error(None, e.to_string()));
+            return Err(errors);
+        }
+    };
+
+    let mut seen = HashSet::new();
+    for rule in &ruleset.rules {
+        let label = Some(rule.id.clone());
+        if !valid_id(&rule.id) {
+            errors.push(error(
+                label.clone(),
+                "id must be letters, digits, '-', '_' or '.'".to_string(),
+            ));
+        } else if !seen.insert(rule.id.as_str()) {
+            errors.push(error(label.clone(), "duplicate id".to_string()));
+        }
+        if let Err(e) = Regex::new(&rule.pattern) {
+            // The regex error spans several lines; its last one says what is wrong.
+            let text = e.to_string();
+            let reason = text.lines().last().unwrap_or_default();
+            let reason = reason.strip_prefix("error: ").unwrap_or(reason);
+            errors.push(error(label.clone(), format!("invalid pattern: {reason}")));
+        }
+        if rule.message.trim().is_empty() {
+            errors.push(error(label.clone(), "message is empty".to_string()));
+        }
+        for language in &rule.languages {
+            if Language::from_str_loose(language).is_none() {
+                errors.push(error(
+                    label.clone(),
+                    format!("unknown language `{language}`"),
+                ));
+            }
+        }
+    }
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+
+    let rules = ruleset
+        .rules
+        .into_iter()
+        .map(|mut rule| {
+            rule.id = format!("{name}/{}", rule.id);
+            rule
+        })
+        .collect();
+    Ok(RulePack {
+        name,
+        path: path.to_path_buf(),
+        description: ruleset.description,
+        rules,
+    })
+}
+
+/// Load every pack in `dir`, in file name order. Packs with errors are
+/// left out and their errors returned. A missing directory has no packs.
+pub fn load_packs(dir: &Path) -> (Vec<RulePack>, Vec<PackError>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return (Vec::new(), Vec::new());
+    };
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| {
+            p.is_file()
+                && p.extension()
+                    .is_some_and(|ext| ext == "yaml" || ext == "yml")
+        })
+        .collect();
+    paths.sort();
+
+    let mut packs = Vec::new();
+    let mut errors = Vec::new();
+    for path in paths {
+        match load_pack(&path) {
+            Ok(pack) => packs.push(pack),
+            Err(e) => errors.extend(e),
+        }
+    }
+    (packs, errors)
+}
+
+/// The rules in effect for a project: the bundled rules plus its valid
+/// packs. Invalid packs are logged and skipped.
+pub fn project_rules(project_root: &Path) -> Vec<SecurityRule> {
+    let mut rules = load_bundled_rules();
+    let (packs, errors) = load_packs(&project_root.join(RULES_DIR));
+    for error in errors {
+        tracing::warn!("Skipping invalid rule pack: {error}");
+    }
+    rules.extend(packs.into_iter().flat_map(|p| p.rules));
+    rules
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ACME: &str = r#"
+name: Acme
+description: Acme house rules
+rules:
+  - id: NO-EVAL
+    name: No eval
+    severity: high
+    languages: [python, javascript]
+    pattern: '\beval\('
+    message: eval is banned at Acme
+    category: injection
+"#;
+
+    #[test]
+    fn packs_are_validated_and_namespaced() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("acme.yaml"), ACME).unwrap();
+        std::fs::write(
+            dir.path().join("broken.yml"),
+            r#"
+rules:
+  - id: BAD
+    name: Bad
+    severity: low
+    pattern: '('
+    message: ""
+    languages: [cobol]
+    sevrity: high
+  - id: BAD
+    name: Bad again
+    severity: low
+    pattern: x
+    message: dup
+"#,
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("notes.txt"), "ignored").unwrap();
+
+        let (packs, errors) = load_packs(dir.path());
+        assert_eq!(packs.len(), 1);
+        assert_eq!(packs[0].name, "acme");
+        assert_eq!(packs[0].description, "Acme house rules");
+        assert_eq!(packs[0].rules[0].id, "acme/NO-EVAL");
+
+        let messages: Vec<String> = errors
+            .iter()
+            .map(|e| format!("{}: {}", e.rule.as_deref().unwrap_or("-"), e.message))
+            .collect();
+        assert!(messages.contains(&"BAD: unknown key `sevrity`".to_string()));
+        assert!(messages.contains(&"BAD: invalid pattern: unclosed group".to_string()));
+        assert!(messages.contains(&"BAD: message is empty".to_string()));
+        assert!(messages.contains(&"BAD: unknown language `cobol`".to_string()));
+        assert!(messages.contains(&"BAD: duplicate id".to_string()));
+
+        let missing = load_pack(&dir.path().join("missing.yaml")).unwrap_err();
+        assert!(missing[0].message.starts_with("cannot read"));
+        assert_eq!(load_packs(&dir.path().join("none")).0.len(), 0);
+    }
+
+    #[test]
+    fn project_rules_add_packs_to_bundled_rules() {
+        let dir = tempfile::tempdir().unwrap();
+        let bundled = load_bundled_rules().len();
+        assert_eq!(project_rules(dir.path()).len(), bundled);
+
+        std::fs::create_dir_all(dir.path().join(RULES_DIR)).unwrap();
+        std::fs::write(dir.path().join(RULES_DIR).join("acme.yaml"), ACME).unwrap();
+        let rules = project_rules(dir.path());
+        assert_eq!(rules.len(), bundled + 1);
+        assert!(rules.iter().any(|r| r.id == "acme/NO-EVAL"));
+    }
+}
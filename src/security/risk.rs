@@ -0,0 +1,504 @@
+//! Risk scoring and aggregation.
+//!
+//! Rule matches, secrets and taint flows all come as the scanner's
+//! [`SecurityFinding`]; [`from_taint_flow`] converts a flow, reporting it at
+//! its sink under a `TAINT-*` rule ID. [`score`] rates a finding on a
+//! CVSS-like 0.0–10.0 scale: the impact of its severity, weighed by how
+//! exposed its category is, raised when a taint flow confirms untrusted
+//! input reaches it or when several rules agree.
+//!
+//! [`aggregate`] merges the findings for one issue — same file, line and
+//! category — into a single [`RiskFinding`], ranks them by score and rolls
+//! the scores up per directory.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use super::baseline::is_suppressed;
+use super::rules::{RuleCategory, SecurityRule, Severity};
+use super::scanner::{scan_source, walk_source_files, SecurityFinding, SecuritySummary};
+use super::taint::{find_injection_vulnerabilities, TaintFlow};
+use crate::sandbox;
+
+/// Rule ID prefix of findings converted from taint flows.
+pub const TAINT_RULE_PREFIX: &str = "TAINT-";
+
+/// Directory name of files at the project root.
+pub const ROOT_DIRECTORY: &str = ".";
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// One issue, possibly reported by several rules and a taint flow.
+#[derive(Debug, Clone)]
+pub struct RiskFinding {
+    /// The most severe of the merged findings.
+    pub finding: SecurityFinding,
+    /// Every rule that reported the issue, in report order.
+    pub rule_ids: Vec<String>,
+    /// A taint flow shows untrusted input reaching it.
+    pub taint_confirmed: bool,
+    pub score: f64,
+}
+
+/// Combined risk of the findings in one directory (not its subdirectories).
+#[derive(Debug, Clone, PartialEq)]
+pub struct DirectoryRisk {
+    /// Project-relative directory, [`ROOT_DIRECTORY`] for the project root.
+    pub directory: String,
+    pub score: f64,
+    pub findings: usize,
+    /// Findings rated critical by their score.
+    pub critical: usize,
+    /// Findings rated high by their score.
+    pub high: usize,
+}
+
+/// Deduplicated, ranked findings of a scan.
+#[derive(Debug, Clone)]
+pub struct RiskReport {
+    /// Highest score first.
+    pub findings: Vec<RiskFinding>,
+    /// Highest score first.
+    pub directories: Vec<DirectoryRisk>,
+    /// Input findings folded into another one for the same issue.
+    pub merged: usize,
+    /// Combined risk of every finding.
+    pub score: f64,
+}
+
+// ---------------------------------------------------------------------------
+// Scoring
+// ---------------------------------------------------------------------------
+
+fn impact(severity: Severity) -> f64 {
+    match severity {
+        Severity::Critical => 9.0,
+        Severity::High => 7.0,
+        Severity::Medium => 4.5,
+        Severity::Low => 2.0,
+        Severity::Info => 0.0,
+    }
+}
+
+/// How directly an attacker can reach an issue of `category`.
+fn exposure(category: RuleCategory) -> f64 {
+    match category {
+        RuleCategory::Injection
+        | RuleCategory::Xss
+        | RuleCategory::PathTraversal
+        | RuleCategory::Deserialization => 1.0,
+        RuleCategory::Secrets | RuleCategory::Authentication => 0.9,
+        RuleCategory::Other => 0.8,
+        RuleCategory::Crypto | RuleCategory::Config => 0.7,
+    }
+}
+
+/// Round up to one decimal, as CVSS does, ignoring floating-point noise.
+fn round_up(score: f64) -> f64 {
+    let scaled = (score * 100_000.0).round() as i64;
+    if scaled % 10_000 == 0 {
+        scaled as f64 / 100_000.0
+    } else {
+        (scaled / 10_000 + 1) as f64 / 10.0
+    }
+}
+
+/// CVSS-like score of `finding`, reported by `rules` distinct rules and
+/// confirmed or not by a taint flow.
+pub fn score(finding: &SecurityFinding, rules: usize, taint_confirmed: bool) -> f64 {
+    let mut score = impact(finding.severity) * exposure(finding.category);
+    if taint_confirmed {
+        score += 1.0;
+    }
+    score += 0.5 * rules.saturating_sub(1).min(2) as f64;
+    round_up(score.min(10.0))
+}
+
+/// The CVSS qualitative rating of `score`.
+pub fn rating(score: f64) -> Severity {
+    match score {
+        s if s >= 9.0 => Severity::Critical,
+        s if s >= 7.0 => Severity::High,
+        s if s >= 4.0 => Severity::Medium,
+        s if s > 0.0 => Severity::Low,
+        _ => Severity::Info,
+    }
+}
+
+/// Combined risk of independent issues: the chance, read off the 0–10
+/// scale, that at least one of them is exploited.
+pub fn combine(scores: impl IntoIterator<Item = f64>) -> f64 {
+    let safe: f64 = scores.into_iter().map(|s| 1.0 - s / 10.0).product();
+    round_up(10.0 * (1.0 - safe))
+}
+
+// ---------------------------------------------------------------------------
+// Taint flows
+// ---------------------------------------------------------------------------
+
+/// Category, CWE, OWASP entry and severity of a taint vulnerability type.
+fn classify(
+    vulnerability_type: &str,
+) -> (
+    RuleCategory,
+    Option<&'static str>,
+    Option<&'static str>,
+    Severity,
+) {
+    match vulnerability_type {
+        "SQL Injection" => (
+            RuleCategory::Injection,
+            Some("CWE-89"),
+            Some("A03:2021"),
+            Severity::Critical,
+        ),
+        "Command Injection" => (
+            RuleCategory::Injection,
+            Some("CWE-78"),
+            Some("A03:2021"),
+            Severity::Critical,
+        ),
+        "Code Injection" => (
+            RuleCategory::Injection,
+            Some("CWE-94"),
+            Some("A03:2021"),
+            Severity::Critical,
+        ),
+        "Cross-Site Scripting (XSS)" => (
+            RuleCategory::Xss,
+            Some("CWE-79"),
+            Some("A03:2021"),
+            Severity::High,
+        ),
+        "Path Traversal" => (
+            RuleCategory::PathTraversal,
+            Some("CWE-22"),
+            Some("A01:2021"),
+            Severity::High,
+        ),
+        "Insecure Deserialization" => (
+            RuleCategory::Deserialization,
+            Some("CWE-502"),
+            Some("A08:2021"),
+            Severity::High,
+        ),
+        _ => (RuleCategory::Other, None, None, Severity::Medium),
+    }
+}
+
+/// `flow` as a finding at its sink in `file_path`. The rule ID is
+/// [`TAINT_RULE_PREFIX`] followed by the vulnerability type, e.g.
+/// `TAINT-SQL-INJECTION`.
+pub fn from_taint_flow(file_path: &str, flow: &TaintFlow) -> SecurityFinding {
+    let (category, cwe, owasp, severity) = classify(&flow.vulnerability_type);
+    let slug: String = flow
+        .vulnerability_type
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|w| !w.is_empty() && *w != "XSS")
+        .collect::<Vec<_>>()
+        .join("-")
+        .to_ascii_uppercase();
+    SecurityFinding {
+        rule_id: format!("{TAINT_RULE_PREFIX}{slug}"),
+        rule_name: flow.vulnerability_type.clone(),
+        severity,
+        file_path: file_path.to_string(),
+        line_number: flow.sink.line_number,
+        column: 0,
+        matched_text: flow.sink.expression.trim().to_string(),
+        message: format!(
+            "{} from line {} reaches {} unsanitized",
+            flow.source.kind, flow.source.line_number, flow.sink.function
+        ),
+        fix: None,
+        cwe: cwe.map(str::to_string),
+        owasp: owasp.map(str::to_string),
+        category,
+    }
+}
+
+/// Taint flows in `source` as findings, with how many a `codegraph-ignore:`
+/// comment suppressed.
+pub fn taint_findings(path: &Path, source: &str, language: &str) -> (Vec<SecurityFinding>, usize) {
+    let path_str = path.display().to_string();
+    let lines: Vec<&str> = source.lines().collect();
+    let mut suppressed = 0;
+    let findings = find_injection_vulnerabilities(source, language)
+        .iter()
+        .map(|flow| from_taint_flow(&path_str, flow))
+        .filter(|f| {
+            let hidden = is_suppressed(&lines, f.line_number, &f.rule_id);
+            suppressed += hidden as usize;
+            !hidden
+        })
+        .collect();
+    (findings, suppressed)
+}
+
+/// Scan `dir` with `rules` and taint analysis in one pass. Returns the rule
+/// scan's summary and the taint flows as findings; suppressed flows count in
+/// the summary's `suppressed`.
+pub fn scan_with_taint(
+    dir: &Path,
+    rules: &[SecurityRule],
+    exclude_tests: bool,
+) -> (SecuritySummary, Vec<SecurityFinding>) {
+    let mut summary = SecuritySummary::new();
+    summary.rules_applied = rules.len();
+    let mut taint = Vec::new();
+    let policy = sandbox::current();
+    walk_source_files(
+        dir,
+        exclude_tests,
+        &policy,
+        &mut |path, language, source| {
+            let (file_findings, suppressed) = scan_source(path, source, language, rules);
+            summary.suppressed += suppressed;
+            for f in file_findings {
+                summary.add_finding(f);
+            }
+            let (flows, suppressed) = taint_findings(path, source, language);
+            summary.suppressed += suppressed;
+            taint.extend(flows);
+            summary.files_scanned += 1;
+        },
+    );
+    summary.finalize();
+    (summary, taint)
+}
+
+// ---------------------------------------------------------------------------
+// Aggregation
+// ---------------------------------------------------------------------------
+
+/// Project-relative directory of `file_path`.
+fn directory_of(file_path: &str, project_root: &Path) -> String {
+    let path = Path::new(file_path);
+    let relative = path.strip_prefix(project_root).unwrap_or(path);
+    match relative
+        .parent()
+        .map(|p| p.to_string_lossy().replace('\\', "/"))
+    {
+        Some(dir) if !dir.is_empty() => dir,
+        _ => ROOT_DIRECTORY.to_string(),
+    }
+}
+
+/// Merge, score and rank `findings`, rolling the scores up per directory
+/// relative to `project_root`.
+pub fn aggregate(findings: Vec<SecurityFinding>, project_root: &Path) -> RiskReport {
+    let total = findings.len();
+    let mut groups: Vec<Vec<SecurityFinding>> = Vec::new();
+    let mut index: HashMap<(String, usize, RuleCategory), usize> = HashMap::new();
+    for f in findings {
+        let key = (f.file_path.clone(), f.line_number, f.category);
+        match index.get(&key) {
+            Some(&i) => groups[i].push(f),
+            None => {
+                index.insert(key, groups.len());
+                groups.push(vec![f]);
+            }
+        }
+    }
+
+    let mut ranked: Vec<RiskFinding> = groups
+        .into_iter()
+        .map(|group| {
+            let mut rule_ids: Vec<String> = Vec::new();
+            for f in &group {
+                if !rule_ids.contains(&f.rule_id) {
+                    rule_ids.push(f.rule_id.clone());
+                }
+            }
+            let taint_confirmed = rule_ids.iter().any(|id| id.starts_with(TAINT_RULE_PREFIX));
+            let rules = rule_ids
+                .iter()
+                .filter(|id| !id.starts_with(TAINT_RULE_PREFIX))
+                .count();
+            let finding = group
+                .into_iter()
+                .reduce(|best, f| if f.severity > best.severity { f } else { best })
+                .expect("groups are never empty");
+            RiskFinding {
+                score: score(&finding, rules.max(1), taint_confirmed),
+                finding,
+                rule_ids,
+                taint_confirmed,
+            }
+        })
+        .collect();
+    ranked.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then_with(|| a.finding.file_path.cmp(&b.finding.file_path))
+            .then_with(|| a.finding.line_number.cmp(&b.finding.line_number))
+    });
+
+    let mut by_directory: HashMap<String, Vec<f64>> = HashMap::new();
+    for r in &ranked {
+        by_directory
+            .entry(directory_of(&r.finding.file_path, project_root))
+            .or_default()
+            .push(r.score);
+    }
+    let mut directories: Vec<DirectoryRisk> = by_directory
+        .into_iter()
+        .map(|(directory, scores)| DirectoryRisk {
+            directory,
+            score: combine(scores.iter().copied()),
+            findings: scores.len(),
+            critical: scores
+                .iter()
+                .filter(|s| rating(**s) == Severity::Critical)
+                .count(),
+            high: scores
+                .iter()
+                .filter(|s| rating(**s) == Severity::High)
+                .count(),
+        })
+        .collect();
+    directories.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then_with(|| a.directory.cmp(&b.directory))
+    });
+
+    RiskReport {
+        score: combine(ranked.iter().map(|r| r.score)),
+        merged: total - ranked.len(),
+        findings: ranked,
+        directories,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn finding(
+        file: &str,
+        line: usize,
+        rule: &str,
+        severity: Severity,
+        category: RuleCategory,
+    ) -> SecurityFinding {
+        SecurityFinding {
+            rule_id: rule.to_string(),
+            rule_name: rule.to_string(),
+            severity,
+            file_path: file.to_string(),
+            line_number: line,
+            column: 0,
+            matched_text: String::new(),
+            message: String::new(),
+            fix: None,
+            cwe: None,
+            owasp: None,
+            category,
+        }
+    }
+
+    #[test]
+    fn scores_follow_severity_exposure_and_corroboration() {
+        let sqli = finding("a.py", 1, "R1", Severity::High, RuleCategory::Injection);
+        assert_eq!(score(&sqli, 1, false), 7.0);
+        assert_eq!(score(&sqli, 1, true), 8.0);
+        assert_eq!(score(&sqli, 5, true), 9.0);
+        let weak_hash = finding("a.py", 2, "R2", Severity::High, RuleCategory::Crypto);
+        assert_eq!(score(&weak_hash, 1, false), 4.9);
+        let critical = finding("a.py", 3, "R3", Severity::Critical, RuleCategory::Injection);
+        assert_eq!(score(&critical, 3, true), 10.0);
+
+        assert_eq!(rating(9.0), Severity::Critical);
+        assert_eq!(rating(6.9), Severity::Medium);
+        assert_eq!(rating(0.0), Severity::Info);
+        assert_eq!(combine([5.0, 5.0]), 7.5);
+        assert_eq!(combine([]), 0.0);
+    }
+
+    #[test]
+    fn duplicates_merge_and_directories_roll_up() {
+        let root = Path::new("/repo");
+        let report = aggregate(
+            vec![
+                finding(
+                    "/repo/src/db.py",
+                    4,
+                    "SQL-1",
+                    Severity::High,
+                    RuleCategory::Injection,
+                ),
+                finding(
+                    "/repo/src/db.py",
+                    4,
+                    "SQL-2",
+                    Severity::Medium,
+                    RuleCategory::Injection,
+                ),
+                finding(
+                    "/repo/src/db.py",
+                    4,
+                    "TAINT-SQL-INJECTION",
+                    Severity::Critical,
+                    RuleCategory::Injection,
+                ),
+                finding(
+                    "/repo/src/db.py",
+                    4,
+                    "WEAK-HASH",
+                    Severity::Low,
+                    RuleCategory::Crypto,
+                ),
+                finding(
+                    "/repo/setup.py",
+                    1,
+                    "DEBUG",
+                    Severity::Medium,
+                    RuleCategory::Config,
+                ),
+            ],
+            root,
+        );
+        assert_eq!(report.merged, 2);
+        assert_eq!(report.findings.len(), 3);
+        let top = &report.findings[0];
+        assert_eq!(top.rule_ids, vec!["SQL-1", "SQL-2", "TAINT-SQL-INJECTION"]);
+        assert!(top.taint_confirmed);
+        assert_eq!(top.finding.severity, Severity::Critical);
+        assert_eq!(top.score, 10.0);
+
+        let dirs: Vec<_> = report
+            .directories
+            .iter()
+            .map(|d| (d.directory.as_str(), d.findings))
+            .collect();
+        assert_eq!(dirs, vec![("src", 2), (".", 1)]);
+        assert_eq!(report.directories[0].critical, 1);
+        assert_eq!(report.score, 10.0);
+    }
+
+    #[test]
+    fn taint_flows_become_findings_at_their_sink() {
+        let source = "username = request.args.get('name')\nquery = \"SELECT * FROM users WHERE name = '\" + username + \"'\"\ncursor.execute(query)\n";
+        let (findings, suppressed) = taint_findings(Path::new("app.py"), source, "python");
+        assert_eq!(suppressed, 0);
+        let f = &findings[0];
+        assert_eq!(f.rule_id, "TAINT-SQL-INJECTION");
+        assert_eq!((f.line_number, f.category), (3, RuleCategory::Injection));
+        assert_eq!(f.cwe.as_deref(), Some("CWE-89"));
+
+        let ignored = source.replace(
+            "cursor.execute(query)",
+            "cursor.execute(query)  # codegraph-ignore: TAINT-SQL-INJECTION",
+        );
+        let (findings, suppressed) = taint_findings(Path::new("app.py"), &ignored, "python");
+        assert!(findings.is_empty());
+        assert!(suppressed > 0);
+    }
+}
@@ -2,18 +2,22 @@
 //!
 //! Builds on the rules engine to scan files and directories, aggregate findings
 //! into summaries, and provide vulnerability explanations and fix suggestions.
+//! Matches suppressed by a `codegraph-ignore:` comment are left out.
 
 use std::collections::HashMap;
 use std::path::Path;
 
+use super::baseline::{is_suppressed, Baseline};
 use super::rules::{self, load_bundled_rules, match_rule, RuleCategory, SecurityRule, Severity};
+use crate::sandbox::{self, SandboxPolicy};
 use crate::types::Language;
 
 // ---------------------------------------------------------------------------
 // Types
 // ---------------------------------------------------------------------------
 
-/// A single security finding produced by scanning source code.
+/// A single security finding: a rule match, a detected secret, or a taint
+/// flow converted by [`super::risk::from_taint_flow`].
 #[derive(Debug, Clone)]
 pub struct SecurityFinding {
     pub rule_id: String,
@@ -43,10 +47,14 @@ pub struct SecuritySummary {
     pub rules_applied: usize,
     pub findings: Vec<SecurityFinding>,
     pub top_issues: Vec<(String, usize)>,
+    /// Findings left out for a `codegraph-ignore:` comment.
+    pub suppressed: usize,
+    /// Findings left out because the baseline accepts them.
+    pub baselined: usize,
 }
 
 impl SecuritySummary {
-    fn new() -> Self {
+    pub(crate) fn new() -> Self {
         SecuritySummary {
             total_findings: 0,
             critical: 0,
@@ -58,10 +66,12 @@ impl SecuritySummary {
             rules_applied: 0,
             findings: Vec::new(),
             top_issues: Vec::new(),
+            suppressed: 0,
+            baselined: 0,
         }
     }
 
-    fn add_finding(&mut self, f: SecurityFinding) {
+    pub(crate) fn add_finding(&mut self, f: SecurityFinding) {
         match f.severity {
             Severity::Critical => self.critical += 1,
             Severity::High => self.high += 1,
@@ -73,7 +83,29 @@ impl SecuritySummary {
         self.findings.push(f);
     }
 
-    fn finalize(&mut self) {
+    /// Leave out the findings `baseline` accepts, counting them in
+    /// `baselined`.
+    pub fn apply_baseline(&mut self, baseline: &Baseline, project_root: &Path) {
+        let findings = std::mem::take(&mut self.findings);
+        let suppressed = self.suppressed;
+        let rules_applied = self.rules_applied;
+        let files_scanned = self.files_scanned;
+        let accepted = baseline.accepts(&findings, project_root);
+        *self = SecuritySummary::new();
+        for (finding, accepted) in findings.into_iter().zip(accepted) {
+            if accepted {
+                self.baselined += 1;
+            } else {
+                self.add_finding(finding);
+            }
+        }
+        self.suppressed = suppressed;
+        self.rules_applied = rules_applied;
+        self.files_scanned = files_scanned;
+        self.finalize();
+    }
+
+    pub(crate) fn finalize(&mut self) {
         // Build top issues: count by rule_name.
         let mut counts: HashMap<String, usize> = HashMap::new();
         for f in &self.findings {
@@ -112,11 +144,28 @@ pub fn scan_file(
     language: &str,
     rules: &[SecurityRule],
 ) -> Vec<SecurityFinding> {
+    scan_source(path, source, language, rules).0
+}
+
+/// [`scan_file`], also returning how many matches a `codegraph-ignore:`
+/// comment suppressed.
+pub(crate) fn scan_source(
+    path: &Path,
+    source: &str,
+    language: &str,
+    rules: &[SecurityRule],
+) -> (Vec<SecurityFinding>, usize) {
     let path_str = path.display().to_string();
+    let lines: Vec<&str> = source.lines().collect();
     let mut findings = Vec::new();
+    let mut suppressed = 0;
 
     for rule in rules {
         for m in match_rule(rule, source, language) {
+            if is_suppressed(&lines, m.line_number, &rule.id) {
+                suppressed += 1;
+                continue;
+            }
             findings.push(SecurityFinding {
                 rule_id: rule.id.clone(),
                 rule_name: rule.name.clone(),
@@ -135,24 +184,53 @@ pub fn scan_file(
     }
 
     findings.sort_by(|a, b| b.severity.cmp(&a.severity));
-    findings
+    (findings, suppressed)
 }
 
-/// Recursively scan a directory, loading bundled rules.
+/// Recursively scan a directory, loading bundled rules. In an untrusted
+/// workspace, files the sandbox policy rejects are skipped.
 pub fn scan_directory(dir: &Path, rules: &[SecurityRule], exclude_tests: bool) -> SecuritySummary {
+    scan_directory_with_progress(dir, rules, exclude_tests, &|_| {})
+}
+
+/// [`scan_directory`], calling `on_file` with the number of files scanned
+/// so far after each file.
+pub fn scan_directory_with_progress(
+    dir: &Path,
+    rules: &[SecurityRule],
+    exclude_tests: bool,
+    on_file: &dyn Fn(usize),
+) -> SecuritySummary {
     let mut summary = SecuritySummary::new();
     summary.rules_applied = rules.len();
 
-    scan_dir_recursive(dir, rules, exclude_tests, &mut summary);
+    let policy = sandbox::current();
+    walk_source_files(
+        dir,
+        exclude_tests,
+        &policy,
+        &mut |path, language, source| {
+            let (file_findings, suppressed) = scan_source(path, source, language, rules);
+            summary.suppressed += suppressed;
+            for f in file_findings {
+                summary.add_finding(f);
+            }
+            summary.files_scanned += 1;
+            on_file(summary.files_scanned);
+        },
+    );
     summary.finalize();
     summary
 }
 
-fn scan_dir_recursive(
+/// Call `visit` with the path, language and contents of every source file
+/// under `dir`, skipping hidden and build directories, files `policy`
+/// rejects and, with `exclude_tests`, test files.
+pub(crate) fn walk_source_files(
     dir: &Path,
-    rules: &[SecurityRule],
     exclude_tests: bool,
-    summary: &mut SecuritySummary,
+    policy: &SandboxPolicy,
+    visit: &mut dyn FnMut(&Path, &str, &str),
 ) {
     let entries = match std::fs::read_dir(dir) {
         Ok(e) => e,
@@ -176,8 +254,12 @@ fn scan_dir_recursive(
             }
         }
 
+        if !policy.allows_file(&path) {
+            continue;
+        }
+
         if path.is_dir() {
-            scan_dir_recursive(&path, rules, exclude_tests, summary);
+            walk_source_files(&path, exclude_tests, policy, visit);
             continue;
         }
 
@@ -195,7 +277,7 @@ fn scan_dir_recursive(
             .map(|e| format!(".{}", e))
             .unwrap_or_default();
         let language = match Language::from_extension(&ext) {
-            Some(lang) => lang.as_str().to_string(),
+            Some(lang) => lang.as_str(),
             None => continue,
         };
 
@@ -204,29 +286,29 @@ fn scan_dir_recursive(
             Ok(s) => s,
             Err(_) => continue,
         };
-
-        let file_findings = scan_file(&path, &source, &language, rules);
-        for f in file_findings {
-            summary.add_finding(f);
-        }
-        summary.files_scanned += 1;
+        visit(&path, language, &source);
     }
 }
 
 /// Scan for OWASP Top 10 issues only.
 pub fn check_owasp_top10(dir: &Path) -> SecuritySummary {
-    let all_rules = load_bundled_rules();
-    let owasp_rules: Vec<_> = all_rules
-        .into_iter()
-        .filter(|r| r.owasp.is_some())
-        .collect();
+    check_owasp_top10_with(dir, load_bundled_rules())
+}
+
+/// [`check_owasp_top10`] with `rules` in place of the bundled rules.
+pub fn check_owasp_top10_with(dir: &Path, rules: Vec<SecurityRule>) -> SecuritySummary {
+    let owasp_rules: Vec<_> = rules.into_iter().filter(|r| r.owasp.is_some()).collect();
     scan_directory(dir, &owasp_rules, true)
 }
 
 /// Scan for CWE Top 25 issues only.
 pub fn check_cwe_top25(dir: &Path) -> SecuritySummary {
-    let all_rules = load_bundled_rules();
-    let cwe_rules: Vec<_> = all_rules.into_iter().filter(|r| r.cwe.is_some()).collect();
+    check_cwe_top25_with(dir, load_bundled_rules())
+}
+
+/// [`check_cwe_top25`] with `rules` in place of the bundled rules.
+pub fn check_cwe_top25_with(dir: &Path, rules: Vec<SecurityRule>) -> SecuritySummary {
+    let cwe_rules: Vec<_> = rules.into_iter().filter(|r| r.cwe.is_some()).collect();
     scan_directory(dir, &cwe_rules, true)
 }
 
@@ -468,6 +550,38 @@ mod tests {
         assert_eq!(findings[0].file_path, "test.py");
     }
 
+    #[test]
+    fn test_scan_directory_skips_suppressed_and_baselined() {
+        let rules = vec![make_rule(
+            "R1",
+            r"eval\(",
+            Severity::High,
+            RuleCategory::Injection,
+        )];
+        let dir = TempDir::new().unwrap();
+        std::fs::write(
+            dir.path().join("a.py"),
+            "x = eval(a)  # codegraph-ignore: R1\n# codegraph-ignore: R1\ny = eval(b)\nz = eval(c)\n",
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("b.py"), "w = eval(d)\n").unwrap();
+
+        let mut summary = scan_directory(dir.path(), &rules, true);
+        assert_eq!((summary.total_findings, summary.suppressed), (2, 2));
+
+        let accepted: Vec<_> = summary
+            .findings
+            .iter()
+            .filter(|f| f.file_path.ends_with("b.py"))
+            .cloned()
+            .collect();
+        let baseline = Baseline::from_findings(&accepted, dir.path());
+        summary.apply_baseline(&baseline, dir.path());
+        assert_eq!((summary.total_findings, summary.high), (1, 1));
+        assert_eq!((summary.baselined, summary.suppressed), (1, 2));
+        assert_eq!(summary.findings[0].line_number, 4);
+    }
+
     #[test]
     fn test_scan_file_no_findings() {
         let rules = vec![make_rule(
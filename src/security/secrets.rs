@@ -0,0 +1,451 @@
+//! Secret detection.
+//!
+//! One table of credential patterns serves two purposes: [`redact`] masks
+//! secrets in text the server logs or returns (it backs
+//! [`crate::observability::redact_secrets`]), and [`scan_secrets`] reports
+//! them in source files. The scan also applies the `secrets` category of
+//! the security rules, and reports quoted string literals whose Shannon
+//! entropy marks them as likely keys or tokens.
+//!
+//! Findings come in the security scanner's [`SecurityFinding`] format, with
+//! the secret itself masked. They can be suppressed inline with
+//! `codegraph-ignore: <rule-id>`, or per path with the `secrets.allowlist`
+//! section of `.codegraph.yaml`.
+
+use std::sync::OnceLock;
+
+use regex::{Captures, Regex};
+
+use super::baseline::is_suppressed;
+use super::rules::{RuleCategory, SecurityRule, Severity};
+use super::scanner::{scan_source, SecurityFinding};
+use crate::graph::api_guard::in_directory;
+
+// ---------------------------------------------------------------------------
+// Patterns
+// ---------------------------------------------------------------------------
+
+/// A credential pattern shared by redaction and scanning.
+#[derive(Debug, Clone, Copy)]
+pub struct SecretPattern {
+    pub id: &'static str,
+    pub name: &'static str,
+    pub pattern: &'static str,
+    pub severity: Severity,
+    /// Replacement used by [`redact`]; `$1` is the key name.
+    pub redaction: &'static str,
+}
+
+/// Credential patterns. In the `key = value` patterns group 2 is the value.
+pub const SECRET_PATTERNS: &[SecretPattern] = &[
+    SecretPattern {
+        id: "SECRET-KV-API-KEY",
+        name: "API key assignment",
+        pattern: r#"(?i)(api[_-]?key|apikey)\s*[:=]\s*['"]?([a-zA-Z0-9_\-]{20,})['"]?"#,
+        severity: Severity::High,
+        redaction: "$1=***REDACTED***",
+    },
+    SecretPattern {
+        id: "SECRET-KV-PASSWORD",
+        name: "Password assignment",
+        pattern: r#"(?i)(password|passwd|pwd)\s*[:=]\s*['"]?([^\s'"]{8,})['"]?"#,
+        severity: Severity::High,
+        redaction: "$1=***REDACTED***",
+    },
+    SecretPattern {
+        id: "SECRET-KV-TOKEN",
+        name: "Secret or token assignment",
+        pattern: r#"(?i)(secret|token)\s*[:=]\s*['"]?([a-zA-Z0-9_\-]{20,})['"]?"#,
+        severity: Severity::High,
+        redaction: "$1=***REDACTED***",
+    },
+    SecretPattern {
+        id: "SECRET-KV-AWS-ACCESS-KEY",
+        name: "AWS access key ID assignment",
+        pattern: r#"(?i)(aws_access_key_id)\s*[:=]\s*['"]?(AKIA[0-9A-Z]{16})['"]?"#,
+        severity: Severity::Critical,
+        redaction: "$1=***REDACTED***",
+    },
+    SecretPattern {
+        id: "SECRET-KV-AWS-SECRET-KEY",
+        name: "AWS secret access key assignment",
+        pattern: r#"(?i)(aws_secret_access_key)\s*[:=]\s*['"]?([a-zA-Z0-9/+]{40})['"]?"#,
+        severity: Severity::Critical,
+        redaction: "$1=***REDACTED***",
+    },
+    SecretPattern {
+        id: "SECRET-BEARER",
+        name: "Bearer token",
+        pattern: r"(?i)Bearer\s+[a-zA-Z0-9_\-\.]{20,}",
+        severity: Severity::High,
+        redaction: "Bearer ***REDACTED***",
+    },
+    SecretPattern {
+        id: "SECRET-KV-CONNECTION-STRING",
+        name: "Connection string assignment",
+        pattern: r#"(?i)(connection_string|conn_str)\s*[:=]\s*['"]?([^\s'"]{20,})['"]?"#,
+        severity: Severity::Medium,
+        redaction: "$1=***REDACTED***",
+    },
+];
+
+/// Rule ID of high-entropy string literals.
+pub const ENTROPY_RULE_ID: &str = "SECRET-ENTROPY";
+
+/// Shannon entropy (bits per character) from which a literal is reported.
+pub const DEFAULT_MIN_ENTROPY: f64 = 4.0;
+
+/// Shortest literal checked for entropy.
+const MIN_ENTROPY_LEN: usize = 20;
+
+const SECRET_CWE: &str = "CWE-798";
+const SECRET_OWASP: &str = "A07:2021";
+const SECRET_FIX: &str =
+    "Move the secret to an environment variable or a secret manager, and rotate it";
+
+fn compiled_patterns() -> &'static [(SecretPattern, Regex)] {
+    static RE: OnceLock<Vec<(SecretPattern, Regex)>> = OnceLock::new();
+    RE.get_or_init(|| {
+        SECRET_PATTERNS
+            .iter()
+            .map(|p| (*p, Regex::new(p.pattern).expect("valid regex")))
+            .collect()
+    })
+}
+
+fn literal_re() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r#"["'`]([A-Za-z0-9+/=_\-.]{20,})["'`]"#).expect("valid regex"))
+}
+
+/// Replace everything [`SECRET_PATTERNS`] matches in `text`.
+pub fn redact(text: &str) -> String {
+    compiled_patterns()
+        .iter()
+        .fold(text.to_string(), |text, (pattern, re)| {
+            re.replace_all(&text, pattern.redaction).into_owned()
+        })
+}
+
+/// Shannon entropy of `s`, in bits per character.
+pub fn shannon_entropy(s: &str) -> f64 {
+    let mut counts = [0usize; 256];
+    for b in s.bytes() {
+        counts[b as usize] += 1;
+    }
+    let len = s.len() as f64;
+    counts
+        .iter()
+        .filter(|&&c| c > 0)
+        .map(|&c| {
+            let p = c as f64 / len;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+/// The first characters of `secret` and its length, for reports.
+fn mask(secret: &str) -> String {
+    let shown: String = secret.chars().take(4).collect();
+    format!("{shown}*** ({} chars)", secret.chars().count())
+}
+
+// ---------------------------------------------------------------------------
+// Scanning
+// ---------------------------------------------------------------------------
+
+/// What [`scan_secrets`] reports.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SecretScanOptions {
+    /// Report high-entropy string literals.
+    pub entropy: bool,
+    pub min_entropy: f64,
+    /// Directory prefixes with the rule IDs not reported under them; `*`
+    /// stands for every rule.
+    pub allowlist: Vec<(String, Vec<String>)>,
+}
+
+impl Default for SecretScanOptions {
+    fn default() -> Self {
+        Self {
+            entropy: true,
+            min_entropy: DEFAULT_MIN_ENTROPY,
+            allowlist: Vec::new(),
+        }
+    }
+}
+
+impl SecretScanOptions {
+    /// Whether the allowlist accepts `rule_id` in `file_path`
+    /// (project-relative).
+    pub fn allows(&self, file_path: &str, rule_id: &str) -> bool {
+        self.allowlist.iter().any(|(dir, rules)| {
+            in_directory(file_path, dir)
+                && rules
+                    .iter()
+                    .any(|r| r == "*" || r.eq_ignore_ascii_case(rule_id))
+        })
+    }
+}
+
+/// Findings of one file, and how many were left out.
+#[derive(Debug, Clone, Default)]
+pub struct SecretScan {
+    pub findings: Vec<SecurityFinding>,
+    /// Left out for a `codegraph-ignore:` comment.
+    pub suppressed: usize,
+    /// Left out by the allowlist.
+    pub allowlisted: usize,
+}
+
+fn pattern_finding(
+    pattern: &SecretPattern,
+    file_path: &str,
+    line_number: usize,
+    column: usize,
+    masked: String,
+) -> SecurityFinding {
+    SecurityFinding {
+        rule_id: pattern.id.to_string(),
+        rule_name: pattern.name.to_string(),
+        severity: pattern.severity,
+        file_path: file_path.to_string(),
+        line_number,
+        column,
+        matched_text: masked,
+        message: format!("{} detected", pattern.name),
+        fix: Some(SECRET_FIX.to_string()),
+        cwe: Some(SECRET_CWE.to_string()),
+        owasp: Some(SECRET_OWASP.to_string()),
+        category: RuleCategory::Secrets,
+    }
+}
+
+/// Whether the value a `key = value` pattern captured is a quoted literal:
+/// unquoted values in source code are expressions (`password = form.pw`).
+fn value_is_literal(line: &str, caps: &Captures) -> bool {
+    match caps.get(2) {
+        Some(value) => line[..value.start()].ends_with(['"', '\'']),
+        None => true,
+    }
+}
+
+/// Secrets in `source`, the contents of `file_path` (project-relative):
+/// matches of [`SECRET_PATTERNS`] and of the `secrets` rules among
+/// `rules`, then high-entropy literals on lines without another finding.
+/// Each line is reported once, under its most severe finding.
+pub fn scan_secrets(
+    file_path: &str,
+    source: &str,
+    language: &str,
+    rules: &[SecurityRule],
+    options: &SecretScanOptions,
+) -> SecretScan {
+    let secret_rules: Vec<SecurityRule> = rules
+        .iter()
+        .filter(|r| r.category == RuleCategory::Secrets)
+        .cloned()
+        .collect();
+    let (mut candidates, mut suppressed) = scan_source(
+        std::path::Path::new(file_path),
+        source,
+        language,
+        &secret_rules,
+    );
+    for finding in &mut candidates {
+        finding.matched_text = mask(finding.matched_text.trim());
+    }
+
+    let lines: Vec<&str> = source.lines().collect();
+    for (i, line) in lines.iter().enumerate() {
+        for (pattern, re) in compiled_patterns() {
+            for caps in re.captures_iter(line) {
+                if !value_is_literal(line, &caps) {
+                    continue;
+                }
+                if is_suppressed(&lines, i + 1, pattern.id) {
+                    suppressed += 1;
+                    continue;
+                }
+                let whole = caps.get(0).expect("group 0");
+                let secret = caps.get(2).map_or(whole.as_str(), |v| v.as_str());
+                candidates.push(pattern_finding(
+                    pattern,
+                    file_path,
+                    i + 1,
+                    whole.start() + 1,
+                    mask(secret),
+                ));
+            }
+        }
+    }
+
+    // One finding per line, the most severe; rules come before the generic
+    // patterns at equal severity.
+    candidates.sort_by(|a, b| {
+        (a.line_number, std::cmp::Reverse(a.severity))
+            .cmp(&(b.line_number, std::cmp::Reverse(b.severity)))
+    });
+    candidates.dedup_by_key(|f| f.line_number);
+
+    if options.entropy {
+        for (i, line) in lines.iter().enumerate() {
+            if candidates.iter().any(|f| f.line_number == i + 1) {
+                continue;
+            }
+            let Some(literal) = literal_re()
+                .captures_iter(line)
+                .filter_map(|c| c.get(1))
+                .find(|m| is_high_entropy(m.as_str(), options.min_entropy))
+            else {
+                continue;
+            };
+            if is_suppressed(&lines, i + 1, ENTROPY_RULE_ID) {
+                suppressed += 1;
+                continue;
+            }
+            candidates.push(SecurityFinding {
+                rule_id: ENTROPY_RULE_ID.to_string(),
+                rule_name: "High-entropy string".to_string(),
+                severity: Severity::Medium,
+                file_path: file_path.to_string(),
+                line_number: i + 1,
+                column: literal.start() + 1,
+                matched_text: mask(literal.as_str()),
+                message: format!(
+                    "String literal with {:.1} bits of entropy per character, likely a key or token",
+                    shannon_entropy(literal.as_str())
+                ),
+                fix: Some(SECRET_FIX.to_string()),
+                cwe: Some(SECRET_CWE.to_string()),
+                owasp: Some(SECRET_OWASP.to_string()),
+                category: RuleCategory::Secrets,
+            });
+        }
+    }
+
+    let total = candidates.len();
+    candidates.retain(|f| !options.allows(file_path, &f.rule_id));
+    let allowlisted = total - candidates.len();
+    candidates.sort_by(|a, b| {
+        b.severity
+            .cmp(&a.severity)
+            .then(a.line_number.cmp(&b.line_number))
+    });
+    SecretScan {
+        findings: candidates,
+        suppressed,
+        allowlisted,
+    }
+}
+
+/// Whether `literal` looks like a key rather than a word, path or
+/// identifier: long enough, mixing letters and digits, and above the
+/// entropy threshold.
+fn is_high_entropy(literal: &str, min_entropy: f64) -> bool {
+    literal.len() >= MIN_ENTROPY_LEN
+        && literal.bytes().any(|b| b.is_ascii_digit())
+        && literal.bytes().any(|b| b.is_ascii_alphabetic())
+        && shannon_entropy(literal) >= min_entropy
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::security::rules::load_bundled_rules;
+
+    fn scan(source: &str, options: &SecretScanOptions) -> SecretScan {
+        scan_secrets(
+            "src/config.py",
+            source,
+            "python",
+            &load_bundled_rules(),
+            options,
+        )
+    }
+
+    #[test]
+    fn redact_masks_every_pattern() {
+        let text = "api_key=abcdefghijklmnopqrstuvwxyz and Bearer abcdefghijklmnopqrstuvwxyz";
+        assert_eq!(
+            redact(text),
+            "api_key=***REDACTED*** and Bearer ***REDACTED***"
+        );
+    }
+
+    #[test]
+    fn entropy_separates_keys_from_words() {
+        assert!(shannon_entropy("aaaaaaaa") < 0.1);
+        assert!(!is_high_entropy("this_is_a_plain_identifier_name", 4.0));
+        assert!(is_high_entropy("q8Zr2LmX0vT7nKp4Wb9sYc1Hd6Fj3", 4.0));
+        // Hex digests stay under the default threshold.
+        assert!(!is_high_entropy(
+            "3f786850e387550fdab836ed7e6dc881de23001b",
+            4.0
+        ));
+    }
+
+    #[test]
+    fn scan_reports_patterns_rules_and_entropy_masked() {
+        let source = [
+            "API_KEY = 'abcd1234efgh5678ijkl9012'",
+            "password = request.form['password']",
+            "db_password = \"hunter2hunter2\"",
+            "signing = \"q8Zr2LmX0vT7nKp4Wb9sYc1Hd6Fj3\"",
+            "name = \"just_a_long_plain_identifier\"",
+        ]
+        .join("\n");
+        let result = scan(&source, &SecretScanOptions::default());
+        let lines: Vec<usize> = {
+            let mut l: Vec<usize> = result.findings.iter().map(|f| f.line_number).collect();
+            l.sort();
+            l
+        };
+        assert_eq!(lines, vec![1, 3, 4]);
+        for f in &result.findings {
+            assert_eq!(f.category, RuleCategory::Secrets);
+            assert!(
+                !f.matched_text.contains("hunter2hunter2"),
+                "{}",
+                f.matched_text
+            );
+            assert!(!f.matched_text.contains("efgh5678"), "{}", f.matched_text);
+        }
+        let entropy = result.findings.iter().find(|f| f.line_number == 4).unwrap();
+        assert_eq!(entropy.rule_id, ENTROPY_RULE_ID);
+        assert_eq!(entropy.severity, Severity::Medium);
+        assert_eq!(entropy.matched_text, "q8Zr*** (29 chars)");
+
+        let no_entropy = SecretScanOptions {
+            entropy: false,
+            ..Default::default()
+        };
+        assert_eq!(scan(&source, &no_entropy).findings.len(), 2);
+    }
+
+    #[test]
+    fn suppressions_and_allowlist_leave_findings_out() {
+        let source = "# codegraph-ignore: SECRET-KV-PASSWORD, OWASP-A07-001\npassword = \"hunter2hunter2\"\nsigning = \"q8Zr2LmX0vT7nKp4Wb9sYc1Hd6Fj3\"\n";
+        let result = scan(source, &SecretScanOptions::default());
+        assert_eq!(result.suppressed, 2);
+        assert_eq!(result.findings.len(), 1);
+
+        let options = SecretScanOptions {
+            allowlist: vec![("src".to_string(), vec![ENTROPY_RULE_ID.to_lowercase()])],
+            ..Default::default()
+        };
+        let result = scan(source, &options);
+        assert_eq!((result.findings.len(), result.allowlisted), (0, 1));
+        assert!(options.allows("src/config.py", ENTROPY_RULE_ID));
+        assert!(!options.allows("srcx/config.py", ENTROPY_RULE_ID));
+        let everything = SecretScanOptions {
+            allowlist: vec![("tests/fixtures".to_string(), vec!["*".to_string()])],
+            ..Default::default()
+        };
+        assert!(everything.allows("tests/fixtures/keys.py", "SECRET-001"));
+    }
+}
@@ -616,7 +616,7 @@ pub fn trace_taint(source: &str, language: &str, from_line: usize) -> Vec<TaintF
 // Internal helpers
 // ---------------------------------------------------------------------------
 
-fn find_sinks(source: &str, language: &str) -> Vec<TaintSink> {
+pub(crate) fn find_sinks(source: &str, language: &str) -> Vec<TaintSink> {
     let mut sinks = Vec::new();
 
     for (line_num, line) in source.lines().enumerate() {
@@ -695,7 +695,7 @@ fn extract_variable(line: &str) -> String {
     "unknown".to_string()
 }
 
-fn parse_assignment(line: &str) -> Option<(String, String)> {
+pub(crate) fn parse_assignment(line: &str) -> Option<(String, String)> {
     let line = line.trim();
     if let Some(eq_pos) = line.find('=') {
         if eq_pos > 0 {
@@ -748,7 +748,7 @@ fn parse_assignment(line: &str) -> Option<(String, String)> {
     None
 }
 
-fn propagate_method_taint(line: &str, tainted_vars: &mut HashSet<String>) {
+pub(crate) fn propagate_method_taint(line: &str, tainted_vars: &mut HashSet<String>) {
     let methods = [
         ".append(", ".push(", ".add(", ".insert(", ".extend(", ".concat(",
     ];
@@ -780,7 +780,7 @@ fn propagate_method_taint(line: &str, tainted_vars: &mut HashSet<String>) {
     }
 }
 
-fn check_sanitization(
+pub(crate) fn check_sanitization(
     lines: &[&str],
     source_line: usize,
     sink_line: usize,
@@ -851,7 +851,7 @@ fn build_path(
     path
 }
 
-fn sink_to_vuln_type(sink_kind: &str) -> String {
+pub(crate) fn sink_to_vuln_type(sink_kind: &str) -> String {
     for sp in SINK_PATTERNS {
         if sp.kind == sink_kind {
             return sp.vuln_type.to_string();
@@ -272,6 +272,10 @@ pub enum NodeKind {
     Property,
     Namespace,
     Constant,
+    /// An HTTP route a web framework registers, named `GET /users`.
+    Route,
+    /// A key defined in a configuration file, named by its dotted path.
+    ConfigKey,
 }
 
 impl NodeKind {
@@ -290,6 +294,8 @@ impl NodeKind {
             Self::Property => "property",
             Self::Namespace => "namespace",
             Self::Constant => "constant",
+            Self::Route => "route",
+            Self::ConfigKey => "config_key",
         }
     }
 
@@ -308,6 +314,8 @@ impl NodeKind {
             "property" | "field" => Some(Self::Property),
             "namespace" | "package" => Some(Self::Namespace),
             "constant" | "const" => Some(Self::Constant),
+            "route" => Some(Self::Route),
+            "config_key" => Some(Self::ConfigKey),
             _ => None,
         }
     }
@@ -333,6 +341,12 @@ pub enum EdgeKind {
     Extends,
     Implements,
     References,
+    /// A function or method reads a field/property.
+    Reads,
+    /// A function or method assigns to a field/property.
+    Writes,
+    /// A route is served by a handler function.
+    Handles,
 }
 
 impl EdgeKind {
@@ -344,6 +358,9 @@ impl EdgeKind {
             Self::Extends => "extends",
             Self::Implements => "implements",
             Self::References => "references",
+            Self::Reads => "reads",
+            Self::Writes => "writes",
+            Self::Handles => "handles",
         }
     }
 
@@ -355,6 +372,9 @@ impl EdgeKind {
             "extends" => Some(Self::Extends),
             "implements" => Some(Self::Implements),
             "references" => Some(Self::References),
+            "reads" => Some(Self::Reads),
+            "writes" => Some(Self::Writes),
+            "handles" => Some(Self::Handles),
             _ => None,
         }
     }
@@ -551,6 +571,7 @@ mod tests {
             NodeKind::Property,
             NodeKind::Namespace,
             NodeKind::Constant,
+            NodeKind::Route,
         ] {
             let s = kind.as_str();
             assert_eq!(NodeKind::from_str_loose(s), Some(kind));
@@ -566,6 +587,9 @@ mod tests {
             EdgeKind::Extends,
             EdgeKind::Implements,
             EdgeKind::References,
+            EdgeKind::Reads,
+            EdgeKind::Writes,
+            EdgeKind::Handles,
         ] {
             let s = kind.as_str();
             assert_eq!(EdgeKind::from_str_loose(s), Some(kind));
@@ -997,6 +1021,7 @@ mod tests {
     #[test_case(NodeKind::Property, "property" ; "nk_property")]
     #[test_case(NodeKind::Namespace, "namespace" ; "nk_namespace")]
     #[test_case(NodeKind::Constant, "constant" ; "nk_constant")]
+    #[test_case(NodeKind::Route, "route" ; "nk_route")]
     fn node_kind_as_str_expected(kind: NodeKind, expected: &str) {
         assert_eq!(kind.as_str(), expected);
     }
@@ -1032,6 +1057,7 @@ mod tests {
             NodeKind::Property,
             NodeKind::Namespace,
             NodeKind::Constant,
+            NodeKind::Route,
         ];
         for kind in kinds {
             assert_eq!(format!("{kind}"), kind.as_str());
@@ -1048,6 +1074,9 @@ mod tests {
     #[test_case(EdgeKind::Extends, "extends" ; "ek_extends")]
     #[test_case(EdgeKind::Implements, "implements" ; "ek_implements")]
     #[test_case(EdgeKind::References, "references" ; "ek_references")]
+    #[test_case(EdgeKind::Reads, "reads" ; "ek_reads")]
+    #[test_case(EdgeKind::Writes, "writes" ; "ek_writes")]
+    #[test_case(EdgeKind::Handles, "handles" ; "ek_handles")]
     fn edge_kind_as_str_expected(kind: EdgeKind, expected: &str) {
         assert_eq!(kind.as_str(), expected);
     }
@@ -1308,6 +1337,7 @@ mod tests {
             NodeKind::Property,
             NodeKind::Namespace,
             NodeKind::Constant,
+            NodeKind::Route,
         ];
         for kind in kinds {
             let json = serde_json::to_string(&kind).unwrap();
@@ -19,7 +19,9 @@ use serde::{Deserialize, Serialize};
 use tokio::sync::Mutex;
 
 use crate::db::schema::initialize_database;
+use crate::graph::pool::{PooledStore, ReadPool, DEFAULT_MAX_IDLE};
 use crate::graph::search::{HybridSearch, SearchOptions};
+use crate::graph::stats::{load_dir_stats, rollup, RollupKey};
 use crate::graph::store::GraphStore;
 
 // ---------------------------------------------------------------------------
@@ -28,6 +30,38 @@ use crate::graph::store::GraphStore;
 
 struct VizState {
     store: Mutex<GraphStore>,
+    /// Read-only connections for file-backed databases, so requests run in
+    /// parallel with each other and with a concurrent indexer or watcher.
+    readers: Option<ReadPool>,
+}
+
+/// A store borrowed for one request: pooled if available, else the shared one.
+enum StoreGuard<'a> {
+    Pooled(PooledStore<'a>),
+    Shared(tokio::sync::MutexGuard<'a, GraphStore>),
+}
+
+impl std::ops::Deref for StoreGuard<'_> {
+    type Target = GraphStore;
+
+    fn deref(&self) -> &GraphStore {
+        match self {
+            Self::Pooled(store) => store,
+            Self::Shared(store) => store,
+        }
+    }
+}
+
+impl VizState {
+    async fn store(&self) -> StoreGuard<'_> {
+        if let Some(pool) = &self.readers {
+            match pool.get() {
+                Ok(store) => return StoreGuard::Pooled(store),
+                Err(e) => tracing::warn!("read pool unavailable, using shared store: {e}"),
+            }
+        }
+        StoreGuard::Shared(self.store.lock().await)
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -86,6 +120,18 @@ struct StatsJson {
     edges: usize,
     files: usize,
     languages: Vec<String>,
+    directories: Vec<DirStatsJson>,
+}
+
+#[derive(Serialize)]
+struct DirStatsJson {
+    directory: String,
+    languages: Vec<String>,
+    files: usize,
+    nodes: usize,
+    edges: usize,
+    avg_complexity: f64,
+    max_complexity: u32,
 }
 
 // ---------------------------------------------------------------------------
@@ -123,7 +169,7 @@ async fn get_nodes(
     Query(params): Query<NodesQuery>,
 ) -> Json<Vec<NodeJson>> {
     let limit = params.limit.unwrap_or(200).min(1000);
-    let store = state.store.lock().await;
+    let store = state.store().await;
 
     // Build a query that selects top nodes by in-degree (a proxy for importance)
     // with optional kind and language filters.
@@ -186,7 +232,7 @@ async fn get_edges(
     Query(params): Query<EdgesQuery>,
 ) -> Json<Vec<EdgeJson>> {
     let limit = params.limit.unwrap_or(500).min(5000);
-    let store = state.store.lock().await;
+    let store = state.store().await;
 
     let mut stmt = match store
         .conn
@@ -222,7 +268,7 @@ async fn search_nodes(
         _ => return Json(Vec::new()),
     };
     let limit = params.limit.unwrap_or(20).min(100);
-    let store = state.store.lock().await;
+    let store = state.store().await;
 
     let search = HybridSearch::new(&store.conn);
     let opts = SearchOptions {
@@ -251,7 +297,7 @@ async fn get_node_detail(
     State(state): State<Arc<VizState>>,
     Path(node_id): Path<String>,
 ) -> impl IntoResponse {
-    let store = state.store.lock().await;
+    let store = state.store().await;
 
     let node = match store.get_node(&node_id) {
         Ok(Some(n)) => n,
@@ -331,7 +377,7 @@ async fn get_node_detail(
 }
 
 async fn get_stats(State(state): State<Arc<VizState>>) -> Json<StatsJson> {
-    let store = state.store.lock().await;
+    let store = state.store().await;
 
     let stats = store
         .get_stats()
@@ -353,11 +399,26 @@ async fn get_stats(State(state): State<Arc<VizState>>) -> Json<StatsJson> {
         })
         .unwrap_or_default();
 
+    let rows = load_dir_stats(&store.conn).unwrap_or_default();
+    let directories = rollup(&rows, RollupKey::Directory)
+        .into_iter()
+        .map(|d| DirStatsJson {
+            avg_complexity: d.avg_complexity(),
+            directory: d.directory,
+            languages: d.language.split(',').map(str::to_string).collect(),
+            files: d.files,
+            nodes: d.nodes,
+            edges: d.edges,
+            max_complexity: d.complexity_max,
+        })
+        .collect();
+
     Json(StatsJson {
         nodes: stats.nodes,
         edges: stats.edges,
         files: stats.files,
         languages,
+        directories,
     })
 }
 
@@ -389,6 +450,7 @@ pub async fn run_viz_server(
     let store = GraphStore::from_connection(conn);
     let state = Arc::new(VizState {
         store: Mutex::new(store),
+        readers: Some(ReadPool::new(db_path, DEFAULT_MAX_IDLE)),
     });
 
     let app = build_router(state);
@@ -472,6 +534,7 @@ mod tests {
 
         Arc::new(VizState {
             store: Mutex::new(store),
+            readers: None,
         })
     }
 
@@ -540,6 +603,8 @@ mod tests {
         assert_eq!(stats.edges, 1);
         assert_eq!(stats.files, 1);
         assert!(stats.languages.contains(&"typescript".to_string()));
+        // test_state never refreshes the rollups.
+        assert!(stats.directories.is_empty());
     }
 
     #[tokio::test]
@@ -571,6 +636,7 @@ mod tests {
         let store = GraphStore::from_connection(conn);
         let state = Arc::new(VizState {
             store: Mutex::new(store),
+            readers: None,
         });
         let params = NodesQuery {
             limit: None,
@@ -581,6 +647,40 @@ mod tests {
         assert!(nodes.is_empty());
     }
 
+    #[tokio::test]
+    async fn get_stats_reads_through_pool_while_writer_holds_store() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("graph.db");
+        let path = path.to_str().unwrap();
+        let store = GraphStore::new(path).unwrap();
+        store
+            .upsert_node(&CodeNode {
+                id: "fn:a.ts:f:1".into(),
+                name: "f".into(),
+                qualified_name: None,
+                kind: NodeKind::Function,
+                file_path: "a.ts".into(),
+                start_line: 1,
+                end_line: 2,
+                start_column: 0,
+                end_column: 1,
+                language: Language::TypeScript,
+                body: None,
+                documentation: None,
+                exported: None,
+            })
+            .unwrap();
+        let state = Arc::new(VizState {
+            store: Mutex::new(store),
+            readers: Some(ReadPool::new(path, DEFAULT_MAX_IDLE)),
+        });
+
+        // Hold the shared store as a writer would; reads must not wait on it.
+        let _writer = state.store.lock().await;
+        let Json(stats) = get_stats(State(state.clone())).await;
+        assert_eq!(stats.nodes, 1);
+    }
+
     #[tokio::test]
     async fn build_router_creates_valid_router() {
         let state = test_state();
@@ -40,6 +40,32 @@ pub struct RepoEntry {
     pub path: String,
     /// Path to the CodeGraph database, relative to the workspace directory.
     pub db_path: String,
+    /// Resource limits when the workspace is served by `codegraph serve
+    /// --workspace`; defaults apply when absent.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quota: Option<ProjectQuota>,
+}
+
+/// Per-project resource limits for multi-tenant serving.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProjectQuota {
+    /// Tool calls allowed to run at once; further calls are rejected.
+    pub max_concurrent_queries: u32,
+    /// SQLite page cache for the project's connection, in MiB.
+    pub cache_memory_mb: u32,
+    /// Tool calls that run the embedding model, per minute.
+    pub embeddings_per_minute: u32,
+}
+
+impl Default for ProjectQuota {
+    fn default() -> Self {
+        Self {
+            max_concurrent_queries: 4,
+            cache_memory_mb: 64,
+            embeddings_per_minute: 120,
+        }
+    }
 }
 
 /// Search results from a single repository within a workspace search.
@@ -124,6 +150,7 @@ impl Workspace {
             name: name.to_string(),
             path: relative_path,
             db_path: db_relative,
+            quota: None,
         });
         Ok(())
     }
@@ -292,7 +319,7 @@ fn make_relative(target: &Path, base: &Path) -> String {
 }
 
 /// Resolve a potentially relative path against a base directory.
-fn resolve_path(path_str: &str, base: &Path) -> PathBuf {
+pub(crate) fn resolve_path(path_str: &str, base: &Path) -> PathBuf {
     let p = Path::new(path_str);
     if p.is_absolute() {
         p.to_path_buf()
@@ -448,11 +475,13 @@ mod tests {
                     name: "frontend".to_string(),
                     path: "./frontend".to_string(),
                     db_path: "./frontend/.codegraph/codegraph.db".to_string(),
+                    quota: None,
                 },
                 RepoEntry {
                     name: "backend".to_string(),
                     path: "./backend".to_string(),
                     db_path: "./backend/.codegraph/codegraph.db".to_string(),
+                    quota: None,
                 },
             ],
         };
@@ -513,6 +542,7 @@ mod tests {
                 name: "ghost".to_string(),
                 path: "./ghost".to_string(),
                 db_path: "./ghost/.codegraph/codegraph.db".to_string(),
+                quota: None,
             }],
         };
 
@@ -584,11 +614,13 @@ mod tests {
                     name: "repo-a".to_string(),
                     path: "./repo-a".to_string(),
                     db_path: format!("{}", db_a_path.display()),
+                    quota: None,
                 },
                 RepoEntry {
                     name: "repo-b".to_string(),
                     path: "./repo-b".to_string(),
                     db_path: format!("{}", db_b_path.display()),
+                    quota: None,
                 },
             ],
         };
@@ -757,6 +757,7 @@ fn dead_code_result_serialization() {
         kind: "function".to_string(),
         file_path: "src/a.ts".to_string(),
         start_line: 1,
+        referenced_by: Vec::new(),
     };
 
     let json = serde_json::to_string(&result).unwrap();